@@ -0,0 +1,39 @@
+# Meldungen für Benutzer (Deutsch)
+
+## Fehler
+
+error-database-connection = Datenbankverbindungsfehler: { $error }
+error-save-setting = Einstellung konnte nicht gespeichert werden: { $error }
+error-unsupported-language = Nicht unterstützte Sprache: { $language }
+error-port-too-low = Der Port muss 1024 oder höher sein (nicht privilegierte Ports)
+error-invalid-ip = '{ $address }' ist keine gültige IP-Adresse
+error-not-local-address = { $address } ist keine Adresse dieses Computers
+error-restart-server = Server konnte nicht neu gestartet werden: { $error }
+error-autostart-check = Autostart-Status konnte nicht geprüft werden
+error-autostart-enable = Autostart konnte nicht aktiviert werden
+error-autostart-disable = Autostart konnte nicht deaktiviert werden
+error-autostart-save = Autostart-Einstellung konnte nicht gespeichert werden
+
+## Ereignisprotokoll
+
+event-server-port-changed = Konfiguration geändert: Server-Port { $old } → { $new }
+event-bind-address-changed = Konfiguration geändert: Bind-Adresse { $old } → { $new }
+event-bind-all-interfaces = Der Server lauscht auf allen Netzwerkschnittstellen und ist für jedes Gerät im Netzwerk erreichbar
+event-tuner-name-changed = Konfiguration geändert: Tuner-Name '{ $name }' ({ $model })
+event-access-token-generated = Konfiguration geändert: Zugriffstoken erzeugt
+event-access-token-removed = Konfiguration geändert: Zugriffstoken entfernt
+event-ts-padding-enabled = Konfiguration geändert: TS-Padding für Streams aktiviert
+event-ts-padding-disabled = Konfiguration geändert: TS-Padding für Streams deaktiviert
+event-ts-continuity-enabled = Konfiguration geändert: TS-Kontinuität bei Failover aktiviert
+event-ts-continuity-disabled = Konfiguration geändert: TS-Kontinuität bei Failover deaktiviert
+event-preferred-quality-changed = Konfiguration geändert: Bevorzugte Streamqualität { $quality }
+event-channel-preferred-quality-changed = Konfiguration geändert: Bevorzugte Streamqualität für Kanal { $channel }: { $quality }
+event-ffmpeg-path-changed = Konfiguration geändert: FFmpeg-Pfad { $path }
+event-ffmpeg-path-reset = Konfiguration geändert: ffmpeg aus PATH wird verwendet
+event-maintenance-window-set = Konfiguration geändert: Wartungsfenster { $start }–{ $end }
+event-maintenance-window-disabled = Konfiguration geändert: Wartungsfenster deaktiviert
+event-server-restarted = HTTP-Server auf Port { $port } neu gestartet ({ $streams } Stream(s) beendet)
+event-server-restarted-forced = HTTP-Server auf Port { $port } neu gestartet ({ $streams } Stream(s) beendet, Verbindungen nach Zeitüberschreitung geschlossen)
+event-autostart-enabled = Konfiguration geändert: Autostart aktiviert
+event-autostart-disabled = Konfiguration geändert: Autostart deaktiviert
+event-language-changed = Konfiguration geändert: Sprache { $language }
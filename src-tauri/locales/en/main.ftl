@@ -0,0 +1,39 @@
+# User-facing messages (English, also the fallback for missing translations)
+
+## Errors
+
+error-database-connection = Database connection error: { $error }
+error-save-setting = Failed to save setting: { $error }
+error-unsupported-language = Unsupported language: { $language }
+error-port-too-low = Port must be 1024 or higher (non-privileged ports)
+error-invalid-ip = '{ $address }' is not a valid IP address
+error-not-local-address = { $address } is not an address of this computer
+error-restart-server = Failed to restart server: { $error }
+error-autostart-check = Failed to check autostart status
+error-autostart-enable = Failed to enable autostart
+error-autostart-disable = Failed to disable autostart
+error-autostart-save = Failed to save autostart setting
+
+## Event log
+
+event-server-port-changed = Configuration changed: Server port { $old } → { $new }
+event-bind-address-changed = Configuration changed: Bind address { $old } → { $new }
+event-bind-all-interfaces = Server is bound to all network interfaces and reachable by any device on the network
+event-tuner-name-changed = Configuration changed: Tuner name set to '{ $name }' ({ $model })
+event-access-token-generated = Configuration changed: Access token generated
+event-access-token-removed = Configuration changed: Access token removed
+event-ts-padding-enabled = Configuration changed: Stream TS padding enabled
+event-ts-padding-disabled = Configuration changed: Stream TS padding disabled
+event-ts-continuity-enabled = Configuration changed: Stream TS continuity across failover enabled
+event-ts-continuity-disabled = Configuration changed: Stream TS continuity across failover disabled
+event-preferred-quality-changed = Configuration changed: Preferred stream quality set to { $quality }
+event-channel-preferred-quality-changed = Configuration changed: Preferred stream quality for channel { $channel } set to { $quality }
+event-ffmpeg-path-changed = Configuration changed: FFmpeg path set to { $path }
+event-ffmpeg-path-reset = Configuration changed: FFmpeg path reset to ffmpeg from PATH
+event-maintenance-window-set = Configuration changed: Maintenance window set to { $start }–{ $end }
+event-maintenance-window-disabled = Configuration changed: Maintenance window disabled
+event-server-restarted = HTTP server restarted on port { $port } ({ $streams } stream(s) stopped)
+event-server-restarted-forced = HTTP server restarted on port { $port } ({ $streams } stream(s) stopped, connections closed after drain timeout)
+event-autostart-enabled = Configuration changed: Auto-start enabled
+event-autostart-disabled = Configuration changed: Auto-start disabled
+event-language-changed = Configuration changed: Language set to { $language }
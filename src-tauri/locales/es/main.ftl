@@ -0,0 +1,39 @@
+# Mensajes para el usuario (español)
+
+## Errores
+
+error-database-connection = Error de conexión con la base de datos: { $error }
+error-save-setting = No se pudo guardar el ajuste: { $error }
+error-unsupported-language = Idioma no compatible: { $language }
+error-port-too-low = El puerto debe ser 1024 o superior (puertos no privilegiados)
+error-invalid-ip = '{ $address }' no es una dirección IP válida
+error-not-local-address = { $address } no es una dirección de este equipo
+error-restart-server = No se pudo reiniciar el servidor: { $error }
+error-autostart-check = No se pudo comprobar el inicio automático
+error-autostart-enable = No se pudo activar el inicio automático
+error-autostart-disable = No se pudo desactivar el inicio automático
+error-autostart-save = No se pudo guardar el ajuste de inicio automático
+
+## Registro de eventos
+
+event-server-port-changed = Configuración modificada: puerto del servidor { $old } → { $new }
+event-bind-address-changed = Configuración modificada: dirección de escucha { $old } → { $new }
+event-bind-all-interfaces = El servidor escucha en todas las interfaces de red y cualquier dispositivo de la red puede acceder a él
+event-tuner-name-changed = Configuración modificada: nombre del sintonizador '{ $name }' ({ $model })
+event-access-token-generated = Configuración modificada: token de acceso generado
+event-access-token-removed = Configuración modificada: token de acceso eliminado
+event-ts-padding-enabled = Configuración modificada: relleno TS del stream activado
+event-ts-padding-disabled = Configuración modificada: relleno TS del stream desactivado
+event-ts-continuity-enabled = Configuración modificada: continuidad TS tras failover activada
+event-ts-continuity-disabled = Configuración modificada: continuidad TS tras failover desactivada
+event-preferred-quality-changed = Configuración modificada: calidad de stream preferida { $quality }
+event-channel-preferred-quality-changed = Configuración modificada: calidad de stream preferida del canal { $channel }: { $quality }
+event-ffmpeg-path-changed = Configuración modificada: ruta de FFmpeg { $path }
+event-ffmpeg-path-reset = Configuración modificada: se usa ffmpeg del PATH
+event-maintenance-window-set = Configuración modificada: ventana de mantenimiento { $start }–{ $end }
+event-maintenance-window-disabled = Configuración modificada: ventana de mantenimiento desactivada
+event-server-restarted = Servidor HTTP reiniciado en el puerto { $port } ({ $streams } stream(s) detenido(s))
+event-server-restarted-forced = Servidor HTTP reiniciado en el puerto { $port } ({ $streams } stream(s) detenido(s), conexiones cerradas tras el tiempo de espera)
+event-autostart-enabled = Configuración modificada: inicio automático activado
+event-autostart-disabled = Configuración modificada: inicio automático desactivado
+event-language-changed = Configuración modificada: idioma { $language }
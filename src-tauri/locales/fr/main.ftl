@@ -0,0 +1,39 @@
+# Messages destinés à l'utilisateur (français)
+
+## Erreurs
+
+error-database-connection = Erreur de connexion à la base de données : { $error }
+error-save-setting = Impossible d'enregistrer le paramètre : { $error }
+error-unsupported-language = Langue non prise en charge : { $language }
+error-port-too-low = Le port doit être supérieur ou égal à 1024 (ports non privilégiés)
+error-invalid-ip = '{ $address }' n'est pas une adresse IP valide
+error-not-local-address = { $address } n'est pas une adresse de cet ordinateur
+error-restart-server = Impossible de redémarrer le serveur : { $error }
+error-autostart-check = Impossible de vérifier le démarrage automatique
+error-autostart-enable = Impossible d'activer le démarrage automatique
+error-autostart-disable = Impossible de désactiver le démarrage automatique
+error-autostart-save = Impossible d'enregistrer le paramètre de démarrage automatique
+
+## Journal des événements
+
+event-server-port-changed = Configuration modifiée : port du serveur { $old } → { $new }
+event-bind-address-changed = Configuration modifiée : adresse d'écoute { $old } → { $new }
+event-bind-all-interfaces = Le serveur écoute sur toutes les interfaces réseau et est accessible à tout appareil du réseau
+event-tuner-name-changed = Configuration modifiée : nom du tuner '{ $name }' ({ $model })
+event-access-token-generated = Configuration modifiée : jeton d'accès généré
+event-access-token-removed = Configuration modifiée : jeton d'accès supprimé
+event-ts-padding-enabled = Configuration modifiée : remplissage TS des flux activé
+event-ts-padding-disabled = Configuration modifiée : remplissage TS des flux désactivé
+event-ts-continuity-enabled = Configuration modifiée : continuité TS après basculement activée
+event-ts-continuity-disabled = Configuration modifiée : continuité TS après basculement désactivée
+event-preferred-quality-changed = Configuration modifiée : qualité de flux préférée { $quality }
+event-channel-preferred-quality-changed = Configuration modifiée : qualité de flux préférée de la chaîne { $channel } : { $quality }
+event-ffmpeg-path-changed = Configuration modifiée : chemin de FFmpeg { $path }
+event-ffmpeg-path-reset = Configuration modifiée : ffmpeg du PATH utilisé
+event-maintenance-window-set = Configuration modifiée : fenêtre de maintenance { $start }–{ $end }
+event-maintenance-window-disabled = Configuration modifiée : fenêtre de maintenance désactivée
+event-server-restarted = Serveur HTTP redémarré sur le port { $port } ({ $streams } flux arrêté(s))
+event-server-restarted-forced = Serveur HTTP redémarré sur le port { $port } ({ $streams } flux arrêté(s), connexions fermées après le délai d'attente)
+event-autostart-enabled = Configuration modifiée : démarrage automatique activé
+event-autostart-disabled = Configuration modifiée : démarrage automatique désactivé
+event-language-changed = Configuration modifiée : langue { $language }
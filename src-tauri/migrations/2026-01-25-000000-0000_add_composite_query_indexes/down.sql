@@ -0,0 +1,4 @@
+-- Rollback: Remove composite query indexes
+
+DROP INDEX IF EXISTS idx_channel_mappings_channel_priority;
+DROP INDEX IF EXISTS idx_event_log_level_timestamp;
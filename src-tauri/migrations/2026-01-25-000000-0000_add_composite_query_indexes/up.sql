@@ -0,0 +1,17 @@
+-- Migration: Composite indexes for the hottest filters
+--
+-- Already covered by earlier migrations:
+--   programs(xmltv_channel_id, start_time)      -> idx_programs_channel_time
+--   xtream_channels(account_id, stream_id)      -> UNIQUE constraint autoindex
+--
+-- Guarded by db::connection tests (EXPLAIN QUERY PLAN must not scan or sort).
+
+-- Stream lookup for a channel ordered by priority (failover chain, match lists)
+-- Supports ORDER BY stream_priority ASC, is_primary DESC without a temp sort
+CREATE INDEX IF NOT EXISTS idx_channel_mappings_channel_priority
+ON channel_mappings(xmltv_channel_id, stream_priority, is_primary DESC);
+
+-- Event log filtered by level and ordered/ranged by timestamp
+-- (recent errors on the dashboard, level filter in the event log viewer)
+CREATE INDEX IF NOT EXISTS idx_event_log_level_timestamp
+ON event_log(level, timestamp DESC);
@@ -0,0 +1,3 @@
+DROP INDEX IF EXISTS idx_channel_aliases_alias;
+DROP TABLE IF EXISTS channel_aliases;
+DROP TABLE IF EXISTS alias_packs;
@@ -0,0 +1,24 @@
+-- Community alias packs: provider channel name -> XMLTV tvg-id hints
+-- Each alias belongs to the pack it was imported from, so a pack can be
+-- updated (aliases replaced) or removed without touching other packs.
+
+CREATE TABLE IF NOT EXISTS alias_packs (
+    id INTEGER PRIMARY KEY,
+    name TEXT NOT NULL,
+    source_url TEXT NOT NULL UNIQUE,
+    version TEXT,
+    alias_count INTEGER NOT NULL DEFAULT 0,
+    imported_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS channel_aliases (
+    id INTEGER PRIMARY KEY,
+    pack_id INTEGER NOT NULL REFERENCES alias_packs(id) ON DELETE CASCADE,
+    -- Provider channel name, normalized with normalize_channel_name
+    alias TEXT NOT NULL,
+    tvg_id TEXT NOT NULL,
+    UNIQUE(pack_id, alias, tvg_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_channel_aliases_alias ON channel_aliases(alias);
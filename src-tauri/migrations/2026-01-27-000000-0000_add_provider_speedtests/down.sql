@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_provider_speedtests_account_tested;
+DROP TABLE IF EXISTS provider_speedtests;
@@ -0,0 +1,21 @@
+-- Provider speed test history (one row per run)
+
+CREATE TABLE IF NOT EXISTS provider_speedtests (
+    id INTEGER PRIMARY KEY,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    -- Xtream stream ID that was downloaded
+    stream_id INTEGER,
+    tested_at TEXT NOT NULL DEFAULT (datetime('now')),
+    success INTEGER NOT NULL DEFAULT 0,
+    -- Time to first byte of the stream body
+    ttfb_ms INTEGER,
+    bytes_downloaded INTEGER NOT NULL DEFAULT 0,
+    download_ms INTEGER NOT NULL DEFAULT 0,
+    throughput_kbps INTEGER,
+    -- JSON array of advertised qualities the measured bandwidth cannot sustain
+    unsupported_qualities TEXT,
+    error TEXT
+);
+
+CREATE INDEX IF NOT EXISTS idx_provider_speedtests_account_tested
+    ON provider_speedtests(account_id, tested_at DESC);
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS channel_policy_actions;
+DROP TABLE IF EXISTS stream_health;
@@ -0,0 +1,20 @@
+-- Automatic channel enable/disable policy
+--
+-- stream_health records the outcome of the most recent connection attempts to
+-- each provider stream, so the policy can tell how long a stream has been dead.
+-- channel_policy_actions remembers what the policy last did to each channel,
+-- so a later manual change by the user is not overridden.
+
+CREATE TABLE IF NOT EXISTS stream_health (
+    xtream_channel_id INTEGER PRIMARY KEY REFERENCES xtream_channels(id) ON DELETE CASCADE,
+    last_success_at TEXT,
+    last_failure_at TEXT,
+    -- First failure since the last success (NULL while the stream works)
+    failing_since TEXT
+);
+
+CREATE TABLE IF NOT EXISTS channel_policy_actions (
+    xmltv_channel_id INTEGER PRIMARY KEY REFERENCES xmltv_channels(id) ON DELETE CASCADE,
+    action TEXT CHECK(action IN ('enabled', 'disabled')) NOT NULL,
+    applied_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
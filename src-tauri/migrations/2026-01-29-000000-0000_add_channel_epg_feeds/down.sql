@@ -0,0 +1 @@
+DROP TABLE IF EXISTS channel_epg_feeds;
@@ -0,0 +1,16 @@
+-- Per-channel XMLTV feeds
+--
+-- Attaches a small XMLTV feed (typically a single-channel feed) to a guide
+-- channel by its tvg-id. During EPG refresh the feed's programs replace the
+-- programs of that channel, without adding a visible XMLTV source.
+
+CREATE TABLE IF NOT EXISTS channel_epg_feeds (
+    tvg_id TEXT PRIMARY KEY NOT NULL,
+    url TEXT NOT NULL,
+    -- Channel ID inside the feed (NULL when the feed carries a single channel)
+    feed_channel_id TEXT,
+    last_fetched_at TEXT,
+    program_count INTEGER NOT NULL DEFAULT 0,
+    last_error TEXT,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
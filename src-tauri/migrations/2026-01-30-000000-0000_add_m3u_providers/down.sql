@@ -0,0 +1,3 @@
+-- DROP COLUMN needs SQLite 3.35+, which the bundled SQLite provides
+ALTER TABLE xtream_channels DROP COLUMN stream_url;
+ALTER TABLE accounts DROP COLUMN provider_type;
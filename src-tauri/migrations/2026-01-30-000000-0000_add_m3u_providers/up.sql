@@ -0,0 +1,10 @@
+-- M3U playlist providers
+--
+-- provider_type selects how an account's channels are scanned: 'xtream' uses
+-- the Xtream Codes API, 'm3u' downloads the playlist at server_url. Channels
+-- of M3U accounts carry their upstream URL in stream_url, since it cannot be
+-- built from credentials and a stream ID.
+
+ALTER TABLE accounts ADD COLUMN provider_type TEXT NOT NULL DEFAULT 'xtream'
+    CHECK(provider_type IN ('xtream', 'm3u'));
+ALTER TABLE xtream_channels ADD COLUMN stream_url TEXT;
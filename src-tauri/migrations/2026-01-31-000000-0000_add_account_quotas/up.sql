@@ -0,0 +1,22 @@
+-- Monthly usage quotas per provider account
+--
+-- account_quotas holds the optional limits; account_usage accumulates the
+-- traffic and watch time of finished stream sessions per calendar month
+-- ("YYYY-MM", local time). alert_level remembers the highest threshold
+-- (80 or 100 percent) already reported for the month.
+
+CREATE TABLE IF NOT EXISTS account_quotas (
+    account_id INTEGER PRIMARY KEY NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    monthly_gb REAL,
+    monthly_hours REAL,
+    block_when_exceeded INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS account_usage (
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    month TEXT NOT NULL,
+    bytes BIGINT NOT NULL DEFAULT 0,
+    seconds BIGINT NOT NULL DEFAULT 0,
+    alert_level INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (account_id, month)
+);
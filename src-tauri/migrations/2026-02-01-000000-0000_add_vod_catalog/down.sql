@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS vod_episodes;
+DROP TABLE IF EXISTS vod_items;
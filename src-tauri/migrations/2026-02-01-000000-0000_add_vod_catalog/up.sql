@@ -0,0 +1,36 @@
+-- Provider VOD catalog (movies and series) from Xtream accounts
+--
+-- vod_items holds one row per movie or series; provider_id is the Xtream
+-- stream_id of a movie or the series_id of a series. Episodes are loaded per
+-- series on demand into vod_episodes.
+
+CREATE TABLE IF NOT EXISTS vod_items (
+    id INTEGER PRIMARY KEY,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    kind TEXT CHECK(kind IN ('movie', 'series')) NOT NULL,
+    provider_id INTEGER NOT NULL,
+    name TEXT NOT NULL,
+    icon TEXT,
+    category_id INTEGER,
+    category_name TEXT,
+    -- File extension of movies (series episodes carry their own)
+    container_extension TEXT,
+    plot TEXT,
+    rating REAL,
+    added_at TEXT,
+    updated_at TEXT NOT NULL DEFAULT (datetime('now')),
+    UNIQUE(account_id, kind, provider_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_vod_items_kind_name ON vod_items(kind, name);
+
+CREATE TABLE IF NOT EXISTS vod_episodes (
+    id INTEGER PRIMARY KEY,
+    vod_item_id INTEGER NOT NULL REFERENCES vod_items(id) ON DELETE CASCADE,
+    episode_id INTEGER NOT NULL,
+    season INTEGER,
+    episode_num INTEGER,
+    title TEXT,
+    container_extension TEXT,
+    UNIQUE(vod_item_id, episode_id)
+);
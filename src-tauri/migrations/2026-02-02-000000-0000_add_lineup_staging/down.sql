@@ -0,0 +1,3 @@
+DROP VIEW IF EXISTS published_channel_settings;
+DROP TABLE IF EXISTS lineup_published;
+DELETE FROM settings WHERE key IN ('lineup_staging_enabled', 'lineup_published_at');
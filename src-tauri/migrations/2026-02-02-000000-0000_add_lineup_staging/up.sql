@@ -0,0 +1,26 @@
+-- Lineup staging
+--
+-- lineup_published holds the lineup Plex sees while staging is on (settings
+-- key lineup_staging_enabled = 'true'); edits to xmltv_channel_settings are
+-- then a draft until they are published. Plex-facing outputs read
+-- published_channel_settings, which resolves to the draft table when staging
+-- is off and to the published snapshot when it is on.
+
+CREATE TABLE IF NOT EXISTS lineup_published (
+    xmltv_channel_id INTEGER PRIMARY KEY NOT NULL REFERENCES xmltv_channels(id) ON DELETE CASCADE,
+    is_enabled INTEGER NOT NULL DEFAULT 0 CHECK (is_enabled IN (0, 1)),
+    plex_display_order INTEGER
+);
+
+CREATE VIEW IF NOT EXISTS published_channel_settings AS
+    SELECT xmltv_channel_id, is_enabled, plex_display_order
+    FROM xmltv_channel_settings
+    WHERE NOT EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    )
+    UNION ALL
+    SELECT xmltv_channel_id, is_enabled, plex_display_order
+    FROM lineup_published
+    WHERE EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    );
@@ -0,0 +1,19 @@
+-- Per-session bandwidth statistics
+--
+-- One row per finished stream session: the channel, the account it was
+-- played from, and the bytes sent to the client over its duration. day is
+-- the local date the session started ("YYYY-MM-DD"); daily and weekly
+-- totals are aggregated from it. Rows outlive deleted accounts (account_id
+-- becomes NULL) and are pruned after a year.
+
+CREATE TABLE IF NOT EXISTS stream_stats (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    xmltv_channel_id INTEGER NOT NULL,
+    account_id INTEGER REFERENCES accounts(id) ON DELETE SET NULL,
+    day TEXT NOT NULL,
+    started_at TEXT NOT NULL,
+    duration_seconds BIGINT NOT NULL DEFAULT 0,
+    bytes BIGINT NOT NULL DEFAULT 0
+);
+
+CREATE INDEX IF NOT EXISTS idx_stream_stats_day ON stream_stats(day);
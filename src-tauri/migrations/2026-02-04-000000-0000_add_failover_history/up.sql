@@ -0,0 +1,17 @@
+-- Failover history for automatic primary demotion
+--
+-- One row per successful failover from a channel's stream to a backup. When
+-- the same primary keeps failing over to backups within the configured
+-- window, the backup is promoted in its place. Rows are pruned once they are
+-- older than the longest configurable window.
+
+CREATE TABLE IF NOT EXISTS failover_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    xmltv_channel_id INTEGER NOT NULL REFERENCES xmltv_channels(id) ON DELETE CASCADE,
+    from_xtream_channel_id INTEGER NOT NULL REFERENCES xtream_channels(id) ON DELETE CASCADE,
+    to_xtream_channel_id INTEGER NOT NULL REFERENCES xtream_channels(id) ON DELETE CASCADE,
+    occurred_at TEXT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_failover_history_from
+    ON failover_history(xmltv_channel_id, from_xtream_channel_id, occurred_at);
@@ -0,0 +1 @@
+ALTER TABLE accounts DROP COLUMN reconnect_interval_minutes;
@@ -0,0 +1,8 @@
+-- Rolling upstream reconnect
+--
+-- Some providers drop every connection after a fixed time. When
+-- reconnect_interval_minutes is set, streams from the account open a fresh
+-- upstream connection that often and hand over to it without a gap. NULL
+-- disables the policy.
+
+ALTER TABLE accounts ADD COLUMN reconnect_interval_minutes INTEGER;
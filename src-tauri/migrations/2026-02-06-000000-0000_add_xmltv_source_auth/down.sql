@@ -0,0 +1,4 @@
+ALTER TABLE xmltv_sources DROP COLUMN auth_header_value_encrypted;
+ALTER TABLE xmltv_sources DROP COLUMN auth_header_name;
+ALTER TABLE xmltv_sources DROP COLUMN auth_password_encrypted;
+ALTER TABLE xmltv_sources DROP COLUMN auth_username;
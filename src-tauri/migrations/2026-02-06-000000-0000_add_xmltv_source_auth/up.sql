@@ -0,0 +1,10 @@
+-- Authentication for XMLTV sources
+--
+-- Commercial EPG feeds may need HTTP basic auth or a custom header (e.g. an
+-- API key). Secrets are stored through the credential manager like account
+-- passwords: a keychain placeholder or AES-encrypted bytes.
+
+ALTER TABLE xmltv_sources ADD COLUMN auth_username TEXT;
+ALTER TABLE xmltv_sources ADD COLUMN auth_password_encrypted BLOB;
+ALTER TABLE xmltv_sources ADD COLUMN auth_header_name TEXT;
+ALTER TABLE xmltv_sources ADD COLUMN auth_header_value_encrypted BLOB;
@@ -0,0 +1,8 @@
+ALTER TABLE programs DROP COLUMN credits;
+ALTER TABLE programs DROP COLUMN star_rating;
+ALTER TABLE programs DROP COLUMN rating_system;
+ALTER TABLE programs DROP COLUMN rating;
+ALTER TABLE programs DROP COLUMN episode_num_onscreen;
+ALTER TABLE programs DROP COLUMN episode_num_xmltv_ns;
+ALTER TABLE programs DROP COLUMN icon;
+ALTER TABLE programs DROP COLUMN sub_title;
@@ -0,0 +1,14 @@
+-- Extended program metadata from XMLTV
+--
+-- Sub-titles, artwork, both episode numbering systems, ratings and cast are
+-- kept so the generated /epg.xml carries them through to Plex. Credits are a
+-- JSON array of {role, name, character}.
+
+ALTER TABLE programs ADD COLUMN sub_title TEXT;
+ALTER TABLE programs ADD COLUMN icon TEXT;
+ALTER TABLE programs ADD COLUMN episode_num_xmltv_ns TEXT;
+ALTER TABLE programs ADD COLUMN episode_num_onscreen TEXT;
+ALTER TABLE programs ADD COLUMN rating TEXT;
+ALTER TABLE programs ADD COLUMN rating_system TEXT;
+ALTER TABLE programs ADD COLUMN star_rating TEXT;
+ALTER TABLE programs ADD COLUMN credits TEXT;
@@ -0,0 +1,2 @@
+DROP INDEX IF EXISTS idx_program_history_end_time;
+DROP TABLE IF EXISTS program_history;
@@ -0,0 +1,24 @@
+-- Guide history of programs that have aired
+--
+-- Programs are replaced on every EPG refresh and pruned after the retention
+-- window, so aired programs are copied here first to answer "what was on".
+-- Rows are keyed by the XMLTV channel id string rather than the channel row,
+-- which is recreated on each refresh, and pruned after the history window.
+
+CREATE TABLE IF NOT EXISTS program_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_id INTEGER NOT NULL REFERENCES xmltv_sources(id) ON DELETE CASCADE,
+    channel_id TEXT NOT NULL,
+    channel_name TEXT NOT NULL,
+    title TEXT NOT NULL,
+    sub_title TEXT,
+    description TEXT,
+    start_time TEXT NOT NULL,
+    end_time TEXT NOT NULL,
+    category TEXT,
+    episode_info TEXT,
+    archived_at TEXT NOT NULL DEFAULT (strftime('%Y-%m-%dT%H:%M:%SZ', 'now')),
+    UNIQUE(source_id, channel_id, start_time)
+);
+
+CREATE INDEX IF NOT EXISTS idx_program_history_end_time ON program_history(end_time);
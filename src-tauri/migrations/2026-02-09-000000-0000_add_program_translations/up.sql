@@ -0,0 +1,8 @@
+-- Program titles and descriptions in every language the guide provides
+--
+-- The title, sub_title and description columns keep the first value of each
+-- element. When a programme carries more than one language, all variants
+-- are kept as a JSON array of {lang, title, subTitle, description} so the
+-- generated /epg.xml can prefer the lineup's languages.
+
+ALTER TABLE programs ADD COLUMN translations TEXT;
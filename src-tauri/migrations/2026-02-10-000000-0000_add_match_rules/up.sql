@@ -0,0 +1,19 @@
+-- User-defined name rules applied while normalizing channel names for matching
+--
+-- rule_type is one of:
+--   strip_prefix  remove `pattern` from the start of the name (case-insensitive)
+--   strip_suffix  remove `pattern` from the end of the name (case-insensitive)
+--   regex         replace matches of `pattern` with `replacement` in the raw name
+--   alias         treat the words `pattern` and `replacement` as the same name
+-- Rules apply in sort_order, then id.
+
+CREATE TABLE IF NOT EXISTS match_rules (
+    id INTEGER PRIMARY KEY,
+    rule_type TEXT NOT NULL,
+    pattern TEXT NOT NULL,
+    replacement TEXT NOT NULL DEFAULT '',
+    is_enabled INTEGER NOT NULL DEFAULT 1,
+    sort_order INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
@@ -0,0 +1,2 @@
+ALTER TABLE xmltv_channel_settings DROP COLUMN placeholder_title;
+ALTER TABLE xmltv_channel_settings DROP COLUMN placeholder_mode;
@@ -0,0 +1,9 @@
+-- Per-channel override of placeholder guide generation
+--
+-- Enabled channels without programs in the guide window can get placeholder
+-- programs (settings key epg_placeholder). placeholder_mode overrides that
+-- for one channel: NULL follows the global setting, 1 always fills an empty
+-- guide, 0 never does. placeholder_title replaces the global title template.
+
+ALTER TABLE xmltv_channel_settings ADD COLUMN placeholder_mode INTEGER;
+ALTER TABLE xmltv_channel_settings ADD COLUMN placeholder_title TEXT;
@@ -0,0 +1,16 @@
+DROP VIEW IF EXISTS published_channel_settings;
+CREATE VIEW published_channel_settings AS
+    SELECT xmltv_channel_id, is_enabled, plex_display_order
+    FROM xmltv_channel_settings
+    WHERE NOT EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    )
+    UNION ALL
+    SELECT xmltv_channel_id, is_enabled, plex_display_order
+    FROM lineup_published
+    WHERE EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    );
+
+ALTER TABLE lineup_published DROP COLUMN channel_number;
+ALTER TABLE xmltv_channel_settings DROP COLUMN channel_number;
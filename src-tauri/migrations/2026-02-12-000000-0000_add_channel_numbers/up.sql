@@ -0,0 +1,23 @@
+-- Persistent channel numbers
+--
+-- channel_number is the number Plex shows for a channel (tvg-chno, GuideNumber
+-- and the /epg.xml channel id). Unlike the position in plex_display_order it
+-- does not change when the lineup is reordered. NULL falls back to the
+-- position. Numbers are staged with the rest of the lineup.
+
+ALTER TABLE xmltv_channel_settings ADD COLUMN channel_number INTEGER;
+ALTER TABLE lineup_published ADD COLUMN channel_number INTEGER;
+
+DROP VIEW IF EXISTS published_channel_settings;
+CREATE VIEW published_channel_settings AS
+    SELECT xmltv_channel_id, is_enabled, plex_display_order, channel_number
+    FROM xmltv_channel_settings
+    WHERE NOT EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    )
+    UNION ALL
+    SELECT xmltv_channel_id, is_enabled, plex_display_order, channel_number
+    FROM lineup_published
+    WHERE EXISTS (
+        SELECT 1 FROM settings WHERE key = 'lineup_staging_enabled' AND value = 'true'
+    );
@@ -0,0 +1,2 @@
+DROP TABLE IF EXISTS device_profile_channels;
+DROP TABLE IF EXISTS device_profiles;
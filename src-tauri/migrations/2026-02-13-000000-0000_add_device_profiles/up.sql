@@ -0,0 +1,25 @@
+-- HDHomeRun device profiles
+--
+-- Each profile is an extra virtual tuner with its own DeviceID and a subset
+-- of the lineup, served under /device/{slug}/ (discover.json, lineup.json,
+-- playlist.m3u). Plex sees every profile as a separate tuner, so different
+-- libraries can record from different channels. Channels keep the numbers
+-- they have in the main lineup, so all tuners share /epg.xml.
+--
+-- tuner_count NULL advertises the same tuner count as the main device.
+
+CREATE TABLE IF NOT EXISTS device_profiles (
+    id INTEGER PRIMARY KEY,
+    slug TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL,
+    device_id TEXT NOT NULL UNIQUE,
+    tuner_count INTEGER,
+    created_at TEXT NOT NULL DEFAULT (datetime('now')),
+    updated_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
+
+CREATE TABLE IF NOT EXISTS device_profile_channels (
+    profile_id INTEGER NOT NULL REFERENCES device_profiles(id) ON DELETE CASCADE,
+    xmltv_channel_id INTEGER NOT NULL REFERENCES xmltv_channels(id) ON DELETE CASCADE,
+    PRIMARY KEY (profile_id, xmltv_channel_id)
+);
@@ -0,0 +1,20 @@
+-- Channel mappings imported from a configuration file
+--
+-- Exports identify mappings by stable keys instead of database IDs: the
+-- XMLTV source URL and channel_id string, and the account URL, username and
+-- stream_id. Imported mappings wait here until channels and streams have been
+-- fetched again, then resolve_imported_mappings re-links them.
+
+CREATE TABLE IF NOT EXISTS pending_channel_mappings (
+    id INTEGER PRIMARY KEY,
+    source_url TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    account_url TEXT NOT NULL,
+    account_username TEXT NOT NULL,
+    stream_id INTEGER NOT NULL,
+    match_confidence REAL,
+    is_manual INTEGER NOT NULL DEFAULT 0,
+    is_primary INTEGER NOT NULL DEFAULT 0,
+    stream_priority INTEGER NOT NULL DEFAULT 0,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
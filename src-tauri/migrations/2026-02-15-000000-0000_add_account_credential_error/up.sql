@@ -0,0 +1,9 @@
+-- Credential health
+--
+-- verify_credentials tries to read every account password back from the
+-- keychain or the encrypted fallback. When that fails (for example because
+-- the keychain entry was deleted) the reason is kept here so the UI can ask
+-- for the password again before streams start failing. NULL means the last
+-- check succeeded, or none has run.
+
+ALTER TABLE accounts ADD COLUMN credential_error TEXT;
@@ -0,0 +1,2 @@
+ALTER TABLE accounts DROP COLUMN http_headers;
+ALTER TABLE accounts DROP COLUMN http_user_agent;
@@ -0,0 +1,9 @@
+-- Per-account request headers
+--
+-- Some providers reject the default User-Agent or need extra headers (a
+-- Referer, a token). http_user_agent replaces the User-Agent and
+-- http_headers holds extra headers as a JSON object; both apply to requests
+-- to the account's server host. NULL keeps the defaults.
+
+ALTER TABLE accounts ADD COLUMN http_user_agent TEXT;
+ALTER TABLE accounts ADD COLUMN http_headers TEXT;
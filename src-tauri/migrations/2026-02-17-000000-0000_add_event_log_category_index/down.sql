@@ -0,0 +1,3 @@
+-- Rollback: Remove the event log category index
+
+DROP INDEX IF EXISTS idx_event_log_category_timestamp;
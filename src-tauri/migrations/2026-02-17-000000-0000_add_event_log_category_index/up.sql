@@ -0,0 +1,7 @@
+-- Migration: Event log index for category filters
+--
+-- Event log search pages newest first by (timestamp, id); the category filter
+-- needs its own composite index, like the level filter
+-- (idx_event_log_level_timestamp).
+CREATE INDEX IF NOT EXISTS idx_event_log_category_timestamp
+ON event_log(category, timestamp DESC);
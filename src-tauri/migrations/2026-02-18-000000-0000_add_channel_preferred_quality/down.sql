@@ -0,0 +1 @@
+ALTER TABLE xmltv_channel_settings DROP COLUMN preferred_quality;
@@ -0,0 +1,8 @@
+-- Per-channel cap on stream quality
+--
+-- The stream proxy picks the highest quality at or below the preferred
+-- quality (settings key stream_preferred_quality, "best" for no cap).
+-- preferred_quality overrides that for one channel: NULL follows the global
+-- setting, otherwise one of 4K, FHD, HD, SD.
+
+ALTER TABLE xmltv_channel_settings ADD COLUMN preferred_quality TEXT;
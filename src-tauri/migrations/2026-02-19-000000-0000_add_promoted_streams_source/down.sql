@@ -0,0 +1 @@
+DELETE FROM xmltv_sources WHERE id = -1;
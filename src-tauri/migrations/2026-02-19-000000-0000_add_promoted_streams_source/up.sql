@@ -0,0 +1,8 @@
+-- Source of the synthetic channels created from orphan Xtream streams
+--
+-- promote_orphan_to_plex gives these channels source_id -1. With foreign
+-- keys enforced that must name a real xmltv_sources row. The row is never
+-- refreshed (inactive, no URL to fetch) and is hidden from the source lists.
+
+INSERT OR IGNORE INTO xmltv_sources (id, name, url, format, refresh_hour, is_active)
+VALUES (-1, 'Promoted streams', 'streamforge:promoted-streams', 'xml', 0, 0);
@@ -0,0 +1,13 @@
+-- Channel numbers imported from a configuration file
+--
+-- Like pending_channel_mappings, numbers are exported by the XMLTV source URL
+-- and channel_id string. They wait here until the channels have been fetched
+-- again, then resolve_imported_mappings restores them.
+
+CREATE TABLE IF NOT EXISTS pending_channel_numbers (
+    id INTEGER PRIMARY KEY,
+    source_url TEXT NOT NULL,
+    channel_id TEXT NOT NULL,
+    channel_number INTEGER NOT NULL,
+    created_at TEXT NOT NULL DEFAULT (datetime('now'))
+);
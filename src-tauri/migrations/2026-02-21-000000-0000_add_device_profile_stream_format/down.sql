@@ -0,0 +1 @@
+ALTER TABLE device_profiles DROP COLUMN stream_format;
@@ -0,0 +1,7 @@
+-- Stream container advertised by a device profile
+--
+-- Stream URLs in the profile's playlist.m3u and lineup.json carry
+-- ?format=m3u8 when set to m3u8, for clients that need HLS. "ts" keeps the
+-- plain MPEG-TS URLs.
+
+ALTER TABLE device_profiles ADD COLUMN stream_format TEXT NOT NULL DEFAULT 'ts';
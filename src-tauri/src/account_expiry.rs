@@ -0,0 +1,175 @@
+//! Xtream subscription expiry
+//!
+//! The Xtream login response carries the subscription's expiry date and
+//! connection limit, which `test_connection` and channel scans store on the
+//! account. The hourly scheduler job checks once a day for subscriptions that
+//! end within the warning window and logs a warning event for each, so a
+//! lapsing subscription shows up before streams stop working.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{accounts, settings};
+use crate::db::{Account, ProviderType, Setting};
+
+/// Settings key for how many days before expiry to warn
+pub const EXPIRY_WARNING_DAYS_KEY: &str = "account_expiry_warning_days";
+
+/// Default warning window in days
+pub const DEFAULT_EXPIRY_WARNING_DAYS: u32 = 7;
+
+/// Longest configurable warning window in days
+pub const MAX_EXPIRY_WARNING_DAYS: u32 = 90;
+
+const EXPIRY_LAST_CHECKED_KEY: &str = "account_expiry_last_checked";
+
+/// Hours between expiry checks
+const CHECK_INTERVAL_HOURS: i64 = 24;
+
+/// Expiry state of one Xtream account
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountExpiryInfo {
+    pub account_id: i32,
+    pub account_name: String,
+    /// RFC 3339; None if the provider reports no expiry or it was never checked
+    pub expiry_date: Option<String>,
+    /// Whole days left, negative once expired
+    pub days_remaining: Option<i64>,
+    pub expired: bool,
+    /// Ends within the warning window
+    pub expiring_soon: bool,
+    /// Connection limit reported by the provider
+    pub max_connections: Option<i32>,
+    pub last_check: Option<String>,
+}
+
+/// Expiry state of every active Xtream account at `now`
+pub fn expiry_info(
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+) -> QueryResult<Vec<AccountExpiryInfo>> {
+    let warning_days = i64::from(get_expiry_warning_days(conn));
+    let active: Vec<Account> = accounts::table
+        .filter(accounts::is_active.eq(1))
+        .filter(accounts::provider_type.eq(ProviderType::Xtream.to_string()))
+        .order(accounts::name.asc())
+        .load(conn)?;
+
+    Ok(active
+        .into_iter()
+        .filter_map(|account| {
+            let account_id = account.id?;
+            let expires_at = account
+                .expiry_date
+                .as_deref()
+                .and_then(|date| DateTime::parse_from_rfc3339(date).ok())
+                .map(|date| date.with_timezone(&Utc));
+            let days_remaining = expires_at.map(|date| (date - now).num_days());
+            Some(AccountExpiryInfo {
+                account_id,
+                account_name: account.name,
+                expiry_date: account.expiry_date,
+                days_remaining,
+                expired: expires_at.is_some_and(|date| date <= now),
+                expiring_soon: expires_at.is_some_and(|date| {
+                    date > now && date - now <= chrono::Duration::days(warning_days)
+                }),
+                max_connections: account.max_connections_actual,
+                last_check: account.last_check,
+            })
+        })
+        .collect())
+}
+
+/// Log a warning for each subscription that is expired or ending soon
+///
+/// Runs at most once per `CHECK_INTERVAL_HOURS`; returns how many warnings
+/// were logged.
+pub fn check_if_due(conn: &mut SqliteConnection, now: DateTime<Utc>) -> QueryResult<usize> {
+    let last_checked = settings::table
+        .filter(settings::key.eq(EXPIRY_LAST_CHECKED_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .optional()?
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|date| date.with_timezone(&Utc));
+    if last_checked.is_some_and(|last| now - last < chrono::Duration::hours(CHECK_INTERVAL_HOURS)) {
+        return Ok(0);
+    }
+
+    let mut warned = 0;
+    for info in expiry_info(conn, now)? {
+        let message = match info.days_remaining {
+            _ if info.expired => format!("Subscription expired: {}", info.account_name),
+            Some(0) if info.expiring_soon => {
+                format!("Subscription expires today: {}", info.account_name)
+            }
+            Some(days) if info.expiring_soon => format!(
+                "Subscription expires in {} day{}: {}",
+                days,
+                if days == 1 { "" } else { "s" },
+                info.account_name
+            ),
+            _ => continue,
+        };
+        let details = serde_json::to_string(&info).unwrap_or_default();
+        let _ = log_event_internal(conn, "warn", "connection", &message, Some(&details));
+        warned += 1;
+    }
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(EXPIRY_LAST_CHECKED_KEY, now.to_rfc3339()))
+        .execute(conn)?;
+    Ok(warned)
+}
+
+/// Days before expiry to warn
+pub fn get_expiry_warning_days(conn: &mut SqliteConnection) -> u32 {
+    settings::table
+        .filter(settings::key.eq(EXPIRY_WARNING_DAYS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_EXPIRY_WARNING_DAYS)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expiry_warnings() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, expiry_date) VALUES
+             (1, 'Soon', 'http://a', 'u', x'', '2026-03-04T12:00:00+00:00'),
+             (2, 'Later', 'http://b', 'u', x'', '2026-06-01T00:00:00+00:00'),
+             (3, 'Lapsed', 'http://c', 'u', x'', '2026-02-01T00:00:00+00:00'),
+             (4, 'Unknown', 'http://d', 'u', x'', NULL)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        let now = DateTime::parse_from_rfc3339("2026-03-01T00:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        let info = expiry_info(&mut conn, now).unwrap();
+        let by_id = |id: i32| info.iter().find(|i| i.account_id == id).unwrap();
+        assert_eq!(by_id(1).days_remaining, Some(3));
+        assert!(by_id(1).expiring_soon && !by_id(1).expired);
+        assert!(!by_id(2).expiring_soon);
+        assert!(by_id(3).expired);
+        assert_eq!(by_id(4).days_remaining, None);
+
+        assert_eq!(check_if_due(&mut conn, now).unwrap(), 2);
+        // Not due again within the day
+        assert_eq!(
+            check_if_due(&mut conn, now + chrono::Duration::hours(1)).unwrap(),
+            0
+        );
+    }
+}
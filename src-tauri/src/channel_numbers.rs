@@ -0,0 +1,315 @@
+//! Persistent channel numbers
+//!
+//! Every channel in the lineup has the number Plex shows and sorts by:
+//! `tvg-chno` in /playlist.m3u, `GuideNumber` in /lineup.json and the channel
+//! id in /epg.xml. A number set in `xmltv_channel_settings.channel_number`
+//! stays with the channel when the lineup is reordered. Channels without one
+//! fall back to their position (`plex_display_order + 1`), and channels with
+//! neither are numbered after the highest number in use.
+//!
+//! Numbers are part of the staged lineup (see `crate::lineup_staging`), so
+//! the outputs read them from `published_channel_settings`.
+
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+use crate::db::schema::{xmltv_channel_settings, xmltv_channels};
+
+/// Highest channel number that can be set
+pub const MAX_CHANNEL_NUMBER: i32 = 99_999;
+
+/// Channel numbers of lineup channels, given in output order as
+/// (channel_number, plex_display_order)
+///
+/// A position-based number already set explicitly on another channel is
+/// skipped in favour of a fallback number, so no two channels share one.
+pub fn resolve_channel_numbers(channels: &[(Option<i32>, Option<i32>)]) -> Vec<i32> {
+    let explicit: HashSet<i32> = channels.iter().filter_map(|c| c.0).collect();
+    let mut fallback = channels
+        .iter()
+        .filter_map(|&(number, order)| number.or(order.map(|o| o + 1)))
+        .max()
+        .unwrap_or(0)
+        + 1;
+
+    channels
+        .iter()
+        .map(|&(number, order)| {
+            if let Some(number) = number {
+                return number;
+            }
+            match order.map(|o| o + 1) {
+                Some(position) if !explicit.contains(&position) => position,
+                _ => {
+                    fallback += 1;
+                    fallback - 1
+                }
+            }
+        })
+        .collect()
+}
+
+/// How automatically assigned numbers are spaced
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum NumberGap {
+    /// Consecutive numbers
+    Sequential,
+    /// `step` apart, leaving room to add channels in between
+    Step { step: i32 },
+    /// Each stream category starts at the next multiple of `block`
+    CategoryBlocks { block: i32 },
+}
+
+/// Options of an automatic numbering run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutoNumberOptions {
+    /// Number of the first channel
+    pub start: i32,
+    pub gap: NumberGap,
+    /// Only number channels without a number, keeping the numbers in use
+    pub keep_existing: bool,
+}
+
+impl AutoNumberOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_CHANNEL_NUMBER).contains(&self.start) {
+            return Err(format!(
+                "Start number must be between 1 and {}",
+                MAX_CHANNEL_NUMBER
+            ));
+        }
+        match self.gap {
+            NumberGap::Sequential => Ok(()),
+            NumberGap::Step { step } if (1..=1000).contains(&step) => Ok(()),
+            NumberGap::Step { .. } => Err("Step must be between 1 and 1000".to_string()),
+            NumberGap::CategoryBlocks { block } if (2..=10_000).contains(&block) => Ok(()),
+            NumberGap::CategoryBlocks { .. } => {
+                Err("Category block must be between 2 and 10000".to_string())
+            }
+        }
+    }
+}
+
+/// An enabled channel, in lineup order, to be numbered
+#[derive(QueryableByName, Debug, Clone)]
+pub struct NumberingChannel {
+    #[diesel(sql_type = Integer)]
+    pub id: i32,
+    #[diesel(sql_type = Nullable<Integer>)]
+    pub channel_number: Option<i32>,
+    /// Category of the primary stream
+    #[diesel(sql_type = Nullable<Text>)]
+    pub category: Option<String>,
+}
+
+/// Numbers for `channels` as (channel id, number), skipping channels that keep theirs
+pub fn plan_numbers(channels: &[NumberingChannel], options: &AutoNumberOptions) -> Vec<(i32, i32)> {
+    let mut taken: HashSet<i32> = if options.keep_existing {
+        channels.iter().filter_map(|c| c.channel_number).collect()
+    } else {
+        HashSet::new()
+    };
+    let step = match options.gap {
+        NumberGap::Step { step } => step,
+        _ => 1,
+    };
+
+    let mut planned = Vec::new();
+    let mut next = options.start;
+    let mut previous_category: Option<Option<&str>> = None;
+    for channel in channels {
+        if options.keep_existing && channel.channel_number.is_some() {
+            continue;
+        }
+        let category = channel.category.as_deref();
+        if let NumberGap::CategoryBlocks { block } = options.gap {
+            if previous_category.is_some_and(|previous| previous != category) {
+                next = (next + block - 1) / block * block;
+            }
+            previous_category = Some(category);
+        }
+        while taken.contains(&next) {
+            next += 1;
+        }
+        planned.push((channel.id, next));
+        taken.insert(next);
+        next += step;
+    }
+    planned
+}
+
+/// Enabled channels of the draft lineup in lineup order
+fn lineup_channels(conn: &mut SqliteConnection) -> QueryResult<Vec<NumberingChannel>> {
+    diesel::sql_query(
+        r#"
+        SELECT
+            xc.id,
+            xcs.channel_number,
+            (
+                SELECT xtc.category_name
+                FROM channel_mappings cm
+                INNER JOIN xtream_channels xtc ON cm.xtream_channel_id = xtc.id
+                WHERE cm.xmltv_channel_id = xc.id
+                ORDER BY
+                    CASE WHEN cm.is_primary = 1 THEN 0 ELSE 1 END,
+                    cm.stream_priority ASC,
+                    cm.id ASC
+                LIMIT 1
+            ) AS category
+        FROM xmltv_channels xc
+        INNER JOIN xmltv_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
+        WHERE xcs.is_enabled = 1
+        ORDER BY
+            CASE WHEN xcs.plex_display_order IS NULL THEN 1 ELSE 0 END,
+            xcs.plex_display_order ASC,
+            xc.display_name ASC
+        "#,
+    )
+    .load(conn)
+}
+
+/// Number the enabled channels, returning the numbers given as (channel id, number)
+pub fn auto_assign(
+    conn: &mut SqliteConnection,
+    options: &AutoNumberOptions,
+) -> Result<Vec<(i32, i32)>, String> {
+    options.validate()?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let channels = lineup_channels(conn)?;
+        let planned = plan_numbers(&channels, options);
+        if !options.keep_existing {
+            // Disabled channels give up their numbers so they can't collide
+            diesel::update(xmltv_channel_settings::table)
+                .set(xmltv_channel_settings::channel_number.eq(None::<i32>))
+                .execute(conn)?;
+        }
+        let now = chrono::Utc::now().to_rfc3339();
+        for &(id, number) in &planned {
+            diesel::update(
+                xmltv_channel_settings::table
+                    .filter(xmltv_channel_settings::xmltv_channel_id.eq(id)),
+            )
+            .set((
+                xmltv_channel_settings::channel_number.eq(number),
+                xmltv_channel_settings::updated_at.eq(&now),
+            ))
+            .execute(conn)?;
+        }
+        Ok(planned)
+    })
+    .map_err(|e| format!("Failed to assign channel numbers: {}", e))
+}
+
+/// Set or clear (None) the number of a channel
+///
+/// A number can only belong to one channel, enabled or not.
+pub fn set_channel_number(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    number: Option<i32>,
+) -> Result<(), String> {
+    if let Some(number) = number {
+        if !(1..=MAX_CHANNEL_NUMBER).contains(&number) {
+            return Err(format!(
+                "Channel number must be between 1 and {}",
+                MAX_CHANNEL_NUMBER
+            ));
+        }
+        let holder: Option<String> = xmltv_channel_settings::table
+            .inner_join(
+                xmltv_channels::table.on(xmltv_channels::id
+                    .assume_not_null()
+                    .eq(xmltv_channel_settings::xmltv_channel_id)),
+            )
+            .filter(xmltv_channel_settings::channel_number.eq(number))
+            .filter(xmltv_channel_settings::xmltv_channel_id.ne(xmltv_channel_id))
+            .select(xmltv_channels::display_name)
+            .first(conn)
+            .optional()
+            .map_err(|e| format!("Failed to check channel numbers: {}", e))?;
+        if let Some(holder) = holder {
+            return Err(format!("Channel number {} is used by {}", number, holder));
+        }
+    }
+
+    let updated = diesel::update(
+        xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(xmltv_channel_id)),
+    )
+    .set((
+        xmltv_channel_settings::channel_number.eq(number),
+        xmltv_channel_settings::updated_at.eq(chrono::Utc::now().to_rfc3339()),
+    ))
+    .execute(conn)
+    .map_err(|e| format!("Failed to save channel number: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Channel {} has no settings", xmltv_channel_id));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn channel(id: i32, channel_number: Option<i32>, category: &str) -> NumberingChannel {
+        NumberingChannel {
+            id,
+            channel_number,
+            category: Some(category.to_string()),
+        }
+    }
+
+    #[test]
+    fn test_resolve_channel_numbers() {
+        // Positions as before, fallback after the highest number
+        assert_eq!(
+            resolve_channel_numbers(&[(None, None), (None, Some(5)), (None, None)]),
+            vec![7, 6, 8]
+        );
+        // Set numbers stay, a position taken by one falls back
+        assert_eq!(
+            resolve_channel_numbers(&[(Some(2), Some(0)), (None, Some(1)), (Some(101), None)]),
+            vec![2, 102, 101]
+        );
+    }
+
+    #[test]
+    fn test_plan_numbers() {
+        let channels = vec![
+            channel(1, None, "News"),
+            channel(2, Some(3), "News"),
+            channel(3, None, "Sports"),
+            channel(4, None, "Sports"),
+        ];
+        let options = |gap, keep_existing| AutoNumberOptions {
+            start: 1,
+            gap,
+            keep_existing,
+        };
+
+        assert_eq!(
+            plan_numbers(&channels, &options(NumberGap::Sequential, false)),
+            vec![(1, 1), (2, 2), (3, 3), (4, 4)]
+        );
+        assert_eq!(
+            plan_numbers(&channels, &options(NumberGap::Sequential, true)),
+            vec![(1, 1), (3, 2), (4, 4)]
+        );
+        assert_eq!(
+            plan_numbers(&channels, &options(NumberGap::Step { step: 10 }, false)),
+            vec![(1, 1), (2, 11), (3, 21), (4, 31)]
+        );
+        assert_eq!(
+            plan_numbers(
+                &channels,
+                &options(NumberGap::CategoryBlocks { block: 100 }, false)
+            ),
+            vec![(1, 1), (2, 2), (3, 100), (4, 101)]
+        );
+    }
+}
@@ -0,0 +1,530 @@
+//! Automatic channel enablement policy
+//!
+//! An optional policy that keeps the lineup in step with guide data and
+//! stream health:
+//!
+//! - **Auto-enable**: a disabled channel is enabled once it has a mapped
+//!   stream and guide data at least `min_epg_hours` ahead
+//! - **Auto-disable**: an enabled channel is disabled when every mapped stream
+//!   has failed continuously for `dead_days`
+//!
+//! Stream health comes from the stream proxy, which records each connection
+//! attempt in `stream_health`. A stream that is never played has no health
+//! record and is never considered dead.
+//!
+//! The policy remembers its last action per channel. If the user reverses it
+//! (disables a channel the policy enabled, or re-enables one it disabled),
+//! the policy leaves that channel alone.
+//!
+//! The policy runs after each scheduled EPG refresh, when guide coverage
+//! changes, and can be previewed as a dry run.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+use crate::commands::logs::log_event_internal;
+use crate::commands::xmltv_channels::bulk_toggle_channels_internal;
+use crate::db::schema::{
+    channel_mappings, channel_policy_actions, programs, settings, stream_health,
+    xmltv_channel_settings, xmltv_channels,
+};
+use crate::db::Setting;
+
+/// Settings key for the policy configuration (JSON)
+pub const CHANNEL_POLICY_KEY: &str = "channel_auto_policy";
+
+/// Timestamp format shared with stored XMLTV program times
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+const POLICY_ACTION_ENABLED: &str = "enabled";
+const POLICY_ACTION_DISABLED: &str = "disabled";
+
+/// Policy configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChannelPolicyConfig {
+    /// Run the policy after scheduled EPG refreshes
+    pub enabled: bool,
+    pub auto_enable: bool,
+    pub auto_disable: bool,
+    /// Guide data needed ahead of now before a channel is auto-enabled
+    pub min_epg_hours: u32,
+    /// Days all mapped streams must have been failing before auto-disable
+    pub dead_days: u32,
+}
+
+impl Default for ChannelPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            auto_enable: true,
+            auto_disable: true,
+            min_epg_hours: 24,
+            dead_days: 7,
+        }
+    }
+}
+
+impl ChannelPolicyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=336).contains(&self.min_epg_hours) {
+            return Err("Minimum EPG hours must be between 1 and 336".to_string());
+        }
+        if !(1..=365).contains(&self.dead_days) {
+            return Err("Dead stream days must be between 1 and 365".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// A channel the policy would enable or disable
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyChange {
+    pub channel_id: i32,
+    pub display_name: String,
+    pub reason: String,
+}
+
+/// Result of evaluating (and optionally applying) the policy
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PolicyReport {
+    /// True when nothing was changed (preview)
+    pub dry_run: bool,
+    pub to_enable: Vec<PolicyChange>,
+    pub to_disable: Vec<PolicyChange>,
+}
+
+impl PolicyReport {
+    /// Whether applying the report enabled or disabled any channel, which
+    /// outdates the EPG and playlist
+    pub fn changes_lineup(&self) -> bool {
+        !self.dry_run && (!self.to_enable.is_empty() || !self.to_disable.is_empty())
+    }
+}
+
+fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Load the policy configuration (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> ChannelPolicyConfig {
+    settings::table
+        .filter(settings::key.eq(CHANNEL_POLICY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the policy configuration
+pub fn save_config(
+    conn: &mut SqliteConnection,
+    config: &ChannelPolicyConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize channel policy: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(CHANNEL_POLICY_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Record the outcome of a connection attempt to a provider stream
+pub fn record_stream_result(
+    conn: &mut SqliteConnection,
+    xtream_channel_id: i32,
+    success: bool,
+) -> QueryResult<()> {
+    let now = format_timestamp(Utc::now());
+
+    if success {
+        diesel::insert_into(stream_health::table)
+            .values((
+                stream_health::xtream_channel_id.eq(xtream_channel_id),
+                stream_health::last_success_at.eq(&now),
+            ))
+            .on_conflict(stream_health::xtream_channel_id)
+            .do_update()
+            .set((
+                stream_health::last_success_at.eq(&now),
+                stream_health::failing_since.eq(None::<String>),
+            ))
+            .execute(conn)?;
+    } else {
+        // failing_since keeps the first failure of the current streak
+        diesel::insert_into(stream_health::table)
+            .values((
+                stream_health::xtream_channel_id.eq(xtream_channel_id),
+                stream_health::last_failure_at.eq(&now),
+                stream_health::failing_since.eq(&now),
+            ))
+            .on_conflict(stream_health::xtream_channel_id)
+            .do_update()
+            .set((
+                stream_health::last_failure_at.eq(&now),
+                stream_health::failing_since.eq(diesel::dsl::sql::<
+                    diesel::sql_types::Nullable<diesel::sql_types::Text>,
+                >(
+                    "COALESCE(stream_health.failing_since, excluded.failing_since)",
+                )),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Work out which channels the policy would change
+pub fn evaluate(
+    conn: &mut SqliteConnection,
+    config: &ChannelPolicyConfig,
+    now: DateTime<Utc>,
+) -> QueryResult<PolicyReport> {
+    let channels: Vec<(Option<i32>, String)> = xmltv_channels::table
+        .select((xmltv_channels::id, xmltv_channels::display_name))
+        .load(conn)?;
+
+    let enabled: HashSet<i32> = xmltv_channel_settings::table
+        .filter(xmltv_channel_settings::is_enabled.eq(1))
+        .select(xmltv_channel_settings::xmltv_channel_id)
+        .load::<i32>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut mapped: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (xmltv_id, xtream_id) in channel_mappings::table
+        .select((
+            channel_mappings::xmltv_channel_id,
+            channel_mappings::xtream_channel_id,
+        ))
+        .load::<(i32, i32)>(conn)?
+    {
+        mapped.entry(xmltv_id).or_default().push(xtream_id);
+    }
+
+    let failing_since: HashMap<i32, String> = stream_health::table
+        .filter(stream_health::failing_since.is_not_null())
+        .select((
+            stream_health::xtream_channel_id,
+            stream_health::failing_since,
+        ))
+        .load::<(i32, Option<String>)>(conn)?
+        .into_iter()
+        .filter_map(|(id, since)| since.map(|since| (id, since)))
+        .collect();
+
+    let guide_until: HashMap<i32, String> = programs::table
+        .group_by(programs::xmltv_channel_id)
+        .select((
+            programs::xmltv_channel_id,
+            diesel::dsl::max(programs::end_time),
+        ))
+        .load::<(i32, Option<String>)>(conn)?
+        .into_iter()
+        .filter_map(|(id, end)| end.map(|end| (id, end)))
+        .collect();
+
+    let last_actions: HashMap<i32, String> = channel_policy_actions::table
+        .select((
+            channel_policy_actions::xmltv_channel_id,
+            channel_policy_actions::action,
+        ))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .collect();
+
+    let dead_cutoff = format_timestamp(now - chrono::Duration::days(config.dead_days as i64));
+    let epg_needed_until =
+        format_timestamp(now + chrono::Duration::hours(config.min_epg_hours as i64));
+
+    let mut report = PolicyReport::default();
+    for (id, display_name) in channels {
+        let Some(id) = id else { continue };
+        let Some(streams) = mapped.get(&id) else {
+            continue;
+        };
+
+        // Dead: every mapped stream has been failing since before the cutoff
+        let dead_since = streams
+            .iter()
+            .map(|stream| failing_since.get(stream))
+            .collect::<Option<Vec<_>>>()
+            .and_then(|since| since.into_iter().max().cloned())
+            .filter(|latest| *latest <= dead_cutoff);
+        let last_action = last_actions.get(&id).map(String::as_str);
+
+        if enabled.contains(&id) {
+            if config.auto_disable && last_action != Some(POLICY_ACTION_DISABLED) {
+                if let Some(since) = dead_since {
+                    report.to_disable.push(PolicyChange {
+                        channel_id: id,
+                        display_name,
+                        reason: format!(
+                            "All {} mapped streams failing since {}",
+                            streams.len(),
+                            since
+                        ),
+                    });
+                }
+            }
+        } else if config.auto_enable
+            && last_action != Some(POLICY_ACTION_ENABLED)
+            && dead_since.is_none()
+        {
+            if let Some(until) = guide_until
+                .get(&id)
+                .filter(|until| **until >= epg_needed_until)
+            {
+                report.to_enable.push(PolicyChange {
+                    channel_id: id,
+                    display_name,
+                    reason: format!("Mapped stream and guide data until {}", until),
+                });
+            }
+        }
+    }
+
+    report.dry_run = true;
+    Ok(report)
+}
+
+/// Evaluate the policy and apply the changes
+///
+/// Logs a summary event when anything changed.
+pub fn apply(
+    conn: &mut SqliteConnection,
+    config: &ChannelPolicyConfig,
+    now: DateTime<Utc>,
+) -> QueryResult<PolicyReport> {
+    let mut report = evaluate(conn, config, now)?;
+    report.dry_run = false;
+    if report.to_enable.is_empty() && report.to_disable.is_empty() {
+        return Ok(report);
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        for (changes, enable, action) in [
+            (&report.to_enable, true, POLICY_ACTION_ENABLED),
+            (&report.to_disable, false, POLICY_ACTION_DISABLED),
+        ] {
+            if changes.is_empty() {
+                continue;
+            }
+            let ids: Vec<i32> = changes.iter().map(|c| c.channel_id).collect();
+            bulk_toggle_channels_internal(conn, &ids, enable)?;
+
+            let applied_at = format_timestamp(now);
+            let rows: Vec<_> = ids
+                .iter()
+                .map(|id| {
+                    (
+                        channel_policy_actions::xmltv_channel_id.eq(*id),
+                        channel_policy_actions::action.eq(action),
+                        channel_policy_actions::applied_at.eq(&applied_at),
+                    )
+                })
+                .collect();
+            diesel::insert_into(channel_policy_actions::table)
+                .values(&rows)
+                .on_conflict(channel_policy_actions::xmltv_channel_id)
+                .do_update()
+                .set((
+                    channel_policy_actions::action.eq(excluded(channel_policy_actions::action)),
+                    channel_policy_actions::applied_at
+                        .eq(excluded(channel_policy_actions::applied_at)),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })?;
+
+    let details = serde_json::json!({
+        "enabled": report.to_enable,
+        "disabled": report.to_disable,
+    });
+    let _ = log_event_internal(
+        conn,
+        "info",
+        "match",
+        &format!(
+            "Channel policy: enabled {} and disabled {} channels",
+            report.to_enable.len(),
+            report.to_disable.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(report)
+}
+
+/// Apply the policy if it is turned on (called after scheduled EPG refreshes)
+///
+/// Marks the EPG and playlist outdated when channels changed, like
+/// `run_channel_policy` does.
+pub fn run_scheduled(conn: &mut SqliteConnection) {
+    let config = load_config(conn);
+    if !config.enabled {
+        return;
+    }
+    match apply(conn, &config, Utc::now()) {
+        Ok(report) if report.changes_lineup() => {
+            crate::server::priming::bump_epg_generation();
+        }
+        Ok(_) => {}
+        Err(e) => tracing::error!("Channel policy failed: {}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn seed_channel(
+        conn: &mut SqliteConnection,
+        id: i32,
+        enabled: bool,
+        guide_until: Option<&str>,
+    ) {
+        diesel::sql_query(format!(
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES ({id}, 1, 'ch{id}', 'Channel {id}')"
+        ))
+        .execute(conn)
+        .unwrap();
+        diesel::sql_query(format!(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled) VALUES ({id}, {})",
+            enabled as i32
+        ))
+        .execute(conn)
+        .unwrap();
+        diesel::sql_query(format!(
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES ({id}, {}, 1.0, 0, 1, 0)",
+            id * 10
+        ))
+        .execute(conn)
+        .unwrap();
+        if let Some(until) = guide_until {
+            diesel::sql_query(format!(
+                "INSERT INTO programs (xmltv_channel_id, title, start_time, end_time) VALUES ({id}, 'Show', '2026-01-01T00:00:00Z', '{until}')"
+            ))
+            .execute(conn)
+            .unwrap();
+        }
+    }
+
+    fn mark_failing(conn: &mut SqliteConnection, xtream_id: i32, since: &str) {
+        diesel::sql_query(format!(
+            "INSERT INTO stream_health (xtream_channel_id, last_failure_at, failing_since) VALUES ({xtream_id}, '{since}', '{since}')"
+        ))
+        .execute(conn)
+        .unwrap();
+    }
+
+    fn now() -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339("2026-01-20T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_evaluate_policy() {
+        let mut conn = test_connection();
+        let config = ChannelPolicyConfig::default();
+
+        // Disabled, guide covers 2 days -> enable
+        seed_channel(&mut conn, 1, false, Some("2026-01-22T12:00:00Z"));
+        // Disabled, guide covers only 6 hours -> leave
+        seed_channel(&mut conn, 2, false, Some("2026-01-20T18:00:00Z"));
+        // Enabled, stream failing for 10 days -> disable
+        seed_channel(&mut conn, 3, true, Some("2026-01-22T12:00:00Z"));
+        mark_failing(&mut conn, 30, "2026-01-10T00:00:00Z");
+        // Enabled, stream failing for 2 days -> leave
+        seed_channel(&mut conn, 4, true, None);
+        mark_failing(&mut conn, 40, "2026-01-18T12:00:00Z");
+
+        let report = evaluate(&mut conn, &config, now()).unwrap();
+        assert!(report.dry_run);
+        assert!(!report.changes_lineup());
+        assert_eq!(
+            report
+                .to_enable
+                .iter()
+                .map(|c| c.channel_id)
+                .collect::<Vec<_>>(),
+            vec![1]
+        );
+        assert_eq!(
+            report
+                .to_disable
+                .iter()
+                .map(|c| c.channel_id)
+                .collect::<Vec<_>>(),
+            vec![3]
+        );
+
+        // Applying changes the lineup and the policy does not repeat itself
+        let applied = apply(&mut conn, &config, now()).unwrap();
+        assert!(!applied.dry_run);
+        assert!(applied.changes_lineup());
+        let report = evaluate(&mut conn, &config, now()).unwrap();
+        assert!(report.to_enable.is_empty());
+        assert!(report.to_disable.is_empty());
+        assert!(!apply(&mut conn, &config, now()).unwrap().changes_lineup());
+
+        // The user re-enables the dead channel: the policy leaves it alone
+        bulk_toggle_channels_internal(&mut conn, &[3], true).unwrap();
+        assert!(evaluate(&mut conn, &config, now())
+            .unwrap()
+            .to_disable
+            .is_empty());
+    }
+
+    #[test]
+    fn test_record_stream_result() {
+        let mut conn = test_connection();
+
+        record_stream_result(&mut conn, 5, false).unwrap();
+        let first: Option<String> = stream_health::table
+            .select(stream_health::failing_since)
+            .first(&mut conn)
+            .unwrap();
+        assert!(first.is_some());
+
+        // Later failures keep the start of the streak
+        diesel::update(stream_health::table)
+            .set(stream_health::failing_since.eq("2026-01-01T00:00:00Z"))
+            .execute(&mut conn)
+            .unwrap();
+        record_stream_result(&mut conn, 5, false).unwrap();
+        let since: Option<String> = stream_health::table
+            .select(stream_health::failing_since)
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(since.as_deref(), Some("2026-01-01T00:00:00Z"));
+
+        // A success ends the streak
+        record_stream_result(&mut conn, 5, true).unwrap();
+        let since: Option<String> = stream_health::table
+            .select(stream_health::failing_since)
+            .first(&mut conn)
+            .unwrap();
+        assert!(since.is_none());
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(ChannelPolicyConfig::default().validate().is_ok());
+        let config = ChannelPolicyConfig {
+            dead_days: 0,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+    }
+}
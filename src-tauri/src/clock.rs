@@ -0,0 +1,68 @@
+//! Time source for time-based features
+//!
+//! The scheduler (missed refreshes, pruning and re-scan intervals), the
+//! maintenance window and the stream statistics recorder read the current
+//! time through `Clock` instead of calling `Local::now()` directly, so tests
+//! can drive them with `MockClock` independent of the wall clock and the
+//! machine's time zone.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+
+/// Source of the current time
+pub trait Clock: Send + Sync {
+    /// Current local time, carrying the local UTC offset
+    fn now(&self) -> DateTime<FixedOffset>;
+
+    /// Current time in UTC
+    fn now_utc(&self) -> DateTime<Utc> {
+        self.now().with_timezone(&Utc)
+    }
+}
+
+/// The system clock and time zone
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        Local::now().fixed_offset()
+    }
+}
+
+/// Shared handle to the system clock
+pub fn system() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// Clock that only moves when told to
+#[cfg(test)]
+#[derive(Debug)]
+pub struct MockClock {
+    now: std::sync::Mutex<DateTime<FixedOffset>>,
+}
+
+#[cfg(test)]
+impl MockClock {
+    /// Start at an RFC 3339 time; its offset is the simulated local time zone
+    pub fn at(rfc3339: &str) -> Arc<Self> {
+        let now = DateTime::parse_from_rfc3339(rfc3339).expect("valid RFC 3339 time");
+        Arc::new(Self {
+            now: std::sync::Mutex::new(now),
+        })
+    }
+
+    /// Move the clock forward
+    pub fn advance(&self, by: chrono::Duration) {
+        let mut now = self.now.lock().unwrap();
+        *now += by;
+    }
+}
+
+#[cfg(test)]
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<FixedOffset> {
+        *self.now.lock().unwrap()
+    }
+}
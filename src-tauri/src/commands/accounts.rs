@@ -1,12 +1,14 @@
 //! Account management Tauri commands
 //!
 //! This module provides commands for adding, retrieving, updating, and deleting
-//! Xtream Codes account credentials with secure password storage.
+//! Xtream Codes account credentials with secure password storage, and M3U
+//! playlist accounts, which need no credentials.
 //!
 //! Story 6-3: Connection event logging for Xtream authentication
 
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use tauri::{AppHandle, Manager, State};
 use thiserror::Error;
 
@@ -14,9 +16,9 @@ use crate::commands::logs::log_event_internal;
 use crate::credentials::CredentialManager;
 use crate::db::{
     schema::accounts,
-    Account, AccountStatusUpdate, DbConnection, NewAccount,
+    Account, AccountStatusUpdate, DbConnection, NewAccount, ProviderType,
 };
-use crate::xtream::XtreamClient;
+use crate::xtream::{request_headers, M3uClient, XtreamClient};
 
 /// Error types for account operations
 #[derive(Debug, Error)]
@@ -67,11 +69,19 @@ pub struct AccountResponse {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    pub provider_type: String,
     // Connection status fields
     pub connection_status: Option<String>,
     pub expiry_date: Option<String>,
     pub max_connections_actual: Option<i32>,
     pub active_connections: Option<i32>,
+    pub reconnect_interval_minutes: Option<i32>,
+    /// Set when the stored password could not be read; the user must re-enter it
+    pub credential_error: Option<String>,
+    /// User-Agent sent to the server (None = default)
+    pub http_user_agent: Option<String>,
+    /// Extra headers sent to the server
+    pub http_headers: BTreeMap<String, String>,
 }
 
 impl From<Account> for AccountResponse {
@@ -85,11 +95,16 @@ impl From<Account> for AccountResponse {
             is_active: account.is_active != 0,
             created_at: account.created_at,
             updated_at: account.updated_at,
+            provider_type: account.provider_type,
             // Connection status fields
             connection_status: account.connection_status,
             expiry_date: account.expiry_date,
             max_connections_actual: account.max_connections_actual,
             active_connections: account.active_connections,
+            reconnect_interval_minutes: account.reconnect_interval_minutes,
+            credential_error: account.credential_error,
+            http_headers: request_headers::parse_stored(account.http_headers.as_deref()),
+            http_user_agent: account.http_user_agent,
         }
     }
 }
@@ -99,9 +114,14 @@ impl From<Account> for AccountResponse {
 #[serde(rename_all = "camelCase")]
 pub struct AddAccountRequest {
     pub name: String,
+    /// Xtream server URL, or the playlist URL for M3U accounts
     pub server_url: String,
+    #[serde(default)]
     pub username: String,
+    #[serde(default)]
     pub password: String,
+    #[serde(default)]
+    pub provider_type: ProviderType,
 }
 
 /// Request type for updating an account
@@ -110,8 +130,15 @@ pub struct AddAccountRequest {
 pub struct UpdateAccountRequest {
     pub name: String,
     pub server_url: String,
+    #[serde(default)]
     pub username: String,
     pub password: Option<String>, // Optional - only update if provided
+    /// User-Agent override; None or blank restores the default
+    #[serde(default)]
+    pub http_user_agent: Option<String>,
+    /// Extra request headers (replaces the stored ones)
+    #[serde(default)]
+    pub http_headers: BTreeMap<String, String>,
 }
 
 /// Normalize server URL by removing trailing slashes
@@ -119,13 +146,8 @@ fn normalize_url(url: &str) -> String {
     url.trim().trim_end_matches('/').to_string()
 }
 
-/// Validate account input fields
-fn validate_account_input(
-    name: &str,
-    server_url: &str,
-    username: &str,
-    password: Option<&str>,
-) -> Result<(), AccountError> {
+/// Validate the name and server URL of an account
+fn validate_name_and_url(name: &str, server_url: &str) -> Result<(), AccountError> {
     // Validate name
     if name.trim().is_empty() {
         return Err(AccountError::NameRequired);
@@ -149,6 +171,18 @@ fn validate_account_input(
         return Err(AccountError::InvalidServerUrl);
     }
 
+    Ok(())
+}
+
+/// Validate account input fields
+fn validate_account_input(
+    name: &str,
+    server_url: &str,
+    username: &str,
+    password: Option<&str>,
+) -> Result<(), AccountError> {
+    validate_name_and_url(name, server_url)?;
+
     // Validate username
     if username.trim().is_empty() {
         return Err(AccountError::UsernameRequired);
@@ -170,15 +204,20 @@ fn validate_account_input(
     Ok(())
 }
 
-/// Add a new Xtream Codes account
+/// Add a new Xtream Codes or M3U account
 ///
 /// Stores the password securely using OS keychain (preferred) or AES-256-GCM encryption (fallback).
+/// M3U accounts store no credentials; their server URL is the playlist URL.
 #[tauri::command]
 pub async fn add_account(
     app: AppHandle,
     db: State<'_, DbConnection>,
     request: AddAccountRequest,
 ) -> Result<AccountResponse, String> {
+    if request.provider_type == ProviderType::M3u {
+        return add_m3u_account(db, request).await;
+    }
+
     // Validate input
     validate_account_input(
         &request.name,
@@ -243,6 +282,34 @@ pub async fn add_account(
     Ok(AccountResponse::from(account))
 }
 
+/// Add an M3U playlist account
+async fn add_m3u_account(
+    db: State<'_, DbConnection>,
+    request: AddAccountRequest,
+) -> Result<AccountResponse, String> {
+    validate_name_and_url(&request.name, &request.server_url)?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    // Playlist URLs keep trailing slashes and query strings as given
+    let new_account = NewAccount::new(
+        request.name.clone(),
+        request.server_url.trim(),
+        "",
+        vec![],
+    )
+    .with_provider_type(ProviderType::M3u);
+
+    let account: Account = diesel::insert_into(accounts::table)
+        .values(&new_account)
+        .get_result(&mut conn)
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    Ok(AccountResponse::from(account))
+}
+
 /// Get all accounts (without passwords)
 #[tauri::command]
 pub async fn get_accounts(db: State<'_, DbConnection>) -> Result<Vec<AccountResponse>, String> {
@@ -289,6 +356,14 @@ pub async fn delete_account(
         .execute(&mut conn)
         .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
 
+    // Drop its DNS override so a later account reusing the ID doesn't inherit it
+    let mut dns_config = crate::dns::load_config(&mut conn);
+    if dns_config.account_overrides.remove(&id).is_some() {
+        crate::dns::save_config(&mut conn, &dns_config)?;
+        crate::dns::load_from_db(&mut conn);
+    }
+    request_headers::load_from_db(&mut conn);
+
     Ok(())
 }
 
@@ -300,17 +375,6 @@ pub async fn update_account(
     id: i32,
     request: UpdateAccountRequest,
 ) -> Result<AccountResponse, String> {
-    // Validate input (password is optional for updates)
-    validate_account_input(
-        &request.name,
-        &request.server_url,
-        &request.username,
-        request.password.as_deref(),
-    )?;
-
-    // Normalize server URL
-    let normalized_server_url = normalize_url(&request.server_url);
-
     // Get app data directory for credential storage
     let app_data_dir = app
         .path()
@@ -327,6 +391,43 @@ pub async fn update_account(
         .first(&mut conn)
         .map_err(|_| AccountError::NotFound)?;
 
+    let (http_user_agent, http_headers) =
+        request_headers::validate(request.http_user_agent.as_deref(), &request.http_headers)?;
+    let http_headers = (!http_headers.is_empty())
+        .then(|| serde_json::to_string(&http_headers))
+        .transpose()
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    if existing.is_m3u() {
+        validate_name_and_url(&request.name, &request.server_url)?;
+
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        let account: Account = diesel::update(accounts::table.filter(accounts::id.eq(id)))
+            .set((
+                accounts::name.eq(&request.name),
+                accounts::server_url.eq(request.server_url.trim()),
+                accounts::http_user_agent.eq(&http_user_agent),
+                accounts::http_headers.eq(&http_headers),
+                accounts::updated_at.eq(&now),
+            ))
+            .get_result(&mut conn)
+            .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+        crate::dns::load_from_db(&mut conn);
+        request_headers::load_from_db(&mut conn);
+        return Ok(AccountResponse::from(account));
+    }
+
+    // Validate input (password is optional for updates)
+    validate_account_input(
+        &request.name,
+        &request.server_url,
+        &request.username,
+        request.password.as_deref(),
+    )?;
+
+    // Normalize server URL
+    let normalized_server_url = normalize_url(&request.server_url);
+
     // Get current timestamp for updated_at
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
@@ -336,6 +437,8 @@ pub async fn update_account(
             accounts::name.eq(&request.name),
             accounts::server_url.eq(&normalized_server_url),
             accounts::username.eq(&request.username),
+            accounts::http_user_agent.eq(&http_user_agent),
+            accounts::http_headers.eq(&http_headers),
             accounts::updated_at.eq(&now),
         ))
         .execute(&mut conn)
@@ -353,13 +456,20 @@ pub async fn update_account(
             .store_password(&id.to_string(), password)
             .map_err(|_| AccountError::CredentialStorageError)?;
 
-        // Update the encrypted password in database
+        // Update the encrypted password in database; it is readable again
         diesel::update(accounts::table.filter(accounts::id.eq(id)))
-            .set(accounts::password_encrypted.eq(&encrypted_password))
+            .set((
+                accounts::password_encrypted.eq(&encrypted_password),
+                accounts::credential_error.eq(None::<String>),
+            ))
             .execute(&mut conn)
             .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
     }
 
+    // DNS and header overrides follow the account's server host
+    crate::dns::load_from_db(&mut conn);
+    request_headers::load_from_db(&mut conn);
+
     // Retrieve and return the updated account
     let account: Account = accounts::table
         .filter(accounts::id.eq(id))
@@ -369,6 +479,68 @@ pub async fn update_account(
     Ok(AccountResponse::from(account))
 }
 
+/// Shortest and longest allowed rolling reconnect intervals, in minutes
+const RECONNECT_INTERVAL_RANGE: std::ops::RangeInclusive<i32> = 5..=1440;
+
+/// Set how often streams from an account re-establish their upstream connection
+///
+/// Some providers cut every connection after a fixed time; reconnecting a
+/// little earlier hands playback over to a fresh connection without a gap.
+/// `None` turns the policy off.
+#[tauri::command]
+pub async fn set_account_reconnect_interval(
+    db: State<'_, DbConnection>,
+    account_id: i32,
+    minutes: Option<i32>,
+) -> Result<AccountResponse, String> {
+    if let Some(minutes) = minutes {
+        if !RECONNECT_INTERVAL_RANGE.contains(&minutes) {
+            return Err(format!(
+                "Reconnect interval must be between {} and {} minutes",
+                RECONNECT_INTERVAL_RANGE.start(),
+                RECONNECT_INTERVAL_RANGE.end()
+            ));
+        }
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let account: Account = diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
+        .set((
+            accounts::reconnect_interval_minutes.eq(minutes),
+            accounts::updated_at.eq(&now),
+        ))
+        .get_result(&mut conn)
+        .map_err(|_| AccountError::NotFound)?;
+
+    let details = serde_json::json!({
+        "accountId": account_id,
+        "reconnectIntervalMinutes": minutes,
+    });
+    let message = match minutes {
+        Some(minutes) => format!(
+            "Configuration changed: Streams from '{}' reconnect every {} minutes",
+            account.name, minutes
+        ),
+        None => format!(
+            "Configuration changed: Rolling reconnect disabled for '{}'",
+            account.name
+        ),
+    };
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &message,
+        Some(&details.to_string()),
+    );
+
+    Ok(AccountResponse::from(account))
+}
+
 /// Response type for test_connection command
 #[derive(Debug, Serialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -409,11 +581,21 @@ pub async fn test_connection(
         .first(&mut conn)
         .map_err(|_| AccountError::NotFound)?;
 
+    if account.is_m3u() {
+        return test_m3u_connection(&mut conn, &account).await;
+    }
+
     // Retrieve password from keyring/fallback (password is NEVER logged)
     let credential_manager = CredentialManager::new(app_data_dir);
-    let password = credential_manager
+    let password = match credential_manager
         .retrieve_password(&account_id.to_string(), &account.password_encrypted)
-        .map_err(|_| "Failed to retrieve credentials".to_string())?;
+    {
+        Ok(password) => password,
+        Err(e) => {
+            let _ = set_credential_error(&mut conn, account_id, Some(e.to_string()));
+            return Err("Failed to retrieve credentials".to_string());
+        }
+    };
 
     // Create Xtream client and authenticate
     let client = XtreamClient::new(&account.server_url, &account.username, &password)
@@ -506,3 +688,237 @@ pub async fn test_connection(
         }
     }
 }
+
+/// Expiry date and connection limit of every active Xtream account
+///
+/// Filled in by `test_connection` and channel scans; accounts never checked
+/// have no expiry date.
+#[tauri::command]
+pub async fn get_account_expiry_info(
+    db: State<'_, DbConnection>,
+) -> Result<Vec<crate::account_expiry::AccountExpiryInfo>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+    crate::account_expiry::expiry_info(&mut conn, chrono::Utc::now())
+        .map_err(|e| AccountError::DatabaseError(e.to_string()).into())
+}
+
+/// Result of reading back one account's stored password
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialCheck {
+    pub account_id: i32,
+    pub account_name: String,
+    pub ok: bool,
+    /// Why the password could not be read
+    pub error: Option<String>,
+}
+
+/// Check that every Xtream account's password can still be read
+///
+/// A keychain entry deleted outside the app otherwise only shows up as failing
+/// streams. Broken accounts are flagged in `credential_error` so the UI can ask
+/// for the password again; accounts that read fine have the flag cleared.
+#[tauri::command]
+pub async fn verify_credentials(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<Vec<CredentialCheck>, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| AccountError::AppDataDirError)?;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    let credential_manager = CredentialManager::new(app_data_dir);
+    let checks = check_account_credentials(&mut conn, &credential_manager)
+        .map_err(|e| AccountError::DatabaseError(e.to_string()))?;
+
+    let broken: Vec<&CredentialCheck> = checks.iter().filter(|check| !check.ok).collect();
+    if !broken.is_empty() {
+        let names: Vec<&str> = broken
+            .iter()
+            .map(|check| check.account_name.as_str())
+            .collect();
+        let details = serde_json::to_string(&broken).unwrap_or_default();
+        let _ = log_event_internal(
+            &mut conn,
+            "warn",
+            "system",
+            &format!(
+                "Stored password unreadable for {} account(s): {}",
+                broken.len(),
+                names.join(", ")
+            ),
+            Some(&details),
+        );
+    }
+
+    Ok(checks)
+}
+
+/// Try to read each Xtream account's password and record the outcome
+fn check_account_credentials(
+    conn: &mut SqliteConnection,
+    credential_manager: &CredentialManager,
+) -> QueryResult<Vec<CredentialCheck>> {
+    let all_accounts: Vec<Account> = accounts::table.order(accounts::id.asc()).load(conn)?;
+
+    let mut checks = Vec::new();
+    for account in all_accounts {
+        let Some(account_id) = account.id else {
+            continue;
+        };
+        if account.is_m3u() {
+            continue;
+        }
+
+        let error = if account.password_encrypted.is_empty() {
+            Some("No password stored".to_string())
+        } else {
+            credential_manager
+                .retrieve_password(&account_id.to_string(), &account.password_encrypted)
+                .err()
+                .map(|e| e.to_string())
+        };
+        if error != account.credential_error {
+            set_credential_error(conn, account_id, error.clone())?;
+        }
+
+        checks.push(CredentialCheck {
+            account_id,
+            account_name: account.name,
+            ok: error.is_none(),
+            error,
+        });
+    }
+    Ok(checks)
+}
+
+/// Flag an account's stored password as unreadable, or clear the flag
+fn set_credential_error(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    error: Option<String>,
+) -> QueryResult<usize> {
+    diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
+        .set(accounts::credential_error.eq(error))
+        .execute(conn)
+}
+
+/// Test an M3U account by downloading and parsing its playlist
+async fn test_m3u_connection(
+    conn: &mut SqliteConnection,
+    account: &Account,
+) -> Result<TestConnectionResponse, String> {
+    let account_id = account.id.unwrap_or(0);
+    let client = M3uClient::new(&account.server_url).map_err(|e| e.user_message())?;
+    let result = client.playlist().await.map(|playlist| playlist.streams.len());
+
+    let status_update = AccountStatusUpdate {
+        expiry_date: None,
+        max_connections_actual: None,
+        active_connections: None,
+        last_check: Some(chrono::Utc::now().to_rfc3339()),
+        connection_status: Some(if result.is_ok() { "connected" } else { "failed" }.to_string()),
+    };
+    let _ = diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
+        .set(&status_update)
+        .execute(conn);
+
+    match result {
+        Ok(channel_count) => {
+            let details = serde_json::json!({
+                "accountId": account_id,
+                "accountName": account.name,
+                "channelCount": channel_count
+            });
+            let _ = log_event_internal(
+                conn,
+                "info",
+                "connection",
+                &format!("Connection successful: {}", account.name),
+                Some(&details.to_string()),
+            );
+
+            Ok(TestConnectionResponse {
+                success: true,
+                status: Some(format!("Playlist with {} channels", channel_count)),
+                expiry_date: None,
+                max_connections: None,
+                active_connections: None,
+                error_message: None,
+                suggestions: None,
+            })
+        }
+        Err(e) => {
+            let error_message = match e {
+                crate::xtream::XtreamError::InvalidResponse => {
+                    "Server did not return an M3U playlist".to_string()
+                }
+                ref other => other.user_message(),
+            };
+            let details = serde_json::json!({
+                "accountId": account_id,
+                "accountName": account.name,
+                "error": error_message
+            });
+            let _ = log_event_internal(
+                conn,
+                "error",
+                "connection",
+                &format!("Connection failed: {} - {}", account.name, error_message),
+                Some(&details.to_string()),
+            );
+
+            Ok(TestConnectionResponse {
+                success: false,
+                status: None,
+                expiry_date: None,
+                max_connections: None,
+                active_connections: None,
+                error_message: Some(error_message),
+                suggestions: Some(vec![
+                    "Verify the playlist URL opens in a browser or media player".into(),
+                    "Check your internet connection".into(),
+                ]),
+            })
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_account_credentials_flags_unreadable_passwords() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, provider_type) VALUES
+             (1, 'Empty', 'http://a', 'u', x'', 'xtream'),
+             (2, 'Garbage', 'http://b', 'u', x'0102030405', 'xtream'),
+             (3, 'Playlist', 'http://c/list.m3u', '', x'', 'm3u')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let manager = CredentialManager::new(std::env::temp_dir());
+        let checks = check_account_credentials(&mut conn, &manager).unwrap();
+        assert_eq!(checks.len(), 2);
+        assert!(checks.iter().all(|check| !check.ok));
+        assert_eq!(checks[0].error.as_deref(), Some("No password stored"));
+
+        let flagged: Vec<Option<String>> = accounts::table
+            .order(accounts::id.asc())
+            .select(accounts::credential_error)
+            .load(&mut conn)
+            .unwrap();
+        assert!(flagged[0].is_some());
+        assert!(flagged[1].is_some());
+        assert!(flagged[2].is_none());
+    }
+}
@@ -0,0 +1,143 @@
+//! Alias Pack Commands
+//!
+//! Import, update, list, and remove community alias packs. Aliases are used
+//! as provider name -> tvg-id hints the next time matching runs.
+
+use diesel::prelude::*;
+use std::time::Duration;
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::models::AliasPack;
+use crate::db::schema::alias_packs;
+use crate::db::DbConnection;
+use crate::matcher::{merge_alias_pack, parse_alias_pack, remove_alias_pack as db_remove_alias_pack};
+use crate::xmltv::fetcher::validate_url_for_ssrf;
+
+/// Download timeout for alias packs
+const ALIAS_PACK_TIMEOUT_SECS: u64 = 30;
+
+/// Largest alias pack accepted (bytes)
+const MAX_ALIAS_PACK_BYTES: usize = 10 * 1024 * 1024;
+
+/// Download an alias pack
+async fn fetch_alias_pack(url: &str) -> Result<Vec<u8>, String> {
+    validate_url_for_ssrf(url).map_err(|e| e.to_string())?;
+
+    let client = crate::dns::configure(reqwest::Client::builder())
+        .timeout(Duration::from_secs(ALIAS_PACK_TIMEOUT_SECS))
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to fetch alias pack: {}", e))?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+    if response
+        .content_length()
+        .is_some_and(|len| len as usize > MAX_ALIAS_PACK_BYTES)
+    {
+        return Err("Alias pack is too large".to_string());
+    }
+
+    let data = response
+        .bytes()
+        .await
+        .map_err(|e| format!("Failed to read alias pack: {}", e))?;
+    if data.len() > MAX_ALIAS_PACK_BYTES {
+        return Err("Alias pack is too large".to_string());
+    }
+
+    Ok(data.to_vec())
+}
+
+/// Import an alias pack from a URL, or update it if already imported
+///
+/// The pack is validated before anything is written; an update replaces all
+/// aliases previously imported from the same URL.
+#[tauri::command]
+pub async fn import_alias_pack(db: State<'_, DbConnection>, url: String) -> Result<AliasPack, String> {
+    let url = url.trim().to_string();
+    let data = fetch_alias_pack(&url).await?;
+    let (pack, entries) = parse_alias_pack(&data)?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let pack_id = merge_alias_pack(&mut conn, &url, &pack, &entries)
+        .map_err(|e| format!("Failed to save alias pack: {}", e))?;
+
+    let saved: AliasPack = alias_packs::table
+        .filter(alias_packs::id.eq(pack_id))
+        .first(&mut conn)
+        .map_err(|e| format!("Failed to load alias pack: {}", e))?;
+
+    let details = serde_json::json!({
+        "packId": pack_id,
+        "name": saved.name,
+        "version": saved.version,
+        "sourceUrl": url,
+        "aliasCount": saved.alias_count
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "match",
+        &format!(
+            "Alias pack imported: {} ({} aliases)",
+            saved.name, saved.alias_count
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(saved)
+}
+
+/// List imported alias packs
+#[tauri::command]
+pub fn list_alias_packs(db: State<DbConnection>) -> Result<Vec<AliasPack>, String> {
+    let mut conn = db
+        .get_read_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    alias_packs::table
+        .order(alias_packs::name.asc())
+        .load(&mut conn)
+        .map_err(|e| format!("Failed to load alias packs: {}", e))
+}
+
+/// Remove an alias pack and the aliases imported from it
+///
+/// Existing channel mappings are kept until matching runs again.
+#[tauri::command]
+pub fn remove_alias_pack(db: State<DbConnection>, pack_id: i32) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let name: String = alias_packs::table
+        .filter(alias_packs::id.eq(pack_id))
+        .select(alias_packs::name)
+        .first(&mut conn)
+        .map_err(|_| "Alias pack not found".to_string())?;
+
+    db_remove_alias_pack(&mut conn, pack_id)
+        .map_err(|e| format!("Failed to remove alias pack: {}", e))?;
+
+    let details = serde_json::json!({ "packId": pack_id, "name": name });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "match",
+        &format!("Alias pack removed: {}", name),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
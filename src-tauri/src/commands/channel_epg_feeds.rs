@@ -0,0 +1,152 @@
+//! Per-channel XMLTV feed Tauri commands
+//!
+//! Attaches, removes, and refreshes the feeds in `crate::xmltv::channel_feed`.
+
+use diesel::prelude::*;
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::channel_epg_feeds;
+use crate::db::{ChannelEpgFeed, DbConnection};
+use crate::server::AppState;
+use crate::xmltv::channel_feed;
+use crate::xmltv::fetcher::validate_url_for_ssrf;
+
+/// List all per-channel XMLTV feeds
+#[tauri::command]
+pub fn get_channel_epg_feeds(db: State<DbConnection>) -> Result<Vec<ChannelEpgFeed>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    channel_epg_feeds::table
+        .order(channel_epg_feeds::tvg_id.asc())
+        .load(&mut conn)
+        .map_err(|e| format!("Failed to load channel feeds: {}", e))
+}
+
+/// Attach (or replace) the XMLTV feed of a guide channel
+///
+/// `tvg_id` is the XMLTV channel ID the feed fills. `feed_channel_id` selects
+/// the channel inside the feed and may be omitted for single-channel feeds.
+/// The feed is fetched on the next EPG refresh.
+#[tauri::command]
+pub fn set_channel_epg_feed(
+    db: State<DbConnection>,
+    tvg_id: String,
+    url: String,
+    feed_channel_id: Option<String>,
+) -> Result<ChannelEpgFeed, String> {
+    let tvg_id = tvg_id.trim().to_string();
+    let url = url.trim().to_string();
+    let feed_channel_id = feed_channel_id
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty());
+
+    if tvg_id.is_empty() {
+        return Err("Channel ID is required".to_string());
+    }
+    if url.is_empty() {
+        return Err("URL is required".to_string());
+    }
+    validate_url_for_ssrf(&url).map_err(|e| e.to_string())?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    diesel::insert_into(channel_epg_feeds::table)
+        .values((
+            channel_epg_feeds::tvg_id.eq(&tvg_id),
+            channel_epg_feeds::url.eq(&url),
+            channel_epg_feeds::feed_channel_id.eq(&feed_channel_id),
+        ))
+        .on_conflict(channel_epg_feeds::tvg_id)
+        .do_update()
+        .set((
+            channel_epg_feeds::url.eq(&url),
+            channel_epg_feeds::feed_channel_id.eq(&feed_channel_id),
+            channel_epg_feeds::last_error.eq(None::<String>),
+        ))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to save channel feed: {}", e))?;
+
+    let feed: ChannelEpgFeed = channel_epg_feeds::table
+        .find(&tvg_id)
+        .first(&mut conn)
+        .map_err(|e| format!("Failed to load channel feed: {}", e))?;
+
+    let details = serde_json::json!({
+        "tvgId": tvg_id,
+        "url": url,
+        "feedChannelId": feed_channel_id,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: Channel feed set for {}", tvg_id),
+        Some(&details.to_string()),
+    );
+
+    Ok(feed)
+}
+
+/// Remove the XMLTV feed of a guide channel
+///
+/// Programs already merged stay until the channel's source is refreshed.
+#[tauri::command]
+pub fn remove_channel_epg_feed(db: State<DbConnection>, tvg_id: String) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let removed = diesel::delete(channel_epg_feeds::table.find(&tvg_id))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to remove channel feed: {}", e))?;
+    if removed == 0 {
+        return Err(format!("No channel feed for {}", tvg_id));
+    }
+
+    let details = serde_json::json!({ "tvgId": tvg_id });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: Channel feed removed for {}", tvg_id),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Fetch a channel's feed now and merge it into the guide
+#[tauri::command]
+pub async fn refresh_channel_epg_feed(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    tvg_id: String,
+) -> Result<ChannelEpgFeed, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let feed: ChannelEpgFeed = channel_epg_feeds::table
+        .find(&tvg_id)
+        .first(&mut conn)
+        .map_err(|e| {
+            if e == diesel::NotFound {
+                format!("No channel feed for {}", tvg_id)
+            } else {
+                format!("Failed to load channel feed: {}", e)
+            }
+        })?;
+
+    channel_feed::refresh_channel_feed(&mut conn, &feed).await?;
+    server_state.bump_epg_generation();
+
+    channel_epg_feeds::table
+        .find(&tvg_id)
+        .first(&mut conn)
+        .map_err(|e| format!("Failed to load channel feed: {}", e))
+}
@@ -0,0 +1,87 @@
+//! Channel number Tauri commands
+//!
+//! Sets and automatically assigns the persistent numbers in
+//! `crate::channel_numbers`.
+
+use tauri::State;
+
+use crate::channel_numbers::{self, AutoNumberOptions};
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::server::AppState;
+
+/// Set or clear (None) the number of a channel
+///
+/// A cleared channel is numbered by its lineup position again.
+#[tauri::command]
+pub fn set_channel_number(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    channel_id: i32,
+    number: Option<i32>,
+) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    channel_numbers::set_channel_number(&mut conn, channel_id, number)?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "channelId": channel_id,
+        "number": number,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &match number {
+            Some(number) => format!(
+                "Configuration changed: channel {} numbered {}",
+                channel_id, number
+            ),
+            None => format!(
+                "Configuration changed: channel {} number cleared",
+                channel_id
+            ),
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Number the enabled channels in lineup order
+///
+/// Returns how many channels were numbered.
+#[tauri::command]
+pub fn auto_assign_channel_numbers(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    options: AutoNumberOptions,
+) -> Result<usize, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let assigned = channel_numbers::auto_assign(&mut conn, &options)?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "options": options,
+        "assigned": assigned.len(),
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: {} channels numbered from {}",
+            assigned.len(),
+            options.start
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(assigned.len())
+}
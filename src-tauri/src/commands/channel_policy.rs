@@ -0,0 +1,90 @@
+//! Channel enablement policy Tauri commands
+//!
+//! Configures, previews, and runs the policy in `crate::channel_policy`.
+
+use tauri::State;
+
+use crate::channel_policy::{self, ChannelPolicyConfig, PolicyReport, CHANNEL_POLICY_KEY};
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::server::AppState;
+
+/// Get the channel enablement policy
+#[tauri::command]
+pub fn get_channel_policy(db: State<DbConnection>) -> Result<ChannelPolicyConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(channel_policy::load_config(&mut conn))
+}
+
+/// Save the channel enablement policy
+#[tauri::command]
+pub fn set_channel_policy(
+    db: State<DbConnection>,
+    config: ChannelPolicyConfig,
+) -> Result<ChannelPolicyConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    channel_policy::save_config(&mut conn, &config)?;
+
+    let details = serde_json::json!({
+        "setting": CHANNEL_POLICY_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Channel auto-policy {}",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
+
+/// Preview what the policy would change without applying it
+#[tauri::command]
+pub fn preview_channel_policy(db: State<DbConnection>) -> Result<PolicyReport, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let config = channel_policy::load_config(&mut conn);
+    channel_policy::evaluate(&mut conn, &config, chrono::Utc::now())
+        .map_err(|e| format!("Failed to evaluate channel policy: {}", e))
+}
+
+/// Apply the policy now
+///
+/// Runs regardless of the policy's `enabled` flag, which only controls the
+/// scheduled runs.
+#[tauri::command]
+pub fn run_channel_policy(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<PolicyReport, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let config = channel_policy::load_config(&mut conn);
+    let report = channel_policy::apply(&mut conn, &config, chrono::Utc::now())
+        .map_err(|e| format!("Failed to apply channel policy: {}", e))?;
+
+    if report.changes_lineup() {
+        server_state.bump_epg_generation();
+    }
+
+    Ok(report)
+}
@@ -1,11 +1,12 @@
 //! Channel scanning Tauri commands
 //!
-//! This module provides commands for scanning channels from Xtream providers
+//! This module provides commands for scanning channels from Xtream and M3U providers
 //! and storing them in the database.
 
 use diesel::prelude::*;
 use serde::Serialize;
 use std::collections::HashMap;
+use std::path::PathBuf;
 use std::time::Instant;
 use tauri::{AppHandle, Manager, State};
 
@@ -14,7 +15,7 @@ use crate::db::{
     schema::{accounts, xtream_channels},
     Account, DbConnection, NewXtreamChannel, XtreamChannel, XtreamChannelUpdate,
 };
-use crate::xtream::{quality, XtreamClient};
+use crate::xtream::{quality, M3uClient, ProviderClient, XtreamClient};
 
 /// Response type for scan_channels command
 #[derive(Debug, Serialize, Clone)]
@@ -72,10 +73,10 @@ impl From<XtreamChannel> for ChannelResponse {
     }
 }
 
-/// Scan channels from an Xtream provider
+/// Scan channels from an Xtream or M3U provider
 ///
-/// Retrieves live streams from the Xtream API, detects quality tiers,
-/// and stores/updates channels in the database.
+/// Retrieves live streams from the Xtream API or the account's playlist,
+/// detects quality tiers, and stores/updates channels in the database.
 #[tauri::command]
 pub async fn scan_channels(
     app: AppHandle,
@@ -101,30 +102,8 @@ pub async fn scan_channels(
         .first(&mut conn)
         .map_err(|_| "Account not found".to_string())?;
 
-    // Retrieve password from keyring/fallback (password is NEVER logged)
-    let credential_manager = CredentialManager::new(app_data_dir);
-    let password = credential_manager
-        .retrieve_password(&account_id.to_string(), &account.password_encrypted)
-        .map_err(|_| "Failed to retrieve credentials".to_string())?;
-
-    // Create Xtream client
-    let client = XtreamClient::new(&account.server_url, &account.username, &password)
-        .map_err(|e| e.user_message())?;
-
-    // Fetch account info to refresh tuner limits (FR6 requirement)
-    if let Ok(account_info) = client.authenticate().await {
-        use crate::db::AccountStatusUpdate;
-        let status_update = AccountStatusUpdate {
-            expiry_date: account_info.expiry_date.map(|dt| dt.to_rfc3339()),
-            max_connections_actual: Some(account_info.max_connections),
-            active_connections: Some(account_info.active_connections),
-            last_check: Some(chrono::Utc::now().to_rfc3339()),
-            connection_status: Some("Active".to_string()),
-        };
-        let _ = diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
-            .set(&status_update)
-            .execute(&mut conn);
-    }
+    let client = provider_client(&mut conn, &account, app_data_dir).await?;
+    let is_m3u = account.is_m3u();
 
     // Fetch categories first (for category name lookup)
     let categories = match client.get_live_categories().await {
@@ -228,6 +207,7 @@ pub async fn scan_channels(
                     tv_archive: stream.tv_archive.unwrap_or(0),
                     tv_archive_duration: stream.tv_archive_duration.unwrap_or(0),
                     updated_at: now.clone(),
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 };
 
                 diesel::update(
@@ -252,6 +232,7 @@ pub async fn scan_channels(
                     epg_channel_id: stream.epg_channel_id.clone(),
                     tv_archive: stream.tv_archive.unwrap_or(0),
                     tv_archive_duration: stream.tv_archive_duration.unwrap_or(0),
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 };
 
                 diesel::insert_into(xtream_channels::table)
@@ -289,6 +270,48 @@ pub async fn scan_channels(
     })
 }
 
+/// Create the channel listing client for an account
+///
+/// Xtream accounts are authenticated first to refresh tuner limits (FR6
+/// requirement). M3U accounts need no credentials.
+async fn provider_client(
+    conn: &mut SqliteConnection,
+    account: &Account,
+    app_data_dir: std::path::PathBuf,
+) -> Result<ProviderClient, String> {
+    if account.is_m3u() {
+        let client = M3uClient::new(&account.server_url).map_err(|e| e.user_message())?;
+        return Ok(ProviderClient::M3u(client));
+    }
+
+    let account_id = account.id.unwrap_or(0);
+
+    // Retrieve password from keyring/fallback (password is NEVER logged)
+    let credential_manager = CredentialManager::new(app_data_dir);
+    let password = credential_manager
+        .retrieve_password(&account_id.to_string(), &account.password_encrypted)
+        .map_err(|_| "Failed to retrieve credentials".to_string())?;
+
+    let client = XtreamClient::new(&account.server_url, &account.username, &password)
+        .map_err(|e| e.user_message())?;
+
+    if let Ok(account_info) = client.authenticate().await {
+        use crate::db::AccountStatusUpdate;
+        let status_update = AccountStatusUpdate {
+            expiry_date: account_info.expiry_date.map(|dt| dt.to_rfc3339()),
+            max_connections_actual: Some(account_info.max_connections),
+            active_connections: Some(account_info.active_connections),
+            last_check: Some(chrono::Utc::now().to_rfc3339()),
+            connection_status: Some("Active".to_string()),
+        };
+        let _ = diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
+            .set(&status_update)
+            .execute(conn);
+    }
+
+    Ok(ProviderClient::Xtream(client))
+}
+
 /// Get channels for an account
 #[tauri::command]
 pub async fn get_channels(
@@ -332,7 +355,9 @@ pub async fn get_channel_count(
 // ============================================================================
 
 use crate::commands::logs::log_provider_event;
-use crate::matcher::{perform_auto_rematch, MatchConfig, ProviderChanges, RematchResult};
+use crate::matcher::{
+    load_alias_hints, load_icon_match_config, load_match_profiles, load_name_rules, load_region_match_config, perform_auto_rematch, MatchConfig, ProviderChanges, RematchResult,
+};
 
 /// Enhanced response type for scan_and_rematch command
 #[derive(Debug, Serialize, Clone)]
@@ -384,8 +409,6 @@ pub async fn scan_and_rematch(
     db: State<'_, DbConnection>,
     account_id: i32,
 ) -> Result<ScanAndRematchResponse, String> {
-    let start_time = Instant::now();
-
     // Get app data directory for credential retrieval
     let app_data_dir = app
         .path()
@@ -397,36 +420,25 @@ pub async fn scan_and_rematch(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    scan_and_rematch_internal(&mut conn, app_data_dir, account_id).await
+}
+
+/// Scan one account and auto-rematch (shared by the command and the scheduled re-scan)
+pub async fn scan_and_rematch_internal(
+    conn: &mut SqliteConnection,
+    app_data_dir: PathBuf,
+    account_id: i32,
+) -> Result<ScanAndRematchResponse, String> {
+    let start_time = Instant::now();
+
     // Load account from database
     let account: Account = accounts::table
         .filter(accounts::id.eq(account_id))
-        .first(&mut conn)
+        .first(conn)
         .map_err(|_| "Account not found".to_string())?;
 
-    // Retrieve password from keyring/fallback
-    let credential_manager = CredentialManager::new(app_data_dir);
-    let password = credential_manager
-        .retrieve_password(&account_id.to_string(), &account.password_encrypted)
-        .map_err(|_| "Failed to retrieve credentials".to_string())?;
-
-    // Create Xtream client
-    let client = XtreamClient::new(&account.server_url, &account.username, &password)
-        .map_err(|e| e.user_message())?;
-
-    // Fetch account info to refresh tuner limits
-    if let Ok(account_info) = client.authenticate().await {
-        use crate::db::AccountStatusUpdate;
-        let status_update = AccountStatusUpdate {
-            expiry_date: account_info.expiry_date.map(|dt| dt.to_rfc3339()),
-            max_connections_actual: Some(account_info.max_connections),
-            active_connections: Some(account_info.active_connections),
-            last_check: Some(chrono::Utc::now().to_rfc3339()),
-            connection_status: Some("Active".to_string()),
-        };
-        let _ = diesel::update(accounts::table.filter(accounts::id.eq(account_id)))
-            .set(&status_update)
-            .execute(&mut conn);
-    }
+    let client = provider_client(conn, &account, app_data_dir.clone()).await?;
+    let is_m3u = account.is_m3u();
 
     // Fetch categories for category name lookup
     let categories = match client.get_live_categories().await {
@@ -440,7 +452,7 @@ pub async fn scan_and_rematch(
                 "operation": "fetch_categories"
             });
             let _ = log_provider_event(
-                &mut conn,
+                conn,
                 "error",
                 &format!("Failed to fetch categories for account {}: {}", account.name, e.user_message()),
                 Some(error_details),
@@ -479,7 +491,7 @@ pub async fn scan_and_rematch(
                 "operation": "fetch_streams"
             });
             let _ = log_provider_event(
-                &mut conn,
+                conn,
                 "error",
                 &format!("Failed to fetch streams for account {}: {}", account.name, e.user_message()),
                 Some(error_details),
@@ -505,7 +517,7 @@ pub async fn scan_and_rematch(
     // Get existing channels for this account
     let existing_channels: Vec<XtreamChannel> = xtream_channels::table
         .filter(xtream_channels::account_id.eq(account_id))
-        .load(&mut conn)
+        .load(conn)
         .map_err(|e| format!("Failed to load existing channels: {}", e))?;
 
     // Build lookup map for existing channels by stream_id
@@ -567,6 +579,7 @@ pub async fn scan_and_rematch(
                     tv_archive: stream.tv_archive.unwrap_or(0),
                     tv_archive_duration: stream.tv_archive_duration.unwrap_or(0),
                     updated_at: now.clone(),
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 };
 
                 diesel::update(
@@ -594,6 +607,7 @@ pub async fn scan_and_rematch(
                     tv_archive_duration: stream.tv_archive_duration,
                     added_at: existing.added_at.clone(),
                     updated_at: Some(now.clone()),
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 });
             } else {
                 // Insert new channel
@@ -608,6 +622,7 @@ pub async fn scan_and_rematch(
                     epg_channel_id: stream.epg_channel_id.clone(),
                     tv_archive: stream.tv_archive.unwrap_or(0),
                     tv_archive_duration: stream.tv_archive_duration.unwrap_or(0),
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 };
 
                 diesel::insert_into(xtream_channels::table)
@@ -637,6 +652,7 @@ pub async fn scan_and_rematch(
                     tv_archive_duration: stream.tv_archive_duration,
                     added_at: Some(now.clone()),
                     updated_at: None,
+                    stream_url: stream.direct_source.clone().filter(|_| is_m3u),
                 });
             }
         }
@@ -656,13 +672,23 @@ pub async fn scan_and_rematch(
     .map_err(|e| format!("Database transaction error: {}", e))?;
 
     // Perform auto-rematch on the updated channel list
-    let config = MatchConfig::default();
+    let alias_hints =
+        load_alias_hints(conn).map_err(|e| format!("Failed to load alias packs: {}", e))?;
+    let name_rules =
+        load_name_rules(conn).map_err(|e| format!("Failed to load match rules: {}", e))?;
+    let config = MatchConfig::default()
+        .with_alias_hints(alias_hints)
+        .with_name_rules(name_rules)
+        .with_profiles(load_match_profiles(conn))
+        .with_region_matching(&load_region_match_config(conn))
+        .with_icon_matching(&load_icon_match_config(conn), &app_data_dir)
+        .with_plugins(crate::plugins::enabled(conn));
     let (changes, rematch_result) =
-        perform_auto_rematch(&mut conn, account_id, &current_xtream_channels, &config)
+        perform_auto_rematch(conn, account_id, &current_xtream_channels, &config)
             .map_err(|e| format!("Auto-rematch error: {}", e))?;
 
     // Log provider changes to event log
-    log_provider_changes(&mut conn, &account.name, &changes, &rematch_result);
+    log_provider_changes(conn, &account.name, &changes, &rematch_result);
 
     let scan_duration_ms = start_time.elapsed().as_millis() as u64;
 
@@ -681,6 +707,87 @@ pub async fn scan_and_rematch(
     })
 }
 
+// ============================================================================
+// Scheduled Channel Re-scan
+// ============================================================================
+
+use crate::scheduler::{ChannelRescanSchedule, EpgScheduler};
+
+/// Channel re-scan schedule with the time of the last scheduled re-scan
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelRescanScheduleResponse {
+    #[serde(flatten)]
+    pub schedule: ChannelRescanSchedule,
+    pub last_scheduled_rescan: Option<String>,
+}
+
+fn channel_rescan_response(conn: &mut SqliteConnection) -> ChannelRescanScheduleResponse {
+    ChannelRescanScheduleResponse {
+        schedule: crate::scheduler::get_channel_rescan_schedule(conn),
+        last_scheduled_rescan: crate::scheduler::get_last_channel_rescan(conn)
+            .map(|dt| dt.to_rfc3339()),
+    }
+}
+
+/// Get the channel re-scan schedule
+#[tauri::command]
+pub async fn get_channel_rescan_schedule(
+    db: State<'_, DbConnection>,
+) -> Result<ChannelRescanScheduleResponse, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    Ok(channel_rescan_response(&mut conn))
+}
+
+/// Set the channel re-scan schedule
+///
+/// Saves the schedule and updates the running scheduler. Scheduled re-scans
+/// wait for the maintenance window.
+#[tauri::command]
+pub async fn set_channel_rescan_schedule(
+    db: State<'_, DbConnection>,
+    scheduler: State<'_, EpgScheduler>,
+    schedule: ChannelRescanSchedule,
+) -> Result<ChannelRescanScheduleResponse, String> {
+    {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+        crate::scheduler::set_channel_rescan_schedule(&mut conn, &schedule)?;
+    }
+
+    scheduler
+        .update_rescan_schedule(&schedule)
+        .await
+        .map_err(|e| format!("Failed to update re-scan schedule: {}", e))?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    let details = serde_json::json!({
+        "setting": crate::scheduler::CHANNEL_RESCAN_SCHEDULE_KEY,
+        "value": &schedule,
+    });
+    let _ = crate::commands::logs::log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &if schedule.enabled {
+            format!(
+                "Configuration changed: channel re-scan every {} day(s) at {:02}:{:02}",
+                schedule.interval_days, schedule.hour, schedule.minute
+            )
+        } else {
+            "Configuration changed: channel re-scan disabled".to_string()
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(channel_rescan_response(&mut conn))
+}
+
 /// Log provider changes to the event log
 fn log_provider_changes(
     conn: &mut diesel::SqliteConnection,
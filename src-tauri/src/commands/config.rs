@@ -6,23 +6,44 @@
 //! This module provides commands for exporting and importing application configuration,
 //! enabling backup/restore and migration between machines.
 //!
-//! SECURITY: Passwords are NEVER exported. Accounts are imported with empty passwords
-//! and users must re-enter credentials after import.
+//! SECURITY: Passwords are NEVER exported in the clear. Accounts are imported with empty
+//! passwords and users must re-enter credentials after import, unless the export was made
+//! with a passphrase: account passwords are then sealed with it (see
+//! `crate::credentials::transfer`) and restored when the same passphrase is given on import.
+//!
+//! Exports carry an integrity hash and signature (see `crate::config_integrity`);
+//! files that fail the check are refused before anything is replaced.
+//!
+//! Since version 2.0, channel mappings are also exported by stable identifiers
+//! (XMLTV source URL + channel_id, account URL + username + stream_id). An
+//! import keeps them as pending mappings; once channels and streams have been
+//! fetched again, `resolve_imported_mappings` links them to the new rows.
 
 use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use tauri::{AppHandle, Manager, State};
 use thiserror::Error;
 
 use crate::commands::logs::log_event_internal;
+use crate::config_integrity::{self, ExportIntegrity, IntegrityStatus};
+use crate::credentials::transfer::{self, SealedSecrets};
+use crate::credentials::CredentialManager;
 use crate::db::{
-    schema::{accounts, channel_mappings, settings, xmltv_channel_settings, xmltv_sources},
-    Account, ChannelMapping, DbConnection, NewAccount, NewXmltvSource, Setting,
-    XmltvChannelSettings, XmltvSource,
+    schema::{
+        accounts, channel_mappings, pending_channel_mappings, pending_channel_numbers, settings,
+        xmltv_channel_settings, xmltv_channels, xmltv_sources, xtream_channels,
+    },
+    Account, ChannelMapping, DbConnection, NewAccount, NewChannelMapping, NewPendingChannelMapping,
+    NewPendingChannelNumber, NewXmltvSource, PendingChannelMapping, PendingChannelNumber,
+    ProviderType, Setting, XmltvChannelSettings, XmltvSource, SYNTHETIC_SOURCE_ID,
 };
+use crate::server::AppState;
 
 /// Current configuration export format version
-const CONFIG_VERSION: &str = "1.0";
+///
+/// 2.0 added `portableMappings`; 1.x files import without mappings.
+/// 2.1 added `channelNumbers`.
+const CONFIG_VERSION: &str = "2.1";
 
 /// Minimum supported import version
 const MIN_SUPPORTED_VERSION: &str = "1.0";
@@ -53,6 +74,12 @@ pub enum ConfigError {
 
     #[error("File operation failed: {0}")]
     FileError(String),
+
+    #[error("Credentials could not be exported: {0}")]
+    CredentialExportFailed(String),
+
+    #[error("Credentials could not be read: {0}")]
+    CredentialImportFailed(String),
 }
 
 impl From<ConfigError> for String {
@@ -87,6 +114,9 @@ pub struct ExportedAccount {
     pub username: String,
     pub max_connections: i32,
     pub is_active: bool,
+    /// Missing in exports made before M3U accounts existed
+    #[serde(default)]
+    pub provider_type: ProviderType,
 }
 
 /// Exported XMLTV source
@@ -113,6 +143,41 @@ pub struct ExportedChannelMapping {
     pub stream_priority: i32,
 }
 
+/// Channel mapping identified by stable keys instead of database IDs
+///
+/// Survives an import: the channel and stream are found again by these keys
+/// after they are re-fetched from the providers.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedPortableMapping {
+    /// URL of the XMLTV source the channel comes from
+    pub source_url: String,
+    /// XMLTV channel id attribute (e.g. "bbc1.uk")
+    pub channel_id: String,
+    /// Server URL of the account the stream comes from
+    pub account_url: String,
+    pub account_username: String,
+    /// Provider stream ID
+    pub stream_id: i32,
+    pub match_confidence: Option<f32>,
+    pub is_manual: bool,
+    pub is_primary: bool,
+    pub stream_priority: i32,
+}
+
+/// Channel number identified by the XMLTV source URL and channel id
+///
+/// Restored together with the portable mappings.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportedChannelNumber {
+    /// URL of the XMLTV source the channel comes from
+    pub source_url: String,
+    /// XMLTV channel id attribute (e.g. "bbc1.uk")
+    pub channel_id: String,
+    pub channel_number: i32,
+}
+
 /// Exported XMLTV channel settings
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -131,6 +196,12 @@ pub struct ExportData {
     pub xmltv_sources: Vec<ExportedXmltvSource>,
     pub channel_mappings: Vec<ExportedChannelMapping>,
     pub xmltv_channel_settings: Vec<ExportedXmltvChannelSettings>,
+    /// Missing in exports made before version 2.0
+    #[serde(default)]
+    pub portable_mappings: Vec<ExportedPortableMapping>,
+    /// Missing in exports made before version 2.1
+    #[serde(default)]
+    pub channel_numbers: Vec<ExportedChannelNumber>,
 }
 
 /// Complete configuration export structure
@@ -141,6 +212,23 @@ pub struct ConfigExport {
     pub export_date: String,
     pub app_version: String,
     pub data: ExportData,
+    /// Hash and signature of `data` (missing in exports made before they existed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub integrity: Option<ExportIntegrity>,
+    /// Account passwords sealed with the export passphrase (opt-in)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub credentials: Option<SealedSecrets>,
+}
+
+/// An account password inside `ConfigExport::credentials`
+///
+/// Only ever serialized into the sealed payload.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+struct ExportedCredential {
+    server_url: String,
+    username: String,
+    password: String,
 }
 
 // ============================================================================
@@ -160,6 +248,85 @@ pub struct ImportPreview {
     pub xmltv_channel_settings_count: usize,
     pub settings_summary: Vec<String>,
     pub error_message: Option<String>,
+    /// Result of checking the file's integrity hash and signature
+    pub integrity: IntegrityStatus,
+    /// Integrity problem worth confirming before import (valid files only)
+    pub integrity_warning: Option<String>,
+    /// Consequences of importing over the current database (valid files only)
+    pub conflicts: Option<ImportConflicts>,
+    /// Whether the file carries passphrase-sealed account passwords
+    pub has_credentials: bool,
+}
+
+/// Dry-run report of what an import would change in the current database
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportConflicts {
+    /// Imported accounts matching an existing account (same server URL and username)
+    pub existing_accounts: Vec<ImportCollision>,
+    /// Existing accounts not in the file (deleted by the import)
+    pub removed_accounts: Vec<String>,
+    /// Imported XMLTV sources matching an existing source URL
+    pub existing_xmltv_sources: Vec<ImportCollision>,
+    /// Existing XMLTV sources not in the file (deleted by the import)
+    pub removed_xmltv_sources: Vec<String>,
+    /// Exported settings whose value would change
+    pub setting_changes: Vec<SettingChange>,
+    /// Settings not included in exports (reset to defaults by the import)
+    pub settings_reset: Vec<String>,
+    /// Mappings referencing channels that do not exist in this database
+    pub unresolved_mappings: Vec<UnresolvedMapping>,
+    /// Portable mappings whose source or account is not in the file
+    pub unresolved_portable_mappings: Vec<UnresolvedPortableMapping>,
+}
+
+/// An imported item that collides with an existing one
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportCollision {
+    pub name: String,
+    pub url: String,
+    /// Name of the existing item it replaces
+    pub existing_name: String,
+}
+
+/// A setting whose value the import would change
+#[derive(Debug, Serialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChange {
+    pub key: String,
+    pub current_value: Option<String>,
+    pub imported_value: Option<String>,
+}
+
+/// A channel mapping in the file that cannot be resolved
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedMapping {
+    pub xmltv_channel_id: i32,
+    pub xtream_channel_id: i32,
+    pub reason: String,
+}
+
+/// A portable mapping that cannot be linked to a channel and stream
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedPortableMapping {
+    pub channel_id: String,
+    pub stream_id: i32,
+    pub reason: String,
+}
+
+/// Result of re-linking imported mappings
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingResolution {
+    /// Mappings linked to their channel and stream
+    pub resolved: usize,
+    /// Mappings still pending, with the reason
+    pub unresolved: Vec<UnresolvedPortableMapping>,
+    /// Channel numbers set again
+    pub numbers_restored: usize,
 }
 
 /// Result of import operation
@@ -171,6 +338,8 @@ pub struct ImportResult {
     pub xmltv_sources_imported: usize,
     pub channel_mappings_imported: usize,
     pub settings_imported: usize,
+    /// Account passwords restored from the file's sealed credentials
+    pub credentials_restored: usize,
     pub message: String,
 }
 
@@ -184,8 +353,14 @@ pub struct ImportResult {
 ///
 /// Returns the complete configuration as a JSON string.
 /// The frontend will use Tauri's file dialog to save to user-selected location.
+///
+/// With a `passphrase`, account passwords are included, sealed with it.
 #[tauri::command]
-pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
+pub fn export_configuration(
+    app: AppHandle,
+    db: State<DbConnection>,
+    passphrase: Option<String>,
+) -> Result<String, String> {
     let mut conn = db
         .get_connection()
         .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
@@ -227,9 +402,25 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
             ConfigError::DatabaseError(e.to_string())
         })?;
 
+    // Read passwords before the rows are consumed (opt-in)
+    let app_data_dir = app.path().app_data_dir().ok();
+    let credentials = match (&passphrase, &app_data_dir) {
+        (Some(_), None) => {
+            return Err(ConfigError::CredentialExportFailed(
+                "App data directory not available".to_string(),
+            )
+            .into())
+        }
+        (Some(_), Some(dir)) => {
+            collect_credentials(&CredentialManager::new(dir.clone()), &account_rows)?
+        }
+        (None, _) => vec![],
+    };
+
     let exported_accounts: Vec<ExportedAccount> = account_rows
         .into_iter()
         .map(|a| ExportedAccount {
+            provider_type: a.provider(),
             id: a.id.unwrap_or(0),
             name: a.name,
             server_url: a.server_url,
@@ -241,6 +432,7 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
 
     // Query all XMLTV sources (Task 1.5)
     let source_rows: Vec<XmltvSource> = xmltv_sources::table
+        .filter(xmltv_sources::id.ne(SYNTHETIC_SOURCE_ID))
         .load(&mut conn)
         .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
 
@@ -273,6 +465,61 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
         })
         .collect();
 
+    // The same mappings by stable identifiers, importable on another database
+    let portable_mappings: Vec<ExportedPortableMapping> = channel_mappings::table
+        .inner_join(xmltv_channels::table.inner_join(xmltv_sources::table))
+        .inner_join(xtream_channels::table.inner_join(accounts::table))
+        .select((
+            xmltv_sources::url,
+            xmltv_channels::channel_id,
+            accounts::server_url,
+            accounts::username,
+            xtream_channels::stream_id,
+            channel_mappings::match_confidence,
+            channel_mappings::is_manual,
+            channel_mappings::is_primary,
+            channel_mappings::stream_priority,
+        ))
+        .load::<(
+            String,
+            String,
+            String,
+            String,
+            i32,
+            Option<f32>,
+            Option<i32>,
+            Option<i32>,
+            Option<i32>,
+        )>(&mut conn)
+        .map_err(|e| ConfigError::DatabaseError(e.to_string()))?
+        .into_iter()
+        .map(
+            |(
+                source_url,
+                channel_id,
+                account_url,
+                account_username,
+                stream_id,
+                confidence,
+                manual,
+                primary,
+                priority,
+            )| {
+                ExportedPortableMapping {
+                    source_url,
+                    channel_id,
+                    account_url,
+                    account_username,
+                    stream_id,
+                    match_confidence: confidence,
+                    is_manual: manual.is_some_and(|v| v != 0),
+                    is_primary: primary.is_some_and(|v| v != 0),
+                    stream_priority: priority.unwrap_or(0),
+                }
+            },
+        )
+        .collect();
+
     // Query all XMLTV channel settings (Task 1.7)
     let settings_rows: Vec<XmltvChannelSettings> = xmltv_channel_settings::table
         .load(&mut conn)
@@ -287,18 +534,30 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
         })
         .collect();
 
+    let channel_numbers =
+        export_channel_numbers(&mut conn).map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
+
     // Build export structure with metadata (Task 1.8)
-    let export = ConfigExport {
+    let data = ExportData {
+        settings: exported_settings,
+        accounts: exported_accounts,
+        xmltv_sources: exported_sources,
+        channel_mappings: exported_mappings,
+        xmltv_channel_settings: exported_channel_settings,
+        portable_mappings,
+        channel_numbers,
+    };
+    let data_value =
+        data_as_read(&data).map_err(|e| ConfigError::SerializationError(e.to_string()))?;
+    let integrity = config_integrity::seal(&data_value, app_data_dir.as_deref());
+
+    let mut export = ConfigExport {
         version: CONFIG_VERSION.to_string(),
         export_date: chrono::Utc::now().to_rfc3339(),
         app_version: env!("CARGO_PKG_VERSION").to_string(),
-        data: ExportData {
-            settings: exported_settings,
-            accounts: exported_accounts,
-            xmltv_sources: exported_sources,
-            channel_mappings: exported_mappings,
-            xmltv_channel_settings: exported_channel_settings,
-        },
+        data,
+        integrity: Some(integrity),
+        credentials: None,
     };
 
     // Serialize to JSON
@@ -315,12 +574,30 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
         ).into());
     }
 
+    // Passwords are added only sealed, after the check above
+    let json = match &passphrase {
+        Some(passphrase) => {
+            let payload = serde_json::to_vec(&credentials)
+                .map_err(|e| ConfigError::SerializationError(e.to_string()))?;
+            let sealed = transfer::seal(passphrase, &payload)
+                .map_err(|e| ConfigError::CredentialExportFailed(e.to_string()))?;
+            export.credentials = Some(sealed);
+            serde_json::to_string_pretty(&export)
+                .map_err(|e| ConfigError::SerializationError(e.to_string()))?
+        }
+        None => json,
+    };
+
     // Story 6-3: Log configuration export event (AC #1)
     let details = serde_json::json!({
         "accountsExported": export.data.accounts.len(),
         "xmltvSourcesExported": export.data.xmltv_sources.len(),
         "channelMappingsExported": export.data.channel_mappings.len(),
+        "portableMappingsExported": export.data.portable_mappings.len(),
+        "channelNumbersExported": export.data.channel_numbers.len(),
         "version": CONFIG_VERSION,
+        "signed": export.integrity.as_ref().is_some_and(|i| i.signature.is_some()),
+        "credentialsIncluded": export.credentials.as_ref().map(|_| credentials.len()),
     });
     let _ = log_event_internal(
         &mut conn,
@@ -346,42 +623,111 @@ pub fn export_configuration(db: State<DbConnection>) -> Result<String, String> {
 /// Story 6-2: AC #3, #4
 ///
 /// Parses the JSON content and returns a preview of what will be imported.
+/// For valid files this is a dry run against the current database: the
+/// preview lists colliding and removed accounts/sources, changed settings, and
+/// mappings that cannot be resolved.
 #[tauri::command]
-pub fn validate_import_file(content: String) -> Result<ImportPreview, String> {
+pub fn validate_import_file(
+    app: AppHandle,
+    db: State<DbConnection>,
+    content: String,
+) -> Result<ImportPreview, String> {
+    let local_key = local_public_key(&app);
+    let (mut preview, config) = build_import_preview(&content, local_key.as_deref());
+
+    if let Some(config) = config {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
+        preview.conflicts = Some(
+            find_import_conflicts(&mut conn, &config.data)
+                .map_err(|e| ConfigError::DatabaseError(e.to_string()))?,
+        );
+    }
+
+    Ok(preview)
+}
+
+/// Parse an import file into a preview
+///
+/// Returns the parsed configuration only when it is valid for import.
+/// `local_public_key` is this installation's export signing key.
+fn build_import_preview(
+    content: &str,
+    local_public_key: Option<&str>,
+) -> (ImportPreview, Option<ConfigExport>) {
     // Parse JSON (Task 2.4)
-    let config: ConfigExport = match serde_json::from_str(&content) {
+    let config: ConfigExport = match serde_json::from_str(content) {
         Ok(c) => c,
         Err(e) => {
-            return Ok(ImportPreview {
+            return (
+                ImportPreview {
+                    valid: false,
+                    version: String::new(),
+                    export_date: String::new(),
+                    account_count: 0,
+                    xmltv_source_count: 0,
+                    channel_mapping_count: 0,
+                    xmltv_channel_settings_count: 0,
+                    settings_summary: vec![],
+                    error_message: Some(format!("Invalid JSON format: {}", e)),
+                    integrity: IntegrityStatus::Missing,
+                    integrity_warning: None,
+                    conflicts: None,
+                    has_credentials: false,
+                },
+                None,
+            );
+        }
+    };
+
+    let integrity = check_integrity(content, &config, local_public_key);
+
+    // Validate version compatibility (Task 2.4)
+    if !is_version_compatible(&config.version) {
+        return (
+            ImportPreview {
                 valid: false,
-                version: String::new(),
-                export_date: String::new(),
+                version: config.version.clone(),
+                export_date: config.export_date,
                 account_count: 0,
                 xmltv_source_count: 0,
                 channel_mapping_count: 0,
                 xmltv_channel_settings_count: 0,
                 settings_summary: vec![],
-                error_message: Some(format!("Invalid JSON format: {}", e)),
-            });
-        }
-    };
+                error_message: Some(format!(
+                    "Unsupported configuration version: {}. Minimum supported: {}",
+                    config.version, MIN_SUPPORTED_VERSION
+                )),
+                integrity,
+                integrity_warning: None,
+                conflicts: None,
+                has_credentials: config.credentials.is_some(),
+            },
+            None,
+        );
+    }
 
-    // Validate version compatibility (Task 2.4)
-    if !is_version_compatible(&config.version) {
-        return Ok(ImportPreview {
-            valid: false,
-            version: config.version.clone(),
-            export_date: config.export_date,
-            account_count: 0,
-            xmltv_source_count: 0,
-            channel_mapping_count: 0,
-            xmltv_channel_settings_count: 0,
-            settings_summary: vec![],
-            error_message: Some(format!(
-                "Unsupported configuration version: {}. Minimum supported: {}",
-                config.version, MIN_SUPPORTED_VERSION
-            )),
-        });
+    // Refuse truncated or tampered files before anything is compared
+    if integrity.is_failure() {
+        return (
+            ImportPreview {
+                valid: false,
+                version: config.version.clone(),
+                export_date: config.export_date,
+                account_count: 0,
+                xmltv_source_count: 0,
+                channel_mapping_count: 0,
+                xmltv_channel_settings_count: 0,
+                settings_summary: vec![],
+                error_message: integrity.message().map(str::to_string),
+                integrity,
+                integrity_warning: None,
+                conflicts: None,
+                has_credentials: config.credentials.is_some(),
+            },
+            None,
+        );
     }
 
     // Build settings summary
@@ -404,17 +750,153 @@ pub fn validate_import_file(content: String) -> Result<ImportPreview, String> {
         settings_summary.push(format!("EPG refresh: {}:{:0>2}", hour, minute));
     }
 
-    Ok(ImportPreview {
+    let preview = ImportPreview {
         valid: true,
-        version: config.version,
-        export_date: config.export_date,
+        version: config.version.clone(),
+        export_date: config.export_date.clone(),
         account_count: config.data.accounts.len(),
         xmltv_source_count: config.data.xmltv_sources.len(),
-        channel_mapping_count: config.data.channel_mappings.len(),
+        channel_mapping_count: mapping_count(&config.data),
         xmltv_channel_settings_count: config.data.xmltv_channel_settings.len(),
         settings_summary,
         error_message: None,
-    })
+        integrity,
+        integrity_warning: integrity.message().map(str::to_string),
+        conflicts: None,
+        has_credentials: config.credentials.is_some(),
+    };
+    (preview, Some(config))
+}
+
+/// Compare an import file against the current database (dry run)
+///
+/// Mirrors `import_configuration`, which replaces accounts, XMLTV sources, and
+/// all settings.
+fn find_import_conflicts(
+    conn: &mut SqliteConnection,
+    data: &ExportData,
+) -> QueryResult<ImportConflicts> {
+    let mut conflicts = ImportConflicts::default();
+
+    // Accounts: same server and username
+    let existing_accounts: Vec<Account> = accounts::table.load(conn)?;
+    for account in &data.accounts {
+        if let Some(existing) = existing_accounts.iter().find(|a| {
+            same_url(&a.server_url, &account.server_url) && a.username == account.username
+        }) {
+            conflicts.existing_accounts.push(ImportCollision {
+                name: account.name.clone(),
+                url: account.server_url.clone(),
+                existing_name: existing.name.clone(),
+            });
+        }
+    }
+    conflicts.removed_accounts = existing_accounts
+        .iter()
+        .filter(|a| {
+            !data.accounts.iter().any(|account| {
+                same_url(&a.server_url, &account.server_url) && a.username == account.username
+            })
+        })
+        .map(|a| a.name.clone())
+        .collect();
+
+    // XMLTV sources: same URL
+    let existing_sources: Vec<XmltvSource> = xmltv_sources::table.load(conn)?;
+    for source in &data.xmltv_sources {
+        if let Some(existing) = existing_sources.iter().find(|s| same_url(&s.url, &source.url)) {
+            conflicts.existing_xmltv_sources.push(ImportCollision {
+                name: source.name.clone(),
+                url: source.url.clone(),
+                existing_name: existing.name.clone(),
+            });
+        }
+    }
+    conflicts.removed_xmltv_sources = existing_sources
+        .iter()
+        .filter(|s| !data.xmltv_sources.iter().any(|source| same_url(&s.url, &source.url)))
+        .map(|s| s.name.clone())
+        .collect();
+
+    // Settings: the import clears the table and writes only exported keys
+    let current_settings: Vec<Setting> = settings::table.load(conn)?;
+    let imported = exported_setting_values(&data.settings);
+    for (key, imported_value) in &imported {
+        let current_value = current_settings
+            .iter()
+            .find(|s| s.key == *key)
+            .map(|s| s.value.clone());
+        if current_value != *imported_value {
+            conflicts.setting_changes.push(SettingChange {
+                key: key.to_string(),
+                current_value,
+                imported_value: imported_value.clone(),
+            });
+        }
+    }
+    conflicts.settings_reset = current_settings
+        .into_iter()
+        .map(|s| s.key)
+        .filter(|key| !imported.iter().any(|(k, _)| k == key))
+        .collect();
+
+    // Portable mappings: their source and account must be imported with them
+    if !data.portable_mappings.is_empty() {
+        for mapping in &data.portable_mappings {
+            let reason = if !data
+                .xmltv_sources
+                .iter()
+                .any(|s| same_url(&s.url, &mapping.source_url))
+            {
+                format!("EPG source {} is not in the file", mapping.source_url)
+            } else if !data.accounts.iter().any(|a| {
+                same_url(&a.server_url, &mapping.account_url)
+                    && a.username == mapping.account_username
+            }) {
+                format!("Account {} is not in the file", mapping.account_url)
+            } else {
+                continue;
+            };
+            conflicts
+                .unresolved_portable_mappings
+                .push(UnresolvedPortableMapping {
+                    channel_id: mapping.channel_id.clone(),
+                    stream_id: mapping.stream_id,
+                    reason,
+                });
+        }
+        return Ok(conflicts);
+    }
+
+    // Mappings (1.x files): both channel IDs must exist in this database
+    let xmltv_ids: std::collections::HashSet<i32> = xmltv_channels::table
+        .select(xmltv_channels::id)
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+    let xtream_ids: std::collections::HashSet<i32> = xtream_channels::table
+        .select(xtream_channels::id)
+        .load::<Option<i32>>(conn)?
+        .into_iter()
+        .flatten()
+        .collect();
+    for mapping in &data.channel_mappings {
+        let reason = if !xmltv_ids.contains(&mapping.xmltv_channel_id) {
+            format!("XMLTV channel {} not found", mapping.xmltv_channel_id)
+        } else if !xtream_ids.contains(&mapping.xtream_channel_id) {
+            format!("Xtream stream {} not found", mapping.xtream_channel_id)
+        } else {
+            continue;
+        };
+        conflicts.unresolved_mappings.push(UnresolvedMapping {
+            xmltv_channel_id: mapping.xmltv_channel_id,
+            xtream_channel_id: mapping.xtream_channel_id,
+            reason,
+        });
+    }
+
+    Ok(conflicts)
 }
 
 /// Import configuration from JSON content (Task 2.3)
@@ -422,16 +904,29 @@ pub fn validate_import_file(content: String) -> Result<ImportPreview, String> {
 /// Story 6-2: AC #5, #6
 ///
 /// Performs atomic import: all data is replaced (not merged).
-/// Accounts are imported with empty passwords - user must re-enter.
+/// Accounts are imported with empty passwords - user must re-enter - unless the
+/// file carries sealed credentials and `passphrase` opens them.
 #[tauri::command]
 pub fn import_configuration(
+    app: AppHandle,
     db: State<DbConnection>,
     content: String,
+    passphrase: Option<String>,
 ) -> Result<ImportResult, String> {
     // Parse JSON
     let config: ConfigExport = serde_json::from_str(&content)
         .map_err(|e| ConfigError::ParseError(e.to_string()))?;
 
+    // Verify the integrity hash and signature before replacing anything
+    let local_key = local_public_key(&app);
+    let integrity = check_integrity(&content, &config, local_key.as_deref());
+    if integrity.is_failure() {
+        return Err(ConfigError::ImportFailed(
+            integrity.message().unwrap_or_default().to_string(),
+        )
+        .into());
+    }
+
     // Validate version
     if !is_version_compatible(&config.version) {
         return Err(ConfigError::UnsupportedVersion(
@@ -441,6 +936,12 @@ pub fn import_configuration(
         .into());
     }
 
+    // Open sealed credentials first, so a wrong passphrase changes nothing
+    let credentials = match (&config.credentials, &passphrase) {
+        (Some(sealed), Some(passphrase)) => open_credentials(sealed, passphrase)?,
+        _ => vec![],
+    };
+
     let mut conn = db
         .get_connection()
         .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
@@ -450,8 +951,11 @@ pub fn import_configuration(
         // Clear existing data (Task 2.7)
         // Order matters due to foreign key constraints
         diesel::delete(channel_mappings::table).execute(conn)?;
+        diesel::delete(pending_channel_mappings::table).execute(conn)?;
+        diesel::delete(pending_channel_numbers::table).execute(conn)?;
         diesel::delete(xmltv_channel_settings::table).execute(conn)?;
-        diesel::delete(xmltv_sources::table).execute(conn)?;
+        diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.ne(SYNTHETIC_SOURCE_ID)))
+            .execute(conn)?;
         diesel::delete(accounts::table).execute(conn)?;
         // Clear settings (but keep them - they're just key-value pairs)
         diesel::delete(settings::table).execute(conn)?;
@@ -498,7 +1002,8 @@ pub fn import_configuration(
             if account.server_url.trim().is_empty() {
                 return Err(diesel::result::Error::RollbackTransaction);
             }
-            if account.username.trim().is_empty() {
+            let is_m3u = account.provider_type == ProviderType::M3u;
+            if !is_m3u && account.username.trim().is_empty() {
                 return Err(diesel::result::Error::RollbackTransaction);
             }
 
@@ -508,7 +1013,9 @@ pub fn import_configuration(
                 username: account.username.clone(),
                 password_encrypted: vec![], // Empty - user must re-enter
                 max_connections: account.max_connections,
-                is_active: 0, // Mark as inactive until password is set
+                // Mark as inactive until password is set (M3U accounts have none)
+                is_active: if is_m3u { account.is_active as i32 } else { 0 },
+                provider_type: account.provider_type.to_string(),
             };
             diesel::insert_into(accounts::table)
                 .values(&new_account)
@@ -537,24 +1044,44 @@ pub fn import_configuration(
                 .execute(conn)?;
         }
 
-        // NOTE: Channel mappings and settings are NOT imported here because:
-        // 1. They reference xmltv_channels and xtream_channels which are re-fetched from providers
-        // 2. The database IDs won't match after provider data is refreshed
-        // 3. Importing stale IDs would create foreign key constraint violations or point to wrong channels
-        // 4. User will need to re-run channel matching after import to regenerate mappings
-        //
-        // TECHNICAL EXPLANATION: The exported mappings contain database primary keys (integers)
-        // that are auto-incremented. After importing accounts and sources, when the user fetches
-        // channels from providers, new xmltv_channels and xtream_channels records will be created
-        // with DIFFERENT IDs. The old mapping IDs would either:
-        //   - Fail foreign key constraints (if IDs don't exist)
-        //   - Map to completely wrong channels (if IDs happen to exist but point elsewhere)
-        //
-        // To preserve mappings across import, we would need to:
-        // - Export channel identifiers (channel_id strings, not database IDs)
-        // - After import, wait for user to fetch channels from providers
-        // - Re-resolve mappings by looking up new database IDs for the channel_id strings
-        // - This is complex and out of scope for current story requirements
+        // Mappings reference channels and streams by database ID, and those rows
+        // are only created when the providers are fetched again. Keep the
+        // portable mappings pending until then (see `resolve_imported_mappings`);
+        // the ID-based `channel_mappings` of 1.x files cannot be carried over.
+        let pending: Vec<NewPendingChannelMapping> = config
+            .data
+            .portable_mappings
+            .iter()
+            .map(|m| NewPendingChannelMapping {
+                source_url: m.source_url.clone(),
+                channel_id: m.channel_id.clone(),
+                account_url: m.account_url.clone(),
+                account_username: m.account_username.clone(),
+                stream_id: m.stream_id,
+                match_confidence: m.match_confidence,
+                is_manual: m.is_manual as i32,
+                is_primary: m.is_primary as i32,
+                stream_priority: m.stream_priority,
+            })
+            .collect();
+        diesel::insert_into(pending_channel_mappings::table)
+            .values(&pending)
+            .execute(conn)?;
+
+        // Channel numbers wait for their channels the same way
+        let numbers: Vec<NewPendingChannelNumber> = config
+            .data
+            .channel_numbers
+            .iter()
+            .map(|n| NewPendingChannelNumber {
+                source_url: n.source_url.clone(),
+                channel_id: n.channel_id.clone(),
+                channel_number: n.channel_number,
+            })
+            .collect();
+        diesel::insert_into(pending_channel_numbers::table)
+            .values(&numbers)
+            .execute(conn)?;
 
         Ok(())
     })
@@ -589,6 +1116,25 @@ pub fn import_configuration(
     let settings_count = count_settings(&config.data.settings);
     let accounts_count = config.data.accounts.len();
     let sources_count = config.data.xmltv_sources.len();
+    let mappings_count = config.data.portable_mappings.len();
+
+    // Store opened passwords for the new accounts (keychain entries are keyed by
+    // account ID, which only exists now)
+    let credentials_restored = if credentials.is_empty() {
+        0
+    } else {
+        let app_data_dir = app.path().app_data_dir().map_err(|e| {
+            ConfigError::CredentialImportFailed(format!("App data directory not available: {}", e))
+        })?;
+        restore_credentials(
+            &mut conn,
+            &CredentialManager::new(app_data_dir),
+            &credentials,
+            &config.data.accounts,
+        )
+        .map_err(|e| ConfigError::DatabaseError(e.to_string()))?
+    };
+    let passwords_missing = accounts_count.saturating_sub(credentials_restored);
 
     // Story 6-3: Log configuration import event (AC #1)
     // Need a fresh connection after the transaction
@@ -597,7 +1143,10 @@ pub fn import_configuration(
             "accountsImported": accounts_count,
             "xmltvSourcesImported": sources_count,
             "settingsImported": settings_count,
+            "mappingsPending": mappings_count,
+            "credentialsRestored": credentials_restored,
             "version": config.version,
+            "integrity": integrity,
         });
         let _ = log_event_internal(
             &mut log_conn,
@@ -615,19 +1164,358 @@ pub fn import_configuration(
         success: true,
         accounts_imported: accounts_count,
         xmltv_sources_imported: sources_count,
-        channel_mappings_imported: 0, // Not imported - see note above
+        channel_mappings_imported: mappings_count,
         settings_imported: settings_count,
-        message: format!(
-            "Configuration imported successfully. {} accounts need passwords re-entered.",
-            accounts_count
-        ),
+        credentials_restored,
+        message: if mappings_count > 0 {
+            format!(
+                "Configuration imported successfully. {} accounts need passwords re-entered. \
+                 {} channel mappings will be restored once channels are refreshed.",
+                passwords_missing, mappings_count
+            )
+        } else {
+            format!(
+                "Configuration imported successfully. {} accounts need passwords re-entered.",
+                passwords_missing
+            )
+        },
+    })
+}
+
+/// Link imported mappings to the channels and streams fetched since the import
+///
+/// Mappings whose XMLTV channel or stream does not exist yet stay pending, so
+/// this can be run again after the next refresh.
+#[tauri::command]
+pub fn resolve_imported_mappings(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<MappingResolution, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
+
+    let resolution = resolve_pending_mappings(&mut conn)
+        .map_err(|e| ConfigError::DatabaseError(e.to_string()))?;
+
+    if resolution.resolved > 0 || resolution.numbers_restored > 0 {
+        server_state.bump_epg_generation();
+        let details = serde_json::json!({
+            "resolved": resolution.resolved,
+            "pending": resolution.unresolved.len(),
+            "numbersRestored": resolution.numbers_restored,
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!(
+                "Configuration changed: {} imported channel mappings restored ({} pending)",
+                resolution.resolved,
+                resolution.unresolved.len()
+            ),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(resolution)
+}
+
+/// Link every pending mapping whose channel and stream exist
+///
+/// Resolved mappings replace any existing mapping of the same pair; a primary
+/// one demotes the other streams of its channel. Pending channel numbers are
+/// set on their channels once those exist.
+fn resolve_pending_mappings(conn: &mut SqliteConnection) -> QueryResult<MappingResolution> {
+    conn.transaction(|conn| {
+        let pending: Vec<PendingChannelMapping> = pending_channel_mappings::table
+            .select(PendingChannelMapping::as_select())
+            .order(pending_channel_mappings::id.asc())
+            .load(conn)?;
+        let sources: Vec<(Option<i32>, String)> = xmltv_sources::table
+            .select((xmltv_sources::id, xmltv_sources::url))
+            .load(conn)?;
+        let all_accounts: Vec<(Option<i32>, String, String)> = accounts::table
+            .select((accounts::id, accounts::server_url, accounts::username))
+            .load(conn)?;
+
+        let mut resolution = MappingResolution::default();
+        for mapping in pending {
+            let (xmltv_channel_id, xtream_channel_id) =
+                match find_mapping_targets(conn, &mapping, &sources, &all_accounts)? {
+                    Ok(ids) => ids,
+                    Err(reason) => {
+                        resolution.unresolved.push(UnresolvedPortableMapping {
+                            channel_id: mapping.channel_id,
+                            stream_id: mapping.stream_id,
+                            reason,
+                        });
+                        continue;
+                    }
+                };
+
+            if mapping.is_primary != 0 {
+                diesel::update(
+                    channel_mappings::table
+                        .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+                        .filter(channel_mappings::xtream_channel_id.ne(xtream_channel_id)),
+                )
+                .set(channel_mappings::is_primary.eq(0))
+                .execute(conn)?;
+            }
+            diesel::replace_into(channel_mappings::table)
+                .values(&NewChannelMapping {
+                    xmltv_channel_id,
+                    xtream_channel_id,
+                    match_confidence: mapping.match_confidence,
+                    is_manual: mapping.is_manual,
+                    is_primary: mapping.is_primary,
+                    stream_priority: mapping.stream_priority,
+                })
+                .execute(conn)?;
+            diesel::delete(
+                pending_channel_mappings::table.filter(pending_channel_mappings::id.eq(mapping.id)),
+            )
+            .execute(conn)?;
+            resolution.resolved += 1;
+        }
+
+        let numbers: Vec<PendingChannelNumber> = pending_channel_numbers::table
+            .select(PendingChannelNumber::as_select())
+            .order(pending_channel_numbers::id.asc())
+            .load(conn)?;
+        for number in numbers {
+            let Some(source_id) = sources
+                .iter()
+                .find(|(_, url)| same_url(url, &number.source_url))
+                .and_then(|(id, _)| *id)
+            else {
+                continue;
+            };
+            let Some(xmltv_channel_id) = xmltv_channels::table
+                .filter(xmltv_channels::source_id.eq(source_id))
+                .filter(xmltv_channels::channel_id.eq(&number.channel_id))
+                .select(xmltv_channels::id)
+                .first::<Option<i32>>(conn)
+                .optional()?
+                .flatten()
+            else {
+                continue;
+            };
+            diesel::insert_or_ignore_into(xmltv_channel_settings::table)
+                .values((
+                    xmltv_channel_settings::xmltv_channel_id.eq(xmltv_channel_id),
+                    xmltv_channel_settings::is_enabled.eq(0),
+                ))
+                .execute(conn)?;
+            // A number taken since the import stays pending
+            if crate::channel_numbers::set_channel_number(
+                conn,
+                xmltv_channel_id,
+                Some(number.channel_number),
+            )
+            .is_err()
+            {
+                continue;
+            }
+            diesel::delete(
+                pending_channel_numbers::table.filter(pending_channel_numbers::id.eq(number.id)),
+            )
+            .execute(conn)?;
+            resolution.numbers_restored += 1;
+        }
+        Ok(resolution)
     })
 }
 
+/// Database IDs of the XMLTV channel and stream of a pending mapping
+///
+/// The inner error says which of them cannot be found.
+fn find_mapping_targets(
+    conn: &mut SqliteConnection,
+    mapping: &PendingChannelMapping,
+    sources: &[(Option<i32>, String)],
+    all_accounts: &[(Option<i32>, String, String)],
+) -> QueryResult<Result<(i32, i32), String>> {
+    let Some(source_id) = sources
+        .iter()
+        .find(|(_, url)| same_url(url, &mapping.source_url))
+        .and_then(|(id, _)| *id)
+    else {
+        return Ok(Err(format!("EPG source {} not found", mapping.source_url)));
+    };
+    let Some(account_id) = all_accounts
+        .iter()
+        .find(|(_, url, username)| {
+            same_url(url, &mapping.account_url) && *username == mapping.account_username
+        })
+        .and_then(|(id, _, _)| *id)
+    else {
+        return Ok(Err(format!("Account {} not found", mapping.account_url)));
+    };
+
+    let xmltv_channel_id = xmltv_channels::table
+        .filter(xmltv_channels::source_id.eq(source_id))
+        .filter(xmltv_channels::channel_id.eq(&mapping.channel_id))
+        .select(xmltv_channels::id.assume_not_null())
+        .first::<i32>(conn)
+        .optional()?;
+    let Some(xmltv_channel_id) = xmltv_channel_id else {
+        return Ok(Err(format!(
+            "XMLTV channel {} not fetched yet",
+            mapping.channel_id
+        )));
+    };
+    let xtream_channel_id = xtream_channels::table
+        .filter(xtream_channels::account_id.eq(account_id))
+        .filter(xtream_channels::stream_id.eq(mapping.stream_id))
+        .select(xtream_channels::id.assume_not_null())
+        .first::<i32>(conn)
+        .optional()?;
+    let Some(xtream_channel_id) = xtream_channel_id else {
+        return Ok(Err(format!("Stream {} not fetched yet", mapping.stream_id)));
+    };
+
+    Ok(Ok((xmltv_channel_id, xtream_channel_id)))
+}
+
 // ============================================================================
 // Helper Functions
 // ============================================================================
 
+/// The `data` section as a JSON value, as it will be read back on import
+///
+/// Goes through the serialized text so numbers hash the same as when the
+/// file is parsed (f32 confidences would otherwise widen differently).
+fn data_as_read(data: &ExportData) -> Result<serde_json::Value, serde_json::Error> {
+    serde_json::from_str(&serde_json::to_string(data)?)
+}
+
+/// Channel numbers set on channels, by source URL and channel id
+fn export_channel_numbers(conn: &mut SqliteConnection) -> QueryResult<Vec<ExportedChannelNumber>> {
+    let rows: Vec<(String, String, i32)> = xmltv_channel_settings::table
+        .inner_join(
+            xmltv_channels::table
+                .on(xmltv_channels::id
+                    .assume_not_null()
+                    .eq(xmltv_channel_settings::xmltv_channel_id))
+                .inner_join(xmltv_sources::table),
+        )
+        .filter(xmltv_channel_settings::channel_number.is_not_null())
+        .select((
+            xmltv_sources::url,
+            xmltv_channels::channel_id,
+            xmltv_channel_settings::channel_number.assume_not_null(),
+        ))
+        .load(conn)?;
+    Ok(rows
+        .into_iter()
+        .map(
+            |(source_url, channel_id, channel_number)| ExportedChannelNumber {
+                source_url,
+                channel_id,
+                channel_number,
+            },
+        )
+        .collect())
+}
+
+/// Stored passwords of the Xtream accounts that have one
+fn collect_credentials(
+    manager: &CredentialManager,
+    accounts: &[Account],
+) -> Result<Vec<ExportedCredential>, ConfigError> {
+    accounts
+        .iter()
+        .filter(|a| a.provider() != ProviderType::M3u && !a.password_encrypted.is_empty())
+        .map(|a| {
+            let id = a.id.unwrap_or(0).to_string();
+            manager
+                .retrieve_password(&id, &a.password_encrypted)
+                .map(|password| ExportedCredential {
+                    server_url: a.server_url.clone(),
+                    username: a.username.clone(),
+                    password,
+                })
+                .map_err(|e| {
+                    ConfigError::CredentialExportFailed(format!("account {}: {}", a.name, e))
+                })
+        })
+        .collect()
+}
+
+/// Decrypt the credentials section of an import file
+fn open_credentials(
+    sealed: &SealedSecrets,
+    passphrase: &str,
+) -> Result<Vec<ExportedCredential>, ConfigError> {
+    let payload = transfer::open(passphrase, sealed)
+        .map_err(|e| ConfigError::CredentialImportFailed(e.to_string()))?;
+    serde_json::from_slice(&payload).map_err(|e| ConfigError::CredentialImportFailed(e.to_string()))
+}
+
+/// Store imported passwords for the matching accounts and re-activate them
+///
+/// Returns how many accounts got their password back. Accounts whose password
+/// cannot be stored stay inactive with an empty password, as without credentials.
+fn restore_credentials(
+    conn: &mut SqliteConnection,
+    manager: &CredentialManager,
+    credentials: &[ExportedCredential],
+    imported: &[ExportedAccount],
+) -> QueryResult<usize> {
+    let rows: Vec<Account> = accounts::table.load(conn)?;
+    let mut restored = 0;
+    for row in rows {
+        let Some(id) = row.id else { continue };
+        let Some(credential) = credentials
+            .iter()
+            .find(|c| same_url(&c.server_url, &row.server_url) && c.username == row.username)
+        else {
+            continue;
+        };
+        let is_active = imported
+            .iter()
+            .find(|a| same_url(&a.server_url, &row.server_url) && a.username == row.username)
+            .is_none_or(|a| a.is_active);
+
+        let encrypted = match manager.store_password(&id.to_string(), &credential.password) {
+            Ok((_, encrypted)) => encrypted,
+            Err(e) => {
+                eprintln!("Import: could not store password for {}: {}", row.name, e);
+                continue;
+            }
+        };
+        diesel::update(accounts::table.filter(accounts::id.eq(id)))
+            .set((
+                accounts::password_encrypted.eq(&encrypted),
+                accounts::is_active.eq(is_active as i32),
+            ))
+            .execute(conn)?;
+        restored += 1;
+    }
+    Ok(restored)
+}
+
+/// Check the `data` section of an import file against its integrity section
+fn check_integrity(
+    content: &str,
+    config: &ConfigExport,
+    local_public_key: Option<&str>,
+) -> IntegrityStatus {
+    let data = serde_json::from_str::<serde_json::Value>(content)
+        .ok()
+        .and_then(|mut value| value.get_mut("data").map(serde_json::Value::take))
+        .unwrap_or_default();
+    config_integrity::verify(&data, config.integrity.as_ref(), local_public_key)
+}
+
+/// This installation's export signing key, if it has one
+fn local_public_key(app: &AppHandle) -> Option<String> {
+    let dir = app.path().app_data_dir().ok()?;
+    config_integrity::local_public_key(&dir)
+}
+
 /// Check if the import version is compatible
 fn is_version_compatible(version: &str) -> bool {
     // Version comparison - check major.minor
@@ -656,6 +1544,32 @@ fn is_version_compatible(version: &str) -> bool {
     minor >= min_minor
 }
 
+/// Compare URLs ignoring case, whitespace, and trailing slashes
+fn same_url(a: &str, b: &str) -> bool {
+    a.trim().trim_end_matches('/').eq_ignore_ascii_case(b.trim().trim_end_matches('/'))
+}
+
+/// Exported settings as `(key, value)` pairs in database key form
+fn exported_setting_values(settings: &ExportedSettings) -> [(&'static str, Option<String>); 6] {
+    [
+        ("server_port", settings.server_port.clone()),
+        ("autostart_enabled", settings.autostart_enabled.clone()),
+        ("epg_schedule_hour", settings.epg_schedule_hour.clone()),
+        ("epg_schedule_minute", settings.epg_schedule_minute.clone()),
+        ("epg_schedule_enabled", settings.epg_schedule_enabled.clone()),
+        ("match_threshold", settings.match_threshold.clone()),
+    ]
+}
+
+/// Mappings a file carries: portable ones, or the ID-based ones of 1.x files
+fn mapping_count(data: &ExportData) -> usize {
+    if data.portable_mappings.is_empty() {
+        data.channel_mappings.len()
+    } else {
+        data.portable_mappings.len()
+    }
+}
+
 /// Count non-None settings
 fn count_settings(settings: &ExportedSettings) -> usize {
     let mut count = 0;
@@ -710,6 +1624,7 @@ mod tests {
                     username: "testuser".to_string(),
                     max_connections: 2,
                     is_active: true,
+                    provider_type: ProviderType::Xtream,
                 }],
                 xmltv_sources: vec![ExportedXmltvSource {
                     id: 1,
@@ -732,7 +1647,11 @@ mod tests {
                     is_enabled: true,
                     plex_display_order: Some(1),
                 }],
+                portable_mappings: vec![],
+                channel_numbers: vec![],
             },
+            integrity: None,
+            credentials: None,
         };
 
         let json = serde_json::to_string_pretty(&export).unwrap();
@@ -781,6 +1700,8 @@ mod tests {
         assert_eq!(config.version, "1.0");
         assert_eq!(config.data.settings.server_port, Some("5004".to_string()));
         assert!(config.data.settings.match_threshold.is_none());
+        // 1.x files carry no portable mappings
+        assert!(config.data.portable_mappings.is_empty());
     }
 
     #[test]
@@ -801,7 +1722,8 @@ mod tests {
 
     #[test]
     fn test_import_preview_invalid_json() {
-        let result = validate_import_file("not valid json".to_string()).unwrap();
+        let (result, config) = build_import_preview("not valid json", None);
+        assert!(config.is_none());
         assert!(!result.valid);
         assert!(result.error_message.is_some());
         assert!(result.error_message.unwrap().contains("Invalid JSON"));
@@ -822,7 +1744,7 @@ mod tests {
             }
         }"#;
 
-        let result = validate_import_file(json.to_string()).unwrap();
+        let (result, _) = build_import_preview(json, None);
         assert!(!result.valid);
         assert!(result.error_message.unwrap().contains("Unsupported"));
     }
@@ -862,13 +1784,59 @@ mod tests {
             }
         }"#;
 
-        let result = validate_import_file(json.to_string()).unwrap();
+        let (result, _) = build_import_preview(json, None);
         assert!(result.valid);
         assert_eq!(result.account_count, 1);
         assert_eq!(result.xmltv_source_count, 1);
         assert!(result.error_message.is_none());
     }
 
+    #[test]
+    fn test_import_preview_checks_integrity() {
+        let json = r#"{
+            "version": "1.0",
+            "exportDate": "2026-01-23T12:00:00Z",
+            "appVersion": "0.1.0",
+            "data": {
+                "settings": {},
+                "accounts": [],
+                "xmltvSources": [],
+                "channelMappings": [
+                    {
+                        "xmltvChannelId": 1,
+                        "xtreamChannelId": 42,
+                        "matchConfidence": 0.9,
+                        "isManual": false,
+                        "isPrimary": true,
+                        "streamPriority": 0
+                    }
+                ],
+                "xmltvChannelSettings": []
+            }
+        }"#;
+        let (result, config) = build_import_preview(json, None);
+        assert_eq!(result.integrity, IntegrityStatus::Missing);
+        assert!(result.valid);
+        assert!(result.integrity_warning.is_some());
+
+        // Seal as an export would, then write the file out
+        let mut config = config.unwrap();
+        let data = data_as_read(&config.data).unwrap();
+        config.integrity = Some(config_integrity::seal(&data, None));
+        let sealed = serde_json::to_string_pretty(&config).unwrap();
+        let (result, _) = build_import_preview(&sealed, None);
+        assert_eq!(result.integrity, IntegrityStatus::Verified);
+        assert!(result.valid);
+        assert!(result.integrity_warning.is_none());
+
+        let tampered = sealed.replace("\"xtreamChannelId\": 42", "\"xtreamChannelId\": 43");
+        assert_ne!(tampered, sealed);
+        let (result, config) = build_import_preview(&tampered, None);
+        assert_eq!(result.integrity, IntegrityStatus::HashMismatch);
+        assert!(!result.valid);
+        assert!(config.is_none());
+    }
+
     #[test]
     fn test_count_settings() {
         let settings = ExportedSettings {
@@ -882,4 +1850,207 @@ mod tests {
 
         assert_eq!(count_settings(&settings), 2);
     }
+
+    #[test]
+    fn test_find_import_conflicts() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://provider.example.com/");
+        crate::db::seed_account(&mut conn, 2, "Old", "http://old.example.com");
+        diesel::sql_query(
+            "INSERT INTO xmltv_sources (name, url) VALUES ('Guide', 'http://epg.example.com/guide.xml')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::replace_into(settings::table)
+            .values(&Setting::new("server_port", "5004"))
+            .execute(&mut conn)
+            .unwrap();
+        diesel::replace_into(settings::table)
+            .values(&Setting::new("custom_setting", "1"))
+            .execute(&mut conn)
+            .unwrap();
+
+        let data = ExportData {
+            settings: ExportedSettings {
+                server_port: Some("6000".to_string()),
+                autostart_enabled: None,
+                epg_schedule_hour: None,
+                epg_schedule_minute: None,
+                epg_schedule_enabled: None,
+                match_threshold: None,
+            },
+            accounts: vec![ExportedAccount {
+                id: 1,
+                name: "Provider".to_string(),
+                server_url: "HTTP://provider.example.com".to_string(),
+                username: "user".to_string(),
+                max_connections: 1,
+                is_active: true,
+                provider_type: ProviderType::Xtream,
+            }],
+            xmltv_sources: vec![ExportedXmltvSource {
+                id: 1,
+                name: "New Guide".to_string(),
+                url: "http://other.example.com/guide.xml".to_string(),
+                format: "xml".to_string(),
+                refresh_hour: 4,
+                is_active: true,
+            }],
+            channel_mappings: vec![ExportedChannelMapping {
+                xmltv_channel_id: 99,
+                xtream_channel_id: 42,
+                match_confidence: None,
+                is_manual: true,
+                is_primary: true,
+                stream_priority: 0,
+            }],
+            xmltv_channel_settings: vec![],
+            portable_mappings: vec![],
+            channel_numbers: vec![],
+        };
+
+        let conflicts = find_import_conflicts(&mut conn, &data).unwrap();
+        assert_eq!(conflicts.existing_accounts.len(), 1);
+        assert_eq!(conflicts.existing_accounts[0].existing_name, "Main");
+        assert_eq!(conflicts.removed_accounts, vec!["Old".to_string()]);
+        assert!(conflicts.existing_xmltv_sources.is_empty());
+        assert_eq!(conflicts.removed_xmltv_sources, vec!["Guide".to_string()]);
+        assert!(conflicts.setting_changes.contains(&SettingChange {
+            key: "server_port".to_string(),
+            current_value: Some("5004".to_string()),
+            imported_value: Some("6000".to_string()),
+        }));
+        assert!(conflicts.settings_reset.contains(&"custom_setting".to_string()));
+        assert_eq!(conflicts.unresolved_mappings.len(), 1);
+        assert!(conflicts.unresolved_mappings[0].reason.contains("XMLTV channel 99"));
+    }
+
+    #[test]
+    fn test_resolve_imported_mappings() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://provider.example.com");
+        diesel::sql_query(
+            "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://epg.example.com/guide.xml')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name)
+                VALUES (10, 1, 'bbc1.uk', 'BBC One')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+                (20, 1, 501, 'BBC One HD'),
+                (21, 1, 502, 'BBC One SD')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        // Matched automatically after the refresh
+        diesel::insert_into(channel_mappings::table)
+            .values(&NewChannelMapping::new(10, 21, Some(0.8), true, 0))
+            .execute(&mut conn)
+            .unwrap();
+
+        let pending = |channel_id: &str, stream_id: i32| NewPendingChannelMapping {
+            source_url: "http://epg.example.com/guide.xml/".to_string(),
+            channel_id: channel_id.to_string(),
+            account_url: "HTTP://provider.example.com".to_string(),
+            account_username: "user".to_string(),
+            stream_id,
+            match_confidence: None,
+            is_manual: 1,
+            is_primary: 1,
+            stream_priority: 0,
+        };
+        diesel::insert_into(pending_channel_mappings::table)
+            .values(&vec![pending("bbc1.uk", 501), pending("bbc1.uk", 999)])
+            .execute(&mut conn)
+            .unwrap();
+
+        let resolution = resolve_pending_mappings(&mut conn).unwrap();
+        assert_eq!(resolution.resolved, 1);
+        assert_eq!(resolution.unresolved.len(), 1);
+        assert!(resolution.unresolved[0].reason.contains("Stream 999"));
+
+        let mut mappings: Vec<(i32, Option<i32>, Option<i32>)> = channel_mappings::table
+            .filter(channel_mappings::xmltv_channel_id.eq(10))
+            .select((
+                channel_mappings::xtream_channel_id,
+                channel_mappings::is_manual,
+                channel_mappings::is_primary,
+            ))
+            .load(&mut conn)
+            .unwrap();
+        mappings.sort();
+        assert_eq!(
+            mappings,
+            vec![(20, Some(1), Some(1)), (21, Some(0), Some(0))]
+        );
+
+        // The unresolved mapping stays pending for the next run
+        let remaining: i64 = pending_channel_mappings::table
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(remaining, 1);
+    }
+    #[test]
+    fn test_channel_numbers_survive_export_and_import() {
+        let source_row = "INSERT INTO xmltv_sources (id, name, url)
+            VALUES (1, 'Guide', 'http://epg.example.com/guide.xml')";
+        let channel_row = |id: i32| {
+            format!(
+                "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name)
+                    VALUES ({id}, 1, 'bbc1.uk', 'BBC One')"
+            )
+        };
+
+        let mut source = crate::db::test_connection();
+        diesel::sql_query(source_row).execute(&mut source).unwrap();
+        diesel::sql_query(channel_row(10))
+            .execute(&mut source)
+            .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, channel_number)
+                VALUES (10, 1, 101)",
+        )
+        .execute(&mut source)
+        .unwrap();
+        let json = serde_json::to_string(&export_channel_numbers(&mut source).unwrap()).unwrap();
+        let exported: Vec<ExportedChannelNumber> = serde_json::from_str(&json).unwrap();
+        assert_eq!(exported.len(), 1);
+
+        // Imported before the channels are fetched, so the number stays pending
+        let mut target = crate::db::test_connection();
+        diesel::sql_query(source_row).execute(&mut target).unwrap();
+        diesel::insert_into(pending_channel_numbers::table)
+            .values(&NewPendingChannelNumber {
+                source_url: exported[0].source_url.clone(),
+                channel_id: exported[0].channel_id.clone(),
+                channel_number: exported[0].channel_number,
+            })
+            .execute(&mut target)
+            .unwrap();
+        let resolution = resolve_pending_mappings(&mut target).unwrap();
+        assert_eq!(resolution.numbers_restored, 0);
+
+        diesel::sql_query(channel_row(42))
+            .execute(&mut target)
+            .unwrap();
+        let resolution = resolve_pending_mappings(&mut target).unwrap();
+        assert_eq!(resolution.numbers_restored, 1);
+        let number: Option<i32> = xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(42))
+            .select(xmltv_channel_settings::channel_number)
+            .first(&mut target)
+            .unwrap();
+        assert_eq!(number, Some(101));
+        let remaining: i64 = pending_channel_numbers::table
+            .count()
+            .get_result(&mut target)
+            .unwrap();
+        assert_eq!(remaining, 0);
+    }
 }
@@ -0,0 +1,237 @@
+//! Credential maintenance Tauri commands
+//!
+//! Re-encrypts passwords stored with the AES fallback (account passwords, XMLTV
+//! source secrets and the Plex token) to the current blob version, optionally
+//! rotating the salt the keys are derived from. Keychain entries are untouched.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use diesel::prelude::*;
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::credentials::{CredentialManager, SALT_LENGTH};
+use crate::db::schema::{accounts, settings, xmltv_sources};
+use crate::db::{DbConnection, Setting};
+use crate::plex::{PLEX_TOKEN_CREDENTIAL_ID, PLEX_TOKEN_KEY};
+use crate::xmltv::fetcher::{source_credential_key, HEADER_SECRET, PASSWORD_SECRET};
+
+/// XMLTV source ID, name, and encrypted password and header value
+type SourceSecrets = (Option<i32>, String, Option<Vec<u8>>, Option<Vec<u8>>);
+
+/// Outcome of `migrate_credentials`
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct CredentialMigration {
+    /// Stored secrets rewritten with the current scheme (or new salt)
+    pub migrated: usize,
+    /// Secrets already current, or kept in the keychain
+    pub unchanged: usize,
+    /// Secrets that could not be decrypted, with the reason
+    pub failed: Vec<String>,
+    /// Whether the salt was replaced
+    pub rotated: bool,
+}
+
+/// Re-encrypt stored credentials with the current scheme
+///
+/// With `rotate`, every fallback blob is also re-encrypted under a new salt.
+/// Rotation is all-or-nothing: it is refused if any secret cannot be read.
+#[tauri::command]
+pub fn migrate_credentials(
+    app: AppHandle,
+    db: State<DbConnection>,
+    rotate: bool,
+) -> Result<CredentialMigration, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let manager = CredentialManager::new(app_data_dir);
+    let migration = migrate_all(&mut conn, &manager, rotate)?;
+
+    let details = serde_json::to_string(&migration).unwrap_or_default();
+    let level = if migration.failed.is_empty() {
+        "info"
+    } else {
+        "warn"
+    };
+    let rotated = if migration.rotated {
+        " with a new key"
+    } else {
+        ""
+    };
+    let _ = log_event_internal(
+        &mut conn,
+        level,
+        "system",
+        &format!(
+            "Configuration changed: {} stored credentials re-encrypted{}",
+            migration.migrated, rotated
+        ),
+        Some(&details),
+    );
+
+    Ok(migration)
+}
+
+/// Rewrite every stored secret that needs it, in one transaction
+fn migrate_all(
+    conn: &mut SqliteConnection,
+    manager: &CredentialManager,
+    rotate: bool,
+) -> Result<CredentialMigration, String> {
+    let new_salt = rotate.then(CredentialManager::generate_salt);
+    let old_salt = rotate
+        .then(|| manager.current_salt())
+        .transpose()
+        .map_err(|e| e.to_string())?;
+    let mut migration = CredentialMigration {
+        rotated: rotate,
+        ..Default::default()
+    };
+    let mut salt_error = None;
+
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        let account_rows: Vec<(Option<i32>, String, Vec<u8>)> = accounts::table
+            .select((accounts::id, accounts::name, accounts::password_encrypted))
+            .load(conn)?;
+        for (id, name, data) in account_rows {
+            let Some(id) = id else { continue };
+            let label = format!("Account {}", name);
+            if let Some(blob) = reencrypt(
+                manager,
+                &id.to_string(),
+                &data,
+                new_salt.as_ref(),
+                &label,
+                &mut migration,
+            ) {
+                diesel::update(accounts::table.filter(accounts::id.eq(id)))
+                    .set(accounts::password_encrypted.eq(blob))
+                    .execute(conn)?;
+            }
+        }
+
+        let source_rows: Vec<SourceSecrets> = xmltv_sources::table
+            .select((
+                xmltv_sources::id,
+                xmltv_sources::name,
+                xmltv_sources::auth_password_encrypted,
+                xmltv_sources::auth_header_value_encrypted,
+            ))
+            .load(conn)?;
+        for (id, name, password, header) in source_rows {
+            let Some(id) = id else { continue };
+            if let Some(data) = password {
+                let key = source_credential_key(id, PASSWORD_SECRET);
+                let label = format!("EPG source {} password", name);
+                if let Some(blob) = reencrypt(
+                    manager,
+                    &key,
+                    &data,
+                    new_salt.as_ref(),
+                    &label,
+                    &mut migration,
+                ) {
+                    diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(id)))
+                        .set(xmltv_sources::auth_password_encrypted.eq(blob))
+                        .execute(conn)?;
+                }
+            }
+            if let Some(data) = header {
+                let key = source_credential_key(id, HEADER_SECRET);
+                let label = format!("EPG source {} header", name);
+                if let Some(blob) = reencrypt(
+                    manager,
+                    &key,
+                    &data,
+                    new_salt.as_ref(),
+                    &label,
+                    &mut migration,
+                ) {
+                    diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(id)))
+                        .set(xmltv_sources::auth_header_value_encrypted.eq(blob))
+                        .execute(conn)?;
+                }
+            }
+        }
+
+        let plex_token: Option<String> = settings::table
+            .filter(settings::key.eq(PLEX_TOKEN_KEY))
+            .select(settings::value)
+            .first(conn)
+            .optional()?;
+        if let Some(data) = plex_token.and_then(|value| STANDARD.decode(value).ok()) {
+            if let Some(blob) = reencrypt(
+                manager,
+                PLEX_TOKEN_CREDENTIAL_ID,
+                &data,
+                new_salt.as_ref(),
+                "Plex token",
+                &mut migration,
+            ) {
+                diesel::replace_into(settings::table)
+                    .values(&Setting::new(PLEX_TOKEN_KEY, STANDARD.encode(blob)))
+                    .execute(conn)?;
+            }
+        }
+
+        // The new salt is saved last: a failure rolls every rewrite back
+        if let Some(salt) = &new_salt {
+            if !migration.failed.is_empty() {
+                salt_error = Some(format!(
+                    "Key rotation cancelled, some credentials cannot be read: {}",
+                    migration.failed.join("; ")
+                ));
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+            if let Err(e) = manager.save_salt(salt) {
+                salt_error = Some(format!("Failed to save the new key salt: {}", e));
+                return Err(diesel::result::Error::RollbackTransaction);
+            }
+        }
+        Ok(())
+    });
+
+    match (result, salt_error) {
+        (Ok(()), _) => Ok(migration),
+        (Err(_), Some(message)) => Err(message),
+        (Err(e), None) => {
+            // The commit failed after the new salt was saved; keep the old one
+            if let Some(salt) = &old_salt {
+                let _ = manager.save_salt(salt);
+            }
+            Err(format!("Database error: {}", e))
+        }
+    }
+}
+
+/// Re-encrypt one secret, counting the outcome
+fn reencrypt(
+    manager: &CredentialManager,
+    credential_id: &str,
+    data: &[u8],
+    new_salt: Option<&[u8; SALT_LENGTH]>,
+    label: &str,
+    migration: &mut CredentialMigration,
+) -> Option<Vec<u8>> {
+    match manager.reencrypt(credential_id, data, new_salt) {
+        Ok(Some(blob)) => {
+            migration.migrated += 1;
+            Some(blob)
+        }
+        Ok(None) => {
+            migration.unchanged += 1;
+            None
+        }
+        Err(e) => {
+            migration.failed.push(format!("{}: {}", label, e));
+            None
+        }
+    }
+}
@@ -0,0 +1,609 @@
+//! Data dump export
+//!
+//! Writes one kind of data (guide channels, provider streams, mappings,
+//! programs or events) to a JSON Lines or CSV file for analysis in
+//! spreadsheets or scripts, without opening the SQLite file directly. Rows are
+//! read in pages ordered by ID and written as they are read, so large program
+//! tables are exported with bounded memory. Nothing in the database is
+//! changed.
+//!
+//! Account credentials and stream URLs (which embed them) are not exported.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+use crate::commands::lineup_report::csv_field;
+use crate::db::schema::{
+    accounts, channel_mappings, event_log, programs, xmltv_channel_settings, xmltv_channels,
+    xmltv_sources, xtream_channels,
+};
+use crate::db::DbConnection;
+
+/// Rows read per query
+const DUMP_PAGE_SIZE: i64 = 5_000;
+
+/// Program times as stored
+const PROGRAM_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Event log timestamps as stored by SQLite's `datetime('now')`
+const EVENT_TIME_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+/// Data to export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDumpKind {
+    /// XMLTV guide channels with their source and lineup settings
+    Channels,
+    /// Xtream provider streams
+    Streams,
+    /// Channel to stream mappings
+    Mappings,
+    /// Guide programs
+    Programs,
+    /// Event log entries
+    Events,
+}
+
+/// File format of the export
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DataDumpFormat {
+    /// One JSON object per line
+    Jsonl,
+    /// RFC 4180 CSV with a header row
+    Csv,
+}
+
+/// Time window for programs (overlapping it) and events (logged in it)
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDumpRange {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+/// Result of an export
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataDumpResult {
+    pub path: String,
+    pub rows: usize,
+    pub bytes: u64,
+}
+
+/// A page of rows: (id, values in column order)
+type DumpPage = Vec<(i32, Vec<Value>)>;
+
+impl DataDumpKind {
+    /// Column names, in the order values are written
+    fn columns(self) -> &'static [&'static str] {
+        match self {
+            DataDumpKind::Channels => &[
+                "id",
+                "source_id",
+                "source_name",
+                "channel_id",
+                "display_name",
+                "icon",
+                "is_synthetic",
+                "is_enabled",
+                "plex_display_order",
+            ],
+            DataDumpKind::Streams => &[
+                "id",
+                "account_id",
+                "account_name",
+                "stream_id",
+                "name",
+                "category_name",
+                "epg_channel_id",
+                "qualities",
+                "tv_archive",
+                "added_at",
+                "updated_at",
+            ],
+            DataDumpKind::Mappings => &[
+                "id",
+                "xmltv_channel_id",
+                "channel_name",
+                "xtream_channel_id",
+                "stream_name",
+                "match_confidence",
+                "is_manual",
+                "is_primary",
+                "stream_priority",
+                "created_at",
+            ],
+            DataDumpKind::Programs => &[
+                "id",
+                "xmltv_channel_id",
+                "channel_id",
+                "title",
+                "sub_title",
+                "description",
+                "start_time",
+                "end_time",
+                "category",
+                "episode_info",
+            ],
+            DataDumpKind::Events => &["id", "timestamp", "level", "category", "message", "details"],
+        }
+    }
+
+    /// Read the rows with an ID above `after_id`
+    fn fetch_page(
+        self,
+        conn: &mut SqliteConnection,
+        range: &DataDumpRange,
+        after_id: i32,
+    ) -> QueryResult<DumpPage> {
+        match self {
+            DataDumpKind::Channels => Ok(xmltv_channels::table
+                .inner_join(
+                    xmltv_sources::table
+                        .on(xmltv_channels::source_id.eq(xmltv_sources::id.assume_not_null())),
+                )
+                .left_join(
+                    xmltv_channel_settings::table.on(xmltv_channel_settings::xmltv_channel_id
+                        .eq(xmltv_channels::id.assume_not_null())),
+                )
+                .filter(xmltv_channels::id.gt(after_id))
+                .order(xmltv_channels::id.asc())
+                .limit(DUMP_PAGE_SIZE)
+                .select((
+                    xmltv_channels::id.assume_not_null(),
+                    xmltv_channels::source_id,
+                    xmltv_sources::name,
+                    xmltv_channels::channel_id,
+                    xmltv_channels::display_name,
+                    xmltv_channels::icon,
+                    xmltv_channels::is_synthetic,
+                    xmltv_channel_settings::is_enabled.nullable(),
+                    xmltv_channel_settings::plex_display_order.nullable(),
+                ))
+                .load::<(
+                    i32,
+                    i32,
+                    String,
+                    String,
+                    String,
+                    Option<String>,
+                    Option<i32>,
+                    Option<i32>,
+                    Option<i32>,
+                )>(conn)?
+                .into_iter()
+                .map(|r| {
+                    (
+                        r.0,
+                        vec![
+                            r.0.into(),
+                            r.1.into(),
+                            r.2.into(),
+                            r.3.into(),
+                            r.4.into(),
+                            r.5.into(),
+                            flag(r.6),
+                            flag(r.7),
+                            r.8.into(),
+                        ],
+                    )
+                })
+                .collect()),
+            DataDumpKind::Streams => Ok(xtream_channels::table
+                .inner_join(
+                    accounts::table
+                        .on(xtream_channels::account_id.eq(accounts::id.assume_not_null())),
+                )
+                .filter(xtream_channels::id.gt(after_id))
+                .order(xtream_channels::id.asc())
+                .limit(DUMP_PAGE_SIZE)
+                .select((
+                    xtream_channels::id.assume_not_null(),
+                    xtream_channels::account_id,
+                    accounts::name,
+                    xtream_channels::stream_id,
+                    xtream_channels::name,
+                    xtream_channels::category_name,
+                    xtream_channels::epg_channel_id,
+                    xtream_channels::qualities,
+                    xtream_channels::tv_archive,
+                    xtream_channels::added_at,
+                    xtream_channels::updated_at,
+                ))
+                .load::<(
+                    i32,
+                    i32,
+                    String,
+                    i32,
+                    String,
+                    Option<String>,
+                    Option<String>,
+                    Option<String>,
+                    Option<i32>,
+                    Option<String>,
+                    Option<String>,
+                )>(conn)?
+                .into_iter()
+                .map(|r| {
+                    (
+                        r.0,
+                        vec![
+                            r.0.into(),
+                            r.1.into(),
+                            r.2.into(),
+                            r.3.into(),
+                            r.4.into(),
+                            r.5.into(),
+                            r.6.into(),
+                            r.7.into(),
+                            flag(r.8),
+                            r.9.into(),
+                            r.10.into(),
+                        ],
+                    )
+                })
+                .collect()),
+            DataDumpKind::Mappings => Ok(channel_mappings::table
+                .inner_join(xmltv_channels::table.on(
+                    channel_mappings::xmltv_channel_id.eq(xmltv_channels::id.assume_not_null()),
+                ))
+                .inner_join(xtream_channels::table.on(
+                    channel_mappings::xtream_channel_id.eq(xtream_channels::id.assume_not_null()),
+                ))
+                .filter(channel_mappings::id.gt(after_id))
+                .order(channel_mappings::id.asc())
+                .limit(DUMP_PAGE_SIZE)
+                .select((
+                    channel_mappings::id.assume_not_null(),
+                    channel_mappings::xmltv_channel_id,
+                    xmltv_channels::display_name,
+                    channel_mappings::xtream_channel_id,
+                    xtream_channels::name,
+                    channel_mappings::match_confidence,
+                    channel_mappings::is_manual,
+                    channel_mappings::is_primary,
+                    channel_mappings::stream_priority,
+                    channel_mappings::created_at,
+                ))
+                .load::<(
+                    i32,
+                    i32,
+                    String,
+                    i32,
+                    String,
+                    Option<f32>,
+                    Option<i32>,
+                    Option<i32>,
+                    Option<i32>,
+                    String,
+                )>(conn)?
+                .into_iter()
+                .map(|r| {
+                    (
+                        r.0,
+                        vec![
+                            r.0.into(),
+                            r.1.into(),
+                            r.2.into(),
+                            r.3.into(),
+                            r.4.into(),
+                            // Rounded so f32 noise does not show up as 0.8500000238
+                            r.5.map(|c| (f64::from(c) * 10_000.0).round() / 10_000.0)
+                                .into(),
+                            flag(r.6),
+                            flag(r.7),
+                            r.8.into(),
+                            r.9.into(),
+                        ],
+                    )
+                })
+                .collect()),
+            DataDumpKind::Programs => {
+                let mut query =
+                    programs::table
+                        .inner_join(xmltv_channels::table.on(
+                            programs::xmltv_channel_id.eq(xmltv_channels::id.assume_not_null()),
+                        ))
+                        .filter(programs::id.gt(after_id))
+                        .into_boxed();
+                if let Some(from) = range.from {
+                    query = query.filter(
+                        programs::end_time.gt(from.format(PROGRAM_TIME_FORMAT).to_string()),
+                    );
+                }
+                if let Some(to) = range.to {
+                    query = query.filter(
+                        programs::start_time.lt(to.format(PROGRAM_TIME_FORMAT).to_string()),
+                    );
+                }
+                Ok(query
+                    .order(programs::id.asc())
+                    .limit(DUMP_PAGE_SIZE)
+                    .select((
+                        programs::id.assume_not_null(),
+                        programs::xmltv_channel_id,
+                        xmltv_channels::channel_id,
+                        programs::title,
+                        programs::sub_title,
+                        programs::description,
+                        programs::start_time,
+                        programs::end_time,
+                        programs::category,
+                        programs::episode_info,
+                    ))
+                    .load::<(
+                        i32,
+                        i32,
+                        String,
+                        String,
+                        Option<String>,
+                        Option<String>,
+                        String,
+                        String,
+                        Option<String>,
+                        Option<String>,
+                    )>(conn)?
+                    .into_iter()
+                    .map(|r| {
+                        (
+                            r.0,
+                            vec![
+                                r.0.into(),
+                                r.1.into(),
+                                r.2.into(),
+                                r.3.into(),
+                                r.4.into(),
+                                r.5.into(),
+                                r.6.into(),
+                                r.7.into(),
+                                r.8.into(),
+                                r.9.into(),
+                            ],
+                        )
+                    })
+                    .collect())
+            }
+            DataDumpKind::Events => {
+                let mut query = event_log::table
+                    .filter(event_log::id.gt(after_id))
+                    .into_boxed();
+                if let Some(from) = range.from {
+                    query = query.filter(
+                        event_log::timestamp.ge(from.format(EVENT_TIME_FORMAT).to_string()),
+                    );
+                }
+                if let Some(to) = range.to {
+                    query = query
+                        .filter(event_log::timestamp.lt(to.format(EVENT_TIME_FORMAT).to_string()));
+                }
+                Ok(query
+                    .order(event_log::id.asc())
+                    .limit(DUMP_PAGE_SIZE)
+                    .select((
+                        event_log::id.assume_not_null(),
+                        event_log::timestamp,
+                        event_log::level,
+                        event_log::category,
+                        event_log::message,
+                        event_log::details,
+                    ))
+                    .load::<(i32, String, String, String, String, Option<String>)>(conn)?
+                    .into_iter()
+                    .map(|r| {
+                        (
+                            r.0,
+                            vec![
+                                r.0.into(),
+                                r.1.into(),
+                                r.2.into(),
+                                r.3.into(),
+                                r.4.into(),
+                                r.5.into(),
+                            ],
+                        )
+                    })
+                    .collect())
+            }
+        }
+    }
+}
+
+/// 0/1 integer flag as a JSON boolean
+fn flag(value: Option<i32>) -> Value {
+    value.map(|v| v != 0).into()
+}
+
+/// A value as a CSV field (null as empty)
+fn csv_value(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(s) => csv_field(s),
+        other => csv_field(&other.to_string()),
+    }
+}
+
+/// Write one line of the export
+fn write_row(
+    out: &mut impl Write,
+    format: DataDumpFormat,
+    columns: &[&str],
+    values: &[Value],
+) -> std::io::Result<()> {
+    match format {
+        DataDumpFormat::Csv => {
+            let fields: Vec<String> = values.iter().map(csv_value).collect();
+            write!(out, "{}\r\n", fields.join(","))
+        }
+        DataDumpFormat::Jsonl => {
+            // Written by hand to keep the keys in column order
+            let fields: Vec<String> = columns
+                .iter()
+                .zip(values)
+                .map(|(column, value)| format!("{}:{}", Value::from(*column), value))
+                .collect();
+            writeln!(out, "{{{}}}", fields.join(","))
+        }
+    }
+}
+
+/// Write all rows of `kind` to `out`, returning the number of rows
+pub fn write_data_dump(
+    conn: &mut SqliteConnection,
+    kind: DataDumpKind,
+    format: DataDumpFormat,
+    range: &DataDumpRange,
+    out: &mut impl Write,
+) -> Result<usize, String> {
+    let columns = kind.columns();
+    let write_error = |e: std::io::Error| format!("Failed to write export: {}", e);
+
+    if format == DataDumpFormat::Csv {
+        write!(out, "{}\r\n", columns.join(",")).map_err(write_error)?;
+    }
+
+    let mut rows = 0;
+    let mut after_id = 0;
+    loop {
+        let page = kind
+            .fetch_page(conn, range, after_id)
+            .map_err(|e| format!("Failed to read data: {}", e))?;
+        let Some((last_id, _)) = page.last() else {
+            break;
+        };
+        after_id = *last_id;
+        for (_, values) in &page {
+            write_row(out, format, columns, values).map_err(write_error)?;
+        }
+        rows += page.len();
+        if (page.len() as i64) < DUMP_PAGE_SIZE {
+            break;
+        }
+    }
+    out.flush().map_err(write_error)?;
+
+    Ok(rows)
+}
+
+/// Export one kind of data to a JSON Lines or CSV file.
+///
+/// `path` is the file chosen by the user; it is written under a temporary
+/// name first so a failed export never leaves a truncated file behind.
+/// `range` limits programs (to those overlapping it) and events.
+#[tauri::command]
+pub async fn export_data_dump(
+    db: State<'_, DbConnection>,
+    kind: DataDumpKind,
+    format: DataDumpFormat,
+    path: String,
+    range: Option<DataDumpRange>,
+) -> Result<DataDumpResult, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() || path.file_name().is_none() {
+        return Err("Export path must be an absolute file path".to_string());
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err("Export folder does not exist".to_string());
+    }
+    let range = range.unwrap_or_default();
+    if let (Some(from), Some(to)) = (range.from, range.to) {
+        if from >= to {
+            return Err("The start of the range must be before its end".to_string());
+        }
+    }
+
+    let pool = db.clone_read_pool();
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut conn = pool
+            .get()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+
+        let mut part_name = path.file_name().unwrap_or_default().to_os_string();
+        part_name.push(".part");
+        let part_path = path.with_file_name(part_name);
+        let file = File::create(&part_path).map_err(|e| format!("Failed to create file: {}", e))?;
+
+        let mut out = BufWriter::new(file);
+        let result = write_data_dump(&mut conn, kind, format, &range, &mut out);
+        drop(out);
+        let rows = match result {
+            Ok(rows) => rows,
+            Err(e) => {
+                let _ = fs::remove_file(&part_path);
+                return Err(e);
+            }
+        };
+        fs::rename(&part_path, &path).map_err(|e| {
+            let _ = fs::remove_file(&part_path);
+            format!("Failed to save export: {}", e)
+        })?;
+
+        Ok(DataDumpResult {
+            bytes: fs::metadata(&path).map(|m| m.len()).unwrap_or(0),
+            path: path.to_string_lossy().into_owned(),
+            rows,
+        })
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dump_programs_in_range() {
+        let mut conn = crate::db::test_connection();
+        for sql in [
+            "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://example.com/epg.xml')",
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (1, 1, 'news.uk', 'News')",
+            "INSERT INTO programs (xmltv_channel_id, title, description, start_time, end_time) VALUES
+                (1, 'Early', NULL, '2026-02-10T04:00:00Z', '2026-02-10T05:00:00Z'),
+                (1, 'Headlines, \"live\"', 'Line one', '2026-02-10T06:00:00Z', '2026-02-10T07:00:00Z')",
+        ] {
+            diesel::sql_query(sql).execute(&mut conn).unwrap();
+        }
+        let range = DataDumpRange {
+            from: Some("2026-02-10T05:30:00Z".parse().unwrap()),
+            to: None,
+        };
+
+        let mut csv = Vec::new();
+        let rows = write_data_dump(
+            &mut conn,
+            DataDumpKind::Programs,
+            DataDumpFormat::Csv,
+            &range,
+            &mut csv,
+        )
+        .unwrap();
+        assert_eq!(rows, 1);
+        let csv = String::from_utf8(csv).unwrap();
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert!(lines[0].starts_with("id,xmltv_channel_id,channel_id,title"));
+        assert!(lines[1].contains(",news.uk,\"Headlines, \"\"live\"\"\",,Line one,"));
+
+        let mut jsonl = Vec::new();
+        write_data_dump(
+            &mut conn,
+            DataDumpKind::Programs,
+            DataDumpFormat::Jsonl,
+            &DataDumpRange::default(),
+            &mut jsonl,
+        )
+        .unwrap();
+        let jsonl = String::from_utf8(jsonl).unwrap();
+        let first: Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(first["title"], "Early");
+        assert!(first["description"].is_null());
+        assert!(jsonl.lines().next().unwrap().starts_with("{\"id\":"));
+        assert_eq!(jsonl.lines().count(), 2);
+    }
+}
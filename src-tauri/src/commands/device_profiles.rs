@@ -0,0 +1,157 @@
+//! Device profile Tauri commands
+//!
+//! Manages the extra HDHomeRun tuners in `crate::server::device_profiles`.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::server::device_profiles::{self, DeviceProfile};
+
+/// List the device profiles with their channels
+#[tauri::command]
+pub fn list_device_profiles(db: State<DbConnection>) -> Result<Vec<DeviceProfile>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    device_profiles::list_profiles(&mut conn)
+        .map_err(|e| format!("Failed to load device profiles: {}", e))
+}
+
+/// Create a device profile (the slug defaults to one made from the name)
+#[tauri::command]
+pub fn create_device_profile(
+    db: State<DbConnection>,
+    name: String,
+    slug: Option<String>,
+    tuner_count: Option<i32>,
+    stream_format: Option<String>,
+) -> Result<DeviceProfile, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let profile = device_profiles::create_profile(
+        &mut conn,
+        &name,
+        slug.as_deref(),
+        tuner_count,
+        stream_format.as_deref(),
+    )?;
+
+    let details = serde_json::json!({
+        "slug": profile.slug,
+        "deviceId": profile.device_id,
+        "tunerCount": profile.tuner_count,
+        "streamFormat": profile.stream_format,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: device profile '{}' created",
+            profile.name
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(profile)
+}
+
+/// Rename a device profile or change its tuner count or stream format
+#[tauri::command]
+pub fn update_device_profile(
+    db: State<DbConnection>,
+    id: i32,
+    name: String,
+    tuner_count: Option<i32>,
+    stream_format: Option<String>,
+) -> Result<DeviceProfile, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let profile = device_profiles::update_profile(
+        &mut conn,
+        id,
+        &name,
+        tuner_count,
+        stream_format.as_deref(),
+    )?;
+
+    let details = serde_json::json!({
+        "slug": profile.slug,
+        "tunerCount": profile.tuner_count,
+        "streamFormat": profile.stream_format,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: device profile '{}' updated",
+            profile.name
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(profile)
+}
+
+/// Delete a device profile
+#[tauri::command]
+pub fn delete_device_profile(db: State<DbConnection>, id: i32) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let deleted = device_profiles::delete_profile(&mut conn, id)
+        .map_err(|e| format!("Failed to delete device profile: {}", e))?;
+    if !deleted {
+        return Err("Profile not found".to_string());
+    }
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: device profile {} deleted", id),
+        None,
+    );
+
+    Ok(())
+}
+
+/// Replace the channels of a device profile
+#[tauri::command]
+pub fn set_device_profile_channels(
+    db: State<DbConnection>,
+    id: i32,
+    channel_ids: Vec<i32>,
+) -> Result<DeviceProfile, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let profile = device_profiles::set_profile_channels(&mut conn, id, &channel_ids)?;
+
+    let details = serde_json::json!({
+        "slug": profile.slug,
+        "channelIds": profile.channel_ids,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: device profile '{}' has {} channels",
+            profile.name,
+            profile.channel_ids.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(profile)
+}
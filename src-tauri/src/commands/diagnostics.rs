@@ -0,0 +1,423 @@
+//! Diagnostics Tauri commands
+//!
+//! Reports where the app's memory and disk go, so users on small devices
+//! (e.g. Raspberry Pi) can see what is growing, and toggles low resource mode.
+//! Also shows the failover chain the stream proxy uses for a channel, and
+//! the progress of the background startup phases.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{accounts, channel_mappings, stream_health, xtream_channels};
+use crate::db::workspace::WorkspaceRegistry;
+use crate::db::DbConnection;
+use crate::low_resource;
+use crate::server::failover::plan_channel_streams;
+use crate::server::logos::logo_cache_dir;
+use crate::server::stream::{select_quality, StreamManager};
+use crate::server::AppState;
+
+/// Resource usage snapshot
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    /// Resident memory of the app process (None where unsupported)
+    pub process_rss_bytes: Option<u64>,
+    /// Size of the cached `/epg.xml` document
+    pub epg_cache_bytes: u64,
+    /// Size of the active database, including WAL and shared-memory files
+    pub database_bytes: u64,
+    /// Size of the logo cache on disk
+    pub logo_cache_bytes: u64,
+    /// Number of files in the logo cache
+    pub logo_cache_files: u64,
+    /// Number of active stream sessions
+    pub active_streams: u32,
+    /// Bytes queued across all active stream buffers
+    pub stream_buffer_bytes: u64,
+}
+
+/// Get process memory, cache, database, and stream buffer usage
+#[tauri::command]
+pub fn get_resource_usage(
+    app: AppHandle,
+    server_state: State<AppState>,
+) -> Result<ResourceUsage, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+
+    let (logo_cache_bytes, logo_cache_files) = directory_size(&logo_cache_dir(&app_data_dir));
+    let stream_manager = server_state.stream_manager();
+
+    Ok(ResourceUsage {
+        process_rss_bytes: process_rss_bytes(),
+        epg_cache_bytes: server_state.epg_cache_size() as u64,
+        database_bytes: database_size(&app_data_dir),
+        logo_cache_bytes,
+        logo_cache_files,
+        active_streams: stream_manager.active_count() as u32,
+        stream_buffer_bytes: stream_manager.total_buffered_bytes() as u64,
+    })
+}
+
+/// Get the state of the background startup phases (database, HTTP server, scheduler)
+///
+/// Never waits for the database, so the UI can poll it while migrations run.
+#[tauri::command]
+pub fn get_startup_status() -> crate::startup::StartupStatus {
+    crate::startup::status()
+}
+
+/// Get whether low resource mode is enabled
+#[tauri::command]
+pub fn get_low_resource_mode() -> bool {
+    low_resource::is_enabled()
+}
+
+/// Enable or disable low resource mode
+///
+/// Applies immediately: smaller EPG insert batches, one EPG source refresh at
+/// a time, a shorter EPG cache TTL, and sequential logo pre-warm downloads.
+#[tauri::command]
+pub fn set_low_resource_mode(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    enabled: bool,
+) -> Result<bool, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    low_resource::set_enabled(&mut conn, enabled)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+
+    // Free the cached EPG document now instead of holding it until the next request
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "setting": low_resource::LOW_RESOURCE_MODE_KEY,
+        "enabled": enabled
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Low resource mode {}",
+            if enabled { "enabled" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(enabled)
+}
+
+/// Recent connection health of a stream, from the proxy's connection attempts
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamHealthStatus {
+    /// Last attempt succeeded
+    Healthy,
+    /// Failing since the last success
+    Failing,
+    /// Never tried by the proxy
+    Unknown,
+}
+
+/// One stream in a channel's failover chain
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverChainEntry {
+    /// Order in which the proxy tries the stream (1 = first)
+    pub position: usize,
+    pub xtream_channel_id: i32,
+    pub stream_id: i32,
+    pub stream_name: String,
+    pub account_id: i32,
+    pub account_name: String,
+    /// Quality the proxy selects for this stream
+    pub quality: String,
+    pub stream_priority: i32,
+    /// True when the stream is not mapped itself but is the same stream on a
+    /// sibling account of the same provider
+    pub sibling_account: bool,
+    /// The account is at its connection limit, so a tune request skips the
+    /// stream unless a lower-priority session can be preempted
+    pub account_full: bool,
+    /// The account exceeded its monthly quota, so new streams skip it
+    pub quota_blocked: bool,
+    pub health: StreamHealthStatus,
+    pub last_success_at: Option<String>,
+    pub last_failure_at: Option<String>,
+    pub failing_since: Option<String>,
+}
+
+/// Ordered streams the stream proxy tries for a channel
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct FailoverChain {
+    pub channel_id: i32,
+    /// Channels not enabled in the published lineup are refused by the proxy
+    /// regardless of the chain
+    pub enabled: bool,
+    pub streams: Vec<FailoverChainEntry>,
+}
+
+type HealthRow = (i32, Option<String>, Option<String>, Option<String>);
+
+/// Build the failover chain for a channel
+///
+/// Uses the proxy's stream plan (`plan_channel_streams`), so the order
+/// matches what a tune request would try.
+pub fn build_failover_chain(
+    conn: &mut SqliteConnection,
+    stream_manager: &StreamManager,
+    channel_id: i32,
+) -> Result<FailoverChain, String> {
+    let plan = plan_channel_streams(conn, stream_manager, channel_id, None, None, false)
+        .map_err(|e| e.to_string())?;
+    let xtream_ids: Vec<i32> = plan
+        .streams
+        .iter()
+        .map(|p| p.stream.xtream_channel_id)
+        .collect();
+    let account_ids: Vec<i32> = plan.streams.iter().map(|p| p.stream.account_id).collect();
+
+    let mapped: HashSet<i32> = channel_mappings::table
+        .filter(channel_mappings::xmltv_channel_id.eq(channel_id))
+        .select(channel_mappings::xtream_channel_id)
+        .load::<i32>(conn)
+        .map_err(|e| format!("Failed to load mappings: {}", e))?
+        .into_iter()
+        .collect();
+    let names: HashMap<i32, String> = xtream_channels::table
+        .filter(xtream_channels::id.eq_any(&xtream_ids))
+        .select((xtream_channels::id.assume_not_null(), xtream_channels::name))
+        .load::<(i32, String)>(conn)
+        .map_err(|e| format!("Failed to load streams: {}", e))?
+        .into_iter()
+        .collect();
+    let account_names: HashMap<i32, String> = accounts::table
+        .filter(accounts::id.eq_any(&account_ids))
+        .select((accounts::id.assume_not_null(), accounts::name))
+        .load::<(i32, String)>(conn)
+        .map_err(|e| format!("Failed to load accounts: {}", e))?
+        .into_iter()
+        .collect();
+    let health: HashMap<i32, HealthRow> = stream_health::table
+        .filter(stream_health::xtream_channel_id.eq_any(&xtream_ids))
+        .load::<HealthRow>(conn)
+        .map_err(|e| format!("Failed to load stream health: {}", e))?
+        .into_iter()
+        .map(|row| (row.0, row))
+        .collect();
+
+    let streams = plan
+        .streams
+        .into_iter()
+        .enumerate()
+        .map(|(index, planned)| {
+            let stream = planned.stream;
+            let (last_success_at, last_failure_at, failing_since) = health
+                .get(&stream.xtream_channel_id)
+                .map(|(_, success, failure, since)| {
+                    (success.clone(), failure.clone(), since.clone())
+                })
+                .unwrap_or_default();
+            let health = if failing_since.is_some() {
+                StreamHealthStatus::Failing
+            } else if last_success_at.is_some() {
+                StreamHealthStatus::Healthy
+            } else {
+                StreamHealthStatus::Unknown
+            };
+
+            FailoverChainEntry {
+                position: index + 1,
+                xtream_channel_id: stream.xtream_channel_id,
+                stream_id: stream.stream_id,
+                stream_name: names
+                    .get(&stream.xtream_channel_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                account_id: stream.account_id,
+                account_name: account_names
+                    .get(&stream.account_id)
+                    .cloned()
+                    .unwrap_or_default(),
+                quality: select_quality(&stream.qualities, plan.max_quality.as_deref()),
+                stream_priority: stream.stream_priority,
+                sibling_account: !mapped.contains(&stream.xtream_channel_id),
+                account_full: !planned.has_capacity,
+                quota_blocked: planned.quota_blocked,
+                health,
+                last_success_at,
+                last_failure_at,
+                failing_since,
+            }
+        })
+        .collect();
+
+    Ok(FailoverChain {
+        channel_id,
+        enabled: plan.published,
+        streams,
+    })
+}
+
+/// Get the ordered streams the proxy will try for a channel
+///
+/// Lists each stream with its account, selected quality, priority, and
+/// recent health, so failover behavior can be checked before relying on it.
+#[tauri::command]
+pub fn get_channel_failover_chain(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    channel_id: i32,
+) -> Result<FailoverChain, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    build_failover_chain(&mut conn, server_state.stream_manager(), channel_id)
+}
+
+/// Size of the active workspace database and its `-wal`/`-shm` companions
+fn database_size(app_data_dir: &Path) -> u64 {
+    let db_path = WorkspaceRegistry::load(app_data_dir).active_path(app_data_dir);
+    let db_path = db_path.to_string_lossy();
+
+    ["", "-wal", "-shm"]
+        .iter()
+        .filter_map(|suffix| std::fs::metadata(format!("{}{}", db_path, suffix)).ok())
+        .map(|meta| meta.len())
+        .sum()
+}
+
+/// Total size and file count of a directory (non-recursive; missing = empty)
+fn directory_size(dir: &Path) -> (u64, u64) {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return (0, 0);
+    };
+
+    entries
+        .filter_map(|entry| entry.ok()?.metadata().ok())
+        .filter(|meta| meta.is_file())
+        .fold((0, 0), |(bytes, files), meta| (bytes + meta.len(), files + 1))
+}
+
+/// Resident set size of the current process
+#[cfg(target_os = "linux")]
+fn process_rss_bytes() -> Option<u64> {
+    std::fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| parse_vm_rss(&status))
+}
+
+#[cfg(not(target_os = "linux"))]
+fn process_rss_bytes() -> Option<u64> {
+    None
+}
+
+/// Parse the `VmRSS` line of `/proc/self/status` (reported in kB)
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn parse_vm_rss(status: &str) -> Option<u64> {
+    status
+        .lines()
+        .find_map(|line| line.strip_prefix("VmRSS:"))
+        .and_then(|value| value.split_whitespace().next())
+        .and_then(|kb| kb.parse::<u64>().ok())
+        .map(|kb| kb * 1024)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_vm_rss() {
+        let status = "Name:\tstreamforge\nVmPeak:\t  204800 kB\nVmRSS:\t   51200 kB\nThreads:\t12\n";
+        assert_eq!(parse_vm_rss(status), Some(51200 * 1024));
+        assert_eq!(parse_vm_rss("Name:\tstreamforge\n"), None);
+    }
+
+    #[test]
+    fn test_directory_size() {
+        let dir = std::env::temp_dir().join(format!("streamforge-diag-{}", uuid::Uuid::new_v4()));
+        assert_eq!(directory_size(&dir), (0, 0));
+
+        std::fs::create_dir_all(&dir).unwrap();
+        std::fs::write(dir.join("a"), [0u8; 10]).unwrap();
+        std::fs::write(dir.join("b"), [0u8; 5]).unwrap();
+        assert_eq!(directory_size(&dir), (15, 2));
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_build_failover_chain() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Line A", "http://provider.local");
+        crate::db::seed_account(&mut conn, 2, "Line B", "http://provider.local");
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name, qualities) VALUES
+                (10, 1, 100, 'ESPN HD', '[\"FHD\",\"HD\"]'),
+                (11, 1, 101, 'ESPN Backup', NULL),
+                (20, 2, 100, 'ESPN HD', NULL)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, is_primary, stream_priority) VALUES
+                (5, 11, 0, 1), (5, 10, 1, 0)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, preferred_quality)
+                VALUES (5, 1, 'SD')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO stream_health (xtream_channel_id, last_success_at, last_failure_at, failing_since) VALUES
+                (10, '2026-01-01T00:00:00Z', NULL, NULL),
+                (11, NULL, '2026-01-02T00:00:00Z', '2026-01-02T00:00:00Z')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let stream_manager = StreamManager::new(2);
+        let chain = build_failover_chain(&mut conn, &stream_manager, 5).unwrap();
+        assert!(chain.enabled);
+        let order: Vec<(i32, bool, StreamHealthStatus)> = chain
+            .streams
+            .iter()
+            .map(|s| (s.xtream_channel_id, s.sibling_account, s.health))
+            .collect();
+        assert_eq!(
+            order,
+            // Like the proxy, streams above the SD cap go last
+            vec![
+                (20, true, StreamHealthStatus::Unknown),
+                (11, false, StreamHealthStatus::Failing),
+                (10, false, StreamHealthStatus::Healthy),
+            ]
+        );
+        assert_eq!(chain.streams[0].account_name, "Line B");
+        assert!(!chain.streams[0].account_full);
+        assert_eq!(chain.streams[2].quality, "HD");
+        assert_eq!(chain.streams[2].position, 3);
+
+        // Unmapped channel: empty chain, not enabled
+        let chain = build_failover_chain(&mut conn, &stream_manager, 99).unwrap();
+        assert!(!chain.enabled);
+        assert!(chain.streams.is_empty());
+    }
+}
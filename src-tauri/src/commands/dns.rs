@@ -0,0 +1,131 @@
+//! DNS resolution Tauri commands
+//!
+//! Configures the resolution strategy in `crate::dns` and tests it against
+//! the system resolver.
+
+use serde::Serialize;
+use std::time::Instant;
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::accounts;
+use crate::db::DbConnection;
+use crate::dns::{self, DnsConfig, DnsStrategy, DNS_CONFIG_KEY};
+use diesel::prelude::*;
+
+/// Result of resolving a hostname with a strategy and with the system
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DnsTestResult {
+    pub hostname: String,
+    pub addresses: Vec<String>,
+    pub error: Option<String>,
+    pub elapsed_ms: u64,
+    /// System resolver answer, to spot blocked or poisoned names
+    pub system_addresses: Vec<String>,
+    pub system_error: Option<String>,
+}
+
+/// Get the DNS resolution settings
+#[tauri::command]
+pub fn get_dns_config(db: State<DbConnection>) -> Result<DnsConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(dns::load_config(&mut conn))
+}
+
+/// Save and apply the DNS resolution settings
+///
+/// Takes effect for new requests; running streams keep their connection.
+#[tauri::command]
+pub fn set_dns_config(db: State<DbConnection>, config: DnsConfig) -> Result<DnsConfig, String> {
+    config.validate()?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let account_ids: Vec<Option<i32>> = accounts::table
+        .select(accounts::id)
+        .load(&mut conn)
+        .map_err(|e| format!("Failed to load accounts: {}", e))?;
+    if let Some(unknown) = config
+        .account_overrides
+        .keys()
+        .find(|id| !account_ids.contains(&Some(**id)))
+    {
+        return Err(format!("Account {} not found", unknown));
+    }
+
+    dns::save_config(&mut conn, &config)?;
+    dns::load_from_db(&mut conn);
+
+    let details = serde_json::json!({
+        "setting": DNS_CONFIG_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: DNS resolution set to {:?} ({} account overrides)",
+            config.global.mode,
+            config.account_overrides.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
+
+/// Resolve a hostname (or the host of a URL)
+///
+/// Uses `strategy` when given, so settings can be tried before saving, and
+/// otherwise the strategy currently applied to the host.
+#[tauri::command]
+pub async fn test_dns_resolution(
+    hostname: String,
+    strategy: Option<DnsStrategy>,
+) -> Result<DnsTestResult, String> {
+    let hostname = if hostname.contains("://") {
+        dns::url_host(&hostname).ok_or_else(|| "Invalid URL".to_string())?
+    } else {
+        hostname.trim().to_ascii_lowercase()
+    };
+    if hostname.is_empty() {
+        return Err("Hostname is required".to_string());
+    }
+    if let Some(strategy) = &strategy {
+        strategy.validate()?;
+    }
+
+    let started = Instant::now();
+    let result = match &strategy {
+        Some(strategy) => dns::resolve_with(strategy, &hostname).await,
+        None => dns::resolve_host(&hostname).await,
+    };
+    let elapsed_ms = started.elapsed().as_millis() as u64;
+    let system = dns::resolve_with(&DnsStrategy::default(), &hostname).await;
+
+    let to_strings = |ips: Vec<std::net::IpAddr>| ips.iter().map(|ip| ip.to_string()).collect();
+    let (addresses, error) = match result {
+        Ok(ips) => (to_strings(ips), None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+    let (system_addresses, system_error) = match system {
+        Ok(ips) => (to_strings(ips), None),
+        Err(e) => (Vec::new(), Some(e)),
+    };
+
+    Ok(DnsTestResult {
+        hostname,
+        addresses,
+        error,
+        elapsed_ms,
+        system_addresses,
+        system_error,
+    })
+}
@@ -6,20 +6,30 @@
 //! Story 6-3: EPG event logging for refresh success/failure
 
 use std::collections::HashMap;
+use std::sync::Arc;
 
 use diesel::prelude::*;
-use serde::Serialize;
-use tauri::State;
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Emitter, Manager, State};
 use thiserror::Error;
 
 use crate::commands::logs::log_event_internal;
+use crate::credentials::CredentialManager;
+use crate::hooks::{self, HookEvent};
+use crate::plex;
 use crate::db::{
-    schema::{channel_mappings, programs, xmltv_channel_settings, xmltv_channels, xmltv_sources},
-    ChannelMapping, DbConnection, NewChannelMapping, NewProgram, NewXmltvChannel,
-    NewXmltvChannelSettings, NewXmltvSource, Program, XmltvChannel, XmltvChannelSettings,
-    XmltvSource, XmltvSourceUpdate,
+    schema::{
+        channel_mappings, channel_policy_actions, device_profile_channels, failover_history,
+        lineup_published, programs, xmltv_channel_settings, xmltv_channels, xmltv_sources,
+    },
+    ChannelMapping, DbConnection, NewChannelMapping, NewXmltvSource, Program, XmltvChannel,
+    XmltvChannelSettings, XmltvSource, XmltvSourceUpdate, SYNTHETIC_SOURCE_ID,
 };
-use crate::xmltv::{fetch_xmltv, parse_xmltv_data, XmltvError};
+use crate::server::priming;
+use crate::server::AppState;
+use crate::xmltv::fetcher::{source_credential_key, HEADER_SECRET, PASSWORD_SECRET};
+use crate::xmltv::history::GuideHistoryEntry;
+use crate::xmltv::{channel_feed, refresh, XmltvError};
 
 /// Error types for EPG source operations
 #[derive(Debug, Error)]
@@ -33,7 +43,7 @@ pub enum EpgSourceError {
     #[error("Invalid URL format")]
     InvalidUrl,
 
-    #[error("URL must use http or https")]
+    #[error("URL must use http, https or file")]
     InvalidUrlScheme,
 
     #[error("Invalid format. Must be one of: xml, xml_gz, auto")]
@@ -89,8 +99,9 @@ impl From<EpgSourceError> for String {
 
 /// Preserved data from XMLTV channels before refresh
 ///
-/// Stores mappings and settings by channel_id (string) so they can be restored
-/// after channels are recreated with new database IDs.
+/// Stores mappings, settings, channel policy actions, failover history, the
+/// published lineup and device profile lineups by channel_id (string) so they
+/// can be restored after channels are recreated with new database IDs.
 ///
 /// # Usage
 /// This struct is used internally by `preserve_channel_data` and `restore_channel_data`.
@@ -103,8 +114,16 @@ impl From<EpgSourceError> for String {
 pub(crate) struct PreservedChannelData {
     /// Manual mappings: (channel_id, xtream_channel_id, is_primary, stream_priority)
     pub manual_mappings: Vec<(String, i32, i32, i32)>,
-    /// Channel settings: (channel_id, is_enabled, plex_display_order)
-    pub settings: Vec<(String, i32, Option<i32>)>,
+    /// Channel settings: (channel_id, settings row)
+    pub settings: Vec<(String, XmltvChannelSettings)>,
+    /// Channel policy actions: (channel_id, action, applied_at)
+    pub policy_actions: Vec<(String, String, String)>,
+    /// Failover history, oldest first: (channel_id, from, to, occurred_at)
+    pub failovers: Vec<(String, i32, i32, String)>,
+    /// Published lineup: (channel_id, is_enabled, plex_display_order, channel_number)
+    pub published: Vec<(String, i32, Option<i32>, Option<i32>)>,
+    /// Device profile lineups: (channel_id, profile_id)
+    pub profile_channels: Vec<(String, i32)>,
 }
 
 /// Save manual mappings and channel settings before deleting XMLTV channels
@@ -145,22 +164,89 @@ pub(crate) fn preserve_channel_data(
     // Save channel settings with their channel_id
     let all_settings: Vec<XmltvChannelSettings> = xmltv_channel_settings::table.load(conn)?;
 
-    let settings: Vec<(String, i32, Option<i32>)> = all_settings
+    let settings: Vec<(String, XmltvChannelSettings)> = all_settings
         .into_iter()
         .filter_map(|s| {
-            old_id_to_channel_id.get(&s.xmltv_channel_id).map(|channel_id| {
-                (
-                    channel_id.clone(),
-                    s.is_enabled.unwrap_or(0),
-                    s.plex_display_order,
-                )
-            })
+            old_id_to_channel_id
+                .get(&s.xmltv_channel_id)
+                .map(|channel_id| (channel_id.clone(), s))
+        })
+        .collect();
+
+    // Save what the channel policy last did to each channel, so it can still
+    // undo its automatic disables
+    let policy_actions: Vec<(String, String, String)> = channel_policy_actions::table
+        .select((
+            channel_policy_actions::xmltv_channel_id,
+            channel_policy_actions::action,
+            channel_policy_actions::applied_at,
+        ))
+        .load::<(i32, String, String)>(conn)?
+        .into_iter()
+        .filter_map(|(xmltv_channel_id, action, applied_at)| {
+            old_id_to_channel_id
+                .get(&xmltv_channel_id)
+                .map(|channel_id| (channel_id.clone(), action, applied_at))
+        })
+        .collect();
+
+    // Save the failover history, which automatic primary demotion counts
+    let failovers: Vec<(String, i32, i32, String)> = failover_history::table
+        .order(failover_history::id.asc())
+        .select((
+            failover_history::xmltv_channel_id,
+            failover_history::from_xtream_channel_id,
+            failover_history::to_xtream_channel_id,
+            failover_history::occurred_at,
+        ))
+        .load::<(i32, i32, i32, String)>(conn)?
+        .into_iter()
+        .filter_map(|(xmltv_channel_id, from, to, occurred_at)| {
+            old_id_to_channel_id
+                .get(&xmltv_channel_id)
+                .map(|channel_id| (channel_id.clone(), from, to, occurred_at))
+        })
+        .collect();
+
+    // Save the published lineup (lineup staging) with its channel_id
+    let published: Vec<(String, i32, Option<i32>, Option<i32>)> = lineup_published::table
+        .select((
+            lineup_published::xmltv_channel_id,
+            lineup_published::is_enabled,
+            lineup_published::plex_display_order,
+            lineup_published::channel_number,
+        ))
+        .load::<(i32, i32, Option<i32>, Option<i32>)>(conn)?
+        .into_iter()
+        .filter_map(|(xmltv_channel_id, is_enabled, order, number)| {
+            old_id_to_channel_id
+                .get(&xmltv_channel_id)
+                .map(|channel_id| (channel_id.clone(), is_enabled, order, number))
+        })
+        .collect();
+
+    // Save device profile lineups with their channel_id
+    let profile_channels: Vec<(String, i32)> = device_profile_channels::table
+        .select((
+            device_profile_channels::xmltv_channel_id,
+            device_profile_channels::profile_id,
+        ))
+        .load::<(i32, i32)>(conn)?
+        .into_iter()
+        .filter_map(|(xmltv_channel_id, profile_id)| {
+            old_id_to_channel_id
+                .get(&xmltv_channel_id)
+                .map(|channel_id| (channel_id.clone(), profile_id))
         })
         .collect();
 
     Ok(PreservedChannelData {
         manual_mappings,
         settings,
+        policy_actions,
+        failovers,
+        published,
+        profile_channels,
     })
 }
 
@@ -183,20 +269,106 @@ pub(crate) fn restore_channel_data(
         }
     }
 
-    // Restore channel settings
-    for (channel_id, is_enabled, plex_display_order) in &preserved.settings {
+    // Restore channel settings, every column but the row ID
+    for (channel_id, settings) in &preserved.settings {
         if let Some(&new_xmltv_id) = channel_id_map.get(channel_id) {
-            let mut new_settings = NewXmltvChannelSettings::new(new_xmltv_id, *is_enabled == 1);
-            if let Some(order) = plex_display_order {
-                new_settings = new_settings.with_display_order(*order);
-            }
-
             diesel::insert_into(xmltv_channel_settings::table)
-                .values(&new_settings)
+                .values((
+                    xmltv_channel_settings::xmltv_channel_id.eq(new_xmltv_id),
+                    xmltv_channel_settings::is_enabled.eq(settings.is_enabled.unwrap_or(0)),
+                    xmltv_channel_settings::plex_display_order.eq(settings.plex_display_order),
+                    xmltv_channel_settings::created_at.eq(&settings.created_at),
+                    xmltv_channel_settings::updated_at.eq(&settings.updated_at),
+                    xmltv_channel_settings::placeholder_mode.eq(settings.placeholder_mode),
+                    xmltv_channel_settings::placeholder_title.eq(&settings.placeholder_title),
+                    xmltv_channel_settings::channel_number.eq(settings.channel_number),
+                    xmltv_channel_settings::preferred_quality.eq(&settings.preferred_quality),
+                ))
                 .execute(conn)?;
         }
     }
 
+    // Restore channel policy actions
+    let policy_actions: Vec<_> = preserved
+        .policy_actions
+        .iter()
+        .filter_map(|(channel_id, action, applied_at)| {
+            channel_id_map.get(channel_id).map(|&new_xmltv_id| {
+                (
+                    channel_policy_actions::xmltv_channel_id.eq(new_xmltv_id),
+                    channel_policy_actions::action.eq(action),
+                    channel_policy_actions::applied_at.eq(applied_at),
+                )
+            })
+        })
+        .collect();
+    if !policy_actions.is_empty() {
+        diesel::insert_or_ignore_into(channel_policy_actions::table)
+            .values(&policy_actions)
+            .execute(conn)?;
+    }
+
+    // Restore the failover history in its original order
+    let failovers: Vec<_> = preserved
+        .failovers
+        .iter()
+        .filter_map(|(channel_id, from, to, occurred_at)| {
+            channel_id_map.get(channel_id).map(|&new_xmltv_id| {
+                (
+                    failover_history::xmltv_channel_id.eq(new_xmltv_id),
+                    failover_history::from_xtream_channel_id.eq(*from),
+                    failover_history::to_xtream_channel_id.eq(*to),
+                    failover_history::occurred_at.eq(occurred_at),
+                )
+            })
+        })
+        .collect();
+    if !failovers.is_empty() {
+        diesel::insert_into(failover_history::table)
+            .values(&failovers)
+            .execute(conn)?;
+    }
+
+    // Restore the published lineup
+    let published: Vec<_> = preserved
+        .published
+        .iter()
+        .filter_map(|(channel_id, is_enabled, order, number)| {
+            channel_id_map.get(channel_id).map(|&new_xmltv_id| {
+                (
+                    lineup_published::xmltv_channel_id.eq(new_xmltv_id),
+                    lineup_published::is_enabled.eq(*is_enabled),
+                    lineup_published::plex_display_order.eq(*order),
+                    lineup_published::channel_number.eq(*number),
+                )
+            })
+        })
+        .collect();
+    if !published.is_empty() {
+        diesel::insert_or_ignore_into(lineup_published::table)
+            .values(&published)
+            .execute(conn)?;
+    }
+
+    // Restore device profile lineups
+    let profile_channels: Vec<_> = preserved
+        .profile_channels
+        .iter()
+        .filter_map(|(channel_id, profile_id)| {
+            channel_id_map.get(channel_id).map(|&new_xmltv_id| {
+                (
+                    device_profile_channels::profile_id.eq(*profile_id),
+                    device_profile_channels::xmltv_channel_id.eq(new_xmltv_id),
+                )
+            })
+        })
+        .collect();
+    if !profile_channels.is_empty() {
+        diesel::insert_or_ignore_into(device_profile_channels::table)
+            .values(&profile_channels)
+            .execute(conn)?;
+    }
+
     Ok(())
 }
 
@@ -213,6 +385,12 @@ pub struct XmltvSourceResponse {
     pub is_active: bool,
     pub created_at: String,
     pub updated_at: String,
+    /// Basic auth user; the password itself is never returned
+    pub auth_username: Option<String>,
+    pub has_auth_password: bool,
+    /// Custom header name; the value itself is never returned
+    pub auth_header_name: Option<String>,
+    pub has_auth_header_value: bool,
 }
 
 impl From<XmltvSource> for XmltvSourceResponse {
@@ -227,6 +405,10 @@ impl From<XmltvSource> for XmltvSourceResponse {
             is_active: source.is_active != 0,
             created_at: source.created_at,
             updated_at: source.updated_at,
+            auth_username: source.auth_username,
+            has_auth_password: source.auth_password_encrypted.is_some(),
+            auth_header_name: source.auth_header_name,
+            has_auth_header_value: source.auth_header_value_encrypted.is_some(),
         }
     }
 }
@@ -280,6 +462,9 @@ pub struct ProgramResponse {
     pub category: Option<String>,
     pub episode_info: Option<String>,
     pub created_at: String,
+    pub sub_title: Option<String>,
+    /// Program artwork URL
+    pub icon: Option<String>,
 }
 
 impl From<Program> for ProgramResponse {
@@ -294,11 +479,17 @@ impl From<Program> for ProgramResponse {
             category: program.category,
             episode_info: program.episode_info,
             created_at: program.created_at,
+            sub_title: program.sub_title,
+            icon: program.icon,
         }
     }
 }
 
 /// Validate URL format and check for SSRF risks
+///
+/// `file://` URLs of local XMLTV files (e.g. written by an EPG grabber) are
+/// allowed if they end in .xml, .xmltv or .gz; the file doesn't have to
+/// exist yet.
 fn validate_url(url_str: &str) -> Result<(), EpgSourceError> {
     if url_str.trim().is_empty() {
         return Err(EpgSourceError::UrlRequired);
@@ -306,6 +497,21 @@ fn validate_url(url_str: &str) -> Result<(), EpgSourceError> {
 
     let parsed = url::Url::parse(url_str.trim()).map_err(|_| EpgSourceError::InvalidUrl)?;
 
+    if parsed.scheme() == "file" {
+        let path = parsed
+            .to_file_path()
+            .map_err(|_| EpgSourceError::InvalidUrl)?;
+        let is_xmltv_file = path.extension().is_some_and(|ext| {
+            ["xml", "xmltv", "gz"]
+                .iter()
+                .any(|allowed| ext.eq_ignore_ascii_case(allowed))
+        });
+        if !parsed.host_str().is_none_or(str::is_empty) || !is_xmltv_file {
+            return Err(EpgSourceError::InvalidUrl);
+        }
+        return Ok(());
+    }
+
     if parsed.scheme() != "http" && parsed.scheme() != "https" {
         return Err(EpgSourceError::InvalidUrlScheme);
     }
@@ -411,6 +617,7 @@ pub async fn get_xmltv_sources(
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     let sources: Vec<XmltvSource> = xmltv_sources::table
+        .filter(xmltv_sources::id.ne(SYNTHETIC_SOURCE_ID))
         .order(xmltv_sources::name.asc())
         .load(&mut conn)
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
@@ -475,13 +682,21 @@ pub async fn update_xmltv_source(
 /// Delete an XMLTV source
 #[tauri::command]
 pub async fn delete_xmltv_source(
+    app: AppHandle,
     db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
     source_id: i32,
 ) -> Result<(), String> {
     let mut conn = db
         .get_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
+    let source: Option<XmltvSource> = xmltv_sources::table
+        .filter(xmltv_sources::id.eq(source_id))
+        .first(&mut conn)
+        .optional()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
     let deleted = diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
         .execute(&mut conn)
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
@@ -490,9 +705,159 @@ pub async fn delete_xmltv_source(
         return Err(EpgSourceError::NotFound.into());
     }
 
+    // The source's channels left the lineup
+    server_state.bump_epg_generation();
+
+    // Remove stored source secrets from the keychain
+    if let (Some(source), Ok(app_data_dir)) = (source, app.path().app_data_dir()) {
+        let credential_manager = CredentialManager::new(app_data_dir);
+        for (secret, encrypted) in [
+            (PASSWORD_SECRET, &source.auth_password_encrypted),
+            (HEADER_SECRET, &source.auth_header_value_encrypted),
+        ] {
+            if let Some(encrypted) = encrypted {
+                let key = source_credential_key(source_id, secret);
+                let _ = credential_manager.delete_password(&key, encrypted);
+            }
+        }
+    }
+
     Ok(())
 }
 
+/// Authentication settings for an XMLTV source
+///
+/// Empty names clear basic auth or the custom header. A `None` password or
+/// header value keeps the stored secret, so names can be edited without
+/// re-entering it.
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct XmltvSourceAuthRequest {
+    pub username: Option<String>,
+    pub password: Option<String>,
+    pub header_name: Option<String>,
+    pub header_value: Option<String>,
+}
+
+/// Set the HTTP basic auth and/or custom header sent when downloading a source
+///
+/// Secrets are kept in the credential store like account passwords.
+#[tauri::command]
+pub async fn set_xmltv_source_auth(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    source_id: i32,
+    auth: XmltvSourceAuthRequest,
+) -> Result<XmltvSourceResponse, String> {
+    let non_empty = |value: &Option<String>| {
+        value
+            .as_deref()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+    };
+    let username = non_empty(&auth.username);
+    let header_name = non_empty(&auth.header_name);
+    if let Some(name) = &header_name {
+        reqwest::header::HeaderName::from_bytes(name.as_bytes())
+            .map_err(|_| format!("'{}' is not a valid header name", name))?;
+    }
+    if let Some(value) = &auth.header_value {
+        reqwest::header::HeaderValue::from_str(value)
+            .map_err(|_| "Header value contains invalid characters".to_string())?;
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())?;
+    let credential_manager = CredentialManager::new(app_data_dir);
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    let source: XmltvSource = xmltv_sources::table
+        .filter(xmltv_sources::id.eq(source_id))
+        .first(&mut conn)
+        .map_err(|_| EpgSourceError::NotFound)?;
+
+    let header_value_missing = match auth.header_value.as_deref() {
+        Some(value) => value.is_empty(),
+        None => source.auth_header_value_encrypted.is_none(),
+    };
+    if header_name.is_some() && header_value_missing {
+        return Err("Header value is required".to_string());
+    }
+
+    // Replace, keep or clear one secret
+    let update_secret = |secret: &str,
+                         enabled: bool,
+                         new_value: &Option<String>,
+                         stored: &Option<Vec<u8>>|
+     -> Result<Option<Vec<u8>>, String> {
+        let key = source_credential_key(source_id, secret);
+        if enabled && new_value.is_none() {
+            return Ok(stored.clone());
+        }
+        if let Some(stored) = stored {
+            let _ = credential_manager.delete_password(&key, stored);
+        }
+        match new_value.as_deref().filter(|v| enabled && !v.is_empty()) {
+            Some(value) => credential_manager
+                .store_password(&key, value)
+                .map(|(_, encrypted)| Some(encrypted))
+                .map_err(|e| format!("Failed to store credentials securely: {}", e)),
+            None => Ok(None),
+        }
+    };
+
+    let password = update_secret(
+        PASSWORD_SECRET,
+        username.is_some(),
+        &auth.password,
+        &source.auth_password_encrypted,
+    )?;
+    let header_value = update_secret(
+        HEADER_SECRET,
+        header_name.is_some(),
+        &auth.header_value,
+        &source.auth_header_value_encrypted,
+    )?;
+
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let updated: XmltvSource =
+        diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
+            .set((
+                xmltv_sources::auth_username.eq(&username),
+                xmltv_sources::auth_password_encrypted.eq(&password),
+                xmltv_sources::auth_header_name.eq(&header_name),
+                xmltv_sources::auth_header_value_encrypted.eq(&header_value),
+                xmltv_sources::updated_at.eq(&now),
+            ))
+            .get_result(&mut conn)
+            .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    // Secrets are not logged
+    let details = serde_json::json!({
+        "sourceId": source_id,
+        "basicAuth": username.is_some(),
+        "headerName": header_name,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Authentication updated for EPG source '{}'",
+            updated.name
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(XmltvSourceResponse::from(updated))
+}
+
 /// Toggle XMLTV source active state
 #[tauri::command]
 pub async fn toggle_xmltv_source(
@@ -504,6 +869,17 @@ pub async fn toggle_xmltv_source(
         .get_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
+    toggle_xmltv_source_internal(&mut conn, source_id, active)
+}
+
+/// Set XMLTV source active state on an existing connection
+///
+/// Shared by the Tauri command and the web admin API.
+pub fn toggle_xmltv_source_internal(
+    conn: &mut SqliteConnection,
+    source_id: i32,
+    active: bool,
+) -> Result<XmltvSourceResponse, String> {
     let is_active_int = if active { 1 } else { 0 };
     let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
 
@@ -513,7 +889,7 @@ pub async fn toggle_xmltv_source(
             xmltv_sources::is_active.eq(is_active_int),
             xmltv_sources::updated_at.eq(&now),
         ))
-        .execute(&mut conn)
+        .execute(conn)
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     if affected == 0 {
@@ -522,7 +898,7 @@ pub async fn toggle_xmltv_source(
 
     let updated: XmltvSource = xmltv_sources::table
         .filter(xmltv_sources::id.eq(source_id))
-        .first(&mut conn)
+        .first(conn)
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     Ok(XmltvSourceResponse::from(updated))
@@ -532,8 +908,13 @@ pub async fn toggle_xmltv_source(
 // EPG Refresh Commands
 // ============================================================================
 
-/// Batch size for inserting programs
-const BATCH_SIZE: usize = 500;
+/// Forward refresh progress to the frontend as `epg-refresh-progress` events
+pub(crate) fn progress_emitter(app: &AppHandle) -> refresh::ProgressCallback {
+    let app = app.clone();
+    Arc::new(move |progress| {
+        let _ = app.emit(refresh::EPG_REFRESH_PROGRESS_EVENT, progress);
+    })
+}
 
 /// Refresh EPG data for a single source
 ///
@@ -542,6 +923,7 @@ const BATCH_SIZE: usize = 500;
 /// Clears existing data before inserting new data.
 #[tauri::command]
 pub async fn refresh_epg_source(
+    app: AppHandle,
     db: State<'_, DbConnection>,
     source_id: i32,
 ) -> Result<(), String> {
@@ -563,33 +945,25 @@ pub async fn refresh_epg_source(
 
     let source_name = source.name.clone();
 
-    // Fetch and parse XMLTV data
-    let data = match fetch_xmltv(&source.url, &source.format).await {
-        Ok(d) => d,
+    let result = refresh::refresh_sources(
+        db.clone_pool(),
+        vec![source],
+        app.path().app_data_dir().ok(),
+        Some(progress_emitter(&app)),
+    )
+    .await
+        .pop()
+        .ok_or_else(|| EpgSourceError::NotFound.to_string())?;
+
+    let (channel_count, program_count) = match result.outcome {
+        Ok(counts) => counts,
         Err(e) => {
-            // Story 6-3: Log EPG fetch failure (AC #2)
-            let epg_error = EpgSourceError::from(e);
-            let details = serde_json::json!({
-                "sourceId": source_id,
-                "sourceName": source_name,
-                "error": epg_error.to_string(),
-            });
-            let _ = log_event_internal(
-                &mut conn,
-                "error",
-                "epg",
-                &format!("EPG refresh failed: {} - {}", source_name, epg_error),
-                Some(&details.to_string()),
-            );
-            return Err(epg_error.to_string());
-        }
-    };
-
-    let (parsed_channels, parsed_programs) = match parse_xmltv_data(&data) {
-        Ok(result) => result,
-        Err(e) => {
-            // Story 6-3: Log EPG parse failure (AC #2)
-            let epg_error = EpgSourceError::from(e);
+            // Story 6-3: Log EPG fetch/parse failure (AC #2)
+            let epg_error = EpgSourceError::from(e.error);
+            let message = match e.stage {
+                refresh::RefreshStage::Parsing => "EPG parse failed",
+                _ => "EPG refresh failed",
+            };
             let details = serde_json::json!({
                 "sourceId": source_id,
                 "sourceName": source_name,
@@ -599,127 +973,67 @@ pub async fn refresh_epg_source(
                 &mut conn,
                 "error",
                 "epg",
-                &format!("EPG parse failed: {} - {}", source_name, epg_error),
+                &format!("{}: {} - {}", message, source_name, epg_error),
                 Some(&details.to_string()),
             );
             return Err(epg_error.to_string());
         }
     };
 
-    let channel_count = parsed_channels.len();
-    let program_count = parsed_programs.len();
-
-    // Wrap all database operations in a transaction for atomicity
-    conn.transaction::<_, EpgSourceError, _>(|conn| {
-        // Preserve manual mappings and channel settings before deletion
-        let preserved = preserve_channel_data(conn, source_id)
-            .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-
-        // Clear existing data for this source (cascade deletes mappings)
-        diesel::delete(xmltv_channels::table.filter(xmltv_channels::source_id.eq(source_id)))
-            .execute(conn)
-            .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-
-        // Insert new channels and build mapping from channel_id to db id
-        let mut channel_id_map: HashMap<String, i32> = HashMap::new();
-
-        for parsed_channel in &parsed_channels {
-        let new_channel = NewXmltvChannel::new(
-            source_id,
-            &parsed_channel.channel_id,
-            &parsed_channel.display_name,
-            parsed_channel.icon.clone(),
-        );
-
-            let inserted: XmltvChannel = diesel::insert_into(xmltv_channels::table)
-                .values(&new_channel)
-                .get_result(conn)
-                .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-
-            if let Some(id) = inserted.id {
-                channel_id_map.insert(parsed_channel.channel_id.clone(), id);
-            }
-        }
-
-        // Insert programs in batches for performance
-        let mut programs_to_insert: Vec<NewProgram> = Vec::with_capacity(BATCH_SIZE);
-
-        for parsed_program in &parsed_programs {
-            // Look up the db id for this program's channel
-            if let Some(&channel_db_id) = channel_id_map.get(&parsed_program.channel_id) {
-                let mut new_program = NewProgram::new(
-                    channel_db_id,
-                    &parsed_program.title,
-                    &parsed_program.start_time,
-                    &parsed_program.end_time,
-                );
-
-                if let Some(ref desc) = parsed_program.description {
-                    new_program = new_program.with_description(desc);
-                }
-                if let Some(ref cat) = parsed_program.category {
-                    new_program = new_program.with_category(cat);
-                }
-                if let Some(ref ep) = parsed_program.episode_info {
-                    new_program = new_program.with_episode_info(ep);
-                }
-
-                programs_to_insert.push(new_program);
-
-                // Insert in batches
-                if programs_to_insert.len() >= BATCH_SIZE {
-                    diesel::insert_into(programs::table)
-                        .values(&programs_to_insert)
-                        .execute(conn)
-                        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-                    programs_to_insert.clear();
-                }
-            }
-        }
-
-        // Insert remaining programs
-        if !programs_to_insert.is_empty() {
-            diesel::insert_into(programs::table)
-                .values(&programs_to_insert)
-                .execute(conn)
-                .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-        }
-
-        // Restore manual mappings and channel settings
-        restore_channel_data(conn, &preserved, &channel_id_map)
-            .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-
-        // Update last_refresh timestamp on the source
-        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-        diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
-            .set(xmltv_sources::last_refresh.eq(&now))
-            .execute(conn)
-            .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
-
-        // Story 6-3: Log EPG refresh success (AC #1)
-        let details = serde_json::json!({
+    // Story 6-3: Log EPG refresh success (AC #1)
+    let details = serde_json::json!({
+        "sourceId": source_id,
+        "sourceName": source_name,
+        "channelCount": channel_count,
+        "programCount": program_count,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "epg",
+        &format!("EPG refresh completed: {} ({} channels, {} programs)", source_name, channel_count, program_count),
+        Some(&details.to_string()),
+    );
+
+    // The refresh replaced programs that per-channel feeds had merged into
+    channel_feed::refresh_channel_feeds(&mut conn, Some(source_id)).await;
+    priming::prime_in_background("manual");
+
+    hooks::fire(
+        HookEvent::EpgRefreshCompleted,
+        serde_json::json!({
+            "trigger": "manual",
             "sourceId": source_id,
-            "sourceName": source_name,
-            "channelCount": channel_count,
-            "programCount": program_count,
-        });
-        let _ = log_event_internal(
-            conn,
-            "info",
-            "epg",
-            &format!("EPG refresh completed: {} ({} channels, {} programs)", source_name, channel_count, program_count),
-            Some(&details.to_string()),
-        );
-
-        Ok(())
-    }).map_err(|e: EpgSourceError| e.to_string())?;
+            "succeeded": 1,
+            "failed": 0,
+        }),
+    );
+    plex::request_guide_reload("manual");
 
     Ok(())
 }
 
 /// Refresh EPG data for all active sources
+///
+/// Sources are downloaded and parsed concurrently; per-source progress is
+/// emitted as `epg-refresh-progress` events.
 #[tauri::command]
-pub async fn refresh_all_epg_sources(db: State<'_, DbConnection>) -> Result<(), String> {
+pub async fn refresh_all_epg_sources(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<(), String> {
+    refresh_all_epg_sources_internal(&db, app.path().app_data_dir().ok(), progress_emitter(&app))
+        .await
+}
+
+/// Refresh all active sources, reporting per-source progress to `on_progress`
+///
+/// Shared by `refresh_all_epg_sources` and the background task version.
+pub async fn refresh_all_epg_sources_internal(
+    db: &DbConnection,
+    app_data_dir: Option<std::path::PathBuf>,
+    on_progress: refresh::ProgressCallback,
+) -> Result<(), String> {
     let mut conn = db
         .get_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
@@ -730,125 +1044,38 @@ pub async fn refresh_all_epg_sources(db: State<'_, DbConnection>) -> Result<(),
         .load(&mut conn)
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
+    let results =
+        refresh::refresh_sources(db.clone_pool(), sources, app_data_dir, Some(on_progress)).await;
+
     // Track errors for reporting
     let mut failed_sources: Vec<String> = Vec::new();
     let mut success_count = 0;
-
-    // Refresh each source
-    // Note: We can't use refresh_epg_source directly due to State ownership
-    // So we implement the refresh logic inline
-    for source in sources {
-        let source_id = source.id.unwrap_or(0);
-
-        // Fetch and parse XMLTV data (outside transaction - network I/O)
-        let data = match fetch_xmltv(&source.url, &source.format).await {
-            Ok(d) => d,
-            Err(e) => {
-                eprintln!("Failed to fetch source {}: {}", source.name, e);
-                failed_sources.push(format!("{}: {}", source.name, e));
-                continue; // Skip this source but continue with others
-            }
-        };
-
-        let (parsed_channels, parsed_programs) = match parse_xmltv_data(&data) {
-            Ok(p) => p,
+    for result in results {
+        match result.outcome {
+            Ok(_) => success_count += 1,
             Err(e) => {
-                eprintln!("Failed to parse source {}: {}", source.name, e);
-                failed_sources.push(format!("{}: {}", source.name, e));
-                continue;
-            }
-        };
-
-        // Wrap all database operations in a transaction for atomicity
-        let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
-            // Preserve manual mappings and channel settings before deletion
-            let preserved = preserve_channel_data(conn, source_id)?;
-
-            // Clear existing data for this source
-            diesel::delete(xmltv_channels::table.filter(xmltv_channels::source_id.eq(source_id)))
-                .execute(conn)?;
-
-            // Insert channels
-            let mut channel_id_map: HashMap<String, i32> = HashMap::new();
-
-            for parsed_channel in &parsed_channels {
-                let new_channel = NewXmltvChannel::new(
-                    source_id,
-                    &parsed_channel.channel_id,
-                    &parsed_channel.display_name,
-                    parsed_channel.icon.clone(),
-                );
-
-                let inserted: XmltvChannel = diesel::insert_into(xmltv_channels::table)
-                    .values(&new_channel)
-                    .get_result(conn)?;
-
-                if let Some(id) = inserted.id {
-                    channel_id_map.insert(parsed_channel.channel_id.clone(), id);
-                }
-            }
-
-            // Insert programs in batches
-            let mut programs_to_insert: Vec<NewProgram> = Vec::with_capacity(BATCH_SIZE);
-
-            for parsed_program in &parsed_programs {
-                if let Some(&channel_db_id) = channel_id_map.get(&parsed_program.channel_id) {
-                    let mut new_program = NewProgram::new(
-                        channel_db_id,
-                        &parsed_program.title,
-                        &parsed_program.start_time,
-                        &parsed_program.end_time,
-                    );
-
-                    if let Some(ref desc) = parsed_program.description {
-                        new_program = new_program.with_description(desc);
-                    }
-                    if let Some(ref cat) = parsed_program.category {
-                        new_program = new_program.with_category(cat);
-                    }
-                    if let Some(ref ep) = parsed_program.episode_info {
-                        new_program = new_program.with_episode_info(ep);
-                    }
-
-                    programs_to_insert.push(new_program);
-
-                    if programs_to_insert.len() >= BATCH_SIZE {
-                        diesel::insert_into(programs::table)
-                            .values(&programs_to_insert)
-                            .execute(conn)?;
-                        programs_to_insert.clear();
-                    }
-                }
+                eprintln!("Failed to refresh source {}: {}", result.source_name, e.error);
+                failed_sources.push(format!("{}: {}", result.source_name, e.error));
             }
+        }
+    }
 
-            // Insert remaining programs
-            if !programs_to_insert.is_empty() {
-                diesel::insert_into(programs::table)
-                    .values(&programs_to_insert)
-                    .execute(conn)?;
-            }
-
-            // Restore manual mappings and channel settings
-            restore_channel_data(conn, &preserved, &channel_id_map)?;
-
-            // Update last_refresh timestamp
-            let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-            diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
-                .set(xmltv_sources::last_refresh.eq(&now))
-                .execute(conn)?;
-
-            Ok(())
-        });
+    if success_count > 0 {
+        channel_feed::refresh_channel_feeds(&mut conn, None).await;
+        priming::prime_in_background("manual");
+    }
 
-        match result {
-            Ok(()) => {
-                success_count += 1;
-            }
-            Err(e) => {
-                eprintln!("Failed to refresh source {}: {}", source.name, e);
-                failed_sources.push(format!("{}: {}", source.name, e));
-            }
-        }
+    hooks::fire(
+        HookEvent::EpgRefreshCompleted,
+        serde_json::json!({
+            "trigger": "manual",
+            "succeeded": success_count,
+            "failed": failed_sources.len(),
+            "errors": failed_sources,
+        }),
+    );
+    if success_count > 0 {
+        plex::request_guide_reload("manual");
     }
 
     // Return error if all sources failed
@@ -878,7 +1105,7 @@ pub async fn get_epg_stats(
     source_id: i32,
 ) -> Result<EpgStatsResponse, String> {
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     // Get the source (for last_refresh)
@@ -1057,6 +1284,343 @@ pub async fn set_epg_schedule(
     })
 }
 
+// ============================================================================
+// EPG Retention Commands
+// ============================================================================
+
+/// Response type for the EPG retention settings
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EpgRetentionResponse {
+    /// Days programs are kept after they end (0 = keep forever)
+    pub retention_days: u32,
+    /// Days aired programs are kept in the guide history (0 = no history)
+    pub history_days: u32,
+    pub last_pruned_at: Option<String>,
+    pub last_vacuum_at: Option<String>,
+}
+
+fn epg_retention_response(conn: &mut SqliteConnection) -> EpgRetentionResponse {
+    EpgRetentionResponse {
+        retention_days: crate::scheduler::get_epg_retention_days(conn),
+        history_days: crate::xmltv::history::get_epg_history_days(conn),
+        last_pruned_at: crate::scheduler::get_last_pruned(conn).map(|dt| dt.to_rfc3339()),
+        last_vacuum_at: crate::scheduler::get_last_vacuum(conn).map(|dt| dt.to_rfc3339()),
+    }
+}
+
+/// Get the EPG retention settings
+#[tauri::command]
+pub async fn get_epg_retention(
+    db: State<'_, DbConnection>,
+) -> Result<EpgRetentionResponse, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    Ok(epg_retention_response(&mut conn))
+}
+
+/// Set how many days programs are kept after they end (0 = keep forever)
+///
+/// Old programs are pruned by the scheduler once a day.
+#[tauri::command]
+pub async fn set_epg_retention(
+    db: State<'_, DbConnection>,
+    retention_days: u32,
+) -> Result<EpgRetentionResponse, String> {
+    if retention_days > crate::scheduler::MAX_EPG_RETENTION_DAYS {
+        return Err(format!(
+            "Retention must be between 0 and {} days",
+            crate::scheduler::MAX_EPG_RETENTION_DAYS
+        ));
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    crate::scheduler::set_epg_retention_days(&mut conn, retention_days)
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    let details = serde_json::json!({ "retentionDays": retention_days });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &if retention_days == 0 {
+            "Configuration changed: EPG retention disabled".to_string()
+        } else {
+            format!(
+                "Configuration changed: EPG retention set to {} days",
+                retention_days
+            )
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(epg_retention_response(&mut conn))
+}
+
+/// Set how many days aired programs are kept in the guide history (0 = off)
+///
+/// Turning the history off deletes it at the next pruning.
+#[tauri::command]
+pub async fn set_epg_history(
+    db: State<'_, DbConnection>,
+    history_days: u32,
+) -> Result<EpgRetentionResponse, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    crate::xmltv::history::set_epg_history_days(&mut conn, history_days)?;
+
+    let details = serde_json::json!({ "historyDays": history_days });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &if history_days == 0 {
+            "Configuration changed: guide history disabled".to_string()
+        } else {
+            format!(
+                "Configuration changed: guide history set to {} days",
+                history_days
+            )
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(epg_retention_response(&mut conn))
+}
+
+/// Get how many days of programs are generated into /epg.xml
+#[tauri::command]
+pub async fn get_epg_window(db: State<'_, DbConnection>) -> Result<u32, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    Ok(crate::server::epg::get_epg_window_days(&mut conn))
+}
+
+/// Set how many days of programs are generated into /epg.xml
+///
+/// A shorter window keeps the guide small so Plex refreshes it faster. The
+/// cached guide is dropped and regenerated on the next request.
+#[tauri::command]
+pub async fn set_epg_window(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    window_days: u32,
+) -> Result<u32, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    crate::server::epg::set_epg_window_days(&mut conn, window_days)?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "setting": crate::server::epg::EPG_WINDOW_DAYS_KEY,
+        "value": window_days
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: guide window set to {} days",
+            window_days
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(window_days)
+}
+
+/// Get the lineup's preferred program languages, most preferred first
+#[tauri::command]
+pub async fn get_epg_languages(db: State<'_, DbConnection>) -> Result<Vec<String>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    Ok(crate::server::epg::get_epg_languages(&mut conn))
+}
+
+/// Set the lineup's preferred program languages (e.g. `["de", "en"]`)
+///
+/// /epg.xml uses the first of these languages each title and description is
+/// available in, and the guide's own text otherwise. An empty list keeps the
+/// guide's texts. Returns the normalized list.
+#[tauri::command]
+pub async fn set_epg_languages(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    languages: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    let languages = crate::server::epg::set_epg_languages(&mut conn, &languages)?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "setting": crate::server::epg::EPG_LANGUAGES_KEY,
+        "value": languages
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &if languages.is_empty() {
+            "Configuration changed: guide languages cleared".to_string()
+        } else {
+            format!(
+                "Configuration changed: guide languages set to {}",
+                languages.join(", ")
+            )
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(languages)
+}
+
+/// Get the placeholder program settings for channels without guide data
+#[tauri::command]
+pub async fn get_placeholder_epg(
+    db: State<'_, DbConnection>,
+) -> Result<crate::server::epg::PlaceholderEpgConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    Ok(crate::server::epg::load_placeholder_epg_config(&mut conn))
+}
+
+/// Save the placeholder program settings
+///
+/// The cached guide is dropped and regenerated on the next request.
+#[tauri::command]
+pub async fn set_placeholder_epg(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    config: crate::server::epg::PlaceholderEpgConfig,
+) -> Result<crate::server::epg::PlaceholderEpgConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    crate::server::epg::save_placeholder_epg_config(&mut conn, &config)?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "setting": crate::server::epg::EPG_PLACEHOLDER_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: placeholder programs {}",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
+
+/// Override placeholder programs for one channel
+///
+/// `mode` None follows the global setting; true always fills the channel's
+/// guide when it has no programs, false never does. `title` replaces the
+/// global title template (`{channel}` is the channel name).
+#[tauri::command]
+pub async fn set_channel_placeholder(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    channel_id: i32,
+    mode: Option<bool>,
+    title: Option<String>,
+) -> Result<(), String> {
+    let title = title
+        .map(|t| t.trim().to_string())
+        .filter(|t| !t.is_empty());
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    let updated = diesel::update(
+        xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id)),
+    )
+    .set((
+        xmltv_channel_settings::placeholder_mode.eq(mode.map(i32::from)),
+        xmltv_channel_settings::placeholder_title.eq(&title),
+        xmltv_channel_settings::updated_at.eq(chrono::Utc::now().to_rfc3339()),
+    ))
+    .execute(&mut conn)
+    .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+    if updated == 0 {
+        return Err(format!("Channel {} has no settings", channel_id));
+    }
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "channelId": channel_id,
+        "mode": mode,
+        "title": title,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: placeholder programs {} for channel {}",
+            match mode {
+                Some(true) => "always on",
+                Some(false) => "off",
+                None => "default",
+            },
+            channel_id
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get what was on a channel during a day
+///
+/// `channel` is an XMLTV channel id or channel name, and `date` a UTC day
+/// (YYYY-MM-DD, default today). Includes programs from the guide history.
+#[tauri::command]
+pub async fn get_epg_history(
+    db: State<'_, DbConnection>,
+    channel: String,
+    date: Option<String>,
+) -> Result<Vec<GuideHistoryEntry>, String> {
+    let date = crate::xmltv::history::parse_history_date(date.as_deref())?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
+
+    crate::xmltv::history::load_channel_history(&mut conn, &channel, date)
+        .map_err(|e| EpgSourceError::DatabaseError(e.to_string()).into())
+}
+
 // ============================================================================
 // EPG Grid Commands (Story 5.1)
 // ============================================================================
@@ -1137,7 +1701,7 @@ pub async fn search_epg_programs(
     }
 
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     // Current time for filtering past programs
@@ -1319,7 +1883,7 @@ pub async fn get_enabled_channels_with_programs(
     end_time: String,
 ) -> Result<Vec<EpgGridChannel>, String> {
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| EpgSourceError::DatabaseError(e.to_string()))?;
 
     // Get all enabled channels with their settings, ordered by display order
@@ -1504,6 +2068,17 @@ mod tests {
         assert!(validate_url("https://example.com/epg.xml.gz").is_ok());
     }
 
+    #[test]
+    fn test_validate_url_local_file() {
+        let guide = std::env::temp_dir().join("guide.xml.gz");
+        let url = url::Url::from_file_path(&guide).unwrap();
+        assert!(validate_url(url.as_str()).is_ok());
+
+        let not_xmltv = std::env::temp_dir().join("notes.txt");
+        assert!(validate_url(url::Url::from_file_path(not_xmltv).unwrap().as_str()).is_err());
+        assert!(validate_url("file://fileserver/share/guide.xml").is_err());
+    }
+
     #[test]
     fn test_validate_url_invalid() {
         assert!(validate_url("").is_err());
@@ -0,0 +1,53 @@
+//! Automatic primary demotion Tauri commands
+//!
+//! Configures the policy in `crate::failover_policy`.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::failover_policy::{self, FailoverPolicyConfig, FAILOVER_POLICY_KEY};
+
+/// Get the automatic primary demotion settings
+#[tauri::command]
+pub fn get_failover_policy(db: State<DbConnection>) -> Result<FailoverPolicyConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(failover_policy::load_config(&mut conn))
+}
+
+/// Save the automatic primary demotion settings
+#[tauri::command]
+pub fn set_failover_policy(
+    db: State<DbConnection>,
+    config: FailoverPolicyConfig,
+) -> Result<FailoverPolicyConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    failover_policy::save_config(&mut conn, &config)?;
+
+    let details = serde_json::json!({
+        "setting": FAILOVER_POLICY_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Automatic primary demotion {}",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
@@ -0,0 +1,69 @@
+//! Automation hook Tauri commands
+//!
+//! Reads and saves the hooks run by `crate::hooks` and the opt-in that
+//! allows them to run at all.
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::hooks::{self, AutomationHook, AUTOMATION_HOOKS_KEY};
+
+/// Automation hook settings
+#[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationHookSettings {
+    /// Whether hooks may run (explicit opt-in)
+    pub enabled: bool,
+    pub hooks: Vec<AutomationHook>,
+}
+
+/// Get the automation hook settings
+#[tauri::command]
+pub fn get_automation_hooks(db: State<DbConnection>) -> Result<AutomationHookSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(AutomationHookSettings {
+        enabled: hooks::is_enabled(&mut conn),
+        hooks: hooks::load_hooks(&mut conn),
+    })
+}
+
+/// Save the automation hook settings
+///
+/// Hooks execute the configured programs with the app's privileges, so they
+/// only run while `enabled` is true.
+#[tauri::command]
+pub fn set_automation_hooks(
+    db: State<DbConnection>,
+    settings: AutomationHookSettings,
+) -> Result<AutomationHookSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    hooks::save_hooks(&mut conn, settings.enabled, &settings.hooks)?;
+
+    let details = serde_json::json!({
+        "setting": AUTOMATION_HOOKS_KEY,
+        "enabled": settings.enabled,
+        "hookCount": settings.hooks.len(),
+        "programs": settings.hooks.iter().map(|h| h.program.as_str()).collect::<Vec<_>>()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Automation hooks {} ({} configured)",
+            if settings.enabled { "enabled" } else { "disabled" },
+            settings.hooks.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(settings)
+}
@@ -0,0 +1,725 @@
+//! Lineup export/import Tauri commands
+//!
+//! A lineup export contains only channel curation: which channels are
+//! enabled, their order and channel numbers, and which streams are mapped
+//! to them. Unlike the full configuration export it has no accounts,
+//! EPG sources, or settings, and it refers to channels by natural keys
+//! (XMLTV tvg-id, Xtream stream ID and name) instead of database IDs, so it
+//! can be shared between users with the same providers and EPG sources.
+//!
+//! Importing resolves each entry against the local channels and reports the
+//! entries that could not be resolved. The app has no channel tags, so none
+//! are exported.
+//!
+//! Also exposes lineup staging (see `crate::lineup_staging`): drafting
+//! enable/order changes and publishing them to Plex in one step, and the
+//! lineup preview (see `crate::server::lineup_preview`).
+
+use diesel::prelude::*;
+use diesel::upsert::excluded;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{
+    channel_mappings, xmltv_channel_settings, xmltv_channels, xtream_channels,
+};
+use crate::db::DbConnection;
+use crate::lineup_staging::{self, LineupStagingStatus};
+use crate::server::lineup_preview::{self, LineupPreviewEntry, DEBUG_LINEUP_KEY};
+use crate::server::{hdhr, AppState};
+
+/// Current lineup export format version
+///
+/// 1.1 added `channelNumber`; 1.0 files leave the local numbers unchanged.
+const LINEUP_VERSION: &str = "1.1";
+
+/// (is_enabled, plex_display_order, channel_number)
+type SettingsRow = (Option<i32>, Option<i32>, Option<i32>);
+
+/// (xmltv_channel_id, is_primary, stream_priority, stream_id, stream name)
+type MappingRow = (i32, Option<i32>, Option<i32>, i32, String);
+
+/// Exported lineup
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupExport {
+    pub version: String,
+    pub export_date: String,
+    pub app_version: String,
+    pub channels: Vec<LineupChannel>,
+}
+
+/// A curated channel, identified by its XMLTV tvg-id
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupChannel {
+    pub channel_id: String,
+    pub display_name: String,
+    pub enabled: bool,
+    /// Position in the lineup (0-indexed)
+    pub display_order: Option<i32>,
+    /// Number set for the channel; without one the number is position + 1
+    #[serde(default)]
+    pub channel_number: Option<i32>,
+    pub streams: Vec<LineupStream>,
+}
+
+/// A stream mapped to a channel, identified by provider stream ID and name
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupStream {
+    pub stream_id: i32,
+    pub name: String,
+    pub is_primary: bool,
+    pub stream_priority: i32,
+}
+
+/// Result of a lineup import
+#[derive(Debug, Serialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupImportResult {
+    pub channels_applied: usize,
+    pub mappings_applied: usize,
+    pub unresolved: Vec<UnresolvedLineupEntry>,
+}
+
+/// A lineup entry that could not be (fully) applied
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct UnresolvedLineupEntry {
+    pub channel_id: String,
+    pub display_name: String,
+    pub reason: String,
+}
+
+/// Build a lineup export from the current database
+///
+/// Includes every channel that has settings or mapped streams.
+pub fn build_lineup_export(conn: &mut SqliteConnection) -> QueryResult<LineupExport> {
+    let channels: Vec<(Option<i32>, String, String)> = xmltv_channels::table
+        .order(xmltv_channels::id.asc())
+        .select((
+            xmltv_channels::id,
+            xmltv_channels::channel_id,
+            xmltv_channels::display_name,
+        ))
+        .load(conn)?;
+
+    let settings: HashMap<i32, SettingsRow> = xmltv_channel_settings::table
+        .select((
+            xmltv_channel_settings::xmltv_channel_id,
+            xmltv_channel_settings::is_enabled,
+            xmltv_channel_settings::plex_display_order,
+            xmltv_channel_settings::channel_number,
+        ))
+        .load::<(i32, Option<i32>, Option<i32>, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, enabled, order, number)| (id, (enabled, order, number)))
+        .collect();
+
+    let mut streams: HashMap<i32, Vec<LineupStream>> = HashMap::new();
+    let mapping_rows: Vec<MappingRow> = channel_mappings::table
+        .inner_join(xtream_channels::table)
+        .order((
+            channel_mappings::xmltv_channel_id.asc(),
+            channel_mappings::stream_priority.asc(),
+        ))
+        .select((
+            channel_mappings::xmltv_channel_id,
+            channel_mappings::is_primary,
+            channel_mappings::stream_priority,
+            xtream_channels::stream_id,
+            xtream_channels::name,
+        ))
+        .load(conn)?;
+    for (xmltv_id, is_primary, priority, stream_id, name) in mapping_rows {
+        streams.entry(xmltv_id).or_default().push(LineupStream {
+            stream_id,
+            name,
+            is_primary: is_primary == Some(1),
+            stream_priority: priority.unwrap_or(0),
+        });
+    }
+
+    let channels = channels
+        .into_iter()
+        .filter_map(|(id, channel_id, display_name)| {
+            let id = id?;
+            let setting = settings.get(&id).copied();
+            let streams = streams.remove(&id).unwrap_or_default();
+            if setting.is_none() && streams.is_empty() {
+                return None;
+            }
+            let (enabled, display_order, channel_number) = setting.unwrap_or((None, None, None));
+            Some(LineupChannel {
+                channel_id,
+                display_name,
+                enabled: enabled == Some(1),
+                display_order,
+                channel_number,
+                streams,
+            })
+        })
+        .collect();
+
+    Ok(LineupExport {
+        version: LINEUP_VERSION.to_string(),
+        export_date: chrono::Utc::now().to_rfc3339(),
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        channels,
+    })
+}
+
+/// Apply a lineup to the local channels
+///
+/// Each channel is matched by tvg-id. Its streams are matched by stream ID
+/// and name, falling back to name alone (providers sometimes renumber
+/// streams). Resolved streams replace the channel's mappings as manual
+/// mappings; if none resolve, existing mappings are kept. A channel is only
+/// enabled if it has a mapped stream afterwards. Channel numbers replace the
+/// numbers of the imported channels, unless another channel holds them.
+pub fn apply_lineup(
+    conn: &mut SqliteConnection,
+    lineup: &LineupExport,
+) -> QueryResult<LineupImportResult> {
+    // tvg-id -> local channel IDs (several sources may carry the same tvg-id)
+    let mut local_channels: HashMap<String, Vec<i32>> = HashMap::new();
+    for (id, channel_id) in xmltv_channels::table
+        .order(xmltv_channels::id.asc())
+        .select((xmltv_channels::id, xmltv_channels::channel_id))
+        .load::<(Option<i32>, String)>(conn)?
+    {
+        if let Some(id) = id {
+            local_channels
+                .entry(channel_id.to_lowercase())
+                .or_default()
+                .push(id);
+        }
+    }
+
+    let local_streams: Vec<(Option<i32>, i32, String)> = xtream_channels::table
+        .order(xtream_channels::id.asc())
+        .select((
+            xtream_channels::id,
+            xtream_channels::stream_id,
+            xtream_channels::name,
+        ))
+        .load(conn)?;
+    let resolve_stream = |stream: &LineupStream| -> Option<i32> {
+        local_streams
+            .iter()
+            .find(|(_, stream_id, name)| {
+                *stream_id == stream.stream_id && name.eq_ignore_ascii_case(&stream.name)
+            })
+            .or_else(|| {
+                local_streams
+                    .iter()
+                    .find(|(_, _, name)| name.eq_ignore_ascii_case(&stream.name))
+            })
+            .and_then(|(id, _, _)| *id)
+    };
+
+    // 1.0 files carry no channel numbers
+    let has_numbers = lineup.version != "1.0";
+
+    conn.transaction(|conn| {
+        let mut result = LineupImportResult::default();
+        let now = chrono::Utc::now().to_rfc3339();
+        let mut numbers: Vec<(i32, &LineupChannel)> = Vec::new();
+
+        for channel in &lineup.channels {
+            let unresolved = |reason: String| UnresolvedLineupEntry {
+                channel_id: channel.channel_id.clone(),
+                display_name: channel.display_name.clone(),
+                reason,
+            };
+
+            let Some(xmltv_id) = local_channels
+                .get_mut(&channel.channel_id.to_lowercase())
+                .filter(|ids| !ids.is_empty())
+                .map(|ids| ids.remove(0))
+            else {
+                result
+                    .unresolved
+                    .push(unresolved("EPG channel not found".to_string()));
+                continue;
+            };
+
+            // Replace mappings with the resolved streams
+            let mut resolved: Vec<(i32, &LineupStream)> = Vec::new();
+            for stream in &channel.streams {
+                match resolve_stream(stream) {
+                    Some(id) if !resolved.iter().any(|(r, _)| *r == id) => {
+                        resolved.push((id, stream))
+                    }
+                    Some(_) => {}
+                    None => result.unresolved.push(unresolved(format!(
+                        "Stream '{}' ({}) not found",
+                        stream.name, stream.stream_id
+                    ))),
+                }
+            }
+            if !resolved.is_empty() {
+                let has_primary = resolved.iter().any(|(_, s)| s.is_primary);
+                diesel::delete(
+                    channel_mappings::table.filter(channel_mappings::xmltv_channel_id.eq(xmltv_id)),
+                )
+                .execute(conn)?;
+                let rows: Vec<_> = resolved
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (xtream_id, stream))| {
+                        let primary = stream.is_primary || (!has_primary && index == 0);
+                        (
+                            channel_mappings::xmltv_channel_id.eq(xmltv_id),
+                            channel_mappings::xtream_channel_id.eq(*xtream_id),
+                            channel_mappings::is_manual.eq(1),
+                            channel_mappings::is_primary.eq(primary as i32),
+                            channel_mappings::stream_priority.eq(stream.stream_priority),
+                        )
+                    })
+                    .collect();
+                result.mappings_applied += diesel::insert_into(channel_mappings::table)
+                    .values(&rows)
+                    .execute(conn)?;
+            }
+
+            let has_mapping = diesel::select(diesel::dsl::exists(
+                channel_mappings::table.filter(channel_mappings::xmltv_channel_id.eq(xmltv_id)),
+            ))
+            .get_result::<bool>(conn)?;
+            let enabled = channel.enabled && has_mapping;
+            if channel.enabled && !has_mapping {
+                result.unresolved.push(unresolved(
+                    "No stream mapped; channel left disabled".to_string(),
+                ));
+            }
+
+            diesel::insert_into(xmltv_channel_settings::table)
+                .values((
+                    xmltv_channel_settings::xmltv_channel_id.eq(xmltv_id),
+                    xmltv_channel_settings::is_enabled.eq(enabled as i32),
+                    xmltv_channel_settings::plex_display_order.eq(channel.display_order),
+                ))
+                .on_conflict(xmltv_channel_settings::xmltv_channel_id)
+                .do_update()
+                .set((
+                    xmltv_channel_settings::is_enabled
+                        .eq(excluded(xmltv_channel_settings::is_enabled)),
+                    xmltv_channel_settings::plex_display_order
+                        .eq(excluded(xmltv_channel_settings::plex_display_order)),
+                    xmltv_channel_settings::updated_at.eq(&now),
+                ))
+                .execute(conn)?;
+            result.channels_applied += 1;
+            if has_numbers {
+                numbers.push((xmltv_id, channel));
+            }
+        }
+
+        // Clear first so that channels can swap numbers
+        diesel::update(
+            xmltv_channel_settings::table.filter(
+                xmltv_channel_settings::xmltv_channel_id
+                    .eq_any(numbers.iter().map(|(id, _)| *id).collect::<Vec<_>>()),
+            ),
+        )
+        .set(xmltv_channel_settings::channel_number.eq(None::<i32>))
+        .execute(conn)?;
+        for (xmltv_id, channel) in numbers {
+            if channel.channel_number.is_none() {
+                continue;
+            }
+            if let Err(reason) =
+                crate::channel_numbers::set_channel_number(conn, xmltv_id, channel.channel_number)
+            {
+                result.unresolved.push(UnresolvedLineupEntry {
+                    channel_id: channel.channel_id.clone(),
+                    display_name: channel.display_name.clone(),
+                    reason,
+                });
+            }
+        }
+
+        Ok(result)
+    })
+}
+
+/// Export the channel lineup to JSON
+///
+/// The frontend saves the JSON with Tauri's file dialog.
+#[tauri::command]
+pub fn export_lineup(db: State<DbConnection>) -> Result<String, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let lineup =
+        build_lineup_export(&mut conn).map_err(|e| format!("Failed to export lineup: {}", e))?;
+    let json = serde_json::to_string_pretty(&lineup)
+        .map_err(|e| format!("Failed to serialize lineup: {}", e))?;
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Lineup exported: {} channels", lineup.channels.len()),
+        None,
+    );
+
+    Ok(json)
+}
+
+/// Import a channel lineup from JSON
+///
+/// Applies the entries that resolve against local channels and returns the
+/// ones that do not.
+#[tauri::command]
+pub fn import_lineup(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    content: String,
+) -> Result<LineupImportResult, String> {
+    let lineup: LineupExport =
+        serde_json::from_str(&content).map_err(|e| format!("Invalid lineup file: {}", e))?;
+    if lineup.version.split('.').next() != LINEUP_VERSION.split('.').next() {
+        return Err(format!(
+            "Unsupported lineup version: {} (expected {})",
+            lineup.version, LINEUP_VERSION
+        ));
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let result =
+        apply_lineup(&mut conn, &lineup).map_err(|e| format!("Failed to import lineup: {}", e))?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "channelsApplied": result.channels_applied,
+        "mappingsApplied": result.mappings_applied,
+        "unresolved": result.unresolved,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        if result.unresolved.is_empty() {
+            "info"
+        } else {
+            "warn"
+        },
+        "system",
+        &format!(
+            "Lineup imported: {} channels, {} unresolved entries",
+            result.channels_applied,
+            result.unresolved.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(result)
+}
+
+/// Get the lineup staging mode and pending draft changes
+#[tauri::command]
+pub fn get_lineup_staging(db: State<DbConnection>) -> Result<LineupStagingStatus, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    lineup_staging::status(&mut conn).map_err(|e| format!("Failed to load lineup draft: {}", e))
+}
+
+/// Turn lineup staging on or off
+///
+/// Turning staging off makes any unpublished draft live.
+#[tauri::command]
+pub fn set_lineup_staging(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    enabled: bool,
+) -> Result<LineupStagingStatus, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let pending = lineup_staging::draft_changes(&mut conn)
+        .map_err(|e| format!("Failed to load lineup draft: {}", e))?
+        .len();
+    lineup_staging::set_enabled(&mut conn, enabled)
+        .map_err(|e| format!("Failed to update lineup staging: {}", e))?;
+    if !enabled && pending > 0 {
+        server_state.bump_epg_generation();
+    }
+
+    let details = serde_json::json!({ "enabled": enabled, "pendingChanges": pending });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Lineup staging {}",
+            if enabled { "enabled" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    lineup_staging::status(&mut conn).map_err(|e| format!("Failed to load lineup draft: {}", e))
+}
+
+/// Publish the lineup draft to Plex
+///
+/// Output caches are invalidated once, after the whole draft is live.
+#[tauri::command]
+pub fn publish_lineup(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<LineupStagingStatus, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    if !lineup_staging::is_enabled(&mut conn) {
+        return Err("Lineup staging is not enabled".to_string());
+    }
+    let changes = lineup_staging::publish(&mut conn)
+        .map_err(|e| format!("Failed to publish lineup: {}", e))?;
+    server_state.bump_epg_generation();
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Lineup published: {} channel changes", changes),
+        None,
+    );
+
+    lineup_staging::status(&mut conn).map_err(|e| format!("Failed to load lineup draft: {}", e))
+}
+
+/// Discard the lineup draft, restoring the published lineup
+#[tauri::command]
+pub fn discard_lineup_draft(db: State<DbConnection>) -> Result<LineupStagingStatus, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    if !lineup_staging::is_enabled(&mut conn) {
+        return Err("Lineup staging is not enabled".to_string());
+    }
+    let changes = lineup_staging::discard(&mut conn)
+        .map_err(|e| format!("Failed to discard lineup draft: {}", e))?;
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Lineup draft discarded: {} channel changes reverted", changes),
+        None,
+    );
+
+    lineup_staging::status(&mut conn).map_err(|e| format!("Failed to load lineup draft: {}", e))
+}
+
+/// Get the channel list exactly as Plex sees it, with problems per channel
+#[tauri::command]
+pub fn get_lineup_preview(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<Vec<LineupPreviewEntry>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let host = hdhr::advertised_host(server_state.get_bind_address());
+    lineup_preview::build_lineup_preview(&mut conn, &host, server_state.get_port())
+        .map_err(|e| format!("Failed to build lineup preview: {}", e))
+}
+
+/// Whether the lineup preview is served at `/debug/lineup`
+#[tauri::command]
+pub fn get_debug_lineup_enabled(db: State<DbConnection>) -> Result<bool, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(lineup_preview::get_debug_lineup_enabled(&mut conn))
+}
+
+/// Serve the lineup preview at `/debug/lineup`
+#[tauri::command]
+pub fn set_debug_lineup_enabled(db: State<DbConnection>, enabled: bool) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    lineup_preview::set_debug_lineup_enabled(&mut conn, enabled)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+
+    let details = serde_json::json!({ "setting": DEBUG_LINEUP_KEY, "value": enabled });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Lineup debug endpoint {}",
+            if enabled { "enabled" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    fn seed(conn: &mut SqliteConnection, channels: &[(i32, &str)], streams: &[(i32, i32, &str)]) {
+        for (id, tvg_id) in channels {
+            diesel::sql_query(format!(
+                "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES ({id}, 1, '{tvg_id}', '{tvg_id}')"
+            ))
+            .execute(conn)
+            .unwrap();
+        }
+        for (id, stream_id, name) in streams {
+            diesel::sql_query(format!(
+                "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES ({id}, 1, {stream_id}, '{name}')"
+            ))
+            .execute(conn)
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_lineup_round_trip_by_natural_keys() {
+        // Source database
+        let mut source = test_connection();
+        seed(
+            &mut source,
+            &[(1, "ESPN.us"), (2, "CNN.us"), (3, "Unused.us")],
+            &[
+                (10, 100, "US: ESPN"),
+                (11, 101, "US: ESPN Backup"),
+                (20, 200, "US: CNN"),
+            ],
+        );
+        diesel::sql_query(
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, is_manual, is_primary, stream_priority) VALUES
+                (1, 10, 1, 1, 0), (1, 11, 0, 0, 1), (2, 20, 0, 1, 0)",
+        )
+        .execute(&mut source)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order) VALUES
+                (1, 1, 0), (2, 1, 1)",
+        )
+        .execute(&mut source)
+        .unwrap();
+
+        let lineup = build_lineup_export(&mut source).unwrap();
+        assert_eq!(lineup.channels.len(), 2);
+        assert_eq!(lineup.channels[0].streams.len(), 2);
+
+        // Target database: different IDs, one stream renumbered, CNN stream missing
+        let mut target = test_connection();
+        seed(
+            &mut target,
+            &[(7, "espn.us"), (8, "CNN.us")],
+            &[(50, 100, "US: ESPN"), (51, 999, "US: ESPN Backup")],
+        );
+
+        let result = apply_lineup(&mut target, &lineup).unwrap();
+        assert_eq!(result.channels_applied, 2);
+        assert_eq!(result.mappings_applied, 2);
+        assert_eq!(result.unresolved.len(), 2);
+        assert!(result.unresolved[0].reason.contains("US: CNN"));
+        assert!(result.unresolved[1].reason.contains("left disabled"));
+
+        let enabled: Vec<(i32, Option<i32>)> = xmltv_channel_settings::table
+            .order(xmltv_channel_settings::xmltv_channel_id.asc())
+            .select((
+                xmltv_channel_settings::xmltv_channel_id,
+                xmltv_channel_settings::is_enabled,
+            ))
+            .load(&mut target)
+            .unwrap();
+        assert_eq!(enabled, vec![(7, Some(1)), (8, Some(0))]);
+    }
+
+    #[test]
+    fn test_lineup_round_trip_keeps_channel_numbers() {
+        let mut source = test_connection();
+        seed(
+            &mut source,
+            &[(1, "ESPN.us"), (2, "CNN.us")],
+            &[(10, 100, "US: ESPN"), (20, 200, "US: CNN")],
+        );
+        diesel::sql_query(
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, is_manual, is_primary, stream_priority) VALUES
+                (1, 10, 1, 1, 0), (2, 20, 1, 1, 0)",
+        )
+        .execute(&mut source)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order, channel_number) VALUES
+                (1, 1, 0, 206), (2, 1, 1, NULL)",
+        )
+        .execute(&mut source)
+        .unwrap();
+
+        let json = serde_json::to_string(&build_lineup_export(&mut source).unwrap()).unwrap();
+        let lineup: LineupExport = serde_json::from_str(&json).unwrap();
+
+        // Target already uses 206 for CNN, which the import moves to ESPN
+        let mut target = test_connection();
+        seed(
+            &mut target,
+            &[(7, "ESPN.us"), (8, "CNN.us")],
+            &[(50, 100, "US: ESPN"), (60, 200, "US: CNN")],
+        );
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, channel_number) VALUES (8, 1, 206)",
+        )
+        .execute(&mut target)
+        .unwrap();
+
+        let result = apply_lineup(&mut target, &lineup).unwrap();
+        assert!(result.unresolved.is_empty());
+        let numbers: Vec<(i32, Option<i32>)> = xmltv_channel_settings::table
+            .order(xmltv_channel_settings::xmltv_channel_id.asc())
+            .select((
+                xmltv_channel_settings::xmltv_channel_id,
+                xmltv_channel_settings::channel_number,
+            ))
+            .load(&mut target)
+            .unwrap();
+        assert_eq!(numbers, vec![(7, Some(206)), (8, None)]);
+    }
+
+    #[test]
+    fn test_unknown_channel_is_reported() {
+        let mut conn = test_connection();
+        let lineup = LineupExport {
+            version: LINEUP_VERSION.to_string(),
+            export_date: String::new(),
+            app_version: String::new(),
+            channels: vec![LineupChannel {
+                channel_id: "Missing.us".to_string(),
+                display_name: "Missing".to_string(),
+                enabled: true,
+                display_order: Some(0),
+                channel_number: None,
+                streams: vec![],
+            }],
+        };
+
+        let result = apply_lineup(&mut conn, &lineup).unwrap();
+        assert_eq!(result.channels_applied, 0);
+        assert_eq!(result.unresolved[0].reason, "EPG channel not found");
+    }
+}
@@ -0,0 +1,428 @@
+//! Lineup health report Tauri commands
+//!
+//! One row per enabled channel with its guide source, mapped streams, best
+//! available quality, stream reliability and EPG coverage, so a large lineup
+//! can be audited in a single sheet. The report can be exported as CSV; the
+//! frontend saves the file with Tauri's file dialog.
+//!
+//! Reliability comes from the stream proxy's `stream_health` records: the share
+//! of mapped streams whose last connection attempt succeeded. Streams that
+//! were never played are not counted.
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{
+    channel_epg_feeds, channel_mappings, programs, stream_health, xmltv_channel_settings,
+    xmltv_channels, xmltv_sources, xtream_channels,
+};
+use crate::db::DbConnection;
+use crate::server::stream::select_best_quality;
+
+/// Window used for the EPG coverage percentage
+const COVERAGE_WINDOW_HOURS: i64 = 24;
+
+/// CSV header, in the column order of `LineupReportRow::csv_fields`
+const CSV_HEADER: [&str; 12] = [
+    "Channel Number",
+    "Channel",
+    "TVG ID",
+    "Guide Source",
+    "Mapped Streams",
+    "Providers",
+    "Best Quality",
+    "Match Confidence",
+    "Reliability %",
+    "Streams With Health Data",
+    "Guide Hours Ahead",
+    "EPG Coverage 24h %",
+];
+
+/// Health summary of one enabled channel
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupReportRow {
+    pub channel_id: i32,
+    /// Channel number shown in Plex (None without an explicit order)
+    pub channel_number: Option<i32>,
+    pub display_name: String,
+    pub tvg_id: String,
+    /// XMLTV source name, with " + channel feed" when a per-channel feed is set
+    pub guide_source: String,
+    pub mapped_streams: usize,
+    /// Distinct provider accounts among the mapped streams
+    pub providers: usize,
+    /// Best quality across the mapped streams (None without streams)
+    pub best_quality: Option<String>,
+    /// Highest match confidence among the mappings (0.0-1.0)
+    pub match_confidence: Option<f32>,
+    /// Share of streams with health data whose last attempt succeeded (0-100)
+    pub reliability_percent: Option<f64>,
+    pub streams_with_health: usize,
+    /// Hours of guide data ahead of now
+    pub guide_hours_ahead: f64,
+    /// Share of the next 24 hours covered by programmes (0-100)
+    pub coverage_percent: f64,
+}
+
+impl LineupReportRow {
+    fn csv_fields(&self) -> Vec<String> {
+        vec![
+            self.channel_number
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            self.display_name.clone(),
+            self.tvg_id.clone(),
+            self.guide_source.clone(),
+            self.mapped_streams.to_string(),
+            self.providers.to_string(),
+            self.best_quality.clone().unwrap_or_default(),
+            self.match_confidence
+                .map(|c| format!("{:.2}", c))
+                .unwrap_or_default(),
+            self.reliability_percent
+                .map(|r| format!("{:.0}", r))
+                .unwrap_or_default(),
+            self.streams_with_health.to_string(),
+            format!("{:.1}", self.guide_hours_ahead),
+            format!("{:.0}", self.coverage_percent),
+        ]
+    }
+}
+
+/// (xmltv_channel_id, xtream_channel_id, account_id, qualities, match_confidence)
+type MappedStreamRow = (i32, i32, i32, Option<String>, Option<f32>);
+
+/// Programme start and end
+type Interval = (DateTime<Utc>, DateTime<Utc>);
+
+fn parse_time(value: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(value)
+        .ok()
+        .map(|dt| dt.with_timezone(&Utc))
+}
+
+/// Share of `[start, end)` covered by the given intervals, in percent
+fn coverage_percent(
+    mut intervals: Vec<Interval>,
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> f64 {
+    let window = (end - start).num_seconds();
+    if window <= 0 {
+        return 0.0;
+    }
+
+    intervals.sort();
+    let mut covered = 0;
+    let mut cursor = start;
+    for (from, to) in intervals {
+        let from = from.max(cursor);
+        let to = to.min(end);
+        if to > from {
+            covered += (to - from).num_seconds();
+            cursor = to;
+        }
+    }
+    covered as f64 * 100.0 / window as f64
+}
+
+/// Build the report for all enabled channels, ordered like the lineup
+pub fn build_lineup_report(
+    conn: &mut SqliteConnection,
+    now: DateTime<Utc>,
+) -> QueryResult<Vec<LineupReportRow>> {
+    let channels: Vec<(i32, String, String, Option<i32>, String)> = xmltv_channels::table
+        .inner_join(xmltv_channel_settings::table)
+        .inner_join(xmltv_sources::table)
+        .filter(xmltv_channel_settings::is_enabled.eq(1))
+        .select((
+            xmltv_channels::id.assume_not_null(),
+            xmltv_channels::display_name,
+            xmltv_channels::channel_id,
+            xmltv_channel_settings::plex_display_order,
+            xmltv_sources::name,
+        ))
+        .load(conn)?;
+    let channel_ids: Vec<i32> = channels.iter().map(|c| c.0).collect();
+
+    let feeds: HashSet<String> = channel_epg_feeds::table
+        .select(channel_epg_feeds::tvg_id)
+        .load::<String>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut streams: HashMap<i32, Vec<MappedStreamRow>> = HashMap::new();
+    for row in channel_mappings::table
+        .inner_join(xtream_channels::table)
+        .filter(channel_mappings::xmltv_channel_id.eq_any(&channel_ids))
+        .select((
+            channel_mappings::xmltv_channel_id,
+            xtream_channels::id.assume_not_null(),
+            xtream_channels::account_id,
+            xtream_channels::qualities,
+            channel_mappings::match_confidence,
+        ))
+        .load::<MappedStreamRow>(conn)?
+    {
+        streams.entry(row.0).or_default().push(row);
+    }
+
+    let stream_ids: Vec<i32> = streams.values().flatten().map(|s| s.1).collect();
+    let health: HashMap<i32, bool> = stream_health::table
+        .filter(stream_health::xtream_channel_id.eq_any(&stream_ids))
+        .select((
+            stream_health::xtream_channel_id,
+            stream_health::last_success_at,
+            stream_health::failing_since,
+        ))
+        .load::<(i32, Option<String>, Option<String>)>(conn)?
+        .into_iter()
+        .map(|(id, success, failing)| (id, success.is_some() && failing.is_none()))
+        .collect();
+
+    let window_start = now;
+    let window_end = now + Duration::hours(COVERAGE_WINDOW_HOURS);
+    let now_text = now.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+    let window_end_text = window_end.format("%Y-%m-%dT%H:%M:%SZ").to_string();
+
+    let guide_until: HashMap<i32, Option<String>> = programs::table
+        .filter(programs::xmltv_channel_id.eq_any(&channel_ids))
+        .group_by(programs::xmltv_channel_id)
+        .select((
+            programs::xmltv_channel_id,
+            diesel::dsl::max(programs::end_time),
+        ))
+        .load::<(i32, Option<String>)>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut upcoming: HashMap<i32, Vec<Interval>> = HashMap::new();
+    for (channel_id, start, end) in programs::table
+        .filter(programs::xmltv_channel_id.eq_any(&channel_ids))
+        .filter(programs::end_time.gt(&now_text))
+        .filter(programs::start_time.lt(&window_end_text))
+        .select((
+            programs::xmltv_channel_id,
+            programs::start_time,
+            programs::end_time,
+        ))
+        .load::<(i32, String, String)>(conn)?
+    {
+        if let (Some(start), Some(end)) = (parse_time(&start), parse_time(&end)) {
+            upcoming.entry(channel_id).or_default().push((start, end));
+        }
+    }
+
+    let mut rows: Vec<LineupReportRow> = channels
+        .into_iter()
+        .map(|(id, display_name, tvg_id, order, source_name)| {
+            let mapped = streams.remove(&id).unwrap_or_default();
+
+            let providers: HashSet<i32> = mapped.iter().map(|s| s.2).collect();
+            let qualities: Vec<String> = mapped
+                .iter()
+                .filter_map(|s| s.3.as_deref())
+                .flat_map(crate::xtream::quality::qualities_from_json)
+                .collect();
+            let best_quality = if mapped.is_empty() {
+                None
+            } else {
+                serde_json::to_string(&qualities)
+                    .ok()
+                    .map(|json| select_best_quality(Some(&json)))
+            };
+            let match_confidence = mapped
+                .iter()
+                .filter_map(|s| s.4)
+                .fold(None, |best: Option<f32>, c| {
+                    Some(best.map_or(c, |b| b.max(c)))
+                });
+
+            let known: Vec<bool> = mapped
+                .iter()
+                .filter_map(|s| health.get(&s.1).copied())
+                .collect();
+            let reliability_percent = if known.is_empty() {
+                None
+            } else {
+                Some(known.iter().filter(|ok| **ok).count() as f64 * 100.0 / known.len() as f64)
+            };
+
+            let guide_hours_ahead = guide_until
+                .get(&id)
+                .and_then(|end| end.as_deref())
+                .and_then(parse_time)
+                .map(|end| ((end - now).num_minutes() as f64 / 60.0).max(0.0))
+                .unwrap_or(0.0);
+            let coverage = coverage_percent(
+                upcoming.remove(&id).unwrap_or_default(),
+                window_start,
+                window_end,
+            );
+
+            let guide_source = if feeds.contains(&tvg_id) {
+                format!("{} + channel feed", source_name)
+            } else {
+                source_name
+            };
+
+            LineupReportRow {
+                channel_id: id,
+                channel_number: order.map(|o| o + 1),
+                display_name,
+                tvg_id,
+                guide_source,
+                mapped_streams: mapped.len(),
+                providers: providers.len(),
+                best_quality,
+                match_confidence,
+                reliability_percent,
+                streams_with_health: known.len(),
+                guide_hours_ahead,
+                coverage_percent: coverage,
+            }
+        })
+        .collect();
+
+    rows.sort_by(|a, b| {
+        (
+            a.channel_number.is_none(),
+            a.channel_number,
+            &a.display_name,
+        )
+            .cmp(&(
+                b.channel_number.is_none(),
+                b.channel_number,
+                &b.display_name,
+            ))
+    });
+    Ok(rows)
+}
+
+/// Quote a CSV field when it contains a separator, quote or line break
+pub(crate) fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render the report as CSV (RFC 4180, header row first)
+pub fn report_to_csv(rows: &[LineupReportRow]) -> String {
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+    for row in rows {
+        let fields: Vec<String> = row.csv_fields().iter().map(|f| csv_field(f)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    csv
+}
+
+/// Get the health report of all enabled channels
+#[tauri::command]
+pub fn get_lineup_report(db: State<DbConnection>) -> Result<Vec<LineupReportRow>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    build_lineup_report(&mut conn, Utc::now())
+        .map_err(|e| format!("Failed to build lineup report: {}", e))
+}
+
+/// Export the lineup health report as CSV
+#[tauri::command]
+pub fn export_lineup_report_csv(db: State<DbConnection>) -> Result<String, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    let rows = build_lineup_report(&mut conn, Utc::now())
+        .map_err(|e| format!("Failed to build lineup report: {}", e))?;
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Lineup report exported: {} channels", rows.len()),
+        None,
+    );
+
+    Ok(report_to_csv(&rows))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec(conn: &mut SqliteConnection, sql: &str) {
+        diesel::sql_query(sql).execute(conn).unwrap();
+    }
+
+    #[test]
+    fn test_build_lineup_report() {
+        let mut conn = crate::db::test_connection();
+        exec(&mut conn, "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Main Guide', 'http://epg.local/guide.xml')");
+        exec(
+            &mut conn,
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES
+            (1, 1, 'news.us', 'News, Live'), (2, 1, 'sport.us', 'Sport'), (3, 1, 'off.us', 'Off')",
+        );
+        exec(&mut conn, "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order) VALUES
+            (1, 1, 0), (2, 1, NULL), (3, 0, 1)");
+        crate::db::seed_account(&mut conn, 1, "A", "http://a.local");
+        crate::db::seed_account(&mut conn, 2, "B", "http://b.local");
+        exec(
+            &mut conn,
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name, qualities) VALUES
+            (10, 1, 100, 'News HD', '[\"HD\"]'), (11, 2, 100, 'News FHD', '[\"FHD\"]')",
+        );
+        exec(&mut conn, "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_primary, stream_priority) VALUES
+            (1, 10, 0.9, 1, 0), (1, 11, 0.95, 0, 1)");
+        exec(
+            &mut conn,
+            "INSERT INTO stream_health (xtream_channel_id, last_success_at, failing_since) VALUES
+            (10, '2026-01-20T11:00:00Z', NULL), (11, NULL, '2026-01-19T00:00:00Z')",
+        );
+        exec(&mut conn, "INSERT INTO channel_epg_feeds (tvg_id, url) VALUES ('news.us', 'http://feed.local/news.xml')");
+        exec(
+            &mut conn,
+            "INSERT INTO programs (xmltv_channel_id, title, start_time, end_time) VALUES
+            (1, 'Morning', '2026-01-20T10:00:00Z', '2026-01-20T18:00:00Z'),
+            (1, 'Evening', '2026-01-20T18:00:00Z', '2026-01-21T00:00:00Z')",
+        );
+
+        let now = parse_time("2026-01-20T12:00:00Z").unwrap();
+        let rows = build_lineup_report(&mut conn, now).unwrap();
+        assert_eq!(rows.len(), 2);
+
+        let news = &rows[0];
+        assert_eq!(news.channel_number, Some(1));
+        assert_eq!(news.guide_source, "Main Guide + channel feed");
+        assert_eq!(news.mapped_streams, 2);
+        assert_eq!(news.providers, 2);
+        assert_eq!(news.best_quality.as_deref(), Some("FHD"));
+        assert_eq!(news.match_confidence, Some(0.95));
+        assert_eq!(news.reliability_percent, Some(50.0));
+        assert_eq!(news.guide_hours_ahead, 12.0);
+        assert_eq!(news.coverage_percent, 50.0);
+
+        let sport = &rows[1];
+        assert_eq!(sport.channel_number, None);
+        assert_eq!(sport.mapped_streams, 0);
+        assert_eq!(sport.best_quality, None);
+        assert_eq!(sport.reliability_percent, None);
+        assert_eq!(sport.coverage_percent, 0.0);
+
+        let csv = report_to_csv(&rows);
+        let lines: Vec<&str> = csv.split("\r\n").collect();
+        assert!(lines[0].starts_with("Channel Number,Channel,TVG ID"));
+        assert!(lines[1].starts_with(
+            "1,\"News, Live\",news.us,Main Guide + channel feed,2,2,FHD,0.95,50,2,12.0,50"
+        ));
+    }
+}
@@ -0,0 +1,115 @@
+//! Channel logo cache Tauri commands
+//!
+//! Exposes the per-output logo mode (remote URL vs local proxy) and a manual
+//! trigger for the logo cache pre-warm job.
+
+use serde::Serialize;
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::server::logos::{self, LogoMode, LogoOutput, LogoPrewarmSummary};
+
+/// Logo mode settings response
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoSettings {
+    /// Logo mode for the M3U playlist ("remote" or "proxy")
+    pub m3u_mode: LogoMode,
+    /// Logo mode for the XMLTV EPG ("remote" or "proxy")
+    pub epg_mode: LogoMode,
+}
+
+/// Get the logo mode for each output
+#[tauri::command]
+pub fn get_logo_settings(db: State<DbConnection>) -> Result<LogoSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(LogoSettings {
+        m3u_mode: logos::get_logo_mode(&mut conn, LogoOutput::M3u),
+        epg_mode: logos::get_logo_mode(&mut conn, LogoOutput::Epg),
+    })
+}
+
+/// Set the logo mode for each output
+///
+/// Accepts "remote" (emit original icon URLs) or "proxy" (emit local `/logo/{key}` URLs).
+#[tauri::command]
+pub fn set_logo_settings(
+    db: State<DbConnection>,
+    m3u_mode: String,
+    epg_mode: String,
+) -> Result<LogoSettings, String> {
+    let m3u = LogoMode::parse(&m3u_mode)
+        .ok_or_else(|| format!("Invalid M3U logo mode: {}", m3u_mode))?;
+    let epg = LogoMode::parse(&epg_mode)
+        .ok_or_else(|| format!("Invalid EPG logo mode: {}", epg_mode))?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    logos::set_logo_mode(&mut conn, LogoOutput::M3u, m3u)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    logos::set_logo_mode(&mut conn, LogoOutput::Epg, epg)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": "logo_mode",
+        "m3uMode": m3u.as_str(),
+        "epgMode": epg.as_str()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Logo mode set to {} (M3U) / {} (EPG)",
+            m3u.as_str(),
+            epg.as_str()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(LogoSettings {
+        m3u_mode: m3u,
+        epg_mode: epg,
+    })
+}
+
+/// Pre-warm the logo cache now
+///
+/// Downloads every logo referenced by the lineup with bounded concurrency and
+/// retries. Logos that still fail are served as a placeholder.
+#[tauri::command]
+pub async fn prewarm_logo_cache(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<LogoPrewarmSummary, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))?;
+    let pool = db.clone_pool();
+
+    let summary = logos::prewarm_logo_cache(&pool, &app_data_dir).await?;
+
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::to_string(&summary).ok();
+        let level = if summary.failed > 0 { "warn" } else { "info" };
+        let _ = log_event_internal(
+            &mut conn,
+            level,
+            "system",
+            &format!(
+                "Logo cache pre-warm: {} fetched, {} already cached, {} failed",
+                summary.fetched, summary.already_cached, summary.failed
+            ),
+            details.as_deref(),
+        );
+    }
+
+    Ok(summary)
+}
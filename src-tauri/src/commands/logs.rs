@@ -5,8 +5,11 @@
 //! These commands allow the frontend to log events, query event history, and manage read state.
 
 use diesel::prelude::*;
+use diesel::sqlite::Sqlite;
 use serde::{Deserialize, Serialize};
-use tauri::State;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
 
 use crate::db::models::{EventLog, NewEventLog};
 use crate::db::schema::{event_log, settings};
@@ -87,10 +90,19 @@ pub fn log_event(
         details,
     };
 
-    diesel::insert_into(event_log::table)
+    let result = diesel::insert_into(event_log::table)
         .values(&new_event)
-        .execute(&mut conn)
-        .map_err(|e| format!("Failed to insert event: {}", e))?;
+        .execute(&mut conn);
+    if new_event.level == "error" {
+        crate::recent_errors::record(&new_event.category, &new_event.message, result.is_ok());
+    }
+    crate::notifications::dispatch(
+        &new_event.level,
+        &new_event.category,
+        &new_event.message,
+        new_event.details.as_deref(),
+    );
+    result.map_err(|e| format!("Failed to insert event: {}", e))?;
 
     // Get the last inserted event
     let event = event_log::table
@@ -128,7 +140,7 @@ pub fn get_events(
     created_before: Option<String>,
 ) -> Result<EventLogResponse, String> {
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
     let limit = limit.unwrap_or(50);
@@ -208,6 +220,201 @@ pub fn get_events(
     })
 }
 
+/// Default page size for `search_events`
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Largest page `search_events` returns
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+/// Filters and page position for `search_events`
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EventSearch {
+    pub level: Option<String>,
+    pub category: Option<String>,
+    pub unread_only: bool,
+    /// Only events at or after this time (ISO 8601)
+    pub created_after: Option<String>,
+    /// Only events before this time (ISO 8601)
+    pub created_before: Option<String>,
+    /// Text to find in the message or details (case-insensitive)
+    pub query: Option<String>,
+    /// `next_cursor` of the previous page; None for the first page
+    pub cursor: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// Event counts per level and per category
+///
+/// Each breakdown applies every filter except its own, so the counts show
+/// how many events picking that level or category would give.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EventCounts {
+    pub by_level: BTreeMap<String, i64>,
+    pub by_category: BTreeMap<String, i64>,
+}
+
+/// One page of `search_events` results
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EventSearchResponse {
+    pub events: Vec<EventLog>,
+    /// Pass as `cursor` to get the next page; None on the last page
+    pub next_cursor: Option<String>,
+    /// Events matching all filters
+    pub total_count: i64,
+    /// Unread events (without filters)
+    pub unread_count: i64,
+    pub counts: EventCounts,
+}
+
+/// Search the event log, newest first.
+///
+/// Unlike `get_events`, pages are fetched with a cursor (the position of the
+/// last event returned), so paging stays fast and stable on large logs while
+/// new events are being added.
+#[tauri::command]
+pub fn search_events(
+    db: State<DbConnection>,
+    search: EventSearch,
+) -> Result<EventSearchResponse, String> {
+    let mut conn = db
+        .get_read_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    search_events_internal(&mut conn, &search)
+}
+
+fn search_events_internal(
+    conn: &mut SqliteConnection,
+    search: &EventSearch,
+) -> Result<EventSearchResponse, String> {
+    let limit = search
+        .limit
+        .unwrap_or(DEFAULT_SEARCH_LIMIT)
+        .clamp(1, MAX_SEARCH_LIMIT);
+    let cursor = match search.cursor.as_deref() {
+        Some(cursor) => Some(parse_cursor(cursor).ok_or("Invalid page cursor")?),
+        None => None,
+    };
+
+    let mut query = filtered_events(search);
+    if let Some((timestamp, id)) = cursor {
+        query = query.filter(
+            event_log::timestamp
+                .lt(timestamp.clone())
+                .or(event_log::timestamp.eq(timestamp).and(event_log::id.lt(id))),
+        );
+    }
+    // One extra row tells whether there is another page
+    let mut events: Vec<EventLog> = query
+        .order((event_log::timestamp.desc(), event_log::id.desc()))
+        .limit(limit + 1)
+        .load(conn)
+        .map_err(|e| format!("Failed to load events: {}", e))?;
+    let next_cursor = if events.len() as i64 > limit {
+        events.truncate(limit as usize);
+        events
+            .last()
+            .and_then(|last| Some(format!("{}|{}", last.id?, last.timestamp)))
+    } else {
+        None
+    };
+
+    let total_count = filtered_events(search)
+        .count()
+        .get_result(conn)
+        .map_err(|e| format!("Failed to count events: {}", e))?;
+    let unread_count = event_log::table
+        .filter(event_log::is_read.eq(0))
+        .count()
+        .get_result(conn)
+        .map_err(|e| format!("Failed to count unread events: {}", e))?;
+
+    // Boxed queries cannot be grouped, so the filters go in a subquery
+    let level_filters = EventSearch {
+        level: None,
+        ..search.clone()
+    };
+    let by_level = event_log::table
+        .filter(event_log::id.eq_any(filtered_events(&level_filters).select(event_log::id)))
+        .group_by(event_log::level)
+        .select((event_log::level, diesel::dsl::count_star()))
+        .load::<(String, i64)>(conn)
+        .map_err(|e| format!("Failed to count events: {}", e))?;
+    let category_filters = EventSearch {
+        category: None,
+        ..search.clone()
+    };
+    let by_category = event_log::table
+        .filter(event_log::id.eq_any(filtered_events(&category_filters).select(event_log::id)))
+        .group_by(event_log::category)
+        .select((event_log::category, diesel::dsl::count_star()))
+        .load::<(String, i64)>(conn)
+        .map_err(|e| format!("Failed to count events: {}", e))?;
+
+    Ok(EventSearchResponse {
+        events,
+        next_cursor,
+        total_count,
+        unread_count,
+        counts: EventCounts {
+            by_level: by_level.into_iter().collect(),
+            by_category: by_category.into_iter().collect(),
+        },
+    })
+}
+
+/// Events matching the filters of `search` (not the cursor)
+fn filtered_events(search: &EventSearch) -> event_log::BoxedQuery<'static, Sqlite> {
+    let mut query = event_log::table.into_boxed();
+    if let Some(level) = &search.level {
+        query = query.filter(event_log::level.eq(level.clone()));
+    }
+    if let Some(category) = &search.category {
+        query = query.filter(event_log::category.eq(category.clone()));
+    }
+    if search.unread_only {
+        query = query.filter(event_log::is_read.eq(0));
+    }
+    if let Some(after) = &search.created_after {
+        query = query.filter(event_log::timestamp.ge(after.clone()));
+    }
+    if let Some(before) = &search.created_before {
+        query = query.filter(event_log::timestamp.lt(before.clone()));
+    }
+    let text = search.query.as_deref().map(str::trim);
+    if let Some(text) = text.filter(|t| !t.is_empty()) {
+        let pattern = format!("%{}%", escape_like(text));
+        query = query.filter(
+            event_log::message
+                .like(pattern.clone())
+                .escape('\\')
+                .or(event_log::details.like(pattern).escape('\\')),
+        );
+    }
+    query
+}
+
+/// Escape LIKE wildcards so the text matches literally
+fn escape_like(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if matches!(c, '%' | '_' | '\\') {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+/// Position encoded by `search_events` as `<id>|<timestamp>`
+fn parse_cursor(cursor: &str) -> Option<(String, i32)> {
+    let (id, timestamp) = cursor.split_once('|')?;
+    Some((timestamp.to_string(), id.parse().ok()?))
+}
+
 /// Get the count of unread events.
 ///
 /// # Returns
@@ -311,6 +518,63 @@ pub fn clear_old_events(db: State<DbConnection>, keep_count: Option<i64>) -> Res
     Ok(count)
 }
 
+/// Get the latest error-level events from memory, newest first.
+///
+/// Answered without a database query, so it also works when the database is
+/// what is failing.
+#[tauri::command]
+pub fn get_recent_errors() -> Vec<crate::recent_errors::RecentError> {
+    crate::recent_errors::recent()
+}
+
+/// Forget the recent errors and reset the tray tooltip.
+///
+/// The errors stay in the event log.
+#[tauri::command]
+pub fn clear_recent_errors() {
+    crate::recent_errors::clear();
+}
+
+/// Get the path of the current diagnostic log file.
+///
+/// Returns the log folder if no file has been written yet.
+#[tauri::command]
+pub fn get_log_file_path(app: AppHandle) -> Result<String, String> {
+    let dir = crate::logging::log_dir(&app_data_dir(&app)?);
+    let path = crate::logging::current_log_file(&dir).unwrap_or(dir);
+    Ok(path.to_string_lossy().into_owned())
+}
+
+/// Zip the diagnostic log files into `path`, e.g. to attach to a bug report.
+///
+/// Returns how many log files were added.
+#[tauri::command]
+pub async fn export_logs(app: AppHandle, path: String) -> Result<usize, String> {
+    let path = PathBuf::from(path);
+    if !path.is_absolute() || path.file_name().is_none() {
+        return Err("Export path must be an absolute file path".to_string());
+    }
+    if !path.parent().is_some_and(Path::is_dir) {
+        return Err("Export folder does not exist".to_string());
+    }
+    let dir = crate::logging::log_dir(&app_data_dir(&app)?);
+
+    tauri::async_runtime::spawn_blocking(move || {
+        crate::logging::export_logs(&dir, &path).map_err(|e| {
+            let _ = std::fs::remove_file(&path);
+            format!("Failed to export logs: {}", e)
+        })
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())
+}
+
 /// Internal function to log an event (for use by other Rust code).
 /// Does not require Tauri state, takes a connection directly.
 ///
@@ -324,6 +588,28 @@ pub fn log_event_internal(
     category: &str,
     message: &str,
     details: Option<&str>,
+) -> Result<(), diesel::result::Error> {
+    record_event(conn, level, category, message, details, true)
+}
+
+/// Log an event like `log_event_internal`, without sending a notification
+pub fn log_event_without_notification(
+    conn: &mut diesel::SqliteConnection,
+    level: &str,
+    category: &str,
+    message: &str,
+    details: Option<&str>,
+) -> Result<(), diesel::result::Error> {
+    record_event(conn, level, category, message, details, false)
+}
+
+fn record_event(
+    conn: &mut diesel::SqliteConnection,
+    level: &str,
+    category: &str,
+    message: &str,
+    details: Option<&str>,
+    notify: bool,
 ) -> Result<(), diesel::result::Error> {
     // Story 6-3: Check verbosity setting for info level events
     if level == "info" {
@@ -344,9 +630,18 @@ pub fn log_event_internal(
         details: details.map(|s| s.to_string()),
     };
 
-    diesel::insert_into(event_log::table)
+    let result = diesel::insert_into(event_log::table)
         .values(&new_event)
-        .execute(conn)?;
+        .execute(conn);
+
+    // Kept in memory too, so errors show even when the event log can't be read
+    if level == "error" {
+        crate::recent_errors::record(category, message, result.is_ok());
+    }
+    if notify {
+        crate::notifications::dispatch(level, category, message, details);
+    }
+    result?;
 
     Ok(())
 }
@@ -367,10 +662,10 @@ pub fn log_provider_event(
 // ============================================================================
 
 /// Log verbosity setting key
-const LOG_VERBOSITY_KEY: &str = "log_verbosity";
+pub(crate) const LOG_VERBOSITY_KEY: &str = "log_verbosity";
 
 /// Default log verbosity (verbose = log all events including info)
-const DEFAULT_LOG_VERBOSITY: &str = "verbose";
+pub(crate) const DEFAULT_LOG_VERBOSITY: &str = "verbose";
 
 /// Get the current log verbosity setting from the database.
 ///
@@ -379,7 +674,7 @@ const DEFAULT_LOG_VERBOSITY: &str = "verbose";
 /// Returns "verbose" (default) or "minimal".
 /// - "verbose": All events (info, warn, error) are logged
 /// - "minimal": Only warn and error events are logged (info filtered out)
-fn get_log_verbosity_internal(
+pub(crate) fn get_log_verbosity_internal(
     conn: &mut diesel::SqliteConnection,
 ) -> Result<String, diesel::result::Error> {
     let result = settings::table
@@ -434,12 +729,136 @@ pub fn set_log_verbosity(db: State<DbConnection>, verbosity: String) -> Result<(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
-    let setting = Setting::new(LOG_VERBOSITY_KEY.to_string(), verbosity);
+    let setting = Setting::new(LOG_VERBOSITY_KEY.to_string(), verbosity.clone());
 
     diesel::replace_into(settings::table)
         .values(&setting)
         .execute(&mut conn)
         .map_err(|e| format!("Failed to set log verbosity: {}", e))?;
 
+    crate::logging::apply_verbosity(&verbosity);
     Ok(())
 }
+
+/// Get the per-channel failover notification cooldown in seconds.
+///
+/// Failovers on the same channel within this window are summarized in one
+/// event instead of one event each. 0 means every failover is logged.
+#[tauri::command]
+pub fn get_failover_notification_cooldown(db: State<DbConnection>) -> Result<u64, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(crate::server::failover::get_failover_notification_cooldown(&mut conn).as_secs())
+}
+
+/// Set the per-channel failover notification cooldown in seconds.
+///
+/// # Arguments
+///
+/// * `seconds` - Cooldown window (0 to 86400, 0 disables the cooldown)
+#[tauri::command]
+pub fn set_failover_notification_cooldown(db: State<DbConnection>, seconds: u64) -> Result<(), String> {
+    use crate::server::failover::{
+        FAILOVER_NOTIFICATION_COOLDOWN_KEY, MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS,
+    };
+
+    if seconds > MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS {
+        return Err(format!(
+            "Cooldown must be at most {} seconds",
+            MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS
+        ));
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(FAILOVER_NOTIFICATION_COOLDOWN_KEY, seconds.to_string()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to set failover notification cooldown: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": FAILOVER_NOTIFICATION_COOLDOWN_KEY,
+        "value": seconds
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: Failover notification cooldown set to {}s", seconds),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_search_events_filters_counts_and_pages() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO event_log (id, timestamp, level, category, message, details) VALUES
+             (1, '2026-03-01 10:00:00', 'info', 'epg', 'EPG refreshed', NULL),
+             (2, '2026-03-01 11:00:00', 'warn', 'stream', 'Failover on BBC One', '{\"reason\":\"timeout\"}'),
+             (3, '2026-03-01 11:00:00', 'error', 'stream', 'Stream failed', '{\"channel\":\"BBC One\"}'),
+             (4, '2026-03-02 09:00:00', 'info', 'stream', '100% buffered', NULL)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let search = EventSearch {
+            query: Some("bbc".to_string()),
+            ..Default::default()
+        };
+        let result = search_events_internal(&mut conn, &search).unwrap();
+        let ids: Vec<_> = result.events.iter().filter_map(|e| e.id).collect();
+        assert_eq!(ids, [3, 2]);
+        assert_eq!(result.counts.by_level["warn"], 1);
+        assert_eq!(result.counts.by_category["stream"], 2);
+
+        // Wildcards match literally
+        let search = EventSearch {
+            query: Some("0%".to_string()),
+            ..Default::default()
+        };
+        let result = search_events_internal(&mut conn, &search).unwrap();
+        assert_eq!(result.total_count, 1);
+
+        // Level counts ignore the level filter, the rest apply
+        let search = EventSearch {
+            level: Some("error".to_string()),
+            category: Some("stream".to_string()),
+            ..Default::default()
+        };
+        let result = search_events_internal(&mut conn, &search).unwrap();
+        assert_eq!(result.total_count, 1);
+        assert_eq!(result.counts.by_level.len(), 3);
+        assert_eq!(result.counts.by_category["stream"], 1);
+        assert!(!result.counts.by_category.contains_key("epg"));
+
+        // Pages follow (timestamp, id) through equal timestamps
+        let mut search = EventSearch {
+            limit: Some(2),
+            ..Default::default()
+        };
+        let mut pages = Vec::new();
+        loop {
+            let page = search_events_internal(&mut conn, &search).unwrap();
+            pages.push(page.events.iter().filter_map(|e| e.id).collect::<Vec<_>>());
+            match page.next_cursor {
+                Some(cursor) => search.cursor = Some(cursor),
+                None => break,
+            }
+        }
+        assert_eq!(pages, [vec![4, 3], vec![2, 1]]);
+
+        search.cursor = Some("not a cursor".to_string());
+        assert!(search_events_internal(&mut conn, &search).is_err());
+    }
+}
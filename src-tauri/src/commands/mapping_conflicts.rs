@@ -0,0 +1,108 @@
+//! Mapping conflict Tauri commands
+//!
+//! Lists streams used as the primary stream of several channels, resolves
+//! them, and configures the policy in `crate::mapping_conflicts`.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::mapping_conflicts::{
+    self, ConflictPolicy, MappingConflict, MAPPING_CONFLICT_POLICY_KEY,
+};
+
+/// List streams that are the primary stream of more than one channel
+#[tauri::command]
+pub fn get_mapping_conflicts(db: State<DbConnection>) -> Result<Vec<MappingConflict>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    mapping_conflicts::find_conflicts(&mut conn)
+        .map_err(|e| format!("Failed to load mapping conflicts: {}", e))
+}
+
+/// Resolve a conflict by choosing the channel that keeps the stream
+///
+/// The stream becomes a backup on every other channel, whose next backup is
+/// promoted to primary. Returns the remaining conflicts.
+#[tauri::command]
+pub fn resolve_mapping_conflict(
+    db: State<DbConnection>,
+    xtream_channel_id: i32,
+    owner_xmltv_channel_id: i32,
+) -> Result<Vec<MappingConflict>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let result =
+        mapping_conflicts::resolve_conflict(&mut conn, xtream_channel_id, owner_xmltv_channel_id);
+    let demoted = match result {
+        Ok(demoted) => demoted,
+        Err(diesel::result::Error::NotFound) => {
+            return Err("The chosen channel does not use this stream as its primary".to_string())
+        }
+        Err(e) => return Err(format!("Failed to resolve mapping conflict: {}", e)),
+    };
+
+    let details = serde_json::json!({
+        "xtreamChannelId": xtream_channel_id,
+        "ownerXmltvChannelId": owner_xmltv_channel_id,
+        "demotedChannels": demoted,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "match",
+        &format!(
+            "Mapping conflict resolved: stream demoted to backup on {} channel(s)",
+            demoted
+        ),
+        Some(&details.to_string()),
+    );
+
+    mapping_conflicts::find_conflicts(&mut conn)
+        .map_err(|e| format!("Failed to load mapping conflicts: {}", e))
+}
+
+/// Get the mapping conflict policy ("allow", "warn" or "prevent")
+#[tauri::command]
+pub fn get_mapping_conflict_policy(db: State<DbConnection>) -> Result<ConflictPolicy, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(mapping_conflicts::load_policy(&mut conn))
+}
+
+/// Set the mapping conflict policy
+#[tauri::command]
+pub fn set_mapping_conflict_policy(
+    db: State<DbConnection>,
+    policy: ConflictPolicy,
+) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    mapping_conflicts::save_policy(&mut conn, policy)
+        .map_err(|e| format!("Failed to save mapping conflict policy: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": MAPPING_CONFLICT_POLICY_KEY,
+        "value": policy.as_str()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Mapping conflict policy set to {}",
+            policy.as_str()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,762 @@
+//! Bulk channel mapping CSV Tauri commands
+//!
+//! Fixing hundreds of matches one at a time in the GUI is slow, so the
+//! current mappings can be exported as CSV, corrected in a spreadsheet and
+//! imported again. Each row maps one stream to one channel:
+//!
+//! `channel_id,channel_name,source,account,stream_id,stream_name,priority,primary`
+//!
+//! Columns are matched by header name, so they may be reordered or left out;
+//! `channel_id` and one of `stream_id`/`stream_name` are required. On import,
+//! every channel listed in the file has its mappings replaced by the file's
+//! rows as manual mappings, in one transaction; channels not in the file are
+//! left alone. A dry-run preview lists the changes and every row that cannot
+//! be resolved, and import refuses files with errors.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use tauri::State;
+
+use crate::commands::lineup_report::csv_field;
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{
+    accounts, channel_mappings, xmltv_channels, xmltv_sources, xtream_channels,
+};
+use crate::db::DbConnection;
+use crate::mapping_conflicts::{self, ConflictPolicy};
+use crate::server::AppState;
+
+/// CSV header written by the export
+const CSV_HEADER: [&str; 8] = [
+    "channel_id",
+    "channel_name",
+    "source",
+    "account",
+    "stream_id",
+    "stream_name",
+    "priority",
+    "primary",
+];
+
+/// A row that cannot be imported
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingCsvRowError {
+    /// Line in the file (1 = header)
+    pub line: usize,
+    pub message: String,
+}
+
+/// A channel whose streams change on import
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingCsvChange {
+    pub channel_id: String,
+    pub display_name: String,
+    /// Stream names in priority order, primary marked with "*"
+    pub before: Vec<String>,
+    pub after: Vec<String>,
+}
+
+/// Dry run of a mapping CSV import
+#[derive(Debug, Clone, Serialize, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingCsvPreview {
+    /// Channels listed in the file
+    pub channels: usize,
+    /// Mappings the file creates
+    pub mappings: usize,
+    /// Existing mappings of listed channels that are not in the file
+    pub mappings_removed: usize,
+    /// Listed channels whose streams, order or primary change
+    pub changes: Vec<MappingCsvChange>,
+    /// Rows that cannot be imported; import is refused while there are any
+    pub errors: Vec<MappingCsvRowError>,
+    /// Problems that do not block the import
+    pub warnings: Vec<String>,
+}
+
+/// Result of a mapping CSV import
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingCsvImportResult {
+    pub channels_updated: usize,
+    pub mappings_created: usize,
+    pub mappings_removed: usize,
+}
+
+/// A mapping the import creates: (xtream channel, priority, primary)
+type PlannedMapping = (i32, i32, bool);
+
+/// A local stream: (id, stream ID, name, account name)
+type LocalStream = (i32, i32, String, String);
+
+/// A local channel: (id, tvg-id, display name, source name)
+type LocalChannel = (i32, String, String, String);
+
+/// A listed stream before priorities are settled: (line, xtream channel,
+/// priority, primary)
+type ListedStream = (usize, i32, Option<i32>, bool);
+
+/// An exported mapping, in `CSV_HEADER` order
+type ExportRow = (
+    String,
+    String,
+    String,
+    String,
+    i32,
+    String,
+    Option<i32>,
+    Option<i32>,
+);
+
+/// One data row of the file, by column name
+struct CsvRow {
+    line: usize,
+    fields: HashMap<String, String>,
+}
+
+impl CsvRow {
+    fn get(&self, column: &str) -> &str {
+        self.fields.get(column).map(|v| v.trim()).unwrap_or("")
+    }
+}
+
+/// Split CSV text into records (RFC 4180: quoted fields may hold separators,
+/// doubled quotes and line breaks), with the line each record starts on
+fn parse_csv(content: &str) -> Vec<(usize, Vec<String>)> {
+    let mut records = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut record_line = 1;
+    let mut chars = content.trim_start_matches('\u{feff}').chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.is_empty() => in_quotes = true,
+            '\n' | '\r' if !in_quotes => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                record.push(std::mem::take(&mut field));
+                if record.iter().any(|f| !f.trim().is_empty()) {
+                    records.push((record_line, std::mem::take(&mut record)));
+                }
+                record.clear();
+                line += 1;
+                record_line = line;
+            }
+            ',' if !in_quotes => record.push(std::mem::take(&mut field)),
+            _ => {
+                if c == '\n' {
+                    line += 1;
+                }
+                field.push(c);
+            }
+        }
+    }
+    record.push(field);
+    if record.iter().any(|f| !f.trim().is_empty()) {
+        records.push((record_line, record));
+    }
+    records
+}
+
+/// Parse the file into rows keyed by lowercase column name
+fn parse_rows(content: &str) -> Result<Vec<CsvRow>, String> {
+    let mut records = parse_csv(content).into_iter();
+    let Some((_, header)) = records.next() else {
+        return Err("The file is empty".to_string());
+    };
+    let header: Vec<String> = header.iter().map(|h| h.trim().to_lowercase()).collect();
+    if !header.iter().any(|h| h == "channel_id") {
+        return Err("Missing required column: channel_id".to_string());
+    }
+    if !header
+        .iter()
+        .any(|h| h == "stream_id" || h == "stream_name")
+    {
+        return Err("Missing required column: stream_id or stream_name".to_string());
+    }
+
+    Ok(records
+        .map(|(line, values)| CsvRow {
+            line,
+            fields: header.iter().cloned().zip(values).collect(),
+        })
+        .collect())
+}
+
+/// Parse a yes/no cell (empty = no)
+fn parse_flag(value: &str) -> Option<bool> {
+    match value.to_lowercase().as_str() {
+        "" | "false" | "no" | "n" | "0" => Some(false),
+        "true" | "yes" | "y" | "1" | "*" => Some(true),
+        _ => None,
+    }
+}
+
+/// Render the current mappings as CSV, ordered by channel and priority
+pub fn mappings_to_csv(conn: &mut SqliteConnection) -> QueryResult<(String, usize)> {
+    let rows: Vec<ExportRow> = channel_mappings::table
+        .inner_join(xmltv_channels::table.inner_join(xmltv_sources::table))
+        .inner_join(xtream_channels::table.inner_join(accounts::table))
+        .order((
+            xmltv_channels::channel_id.asc(),
+            xmltv_sources::name.asc(),
+            channel_mappings::stream_priority.asc(),
+            channel_mappings::is_primary.desc(),
+        ))
+        .select((
+            xmltv_channels::channel_id,
+            xmltv_channels::display_name,
+            xmltv_sources::name,
+            accounts::name,
+            xtream_channels::stream_id,
+            xtream_channels::name,
+            channel_mappings::stream_priority,
+            channel_mappings::is_primary,
+        ))
+        .load(conn)?;
+
+    let mut csv = CSV_HEADER.join(",");
+    csv.push_str("\r\n");
+    for (channel_id, channel_name, source, account, stream_id, stream_name, priority, primary) in
+        &rows
+    {
+        let fields = [
+            csv_field(channel_id),
+            csv_field(channel_name),
+            csv_field(source),
+            csv_field(account),
+            stream_id.to_string(),
+            csv_field(stream_name),
+            priority.unwrap_or(0).to_string(),
+            (*primary == Some(1)).to_string(),
+        ];
+        csv.push_str(&fields.join(","));
+        csv.push_str("\r\n");
+    }
+    Ok((csv, rows.len()))
+}
+
+/// Resolve a mapping CSV against the local channels and streams (dry run)
+///
+/// Returns the preview and, per local channel, the mappings to create.
+pub fn plan_mapping_import(
+    conn: &mut SqliteConnection,
+    content: &str,
+) -> Result<(MappingCsvPreview, BTreeMap<i32, Vec<PlannedMapping>>), String> {
+    let rows = parse_rows(content)?;
+    let db_err = |e: diesel::result::Error| format!("Failed to load channels: {}", e);
+
+    let channels: Vec<LocalChannel> = xmltv_channels::table
+        .inner_join(xmltv_sources::table)
+        .select((
+            xmltv_channels::id.assume_not_null(),
+            xmltv_channels::channel_id,
+            xmltv_channels::display_name,
+            xmltv_sources::name,
+        ))
+        .load(conn)
+        .map_err(db_err)?;
+    let streams: Vec<LocalStream> = xtream_channels::table
+        .inner_join(accounts::table)
+        .select((
+            xtream_channels::id.assume_not_null(),
+            xtream_channels::stream_id,
+            xtream_channels::name,
+            accounts::name,
+        ))
+        .load(conn)
+        .map_err(db_err)?;
+
+    let mut preview = MappingCsvPreview::default();
+    let mut listed: BTreeMap<i32, Vec<ListedStream>> = BTreeMap::new();
+
+    for row in &rows {
+        let mut error = |message: String| {
+            preview.errors.push(MappingCsvRowError {
+                line: row.line,
+                message,
+            })
+        };
+
+        let channel_id = row.get("channel_id");
+        if channel_id.is_empty() {
+            error("channel_id is empty".to_string());
+            continue;
+        }
+        let source = row.get("source");
+        let matches: Vec<&LocalChannel> = channels
+            .iter()
+            .filter(|(_, tvg_id, _, _)| tvg_id.eq_ignore_ascii_case(channel_id))
+            .filter(|(_, _, _, name)| source.is_empty() || name.eq_ignore_ascii_case(source))
+            .collect();
+        let channel = match matches.as_slice() {
+            [channel] => channel,
+            [] => {
+                error(format!("EPG channel '{}' not found", channel_id));
+                continue;
+            }
+            _ => {
+                error(format!(
+                    "EPG channel '{}' is in {} sources; fill in the source column",
+                    channel_id,
+                    matches.len()
+                ));
+                continue;
+            }
+        };
+
+        let stream_id = row.get("stream_id");
+        let stream_name = row.get("stream_name");
+        let account = row.get("account");
+        let stream_id = if stream_id.is_empty() {
+            None
+        } else {
+            match stream_id.parse::<i32>() {
+                Ok(id) => Some(id),
+                Err(_) => {
+                    error(format!("Invalid stream_id '{}'", stream_id));
+                    continue;
+                }
+            }
+        };
+        if stream_id.is_none() && stream_name.is_empty() {
+            error("stream_id or stream_name is required".to_string());
+            continue;
+        }
+        let candidates: Vec<&LocalStream> = streams
+            .iter()
+            .filter(|(_, _, _, name)| account.is_empty() || name.eq_ignore_ascii_case(account))
+            .filter(|(_, id, name, _)| match stream_id {
+                Some(stream_id) => *id == stream_id,
+                None => name.eq_ignore_ascii_case(stream_name),
+            })
+            .collect();
+        // Several accounts may share a stream ID; the name picks one
+        let by_name: Vec<&LocalStream> = candidates
+            .iter()
+            .copied()
+            .filter(|(_, _, name, _)| name.eq_ignore_ascii_case(stream_name))
+            .collect();
+        let stream = match (candidates.as_slice(), by_name.as_slice()) {
+            ([stream], _) | (_, [stream]) => stream,
+            ([], _) => {
+                error(format!(
+                    "Stream '{}' not found",
+                    stream_id.map_or(stream_name.to_string(), |id| id.to_string())
+                ));
+                continue;
+            }
+            _ => {
+                error(format!(
+                    "Stream '{}' matches {} streams; fill in the account column",
+                    stream_id.map_or(stream_name.to_string(), |id| id.to_string()),
+                    candidates.len()
+                ));
+                continue;
+            }
+        };
+
+        let priority = match row.get("priority") {
+            "" => None,
+            value => match value.parse::<i32>() {
+                Ok(priority) if priority >= 0 => Some(priority),
+                _ => {
+                    error(format!("Invalid priority '{}'", value));
+                    continue;
+                }
+            },
+        };
+        let Some(primary) = parse_flag(row.get("primary")) else {
+            error(format!("Invalid primary value '{}'", row.get("primary")));
+            continue;
+        };
+
+        let entries = listed.entry(channel.0).or_default();
+        if entries.iter().any(|(_, id, _, _)| *id == stream.0) {
+            error(format!(
+                "Stream '{}' is listed twice for '{}'",
+                stream.2, channel.1
+            ));
+            continue;
+        }
+        entries.push((row.line, stream.0, priority, primary));
+    }
+
+    // Priorities default to file order; without a primary the first stream is primary
+    let mut planned: BTreeMap<i32, Vec<PlannedMapping>> = BTreeMap::new();
+    for (channel_id, entries) in &listed {
+        let primaries: Vec<usize> = entries
+            .iter()
+            .filter(|(_, _, _, primary)| *primary)
+            .map(|(line, _, _, _)| *line)
+            .collect();
+        if primaries.len() > 1 {
+            preview.errors.push(MappingCsvRowError {
+                line: primaries[1],
+                message: "Channel has more than one primary stream".to_string(),
+            });
+            continue;
+        }
+        let mut mappings: Vec<PlannedMapping> = entries
+            .iter()
+            .enumerate()
+            .map(|(index, (_, xtream_id, priority, primary))| {
+                (*xtream_id, priority.unwrap_or(index as i32), *primary)
+            })
+            .collect();
+        mappings.sort_by_key(|(_, priority, primary)| (*priority, !*primary));
+        if primaries.is_empty() {
+            mappings[0].2 = true;
+        }
+        planned.insert(*channel_id, mappings);
+    }
+
+    describe_changes(conn, &channels, &streams, &planned, &mut preview).map_err(db_err)?;
+    Ok((preview, planned))
+}
+
+/// Fill the preview's counts, per-channel changes and primary conflicts
+fn describe_changes(
+    conn: &mut SqliteConnection,
+    channels: &[LocalChannel],
+    streams: &[LocalStream],
+    planned: &BTreeMap<i32, Vec<PlannedMapping>>,
+    preview: &mut MappingCsvPreview,
+) -> QueryResult<()> {
+    let existing: Vec<(i32, i32, Option<i32>, Option<i32>)> = channel_mappings::table
+        .order(channel_mappings::stream_priority.asc())
+        .select((
+            channel_mappings::xmltv_channel_id,
+            channel_mappings::xtream_channel_id,
+            channel_mappings::stream_priority,
+            channel_mappings::is_primary,
+        ))
+        .load(conn)?;
+    let stream_name = |id: i32| {
+        streams
+            .iter()
+            .find(|s| s.0 == id)
+            .map_or_else(|| id.to_string(), |s| s.2.clone())
+    };
+    let channel = |id: i32| channels.iter().find(|c| c.0 == id);
+    let label = |(xtream_id, _, primary): &PlannedMapping| {
+        let name = stream_name(*xtream_id);
+        if *primary {
+            format!("{} *", name)
+        } else {
+            name
+        }
+    };
+
+    preview.channels = planned.len();
+    preview.mappings = planned.values().map(Vec::len).sum();
+    for (channel_id, mappings) in planned {
+        let current: Vec<PlannedMapping> = existing
+            .iter()
+            .filter(|m| m.0 == *channel_id)
+            .map(|m| (m.1, m.2.unwrap_or(0), m.3 == Some(1)))
+            .collect();
+        preview.mappings_removed += current
+            .iter()
+            .filter(|(id, _, _)| !mappings.iter().any(|m| m.0 == *id))
+            .count();
+        if current != *mappings {
+            let (tvg_id, display_name) = channel(*channel_id)
+                .map(|c| (c.1.clone(), c.2.clone()))
+                .unwrap_or_default();
+            preview.changes.push(MappingCsvChange {
+                channel_id: tvg_id,
+                display_name,
+                before: current.iter().map(label).collect(),
+                after: mappings.iter().map(label).collect(),
+            });
+        }
+    }
+
+    // Primaries after import: unlisted channels keep theirs
+    let mut owners: BTreeMap<i32, Vec<i32>> = BTreeMap::new();
+    for (channel_id, xtream_id, _, primary) in &existing {
+        if *primary == Some(1) && !planned.contains_key(channel_id) {
+            owners.entry(*xtream_id).or_default().push(*channel_id);
+        }
+    }
+    for (channel_id, mappings) in planned {
+        for (xtream_id, _, primary) in mappings {
+            if *primary {
+                owners.entry(*xtream_id).or_default().push(*channel_id);
+            }
+        }
+    }
+    let policy = mapping_conflicts::load_policy(conn);
+    for (xtream_id, owner_ids) in owners.iter().filter(|(_, o)| o.len() > 1) {
+        if policy == ConflictPolicy::Allow || !owner_ids.iter().any(|id| planned.contains_key(id)) {
+            continue;
+        }
+        let names: Vec<String> = owner_ids
+            .iter()
+            .filter_map(|id| channel(*id).map(|c| c.2.clone()))
+            .collect();
+        let message = format!(
+            "Stream '{}' would be the primary stream of {}",
+            stream_name(*xtream_id),
+            names.join(", ")
+        );
+        if policy == ConflictPolicy::Prevent {
+            preview.errors.push(MappingCsvRowError { line: 0, message });
+        } else {
+            preview.warnings.push(message);
+        }
+    }
+    preview.errors.sort_by_key(|e| e.line);
+    Ok(())
+}
+
+/// Replace the mappings of the planned channels with manual mappings
+pub fn apply_mapping_import(
+    conn: &mut SqliteConnection,
+    planned: &BTreeMap<i32, Vec<PlannedMapping>>,
+) -> QueryResult<(usize, usize)> {
+    conn.transaction(|conn| {
+        let mut removed = 0;
+        let mut created = 0;
+        for (channel_id, mappings) in planned {
+            removed += diesel::delete(
+                channel_mappings::table.filter(channel_mappings::xmltv_channel_id.eq(channel_id)),
+            )
+            .execute(conn)?;
+            let rows: Vec<_> = mappings
+                .iter()
+                .map(|(xtream_id, priority, primary)| {
+                    (
+                        channel_mappings::xmltv_channel_id.eq(*channel_id),
+                        channel_mappings::xtream_channel_id.eq(*xtream_id),
+                        channel_mappings::is_manual.eq(1),
+                        channel_mappings::is_primary.eq(*primary as i32),
+                        channel_mappings::stream_priority.eq(*priority),
+                    )
+                })
+                .collect();
+            created += diesel::insert_into(channel_mappings::table)
+                .values(&rows)
+                .execute(conn)?;
+        }
+        Ok((removed, created))
+    })
+}
+
+/// Export the current channel mappings as CSV
+///
+/// The frontend saves the CSV with Tauri's file dialog.
+#[tauri::command]
+pub fn export_mappings_csv(db: State<DbConnection>) -> Result<String, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let (csv, count) =
+        mappings_to_csv(&mut conn).map_err(|e| format!("Failed to export mappings: {}", e))?;
+
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Channel mappings exported as CSV: {} mappings", count),
+        None,
+    );
+
+    Ok(csv)
+}
+
+/// Check a mapping CSV without changing anything
+#[tauri::command]
+pub fn preview_mappings_csv(
+    db: State<DbConnection>,
+    content: String,
+) -> Result<MappingCsvPreview, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    plan_mapping_import(&mut conn, &content).map(|(preview, _)| preview)
+}
+
+/// Import a mapping CSV, replacing the mappings of the channels it lists
+///
+/// Refused when any row has an error; nothing is changed in that case.
+#[tauri::command]
+pub fn import_mappings_csv(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    content: String,
+) -> Result<MappingCsvImportResult, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let (preview, planned) = plan_mapping_import(&mut conn, &content)?;
+    if let Some(first) = preview.errors.first() {
+        return Err(format!(
+            "The file has {} error(s); first on line {}: {}",
+            preview.errors.len(),
+            first.line,
+            first.message
+        ));
+    }
+
+    let (mappings_removed, mappings_created) = apply_mapping_import(&mut conn, &planned)
+        .map_err(|e| format!("Failed to import mappings: {}", e))?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "channels": planned.len(),
+        "mappingsCreated": mappings_created,
+        "mappingsRemoved": mappings_removed,
+        "changedChannels": preview.changes.len(),
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Channel mappings imported from CSV: {} channels, {} mappings",
+            planned.len(),
+            mappings_created
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(MappingCsvImportResult {
+        channels_updated: planned.len(),
+        mappings_created,
+        mappings_removed,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+
+    fn setup_db() -> SqliteConnection {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://a.local");
+        crate::db::seed_account(&mut conn, 2, "Backup", "http://b.local");
+        conn.batch_execute(
+            "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://example.com/epg.xml');
+             INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES
+                (1, 1, 'news.uk', 'News'), (2, 1, 'film.uk', 'Film, Classics');
+             INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+                (10, 1, 100, 'UK: News HD'), (11, 2, 100, 'UK: News'), (20, 1, 200, 'UK: Film');
+             INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES
+                (1, 20, 0.6, 0, 1, 0), (2, 20, 0.9, 0, 1, 0);",
+        )
+        .unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_parse_csv_quoting() {
+        let records = parse_csv("a,b\r\n\"x, \"\"y\"\"\",\"two\nlines\"\r\n\r\nlast,");
+        assert_eq!(
+            records,
+            vec![
+                (1, vec!["a".to_string(), "b".to_string()]),
+                (2, vec!["x, \"y\"".to_string(), "two\nlines".to_string()]),
+                (5, vec!["last".to_string(), String::new()]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_export_import_round_trip() {
+        let mut conn = setup_db();
+        let (csv, count) = mappings_to_csv(&mut conn).unwrap();
+        assert_eq!(count, 2);
+        assert!(csv.contains("\"Film, Classics\""));
+
+        // Unchanged export is a no-op
+        let (preview, _) = plan_mapping_import(&mut conn, &csv).unwrap();
+        assert!(preview.errors.is_empty());
+        assert!(preview.changes.is_empty());
+        assert_eq!(preview.channels, 2);
+
+        // Fix News: HD stream first, backup account second, reordered columns
+        let fixed = "stream_name,channel_id,account,priority\n\
+                     UK: News HD,news.uk,Main,0\n\
+                     UK: News,NEWS.UK,Backup,1\n";
+        let (preview, planned) = plan_mapping_import(&mut conn, fixed).unwrap();
+        assert!(preview.errors.is_empty(), "{:?}", preview.errors);
+        assert_eq!(preview.changes.len(), 1);
+        assert_eq!(preview.changes[0].before, vec!["UK: Film *"]);
+        assert_eq!(preview.changes[0].after, vec!["UK: News HD *", "UK: News"]);
+        assert_eq!(preview.mappings_removed, 1);
+
+        apply_mapping_import(&mut conn, &planned).unwrap();
+        let mappings: Vec<(i32, i32, Option<i32>, Option<i32>)> = channel_mappings::table
+            .order((
+                channel_mappings::xmltv_channel_id,
+                channel_mappings::stream_priority,
+            ))
+            .select((
+                channel_mappings::xmltv_channel_id,
+                channel_mappings::xtream_channel_id,
+                channel_mappings::is_manual,
+                channel_mappings::is_primary,
+            ))
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(
+            mappings,
+            vec![
+                (1, 10, Some(1), Some(1)),
+                (1, 11, Some(1), Some(0)),
+                (2, 20, Some(0), Some(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_import_reports_row_errors() {
+        let mut conn = setup_db();
+        let csv = "channel_id,stream_id,stream_name,priority,primary\n\
+                   missing.uk,100,,,\n\
+                   news.uk,100,,,\n\
+                   news.uk,999,,,\n\
+                   news.uk,200,,-1,\n\
+                   film.uk,200,,,maybe\n";
+        let (preview, _) = plan_mapping_import(&mut conn, csv).unwrap();
+        let lines: Vec<usize> = preview.errors.iter().map(|e| e.line).collect();
+        assert_eq!(lines, vec![2, 3, 4, 5, 6]);
+        assert!(preview.errors[1].message.contains("account column"));
+
+        assert!(plan_mapping_import(&mut conn, "name,url\nx,y\n").is_err());
+    }
+
+    #[test]
+    fn test_prevent_policy_blocks_shared_primary() {
+        let mut conn = setup_db();
+        mapping_conflicts::save_policy(&mut conn, ConflictPolicy::Prevent).unwrap();
+
+        // Film already uses stream 200 as its primary
+        let csv = "channel_id,stream_id,account\nnews.uk,200,Main\n";
+        let (preview, _) = plan_mapping_import(&mut conn, csv).unwrap();
+        assert_eq!(preview.errors.len(), 1);
+
+        // Moving both channels in the same file resolves it
+        let csv = "channel_id,stream_id,account\nnews.uk,200,Main\nfilm.uk,100,Main\n";
+        let (preview, _) = plan_mapping_import(&mut conn, csv).unwrap();
+        assert!(preview.errors.is_empty());
+    }
+}
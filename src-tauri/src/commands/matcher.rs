@@ -4,22 +4,39 @@
 //! Story 6-3: Channel matching event logging
 
 use diesel::prelude::*;
-use serde::Serialize;
-use tauri::{AppHandle, Emitter, State};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::{AppHandle, Emitter, Manager, State};
 
 use crate::commands::logs::log_event_internal;
-use crate::db::models::{ChannelMapping, XmltvChannel, XmltvChannelSettings, XtreamChannel};
-use crate::db::schema::{settings, xmltv_channels, xtream_channels};
+use crate::db::models::{
+    ChannelMapping, MatchRule, XmltvChannel, XmltvChannelSettings, XtreamChannel,
+};
+use crate::db::schema::{
+    accounts, channel_mappings, match_rules, settings, xmltv_channels, xmltv_sources,
+    xtream_channels,
+};
 use crate::db::{DbConnection, Setting};
+use crate::server::AppState;
 use crate::matcher::{
-    calculate_match_stats, get_channel_mappings as db_get_channel_mappings,
-    get_xmltv_channel_settings as db_get_xmltv_channel_settings, match_channels,
-    save_channel_mappings, MatchConfig, MatchStats,
+    calculate_match_stats, detect_channel_region, detect_stream_region,
+    get_channel_mappings as db_get_channel_mappings,
+    get_xmltv_channel_settings as db_get_xmltv_channel_settings, load_alias_hints,
+    load_icon_match_config, load_match_profiles, load_match_rules, load_name_rules,
+    load_region_match_config, match_channels, save_channel_mappings, save_icon_match_config,
+    save_match_profiles, save_region_match_config, IconMatchConfig, MatchAlgorithm, MatchConfig,
+    MatchConfigOverride, MatchProfiles, MatchRuleInput, MatchStats, NameRules, RegionMatchConfig,
+    ICON_MATCH_KEY, MATCH_PROFILES_KEY, REGION_MATCH_KEY,
 };
 
 /// Default match threshold
 const DEFAULT_MATCH_THRESHOLD: f64 = 0.85;
-const MATCH_THRESHOLD_KEY: &str = "match_threshold";
+pub(crate) const MATCH_THRESHOLD_KEY: &str = "match_threshold";
+pub(crate) const MATCH_ALGORITHM_KEY: &str = "match_algorithm";
+
+/// Most sample names accepted when testing a match rule
+const MAX_RULE_TEST_SAMPLES: usize = 500;
 
 /// Response type for match operations
 #[derive(Serialize)]
@@ -34,6 +51,73 @@ pub struct MatchResponse {
     pub message: String,
 }
 
+/// A sample name normalized with and without match rules
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRuleTestResult {
+    pub name: String,
+    /// Built-in normalization only
+    pub builtin: String,
+    /// With the saved, enabled rules
+    pub current: String,
+    /// With the saved rules plus the rule under test
+    pub with_rule: Option<String>,
+    /// Saved rules that changed the name
+    pub applied_rule_ids: Vec<i32>,
+}
+
+/// A stream in a simulated change
+#[derive(Debug, Clone, Serialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedStream {
+    pub xtream_channel_id: i32,
+    pub name: String,
+    /// Current or simulated confidence (0.0-1.0)
+    pub confidence: Option<f64>,
+    /// Region detected from the stream name or category
+    pub region: Option<String>,
+}
+
+/// A channel whose automatic mappings would change
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulatedChannelChange {
+    pub xmltv_channel_id: i32,
+    pub display_name: String,
+    /// Region detected from the channel ID or name
+    pub region: Option<String>,
+    pub current_primary: Option<SimulatedStream>,
+    pub simulated_primary: Option<SimulatedStream>,
+    /// Streams that would be newly mapped
+    pub added: Vec<SimulatedStream>,
+    /// Streams that would no longer be mapped
+    pub removed: Vec<SimulatedStream>,
+    /// The channel has manual mappings, which a matching run keeps
+    pub has_manual: bool,
+}
+
+/// Outcome of matching with trial settings, compared with the current mappings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchSimulation {
+    /// The settings used, after applying the override
+    pub threshold: f64,
+    pub algorithm: MatchAlgorithm,
+    /// Statistics of the simulated run
+    pub stats: MatchStats,
+    /// Channels with at least one mapping now
+    pub currently_matched: usize,
+    /// Unmatched channels that would get a mapping
+    pub gained: usize,
+    /// Matched channels that would lose all mappings
+    pub lost: usize,
+    /// Mappings that would be created and removed
+    pub mappings_added: usize,
+    pub mappings_removed: usize,
+    /// Channels whose automatic mappings or primary stream would change
+    pub changes: Vec<SimulatedChannelChange>,
+}
+
 /// Run the channel matching algorithm.
 ///
 /// Matches all XMLTV channels to Xtream streams using fuzzy matching.
@@ -50,38 +134,46 @@ pub struct MatchResponse {
 pub async fn run_channel_matching(
     app: AppHandle,
     db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
     threshold: Option<f64>,
 ) -> Result<MatchResponse, String> {
-    // Get threshold from parameter or settings or default
-    let threshold = match threshold {
-        Some(t) => t,
-        None => get_match_threshold_internal(&db)?,
-    };
-
-    // Validate threshold
-    if !(0.0..=1.0).contains(&threshold) {
-        return Err("Threshold must be between 0.0 and 1.0".to_string());
-    }
-
-    let config = MatchConfig::default().with_threshold(threshold);
-
-    // Get database connection
     let mut conn = db
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    let app_data_dir = app.path().app_data_dir().ok();
+    run_channel_matching_internal(&mut conn, threshold, app_data_dir.as_deref(), |progress| {
+        let _ = app.emit("match_progress", progress);
+    })
+    .inspect(|_| server_state.bump_epg_generation())
+}
+
+/// Run the channel matching algorithm on an existing connection.
+///
+/// Shared by the Tauri command and the web admin API. `on_progress` receives
+/// the same payloads as the `match_progress` event. Icons are compared from
+/// the logo cache in `app_data_dir` when icon matching is enabled.
+pub fn run_channel_matching_internal(
+    conn: &mut SqliteConnection,
+    threshold: Option<f64>,
+    app_data_dir: Option<&Path>,
+    on_progress: impl Fn(serde_json::Value),
+) -> Result<MatchResponse, String> {
+    let config = load_match_config(conn, threshold, app_data_dir)?;
+    let threshold = config.threshold;
+
     // Load all XMLTV channels
     let xmltv_channels: Vec<XmltvChannel> = xmltv_channels::table
-        .load::<XmltvChannel>(&mut conn)
+        .load::<XmltvChannel>(conn)
         .map_err(|e| format!("Failed to load XMLTV channels: {}", e))?;
 
     // Load all Xtream channels
     let xtream_channels: Vec<XtreamChannel> = xtream_channels::table
-        .load::<XtreamChannel>(&mut conn)
+        .load::<XtreamChannel>(conn)
         .map_err(|e| format!("Failed to load Xtream channels: {}", e))?;
 
     // Emit progress event: starting
-    let _ = app.emit("match_progress", serde_json::json!({
+    on_progress(serde_json::json!({
         "status": "starting",
         "message": format!("Starting match: {} XMLTV channels, {} Xtream streams",
             xmltv_channels.len(), xtream_channels.len())
@@ -91,7 +183,7 @@ pub async fn run_channel_matching(
     let (matches, stats) = match_channels(&xmltv_channels, &xtream_channels, &config);
 
     // Emit progress event: saving
-    let _ = app.emit("match_progress", serde_json::json!({
+    on_progress(serde_json::json!({
         "status": "saving",
         "message": format!("Saving {} matches to database", matches.len())
     }));
@@ -103,11 +195,14 @@ pub async fn run_channel_matching(
         .collect();
 
     // Save to database
-    let saved_count = save_channel_mappings(&mut conn, &matches, &xmltv_ids)
+    let saved_count = save_channel_mappings(conn, &matches, &xmltv_ids)
         .map_err(|e| format!("Failed to save channel mappings: {}", e))?;
 
+    let conflict_count = crate::mapping_conflicts::enforce_after_matching(conn)
+        .map_err(|e| format!("Failed to check mapping conflicts: {}", e))?;
+
     // Emit progress event: complete
-    let _ = app.emit("match_progress", serde_json::json!({
+    on_progress(serde_json::json!({
         "status": "complete",
         "matched": stats.matched,
         "unmatched": stats.unmatched
@@ -122,9 +217,10 @@ pub async fn run_channel_matching(
         "threshold": threshold,
         "durationMs": stats.duration_ms,
         "mappingsSaved": saved_count,
+        "mappingConflicts": conflict_count,
     });
     let _ = log_event_internal(
-        &mut conn,
+        conn,
         "info",
         "match",
         &format!(
@@ -151,10 +247,163 @@ pub async fn run_channel_matching(
     })
 }
 
+/// Match with trial settings without saving anything.
+///
+/// Runs the matcher in memory against the current channels with the saved
+/// settings, overridden by `config_override`, and compares the result with the
+/// current mappings. Manual mappings are kept by a real run, so only automatic
+/// mappings are compared; conflict resolution under the `prevent` policy is
+/// not simulated.
+#[tauri::command]
+pub async fn simulate_matching(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    config_override: Option<MatchConfigOverride>,
+) -> Result<MatchSimulation, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let app_data_dir = app.path().app_data_dir().ok();
+    let config = load_match_config(&mut conn, None, app_data_dir.as_deref())?;
+    let config = config_override.unwrap_or_default().apply(config)?;
+    simulate_matching_internal(&mut conn, &config)
+}
+
+/// Current mapping: (xmltv channel, xtream channel, confidence, primary, manual)
+type MappingRow = (i32, i32, Option<f32>, Option<i32>, Option<i32>);
+
+/// Match with `config` and compare the result with the current mappings
+pub fn simulate_matching_internal(
+    conn: &mut SqliteConnection,
+    config: &MatchConfig,
+) -> Result<MatchSimulation, String> {
+    let xmltv_channels: Vec<XmltvChannel> = xmltv_channels::table
+        .load::<XmltvChannel>(conn)
+        .map_err(|e| format!("Failed to load XMLTV channels: {}", e))?;
+    let xtream_channels: Vec<XtreamChannel> = xtream_channels::table
+        .load::<XtreamChannel>(conn)
+        .map_err(|e| format!("Failed to load Xtream channels: {}", e))?;
+    let mappings: Vec<MappingRow> = channel_mappings::table
+        .order(channel_mappings::stream_priority.asc())
+        .select((
+            channel_mappings::xmltv_channel_id,
+            channel_mappings::xtream_channel_id,
+            channel_mappings::match_confidence,
+            channel_mappings::is_primary,
+            channel_mappings::is_manual,
+        ))
+        .load(conn)
+        .map_err(|e| format!("Failed to load channel mappings: {}", e))?;
+
+    let (matches, stats) = match_channels(&xmltv_channels, &xtream_channels, config);
+
+    let streams_by_id: HashMap<i32, &XtreamChannel> = xtream_channels
+        .iter()
+        .filter_map(|c| c.id.map(|id| (id, c)))
+        .collect();
+    let stream = |id: i32, confidence: Option<f64>| {
+        let channel = streams_by_id.get(&id);
+        SimulatedStream {
+            xtream_channel_id: id,
+            name: channel.map(|c| c.name.clone()).unwrap_or_default(),
+            confidence,
+            region: channel
+                .and_then(|c| detect_stream_region(&c.name, c.category_name.as_deref()))
+                .map(str::to_string),
+        }
+    };
+    let manual: HashSet<i32> = mappings
+        .iter()
+        .filter(|m| m.4 == Some(1))
+        .map(|m| m.0)
+        .collect();
+    let currently_mapped: HashSet<i32> = mappings.iter().map(|m| m.0).collect();
+    let simulated_mapped: HashSet<i32> = matches.iter().map(|m| m.xmltv_channel_id).collect();
+
+    let mut simulation = MatchSimulation {
+        threshold: config.threshold,
+        algorithm: config.algorithm,
+        stats,
+        currently_matched: currently_mapped.len(),
+        gained: 0,
+        lost: 0,
+        mappings_added: 0,
+        mappings_removed: 0,
+        changes: Vec::new(),
+    };
+
+    for channel in &xmltv_channels {
+        let Some(channel_id) = channel.id else {
+            continue;
+        };
+        let current: Vec<_> = mappings
+            .iter()
+            .filter(|m| m.0 == channel_id && m.4 != Some(1))
+            .collect();
+        let simulated: Vec<_> = matches
+            .iter()
+            .filter(|m| m.xmltv_channel_id == channel_id)
+            .collect();
+
+        let has_manual = manual.contains(&channel_id);
+        let now = currently_mapped.contains(&channel_id);
+        let after = has_manual || simulated_mapped.contains(&channel_id);
+        if !now && after {
+            simulation.gained += 1;
+        } else if now && !after {
+            simulation.lost += 1;
+        }
+
+        let added: Vec<SimulatedStream> = simulated
+            .iter()
+            .filter(|m| !current.iter().any(|c| c.1 == m.xtream_channel_id))
+            .map(|m| stream(m.xtream_channel_id, Some(m.confidence)))
+            .collect();
+        let removed: Vec<SimulatedStream> = current
+            .iter()
+            .filter(|c| !simulated.iter().any(|m| m.xtream_channel_id == c.1))
+            .map(|c| stream(c.1, c.2.map(f64::from)))
+            .collect();
+        let current_primary = current
+            .iter()
+            .find(|c| c.3 == Some(1))
+            .map(|c| stream(c.1, c.2.map(f64::from)));
+        let simulated_primary = simulated
+            .iter()
+            .find(|m| m.is_primary)
+            .map(|m| stream(m.xtream_channel_id, Some(m.confidence)));
+        let primary_changed = current_primary.as_ref().map(|s| s.xtream_channel_id)
+            != simulated_primary.as_ref().map(|s| s.xtream_channel_id);
+
+        if added.is_empty() && removed.is_empty() && !primary_changed {
+            continue;
+        }
+        simulation.mappings_added += added.len();
+        simulation.mappings_removed += removed.len();
+        simulation.changes.push(SimulatedChannelChange {
+            xmltv_channel_id: channel_id,
+            display_name: channel.display_name.clone(),
+            region: detect_channel_region(&channel.display_name, &channel.channel_id)
+                .map(str::to_string),
+            current_primary,
+            simulated_primary,
+            added,
+            removed,
+            has_manual,
+        });
+    }
+    simulation
+        .changes
+        .sort_by(|a, b| a.display_name.cmp(&b.display_name));
+
+    Ok(simulation)
+}
+
 /// Get current match statistics from the database.
 #[tauri::command]
 pub fn get_match_stats(db: State<DbConnection>) -> Result<MatchStats, String> {
-    let pool = db.clone_pool();
+    let pool = db.clone_read_pool();
     calculate_match_stats(&pool)
         .map_err(|e| format!("Failed to calculate match stats: {}", e))
 }
@@ -222,6 +471,240 @@ pub fn set_match_threshold(db: State<DbConnection>, threshold: f64) -> Result<()
     Ok(())
 }
 
+/// Get the string similarity algorithm used for matching.
+#[tauri::command]
+pub fn get_match_algorithm(db: State<DbConnection>) -> Result<MatchAlgorithm, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(load_match_algorithm(&mut conn))
+}
+
+/// Set the string similarity algorithm used for matching.
+///
+/// Takes effect on the next matching run.
+#[tauri::command]
+pub fn set_match_algorithm(
+    db: State<DbConnection>,
+    algorithm: MatchAlgorithm,
+) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(MATCH_ALGORITHM_KEY, algorithm.as_str()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to save algorithm: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": MATCH_ALGORITHM_KEY,
+        "value": algorithm
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Match algorithm set to {}",
+            algorithm.as_str()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Where a match profile applies
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchProfileScope {
+    /// Streams of one Xtream account
+    Account,
+    /// Channels of one XMLTV source
+    Source,
+}
+
+/// Get the per-account and per-source match profiles.
+#[tauri::command]
+pub fn get_match_profiles(db: State<DbConnection>) -> Result<MatchProfiles, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(load_match_profiles(&mut conn))
+}
+
+/// Set or remove the match profile of an Xtream account or XMLTV source.
+///
+/// Unset fields fall back to the source profile (for accounts) and the global
+/// settings; a profile with no fields set is removed. Takes effect on the next
+/// matching run.
+#[tauri::command]
+pub fn set_match_profile(
+    db: State<DbConnection>,
+    scope: MatchProfileScope,
+    id: i32,
+    profile: Option<MatchConfigOverride>,
+) -> Result<MatchProfiles, String> {
+    let profile = profile.filter(|p| !p.is_empty());
+    if let Some(profile) = &profile {
+        profile.validate()?;
+    }
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let (label, name) = match scope {
+        MatchProfileScope::Account => (
+            "account",
+            accounts::table
+                .filter(accounts::id.eq(id))
+                .select(accounts::name)
+                .first::<String>(&mut conn)
+                .map_err(|_| "Account not found".to_string())?,
+        ),
+        MatchProfileScope::Source => (
+            "source",
+            xmltv_sources::table
+                .filter(xmltv_sources::id.eq(id))
+                .select(xmltv_sources::name)
+                .first::<String>(&mut conn)
+                .map_err(|_| "XMLTV source not found".to_string())?,
+        ),
+    };
+
+    let mut profiles = load_match_profiles(&mut conn);
+    let scoped = match scope {
+        MatchProfileScope::Account => &mut profiles.accounts,
+        MatchProfileScope::Source => &mut profiles.sources,
+    };
+    match &profile {
+        Some(profile) => scoped.insert(id, profile.clone()),
+        None => scoped.remove(&id),
+    };
+    save_match_profiles(&mut conn, &profiles)?;
+
+    let details = serde_json::json!({
+        "setting": MATCH_PROFILES_KEY,
+        "scope": label,
+        "id": id,
+        "profile": profile
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Match profile for {} '{}' {}",
+            label,
+            name,
+            if profile.is_some() {
+                "saved"
+            } else {
+                "removed"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(profiles)
+}
+
+/// Get the icon similarity matching settings.
+#[tauri::command]
+pub fn get_icon_match_config(db: State<DbConnection>) -> Result<IconMatchConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(load_icon_match_config(&mut conn))
+}
+
+/// Set the icon similarity matching settings.
+///
+/// Takes effect on the next matching run; the logo pre-warm job starts
+/// caching channel and stream icons once enabled.
+#[tauri::command]
+pub fn set_icon_match_config(
+    db: State<DbConnection>,
+    config: IconMatchConfig,
+) -> Result<IconMatchConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    save_icon_match_config(&mut conn, &config)?;
+
+    let details = serde_json::json!({
+        "setting": ICON_MATCH_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Icon similarity matching {}",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
+
+/// Get the region-aware matching settings.
+#[tauri::command]
+pub fn get_region_match_config(db: State<DbConnection>) -> Result<RegionMatchConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(load_region_match_config(&mut conn))
+}
+
+/// Set the region-aware matching settings.
+///
+/// Takes effect on the next matching run.
+#[tauri::command]
+pub fn set_region_match_config(
+    db: State<DbConnection>,
+    config: RegionMatchConfig,
+) -> Result<RegionMatchConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    save_region_match_config(&mut conn, &config)?;
+
+    let details = serde_json::json!({
+        "setting": REGION_MATCH_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Region-aware matching {}",
+            if config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
+
 /// Normalize a channel name (exposed for testing/debugging).
 #[tauri::command]
 pub fn normalize_channel_name(name: String) -> String {
@@ -242,20 +725,247 @@ pub fn calculate_match_score(
         &xtream_name,
         epg_id_match,
         exact_name_match,
+        false,
         &config,
     )
 }
 
+/// List the user-defined match rules in the order they apply.
+#[tauri::command]
+pub fn list_match_rules(db: State<DbConnection>) -> Result<Vec<MatchRule>, String> {
+    let mut conn = db
+        .get_read_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    load_match_rules(&mut conn).map_err(|e| format!("Failed to load match rules: {}", e))
+}
+
+/// Add a match rule.
+///
+/// Takes effect on the next matching run.
+#[tauri::command]
+pub fn add_match_rule(db: State<DbConnection>, rule: MatchRuleInput) -> Result<MatchRule, String> {
+    let new_rule = rule.validate()?;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let saved: MatchRule = diesel::insert_into(match_rules::table)
+        .values(&new_rule)
+        .get_result(&mut conn)
+        .map_err(|e| format!("Failed to save match rule: {}", e))?;
+
+    log_match_rule_change(&mut conn, "added", &saved);
+    Ok(saved)
+}
+
+/// Replace a match rule.
+#[tauri::command]
+pub fn update_match_rule(
+    db: State<DbConnection>,
+    rule_id: i32,
+    rule: MatchRuleInput,
+) -> Result<MatchRule, String> {
+    let new_rule = rule.validate()?;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let saved: MatchRule = diesel::update(match_rules::table.filter(match_rules::id.eq(rule_id)))
+        .set((
+            &new_rule,
+            match_rules::updated_at.eq(chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string()),
+        ))
+        .get_result(&mut conn)
+        .optional()
+        .map_err(|e| format!("Failed to update match rule: {}", e))?
+        .ok_or_else(|| "Match rule not found".to_string())?;
+
+    log_match_rule_change(&mut conn, "updated", &saved);
+    Ok(saved)
+}
+
+/// Delete a match rule.
+///
+/// Existing channel mappings are kept until matching runs again.
+#[tauri::command]
+pub fn delete_match_rule(db: State<DbConnection>, rule_id: i32) -> Result<(), String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let rule: MatchRule = match_rules::table
+        .filter(match_rules::id.eq(rule_id))
+        .first(&mut conn)
+        .map_err(|_| "Match rule not found".to_string())?;
+
+    diesel::delete(match_rules::table.filter(match_rules::id.eq(rule_id)))
+        .execute(&mut conn)
+        .map_err(|e| format!("Failed to delete match rule: {}", e))?;
+
+    log_match_rule_change(&mut conn, "deleted", &rule);
+    Ok(())
+}
+
+/// Normalize sample names with the saved match rules and, optionally, a rule being edited.
+///
+/// `rule` is applied together with the saved rules (at its sort order),
+/// replacing the saved rule `rule_id` when given, so a rule can be tried out
+/// before it is saved.
+#[tauri::command]
+pub fn test_match_rule(
+    db: State<DbConnection>,
+    sample_names: Vec<String>,
+    rule: Option<MatchRuleInput>,
+    rule_id: Option<i32>,
+) -> Result<Vec<MatchRuleTestResult>, String> {
+    if sample_names.len() > MAX_RULE_TEST_SAMPLES {
+        return Err(format!(
+            "At most {} sample names can be tested at once",
+            MAX_RULE_TEST_SAMPLES
+        ));
+    }
+    let mut conn = db
+        .get_read_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let saved =
+        load_match_rules(&mut conn).map_err(|e| format!("Failed to load match rules: {}", e))?;
+    test_match_rule_internal(&saved, &sample_names, rule.as_ref(), rule_id)
+}
+
+/// Normalize `sample_names` with the `saved` rules, with and without `rule`
+fn test_match_rule_internal(
+    saved: &[MatchRule],
+    sample_names: &[String],
+    rule: Option<&MatchRuleInput>,
+    rule_id: Option<i32>,
+) -> Result<Vec<MatchRuleTestResult>, String> {
+    let current = NameRules::compile(saved);
+    let with_rule = match rule {
+        Some(rule) => {
+            let new_rule = rule.validate()?;
+            let mut rules: Vec<MatchRule> = saved
+                .iter()
+                .filter(|r| rule_id.is_none() || r.id != rule_id)
+                .cloned()
+                .collect();
+            rules.push(MatchRule {
+                id: None,
+                rule_type: new_rule.rule_type,
+                pattern: new_rule.pattern,
+                replacement: new_rule.replacement,
+                // Tried out even if it would be saved disabled
+                is_enabled: 1,
+                sort_order: new_rule.sort_order,
+                created_at: String::new(),
+                updated_at: String::new(),
+            });
+            Some(NameRules::compile(&rules))
+        }
+        None => None,
+    };
+
+    Ok(sample_names
+        .iter()
+        .map(|name| {
+            let (normalized, applied_rule_ids) = current.normalize_traced(name);
+            MatchRuleTestResult {
+                name: name.clone(),
+                builtin: crate::matcher::normalize_channel_name(name),
+                current: normalized,
+                with_rule: with_rule.as_ref().map(|rules| rules.normalize(name)),
+                applied_rule_ids,
+            }
+        })
+        .collect())
+}
+
+/// Log a match rule change to the event log
+fn log_match_rule_change(conn: &mut SqliteConnection, action: &str, rule: &MatchRule) {
+    let details = serde_json::json!({
+        "ruleId": rule.id,
+        "ruleType": rule.rule_type,
+        "pattern": rule.pattern,
+        "replacement": rule.replacement,
+        "isEnabled": rule.is_enabled != 0
+    });
+    let _ = log_event_internal(
+        conn,
+        "info",
+        "match",
+        &format!(
+            "Match rule {}: {} '{}'",
+            action, rule.rule_type, rule.pattern
+        ),
+        Some(&details.to_string()),
+    );
+}
+
 /// Internal helper to get threshold from settings
 fn get_match_threshold_internal(db: &State<DbConnection>) -> Result<f64, String> {
     let mut conn = db
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    load_match_threshold(&mut conn)
+}
+
+/// Load the saved matching algorithm (or the default)
+fn load_match_algorithm(conn: &mut SqliteConnection) -> MatchAlgorithm {
+    settings::table
+        .filter(settings::key.eq(MATCH_ALGORITHM_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| MatchAlgorithm::parse(&value))
+        .unwrap_or_default()
+}
+
+/// Build the matching configuration from the saved settings
+///
+/// `threshold` overrides the saved threshold. Icons are compared from the
+/// logo cache in `app_data_dir` when icon matching is enabled.
+fn load_match_config(
+    conn: &mut SqliteConnection,
+    threshold: Option<f64>,
+    app_data_dir: Option<&Path>,
+) -> Result<MatchConfig, String> {
+    // Get threshold from parameter or settings or default
+    let threshold = match threshold {
+        Some(t) => t,
+        None => load_match_threshold(conn)?,
+    };
+
+    // Validate threshold
+    if !(0.0..=1.0).contains(&threshold) {
+        return Err("Threshold must be between 0.0 and 1.0".to_string());
+    }
+
+    let alias_hints =
+        load_alias_hints(conn).map_err(|e| format!("Failed to load alias packs: {}", e))?;
+    let name_rules =
+        load_name_rules(conn).map_err(|e| format!("Failed to load match rules: {}", e))?;
+    let mut config = MatchConfig::default()
+        .with_threshold(threshold)
+        .with_algorithm(load_match_algorithm(conn))
+        .with_alias_hints(alias_hints)
+        .with_name_rules(name_rules)
+        .with_profiles(load_match_profiles(conn))
+        .with_region_matching(&load_region_match_config(conn))
+        .with_plugins(crate::plugins::enabled(conn));
+    if let Some(app_data_dir) = app_data_dir {
+        config = config.with_icon_matching(&load_icon_match_config(conn), app_data_dir);
+    }
+    Ok(config)
+}
+
+/// Load the matching threshold from settings (or the default)
+fn load_match_threshold(conn: &mut SqliteConnection) -> Result<f64, String> {
     let result = settings::table
         .filter(settings::key.eq(MATCH_THRESHOLD_KEY))
         .select(settings::value)
-        .first::<String>(&mut conn)
+        .first::<String>(conn)
         .optional()
         .map_err(|e| format!("Query error: {}", e))?;
 
@@ -320,12 +1030,23 @@ pub fn auto_rematch_new_streams(
     threshold: Option<f64>,
 ) -> Result<i32, String> {
     let threshold = threshold.unwrap_or(get_match_threshold_internal(&db)?);
-    let config = MatchConfig::default().with_threshold(threshold);
-
     let mut conn = db
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    let alias_hints =
+        load_alias_hints(&mut conn).map_err(|e| format!("Failed to load alias packs: {}", e))?;
+    let name_rules =
+        load_name_rules(&mut conn).map_err(|e| format!("Failed to load match rules: {}", e))?;
+    let config = MatchConfig::default()
+        .with_threshold(threshold)
+        .with_algorithm(load_match_algorithm(&mut conn))
+        .with_alias_hints(alias_hints)
+        .with_name_rules(name_rules)
+        .with_profiles(load_match_profiles(&mut conn))
+        .with_region_matching(&load_region_match_config(&mut conn))
+        .with_plugins(crate::plugins::enabled(&mut conn));
+
     core_auto_rematch_new_streams(&mut conn, &new_streams, &config)
         .map_err(|e| format!("Failed to auto-rematch new streams: {}", e))
 }
@@ -375,12 +1096,104 @@ pub fn handle_changed_streams(
     threshold: Option<f64>,
 ) -> Result<i32, String> {
     let threshold = threshold.unwrap_or(get_match_threshold_internal(&db)?);
-    let config = MatchConfig::default().with_threshold(threshold);
-
     let mut conn = db
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    let alias_hints =
+        load_alias_hints(&mut conn).map_err(|e| format!("Failed to load alias packs: {}", e))?;
+    let name_rules =
+        load_name_rules(&mut conn).map_err(|e| format!("Failed to load match rules: {}", e))?;
+    let config = MatchConfig::default()
+        .with_threshold(threshold)
+        .with_algorithm(load_match_algorithm(&mut conn))
+        .with_alias_hints(alias_hints)
+        .with_name_rules(name_rules)
+        .with_profiles(load_match_profiles(&mut conn))
+        .with_region_matching(&load_region_match_config(&mut conn))
+        .with_plugins(crate::plugins::enabled(&mut conn));
+
     core_handle_changed_streams(&mut conn, account_id, &changed_streams, &config)
         .map_err(|e| format!("Failed to handle changed streams: {}", e))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_simulation_lists_changed_mappings_without_saving() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://a.local");
+        for sql in [
+            "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://example.com/epg.xml')",
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (1, 1, 'a.uk', 'Sky Sports News'), (2, 1, 'b.uk', 'BBC One')",
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES (10, 1, 100, 'News Sky Sports'), (20, 1, 200, 'BBC One')",
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES (2, 20, 1.0, 0, 1, 0)",
+        ] {
+            diesel::sql_query(sql).execute(&mut conn).unwrap();
+        }
+
+        // Reordered words only match with an order-insensitive algorithm
+        let config = MatchConfigOverride {
+            algorithm: Some(MatchAlgorithm::SorensenDice),
+            threshold: Some(0.85),
+            ..Default::default()
+        }
+        .apply(MatchConfig::default())
+        .unwrap();
+        let simulation = simulate_matching_internal(&mut conn, &config).unwrap();
+        assert_eq!(simulation.currently_matched, 1);
+        assert_eq!(simulation.gained, 1);
+        assert_eq!(simulation.lost, 0);
+        assert_eq!(simulation.changes.len(), 1);
+        let change = &simulation.changes[0];
+        assert_eq!(change.xmltv_channel_id, 1);
+        assert!(change.current_primary.is_none());
+        assert_eq!(
+            change.simulated_primary.as_ref().unwrap().xtream_channel_id,
+            10
+        );
+
+        let mapped: i64 = channel_mappings::table
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(mapped, 1);
+
+        let invalid = MatchConfigOverride {
+            icon_boost: Some(1.5),
+            ..Default::default()
+        };
+        assert!(invalid.apply(MatchConfig::default()).is_err());
+    }
+
+    #[test]
+    fn test_rule_test_replaces_edited_rule() {
+        let saved = vec![MatchRule {
+            id: Some(1),
+            rule_type: "strip_prefix".to_string(),
+            pattern: "US:".to_string(),
+            replacement: String::new(),
+            is_enabled: 1,
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }];
+        let edited = MatchRuleInput {
+            rule_type: crate::matcher::MatchRuleType::StripPrefix,
+            pattern: "UK:".to_string(),
+            replacement: String::new(),
+            is_enabled: true,
+            sort_order: 0,
+        };
+        let samples = vec!["US: ESPN".to_string(), "UK: Sky News".to_string()];
+
+        let results = test_match_rule_internal(&saved, &samples, Some(&edited), Some(1)).unwrap();
+        assert_eq!(results[0].current, "espn");
+        assert_eq!(results[0].applied_rule_ids, vec![1]);
+        assert_eq!(results[0].with_rule.as_deref(), Some("us espn"));
+        assert_eq!(results[1].builtin, "uk sky news");
+        assert_eq!(results[1].with_rule.as_deref(), Some("sky news"));
+    }
+}
@@ -0,0 +1,68 @@
+//! Metrics export Tauri commands
+//!
+//! Configures the scheduled snapshot export in `crate::metrics`.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::metrics::{self, MetricsExportConfig, METRICS_EXPORT_KEY};
+use crate::server::AppState;
+
+/// Get the metrics export settings
+#[tauri::command]
+pub fn get_metrics_export_config(db: State<DbConnection>) -> Result<MetricsExportConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(metrics::load_config(&mut conn))
+}
+
+/// Save the metrics export settings
+///
+/// When export is enabled, a snapshot is written right away so an unwritable
+/// path is reported here instead of failing silently in the background.
+#[tauri::command]
+pub fn set_metrics_export_config(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    config: MetricsExportConfig,
+) -> Result<MetricsExportConfig, String> {
+    config.validate()?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    if config.enabled {
+        metrics::export_snapshot(
+            &mut conn,
+            server_state.stream_manager(),
+            &config,
+            chrono::Utc::now(),
+        )?;
+    }
+    metrics::save_config(&mut conn, &config)?;
+
+    let details = serde_json::json!({
+        "setting": METRICS_EXPORT_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &if config.enabled {
+            format!(
+                "Configuration changed: Metrics export to {}",
+                config.path.trim()
+            )
+        } else {
+            "Configuration changed: Metrics export disabled".to_string()
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
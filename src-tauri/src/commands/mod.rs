@@ -1,11 +1,40 @@
 pub mod accounts;
+pub mod alias_packs;
+pub mod channel_epg_feeds;
+pub mod channel_numbers;
+pub mod channel_policy;
 pub mod channels;
 pub mod config;
+pub mod credentials;
+pub mod data_dump;
+pub mod device_profiles;
+pub mod diagnostics;
+pub mod dns;
 pub mod epg;
+pub mod failover_policy;
+pub mod hooks;
+pub mod lineup;
+pub mod lineup_report;
+pub mod logos;
 pub mod logs;
+pub mod mapping_conflicts;
+pub mod mapping_csv;
 pub mod matcher;
+pub mod metrics;
+pub mod notifications;
+pub mod plex;
+pub mod plugins;
+pub mod quota;
+pub mod service;
+pub mod speedtest;
+pub mod stream_stats;
+pub mod tasks;
 pub mod test_data;
+pub mod tuner_priority;
 pub mod update;
+pub mod vod;
+pub mod web_admin;
+pub mod workspaces;
 pub mod xmltv_channels;
 pub mod xtream_sources;
 
@@ -14,7 +43,14 @@ use serde::Serialize;
 use tauri::{AppHandle, State};
 
 use crate::db::{schema::settings, DbConnection, Setting};
-use crate::server::hdhr::{get_local_ip, get_tuner_count};
+use crate::i18n::{db_connection_error, tr, tr_args};
+use crate::server::hdhr::{
+    advertised_host, device_identity, get_tuner_count, save_device_identity, DeviceIdentity,
+};
+use crate::server::auth::with_access_token;
+use crate::server::state::{read_bind_address, BIND_ADDRESS_KEY};
+use crate::server::status::{active_streams, ActiveStream};
+use crate::server::{AppState, ServerHandle, ServerRestartResult};
 
 // Re-export account commands for convenient access
 pub use accounts::{add_account, delete_account, get_accounts, test_connection, update_account};
@@ -43,7 +79,7 @@ pub fn greet(name: &str) -> String {
 pub fn get_setting(db: State<DbConnection>, key: String) -> Result<Option<String>, String> {
     let mut conn = db
         .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
+        .map_err(db_connection_error)?;
 
     let result = settings::table
         .filter(settings::key.eq(&key))
@@ -59,7 +95,7 @@ pub fn get_setting(db: State<DbConnection>, key: String) -> Result<Option<String
 pub fn set_setting(db: State<DbConnection>, key: String, value: String) -> Result<(), String> {
     let mut conn = db
         .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
+        .map_err(db_connection_error)?;
 
     let setting = Setting::new(key, value);
 
@@ -71,8 +107,58 @@ pub fn set_setting(db: State<DbConnection>, key: String, value: String) -> Resul
     Ok(())
 }
 
+/// Save several settings at once, all or nothing
+///
+/// Every value is validated against the settings registry first; if any key
+/// is unknown or any value invalid, nothing is saved and the errors are
+/// returned per key. Otherwise all values are written in one transaction and
+/// the running app picks up the changes (language, low resource mode, cached
+/// guide). Server port and bind address still take effect on restart.
+#[tauri::command]
+pub fn apply_settings_batch(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    settings: std::collections::BTreeMap<String, serde_json::Value>,
+) -> Result<crate::settings_registry::SettingsBatchResult, String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::settings_registry::{find_setting, SettingEffect};
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let result = crate::settings_registry::apply_settings_batch(&mut conn, &settings)?;
+    if result.changed.is_empty() {
+        return Ok(result);
+    }
+
+    for change in &result.changed {
+        match find_setting(&change.key).map(|d| d.effect) {
+            Some(SettingEffect::Language) => crate::i18n::init(&mut conn),
+            Some(SettingEffect::LowResourceMode) => {
+                crate::low_resource::load_from_db(&mut conn);
+            }
+            Some(SettingEffect::EpgOutput) => server_state.bump_epg_generation(),
+            Some(SettingEffect::LogVerbosity) => crate::logging::load_from_db(&mut conn),
+            Some(SettingEffect::None) | None => {}
+        }
+    }
+
+    let keys: Vec<&str> = result.changed.iter().map(|c| c.key.as_str()).collect();
+    let details = serde_json::json!({ "changes": result.changed });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: {}", keys.join(", ")),
+        Some(&details.to_string()),
+    );
+
+    Ok(result)
+}
+
 /// Internal helper to get server port without State wrapper
-fn get_server_port_internal(conn: &mut diesel::SqliteConnection) -> Result<u16, diesel::result::Error> {
+pub(crate) fn get_server_port_internal(conn: &mut diesel::SqliteConnection) -> Result<u16, diesel::result::Error> {
     const DEFAULT_SERVER_PORT: u16 = 5004;
     const SERVER_PORT_KEY: &str = "server_port";
 
@@ -96,7 +182,7 @@ fn get_server_port_internal(conn: &mut diesel::SqliteConnection) -> Result<u16,
 pub fn get_server_port(db: State<DbConnection>) -> Result<u16, String> {
     let mut conn = db
         .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
+        .map_err(db_connection_error)?;
 
     get_server_port_internal(&mut conn)
         .map_err(|e| format!("Query error: {}", e))
@@ -104,7 +190,7 @@ pub fn get_server_port(db: State<DbConnection>) -> Result<u16, String> {
 
 /// Set the server port in settings
 ///
-/// Note: Changing the port requires an application restart to take effect.
+/// Note: The port takes effect when the server is restarted (`restart_server`).
 /// Story 6-3: Logs configuration change event (AC #2)
 #[allow(dead_code)] // Used by lib crate via tauri invoke_handler
 #[tauri::command]
@@ -113,12 +199,12 @@ pub fn set_server_port(db: State<DbConnection>, port: u16) -> Result<(), String>
 
     // Validate port range (only check lower bound - u16 max is 65535)
     if port < 1024 {
-        return Err("Port must be 1024 or higher (non-privileged ports)".to_string());
+        return Err(tr("error-port-too-low"));
     }
 
     let mut conn = db
         .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
+        .map_err(db_connection_error)?;
 
     // Get old port for logging (Story 6-3: AC #2)
     let old_port = get_server_port_internal(&mut conn).unwrap_or(5004);
@@ -141,44 +227,730 @@ pub fn set_server_port(db: State<DbConnection>, port: u16) -> Result<(), String>
         &mut conn,
         "info",
         "system",
-        &format!("Configuration changed: Server port {} → {}", old_port, port),
+        &tr_args(
+            "event-server-port-changed",
+            &[("old", &old_port.to_string()), ("new", &port.to_string())],
+        ),
         Some(&details.to_string()),
     );
 
     Ok(())
 }
 
-/// Restart the HTTP server on the new port
+/// Get how the emulated HDHomeRun tuner identifies itself
+#[tauri::command]
+pub fn get_hdhr_identity(db: State<DbConnection>) -> Result<DeviceIdentity, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(device_identity(&mut conn))
+}
+
+/// Set the FriendlyName and ModelNumber advertised in discover.json and
+/// device.xml (empty values restore the defaults)
 ///
-/// Story 6.1: Settings GUI for Server and Startup Options
-/// Task 3.1: Add restart_server Tauri command
+/// The DeviceID is kept so Plex still recognizes the tuner.
+#[tauri::command]
+pub fn set_hdhr_identity(
+    db: State<DbConnection>,
+    friendly_name: String,
+    model_number: String,
+) -> Result<DeviceIdentity, String> {
+    use crate::commands::logs::log_event_internal;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let identity = save_device_identity(&mut conn, &friendly_name, &model_number)?;
+
+    let details = serde_json::json!({
+        "friendlyName": identity.friendly_name,
+        "modelNumber": identity.model_number
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr_args(
+            "event-tuner-name-changed",
+            &[
+                ("name", &identity.friendly_name),
+                ("model", &identity.model_number),
+            ],
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(identity)
+}
+
+/// Whether the tuners are announced on the LAN over SSDP
+#[tauri::command]
+pub fn get_ssdp_enabled(db: State<DbConnection>) -> Result<bool, String> {
+    let mut conn = db.get_connection().map_err(db_connection_error)?;
+
+    Ok(crate::server::ssdp::is_enabled(&mut conn))
+}
+
+/// Announce the tuners on the LAN over SSDP so Plex finds them by itself
 ///
-/// IMPLEMENTATION NOTE: This is a placeholder implementation (Option A).
-/// Port changes do NOT take effect immediately - they require a full application restart.
-/// The frontend has been updated to inform users: "Port saved. Restart the app to apply changes."
+/// Takes effect within half a minute, without restarting the server.
+#[tauri::command]
+pub fn set_ssdp_enabled(db: State<DbConnection>, enabled: bool) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::server::ssdp;
+
+    let mut conn = db.get_connection().map_err(db_connection_error)?;
+
+    ssdp::set_enabled(&mut conn, enabled).map_err(|e| format!("Failed to save setting: {}", e))?;
+
+    let details = serde_json::json!({ "setting": ssdp::SSDP_ENABLED_KEY, "value": enabled });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: SSDP discovery {}",
+            if enabled { "enabled" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get the access token required on playlist, guide and stream URLs
 ///
-/// Why Option A was chosen:
-/// - Simpler implementation with no risk of server state corruption
-/// - No need to manage server handle lifecycle or graceful connection shutdown
-/// - Clear user expectation (restart required)
-/// - Recommended approach in Dev Notes
+/// Returns None while the endpoints are open.
+#[tauri::command]
+pub fn get_access_token(db: State<DbConnection>) -> Result<Option<String>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(crate::server::auth::get_access_token(&mut conn))
+}
+
+/// Generate a new access token, enabling token checks
 ///
-/// Future enhancement (Option B): Implement hot restart by:
-/// 1. Storing server handle in Tauri managed state
-/// 2. Implementing graceful shutdown with connection draining
-/// 3. Restarting server on same Tokio runtime with new port
-#[allow(dead_code)] // Used by lib crate via tauri invoke_handler
+/// URLs configured in Plex with the previous token stop working; the
+/// tokenized URLs from `get_plex_config` must be entered again.
+#[tauri::command]
+pub fn rotate_access_token(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<String, String> {
+    use crate::commands::logs::log_event_internal;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let token = crate::server::auth::rotate_access_token(&mut conn)
+        .map_err(|e| format!("Failed to save access token: {}", e))?;
+    // Cached playlists embed the old token in stream URLs
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({ "setting": crate::server::auth::ACCESS_TOKEN_KEY });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr("event-access-token-generated"),
+        Some(&details.to_string()),
+    );
+
+    Ok(token)
+}
+
+/// Remove the access token, leaving playlist, guide and stream URLs open
+#[tauri::command]
+pub fn clear_access_token(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    crate::server::auth::clear_access_token(&mut conn)
+        .map_err(|e| format!("Failed to remove access token: {}", e))?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({ "setting": crate::server::auth::ACCESS_TOKEN_KEY });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr("event-access-token-removed"),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get the language of backend messages (errors and event log)
+#[tauri::command]
+pub fn get_language() -> String {
+    crate::i18n::language()
+}
+
+/// List the languages backend messages are available in
+#[tauri::command]
+pub fn get_supported_languages() -> Vec<crate::i18n::LanguageOption> {
+    crate::i18n::supported_languages()
+}
+
+/// Set the language of backend messages
+///
+/// Applies to errors and event log entries produced from now on.
+#[tauri::command]
+pub fn set_language(db: State<DbConnection>, language: String) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+
+    let mut conn = db.get_connection().map_err(db_connection_error)?;
+
+    crate::i18n::set_language(&mut conn, &language)?;
+
+    let details = serde_json::json!({
+        "setting": crate::i18n::LANGUAGE_KEY,
+        "newValue": language
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr_args("event-language-changed", &[("language", &language)]),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// A bind address offered in the server settings
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BindAddressOption {
+    pub address: String,
+    pub label: String,
+}
+
+/// Get the address the HTTP server binds to
+#[tauri::command]
+pub fn get_bind_address(db: State<DbConnection>) -> Result<String, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(read_bind_address(&mut conn).to_string())
+}
+
+/// List the bind addresses that can be chosen
+///
+/// All interfaces, this computer only, and the IPv4 address of each local
+/// network interface.
+#[tauri::command]
+pub fn get_bind_address_options() -> Vec<BindAddressOption> {
+    let mut options = vec![
+        BindAddressOption {
+            address: "0.0.0.0".to_string(),
+            label: "All interfaces".to_string(),
+        },
+        BindAddressOption {
+            address: "127.0.0.1".to_string(),
+            label: "This computer only".to_string(),
+        },
+    ];
+    for (name, ip) in local_ip_address::list_afinet_netifas().unwrap_or_default() {
+        if ip.is_ipv4() && !ip.is_loopback() {
+            options.push(BindAddressOption {
+                address: ip.to_string(),
+                label: format!("{} ({})", name, ip),
+            });
+        }
+    }
+    options
+}
+
+/// Check that an address can be bound: all interfaces, loopback, or the
+/// address of a local network interface
+pub(crate) fn validate_bind_address(address: &str) -> Result<std::net::IpAddr, String> {
+    let ip: std::net::IpAddr = address
+        .trim()
+        .parse()
+        .map_err(|_| tr_args("error-invalid-ip", &[("address", address.trim())]))?;
+
+    if ip.is_unspecified() || ip.is_loopback() {
+        return Ok(ip);
+    }
+    let local = local_ip_address::list_afinet_netifas().unwrap_or_default();
+    if local.iter().any(|(_, local_ip)| *local_ip == ip) {
+        Ok(ip)
+    } else {
+        Err(tr_args("error-not-local-address", &[("address", &ip.to_string())]))
+    }
+}
+
+/// Set the address the HTTP server binds to
+///
+/// Note: The address takes effect when the server is restarted (`restart_server`).
+/// Binding to all interfaces is logged as a warning since it exposes the
+/// server to the whole network.
+#[tauri::command]
+pub fn set_bind_address(db: State<DbConnection>, address: String) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+
+    let ip = validate_bind_address(&address)?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let old_address = read_bind_address(&mut conn);
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(BIND_ADDRESS_KEY, ip.to_string()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Insert error: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": BIND_ADDRESS_KEY,
+        "oldValue": old_address.to_string(),
+        "newValue": ip.to_string()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr_args(
+            "event-bind-address-changed",
+            &[("old", &old_address.to_string()), ("new", &ip.to_string())],
+        ),
+        Some(&details.to_string()),
+    );
+    if ip.is_unspecified() {
+        let _ = log_event_internal(
+            &mut conn,
+            "warn",
+            "system",
+            &tr("event-bind-all-interfaces"),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(())
+}
+
+/// Get whether streams are padded with MPEG-TS null packets
+///
+/// When enabled, the stream proxy sends null packets while FFmpeg starts and
+/// during failover, so clients that give up quickly without data stay connected.
+#[tauri::command]
+pub fn get_stream_ts_padding(db: State<DbConnection>) -> Result<bool, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(crate::server::failover::get_ts_padding_enabled(&mut conn))
+}
+
+/// Enable or disable MPEG-TS null packet padding for new streams
+#[tauri::command]
+pub fn set_stream_ts_padding(db: State<DbConnection>, enabled: bool) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::server::failover::TS_PADDING_KEY;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(TS_PADDING_KEY, enabled.to_string()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Insert error: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": TS_PADDING_KEY,
+        "newValue": enabled
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr(if enabled {
+            "event-ts-padding-enabled"
+        } else {
+            "event-ts-padding-disabled"
+        }),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get whether streams stay continuous across failover splices
+///
+/// When enabled, continuity counters and PCR/PTS/DTS are rewritten after the
+/// proxy switches upstream connections, so seeking in Plex's live buffer keeps
+/// working after a failover.
+#[tauri::command]
+pub fn get_stream_ts_continuity(db: State<DbConnection>) -> Result<bool, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(crate::server::failover::get_ts_continuity_enabled(&mut conn))
+}
+
+/// Enable or disable MPEG-TS continuity rewriting for new streams
+#[tauri::command]
+pub fn set_stream_ts_continuity(db: State<DbConnection>, enabled: bool) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::server::failover::TS_CONTINUITY_KEY;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(TS_CONTINUITY_KEY, enabled.to_string()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Insert error: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": TS_CONTINUITY_KEY,
+        "newValue": enabled
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr(if enabled {
+            "event-ts-continuity-enabled"
+        } else {
+            "event-ts-continuity-disabled"
+        }),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get the highest quality streams are played at ("best" for no cap)
+#[tauri::command]
+pub fn get_stream_preferred_quality(db: State<DbConnection>) -> Result<String, String> {
+    use crate::server::stream::PREFERRED_QUALITY_KEY;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let quality = settings::table
+        .filter(settings::key.eq(PREFERRED_QUALITY_KEY))
+        .select(settings::value)
+        .first::<String>(&mut conn)
+        .optional()
+        .map_err(|e| format!("Query error: {}", e))?;
+    Ok(quality.unwrap_or_else(|| "best".to_string()))
+}
+
+/// Cap the quality of new streams ("best", "4K", "FHD", "HD" or "SD")
+///
+/// Streams within the cap are tried before the channel's other streams, and
+/// the highest quality at or below it is selected. Channels can override it
+/// with `set_channel_preferred_quality`.
+#[tauri::command]
+pub fn set_stream_preferred_quality(
+    db: State<DbConnection>,
+    quality: String,
+) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::server::stream::PREFERRED_QUALITY_KEY;
+
+    let quality = crate::settings_registry::find_setting(PREFERRED_QUALITY_KEY)
+        .ok_or_else(|| format!("Unknown setting: {}", PREFERRED_QUALITY_KEY))?
+        .validate(&serde_json::Value::String(quality))?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(PREFERRED_QUALITY_KEY, quality.clone()))
+        .execute(&mut conn)
+        .map_err(|e| format!("Insert error: {}", e))?;
+
+    let details = serde_json::json!({
+        "setting": PREFERRED_QUALITY_KEY,
+        "newValue": quality
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr_args("event-preferred-quality-changed", &[("quality", &quality)]),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Cap the stream quality of one channel
+///
+/// `quality` None follows the global setting; "best" lifts the cap for this
+/// channel.
 #[tauri::command]
-pub async fn restart_server() -> Result<(), String> {
-    // Placeholder implementation - actual restart requires app restart
-    println!("INFO: Server port change saved. Port will take effect on next application restart.");
+pub fn set_channel_preferred_quality(
+    db: State<DbConnection>,
+    channel_id: i32,
+    quality: Option<String>,
+) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::db::schema::xmltv_channel_settings;
+    use crate::server::stream::PREFERRED_QUALITY_CHOICES;
+
+    let quality = quality
+        .map(|q| q.trim().to_string())
+        .filter(|q| !q.is_empty());
+    if let Some(ref quality) = quality {
+        if !PREFERRED_QUALITY_CHOICES.contains(&quality.as_str()) {
+            return Err(format!(
+                "Must be one of: {}",
+                PREFERRED_QUALITY_CHOICES.join(", ")
+            ));
+        }
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let updated = diesel::update(
+        xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id)),
+    )
+    .set((
+        xmltv_channel_settings::preferred_quality.eq(&quality),
+        xmltv_channel_settings::updated_at.eq(chrono::Utc::now().to_rfc3339()),
+    ))
+    .execute(&mut conn)
+    .map_err(|e| format!("Update error: {}", e))?;
+    if updated == 0 {
+        return Err(format!("Channel {} has no settings", channel_id));
+    }
+
+    let details = serde_json::json!({
+        "channelId": channel_id,
+        "quality": quality,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &tr_args(
+            "event-channel-preferred-quality-changed",
+            &[
+                ("channel", &channel_id.to_string()),
+                ("quality", quality.as_deref().unwrap_or("default")),
+            ],
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get the configured FFmpeg path (None when `ffmpeg` is taken from PATH)
+#[tauri::command]
+pub fn get_ffmpeg_path() -> Option<String> {
+    crate::server::buffer::configured_ffmpeg_path()
+}
+
+/// Set the FFmpeg binary the stream proxy uses to remux streams to MPEG-TS
+///
+/// An empty path goes back to `ffmpeg` from PATH. The binary must run
+/// `ffmpeg -version` successfully. Streams started afterwards use it.
+#[tauri::command]
+pub fn set_ffmpeg_path(db: State<DbConnection>, path: Option<String>) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::server::buffer::{self, FFMPEG_PATH_KEY};
+
+    let path = path
+        .map(|p| p.trim().to_string())
+        .filter(|p| !p.is_empty());
+    if let Some(ref path) = path {
+        buffer::check_ffmpeg_binary(path).map_err(|e| e.to_string())?;
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    buffer::set_ffmpeg_path(&mut conn, path.as_deref())
+        .map_err(|e| tr_args("error-save-setting", &[("error", &e.to_string())]))?;
+
+    let details = serde_json::json!({
+        "setting": FFMPEG_PATH_KEY,
+        "newValue": path
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &match path.as_deref() {
+            Some(path) => tr_args("event-ffmpeg-path-changed", &[("path", path)]),
+            None => tr("event-ffmpeg-path-reset"),
+        },
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Get the maintenance window for disruptive scheduled jobs
+#[tauri::command]
+pub fn get_maintenance_window(
+    db: State<DbConnection>,
+) -> Result<crate::maintenance::MaintenanceWindow, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    Ok(crate::maintenance::get_window(&mut conn))
+}
+
+/// Set the maintenance window for disruptive scheduled jobs
+///
+/// Jobs already waiting for the window pick up the change on their next check.
+#[tauri::command]
+pub fn set_maintenance_window(
+    db: State<DbConnection>,
+    window: crate::maintenance::MaintenanceWindow,
+) -> Result<(), String> {
+    use crate::commands::logs::log_event_internal;
+    use crate::maintenance::{self, MAINTENANCE_WINDOW_KEY};
 
-    // This command exists to maintain API compatibility and allow future enhancement
-    // Frontend correctly handles this by informing user to restart the app
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    maintenance::set_window(&mut conn, &window)?;
+
+    let details = serde_json::json!({
+        "setting": MAINTENANCE_WINDOW_KEY,
+        "newValue": window
+    });
+    let message = if window.enabled {
+        tr_args(
+            "event-maintenance-window-set",
+            &[("start", &window.start), ("end", &window.end)],
+        )
+    } else {
+        tr("event-maintenance-window-disabled")
+    };
+    let _ = log_event_internal(&mut conn, "info", "system", &message, Some(&details.to_string()));
 
     Ok(())
 }
 
+/// Whether disruptive jobs may run right now
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MaintenanceStatus {
+    pub enabled: bool,
+    pub in_window: bool,
+    pub active_streams: usize,
+    pub can_run: bool,
+}
+
+/// Get whether the maintenance window is open and streams are idle
+#[tauri::command]
+pub fn get_maintenance_status(db: State<DbConnection>) -> Result<MaintenanceStatus, String> {
+    use crate::maintenance;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(db_connection_error)?;
+
+    let window = maintenance::get_window(&mut conn);
+    Ok(MaintenanceStatus {
+        enabled: window.enabled,
+        in_window: window.contains(chrono::Local::now().time()),
+        active_streams: maintenance::active_streams(),
+        can_run: maintenance::may_run_now(&window),
+    })
+}
+
+/// Restart the HTTP server on the configured port
+///
+/// Story 6.1: Settings GUI for Server and Startup Options
+/// Task 3.1: Add restart_server Tauri command
+///
+/// Stops accepting connections, asks active stream sessions to stop, waits
+/// for connections to drain (closing any left after the drain timeout), and
+/// rebinds on the port saved by `set_server_port`. If the new port cannot be
+/// bound the server keeps running on the old one.
+#[allow(dead_code)] // Used by lib crate via tauri invoke_handler
+#[tauri::command]
+pub async fn restart_server(
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    server_handle: State<'_, ServerHandle>,
+) -> Result<ServerRestartResult, String> {
+    let result = server_handle
+        .restart(server_state.inner().clone())
+        .await
+        .map_err(|e| tr_args("error-restart-server", &[("error", &e.to_string())]))?;
+
+    if let Ok(mut conn) = db.get_connection() {
+        use crate::commands::logs::log_event_internal;
+        let details = serde_json::json!({
+            "previousPort": result.previous_port,
+            "port": result.port,
+            "stoppedStreams": result.stopped_streams,
+            "forced": result.forced
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            if result.forced { "warn" } else { "info" },
+            "system",
+            &tr_args(
+                if result.forced {
+                    "event-server-restarted-forced"
+                } else {
+                    "event-server-restarted"
+                },
+                &[
+                    ("port", &result.port.to_string()),
+                    ("streams", &result.stopped_streams.to_string()),
+                ],
+            ),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(result)
+}
+
+/// List active stream sessions with channel, account, client and bytes sent
+///
+/// The same list is available over HTTP as server-sent events at
+/// `/status/streams`.
+#[tauri::command]
+pub fn get_active_streams(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<Vec<ActiveStream>, String> {
+    let mut conn = db.get_connection().map_err(db_connection_error)?;
+
+    active_streams(&mut conn, server_state.stream_manager())
+        .map_err(|e| format!("Failed to list active streams: {}", e))
+}
+
 /// Get the current autostart status
 ///
 /// Returns whether the application is configured to auto-start on boot.
@@ -193,7 +965,7 @@ pub fn get_autostart_enabled(app: AppHandle) -> Result<AutostartStatus, String>
         .is_enabled()
         .map_err(|e| {
             eprintln!("Autostart status check failed: {}", e);
-            "Failed to check autostart status".to_string()
+            tr("error-autostart-check")
         })?;
 
     Ok(AutostartStatus { enabled })
@@ -218,14 +990,14 @@ pub fn set_autostart_enabled(
             .enable()
             .map_err(|e| {
                 eprintln!("Failed to enable autostart: {}", e);
-                "Failed to enable autostart".to_string()
+                tr("error-autostart-enable")
             })?;
     } else {
         autostart_manager
             .disable()
             .map_err(|e| {
                 eprintln!("Failed to disable autostart: {}", e);
-                "Failed to disable autostart".to_string()
+                tr("error-autostart-disable")
             })?;
     }
 
@@ -235,7 +1007,7 @@ pub fn set_autostart_enabled(
         .get_connection()
         .map_err(|e| {
             eprintln!("Database connection error while saving autostart: {}", e);
-            "Failed to save autostart setting".to_string()
+            tr("error-autostart-save")
         })?;
 
     let setting = Setting::new(AUTOSTART_KEY.to_string(), enabled.to_string());
@@ -245,7 +1017,7 @@ pub fn set_autostart_enabled(
         .execute(&mut conn)
         .map_err(|e| {
             eprintln!("Database insert error for autostart setting: {}", e);
-            "Failed to save autostart setting".to_string()
+            tr("error-autostart-save")
         })?;
 
     // Story 6-3: Log configuration change (AC #2)
@@ -258,7 +1030,11 @@ pub fn set_autostart_enabled(
         &mut conn,
         "info",
         "system",
-        &format!("Configuration changed: Auto-start {}", if enabled { "enabled" } else { "disabled" }),
+        &tr(if enabled {
+            "event-autostart-enabled"
+        } else {
+            "event-autostart-disabled"
+        }),
         Some(&details.to_string()),
     );
 
@@ -273,6 +1049,7 @@ pub fn set_autostart_enabled(
 pub struct PlexConfig {
     pub server_running: bool,
     pub local_ip: String,
+    pub bind_address: String,
     pub port: u16,
     pub m3u_url: String,
     pub epg_url: String,
@@ -286,7 +1063,9 @@ pub struct PlexConfig {
 ///
 /// Returns:
 /// - server_running: Whether the HTTP server is accepting connections
-/// - local_ip: Local network IP address (not 127.0.0.1)
+/// - local_ip: Host used in the URLs (local network IP when bound to all
+///   interfaces, otherwise the bind address)
+/// - bind_address: Address the server binds to
 /// - port: Configured server port (default 5004)
 /// - m3u_url: URL for M3U playlist endpoint
 /// - epg_url: URL for EPG/XMLTV endpoint
@@ -301,7 +1080,7 @@ pub async fn get_plex_config(db: State<'_, DbConnection>) -> Result<PlexConfig,
     // Get database connection
     let mut conn = db
         .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
+        .map_err(db_connection_error)?;
 
     // Get port from settings (reuse existing pattern from get_server_port)
     let port = settings::table
@@ -313,8 +1092,9 @@ pub async fn get_plex_config(db: State<'_, DbConnection>) -> Result<PlexConfig,
         .and_then(|port_str| port_str.parse::<u16>().ok())
         .unwrap_or(DEFAULT_SERVER_PORT);
 
-    // Get local IP (reuse existing function from hdhr.rs)
-    let local_ip = get_local_ip();
+    // Advertise the bind address, or the local IP when bound to all interfaces
+    let bind_address = read_bind_address(&mut conn);
+    let local_ip = advertised_host(bind_address);
 
     // Check if server is running FIRST to ensure data consistency
     let server_running = check_server_health(&local_ip, port).await;
@@ -325,14 +1105,16 @@ pub async fn get_plex_config(db: State<'_, DbConnection>) -> Result<PlexConfig,
         as i32;
 
     // Build URLs
+    let token = crate::server::auth::get_access_token(&mut conn);
     let base_url = format!("http://{}:{}", local_ip, port);
-    let m3u_url = format!("{}/playlist.m3u", base_url);
-    let epg_url = format!("{}/epg.xml", base_url);
+    let m3u_url = with_access_token(format!("{}/playlist.m3u", base_url), token.as_deref());
+    let epg_url = with_access_token(format!("{}/epg.xml", base_url), token.as_deref());
     let hdhr_url = base_url;
 
     Ok(PlexConfig {
         server_running,
         local_ip,
+        bind_address: bind_address.to_string(),
         port,
         m3u_url,
         epg_url,
@@ -351,13 +1133,16 @@ async fn check_server_health(local_ip: &str, port: u16) -> bool {
     let url = format!("http://{}:{}/discover.json", local_ip, port);
 
     // Set a 2-second timeout to avoid blocking UI (NFR5: responsiveness < 100ms requirement)
-    let client = match reqwest::Client::builder()
+    let client = match crate::dns::configure(reqwest::Client::builder())
         .timeout(std::time::Duration::from_secs(2))
         .build()
     {
         Ok(client) => client,
         Err(e) => {
-            eprintln!("Failed to create HTTP client for server health check: {}", e);
+            eprintln!(
+                "Failed to create HTTP client for server health check: {}",
+                e
+            );
             return false;
         }
     };
@@ -390,6 +1175,7 @@ mod tests {
         let config = PlexConfig {
             server_running: true,
             local_ip: "192.168.1.100".to_string(),
+            bind_address: "0.0.0.0".to_string(),
             port: 5004,
             m3u_url: "http://192.168.1.100:5004/playlist.m3u".to_string(),
             epg_url: "http://192.168.1.100:5004/epg.xml".to_string(),
@@ -414,6 +1200,7 @@ mod tests {
         let config = PlexConfig {
             server_running: true,
             local_ip: "10.0.0.5".to_string(),
+            bind_address: "0.0.0.0".to_string(),
             port: 8080,
             m3u_url: "http://10.0.0.5:8080/playlist.m3u".to_string(),
             epg_url: "http://10.0.0.5:8080/epg.xml".to_string(),
@@ -439,6 +1226,7 @@ mod tests {
         let config = PlexConfig {
             server_running: true,
             local_ip: "192.168.1.1".to_string(),
+            bind_address: "0.0.0.0".to_string(),
             port: 5004, // Default port
             m3u_url: "http://192.168.1.1:5004/playlist.m3u".to_string(),
             epg_url: "http://192.168.1.1:5004/epg.xml".to_string(),
@@ -455,6 +1243,7 @@ mod tests {
         let config = PlexConfig {
             server_running: false,
             local_ip: "127.0.0.1".to_string(),
+            bind_address: "0.0.0.0".to_string(),
             port: 5004,
             m3u_url: "http://127.0.0.1:5004/playlist.m3u".to_string(),
             epg_url: "http://127.0.0.1:5004/epg.xml".to_string(),
@@ -472,6 +1261,7 @@ mod tests {
         let config = PlexConfig {
             server_running: false,
             local_ip: "192.168.1.100".to_string(),
+            bind_address: "0.0.0.0".to_string(),
             port: 5004,
             m3u_url: "http://192.168.1.100:5004/playlist.m3u".to_string(),
             epg_url: "http://192.168.1.100:5004/epg.xml".to_string(),
@@ -0,0 +1,74 @@
+//! Notification Tauri commands
+//!
+//! Reads and saves the desktop notification and webhook settings used by
+//! `crate::notifications`, and sends test notifications.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::notifications::{
+    self, NotificationSettings, NotificationTestResult, NOTIFICATION_SETTINGS_KEY,
+};
+
+/// Get the notification settings
+#[tauri::command]
+pub fn get_notification_settings(db: State<DbConnection>) -> Result<NotificationSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(notifications::load_settings(&mut conn))
+}
+
+/// Save the notification settings
+#[tauri::command]
+pub fn set_notification_settings(
+    db: State<DbConnection>,
+    settings: NotificationSettings,
+) -> Result<NotificationSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    notifications::save_settings(&mut conn, &settings)?;
+
+    // Webhook URLs carry their access token, so only names are logged
+    let details = serde_json::json!({
+        "setting": NOTIFICATION_SETTINGS_KEY,
+        "desktop": settings.desktop,
+        "webhooks": settings.webhooks.iter().map(|w| w.name.as_str()).collect::<Vec<_>>(),
+        "categories": settings.categories,
+        "minLevel": settings.min_level,
+        "channelRules": settings.channel_rules.len(),
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Notifications saved (desktop {}, {} webhooks)",
+            if settings.desktop { "on" } else { "off" },
+            settings.webhooks.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(settings)
+}
+
+/// Send a test notification to every enabled target
+///
+/// Uses the given settings, so targets can be tried before they are saved.
+/// Returns the result for each target.
+#[tauri::command]
+pub async fn send_test_notification(
+    settings: NotificationSettings,
+) -> Result<Vec<NotificationTestResult>, String> {
+    settings.validate()?;
+    let results = notifications::send_test(&settings).await;
+    if results.is_empty() {
+        return Err("No notification target is enabled".to_string());
+    }
+    Ok(results)
+}
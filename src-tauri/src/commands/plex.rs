@@ -0,0 +1,97 @@
+//! Plex guide reload Tauri commands
+//!
+//! Reads and saves the Plex server used by `crate::plex` to reload the guide
+//! after EPG refreshes, and reports the reload queue.
+
+use serde::{Deserialize, Serialize};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::plex::{self, PlexReloadConfig, PlexReloadStatus, PLEX_GUIDE_RELOAD_KEY};
+
+/// Plex guide reload settings as shown in the UI
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexReloadSettings {
+    pub config: PlexReloadConfig,
+    /// Whether a Plex token is stored (the token itself is never returned)
+    pub has_token: bool,
+    pub status: PlexReloadStatus,
+}
+
+/// Plex guide reload settings to save
+#[derive(Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexReloadUpdate {
+    pub config: PlexReloadConfig,
+    /// New token; unset keeps the stored token, empty removes it
+    #[serde(default)]
+    pub token: Option<String>,
+}
+
+/// Get the Plex guide reload settings and queue state
+#[tauri::command]
+pub fn get_plex_reload_settings(db: State<DbConnection>) -> Result<PlexReloadSettings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(PlexReloadSettings {
+        config: plex::load_config(&mut conn),
+        has_token: plex::has_token(&mut conn),
+        status: plex::status(&mut conn),
+    })
+}
+
+/// Save the Plex guide reload settings
+#[tauri::command]
+pub fn set_plex_reload_settings(
+    app: AppHandle,
+    db: State<DbConnection>,
+    settings: PlexReloadUpdate,
+) -> Result<PlexReloadSettings, String> {
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    plex::save_config(
+        &mut conn,
+        &app_data_dir,
+        &settings.config,
+        settings.token.as_deref(),
+    )?;
+
+    let details = serde_json::json!({
+        "setting": PLEX_GUIDE_RELOAD_KEY,
+        "enabled": settings.config.enabled,
+        "serverUrl": settings.config.server_url,
+        "minIntervalMinutes": settings.config.min_interval_minutes,
+        "debounceSeconds": settings.config.debounce_seconds,
+        "tokenChanged": settings.token.is_some()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Plex guide reload {}",
+            if settings.config.enabled {
+                "enabled"
+            } else {
+                "disabled"
+            }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(PlexReloadSettings {
+        config: plex::load_config(&mut conn),
+        has_token: plex::has_token(&mut conn),
+        status: plex::status(&mut conn),
+    })
+}
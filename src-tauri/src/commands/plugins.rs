@@ -0,0 +1,77 @@
+//! Plugin Tauri commands
+//!
+//! Lists the plugins found by `crate::plugins` and toggles them.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::plugins::{self, PluginInfo, PLUGIN_ENABLED_KEY_PREFIX};
+use crate::server::AppState;
+
+/// List the discovered plugins with their enable toggle and load errors
+#[tauri::command]
+pub fn list_plugins(db: State<DbConnection>) -> Result<Vec<PluginInfo>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(plugins::list(&mut conn))
+}
+
+/// Turn a plugin on or off
+///
+/// The next match run uses the new set; the playlist is regenerated.
+#[tauri::command]
+pub fn set_plugin_enabled(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+    name: String,
+    enabled: bool,
+) -> Result<Vec<PluginInfo>, String> {
+    if !plugins::exists(&name) {
+        return Err(format!("Plugin '{}' not found", name));
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    plugins::set_enabled(&mut conn, &name, enabled)
+        .map_err(|e| format!("Failed to save plugin setting: {}", e))?;
+    server_state.bump_epg_generation();
+
+    let details = serde_json::json!({
+        "setting": format!("{}{}", PLUGIN_ENABLED_KEY_PREFIX, name),
+        "enabled": enabled,
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Plugin {} {}",
+            name,
+            if enabled { "enabled" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    Ok(plugins::list(&mut conn))
+}
+
+/// Rescan the plugins directory
+#[tauri::command]
+pub fn reload_plugins(
+    db: State<DbConnection>,
+    server_state: State<AppState>,
+) -> Result<Vec<PluginInfo>, String> {
+    plugins::reload();
+    server_state.bump_epg_generation();
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(plugins::list(&mut conn))
+}
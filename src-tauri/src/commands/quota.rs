@@ -0,0 +1,88 @@
+//! Account quota Tauri commands
+//!
+//! Configures the monthly limits in `crate::quota` and reports usage.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::quota::{self, AccountQuotaStatus};
+
+/// Get the quota and current-month usage of every account
+#[tauri::command]
+pub fn get_account_quotas(db: State<DbConnection>) -> Result<Vec<AccountQuotaStatus>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    quota::list_status(&mut conn).map_err(|e| format!("Failed to load quotas: {}", e))
+}
+
+/// Set the monthly limits of an account
+///
+/// Omitting both limits removes the quota. Limits must be positive.
+#[tauri::command]
+pub fn set_account_quota(
+    db: State<DbConnection>,
+    account_id: i32,
+    monthly_gb: Option<f64>,
+    monthly_hours: Option<f64>,
+    block_when_exceeded: bool,
+) -> Result<(), String> {
+    for limit in [monthly_gb, monthly_hours].into_iter().flatten() {
+        if !limit.is_finite() || limit <= 0.0 {
+            return Err("Quota limits must be greater than zero".to_string());
+        }
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let account_name: String = {
+        use crate::db::schema::accounts;
+        use diesel::prelude::*;
+
+        accounts::table
+            .filter(accounts::id.eq(account_id))
+            .select(accounts::name)
+            .first(&mut conn)
+            .map_err(|_| format!("Account {} not found", account_id))?
+    };
+
+    quota::save_quota(
+        &mut conn,
+        account_id,
+        monthly_gb,
+        monthly_hours,
+        block_when_exceeded,
+    )
+    .map_err(|e| format!("Failed to save quota: {}", e))?;
+
+    let details = serde_json::json!({
+        "accountId": account_id,
+        "monthlyGb": monthly_gb,
+        "monthlyHours": monthly_hours,
+        "blockWhenExceeded": block_when_exceeded,
+    });
+    let message = if monthly_gb.is_none() && monthly_hours.is_none() {
+        format!(
+            "Configuration changed: Quota removed for account '{}'",
+            account_name
+        )
+    } else {
+        format!(
+            "Configuration changed: Quota set for account '{}'",
+            account_name
+        )
+    };
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &message,
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+//! System service Tauri commands
+//!
+//! Installs the headless server as a systemd unit, launchd daemon or Windows
+//! boot task so streaming continues after the user logs out (see
+//! `crate::service`).
+
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::service::{self, ServiceDefinition, ServiceStatus};
+
+fn app_data_dir(app: &AppHandle) -> Result<std::path::PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
+
+/// Get whether the system service is supported and installed
+#[tauri::command]
+pub async fn get_service_status() -> Result<ServiceStatus, String> {
+    tauri::async_runtime::spawn_blocking(service::status)
+        .await
+        .map_err(|e| format!("Failed to check service status: {}", e))
+}
+
+/// Get the service definition for this platform without installing it
+///
+/// Lets users review the generated file or install it by hand.
+#[tauri::command]
+pub fn get_service_definition(app: AppHandle) -> Result<ServiceDefinition, String> {
+    service::current_definition(&app_data_dir(&app)?)
+}
+
+/// Install and start the system service
+///
+/// The OS asks for administrator rights.
+#[tauri::command]
+pub async fn install_service(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<ServiceDefinition, String> {
+    let data_dir = app_data_dir(&app)?;
+    let definition = tauri::async_runtime::spawn_blocking(move || service::install(&data_dir))
+        .await
+        .map_err(|e| format!("Failed to install service: {}", e))??;
+
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::json!({
+            "kind": definition.kind,
+            "location": definition.install_location,
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            "System service installed",
+            Some(&details.to_string()),
+        );
+    }
+    Ok(definition)
+}
+
+/// Stop and remove the system service
+///
+/// The OS asks for administrator rights.
+#[tauri::command]
+pub async fn uninstall_service(db: State<'_, DbConnection>) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(service::uninstall)
+        .await
+        .map_err(|e| format!("Failed to remove service: {}", e))??;
+
+    if let Ok(mut conn) = db.get_connection() {
+        let _ = log_event_internal(&mut conn, "info", "system", "System service removed", None);
+    }
+    Ok(())
+}
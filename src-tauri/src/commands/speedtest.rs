@@ -0,0 +1,443 @@
+//! Provider speed test commands
+//!
+//! Downloads a few seconds of a representative live stream from a provider,
+//! measures time to first byte and throughput, and keeps a history per
+//! account. A warning is logged when the measured bandwidth cannot sustain the
+//! qualities the provider advertises.
+//!
+//! A speed test holds a provider connection while it runs, so it is refused
+//! when every tuner slot is in use. It is also refused in low resource mode,
+//! which avoids stream probing.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::credentials::CredentialManager;
+use crate::db::schema::{accounts, provider_speedtests, xtream_channels};
+use crate::db::{Account, DbConnection, ProviderSpeedtest};
+use crate::server::stream::build_stream_url;
+use crate::server::AppState;
+use crate::xtream::quality::qualities_from_json;
+
+/// Connect timeout for the speed test request
+const SPEEDTEST_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Maximum time spent downloading after the first byte
+const SPEEDTEST_DOWNLOAD_DURATION: Duration = Duration::from_secs(8);
+
+/// Stop early after this many bytes (enough for a stable estimate)
+const SPEEDTEST_MAX_BYTES: usize = 16 * 1024 * 1024;
+
+/// Results kept per account
+const SPEEDTEST_HISTORY_LIMIT: i64 = 50;
+
+/// Bandwidth needed to sustain each quality tier (kbps), best first
+const QUALITY_BITRATES_KBPS: [(&str, i32); 4] =
+    [("4K", 25_000), ("FHD", 8_000), ("HD", 5_000), ("SD", 2_000)];
+
+/// Speed test response
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedtestResponse {
+    pub result: ProviderSpeedtest,
+    /// Qualities advertised by the provider's streams
+    pub advertised_qualities: Vec<String>,
+    /// Advertised qualities the measured bandwidth cannot sustain
+    pub unsupported_qualities: Vec<String>,
+}
+
+/// Measured download
+struct SpeedtestMeasurement {
+    ttfb: Duration,
+    bytes: usize,
+    download: Duration,
+}
+
+impl SpeedtestMeasurement {
+    fn throughput_kbps(&self) -> i32 {
+        throughput_kbps(self.bytes, self.download)
+    }
+}
+
+/// Throughput in kilobits per second (0 for an empty or instant download)
+fn throughput_kbps(bytes: usize, duration: Duration) -> i32 {
+    let secs = duration.as_secs_f64();
+    if bytes == 0 || secs <= 0.0 {
+        return 0;
+    }
+    ((bytes as f64 * 8.0 / 1000.0) / secs).round().min(i32::MAX as f64) as i32
+}
+
+/// Rank of a quality label (0 = best), or None if unknown
+fn quality_rank(quality: &str) -> Option<usize> {
+    QUALITY_BITRATES_KBPS
+        .iter()
+        .position(|(label, _)| label.eq_ignore_ascii_case(quality))
+}
+
+/// Pick the stream to test: the one advertising the best quality
+///
+/// Testing the most demanding stream shows whether the top tier is usable.
+fn pick_representative_stream(candidates: &[(i32, Vec<String>)]) -> Option<i32> {
+    candidates
+        .iter()
+        .min_by_key(|(stream_id, qualities)| {
+            let best = qualities
+                .iter()
+                .filter_map(|q| quality_rank(q))
+                .min()
+                .unwrap_or(QUALITY_BITRATES_KBPS.len());
+            (best, *stream_id)
+        })
+        .map(|(stream_id, _)| *stream_id)
+}
+
+/// Distinct advertised qualities, best first
+fn advertised_qualities(candidates: &[(i32, Vec<String>)]) -> Vec<String> {
+    QUALITY_BITRATES_KBPS
+        .iter()
+        .filter(|(label, _)| {
+            candidates
+                .iter()
+                .any(|(_, qualities)| qualities.iter().any(|q| q.eq_ignore_ascii_case(label)))
+        })
+        .map(|(label, _)| label.to_string())
+        .collect()
+}
+
+/// Advertised qualities whose bitrate exceeds the measured throughput
+fn unsupported_qualities(advertised: &[String], throughput_kbps: i32) -> Vec<String> {
+    QUALITY_BITRATES_KBPS
+        .iter()
+        .filter(|(label, required)| {
+            *required > throughput_kbps && advertised.iter().any(|q| q.eq_ignore_ascii_case(label))
+        })
+        .map(|(label, _)| label.to_string())
+        .collect()
+}
+
+/// Download the start of a stream, timing the first byte and the transfer
+async fn measure_stream(url: &str) -> Result<SpeedtestMeasurement, String> {
+    let client = crate::dns::configure(reqwest::Client::builder())
+        .connect_timeout(SPEEDTEST_CONNECT_TIMEOUT)
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let started = Instant::now();
+    let mut response = tokio::time::timeout(SPEEDTEST_CONNECT_TIMEOUT * 2, client.get(url).send())
+        .await
+        .map_err(|_| "Provider did not respond".to_string())?
+        .map_err(|e| format!("Request failed: {}", e.without_url()))?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP error: {}", response.status()));
+    }
+
+    let first = tokio::time::timeout(SPEEDTEST_CONNECT_TIMEOUT, response.chunk())
+        .await
+        .map_err(|_| "No data received from stream".to_string())?
+        .map_err(|e| format!("Download failed: {}", e.without_url()))?;
+    let ttfb = started.elapsed();
+
+    let mut bytes = first.map(|chunk| chunk.len()).unwrap_or(0);
+    let download_started = Instant::now();
+    let deadline = tokio::time::Instant::now() + SPEEDTEST_DOWNLOAD_DURATION;
+
+    while bytes < SPEEDTEST_MAX_BYTES {
+        match tokio::time::timeout_at(deadline, response.chunk()).await {
+            Ok(Ok(Some(chunk))) => bytes += chunk.len(),
+            Ok(Ok(None)) | Err(_) => break,
+            Ok(Err(e)) => return Err(format!("Download failed: {}", e.without_url())),
+        }
+    }
+
+    Ok(SpeedtestMeasurement {
+        ttfb,
+        bytes,
+        download: download_started.elapsed(),
+    })
+}
+
+/// Store a result and trim the account's history
+fn save_result(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    stream_id: Option<i32>,
+    outcome: &Result<SpeedtestMeasurement, String>,
+    unsupported: &[String],
+) -> QueryResult<ProviderSpeedtest> {
+    let (success, ttfb_ms, bytes, download_ms, kbps, error) = match outcome {
+        Ok(m) => (
+            1,
+            Some(m.ttfb.as_millis() as i32),
+            m.bytes as i64,
+            m.download.as_millis() as i32,
+            Some(m.throughput_kbps()),
+            None,
+        ),
+        Err(e) => (0, None, 0, 0, None, Some(e.clone())),
+    };
+    let unsupported_json = (!unsupported.is_empty())
+        .then(|| serde_json::to_string(unsupported).unwrap_or_default());
+
+    conn.transaction(|conn| {
+        let result: ProviderSpeedtest = diesel::insert_into(provider_speedtests::table)
+            .values((
+                provider_speedtests::account_id.eq(account_id),
+                provider_speedtests::stream_id.eq(stream_id),
+                provider_speedtests::success.eq(success),
+                provider_speedtests::ttfb_ms.eq(ttfb_ms),
+                provider_speedtests::bytes_downloaded.eq(bytes),
+                provider_speedtests::download_ms.eq(download_ms),
+                provider_speedtests::throughput_kbps.eq(kbps),
+                provider_speedtests::unsupported_qualities.eq(unsupported_json),
+                provider_speedtests::error.eq(error),
+            ))
+            .returning(ProviderSpeedtest::as_returning())
+            .get_result(conn)?;
+
+        // Keep only the most recent results
+        let keep: Vec<Option<i32>> = provider_speedtests::table
+            .filter(provider_speedtests::account_id.eq(account_id))
+            .order((provider_speedtests::tested_at.desc(), provider_speedtests::id.desc()))
+            .limit(SPEEDTEST_HISTORY_LIMIT)
+            .select(provider_speedtests::id)
+            .load(conn)?;
+        diesel::delete(
+            provider_speedtests::table
+                .filter(provider_speedtests::account_id.eq(account_id))
+                .filter(provider_speedtests::id.ne_all(keep.into_iter().flatten())),
+        )
+        .execute(conn)?;
+
+        Ok(result)
+    })
+}
+
+/// Why a speed test cannot run now, if it cannot
+fn speedtest_refusal(low_resource_mode: bool, tuner_available: bool) -> Option<&'static str> {
+    if low_resource_mode {
+        Some("Speed tests are disabled in low resource mode")
+    } else if !tuner_available {
+        Some("All tuners are in use - try again when a stream ends")
+    } else {
+        None
+    }
+}
+
+/// Run a speed test against a provider account
+///
+/// Downloads a few seconds of the account's highest-quality stream. The
+/// result is stored in the history even when the test fails.
+#[tauri::command]
+pub async fn run_provider_speedtest(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    server_state: State<'_, AppState>,
+    account_id: i32,
+) -> Result<SpeedtestResponse, String> {
+    if let Some(reason) = speedtest_refusal(
+        crate::low_resource::is_enabled(),
+        server_state.stream_manager().can_start_stream(),
+    ) {
+        return Err(reason.to_string());
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())?;
+
+    let (account, candidates) = {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+
+        let account: Account = accounts::table
+            .filter(accounts::id.eq(account_id))
+            .first(&mut conn)
+            .map_err(|_| "Account not found".to_string())?;
+
+        let candidates: Vec<(i32, Vec<String>)> = xtream_channels::table
+            .filter(xtream_channels::account_id.eq(account_id))
+            .select((xtream_channels::stream_id, xtream_channels::qualities))
+            .load::<(i32, Option<String>)>(&mut conn)
+            .map_err(|e| format!("Failed to load streams: {}", e))?
+            .into_iter()
+            .map(|(stream_id, qualities)| {
+                (stream_id, qualities.as_deref().map(qualities_from_json).unwrap_or_default())
+            })
+            .collect();
+
+        (account, candidates)
+    };
+
+    let stream_id = pick_representative_stream(&candidates)
+        .ok_or_else(|| "No streams found for this account - refresh channels first".to_string())?;
+    let advertised = advertised_qualities(&candidates);
+
+    let url = if account.is_m3u() {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+        xtream_channels::table
+            .filter(xtream_channels::account_id.eq(account_id))
+            .filter(xtream_channels::stream_id.eq(stream_id))
+            .select(xtream_channels::stream_url)
+            .first::<Option<String>>(&mut conn)
+            .ok()
+            .flatten()
+            .ok_or_else(|| "Stream URL missing - refresh channels first".to_string())?
+    } else {
+        // Password is never logged or returned
+        let password = CredentialManager::new(app_data_dir)
+            .retrieve_password(&account_id.to_string(), &account.password_encrypted)
+            .map_err(|_| "Failed to retrieve credentials".to_string())?;
+        build_stream_url(&account.server_url, &account.username, &password, stream_id)
+    };
+
+    let outcome = measure_stream(&url).await;
+    let unsupported = match &outcome {
+        Ok(m) => unsupported_qualities(&advertised, m.throughput_kbps()),
+        Err(_) => Vec::new(),
+    };
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+    let result = save_result(&mut conn, account_id, Some(stream_id), &outcome, &unsupported)
+        .map_err(|e| format!("Failed to save speed test result: {}", e))?;
+
+    let details = serde_json::json!({
+        "accountId": account_id,
+        "accountName": account.name,
+        "streamId": stream_id,
+        "ttfbMs": result.ttfb_ms,
+        "throughputKbps": result.throughput_kbps,
+        "advertisedQualities": advertised,
+        "unsupportedQualities": unsupported,
+        "error": result.error,
+    });
+    let (level, message) = match &outcome {
+        Err(e) => ("warn", format!("Speed test failed: {} - {}", account.name, e)),
+        Ok(m) if !unsupported.is_empty() => (
+            "warn",
+            format!(
+                "Speed test: {} measured {:.1} Mbps, too slow for {}",
+                account.name,
+                m.throughput_kbps() as f64 / 1000.0,
+                unsupported.join(", ")
+            ),
+        ),
+        Ok(m) => (
+            "info",
+            format!(
+                "Speed test: {} measured {:.1} Mbps (TTFB {} ms)",
+                account.name,
+                m.throughput_kbps() as f64 / 1000.0,
+                m.ttfb.as_millis()
+            ),
+        ),
+    };
+    let _ = log_event_internal(&mut conn, level, "connection", &message, Some(&details.to_string()));
+
+    Ok(SpeedtestResponse {
+        result,
+        advertised_qualities: advertised,
+        unsupported_qualities: unsupported,
+    })
+}
+
+/// Get speed test history for an account (newest first)
+#[tauri::command]
+pub fn get_provider_speedtest_history(
+    db: State<DbConnection>,
+    account_id: i32,
+    limit: Option<i64>,
+) -> Result<Vec<ProviderSpeedtest>, String> {
+    let mut conn = db
+        .get_read_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    provider_speedtests::table
+        .filter(provider_speedtests::account_id.eq(account_id))
+        .order((provider_speedtests::tested_at.desc(), provider_speedtests::id.desc()))
+        .limit(limit.unwrap_or(SPEEDTEST_HISTORY_LIMIT).clamp(1, SPEEDTEST_HISTORY_LIMIT))
+        .load(&mut conn)
+        .map_err(|e| format!("Failed to load speed test history: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidates() -> Vec<(i32, Vec<String>)> {
+        vec![
+            (10, vec!["SD".to_string()]),
+            (20, vec!["HD".to_string(), "SD".to_string()]),
+            (30, vec!["FHD".to_string()]),
+            (5, vec![]),
+        ]
+    }
+
+    #[test]
+    fn test_throughput_kbps() {
+        assert_eq!(throughput_kbps(1_000_000, Duration::from_secs(1)), 8_000);
+        assert_eq!(throughput_kbps(0, Duration::from_secs(1)), 0);
+        assert_eq!(throughput_kbps(1000, Duration::ZERO), 0);
+    }
+
+    #[test]
+    fn test_speedtest_refusal() {
+        assert_eq!(speedtest_refusal(false, true), None);
+        assert!(speedtest_refusal(true, true)
+            .unwrap()
+            .contains("low resource mode"));
+        assert!(speedtest_refusal(false, false).unwrap().contains("tuners"));
+    }
+
+    #[test]
+    fn test_pick_representative_stream() {
+        assert_eq!(pick_representative_stream(&candidates()), Some(30));
+        assert_eq!(pick_representative_stream(&[(7, vec![])]), Some(7));
+        assert_eq!(pick_representative_stream(&[]), None);
+    }
+
+    #[test]
+    fn test_advertised_and_unsupported_qualities() {
+        let advertised = advertised_qualities(&candidates());
+        assert_eq!(advertised, vec!["FHD", "HD", "SD"]);
+
+        assert_eq!(unsupported_qualities(&advertised, 6_000), vec!["FHD"]);
+        assert_eq!(unsupported_qualities(&advertised, 3_000), vec!["FHD", "HD"]);
+        assert!(unsupported_qualities(&advertised, 30_000).is_empty());
+    }
+
+    #[test]
+    fn test_save_result_trims_history() {
+        let mut conn = crate::db::test_connection();
+
+        for i in 0..(SPEEDTEST_HISTORY_LIMIT + 5) {
+            let outcome = Ok(SpeedtestMeasurement {
+                ttfb: Duration::from_millis(120),
+                bytes: 1_000_000,
+                download: Duration::from_secs(1),
+            });
+            let saved = save_result(&mut conn, 1, Some(i as i32), &outcome, &[]).unwrap();
+            assert_eq!(saved.throughput_kbps, Some(8_000));
+        }
+        let failed = save_result(&mut conn, 1, None, &Err("HTTP error: 403".to_string()), &[]).unwrap();
+        assert_eq!(failed.success, 0);
+
+        let count: i64 = provider_speedtests::table
+            .filter(provider_speedtests::account_id.eq(1))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(count, SPEEDTEST_HISTORY_LIMIT);
+    }
+}
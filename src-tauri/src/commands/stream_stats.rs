@@ -0,0 +1,42 @@
+//! Stream statistics commands
+//!
+//! Daily and weekly traffic per channel and account, from the sessions
+//! recorded in `stream_stats` (see `crate::server::stream`).
+
+use tauri::State;
+
+use crate::db::DbConnection;
+use crate::server::stream::{stream_stats_by_period, StatsPeriod, StreamStatsBucket};
+
+/// Periods returned when the caller doesn't ask for a number
+const DEFAULT_DAYS: u32 = 30;
+const DEFAULT_WEEKS: u32 = 12;
+
+/// Most periods one request can cover (statistics are kept for a year)
+const MAX_DAYS: u32 = 366;
+const MAX_WEEKS: u32 = 53;
+
+/// Get traffic per channel and account for the last `count` days or weeks
+///
+/// The current day or week is included; weeks start on Monday. Newest
+/// period first, then by bytes.
+#[tauri::command]
+pub fn get_stream_stats(
+    db: State<DbConnection>,
+    period: StatsPeriod,
+    count: Option<u32>,
+) -> Result<Vec<StreamStatsBucket>, String> {
+    let (default, max) = match period {
+        StatsPeriod::Day => (DEFAULT_DAYS, MAX_DAYS),
+        StatsPeriod::Week => (DEFAULT_WEEKS, MAX_WEEKS),
+    };
+    let count = count.unwrap_or(default).clamp(1, max);
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let since = period.since(chrono::Local::now().date_naive(), count);
+    stream_stats_by_period(&mut conn, period, since)
+        .map_err(|e| format!("Failed to load stream statistics: {}", e))
+}
@@ -0,0 +1,86 @@
+//! Background task commands
+//!
+//! Start scans, matching and EPG refreshes as tasks (see `crate::tasks`),
+//! and query or cancel them. The synchronous commands are unchanged.
+
+use std::sync::Arc;
+
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::commands::channels::scan_channels;
+use crate::commands::epg::{progress_emitter, refresh_all_epg_sources_internal};
+use crate::commands::matcher::run_channel_matching_internal;
+use crate::db::DbConnection;
+use crate::tasks::{self, TaskInfo};
+
+/// Scan an account's channels in the background
+///
+/// Returns the task ID; the result is a `ScanChannelsResponse`.
+#[tauri::command]
+pub fn start_channel_scan_task(app: AppHandle, account_id: i32) -> String {
+    tasks::spawn("channel_scan", move |_| async move {
+        let db = app.state::<DbConnection>();
+        scan_channels(app.clone(), db, account_id).await
+    })
+}
+
+/// Run channel matching in the background
+///
+/// Progress carries the `match_progress` payloads (which are still emitted);
+/// the result is a `MatchResponse`.
+#[tauri::command]
+pub fn start_channel_matching_task(app: AppHandle, threshold: Option<f64>) -> String {
+    tasks::spawn("channel_matching", move |progress| async move {
+        let db = app.state::<DbConnection>();
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+
+        let app_data_dir = app.path().app_data_dir().ok();
+        run_channel_matching_internal(&mut conn, threshold, app_data_dir.as_deref(), |payload| {
+            let _ = app.emit("match_progress", &payload);
+            progress.report(payload);
+        })
+    })
+}
+
+/// Refresh all active EPG sources in the background
+///
+/// Progress carries the latest `epg-refresh-progress` payload (which is still
+/// emitted). Partial failures fail the task with the same message as
+/// `refresh_all_epg_sources`.
+#[tauri::command]
+pub fn start_epg_refresh_task(app: AppHandle) -> String {
+    tasks::spawn("epg_refresh", move |progress| async move {
+        let emit = progress_emitter(&app);
+        let on_progress = Arc::new(move |update| {
+            if let Ok(payload) = serde_json::to_value(&update) {
+                progress.report(payload);
+            }
+            emit(update);
+        });
+
+        let db = app.state::<DbConnection>();
+        refresh_all_epg_sources_internal(&db, app.path().app_data_dir().ok(), on_progress).await
+    })
+}
+
+/// Get a task's status, progress and result
+#[tauri::command]
+pub fn get_task_status(task_id: String) -> Result<TaskInfo, String> {
+    tasks::status(&task_id).ok_or_else(|| "Task not found".to_string())
+}
+
+/// List running and recently finished tasks, newest first
+#[tauri::command]
+pub fn list_tasks() -> Vec<TaskInfo> {
+    tasks::list()
+}
+
+/// Cancel a running task
+///
+/// Work the task already saved is kept. Returns the task's state afterwards.
+#[tauri::command]
+pub fn cancel_task(task_id: String) -> Result<TaskInfo, String> {
+    tasks::cancel(&task_id).ok_or_else(|| "Task not found".to_string())
+}
@@ -100,7 +100,7 @@ fn clear_test_data(conn: &mut crate::db::DbPooledConnection) -> Result<(), Strin
         .execute(conn)
         .map_err(|e| format!("Failed to clear xmltv_channels: {}", e))?;
 
-    diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.le(10)))
+    diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.between(1, 10)))
         .execute(conn)
         .map_err(|e| format!("Failed to clear xmltv_sources: {}", e))?;
 
@@ -0,0 +1,51 @@
+//! Tuner reservation and priority Tauri commands
+//!
+//! Configures the rules in `crate::tuner_priority`.
+
+use tauri::State;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::tuner_priority::{self, TunerPolicy, TunerPriorityConfig, TUNER_PRIORITY_KEY};
+
+/// Get the tuner reservation and priority settings
+#[tauri::command]
+pub fn get_tuner_priority(db: State<DbConnection>) -> Result<TunerPriorityConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    Ok(tuner_priority::load_config(&mut conn))
+}
+
+/// Save the tuner reservation and priority settings
+#[tauri::command]
+pub fn set_tuner_priority(
+    db: State<DbConnection>,
+    config: TunerPriorityConfig,
+) -> Result<TunerPriorityConfig, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let config = tuner_priority::save_config(&mut conn, &config)?;
+
+    let policy = match config.policy {
+        TunerPolicy::Off => "off",
+        TunerPolicy::Reject => "reserve tuners",
+        TunerPolicy::Preempt => "preempt normal streams",
+    };
+    let details = serde_json::json!({
+        "setting": TUNER_PRIORITY_KEY,
+        "value": config
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: Tuner priority policy {}", policy),
+        Some(&details.to_string()),
+    );
+
+    Ok(config)
+}
@@ -3,11 +3,23 @@
 //! Story 6-5: Auto-Update Mechanism with Signature Verification
 //!
 //! Implements commands for checking, downloading, and installing updates.
+//!
+//! Updates follow a release channel (stable or beta). A release manifest may
+//! carry a `rollout` percentage (0-100) for staged rollouts; each install
+//! picks a persistent bucket and only sees the update once the rollout
+//! reaches it. Users can defer an update, and every check and installed
+//! version change is written to the event log.
+//!
+//! The current version can be pinned to skip updates entirely. Before an
+//! update is installed the running version is remembered, so
+//! `rollback_update` can reinstall it from that release's manifest.
 
 use diesel::prelude::*;
+use rand::Rng;
 use serde::Serialize;
 use tauri::{AppHandle, State};
 
+use crate::commands::logs::log_event_internal;
 use crate::db::{schema::settings, DbConnection, Setting};
 
 // ============================================================================
@@ -25,6 +37,9 @@ pub struct UpdateInfo {
     pub notes: Option<String>,
     /// Release date (null if no update)
     pub date: Option<String>,
+    /// Set while the user has deferred updates (ISO 8601)
+    #[serde(rename = "deferredUntil")]
+    pub deferred_until: Option<String>,
 }
 
 /// Update settings response
@@ -39,6 +54,43 @@ pub struct UpdateSettings {
     /// Current application version
     #[serde(rename = "currentVersion")]
     pub current_version: String,
+    /// Release channel ("stable" or "beta")
+    pub channel: ReleaseChannel,
+    /// Updates are not offered automatically before this time (ISO 8601)
+    #[serde(rename = "deferredUntil")]
+    pub deferred_until: Option<String>,
+    /// Version pinned by the user (updates are skipped while set)
+    #[serde(rename = "pinnedVersion")]
+    pub pinned_version: Option<String>,
+    /// Version `rollback_update` would reinstall (the one replaced by the last update)
+    #[serde(rename = "rollbackVersion")]
+    pub rollback_version: Option<String>,
+}
+
+/// Release channel to receive updates from
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum ReleaseChannel {
+    #[default]
+    Stable,
+    Beta,
+}
+
+impl ReleaseChannel {
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "stable" => Some(Self::Stable),
+            "beta" => Some(Self::Beta),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Stable => "stable",
+            Self::Beta => "beta",
+        }
+    }
 }
 
 /// Download progress information
@@ -56,8 +108,28 @@ pub struct DownloadProgress {
 // Settings Keys
 // ============================================================================
 
-const AUTO_CHECK_UPDATES_KEY: &str = "auto_check_updates";
+pub(crate) const AUTO_CHECK_UPDATES_KEY: &str = "auto_check_updates";
 const LAST_UPDATE_CHECK_KEY: &str = "last_update_check";
+const UPDATE_CHANNEL_KEY: &str = "update_channel";
+const UPDATE_DEFERRED_UNTIL_KEY: &str = "update_deferred_until";
+const UPDATE_ROLLOUT_BUCKET_KEY: &str = "update_rollout_bucket";
+const INSTALLED_VERSION_KEY: &str = "installed_version";
+const UPDATE_PINNED_VERSION_KEY: &str = "update_pinned_version";
+const UPDATE_PREVIOUS_VERSION_KEY: &str = "update_previous_version";
+
+/// Manifest of a specific release, used to reinstall the previous version
+const RELEASE_MANIFEST_URL_TEMPLATE: &str =
+    "https://github.com/javipelopi/streamforge/releases/download/v{version}/latest.json";
+
+/// Manifest for the beta channel (rolling `beta` release; stable uses tauri.conf.json)
+const BETA_UPDATE_ENDPOINT: &str =
+    "https://github.com/javipelopi/streamforge/releases/download/beta/latest.json";
+
+/// Default deferral when the user picks "remind me later"
+const DEFAULT_DEFERRAL_DAYS: u32 = 7;
+
+/// Longest allowed deferral
+const MAX_DEFERRAL_DAYS: u32 = 90;
 
 // ============================================================================
 // Helper Functions
@@ -101,6 +173,192 @@ fn set_setting_value(
     Ok(())
 }
 
+/// Remove a setting
+fn delete_setting(conn: &mut diesel::SqliteConnection, key: &str) -> Result<(), String> {
+    diesel::delete(settings::table.filter(settings::key.eq(key)))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Manifest URL of a specific release
+fn release_manifest_url(version: &str) -> Result<url::Url, String> {
+    url::Url::parse(&RELEASE_MANIFEST_URL_TEMPLATE.replace("{version}", version))
+        .map_err(|e| format!("Invalid release manifest URL: {}", e))
+}
+
+/// Download an update, logging start and completion
+///
+/// The Tauri updater verifies the signature as part of the download.
+async fn download_update(
+    db: &DbConnection,
+    update: &tauri_plugin_updater::Update,
+) -> Result<Vec<u8>, String> {
+    let version = update.version.clone();
+
+    // Log update download started
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::json!({
+            "targetVersion": version
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!("Downloading update v{}", version),
+            Some(&details.to_string()),
+        );
+    }
+
+    let bytes = update
+        .download(
+            |_chunk_len, _content_len| {
+                // Progress callback - emit event to frontend
+                // We could emit progress events here but for simplicity
+                // the frontend will show an indeterminate progress bar
+            },
+            || {
+                // Download finished callback
+            },
+        )
+        .await
+        .map_err(|e| format!("Failed to download update: {}", e))?;
+
+    // Log download complete
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::json!({
+            "targetVersion": version,
+            "downloadSize": bytes.len()
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!("Update v{} downloaded, installing...", version),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(bytes)
+}
+
+/// Get the configured release channel
+fn get_release_channel(conn: &mut diesel::SqliteConnection) -> ReleaseChannel {
+    get_string_setting(conn, UPDATE_CHANNEL_KEY)
+        .and_then(|v| ReleaseChannel::parse(&v))
+        .unwrap_or_default()
+}
+
+/// Get the active deferral, dropping it once it has expired
+fn get_active_deferral(conn: &mut diesel::SqliteConnection) -> Option<String> {
+    let deferred_until = get_string_setting(conn, UPDATE_DEFERRED_UNTIL_KEY)?;
+    if is_deferral_active(&deferred_until, chrono::Utc::now()) {
+        Some(deferred_until)
+    } else {
+        None
+    }
+}
+
+/// Whether a stored deferral timestamp is still in the future
+fn is_deferral_active(deferred_until: &str, now: chrono::DateTime<chrono::Utc>) -> bool {
+    chrono::DateTime::parse_from_rfc3339(deferred_until)
+        .map(|until| until > now)
+        .unwrap_or(false)
+}
+
+/// Get this install's staged rollout bucket (0-99), creating it on first use
+fn get_rollout_bucket(conn: &mut diesel::SqliteConnection) -> u8 {
+    if let Some(bucket) = get_string_setting(conn, UPDATE_ROLLOUT_BUCKET_KEY)
+        .and_then(|v| v.parse::<u8>().ok())
+        .filter(|b| *b < 100)
+    {
+        return bucket;
+    }
+
+    let bucket: u8 = rand::thread_rng().gen_range(0..100);
+    let _ = set_setting_value(conn, UPDATE_ROLLOUT_BUCKET_KEY, &bucket.to_string());
+    bucket
+}
+
+/// Whether a release manifest's staged rollout includes this install
+///
+/// Manifests without a `rollout` field are available to everyone.
+fn rollout_includes(manifest: &serde_json::Value, bucket: u8) -> bool {
+    match manifest.get("rollout").and_then(|v| v.as_u64()) {
+        Some(percentage) => u64::from(bucket) < percentage,
+        None => true,
+    }
+}
+
+/// Build an updater for the configured release channel
+fn build_updater(
+    app: &AppHandle,
+    channel: ReleaseChannel,
+) -> Result<tauri_plugin_updater::Updater, String> {
+    use tauri_plugin_updater::UpdaterExt;
+
+    let mut builder = app.updater_builder();
+    if channel == ReleaseChannel::Beta {
+        let endpoint = url::Url::parse(BETA_UPDATE_ENDPOINT)
+            .map_err(|e| format!("Invalid beta update endpoint: {}", e))?;
+        builder = builder
+            .endpoints(vec![endpoint])
+            .map_err(|e| format!("Failed to configure beta channel: {}", e))?;
+    }
+
+    builder
+        .build()
+        .map_err(|e| format!("Failed to initialize updater: {}", e))
+}
+
+/// Check the channel's manifest, honoring the staged rollout
+async fn find_update(
+    app: &AppHandle,
+    conn: &mut diesel::SqliteConnection,
+) -> Result<Option<tauri_plugin_updater::Update>, String> {
+    if get_string_setting(conn, UPDATE_PINNED_VERSION_KEY).is_some() {
+        return Ok(None);
+    }
+
+    let channel = get_release_channel(conn);
+    let bucket = get_rollout_bucket(conn);
+    let updater = build_updater(app, channel)?;
+
+    match updater.check().await {
+        Ok(Some(update)) if rollout_includes(&update.raw_json, bucket) => Ok(Some(update)),
+        Ok(_) => Ok(None),
+        Err(e) => Err(e.to_string()),
+    }
+}
+
+/// Record the running version and log when it changed since the last start
+///
+/// Called once at startup.
+pub fn record_installed_version(conn: &mut diesel::SqliteConnection) {
+    let current = env!("CARGO_PKG_VERSION");
+    let previous = get_string_setting(conn, INSTALLED_VERSION_KEY);
+
+    if previous.as_deref() == Some(current) {
+        return;
+    }
+
+    if let Some(previous) = &previous {
+        let details = serde_json::json!({
+            "previousVersion": previous,
+            "version": current
+        });
+        let _ = log_event_internal(
+            conn,
+            "info",
+            "system",
+            &format!("StreamForge updated from v{} to v{}", previous, current),
+            Some(&details.to_string()),
+        );
+    }
+
+    let _ = set_setting_value(conn, INSTALLED_VERSION_KEY, current);
+}
+
 // ============================================================================
 // Tauri Commands
 // ============================================================================
@@ -109,28 +367,56 @@ fn set_setting_value(
 ///
 /// Story 6-5: AC #1, #2 - Check for updates using Tauri updater plugin
 /// Uses signature verification via the plugin's built-in mechanism.
+/// Follows the release channel and staged rollout; every check is logged.
 #[tauri::command]
 pub async fn check_for_update(
     app: AppHandle,
     db: State<'_, DbConnection>,
 ) -> Result<UpdateInfo, String> {
-    use tauri_plugin_updater::UpdaterExt;
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
 
     // Update last check timestamp first
-    if let Ok(mut conn) = db.get_connection() {
-        let now = chrono::Utc::now().to_rfc3339();
-        let _ = set_setting_value(&mut conn, LAST_UPDATE_CHECK_KEY, &now);
-    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let _ = set_setting_value(&mut conn, LAST_UPDATE_CHECK_KEY, &now);
 
-    // Check for updates using Tauri updater plugin
-    let updater = app
-        .updater_builder()
-        .build()
-        .map_err(|e| format!("Failed to initialize updater: {}", e))?;
+    let channel = get_release_channel(&mut conn);
+    let deferred_until = get_active_deferral(&mut conn);
 
-    match updater.check().await {
+    if let Some(pinned) = get_string_setting(&mut conn, UPDATE_PINNED_VERSION_KEY) {
+        let details = serde_json::json!({ "pinnedVersion": pinned });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!("Update check skipped: pinned to v{}", pinned),
+            Some(&details.to_string()),
+        );
+        return Ok(UpdateInfo {
+            available: false,
+            version: None,
+            notes: None,
+            date: None,
+            deferred_until: None,
+        });
+    }
+
+    match find_update(&app, &mut conn).await {
         Ok(Some(update)) => {
-            // Update available
+            let details = serde_json::json!({
+                "channel": channel.as_str(),
+                "currentVersion": env!("CARGO_PKG_VERSION"),
+                "availableVersion": update.version
+            });
+            let _ = log_event_internal(
+                &mut conn,
+                "info",
+                "system",
+                &format!("Update check: v{} available ({})", update.version, channel.as_str()),
+                Some(&details.to_string()),
+            );
+
             Ok(UpdateInfo {
                 available: true,
                 version: Some(update.version.clone()),
@@ -138,37 +424,67 @@ pub async fn check_for_update(
                 // Convert OffsetDateTime to string
                 // Note: OffsetDateTime's Display impl outputs ISO 8601 format
                 date: update.date.map(|d| d.to_string()),
+                deferred_until,
             })
         }
         Ok(None) => {
-            // No update available
+            let details = serde_json::json!({
+                "channel": channel.as_str(),
+                "currentVersion": env!("CARGO_PKG_VERSION")
+            });
+            let _ = log_event_internal(
+                &mut conn,
+                "info",
+                "system",
+                &format!("Update check: up to date ({})", channel.as_str()),
+                Some(&details.to_string()),
+            );
+
             Ok(UpdateInfo {
                 available: false,
                 version: None,
                 notes: None,
                 date: None,
+                deferred_until: None,
             })
         }
         Err(e) => {
             // Error checking for updates - log to event_log and return error (Story 6-5 AC#4)
-            if let Ok(mut conn) = db.get_connection() {
-                use crate::commands::logs::log_event_internal;
-                let details = serde_json::json!({
-                    "error": e.to_string()
-                });
-                let _ = log_event_internal(
-                    &mut conn,
-                    "error",
-                    "system",
-                    &format!("Update check failed: {}", e),
-                    Some(&details.to_string()),
-                );
-            }
+            let details = serde_json::json!({
+                "channel": channel.as_str(),
+                "error": e
+            });
+            let _ = log_event_internal(
+                &mut conn,
+                "error",
+                "system",
+                &format!("Update check failed: {}", e),
+                Some(&details.to_string()),
+            );
             Err(format!("Failed to check for updates: {}", e))
         }
     }
 }
 
+/// Get the changelog of the available update for display before installing
+///
+/// Returns null when no update is available on the current channel.
+#[tauri::command]
+pub async fn get_update_changelog(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+) -> Result<Option<String>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let update = find_update(&app, &mut conn)
+        .await
+        .map_err(|e| format!("Failed to check for updates: {}", e))?;
+
+    Ok(update.map(|u| u.body.unwrap_or_default()))
+}
+
 /// Get current update settings
 ///
 /// Story 6-5: AC #4 - Auto-check preference stored in database
@@ -186,9 +502,79 @@ pub fn get_update_settings(db: State<DbConnection>) -> Result<UpdateSettings, St
         auto_check,
         last_check,
         current_version,
+        channel: get_release_channel(&mut conn),
+        deferred_until: get_active_deferral(&mut conn),
+        pinned_version: get_string_setting(&mut conn, UPDATE_PINNED_VERSION_KEY),
+        rollback_version: get_string_setting(&mut conn, UPDATE_PREVIOUS_VERSION_KEY),
     })
 }
 
+/// Set the release channel
+///
+/// Accepts "stable" or "beta".
+#[tauri::command]
+pub fn set_update_channel(db: State<DbConnection>, channel: String) -> Result<(), String> {
+    let channel = ReleaseChannel::parse(&channel)
+        .ok_or_else(|| format!("Invalid release channel: {}", channel))?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    set_setting_value(&mut conn, UPDATE_CHANNEL_KEY, channel.as_str())?;
+
+    let details = serde_json::json!({
+        "setting": UPDATE_CHANNEL_KEY,
+        "newValue": channel.as_str()
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Configuration changed: Update channel set to {}", channel.as_str()),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Defer update notifications ("remind me in a week")
+///
+/// `days` defaults to 7; 0 clears the deferral. Returns the new deferral end.
+#[tauri::command]
+pub fn defer_update(db: State<DbConnection>, days: Option<u32>) -> Result<Option<String>, String> {
+    let days = days.unwrap_or(DEFAULT_DEFERRAL_DAYS);
+    if days > MAX_DEFERRAL_DAYS {
+        return Err(format!("Updates can be deferred for at most {} days", MAX_DEFERRAL_DAYS));
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    if days == 0 {
+        delete_setting(&mut conn, UPDATE_DEFERRED_UNTIL_KEY)?;
+        return Ok(None);
+    }
+
+    let until = (chrono::Utc::now() + chrono::Duration::days(i64::from(days))).to_rfc3339();
+    set_setting_value(&mut conn, UPDATE_DEFERRED_UNTIL_KEY, &until)?;
+
+    let details = serde_json::json!({
+        "deferredUntil": until,
+        "days": days
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("Update deferred for {} days", days),
+        Some(&details.to_string()),
+    );
+
+    Ok(Some(until))
+}
+
 /// Set auto-check updates preference
 ///
 /// Story 6-5: AC #4 - Toggle auto-check preference
@@ -201,7 +587,6 @@ pub fn set_auto_check_updates(db: State<DbConnection>, enabled: bool) -> Result<
     set_setting_value(&mut conn, AUTO_CHECK_UPDATES_KEY, if enabled { "true" } else { "false" })?;
 
     // Log the setting change (Story 6-3 consistency)
-    use crate::commands::logs::log_event_internal;
     let details = serde_json::json!({
         "setting": "auto_check_updates",
         "newValue": enabled
@@ -233,77 +618,139 @@ pub fn set_auto_check_updates(db: State<DbConnection>, enabled: bool) -> Result<
 pub async fn download_and_install_update(
     app: AppHandle,
     db: State<'_, DbConnection>,
+) -> Result<(), String> {
+    // Check for update first (same channel and rollout as the check)
+    let update = {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+        match find_update(&app, &mut conn).await {
+            Ok(Some(update)) => update,
+            Ok(None) => return Err("No update available".to_string()),
+            Err(e) => return Err(format!("Failed to check for updates: {}", e)),
+        }
+    };
+
+    let bytes = download_update(&db, &update).await?;
+
+    // An explicit install ends any deferral; remember the running version for rollback
+    if let Ok(mut conn) = db.get_connection() {
+        let _ = delete_setting(&mut conn, UPDATE_DEFERRED_UNTIL_KEY);
+        let _ = set_setting_value(&mut conn, UPDATE_PREVIOUS_VERSION_KEY, env!("CARGO_PKG_VERSION"));
+    }
+
+    // Install the update (this will restart the app on most platforms)
+    update
+        .install(bytes)
+        .map_err(|e| format!("Failed to install update: {}", e))?;
+
+    // If we reach here, the update is installed and needs a restart
+    // The frontend should prompt the user to restart
+    Ok(())
+}
+
+/// Pin or unpin the current version
+///
+/// While pinned, update checks report no update and installs are refused.
+/// Returns the pinned version, or null when unpinned.
+#[tauri::command]
+pub fn set_version_pinned(db: State<DbConnection>, pinned: bool) -> Result<Option<String>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let current = env!("CARGO_PKG_VERSION");
+    if pinned {
+        set_setting_value(&mut conn, UPDATE_PINNED_VERSION_KEY, current)?;
+    } else {
+        delete_setting(&mut conn, UPDATE_PINNED_VERSION_KEY)?;
+    }
+
+    let details = serde_json::json!({
+        "setting": UPDATE_PINNED_VERSION_KEY,
+        "pinned": pinned,
+        "version": current
+    });
+    let message = if pinned {
+        format!("Configuration changed: Version pinned to v{}", current)
+    } else {
+        "Configuration changed: Version unpinned".to_string()
+    };
+    let _ = log_event_internal(&mut conn, "info", "system", &message, Some(&details.to_string()));
+
+    Ok(pinned.then(|| current.to_string()))
+}
+
+/// Reinstall the version that was replaced by the last update
+///
+/// Downloads the previous release through its own signed manifest, then pins
+/// it so the broken release is not offered again until the user unpins.
+#[tauri::command]
+pub async fn rollback_update(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
 ) -> Result<(), String> {
     use tauri_plugin_updater::UpdaterExt;
 
-    // Check for update first
+    let previous = {
+        let mut conn = db
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+        get_string_setting(&mut conn, UPDATE_PREVIOUS_VERSION_KEY)
+            .ok_or_else(|| "No previous version to roll back to".to_string())?
+    };
+    let current = env!("CARGO_PKG_VERSION");
+    if previous == current {
+        return Err(format!("v{} is already installed", previous));
+    }
+
+    // Accept exactly the previous release, even though it is older
+    let target = previous.clone();
     let updater = app
         .updater_builder()
+        .endpoints(vec![release_manifest_url(&previous)?])
+        .map_err(|e| format!("Failed to configure rollback: {}", e))?
+        .version_comparator(move |_current, release| release.version.to_string() == target)
         .build()
         .map_err(|e| format!("Failed to initialize updater: {}", e))?;
 
     let update = match updater.check().await {
         Ok(Some(update)) => update,
-        Ok(None) => return Err("No update available".to_string()),
-        Err(e) => return Err(format!("Failed to check for updates: {}", e)),
+        Ok(None) => {
+            return Err(format!(
+                "Release v{} is not available for this platform",
+                previous
+            ))
+        }
+        Err(e) => return Err(format!("Failed to fetch release v{}: {}", previous, e)),
     };
 
-    let version = update.version.clone();
-
-    // Log update download started
     if let Ok(mut conn) = db.get_connection() {
-        use crate::commands::logs::log_event_internal;
         let details = serde_json::json!({
-            "targetVersion": version
+            "fromVersion": current,
+            "toVersion": previous
         });
         let _ = log_event_internal(
             &mut conn,
-            "info",
+            "warn",
             "system",
-            &format!("Downloading update v{}", version),
+            &format!("Rolling back from v{} to v{}", current, previous),
             Some(&details.to_string()),
         );
     }
 
-    // Download and install update
-    // Note: The Tauri updater handles signature verification automatically
-    let bytes = update
-        .download(
-            |_chunk_len, _content_len| {
-                // Progress callback - emit event to frontend
-                // We could emit progress events here but for simplicity
-                // the frontend will show an indeterminate progress bar
-            },
-            || {
-                // Download finished callback
-            },
-        )
-        .await
-        .map_err(|e| format!("Failed to download update: {}", e))?;
+    let bytes = download_update(&db, &update).await?;
 
-    // Log download complete
+    // Stay on the rolled-back version until the user unpins
     if let Ok(mut conn) = db.get_connection() {
-        use crate::commands::logs::log_event_internal;
-        let details = serde_json::json!({
-            "targetVersion": version,
-            "downloadSize": bytes.len()
-        });
-        let _ = log_event_internal(
-            &mut conn,
-            "info",
-            "system",
-            &format!("Update v{} downloaded, installing...", version),
-            Some(&details.to_string()),
-        );
+        let _ = set_setting_value(&mut conn, UPDATE_PINNED_VERSION_KEY, &previous);
+        let _ = delete_setting(&mut conn, UPDATE_PREVIOUS_VERSION_KEY);
     }
 
-    // Install the update (this will restart the app on most platforms)
     update
         .install(bytes)
-        .map_err(|e| format!("Failed to install update: {}", e))?;
+        .map_err(|e| format!("Failed to install v{}: {}", previous, e))?;
 
-    // If we reach here, the update is installed and needs a restart
-    // The frontend should prompt the user to restart
     Ok(())
 }
 
@@ -326,6 +773,7 @@ mod tests {
             version: Some("1.2.0".to_string()),
             notes: Some("New features".to_string()),
             date: Some("2026-01-24T00:00:00Z".to_string()),
+            deferred_until: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -340,12 +788,19 @@ mod tests {
             auto_check: true,
             last_check: Some("2026-01-24T12:00:00Z".to_string()),
             current_version: "1.1.0".to_string(),
+            channel: ReleaseChannel::Beta,
+            deferred_until: None,
+            pinned_version: Some("1.1.0".to_string()),
+            rollback_version: None,
         };
 
         let json = serde_json::to_string(&settings).unwrap();
         assert!(json.contains("\"autoCheck\":true"));
         assert!(json.contains("\"lastCheck\":\"2026-01-24T12:00:00Z\""));
         assert!(json.contains("\"currentVersion\":\"1.1.0\""));
+        assert!(json.contains("\"channel\":\"beta\""));
+        assert!(json.contains("\"pinnedVersion\":\"1.1.0\""));
+        assert!(json.contains("\"rollbackVersion\":null"));
     }
 
     #[test]
@@ -355,6 +810,7 @@ mod tests {
             version: None,
             notes: None,
             date: None,
+            deferred_until: None,
         };
 
         let json = serde_json::to_string(&info).unwrap();
@@ -369,4 +825,45 @@ mod tests {
         // Version should be in semver format (x.y.z)
         assert!(version.split('.').count() >= 2);
     }
+
+    #[test]
+    fn test_release_channel_parse() {
+        assert_eq!(ReleaseChannel::parse("stable"), Some(ReleaseChannel::Stable));
+        assert_eq!(ReleaseChannel::parse("beta"), Some(ReleaseChannel::Beta));
+        assert_eq!(ReleaseChannel::parse("nightly"), None);
+        assert_eq!(ReleaseChannel::default().as_str(), "stable");
+    }
+
+    #[test]
+    fn test_rollout_includes() {
+        let full = serde_json::json!({ "version": "1.2.0" });
+        assert!(rollout_includes(&full, 99));
+
+        let staged = serde_json::json!({ "version": "1.2.0", "rollout": 25 });
+        assert!(rollout_includes(&staged, 0));
+        assert!(rollout_includes(&staged, 24));
+        assert!(!rollout_includes(&staged, 25));
+
+        let halted = serde_json::json!({ "version": "1.2.0", "rollout": 0 });
+        assert!(!rollout_includes(&halted, 0));
+    }
+
+    #[test]
+    fn test_is_deferral_active() {
+        let now = chrono::Utc::now();
+        let future = (now + chrono::Duration::days(7)).to_rfc3339();
+        let past = (now - chrono::Duration::days(1)).to_rfc3339();
+
+        assert!(is_deferral_active(&future, now));
+        assert!(!is_deferral_active(&past, now));
+        assert!(!is_deferral_active("not a date", now));
+    }
+
+    #[test]
+    fn test_release_manifest_url() {
+        assert_eq!(
+            release_manifest_url("1.1.0").unwrap().as_str(),
+            "https://github.com/javipelopi/streamforge/releases/download/v1.1.0/latest.json"
+        );
+    }
 }
@@ -0,0 +1,403 @@
+//! VOD catalog Tauri commands
+//!
+//! Scans the movie and series catalog of Xtream accounts into `vod_items` and
+//! loads series episodes on demand. Items are played through the local HTTP
+//! server at `/vod/movie/{id}` and `/vod/episode/{id}` (see `crate::server::vod`).
+//! M3U accounts have no VOD catalog.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::time::Instant;
+use tauri::{AppHandle, Manager, State};
+
+use crate::credentials::CredentialManager;
+use crate::db::{
+    schema::{accounts, vod_episodes, vod_items},
+    Account, DbConnection, NewVodItem, VodEpisode, VodItem,
+};
+use crate::xtream::{XtreamCategory, XtreamClient};
+
+pub const VOD_KIND_MOVIE: &str = "movie";
+pub const VOD_KIND_SERIES: &str = "series";
+
+/// Default page size for `get_vod_items`
+const DEFAULT_VOD_PAGE_SIZE: i64 = 200;
+
+/// Response type for scan_vod command
+#[derive(Debug, Serialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VodScanResponse {
+    pub movies: usize,
+    pub series: usize,
+    pub new_items: usize,
+    pub removed_items: usize,
+    pub scan_duration_ms: u64,
+}
+
+/// Outcome of storing one kind of catalog
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CatalogChanges {
+    pub new_items: usize,
+    pub updated_items: usize,
+    pub removed_items: usize,
+}
+
+/// Build an Xtream client for a VOD-capable account
+pub fn vod_client(
+    account: &Account,
+    app_data_dir: std::path::PathBuf,
+) -> Result<XtreamClient, String> {
+    if account.is_m3u() {
+        return Err("M3U accounts have no VOD catalog".to_string());
+    }
+
+    let account_id = account.id.unwrap_or(0);
+    let password = CredentialManager::new(app_data_dir)
+        .retrieve_password(&account_id.to_string(), &account.password_encrypted)
+        .map_err(|_| "Failed to retrieve credentials".to_string())?;
+
+    XtreamClient::new(&account.server_url, &account.username, &password)
+        .map_err(|e| e.user_message())
+}
+
+/// Replace the stored catalog of one kind for an account
+///
+/// Entries are matched on provider ID: existing rows are updated, new ones
+/// inserted, and rows missing from `entries` removed (with their episodes).
+pub fn store_catalog(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    kind: &str,
+    entries: &[NewVodItem],
+) -> QueryResult<CatalogChanges> {
+    conn.transaction(|conn| {
+        let existing: HashSet<i32> = vod_items::table
+            .filter(vod_items::account_id.eq(account_id))
+            .filter(vod_items::kind.eq(kind))
+            .select(vod_items::provider_id)
+            .load::<i32>(conn)?
+            .into_iter()
+            .collect();
+
+        let mut changes = CatalogChanges::default();
+        let mut seen = HashSet::with_capacity(entries.len());
+        for entry in entries {
+            if !seen.insert(entry.provider_id) {
+                continue;
+            }
+            if existing.contains(&entry.provider_id) {
+                diesel::update(
+                    vod_items::table
+                        .filter(vod_items::account_id.eq(account_id))
+                        .filter(vod_items::kind.eq(kind))
+                        .filter(vod_items::provider_id.eq(entry.provider_id)),
+                )
+                .set(entry)
+                .execute(conn)?;
+                changes.updated_items += 1;
+            } else {
+                diesel::insert_into(vod_items::table)
+                    .values(entry)
+                    .execute(conn)?;
+                changes.new_items += 1;
+            }
+        }
+
+        let removed: Vec<i32> = existing.difference(&seen).copied().collect();
+        if !removed.is_empty() {
+            changes.removed_items = diesel::delete(
+                vod_items::table
+                    .filter(vod_items::account_id.eq(account_id))
+                    .filter(vod_items::kind.eq(kind))
+                    .filter(vod_items::provider_id.eq_any(&removed)),
+            )
+            .execute(conn)?;
+        }
+
+        Ok(changes)
+    })
+}
+
+/// Category ID to name lookup
+fn category_names(categories: Vec<XtreamCategory>) -> HashMap<String, String> {
+    categories
+        .into_iter()
+        .map(|c| (c.category_id, c.category_name))
+        .collect()
+}
+
+/// Scan the movie and series catalog of an Xtream account
+#[tauri::command]
+pub async fn scan_vod(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    account_id: i32,
+) -> Result<VodScanResponse, String> {
+    let start_time = Instant::now();
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())?;
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let account: Account = accounts::table
+        .filter(accounts::id.eq(account_id))
+        .first(&mut conn)
+        .map_err(|_| "Account not found".to_string())?;
+    let client = vod_client(&account, app_data_dir)?;
+
+    let movie_categories = category_names(
+        client
+            .get_vod_categories()
+            .await
+            .map_err(|e| e.user_message())?,
+    );
+    let movies = client
+        .get_vod_streams()
+        .await
+        .map_err(|e| e.user_message())?;
+    let series_categories = category_names(
+        client
+            .get_series_categories()
+            .await
+            .map_err(|e| e.user_message())?,
+    );
+    let series = client.get_series().await.map_err(|e| e.user_message())?;
+
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let movie_rows: Vec<NewVodItem> = movies
+        .into_iter()
+        .map(|movie| NewVodItem {
+            account_id,
+            kind: VOD_KIND_MOVIE.to_string(),
+            provider_id: movie.stream_id,
+            name: movie.name,
+            icon: movie.stream_icon.filter(|s| !s.is_empty()),
+            category_id: movie.category_id.as_deref().and_then(|id| id.parse().ok()),
+            category_name: movie
+                .category_id
+                .as_ref()
+                .and_then(|id| movie_categories.get(id).cloned()),
+            container_extension: movie.container_extension.filter(|s| !s.is_empty()),
+            plot: None,
+            rating: movie.rating,
+            added_at: movie.added,
+            updated_at: now.clone(),
+        })
+        .collect();
+    let series_rows: Vec<NewVodItem> = series
+        .into_iter()
+        .map(|show| NewVodItem {
+            account_id,
+            kind: VOD_KIND_SERIES.to_string(),
+            provider_id: show.series_id,
+            name: show.name,
+            icon: show.cover.filter(|s| !s.is_empty()),
+            category_id: show.category_id.as_deref().and_then(|id| id.parse().ok()),
+            category_name: show
+                .category_id
+                .as_ref()
+                .and_then(|id| series_categories.get(id).cloned()),
+            container_extension: None,
+            plot: show.plot.filter(|s| !s.is_empty()),
+            rating: show.rating,
+            added_at: show.last_modified,
+            updated_at: now.clone(),
+        })
+        .collect();
+
+    let movie_changes = store_catalog(&mut conn, account_id, VOD_KIND_MOVIE, &movie_rows)
+        .map_err(|e| format!("Failed to store movies: {}", e))?;
+    let series_changes = store_catalog(&mut conn, account_id, VOD_KIND_SERIES, &series_rows)
+        .map_err(|e| format!("Failed to store series: {}", e))?;
+
+    Ok(VodScanResponse {
+        movies: movie_rows.len(),
+        series: series_rows.len(),
+        new_items: movie_changes.new_items + series_changes.new_items,
+        removed_items: movie_changes.removed_items + series_changes.removed_items,
+        scan_duration_ms: start_time.elapsed().as_millis() as u64,
+    })
+}
+
+/// List VOD catalog items
+///
+/// Filters by kind ("movie"/"series"), account, and a case-insensitive name
+/// search. Results are ordered by name and paged with `limit`/`offset`.
+#[tauri::command]
+pub fn get_vod_items(
+    db: State<DbConnection>,
+    kind: Option<String>,
+    account_id: Option<i32>,
+    search: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+) -> Result<Vec<VodItem>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let mut query = vod_items::table.into_boxed();
+    if let Some(kind) = kind {
+        query = query.filter(vod_items::kind.eq(kind));
+    }
+    if let Some(account_id) = account_id {
+        query = query.filter(vod_items::account_id.eq(account_id));
+    }
+    if let Some(search) = search
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+    {
+        query = query.filter(vod_items::name.like(format!("%{}%", search)));
+    }
+
+    query
+        .order(vod_items::name.asc())
+        .limit(limit.unwrap_or(DEFAULT_VOD_PAGE_SIZE))
+        .offset(offset.unwrap_or(0))
+        .load(&mut conn)
+        .map_err(|e| format!("Failed to load VOD items: {}", e))
+}
+
+/// Get the episodes of a series
+///
+/// Episodes are fetched from the provider the first time (or with `refresh`)
+/// and stored; later calls read the stored list.
+#[tauri::command]
+pub async fn get_series_episodes(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    vod_item_id: i32,
+    refresh: Option<bool>,
+) -> Result<Vec<VodEpisode>, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let item: VodItem = vod_items::table
+        .filter(vod_items::id.eq(vod_item_id))
+        .filter(vod_items::kind.eq(VOD_KIND_SERIES))
+        .first(&mut conn)
+        .map_err(|_| "Series not found".to_string())?;
+
+    let stored: Vec<VodEpisode> = load_episodes(&mut conn, vod_item_id)?;
+    if !stored.is_empty() && !refresh.unwrap_or(false) {
+        return Ok(stored);
+    }
+
+    let app_data_dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|_| "Failed to get app data directory".to_string())?;
+    let account: Account = accounts::table
+        .filter(accounts::id.eq(item.account_id))
+        .first(&mut conn)
+        .map_err(|_| "Account not found".to_string())?;
+    let info = vod_client(&account, app_data_dir)?
+        .get_series_info(item.provider_id)
+        .await
+        .map_err(|e| e.user_message())?;
+
+    let episodes: Vec<VodEpisode> = info
+        .episodes
+        .into_iter()
+        .flat_map(|(season_key, episodes)| {
+            let season_from_key = season_key.parse::<i32>().ok();
+            episodes.into_iter().map(move |episode| VodEpisode {
+                id: None,
+                vod_item_id,
+                episode_id: episode.id,
+                season: episode.season.or(season_from_key),
+                episode_num: episode.episode_num,
+                title: episode.title,
+                container_extension: episode.container_extension,
+            })
+        })
+        .collect();
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(vod_episodes::table.filter(vod_episodes::vod_item_id.eq(vod_item_id)))
+            .execute(conn)?;
+        for episode in &episodes {
+            diesel::insert_or_ignore_into(vod_episodes::table)
+                .values(episode)
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("Failed to store episodes: {}", e))?;
+
+    load_episodes(&mut conn, vod_item_id)
+}
+
+fn load_episodes(conn: &mut SqliteConnection, vod_item_id: i32) -> Result<Vec<VodEpisode>, String> {
+    vod_episodes::table
+        .filter(vod_episodes::vod_item_id.eq(vod_item_id))
+        .order((vod_episodes::season.asc(), vod_episodes::episode_num.asc()))
+        .load(conn)
+        .map_err(|e| format!("Failed to load episodes: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn movie(provider_id: i32, name: &str) -> NewVodItem {
+        NewVodItem {
+            account_id: 1,
+            kind: VOD_KIND_MOVIE.to_string(),
+            provider_id,
+            name: name.to_string(),
+            icon: None,
+            category_id: None,
+            category_name: None,
+            container_extension: Some("mp4".to_string()),
+            plot: None,
+            rating: None,
+            added_at: None,
+            updated_at: "2026-01-01 00:00:00".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_store_catalog_updates_and_removes() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://example.com");
+
+        let changes = store_catalog(
+            &mut conn,
+            1,
+            VOD_KIND_MOVIE,
+            &[movie(1, "A"), movie(2, "B"), movie(2, "B dup")],
+        )
+        .unwrap();
+        assert_eq!(changes.new_items, 2);
+
+        let changes = store_catalog(
+            &mut conn,
+            1,
+            VOD_KIND_MOVIE,
+            &[movie(2, "B renamed"), movie(3, "C")],
+        )
+        .unwrap();
+        assert_eq!(
+            changes,
+            CatalogChanges {
+                new_items: 1,
+                updated_items: 1,
+                removed_items: 1
+            }
+        );
+
+        let names: Vec<String> = vod_items::table
+            .order(vod_items::provider_id.asc())
+            .select(vod_items::name)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(names, vec!["B renamed", "C"]);
+    }
+}
@@ -0,0 +1,90 @@
+//! Web Admin Tauri commands
+//!
+//! Enables the browser-based admin UI served at `/admin` by setting its
+//! password, and reports where it can be reached.
+
+use serde::Serialize;
+use tauri::State;
+
+use crate::commands::get_server_port_internal;
+use crate::commands::logs::log_event_internal;
+use crate::db::DbConnection;
+use crate::server::auth::{self, WEB_ADMIN_MIN_PASSWORD_LEN, WEB_ADMIN_USERNAME};
+use crate::server::hdhr::advertised_host;
+
+/// Web admin status response
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WebAdminStatus {
+    /// Whether a web admin password is set (UI and REST admin API enabled)
+    pub enabled: bool,
+    /// Username to log in with
+    pub username: String,
+    /// URL of the web admin UI on the local network
+    pub url: String,
+    /// URL of the mobile dashboard on the local network
+    pub mobile_url: String,
+}
+
+/// Get the web admin status
+#[tauri::command]
+pub fn get_web_admin_status(db: State<DbConnection>) -> Result<WebAdminStatus, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    let port = get_server_port_internal(&mut conn).unwrap_or(5004);
+    let host = advertised_host(crate::server::state::read_bind_address(&mut conn));
+    let base_url = format!("http://{}:{}", host, port);
+
+    Ok(WebAdminStatus {
+        enabled: auth::is_web_admin_enabled(&mut conn),
+        username: WEB_ADMIN_USERNAME.to_string(),
+        url: format!("{}/admin", base_url),
+        mobile_url: format!("{}/m", base_url),
+    })
+}
+
+/// Set or clear the web admin password
+///
+/// Passing null disables the web admin UI and REST admin API.
+#[tauri::command]
+pub fn set_web_admin_password(
+    db: State<DbConnection>,
+    password: Option<String>,
+) -> Result<WebAdminStatus, String> {
+    if let Some(password) = &password {
+        if password.chars().count() < WEB_ADMIN_MIN_PASSWORD_LEN {
+            return Err(format!(
+                "Password must be at least {} characters",
+                WEB_ADMIN_MIN_PASSWORD_LEN
+            ));
+        }
+    }
+
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    auth::set_admin_password(&mut conn, password.as_deref())
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+
+    let enabled = password.is_some();
+    let details = serde_json::json!({
+        "setting": "web_admin",
+        "enabled": enabled
+    });
+    let _ = log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!(
+            "Configuration changed: Web admin {}",
+            if enabled { "password set" } else { "disabled" }
+        ),
+        Some(&details.to_string()),
+    );
+
+    drop(conn);
+    get_web_admin_status(db)
+}
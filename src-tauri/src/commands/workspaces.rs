@@ -0,0 +1,225 @@
+//! Workspace Tauri commands
+//!
+//! Lets the user keep several databases (e.g. production and a test setup for
+//! a new provider) and switch between them at runtime. Switching opens and
+//! migrates the target database first, then stops active streams, points the
+//! scheduler, commands, and HTTP server at the new pool, reloads the settings
+//! kept in memory, and drops the old one.
+//!
+//! Keychain entries written while a workspace is active are namespaced by its
+//! database (see `crate::credentials::set_workspace`).
+
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Manager, State};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::workspace::{
+    open_workspace_pool, resolve_new_workspace_path, validate_workspace_name, WorkspaceEntry,
+    WorkspaceRegistry, DEFAULT_WORKSPACE,
+};
+use crate::db::DbConnection;
+use crate::scheduler::EpgScheduler;
+use crate::server::AppState;
+
+/// Workspace information for the picker
+#[derive(Serialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct WorkspaceInfo {
+    pub name: String,
+    /// Database file path
+    pub path: String,
+    pub is_active: bool,
+    /// Whether this is the built-in default workspace (cannot be removed)
+    pub is_default: bool,
+}
+
+fn app_data_dir(app: &AppHandle) -> Result<PathBuf, String> {
+    app.path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data directory: {}", e))
+}
+
+fn workspace_info(registry: &WorkspaceRegistry, app_data_dir: &Path, name: &str) -> WorkspaceInfo {
+    WorkspaceInfo {
+        name: name.to_string(),
+        path: registry
+            .path_of(app_data_dir, name)
+            .map(|p| p.to_string_lossy().to_string())
+            .unwrap_or_default(),
+        is_active: registry.active_name() == name,
+        is_default: name == DEFAULT_WORKSPACE,
+    }
+}
+
+/// List all workspaces (default first)
+#[tauri::command]
+pub fn list_workspaces(app: AppHandle) -> Result<Vec<WorkspaceInfo>, String> {
+    let app_data_dir = app_data_dir(&app)?;
+    let registry = WorkspaceRegistry::load(&app_data_dir);
+
+    let mut result = vec![workspace_info(&registry, &app_data_dir, DEFAULT_WORKSPACE)];
+    result.extend(
+        registry
+            .workspaces
+            .iter()
+            .map(|w| workspace_info(&registry, &app_data_dir, &w.name)),
+    );
+    Ok(result)
+}
+
+/// Create a new workspace
+///
+/// The database is created and migrated immediately. `location` may be a
+/// directory or a database file path; it defaults to the app's `workspaces/` folder.
+#[tauri::command]
+pub fn create_workspace(
+    app: AppHandle,
+    name: String,
+    location: Option<String>,
+) -> Result<WorkspaceInfo, String> {
+    validate_workspace_name(&name)?;
+    let name = name.trim().to_string();
+
+    let app_data_dir = app_data_dir(&app)?;
+    let mut registry = WorkspaceRegistry::load(&app_data_dir);
+
+    if name == DEFAULT_WORKSPACE || registry.path_of(&app_data_dir, &name).is_some() {
+        return Err(format!("Workspace '{}' already exists", name));
+    }
+
+    let path = resolve_new_workspace_path(&app_data_dir, &name, location.as_deref())?;
+    if registry.workspaces.iter().any(|w| w.path == path) {
+        return Err(format!(
+            "Database '{}' is already used by another workspace",
+            path.display()
+        ));
+    }
+
+    // Create and migrate the database (pool is dropped right away)
+    open_workspace_pool(&path)?;
+
+    registry.workspaces.push(WorkspaceEntry {
+        name: name.clone(),
+        path,
+    });
+    registry.save(&app_data_dir)?;
+
+    Ok(workspace_info(&registry, &app_data_dir, &name))
+}
+
+/// Switch to another workspace at runtime
+///
+/// The target database is opened and migrated before anything is torn down,
+/// so a failure leaves the current workspace untouched. Active streams are
+/// stopped because their sessions belong to the previous database.
+#[tauri::command]
+pub async fn switch_workspace(
+    app: AppHandle,
+    db: State<'_, DbConnection>,
+    scheduler: State<'_, EpgScheduler>,
+    server_state: State<'_, AppState>,
+    name: String,
+) -> Result<WorkspaceInfo, String> {
+    let app_data_dir = app_data_dir(&app)?;
+    let mut registry = WorkspaceRegistry::load(&app_data_dir);
+
+    let previous = registry.active_name().to_string();
+    if previous == name {
+        return Ok(workspace_info(&registry, &app_data_dir, &name));
+    }
+
+    let path = registry
+        .path_of(&app_data_dir, &name)
+        .ok_or_else(|| format!("Workspace '{}' not found", name))?;
+
+    // Bring-up first: open and migrate the target database
+    let (new_pool, new_read_pool) = open_workspace_pool(&path)?;
+
+    // Teardown: stop streams tied to the previous database
+    let stream_manager = server_state.stream_manager();
+    let stopped_streams = stream_manager
+        .list_sessions()
+        .iter()
+        .filter(|(session_id, _)| stream_manager.request_stop(session_id))
+        .count();
+
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::json!({
+            "from": previous,
+            "to": name,
+            "stoppedStreams": stopped_streams
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!("Switching workspace to '{}'", name),
+            Some(&details.to_string()),
+        );
+    }
+
+    // Point the scheduler at the new database and apply its schedule
+    scheduler.set_db_pool(new_pool.clone()).await;
+    if let Err(e) = scheduler.apply_schedule_from_db().await {
+        eprintln!("Failed to apply EPG schedule for workspace '{}': {}", name, e);
+    }
+
+    // Swap the shared pool (commands and HTTP server), then reset server caches
+    db.replace_pools(new_pool, new_read_pool);
+    crate::credentials::set_workspace(&path);
+    if let Ok(mut conn) = db.get_connection() {
+        crate::load_runtime_settings(&mut conn);
+        crate::logging::load_from_db(&mut conn);
+    }
+    server_state.bump_epg_generation();
+    server_state.refresh_max_connections();
+
+    registry.active = if name == DEFAULT_WORKSPACE {
+        None
+    } else {
+        Some(name.clone())
+    };
+    registry.save(&app_data_dir)?;
+
+    if let Ok(mut conn) = db.get_connection() {
+        let details = serde_json::json!({
+            "from": previous,
+            "path": path.to_string_lossy()
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "system",
+            &format!("Workspace '{}' activated", name),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(workspace_info(&registry, &app_data_dir, &name))
+}
+
+/// Remove a workspace from the list
+///
+/// The database file is left on disk. The default and active workspaces cannot be removed.
+#[tauri::command]
+pub fn remove_workspace(app: AppHandle, name: String) -> Result<(), String> {
+    if name == DEFAULT_WORKSPACE {
+        return Err("The default workspace cannot be removed".to_string());
+    }
+
+    let app_data_dir = app_data_dir(&app)?;
+    let mut registry = WorkspaceRegistry::load(&app_data_dir);
+
+    if registry.active_name() == name {
+        return Err("Switch to another workspace before removing this one".to_string());
+    }
+
+    let before = registry.workspaces.len();
+    registry.workspaces.retain(|w| w.name != name);
+    if registry.workspaces.len() == before {
+        return Err(format!("Workspace '{}' not found", name));
+    }
+
+    registry.save(&app_data_dir)
+}
@@ -13,6 +13,7 @@ use crate::db::models::{ChannelMapping, XmltvChannel, XmltvChannelSettings, Xtre
 use crate::db::schema::{channel_mappings, xmltv_channel_settings, xmltv_channels, xtream_channels};
 use crate::db::DbConnection;
 use crate::matcher::normalize_channel_name;
+use crate::server::AppState;
 use strsim::jaro_winkler;
 
 /// Xtream stream match info for display
@@ -45,6 +46,8 @@ pub struct XmltvChannelWithMappings {
     // Settings
     pub is_enabled: bool,
     pub plex_display_order: Option<i32>,
+    /// Persistent channel number (None falls back to the lineup position)
+    pub channel_number: Option<i32>,
     // Matches
     pub match_count: i32,
     pub matches: Vec<XtreamStreamMatch>,
@@ -61,18 +64,27 @@ pub fn get_xmltv_channels_with_mappings(
     db: State<DbConnection>,
 ) -> Result<Vec<XmltvChannelWithMappings>, String> {
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    load_xmltv_channels_with_mappings(&mut conn)
+}
+
+/// Load all XMLTV channels with their mapped Xtream streams.
+///
+/// Shared by the Tauri command and the web admin API.
+pub fn load_xmltv_channels_with_mappings(
+    conn: &mut SqliteConnection,
+) -> Result<Vec<XmltvChannelWithMappings>, String> {
     // Load all XMLTV channels
     let channels: Vec<XmltvChannel> = xmltv_channels::table
         .order_by(xmltv_channels::display_name.asc())
-        .load::<XmltvChannel>(&mut conn)
+        .load::<XmltvChannel>(conn)
         .map_err(|e| format!("Failed to load XMLTV channels: {}", e))?;
 
     // Load all settings into a map for efficient lookup
     let settings: Vec<XmltvChannelSettings> = xmltv_channel_settings::table
-        .load::<XmltvChannelSettings>(&mut conn)
+        .load::<XmltvChannelSettings>(conn)
         .map_err(|e| format!("Failed to load channel settings: {}", e))?;
 
     let settings_map: std::collections::HashMap<i32, XmltvChannelSettings> = settings
@@ -86,12 +98,12 @@ pub fn get_xmltv_channels_with_mappings(
             channel_mappings::xmltv_channel_id.asc(),
             channel_mappings::stream_priority.asc(),
         ))
-        .load::<ChannelMapping>(&mut conn)
+        .load::<ChannelMapping>(conn)
         .map_err(|e| format!("Failed to load channel mappings: {}", e))?;
 
     // Load all Xtream channels into a map for lookup
     let all_xtream_channels: Vec<XtreamChannel> = xtream_channels::table
-        .load::<XtreamChannel>(&mut conn)
+        .load::<XtreamChannel>(conn)
         .map_err(|e| format!("Failed to load Xtream channels: {}", e))?;
 
     let xtream_map: std::collections::HashMap<i32, XtreamChannel> = all_xtream_channels
@@ -128,6 +140,7 @@ pub fn get_xmltv_channels_with_mappings(
                 .unwrap_or(false);
 
             let plex_display_order = settings.and_then(|s| s.plex_display_order);
+            let channel_number = settings.and_then(|s| s.channel_number);
 
             // Build matches list (including orphaned manual matches)
             let matches: Vec<XtreamStreamMatch> = channel_mappings
@@ -190,6 +203,7 @@ pub fn get_xmltv_channels_with_mappings(
                 is_synthetic: channel.is_synthetic.unwrap_or(0) != 0,
                 is_enabled,
                 plex_display_order,
+                channel_number,
                 match_count: matches.len() as i32,
                 matches,
             })
@@ -262,6 +276,7 @@ fn build_stream_match(mapping: &ChannelMapping, stream: &XtreamChannel) -> Optio
 #[tauri::command]
 pub fn set_primary_stream(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     xmltv_channel_id: i32,
     xtream_channel_id: i32,
 ) -> Result<Vec<XtreamStreamMatch>, String> {
@@ -269,6 +284,12 @@ pub fn set_primary_stream(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    crate::mapping_conflicts::check_primary_assignment(
+        &mut conn,
+        xmltv_channel_id,
+        xtream_channel_id,
+    )?;
+
     // Run in transaction
     conn.transaction(|conn| {
         // Load all current mappings to preserve original priority order
@@ -326,6 +347,7 @@ pub fn set_primary_stream(
         Ok(result)
     })
     .map_err(|e: diesel::result::Error| format!("Failed to update primary stream: {}", e))
+    .inspect(|_| server_state.bump_epg_generation())
 }
 
 // ============================================================================
@@ -365,7 +387,7 @@ pub fn get_all_xtream_streams(
     db: State<DbConnection>,
 ) -> Result<Vec<XtreamStreamSearchResult>, String> {
     let mut conn = db
-        .get_connection()
+        .get_read_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
     // Load all Xtream channels
@@ -533,6 +555,7 @@ pub fn search_xtream_streams(
 #[tauri::command]
 pub fn add_manual_stream_mapping(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     xmltv_channel_id: i32,
     xtream_channel_id: i32,
     set_as_primary: bool,
@@ -549,6 +572,14 @@ pub fn add_manual_stream_mapping(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    if set_as_primary {
+        crate::mapping_conflicts::check_primary_assignment(
+            &mut conn,
+            xmltv_channel_id,
+            xtream_channel_id,
+        )?;
+    }
+
     conn.transaction(|conn| {
         // Check if mapping already exists
         let existing: Option<ChannelMapping> = channel_mappings::table
@@ -626,6 +657,7 @@ pub fn add_manual_stream_mapping(
             format!("Failed to add manual stream mapping: {}", e)
         }
     })
+    .inspect(|_| server_state.bump_epg_generation())
 }
 
 /// Remove a stream mapping.
@@ -642,6 +674,7 @@ pub fn add_manual_stream_mapping(
 #[tauri::command]
 pub fn remove_stream_mapping(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     mapping_id: i32,
 ) -> Result<Vec<XtreamStreamMatch>, String> {
     // Validate input
@@ -733,6 +766,7 @@ pub fn remove_stream_mapping(
         Ok(result)
     })
     .map_err(|e: diesel::result::Error| format!("Failed to remove stream mapping: {}", e))
+    .inspect(|_| server_state.bump_epg_generation())
 }
 
 /// Update the display order of XMLTV channels for Plex lineup.
@@ -748,10 +782,9 @@ pub fn remove_stream_mapping(
 #[tauri::command]
 pub fn update_channel_order(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     channel_ids: Vec<i32>,
 ) -> Result<(), String> {
-    use crate::db::models::NewXmltvChannelSettings;
-
     // Validate input - empty array is a no-op
     if channel_ids.is_empty() {
         return Ok(());
@@ -761,40 +794,9 @@ pub fn update_channel_order(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
-    conn.transaction::<_, diesel::result::Error, _>(|conn| {
-        for (position, channel_id) in channel_ids.iter().enumerate() {
-            // Check if settings exist for this channel
-            let existing: Option<XmltvChannelSettings> = xmltv_channel_settings::table
-                .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id))
-                .first::<XmltvChannelSettings>(conn)
-                .optional()?;
-
-            if existing.is_some() {
-                // Update existing settings
-                diesel::update(
-                    xmltv_channel_settings::table
-                        .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id)),
-                )
-                .set((
-                    xmltv_channel_settings::plex_display_order.eq(position as i32),
-                    xmltv_channel_settings::updated_at.eq(chrono::Utc::now().to_rfc3339()),
-                ))
-                .execute(conn)?;
-            } else {
-                // Create new settings record with display order
-                let new_settings = NewXmltvChannelSettings {
-                    xmltv_channel_id: *channel_id,
-                    is_enabled: 0, // Default to disabled
-                    plex_display_order: Some(position as i32),
-                };
-                diesel::insert_into(xmltv_channel_settings::table)
-                    .values(&new_settings)
-                    .execute(conn)?;
-            }
-        }
-        Ok(())
-    })
-    .map_err(|e| format!("Failed to update channel order: {}", e))?;
+    update_channel_order_internal(&mut conn, &channel_ids)
+        .map_err(|e| format!("Failed to update channel order: {}", e))?;
+    server_state.bump_epg_generation();
 
     // Log the reorder event
     eprintln!(
@@ -805,6 +807,53 @@ pub fn update_channel_order(
     Ok(())
 }
 
+/// Maximum rows per bulk settings upsert (keeps bind parameters well under SQLite's limit)
+const SETTINGS_UPSERT_CHUNK_SIZE: usize = 500;
+
+/// Write display order for channels with bulk upserts.
+///
+/// Channels without a settings row get one (disabled by default). Runs one
+/// `INSERT ... ON CONFLICT DO UPDATE` per chunk instead of a select and
+/// write per channel.
+pub fn update_channel_order_internal(
+    conn: &mut SqliteConnection,
+    channel_ids: &[i32],
+) -> Result<(), diesel::result::Error> {
+    use diesel::upsert::excluded;
+
+    // Plain column tuples (not `NewXmltvChannelSettings`): SQLite batch inserts
+    // need every value present since it has no DEFAULT keyword in VALUES lists.
+    // New rows default to disabled.
+    let rows: Vec<_> = channel_ids
+        .iter()
+        .enumerate()
+        .map(|(position, channel_id)| {
+            (
+                xmltv_channel_settings::xmltv_channel_id.eq(*channel_id),
+                xmltv_channel_settings::is_enabled.eq(0),
+                xmltv_channel_settings::plex_display_order.eq(position as i32),
+            )
+        })
+        .collect();
+
+    conn.transaction(|conn| {
+        let now = chrono::Utc::now().to_rfc3339();
+        for chunk in rows.chunks(SETTINGS_UPSERT_CHUNK_SIZE) {
+            diesel::insert_into(xmltv_channel_settings::table)
+                .values(chunk)
+                .on_conflict(xmltv_channel_settings::xmltv_channel_id)
+                .do_update()
+                .set((
+                    xmltv_channel_settings::plex_display_order
+                        .eq(excluded(xmltv_channel_settings::plex_display_order)),
+                    xmltv_channel_settings::updated_at.eq(&now),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+}
+
 /// Toggle the enabled status of an XMLTV channel.
 ///
 /// # Arguments
@@ -817,6 +866,22 @@ pub fn update_channel_order(
 #[tauri::command]
 pub fn toggle_xmltv_channel(
     db: State<DbConnection>,
+    server_state: State<AppState>,
+    channel_id: i32,
+) -> Result<XmltvChannelWithMappings, String> {
+    let mut conn = db
+        .get_connection()
+        .map_err(|e| format!("Database connection error: {}", e))?;
+
+    toggle_xmltv_channel_internal(&mut conn, channel_id)
+        .inspect(|_| server_state.bump_epg_generation())
+}
+
+/// Toggle the enabled status of an XMLTV channel on an existing connection.
+///
+/// Shared by the Tauri command and the web admin API.
+pub fn toggle_xmltv_channel_internal(
+    conn: &mut SqliteConnection,
     channel_id: i32,
 ) -> Result<XmltvChannelWithMappings, String> {
     use crate::db::models::NewXmltvChannelSettings;
@@ -826,10 +891,6 @@ pub fn toggle_xmltv_channel(
         return Err("Invalid channel ID".to_string());
     }
 
-    let mut conn = db
-        .get_connection()
-        .map_err(|e| format!("Database connection error: {}", e))?;
-
     conn.transaction::<_, diesel::result::Error, _>(|conn| {
         // Check if settings exist
         let existing_settings: Option<XmltvChannelSettings> = xmltv_channel_settings::table
@@ -932,10 +993,10 @@ pub fn toggle_xmltv_channel(
             channel_id: channel.channel_id,
             display_name: channel.display_name,
             icon: channel.icon,
-            // TODO: is_synthetic field not in DB schema yet - defaults to false
-            is_synthetic: false,
+            is_synthetic: channel.is_synthetic.unwrap_or(0) != 0,
             is_enabled: new_enabled,
-            plex_display_order: settings.and_then(|s| s.plex_display_order),
+            plex_display_order: settings.as_ref().and_then(|s| s.plex_display_order),
+            channel_number: settings.and_then(|s| s.channel_number),
             match_count: matches.len() as i32,
             matches,
         })
@@ -989,11 +1050,10 @@ pub struct BulkToggleResult {
 #[tauri::command]
 pub fn bulk_toggle_channels(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     channel_ids: Vec<i32>,
     enabled: bool,
 ) -> Result<BulkToggleResult, String> {
-    use crate::db::models::NewXmltvChannelSettings;
-
     // Validate input - empty array
     if channel_ids.is_empty() {
         return Ok(BulkToggleResult {
@@ -1012,66 +1072,72 @@ pub fn bulk_toggle_channels(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
-    conn.transaction::<BulkToggleResult, diesel::result::Error, _>(|conn| {
-        let mut success_count = 0;
-        let mut skipped_ids: Vec<i32> = Vec::new();
+    bulk_toggle_channels_internal(&mut conn, &channel_ids, enabled)
+        .map_err(|e| format!("Failed to bulk toggle channels: {}", e))
+        .inspect(|_| server_state.bump_epg_generation())
+}
 
-        for channel_id in &channel_ids {
-            // When enabling, check for matched streams
-            if enabled {
-                let match_count: i64 = channel_mappings::table
-                    .filter(channel_mappings::xmltv_channel_id.eq(channel_id))
-                    .count()
-                    .get_result(conn)?;
+/// Enable or disable channels with bulk queries.
+///
+/// When enabling, channels without matched streams are skipped. Matched
+/// channels are looked up with one query per chunk and settings are written
+/// with one `INSERT ... ON CONFLICT DO UPDATE` per chunk.
+pub fn bulk_toggle_channels_internal(
+    conn: &mut SqliteConnection,
+    channel_ids: &[i32],
+    enabled: bool,
+) -> Result<BulkToggleResult, diesel::result::Error> {
+    use diesel::upsert::excluded;
+    use std::collections::HashSet;
 
-                if match_count == 0 {
-                    // Skip channels without matched streams
-                    skipped_ids.push(*channel_id);
-                    continue;
-                }
+    conn.transaction(|conn| {
+        // When enabling, only channels with matched streams qualify
+        let (toggle_ids, skipped_ids): (Vec<i32>, Vec<i32>) = if enabled {
+            let mut matched: HashSet<i32> = HashSet::new();
+            for chunk in channel_ids.chunks(SETTINGS_UPSERT_CHUNK_SIZE) {
+                let ids: Vec<i32> = channel_mappings::table
+                    .filter(channel_mappings::xmltv_channel_id.eq_any(chunk))
+                    .select(channel_mappings::xmltv_channel_id)
+                    .distinct()
+                    .load(conn)?;
+                matched.extend(ids);
             }
+            channel_ids.iter().partition(|id| matched.contains(id))
+        } else {
+            (channel_ids.to_vec(), Vec::new())
+        };
 
-            // Check if settings exist for this channel
-            let existing: Option<XmltvChannelSettings> = xmltv_channel_settings::table
-                .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id))
-                .first::<XmltvChannelSettings>(conn)
-                .optional()?;
-
-            let new_enabled_value = if enabled { 1 } else { 0 };
-
-            if existing.is_some() {
-                // Update existing settings
-                diesel::update(
-                    xmltv_channel_settings::table
-                        .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id)),
+        let new_enabled_value = if enabled { 1 } else { 0 };
+        let rows: Vec<_> = toggle_ids
+            .iter()
+            .map(|id| {
+                (
+                    xmltv_channel_settings::xmltv_channel_id.eq(*id),
+                    xmltv_channel_settings::is_enabled.eq(new_enabled_value),
                 )
+            })
+            .collect();
+
+        let now = chrono::Utc::now().to_rfc3339();
+        for chunk in rows.chunks(SETTINGS_UPSERT_CHUNK_SIZE) {
+            diesel::insert_into(xmltv_channel_settings::table)
+                .values(chunk)
+                .on_conflict(xmltv_channel_settings::xmltv_channel_id)
+                .do_update()
                 .set((
-                    xmltv_channel_settings::is_enabled.eq(new_enabled_value),
-                    xmltv_channel_settings::updated_at.eq(chrono::Utc::now().to_rfc3339()),
+                    xmltv_channel_settings::is_enabled
+                        .eq(excluded(xmltv_channel_settings::is_enabled)),
+                    xmltv_channel_settings::updated_at.eq(&now),
                 ))
                 .execute(conn)?;
-            } else {
-                // Create new settings record
-                let new_settings = NewXmltvChannelSettings {
-                    xmltv_channel_id: *channel_id,
-                    is_enabled: new_enabled_value,
-                    plex_display_order: None,
-                };
-                diesel::insert_into(xmltv_channel_settings::table)
-                    .values(&new_settings)
-                    .execute(conn)?;
-            }
-
-            success_count += 1;
         }
 
         Ok(BulkToggleResult {
-            success_count,
+            success_count: toggle_ids.len() as i32,
             skipped_count: skipped_ids.len() as i32,
             skipped_ids,
         })
     })
-    .map_err(|e| format!("Failed to bulk toggle channels: {}", e))
 }
 
 // ============================================================================
@@ -1166,9 +1232,6 @@ pub fn promote_orphan_to_plex(
     display_name: String,
     icon_url: Option<String>,
 ) -> Result<XmltvChannelWithMappings, String> {
-    use crate::db::models::{NewChannelMapping, NewXmltvChannel, NewXmltvChannelSettings};
-    use crate::db::schema::programs;
-
     // Validate inputs
     if xtream_channel_id <= 0 {
         return Err("Invalid Xtream channel ID".to_string());
@@ -1181,6 +1244,20 @@ pub fn promote_orphan_to_plex(
         .get_connection()
         .map_err(|e| format!("Database connection error: {}", e))?;
 
+    promote_orphan_to_plex_internal(&mut conn, xtream_channel_id, &display_name, icon_url)
+}
+
+/// Create the synthetic channel, mapping, settings and placeholder EPG of a
+/// promoted orphan stream
+fn promote_orphan_to_plex_internal(
+    conn: &mut SqliteConnection,
+    xtream_channel_id: i32,
+    display_name: &str,
+    icon_url: Option<String>,
+) -> Result<XmltvChannelWithMappings, String> {
+    use crate::db::models::{NewChannelMapping, NewXmltvChannel, NewXmltvChannelSettings};
+    use crate::db::schema::programs;
+
     conn.transaction::<XmltvChannelWithMappings, diesel::result::Error, _>(|conn| {
         // Verify the Xtream stream exists
         let xtream_stream: XtreamChannel = xtream_channels::table
@@ -1200,17 +1277,11 @@ pub fn promote_orphan_to_plex(
             ));
         }
 
-        // Get or create synthetic source (source_id for synthetic channels)
-        // We use source_id = -1 as a special marker for synthetic channels
-        // (Alternative: create a dedicated "Synthetic" source in xmltv_sources)
-        let synthetic_source_id = -1;
-
         // Generate unique channel ID for synthetic channel
         let synthetic_channel_id = format!("synthetic-{}", xtream_channel_id);
 
         // Create synthetic XMLTV channel
         let new_channel = NewXmltvChannel::synthetic(
-            synthetic_source_id,
             &synthetic_channel_id,
             display_name.trim(),
             icon_url.clone(),
@@ -1287,13 +1358,14 @@ pub fn promote_orphan_to_plex(
 
         Ok(XmltvChannelWithMappings {
             id: created_channel_id,
-            source_id: synthetic_source_id,
+            source_id: created_channel.source_id,
             channel_id: synthetic_channel_id,
             display_name: created_channel.display_name,
             icon: created_channel.icon,
             is_synthetic: true,
             is_enabled: false, // Disabled by default
             plex_display_order: None,
+            channel_number: None,
             match_count: 1,
             matches: vec![stream_match],
         })
@@ -1329,6 +1401,7 @@ pub fn promote_orphan_to_plex(
 #[tauri::command]
 pub fn update_synthetic_channel(
     db: State<DbConnection>,
+    server_state: State<AppState>,
     channel_id: i32,
     display_name: String,
     icon_url: Option<String>,
@@ -1420,7 +1493,8 @@ pub fn update_synthetic_channel(
                 .as_ref()
                 .map(|s| s.is_enabled.unwrap_or(0) != 0)
                 .unwrap_or(false),
-            plex_display_order: settings.and_then(|s| s.plex_display_order),
+            plex_display_order: settings.as_ref().and_then(|s| s.plex_display_order),
+            channel_number: settings.and_then(|s| s.channel_number),
             match_count: matches.len() as i32,
             matches,
         })
@@ -1432,6 +1506,7 @@ pub fn update_synthetic_channel(
             _ => format!("Failed to update synthetic channel: {}", e),
         }
     })
+    .inspect(|_| server_state.bump_epg_generation())
 }
 
 // ============================================================================
@@ -1451,6 +1526,8 @@ pub struct TargetLineupChannel {
     pub stream_count: i32,
     /// Display order in Plex lineup
     pub plex_display_order: Option<i32>,
+    /// Persistent channel number (None falls back to the lineup position)
+    pub channel_number: Option<i32>,
 }
 
 /// Get all ENABLED channels for the Target Lineup view.
@@ -1527,6 +1604,7 @@ pub fn get_target_lineup_channels(
                 is_synthetic: channel.is_synthetic.unwrap_or(0) != 0,
                 stream_count,
                 plex_display_order: settings.plex_display_order,
+                channel_number: settings.channel_number,
             })
         })
         .collect();
@@ -1648,6 +1726,10 @@ pub fn get_xmltv_channels_for_source(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::test_connection;
+    use diesel::connection::InstrumentationEvent;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     #[test]
     fn test_search_score_threshold_is_reasonable() {
@@ -1780,4 +1862,142 @@ mod tests {
         assert_eq!(parse_qualities(&None), Vec::<String>::new());
         assert_eq!(parse_qualities(&Some("".to_string())), Vec::<String>::new());
     }
+
+    // Bulk settings writes (N+1 elimination)
+
+    /// Pooled connections enforce foreign keys, so the synthetic channel must
+    /// belong to a real source
+    #[test]
+    fn test_promote_orphan_through_pooled_connection() {
+        let path =
+            std::env::temp_dir().join(format!("streamforge-promote-{}.db", uuid::Uuid::new_v4()));
+        let database_url = path.to_string_lossy().to_string();
+        let pool = crate::db::build_pool(database_url).unwrap();
+        let mut conn = pool.get().unwrap();
+        crate::db::run_migrations(&mut conn).unwrap();
+        crate::db::seed_account(&mut conn, 1, "Line A", "http://provider.local");
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+                (10, 1, 100, 'Local News')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let channel = promote_orphan_to_plex_internal(&mut conn, 10, " Local News ", None).unwrap();
+        assert_eq!(channel.source_id, crate::db::SYNTHETIC_SOURCE_ID);
+        assert_eq!(channel.channel_id, "synthetic-10");
+        assert_eq!(channel.display_name, "Local News");
+        assert_eq!(channel.match_count, 1);
+
+        let err = promote_orphan_to_plex_internal(&mut conn, 10, "Local News", None).unwrap_err();
+        assert_eq!(err, "This stream has already been promoted to a channel");
+
+        drop(conn);
+        drop(pool);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /// Count SELECT/INSERT/UPDATE statements run on the connection
+    fn count_statements(conn: &mut SqliteConnection) -> Arc<AtomicUsize> {
+        let count = Arc::new(AtomicUsize::new(0));
+        let counter = count.clone();
+        conn.set_instrumentation(move |event: InstrumentationEvent<'_>| {
+            if let InstrumentationEvent::StartQuery { query, .. } = event {
+                let sql = query.to_string().trim_start().to_uppercase();
+                if ["SELECT", "INSERT", "UPDATE"].iter().any(|kw| sql.starts_with(kw)) {
+                    counter.fetch_add(1, Ordering::SeqCst);
+                }
+            }
+        });
+        count
+    }
+
+    fn load_settings(conn: &mut SqliteConnection) -> Vec<XmltvChannelSettings> {
+        xmltv_channel_settings::table
+            .order(xmltv_channel_settings::xmltv_channel_id.asc())
+            .load(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_update_channel_order_uses_bulk_upsert() {
+        let mut conn = test_connection();
+        let channel_ids: Vec<i32> = (1..=1000).collect();
+
+        // Existing settings row keeps its enabled state
+        diesel::insert_into(xmltv_channel_settings::table)
+            .values(&crate::db::models::NewXmltvChannelSettings::enabled(1))
+            .execute(&mut conn)
+            .unwrap();
+
+        let count = count_statements(&mut conn);
+        update_channel_order_internal(&mut conn, &channel_ids).unwrap();
+        assert_eq!(
+            count.load(Ordering::SeqCst),
+            channel_ids.len().div_ceil(SETTINGS_UPSERT_CHUNK_SIZE)
+        );
+
+        let settings = load_settings(&mut conn);
+        assert_eq!(settings.len(), 1000);
+        assert_eq!(settings[0].is_enabled, Some(1));
+        assert_eq!(settings[1].is_enabled, Some(0));
+        assert!(settings
+            .iter()
+            .all(|s| s.plex_display_order == Some(s.xmltv_channel_id - 1)));
+
+        // Reversing the order only updates existing rows
+        let reversed: Vec<i32> = channel_ids.iter().rev().copied().collect();
+        update_channel_order_internal(&mut conn, &reversed).unwrap();
+        let settings = load_settings(&mut conn);
+        assert_eq!(settings.len(), 1000);
+        assert_eq!(settings[0].plex_display_order, Some(999));
+        assert_eq!(settings[999].plex_display_order, Some(0));
+    }
+
+    #[test]
+    fn test_bulk_toggle_channels_uses_bulk_queries() {
+        let mut conn = test_connection();
+        let channel_ids: Vec<i32> = (1..=1000).collect();
+
+        // Only even channels have a matched stream
+        let mappings: Vec<_> = channel_ids
+            .iter()
+            .filter(|id| *id % 2 == 0)
+            .map(|id| {
+                (
+                    channel_mappings::xmltv_channel_id.eq(*id),
+                    channel_mappings::xtream_channel_id.eq(*id),
+                )
+            })
+            .collect();
+        diesel::insert_into(channel_mappings::table)
+            .values(&mappings)
+            .execute(&mut conn)
+            .unwrap();
+
+        let count = count_statements(&mut conn);
+        let result = bulk_toggle_channels_internal(&mut conn, &channel_ids, true).unwrap();
+        let chunks = channel_ids.len().div_ceil(SETTINGS_UPSERT_CHUNK_SIZE);
+        let upsert_chunks = 500usize.div_ceil(SETTINGS_UPSERT_CHUNK_SIZE);
+        assert_eq!(count.load(Ordering::SeqCst), chunks + upsert_chunks);
+
+        assert_eq!(result.success_count, 500);
+        assert_eq!(result.skipped_count, 500);
+        assert!(result.skipped_ids.iter().all(|id| id % 2 == 1));
+
+        let settings = load_settings(&mut conn);
+        assert_eq!(settings.len(), 500);
+        assert!(settings.iter().all(|s| s.is_enabled == Some(1)));
+
+        // Disabling needs no mapping lookup and touches every channel
+        count.store(0, Ordering::SeqCst);
+        let result = bulk_toggle_channels_internal(&mut conn, &channel_ids, false).unwrap();
+        assert_eq!(count.load(Ordering::SeqCst), chunks);
+        assert_eq!(result.success_count, 1000);
+        assert_eq!(result.skipped_count, 0);
+
+        let settings = load_settings(&mut conn);
+        assert_eq!(settings.len(), 1000);
+        assert!(settings.iter().all(|s| s.is_enabled == Some(0)));
+    }
 }
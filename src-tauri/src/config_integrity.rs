@@ -0,0 +1,227 @@
+//! Integrity hashes and signatures for configuration exports
+//!
+//! An export embeds the SHA-256 of its `data` section and, when a signing key
+//! is available, an Ed25519 signature of that hash made with a key generated
+//! on first export and kept in the app data directory. Import recomputes the
+//! hash so truncated or edited backups are rejected before they replace the
+//! current configuration, and the signature tells whether the file was made
+//! by this installation.
+//!
+//! The hash covers the `data` section as compact JSON with sorted object keys,
+//! so it does not depend on formatting.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::Path;
+
+/// Signing key filename in the app data directory (PKCS#8)
+const SIGNING_KEY_FILENAME: &str = "export_signing_key";
+
+/// Integrity section of an export file
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportIntegrity {
+    /// Hex SHA-256 of the canonical `data` section
+    pub sha256: String,
+    /// Base64 Ed25519 signature of `sha256`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    /// Base64 Ed25519 public key that made `signature`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub public_key: Option<String>,
+}
+
+/// Outcome of checking an import file
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum IntegrityStatus {
+    /// No integrity section (made before exports carried one)
+    Missing,
+    /// Hash matches, file not signed
+    Verified,
+    /// Hash matches and signed by this installation's key
+    SignedHere,
+    /// Hash matches and validly signed by another installation
+    SignedElsewhere,
+    /// Content does not match the hash (truncated or edited)
+    HashMismatch,
+    /// Signature does not match the hash
+    InvalidSignature,
+}
+
+impl IntegrityStatus {
+    /// Whether the file must not be imported
+    pub fn is_failure(self) -> bool {
+        matches!(self, Self::HashMismatch | Self::InvalidSignature)
+    }
+
+    /// Message shown before importing, if any
+    pub fn message(self) -> Option<&'static str> {
+        match self {
+            Self::Missing => Some(
+                "This file has no integrity hash (made by an older version), so it cannot be checked for truncation or edits",
+            ),
+            Self::SignedElsewhere => Some("This file was signed by another StreamForge installation"),
+            Self::HashMismatch => Some(
+                "The file content does not match its integrity hash. It may be truncated or was edited after export",
+            ),
+            Self::InvalidSignature => Some("The file signature is invalid. It may have been tampered with"),
+            Self::Verified | Self::SignedHere => None,
+        }
+    }
+}
+
+/// Hex SHA-256 of a JSON value in canonical form
+pub fn content_hash(data: &serde_json::Value) -> String {
+    // serde_json::Map keeps keys sorted, so this serialization is canonical
+    let bytes = serde_json::to_vec(data).unwrap_or_default();
+    format!("{:x}", Sha256::digest(&bytes))
+}
+
+/// Build the integrity section for an export's `data` section
+///
+/// Signs with the local key when `app_data_dir` is given and the key can be
+/// loaded or created; otherwise the export carries only the hash.
+pub fn seal(data: &serde_json::Value, app_data_dir: Option<&Path>) -> ExportIntegrity {
+    let sha256 = content_hash(data);
+    let key = app_data_dir.and_then(|dir| match load_or_create_key(dir) {
+        Ok(key) => Some(key),
+        Err(e) => {
+            eprintln!("Export signing unavailable: {}", e);
+            None
+        }
+    });
+    let (signature, public_key) = match key {
+        Some(key) => (
+            Some(STANDARD.encode(key.sign(sha256.as_bytes()).as_ref())),
+            Some(STANDARD.encode(key.public_key().as_ref())),
+        ),
+        None => (None, None),
+    };
+    ExportIntegrity {
+        sha256,
+        signature,
+        public_key,
+    }
+}
+
+/// Check an import file's `data` section against its integrity section
+///
+/// `local_public_key` is this installation's key (see `local_public_key`).
+pub fn verify(
+    data: &serde_json::Value,
+    integrity: Option<&ExportIntegrity>,
+    local_public_key: Option<&str>,
+) -> IntegrityStatus {
+    let Some(integrity) = integrity else {
+        return IntegrityStatus::Missing;
+    };
+    if !content_hash(data).eq_ignore_ascii_case(integrity.sha256.trim()) {
+        return IntegrityStatus::HashMismatch;
+    }
+    let (signature, public_key) = match (&integrity.signature, &integrity.public_key) {
+        (None, None) => return IntegrityStatus::Verified,
+        (Some(signature), Some(public_key)) => (signature, public_key),
+        _ => return IntegrityStatus::InvalidSignature,
+    };
+    let valid = match (STANDARD.decode(signature), STANDARD.decode(public_key)) {
+        (Ok(signature), Ok(key)) => UnparsedPublicKey::new(&ED25519, key)
+            .verify(integrity.sha256.as_bytes(), &signature)
+            .is_ok(),
+        _ => false,
+    };
+    if !valid {
+        IntegrityStatus::InvalidSignature
+    } else if local_public_key == Some(public_key.as_str()) {
+        IntegrityStatus::SignedHere
+    } else {
+        IntegrityStatus::SignedElsewhere
+    }
+}
+
+/// Base64 public key of this installation, if it has exported before
+pub fn local_public_key(app_data_dir: &Path) -> Option<String> {
+    let pkcs8 = fs::read(app_data_dir.join(SIGNING_KEY_FILENAME)).ok()?;
+    let key = Ed25519KeyPair::from_pkcs8(&pkcs8).ok()?;
+    Some(STANDARD.encode(key.public_key().as_ref()))
+}
+
+/// Load the signing key, generating it on first use
+fn load_or_create_key(app_data_dir: &Path) -> Result<Ed25519KeyPair, String> {
+    let path = app_data_dir.join(SIGNING_KEY_FILENAME);
+    if let Ok(pkcs8) = fs::read(&path) {
+        if let Ok(key) = Ed25519KeyPair::from_pkcs8(&pkcs8) {
+            return Ok(key);
+        }
+    }
+
+    let pkcs8 = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+        .map_err(|_| "Failed to generate signing key".to_string())?;
+    fs::create_dir_all(app_data_dir).map_err(|e| e.to_string())?;
+    fs::write(&path, pkcs8.as_ref()).map_err(|e| e.to_string())?;
+    Ed25519KeyPair::from_pkcs8(pkcs8.as_ref()).map_err(|_| "Invalid signing key".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_hash_ignores_formatting() {
+        let compact: serde_json::Value = serde_json::from_str(r#"{"b":1,"a":[1,2]}"#).unwrap();
+        let pretty: serde_json::Value =
+            serde_json::from_str("{\n  \"a\": [1, 2],\n  \"b\": 1\n}").unwrap();
+        assert_eq!(content_hash(&compact), content_hash(&pretty));
+        assert_ne!(
+            content_hash(&compact),
+            content_hash(&json!({"a": [1], "b": 1}))
+        );
+    }
+
+    #[test]
+    fn test_seal_and_verify() {
+        let dir = std::env::temp_dir().join(format!("sf-integrity-{}", std::process::id()));
+        let data = json!({"accounts": [{"name": "Main"}]});
+
+        let unsigned = seal(&data, None);
+        assert_eq!(
+            verify(&data, Some(&unsigned), None),
+            IntegrityStatus::Verified
+        );
+        assert_eq!(verify(&data, None, None), IntegrityStatus::Missing);
+
+        let signed = seal(&data, Some(&dir));
+        let local = local_public_key(&dir);
+        assert_eq!(signed.public_key, local);
+        assert_eq!(
+            verify(&data, Some(&signed), local.as_deref()),
+            IntegrityStatus::SignedHere
+        );
+        assert_eq!(
+            verify(&data, Some(&signed), None),
+            IntegrityStatus::SignedElsewhere
+        );
+
+        // Edited content, and a re-hashed file with the old signature
+        let edited = json!({"accounts": [{"name": "Other"}]});
+        assert_eq!(
+            verify(&edited, Some(&signed), local.as_deref()),
+            IntegrityStatus::HashMismatch
+        );
+        let rehashed = ExportIntegrity {
+            sha256: content_hash(&edited),
+            ..signed
+        };
+        assert_eq!(
+            verify(&edited, Some(&rehashed), local.as_deref()),
+            IntegrityStatus::InvalidSignature
+        );
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
@@ -3,6 +3,23 @@
 //! This module provides secure credential storage using:
 //! 1. OS Keychain (via keyring crate) as primary storage
 //! 2. AES-256-GCM encryption as fallback when keychain is unavailable
+//!
+//! Fallback blobs start with a version header. Version 2 derives the key with
+//! HKDF-SHA256 from a stored random salt and the OS machine ID (or an ID kept in
+//! the app data directory where the OS has none), so it survives hostname
+//! changes. Blobs without a header are version 1, keyed on the hostname; they
+//! are still read, and `reencrypt` moves them (or everything, when rotating the
+//! salt) to the current version.
+//!
+//! Keychain entries are named `{workspace}/{account_id}`, where the workspace
+//! part is derived from the active database path (`set_workspace`), so
+//! accounts with the same ID in different workspaces do not share a password.
+//! The database placeholder records the entry name; placeholders of older
+//! versions (`keychain:{account_id}`) still resolve to their unprefixed entry.
+//!
+//! `transfer` wraps passwords with a passphrase for configuration exports.
+
+pub mod transfer;
 
 use aes_gcm::{
     aead::{Aead, KeyInit},
@@ -10,23 +27,39 @@ use aes_gcm::{
 };
 use hkdf::Hkdf;
 use rand::RngCore;
-use sha2::Sha256;
+use sha2::{Digest, Sha256};
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::RwLock;
 use thiserror::Error;
 
 /// Service name for keyring entries
 const SERVICE_NAME: &str = "iptv";
 
+/// Prefix of the database placeholder for passwords kept in the keychain
+const KEYCHAIN_PLACEHOLDER_PREFIX: &str = "keychain:";
+
+/// Keychain namespace of the active workspace (empty until set)
+static WORKSPACE: RwLock<String> = RwLock::new(String::new());
+
 /// Salt filename for AES fallback encryption
 const SALT_FILENAME: &str = "credential_salt";
 
 /// Length of the salt used for key derivation
-const SALT_LENGTH: usize = 32;
+pub const SALT_LENGTH: usize = 32;
 
 /// Nonce length for AES-256-GCM
 const NONCE_LENGTH: usize = 12;
 
+/// Marker starting versioned fallback blobs, followed by the version byte
+const BLOB_MAGIC: &[u8; 3] = b"SFC";
+
+/// Current fallback blob version
+const BLOB_VERSION: u8 = 2;
+
+/// Filename of the generated machine ID used where the OS provides none
+const MACHINE_ID_FILENAME: &str = "machine_id";
+
 /// Errors that can occur during credential operations
 #[derive(Debug, Error)]
 pub enum CredentialError {
@@ -89,11 +122,12 @@ impl CredentialManager {
         password: &str,
     ) -> Result<(StorageBackend, Vec<u8>)> {
         // Try keychain first
-        match self.store_in_keychain(account_id, password) {
+        let entry = keychain_entry_name(account_id);
+        match self.store_in_keychain(&entry, password) {
             Ok(()) => {
                 // For keychain storage, we still store a placeholder in the database
                 // to indicate we're using keychain storage
-                let placeholder = self.create_keychain_placeholder(account_id);
+                let placeholder = self.create_keychain_placeholder(&entry);
                 Ok((StorageBackend::Keychain, placeholder))
             }
             Err(_) => {
@@ -114,14 +148,71 @@ impl CredentialManager {
     /// The decrypted password
     pub fn retrieve_password(&self, account_id: &str, encrypted_data: &[u8]) -> Result<String> {
         // Check if this is a keychain placeholder
-        if self.is_keychain_placeholder(account_id, encrypted_data) {
-            return self.retrieve_from_keychain(account_id);
+        if let Some(entry) = self.keychain_entry(account_id, encrypted_data) {
+            return self.retrieve_from_keychain(&entry);
         }
 
         // Otherwise, decrypt using AES
         self.decrypt_password(encrypted_data)
     }
 
+    /// Whether stored data is a keychain placeholder or current-version blob
+    pub fn is_current(&self, account_id: &str, encrypted_data: &[u8]) -> bool {
+        encrypted_data.is_empty()
+            || self.is_keychain_placeholder(account_id, encrypted_data)
+            || blob_version(encrypted_data) == Some(BLOB_VERSION)
+    }
+
+    /// Re-encrypt stored data with the current scheme
+    ///
+    /// With `rotate_to`, fallback blobs are encrypted with the key of that new
+    /// salt; the caller saves it with `save_salt` once every blob has been
+    /// rewritten. Returns None when nothing changes: keychain placeholders,
+    /// empty data, and current blobs when not rotating.
+    pub fn reencrypt(
+        &self,
+        account_id: &str,
+        encrypted_data: &[u8],
+        rotate_to: Option<&[u8; SALT_LENGTH]>,
+    ) -> Result<Option<Vec<u8>>> {
+        if encrypted_data.is_empty() || self.is_keychain_placeholder(account_id, encrypted_data) {
+            return Ok(None);
+        }
+        if rotate_to.is_none() && blob_version(encrypted_data) == Some(BLOB_VERSION) {
+            return Ok(None);
+        }
+        let password = self.decrypt_password(encrypted_data)?;
+        let salt = match rotate_to {
+            Some(salt) => *salt,
+            None => self.get_or_create_salt()?,
+        };
+        self.encrypt_with_key(&self.derive_key(&salt)?, &password)
+            .map(Some)
+    }
+
+    /// Generate a salt for key rotation
+    pub fn generate_salt() -> [u8; SALT_LENGTH] {
+        let mut salt = [0u8; SALT_LENGTH];
+        rand::rngs::OsRng.fill_bytes(&mut salt);
+        salt
+    }
+
+    /// The stored salt (created on first use)
+    pub fn current_salt(&self) -> Result<[u8; SALT_LENGTH]> {
+        self.get_or_create_salt()
+    }
+
+    /// Replace the stored salt (completes a key rotation)
+    ///
+    /// Written to a temporary file and renamed, so the salt is never partial.
+    pub fn save_salt(&self, salt: &[u8; SALT_LENGTH]) -> Result<()> {
+        fs::create_dir_all(&self.app_data_dir)?;
+        let tmp_path = self.app_data_dir.join(format!("{}.tmp", SALT_FILENAME));
+        fs::write(&tmp_path, salt)?;
+        fs::rename(&tmp_path, self.app_data_dir.join(SALT_FILENAME))?;
+        Ok(())
+    }
+
     /// Delete a password from storage
     ///
     /// # Arguments
@@ -129,16 +220,16 @@ impl CredentialManager {
     /// * `encrypted_data` - The encrypted data from database (to determine storage type)
     pub fn delete_password(&self, account_id: &str, encrypted_data: &[u8]) -> Result<()> {
         // Try to delete from keychain (if it exists there)
-        if self.is_keychain_placeholder(account_id, encrypted_data) {
-            let _ = self.delete_from_keychain(account_id);
+        if let Some(entry) = self.keychain_entry(account_id, encrypted_data) {
+            let _ = self.delete_from_keychain(&entry);
         }
         // Encrypted data in database is deleted by the caller (database delete)
         Ok(())
     }
 
-    /// Store password in OS keychain
-    fn store_in_keychain(&self, account_id: &str, password: &str) -> Result<()> {
-        let entry = keyring::Entry::new(SERVICE_NAME, account_id)
+    /// Store password in OS keychain under an entry name
+    fn store_in_keychain(&self, entry_name: &str, password: &str) -> Result<()> {
+        let entry = keyring::Entry::new(SERVICE_NAME, entry_name)
             .map_err(|e| CredentialError::KeyringError(e.to_string()))?;
         entry
             .set_password(password)
@@ -146,18 +237,18 @@ impl CredentialManager {
         Ok(())
     }
 
-    /// Retrieve password from OS keychain
-    fn retrieve_from_keychain(&self, account_id: &str) -> Result<String> {
-        let entry = keyring::Entry::new(SERVICE_NAME, account_id)
+    /// Retrieve password from OS keychain by entry name
+    fn retrieve_from_keychain(&self, entry_name: &str) -> Result<String> {
+        let entry = keyring::Entry::new(SERVICE_NAME, entry_name)
             .map_err(|e| CredentialError::KeyringError(e.to_string()))?;
         entry
             .get_password()
             .map_err(|e| CredentialError::KeyringError(e.to_string()))
     }
 
-    /// Delete password from OS keychain
-    fn delete_from_keychain(&self, account_id: &str) -> Result<()> {
-        let entry = keyring::Entry::new(SERVICE_NAME, account_id)
+    /// Delete password from OS keychain by entry name
+    fn delete_from_keychain(&self, entry_name: &str) -> Result<()> {
+        let entry = keyring::Entry::new(SERVICE_NAME, entry_name)
             .map_err(|e| CredentialError::KeyringError(e.to_string()))?;
         entry
             .delete_credential()
@@ -167,21 +258,39 @@ impl CredentialManager {
 
     /// Create a placeholder for keychain storage
     /// This is stored in the database to indicate that the actual password is in keychain
-    fn create_keychain_placeholder(&self, account_id: &str) -> Vec<u8> {
-        // Format: "keychain:" + account_id
-        format!("keychain:{}", account_id).into_bytes()
+    fn create_keychain_placeholder(&self, entry_name: &str) -> Vec<u8> {
+        // Format: "keychain:" + entry name
+        format!("{}{}", KEYCHAIN_PLACEHOLDER_PREFIX, entry_name).into_bytes()
+    }
+
+    /// Keychain entry named by a placeholder of `account_id`
+    ///
+    /// Returns None if the data is not such a placeholder.
+    fn keychain_entry(&self, account_id: &str, data: &[u8]) -> Option<String> {
+        let entry = std::str::from_utf8(data)
+            .ok()?
+            .strip_prefix(KEYCHAIN_PLACEHOLDER_PREFIX)?;
+        let owned = entry == account_id
+            || entry
+                .split_once('/')
+                .is_some_and(|(_, id)| id == account_id);
+        owned.then(|| entry.to_string())
     }
 
     /// Check if the encrypted data is a keychain placeholder
     fn is_keychain_placeholder(&self, account_id: &str, data: &[u8]) -> bool {
-        let expected = format!("keychain:{}", account_id);
-        data == expected.as_bytes()
+        self.keychain_entry(account_id, data).is_some()
     }
 
-    /// Encrypt password using AES-256-GCM
+    /// Encrypt password using AES-256-GCM (current blob version)
     fn encrypt_password(&self, password: &str) -> Result<Vec<u8>> {
-        let key = self.get_or_create_encryption_key()?;
-        let cipher = Aes256Gcm::new_from_slice(&key)
+        let key = self.derive_key(&self.get_or_create_salt()?)?;
+        self.encrypt_with_key(&key, password)
+    }
+
+    /// Encrypt into a versioned blob: header, nonce, ciphertext
+    fn encrypt_with_key(&self, key: &[u8; 32], password: &str) -> Result<Vec<u8>> {
+        let cipher = Aes256Gcm::new_from_slice(key)
             .map_err(|e| CredentialError::EncryptionError(e.to_string()))?;
 
         // Generate random nonce
@@ -194,53 +303,80 @@ impl CredentialManager {
             .encrypt(nonce, password.as_bytes())
             .map_err(|e| CredentialError::EncryptionError(e.to_string()))?;
 
-        // Prepend nonce to ciphertext for storage
-        let mut result = nonce_bytes.to_vec();
+        let mut result = BLOB_MAGIC.to_vec();
+        result.push(BLOB_VERSION);
+        result.extend(nonce_bytes);
         result.extend(ciphertext);
         Ok(result)
     }
 
-    /// Decrypt password using AES-256-GCM
+    /// Decrypt password using AES-256-GCM (any blob version)
     fn decrypt_password(&self, encrypted: &[u8]) -> Result<String> {
         if encrypted.len() < NONCE_LENGTH {
             return Err(CredentialError::InvalidData(
                 "Encrypted data too short".to_string(),
             ));
         }
+        let salt = self.get_or_create_salt()?;
 
-        let key = self.get_or_create_encryption_key()?;
-        let cipher = Aes256Gcm::new_from_slice(&key)
-            .map_err(|e| CredentialError::DecryptionError(e.to_string()))?;
-
-        // Split nonce and ciphertext
-        let (nonce_bytes, ciphertext) = encrypted.split_at(NONCE_LENGTH);
-        let nonce = Nonce::from_slice(nonce_bytes);
-
-        // Decrypt
-        let plaintext = cipher
-            .decrypt(nonce, ciphertext)
-            .map_err(|e| CredentialError::DecryptionError(e.to_string()))?;
+        if blob_version(encrypted) == Some(BLOB_VERSION) {
+            let body = &encrypted[BLOB_MAGIC.len() + 1..];
+            // Try every machine ID the key may have been made with
+            for machine_id in self.machine_id_candidates() {
+                if let Ok(password) =
+                    decrypt_blob(&hkdf_key(&salt, &machine_id, KEY_INFO_V2)?, body)
+                {
+                    return Ok(password);
+                }
+            }
+            // A version 1 blob can start with the header by chance; try it as one
+        }
 
-        String::from_utf8(plaintext)
-            .map_err(|e| CredentialError::DecryptionError(e.to_string()))
+        let key = hkdf_key(&salt, &hostname_identifier(), KEY_INFO_V1)?;
+        decrypt_blob(&key, encrypted)
     }
 
-    /// Get or create the encryption key
-    /// Key is derived from a stored salt combined with machine-specific info using HKDF-SHA256
-    fn get_or_create_encryption_key(&self) -> Result<[u8; 32]> {
-        let salt = self.get_or_create_salt()?;
-        let machine_id = self.get_machine_identifier();
+    /// Current-version key for a salt
+    fn derive_key(&self, salt: &[u8; SALT_LENGTH]) -> Result<[u8; 32]> {
+        let machine_id = self
+            .machine_id_candidates()
+            .into_iter()
+            .next()
+            .unwrap_or_default();
+        hkdf_key(salt, &machine_id, KEY_INFO_V2)
+    }
 
-        // Use HKDF-SHA256 for proper key derivation
-        // IKM (Input Key Material) = machine identifier
-        // Salt = stored random salt
-        // Info = application context
-        let hk = Hkdf::<Sha256>::new(Some(&salt), &machine_id);
-        let mut key = [0u8; 32];
-        hk.expand(b"iptv-credential-encryption-key-v1", &mut key)
-            .map_err(|e| CredentialError::EncryptionError(format!("HKDF expand failed: {}", e)))?;
+    /// Machine IDs to derive keys from, preferred first
+    ///
+    /// The OS machine ID when there is one, then the generated ID in the app data
+    /// directory (created on first use), so a key stays readable if the OS ID is
+    /// unavailable later.
+    fn machine_id_candidates(&self) -> Vec<Vec<u8>> {
+        let mut candidates = vec![];
+        if let Some(id) = os_machine_id() {
+            candidates.push(id.into_bytes());
+        }
+        match self.get_or_create_local_machine_id() {
+            Ok(id) => candidates.push(id),
+            Err(e) => eprintln!("Credentials: local machine ID unavailable: {}", e),
+        }
+        candidates
+    }
 
-        Ok(key)
+    /// Random ID generated once and kept in the app data directory
+    fn get_or_create_local_machine_id(&self) -> Result<Vec<u8>> {
+        let path = self.app_data_dir.join(MACHINE_ID_FILENAME);
+        if let Ok(id) = fs::read(&path) {
+            if !id.is_empty() {
+                return Ok(id);
+            }
+        }
+        let mut bytes = [0u8; 16];
+        rand::rngs::OsRng.fill_bytes(&mut bytes);
+        let id: String = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+        fs::create_dir_all(&self.app_data_dir)?;
+        fs::write(&path, &id)?;
+        Ok(id.into_bytes())
     }
 
     /// Get or create the encryption salt
@@ -270,19 +406,124 @@ impl CredentialManager {
 
         Ok(salt)
     }
+}
 
-    /// Get a machine-specific identifier
-    /// This helps tie the encryption to this specific machine
-    fn get_machine_identifier(&self) -> Vec<u8> {
-        // Use hostname as a simple machine identifier
-        // In production, consider using more robust machine identification
-        hostname::get()
-            .map(|h| h.to_string_lossy().into_owned())
-            .unwrap_or_else(|_| "default-machine".to_string())
-            .into_bytes()
+/// HKDF info of version 1 blobs (hostname keyed)
+const KEY_INFO_V1: &[u8] = b"iptv-credential-encryption-key-v1";
+
+/// HKDF info of version 2 blobs (machine ID keyed)
+const KEY_INFO_V2: &[u8] = b"streamforge-credential-encryption-key-v2";
+
+/// Namespace new keychain entries by the workspace database at `database_path`
+pub fn set_workspace(database_path: &Path) {
+    let digest = Sha256::digest(database_path.to_string_lossy().as_bytes());
+    let namespace: String = digest
+        .iter()
+        .take(8)
+        .map(|b| format!("{:02x}", b))
+        .collect();
+    if let Ok(mut workspace) = WORKSPACE.write() {
+        *workspace = namespace;
     }
 }
 
+/// Keychain entry name for an account in the active workspace
+fn keychain_entry_name(account_id: &str) -> String {
+    match WORKSPACE.read() {
+        Ok(workspace) if !workspace.is_empty() => format!("{}/{}", workspace, account_id),
+        _ => account_id.to_string(),
+    }
+}
+
+/// Version of a versioned blob (None for version 1 blobs, which have no header)
+fn blob_version(data: &[u8]) -> Option<u8> {
+    let header_length = BLOB_MAGIC.len() + 1;
+    (data.len() > header_length + NONCE_LENGTH && data.starts_with(BLOB_MAGIC))
+        .then(|| data[BLOB_MAGIC.len()])
+}
+
+/// HKDF-SHA256 key: IKM = machine identifier, salt = stored random salt
+fn hkdf_key(salt: &[u8], machine_id: &[u8], info: &[u8]) -> Result<[u8; 32]> {
+    let hk = Hkdf::<Sha256>::new(Some(salt), machine_id);
+    let mut key = [0u8; 32];
+    hk.expand(info, &mut key)
+        .map_err(|e| CredentialError::EncryptionError(format!("HKDF expand failed: {}", e)))?;
+    Ok(key)
+}
+
+/// Decrypt nonce + ciphertext
+fn decrypt_blob(key: &[u8; 32], data: &[u8]) -> Result<String> {
+    if data.len() < NONCE_LENGTH {
+        return Err(CredentialError::InvalidData(
+            "Encrypted data too short".to_string(),
+        ));
+    }
+    let cipher = Aes256Gcm::new_from_slice(key)
+        .map_err(|e| CredentialError::DecryptionError(e.to_string()))?;
+    let (nonce_bytes, ciphertext) = data.split_at(NONCE_LENGTH);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce_bytes), ciphertext)
+        .map_err(|e| CredentialError::DecryptionError(e.to_string()))?;
+    String::from_utf8(plaintext).map_err(|e| CredentialError::DecryptionError(e.to_string()))
+}
+
+/// Hostname, the machine identifier of version 1 blobs
+fn hostname_identifier() -> Vec<u8> {
+    hostname::get()
+        .map(|h| h.to_string_lossy().into_owned())
+        .unwrap_or_else(|_| "default-machine".to_string())
+        .into_bytes()
+}
+
+/// Machine ID provided by the OS, if any
+#[cfg(target_os = "linux")]
+fn os_machine_id() -> Option<String> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| fs::read_to_string(path).ok())
+        .map(|id| id.trim().to_string())
+        .filter(|id| !id.is_empty())
+}
+
+/// Machine ID provided by the OS, if any
+#[cfg(target_os = "macos")]
+fn os_machine_id() -> Option<String> {
+    let output = std::process::Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("IOPlatformUUID"))
+        .and_then(|line| line.split('"').nth(3))
+        .map(str::to_string)
+}
+
+/// Machine ID provided by the OS, if any
+#[cfg(target_os = "windows")]
+fn os_machine_id() -> Option<String> {
+    let output = std::process::Command::new("reg")
+        .args([
+            "query",
+            r"HKLM\SOFTWARE\Microsoft\Cryptography",
+            "/v",
+            "MachineGuid",
+        ])
+        .output()
+        .ok()?;
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .find(|line| line.contains("MachineGuid"))
+        .and_then(|line| line.split_whitespace().last())
+        .map(str::to_string)
+}
+
+/// Machine ID provided by the OS, if any
+#[cfg(not(any(target_os = "linux", target_os = "macos", target_os = "windows")))]
+fn os_machine_id() -> Option<String> {
+    None
+}
+
 /// Standalone function to store a password (for backward compatibility)
 pub fn store_password(
     app_data_dir: &Path,
@@ -400,6 +641,18 @@ mod tests {
         assert!(manager.is_keychain_placeholder(account_id, &placeholder));
         assert!(!manager.is_keychain_placeholder("other_account", &placeholder));
 
+        // Workspace entries resolve to their own name, legacy ones to the account ID
+        let placeholder = manager.create_keychain_placeholder("0a1b2c3d4e5f6a7b/7");
+        assert_eq!(
+            manager.keychain_entry("7", &placeholder).as_deref(),
+            Some("0a1b2c3d4e5f6a7b/7")
+        );
+        assert_eq!(manager.keychain_entry("17", &placeholder), None);
+        assert_eq!(
+            manager.keychain_entry("7", b"keychain:7").as_deref(),
+            Some("7")
+        );
+
         // Cleanup
         let _ = fs::remove_dir_all(&app_data_dir);
     }
@@ -416,4 +669,51 @@ mod tests {
         // Cleanup
         let _ = fs::remove_dir_all(&app_data_dir);
     }
+
+    #[test]
+    fn test_legacy_blob_reencrypt_and_rotation() {
+        let app_data_dir = get_unique_test_app_data_dir();
+        let manager = CredentialManager::new(app_data_dir.clone());
+
+        // Version 1 blob: nonce + ciphertext under the hostname key
+        let salt = manager.get_or_create_salt().unwrap();
+        let legacy_key = hkdf_key(&salt, &hostname_identifier(), KEY_INFO_V1).unwrap();
+        let cipher = Aes256Gcm::new_from_slice(&legacy_key).unwrap();
+        let nonce = [7u8; NONCE_LENGTH];
+        let mut legacy = nonce.to_vec();
+        legacy.extend(
+            cipher
+                .encrypt(Nonce::from_slice(&nonce), b"old".as_ref())
+                .unwrap(),
+        );
+
+        assert!(!manager.is_current("1", &legacy));
+        assert_eq!(manager.decrypt_password(&legacy).unwrap(), "old");
+
+        let migrated = manager.reencrypt("1", &legacy, None).unwrap().unwrap();
+        assert_eq!(blob_version(&migrated), Some(BLOB_VERSION));
+        assert!(manager.is_current("1", &migrated));
+        assert_eq!(manager.decrypt_password(&migrated).unwrap(), "old");
+        assert!(manager.reencrypt("1", &migrated, None).unwrap().is_none());
+
+        // Keychain placeholders are left alone
+        let placeholder = manager.create_keychain_placeholder("1");
+        assert!(manager
+            .reencrypt("1", &placeholder, None)
+            .unwrap()
+            .is_none());
+
+        // Rotation: readable only once the new salt is saved
+        let new_salt = CredentialManager::generate_salt();
+        let rotated = manager
+            .reencrypt("1", &migrated, Some(&new_salt))
+            .unwrap()
+            .unwrap();
+        assert!(manager.decrypt_password(&rotated).is_err());
+        manager.save_salt(&new_salt).unwrap();
+        assert_eq!(manager.decrypt_password(&rotated).unwrap(), "old");
+
+        // Cleanup
+        let _ = fs::remove_dir_all(&app_data_dir);
+    }
 }
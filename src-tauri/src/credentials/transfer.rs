@@ -0,0 +1,152 @@
+//! Passphrase-wrapped secrets for moving credentials between machines
+//!
+//! Stored passwords are tied to this machine (keychain entries, or the AES
+//! fallback keyed on a local salt), so they cannot be copied as they are. An
+//! export that includes credentials instead encrypts them with a key derived
+//! from a passphrase the user chooses: PBKDF2-HMAC-SHA256 with a random salt,
+//! then AES-256-GCM. The KDF name and iteration count travel with the data so
+//! they can be raised later without breaking older files.
+
+use std::num::NonZeroU32;
+
+use aes_gcm::{
+    aead::{Aead, KeyInit},
+    Aes256Gcm, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use rand::RngCore;
+use ring::pbkdf2;
+use serde::{Deserialize, Serialize};
+
+use super::{CredentialError, Result, NONCE_LENGTH, SALT_LENGTH};
+
+/// Key derivation function of sealed secrets
+pub const KDF_PBKDF2_SHA256: &str = "pbkdf2-sha256";
+
+/// PBKDF2 iterations for new exports (OWASP recommendation for SHA-256)
+pub const PBKDF2_ITERATIONS: u32 = 600_000;
+
+/// Shortest passphrase accepted for new exports
+pub const MIN_PASSPHRASE_LENGTH: usize = 8;
+
+/// Secrets encrypted with a passphrase (all binary fields base64)
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SealedSecrets {
+    pub kdf: String,
+    pub iterations: u32,
+    pub salt: String,
+    pub nonce: String,
+    pub ciphertext: String,
+}
+
+/// Encrypt `plaintext` with a key derived from `passphrase`
+pub fn seal(passphrase: &str, plaintext: &[u8]) -> Result<SealedSecrets> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LENGTH {
+        return Err(CredentialError::InvalidData(format!(
+            "Passphrase must be at least {} characters",
+            MIN_PASSPHRASE_LENGTH
+        )));
+    }
+    seal_with_iterations(passphrase, plaintext, PBKDF2_ITERATIONS)
+}
+
+fn seal_with_iterations(
+    passphrase: &str,
+    plaintext: &[u8],
+    iterations: u32,
+) -> Result<SealedSecrets> {
+    let mut salt = [0u8; SALT_LENGTH];
+    rand::rngs::OsRng.fill_bytes(&mut salt);
+    let mut nonce = [0u8; NONCE_LENGTH];
+    rand::rngs::OsRng.fill_bytes(&mut nonce);
+
+    let key = derive_key(passphrase, &salt, iterations)?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| CredentialError::EncryptionError(e.to_string()))?;
+    let ciphertext = cipher
+        .encrypt(Nonce::from_slice(&nonce), plaintext)
+        .map_err(|e| CredentialError::EncryptionError(e.to_string()))?;
+
+    Ok(SealedSecrets {
+        kdf: KDF_PBKDF2_SHA256.to_string(),
+        iterations,
+        salt: STANDARD.encode(salt),
+        nonce: STANDARD.encode(nonce),
+        ciphertext: STANDARD.encode(ciphertext),
+    })
+}
+
+/// Decrypt sealed secrets with `passphrase`
+///
+/// A wrong passphrase and damaged data both fail authentication and give the
+/// same error.
+pub fn open(passphrase: &str, sealed: &SealedSecrets) -> Result<Vec<u8>> {
+    if sealed.kdf != KDF_PBKDF2_SHA256 {
+        return Err(CredentialError::InvalidData(format!(
+            "Unsupported key derivation: {}",
+            sealed.kdf
+        )));
+    }
+    let decode = |field: &str| {
+        STANDARD
+            .decode(field)
+            .map_err(|e| CredentialError::InvalidData(e.to_string()))
+    };
+    let salt = decode(&sealed.salt)?;
+    let nonce = decode(&sealed.nonce)?;
+    let ciphertext = decode(&sealed.ciphertext)?;
+    if nonce.len() != NONCE_LENGTH {
+        return Err(CredentialError::InvalidData(
+            "Invalid nonce length".to_string(),
+        ));
+    }
+
+    let key = derive_key(passphrase, &salt, sealed.iterations)?;
+    let cipher = Aes256Gcm::new_from_slice(&key)
+        .map_err(|e| CredentialError::DecryptionError(e.to_string()))?;
+    cipher
+        .decrypt(Nonce::from_slice(&nonce), ciphertext.as_slice())
+        .map_err(|_| {
+            CredentialError::DecryptionError("Wrong passphrase or damaged data".to_string())
+        })
+}
+
+/// PBKDF2-HMAC-SHA256 key for AES-256
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> Result<[u8; 32]> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| CredentialError::InvalidData("Invalid iteration count".to_string()))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    Ok(key)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seal_roundtrip_and_wrong_passphrase() {
+        let sealed = seal_with_iterations("correct horse", b"secret", 1_000).unwrap();
+        assert_eq!(sealed.kdf, KDF_PBKDF2_SHA256);
+        assert!(!sealed.ciphertext.contains("secret"));
+
+        assert_eq!(open("correct horse", &sealed).unwrap(), b"secret");
+        assert!(matches!(
+            open("wrong horse", &sealed),
+            Err(CredentialError::DecryptionError(_))
+        ));
+
+        let mut tampered = sealed.clone();
+        tampered.iterations = 999;
+        assert!(open("correct horse", &tampered).is_err());
+
+        assert!(seal("short", b"secret").is_err());
+    }
+}
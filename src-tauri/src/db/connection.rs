@@ -1,7 +1,9 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
 
+use diesel::connection::SimpleConnection;
 use diesel::prelude::*;
-use diesel::r2d2::{ConnectionManager, Pool, PooledConnection};
+use diesel::r2d2::{ConnectionManager, CustomizeConnection, Pool, PooledConnection};
 use diesel::sqlite::SqliteConnection;
 use diesel_migrations::{embed_migrations, EmbeddedMigrations, MigrationHarness};
 
@@ -10,40 +12,170 @@ pub const MIGRATIONS: EmbeddedMigrations = embed_migrations!("migrations");
 pub type DbPool = Pool<ConnectionManager<SqliteConnection>>;
 pub type DbPooledConnection = PooledConnection<ConnectionManager<SqliteConnection>>;
 
+/// Swappable pool handle shared by Tauri commands and the HTTP server
+///
+/// The inner pool is replaced when switching workspaces, so every holder
+/// of the handle sees the new database on its next connection.
+pub type SharedDbPool = Arc<RwLock<DbPool>>;
+
+/// Maximum connections in the read-only pool
+const READ_POOL_MAX_SIZE: u32 = 4;
+
+/// How long a connection waits for a lock before failing with "database is locked"
+const BUSY_TIMEOUT_MS: u32 = 5000;
+
 /// Database connection pool wrapper for Tauri state management
+///
+/// Holds the read-write pool plus a small read-only pool used by heavy
+/// list/report commands, so long analytical queries stay off the write path
+/// used by EPG refresh and the stream proxy.
 pub struct DbConnection {
-    pool: DbPool,
+    pool: SharedDbPool,
+    read_pool: SharedDbPool,
 }
 
 impl DbConnection {
     /// Create a new database connection pool
     pub fn new(database_url: String) -> Result<Self, Box<dyn std::error::Error>> {
-        let manager = ConnectionManager::<SqliteConnection>::new(database_url);
-        let pool = Pool::builder()
-            .max_size(16) // Reasonable pool size for desktop app
-            .build(manager)
-            .map_err(|e| format!("Failed to create connection pool: {}", e))?;
+        // The read-write pool first: it switches the database to WAL, which
+        // read-only connections can't do themselves
+        let pool = build_pool(database_url.clone())?;
+        let read_pool = build_read_pool(&database_url).unwrap_or_else(|e| {
+            eprintln!("Read-only pool unavailable, using read-write pool: {}", e);
+            pool.clone()
+        });
 
-        Ok(Self { pool })
+        Ok(Self {
+            pool: Arc::new(RwLock::new(pool)),
+            read_pool: Arc::new(RwLock::new(read_pool)),
+        })
     }
 
     /// Get a pooled connection from the pool
+    ///
+    /// Waits while startup migrations are still running (see `crate::startup`).
     pub fn get_connection(&self) -> Result<DbPooledConnection, Box<dyn std::error::Error>> {
-        self.pool.get()
+        crate::startup::wait_for_database();
+        self.clone_pool().get()
             .map_err(|e| format!("Failed to get connection from pool: {}", e).into())
     }
 
+    /// Get a read-only connection for heavy list/report queries
+    ///
+    /// Writes on this connection fail (`PRAGMA query_only`). Waits while
+    /// startup migrations are still running.
+    pub fn get_read_connection(&self) -> Result<DbPooledConnection, Box<dyn std::error::Error>> {
+        crate::startup::wait_for_database();
+        self.clone_read_pool().get()
+            .map_err(|e| format!("Failed to get connection from read pool: {}", e).into())
+    }
+
+    /// Clone the read-only pool
+    pub fn clone_read_pool(&self) -> DbPool {
+        self.read_pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
     /// Clone the database pool for use by the HTTP server
     ///
     /// This allows the server module to have its own pool reference
     /// while Tauri manages the DbConnection state.
     #[allow(dead_code)] // Used by lib crate for server initialization
     pub fn clone_pool(&self) -> DbPool {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Get the swappable pool handle (follows workspace switches)
+    pub fn shared_pool(&self) -> SharedDbPool {
         self.pool.clone()
     }
+
+    /// Replace both pools (workspace switch)
+    pub fn replace_pools(&self, pool: DbPool, read_pool: DbPool) {
+        *self.pool.write().unwrap_or_else(|e| e.into_inner()) = pool;
+        *self.read_pool.write().unwrap_or_else(|e| e.into_inner()) = read_pool;
+    }
+}
+
+/// Tunes every connection of the read-write pool for concurrent use
+///
+/// - `journal_mode = WAL`: readers (Plex pulling /epg.xml, the UI) no longer
+///   block on a writer (EPG refresh) or the other way round
+/// - `busy_timeout`: writers queue for the lock instead of failing at once
+/// - `synchronous = NORMAL`: safe with WAL, far fewer fsyncs during refresh
+/// - `foreign_keys = ON`: the `ON DELETE` rules of the schema are applied
+///
+/// The migration connection (`establish_connection`) keeps foreign keys off,
+/// since migrations rebuild tables and would cascade deletes otherwise.
+#[derive(Debug)]
+struct PragmaCustomizer;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for PragmaCustomizer {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        conn.batch_execute(&format!(
+            "PRAGMA journal_mode = WAL; \
+             PRAGMA busy_timeout = {}; \
+             PRAGMA synchronous = NORMAL; \
+             PRAGMA foreign_keys = ON;",
+            BUSY_TIMEOUT_MS
+        ))
+        .map_err(diesel::r2d2::Error::QueryError)
+    }
+}
+
+/// Makes every connection of the read pool read-only
+#[derive(Debug)]
+struct ReadOnlyCustomizer;
+
+impl CustomizeConnection<SqliteConnection, diesel::r2d2::Error> for ReadOnlyCustomizer {
+    fn on_acquire(&self, conn: &mut SqliteConnection) -> Result<(), diesel::r2d2::Error> {
+        diesel::sql_query("PRAGMA query_only = ON")
+            .execute(conn)
+            .map_err(diesel::r2d2::Error::QueryError)?;
+        diesel::sql_query(format!("PRAGMA busy_timeout = {}", BUSY_TIMEOUT_MS))
+            .execute(conn)
+            .map_err(diesel::r2d2::Error::QueryError)?;
+        Ok(())
+    }
+}
+
+/// Build a connection pool for a database URL
+///
+/// Connections are set up by `PragmaCustomizer` (WAL, busy timeout).
+pub fn build_pool(database_url: String) -> Result<DbPool, String> {
+    let manager = ConnectionManager::<SqliteConnection>::new(database_url);
+    Pool::builder()
+        .max_size(16) // Reasonable pool size for desktop app
+        .connection_customizer(Box::new(PragmaCustomizer))
+        .build(manager)
+        .map_err(|e| format!("Failed to create connection pool: {}", e))
+}
+
+/// Build the SQLite URI that opens a database file read-only
+pub fn read_only_database_url(database_path: &Path) -> Result<String, String> {
+    let path = std::path::absolute(database_path)
+        .map_err(|e| format!("Invalid database path '{}': {}", database_path.display(), e))?;
+    let url = url::Url::from_file_path(&path)
+        .map_err(|_| format!("Invalid database path '{}'", path.display()))?;
+    Ok(format!("{}?mode=ro", url))
+}
+
+/// Build the read-only pool for a database file
+///
+/// Connections are opened with `mode=ro` and `PRAGMA query_only`, so they can
+/// never take the write lock.
+pub fn build_read_pool(database_path: &str) -> Result<DbPool, String> {
+    let manager =
+        ConnectionManager::<SqliteConnection>::new(read_only_database_url(Path::new(database_path))?);
+    Pool::builder()
+        .max_size(READ_POOL_MAX_SIZE)
+        .connection_customizer(Box::new(ReadOnlyCustomizer))
+        .build(manager)
+        .map_err(|e| format!("Failed to create read-only connection pool: {}", e))
 }
 
 /// Get the database path using Tauri's app data directory API
+///
+/// Returns the database of the active workspace (see `db::workspace`).
 pub fn get_db_path(app: &tauri::App) -> Result<PathBuf, Box<dyn std::error::Error>> {
     use tauri::Manager;
 
@@ -57,7 +189,8 @@ pub fn get_db_path(app: &tauri::App) -> Result<PathBuf, Box<dyn std::error::Erro
             e
         ))?;
 
-    Ok(app_data_dir.join("iptv.db"))
+    // Honor the active workspace (defaults to iptv.db)
+    Ok(super::workspace::WorkspaceRegistry::load(&app_data_dir).active_path(&app_data_dir))
 }
 
 /// Establish a connection to the SQLite database with busy timeout
@@ -65,7 +198,7 @@ pub fn establish_connection(database_url: &str) -> Result<SqliteConnection, dies
     let mut conn = SqliteConnection::establish(database_url)?;
 
     // Set busy timeout to 5 seconds to handle concurrent access gracefully
-    diesel::sql_query("PRAGMA busy_timeout = 5000")
+    diesel::sql_query(format!("PRAGMA busy_timeout = {}", BUSY_TIMEOUT_MS))
         .execute(&mut conn)
         .map_err(|e| diesel::ConnectionError::BadConnection(format!("Failed to set busy_timeout: {}", e)))?;
 
@@ -79,3 +212,222 @@ pub fn run_migrations(
     conn.run_pending_migrations(MIGRATIONS)?;
     Ok(())
 }
+
+/// In-memory database with all migrations applied, for tests
+#[cfg(test)]
+pub fn test_connection() -> SqliteConnection {
+    let mut conn = SqliteConnection::establish(":memory:").unwrap();
+    run_migrations(&mut conn).unwrap();
+    conn
+}
+
+/// Insert an active Xtream account (user "user", no password), for tests
+#[cfg(test)]
+pub fn seed_account(conn: &mut SqliteConnection, id: i32, name: &str, server_url: &str) {
+    use crate::db::schema::accounts;
+
+    diesel::insert_into(accounts::table)
+        .values((
+            accounts::id.eq(id),
+            accounts::name.eq(name),
+            accounts::server_url.eq(server_url),
+            accounts::username.eq("user"),
+            accounts::password_encrypted.eq(Vec::<u8>::new()),
+        ))
+        .execute(conn)
+        .unwrap();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::settings;
+    use crate::db::Setting;
+
+    #[test]
+    fn test_read_pool_rejects_writes() {
+        let path = std::env::temp_dir().join(format!("streamforge-read-pool-{}.db", uuid::Uuid::new_v4()));
+        let database_url = path.to_string_lossy().to_string();
+
+        let mut conn = establish_connection(&database_url).unwrap();
+        run_migrations(&mut conn).unwrap();
+        diesel::insert_into(settings::table)
+            .values(&Setting::new("read_pool_test", "1"))
+            .execute(&mut conn)
+            .unwrap();
+
+        let read_pool = build_read_pool(&database_url).unwrap();
+        let mut read_conn = read_pool.get().unwrap();
+
+        let value: String = settings::table
+            .filter(settings::key.eq("read_pool_test"))
+            .select(settings::value)
+            .first(&mut read_conn)
+            .unwrap();
+        assert_eq!(value, "1");
+
+        let write = diesel::insert_into(settings::table)
+            .values(&Setting::new("read_pool_write", "1"))
+            .execute(&mut read_conn);
+        assert!(write.is_err());
+
+        drop(read_conn);
+        drop(read_pool);
+        drop(conn);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[test]
+    fn test_read_only_database_url() {
+        let url = read_only_database_url(Path::new("/tmp/stream forge/iptv.db")).unwrap();
+        assert_eq!(url, "file:///tmp/stream%20forge/iptv.db?mode=ro");
+    }
+
+    #[derive(QueryableByName)]
+    struct JournalModeRow {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        journal_mode: String,
+    }
+
+    #[derive(QueryableByName)]
+    struct ForeignKeysRow {
+        #[diesel(sql_type = diesel::sql_types::Integer)]
+        foreign_keys: i32,
+    }
+
+    /// Several threads writing and reading through the pools at once, as
+    /// during an EPG refresh while Plex pulls the guide
+    #[test]
+    fn test_pool_handles_concurrent_reads_and_writes() {
+        use crate::db::schema::event_log;
+        use crate::db::NewEventLog;
+
+        const WORKERS: usize = 8;
+        const ROUNDS: usize = 50;
+
+        let path = std::env::temp_dir().join(format!("streamforge-stress-{}.db", uuid::Uuid::new_v4()));
+        let database_url = path.to_string_lossy().to_string();
+        run_migrations(&mut establish_connection(&database_url).unwrap()).unwrap();
+
+        let pool = build_pool(database_url.clone()).unwrap();
+        let read_pool = build_read_pool(&database_url).unwrap();
+        {
+            let mut conn = pool.get().unwrap();
+            let mode = diesel::sql_query("PRAGMA journal_mode")
+                .get_result::<JournalModeRow>(&mut conn)
+                .unwrap();
+            assert_eq!(mode.journal_mode, "wal");
+            let fk = diesel::sql_query("PRAGMA foreign_keys")
+                .get_result::<ForeignKeysRow>(&mut conn)
+                .unwrap();
+            assert_eq!(fk.foreign_keys, 1);
+        }
+
+        let workers: Vec<_> = (0..WORKERS)
+            .map(|worker| {
+                let pool = pool.clone();
+                let read_pool = read_pool.clone();
+                std::thread::spawn(move || -> Result<(), String> {
+                    for round in 0..ROUNDS {
+                        if worker % 2 == 0 {
+                            let mut conn = pool.get().map_err(|e| e.to_string())?;
+                            conn.immediate_transaction(|conn| {
+                                diesel::insert_into(event_log::table)
+                                    .values(&NewEventLog::info("system", format!("stress {} {}", worker, round)))
+                                    .execute(conn)?;
+                                diesel::replace_into(settings::table)
+                                    .values(&Setting::new(format!("stress_{}", worker), round.to_string()))
+                                    .execute(conn)
+                            })
+                            .map_err(|e| e.to_string())?;
+                        } else {
+                            let mut read_conn = read_pool.get().map_err(|e| e.to_string())?;
+                            event_log::table
+                                .count()
+                                .get_result::<i64>(&mut read_conn)
+                                .map_err(|e| e.to_string())?;
+                            let mut conn = pool.get().map_err(|e| e.to_string())?;
+                            settings::table
+                                .count()
+                                .get_result::<i64>(&mut conn)
+                                .map_err(|e| e.to_string())?;
+                        }
+                    }
+                    Ok(())
+                })
+            })
+            .collect();
+        for worker in workers {
+            worker.join().unwrap().unwrap();
+        }
+
+        let mut conn = pool.get().unwrap();
+        let written: i64 = event_log::table
+            .filter(event_log::message.like("stress %"))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(written, (WORKERS / 2 * ROUNDS) as i64);
+
+        drop(conn);
+        drop(read_pool);
+        drop(pool);
+        for suffix in ["", "-wal", "-shm"] {
+            let _ = std::fs::remove_file(format!("{}{}", database_url, suffix));
+        }
+    }
+
+    #[derive(QueryableByName)]
+    struct QueryPlanRow {
+        #[diesel(sql_type = diesel::sql_types::Text)]
+        detail: String,
+    }
+
+    /// EXPLAIN QUERY PLAN details for a statement
+    fn query_plan(conn: &mut SqliteConnection, sql: &str) -> Vec<String> {
+        diesel::sql_query(format!("EXPLAIN QUERY PLAN {}", sql))
+            .load::<QueryPlanRow>(conn)
+            .unwrap()
+            .into_iter()
+            .map(|row| row.detail)
+            .collect()
+    }
+
+    #[test]
+    fn test_hot_queries_use_indexes() {
+        let mut conn = establish_connection(":memory:").unwrap();
+        run_migrations(&mut conn).unwrap();
+
+        let hot_queries = [
+            // EPG: programs airing on a channel in a time window
+            "SELECT * FROM programs WHERE xmltv_channel_id = 1 \
+             AND start_time < '2026-01-02T00:00:00' AND end_time > '2026-01-01T00:00:00' \
+             ORDER BY start_time",
+            // Failover chain: streams for a channel by priority
+            "SELECT * FROM channel_mappings WHERE xmltv_channel_id = 1 \
+             ORDER BY stream_priority ASC, is_primary DESC",
+            // Xtream sync: stream lookup by account
+            "SELECT * FROM xtream_channels WHERE account_id = 1 AND stream_id = 100",
+            // Dashboard: recent errors
+            "SELECT * FROM event_log WHERE level = 'error' ORDER BY timestamp DESC LIMIT 10",
+        ];
+
+        for sql in hot_queries {
+            let plan = query_plan(&mut conn, sql);
+            assert!(
+                plan.iter().any(|detail| detail.starts_with("SEARCH")),
+                "Expected an index search for `{}`, got {:?}",
+                sql,
+                plan
+            );
+            assert!(
+                !plan
+                    .iter()
+                    .any(|detail| detail.starts_with("SCAN") || detail.contains("TEMP B-TREE")),
+                "Table scan or temp sort for `{}`: {:?}",
+                sql,
+                plan
+            );
+        }
+    }
+}
@@ -1,14 +1,23 @@
 pub mod connection;
 pub mod models;
+pub mod repair;
 pub mod schema;
+pub mod workspace;
 
 // Note: These exports are used by the lib crate (server module, tests), not the bin crate
 // Clippy's dead_code lint doesn't understand the lib/bin split
 #[allow(unused_imports)]
-pub use connection::{establish_connection, get_db_path, run_migrations, DbConnection, DbPool, DbPooledConnection};
+pub use connection::{
+    build_pool, build_read_pool, establish_connection, get_db_path, run_migrations, DbConnection, DbPool,
+    DbPooledConnection, SharedDbPool,
+};
+#[cfg(test)]
+pub use connection::{seed_account, test_connection};
 pub use models::{
-    Account, AccountStatusUpdate, ChannelMapping, EventCategory, EventLevel, EventLog,
-    NewAccount, NewChannelMapping, NewEventLog, NewProgram, NewXmltvChannel,
-    NewXmltvChannelSettings, NewXmltvSource, NewXtreamChannel, Program, Setting, XmltvChannel,
-    XmltvChannelSettings, XmltvSource, XmltvSourceUpdate, XtreamChannel, XtreamChannelUpdate,
+    Account, AccountQuota, AccountStatusUpdate, AccountUsage, AliasPack, ChannelEpgFeed, ChannelMapping, EventCategory, EventLevel, EventLog,
+    NewAccount, NewChannelMapping, NewEventLog, NewProgram, NewVodItem, NewXmltvChannel,
+    NewPendingChannelMapping, NewPendingChannelNumber, NewXmltvChannelSettings, NewXmltvSource, NewXtreamChannel,
+    PendingChannelMapping, PendingChannelNumber, Program, ProviderSpeedtest, ProviderType, Setting, VodEpisode,
+    VodItem, XmltvChannel, XmltvChannelSettings, XmltvSource, XmltvSourceUpdate, XtreamChannel, XtreamChannelUpdate,
+    SYNTHETIC_SOURCE_ID,
 };
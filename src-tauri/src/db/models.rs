@@ -2,8 +2,8 @@ use diesel::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use crate::db::schema::{
-    accounts, channel_mappings, event_log, programs, settings, xmltv_channel_settings, xmltv_channels,
-    xmltv_sources, xtream_channels,
+    account_quotas, account_usage, accounts, alias_packs, channel_epg_feeds, channel_mappings, event_log, match_rules, pending_channel_mappings, pending_channel_numbers, programs, provider_speedtests, settings, vod_episodes, vod_items,
+    xmltv_channel_settings, xmltv_channels, xmltv_sources, xtream_channels,
 };
 
 #[derive(Queryable, Selectable, Insertable, Debug, Clone)]
@@ -43,6 +43,51 @@ pub struct Account {
     pub active_connections: Option<i32>,
     pub last_check: Option<String>,
     pub connection_status: Option<String>,
+    /// "xtream" or "m3u" (see `ProviderType`)
+    pub provider_type: String,
+    /// Minutes between proactive upstream reconnects (None = never)
+    pub reconnect_interval_minutes: Option<i32>,
+    /// Why the stored password could not be read at the last check (None = OK)
+    pub credential_error: Option<String>,
+    /// User-Agent for requests to the server (None = default)
+    pub http_user_agent: Option<String>,
+    /// Extra request headers as a JSON object (see `xtream::request_headers`)
+    pub http_headers: Option<String>,
+}
+
+impl Account {
+    pub fn provider(&self) -> ProviderType {
+        if self.provider_type == ProviderType::M3u.to_string() {
+            ProviderType::M3u
+        } else {
+            ProviderType::Xtream
+        }
+    }
+
+    /// Whether channels come from an M3U playlist rather than the Xtream API
+    pub fn is_m3u(&self) -> bool {
+        self.provider() == ProviderType::M3u
+    }
+}
+
+/// How an account's channels are fetched
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProviderType {
+    /// Xtream Codes API with username and password
+    #[default]
+    Xtream,
+    /// M3U/M3U8 playlist downloaded from the account's server URL
+    M3u,
+}
+
+impl std::fmt::Display for ProviderType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProviderType::Xtream => write!(f, "xtream"),
+            ProviderType::M3u => write!(f, "m3u"),
+        }
+    }
 }
 
 /// Changeset for updating account status fields after connection test
@@ -67,6 +112,7 @@ pub struct NewAccount {
     pub password_encrypted: Vec<u8>,
     pub max_connections: i32,
     pub is_active: i32,
+    pub provider_type: String,
 }
 
 impl NewAccount {
@@ -83,8 +129,14 @@ impl NewAccount {
             password_encrypted,
             max_connections: 1,
             is_active: 1,
+            provider_type: ProviderType::Xtream.to_string(),
         }
     }
+
+    pub fn with_provider_type(mut self, provider_type: ProviderType) -> Self {
+        self.provider_type = provider_type.to_string();
+        self
+    }
 }
 
 /// Xtream channel model for querying existing channels
@@ -106,6 +158,8 @@ pub struct XtreamChannel {
     pub tv_archive_duration: Option<i32>,
     pub added_at: Option<String>,
     pub updated_at: Option<String>,
+    /// Upstream URL for M3U channels (Xtream URLs are built from credentials)
+    pub stream_url: Option<String>,
 }
 
 /// New xtream channel model for inserting records
@@ -123,6 +177,7 @@ pub struct NewXtreamChannel {
     pub epg_channel_id: Option<String>,
     pub tv_archive: i32,
     pub tv_archive_duration: i32,
+    pub stream_url: Option<String>,
 }
 
 /// Changeset for updating xtream channel fields
@@ -138,6 +193,7 @@ pub struct XtreamChannelUpdate {
     pub tv_archive: i32,
     pub tv_archive_duration: i32,
     pub updated_at: String,
+    pub stream_url: Option<String>,
 }
 
 // ============================================================================
@@ -159,6 +215,12 @@ pub struct XmltvSource {
     pub is_active: i32,
     pub created_at: String,
     pub updated_at: String,
+    /// HTTP basic auth user (the password is in `auth_password_encrypted`)
+    pub auth_username: Option<String>,
+    pub auth_password_encrypted: Option<Vec<u8>>,
+    /// Custom request header, e.g. an API key header
+    pub auth_header_name: Option<String>,
+    pub auth_header_value_encrypted: Option<Vec<u8>>,
 }
 
 /// New XMLTV source for insertion
@@ -230,6 +292,12 @@ pub struct XmltvChannel {
     pub is_synthetic: Option<i32>,
 }
 
+/// Source of the synthetic channels promoted from orphan Xtream streams
+///
+/// A fixed, inactive `xmltv_sources` row created by a migration and hidden
+/// from the source lists.
+pub const SYNTHETIC_SOURCE_ID: i32 = -1;
+
 /// New XMLTV channel for insertion
 #[derive(Insertable, Debug, Clone)]
 #[diesel(table_name = xmltv_channels)]
@@ -260,14 +328,15 @@ impl NewXmltvChannel {
     }
 
     /// Create a synthetic XMLTV channel (for orphan Xtream streams promoted to Plex)
+    ///
+    /// Synthetic channels belong to the `SYNTHETIC_SOURCE_ID` source.
     pub fn synthetic(
-        source_id: i32,
         channel_id: impl Into<String>,
         display_name: impl Into<String>,
         icon: Option<String>,
     ) -> Self {
         Self {
-            source_id,
+            source_id: SYNTHETIC_SOURCE_ID,
             channel_id: channel_id.into(),
             display_name: display_name.into(),
             icon,
@@ -295,6 +364,22 @@ pub struct Program {
     pub category: Option<String>,
     pub episode_info: Option<String>,
     pub created_at: String,
+    pub sub_title: Option<String>,
+    /// Program artwork URL
+    pub icon: Option<String>,
+    /// Episode number in the zero-based `xmltv_ns` system (e.g. "0.4.0/1")
+    pub episode_num_xmltv_ns: Option<String>,
+    /// Episode number as shown on screen (e.g. "S01E05")
+    pub episode_num_onscreen: Option<String>,
+    pub rating: Option<String>,
+    /// Rating system (e.g. "VCHIP", "MPAA")
+    pub rating_system: Option<String>,
+    /// Star rating as given (e.g. "7.5/10")
+    pub star_rating: Option<String>,
+    /// Cast and crew as a JSON array of `ProgramCredit`
+    pub credits: Option<String>,
+    /// Texts by language as a JSON array of `ProgramTranslation`
+    pub translations: Option<String>,
 }
 
 /// New program for insertion
@@ -309,6 +394,15 @@ pub struct NewProgram {
     pub end_time: String,
     pub category: Option<String>,
     pub episode_info: Option<String>,
+    pub sub_title: Option<String>,
+    pub icon: Option<String>,
+    pub episode_num_xmltv_ns: Option<String>,
+    pub episode_num_onscreen: Option<String>,
+    pub rating: Option<String>,
+    pub rating_system: Option<String>,
+    pub star_rating: Option<String>,
+    pub credits: Option<String>,
+    pub translations: Option<String>,
 }
 
 impl NewProgram {
@@ -326,6 +420,15 @@ impl NewProgram {
             end_time: end_time.into(),
             category: None,
             episode_info: None,
+            sub_title: None,
+            icon: None,
+            episode_num_xmltv_ns: None,
+            episode_num_onscreen: None,
+            rating: None,
+            rating_system: None,
+            star_rating: None,
+            credits: None,
+            translations: None,
         }
     }
 
@@ -432,6 +535,67 @@ impl NewChannelMapping {
     }
 }
 
+/// Imported channel mapping waiting for its channel and stream to be fetched
+///
+/// Identified by stable keys (source URL + channel_id, account URL + username
+/// + stream_id) rather than database IDs; see `resolve_imported_mappings`.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = pending_channel_mappings)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct PendingChannelMapping {
+    pub id: Option<i32>,
+    pub source_url: String,
+    pub channel_id: String,
+    pub account_url: String,
+    pub account_username: String,
+    pub stream_id: i32,
+    pub match_confidence: Option<f32>,
+    pub is_manual: i32,
+    pub is_primary: i32,
+    pub stream_priority: i32,
+    pub created_at: String,
+}
+
+/// New pending channel mapping for insertion
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = pending_channel_mappings)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct NewPendingChannelMapping {
+    pub source_url: String,
+    pub channel_id: String,
+    pub account_url: String,
+    pub account_username: String,
+    pub stream_id: i32,
+    pub match_confidence: Option<f32>,
+    pub is_manual: i32,
+    pub is_primary: i32,
+    pub stream_priority: i32,
+}
+
+/// Imported channel number waiting for its channel to be fetched
+///
+/// Identified by source URL + channel_id like `PendingChannelMapping`.
+#[derive(Queryable, Selectable, Debug, Clone)]
+#[diesel(table_name = pending_channel_numbers)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct PendingChannelNumber {
+    pub id: Option<i32>,
+    pub source_url: String,
+    pub channel_id: String,
+    pub channel_number: i32,
+    pub created_at: String,
+}
+
+/// New pending channel number for insertion
+#[derive(Insertable, Debug, Clone)]
+#[diesel(table_name = pending_channel_numbers)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct NewPendingChannelNumber {
+    pub source_url: String,
+    pub channel_id: String,
+    pub channel_number: i32,
+}
+
 // ============================================================================
 // XMLTV Channel Settings Models (Story 3-1)
 // ============================================================================
@@ -448,6 +612,14 @@ pub struct XmltvChannelSettings {
     pub plex_display_order: Option<i32>,
     pub created_at: String,
     pub updated_at: String,
+    /// Placeholder guide override: None follows the global setting, 1 always, 0 never
+    pub placeholder_mode: Option<i32>,
+    /// Placeholder program title template replacing the global one
+    pub placeholder_title: Option<String>,
+    /// Persistent channel number (None falls back to the lineup position)
+    pub channel_number: Option<i32>,
+    /// Highest quality to stream (None follows the global setting)
+    pub preferred_quality: Option<String>,
 }
 
 /// New XMLTV channel settings for insertion
@@ -605,3 +777,199 @@ impl std::fmt::Display for EventCategory {
         }
     }
 }
+
+// ============================================================================
+// Alias Pack Models
+// ============================================================================
+
+/// Imported community alias pack
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = alias_packs)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct AliasPack {
+    pub id: Option<i32>,
+    pub name: String,
+    pub source_url: String,
+    pub version: Option<String>,
+    pub alias_count: i32,
+    pub imported_at: String,
+    pub updated_at: String,
+}
+
+// ============================================================================
+// Match Rule Models
+// ============================================================================
+
+/// User-defined name rule applied while normalizing channel names for matching
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = match_rules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRule {
+    pub id: Option<i32>,
+    /// strip_prefix, strip_suffix, regex or alias
+    pub rule_type: String,
+    pub pattern: String,
+    pub replacement: String,
+    pub is_enabled: i32,
+    pub sort_order: i32,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+/// New or updated match rule
+#[derive(Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = match_rules)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+pub struct NewMatchRule {
+    pub rule_type: String,
+    pub pattern: String,
+    pub replacement: String,
+    pub is_enabled: i32,
+    pub sort_order: i32,
+}
+
+// ============================================================================
+// Channel EPG Feed Models
+// ============================================================================
+
+/// Per-channel XMLTV feed merged into a guide channel during refresh
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = channel_epg_feeds)]
+#[diesel(primary_key(tvg_id))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelEpgFeed {
+    /// XMLTV channel ID (tvg-id) of the guide channel the feed fills
+    pub tvg_id: String,
+    pub url: String,
+    /// Channel ID inside the feed; None when the feed carries one channel
+    pub feed_channel_id: Option<String>,
+    pub last_fetched_at: Option<String>,
+    pub program_count: i32,
+    pub last_error: Option<String>,
+    pub created_at: String,
+}
+
+// ============================================================================
+// Provider Speed Test Models
+// ============================================================================
+
+/// Stored provider speed test result
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = provider_speedtests)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct ProviderSpeedtest {
+    pub id: Option<i32>,
+    pub account_id: i32,
+    pub stream_id: Option<i32>,
+    pub tested_at: String,
+    #[serde(serialize_with = "serialize_bool")]
+    pub success: i32,
+    pub ttfb_ms: Option<i32>,
+    pub bytes_downloaded: i64,
+    pub download_ms: i32,
+    pub throughput_kbps: Option<i32>,
+    /// JSON array of advertised qualities the measured bandwidth cannot sustain
+    pub unsupported_qualities: Option<String>,
+    pub error: Option<String>,
+}
+
+// ============================================================================
+// Account Quota Models
+// ============================================================================
+
+/// Monthly usage limits of a provider account
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize)]
+#[diesel(table_name = account_quotas)]
+#[diesel(primary_key(account_id))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountQuota {
+    pub account_id: i32,
+    pub monthly_gb: Option<f64>,
+    pub monthly_hours: Option<f64>,
+    /// Refuse new streams on the account once a limit is reached
+    #[serde(serialize_with = "serialize_bool")]
+    pub block_when_exceeded: i32,
+}
+
+/// Stream usage of a provider account in one calendar month
+#[derive(Queryable, Selectable, Debug, Clone, Default, Serialize)]
+#[diesel(table_name = account_usage)]
+#[diesel(primary_key(account_id, month))]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct AccountUsage {
+    pub account_id: i32,
+    /// "YYYY-MM" in local time
+    pub month: String,
+    pub bytes: i64,
+    pub seconds: i64,
+    /// Highest quota threshold (80 or 100 percent) reported this month
+    pub alert_level: i32,
+}
+
+// ============================================================================
+// VOD Catalog Models
+// ============================================================================
+
+/// Movie or series in a provider's VOD catalog
+#[derive(Queryable, Selectable, Identifiable, Debug, Clone, Serialize)]
+#[diesel(table_name = vod_items)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct VodItem {
+    pub id: Option<i32>,
+    pub account_id: i32,
+    /// "movie" or "series"
+    pub kind: String,
+    /// Xtream stream_id (movies) or series_id (series)
+    pub provider_id: i32,
+    pub name: String,
+    pub icon: Option<String>,
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    pub container_extension: Option<String>,
+    pub plot: Option<String>,
+    pub rating: Option<f32>,
+    pub added_at: Option<String>,
+    pub updated_at: String,
+}
+
+/// New VOD catalog entry (also used to update existing entries on rescan)
+#[derive(Insertable, AsChangeset, Debug, Clone)]
+#[diesel(table_name = vod_items)]
+#[diesel(treat_none_as_null = true)]
+pub struct NewVodItem {
+    pub account_id: i32,
+    pub kind: String,
+    pub provider_id: i32,
+    pub name: String,
+    pub icon: Option<String>,
+    pub category_id: Option<i32>,
+    pub category_name: Option<String>,
+    pub container_extension: Option<String>,
+    pub plot: Option<String>,
+    pub rating: Option<f32>,
+    pub added_at: Option<String>,
+    pub updated_at: String,
+}
+
+/// Episode of a series in the VOD catalog
+#[derive(Queryable, Selectable, Insertable, Debug, Clone, Serialize)]
+#[diesel(table_name = vod_episodes)]
+#[diesel(check_for_backend(diesel::sqlite::Sqlite))]
+#[serde(rename_all = "camelCase")]
+pub struct VodEpisode {
+    pub id: Option<i32>,
+    pub vod_item_id: i32,
+    /// Xtream episode ID used in the playback URL
+    pub episode_id: i32,
+    pub season: Option<i32>,
+    pub episode_num: Option<i32>,
+    pub title: Option<String>,
+    pub container_extension: Option<String>,
+}
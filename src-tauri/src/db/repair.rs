@@ -0,0 +1,664 @@
+//! Startup schema check and repair
+//!
+//! `migrate_with_repair` replaces a plain `run_pending_migrations` at startup:
+//!
+//! 1. `PRAGMA quick_check`; on corruption the database is backed up,
+//!    reindexed, and tables that are still damaged are rebuilt from the rows
+//!    that can be read.
+//! 2. Migrations recorded in the database but unknown to this build (the
+//!    database was used by a newer version) are reported, not reverted.
+//! 3. Pending migrations run one at a time. When one fails because the schema
+//!    already partly contains it (an interrupted or half-applied migration),
+//!    the database is backed up and brought to the schema that migration
+//!    produces: missing tables and indexes are created and tables missing
+//!    columns are rebuilt with their rows copied over. The migration is then
+//!    recorded as applied and the remaining ones run normally.
+//!
+//! Anything repaired is written to the event log. Schema problems that can't
+//! be repaired fail startup with a message naming them.
+
+use std::collections::{HashMap, HashSet};
+use std::path::PathBuf;
+
+use diesel::connection::SimpleConnection;
+use diesel::migration::{Migration, MigrationSource};
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text};
+use diesel::sqlite::Sqlite;
+use diesel_migrations::MigrationHarness;
+use serde::Serialize;
+
+use super::connection::MIGRATIONS;
+
+/// Diesel's migration bookkeeping table
+const MIGRATIONS_TABLE: &str = "__diesel_schema_migrations";
+
+/// Rows copied per statement when salvaging a damaged table
+const SALVAGE_CHUNK_ROWS: i64 = 500;
+
+/// What was checked and repaired at startup
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// Backup written before the first repair
+    pub backup_path: Option<String>,
+    /// Problems reported by `PRAGMA quick_check` before repairing
+    pub integrity_errors: Vec<String>,
+    /// Tables rebuilt from their readable rows
+    pub salvaged_tables: Vec<String>,
+    /// Rows that could not be read while salvaging
+    pub rows_lost: i64,
+    /// Migrations applied by this build
+    pub applied_migrations: Vec<String>,
+    /// Migrations that failed and were completed by repairing the schema
+    pub repaired_migrations: Vec<String>,
+    /// Tables created because they were missing
+    pub created_tables: Vec<String>,
+    /// Tables rebuilt because they were missing columns
+    pub rebuilt_tables: Vec<String>,
+    /// Indexes and triggers created because they were missing
+    pub created_indexes: Vec<String>,
+    /// Recorded migrations this build doesn't know (database from a newer version)
+    pub unknown_migrations: Vec<String>,
+}
+
+impl RepairReport {
+    /// Whether anything was repaired
+    pub fn repaired(&self) -> bool {
+        !self.integrity_errors.is_empty() || !self.repaired_migrations.is_empty()
+    }
+}
+
+#[derive(QueryableByName)]
+struct CheckRow {
+    #[diesel(sql_type = Text)]
+    quick_check: String,
+}
+
+#[derive(QueryableByName)]
+struct FileRow {
+    #[diesel(sql_type = Text)]
+    file: String,
+}
+
+#[derive(QueryableByName)]
+struct ColumnRow {
+    #[diesel(sql_type = Text)]
+    name: String,
+}
+
+#[derive(QueryableByName)]
+struct RowidRange {
+    #[diesel(sql_type = Nullable<diesel::sql_types::BigInt>)]
+    low: Option<i64>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::BigInt>)]
+    high: Option<i64>,
+}
+
+#[derive(QueryableByName)]
+struct CountRow {
+    #[diesel(sql_type = Integer)]
+    count: i32,
+}
+
+/// A table, index or trigger from `sqlite_master`
+#[derive(Debug, Clone, QueryableByName)]
+struct SchemaObject {
+    #[diesel(sql_type = Text)]
+    kind: String,
+    #[diesel(sql_type = Text)]
+    name: String,
+    #[diesel(sql_type = Text)]
+    tbl_name: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    sql: Option<String>,
+}
+
+/// Tables, their columns, and indexes/triggers of a database
+#[derive(Debug, Default)]
+struct Schema {
+    objects: Vec<SchemaObject>,
+    columns: HashMap<String, Vec<String>>,
+}
+
+impl Schema {
+    fn has(&self, kind: &str, name: &str) -> bool {
+        self.objects
+            .iter()
+            .any(|object| object.kind == kind && object.name == name)
+    }
+}
+
+/// Quote an SQL identifier
+fn quote(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
+
+fn read_schema(conn: &mut SqliteConnection) -> QueryResult<Schema> {
+    let objects: Vec<SchemaObject> = diesel::sql_query(
+        "SELECT type AS kind, name, tbl_name, sql FROM sqlite_master
+         WHERE name NOT LIKE 'sqlite_%' AND name != '__diesel_schema_migrations'
+         ORDER BY CASE type WHEN 'table' THEN 0 ELSE 1 END, rowid",
+    )
+    .load(conn)?;
+
+    let mut columns = HashMap::new();
+    for object in objects.iter().filter(|object| object.kind == "table") {
+        let names: Vec<ColumnRow> = diesel::sql_query("SELECT name FROM pragma_table_info(?)")
+            .bind::<Text, _>(&object.name)
+            .load(conn)?;
+        columns.insert(
+            object.name.clone(),
+            names.into_iter().map(|row| row.name).collect(),
+        );
+    }
+    Ok(Schema { objects, columns })
+}
+
+type SqliteMigration = Box<dyn Migration<Sqlite>>;
+
+fn all_migrations() -> Result<Vec<SqliteMigration>, String> {
+    let mut migrations = MigrationSource::<Sqlite>::migrations(&MIGRATIONS)
+        .map_err(|e| format!("Failed to load migrations: {}", e))?;
+    migrations.sort_by(|a, b| a.name().version().cmp(&b.name().version()));
+    Ok(migrations)
+}
+
+/// Schema a fresh database has after running migrations up to `through`
+fn expected_schema(through: &str) -> Result<Schema, String> {
+    let mut conn = SqliteConnection::establish(":memory:")
+        .map_err(|e| format!("Failed to open scratch database: {}", e))?;
+    for migration in all_migrations()? {
+        let version = migration.name().version().to_string();
+        if version.as_str() > through {
+            break;
+        }
+        conn.run_migration(migration.as_ref()).map_err(|e| {
+            format!(
+                "Migration {} failed on a fresh database: {}",
+                migration.name(),
+                e
+            )
+        })?;
+    }
+    read_schema(&mut conn).map_err(|e| format!("Failed to read expected schema: {}", e))
+}
+
+/// Problems reported by `PRAGMA quick_check` (empty when the database is intact)
+fn quick_check(conn: &mut SqliteConnection) -> Vec<String> {
+    match diesel::sql_query("PRAGMA quick_check").load::<CheckRow>(conn) {
+        Ok(rows) => rows
+            .into_iter()
+            .map(|row| row.quick_check)
+            .filter(|line| line != "ok")
+            .collect(),
+        Err(e) => vec![e.to_string()],
+    }
+}
+
+/// Copy the database file next to itself before repairing
+///
+/// Returns None for in-memory databases.
+fn backup(conn: &mut SqliteConnection) -> Result<Option<PathBuf>, String> {
+    let file = diesel::sql_query("SELECT file FROM pragma_database_list WHERE name = 'main'")
+        .get_result::<FileRow>(conn)
+        .map(|row| row.file)
+        .unwrap_or_default();
+    if file.is_empty() {
+        return Ok(None);
+    }
+
+    let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S");
+    let path = PathBuf::from(format!("{}.pre-repair-{}.bak", file, stamp));
+    // VACUUM INTO writes a consistent copy; a damaged database may refuse it
+    let vacuumed = diesel::sql_query("VACUUM INTO ?")
+        .bind::<Text, _>(path.to_string_lossy().to_string())
+        .execute(conn);
+    if vacuumed.is_err() {
+        std::fs::copy(&file, &path)
+            .map_err(|e| format!("Failed to back up the database before repairing: {}", e))?;
+    }
+    Ok(Some(path))
+}
+
+fn ensure_backup(conn: &mut SqliteConnection, report: &mut RepairReport) -> Result<(), String> {
+    if report.backup_path.is_none() {
+        if let Some(path) = backup(conn)? {
+            report.backup_path = Some(path.to_string_lossy().to_string());
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite `CREATE TABLE <name> ...` to create `new_name` instead
+fn rename_create_table(sql: &str, new_name: &str) -> Option<String> {
+    let rest = sql.trim_start();
+    let prefix = "CREATE TABLE";
+    if !rest.get(..prefix.len())?.eq_ignore_ascii_case(prefix) {
+        return None;
+    }
+    let rest = rest[prefix.len()..].trim_start();
+    let after_name = match rest.chars().next()? {
+        '"' | '`' | '[' => {
+            let close = match rest.as_bytes()[0] {
+                b'[' => ']',
+                b'`' => '`',
+                _ => '"',
+            };
+            let end = rest[1..].find(close)? + 2;
+            &rest[end..]
+        }
+        _ => {
+            let end = rest.find(|c: char| c.is_whitespace() || c == '(')?;
+            &rest[end..]
+        }
+    };
+    Some(format!("CREATE TABLE {}{}", quote(new_name), after_name))
+}
+
+/// Recreate `table` from `create_sql`, copying `columns` from the old table
+///
+/// Indexes and triggers of the table are dropped with it; callers recreate
+/// them afterwards.
+fn rebuild_table(
+    conn: &mut SqliteConnection,
+    table: &str,
+    create_sql: &str,
+    columns: &[String],
+) -> Result<(), String> {
+    let temp = format!("{}__repair", table);
+    let create = rename_create_table(create_sql, &temp)
+        .ok_or_else(|| format!("Cannot parse the definition of table {}", table))?;
+    let column_list = columns
+        .iter()
+        .map(|c| quote(c))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    conn.batch_execute("PRAGMA foreign_keys = OFF")
+        .map_err(|e| e.to_string())?;
+    let result = conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        conn.batch_execute(&format!("DROP TABLE IF EXISTS {}", quote(&temp)))?;
+        conn.batch_execute(&create)?;
+        if !columns.is_empty() {
+            conn.batch_execute(&format!(
+                "INSERT INTO {} ({cols}) SELECT {cols} FROM {}",
+                quote(&temp),
+                quote(table),
+                cols = column_list
+            ))?;
+        }
+        conn.batch_execute(&format!("DROP TABLE {}", quote(table)))?;
+        conn.batch_execute(&format!(
+            "ALTER TABLE {} RENAME TO {}",
+            quote(&temp),
+            quote(table)
+        ))?;
+        Ok(())
+    });
+    let _ = conn.batch_execute("PRAGMA foreign_keys = ON");
+    result.map_err(|e| format!("Failed to rebuild table {}: {}", table, e))
+}
+
+/// Bring the database to `expected`: create missing tables, indexes and
+/// triggers, and rebuild tables that lack columns
+fn repair_drift(
+    conn: &mut SqliteConnection,
+    expected: &Schema,
+    report: &mut RepairReport,
+) -> Result<(), String> {
+    let actual = read_schema(conn).map_err(|e| format!("Failed to read schema: {}", e))?;
+    let mut rebuilt = HashSet::new();
+
+    for table in expected
+        .objects
+        .iter()
+        .filter(|object| object.kind == "table")
+    {
+        let Some(sql) = &table.sql else { continue };
+        let wanted = &expected.columns[&table.name];
+        match actual.columns.get(&table.name) {
+            None => {
+                conn.batch_execute(sql)
+                    .map_err(|e| format!("Failed to create table {}: {}", table.name, e))?;
+                report.created_tables.push(table.name.clone());
+                rebuilt.insert(table.name.clone());
+            }
+            Some(present) if wanted.iter().any(|column| !present.contains(column)) => {
+                // Keep the data of every column both versions have
+                let shared: Vec<String> = wanted
+                    .iter()
+                    .filter(|column| present.contains(column))
+                    .cloned()
+                    .collect();
+                rebuild_table(conn, &table.name, sql, &shared)?;
+                report.rebuilt_tables.push(table.name.clone());
+                rebuilt.insert(table.name.clone());
+            }
+            Some(_) => {}
+        }
+    }
+
+    for object in expected
+        .objects
+        .iter()
+        .filter(|object| object.kind == "index" || object.kind == "trigger")
+    {
+        let Some(sql) = &object.sql else { continue };
+        if rebuilt.contains(&object.tbl_name) || !actual.has(&object.kind, &object.name) {
+            conn.batch_execute(sql)
+                .map_err(|e| format!("Failed to create {} {}: {}", object.kind, object.name, e))?;
+            if !actual.has(&object.kind, &object.name) {
+                report.created_indexes.push(object.name.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Copy the readable rows of a damaged table into a fresh copy of it
+///
+/// Returns the number of rows that could not be read.
+fn salvage_table(conn: &mut SqliteConnection, table: &SchemaObject) -> Result<i64, String> {
+    let Some(sql) = &table.sql else { return Ok(0) };
+    let temp = format!("{}__salvage", table.name);
+    let create = rename_create_table(sql, &temp)
+        .ok_or_else(|| format!("Cannot parse the definition of table {}", table.name))?;
+    let range = diesel::sql_query(format!(
+        "SELECT min(rowid) AS low, max(rowid) AS high FROM {}",
+        quote(&table.name)
+    ))
+    .get_result::<RowidRange>(conn)
+    .map_err(|e| format!("Table {} is unreadable: {}", table.name, e))?;
+
+    conn.batch_execute(&format!("DROP TABLE IF EXISTS {}", quote(&temp)))
+        .and_then(|_| conn.batch_execute(&create))
+        .map_err(|e| format!("Failed to create salvage table for {}: {}", table.name, e))?;
+
+    let mut lost = 0;
+    if let (Some(low), Some(high)) = (range.low, range.high) {
+        let copy = |conn: &mut SqliteConnection, from: i64, to: i64| {
+            conn.batch_execute(&format!(
+                "INSERT OR IGNORE INTO {} SELECT * FROM {} WHERE rowid BETWEEN {} AND {}",
+                quote(&temp),
+                quote(&table.name),
+                from,
+                to
+            ))
+        };
+        let mut start = low;
+        while start <= high {
+            let end = start.saturating_add(SALVAGE_CHUNK_ROWS - 1).min(high);
+            if copy(conn, start, end).is_err() {
+                // Retry row by row so one bad page loses as little as possible
+                for rowid in start..=end {
+                    if copy(conn, rowid, rowid).is_err() {
+                        lost += 1;
+                    }
+                }
+            }
+            start = end + 1;
+        }
+    }
+
+    conn.batch_execute("PRAGMA foreign_keys = OFF")
+        .map_err(|e| e.to_string())?;
+    let swapped = conn.batch_execute(&format!(
+        "DROP TABLE {}; ALTER TABLE {} RENAME TO {};",
+        quote(&table.name),
+        quote(&temp),
+        quote(&table.name)
+    ));
+    let _ = conn.batch_execute("PRAGMA foreign_keys = ON");
+    swapped.map_err(|e| format!("Failed to replace damaged table {}: {}", table.name, e))?;
+    Ok(lost)
+}
+
+/// Reindex, then salvage tables that are still damaged
+fn repair_corruption(conn: &mut SqliteConnection, report: &mut RepairReport) -> Result<(), String> {
+    if conn.batch_execute("REINDEX").is_ok() && quick_check(conn).is_empty() {
+        return Ok(());
+    }
+
+    let schema = read_schema(conn).map_err(|e| format!("Failed to read schema: {}", e))?;
+    for table in schema
+        .objects
+        .iter()
+        .filter(|object| object.kind == "table")
+    {
+        let readable = diesel::sql_query(format!(
+            "SELECT count(*) AS count FROM {}",
+            quote(&table.name)
+        ))
+        .get_result::<CountRow>(conn)
+        .map(|row| row.count)
+        .is_ok();
+        // Damaged tables usually fail a full scan; salvage every table otherwise
+        if readable
+            && !quick_check(conn)
+                .iter()
+                .any(|line| line.contains(&table.name))
+        {
+            continue;
+        }
+        report.rows_lost += salvage_table(conn, table)?;
+        report.salvaged_tables.push(table.name.clone());
+    }
+
+    // Salvaged tables lost their indexes and triggers
+    for object in schema.objects.iter().filter(|object| {
+        (object.kind == "index" || object.kind == "trigger")
+            && report.salvaged_tables.contains(&object.tbl_name)
+    }) {
+        if let Some(sql) = &object.sql {
+            let _ = conn.batch_execute(sql);
+        }
+    }
+
+    let remaining = quick_check(conn);
+    if !remaining.is_empty() {
+        return Err(format!(
+            "The database is damaged and could not be fully repaired ({}). A backup was kept{}.",
+            remaining.join("; "),
+            report
+                .backup_path
+                .as_ref()
+                .map(|path| format!(" at {}", path))
+                .unwrap_or_default()
+        ));
+    }
+    Ok(())
+}
+
+/// Check integrity, run pending migrations and repair schema drift
+pub fn migrate_with_repair(conn: &mut SqliteConnection) -> Result<RepairReport, String> {
+    let mut report = RepairReport {
+        integrity_errors: quick_check(conn),
+        ..Default::default()
+    };
+    if !report.integrity_errors.is_empty() {
+        ensure_backup(conn, &mut report)?;
+        repair_corruption(conn, &mut report)?;
+    }
+
+    let migrations = all_migrations()?;
+    let known: HashSet<String> = migrations
+        .iter()
+        .map(|migration| migration.name().version().to_string())
+        .collect();
+    let applied: HashSet<String> = conn
+        .applied_migrations()
+        .map_err(|e| format!("Failed to read applied migrations: {}", e))?
+        .into_iter()
+        .map(|version| version.to_string())
+        .collect();
+    report.unknown_migrations = applied
+        .iter()
+        .filter(|version| !known.contains(*version))
+        .cloned()
+        .collect();
+    report.unknown_migrations.sort();
+
+    for migration in migrations {
+        let version = migration.name().version().to_string();
+        if applied.contains(&version) {
+            continue;
+        }
+        let name = migration.name().to_string();
+        match conn.run_migration(migration.as_ref()) {
+            Ok(_) => report.applied_migrations.push(name),
+            Err(error) => {
+                tracing::warn!("Migration {} failed, repairing schema: {}", name, error);
+                ensure_backup(conn, &mut report)?;
+                let expected = expected_schema(&version)?;
+                repair_drift(conn, &expected, &mut report).map_err(|e| {
+                    format!(
+                        "Migration {} failed ({}) and could not be repaired: {}",
+                        name, error, e
+                    )
+                })?;
+                diesel::sql_query(format!(
+                    "INSERT OR IGNORE INTO {} (version) VALUES (?)",
+                    MIGRATIONS_TABLE
+                ))
+                .bind::<Text, _>(&version)
+                .execute(conn)
+                .map_err(|e| format!("Failed to record migration {}: {}", name, e))?;
+                report
+                    .repaired_migrations
+                    .push(format!("{} ({})", name, error));
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Write the repairs and version warnings to the event log
+pub fn log_report(conn: &mut SqliteConnection, report: &RepairReport) {
+    let details = serde_json::to_string(report).ok();
+    if report.repaired() {
+        let mut parts = Vec::new();
+        if !report.salvaged_tables.is_empty() {
+            parts.push(format!(
+                "rebuilt damaged tables {} ({} unreadable rows dropped)",
+                report.salvaged_tables.join(", "),
+                report.rows_lost
+            ));
+        } else if !report.integrity_errors.is_empty() {
+            parts.push("rebuilt damaged indexes".to_string());
+        }
+        if !report.repaired_migrations.is_empty() {
+            parts.push(format!(
+                "completed {} interrupted migration(s)",
+                report.repaired_migrations.len()
+            ));
+        }
+        if !report.created_tables.is_empty() {
+            parts.push(format!("created {}", report.created_tables.join(", ")));
+        }
+        if !report.rebuilt_tables.is_empty() {
+            parts.push(format!(
+                "added missing columns to {}",
+                report.rebuilt_tables.join(", ")
+            ));
+        }
+        let backup = report
+            .backup_path
+            .as_ref()
+            .map(|path| format!(". Backup saved to {}", path))
+            .unwrap_or_default();
+        let _ = crate::commands::logs::log_event_internal(
+            conn,
+            "warn",
+            "system",
+            &format!("Database repaired: {}{}", parts.join("; "), backup),
+            details.as_deref(),
+        );
+    }
+    if !report.unknown_migrations.is_empty() {
+        let _ = crate::commands::logs::log_event_internal(
+            conn,
+            "warn",
+            "system",
+            "Database was last used by a newer version of StreamForge; some data may not be shown until you update",
+            details.as_deref(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rename_create_table() {
+        assert_eq!(
+            rename_create_table("CREATE TABLE stream_stats (id INTEGER)", "tmp").unwrap(),
+            "CREATE TABLE \"tmp\" (id INTEGER)"
+        );
+        assert_eq!(
+            rename_create_table("CREATE TABLE \"my table\"(id INTEGER)", "tmp").unwrap(),
+            "CREATE TABLE \"tmp\"(id INTEGER)"
+        );
+        assert!(rename_create_table("CREATE INDEX idx ON t(a)", "tmp").is_none());
+    }
+
+    #[test]
+    fn test_half_applied_migration_is_repaired() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://example.com");
+        conn.batch_execute(
+            "INSERT INTO xtream_channels (account_id, stream_id, name) VALUES (1, 100, 'News');
+             -- The M3U migration added accounts.provider_type but not
+             -- xtream_channels.stream_url, and was never recorded
+             ALTER TABLE xtream_channels DROP COLUMN stream_url;
+             DELETE FROM __diesel_schema_migrations WHERE version >= '20260130000000';",
+        )
+        .unwrap();
+        assert!(crate::db::run_migrations(&mut conn).is_err());
+
+        let report = migrate_with_repair(&mut conn).unwrap();
+        assert!(report.repaired());
+        assert_eq!(report.repaired_migrations.len(), 1);
+        assert_eq!(report.rebuilt_tables, vec!["xtream_channels".to_string()]);
+        assert_eq!(report.applied_migrations.len(), 4);
+        assert!(report.unknown_migrations.is_empty());
+
+        // Rows survived and the schema matches a fresh database
+        let names: Vec<ColumnRow> = diesel::sql_query("SELECT name FROM xtream_channels")
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(names.len(), 1);
+        assert!(crate::db::run_migrations(&mut conn).is_ok());
+        let actual = read_schema(&mut conn).unwrap();
+        let expected = expected_schema("99999999999999").unwrap();
+        assert_eq!(actual.columns, expected.columns);
+        for object in &expected.objects {
+            assert!(
+                actual.has(&object.kind, &object.name),
+                "{} missing",
+                object.name
+            );
+        }
+
+        log_report(&mut conn, &report);
+    }
+
+    #[test]
+    fn test_newer_database_is_reported() {
+        let mut conn = crate::db::test_connection();
+        conn.batch_execute(
+            "INSERT INTO __diesel_schema_migrations (version) VALUES ('20991231000000')",
+        )
+        .unwrap();
+
+        let report = migrate_with_repair(&mut conn).unwrap();
+        assert!(!report.repaired());
+        assert_eq!(
+            report.unknown_migrations,
+            vec!["20991231000000".to_string()]
+        );
+        assert!(report.applied_migrations.is_empty());
+    }
+}
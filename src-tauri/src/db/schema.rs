@@ -1,5 +1,24 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    account_quotas (account_id) {
+        account_id -> Integer,
+        monthly_gb -> Nullable<Double>,
+        monthly_hours -> Nullable<Double>,
+        block_when_exceeded -> Integer,
+    }
+}
+
+diesel::table! {
+    account_usage (account_id, month) {
+        account_id -> Integer,
+        month -> Text,
+        bytes -> BigInt,
+        seconds -> BigInt,
+        alert_level -> Integer,
+    }
+}
+
 diesel::table! {
     accounts (id) {
         id -> Nullable<Integer>,
@@ -16,6 +35,44 @@ diesel::table! {
         active_connections -> Nullable<Integer>,
         last_check -> Nullable<Text>,
         connection_status -> Nullable<Text>,
+        provider_type -> Text,
+        reconnect_interval_minutes -> Nullable<Integer>,
+        credential_error -> Nullable<Text>,
+        http_user_agent -> Nullable<Text>,
+        http_headers -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    alias_packs (id) {
+        id -> Nullable<Integer>,
+        name -> Text,
+        source_url -> Text,
+        version -> Nullable<Text>,
+        alias_count -> Integer,
+        imported_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    channel_aliases (id) {
+        id -> Nullable<Integer>,
+        pack_id -> Integer,
+        alias -> Text,
+        tvg_id -> Text,
+    }
+}
+
+diesel::table! {
+    channel_epg_feeds (tvg_id) {
+        tvg_id -> Text,
+        url -> Text,
+        feed_channel_id -> Nullable<Text>,
+        last_fetched_at -> Nullable<Text>,
+        program_count -> Integer,
+        last_error -> Nullable<Text>,
+        created_at -> Text,
     }
 }
 
@@ -32,6 +89,34 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    channel_policy_actions (xmltv_channel_id) {
+        xmltv_channel_id -> Integer,
+        action -> Text,
+        applied_at -> Text,
+    }
+}
+
+diesel::table! {
+    device_profile_channels (profile_id, xmltv_channel_id) {
+        profile_id -> Integer,
+        xmltv_channel_id -> Integer,
+    }
+}
+
+diesel::table! {
+    device_profiles (id) {
+        id -> Nullable<Integer>,
+        slug -> Text,
+        name -> Text,
+        device_id -> Text,
+        tuner_count -> Nullable<Integer>,
+        created_at -> Text,
+        updated_at -> Text,
+        stream_format -> Text,
+    }
+}
+
 diesel::table! {
     event_log (id) {
         id -> Nullable<Integer>,
@@ -44,6 +129,25 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    failover_history (id) {
+        id -> Nullable<Integer>,
+        xmltv_channel_id -> Integer,
+        from_xtream_channel_id -> Integer,
+        to_xtream_channel_id -> Integer,
+        occurred_at -> Text,
+    }
+}
+
+diesel::table! {
+    lineup_published (xmltv_channel_id) {
+        xmltv_channel_id -> Integer,
+        is_enabled -> Integer,
+        plex_display_order -> Nullable<Integer>,
+        channel_number -> Nullable<Integer>,
+    }
+}
+
 diesel::table! {
     programs (id) {
         id -> Nullable<Integer>,
@@ -55,6 +159,87 @@ diesel::table! {
         category -> Nullable<Text>,
         episode_info -> Nullable<Text>,
         created_at -> Text,
+        sub_title -> Nullable<Text>,
+        icon -> Nullable<Text>,
+        episode_num_xmltv_ns -> Nullable<Text>,
+        episode_num_onscreen -> Nullable<Text>,
+        rating -> Nullable<Text>,
+        rating_system -> Nullable<Text>,
+        star_rating -> Nullable<Text>,
+        credits -> Nullable<Text>,
+        translations -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    match_rules (id) {
+        id -> Nullable<Integer>,
+        rule_type -> Text,
+        pattern -> Text,
+        replacement -> Text,
+        is_enabled -> Integer,
+        sort_order -> Integer,
+        created_at -> Text,
+        updated_at -> Text,
+    }
+}
+
+diesel::table! {
+    pending_channel_mappings (id) {
+        id -> Nullable<Integer>,
+        source_url -> Text,
+        channel_id -> Text,
+        account_url -> Text,
+        account_username -> Text,
+        stream_id -> Integer,
+        match_confidence -> Nullable<Float>,
+        is_manual -> Integer,
+        is_primary -> Integer,
+        stream_priority -> Integer,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    pending_channel_numbers (id) {
+        id -> Nullable<Integer>,
+        source_url -> Text,
+        channel_id -> Text,
+        channel_number -> Integer,
+        created_at -> Text,
+    }
+}
+
+diesel::table! {
+    program_history (id) {
+        id -> Nullable<Integer>,
+        source_id -> Integer,
+        channel_id -> Text,
+        channel_name -> Text,
+        title -> Text,
+        sub_title -> Nullable<Text>,
+        description -> Nullable<Text>,
+        start_time -> Text,
+        end_time -> Text,
+        category -> Nullable<Text>,
+        episode_info -> Nullable<Text>,
+        archived_at -> Text,
+    }
+}
+
+diesel::table! {
+    provider_speedtests (id) {
+        id -> Nullable<Integer>,
+        account_id -> Integer,
+        stream_id -> Nullable<Integer>,
+        tested_at -> Text,
+        success -> Integer,
+        ttfb_ms -> Nullable<Integer>,
+        bytes_downloaded -> BigInt,
+        download_ms -> Integer,
+        throughput_kbps -> Nullable<Integer>,
+        unsupported_qualities -> Nullable<Text>,
+        error -> Nullable<Text>,
     }
 }
 
@@ -65,6 +250,57 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    stream_health (xtream_channel_id) {
+        xtream_channel_id -> Integer,
+        last_success_at -> Nullable<Text>,
+        last_failure_at -> Nullable<Text>,
+        failing_since -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    stream_stats (id) {
+        id -> Nullable<Integer>,
+        xmltv_channel_id -> Integer,
+        account_id -> Nullable<Integer>,
+        day -> Text,
+        started_at -> Text,
+        duration_seconds -> BigInt,
+        bytes -> BigInt,
+    }
+}
+
+diesel::table! {
+    vod_episodes (id) {
+        id -> Nullable<Integer>,
+        vod_item_id -> Integer,
+        episode_id -> Integer,
+        season -> Nullable<Integer>,
+        episode_num -> Nullable<Integer>,
+        title -> Nullable<Text>,
+        container_extension -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    vod_items (id) {
+        id -> Nullable<Integer>,
+        account_id -> Integer,
+        kind -> Text,
+        provider_id -> Integer,
+        name -> Text,
+        icon -> Nullable<Text>,
+        category_id -> Nullable<Integer>,
+        category_name -> Nullable<Text>,
+        container_extension -> Nullable<Text>,
+        plot -> Nullable<Text>,
+        rating -> Nullable<Float>,
+        added_at -> Nullable<Text>,
+        updated_at -> Text,
+    }
+}
+
 diesel::table! {
     xmltv_channel_settings (id) {
         id -> Nullable<Integer>,
@@ -73,6 +309,10 @@ diesel::table! {
         plex_display_order -> Nullable<Integer>,
         created_at -> Text,
         updated_at -> Text,
+        placeholder_mode -> Nullable<Integer>,
+        placeholder_title -> Nullable<Text>,
+        channel_number -> Nullable<Integer>,
+        preferred_quality -> Nullable<Text>,
     }
 }
 
@@ -100,6 +340,10 @@ diesel::table! {
         is_active -> Integer,
         created_at -> Text,
         updated_at -> Text,
+        auth_username -> Nullable<Text>,
+        auth_password_encrypted -> Nullable<Binary>,
+        auth_header_name -> Nullable<Text>,
+        auth_header_value_encrypted -> Nullable<Binary>,
     }
 }
 
@@ -118,22 +362,56 @@ diesel::table! {
         tv_archive_duration -> Nullable<Integer>,
         added_at -> Nullable<Text>,
         updated_at -> Nullable<Text>,
+        stream_url -> Nullable<Text>,
     }
 }
 
+diesel::joinable!(account_quotas -> accounts (account_id));
+diesel::joinable!(account_usage -> accounts (account_id));
+diesel::joinable!(channel_aliases -> alias_packs (pack_id));
 diesel::joinable!(channel_mappings -> xmltv_channels (xmltv_channel_id));
 diesel::joinable!(channel_mappings -> xtream_channels (xtream_channel_id));
+diesel::joinable!(channel_policy_actions -> xmltv_channels (xmltv_channel_id));
+diesel::joinable!(device_profile_channels -> device_profiles (profile_id));
+diesel::joinable!(device_profile_channels -> xmltv_channels (xmltv_channel_id));
+diesel::joinable!(failover_history -> xmltv_channels (xmltv_channel_id));
+diesel::joinable!(lineup_published -> xmltv_channels (xmltv_channel_id));
+diesel::joinable!(program_history -> xmltv_sources (source_id));
 diesel::joinable!(programs -> xmltv_channels (xmltv_channel_id));
+diesel::joinable!(provider_speedtests -> accounts (account_id));
+diesel::joinable!(stream_health -> xtream_channels (xtream_channel_id));
+diesel::joinable!(stream_stats -> accounts (account_id));
+diesel::joinable!(vod_episodes -> vod_items (vod_item_id));
+diesel::joinable!(vod_items -> accounts (account_id));
 diesel::joinable!(xmltv_channel_settings -> xmltv_channels (xmltv_channel_id));
 diesel::joinable!(xmltv_channels -> xmltv_sources (source_id));
 diesel::joinable!(xtream_channels -> accounts (account_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
+    account_quotas,
+    account_usage,
     accounts,
+    alias_packs,
+    channel_aliases,
+    channel_epg_feeds,
     channel_mappings,
+    channel_policy_actions,
+    device_profile_channels,
+    device_profiles,
     event_log,
+    failover_history,
+    lineup_published,
+    match_rules,
+    pending_channel_mappings,
+    pending_channel_numbers,
+    program_history,
     programs,
+    provider_speedtests,
     settings,
+    stream_health,
+    stream_stats,
+    vod_episodes,
+    vod_items,
     xmltv_channel_settings,
     xmltv_channels,
     xmltv_sources,
@@ -0,0 +1,209 @@
+//! Database workspaces
+//!
+//! A workspace is a named SQLite database. The default workspace is the
+//! original `iptv.db` in the app data directory; additional workspaces can live
+//! in `workspaces/` or at a user-chosen location. The registry of workspaces and
+//! the active one are kept in `workspaces.json` next to the default database,
+//! since they must be known before any database is opened.
+
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use super::connection::{build_pool, build_read_pool, establish_connection, DbPool};
+
+/// Name of the built-in workspace backed by `iptv.db`
+pub const DEFAULT_WORKSPACE: &str = "default";
+
+/// File name of the default workspace database
+pub const DEFAULT_DB_FILENAME: &str = "iptv.db";
+
+/// Registry file in the app data directory
+const REGISTRY_FILENAME: &str = "workspaces.json";
+
+/// Subdirectory for workspaces created without an explicit location
+const WORKSPACES_DIR: &str = "workspaces";
+
+/// Maximum workspace name length
+const MAX_WORKSPACE_NAME_LEN: usize = 64;
+
+/// A registered (non-default) workspace
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkspaceEntry {
+    pub name: String,
+    pub path: PathBuf,
+}
+
+/// Persisted workspace registry
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkspaceRegistry {
+    /// Active workspace name (None means the default workspace)
+    #[serde(default)]
+    pub active: Option<String>,
+    #[serde(default)]
+    pub workspaces: Vec<WorkspaceEntry>,
+}
+
+impl WorkspaceRegistry {
+    /// Load the registry, returning an empty one if it does not exist or is unreadable
+    pub fn load(app_data_dir: &Path) -> Self {
+        std::fs::read_to_string(app_data_dir.join(REGISTRY_FILENAME))
+            .ok()
+            .and_then(|content| serde_json::from_str(&content).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the registry
+    pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
+        let content = serde_json::to_string_pretty(self)
+            .map_err(|e| format!("Failed to serialize workspace registry: {}", e))?;
+        std::fs::write(app_data_dir.join(REGISTRY_FILENAME), content)
+            .map_err(|e| format!("Failed to save workspace registry: {}", e))
+    }
+
+    /// Name of the active workspace
+    pub fn active_name(&self) -> &str {
+        self.active.as_deref().unwrap_or(DEFAULT_WORKSPACE)
+    }
+
+    /// Resolve the database path of a workspace by name
+    pub fn path_of(&self, app_data_dir: &Path, name: &str) -> Option<PathBuf> {
+        if name == DEFAULT_WORKSPACE {
+            return Some(app_data_dir.join(DEFAULT_DB_FILENAME));
+        }
+        self.workspaces
+            .iter()
+            .find(|w| w.name == name)
+            .map(|w| w.path.clone())
+    }
+
+    /// Database path of the active workspace (falls back to the default workspace)
+    pub fn active_path(&self, app_data_dir: &Path) -> PathBuf {
+        self.path_of(app_data_dir, self.active_name())
+            .unwrap_or_else(|| app_data_dir.join(DEFAULT_DB_FILENAME))
+    }
+}
+
+/// Validate a workspace name
+///
+/// Names are 1-64 characters of letters, digits, spaces, `-` and `_`.
+pub fn validate_workspace_name(name: &str) -> Result<(), String> {
+    let trimmed = name.trim();
+    if trimmed.is_empty() {
+        return Err("Workspace name cannot be empty".to_string());
+    }
+    if trimmed.len() > MAX_WORKSPACE_NAME_LEN {
+        return Err(format!(
+            "Workspace name must be at most {} characters",
+            MAX_WORKSPACE_NAME_LEN
+        ));
+    }
+    if !trimmed
+        .chars()
+        .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == ' ')
+    {
+        return Err("Workspace name may only contain letters, digits, spaces, '-' and '_'".to_string());
+    }
+    Ok(())
+}
+
+/// Resolve where a new workspace database should be created
+///
+/// - No location: `{app_data_dir}/workspaces/{name}.db`
+/// - Existing directory: `{location}/{name}.db`
+/// - Otherwise: the location is used as the database file path
+pub fn resolve_new_workspace_path(
+    app_data_dir: &Path,
+    name: &str,
+    location: Option<&str>,
+) -> Result<PathBuf, String> {
+    let file_name = format!("{}.db", name.trim().replace(' ', "_"));
+
+    let path = match location.map(str::trim).filter(|l| !l.is_empty()) {
+        None => app_data_dir.join(WORKSPACES_DIR).join(file_name),
+        Some(location) => {
+            let location = PathBuf::from(location);
+            if !location.is_absolute() {
+                return Err("Workspace location must be an absolute path".to_string());
+            }
+            if location.is_dir() {
+                location.join(file_name)
+            } else {
+                location
+            }
+        }
+    };
+
+    Ok(path)
+}
+
+/// Open (creating if needed) a workspace database and run migrations
+///
+/// Returns ready-to-use read-write and read-only pools. Nothing is torn down
+/// if this fails, so it is safe to call before switching away from the
+/// current workspace.
+pub fn open_workspace_pool(path: &Path) -> Result<(DbPool, DbPool), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|e| {
+            format!(
+                "Cannot create workspace directory '{}': {}",
+                parent.display(),
+                e
+            )
+        })?;
+    }
+
+    let database_url = path.to_string_lossy().to_string();
+    let mut conn = establish_connection(&database_url)
+        .map_err(|e| format!("Failed to open workspace database: {}", e))?;
+    let repair = super::repair::migrate_with_repair(&mut conn)
+        .map_err(|e| format!("Failed to run migrations on workspace database: {}", e))?;
+    super::repair::log_report(&mut conn, &repair);
+
+    // The read-write pool first, so the database is in WAL mode for readers
+    let pool = build_pool(database_url.clone())?;
+    Ok((pool, build_read_pool(&database_url)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_workspace_name() {
+        assert!(validate_workspace_name("testing").is_ok());
+        assert!(validate_workspace_name("New provider_2").is_ok());
+        assert!(validate_workspace_name("").is_err());
+        assert!(validate_workspace_name("   ").is_err());
+        assert!(validate_workspace_name("../escape").is_err());
+        assert!(validate_workspace_name(&"a".repeat(65)).is_err());
+    }
+
+    #[test]
+    fn test_registry_paths() {
+        let app_dir = PathBuf::from("/data/streamforge");
+        let mut registry = WorkspaceRegistry::default();
+        assert_eq!(registry.active_name(), DEFAULT_WORKSPACE);
+        assert_eq!(registry.active_path(&app_dir), app_dir.join(DEFAULT_DB_FILENAME));
+
+        registry.workspaces.push(WorkspaceEntry {
+            name: "staging".to_string(),
+            path: PathBuf::from("/tmp/staging.db"),
+        });
+        registry.active = Some("staging".to_string());
+        assert_eq!(registry.active_path(&app_dir), PathBuf::from("/tmp/staging.db"));
+
+        // Unknown active workspace falls back to the default database
+        registry.active = Some("missing".to_string());
+        assert_eq!(registry.active_path(&app_dir), app_dir.join(DEFAULT_DB_FILENAME));
+    }
+
+    #[test]
+    fn test_resolve_new_workspace_path() {
+        let app_dir = PathBuf::from("/data/streamforge");
+        assert_eq!(
+            resolve_new_workspace_path(&app_dir, "my test", None).unwrap(),
+            app_dir.join("workspaces").join("my_test.db")
+        );
+        assert!(resolve_new_workspace_path(&app_dir, "x", Some("relative/path.db")).is_err());
+    }
+}
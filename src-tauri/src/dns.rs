@@ -0,0 +1,421 @@
+//! DNS resolution strategy
+//!
+//! ISPs often block or poison IPTV provider hostnames. Provider, EPG, logo
+//! and VOD requests can resolve names through custom DNS servers or a
+//! DNS-over-HTTPS service instead of the system resolver. The strategy is set
+//! globally and can be overridden per account; an override applies to the
+//! host of the account's server (or playlist) URL.
+//!
+//! FFmpeg resolves stream hosts itself. For plain `http` streams whose host
+//! uses a custom strategy, the URL handed to FFmpeg points at the address
+//! last resolved here, with the original name in the Host header. `https`
+//! streams keep the hostname so certificate checks still pass, and are
+//! resolved by the system.
+//!
+//! The configuration is stored in the settings table and mirrored in
+//! process-wide resolvers so HTTP clients don't need a database connection.
+
+use diesel::prelude::*;
+use hickory_resolver::config::{NameServerConfigGroup, ResolverConfig, ResolverOpts};
+use hickory_resolver::TokioAsyncResolver;
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{Arc, LazyLock, Mutex, RwLock};
+use std::time::Duration;
+
+use crate::db::schema::{accounts, settings};
+use crate::db::Setting;
+
+/// Settings key for the DNS configuration (JSON)
+pub const DNS_CONFIG_KEY: &str = "dns_resolution";
+
+/// Timeout for one query to a custom DNS server
+const DNS_QUERY_TIMEOUT: Duration = Duration::from_secs(4);
+
+/// Most custom DNS servers per strategy
+const MAX_DNS_SERVERS: usize = 8;
+
+/// How names are resolved
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DnsMode {
+    /// The operating system resolver
+    #[default]
+    System,
+    /// Plain DNS to the listed servers
+    Custom,
+    /// DNS-over-HTTPS to a public resolver
+    Doh,
+}
+
+/// Public DNS-over-HTTPS resolvers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DohProvider {
+    #[default]
+    Cloudflare,
+    Google,
+    Quad9,
+}
+
+/// Resolution strategy for a set of hosts
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DnsStrategy {
+    pub mode: DnsMode,
+    /// Server addresses for custom mode ("1.1.1.1" or "1.1.1.1:53")
+    pub servers: Vec<String>,
+    pub doh_provider: DohProvider,
+}
+
+impl DnsStrategy {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.mode != DnsMode::Custom {
+            return Ok(());
+        }
+        if self.servers.is_empty() {
+            return Err("At least one DNS server is required".to_string());
+        }
+        if self.servers.len() > MAX_DNS_SERVERS {
+            return Err(format!(
+                "At most {} DNS servers are allowed",
+                MAX_DNS_SERVERS
+            ));
+        }
+        for server in &self.servers {
+            parse_server(server)
+                .ok_or_else(|| format!("Invalid DNS server address: {}", server))?;
+        }
+        Ok(())
+    }
+
+    /// Resolver for this strategy, or None for the system resolver
+    fn resolver(&self) -> Option<Arc<TokioAsyncResolver>> {
+        let config = match self.mode {
+            DnsMode::System => return None,
+            DnsMode::Custom => {
+                let mut group = NameServerConfigGroup::new();
+                for server in self.servers.iter().filter_map(|s| parse_server(s)) {
+                    group.merge(NameServerConfigGroup::from_ips_clear(
+                        &[server.ip()],
+                        server.port(),
+                        true,
+                    ));
+                }
+                ResolverConfig::from_parts(None, vec![], group)
+            }
+            DnsMode::Doh => match self.doh_provider {
+                DohProvider::Cloudflare => ResolverConfig::cloudflare_https(),
+                DohProvider::Google => ResolverConfig::google_https(),
+                DohProvider::Quad9 => ResolverConfig::quad9_https(),
+            },
+        };
+        let mut options = ResolverOpts::default();
+        options.timeout = DNS_QUERY_TIMEOUT;
+        options.attempts = 2;
+        Some(Arc::new(TokioAsyncResolver::tokio(config, options)))
+    }
+}
+
+/// Parse a DNS server address, defaulting to port 53
+fn parse_server(server: &str) -> Option<SocketAddr> {
+    let server = server.trim();
+    server.parse::<SocketAddr>().ok().or_else(|| {
+        server
+            .parse::<IpAddr>()
+            .ok()
+            .map(|ip| SocketAddr::new(ip, 53))
+    })
+}
+
+/// DNS configuration: a global strategy and per-account overrides
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DnsConfig {
+    pub global: DnsStrategy,
+    /// Strategy by account ID
+    pub account_overrides: BTreeMap<i32, DnsStrategy>,
+}
+
+impl DnsConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        self.global.validate()?;
+        for (account_id, strategy) in &self.account_overrides {
+            strategy
+                .validate()
+                .map_err(|e| format!("Account {}: {}", account_id, e))?;
+        }
+        Ok(())
+    }
+}
+
+/// Resolvers currently in use
+#[derive(Default)]
+struct ActiveResolvers {
+    global: Option<Arc<TokioAsyncResolver>>,
+    /// Overrides by lowercase hostname (None uses the system resolver)
+    hosts: HashMap<String, Option<Arc<TokioAsyncResolver>>>,
+}
+
+impl ActiveResolvers {
+    fn for_host(&self, host: &str) -> Option<Arc<TokioAsyncResolver>> {
+        match self.hosts.get(&host.to_ascii_lowercase()) {
+            Some(resolver) => resolver.clone(),
+            None => self.global.clone(),
+        }
+    }
+}
+
+static ACTIVE: LazyLock<RwLock<ActiveResolvers>> =
+    LazyLock::new(|| RwLock::new(ActiveResolvers::default()));
+
+/// Address last resolved with a custom strategy, by lowercase hostname
+static RESOLVED: LazyLock<Mutex<HashMap<String, IpAddr>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Load the DNS configuration (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> DnsConfig {
+    settings::table
+        .filter(settings::key.eq(DNS_CONFIG_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the DNS configuration (applied by the next `load_from_db`)
+pub fn save_config(conn: &mut SqliteConnection, config: &DnsConfig) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize DNS settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(DNS_CONFIG_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Read the persisted configuration and apply it
+///
+/// Called at startup, after switching workspaces, after the configuration is
+/// saved and after accounts change.
+pub fn load_from_db(conn: &mut SqliteConnection) {
+    let config = load_config(conn);
+    let server_urls: HashMap<i32, String> = accounts::table
+        .select((accounts::id, accounts::server_url))
+        .load::<(Option<i32>, String)>(conn)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(id, url)| Some((id?, url)))
+        .collect();
+
+    let hosts = config
+        .account_overrides
+        .iter()
+        .filter_map(|(account_id, strategy)| {
+            let host = url_host(server_urls.get(account_id)?)?;
+            Some((host, strategy.resolver()))
+        })
+        .collect();
+
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = ActiveResolvers {
+            global: config.global.resolver(),
+            hosts,
+        };
+    }
+    RESOLVED.lock().unwrap_or_else(|e| e.into_inner()).clear();
+}
+
+/// Lowercase hostname of a URL
+pub fn url_host(url: &str) -> Option<String> {
+    url::Url::parse(url.trim())
+        .ok()?
+        .host_str()
+        .map(|host| host.trim_matches(['[', ']']).to_ascii_lowercase())
+}
+
+/// Resolve a hostname with the strategy that applies to it
+pub async fn resolve_host(host: &str) -> Result<Vec<IpAddr>, String> {
+    let resolver = ACTIVE.read().ok().and_then(|active| active.for_host(host));
+    match resolver {
+        Some(resolver) => {
+            let addresses = lookup(&resolver, host).await?;
+            if let Some(first) = addresses.first() {
+                RESOLVED
+                    .lock()
+                    .unwrap_or_else(|e| e.into_inner())
+                    .insert(host.to_ascii_lowercase(), *first);
+            }
+            Ok(addresses)
+        }
+        None => system_lookup(host).await,
+    }
+}
+
+/// Resolve a hostname with a specific strategy, without caching the result
+pub async fn resolve_with(strategy: &DnsStrategy, host: &str) -> Result<Vec<IpAddr>, String> {
+    match strategy.resolver() {
+        Some(resolver) => lookup(&resolver, host).await,
+        None => system_lookup(host).await,
+    }
+}
+
+async fn lookup(resolver: &TokioAsyncResolver, host: &str) -> Result<Vec<IpAddr>, String> {
+    let response = resolver
+        .lookup_ip(host)
+        .await
+        .map_err(|e| format!("DNS lookup for {} failed: {}", host, e))?;
+    Ok(response.iter().collect())
+}
+
+async fn system_lookup(host: &str) -> Result<Vec<IpAddr>, String> {
+    let addresses = tokio::net::lookup_host((host, 0))
+        .await
+        .map_err(|e| format!("DNS lookup for {} failed: {}", host, e))?;
+    Ok(addresses.map(|address| address.ip()).collect())
+}
+
+/// `reqwest` resolver that applies the configured strategy per host
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ConfiguredResolver;
+
+impl Resolve for ConfiguredResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        Box::pin(async move {
+            let addresses = resolve_host(&host).await?;
+            let addrs: Addrs = Box::new(
+                addresses
+                    .into_iter()
+                    .map(|ip| SocketAddr::new(ip, 0))
+                    .collect::<Vec<_>>()
+                    .into_iter(),
+            );
+            Ok(addrs)
+        })
+    }
+}
+
+/// Have an HTTP client resolve names with the configured strategy
+pub fn configure(builder: reqwest::ClientBuilder) -> reqwest::ClientBuilder {
+    builder.dns_resolver(Arc::new(ConfiguredResolver))
+}
+
+/// FFmpeg input URL and the extra arguments it needs
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FfmpegInput {
+    pub url: String,
+    /// Options placed before `-i`
+    pub args: Vec<String>,
+}
+
+/// Point FFmpeg at the custom-resolved address of an `http` stream host
+///
+/// The URL is unchanged when the host uses the system resolver, the stream
+/// is not plain `http`, or the host hasn't been resolved yet.
+pub fn ffmpeg_input(upstream_url: &str) -> FfmpegInput {
+    let unchanged = || FfmpegInput {
+        url: upstream_url.to_string(),
+        args: Vec::new(),
+    };
+
+    let Ok(mut url) = url::Url::parse(upstream_url) else {
+        return unchanged();
+    };
+    let Some(host) = url_host(upstream_url) else {
+        return unchanged();
+    };
+    if url.scheme() != "http" || host.parse::<IpAddr>().is_ok() {
+        return unchanged();
+    }
+    let Some(ip) = RESOLVED
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .get(&host)
+        .copied()
+    else {
+        return unchanged();
+    };
+
+    let host_header = match url.port() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    };
+    if url.set_ip_host(ip).is_err() {
+        return unchanged();
+    }
+    FfmpegInput {
+        url: url.to_string(),
+        args: vec!["-headers".to_string(), format!("Host: {}\r\n", host_header)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_custom_servers() {
+        let mut strategy = DnsStrategy {
+            mode: DnsMode::Custom,
+            servers: vec!["1.1.1.1".to_string(), "[2606:4700::1111]:5353".to_string()],
+            ..Default::default()
+        };
+        assert!(strategy.validate().is_ok());
+        assert_eq!(parse_server("9.9.9.9"), Some("9.9.9.9:53".parse().unwrap()));
+
+        strategy.servers.push("dns.example.com".to_string());
+        assert!(strategy.validate().is_err());
+
+        strategy.servers.clear();
+        assert!(strategy.validate().is_err());
+
+        // Servers are ignored outside custom mode
+        strategy.mode = DnsMode::Doh;
+        assert!(strategy.validate().is_ok());
+    }
+
+    #[test]
+    fn test_config_round_trip() {
+        let mut conn = crate::db::test_connection();
+        assert_eq!(load_config(&mut conn), DnsConfig::default());
+
+        let mut config = DnsConfig::default();
+        config.global.mode = DnsMode::Doh;
+        config.account_overrides.insert(
+            3,
+            DnsStrategy {
+                mode: DnsMode::Custom,
+                servers: vec!["8.8.8.8".to_string()],
+                ..Default::default()
+            },
+        );
+        save_config(&mut conn, &config).unwrap();
+        assert_eq!(load_config(&mut conn), config);
+    }
+
+    #[test]
+    fn test_ffmpeg_input_uses_resolved_address() {
+        RESOLVED.lock().unwrap().insert(
+            "live.dns-test.example".to_string(),
+            "203.0.113.7".parse().unwrap(),
+        );
+
+        let input = ffmpeg_input("http://live.dns-test.example:8080/live/u/p/1.ts");
+        assert_eq!(input.url, "http://203.0.113.7:8080/live/u/p/1.ts");
+        assert_eq!(
+            input.args,
+            vec!["-headers", "Host: live.dns-test.example:8080\r\n"]
+        );
+
+        // TLS needs the hostname; unresolved hosts are left to FFmpeg
+        let https = "https://live.dns-test.example/live/u/p/1.ts";
+        assert_eq!(ffmpeg_input(https).url, https);
+        let other = "http://other.dns-test.example/1.ts";
+        assert_eq!(ffmpeg_input(other).url, other);
+    }
+}
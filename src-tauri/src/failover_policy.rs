@@ -0,0 +1,385 @@
+//! Automatic primary demotion
+//!
+//! The stream proxy records every successful failover from one of a
+//! channel's streams to a backup in `failover_history`. When a channel's
+//! primary stream has failed over `failover_count` times within
+//! `window_hours`, the backup that took over most often becomes the primary
+//! and the failing stream takes the backup's place in the failover order.
+//!
+//! Primaries the user chose manually are left alone, as are promotions the
+//! mapping conflict policy would reject. Each demotion is written to the
+//! event log.
+
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{
+    channel_mappings, failover_history, settings, xmltv_channels, xtream_channels,
+};
+use crate::db::Setting;
+
+/// Settings key for the demotion configuration (JSON)
+pub const FAILOVER_POLICY_KEY: &str = "failover_auto_demotion";
+
+/// Longest configurable window; older history is pruned
+const MAX_WINDOW_HOURS: u32 = 168;
+
+/// Timestamp format shared with stored XMLTV program times
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Demotion configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FailoverPolicyConfig {
+    pub enabled: bool,
+    /// Failovers away from the primary that trigger a demotion
+    pub failover_count: u32,
+    /// Window the failovers must fall within
+    pub window_hours: u32,
+}
+
+impl Default for FailoverPolicyConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            failover_count: 3,
+            window_hours: 24,
+        }
+    }
+}
+
+impl FailoverPolicyConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(2..=50).contains(&self.failover_count) {
+            return Err("Failover count must be between 2 and 50".to_string());
+        }
+        if !(1..=MAX_WINDOW_HOURS).contains(&self.window_hours) {
+            return Err(format!(
+                "Failover window must be between 1 and {} hours",
+                MAX_WINDOW_HOURS
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A primary stream replaced by one of its backups
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Demotion {
+    pub xmltv_channel_id: i32,
+    /// xtream_channels ID of the demoted primary
+    pub demoted_stream_id: i32,
+    /// xtream_channels ID of the promoted backup
+    pub promoted_stream_id: i32,
+    pub failover_count: u32,
+}
+
+fn format_timestamp(time: DateTime<Utc>) -> String {
+    time.format(TIMESTAMP_FORMAT).to_string()
+}
+
+/// Load the demotion configuration (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> FailoverPolicyConfig {
+    settings::table
+        .filter(settings::key.eq(FAILOVER_POLICY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the demotion configuration
+pub fn save_config(
+    conn: &mut SqliteConnection,
+    config: &FailoverPolicyConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize failover policy: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(FAILOVER_POLICY_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Record a successful failover and demote the primary if it keeps failing
+///
+/// `from` and `to` are xtream_channels IDs. Returns the demotion made, if any.
+pub fn record_failover(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    from: i32,
+    to: i32,
+    now: DateTime<Utc>,
+) -> QueryResult<Option<Demotion>> {
+    diesel::insert_into(failover_history::table)
+        .values((
+            failover_history::xmltv_channel_id.eq(xmltv_channel_id),
+            failover_history::from_xtream_channel_id.eq(from),
+            failover_history::to_xtream_channel_id.eq(to),
+            failover_history::occurred_at.eq(format_timestamp(now)),
+        ))
+        .execute(conn)?;
+    let oldest_kept = format_timestamp(now - Duration::hours(MAX_WINDOW_HOURS as i64));
+    diesel::delete(failover_history::table.filter(failover_history::occurred_at.lt(oldest_kept)))
+        .execute(conn)?;
+
+    let config = load_config(conn);
+    if !config.enabled {
+        return Ok(None);
+    }
+
+    let primary = channel_mappings::table
+        .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+        .filter(channel_mappings::xtream_channel_id.eq(from))
+        .filter(channel_mappings::is_primary.eq(1))
+        .select(channel_mappings::is_manual)
+        .first::<Option<i32>>(conn)
+        .optional()?;
+    match primary {
+        Some(is_manual) if is_manual.unwrap_or(0) == 0 => {}
+        _ => return Ok(None),
+    }
+
+    let since = format_timestamp(now - Duration::hours(config.window_hours as i64));
+    let targets: Vec<i32> = failover_history::table
+        .filter(failover_history::xmltv_channel_id.eq(xmltv_channel_id))
+        .filter(failover_history::from_xtream_channel_id.eq(from))
+        .filter(failover_history::occurred_at.ge(since))
+        .order(failover_history::id.desc())
+        .select(failover_history::to_xtream_channel_id)
+        .load(conn)?;
+    if (targets.len() as u32) < config.failover_count {
+        return Ok(None);
+    }
+
+    let mapped: Vec<i32> = channel_mappings::table
+        .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+        .select(channel_mappings::xtream_channel_id)
+        .load(conn)?;
+    let Some(promoted) = most_reliable_backup(&targets, &mapped, from) else {
+        return Ok(None);
+    };
+
+    if let Err(reason) =
+        crate::mapping_conflicts::check_primary_assignment(conn, xmltv_channel_id, promoted)
+    {
+        eprintln!(
+            "Failover policy - not promoting stream {} on channel {}: {}",
+            promoted, xmltv_channel_id, reason
+        );
+        return Ok(None);
+    }
+
+    conn.transaction(|conn| {
+        let priorities: HashMap<i32, Option<i32>> = channel_mappings::table
+            .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+            .filter(channel_mappings::xtream_channel_id.eq_any([from, promoted]))
+            .select((
+                channel_mappings::xtream_channel_id,
+                channel_mappings::stream_priority,
+            ))
+            .load::<(i32, Option<i32>)>(conn)?
+            .into_iter()
+            .collect();
+
+        // The two streams swap places; the other backups keep their order
+        let mapping = |stream: i32| {
+            channel_mappings::table
+                .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+                .filter(channel_mappings::xtream_channel_id.eq(stream))
+        };
+        diesel::update(mapping(promoted))
+            .set((
+                channel_mappings::is_primary.eq(1),
+                channel_mappings::stream_priority.eq(priorities.get(&from).copied().flatten()),
+            ))
+            .execute(conn)?;
+        diesel::update(mapping(from))
+            .set((
+                channel_mappings::is_primary.eq(0),
+                channel_mappings::stream_priority.eq(priorities.get(&promoted).copied().flatten()),
+            ))
+            .execute(conn)?;
+
+        diesel::delete(
+            failover_history::table.filter(failover_history::xmltv_channel_id.eq(xmltv_channel_id)),
+        )
+        .execute(conn)
+    })?;
+
+    let demotion = Demotion {
+        xmltv_channel_id,
+        demoted_stream_id: from,
+        promoted_stream_id: promoted,
+        failover_count: targets.len() as u32,
+    };
+    log_demotion(conn, &demotion, config.window_hours);
+    Ok(Some(demotion))
+}
+
+/// The backup that took over most often (the latest one on a tie)
+///
+/// `targets` is newest first; streams no longer mapped to the channel are
+/// skipped.
+fn most_reliable_backup(targets: &[i32], mapped: &[i32], from: i32) -> Option<i32> {
+    let mut counts: HashMap<i32, usize> = HashMap::new();
+    for target in targets {
+        if *target != from && mapped.contains(target) {
+            *counts.entry(*target).or_default() += 1;
+        }
+    }
+    let best = counts.values().copied().max()?;
+    targets
+        .iter()
+        .copied()
+        .find(|target| counts.get(target) == Some(&best))
+}
+
+fn log_demotion(conn: &mut SqliteConnection, demotion: &Demotion, window_hours: u32) {
+    let channel = xmltv_channels::table
+        .filter(xmltv_channels::id.eq(demotion.xmltv_channel_id))
+        .select(xmltv_channels::display_name)
+        .first::<String>(conn)
+        .unwrap_or_else(|_| format!("channel {}", demotion.xmltv_channel_id));
+    let mut stream_name = |id: i32| {
+        xtream_channels::table
+            .filter(xtream_channels::id.eq(id))
+            .select(xtream_channels::name)
+            .first::<String>(conn)
+            .unwrap_or_else(|_| format!("stream {}", id))
+    };
+    let demoted = stream_name(demotion.demoted_stream_id);
+    let promoted = stream_name(demotion.promoted_stream_id);
+
+    let details = serde_json::json!({
+        "channelId": demotion.xmltv_channel_id,
+        "demotedStreamId": demotion.demoted_stream_id,
+        "promotedStreamId": demotion.promoted_stream_id,
+        "failoverCount": demotion.failover_count,
+        "windowHours": window_hours,
+    });
+    let _ = log_event_internal(
+        conn,
+        "warn",
+        "match",
+        &format!(
+            "Primary stream of {} changed to {}: {} failed over {} times in {} hours",
+            channel, promoted, demoted, demotion.failover_count, window_hours
+        ),
+        Some(&details.to_string()),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_connection() -> SqliteConnection {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES
+                 (1, 10, 1.0, 0, 1, 0), (1, 11, 0.9, 0, 0, 1), (1, 12, 0.8, 0, 0, 2)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        conn
+    }
+
+    fn mappings(conn: &mut SqliteConnection) -> Vec<(i32, Option<i32>, Option<i32>)> {
+        channel_mappings::table
+            .filter(channel_mappings::xmltv_channel_id.eq(1))
+            .order(channel_mappings::stream_priority.asc())
+            .select((
+                channel_mappings::xtream_channel_id,
+                channel_mappings::is_primary,
+                channel_mappings::stream_priority,
+            ))
+            .load(conn)
+            .unwrap()
+    }
+
+    fn at(time: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(time)
+            .unwrap()
+            .with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_repeated_failovers_demote_primary() {
+        let mut conn = test_connection();
+
+        // An old failover falls outside the window and doesn't count
+        assert!(
+            record_failover(&mut conn, 1, 10, 11, at("2026-03-01T00:00:00Z"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            record_failover(&mut conn, 1, 10, 12, at("2026-03-02T10:00:00Z"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(
+            record_failover(&mut conn, 1, 10, 12, at("2026-03-02T11:00:00Z"))
+                .unwrap()
+                .is_none()
+        );
+        let demotion = record_failover(&mut conn, 1, 10, 11, at("2026-03-02T12:00:00Z"))
+            .unwrap()
+            .unwrap();
+        assert_eq!(demotion.demoted_stream_id, 10);
+        assert_eq!(demotion.promoted_stream_id, 12);
+        assert_eq!(
+            mappings(&mut conn),
+            vec![
+                (12, Some(1), Some(0)),
+                (11, Some(0), Some(1)),
+                (10, Some(0), Some(2))
+            ]
+        );
+
+        // History starts over after a demotion
+        assert!(
+            record_failover(&mut conn, 1, 12, 11, at("2026-03-02T13:00:00Z"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_disabled_policy_and_manual_primary_are_left_alone() {
+        let mut conn = test_connection();
+        save_config(
+            &mut conn,
+            &FailoverPolicyConfig {
+                enabled: false,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        for hour in 0..5 {
+            let now = at("2026-03-02T00:00:00Z") + Duration::hours(hour);
+            assert!(record_failover(&mut conn, 1, 10, 11, now)
+                .unwrap()
+                .is_none());
+        }
+
+        save_config(&mut conn, &FailoverPolicyConfig::default()).unwrap();
+        diesel::sql_query("UPDATE channel_mappings SET is_manual = 1 WHERE xtream_channel_id = 10")
+            .execute(&mut conn)
+            .unwrap();
+        assert!(
+            record_failover(&mut conn, 1, 10, 11, at("2026-03-02T06:00:00Z"))
+                .unwrap()
+                .is_none()
+        );
+        assert_eq!(mappings(&mut conn)[0], (10, Some(1), Some(0)));
+    }
+}
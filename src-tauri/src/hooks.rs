@@ -0,0 +1,283 @@
+//! Automation hooks
+//!
+//! Runs user-defined commands when notable events happen, so users can wire up
+//! their own integrations (notifications, home automation, scripts) without a
+//! dedicated feature for each. Each hook names a program and its arguments;
+//! the program is started directly (no shell interpolation) and receives the
+//! event as JSON on stdin:
+//!
+//! ```json
+//! { "event": "epg_refresh_completed", "timestamp": "2026-01-25T10:00:00Z", "data": { ... } }
+//! ```
+//!
+//! Hooks are off until the user explicitly enables them, since they execute
+//! arbitrary commands with the app's privileges. Hook runs are fire-and-forget;
+//! failures and timeouts are written to the event log.
+//!
+//! Supported events:
+//! - `epg_refresh_completed` - a manual or scheduled EPG refresh finished
+//! - `all_streams_failed` - every stream of a channel failed and playback stopped
+//!
+//! The app has no recording feature, so there is no "recording finished" event.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::process::Stdio;
+use std::sync::OnceLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tauri_plugin_shell::ShellExt;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::settings;
+use crate::db::{DbConnection, Setting};
+
+/// Settings key for the explicit opt-in ("true"/"false")
+pub const AUTOMATION_HOOKS_ENABLED_KEY: &str = "automation_hooks_enabled";
+
+/// Settings key for the configured hooks (JSON array)
+pub const AUTOMATION_HOOKS_KEY: &str = "automation_hooks";
+
+/// A hook is killed if it runs longer than this
+const HOOK_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Maximum stderr kept in the event log when a hook fails
+const MAX_LOGGED_STDERR: usize = 1000;
+
+/// Handle used to spawn hook processes (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Events that can trigger a hook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookEvent {
+    EpgRefreshCompleted,
+    AllStreamsFailed,
+}
+
+impl HookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookEvent::EpgRefreshCompleted => "epg_refresh_completed",
+            HookEvent::AllStreamsFailed => "all_streams_failed",
+        }
+    }
+}
+
+/// A configured hook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AutomationHook {
+    pub event: HookEvent,
+    /// Program to run (absolute path or a name on PATH)
+    pub program: String,
+    #[serde(default)]
+    pub args: Vec<String>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// Register the app handle so events can start hook processes
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Whether the user has opted in to running hooks
+pub fn is_enabled(conn: &mut SqliteConnection) -> bool {
+    settings::table
+        .filter(settings::key.eq(AUTOMATION_HOOKS_ENABLED_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Load the configured hooks (empty if unset or unreadable)
+pub fn load_hooks(conn: &mut SqliteConnection) -> Vec<AutomationHook> {
+    settings::table
+        .filter(settings::key.eq(AUTOMATION_HOOKS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Validate and persist the opt-in flag and hooks
+pub fn save_hooks(
+    conn: &mut SqliteConnection,
+    enabled: bool,
+    hooks: &[AutomationHook],
+) -> Result<(), String> {
+    if hooks.iter().any(|hook| hook.program.trim().is_empty()) {
+        return Err("Every hook needs a program to run".to_string());
+    }
+
+    let hooks_json =
+        serde_json::to_string(hooks).map_err(|e| format!("Failed to serialize hooks: {}", e))?;
+
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::replace_into(settings::table)
+            .values(&Setting::new(AUTOMATION_HOOKS_ENABLED_KEY, enabled.to_string()))
+            .execute(conn)?;
+        diesel::replace_into(settings::table)
+            .values(&Setting::new(AUTOMATION_HOOKS_KEY, hooks_json))
+            .execute(conn)?;
+        Ok(())
+    })
+    .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+/// JSON written to a hook's stdin
+fn hook_input(event: HookEvent, data: &serde_json::Value, timestamp: &str) -> String {
+    serde_json::json!({
+        "event": event.as_str(),
+        "timestamp": timestamp,
+        "data": data,
+    })
+    .to_string()
+}
+
+/// Run the hooks configured for an event in the background
+///
+/// Does nothing before `init`, when hooks are not enabled, or when no hook
+/// matches the event, so callers can fire unconditionally.
+pub fn fire(event: HookEvent, data: serde_json::Value) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let Some(db) = app.try_state::<DbConnection>() else {
+        return;
+    };
+    let Ok(mut conn) = db.get_connection() else {
+        return;
+    };
+    if !is_enabled(&mut conn) {
+        return;
+    }
+    let hooks: Vec<AutomationHook> = load_hooks(&mut conn)
+        .into_iter()
+        .filter(|hook| hook.enabled && hook.event == event)
+        .collect();
+    drop(conn);
+    if hooks.is_empty() {
+        return;
+    }
+
+    let input = hook_input(event, &data, &chrono::Utc::now().to_rfc3339());
+    for hook in hooks {
+        let app = app.clone();
+        let input = input.clone();
+        tauri::async_runtime::spawn(async move {
+            if let Err(e) = run_hook(&app, &hook, &input).await {
+                eprintln!("Automation hook '{}' failed: {}", hook.program, e);
+                if let Ok(mut conn) = app.state::<DbConnection>().get_connection() {
+                    let details = serde_json::json!({
+                        "event": event.as_str(),
+                        "program": hook.program,
+                        "error": e,
+                    });
+                    let _ = log_event_internal(
+                        &mut conn,
+                        "warn",
+                        "system",
+                        &format!("Automation hook failed: {} ({})", hook.program, event.as_str()),
+                        Some(&details.to_string()),
+                    );
+                }
+            }
+        });
+    }
+}
+
+/// Start one hook, write the event to its stdin, and wait for it to exit
+async fn run_hook(app: &AppHandle, hook: &AutomationHook, input: &str) -> Result<(), String> {
+    let command: std::process::Command = app.shell().command(&hook.program).args(&hook.args).into();
+    let mut child = tokio::process::Command::from(command)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start: {}", e))?;
+
+    // Dropping stdin signals EOF; a hook that ignores stdin may close it early
+    if let Some(mut stdin) = child.stdin.take() {
+        let _ = stdin.write_all(input.as_bytes()).await;
+    }
+
+    let mut stderr_pipe = child.stderr.take();
+    let wait = async {
+        let mut stderr = String::new();
+        if let Some(pipe) = stderr_pipe.as_mut() {
+            let _ = pipe.read_to_string(&mut stderr).await;
+        }
+        (child.wait().await, stderr)
+    };
+
+    match tokio::time::timeout(HOOK_TIMEOUT, wait).await {
+        Err(_) => Err(format!("Timed out after {}s", HOOK_TIMEOUT.as_secs())),
+        Ok((Err(e), _)) => Err(format!("Failed to wait for exit: {}", e)),
+        Ok((Ok(status), _)) if status.success() => Ok(()),
+        Ok((Ok(status), stderr)) => {
+            let stderr: String = stderr.trim().chars().take(MAX_LOGGED_STDERR).collect();
+            Err(format!("Exited with {}: {}", status, stderr))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_and_load_hooks() {
+        let mut conn = crate::db::test_connection();
+
+        assert!(!is_enabled(&mut conn));
+        assert!(load_hooks(&mut conn).is_empty());
+
+        let hooks = vec![AutomationHook {
+            event: HookEvent::AllStreamsFailed,
+            program: "/usr/local/bin/notify".to_string(),
+            args: vec!["--urgent".to_string()],
+            enabled: true,
+        }];
+        save_hooks(&mut conn, true, &hooks).unwrap();
+        assert!(is_enabled(&mut conn));
+        assert_eq!(load_hooks(&mut conn), hooks);
+
+        let blank = vec![AutomationHook {
+            program: "  ".to_string(),
+            ..hooks[0].clone()
+        }];
+        assert!(save_hooks(&mut conn, true, &blank).is_err());
+    }
+
+    #[test]
+    fn test_hook_deserialization_defaults() {
+        let hook: AutomationHook =
+            serde_json::from_str(r#"{"event":"epg_refresh_completed","program":"notify"}"#).unwrap();
+        assert_eq!(hook.event, HookEvent::EpgRefreshCompleted);
+        assert!(hook.args.is_empty());
+        assert!(hook.enabled);
+    }
+
+    #[test]
+    fn test_hook_input() {
+        let input = hook_input(
+            HookEvent::AllStreamsFailed,
+            &serde_json::json!({ "channelId": 7 }),
+            "2026-01-25T10:00:00+00:00",
+        );
+        let value: serde_json::Value = serde_json::from_str(&input).unwrap();
+        assert_eq!(value["event"], "all_streams_failed");
+        assert_eq!(value["timestamp"], "2026-01-25T10:00:00+00:00");
+        assert_eq!(value["data"]["channelId"], 7);
+    }
+}
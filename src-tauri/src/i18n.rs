@@ -0,0 +1,222 @@
+//! Localized user-facing messages
+//!
+//! Command errors and event log texts are looked up by message ID in the
+//! Fluent files under `locales/{lang}/main.ftl`, which are compiled into the
+//! binary. The language is stored in settings (`ui_language`) and cached
+//! process-wide; `init` loads it at startup and `set_language` changes it.
+//!
+//! Messages missing from a translation fall back to English, and unknown IDs
+//! are returned as-is. Event log entries are stored in the language active
+//! when they were logged. Strings not yet moved to the Fluent files are still
+//! English-only.
+
+use std::collections::HashMap;
+use std::sync::{OnceLock, RwLock};
+
+use diesel::prelude::*;
+use fluent_bundle::concurrent::FluentBundle;
+use fluent_bundle::{FluentArgs, FluentResource, FluentValue};
+use serde::Serialize;
+use unic_langid::LanguageIdentifier;
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key holding the language code
+pub const LANGUAGE_KEY: &str = "ui_language";
+
+/// Language used when none is configured and for missing translations
+pub const DEFAULT_LANGUAGE: &str = "en";
+
+/// Supported languages: (code, native name, Fluent source)
+const LANGUAGES: [(&str, &str, &str); 4] = [
+    ("en", "English", include_str!("../locales/en/main.ftl")),
+    ("es", "Español", include_str!("../locales/es/main.ftl")),
+    ("de", "Deutsch", include_str!("../locales/de/main.ftl")),
+    ("fr", "Français", include_str!("../locales/fr/main.ftl")),
+];
+
+/// A language that can be selected
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LanguageOption {
+    pub code: String,
+    pub name: String,
+}
+
+type Bundles = HashMap<&'static str, FluentBundle<FluentResource>>;
+
+static BUNDLES: OnceLock<Bundles> = OnceLock::new();
+static CURRENT_LANGUAGE: OnceLock<RwLock<String>> = OnceLock::new();
+
+fn bundles() -> &'static Bundles {
+    BUNDLES.get_or_init(|| {
+        LANGUAGES
+            .iter()
+            .map(|(code, _, source)| {
+                let langid: LanguageIdentifier = code.parse().expect("valid language code");
+                let resource = FluentResource::try_new(source.to_string()).unwrap_or_else(
+                    |(resource, errors)| {
+                        eprintln!("[i18n] Errors in {} messages: {:?}", code, errors);
+                        resource
+                    },
+                );
+                let mut bundle = FluentBundle::new_concurrent(vec![langid]);
+                // Messages are shown as plain text, not mixed into bidi layouts
+                bundle.set_use_isolating(false);
+                if let Err(errors) = bundle.add_resource(resource) {
+                    eprintln!("[i18n] Duplicate {} messages: {:?}", code, errors);
+                }
+                (*code, bundle)
+            })
+            .collect()
+    })
+}
+
+fn current() -> &'static RwLock<String> {
+    CURRENT_LANGUAGE.get_or_init(|| RwLock::new(DEFAULT_LANGUAGE.to_string()))
+}
+
+/// Languages that can be selected
+pub fn supported_languages() -> Vec<LanguageOption> {
+    LANGUAGES
+        .iter()
+        .map(|(code, name, _)| LanguageOption {
+            code: code.to_string(),
+            name: name.to_string(),
+        })
+        .collect()
+}
+
+pub(crate) fn is_supported(code: &str) -> bool {
+    LANGUAGES.iter().any(|(supported, _, _)| *supported == code)
+}
+
+/// The active language code
+pub fn language() -> String {
+    current()
+        .read()
+        .map(|code| code.clone())
+        .unwrap_or_else(|_| DEFAULT_LANGUAGE.to_string())
+}
+
+/// Load the configured language (at startup and after switching workspaces)
+pub fn init(conn: &mut SqliteConnection) {
+    let code = settings::table
+        .filter(settings::key.eq(LANGUAGE_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .filter(|code| is_supported(code))
+        .unwrap_or_else(|| DEFAULT_LANGUAGE.to_string());
+    if let Ok(mut language) = current().write() {
+        *language = code;
+    }
+}
+
+/// Store and activate a language
+pub fn set_language(conn: &mut SqliteConnection, code: &str) -> Result<(), String> {
+    if !is_supported(code) {
+        return Err(tr_args("error-unsupported-language", &[("language", code)]));
+    }
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(LANGUAGE_KEY, code))
+        .execute(conn)
+        .map_err(|e| tr_args("error-save-setting", &[("error", &e.to_string())]))?;
+    if let Ok(mut language) = current().write() {
+        *language = code.to_string();
+    }
+    Ok(())
+}
+
+fn format_in(code: &str, id: &str, args: Option<&FluentArgs>) -> Option<String> {
+    let bundle = bundles().get(code)?;
+    let pattern = bundle.get_message(id)?.value()?;
+    let mut errors = Vec::new();
+    let text = bundle.format_pattern(pattern, args, &mut errors);
+    if !errors.is_empty() {
+        eprintln!("[i18n] Errors formatting '{}' ({}): {:?}", id, code, errors);
+    }
+    Some(text.into_owned())
+}
+
+fn format(id: &str, args: Option<&FluentArgs>) -> String {
+    format_in(&language(), id, args)
+        .or_else(|| format_in(DEFAULT_LANGUAGE, id, args))
+        .unwrap_or_else(|| id.to_string())
+}
+
+/// Translate a message in the active language
+pub fn tr(id: &str) -> String {
+    format(id, None)
+}
+
+/// Translate a message with string arguments in the active language
+pub fn tr_args(id: &str, args: &[(&str, &str)]) -> String {
+    let mut fluent_args = FluentArgs::new();
+    for (name, value) in args {
+        fluent_args.set(*name, FluentValue::from(*value));
+    }
+    format(id, Some(&fluent_args))
+}
+
+/// "Database connection error: {error}" in the active language
+pub fn db_connection_error(error: impl std::fmt::Display) -> String {
+    let message = tr_args(
+        "error-database-connection",
+        &[("error", &error.to_string())],
+    );
+    // Can't go to the event log without a connection
+    crate::recent_errors::record("database", &message, false);
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translations_fall_back_to_english() {
+        for (code, _, _) in LANGUAGES {
+            assert!(bundles().contains_key(code));
+        }
+        let english = format_in("en", "error-port-too-low", None).unwrap();
+        assert_eq!(
+            english,
+            "Port must be 1024 or higher (non-privileged ports)"
+        );
+        assert_ne!(
+            format_in("es", "error-port-too-low", None).unwrap(),
+            english
+        );
+
+        let mut args = FluentArgs::new();
+        args.set("error", FluentValue::from("locked"));
+        assert_eq!(
+            format_in("de", "error-database-connection", Some(&args)).unwrap(),
+            "Datenbankverbindungsfehler: locked"
+        );
+
+        // Unknown IDs come back unchanged
+        assert_eq!(format("no-such-message", None), "no-such-message");
+    }
+
+    #[test]
+    fn test_every_translation_has_the_english_messages() {
+        let english = bundles().get("en").unwrap();
+        let ids: Vec<&str> = LANGUAGES[0]
+            .2
+            .lines()
+            .filter_map(|line| line.split_once(" = ").map(|(id, _)| id.trim()))
+            .filter(|id| !id.is_empty() && !id.starts_with('#'))
+            .collect();
+        assert!(!ids.is_empty());
+        for (code, _, _) in LANGUAGES {
+            let bundle = bundles().get(code).unwrap();
+            for id in &ids {
+                assert!(english.has_message(id));
+                assert!(bundle.has_message(id), "{} is missing '{}'", code, id);
+            }
+        }
+    }
+}
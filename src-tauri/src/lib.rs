@@ -1,9 +1,35 @@
+pub mod account_expiry;
+pub mod channel_numbers;
+pub mod channel_policy;
+pub mod clock;
 pub mod commands;
+pub mod config_integrity;
 pub mod credentials;
 pub mod db;
+pub mod dns;
+pub mod failover_policy;
+pub mod hooks;
+pub mod i18n;
+pub mod lineup_staging;
+pub mod logging;
+pub mod low_resource;
+pub mod maintenance;
+pub mod mapping_conflicts;
 pub mod matcher;
+pub mod metrics;
+pub mod notifications;
+pub mod plex;
+pub mod plugins;
+pub mod quota;
+pub mod recent_errors;
 pub mod scheduler;
 pub mod server;
+pub mod service;
+pub mod settings_registry;
+pub mod shutdown;
+pub mod startup;
+pub mod tasks;
+pub mod tuner_priority;
 pub mod xmltv;
 pub mod xtream;
 
@@ -18,6 +44,7 @@ use tauri_plugin_autostart::MacosLauncher;
 
 // Constants
 const MAIN_WINDOW_NAME: &str = "main";
+pub(crate) const TRAY_ICON_ID: &str = "main-tray";
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -33,132 +60,122 @@ pub fn run() {
             ))
             .plugin(tauri_plugin_dialog::init())
             .plugin(tauri_plugin_updater::Builder::new().build())
-            .plugin(tauri_plugin_process::init());
+            .plugin(tauri_plugin_process::init())
+            .plugin(tauri_plugin_notification::init());
     }
 
     builder.setup(|app| {
-            // Initialize database
+            // Open (or create) the database file; migrations and anything that
+            // reads settings run in the background so the window shows right away
             let db_path = db::get_db_path(app)?;
             let database_url = db_path.to_string_lossy().to_string();
-
-            // Establish connection and run migrations
+            credentials::set_workspace(&db_path);
             let mut conn = db::establish_connection(&database_url)
                 .map_err(|e| format!("Failed to connect to database: {}", e))?;
-            db::run_migrations(&mut conn)
-                .map_err(|e| format!("Failed to run migrations: {}", e))?;
+
+            // Connections handed out by DbConnection wait until this phase is done
+            startup::begin(startup::StartupPhase::Database);
+
+            // Automation hooks spawn processes through the shell plugin
+            hooks::init(app.handle().clone());
+            notifications::init(app.handle().clone());
+            plex::init(app.handle().clone());
+            tasks::init(app.handle().clone());
+            recent_errors::init(app.handle().clone());
 
             // Create connection pool and store for later use by commands
             let db_connection = db::DbConnection::new(database_url)
                 .map_err(|e| format!("Failed to create connection pool: {}", e))?;
 
-            // Story 6-3: Log application startup event (AC #1)
-            {
-                use commands::logs::log_event_internal;
-                if let Ok(mut log_conn) = db_connection.get_connection() {
-                    let details = serde_json::json!({
-                        "version": env!("CARGO_PKG_VERSION"),
-                    });
-                    let _ = log_event_internal(
-                        &mut log_conn,
-                        "info",
-                        "system",
-                        &format!("StreamForge v{} started", env!("CARGO_PKG_VERSION")),
-                        Some(&details.to_string()),
-                    );
-                }
-            }
-
             // Get app data directory for credential retrieval in stream proxy
             let app_data_dir = app.path()
                 .app_data_dir()
                 .map_err(|_| "Failed to get app data directory".to_string())?;
 
+            // Log files (the setting may not exist yet on first run)
+            logging::init(&app_data_dir, &logging::stored_verbosity(&mut conn));
+
+            // Matcher and playlist plugins (enabled per plugin in settings)
+            plugins::init(&app_data_dir);
+
             // Create HTTP server state with database pool and app data dir
-            let server_state = server::create_app_state_with_dir(
-                db_connection.clone_pool(),
-                app_data_dir
+            // Shares the swappable pool handle so workspace switches reach the server
+            let server_state = server::create_app_state_with_shared_pool(
+                db_connection.shared_pool(),
+                app_data_dir.clone()
             );
 
-            // Spawn HTTP server in background - MUST use tauri::async_runtime
-            // Server runs independently of GUI and continues when window is hidden
-            tauri::async_runtime::spawn(async move {
-                if let Err(e) = server::start_server(server_state).await {
-                    eprintln!("HTTP server error: {}", e);
-                }
-            });
+            // Deferred maintenance jobs wait until no streams are active
+            maintenance::init(server_state.stream_manager().clone());
+            server::failover::init(db_connection.shared_pool());
+            server::priming::init(app.handle().clone());
 
-            // Initialize EPG scheduler
-            // Clone pool for scheduler - scheduler runs independently of commands
-            let scheduler_pool = db_connection.clone_pool();
+            // Managed so commands (e.g. workspace switching) can reach server state
+            app.manage(server_state.clone());
+
+            // The handle is managed so restart_server can rebind on a new port
+            let server_handle = server::ServerHandle::new();
+            app.manage(server_handle.clone());
+
+            // Store scheduler in managed state for commands to access
+            // Scheduler runs independently of commands on its own pool clone
             let epg_scheduler = scheduler::EpgScheduler::new();
+            app.manage(epg_scheduler.clone());
+
+            let scheduler_pool = db_connection.clone_pool();
+            let logo_pool = db_connection.shared_pool();
+            app.manage(db_connection);
 
-            // Spawn scheduler initialization in background
-            let scheduler_clone = epg_scheduler.clone();
+            // Bring up the database, HTTP server and scheduler in the background
+            // MUST use tauri::async_runtime; the server runs independently of the
+            // GUI and continues when the window is hidden
             tauri::async_runtime::spawn(async move {
-                // Set up database pool
-                scheduler_clone.set_db_pool(scheduler_pool).await;
-
-                // Start the scheduler
-                if let Err(e) = scheduler_clone.start().await {
-                    tracing::error!(
-                        "CRITICAL: Failed to start EPG scheduler: {}. Automatic EPG refresh will not work!",
-                        e
+                // The phase is finished on the blocking thread itself: commands
+                // waiting for the database may be occupying the async workers
+                let database_ok = tauri::async_runtime::spawn_blocking(move || {
+                    let result = initialize_database(conn);
+                    let ok = result.is_ok();
+                    startup::finish(startup::StartupPhase::Database, result);
+                    ok
+                })
+                .await
+                .unwrap_or_else(|e| {
+                    startup::finish(
+                        startup::StartupPhase::Database,
+                        Err(format!("Database initialization panicked: {}", e)),
                     );
-                    eprintln!("Failed to start EPG scheduler: {}. Automatic EPG refresh will not work!", e);
+                    false
+                });
+                if !database_ok {
+                    startup::skip(startup::StartupPhase::Server, "Database initialization failed");
+                    startup::skip(startup::StartupPhase::Scheduler, "Database initialization failed");
                     return;
                 }
-                tracing::info!("EPG scheduler started successfully");
-
-                // Get a temporary connection to read schedule settings
-                if let Some(mut conn) = scheduler_clone.get_db_connection().await {
-                    let schedule = scheduler::get_epg_schedule(&mut conn);
 
-                    // Set enabled state
-                    if let Err(e) = scheduler_clone.set_enabled(schedule.enabled).await {
-                        tracing::error!("Failed to set scheduler enabled state: {}. Using default enabled state.", e);
-                        eprintln!("Failed to set scheduler enabled state: {}", e);
-                    }
+                // Tuner limits were read before migrations could run
+                server_state.refresh_max_connections();
+                let stream_manager = server_state.stream_manager().clone();
 
-                    // Update schedule if enabled
-                    if schedule.enabled {
-                        if let Err(e) = scheduler_clone.update_schedule(schedule.hour, schedule.minute).await {
-                            tracing::error!(
-                                "Failed to configure EPG schedule ({:02}:{:02}): {}. Automatic refresh will not work!",
-                                schedule.hour,
-                                schedule.minute,
-                                e
-                            );
-                            eprintln!("Failed to update EPG schedule: {}", e);
-                        } else {
-                            tracing::info!(
-                                "EPG scheduler configured: refresh at {:02}:{:02} daily",
-                                schedule.hour,
-                                schedule.minute
-                            );
-                        }
-                    } else {
-                        tracing::info!("EPG automatic refresh is disabled in settings");
-                    }
-
-                    // Wait 7 seconds after scheduler initialization before checking for missed refresh
-                    // This ensures the schedule is fully configured before checking for missed refreshes
-                    tokio::time::sleep(tokio::time::Duration::from_secs(7)).await;
-
-                    // Check for missed refresh and trigger if needed
-                    // This must happen AFTER schedule is configured to detect missed refreshes correctly
-                    scheduler::check_and_trigger_missed_refresh(&scheduler_clone).await;
-                } else {
-                    tracing::error!(
-                        "CRITICAL: Failed to get database connection for scheduler initialization. Automatic EPG refresh will not work!"
-                    );
-                    eprintln!("Failed to get database connection for scheduler initialization");
+                startup::begin(startup::StartupPhase::Server);
+                let server = server_handle
+                    .start(server_state)
+                    .await
+                    .map(|_| ())
+                    .map_err(|e| e.to_string());
+                if let Err(e) = &server {
+                    eprintln!("HTTP server error: {}", e);
                 }
-            });
+                startup::finish(startup::StartupPhase::Server, server);
 
-            // Store scheduler in managed state for commands to access
-            app.manage(epg_scheduler);
+                // Spawn logo cache pre-warm job (no-op while all outputs use remote logos)
+                let logo_data_dir = app_data_dir.clone();
+                tauri::async_runtime::spawn(async move {
+                    server::logos::run_logo_prewarm_job(logo_pool, logo_data_dir).await;
+                });
 
-            app.manage(db_connection);
+                start_epg_scheduler(epg_scheduler, scheduler_pool, app_data_dir, stream_manager)
+                    .await;
+            });
 
             // Create tray menu items
             let show_i = MenuItem::with_id(app, "show", "Show Window", true, None::<&str>)?;
@@ -188,14 +205,14 @@ pub fn run() {
             if let Some(icon) = tray_icon {
                 // On macOS, mark as template so it adapts to light/dark menubar automatically
                 #[cfg(target_os = "macos")]
-                let tray_builder = TrayIconBuilder::new()
+                let tray_builder = TrayIconBuilder::with_id(TRAY_ICON_ID)
                     .icon(icon)
                     .icon_as_template(true)
                     .menu(&menu)
                     .show_menu_on_left_click(false);
 
                 #[cfg(not(target_os = "macos"))]
-                let tray_builder = TrayIconBuilder::new()
+                let tray_builder = TrayIconBuilder::with_id(TRAY_ICON_ID)
                     .icon(icon)
                     .menu(&menu)
                     .show_menu_on_left_click(false);
@@ -306,9 +323,49 @@ pub fn run() {
             commands::greet,
             commands::get_setting,
             commands::set_setting,
+            commands::apply_settings_batch,
             commands::get_server_port,
             commands::set_server_port,
+            commands::get_bind_address,
+            commands::get_bind_address_options,
+            commands::set_bind_address,
+            commands::get_hdhr_identity,
+            commands::set_hdhr_identity,
+            commands::get_ssdp_enabled,
+            commands::set_ssdp_enabled,
+            // Device profile commands
+            commands::device_profiles::list_device_profiles,
+            commands::device_profiles::create_device_profile,
+            commands::device_profiles::update_device_profile,
+            commands::device_profiles::delete_device_profile,
+            commands::device_profiles::set_device_profile_channels,
+            commands::get_access_token,
+            commands::rotate_access_token,
+            commands::clear_access_token,
+            commands::get_language,
+            commands::get_supported_languages,
+            commands::set_language,
+            commands::get_active_streams,
+            commands::stream_stats::get_stream_stats,
+            commands::tasks::start_channel_scan_task,
+            commands::tasks::start_channel_matching_task,
+            commands::tasks::start_epg_refresh_task,
+            commands::tasks::get_task_status,
+            commands::tasks::list_tasks,
+            commands::tasks::cancel_task,
             commands::restart_server,
+            commands::get_stream_ts_padding,
+            commands::set_stream_ts_padding,
+            commands::get_stream_ts_continuity,
+            commands::set_stream_ts_continuity,
+            commands::get_stream_preferred_quality,
+            commands::set_stream_preferred_quality,
+            commands::set_channel_preferred_quality,
+            commands::get_ffmpeg_path,
+            commands::set_ffmpeg_path,
+            commands::get_maintenance_window,
+            commands::set_maintenance_window,
+            commands::get_maintenance_status,
             commands::get_autostart_enabled,
             commands::set_autostart_enabled,
             commands::get_plex_config,
@@ -316,15 +373,28 @@ pub fn run() {
             commands::accounts::get_accounts,
             commands::accounts::delete_account,
             commands::accounts::update_account,
+            commands::accounts::set_account_reconnect_interval,
             commands::accounts::test_connection,
+            commands::accounts::verify_credentials,
+            commands::accounts::get_account_expiry_info,
+            commands::speedtest::run_provider_speedtest,
+            commands::quota::get_account_quotas,
+            commands::quota::set_account_quota,
+            commands::vod::scan_vod,
+            commands::vod::get_vod_items,
+            commands::vod::get_series_episodes,
+            commands::speedtest::get_provider_speedtest_history,
             commands::channels::scan_channels,
             commands::channels::scan_and_rematch,
+            commands::channels::get_channel_rescan_schedule,
+            commands::channels::set_channel_rescan_schedule,
             commands::channels::get_channels,
             commands::channels::get_channel_count,
             commands::epg::add_xmltv_source,
             commands::epg::get_xmltv_sources,
             commands::epg::update_xmltv_source,
             commands::epg::delete_xmltv_source,
+            commands::epg::set_xmltv_source_auth,
             commands::epg::toggle_xmltv_source,
             commands::epg::refresh_epg_source,
             commands::epg::refresh_all_epg_sources,
@@ -333,6 +403,17 @@ pub fn run() {
             commands::epg::get_programs,
             commands::epg::get_epg_schedule,
             commands::epg::set_epg_schedule,
+            commands::epg::get_epg_retention,
+            commands::epg::set_epg_retention,
+            commands::epg::set_epg_history,
+            commands::epg::get_epg_history,
+            commands::epg::get_epg_window,
+            commands::epg::set_epg_window,
+            commands::epg::get_epg_languages,
+            commands::epg::get_placeholder_epg,
+            commands::epg::set_placeholder_epg,
+            commands::epg::set_channel_placeholder,
+            commands::epg::set_epg_languages,
             commands::epg::get_enabled_channels_with_programs,
             commands::epg::search_epg_programs,
             commands::epg::get_channel_stream_info,
@@ -343,12 +424,30 @@ pub fn run() {
             commands::matcher::get_xmltv_channel_settings,
             commands::matcher::get_match_threshold,
             commands::matcher::set_match_threshold,
+            commands::matcher::get_match_algorithm,
+            commands::matcher::set_match_algorithm,
+            commands::matcher::simulate_matching,
+            commands::matcher::get_match_profiles,
+            commands::matcher::set_match_profile,
+            commands::matcher::get_icon_match_config,
+            commands::matcher::set_icon_match_config,
+            commands::matcher::get_region_match_config,
+            commands::matcher::set_region_match_config,
             commands::matcher::normalize_channel_name,
             commands::matcher::calculate_match_score,
+            commands::matcher::list_match_rules,
+            commands::matcher::add_match_rule,
+            commands::matcher::update_match_rule,
+            commands::matcher::delete_match_rule,
+            commands::matcher::test_match_rule,
             commands::matcher::detect_provider_changes,
             commands::matcher::auto_rematch_new_streams,
             commands::matcher::handle_removed_streams,
             commands::matcher::handle_changed_streams,
+            // Alias pack commands
+            commands::alias_packs::import_alias_pack,
+            commands::alias_packs::list_alias_packs,
+            commands::alias_packs::remove_alias_pack,
             commands::xmltv_channels::get_xmltv_channels_with_mappings,
             commands::xmltv_channels::set_primary_stream,
             commands::xmltv_channels::toggle_xmltv_channel,
@@ -368,22 +467,111 @@ pub fn run() {
             commands::xtream_sources::unlink_xtream_stream,
             commands::logs::log_event,
             commands::logs::get_events,
+            commands::logs::search_events,
             commands::logs::get_unread_event_count,
+            commands::logs::get_recent_errors,
+            commands::logs::clear_recent_errors,
             commands::logs::mark_event_read,
             commands::logs::mark_all_events_read,
             commands::logs::clear_old_events,
             commands::logs::get_log_verbosity,
             commands::logs::set_log_verbosity,
+            commands::logs::get_log_file_path,
+            commands::logs::export_logs,
+            commands::logs::get_failover_notification_cooldown,
+            commands::logs::set_failover_notification_cooldown,
             // Configuration export/import commands (Story 6-2)
             commands::config::export_configuration,
             commands::config::validate_import_file,
             commands::config::import_configuration,
+            commands::config::resolve_imported_mappings,
+            commands::credentials::migrate_credentials,
             // Update commands (Story 6-5)
             commands::update::check_for_update,
+            commands::update::get_update_changelog,
+            commands::update::set_update_channel,
+            commands::update::defer_update,
+            commands::update::set_version_pinned,
+            commands::update::rollback_update,
             commands::update::get_update_settings,
             commands::update::set_auto_check_updates,
             commands::update::download_and_install_update,
             commands::update::get_current_version,
+            // Logo cache commands
+            commands::logos::get_logo_settings,
+            commands::logos::set_logo_settings,
+            commands::logos::prewarm_logo_cache,
+            // Web admin commands
+            commands::web_admin::get_web_admin_status,
+            commands::web_admin::set_web_admin_password,
+            // Workspace commands
+            commands::workspaces::list_workspaces,
+            commands::workspaces::create_workspace,
+            commands::workspaces::switch_workspace,
+            commands::workspaces::remove_workspace,
+            // Diagnostics commands
+            commands::diagnostics::get_resource_usage,
+            commands::diagnostics::get_startup_status,
+            commands::diagnostics::get_low_resource_mode,
+            commands::diagnostics::set_low_resource_mode,
+            commands::diagnostics::get_channel_failover_chain,
+            // System service commands
+            commands::service::get_service_status,
+            commands::service::get_service_definition,
+            commands::service::install_service,
+            commands::service::uninstall_service,
+            // Automation hook commands
+            commands::hooks::get_automation_hooks,
+            commands::hooks::set_automation_hooks,
+            commands::plugins::list_plugins,
+            commands::plugins::set_plugin_enabled,
+            commands::plugins::reload_plugins,
+            commands::notifications::get_notification_settings,
+            commands::notifications::set_notification_settings,
+            commands::notifications::send_test_notification,
+            commands::plex::get_plex_reload_settings,
+            commands::plex::set_plex_reload_settings,
+            // Channel enablement policy commands
+            commands::channel_numbers::set_channel_number,
+            commands::channel_numbers::auto_assign_channel_numbers,
+            commands::channel_policy::get_channel_policy,
+            commands::channel_policy::set_channel_policy,
+            commands::channel_policy::preview_channel_policy,
+            commands::channel_policy::run_channel_policy,
+            commands::failover_policy::get_failover_policy,
+            commands::failover_policy::set_failover_policy,
+            commands::tuner_priority::get_tuner_priority,
+            commands::tuner_priority::set_tuner_priority,
+            commands::metrics::get_metrics_export_config,
+            commands::metrics::set_metrics_export_config,
+            commands::dns::get_dns_config,
+            commands::dns::set_dns_config,
+            commands::dns::test_dns_resolution,
+            // Lineup export/import commands
+            commands::lineup::export_lineup,
+            commands::lineup::import_lineup,
+            commands::lineup::get_lineup_staging,
+            commands::lineup::set_lineup_staging,
+            commands::lineup::publish_lineup,
+            commands::lineup::discard_lineup_draft,
+            commands::lineup::get_lineup_preview,
+            commands::lineup::get_debug_lineup_enabled,
+            commands::lineup::set_debug_lineup_enabled,
+            commands::mapping_conflicts::get_mapping_conflicts,
+            commands::mapping_conflicts::resolve_mapping_conflict,
+            commands::mapping_conflicts::get_mapping_conflict_policy,
+            commands::mapping_conflicts::set_mapping_conflict_policy,
+            commands::mapping_csv::export_mappings_csv,
+            commands::mapping_csv::preview_mappings_csv,
+            commands::mapping_csv::import_mappings_csv,
+            commands::data_dump::export_data_dump,
+            commands::lineup_report::get_lineup_report,
+            commands::lineup_report::export_lineup_report_csv,
+            // Per-channel XMLTV feed commands
+            commands::channel_epg_feeds::get_channel_epg_feeds,
+            commands::channel_epg_feeds::set_channel_epg_feed,
+            commands::channel_epg_feeds::remove_channel_epg_feed,
+            commands::channel_epg_feeds::refresh_channel_epg_feed,
             // Test data commands (only functional when IPTV_TEST_MODE=1)
             commands::test_data::seed_stream_proxy_test_data,
             commands::test_data::clear_stream_proxy_test_data,
@@ -396,13 +584,171 @@ pub fn run() {
         ])
         .build(tauri::generate_context!())
         .expect("error while building tauri application")
-        .run(|_app_handle, event| {
+        .run(|app_handle, event| {
             if let RunEvent::ExitRequested { api, code, .. } = event {
-                if code.is_none() {
+                match code {
                     // Prevent exit when window is closed (no explicit exit code)
-                    api.prevent_exit();
+                    None => api.prevent_exit(),
+                    // Explicit quit (tray menu): shut down in order, then exit
+                    // again once the shutdown has finished
+                    Some(code) if !shutdown::is_finished() => {
+                        api.prevent_exit();
+                        if shutdown::begin() {
+                            let app = app_handle.clone();
+                            tauri::async_runtime::spawn(async move {
+                                let server_handle = app.state::<server::ServerHandle>();
+                                let server_state = app.state::<server::AppState>();
+                                let scheduler = app.state::<scheduler::EpgScheduler>();
+                                let report = tokio::time::timeout(
+                                    shutdown::SHUTDOWN_TIMEOUT,
+                                    shutdown::shutdown(&server_handle, &server_state, &scheduler),
+                                )
+                                .await;
+                                if report.is_err() {
+                                    eprintln!("Shutdown timed out; exiting anyway");
+                                }
+                                shutdown::finish();
+                                app.exit(code);
+                            });
+                        }
+                    }
+                    Some(_) => {}
                 }
-                // Allow exit when code is Some (explicit quit from tray menu)
             }
         });
 }
+
+/// Run migrations and load persisted settings (the database startup phase)
+///
+/// Runs on a blocking thread before the HTTP server and scheduler start.
+pub(crate) fn initialize_database(mut conn: diesel::SqliteConnection) -> Result<(), String> {
+    let repair = db::repair::migrate_with_repair(&mut conn)
+        .map_err(|e| format!("Failed to run migrations: {}", e))?;
+    db::repair::log_report(&mut conn, &repair);
+
+    // Apply persisted settings (e.g. low resource mode) before any subsystem starts
+    load_runtime_settings(&mut conn);
+
+    // Log installed version changes (e.g. after an update)
+    commands::update::record_installed_version(&mut conn);
+
+    // Story 6-3: Log application startup event (AC #1)
+    let details = serde_json::json!({
+        "version": env!("CARGO_PKG_VERSION"),
+    });
+    let _ = commands::logs::log_event_internal(
+        &mut conn,
+        "info",
+        "system",
+        &format!("StreamForge v{} started", env!("CARGO_PKG_VERSION")),
+        Some(&details.to_string()),
+    );
+
+    Ok(())
+}
+
+/// Apply the settings that are kept in memory at runtime
+///
+/// Called at startup and after switching workspaces, so every subsystem
+/// follows the active database.
+pub(crate) fn load_runtime_settings(conn: &mut diesel::SqliteConnection) {
+    low_resource::load_from_db(conn);
+    server::buffer::load_ffmpeg_path(conn);
+    dns::load_from_db(conn);
+    xtream::request_headers::load_from_db(conn);
+    notifications::load_from_db(conn);
+    i18n::init(conn);
+}
+
+/// Start the EPG scheduler and apply the saved schedule (the scheduler startup phase)
+///
+/// Afterwards checks for a refresh missed while the app was closed.
+pub(crate) async fn start_epg_scheduler(
+    epg_scheduler: scheduler::EpgScheduler,
+    pool: db::DbPool,
+    app_data_dir: std::path::PathBuf,
+    stream_manager: std::sync::Arc<server::stream::StreamManager>,
+) {
+    use startup::StartupPhase;
+
+    startup::begin(StartupPhase::Scheduler);
+
+    // Set up database pool
+    epg_scheduler.set_db_pool(pool).await;
+    epg_scheduler.set_app_data_dir(app_data_dir).await;
+    epg_scheduler.set_stream_manager(stream_manager).await;
+
+    // Start the scheduler
+    if let Err(e) = epg_scheduler.start().await {
+        tracing::error!(
+            "CRITICAL: Failed to start EPG scheduler: {}. Automatic EPG refresh will not work!",
+            e
+        );
+        eprintln!("Failed to start EPG scheduler: {}. Automatic EPG refresh will not work!", e);
+        startup::finish(StartupPhase::Scheduler, Err(format!("Failed to start EPG scheduler: {}", e)));
+        return;
+    }
+    tracing::info!("EPG scheduler started successfully");
+
+    // Get a temporary connection to read schedule settings
+    let Some(mut conn) = epg_scheduler.get_db_connection().await else {
+        tracing::error!(
+            "CRITICAL: Failed to get database connection for scheduler initialization. Automatic EPG refresh will not work!"
+        );
+        eprintln!("Failed to get database connection for scheduler initialization");
+        startup::finish(
+            StartupPhase::Scheduler,
+            Err("Failed to get database connection for scheduler initialization".to_string()),
+        );
+        return;
+    };
+    let schedule = scheduler::get_epg_schedule(&mut conn);
+    let rescan = scheduler::get_channel_rescan_schedule(&mut conn);
+    drop(conn);
+
+    if let Err(e) = epg_scheduler.update_rescan_schedule(&rescan).await {
+        tracing::error!("Failed to schedule channel re-scan: {}", e);
+    }
+
+    // Set enabled state
+    if let Err(e) = epg_scheduler.set_enabled(schedule.enabled).await {
+        tracing::error!("Failed to set scheduler enabled state: {}. Using default enabled state.", e);
+        eprintln!("Failed to set scheduler enabled state: {}", e);
+    }
+
+    // Update schedule if enabled
+    let configured = if schedule.enabled {
+        match epg_scheduler.update_schedule(schedule.hour, schedule.minute).await {
+            Err(e) => {
+                tracing::error!(
+                    "Failed to configure EPG schedule ({:02}:{:02}): {}. Automatic refresh will not work!",
+                    schedule.hour,
+                    schedule.minute,
+                    e
+                );
+                eprintln!("Failed to update EPG schedule: {}", e);
+                Err(format!("Failed to configure EPG schedule: {}", e))
+            }
+            Ok(_) => {
+                tracing::info!(
+                    "EPG scheduler configured: refresh at {:02}:{:02} daily",
+                    schedule.hour,
+                    schedule.minute
+                );
+                Ok(())
+            }
+        }
+    } else {
+        tracing::info!("EPG automatic refresh is disabled in settings");
+        Ok(())
+    };
+    startup::finish(StartupPhase::Scheduler, configured);
+
+    // Wait 7 seconds after scheduler initialization before checking for missed refresh
+    // This ensures the schedule is fully configured before checking for missed refreshes
+    tokio::time::sleep(tokio::time::Duration::from_secs(7)).await;
+
+    // Check for missed refresh and trigger if needed
+    // This must happen AFTER schedule is configured to detect missed refreshes correctly
+    scheduler::check_and_trigger_missed_refresh(&epg_scheduler).await;
+}
@@ -0,0 +1,352 @@
+//! Lineup staging
+//!
+//! With staging on, channel enables, ordering and channel numbers edited in
+//! the app form a draft in `xmltv_channel_settings`, while the
+//! Plex-facing outputs (`/playlist.m3u`, `/epg.xml`, `/lineup.json` and stream
+//! requests) keep serving the snapshot in `lineup_published`. Publishing copies
+//! the draft into the snapshot in one transaction, so Plex never sees a
+//! half-edited lineup; discarding restores the draft from the snapshot.
+//!
+//! Outputs read the `published_channel_settings` view, which follows the draft
+//! directly while staging is off. Stream mappings are not staged.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+
+use crate::db::schema::{lineup_published, settings, xmltv_channel_settings, xmltv_channels};
+use crate::db::Setting;
+
+/// Settings key enabling lineup staging ("true"/"false")
+pub const LINEUP_STAGING_KEY: &str = "lineup_staging_enabled";
+
+/// Settings key holding when the lineup was last published (RFC 3339)
+const PUBLISHED_AT_KEY: &str = "lineup_published_at";
+
+/// A channel whose draft differs from the published lineup
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupDraftChange {
+    pub xmltv_channel_id: i32,
+    pub display_name: String,
+    pub published_enabled: bool,
+    pub draft_enabled: bool,
+    pub published_order: Option<i32>,
+    pub draft_order: Option<i32>,
+    pub published_number: Option<i32>,
+    pub draft_number: Option<i32>,
+}
+
+/// Staging mode and pending draft changes
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupStagingStatus {
+    pub enabled: bool,
+    pub published_at: Option<String>,
+    pub changes: Vec<LineupDraftChange>,
+}
+
+/// (is_enabled, plex_display_order, channel_number) per XMLTV channel
+type LineupState = HashMap<i32, (bool, Option<i32>, Option<i32>)>;
+
+fn get_setting(conn: &mut SqliteConnection, key: &str) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(key))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+}
+
+fn set_setting(conn: &mut SqliteConnection, key: &str, value: String) -> QueryResult<()> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(key, value))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Whether lineup staging is on
+pub fn is_enabled(conn: &mut SqliteConnection) -> bool {
+    get_setting(conn, LINEUP_STAGING_KEY).as_deref() == Some("true")
+}
+
+fn draft_state(conn: &mut SqliteConnection) -> QueryResult<LineupState> {
+    Ok(xmltv_channel_settings::table
+        .select((
+            xmltv_channel_settings::xmltv_channel_id,
+            xmltv_channel_settings::is_enabled,
+            xmltv_channel_settings::plex_display_order,
+            xmltv_channel_settings::channel_number,
+        ))
+        .load::<(i32, Option<i32>, Option<i32>, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, enabled, order, number)| (id, (enabled == Some(1), order, number)))
+        .collect())
+}
+
+fn published_state(conn: &mut SqliteConnection) -> QueryResult<LineupState> {
+    Ok(lineup_published::table
+        .select((
+            lineup_published::xmltv_channel_id,
+            lineup_published::is_enabled,
+            lineup_published::plex_display_order,
+            lineup_published::channel_number,
+        ))
+        .load::<(i32, i32, Option<i32>, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, enabled, order, number)| (id, (enabled == 1, order, number)))
+        .collect())
+}
+
+/// Channels whose draft differs from the published lineup (empty while staging is off)
+///
+/// The order and number of a channel only count while it is enabled, since
+/// disabled channels are not in the lineup.
+pub fn draft_changes(conn: &mut SqliteConnection) -> QueryResult<Vec<LineupDraftChange>> {
+    if !is_enabled(conn) {
+        return Ok(Vec::new());
+    }
+
+    let draft = draft_state(conn)?;
+    let published = published_state(conn)?;
+    let ids: BTreeSet<i32> = draft.keys().chain(published.keys()).copied().collect();
+
+    let mut changed = Vec::new();
+    for id in ids {
+        let published = published.get(&id).copied().unwrap_or((false, None, None));
+        let draft = draft.get(&id).copied().unwrap_or((false, None, None));
+        let differs = published.0 != draft.0
+            || (draft.0 && (published.1 != draft.1 || published.2 != draft.2));
+        if differs {
+            changed.push((id, published, draft));
+        }
+    }
+    if changed.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let changed_ids: Vec<i32> = changed.iter().map(|c| c.0).collect();
+    let names: HashMap<i32, String> = xmltv_channels::table
+        .filter(xmltv_channels::id.eq_any(&changed_ids))
+        .select((
+            xmltv_channels::id.assume_not_null(),
+            xmltv_channels::display_name,
+        ))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut changes: Vec<LineupDraftChange> = changed
+        .into_iter()
+        .map(|(id, published, draft)| LineupDraftChange {
+            xmltv_channel_id: id,
+            display_name: names.get(&id).cloned().unwrap_or_default(),
+            published_enabled: published.0,
+            draft_enabled: draft.0,
+            published_order: published.1,
+            draft_order: draft.1,
+            published_number: published.2,
+            draft_number: draft.2,
+        })
+        .collect();
+    changes.sort_by(|a, b| a.display_name.cmp(&b.display_name));
+    Ok(changes)
+}
+
+/// Current staging mode and pending changes
+pub fn status(conn: &mut SqliteConnection) -> QueryResult<LineupStagingStatus> {
+    let enabled = is_enabled(conn);
+    Ok(LineupStagingStatus {
+        enabled,
+        published_at: get_setting(conn, PUBLISHED_AT_KEY),
+        changes: draft_changes(conn)?,
+    })
+}
+
+/// Replace the published snapshot with the current draft
+fn snapshot(conn: &mut SqliteConnection) -> QueryResult<()> {
+    diesel::delete(lineup_published::table).execute(conn)?;
+    diesel::sql_query(
+        "INSERT INTO lineup_published (xmltv_channel_id, is_enabled, plex_display_order, channel_number)
+         SELECT xmltv_channel_id, COALESCE(is_enabled, 0), plex_display_order, channel_number
+         FROM xmltv_channel_settings",
+    )
+    .execute(conn)?;
+    set_setting(conn, PUBLISHED_AT_KEY, chrono::Utc::now().to_rfc3339())
+}
+
+/// Turn staging on or off
+///
+/// Turning it on publishes the current lineup as the starting snapshot.
+/// Turning it off makes the draft live immediately.
+pub fn set_enabled(conn: &mut SqliteConnection, enabled: bool) -> QueryResult<()> {
+    conn.transaction(|conn| {
+        if enabled && !is_enabled(conn) {
+            snapshot(conn)?;
+        }
+        set_setting(conn, LINEUP_STAGING_KEY, enabled.to_string())
+    })
+}
+
+/// Publish the draft, returning the number of channels that changed
+pub fn publish(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let changes = draft_changes(conn)?.len();
+        snapshot(conn)?;
+        Ok(changes)
+    })
+}
+
+/// Restore the draft from the published lineup, returning the number of channels reverted
+pub fn discard(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let changes = draft_changes(conn)?.len();
+        diesel::sql_query(
+            "UPDATE xmltv_channel_settings SET
+                is_enabled = COALESCE((
+                    SELECT p.is_enabled FROM lineup_published p
+                    WHERE p.xmltv_channel_id = xmltv_channel_settings.xmltv_channel_id
+                ), 0),
+                plex_display_order = (
+                    SELECT p.plex_display_order FROM lineup_published p
+                    WHERE p.xmltv_channel_id = xmltv_channel_settings.xmltv_channel_id
+                ),
+                channel_number = (
+                    SELECT p.channel_number FROM lineup_published p
+                    WHERE p.xmltv_channel_id = xmltv_channel_settings.xmltv_channel_id
+                )",
+        )
+        .execute(conn)?;
+        diesel::sql_query(
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order, channel_number)
+             SELECT p.xmltv_channel_id, p.is_enabled, p.plex_display_order, p.channel_number
+             FROM lineup_published p
+             WHERE NOT EXISTS (
+                 SELECT 1 FROM xmltv_channel_settings s
+                 WHERE s.xmltv_channel_id = p.xmltv_channel_id
+             )",
+        )
+        .execute(conn)?;
+        Ok(changes)
+    })
+}
+
+/// Whether a channel is enabled in the lineup Plex sees
+pub fn is_channel_published(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+) -> QueryResult<bool> {
+    if is_enabled(conn) {
+        lineup_published::table
+            .filter(lineup_published::xmltv_channel_id.eq(xmltv_channel_id))
+            .select(lineup_published::is_enabled)
+            .first::<i32>(conn)
+            .optional()
+            .map(|enabled| enabled == Some(1))
+    } else {
+        xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(xmltv_channel_id))
+            .select(xmltv_channel_settings::is_enabled)
+            .first::<Option<i32>>(conn)
+            .optional()
+            .map(|enabled| enabled.flatten() == Some(1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_connection() -> SqliteConnection {
+        let mut conn = crate::db::test_connection();
+        for id in 1..=3 {
+            diesel::sql_query(format!(
+                "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES ({id}, 1, 'ch{id}', 'Channel {id}')"
+            ))
+            .execute(&mut conn)
+            .unwrap();
+            diesel::sql_query(format!(
+                "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order) VALUES ({id}, {}, {})",
+                (id != 3) as i32,
+                id - 1
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        }
+        conn
+    }
+
+    fn set_draft(conn: &mut SqliteConnection, id: i32, enabled: bool, order: i32) {
+        diesel::update(
+            xmltv_channel_settings::table.filter(xmltv_channel_settings::xmltv_channel_id.eq(id)),
+        )
+        .set((
+            xmltv_channel_settings::is_enabled.eq(enabled as i32),
+            xmltv_channel_settings::plex_display_order.eq(order),
+        ))
+        .execute(conn)
+        .unwrap();
+    }
+
+    fn published_view_count(conn: &mut SqliteConnection) -> i64 {
+        #[derive(QueryableByName)]
+        struct Count {
+            #[diesel(sql_type = diesel::sql_types::BigInt)]
+            count: i64,
+        }
+        diesel::sql_query(
+            "SELECT COUNT(*) AS count FROM published_channel_settings WHERE is_enabled = 1",
+        )
+        .get_result::<Count>(conn)
+        .unwrap()
+        .count
+    }
+
+    #[test]
+    fn test_publish_and_discard() {
+        let mut conn = test_connection();
+        assert_eq!(published_view_count(&mut conn), 2);
+
+        set_enabled(&mut conn, true).unwrap();
+        set_draft(&mut conn, 3, true, 0);
+        set_draft(&mut conn, 1, false, 0);
+
+        // Plex keeps seeing the published lineup
+        assert_eq!(published_view_count(&mut conn), 2);
+        assert!(is_channel_published(&mut conn, 1).unwrap());
+        assert!(!is_channel_published(&mut conn, 3).unwrap());
+
+        let changes = draft_changes(&mut conn).unwrap();
+        let ids: Vec<i32> = changes.iter().map(|c| c.xmltv_channel_id).collect();
+        assert_eq!(ids, vec![1, 3]);
+
+        assert_eq!(discard(&mut conn).unwrap(), 2);
+        assert!(draft_changes(&mut conn).unwrap().is_empty());
+        assert_eq!(draft_state(&mut conn).unwrap()[&1], (true, Some(0), None));
+
+        set_draft(&mut conn, 3, true, 2);
+        assert_eq!(publish(&mut conn).unwrap(), 1);
+        assert_eq!(published_view_count(&mut conn), 3);
+        assert!(is_channel_published(&mut conn, 3).unwrap());
+
+        // Channel numbers are staged too
+        crate::channel_numbers::set_channel_number(&mut conn, 2, Some(200)).unwrap();
+        let changes = draft_changes(&mut conn).unwrap();
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].draft_number, Some(200));
+        assert_eq!(publish(&mut conn).unwrap(), 1);
+        assert_eq!(published_state(&mut conn).unwrap()[&2].2, Some(200));
+    }
+
+    #[test]
+    fn test_disabling_staging_makes_draft_live() {
+        let mut conn = test_connection();
+        set_enabled(&mut conn, true).unwrap();
+        set_draft(&mut conn, 2, false, 1);
+        assert!(is_channel_published(&mut conn, 2).unwrap());
+
+        set_enabled(&mut conn, false).unwrap();
+        assert!(!is_channel_published(&mut conn, 2).unwrap());
+        assert_eq!(published_view_count(&mut conn), 1);
+        assert!(draft_changes(&mut conn).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,199 @@
+//! Diagnostic log files
+//!
+//! `tracing` output goes to daily rotating files in `<app data>/logs` (the
+//! last `MAX_LOG_FILES` days are kept) as well as to stderr. The level
+//! follows the log verbosity setting: verbose logs info (and debug for
+//! StreamForge itself), minimal only warnings and errors. `RUST_LOG`, when
+//! set, overrides the setting.
+//!
+//! These files are separate from the event log in the database, which holds
+//! the user-facing events shown in the app.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::util::SubscriberInitExt;
+use tracing_subscriber::{reload, EnvFilter, Registry};
+
+/// Folder under the app data directory holding the log files
+const LOG_DIR_NAME: &str = "logs";
+
+/// Log file names are `streamforge.<date>.log`
+const LOG_FILE_PREFIX: &str = "streamforge";
+const LOG_FILE_SUFFIX: &str = "log";
+
+/// Daily files kept before the oldest is deleted
+const MAX_LOG_FILES: usize = 7;
+
+/// Log folder in use, set once by `init`
+static LOG_DIR: OnceLock<PathBuf> = OnceLock::new();
+
+/// Flushes buffered lines when the process exits
+static GUARD: OnceLock<WorkerGuard> = OnceLock::new();
+
+/// Swaps the level filter when the verbosity setting changes
+static FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
+/// Start writing log files under `app_data_dir` at the given verbosity
+///
+/// Only the first call has any effect. If the log folder cannot be created
+/// the app logs to stderr only.
+pub fn init(app_data_dir: &Path, verbosity: &str) {
+    if LOG_DIR.get().is_some() {
+        return;
+    }
+    let dir = log_dir(app_data_dir);
+    let _ = LOG_DIR.set(dir.clone());
+
+    let (filter, handle) = reload::Layer::new(filter_for(verbosity));
+    let stderr = tracing_subscriber::fmt::layer().with_writer(io::stderr);
+
+    let appender = fs::create_dir_all(&dir)
+        .map_err(|e| e.to_string())
+        .and_then(|_| {
+            RollingFileAppender::builder()
+                .rotation(Rotation::DAILY)
+                .filename_prefix(LOG_FILE_PREFIX)
+                .filename_suffix(LOG_FILE_SUFFIX)
+                .max_log_files(MAX_LOG_FILES)
+                .build(&dir)
+                .map_err(|e| e.to_string())
+        });
+    let file = match appender {
+        Ok(appender) => {
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+            let _ = GUARD.set(guard);
+            Some(
+                tracing_subscriber::fmt::layer()
+                    .with_ansi(false)
+                    .with_writer(writer),
+            )
+        }
+        Err(e) => {
+            eprintln!("Cannot write log files to {}: {}", dir.display(), e);
+            None
+        }
+    };
+
+    if tracing_subscriber::registry()
+        .with(filter)
+        .with(stderr)
+        .with(file)
+        .try_init()
+        .is_ok()
+    {
+        let _ = FILTER.set(handle);
+    }
+}
+
+/// Change the level after the log verbosity setting changed
+pub fn apply_verbosity(verbosity: &str) {
+    if let Some(handle) = FILTER.get() {
+        let _ = handle.reload(filter_for(verbosity));
+    }
+}
+
+/// Read the log verbosity setting and apply it
+///
+/// Called after switching workspaces.
+pub fn load_from_db(conn: &mut diesel::SqliteConnection) {
+    apply_verbosity(&stored_verbosity(conn));
+}
+
+/// Log verbosity setting, or the default if it cannot be read
+pub fn stored_verbosity(conn: &mut diesel::SqliteConnection) -> String {
+    crate::commands::logs::get_log_verbosity_internal(conn)
+        .unwrap_or_else(|_| crate::commands::logs::DEFAULT_LOG_VERBOSITY.to_string())
+}
+
+/// Level filter for a log verbosity setting ("minimal" or "verbose")
+fn filter_for(verbosity: &str) -> EnvFilter {
+    if let Ok(filter) = EnvFilter::try_from_default_env() {
+        return filter;
+    }
+    match verbosity {
+        "minimal" => EnvFilter::new("warn"),
+        _ => EnvFilter::new("info,streamforge_lib=debug"),
+    }
+}
+
+/// Log folder for an app data directory
+pub fn log_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(LOG_DIR_NAME)
+}
+
+/// Newest log file in `dir`, if any
+pub fn current_log_file(dir: &Path) -> Option<PathBuf> {
+    log_files(dir).ok()?.pop()
+}
+
+/// Log files in `dir`, oldest first
+///
+/// The date in the file name sorts chronologically.
+fn log_files(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|name| {
+                        name.starts_with(LOG_FILE_PREFIX) && name.ends_with(LOG_FILE_SUFFIX)
+                    })
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Zip the log files in `dir` into `destination`
+///
+/// Returns how many files were added.
+pub fn export_logs(dir: &Path, destination: &Path) -> io::Result<usize> {
+    let files = log_files(dir)?;
+    let mut zip = zip::ZipWriter::new(BufWriter::new(File::create(destination)?));
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    for path in &files {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        zip.start_file(name, options)?;
+        io::copy(&mut BufReader::new(File::open(path)?), &mut zip)?;
+    }
+    zip.finish()?;
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_logs_zips_log_files() {
+        let dir = std::env::temp_dir().join(format!("streamforge-logs-{}", uuid::Uuid::new_v4()));
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("streamforge.2026-03-01.log"), "first day\n").unwrap();
+        fs::write(dir.join("streamforge.2026-03-02.log"), "second day\n").unwrap();
+        fs::write(dir.join("notes.txt"), "not a log").unwrap();
+
+        assert_eq!(
+            current_log_file(&dir),
+            Some(dir.join("streamforge.2026-03-02.log"))
+        );
+
+        let destination = dir.join("export.zip");
+        assert_eq!(export_logs(&dir, &destination).unwrap(), 2);
+        let bytes = fs::read(&destination).unwrap();
+        assert!(bytes.starts_with(b"PK"));
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
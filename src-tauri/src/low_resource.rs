@@ -0,0 +1,157 @@
+//! Low resource mode
+//!
+//! A runtime toggle for ARM boards and other low-power devices. When enabled,
+//! subsystems trade throughput for a smaller memory and CPU footprint:
+//!
+//! - EPG refresh inserts programs in smaller batches and refreshes one source
+//!   at a time, even when a manual refresh overlaps the scheduled one
+//! - The `/epg.xml` cache expires sooner
+//! - Logo pre-warm downloads one logo at a time
+//! - Channel matching normalizes streams and scores channels on one thread
+//!   instead of the rayon pool
+//! - Provider speed tests, the only stream probing the app does, are refused
+//!
+//! The mode is stored in the settings table and mirrored in a process-wide
+//! flag so hot paths can read it without a database round-trip.
+
+use diesel::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::{Semaphore, SemaphorePermit};
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key for low resource mode ("true"/"false")
+pub const LOW_RESOURCE_MODE_KEY: &str = "low_resource_mode";
+
+/// Program insert batch size in normal / low resource mode
+const PROGRAM_BATCH_SIZE: usize = 500;
+const LOW_RESOURCE_PROGRAM_BATCH_SIZE: usize = 100;
+
+/// `/epg.xml` cache TTL in normal / low resource mode
+const EPG_CACHE_TTL: Duration = Duration::from_secs(300);
+const LOW_RESOURCE_EPG_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Concurrent logo downloads in normal / low resource mode
+const LOGO_PREWARM_CONCURRENCY: usize = 4;
+const LOW_RESOURCE_LOGO_PREWARM_CONCURRENCY: usize = 1;
+
+static LOW_RESOURCE_MODE: AtomicBool = AtomicBool::new(false);
+
+/// Serializes EPG source refreshes while low resource mode is on
+static EPG_REFRESH_SLOT: Semaphore = Semaphore::const_new(1);
+
+/// Whether low resource mode is active
+pub fn is_enabled() -> bool {
+    LOW_RESOURCE_MODE.load(Ordering::Relaxed)
+}
+
+/// Read the persisted setting and apply it to the process-wide flag
+///
+/// Called at startup and after switching workspaces.
+pub fn load_from_db(conn: &mut SqliteConnection) -> bool {
+    let enabled = settings::table
+        .filter(settings::key.eq(LOW_RESOURCE_MODE_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value == "true")
+        .unwrap_or(false);
+    LOW_RESOURCE_MODE.store(enabled, Ordering::Relaxed);
+    enabled
+}
+
+/// Persist the setting and apply it immediately
+pub fn set_enabled(conn: &mut SqliteConnection, enabled: bool) -> Result<(), diesel::result::Error> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(LOW_RESOURCE_MODE_KEY, enabled.to_string()))
+        .execute(conn)?;
+    LOW_RESOURCE_MODE.store(enabled, Ordering::Relaxed);
+    Ok(())
+}
+
+/// Number of programs inserted per statement during EPG refresh
+pub fn program_batch_size() -> usize {
+    if is_enabled() {
+        LOW_RESOURCE_PROGRAM_BATCH_SIZE
+    } else {
+        PROGRAM_BATCH_SIZE
+    }
+}
+
+/// How long a generated `/epg.xml` stays cached
+pub fn epg_cache_ttl() -> Duration {
+    if is_enabled() {
+        LOW_RESOURCE_EPG_CACHE_TTL
+    } else {
+        EPG_CACHE_TTL
+    }
+}
+
+/// Number of logos downloaded at once by the pre-warm job
+pub fn logo_prewarm_concurrency() -> usize {
+    if is_enabled() {
+        LOW_RESOURCE_LOGO_PREWARM_CONCURRENCY
+    } else {
+        LOGO_PREWARM_CONCURRENCY
+    }
+}
+
+/// Wait for the EPG refresh slot when low resource mode is on
+///
+/// Returns `None` (no waiting) in normal mode. Hold the permit for the
+/// duration of one source's download, parse, and insert.
+pub async fn acquire_epg_refresh_slot() -> Option<SemaphorePermit<'static>> {
+    if !is_enabled() {
+        return None;
+    }
+    EPG_REFRESH_SLOT.acquire().await.ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Mutex, MutexGuard};
+
+    /// Serializes tests that flip the process-wide flag
+    static MODE_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Holds the flag for one test and restores its previous value on drop
+    struct ModeGuard {
+        previous: bool,
+        _lock: MutexGuard<'static, ()>,
+    }
+
+    impl ModeGuard {
+        fn acquire() -> Self {
+            let lock = MODE_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+            Self {
+                previous: is_enabled(),
+                _lock: lock,
+            }
+        }
+    }
+
+    impl Drop for ModeGuard {
+        fn drop(&mut self) {
+            LOW_RESOURCE_MODE.store(self.previous, Ordering::Relaxed);
+        }
+    }
+
+    #[test]
+    fn test_set_and_load_from_db() {
+        let _guard = ModeGuard::acquire();
+        let mut conn = crate::db::test_connection();
+
+        assert!(!load_from_db(&mut conn));
+
+        set_enabled(&mut conn, true).unwrap();
+        assert!(is_enabled());
+        assert_eq!(program_batch_size(), LOW_RESOURCE_PROGRAM_BATCH_SIZE);
+        assert_eq!(epg_cache_ttl(), LOW_RESOURCE_EPG_CACHE_TTL);
+
+        set_enabled(&mut conn, false).unwrap();
+        assert!(!load_from_db(&mut conn));
+        assert_eq!(program_batch_size(), PROGRAM_BATCH_SIZE);
+    }
+}
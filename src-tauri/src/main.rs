@@ -2,5 +2,13 @@
 #![cfg_attr(not(debug_assertions), windows_subsystem = "windows")]
 
 fn main() {
+    // `--headless` runs only the server and scheduler (used by the system service)
+    if let Some(options) = streamforge_lib::service::headless_options(std::env::args()) {
+        if let Err(e) = streamforge_lib::service::run_headless(options) {
+            eprintln!("StreamForge headless mode failed: {}", e);
+            std::process::exit(1);
+        }
+        return;
+    }
     streamforge_lib::run();
 }
@@ -0,0 +1,281 @@
+//! Maintenance window for disruptive background jobs
+//!
+//! Scheduled jobs that change the lineup or load the database heavily (such as
+//! the channel policy run after a scheduled EPG refresh) are deferred until the
+//! configured window (e.g. 03:00–06:00 local time) is open and no stream
+//! sessions are active. Manually triggered actions are never deferred.
+//!
+//! With the window disabled, jobs run as soon as they are scheduled.
+
+use chrono::{NaiveTime, Timelike};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::clock::{Clock, SystemClock};
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::settings;
+use crate::db::{DbPool, Setting};
+use crate::server::stream::StreamManager;
+
+/// Settings key for the maintenance window (JSON)
+pub const MAINTENANCE_WINDOW_KEY: &str = "maintenance_window";
+
+/// How often a deferred job re-checks the window and active sessions
+const POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Stream manager used to check for active sessions (set once at startup)
+static STREAM_MANAGER: OnceLock<Arc<StreamManager>> = OnceLock::new();
+
+/// Jobs currently waiting for the window, so a job is never queued twice
+static PENDING_JOBS: Mutex<Option<HashSet<&'static str>>> = Mutex::new(None);
+
+/// Configured maintenance window
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MaintenanceWindow {
+    pub enabled: bool,
+    /// Local start time, "HH:MM"
+    pub start: String,
+    /// Local end time, "HH:MM" (before `start` for windows spanning midnight)
+    pub end: String,
+}
+
+impl Default for MaintenanceWindow {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            start: "03:00".to_string(),
+            end: "06:00".to_string(),
+        }
+    }
+}
+
+impl MaintenanceWindow {
+    /// Check that both times are valid "HH:MM" values and differ
+    pub fn validate(&self) -> Result<(), String> {
+        let start = parse_time(&self.start)?;
+        let end = parse_time(&self.end)?;
+        if start == end {
+            return Err("Maintenance window start and end must differ".to_string());
+        }
+        Ok(())
+    }
+
+    /// Whether `time` falls inside the window (start inclusive, end exclusive)
+    ///
+    /// A disabled window contains every time.
+    pub fn contains(&self, time: NaiveTime) -> bool {
+        if !self.enabled {
+            return true;
+        }
+        let (Ok(start), Ok(end)) = (parse_time(&self.start), parse_time(&self.end)) else {
+            return true;
+        };
+        if start < end {
+            start <= time && time < end
+        } else {
+            time >= start || time < end
+        }
+    }
+}
+
+/// Parse an "HH:MM" time of day
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value.trim(), "%H:%M")
+        .map_err(|_| format!("Invalid time '{}': expected HH:MM", value))
+}
+
+/// Register the stream manager used to detect active sessions
+pub fn init(stream_manager: Arc<StreamManager>) {
+    let _ = STREAM_MANAGER.set(stream_manager);
+}
+
+/// Number of active stream sessions (0 before `init`)
+pub fn active_streams() -> usize {
+    STREAM_MANAGER
+        .get()
+        .map(|manager| manager.active_count())
+        .unwrap_or(0)
+}
+
+/// Read the maintenance window (defaults if unset or invalid)
+pub fn get_window(conn: &mut SqliteConnection) -> MaintenanceWindow {
+    settings::table
+        .filter(settings::key.eq(MAINTENANCE_WINDOW_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the maintenance window
+pub fn set_window(conn: &mut SqliteConnection, window: &MaintenanceWindow) -> Result<(), String> {
+    window.validate()?;
+    let value = serde_json::to_string(window).map_err(|e| e.to_string())?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(MAINTENANCE_WINDOW_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save maintenance window: {}", e))?;
+    Ok(())
+}
+
+/// Whether a disruptive job may run right now
+pub fn may_run_now(window: &MaintenanceWindow) -> bool {
+    may_run_at(window, &SystemClock)
+}
+
+/// Whether a disruptive job may run at the clock's current local time
+pub fn may_run_at(window: &MaintenanceWindow, clock: &dyn Clock) -> bool {
+    !window.enabled || (window.contains(local_time(clock)) && active_streams() == 0)
+}
+
+fn local_time(clock: &dyn Clock) -> NaiveTime {
+    let now = clock.now();
+    NaiveTime::from_hms_opt(now.hour(), now.minute(), 0).unwrap_or_default()
+}
+
+/// Wait until the maintenance window allows a disruptive job to run
+///
+/// While the window is closed or streams are active the job is deferred and
+/// re-checked every `POLL_INTERVAL`; the window is re-read each time so
+/// configuration changes apply to waiting jobs. Returns false without waiting
+/// when a job with the same name is already waiting.
+pub async fn wait_for_window(pool: &DbPool, job: &'static str) -> bool {
+    {
+        let mut pending = PENDING_JOBS.lock().unwrap_or_else(|e| e.into_inner());
+        if !pending.get_or_insert_with(HashSet::new).insert(job) {
+            tracing::info!(
+                "Maintenance job '{}' is already waiting for the window",
+                job
+            );
+            return false;
+        }
+    }
+
+    let mut deferred = false;
+    loop {
+        let window = match pool.get() {
+            Ok(mut conn) => get_window(&mut conn),
+            Err(_) => MaintenanceWindow::default(),
+        };
+        if may_run_now(&window) {
+            break;
+        }
+        if !deferred {
+            deferred = true;
+            let reason = if window.contains(local_time(&SystemClock)) {
+                format!("{} active stream(s)", active_streams())
+            } else {
+                format!("outside {}–{}", window.start, window.end)
+            };
+            tracing::info!("Deferring maintenance job '{}': {}", job, reason);
+            if let Ok(mut conn) = pool.get() {
+                let details = serde_json::json!({ "job": job, "reason": reason });
+                let _ = log_event_internal(
+                    &mut conn,
+                    "info",
+                    "system",
+                    &format!("Deferred '{}' until the maintenance window", job),
+                    Some(&details.to_string()),
+                );
+            }
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+
+    if let Some(pending) = PENDING_JOBS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .as_mut()
+    {
+        pending.remove(job);
+    }
+    if deferred {
+        tracing::info!("Running deferred maintenance job '{}'", job);
+    }
+    true
+}
+
+/// Run a disruptive job once the maintenance window allows it
+///
+/// Runs on a background task after `wait_for_window`. A job already waiting
+/// under the same name is not queued again.
+pub fn run_in_window<F>(pool: DbPool, job: &'static str, run: F)
+where
+    F: FnOnce(&mut SqliteConnection) + Send + 'static,
+{
+    tauri::async_runtime::spawn(async move {
+        if !wait_for_window(&pool, job).await {
+            return;
+        }
+        match pool.get() {
+            Ok(mut conn) => run(&mut conn),
+            Err(e) => tracing::error!("Maintenance job '{}' skipped: {}", job, e),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(start: &str, end: &str) -> MaintenanceWindow {
+        MaintenanceWindow {
+            enabled: true,
+            start: start.to_string(),
+            end: end.to_string(),
+        }
+    }
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    #[test]
+    fn test_contains_same_day_window() {
+        let w = window("03:00", "06:00");
+        assert!(w.contains(at(3, 0)));
+        assert!(w.contains(at(5, 59)));
+        assert!(!w.contains(at(6, 0)));
+        assert!(!w.contains(at(20, 0)));
+
+        let disabled = MaintenanceWindow::default();
+        assert!(disabled.contains(at(20, 0)));
+    }
+
+    #[test]
+    fn test_contains_window_spanning_midnight() {
+        let w = window("23:30", "02:00");
+        assert!(w.contains(at(23, 45)));
+        assert!(w.contains(at(1, 0)));
+        assert!(!w.contains(at(2, 0)));
+        assert!(!w.contains(at(12, 0)));
+    }
+
+    #[test]
+    fn test_may_run_follows_the_clock() {
+        use crate::clock::MockClock;
+
+        let w = window("03:00", "06:00");
+        // 01:59 UTC is 02:59 local at UTC+1
+        let clock = MockClock::at("2026-03-04T02:59:00+01:00");
+        assert!(!may_run_at(&w, clock.as_ref()));
+        clock.advance(chrono::Duration::minutes(1));
+        assert!(may_run_at(&w, clock.as_ref()));
+        clock.advance(chrono::Duration::hours(3));
+        assert!(!may_run_at(&w, clock.as_ref()));
+        assert!(may_run_at(&MaintenanceWindow::default(), clock.as_ref()));
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(window("03:00", "06:00").validate().is_ok());
+        assert!(window("3:00", "25:00").validate().is_err());
+        assert!(window("03:00", "03:00").validate().is_err());
+        assert!(window("soon", "06:00").validate().is_err());
+    }
+}
@@ -0,0 +1,441 @@
+//! Mapping conflicts
+//!
+//! A stream that is the primary stream of several XMLTV channels is opened
+//! once per channel, so each of those channels takes its own tuner slot on the
+//! same provider connection. This module detects such conflicts and resolves
+//! them by keeping one owner channel and demoting the stream to a backup
+//! everywhere else (promoting that channel's next backup in its place).
+//!
+//! The `mapping_conflict_policy` setting decides what happens when a new
+//! conflict is created: `allow` ignores it, `warn` (default) logs a warning,
+//! and `prevent` rejects manual primary assignments and resolves conflicts
+//! left by auto-matching.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{
+    channel_mappings, settings, xmltv_channel_settings, xmltv_channels, xtream_channels,
+};
+use crate::db::Setting;
+
+/// Settings key holding the conflict policy
+pub const MAPPING_CONFLICT_POLICY_KEY: &str = "mapping_conflict_policy";
+
+/// What to do when a stream becomes primary for more than one channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConflictPolicy {
+    Allow,
+    #[default]
+    Warn,
+    Prevent,
+}
+
+impl ConflictPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConflictPolicy::Allow => "allow",
+            ConflictPolicy::Warn => "warn",
+            ConflictPolicy::Prevent => "prevent",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "allow" => Some(ConflictPolicy::Allow),
+            "warn" => Some(ConflictPolicy::Warn),
+            "prevent" => Some(ConflictPolicy::Prevent),
+            _ => None,
+        }
+    }
+}
+
+/// A channel using the conflicting stream as its primary
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConflictOwner {
+    pub mapping_id: i32,
+    pub xmltv_channel_id: i32,
+    pub display_name: String,
+    pub is_enabled: bool,
+    pub is_manual: bool,
+    pub match_confidence: Option<f32>,
+}
+
+/// A stream that is the primary stream of several channels
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingConflict {
+    pub xtream_channel_id: i32,
+    pub stream_name: String,
+    pub channels: Vec<ConflictOwner>,
+}
+
+/// (mapping_id, xtream_channel_id, xmltv_channel_id, display_name, stream_name,
+/// is_manual, match_confidence)
+type PrimaryRow = (i32, i32, i32, String, String, Option<i32>, Option<f32>);
+
+/// Load the conflict policy (default `warn`)
+pub fn load_policy(conn: &mut SqliteConnection) -> ConflictPolicy {
+    settings::table
+        .filter(settings::key.eq(MAPPING_CONFLICT_POLICY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| ConflictPolicy::parse(&value))
+        .unwrap_or_default()
+}
+
+/// Save the conflict policy
+pub fn save_policy(conn: &mut SqliteConnection, policy: ConflictPolicy) -> QueryResult<()> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(MAPPING_CONFLICT_POLICY_KEY, policy.as_str()))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Find all streams that are primary for more than one channel
+pub fn find_conflicts(conn: &mut SqliteConnection) -> QueryResult<Vec<MappingConflict>> {
+    let rows: Vec<PrimaryRow> = channel_mappings::table
+        .inner_join(xmltv_channels::table)
+        .inner_join(xtream_channels::table)
+        .filter(channel_mappings::is_primary.eq(1))
+        .order((
+            channel_mappings::xtream_channel_id.asc(),
+            channel_mappings::xmltv_channel_id.asc(),
+        ))
+        .select((
+            channel_mappings::id.assume_not_null(),
+            channel_mappings::xtream_channel_id,
+            channel_mappings::xmltv_channel_id,
+            xmltv_channels::display_name,
+            xtream_channels::name,
+            channel_mappings::is_manual,
+            channel_mappings::match_confidence,
+        ))
+        .load(conn)?;
+
+    let enabled: HashSet<i32> = xmltv_channel_settings::table
+        .filter(xmltv_channel_settings::is_enabled.eq(1))
+        .select(xmltv_channel_settings::xmltv_channel_id)
+        .load::<i32>(conn)?
+        .into_iter()
+        .collect();
+
+    let mut by_stream: BTreeMap<i32, MappingConflict> = BTreeMap::new();
+    for (mapping_id, stream_id, xmltv_channel_id, display_name, stream_name, manual, confidence) in
+        rows
+    {
+        by_stream
+            .entry(stream_id)
+            .or_insert_with(|| MappingConflict {
+                xtream_channel_id: stream_id,
+                stream_name,
+                channels: Vec::new(),
+            })
+            .channels
+            .push(ConflictOwner {
+                mapping_id,
+                xmltv_channel_id,
+                display_name,
+                is_enabled: enabled.contains(&xmltv_channel_id),
+                is_manual: manual == Some(1),
+                match_confidence: confidence,
+            });
+    }
+
+    Ok(by_stream
+        .into_values()
+        .filter(|conflict| conflict.channels.len() > 1)
+        .collect())
+}
+
+/// Names of the other channels already using a stream as their primary
+pub fn other_owners(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    xtream_channel_id: i32,
+) -> QueryResult<Vec<String>> {
+    channel_mappings::table
+        .inner_join(xmltv_channels::table)
+        .filter(channel_mappings::xtream_channel_id.eq(xtream_channel_id))
+        .filter(channel_mappings::xmltv_channel_id.ne(xmltv_channel_id))
+        .filter(channel_mappings::is_primary.eq(1))
+        .order(xmltv_channels::display_name.asc())
+        .select(xmltv_channels::display_name)
+        .load(conn)
+}
+
+/// Apply the conflict policy before making a stream a channel's primary
+///
+/// Returns an error under `prevent` when another channel already uses the
+/// stream as its primary; logs a warning under `warn`.
+pub fn check_primary_assignment(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    xtream_channel_id: i32,
+) -> Result<(), String> {
+    let policy = load_policy(conn);
+    if policy == ConflictPolicy::Allow {
+        return Ok(());
+    }
+
+    let owners = other_owners(conn, xmltv_channel_id, xtream_channel_id)
+        .map_err(|e| format!("Failed to check mapping conflicts: {}", e))?;
+    if owners.is_empty() {
+        return Ok(());
+    }
+
+    let message = format!(
+        "Stream is already the primary stream of {}",
+        owners.join(", ")
+    );
+    if policy == ConflictPolicy::Prevent {
+        return Err(message);
+    }
+
+    let details = serde_json::json!({
+        "xmltvChannelId": xmltv_channel_id,
+        "xtreamChannelId": xtream_channel_id,
+        "otherChannels": owners,
+    });
+    let _ = log_event_internal(
+        conn,
+        "warn",
+        "match",
+        &format!("Mapping conflict: {}", message),
+        Some(&details.to_string()),
+    );
+    Ok(())
+}
+
+/// Demote a stream to the last backup of a channel
+///
+/// The channel's first backup that is not primary elsewhere becomes its new
+/// primary. Without such a backup the channel is left with no primary stream.
+fn demote_stream(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    xtream_channel_id: i32,
+) -> QueryResult<()> {
+    let mut mappings: Vec<(i32, i32)> = channel_mappings::table
+        .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+        .order((
+            channel_mappings::stream_priority.asc(),
+            channel_mappings::is_primary.desc(),
+        ))
+        .select((
+            channel_mappings::id.assume_not_null(),
+            channel_mappings::xtream_channel_id,
+        ))
+        .load(conn)?;
+
+    let primary_elsewhere: HashSet<i32> = channel_mappings::table
+        .filter(channel_mappings::xmltv_channel_id.ne(xmltv_channel_id))
+        .filter(channel_mappings::is_primary.eq(1))
+        .select(channel_mappings::xtream_channel_id)
+        .load::<i32>(conn)?
+        .into_iter()
+        .collect();
+
+    let (demoted, mut rest): (Vec<_>, Vec<_>) = mappings
+        .drain(..)
+        .partition(|(_, stream_id)| *stream_id == xtream_channel_id);
+    if let Some(pos) = rest
+        .iter()
+        .position(|(_, stream_id)| !primary_elsewhere.contains(stream_id))
+    {
+        let promoted = rest.remove(pos);
+        rest.insert(0, promoted);
+    }
+    let has_primary = rest
+        .first()
+        .is_some_and(|(_, stream_id)| !primary_elsewhere.contains(stream_id));
+
+    for (priority, (mapping_id, _)) in rest.iter().chain(demoted.iter()).enumerate() {
+        let is_primary = has_primary && priority == 0;
+        diesel::update(channel_mappings::table.filter(channel_mappings::id.eq(Some(*mapping_id))))
+            .set((
+                channel_mappings::is_primary.eq(if is_primary { 1 } else { 0 }),
+                channel_mappings::stream_priority.eq(priority as i32),
+            ))
+            .execute(conn)?;
+    }
+    Ok(())
+}
+
+/// Resolve a conflict by keeping `owner_xmltv_channel_id` as the only
+/// channel using the stream as its primary
+///
+/// Returns the number of channels the stream was demoted on. Fails with
+/// `NotFound` if the owner does not use the stream as its primary.
+pub fn resolve_conflict(
+    conn: &mut SqliteConnection,
+    xtream_channel_id: i32,
+    owner_xmltv_channel_id: i32,
+) -> QueryResult<usize> {
+    conn.transaction(|conn| {
+        let owners: Vec<i32> = channel_mappings::table
+            .filter(channel_mappings::xtream_channel_id.eq(xtream_channel_id))
+            .filter(channel_mappings::is_primary.eq(1))
+            .select(channel_mappings::xmltv_channel_id)
+            .load(conn)?;
+        if !owners.contains(&owner_xmltv_channel_id) {
+            return Err(diesel::result::Error::NotFound);
+        }
+
+        let mut demoted = 0;
+        for xmltv_channel_id in owners {
+            if xmltv_channel_id != owner_xmltv_channel_id {
+                demote_stream(conn, xmltv_channel_id, xtream_channel_id)?;
+                demoted += 1;
+            }
+        }
+        Ok(demoted)
+    })
+}
+
+/// Pick the channel that keeps a stream: manual mappings first, then the
+/// highest match confidence, then the lowest channel ID
+fn preferred_owner(conflict: &MappingConflict) -> Option<i32> {
+    conflict
+        .channels
+        .iter()
+        .max_by(|a, b| {
+            a.is_manual
+                .cmp(&b.is_manual)
+                .then(
+                    a.match_confidence
+                        .unwrap_or(0.0)
+                        .total_cmp(&b.match_confidence.unwrap_or(0.0)),
+                )
+                .then(b.xmltv_channel_id.cmp(&a.xmltv_channel_id))
+        })
+        .map(|owner| owner.xmltv_channel_id)
+}
+
+/// Apply the conflict policy after auto-matching
+///
+/// Under `prevent` every conflict is resolved in favour of its preferred
+/// owner; under `warn` remaining conflicts are logged. Returns the number of
+/// conflicts found.
+pub fn enforce_after_matching(conn: &mut SqliteConnection) -> QueryResult<usize> {
+    let policy = load_policy(conn);
+    if policy == ConflictPolicy::Allow {
+        return Ok(0);
+    }
+
+    let conflicts = find_conflicts(conn)?;
+    if conflicts.is_empty() {
+        return Ok(0);
+    }
+
+    let streams: HashMap<i32, Vec<i32>> = conflicts
+        .iter()
+        .map(|c| {
+            (
+                c.xtream_channel_id,
+                c.channels.iter().map(|o| o.xmltv_channel_id).collect(),
+            )
+        })
+        .collect();
+    let details = serde_json::json!({ "policy": policy.as_str(), "conflicts": streams });
+
+    if policy == ConflictPolicy::Prevent {
+        for conflict in &conflicts {
+            if let Some(owner) = preferred_owner(conflict) {
+                resolve_conflict(conn, conflict.xtream_channel_id, owner)?;
+            }
+        }
+        let _ = log_event_internal(
+            conn,
+            "info",
+            "match",
+            &format!(
+                "Resolved {} mapping conflicts left by channel matching",
+                conflicts.len()
+            ),
+            Some(&details.to_string()),
+        );
+    } else {
+        let _ = log_event_internal(
+            conn,
+            "warn",
+            "match",
+            &format!(
+                "Channel matching left {} streams primary for more than one channel",
+                conflicts.len()
+            ),
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(conflicts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exec(conn: &mut SqliteConnection, sql: &str) {
+        diesel::sql_query(sql).execute(conn).unwrap();
+    }
+
+    fn primaries(conn: &mut SqliteConnection, xmltv_channel_id: i32) -> Vec<(i32, i32, i32)> {
+        channel_mappings::table
+            .filter(channel_mappings::xmltv_channel_id.eq(xmltv_channel_id))
+            .order(channel_mappings::stream_priority.asc())
+            .select((
+                channel_mappings::xtream_channel_id,
+                channel_mappings::is_primary.assume_not_null(),
+                channel_mappings::stream_priority.assume_not_null(),
+            ))
+            .load(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find_and_resolve_conflicts() {
+        let mut conn = crate::db::test_connection();
+        exec(&mut conn, "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://epg.local/guide.xml')");
+        exec(
+            &mut conn,
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES
+            (1, 1, 'news.us', 'News'), (2, 1, 'news.hd.us', 'News HD'), (3, 1, 'sport.us', 'Sport')",
+        );
+        crate::db::seed_account(&mut conn, 1, "A", "http://a.local");
+        exec(
+            &mut conn,
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+            (10, 1, 100, 'News'), (11, 1, 101, 'News HD'), (12, 1, 102, 'Sport')",
+        );
+        exec(&mut conn, "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES
+            (1, 10, 0.95, 0, 1, 0),
+            (2, 10, 0.90, 0, 1, 0), (2, 11, 0.85, 0, 0, 1),
+            (3, 12, 0.99, 0, 1, 0)");
+
+        let conflicts = find_conflicts(&mut conn).unwrap();
+        assert_eq!(conflicts.len(), 1);
+        assert_eq!(conflicts[0].xtream_channel_id, 10);
+        assert_eq!(conflicts[0].channels.len(), 2);
+        assert_eq!(preferred_owner(&conflicts[0]), Some(1));
+
+        assert_eq!(
+            other_owners(&mut conn, 3, 10).unwrap(),
+            vec!["News", "News HD"]
+        );
+
+        save_policy(&mut conn, ConflictPolicy::Prevent).unwrap();
+        assert!(check_primary_assignment(&mut conn, 3, 10).is_err());
+        assert!(check_primary_assignment(&mut conn, 3, 12).is_ok());
+
+        assert!(resolve_conflict(&mut conn, 10, 3).is_err());
+        assert_eq!(resolve_conflict(&mut conn, 10, 1).unwrap(), 1);
+        assert!(find_conflicts(&mut conn).unwrap().is_empty());
+        assert_eq!(primaries(&mut conn, 1), vec![(10, 1, 0)]);
+        assert_eq!(primaries(&mut conn, 2), vec![(11, 1, 0), (10, 0, 1)]);
+    }
+}
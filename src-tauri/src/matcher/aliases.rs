@@ -0,0 +1,271 @@
+//! Community Alias Packs
+//!
+//! Alias packs are community-maintained JSON files that map provider channel
+//! names to XMLTV tvg-ids, e.g. `"US: ESPN HD" -> "ESPN.us"`. They are
+//! imported into the alias dictionary (`channel_aliases`) with the pack they
+//! came from, so re-importing a pack replaces its aliases and removing it
+//! leaves other packs untouched.
+//!
+//! During matching an alias hit is treated like an EPG ID match: the Xtream
+//! stream's normalized name maps to the XMLTV channel's tvg-id.
+//!
+//! Pack format:
+//!
+//! ```json
+//! {
+//!   "name": "Community US",
+//!   "version": "2026.01",
+//!   "aliases": [
+//!     { "name": "US: ESPN HD", "tvgId": "ESPN.us" }
+//!   ]
+//! }
+//! ```
+
+use diesel::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use super::fuzzy::normalize_channel_name;
+use crate::db::schema::{alias_packs, channel_aliases};
+
+/// Maximum number of aliases accepted from one pack
+pub const MAX_ALIASES_PER_PACK: usize = 50_000;
+
+/// Maximum length of a pack name
+const MAX_PACK_NAME_LEN: usize = 100;
+
+/// Maximum length of a tvg-id
+const MAX_TVG_ID_LEN: usize = 200;
+
+/// Rows inserted per statement when importing aliases
+const ALIAS_INSERT_CHUNK_SIZE: usize = 500;
+
+/// Alias hints used during matching: normalized provider name -> lowercase tvg-ids
+pub type AliasHints = HashMap<String, HashSet<String>>;
+
+/// Alias pack file as published by the community
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasPackFile {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<String>,
+    pub aliases: Vec<AliasPackEntry>,
+}
+
+/// A single provider name -> tvg-id hint
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AliasPackEntry {
+    pub name: String,
+    pub tvg_id: String,
+}
+
+/// Parse and validate an alias pack
+///
+/// Returns the pack and its deduplicated `(normalized alias, tvg-id)` pairs.
+/// The whole pack is rejected if any entry is invalid, so a broken pack never
+/// half-imports.
+pub fn parse_alias_pack(data: &[u8]) -> Result<(AliasPackFile, Vec<(String, String)>), String> {
+    let pack: AliasPackFile =
+        serde_json::from_slice(data).map_err(|e| format!("Invalid alias pack: {}", e))?;
+
+    let name = pack.name.trim();
+    if name.is_empty() || name.chars().count() > MAX_PACK_NAME_LEN {
+        return Err(format!(
+            "Alias pack name must be 1-{} characters",
+            MAX_PACK_NAME_LEN
+        ));
+    }
+    if pack.aliases.is_empty() {
+        return Err("Alias pack contains no aliases".to_string());
+    }
+    if pack.aliases.len() > MAX_ALIASES_PER_PACK {
+        return Err(format!(
+            "Alias pack has {} aliases (maximum {})",
+            pack.aliases.len(),
+            MAX_ALIASES_PER_PACK
+        ));
+    }
+
+    let mut seen = HashSet::new();
+    let mut entries = Vec::with_capacity(pack.aliases.len());
+    for (index, entry) in pack.aliases.iter().enumerate() {
+        let alias = normalize_channel_name(&entry.name);
+        let tvg_id = entry.tvg_id.trim();
+        if alias.is_empty() {
+            return Err(format!("Alias #{} has an empty name", index + 1));
+        }
+        if tvg_id.is_empty() || tvg_id.len() > MAX_TVG_ID_LEN {
+            return Err(format!(
+                "Alias #{} ('{}') has an invalid tvgId",
+                index + 1,
+                entry.name
+            ));
+        }
+        if seen.insert((alias.clone(), tvg_id.to_string())) {
+            entries.push((alias, tvg_id.to_string()));
+        }
+    }
+
+    Ok((pack, entries))
+}
+
+/// Insert or replace an alias pack and its aliases
+///
+/// Packs are identified by source URL: importing the same URL again replaces
+/// the previous aliases. Returns the pack ID.
+pub fn merge_alias_pack(
+    conn: &mut SqliteConnection,
+    source_url: &str,
+    pack: &AliasPackFile,
+    entries: &[(String, String)],
+) -> QueryResult<i32> {
+    conn.transaction(|conn| {
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+
+        let existing_id: Option<i32> = alias_packs::table
+            .filter(alias_packs::source_url.eq(source_url))
+            .select(alias_packs::id)
+            .first::<Option<i32>>(conn)
+            .optional()?
+            .flatten();
+
+        let pack_id = match existing_id {
+            Some(id) => {
+                diesel::update(alias_packs::table.filter(alias_packs::id.eq(id)))
+                    .set((
+                        alias_packs::name.eq(pack.name.trim()),
+                        alias_packs::version.eq(&pack.version),
+                        alias_packs::alias_count.eq(entries.len() as i32),
+                        alias_packs::updated_at.eq(&now),
+                    ))
+                    .execute(conn)?;
+                diesel::delete(channel_aliases::table.filter(channel_aliases::pack_id.eq(id)))
+                    .execute(conn)?;
+                id
+            }
+            None => diesel::insert_into(alias_packs::table)
+                .values((
+                    alias_packs::name.eq(pack.name.trim()),
+                    alias_packs::source_url.eq(source_url),
+                    alias_packs::version.eq(&pack.version),
+                    alias_packs::alias_count.eq(entries.len() as i32),
+                ))
+                .returning(alias_packs::id)
+                .get_result::<Option<i32>>(conn)?
+                .ok_or(diesel::result::Error::NotFound)?,
+        };
+
+        for chunk in entries.chunks(ALIAS_INSERT_CHUNK_SIZE) {
+            let rows: Vec<_> = chunk
+                .iter()
+                .map(|(alias, tvg_id)| {
+                    (
+                        channel_aliases::pack_id.eq(pack_id),
+                        channel_aliases::alias.eq(alias),
+                        channel_aliases::tvg_id.eq(tvg_id),
+                    )
+                })
+                .collect();
+            diesel::insert_into(channel_aliases::table)
+                .values(&rows)
+                .execute(conn)?;
+        }
+
+        Ok(pack_id)
+    })
+}
+
+/// Remove an alias pack and all aliases imported from it
+///
+/// Returns whether the pack existed.
+pub fn remove_alias_pack(conn: &mut SqliteConnection, pack_id: i32) -> QueryResult<bool> {
+    conn.transaction(|conn| {
+        diesel::delete(channel_aliases::table.filter(channel_aliases::pack_id.eq(pack_id)))
+            .execute(conn)?;
+        let removed = diesel::delete(alias_packs::table.filter(alias_packs::id.eq(pack_id)))
+            .execute(conn)?;
+        Ok(removed > 0)
+    })
+}
+
+/// Load all aliases for matching
+pub fn load_alias_hints(conn: &mut SqliteConnection) -> QueryResult<AliasHints> {
+    let rows: Vec<(String, String)> = channel_aliases::table
+        .select((channel_aliases::alias, channel_aliases::tvg_id))
+        .load(conn)?;
+
+    let mut hints = AliasHints::new();
+    for (alias, tvg_id) in rows {
+        hints
+            .entry(alias)
+            .or_default()
+            .insert(tvg_id.to_lowercase());
+    }
+    Ok(hints)
+}
+
+/// Check whether an alias maps a normalized Xtream name to an XMLTV channel ID
+pub fn alias_hint_matches(hints: &AliasHints, xtream_normalized: &str, xmltv_channel_id: &str) -> bool {
+    hints
+        .get(xtream_normalized)
+        .is_some_and(|tvg_ids| tvg_ids.contains(&xmltv_channel_id.trim().to_lowercase()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::test_connection;
+
+    const PACK: &str = r#"{
+        "name": "Community US",
+        "version": "1",
+        "aliases": [
+            { "name": "US: ESPN HD", "tvgId": "ESPN.us" },
+            { "name": "US: ESPN FHD", "tvgId": "ESPN.us" },
+            { "name": "FOX News", "tvgId": "FoxNews.us" }
+        ]
+    }"#;
+
+    #[test]
+    fn test_parse_alias_pack() {
+        let (pack, entries) = parse_alias_pack(PACK.as_bytes()).unwrap();
+        assert_eq!(pack.name, "Community US");
+        // "US: ESPN HD" and "US: ESPN FHD" normalize to the same alias
+        assert_eq!(entries.len(), 2);
+        assert!(entries.contains(&("us espn".to_string(), "ESPN.us".to_string())));
+
+        assert!(parse_alias_pack(b"not json").is_err());
+        assert!(parse_alias_pack(br#"{"name":"x","aliases":[]}"#).is_err());
+        assert!(parse_alias_pack(br#"{"name":"x","aliases":[{"name":"A","tvgId":" "}]}"#).is_err());
+        assert!(parse_alias_pack(br#"{"name":"","aliases":[{"name":"A","tvgId":"a"}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_merge_replace_and_remove_pack() {
+        let mut conn = test_connection();
+        let url = "https://example.com/us.json";
+
+        let (pack, entries) = parse_alias_pack(PACK.as_bytes()).unwrap();
+        let pack_id = merge_alias_pack(&mut conn, url, &pack, &entries).unwrap();
+
+        let hints = load_alias_hints(&mut conn).unwrap();
+        assert!(alias_hint_matches(&hints, "us espn", "espn.US"));
+        assert!(alias_hint_matches(&hints, "fox news", "FoxNews.us"));
+        assert!(!alias_hint_matches(&hints, "us espn", "FoxNews.us"));
+
+        // Re-importing the same URL replaces the pack's aliases
+        let updated = br#"{"name":"Community US","version":"2","aliases":[{"name":"CNN","tvgId":"CNN.us"}]}"#;
+        let (pack, entries) = parse_alias_pack(updated).unwrap();
+        assert_eq!(merge_alias_pack(&mut conn, url, &pack, &entries).unwrap(), pack_id);
+
+        let hints = load_alias_hints(&mut conn).unwrap();
+        assert!(!hints.contains_key("us espn"));
+        assert!(alias_hint_matches(&hints, "cnn", "CNN.us"));
+
+        assert!(remove_alias_pack(&mut conn, pack_id).unwrap());
+        assert!(load_alias_hints(&mut conn).unwrap().is_empty());
+        assert!(!remove_alias_pack(&mut conn, pack_id).unwrap());
+    }
+}
@@ -441,6 +441,7 @@ pub fn handle_changed_streams(
             tv_archive: changed.new_stream.tv_archive.unwrap_or(0),
             tv_archive_duration: changed.new_stream.tv_archive_duration.unwrap_or(0),
             updated_at: now.clone(),
+            stream_url: changed.new_stream.stream_url.clone(),
         };
 
         diesel::update(
@@ -618,6 +619,7 @@ mod tests {
             tv_archive_duration: None,
             added_at: None,
             updated_at: None,
+            stream_url: None,
         };
         let mut new = old.clone();
         new.name = "ESPN HD".to_string();
@@ -641,6 +643,7 @@ mod tests {
             tv_archive_duration: None,
             added_at: None,
             updated_at: None,
+            stream_url: None,
         };
         let mut new = old.clone();
         new.stream_icon = Some("http://new.com/icon.png".to_string());
@@ -664,6 +667,7 @@ mod tests {
             tv_archive_duration: None,
             added_at: None,
             updated_at: None,
+            stream_url: None,
         };
         let new = old.clone();
 
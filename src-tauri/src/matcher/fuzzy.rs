@@ -3,12 +3,36 @@
 //! Provides the core fuzzy matching functionality for matching XMLTV channels
 //! to Xtream streams. This module handles name normalization and the matching
 //! algorithm itself.
+//!
+//! Large catalogs (tens of thousands of streams) are matched in two ways that
+//! keep a full run to seconds:
+//!
+//! - Streams are bucketed by the first letter and the words of their
+//!   normalized name. A channel is only scored against streams sharing its
+//!   first letter or one of its words, plus streams whose EPG ID or alias
+//!   pack hint names the channel's tvg-id.
+//! - Streams are normalized and channels scored in parallel on the rayon
+//!   thread pool (one at a time in low resource mode).
+//!
+//! Enabled plugins run after the user's name rules and after the region
+//! adjustment of each score (see `crate::plugins`).
 
+use rayon::prelude::*;
 use regex::Regex;
+use std::collections::{HashMap, HashSet};
 use std::sync::LazyLock;
 
-use super::{scorer::calculate_match_score, MatchConfig, MatchResult, MatchStats, MatchType};
+use super::{
+    aliases::{alias_hint_matches, AliasHints},
+    icons::{cached_icon_hash, hamming_distance},
+    regions::{detect_channel_region, detect_stream_region, regions_agree},
+    scorer::{calculate_match_score, region_adjustment},
+    variants::{group_variants, variant_key},
+    MatchConfig, MatchResult, MatchStats, MatchType,
+};
 use crate::db::models::{XmltvChannel, XtreamChannel};
+use crate::plugins::PluginSession;
+use crate::xtream::quality::{best_tier_rank, qualities_from_json};
 
 /// Regex pattern for removing quality suffixes (HD, SD, FHD, 4K, UHD, etc.)
 /// Matches quality indicators both at end and before parentheses/punctuation
@@ -67,12 +91,88 @@ pub fn normalize_channel_name(name: &str) -> String {
     collapsed.trim().to_string()
 }
 
+/// An Xtream stream prepared for matching
+struct XtreamCandidate<'a> {
+    id: i32,
+    account_id: i32,
+    /// Name normalized with the user's match rules
+    normalized: String,
+    /// Name normalized without rules, for alias pack lookups
+    hint_key: String,
+    epg_id: Option<&'a str>,
+    icon_hash: Option<u64>,
+    region: Option<&'static str>,
+    /// Rank of the best quality (0 is the highest)
+    quality_rank: usize,
+    /// Key shared with the stream's quality variants
+    variant: Option<(i32, String)>,
+}
+
+/// Keys bucketing a normalized name: its first letter and each word of two or
+/// more characters
+fn bucket_keys(normalized: &str) -> impl Iterator<Item = String> + '_ {
+    let first = normalized.chars().next().map(|c| format!("^{}", c));
+    first.into_iter().chain(
+        normalized
+            .split(' ')
+            .filter(|word| word.chars().count() >= 2)
+            .map(str::to_string),
+    )
+}
+
+/// Streams indexed by what a channel must share with them to be scored
+#[derive(Default)]
+struct CandidateIndex {
+    /// Positions of the streams per bucket key
+    by_key: HashMap<String, Vec<usize>>,
+    /// Positions of the streams per lowercased EPG ID or alias pack tvg-id
+    by_tvg_id: HashMap<String, Vec<usize>>,
+}
+
+impl CandidateIndex {
+    fn build(candidates: &[XtreamCandidate], alias_hints: &AliasHints) -> Self {
+        let mut index = Self::default();
+        for (position, candidate) in candidates.iter().enumerate() {
+            for key in bucket_keys(&candidate.normalized) {
+                index.by_key.entry(key).or_default().push(position);
+            }
+            let epg_id = candidate.epg_id.map(|id| id.trim().to_lowercase());
+            let hinted = alias_hints.get(&candidate.hint_key).into_iter().flatten();
+            for tvg_id in epg_id.iter().chain(hinted) {
+                index
+                    .by_tvg_id
+                    .entry(tvg_id.clone())
+                    .or_default()
+                    .push(position);
+            }
+        }
+        index
+    }
+
+    /// Positions of the streams to score against a channel, in catalog order
+    fn candidates(&self, normalized: &str, channel_id: &str) -> Vec<usize> {
+        let tvg_id = channel_id.trim().to_lowercase();
+        let mut positions: Vec<usize> = bucket_keys(normalized)
+            .filter_map(|key| self.by_key.get(&key))
+            .chain(self.by_tvg_id.get(&tvg_id))
+            .flatten()
+            .copied()
+            .collect();
+        positions.sort_unstable();
+        positions.dedup();
+        positions
+    }
+}
+
 /// Match XMLTV channels to Xtream streams using fuzzy matching.
 ///
 /// For each XMLTV channel, this function finds all Xtream streams that match
 /// above the confidence threshold. The matches are sorted by confidence score
 /// (descending) and assigned priority accordingly.
 ///
+/// Synthetic channels (promoted orphan streams) keep the stream they were
+/// created from and are never matched, so a rematch can't add streams to them.
+///
 /// # Arguments
 ///
 /// * `xmltv_channels` - List of XMLTV channels to match
@@ -82,62 +182,134 @@ pub fn normalize_channel_name(name: &str) -> String {
 /// # Returns
 ///
 /// A tuple of (Vec<MatchResult>, MatchStats) containing all matches and statistics
-pub fn match_channels(
+pub fn match_channels<'a>(
     xmltv_channels: &[XmltvChannel],
-    xtream_channels: &[XtreamChannel],
+    xtream_channels: &'a [XtreamChannel],
     config: &MatchConfig,
 ) -> (Vec<MatchResult>, MatchStats) {
     let start = std::time::Instant::now();
     let mut all_matches: Vec<MatchResult> = Vec::new();
+    let is_synthetic = |c: &XmltvChannel| c.is_synthetic.unwrap_or(0) != 0;
     let mut stats = MatchStats {
-        total_xmltv: xmltv_channels.len(),
+        total_xmltv: xmltv_channels.iter().filter(|c| !is_synthetic(c)).count(),
         total_xtream: xtream_channels.len(),
         ..Default::default()
     };
 
-    // Pre-normalize all Xtream channel names for efficiency
-    let xtream_normalized: Vec<(i32, String, Option<&str>)> = xtream_channels
-        .iter()
-        .filter_map(|c| {
-            c.id.map(|id| {
-                (
-                    id,
-                    normalize_channel_name(&c.name),
-                    c.epg_channel_id.as_deref(),
-                )
-            })
+    // Icon hashes from the logo cache, when icon matching is enabled
+    let icon_cache_dir = config
+        .icon_cache_dir
+        .as_deref()
+        .filter(|_| config.icon_boost > 0.0);
+    let icon_hash = |url: Option<&str>| {
+        icon_cache_dir.and_then(|dir| url.and_then(|url| cached_icon_hash(dir, url)))
+    };
+
+    // Pre-normalize all Xtream channel names (and hash their icons) for efficiency.
+    // Alias pack hints are keyed by the built-in normalization, without user rules.
+    let to_candidate = |plugins: &mut PluginSession, c: &'a XtreamChannel| {
+        c.id.map(|id| XtreamCandidate {
+            id,
+            account_id: c.account_id,
+            normalized: plugins.normalize(config.name_rules.normalize(&c.name)),
+            hint_key: normalize_channel_name(&c.name),
+            epg_id: c.epg_channel_id.as_deref(),
+            icon_hash: icon_hash(c.stream_icon.as_deref()),
+            region: detect_stream_region(&c.name, c.category_name.as_deref()),
+            quality_rank: best_tier_rank(
+                &c.qualities
+                    .as_deref()
+                    .map(qualities_from_json)
+                    .unwrap_or_default(),
+            ),
+            variant: variant_key(c.account_id, &c.name),
         })
-        .collect();
+    };
+    let xtream_normalized: Vec<XtreamCandidate> = if crate::low_resource::is_enabled() {
+        let mut plugins = config.plugins.session();
+        xtream_channels
+            .iter()
+            .filter_map(|c| to_candidate(&mut plugins, c))
+            .collect()
+    } else {
+        xtream_channels
+            .par_iter()
+            .map_init(|| config.plugins.session(), to_candidate)
+            .flatten()
+            .collect()
+    };
+    let index = CandidateIndex::build(&xtream_normalized, &config.alias_hints);
+
+    // Scoring settings of each source/account pair that has a profile
+    let mut pair_configs: HashMap<(i32, i32), MatchConfig> = HashMap::new();
+    if !config.profiles.is_empty() {
+        let sources: HashSet<i32> = xmltv_channels.iter().map(|c| c.source_id).collect();
+        let accounts: HashSet<i32> = xtream_normalized.iter().map(|c| c.account_id).collect();
+        for &source_id in &sources {
+            for &account_id in &accounts {
+                if let Some(resolved) = config.profiles.resolve(config, source_id, account_id) {
+                    pair_configs.insert((source_id, account_id), resolved);
+                }
+            }
+        }
+    }
 
-    for xmltv in xmltv_channels {
-        let xmltv_id = match xmltv.id {
-            Some(id) => id,
-            None => continue,
-        };
+    // Matches of one XMLTV channel, best first (None for unsaved and synthetic channels)
+    let match_channel = |plugins: &mut PluginSession,
+                         xmltv: &XmltvChannel|
+     -> Option<Vec<MatchResult>> {
+        if is_synthetic(xmltv) {
+            return None;
+        }
+        let xmltv_id = xmltv.id?;
 
-        let xmltv_normalized = normalize_channel_name(&xmltv.display_name);
+        let xmltv_normalized = plugins.normalize(config.name_rules.normalize(&xmltv.display_name));
         let xmltv_channel_id = &xmltv.channel_id;
+        let xmltv_icon_hash = icon_hash(xmltv.icon.as_deref());
+        let xmltv_region = detect_channel_region(&xmltv.display_name, xmltv_channel_id);
 
-        let mut channel_matches: Vec<MatchResult> = Vec::new();
+        // Matches with the position of their stream
+        let mut channel_matches: Vec<(MatchResult, usize)> = Vec::new();
 
-        for (xtream_id, xtream_normalized, xtream_epg_id) in &xtream_normalized {
-            // Check for EPG ID match (Xtream's epg_channel_id matches XMLTV's channel_id)
-            let epg_id_match = epg_ids_match(*xtream_epg_id, xmltv_channel_id);
+        for position in index.candidates(&xmltv_normalized, xmltv_channel_id) {
+            let xtream = &xtream_normalized[position];
+            let pair_config = pair_configs
+                .get(&(xmltv.source_id, xtream.account_id))
+                .unwrap_or(config);
+
+            // Check for EPG ID match (Xtream's epg_channel_id matches XMLTV's channel_id),
+            // or an alias pack hint mapping this stream name to the channel's tvg-id
+            let epg_id_match = epg_ids_match(xtream.epg_id, xmltv_channel_id)
+                || alias_hint_matches(&config.alias_hints, &xtream.hint_key, xmltv_channel_id);
 
             // Check for exact normalized name match
-            let exact_name_match = xmltv_normalized == *xtream_normalized;
+            let exact_name_match = xmltv_normalized == xtream.normalized;
+
+            // Check whether the icons look the same
+            let icon_match = match (xmltv_icon_hash, xtream.icon_hash) {
+                (Some(a), Some(b)) => hamming_distance(a, b) <= pair_config.icon_max_distance,
+                _ => false,
+            };
 
             // Calculate match score
-            let score = calculate_match_score(
+            let mut score = calculate_match_score(
                 &xmltv_normalized,
-                xtream_normalized,
+                &xtream.normalized,
                 epg_id_match,
                 exact_name_match,
-                config,
+                icon_match,
+                pair_config,
             );
 
+            // Same or different region; an EPG ID match already pins the channel
+            if !epg_id_match {
+                let agreement = regions_agree(xmltv_region, xtream.region);
+                score = (score + region_adjustment(agreement, pair_config)).clamp(0.0, 1.0);
+            }
+            let score = plugins.score(&xmltv_normalized, &xtream.normalized, score);
+
             // Only include matches above threshold
-            if score >= config.threshold {
+            if score >= pair_config.threshold {
                 let match_type = if epg_id_match {
                     MatchType::ExactEpgId
                 } else if exact_name_match {
@@ -146,21 +318,59 @@ pub fn match_channels(
                     MatchType::Fuzzy
                 };
 
-                channel_matches.push(MatchResult::new(xmltv_id, *xtream_id, score, match_type));
+                channel_matches.push((
+                    MatchResult::new(xmltv_id, xtream.id, score, match_type)
+                        .with_regions(xmltv_region, xtream.region),
+                    position,
+                ));
             }
         }
 
-        // Sort matches by confidence (descending)
-        channel_matches.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+        // Sort matches by confidence (descending), the better quality first on ties
+        channel_matches.sort_by(|(a, a_pos), (b, b_pos)| {
+            b.confidence.partial_cmp(&a.confidence).unwrap().then(
+                xtream_normalized[*a_pos]
+                    .quality_rank
+                    .cmp(&xtream_normalized[*b_pos].quality_rank),
+            )
+        });
+
+        // Quality variants of a stream follow it as one logical mapping
+        let mut channel_matches: Vec<MatchResult> = group_variants(
+            channel_matches,
+            |(_, position)| xtream_normalized[*position].variant.clone(),
+            |(_, position)| xtream_normalized[*position].quality_rank,
+        )
+        .into_iter()
+        .map(|(m, _)| m)
+        .collect();
 
         // Assign priority and primary status
-        let match_count = channel_matches.len();
         for (i, m) in channel_matches.iter_mut().enumerate() {
             m.is_primary = i == 0;
             m.stream_priority = i as i32;
         }
+        Some(channel_matches)
+    };
+
+    // Score channels in parallel, except on devices with little headroom
+    let per_channel: Vec<Vec<MatchResult>> = if crate::low_resource::is_enabled() {
+        let mut plugins = config.plugins.session();
+        xmltv_channels
+            .iter()
+            .filter_map(|c| match_channel(&mut plugins, c))
+            .collect()
+    } else {
+        xmltv_channels
+            .par_iter()
+            .map_init(|| config.plugins.session(), match_channel)
+            .flatten()
+            .collect()
+    };
 
+    for channel_matches in per_channel {
         // Update stats
+        let match_count = channel_matches.len();
         if match_count > 0 {
             stats.matched += 1;
             if match_count > 1 {
@@ -283,6 +493,147 @@ mod tests {
         assert!(!epg_ids_match(None, "espn.us"));
     }
 
+    fn candidate(normalized: &str, epg_id: Option<&'static str>) -> XtreamCandidate<'static> {
+        XtreamCandidate {
+            id: 1,
+            account_id: 1,
+            normalized: normalized.to_string(),
+            hint_key: normalized.to_string(),
+            epg_id,
+            icon_hash: None,
+            region: None,
+            quality_rank: 3,
+            variant: None,
+        }
+    }
+
+    #[test]
+    fn test_candidate_index_buckets() {
+        let candidates = vec![
+            candidate("sky sports main event", None),
+            candidate("cnn international", None),
+            candidate("bbc one", None),
+            candidate("discovery", Some("SportsChannel.uk")),
+        ];
+        let mut hints = AliasHints::new();
+        hints
+            .entry("bbc one".to_string())
+            .or_default()
+            .insert("eurosport.de".to_string());
+        let index = CandidateIndex::build(&candidates, &hints);
+
+        // Shared word, shared first letter, EPG ID
+        assert_eq!(
+            index.candidates("main event", " sportschannel.UK "),
+            vec![0, 3]
+        );
+        assert_eq!(index.candidates("cbs", "cbs.us"), vec![1]);
+        // Alias pack hint
+        assert_eq!(index.candidates("eurosport 1", "eurosport.de"), vec![2]);
+        assert!(index.candidates("ard", "ard.de").is_empty());
+    }
+
+    fn xmltv_channel(id: i32, display_name: String) -> XmltvChannel {
+        XmltvChannel {
+            id: Some(id),
+            source_id: 1,
+            channel_id: format!("channel{}.uk", id),
+            display_name,
+            icon: None,
+            created_at: String::new(),
+            updated_at: String::new(),
+            is_synthetic: None,
+        }
+    }
+
+    fn xtream_channel(id: i32, name: String) -> XtreamChannel {
+        XtreamChannel {
+            id: Some(id),
+            account_id: 1,
+            stream_id: id,
+            name,
+            stream_icon: None,
+            category_id: None,
+            category_name: None,
+            qualities: None,
+            epg_channel_id: None,
+            tv_archive: None,
+            tv_archive_duration: None,
+            added_at: None,
+            updated_at: None,
+            stream_url: None,
+        }
+    }
+
+    #[test]
+    fn test_synthetic_channels_are_not_matched() {
+        let mut synthetic = xmltv_channel(2, "ESPN".to_string());
+        synthetic.is_synthetic = Some(1);
+        let xmltv = vec![xmltv_channel(1, "ESPN".to_string()), synthetic];
+        let xtream = vec![xtream_channel(10, "ESPN HD".to_string())];
+
+        let (matches, stats) = match_channels(&xmltv, &xtream, &MatchConfig::default());
+        assert_eq!(stats.total_xmltv, 1);
+        assert_eq!(stats.matched, 1);
+        assert!(matches.iter().all(|m| m.xmltv_channel_id == 1));
+    }
+
+    #[test]
+    fn test_quality_variants_form_one_mapping() {
+        let xmltv = vec![xmltv_channel(1, "ESPN".to_string())];
+        let mut xtream = vec![
+            xtream_channel(10, "ESPN HD".to_string()),
+            xtream_channel(11, "ESPN FHD".to_string()),
+            xtream_channel(12, "ESPN HD".to_string()),
+        ];
+        xtream[0].qualities = Some(r#"["HD"]"#.to_string());
+        xtream[1].qualities = Some(r#"["FHD"]"#.to_string());
+        xtream[2].qualities = Some(r#"["HD"]"#.to_string());
+        xtream[2].account_id = 2;
+
+        let (matches, _) = match_channels(&xmltv, &xtream, &MatchConfig::default());
+        let order: Vec<i32> = matches.iter().map(|m| m.xtream_channel_id).collect();
+        // The FHD variant leads its group, the other provider comes last
+        assert_eq!(order, [11, 10, 12]);
+        assert!(matches[0].is_primary && !matches[1].is_primary);
+    }
+
+    /// Full match of a large catalog; run with `cargo test --release -- --ignored`
+    #[test]
+    #[ignore]
+    fn bench_match_large_catalog() {
+        let words: Vec<&str> = "sky sports news cinema discovery history comedy kids music \
+             action drama nature travel food science crime"
+            .split_whitespace()
+            .collect();
+        let name = |i: usize| {
+            format!(
+                "{} {} {}",
+                words[i % words.len()],
+                words[(i / words.len()) % words.len()],
+                i
+            )
+        };
+        let xmltv: Vec<XmltvChannel> = (0..1_000)
+            .map(|i| xmltv_channel(i as i32, name(i)))
+            .collect();
+        let xtream: Vec<XtreamChannel> = (0..20_000)
+            .map(|i| xtream_channel(i as i32, format!("{} HD", name(i % 5_000))))
+            .collect();
+
+        let start = std::time::Instant::now();
+        let (matches, stats) = match_channels(&xmltv, &xtream, &MatchConfig::default());
+        let elapsed = start.elapsed();
+
+        assert_eq!(stats.matched, 1_000);
+        assert!(!matches.is_empty());
+        assert!(
+            elapsed < std::time::Duration::from_secs(10),
+            "full match took {:?}",
+            elapsed
+        );
+    }
+
     // Integration tests for match_channels would require mocking database models
     // These are tested in the integration test suite
 }
@@ -0,0 +1,237 @@
+//! Channel Icon Similarity
+//!
+//! Many providers use the same logo art as the guide, so a stream whose icon
+//! looks like the XMLTV channel's icon is more likely to be that channel.
+//! Icons are compared with a perceptual hash (pHash): the logo is flattened
+//! onto white, scaled to 32x32 grayscale, and the signs of its lowest 8x8 DCT
+//! frequencies relative to their median form a 64-bit hash. Re-encoded or
+//! resized copies of a logo differ in only a few bits.
+//!
+//! Only logos already in the logo cache are compared; nothing is downloaded
+//! while matching. While icon matching is enabled the logo pre-warm job also
+//! caches the icons of all XMLTV channels and provider streams.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::{LazyLock, Mutex};
+use std::time::SystemTime;
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+use crate::server::logos::{logo_cache_dir, logo_cache_key};
+
+/// Settings key for the icon matching configuration (JSON)
+pub const ICON_MATCH_KEY: &str = "match_icon_similarity";
+
+/// Side of the grayscale image the DCT is computed on
+const HASH_IMAGE_SIZE: usize = 32;
+
+/// Side of the block of low frequencies kept in the hash
+const HASH_BLOCK_SIZE: usize = 8;
+
+/// Icons with less luminance variation than this (e.g. blank placeholders)
+/// are not hashed
+const MIN_ICON_STD_DEV: f64 = 2.0;
+
+/// Icon matching configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct IconMatchConfig {
+    pub enabled: bool,
+    /// Boost added to the match score when the icons look the same
+    pub boost: f64,
+    /// Most differing hash bits (of 64) for icons to count as the same
+    pub max_distance: u32,
+}
+
+impl Default for IconMatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            boost: 0.10,
+            max_distance: 6,
+        }
+    }
+}
+
+impl IconMatchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=0.5).contains(&self.boost) {
+            return Err("Icon boost must be between 0.0 and 0.5".to_string());
+        }
+        if self.max_distance > 16 {
+            return Err("Icon distance must be between 0 and 16".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Load the icon matching configuration (defaults if unset or unreadable)
+pub fn load_icon_match_config(conn: &mut SqliteConnection) -> IconMatchConfig {
+    settings::table
+        .filter(settings::key.eq(ICON_MATCH_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the icon matching configuration
+pub fn save_icon_match_config(
+    conn: &mut SqliteConnection,
+    config: &IconMatchConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize icon matching settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(ICON_MATCH_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Perceptual hash of an image, or None if it can't be decoded or is blank
+pub fn icon_phash(data: &[u8]) -> Option<u64> {
+    let size = HASH_IMAGE_SIZE as u32;
+    let image = image::load_from_memory(data).ok()?.to_rgba8();
+    let small = image::imageops::resize(&image, size, size, image::imageops::FilterType::Triangle);
+
+    // Luminance with transparency flattened onto white
+    let pixels: Vec<f64> = small
+        .pixels()
+        .map(|p| {
+            let [r, g, b, a] = p.0.map(f64::from);
+            let luma = 0.299 * r + 0.587 * g + 0.114 * b;
+            let alpha = a / 255.0;
+            luma * alpha + 255.0 * (1.0 - alpha)
+        })
+        .collect();
+
+    let mean = pixels.iter().sum::<f64>() / pixels.len() as f64;
+    let variance = pixels.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / pixels.len() as f64;
+    if variance.sqrt() < MIN_ICON_STD_DEV {
+        return None;
+    }
+
+    let coefficients = low_frequency_dct(&pixels);
+    // The DC term only carries overall brightness
+    let mut sorted: Vec<f64> = coefficients[1..].to_vec();
+    sorted.sort_by(|a, b| a.total_cmp(b));
+    let median = sorted[sorted.len() / 2];
+
+    Some(
+        coefficients
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| **c > median)
+            .fold(0u64, |hash, (i, _)| hash | (1 << i)),
+    )
+}
+
+/// Lowest `HASH_BLOCK_SIZE`² coefficients of the 2D DCT-II, row-major
+fn low_frequency_dct(pixels: &[f64]) -> Vec<f64> {
+    let n = HASH_IMAGE_SIZE;
+    let basis = |k: usize, x: usize| {
+        (std::f64::consts::PI * (2 * x + 1) as f64 * k as f64 / (2 * n) as f64).cos()
+    };
+
+    // Rows first, then columns of the row results
+    let mut rows = vec![0.0; n * HASH_BLOCK_SIZE];
+    for y in 0..n {
+        for u in 0..HASH_BLOCK_SIZE {
+            rows[y * HASH_BLOCK_SIZE + u] = (0..n).map(|x| pixels[y * n + x] * basis(u, x)).sum();
+        }
+    }
+    let mut coefficients = vec![0.0; HASH_BLOCK_SIZE * HASH_BLOCK_SIZE];
+    for v in 0..HASH_BLOCK_SIZE {
+        for u in 0..HASH_BLOCK_SIZE {
+            coefficients[v * HASH_BLOCK_SIZE + u] = (0..n)
+                .map(|y| rows[y * HASH_BLOCK_SIZE + u] * basis(v, y))
+                .sum();
+        }
+    }
+    coefficients
+}
+
+/// Number of differing bits between two hashes
+pub fn hamming_distance(a: u64, b: u64) -> u32 {
+    (a ^ b).count_ones()
+}
+
+/// Hash of a cached logo and the file time it was computed for
+type CachedHash = (SystemTime, Option<u64>);
+
+/// Hashes of cached logos by cache key
+static ICON_HASHES: LazyLock<Mutex<HashMap<String, CachedHash>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Hash of a logo in the cache, or None if it isn't cached or can't be hashed
+///
+/// Hashes are remembered until the cached file changes.
+pub fn cached_icon_hash(app_data_dir: &Path, url: &str) -> Option<u64> {
+    let url = url.trim();
+    if !(url.starts_with("http://") || url.starts_with("https://")) {
+        return None;
+    }
+    let key = logo_cache_key(url);
+    let path = logo_cache_dir(app_data_dir).join(&key);
+    let modified = std::fs::metadata(&path).and_then(|m| m.modified()).ok()?;
+
+    let mut hashes = ICON_HASHES.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((at, hash)) = hashes.get(&key) {
+        if *at == modified {
+            return *hash;
+        }
+    }
+    let hash = std::fs::read(&path).ok().and_then(|data| icon_phash(&data));
+    hashes.insert(key, (modified, hash));
+    hash
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// PNG of a logo-like test image: a dark bar and a disc on transparency,
+    /// with the bar across the top or down the left side
+    fn logo_png(size: u32, vertical: bool) -> Vec<u8> {
+        let image = image::RgbaImage::from_fn(size, size, |x, y| {
+            let (fx, fy) = (x * 64 / size, y * 64 / size);
+            let (along, across) = if vertical { (fy, fx) } else { (fx, fy) };
+            let dx = fx as i32 - 40;
+            let dy = fy as i32 - 44;
+            if (8..24).contains(&across) && (4..60).contains(&along) {
+                image::Rgba([20, 20, 120, 255])
+            } else if dx * dx + dy * dy < 120 {
+                image::Rgba([200, 30, 30, 255])
+            } else {
+                image::Rgba([0, 0, 0, 0])
+            }
+        });
+        let mut png = Vec::new();
+        image
+            .write_to(&mut std::io::Cursor::new(&mut png), image::ImageFormat::Png)
+            .unwrap();
+        png
+    }
+
+    #[test]
+    fn test_icon_phash_matches_resized_copies() {
+        let original = icon_phash(&logo_png(256, false)).unwrap();
+        let resized = icon_phash(&logo_png(90, false)).unwrap();
+        let different = icon_phash(&logo_png(256, true)).unwrap();
+
+        assert!(hamming_distance(original, resized) <= IconMatchConfig::default().max_distance);
+        assert!(hamming_distance(original, different) > IconMatchConfig::default().max_distance);
+    }
+
+    #[test]
+    fn test_blank_and_invalid_icons_are_not_hashed() {
+        assert_eq!(icon_phash(crate::server::logos::PLACEHOLDER_LOGO_PNG), None);
+        assert_eq!(icon_phash(b"<svg></svg>"), None);
+    }
+}
@@ -16,18 +16,71 @@
 //! - `scorer`: Match confidence scoring with boosts
 //! - `persistence`: Database operations for saving/loading mappings
 //! - `auto_rematch`: Change detection and automatic rematch
+//! - `aliases`: Community alias packs (provider name -> tvg-id hints)
+//! - `icons`: Perceptual hashing of channel icons
+//! - `rules`: User-defined name rules applied during normalization
+//! - `profiles`: Per-account and per-source matching settings
+//! - `regions`: Country detection from names, IDs and categories
+//! - `variants`: Grouping of one channel's streams in several qualities
 
+mod aliases;
 mod auto_rematch;
 mod fuzzy;
+mod icons;
 mod persistence;
+mod profiles;
+mod regions;
+mod rules;
 mod scorer;
+mod variants;
 
+pub use aliases::*;
 pub use auto_rematch::*;
 pub use fuzzy::*;
+pub use icons::*;
 pub use persistence::*;
+pub use profiles::*;
+pub use regions::*;
+pub use rules::*;
 pub use scorer::*;
+pub use variants::*;
 
 use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+
+use crate::plugins::Plugins;
+
+/// String similarity used for the base match score
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchAlgorithm {
+    /// Jaro-Winkler, favouring shared prefixes
+    #[default]
+    JaroWinkler,
+    /// Normalized Levenshtein edit distance
+    Levenshtein,
+    /// Sørensen-Dice coefficient of character bigrams, tolerant of reordered words
+    SorensenDice,
+}
+
+impl MatchAlgorithm {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchAlgorithm::JaroWinkler => "jaro_winkler",
+            MatchAlgorithm::Levenshtein => "levenshtein",
+            MatchAlgorithm::SorensenDice => "sorensen_dice",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "jaro_winkler" => Some(MatchAlgorithm::JaroWinkler),
+            "levenshtein" => Some(MatchAlgorithm::Levenshtein),
+            "sorensen_dice" => Some(MatchAlgorithm::SorensenDice),
+            _ => None,
+        }
+    }
+}
 
 /// Configuration for the matching algorithm
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +92,36 @@ pub struct MatchConfig {
     pub epg_id_boost: f64,
     /// Boost applied when normalized names match exactly (default: 0.10)
     pub exact_name_boost: f64,
+    /// Alias pack hints; a hit counts as an EPG ID match
+    #[serde(skip)]
+    pub alias_hints: AliasHints,
+    /// User-defined rules applied when normalizing names
+    #[serde(skip)]
+    pub name_rules: NameRules,
+    /// Per-account and per-source overrides of the scoring settings
+    #[serde(skip)]
+    pub profiles: MatchProfiles,
+    /// Boost applied when the channel and stream icons look the same (0 = off)
+    #[serde(default)]
+    pub icon_boost: f64,
+    /// Most differing icon hash bits for icons to count as the same
+    #[serde(default)]
+    pub icon_max_distance: u32,
+    /// App data directory holding the logo cache icons are compared from
+    #[serde(skip)]
+    pub icon_cache_dir: Option<PathBuf>,
+    /// String similarity for the base score
+    #[serde(default)]
+    pub algorithm: MatchAlgorithm,
+    /// Boost applied when the channel and stream are in the same region (0 = off)
+    #[serde(default)]
+    pub region_boost: f64,
+    /// Penalty applied when the channel and stream are in different regions (0 = off)
+    #[serde(default)]
+    pub region_penalty: f64,
+    /// Enabled plugins adjusting names and scores
+    #[serde(skip)]
+    pub plugins: Plugins,
 }
 
 impl Default for MatchConfig {
@@ -47,6 +130,16 @@ impl Default for MatchConfig {
             threshold: 0.85,
             epg_id_boost: 0.15,
             exact_name_boost: 0.10,
+            alias_hints: AliasHints::new(),
+            name_rules: NameRules::default(),
+            profiles: MatchProfiles::default(),
+            icon_boost: 0.0,
+            icon_max_distance: 0,
+            icon_cache_dir: None,
+            algorithm: MatchAlgorithm::JaroWinkler,
+            region_boost: 0.0,
+            region_penalty: 0.0,
+            plugins: Plugins::default(),
         }
     }
 }
@@ -56,6 +149,50 @@ impl MatchConfig {
         self.threshold = threshold;
         self
     }
+
+    pub fn with_algorithm(mut self, algorithm: MatchAlgorithm) -> Self {
+        self.algorithm = algorithm;
+        self
+    }
+
+    pub fn with_alias_hints(mut self, alias_hints: AliasHints) -> Self {
+        self.alias_hints = alias_hints;
+        self
+    }
+
+    pub fn with_name_rules(mut self, name_rules: NameRules) -> Self {
+        self.name_rules = name_rules;
+        self
+    }
+
+    pub fn with_profiles(mut self, profiles: MatchProfiles) -> Self {
+        self.profiles = profiles;
+        self
+    }
+
+    /// Compare icons from the logo cache in `app_data_dir` if icon matching is enabled
+    pub fn with_icon_matching(mut self, icons: &IconMatchConfig, app_data_dir: &Path) -> Self {
+        if icons.enabled {
+            self.icon_boost = icons.boost;
+            self.icon_max_distance = icons.max_distance;
+            self.icon_cache_dir = Some(app_data_dir.to_path_buf());
+        }
+        self
+    }
+
+    /// Boost same-region and penalize cross-region matches if region matching is enabled
+    pub fn with_region_matching(mut self, regions: &RegionMatchConfig) -> Self {
+        if regions.enabled {
+            self.region_boost = regions.boost;
+            self.region_penalty = regions.penalty;
+        }
+        self
+    }
+
+    pub fn with_plugins(mut self, plugins: Plugins) -> Self {
+        self.plugins = plugins;
+        self
+    }
 }
 
 /// The type of match that was found
@@ -66,7 +203,7 @@ pub enum MatchType {
     ExactEpgId,
     /// Normalized names match exactly
     ExactName,
-    /// Jaro-Winkler fuzzy match above threshold
+    /// Fuzzy name match above threshold
     Fuzzy,
     /// No match found (below threshold)
     None,
@@ -82,6 +219,12 @@ pub struct MatchResult {
     pub is_primary: bool,
     pub stream_priority: i32,
     pub match_type: MatchType,
+    /// Region detected for the XMLTV channel
+    #[serde(default)]
+    pub xmltv_region: Option<String>,
+    /// Region detected for the Xtream stream
+    #[serde(default)]
+    pub xtream_region: Option<String>,
 }
 
 impl MatchResult {
@@ -98,6 +241,8 @@ impl MatchResult {
             is_primary: false,
             stream_priority: 0,
             match_type,
+            xmltv_region: None,
+            xtream_region: None,
         }
     }
 
@@ -106,6 +251,12 @@ impl MatchResult {
         self.stream_priority = stream_priority;
         self
     }
+
+    pub fn with_regions(mut self, xmltv_region: Option<&str>, xtream_region: Option<&str>) -> Self {
+        self.xmltv_region = xmltv_region.map(str::to_string);
+        self.xtream_region = xtream_region.map(str::to_string);
+        self
+    }
 }
 
 /// Statistics about a matching operation
@@ -0,0 +1,225 @@
+//! Match Configuration Profiles
+//!
+//! One global threshold does not fit every provider: one account may have
+//! clean stream names while another prefixes everything with noise. Profiles
+//! override the matching settings for the streams of one Xtream account or
+//! the channels of one XMLTV source. Each field left unset falls back to the
+//! next less specific level: account profile, then source profile, then the
+//! global settings.
+//!
+//! Icon similarity is only compared when it is enabled globally; profiles can
+//! change its boost and distance but not turn it on.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use super::{MatchAlgorithm, MatchConfig};
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key for the saved profiles (JSON)
+pub const MATCH_PROFILES_KEY: &str = "match_profiles";
+
+/// Matching settings that replace the ones they are applied to (unset = keep)
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchConfigOverride {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub threshold: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epg_id_boost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub exact_name_boost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_boost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icon_max_distance: Option<u32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub algorithm: Option<MatchAlgorithm>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_boost: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub region_penalty: Option<f64>,
+}
+
+impl MatchConfigOverride {
+    /// Whether no setting is overridden
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Check that the values are in range
+    pub fn validate(&self) -> Result<(), String> {
+        for (value, name) in [
+            (self.threshold, "Threshold"),
+            (self.epg_id_boost, "EPG ID boost"),
+            (self.exact_name_boost, "Exact name boost"),
+            (self.icon_boost, "Icon boost"),
+            (self.region_boost, "Region boost"),
+            (self.region_penalty, "Region penalty"),
+        ] {
+            if value.is_some_and(|v| !(0.0..=1.0).contains(&v)) {
+                return Err(format!("{} must be between 0.0 and 1.0", name));
+            }
+        }
+        if self.icon_max_distance.is_some_and(|d| d > 64) {
+            return Err("Icon distance must be between 0 and 64".to_string());
+        }
+        Ok(())
+    }
+
+    /// Apply the override to a configuration, validating the values
+    pub fn apply(&self, mut config: MatchConfig) -> Result<MatchConfig, String> {
+        self.validate()?;
+        self.merge_into(&mut config);
+        Ok(config)
+    }
+
+    /// Replace the overridden settings of `config`
+    fn merge_into(&self, config: &mut MatchConfig) {
+        if let Some(threshold) = self.threshold {
+            config.threshold = threshold;
+        }
+        if let Some(boost) = self.epg_id_boost {
+            config.epg_id_boost = boost;
+        }
+        if let Some(boost) = self.exact_name_boost {
+            config.exact_name_boost = boost;
+        }
+        if let Some(boost) = self.icon_boost {
+            config.icon_boost = boost;
+        }
+        if let Some(distance) = self.icon_max_distance {
+            config.icon_max_distance = distance;
+        }
+        if let Some(algorithm) = self.algorithm {
+            config.algorithm = algorithm;
+        }
+        if let Some(boost) = self.region_boost {
+            config.region_boost = boost;
+        }
+        if let Some(penalty) = self.region_penalty {
+            config.region_penalty = penalty;
+        }
+    }
+}
+
+/// Per-account and per-source matching profiles
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchProfiles {
+    /// Keyed by Xtream account ID
+    #[serde(default)]
+    pub accounts: HashMap<i32, MatchConfigOverride>,
+    /// Keyed by XMLTV source ID
+    #[serde(default)]
+    pub sources: HashMap<i32, MatchConfigOverride>,
+}
+
+impl MatchProfiles {
+    pub fn is_empty(&self) -> bool {
+        self.accounts.is_empty() && self.sources.is_empty()
+    }
+
+    /// Scoring settings for a channel of `source_id` and a stream of `account_id`
+    ///
+    /// Returns None when no profile applies, so `base` is used as is.
+    pub fn resolve(
+        &self,
+        base: &MatchConfig,
+        source_id: i32,
+        account_id: i32,
+    ) -> Option<MatchConfig> {
+        let source = self.sources.get(&source_id);
+        let account = self.accounts.get(&account_id);
+        if source.is_none() && account.is_none() {
+            return None;
+        }
+
+        // Only the scoring settings; hints, rules and the icon cache stay with `base`
+        let mut config = MatchConfig {
+            threshold: base.threshold,
+            epg_id_boost: base.epg_id_boost,
+            exact_name_boost: base.exact_name_boost,
+            icon_boost: base.icon_boost,
+            icon_max_distance: base.icon_max_distance,
+            algorithm: base.algorithm,
+            region_boost: base.region_boost,
+            region_penalty: base.region_penalty,
+            ..MatchConfig::default()
+        };
+        for profile in [source, account].into_iter().flatten() {
+            profile.merge_into(&mut config);
+        }
+        Some(config)
+    }
+}
+
+/// Load the saved profiles (none if unset or unreadable)
+pub fn load_match_profiles(conn: &mut SqliteConnection) -> MatchProfiles {
+    settings::table
+        .filter(settings::key.eq(MATCH_PROFILES_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the profiles
+pub fn save_match_profiles(
+    conn: &mut SqliteConnection,
+    profiles: &MatchProfiles,
+) -> Result<(), String> {
+    let json = serde_json::to_string(profiles)
+        .map_err(|e| format!("Failed to serialize match profiles: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(MATCH_PROFILES_KEY, json))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_account_profile_overrides_source_profile() {
+        let base = MatchConfig::default();
+        let mut profiles = MatchProfiles::default();
+        profiles.sources.insert(
+            1,
+            MatchConfigOverride {
+                threshold: Some(0.9),
+                exact_name_boost: Some(0.2),
+                ..Default::default()
+            },
+        );
+        profiles.accounts.insert(
+            7,
+            MatchConfigOverride {
+                threshold: Some(0.75),
+                ..Default::default()
+            },
+        );
+
+        assert!(profiles.resolve(&base, 2, 8).is_none());
+
+        let source_only = profiles.resolve(&base, 1, 8).unwrap();
+        assert!((source_only.threshold - 0.9).abs() < f64::EPSILON);
+
+        let both = profiles.resolve(&base, 1, 7).unwrap();
+        assert!((both.threshold - 0.75).abs() < f64::EPSILON);
+        assert!((both.exact_name_boost - 0.2).abs() < f64::EPSILON);
+        assert!((both.epg_id_boost - base.epg_id_boost).abs() < f64::EPSILON);
+
+        // Integer keys survive the JSON round trip
+        let json = serde_json::to_string(&profiles).unwrap();
+        assert_eq!(
+            serde_json::from_str::<MatchProfiles>(&json).unwrap(),
+            profiles
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! Channel Region Detection
+//!
+//! Providers carry the same channel for several countries ("UK| Sky News",
+//! "US| Sky News") and guides often do too ("SkyNews.uk"), so name similarity
+//! alone cross-matches regions. The region of a guide channel is read from
+//! the country suffix of its XMLTV ID or from its display name; the region of
+//! a stream from its name, then its category ("UK | News", "Germany").
+//!
+//! Regions are ISO 3166 country codes; common provider spellings (UK, USA,
+//! GER, "United Kingdom") are mapped onto them. When region matching is
+//! enabled, a stream in the same region as the channel gets a small boost and
+//! one in a different region a penalty. Nothing changes when either region is
+//! unknown or the EPG IDs already match.
+
+use diesel::prelude::*;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::LazyLock;
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key for the region matching configuration (JSON)
+pub const REGION_MATCH_KEY: &str = "match_region";
+
+/// Provider spellings of a region, uppercase, and the region code they stand for
+const REGION_ALIASES: &[(&str, &str)] = &[
+    ("UK", "GB"),
+    ("GB", "GB"),
+    ("GBR", "GB"),
+    ("ENGLAND", "GB"),
+    ("UNITED KINGDOM", "GB"),
+    ("US", "US"),
+    ("USA", "US"),
+    ("UNITED STATES", "US"),
+    ("CA", "CA"),
+    ("CAN", "CA"),
+    ("CANADA", "CA"),
+    ("AU", "AU"),
+    ("AUS", "AU"),
+    ("AUSTRALIA", "AU"),
+    ("IE", "IE"),
+    ("IRL", "IE"),
+    ("IRELAND", "IE"),
+    ("DE", "DE"),
+    ("GER", "DE"),
+    ("GERMANY", "DE"),
+    ("DEUTSCHLAND", "DE"),
+    ("AT", "AT"),
+    ("AUSTRIA", "AT"),
+    ("CH", "CH"),
+    ("SWISS", "CH"),
+    ("SWITZERLAND", "CH"),
+    ("FR", "FR"),
+    ("FRA", "FR"),
+    ("FRANCE", "FR"),
+    ("BE", "BE"),
+    ("BELGIUM", "BE"),
+    ("NL", "NL"),
+    ("NED", "NL"),
+    ("NETHERLANDS", "NL"),
+    ("HOLLAND", "NL"),
+    ("ES", "ES"),
+    ("ESP", "ES"),
+    ("SPAIN", "ES"),
+    ("ESPANA", "ES"),
+    ("PT", "PT"),
+    ("POR", "PT"),
+    ("PORTUGAL", "PT"),
+    ("IT", "IT"),
+    ("ITA", "IT"),
+    ("ITALY", "IT"),
+    ("ITALIA", "IT"),
+    ("PL", "PL"),
+    ("POL", "PL"),
+    ("POLAND", "PL"),
+    ("SE", "SE"),
+    ("SWE", "SE"),
+    ("SWEDEN", "SE"),
+    ("NO", "NO"),
+    ("NOR", "NO"),
+    ("NORWAY", "NO"),
+    ("DK", "DK"),
+    ("DEN", "DK"),
+    ("DENMARK", "DK"),
+    ("FI", "FI"),
+    ("FIN", "FI"),
+    ("FINLAND", "FI"),
+    ("GR", "GR"),
+    ("GRE", "GR"),
+    ("GREECE", "GR"),
+    ("TR", "TR"),
+    ("TUR", "TR"),
+    ("TURKEY", "TR"),
+    ("RO", "RO"),
+    ("ROM", "RO"),
+    ("ROMANIA", "RO"),
+    ("MX", "MX"),
+    ("MEX", "MX"),
+    ("MEXICO", "MX"),
+    ("BR", "BR"),
+    ("BRA", "BR"),
+    ("BRAZIL", "BR"),
+    ("BRASIL", "BR"),
+    ("ARG", "AR"),
+    ("ARGENTINA", "AR"),
+    ("IN", "IN"),
+    ("IND", "IN"),
+    ("INDIA", "IN"),
+];
+
+/// Short code at the start of a name, followed by a separator ("UK|", "[US]", "DE: ")
+static PREFIX_CODE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\s*[\[(|]?\s*([A-Za-z]{2,3})\s*(?:[\])|:]|-\s|\s-)").unwrap());
+
+/// Short code in brackets or bars anywhere ("BBC One (UK)", "ESPN |US|")
+static BRACKETED_CODE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"[\[(|]\s*([A-Za-z]{2,3})\s*[\])|]").unwrap());
+
+/// Uppercase short code ending a name ("FOX NEWS US")
+static TRAILING_CODE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\s([A-Z]{2,3})\s*$").unwrap());
+
+/// Country suffix of an XMLTV channel ID ("SkyNews.uk", "bbc1.uk@HD")
+static CHANNEL_ID_SUFFIX_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\.([A-Za-z]{2,3})(?:@.*)?$").unwrap());
+
+/// Region code for a provider spelling
+fn lookup(token: &str) -> Option<&'static str> {
+    let token = token.trim().to_uppercase();
+    REGION_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == token)
+        .map(|(_, code)| *code)
+}
+
+/// Region named by a full country name in `text` ("Sky Sports Germany")
+fn country_name(text: &str) -> Option<&'static str> {
+    let words: Vec<String> = text
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|w| !w.is_empty())
+        .map(str::to_uppercase)
+        .collect();
+    let phrase = format!(" {} ", words.join(" "));
+    REGION_ALIASES
+        .iter()
+        .filter(|(alias, _)| alias.len() > 3)
+        .find(|(alias, _)| phrase.contains(&format!(" {} ", alias)))
+        .map(|(_, code)| *code)
+}
+
+/// Region of a channel or stream name, from codes marked by separators or
+/// brackets, an uppercase trailing code, or a country name
+pub fn detect_region(name: &str) -> Option<&'static str> {
+    let marked = PREFIX_CODE_REGEX
+        .captures_iter(name)
+        .chain(BRACKETED_CODE_REGEX.captures_iter(name))
+        .chain(TRAILING_CODE_REGEX.captures_iter(name))
+        .find_map(|caps| lookup(&caps[1]));
+    marked.or_else(|| country_name(name))
+}
+
+/// Region of an XMLTV channel: the country suffix of its ID, else its name
+pub fn detect_channel_region(display_name: &str, channel_id: &str) -> Option<&'static str> {
+    CHANNEL_ID_SUFFIX_REGEX
+        .captures(channel_id.trim())
+        .and_then(|caps| lookup(&caps[1]))
+        .or_else(|| detect_region(display_name))
+}
+
+/// Region of an Xtream stream: from its name, else its category
+///
+/// Any uppercase region code counts in a category ("UK Sports", "DE Entertainment").
+pub fn detect_stream_region(name: &str, category_name: Option<&str>) -> Option<&'static str> {
+    detect_region(name).or_else(|| {
+        let category = category_name?;
+        detect_region(category).or_else(|| {
+            category
+                .split(|c: char| !c.is_alphanumeric())
+                .filter(|word| word.len() >= 2 && word.chars().all(|c| c.is_ascii_uppercase()))
+                .find_map(lookup)
+        })
+    })
+}
+
+/// Whether two regions agree (None when either is unknown)
+pub fn regions_agree(a: Option<&str>, b: Option<&str>) -> Option<bool> {
+    Some(a? == b?)
+}
+
+/// Region matching configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RegionMatchConfig {
+    pub enabled: bool,
+    /// Added to the match score when the regions are the same
+    pub boost: f64,
+    /// Subtracted from the match score when the regions differ
+    pub penalty: f64,
+}
+
+impl Default for RegionMatchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            boost: 0.05,
+            penalty: 0.20,
+        }
+    }
+}
+
+impl RegionMatchConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(0.0..=0.5).contains(&self.boost) {
+            return Err("Region boost must be between 0.0 and 0.5".to_string());
+        }
+        if !(0.0..=1.0).contains(&self.penalty) {
+            return Err("Region penalty must be between 0.0 and 1.0".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Load the region matching configuration (defaults if unset or unreadable)
+pub fn load_region_match_config(conn: &mut SqliteConnection) -> RegionMatchConfig {
+    settings::table
+        .filter(settings::key.eq(REGION_MATCH_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the region matching configuration
+pub fn save_region_match_config(
+    conn: &mut SqliteConnection,
+    config: &RegionMatchConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize region matching settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(REGION_MATCH_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect_stream_region() {
+        assert_eq!(detect_stream_region("UK| Sky News HD", None), Some("GB"));
+        assert_eq!(detect_stream_region("|US| CNN", None), Some("US"));
+        assert_eq!(detect_stream_region("DE: Sky Sport 1", None), Some("DE"));
+        assert_eq!(detect_stream_region("[FR] TF1", None), Some("FR"));
+        assert_eq!(detect_stream_region("BBC One (UK)", None), Some("GB"));
+        assert_eq!(detect_stream_region("FOX NEWS US", None), Some("US"));
+        assert_eq!(detect_stream_region("Discovery Canada", None), Some("CA"));
+        assert_eq!(
+            detect_stream_region("Sky News", Some("UK | News")),
+            Some("GB")
+        );
+        assert_eq!(
+            detect_stream_region("Sky Sport 1", Some("Germany Sports")),
+            Some("DE")
+        );
+        // Quality tags and ordinary words are not regions
+        assert_eq!(detect_stream_region("ESPN HD", Some("Sports")), None);
+        assert_eq!(detect_stream_region("USA Network", None), None);
+        assert_eq!(detect_stream_region("Sky News (HD)", None), None);
+    }
+
+    #[test]
+    fn test_detect_channel_region() {
+        assert_eq!(detect_channel_region("Sky News", "SkyNews.uk"), Some("GB"));
+        assert_eq!(detect_channel_region("BBC One", "bbc1.uk@HD"), Some("GB"));
+        assert_eq!(detect_channel_region("ESPN", "espn.us"), Some("US"));
+        assert_eq!(detect_channel_region("TF1 (FR)", "tf1"), Some("FR"));
+        assert_eq!(detect_channel_region("ESPN", "espn.com"), None);
+
+        assert_eq!(regions_agree(Some("GB"), Some("GB")), Some(true));
+        assert_eq!(regions_agree(Some("GB"), Some("US")), Some(false));
+        assert_eq!(regions_agree(None, Some("US")), None);
+    }
+}
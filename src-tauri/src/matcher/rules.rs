@@ -0,0 +1,316 @@
+//! User-Defined Match Rules
+//!
+//! The built-in normalization (`normalize_channel_name`) only lowercases and
+//! strips quality suffixes and punctuation. Providers add their own noise
+//! ("US: ", "|UK|", "(backup)") and spell channels differently ("HBO2" vs
+//! "HBO 2"), so users can add rules that run as part of normalization:
+//!
+//! - `strip_prefix` / `strip_suffix`: remove a fixed text from the start or
+//!   end of the raw name (case-insensitive)
+//! - `regex`: replace matches of a regular expression in the raw name
+//!   (`$1` etc. refer to capture groups)
+//! - `alias`: treat two spellings as the same name; wherever the words of
+//!   `pattern` appear in a normalized name they are replaced by `replacement`,
+//!   so names using either spelling normalize the same way
+//!
+//! Prefix, suffix and regex rules run on the raw name before the built-in
+//! normalization; alias rules run on the normalized name afterwards. Within
+//! each group rules run in `sort_order`, then creation order.
+
+use diesel::prelude::*;
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+
+use super::fuzzy::normalize_channel_name;
+use crate::db::models::{MatchRule, NewMatchRule};
+use crate::db::schema::match_rules;
+
+/// Longest accepted pattern or replacement
+const MAX_RULE_TEXT_LEN: usize = 200;
+
+/// Compiled size limit for user regexes (bytes)
+const REGEX_SIZE_LIMIT: usize = 1024 * 1024;
+
+/// Kind of match rule
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MatchRuleType {
+    StripPrefix,
+    StripSuffix,
+    Regex,
+    Alias,
+}
+
+impl MatchRuleType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MatchRuleType::StripPrefix => "strip_prefix",
+            MatchRuleType::StripSuffix => "strip_suffix",
+            MatchRuleType::Regex => "regex",
+            MatchRuleType::Alias => "alias",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "strip_prefix" => Some(MatchRuleType::StripPrefix),
+            "strip_suffix" => Some(MatchRuleType::StripSuffix),
+            "regex" => Some(MatchRuleType::Regex),
+            "alias" => Some(MatchRuleType::Alias),
+            _ => None,
+        }
+    }
+}
+
+/// Match rule as entered by the user
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchRuleInput {
+    pub rule_type: MatchRuleType,
+    pub pattern: String,
+    #[serde(default)]
+    pub replacement: String,
+    #[serde(default = "default_enabled")]
+    pub is_enabled: bool,
+    #[serde(default)]
+    pub sort_order: i32,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+impl MatchRuleInput {
+    /// Validate the rule, returning the row to save
+    pub fn validate(&self) -> Result<NewMatchRule, String> {
+        if self.pattern.chars().count() > MAX_RULE_TEXT_LEN
+            || self.replacement.chars().count() > MAX_RULE_TEXT_LEN
+        {
+            return Err(format!(
+                "Pattern and replacement must be at most {} characters",
+                MAX_RULE_TEXT_LEN
+            ));
+        }
+        compile_rule(self.rule_type, &self.pattern, &self.replacement)?;
+
+        Ok(NewMatchRule {
+            rule_type: self.rule_type.as_str().to_string(),
+            pattern: self.pattern.clone(),
+            replacement: self.replacement.clone(),
+            is_enabled: i32::from(self.is_enabled),
+            sort_order: self.sort_order,
+        })
+    }
+}
+
+/// A rule ready to apply
+#[derive(Debug, Clone)]
+struct CompiledRule {
+    id: Option<i32>,
+    /// Runs on the normalized name (alias) rather than the raw name
+    normalized: bool,
+    regex: Regex,
+    replacement: String,
+}
+
+/// Build the regex and replacement for a rule
+fn compile_rule(
+    rule_type: MatchRuleType,
+    pattern: &str,
+    replacement: &str,
+) -> Result<(bool, Regex, String), String> {
+    let build = |pattern: &str| {
+        RegexBuilder::new(pattern)
+            .size_limit(REGEX_SIZE_LIMIT)
+            .build()
+            .map_err(|e| format!("Invalid pattern: {}", e))
+    };
+
+    match rule_type {
+        MatchRuleType::StripPrefix | MatchRuleType::StripSuffix => {
+            let text = pattern.trim();
+            if text.is_empty() {
+                return Err("Text to strip must not be empty".to_string());
+            }
+            let regex = if rule_type == MatchRuleType::StripPrefix {
+                format!(r"(?i)^\s*{}\s*", regex::escape(text))
+            } else {
+                format!(r"(?i)\s*{}\s*$", regex::escape(text))
+            };
+            Ok((false, build(&regex)?, String::new()))
+        }
+        MatchRuleType::Regex => {
+            if pattern.is_empty() {
+                return Err("Pattern must not be empty".to_string());
+            }
+            Ok((false, build(pattern)?, replacement.to_string()))
+        }
+        MatchRuleType::Alias => {
+            let from = normalize_channel_name(pattern);
+            let to = normalize_channel_name(replacement);
+            if from.is_empty() || to.is_empty() {
+                return Err("Both names of an alias must contain letters or digits".to_string());
+            }
+            if from == to {
+                return Err("Both names of an alias normalize to the same name".to_string());
+            }
+            let regex = format!(r"\b{}\b", regex::escape(&from));
+            Ok((true, build(&regex)?, to))
+        }
+    }
+}
+
+/// Enabled match rules, compiled for normalization
+#[derive(Debug, Clone, Default)]
+pub struct NameRules {
+    rules: Vec<CompiledRule>,
+}
+
+impl NameRules {
+    /// Compile the enabled rules, in `sort_order` (stable)
+    ///
+    /// Rules that no longer compile are skipped so one bad row cannot stop
+    /// matching.
+    pub fn compile(rules: &[MatchRule]) -> Self {
+        let mut enabled: Vec<&MatchRule> = rules.iter().filter(|r| r.is_enabled != 0).collect();
+        enabled.sort_by_key(|r| r.sort_order);
+
+        let rules = enabled
+            .into_iter()
+            .filter_map(|rule| {
+                let rule_type = MatchRuleType::parse(&rule.rule_type)?;
+                match compile_rule(rule_type, &rule.pattern, &rule.replacement) {
+                    Ok((normalized, regex, replacement)) => Some(CompiledRule {
+                        id: rule.id,
+                        normalized,
+                        regex,
+                        replacement,
+                    }),
+                    Err(e) => {
+                        eprintln!("Skipping match rule {:?}: {}", rule.id, e);
+                        None
+                    }
+                }
+            })
+            .collect();
+
+        Self { rules }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Normalize a channel name with the built-in normalization and these rules
+    pub fn normalize(&self, name: &str) -> String {
+        self.normalize_traced(name).0
+    }
+
+    /// Normalize a channel name, also returning the IDs of the rules that changed it
+    pub fn normalize_traced(&self, name: &str) -> (String, Vec<i32>) {
+        let mut applied = Vec::new();
+        let mut apply = |value: String, rule: &CompiledRule| {
+            let replaced = rule
+                .regex
+                .replace_all(&value, rule.replacement.as_str())
+                .into_owned();
+            if replaced != value {
+                applied.extend(rule.id);
+            }
+            replaced
+        };
+
+        let mut raw = name.to_string();
+        for rule in self.rules.iter().filter(|r| !r.normalized) {
+            raw = apply(raw, rule);
+        }
+
+        let mut normalized = normalize_channel_name(&raw);
+        for rule in self.rules.iter().filter(|r| r.normalized) {
+            normalized = apply(normalized, rule);
+        }
+        let normalized = normalized.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        (normalized, applied)
+    }
+}
+
+/// Load the saved match rules, in order
+pub fn load_match_rules(conn: &mut SqliteConnection) -> QueryResult<Vec<MatchRule>> {
+    match_rules::table
+        .order((match_rules::sort_order.asc(), match_rules::id.asc()))
+        .load(conn)
+}
+
+/// Load and compile the enabled match rules
+pub fn load_name_rules(conn: &mut SqliteConnection) -> QueryResult<NameRules> {
+    Ok(NameRules::compile(&load_match_rules(conn)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: i32, rule_type: MatchRuleType, pattern: &str, replacement: &str) -> MatchRule {
+        MatchRule {
+            id: Some(id),
+            rule_type: rule_type.as_str().to_string(),
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            is_enabled: 1,
+            sort_order: 0,
+            created_at: String::new(),
+            updated_at: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_rules_normalize_provider_names() {
+        let rules = NameRules::compile(&[
+            rule(1, MatchRuleType::StripPrefix, "US:", ""),
+            rule(2, MatchRuleType::Regex, r"\s*\|[A-Z]{2}\|\s*", " "),
+            rule(3, MatchRuleType::Alias, "HBO2", "HBO 2"),
+            rule(4, MatchRuleType::StripSuffix, "(backup)", ""),
+        ]);
+
+        assert_eq!(rules.normalize("us: HBO2 HD"), "hbo 2");
+        assert_eq!(rules.normalize("HBO 2"), "hbo 2");
+        assert_eq!(rules.normalize("|UK| BBC One (Backup)"), "bbc one");
+        // Alias only replaces whole words
+        assert_eq!(rules.normalize("HBO20"), "hbo20");
+
+        let (_, applied) = rules.normalize_traced("US: HBO2");
+        assert_eq!(applied, vec![1, 3]);
+
+        let mut disabled = rule(5, MatchRuleType::StripPrefix, "UK", "");
+        disabled.is_enabled = 0;
+        assert_eq!(
+            NameRules::compile(&[disabled]).normalize("UK News"),
+            "uk news"
+        );
+    }
+
+    #[test]
+    fn test_invalid_rules_rejected() {
+        let input = |rule_type, pattern: &str, replacement: &str| MatchRuleInput {
+            rule_type,
+            pattern: pattern.to_string(),
+            replacement: replacement.to_string(),
+            is_enabled: true,
+            sort_order: 0,
+        };
+
+        assert!(input(MatchRuleType::Regex, "(unclosed", "")
+            .validate()
+            .is_err());
+        assert!(input(MatchRuleType::StripPrefix, "  ", "")
+            .validate()
+            .is_err());
+        assert!(input(MatchRuleType::Alias, "HBO 2", "hbo-2")
+            .validate()
+            .is_err());
+        assert!(input(MatchRuleType::Alias, "HBO2", "HBO 2")
+            .validate()
+            .is_ok());
+    }
+}
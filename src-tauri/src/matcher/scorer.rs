@@ -1,17 +1,19 @@
 //! Match Confidence Scoring
 //!
-//! Provides scoring algorithms for channel matching using Jaro-Winkler
-//! similarity with boosts for EPG ID and exact name matches.
+//! Provides scoring algorithms for channel matching using string similarity
+//! (Jaro-Winkler by default) with boosts for EPG ID, exact name and icon
+//! matches.
 
-use strsim::jaro_winkler;
+use strsim::{jaro_winkler, normalized_levenshtein, sorensen_dice};
 
-use super::MatchConfig;
+use super::{MatchAlgorithm, MatchConfig};
 
 /// Calculate the match score between an XMLTV channel name and an Xtream stream name.
 ///
-/// The score is based on Jaro-Winkler similarity with optional boosts:
+/// The score is based on the configured string similarity with optional boosts:
 /// - EPG ID match boost (+0.15 default) when the Xtream stream's EPG ID matches the XMLTV channel ID
 /// - Exact name boost (+0.10 default) when normalized names are identical
+/// - Icon boost (off by default) when the channel and stream icons look the same
 ///
 /// The final score is clamped to a maximum of 1.0.
 ///
@@ -21,6 +23,7 @@ use super::MatchConfig;
 /// * `xtream_name` - The normalized Xtream stream name
 /// * `epg_id_match` - Whether the EPG IDs match exactly
 /// * `exact_name_match` - Whether the normalized names are identical
+/// * `icon_match` - Whether the icons are visually identical
 /// * `config` - The match configuration containing boost values
 ///
 /// # Returns
@@ -31,10 +34,11 @@ pub fn calculate_match_score(
     xtream_name: &str,
     epg_id_match: bool,
     exact_name_match: bool,
+    icon_match: bool,
     config: &MatchConfig,
 ) -> f64 {
-    // Base score from Jaro-Winkler similarity
-    let base_score = jaro_winkler(xmltv_name, xtream_name);
+    // Base score from string similarity
+    let base_score = similarity(config.algorithm, xmltv_name, xtream_name);
 
     // Apply boosts
     let epg_boost = if epg_id_match { config.epg_id_boost } else { 0.0 };
@@ -43,9 +47,31 @@ pub fn calculate_match_score(
     } else {
         0.0
     };
+    let icon_boost = if icon_match { config.icon_boost } else { 0.0 };
 
     // Clamp to maximum of 1.0
-    (base_score + epg_boost + exact_boost).min(1.0)
+    (base_score + epg_boost + exact_boost + icon_boost).min(1.0)
+}
+
+/// Score adjustment for the regions of a channel and stream
+///
+/// `agreement` is None when either region is unknown, which leaves the score
+/// unchanged.
+pub fn region_adjustment(agreement: Option<bool>, config: &MatchConfig) -> f64 {
+    match agreement {
+        Some(true) => config.region_boost,
+        Some(false) => -config.region_penalty,
+        None => 0.0,
+    }
+}
+
+/// Similarity of two normalized names (0.0 to 1.0) with the given algorithm
+pub fn similarity(algorithm: MatchAlgorithm, s1: &str, s2: &str) -> f64 {
+    match algorithm {
+        MatchAlgorithm::JaroWinkler => jaro_winkler(s1, s2),
+        MatchAlgorithm::Levenshtein => normalized_levenshtein(s1, s2),
+        MatchAlgorithm::SorensenDice => sorensen_dice(s1, s2),
+    }
 }
 
 /// Calculate the raw Jaro-Winkler similarity score between two strings.
@@ -66,7 +92,7 @@ mod tests {
     #[test]
     fn test_exact_match_score() {
         let config = default_config();
-        let score = calculate_match_score("espn", "espn", false, true, &config);
+        let score = calculate_match_score("espn", "espn", false, true, false, &config);
         // Jaro-Winkler of identical strings is 1.0, plus exact name boost of 0.10, clamped to 1.0
         assert!((score - 1.0).abs() < f64::EPSILON);
     }
@@ -75,7 +101,7 @@ mod tests {
     fn test_fuzzy_match_score() {
         let config = default_config();
         // "espn" vs "espn hd" should have high similarity but not perfect
-        let score = calculate_match_score("espn", "espn hd", false, false, &config);
+        let score = calculate_match_score("espn", "espn hd", false, false, false, &config);
         assert!(score > 0.8);
         assert!(score < 1.0);
     }
@@ -84,8 +110,8 @@ mod tests {
     fn test_epg_id_boost() {
         let config = default_config();
         // Use strings with lower base similarity to avoid clamping
-        let base_score = calculate_match_score("abc", "abd", false, false, &config);
-        let boosted_score = calculate_match_score("abc", "abd", true, false, &config);
+        let base_score = calculate_match_score("abc", "abd", false, false, false, &config);
+        let boosted_score = calculate_match_score("abc", "abd", true, false, false, &config);
 
         // Score with EPG boost should be higher
         assert!(boosted_score > base_score);
@@ -103,8 +129,8 @@ mod tests {
     #[test]
     fn test_exact_name_boost() {
         let config = default_config();
-        let base_score = calculate_match_score("espn", "espn", false, false, &config);
-        let boosted_score = calculate_match_score("espn", "espn", false, true, &config);
+        let base_score = calculate_match_score("espn", "espn", false, false, false, &config);
+        let boosted_score = calculate_match_score("espn", "espn", false, true, false, &config);
 
         // Score with exact name boost should be higher (but clamped to 1.0)
         assert!(boosted_score >= base_score);
@@ -115,14 +141,27 @@ mod tests {
     fn test_combined_boosts_clamped() {
         let config = default_config();
         // With both boosts on an exact match, should clamp to 1.0
-        let score = calculate_match_score("espn", "espn", true, true, &config);
+        let score = calculate_match_score("espn", "espn", true, true, false, &config);
         assert!((score - 1.0).abs() < f64::EPSILON);
     }
 
+    #[test]
+    fn test_icon_boost() {
+        let mut config = default_config();
+        let without_boost = calculate_match_score("abc", "abd", false, false, true, &config);
+        config.icon_boost = 0.10;
+        let base_score = calculate_match_score("abc", "abd", false, false, false, &config);
+        let boosted_score = calculate_match_score("abc", "abd", false, false, true, &config);
+
+        // Icon matching is off unless a boost is configured
+        assert!((without_boost - base_score).abs() < f64::EPSILON);
+        assert!((boosted_score - base_score - 0.10).abs() < 1e-9);
+    }
+
     #[test]
     fn test_low_similarity_no_boost() {
         let config = default_config();
-        let score = calculate_match_score("cnn", "fox news", false, false, &config);
+        let score = calculate_match_score("cnn", "fox news", false, false, false, &config);
         // Completely different names should have low score
         assert!(score < 0.5);
     }
@@ -143,14 +182,42 @@ mod tests {
 
         // Test cases - verifying relative scoring behavior
         // High similarity channels should score high
-        let espn_score = calculate_match_score("espn", "espn hd", false, false, &config);
+        let espn_score = calculate_match_score("espn", "espn hd", false, false, false, &config);
         assert!(espn_score > 0.85, "ESPN vs ESPN HD should score > 0.85, got {}", espn_score);
 
-        let bbc_score = calculate_match_score("bbc one", "bbc one uk", false, false, &config);
+        let bbc_score = calculate_match_score("bbc one", "bbc one uk", false, false, false, &config);
         assert!(bbc_score > 0.85, "BBC One vs BBC One UK should score > 0.85, got {}", bbc_score);
 
         // Low similarity channels should score low
-        let low_score = calculate_match_score("cnn", "fox news", false, false, &config);
+        let low_score = calculate_match_score("cnn", "fox news", false, false, false, &config);
         assert!(low_score < 0.60, "CNN vs Fox News should score < 0.60, got {}", low_score);
     }
+
+    #[test]
+    fn test_algorithms_rank_reordered_words_differently() {
+        for algorithm in [
+            MatchAlgorithm::JaroWinkler,
+            MatchAlgorithm::Levenshtein,
+            MatchAlgorithm::SorensenDice,
+        ] {
+            assert!((similarity(algorithm, "espn", "espn") - 1.0).abs() < f64::EPSILON);
+        }
+
+        let dice = similarity(
+            MatchAlgorithm::SorensenDice,
+            "sky sports news",
+            "news sky sports",
+        );
+        let levenshtein = similarity(
+            MatchAlgorithm::Levenshtein,
+            "sky sports news",
+            "news sky sports",
+        );
+        assert!(dice > 0.9, "got {}", dice);
+        assert!(levenshtein < 0.4, "got {}", levenshtein);
+
+        let config = MatchConfig::default().with_algorithm(MatchAlgorithm::SorensenDice);
+        let score = calculate_match_score("sky sports news", "news sky sports", false, false, false, &config);
+        assert!((score - dice).abs() < f64::EPSILON);
+    }
 }
@@ -0,0 +1,90 @@
+//! Quality Variants
+//!
+//! Providers often list one channel once per quality ("ESPN FHD", "ESPN HD").
+//! Streams of one account whose names are equal once the quality suffix is
+//! removed are variants of the same channel. Matched to an XMLTV channel they
+//! act as one logical mapping: they are kept together in priority order, led
+//! by the best quality, so failover steps down through the variants before
+//! it moves on to another provider.
+
+use super::fuzzy::normalize_channel_name;
+
+/// Key shared by the quality variants of a channel on one account
+///
+/// None for names that are only a quality suffix, which can't be grouped.
+pub fn variant_key(account_id: i32, name: &str) -> Option<(i32, String)> {
+    let normalized = normalize_channel_name(name);
+    (!normalized.is_empty()).then_some((account_id, normalized))
+}
+
+/// Keep the quality variants of each channel together
+///
+/// Each group stays where its first stream is; the rest of the group follows
+/// it, stepping down in quality from the first stream, then any variants of
+/// higher quality. Streams outside groups keep their order.
+///
+/// `key` is the stream's `variant_key` and `rank` its best quality rank
+/// (`crate::xtream::quality::best_tier_rank`, 0 is the highest).
+pub fn group_variants<T>(
+    streams: Vec<T>,
+    key: impl Fn(&T) -> Option<(i32, String)>,
+    rank: impl Fn(&T) -> usize,
+) -> Vec<T> {
+    let keys: Vec<Option<(i32, String)>> = streams.iter().map(&key).collect();
+    let ranks: Vec<usize> = streams.iter().map(&rank).collect();
+
+    let mut order = Vec::with_capacity(streams.len());
+    let mut placed = vec![false; streams.len()];
+    for first in 0..streams.len() {
+        if placed[first] {
+            continue;
+        }
+        placed[first] = true;
+        order.push(first);
+        let Some(group) = &keys[first] else {
+            continue;
+        };
+
+        let mut variants: Vec<usize> = (first + 1..streams.len())
+            .filter(|&i| !placed[i] && keys[i].as_ref() == Some(group))
+            .collect();
+        variants.sort_by_key(|&i| (ranks[i] < ranks[first], ranks[i]));
+        for i in variants {
+            placed[i] = true;
+            order.push(i);
+        }
+    }
+
+    let mut slots: Vec<Option<T>> = streams.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|i| slots[i].take()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_variants_steps_down_quality() {
+        // (id, account, name, quality rank)
+        let streams = vec![
+            (1, 1, "ESPN FHD", 1),
+            (2, 2, "ESPN HD", 2),
+            (3, 1, "ESPN SD", 3),
+            (4, 1, "ESPN 4K", 0),
+            (5, 1, "ESPN HD", 2),
+            (6, 1, "HD", 2),
+            (7, 1, "HD", 2),
+        ];
+        let grouped = group_variants(streams, |s| variant_key(s.1, s.2), |s| s.3);
+        let order: Vec<i32> = grouped.iter().map(|s| s.0).collect();
+        // FHD leads, then HD and SD, then the 4K variant; account 2 is another provider
+        assert_eq!(order, [1, 5, 3, 4, 2, 6, 7]);
+
+        assert_eq!(
+            variant_key(3, "UK: Sky Sports 1 FHD"),
+            variant_key(3, "UK: Sky Sports 1 HD")
+        );
+        assert_ne!(variant_key(3, "ESPN HD"), variant_key(4, "ESPN HD"));
+        assert_eq!(variant_key(1, "HD"), None);
+    }
+}
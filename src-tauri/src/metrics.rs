@@ -0,0 +1,509 @@
+//! Metrics snapshot export
+//!
+//! For setups without a metrics scraper, a snapshot of StreamForge's stats
+//! (active streams, per-account connections and traffic, lineup and EPG
+//! sizes, recent failovers) can be written to a file on a schedule, either as
+//! JSON or in the Prometheus text format read by node_exporter's textfile
+//! collector. The scheduler checks every minute whether an export is due.
+//!
+//! The file is written to a temporary file next to it and renamed into
+//! place, so readers never see a partial snapshot.
+
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::path::Path;
+use std::sync::Mutex;
+
+use crate::db::schema::{
+    accounts, channel_mappings, event_log, failover_history, programs, settings, stream_stats,
+    xmltv_channel_settings, xmltv_channels, xmltv_sources,
+};
+use crate::db::{Setting, SYNTHETIC_SOURCE_ID};
+use crate::server::stream::StreamManager;
+
+/// Settings key for the metrics export configuration (JSON)
+pub const METRICS_EXPORT_KEY: &str = "metrics_export";
+
+/// Longest allowed export interval (one day)
+const MAX_INTERVAL_MINUTES: u32 = 1440;
+
+/// Time format of `failover_history.occurred_at`
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// When the last scheduled export was written
+static LAST_EXPORT: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
+
+/// File format of the snapshot
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MetricsFormat {
+    /// Prometheus text exposition format (node_exporter textfile collector)
+    #[default]
+    Prometheus,
+    Json,
+}
+
+/// Metrics export configuration
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MetricsExportConfig {
+    pub enabled: bool,
+    /// Absolute path of the file to write (e.g. `.../textfile/streamforge.prom`)
+    pub path: String,
+    pub format: MetricsFormat,
+    /// Minutes between exports
+    pub interval_minutes: u32,
+}
+
+impl Default for MetricsExportConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            path: String::new(),
+            format: MetricsFormat::Prometheus,
+            interval_minutes: 1,
+        }
+    }
+}
+
+impl MetricsExportConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(1..=MAX_INTERVAL_MINUTES).contains(&self.interval_minutes) {
+            return Err(format!(
+                "Export interval must be between 1 and {} minutes",
+                MAX_INTERVAL_MINUTES
+            ));
+        }
+        if self.enabled {
+            let path = Path::new(self.path.trim());
+            if self.path.trim().is_empty() || !path.is_absolute() {
+                return Err("Metrics file path must be an absolute path".to_string());
+            }
+            if path.file_name().is_none() {
+                return Err("Metrics file path must name a file".to_string());
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Load the metrics export configuration (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> MetricsExportConfig {
+    settings::table
+        .filter(settings::key.eq(METRICS_EXPORT_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the metrics export configuration
+pub fn save_config(
+    conn: &mut SqliteConnection,
+    config: &MetricsExportConfig,
+) -> Result<(), String> {
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize metrics export settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(METRICS_EXPORT_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Stats of one provider account
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMetrics {
+    pub id: i32,
+    pub name: String,
+    pub is_active: bool,
+    pub active_streams: usize,
+    pub max_connections: i32,
+    /// Bytes of the sessions that ended today (local day)
+    pub bytes_today: i64,
+}
+
+/// Point-in-time stats
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsSnapshot {
+    pub generated_at: DateTime<Utc>,
+    pub active_streams: usize,
+    pub tuner_limit: u32,
+    pub accounts: Vec<AccountMetrics>,
+    pub channels_total: i64,
+    pub channels_enabled: i64,
+    pub channels_mapped: i64,
+    pub epg_sources: i64,
+    pub epg_programs: i64,
+    pub failovers_24h: i64,
+    pub unread_warnings: i64,
+    pub unread_errors: i64,
+}
+
+/// Collect the current stats
+pub fn collect_snapshot(
+    conn: &mut SqliteConnection,
+    stream_manager: &StreamManager,
+    now: DateTime<Utc>,
+) -> QueryResult<MetricsSnapshot> {
+    let sessions = stream_manager.list_sessions();
+    let mut streams_by_account: HashMap<i32, usize> = HashMap::new();
+    for (_, session) in &sessions {
+        if let Some(account_id) = session.account_id {
+            *streams_by_account.entry(account_id).or_default() += 1;
+        }
+    }
+
+    let today = now
+        .with_timezone(&chrono::Local)
+        .format("%Y-%m-%d")
+        .to_string();
+    let mut bytes_today: HashMap<i32, i64> = HashMap::new();
+    for (account_id, bytes) in stream_stats::table
+        .filter(stream_stats::day.eq(&today))
+        .select((stream_stats::account_id, stream_stats::bytes))
+        .load::<(Option<i32>, i64)>(conn)?
+    {
+        if let Some(account_id) = account_id {
+            *bytes_today.entry(account_id).or_default() += bytes;
+        }
+    }
+
+    let accounts = accounts::table
+        .select((
+            accounts::id,
+            accounts::name,
+            accounts::is_active,
+            accounts::max_connections,
+        ))
+        .order(accounts::name.asc())
+        .load::<(Option<i32>, String, i32, i32)>(conn)?
+        .into_iter()
+        .filter_map(|(id, name, is_active, max_connections)| {
+            let id = id?;
+            Some(AccountMetrics {
+                id,
+                name,
+                is_active: is_active != 0,
+                active_streams: streams_by_account.get(&id).copied().unwrap_or(0),
+                max_connections,
+                bytes_today: bytes_today.get(&id).copied().unwrap_or(0),
+            })
+        })
+        .collect();
+
+    let failovers_since = (now - chrono::Duration::hours(24))
+        .format(TIMESTAMP_FORMAT)
+        .to_string();
+
+    Ok(MetricsSnapshot {
+        generated_at: now,
+        active_streams: sessions.len(),
+        tuner_limit: stream_manager.max_connections(),
+        accounts,
+        channels_total: xmltv_channels::table.count().get_result(conn)?,
+        channels_enabled: xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::is_enabled.eq(1))
+            .count()
+            .get_result(conn)?,
+        channels_mapped: channel_mappings::table
+            .select(diesel::dsl::count(channel_mappings::xmltv_channel_id).aggregate_distinct())
+            .first(conn)?,
+        epg_sources: xmltv_sources::table
+            .filter(xmltv_sources::id.ne(SYNTHETIC_SOURCE_ID))
+            .count()
+            .get_result(conn)?,
+        epg_programs: programs::table.count().get_result(conn)?,
+        failovers_24h: failover_history::table
+            .filter(failover_history::occurred_at.ge(failovers_since))
+            .count()
+            .get_result(conn)?,
+        unread_warnings: event_log::table
+            .filter(event_log::level.eq("warn"))
+            .filter(event_log::is_read.eq(0))
+            .count()
+            .get_result(conn)?,
+        unread_errors: event_log::table
+            .filter(event_log::level.eq("error"))
+            .filter(event_log::is_read.eq(0))
+            .count()
+            .get_result(conn)?,
+    })
+}
+
+/// Escape a Prometheus label value
+fn escape_label(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Render a snapshot in the Prometheus text exposition format
+pub fn render_prometheus(snapshot: &MetricsSnapshot) -> String {
+    let mut out = String::new();
+    let mut gauge = |name: &str, help: &str, samples: Vec<(String, String)>| {
+        let _ = writeln!(out, "# HELP streamforge_{} {}", name, help);
+        let _ = writeln!(out, "# TYPE streamforge_{} gauge", name);
+        for (labels, value) in samples {
+            let _ = writeln!(out, "streamforge_{}{} {}", name, labels, value);
+        }
+    };
+    let single = |value: i64| vec![(String::new(), value.to_string())];
+    let per_account = |value: &dyn Fn(&AccountMetrics) -> i64| {
+        snapshot
+            .accounts
+            .iter()
+            .map(|account| {
+                (
+                    format!(
+                        "{{account_id=\"{}\",account=\"{}\"}}",
+                        account.id,
+                        escape_label(&account.name)
+                    ),
+                    value(account).to_string(),
+                )
+            })
+            .collect::<Vec<_>>()
+    };
+
+    gauge(
+        "active_streams",
+        "Streams currently being served.",
+        single(snapshot.active_streams as i64),
+    );
+    gauge(
+        "tuner_limit",
+        "Concurrent streams allowed across all accounts.",
+        single(i64::from(snapshot.tuner_limit)),
+    );
+    gauge(
+        "account_active",
+        "Whether the provider account is enabled.",
+        per_account(&|a| i64::from(a.is_active)),
+    );
+    gauge(
+        "account_active_streams",
+        "Streams currently served from the account.",
+        per_account(&|a| a.active_streams as i64),
+    );
+    gauge(
+        "account_max_connections",
+        "Connection limit of the account.",
+        per_account(&|a| i64::from(a.max_connections)),
+    );
+    gauge(
+        "account_bytes_today",
+        "Bytes streamed today from the account by sessions that have ended.",
+        per_account(&|a| a.bytes_today),
+    );
+    gauge(
+        "channels",
+        "XMLTV channels by lineup state.",
+        vec![
+            (
+                "{state=\"total\"}".to_string(),
+                snapshot.channels_total.to_string(),
+            ),
+            (
+                "{state=\"enabled\"}".to_string(),
+                snapshot.channels_enabled.to_string(),
+            ),
+            (
+                "{state=\"mapped\"}".to_string(),
+                snapshot.channels_mapped.to_string(),
+            ),
+        ],
+    );
+    gauge(
+        "epg_sources",
+        "Configured XMLTV sources.",
+        single(snapshot.epg_sources),
+    );
+    gauge(
+        "epg_programs",
+        "Programs in the guide.",
+        single(snapshot.epg_programs),
+    );
+    gauge(
+        "failovers_24h",
+        "Failovers to a backup stream in the last 24 hours.",
+        single(snapshot.failovers_24h),
+    );
+    gauge(
+        "unread_events",
+        "Unread warning and error events in the event log.",
+        vec![
+            (
+                "{level=\"warn\"}".to_string(),
+                snapshot.unread_warnings.to_string(),
+            ),
+            (
+                "{level=\"error\"}".to_string(),
+                snapshot.unread_errors.to_string(),
+            ),
+        ],
+    );
+    gauge(
+        "snapshot_timestamp_seconds",
+        "When this snapshot was taken (Unix time).",
+        single(snapshot.generated_at.timestamp()),
+    );
+    out
+}
+
+/// Render a snapshot in the configured format
+pub fn render(snapshot: &MetricsSnapshot, format: MetricsFormat) -> Result<String, String> {
+    match format {
+        MetricsFormat::Prometheus => Ok(render_prometheus(snapshot)),
+        MetricsFormat::Json => serde_json::to_string_pretty(snapshot)
+            .map_err(|e| format!("Failed to serialize metrics: {}", e)),
+    }
+}
+
+/// Write `contents` to `path` through a temporary file in the same directory
+fn write_atomically(path: &Path, contents: &str) -> std::io::Result<()> {
+    let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+    // node_exporter only reads *.prom files, so the temporary file is ignored
+    let tmp = path.with_file_name(format!(".{}.tmp", file_name));
+    std::fs::write(&tmp, contents)?;
+    std::fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = std::fs::remove_file(&tmp);
+    })
+}
+
+/// Collect a snapshot and write it as configured
+pub fn export_snapshot(
+    conn: &mut SqliteConnection,
+    stream_manager: &StreamManager,
+    config: &MetricsExportConfig,
+    now: DateTime<Utc>,
+) -> Result<(), String> {
+    let snapshot = collect_snapshot(conn, stream_manager, now)
+        .map_err(|e| format!("Failed to collect metrics: {}", e))?;
+    let contents = render(&snapshot, config.format)?;
+    let path = Path::new(config.path.trim());
+    write_atomically(path, &contents)
+        .map_err(|e| format!("Failed to write metrics to {}: {}", path.display(), e))?;
+    *LAST_EXPORT.lock().unwrap_or_else(|e| e.into_inner()) = Some(now);
+    Ok(())
+}
+
+/// Write a snapshot if export is enabled and the interval has passed
+///
+/// Called by the scheduler every minute. Failures are logged to the event
+/// log once until an export succeeds again.
+pub fn export_if_due(
+    conn: &mut SqliteConnection,
+    stream_manager: &StreamManager,
+    now: DateTime<Utc>,
+) {
+    static FAILING: Mutex<bool> = Mutex::new(false);
+
+    let config = load_config(conn);
+    if !config.enabled {
+        return;
+    }
+    let last = *LAST_EXPORT.lock().unwrap_or_else(|e| e.into_inner());
+    // Slack so a job firing a little early still counts as one interval
+    let interval = chrono::Duration::minutes(i64::from(config.interval_minutes))
+        - chrono::Duration::seconds(5);
+    if last.is_some_and(|last| now - last < interval) {
+        return;
+    }
+
+    let result = export_snapshot(conn, stream_manager, &config, now);
+    let mut failing = FAILING.lock().unwrap_or_else(|e| e.into_inner());
+    match result {
+        Ok(()) => *failing = false,
+        Err(e) => {
+            tracing::warn!("Metrics export failed: {}", e);
+            if !*failing {
+                *failing = true;
+                let details = serde_json::json!({ "path": config.path, "error": e });
+                let _ = crate::commands::logs::log_event_internal(
+                    conn,
+                    "warn",
+                    "system",
+                    &format!("Metrics export failed: {}", e),
+                    Some(&details.to_string()),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::stream::StreamSession;
+
+    #[test]
+    fn test_collect_and_render_snapshot() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, max_connections, is_active) VALUES
+                (1, 'Main \"HD\"', 'http://a.local', 'a', x'', 2, 1), (2, 'Spare', 'http://b.local', 'b', x'', 1, 0)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        let now = Utc::now();
+        let today = now
+            .with_timezone(&chrono::Local)
+            .format("%Y-%m-%d")
+            .to_string();
+        diesel::sql_query(format!(
+            "INSERT INTO stream_stats (xmltv_channel_id, account_id, day, started_at, duration_seconds, bytes) VALUES
+                (1, 1, '{today}', '{today} 10:00:00', 60, 1000), (2, 1, '{today}', '{today} 11:00:00', 60, 500),
+                (1, 1, '2000-01-01', '2000-01-01 10:00:00', 60, 9999)"
+        ))
+        .execute(&mut conn)
+        .unwrap();
+
+        let stream_manager = StreamManager::new(4);
+        stream_manager
+            .start_session(StreamSession::new(1, 100, "HD".to_string()).with_account(1))
+            .unwrap();
+
+        let snapshot = collect_snapshot(&mut conn, &stream_manager, now).unwrap();
+        assert_eq!(snapshot.active_streams, 1);
+        assert_eq!(snapshot.tuner_limit, 4);
+        let main = snapshot.accounts.iter().find(|a| a.id == 1).unwrap();
+        assert_eq!((main.active_streams, main.bytes_today), (1, 1500));
+
+        let text = render_prometheus(&snapshot);
+        assert!(text
+            .contains("# TYPE streamforge_active_streams gauge\nstreamforge_active_streams 1\n"));
+        assert!(text.contains(
+            "streamforge_account_bytes_today{account_id=\"1\",account=\"Main \\\"HD\\\"\"} 1500\n"
+        ));
+        assert!(text.contains("streamforge_account_active{account_id=\"2\",account=\"Spare\"} 0\n"));
+
+        let json: serde_json::Value =
+            serde_json::from_str(&render(&snapshot, MetricsFormat::Json).unwrap()).unwrap();
+        assert_eq!(json["accounts"][0]["bytesToday"], 1500);
+    }
+
+    #[test]
+    fn test_config_validation() {
+        assert!(MetricsExportConfig::default().validate().is_ok());
+        let relative = MetricsExportConfig {
+            enabled: true,
+            path: "metrics.prom".to_string(),
+            ..Default::default()
+        };
+        assert!(relative.validate().is_err());
+        let zero_interval = MetricsExportConfig {
+            interval_minutes: 0,
+            ..Default::default()
+        };
+        assert!(zero_interval.validate().is_err());
+    }
+}
@@ -0,0 +1,524 @@
+//! Notifications for important events
+//!
+//! Events written to the event log can also be sent as OS desktop
+//! notifications and to webhooks (Discord, Slack or a generic JSON POST), so
+//! a failed EPG refresh or a channel whose streams all failed is noticed
+//! without opening the app.
+//!
+//! Which events notify is decided by level and category, with per-channel
+//! rules for events that name a channel (`channelId` in their details):
+//! a muted channel never notifies, an "always" channel notifies on warnings
+//! and errors even when its category is not selected. The same message is
+//! sent at most once per `REPEAT_INTERVAL`.
+//!
+//! The settings are stored as JSON and mirrored in a process-wide copy,
+//! refreshed by `load_from_db`, because every logged event is checked
+//! against them. Sending happens in the background; failures are written to
+//! the event log without notifying again.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{LazyLock, Mutex, OnceLock, RwLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::settings;
+use crate::db::{DbConnection, Setting};
+
+/// Settings key for the notification settings (JSON)
+pub const NOTIFICATION_SETTINGS_KEY: &str = "notification_settings";
+
+/// Most webhooks that can be configured
+const MAX_WEBHOOKS: usize = 10;
+
+/// Most per-channel rules
+const MAX_CHANNEL_RULES: usize = 500;
+
+/// The same message is not sent again within this interval
+const REPEAT_INTERVAL: Duration = Duration::from_secs(5 * 60);
+
+/// Timeout of one webhook request
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Longest message sent (webhook services reject very long content)
+const MAX_MESSAGE_CHARS: usize = 1500;
+
+/// Title of desktop notifications
+const NOTIFICATION_TITLE: &str = "StreamForge";
+
+/// Settings in effect
+static ACTIVE: LazyLock<RwLock<NotificationSettings>> =
+    LazyLock::new(|| RwLock::new(NotificationSettings::default()));
+
+/// When each message was last sent
+static LAST_SENT: LazyLock<Mutex<HashMap<String, Instant>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Handle used to show desktop notifications (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+thread_local! {
+    /// Set while logging a failed notification, so it does not notify again
+    static SUPPRESSED: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Lowest event level that notifies
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NotificationLevel {
+    Warn,
+    #[default]
+    Error,
+}
+
+impl NotificationLevel {
+    fn includes(self, level: &str) -> bool {
+        match level {
+            "error" => true,
+            "warn" => self == NotificationLevel::Warn,
+            _ => false,
+        }
+    }
+}
+
+/// Payload format of a webhook
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WebhookKind {
+    Discord,
+    Slack,
+    /// The event as JSON
+    Generic,
+}
+
+/// A configured webhook
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Webhook {
+    pub name: String,
+    pub kind: WebhookKind,
+    pub url: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+/// What a per-channel rule does
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChannelRuleMode {
+    /// Never notify for this channel
+    Mute,
+    /// Notify on warnings and errors whatever the category filter
+    Always,
+}
+
+/// Notification rule for one channel
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelRule {
+    /// XMLTV channel ID
+    pub channel_id: i32,
+    pub mode: ChannelRuleMode,
+}
+
+/// Notification settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct NotificationSettings {
+    /// Show OS desktop notifications
+    pub desktop: bool,
+    pub webhooks: Vec<Webhook>,
+    /// Event categories that notify
+    pub categories: Vec<String>,
+    pub min_level: NotificationLevel,
+    pub channel_rules: Vec<ChannelRule>,
+}
+
+impl Default for NotificationSettings {
+    fn default() -> Self {
+        Self {
+            desktop: false,
+            webhooks: Vec::new(),
+            categories: vec!["epg".to_string(), "stream".to_string()],
+            min_level: NotificationLevel::Error,
+            channel_rules: Vec::new(),
+        }
+    }
+}
+
+impl NotificationSettings {
+    /// Whether anything would be sent at all
+    fn has_targets(&self) -> bool {
+        self.desktop || self.webhooks.iter().any(|webhook| webhook.enabled)
+    }
+
+    /// Whether an event should notify
+    fn matches(&self, level: &str, category: &str, details: Option<&str>) -> bool {
+        let rule = channel_id(details).and_then(|id| {
+            self.channel_rules
+                .iter()
+                .find(|rule| rule.channel_id == id)
+                .map(|rule| rule.mode)
+        });
+        match rule {
+            Some(ChannelRuleMode::Mute) => false,
+            Some(ChannelRuleMode::Always) => NotificationLevel::Warn.includes(level),
+            None => self.min_level.includes(level) && self.categories.iter().any(|c| c == category),
+        }
+    }
+
+    /// Check the settings before they are saved
+    pub fn validate(&self) -> Result<(), String> {
+        if self.webhooks.len() > MAX_WEBHOOKS {
+            return Err(format!("At most {} webhooks are allowed", MAX_WEBHOOKS));
+        }
+        for webhook in &self.webhooks {
+            if webhook.name.trim().is_empty() {
+                return Err("Every webhook needs a name".to_string());
+            }
+            let url = url::Url::parse(webhook.url.trim())
+                .map_err(|_| format!("Invalid URL for webhook '{}'", webhook.name))?;
+            if !matches!(url.scheme(), "http" | "https") {
+                return Err(format!(
+                    "Webhook '{}' must use an http or https URL",
+                    webhook.name
+                ));
+            }
+        }
+        if self.channel_rules.len() > MAX_CHANNEL_RULES {
+            return Err(format!(
+                "At most {} channel rules are allowed",
+                MAX_CHANNEL_RULES
+            ));
+        }
+        Ok(())
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+/// A notification to send
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct Notification {
+    level: String,
+    category: String,
+    message: String,
+    details: Option<serde_json::Value>,
+    timestamp: String,
+}
+
+/// Result of sending a test notification to one target
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NotificationTestResult {
+    /// "Desktop" or the webhook name
+    pub target: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+/// Register the app handle so desktop notifications can be shown
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Load the saved settings (defaults if unset or unreadable)
+pub fn load_settings(conn: &mut SqliteConnection) -> NotificationSettings {
+    settings::table
+        .filter(settings::key.eq(NOTIFICATION_SETTINGS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Validate, persist and apply the settings
+pub fn save_settings(
+    conn: &mut SqliteConnection,
+    settings: &NotificationSettings,
+) -> Result<(), String> {
+    settings.validate()?;
+    let json = serde_json::to_string(settings)
+        .map_err(|e| format!("Failed to serialize notification settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(NOTIFICATION_SETTINGS_KEY, json))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    set_active(settings.clone());
+    Ok(())
+}
+
+/// Read the saved settings and apply them
+///
+/// Called at startup and after switching workspaces.
+pub fn load_from_db(conn: &mut SqliteConnection) {
+    set_active(load_settings(conn));
+}
+
+fn set_active(settings: NotificationSettings) {
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = settings;
+    }
+}
+
+/// Send a notification for a logged event if the settings select it
+///
+/// Called for every event written to the event log; returns right away when
+/// nothing is configured. Sending happens in the background.
+pub fn dispatch(level: &str, category: &str, message: &str, details: Option<&str>) {
+    if SUPPRESSED.with(Cell::get) {
+        return;
+    }
+    let settings = match ACTIVE.read() {
+        Ok(active) if active.has_targets() && active.matches(level, category, details) => {
+            active.clone()
+        }
+        _ => return,
+    };
+    if !due(message, Instant::now()) {
+        return;
+    }
+
+    let notification = Notification {
+        level: level.to_string(),
+        category: category.to_string(),
+        message: message.chars().take(MAX_MESSAGE_CHARS).collect(),
+        details: details.and_then(|d| serde_json::from_str(d).ok()),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    tauri::async_runtime::spawn(async move {
+        let failures: Vec<NotificationTestResult> = send_all(&settings, &notification)
+            .await
+            .into_iter()
+            .filter(|result| !result.ok)
+            .collect();
+        if !failures.is_empty() {
+            log_failures(&failures);
+        }
+    });
+}
+
+/// Send a test notification to every enabled target, whatever the filters
+pub async fn send_test(settings: &NotificationSettings) -> Vec<NotificationTestResult> {
+    let notification = Notification {
+        level: "info".to_string(),
+        category: "system".to_string(),
+        message: "Test notification from StreamForge".to_string(),
+        details: None,
+        timestamp: chrono::Utc::now().to_rfc3339(),
+    };
+    send_all(settings, &notification).await
+}
+
+/// Whether `message` may be sent at `now`; records it as sent if so
+fn due(message: &str, now: Instant) -> bool {
+    let mut last_sent = LAST_SENT.lock().unwrap_or_else(|e| e.into_inner());
+    last_sent.retain(|_, sent| now.duration_since(*sent) < REPEAT_INTERVAL);
+    if last_sent.contains_key(message) {
+        return false;
+    }
+    last_sent.insert(message.to_string(), now);
+    true
+}
+
+/// `channelId` in an event's details
+fn channel_id(details: Option<&str>) -> Option<i32> {
+    let details: serde_json::Value = serde_json::from_str(details?).ok()?;
+    details.get("channelId")?.as_i64()?.try_into().ok()
+}
+
+async fn send_all(
+    settings: &NotificationSettings,
+    notification: &Notification,
+) -> Vec<NotificationTestResult> {
+    let mut results = Vec::new();
+    if settings.desktop {
+        results.push(NotificationTestResult::from_result(
+            "Desktop",
+            show_desktop(&notification.message),
+        ));
+    }
+
+    let webhooks: Vec<&Webhook> = settings.webhooks.iter().filter(|w| w.enabled).collect();
+    if webhooks.is_empty() {
+        return results;
+    }
+    let client = match crate::dns::configure(reqwest::Client::builder())
+        .timeout(WEBHOOK_TIMEOUT)
+        .build()
+    {
+        Ok(client) => client,
+        Err(e) => {
+            let error = format!("Failed to create HTTP client: {}", e);
+            for webhook in webhooks {
+                results.push(NotificationTestResult::from_result(
+                    &webhook.name,
+                    Err(error.clone()),
+                ));
+            }
+            return results;
+        }
+    };
+    let sends = webhooks
+        .iter()
+        .map(|webhook| send_webhook(&client, webhook, notification));
+    for (webhook, result) in webhooks.iter().zip(futures::future::join_all(sends).await) {
+        results.push(NotificationTestResult::from_result(&webhook.name, result));
+    }
+    results
+}
+
+impl NotificationTestResult {
+    fn from_result(target: &str, result: Result<(), String>) -> Self {
+        Self {
+            target: target.to_string(),
+            ok: result.is_ok(),
+            error: result.err(),
+        }
+    }
+}
+
+#[cfg(desktop)]
+fn show_desktop(message: &str) -> Result<(), String> {
+    use tauri_plugin_notification::NotificationExt;
+
+    let app = APP_HANDLE
+        .get()
+        .ok_or("Desktop notifications are not available")?;
+    app.notification()
+        .builder()
+        .title(NOTIFICATION_TITLE)
+        .body(message)
+        .show()
+        .map_err(|e| format!("Failed to show notification: {}", e))
+}
+
+#[cfg(not(desktop))]
+fn show_desktop(_message: &str) -> Result<(), String> {
+    Err("Desktop notifications are not available".to_string())
+}
+
+async fn send_webhook(
+    client: &reqwest::Client,
+    webhook: &Webhook,
+    notification: &Notification,
+) -> Result<(), String> {
+    client
+        .post(webhook.url.trim())
+        .json(&webhook_payload(webhook.kind, notification))
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map(|_| ())
+        .map_err(|e| e.without_url().to_string())
+}
+
+/// Request body for a webhook of the given kind
+fn webhook_payload(kind: WebhookKind, notification: &Notification) -> serde_json::Value {
+    let prefix = match notification.level.as_str() {
+        "error" => "Error",
+        "warn" => "Warning",
+        _ => "Info",
+    };
+    match kind {
+        WebhookKind::Discord => serde_json::json!({
+            "username": NOTIFICATION_TITLE,
+            "content": format!("**{}** ({}): {}", prefix, notification.category, notification.message),
+        }),
+        WebhookKind::Slack => serde_json::json!({
+            "text": format!("*{}* ({}): {}", prefix, notification.category, notification.message),
+        }),
+        WebhookKind::Generic => serde_json::json!({
+            "source": "streamforge",
+            "event": notification,
+        }),
+    }
+}
+
+/// Write failed sends to the event log without notifying about them
+fn log_failures(failures: &[NotificationTestResult]) {
+    let Some(db) = APP_HANDLE
+        .get()
+        .and_then(|app| app.try_state::<DbConnection>())
+    else {
+        for failure in failures {
+            tracing::warn!(
+                "Notification to {} failed: {}",
+                failure.target,
+                failure.error.as_deref().unwrap_or_default()
+            );
+        }
+        return;
+    };
+    let Ok(mut conn) = db.get_connection() else {
+        return;
+    };
+    SUPPRESSED.with(|suppressed| suppressed.set(true));
+    for failure in failures {
+        let details = serde_json::json!({
+            "target": failure.target,
+            "error": failure.error,
+        });
+        let _ = log_event_internal(
+            &mut conn,
+            "warn",
+            "system",
+            &format!("Notification to {} failed", failure.target),
+            Some(&details.to_string()),
+        );
+    }
+    SUPPRESSED.with(|suppressed| suppressed.set(false));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_and_validation() {
+        let mut settings = NotificationSettings {
+            channel_rules: vec![
+                ChannelRule {
+                    channel_id: 7,
+                    mode: ChannelRuleMode::Mute,
+                },
+                ChannelRule {
+                    channel_id: 9,
+                    mode: ChannelRuleMode::Always,
+                },
+            ],
+            ..Default::default()
+        };
+        assert!(!settings.has_targets());
+
+        assert!(settings.matches("error", "epg", None));
+        assert!(!settings.matches("warn", "epg", None));
+        assert!(!settings.matches("error", "system", None));
+        assert!(!settings.matches("error", "stream", Some(r#"{"channelId":7}"#)));
+        assert!(settings.matches("warn", "connection", Some(r#"{"channelId":9}"#)));
+        assert!(!settings.matches("info", "stream", Some(r#"{"channelId":9}"#)));
+
+        settings.webhooks.push(Webhook {
+            name: "Discord".to_string(),
+            kind: WebhookKind::Discord,
+            url: "https://discord.com/api/webhooks/1/abc".to_string(),
+            enabled: true,
+        });
+        assert!(settings.has_targets());
+        assert!(settings.validate().is_ok());
+        settings.webhooks[0].url = "file:///etc/passwd".to_string();
+        assert!(settings.validate().is_err());
+
+        let now = Instant::now();
+        assert!(due("Stream failed: test", now));
+        assert!(!due("Stream failed: test", now + Duration::from_secs(60)));
+        assert!(due("Stream failed: test", now + REPEAT_INTERVAL));
+    }
+}
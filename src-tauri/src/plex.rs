@@ -0,0 +1,530 @@
+//! Plex guide reloads
+//!
+//! Plex re-reads `/epg.xml` on its own schedule. When a Plex server is
+//! configured, StreamForge asks it to reload the guide after EPG refreshes so
+//! new guide data shows up right away.
+//!
+//! Reload requests are queued and debounced rather than sent per refresh: a
+//! queued reload waits until no EPG refresh is running and no further request
+//! arrived for the debounce period, and at most one reload is sent per minimum
+//! interval (one hour by default). Refreshing several sources one after the
+//! other therefore makes Plex pull the guide once instead of once per source.
+//!
+//! The Plex token is stored like account passwords (keychain, or encrypted
+//! in the database) and is never returned to the frontend.
+
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use chrono::{DateTime, Duration as ChronoDuration, Utc};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{LazyLock, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::commands::logs::log_event_internal;
+use crate::credentials::CredentialManager;
+use crate::db::schema::settings;
+use crate::db::{DbConnection, Setting};
+
+/// Settings key for the Plex guide reload configuration (JSON)
+pub const PLEX_GUIDE_RELOAD_KEY: &str = "plex_guide_reload";
+
+/// Settings key for the stored Plex token (base64 credential data)
+pub const PLEX_TOKEN_KEY: &str = "plex_token_encrypted";
+
+/// Settings key for the time of the last guide reload sent to Plex (RFC 3339)
+const PLEX_LAST_RELOAD_KEY: &str = "plex_guide_last_reload";
+
+/// Credential identifier of the Plex token
+pub const PLEX_TOKEN_CREDENTIAL_ID: &str = "plex-token";
+
+/// Timeout for requests to the Plex server
+const PLEX_REQUEST_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// How often a queued reload re-checks whether EPG refreshes are still running
+const REFRESH_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Longest single wait of the reload worker, so configuration changes apply
+const MAX_WORKER_WAIT: Duration = Duration::from_secs(60);
+
+/// Handle used to reach the database and app data directory (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// EPG refreshes currently running (see `RefreshGuard`)
+static ACTIVE_REFRESHES: AtomicUsize = AtomicUsize::new(0);
+
+/// Queued reload request
+static QUEUE: LazyLock<Mutex<ReloadQueue>> = LazyLock::new(|| Mutex::new(ReloadQueue::default()));
+
+#[derive(Debug, Default)]
+struct ReloadQueue {
+    /// Time of the latest request not yet sent, if any
+    last_request: Option<DateTime<Utc>>,
+    /// What asked for the pending reload (e.g. "scheduled", "manual")
+    trigger: Option<String>,
+    worker_running: bool,
+}
+
+/// Plex guide reload settings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexReloadConfig {
+    pub enabled: bool,
+    /// Plex Media Server address, e.g. `http://192.168.1.10:32400`
+    pub server_url: String,
+    /// At most one reload per this many minutes
+    #[serde(default = "default_min_interval")]
+    pub min_interval_minutes: u32,
+    /// Quiet period after the last request before a reload is sent
+    #[serde(default = "default_debounce")]
+    pub debounce_seconds: u32,
+}
+
+fn default_min_interval() -> u32 {
+    60
+}
+
+fn default_debounce() -> u32 {
+    120
+}
+
+impl Default for PlexReloadConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            server_url: String::new(),
+            min_interval_minutes: default_min_interval(),
+            debounce_seconds: default_debounce(),
+        }
+    }
+}
+
+impl PlexReloadConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        let url = self.server_url.trim();
+        if self.enabled && url.is_empty() {
+            return Err("Plex server address is required".to_string());
+        }
+        if !url.is_empty() {
+            let parsed =
+                url::Url::parse(url).map_err(|e| format!("Invalid Plex server address: {}", e))?;
+            if !matches!(parsed.scheme(), "http" | "https") {
+                return Err("Plex server address must start with http:// or https://".to_string());
+            }
+        }
+        if !(5..=1440).contains(&self.min_interval_minutes) {
+            return Err("Minimum interval must be between 5 and 1440 minutes".to_string());
+        }
+        if !(10..=3600).contains(&self.debounce_seconds) {
+            return Err("Debounce must be between 10 and 3600 seconds".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Current state of the reload queue
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlexReloadStatus {
+    /// A reload is queued
+    pub pending: bool,
+    /// EPG refreshes the queued reload waits for
+    pub active_refreshes: usize,
+    pub last_reload_at: Option<String>,
+    /// Earliest time the queued reload is sent, if no refresh is running
+    pub next_reload_at: Option<String>,
+}
+
+/// Marks an EPG refresh as running; queued reloads wait until all are dropped
+pub struct RefreshGuard;
+
+impl RefreshGuard {
+    pub fn new() -> Self {
+        ACTIVE_REFRESHES.fetch_add(1, Ordering::SeqCst);
+        RefreshGuard
+    }
+}
+
+impl Default for RefreshGuard {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for RefreshGuard {
+    fn drop(&mut self) {
+        ACTIVE_REFRESHES.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Register the app handle used by `request_guide_reload`
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Load the reload settings (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> PlexReloadConfig {
+    read_setting(conn, PLEX_GUIDE_RELOAD_KEY)
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Whether a Plex token is stored
+pub fn has_token(conn: &mut SqliteConnection) -> bool {
+    read_setting(conn, PLEX_TOKEN_KEY).is_some_and(|value| !value.is_empty())
+}
+
+/// Validate and persist the reload settings
+///
+/// `token` replaces the stored token when given; an empty token removes it.
+pub fn save_config(
+    conn: &mut SqliteConnection,
+    app_data_dir: &Path,
+    config: &PlexReloadConfig,
+    token: Option<&str>,
+) -> Result<(), String> {
+    config.validate()?;
+    let token = token.map(str::trim);
+    let token_stored = match token {
+        Some(token) => !token.is_empty(),
+        None => has_token(conn),
+    };
+    if config.enabled && !token_stored {
+        return Err("A Plex token is required".to_string());
+    }
+    let token_data = match token {
+        Some("") => Some(String::new()),
+        Some(token) => {
+            let (_, encrypted) = CredentialManager::new(app_data_dir.to_path_buf())
+                .store_password(PLEX_TOKEN_CREDENTIAL_ID, token)
+                .map_err(|e| format!("Failed to store Plex token: {}", e))?;
+            Some(STANDARD.encode(encrypted))
+        }
+        None => None,
+    };
+
+    let config_json = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::replace_into(settings::table)
+            .values(&Setting::new(PLEX_GUIDE_RELOAD_KEY, config_json))
+            .execute(conn)?;
+        if let Some(token_data) = token_data {
+            diesel::replace_into(settings::table)
+                .values(&Setting::new(PLEX_TOKEN_KEY, token_data))
+                .execute(conn)?;
+        }
+        Ok(())
+    })
+    .map_err(|e| format!("Failed to save setting: {}", e))
+}
+
+/// State of the reload queue
+pub fn status(conn: &mut SqliteConnection) -> PlexReloadStatus {
+    let config = load_config(conn);
+    let last_reload = load_last_reload(conn);
+    let last_request = QUEUE.lock().map(|queue| queue.last_request).unwrap_or(None);
+
+    PlexReloadStatus {
+        pending: last_request.is_some(),
+        active_refreshes: ACTIVE_REFRESHES.load(Ordering::SeqCst),
+        last_reload_at: last_reload.map(|t| t.to_rfc3339()),
+        next_reload_at: last_request.map(|t| due_at(t, last_reload, &config).to_rfc3339()),
+    }
+}
+
+/// Queue a guide reload after an EPG refresh
+///
+/// Does nothing before `init` or when Plex reloads are not enabled, so
+/// callers can request unconditionally.
+pub fn request_guide_reload(trigger: &str) {
+    let Some(app) = APP_HANDLE.get() else {
+        return;
+    };
+    let Some(db) = app.try_state::<DbConnection>() else {
+        return;
+    };
+    let Ok(mut conn) = db.get_connection() else {
+        return;
+    };
+    if !load_config(&mut conn).enabled {
+        return;
+    }
+    drop(conn);
+
+    let start_worker = {
+        let Ok(mut queue) = QUEUE.lock() else {
+            return;
+        };
+        queue.last_request = Some(Utc::now());
+        queue.trigger = Some(trigger.to_string());
+        !std::mem::replace(&mut queue.worker_running, true)
+    };
+    if start_worker {
+        let app = app.clone();
+        tauri::async_runtime::spawn(async move { run_worker(app).await });
+    }
+}
+
+/// When a reload requested at `last_request` may be sent
+///
+/// After the debounce period since the last request, and no sooner than the
+/// minimum interval after the previous reload.
+pub fn due_at(
+    last_request: DateTime<Utc>,
+    last_reload: Option<DateTime<Utc>>,
+    config: &PlexReloadConfig,
+) -> DateTime<Utc> {
+    let debounced = last_request + ChronoDuration::seconds(i64::from(config.debounce_seconds));
+    match last_reload {
+        Some(last) => {
+            debounced.max(last + ChronoDuration::minutes(i64::from(config.min_interval_minutes)))
+        }
+        None => debounced,
+    }
+}
+
+/// Next thing the reload worker does
+enum WorkerStep {
+    Wait(Duration),
+    Send {
+        server_url: String,
+        token: Option<String>,
+        trigger: Option<String>,
+    },
+    /// Reloads were disabled; the queued request was dropped
+    Stop,
+}
+
+/// Decide the worker's next step, taking the queued request when it is due
+fn next_worker_step(app: &AppHandle) -> WorkerStep {
+    let conn = app.state::<DbConnection>().get_connection();
+    let Ok(mut queue) = QUEUE.lock() else {
+        return WorkerStep::Stop;
+    };
+    let (Ok(mut conn), Some(last_request)) = (conn, queue.last_request) else {
+        queue.last_request = None;
+        return WorkerStep::Stop;
+    };
+    let config = load_config(&mut conn);
+    if !config.enabled {
+        queue.last_request = None;
+        return WorkerStep::Stop;
+    }
+    if ACTIVE_REFRESHES.load(Ordering::SeqCst) > 0 {
+        return WorkerStep::Wait(REFRESH_POLL_INTERVAL);
+    }
+
+    let due = due_at(last_request, load_last_reload(&mut conn), &config);
+    if let Some(wait) = (due - Utc::now()).to_std().ok().filter(|w| !w.is_zero()) {
+        return WorkerStep::Wait(wait.min(MAX_WORKER_WAIT));
+    }
+
+    queue.last_request = None;
+    WorkerStep::Send {
+        server_url: config.server_url,
+        token: app
+            .path()
+            .app_data_dir()
+            .ok()
+            .and_then(|dir| load_token(&mut conn, &dir)),
+        trigger: queue.trigger.take(),
+    }
+}
+
+/// Send queued reloads once they are due; exits when the queue is empty
+async fn run_worker(app: AppHandle) {
+    loop {
+        match next_worker_step(&app) {
+            WorkerStep::Wait(wait) => tokio::time::sleep(wait).await,
+            WorkerStep::Send {
+                server_url,
+                token,
+                trigger,
+            } => {
+                let result = match token {
+                    Some(token) => reload_guide(&server_url, &token).await,
+                    None => Err("No Plex token is stored".to_string()),
+                };
+                if let Ok(mut conn) = app.state::<DbConnection>().get_connection() {
+                    log_reload(&mut conn, trigger.as_deref(), &result);
+                }
+            }
+            WorkerStep::Stop => {}
+        }
+
+        // A request that arrived while sending is handled before exiting
+        let Ok(mut queue) = QUEUE.lock() else {
+            return;
+        };
+        if queue.last_request.is_none() {
+            queue.worker_running = false;
+            return;
+        }
+    }
+}
+
+/// Ask Plex to reload the guide of every DVR, returning how many were reloaded
+async fn reload_guide(server_url: &str, token: &str) -> Result<usize, String> {
+    let base = server_url.trim().trim_end_matches('/');
+    let client = crate::dns::configure(reqwest::Client::builder())
+        .timeout(PLEX_REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let dvrs: serde_json::Value = client
+        .get(format!("{}/livetv/dvrs", base))
+        .header("Accept", "application/json")
+        .header("X-Plex-Token", token)
+        .send()
+        .await
+        .and_then(|response| response.error_for_status())
+        .map_err(|e| format!("Failed to list Plex DVRs: {}", e))?
+        .json()
+        .await
+        .map_err(|e| format!("Invalid Plex DVR list: {}", e))?;
+    let keys = dvr_keys(&dvrs);
+    if keys.is_empty() {
+        return Err("Plex has no DVR set up".to_string());
+    }
+
+    for key in &keys {
+        client
+            .post(format!("{}/livetv/dvrs/{}/reloadGuide", base, key))
+            .header("X-Plex-Token", token)
+            .send()
+            .await
+            .and_then(|response| response.error_for_status())
+            .map_err(|e| format!("Failed to reload guide of DVR {}: {}", key, e))?;
+    }
+    Ok(keys.len())
+}
+
+/// DVR keys in a `/livetv/dvrs` response
+fn dvr_keys(response: &serde_json::Value) -> Vec<String> {
+    response["MediaContainer"]["Dvr"]
+        .as_array()
+        .map(|dvrs| {
+            dvrs.iter()
+                .filter_map(|dvr| match &dvr["key"] {
+                    serde_json::Value::String(key) => Some(key.clone()),
+                    serde_json::Value::Number(key) => Some(key.to_string()),
+                    _ => None,
+                })
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Record the outcome of a reload in the event log
+fn log_reload(conn: &mut SqliteConnection, trigger: Option<&str>, result: &Result<usize, String>) {
+    match result {
+        Ok(dvr_count) => {
+            let now = Utc::now().to_rfc3339();
+            let _ = diesel::replace_into(settings::table)
+                .values(&Setting::new(PLEX_LAST_RELOAD_KEY, now))
+                .execute(conn);
+            let details = serde_json::json!({ "trigger": trigger, "dvrCount": dvr_count });
+            let _ = log_event_internal(
+                conn,
+                "info",
+                "epg",
+                &format!("Plex guide reload requested ({} DVRs)", dvr_count),
+                Some(&details.to_string()),
+            );
+        }
+        Err(e) => {
+            let details = serde_json::json!({ "trigger": trigger, "error": e });
+            let _ = log_event_internal(
+                conn,
+                "warn",
+                "epg",
+                &format!("Plex guide reload failed: {}", e),
+                Some(&details.to_string()),
+            );
+        }
+    }
+}
+
+fn load_last_reload(conn: &mut SqliteConnection) -> Option<DateTime<Utc>> {
+    read_setting(conn, PLEX_LAST_RELOAD_KEY)
+        .and_then(|value| DateTime::parse_from_rfc3339(&value).ok())
+        .map(|t| t.with_timezone(&Utc))
+}
+
+fn load_token(conn: &mut SqliteConnection, app_data_dir: &Path) -> Option<String> {
+    let data = STANDARD
+        .decode(read_setting(conn, PLEX_TOKEN_KEY)?)
+        .ok()
+        .filter(|data| !data.is_empty())?;
+    CredentialManager::new(app_data_dir.to_path_buf())
+        .retrieve_password(PLEX_TOKEN_CREDENTIAL_ID, &data)
+        .ok()
+}
+
+fn read_setting(conn: &mut SqliteConnection, key: &str) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(key))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reload_debounced_and_throttled() {
+        let config = PlexReloadConfig {
+            enabled: true,
+            server_url: "http://plex.local:32400".to_string(),
+            min_interval_minutes: 60,
+            debounce_seconds: 120,
+        };
+        let request = DateTime::parse_from_rfc3339("2026-02-10T04:10:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+
+        // First reload waits only for the debounce period
+        assert_eq!(
+            due_at(request, None, &config),
+            request + ChronoDuration::seconds(120)
+        );
+        // A reload 20 minutes ago holds the next one until the hour is up
+        let last = request - ChronoDuration::minutes(20);
+        assert_eq!(
+            due_at(request, Some(last), &config),
+            last + ChronoDuration::minutes(60)
+        );
+        // A reload long ago does not delay past the debounce
+        let last = request - ChronoDuration::hours(5);
+        assert_eq!(
+            due_at(request, Some(last), &config),
+            request + ChronoDuration::seconds(120)
+        );
+    }
+
+    #[test]
+    fn test_dvr_keys_and_validation() {
+        let response = serde_json::json!({
+            "MediaContainer": { "size": 2, "Dvr": [{ "key": "5" }, { "key": 7 }] }
+        });
+        assert_eq!(dvr_keys(&response), vec!["5", "7"]);
+        assert!(dvr_keys(&serde_json::json!({ "MediaContainer": { "size": 0 } })).is_empty());
+
+        let mut config = PlexReloadConfig {
+            enabled: true,
+            ..Default::default()
+        };
+        assert!(config.validate().is_err());
+        config.server_url = "ftp://plex.local".to_string();
+        assert!(config.validate().is_err());
+        config.server_url = "http://plex.local:32400".to_string();
+        assert!(config.validate().is_ok());
+        config.min_interval_minutes = 1;
+        assert!(config.validate().is_err());
+    }
+}
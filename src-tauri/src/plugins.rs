@@ -0,0 +1,679 @@
+//! Matcher and playlist plugins
+//!
+//! Advanced users can customize channel name normalization, match scoring,
+//! and which channels the M3U playlist includes with WebAssembly modules
+//! dropped into the app data directory:
+//!
+//! ```text
+//! {app_data_dir}/plugins/
+//!   my-plugin/
+//!     plugin.json    # manifest
+//!     plugin.wasm    # compiled module
+//! ```
+//!
+//! Modules run in the `wasmi` interpreter without any host imports, so they
+//! cannot reach files, sockets, clocks, or the environment. Every call gets a
+//! fuel budget and every instance a memory cap. A plugin that traps or runs
+//! out of fuel is skipped for the rest of the run and the built-in result is
+//! used instead.
+//!
+//! Plugins are discovered at startup and on reload. They start disabled until
+//! the user turns them on (`plugin_enabled.{name}` settings). See
+//! `docs/plugin-system-notes.md` for the module interface.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, LazyLock, Mutex, OnceLock, RwLock};
+use wasmi::{
+    Config, Engine, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc,
+    WasmResults,
+};
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Directory under the app data directory holding one directory per plugin
+pub const PLUGINS_DIR: &str = "plugins";
+
+const MANIFEST_FILE: &str = "plugin.json";
+const MODULE_FILE: &str = "plugin.wasm";
+
+/// Settings key prefix of the per-plugin enable toggles ("true"/"false")
+pub const PLUGIN_ENABLED_KEY_PREFIX: &str = "plugin_enabled.";
+
+/// Instructions a plugin may run per call
+const FUEL_PER_CALL: u64 = 10_000_000;
+
+/// Linear memory a plugin instance may grow to
+const MEMORY_LIMIT_BYTES: usize = 16 * 1024 * 1024;
+
+/// Longest string a plugin may return
+const MAX_OUTPUT_LEN: usize = 64 * 1024;
+
+/// Interpreter shared by all plugins, with fuel metering
+static ENGINE: LazyLock<Engine> = LazyLock::new(|| {
+    let mut config = Config::default();
+    config.consume_fuel(true);
+    Engine::new(&config)
+});
+
+/// Plugins found by the last scan of the plugins directory
+static REGISTRY: RwLock<Vec<Arc<Plugin>>> = RwLock::new(Vec::new());
+
+/// Plugins directory (set once at startup)
+static PLUGINS_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// Hook a plugin provides
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Capability {
+    /// Adjust normalized channel names before matching
+    Normalize,
+    /// Adjust the confidence of a channel/stream pair
+    Score,
+    /// Include or exclude channels from the M3U playlist
+    PlaylistFilter,
+}
+
+impl Capability {
+    /// Function the module exports for this hook
+    fn export_name(&self) -> &'static str {
+        match self {
+            Capability::Normalize => "normalize",
+            Capability::Score => "score",
+            Capability::PlaylistFilter => "playlist_filter",
+        }
+    }
+}
+
+/// Contents of `plugin.json`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginManifest {
+    /// Must match the plugin's directory name
+    pub name: String,
+    pub version: String,
+    #[serde(default)]
+    pub description: String,
+    pub capabilities: Vec<Capability>,
+}
+
+/// A discovered plugin
+#[derive(Debug)]
+pub struct Plugin {
+    name: String,
+    manifest: Option<PluginManifest>,
+    /// Compiled module (None if the plugin failed to load)
+    module: Option<Module>,
+    /// Why the plugin failed to load, or why it was last skipped
+    error: Mutex<Option<String>>,
+}
+
+impl Plugin {
+    fn has(&self, capability: Capability) -> bool {
+        self.manifest
+            .as_ref()
+            .is_some_and(|m| m.capabilities.contains(&capability))
+    }
+
+    fn set_error(&self, error: String) {
+        *self.error.lock().unwrap_or_else(|e| e.into_inner()) = Some(error);
+    }
+}
+
+/// Plugin as shown in settings
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PluginInfo {
+    pub name: String,
+    pub version: Option<String>,
+    pub description: Option<String>,
+    pub capabilities: Vec<Capability>,
+    pub enabled: bool,
+    /// Load error, or the failure that made the plugin be skipped
+    pub error: Option<String>,
+}
+
+/// Compile a module and check it provides what its manifest promises
+///
+/// Modules may not import anything, which is what keeps them sandboxed.
+fn compile(manifest: &PluginManifest, wasm: &[u8]) -> Result<Module, String> {
+    let module =
+        Module::new(&ENGINE, wasm).map_err(|e| format!("Invalid {}: {}", MODULE_FILE, e))?;
+    if let Some(import) = module.imports().next() {
+        return Err(format!(
+            "Plugins cannot import host functions ({}.{})",
+            import.module(),
+            import.name()
+        ));
+    }
+    let exports: HashSet<&str> = module.exports().map(|export| export.name()).collect();
+    let required = ["memory", "alloc"]
+        .into_iter()
+        .chain(manifest.capabilities.iter().map(Capability::export_name));
+    for name in required {
+        if !exports.contains(name) {
+            return Err(format!("{} does not export `{}`", MODULE_FILE, name));
+        }
+    }
+    Ok(module)
+}
+
+/// Load the plugin in `dir`, keeping the error if it can't be used
+fn load_plugin(dir: &Path) -> Plugin {
+    let name = dir
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let manifest = fs::read_to_string(dir.join(MANIFEST_FILE))
+        .map_err(|e| format!("Failed to read {}: {}", MANIFEST_FILE, e))
+        .and_then(|json| {
+            serde_json::from_str::<PluginManifest>(&json)
+                .map_err(|e| format!("Invalid {}: {}", MANIFEST_FILE, e))
+        });
+    let module = manifest
+        .as_ref()
+        .map_err(Clone::clone)
+        .and_then(|manifest| {
+            if manifest.name != name {
+                return Err(format!(
+                    "Manifest name '{}' does not match the plugin directory '{}'",
+                    manifest.name, name
+                ));
+            }
+            let wasm = fs::read(dir.join(MODULE_FILE))
+                .map_err(|e| format!("Failed to read {}: {}", MODULE_FILE, e))?;
+            let module = compile(manifest, &wasm)?;
+            // Catch export signature mismatches now rather than mid-match
+            PluginInstance::new(manifest, &module)?;
+            Ok(module)
+        });
+
+    Plugin {
+        name,
+        manifest: manifest.ok(),
+        error: Mutex::new(module.as_ref().err().cloned()),
+        module: module.ok(),
+    }
+}
+
+/// Every plugin directory under `dir`, by name
+fn discover(dir: &Path) -> Vec<Arc<Plugin>> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut dirs: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.is_dir())
+        .collect();
+    dirs.sort();
+    dirs.iter().map(|dir| Arc::new(load_plugin(dir))).collect()
+}
+
+/// Scan `{app_data_dir}/plugins` (called once at startup)
+pub fn init(app_data_dir: &Path) {
+    let _ = PLUGINS_PATH.set(app_data_dir.join(PLUGINS_DIR));
+    reload();
+}
+
+/// Rescan the plugins directory, returning how many plugins were found
+pub fn reload() -> usize {
+    let plugins = PLUGINS_PATH
+        .get()
+        .map(|dir| discover(dir))
+        .unwrap_or_default();
+    for plugin in &plugins {
+        if let Some(error) = plugin
+            .error
+            .lock()
+            .unwrap_or_else(|e| e.into_inner())
+            .as_ref()
+        {
+            tracing::warn!("Plugin {} could not be loaded: {}", plugin.name, error);
+        }
+    }
+    let count = plugins.len();
+    *REGISTRY.write().unwrap_or_else(|e| e.into_inner()) = plugins;
+    count
+}
+
+/// Names of the plugins the user turned on
+pub fn enabled_names(conn: &mut SqliteConnection) -> HashSet<String> {
+    settings::table
+        .filter(settings::key.like(format!("{}%", PLUGIN_ENABLED_KEY_PREFIX)))
+        .filter(settings::value.eq("true"))
+        .select(settings::key)
+        .load::<String>(conn)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|key| {
+            key.strip_prefix(PLUGIN_ENABLED_KEY_PREFIX)
+                .map(str::to_string)
+        })
+        .collect()
+}
+
+/// Turn a plugin on or off
+pub fn set_enabled(
+    conn: &mut SqliteConnection,
+    name: &str,
+    enabled: bool,
+) -> Result<(), diesel::result::Error> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(
+            format!("{}{}", PLUGIN_ENABLED_KEY_PREFIX, name),
+            enabled.to_string(),
+        ))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Discovered plugins with their enable toggle and errors
+pub fn list(conn: &mut SqliteConnection) -> Vec<PluginInfo> {
+    let enabled = enabled_names(conn);
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .map(|plugin| PluginInfo {
+            name: plugin.name.clone(),
+            version: plugin.manifest.as_ref().map(|m| m.version.clone()),
+            description: plugin.manifest.as_ref().map(|m| m.description.clone()),
+            capabilities: plugin
+                .manifest
+                .as_ref()
+                .map(|m| m.capabilities.clone())
+                .unwrap_or_default(),
+            enabled: enabled.contains(&plugin.name),
+            error: plugin
+                .error
+                .lock()
+                .unwrap_or_else(|e| e.into_inner())
+                .clone(),
+        })
+        .collect()
+}
+
+/// Whether a discovered plugin is named `name`
+pub fn exists(name: &str) -> bool {
+    REGISTRY
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .any(|plugin| plugin.name == name)
+}
+
+/// The loaded plugins the user turned on, in name order
+pub fn enabled(conn: &mut SqliteConnection) -> Plugins {
+    let names = enabled_names(conn);
+    if names.is_empty() {
+        return Plugins::default();
+    }
+    Plugins(
+        REGISTRY
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .filter(|plugin| plugin.module.is_some() && names.contains(&plugin.name))
+            .cloned()
+            .collect(),
+    )
+}
+
+/// A set of plugins to run
+#[derive(Clone, Default)]
+pub struct Plugins(Vec<Arc<Plugin>>);
+
+impl std::fmt::Debug for Plugins {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.0.iter().map(|plugin| &plugin.name))
+            .finish()
+    }
+}
+
+impl Plugins {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Instantiate the plugins for one thread
+    ///
+    /// Plugins that fail to instantiate are left out.
+    pub fn session(&self) -> PluginSession {
+        let instances = self
+            .0
+            .iter()
+            .filter_map(|plugin| {
+                let (Some(manifest), Some(module)) = (&plugin.manifest, &plugin.module) else {
+                    return None;
+                };
+                match PluginInstance::new(manifest, module) {
+                    Ok(instance) => Some((plugin.clone(), instance)),
+                    Err(e) => {
+                        tracing::warn!("Plugin {} could not be started: {}", plugin.name, e);
+                        plugin.set_error(e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        PluginSession { instances }
+    }
+}
+
+/// Playlist entry passed to `playlist_filter`
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaylistEntry<'a> {
+    pub name: &'a str,
+    pub number: i32,
+    pub group: Option<&'a str>,
+    pub tvg_id: &'a str,
+}
+
+/// Instantiated plugins, used from one thread
+///
+/// Each hook runs the plugins in name order. A plugin that fails is skipped
+/// for the rest of the session.
+#[derive(Default)]
+pub struct PluginSession {
+    instances: Vec<(Arc<Plugin>, PluginInstance)>,
+}
+
+impl PluginSession {
+    /// Run `call` on each plugin with `capability`, dropping plugins that fail
+    fn each<F>(&mut self, capability: Capability, mut call: F)
+    where
+        F: FnMut(&mut PluginInstance) -> Result<(), String>,
+    {
+        self.instances.retain_mut(|(plugin, instance)| {
+            if !plugin.has(capability) {
+                return true;
+            }
+            match call(instance) {
+                Ok(()) => true,
+                Err(e) => {
+                    let error = format!("{} failed: {}", capability.export_name(), e);
+                    tracing::warn!("Plugin {} skipped for this run: {}", plugin.name, error);
+                    plugin.set_error(error);
+                    false
+                }
+            }
+        });
+    }
+
+    /// Apply the plugins' `normalize` to an already normalized name
+    pub fn normalize(&mut self, name: String) -> String {
+        let mut name = name;
+        self.each(Capability::Normalize, |instance| {
+            let func = instance.func(Capability::Normalize)?;
+            let packed = instance.call::<i64>(func, name.as_bytes())?;
+            name = instance.read_string(packed)?;
+            Ok(())
+        });
+        name
+    }
+
+    /// Apply the plugins' `score` to a channel/stream pair's confidence
+    pub fn score(&mut self, xmltv_name: &str, xtream_name: &str, score: f64) -> f64 {
+        let mut score = score;
+        self.each(Capability::Score, |instance| {
+            let input = serde_json::json!({
+                "xmltv": xmltv_name,
+                "xtream": xtream_name,
+                "score": score,
+            })
+            .to_string();
+            let func = instance.func(Capability::Score)?;
+            let adjusted = instance.call::<f64>(func, input.as_bytes())?;
+            if !adjusted.is_finite() {
+                return Err(format!("returned {}", adjusted));
+            }
+            score = adjusted.clamp(0.0, 1.0);
+            Ok(())
+        });
+        score
+    }
+
+    /// Whether every plugin with `playlist_filter` includes the entry
+    pub fn include(&mut self, entry: &PlaylistEntry) -> bool {
+        let Ok(input) = serde_json::to_string(entry) else {
+            return true;
+        };
+        let mut include = true;
+        self.each(Capability::PlaylistFilter, |instance| {
+            if include {
+                let func = instance.func(Capability::PlaylistFilter)?;
+                include = instance.call::<i32>(func, input.as_bytes())? != 0;
+            }
+            Ok(())
+        });
+        include
+    }
+}
+
+/// One instantiated plugin module
+///
+/// Strings are passed as UTF-8 `(ptr, len)` pairs in memory the module hands
+/// out from `alloc`; strings come back packed as `(ptr << 32) | len`.
+struct PluginInstance {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    functions: Vec<(Capability, wasmi::Func)>,
+}
+
+impl PluginInstance {
+    fn new(manifest: &PluginManifest, module: &Module) -> Result<Self, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(MEMORY_LIMIT_BYTES)
+            .instances(1)
+            .build();
+        let mut store = Store::new(&ENGINE, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(FUEL_PER_CALL).map_err(|e| e.to_string())?;
+
+        let instance = Linker::<StoreLimits>::new(&ENGINE)
+            .instantiate(&mut store, module)
+            .and_then(|pre| pre.start(&mut store))
+            .map_err(|e| format!("Failed to instantiate: {}", e))?;
+        let memory = instance
+            .get_memory(&store, "memory")
+            .ok_or("`memory` is not a memory")?;
+        let alloc = instance
+            .get_typed_func::<i32, i32>(&store, "alloc")
+            .map_err(|e| format!("`alloc` must take and return an i32: {}", e))?;
+
+        let mut functions = Vec::new();
+        for &capability in &manifest.capabilities {
+            let name = capability.export_name();
+            let func = instance
+                .get_func(&store, name)
+                .ok_or_else(|| format!("`{}` is not a function", name))?;
+            // Check the signature once so calls can't fail on it
+            let valid = match capability {
+                Capability::Normalize => func.typed::<(i32, i32), i64>(&store).is_ok(),
+                Capability::Score => func.typed::<(i32, i32), f64>(&store).is_ok(),
+                Capability::PlaylistFilter => func.typed::<(i32, i32), i32>(&store).is_ok(),
+            };
+            if !valid {
+                return Err(format!("`{}` has the wrong signature", name));
+            }
+            functions.push((capability, func));
+        }
+
+        Ok(Self {
+            store,
+            memory,
+            alloc,
+            functions,
+        })
+    }
+
+    fn func(&self, capability: Capability) -> Result<wasmi::Func, String> {
+        self.functions
+            .iter()
+            .find(|(c, _)| *c == capability)
+            .map(|(_, func)| *func)
+            .ok_or_else(|| format!("`{}` is missing", capability.export_name()))
+    }
+
+    /// Copy `input` into the module and call `func` with it
+    fn call<R: WasmResults>(&mut self, func: wasmi::Func, input: &[u8]) -> Result<R, String> {
+        self.store
+            .set_fuel(FUEL_PER_CALL)
+            .map_err(|e| e.to_string())?;
+        let len = i32::try_from(input.len()).map_err(|_| "input too large".to_string())?;
+        let ptr = self
+            .alloc
+            .call(&mut self.store, len)
+            .map_err(|e| e.to_string())?;
+        self.memory
+            .write(&mut self.store, ptr as u32 as usize, input)
+            .map_err(|e| e.to_string())?;
+        func.typed::<(i32, i32), R>(&self.store)
+            .and_then(|func| func.call(&mut self.store, (ptr, len)))
+            .map_err(|e| e.to_string())
+    }
+
+    /// Read a string returned as `(ptr << 32) | len`
+    fn read_string(&self, packed: i64) -> Result<String, String> {
+        let ptr = (packed as u64 >> 32) as usize;
+        let len = (packed as u64 & 0xffff_ffff) as usize;
+        if len > MAX_OUTPUT_LEN {
+            return Err(format!("returned {} bytes", len));
+        }
+        let mut buffer = vec![0; len];
+        self.memory
+            .read(&self.store, ptr, &mut buffer)
+            .map_err(|e| e.to_string())?;
+        String::from_utf8(buffer).map_err(|_| "returned invalid UTF-8".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Bump allocator plus the three hooks:
+    /// - `normalize` drops the first 4 bytes ("us: news" -> "news")
+    /// - `score` always answers 0.5
+    /// - `playlist_filter` excludes names starting with "X"
+    const HOOKS_WAT: &str = r#"
+        (module
+          (memory (export "memory") 1)
+          (global $next (mut i32) (i32.const 1024))
+          (func (export "alloc") (param $len i32) (result i32)
+            (local $ptr i32)
+            (local.set $ptr (global.get $next))
+            (global.set $next (i32.add (global.get $next) (local.get $len)))
+            (local.get $ptr))
+          (func (export "normalize") (param $ptr i32) (param $len i32) (result i64)
+            (i64.or
+              (i64.shl (i64.extend_i32_u (i32.add (local.get $ptr) (i32.const 4))) (i64.const 32))
+              (i64.extend_i32_u (i32.sub (local.get $len) (i32.const 4)))))
+          (func (export "score") (param i32 i32) (result f64)
+            (f64.const 0.5))
+          (func (export "playlist_filter") (param $ptr i32) (param $len i32) (result i32)
+            ;; {"name":" is 9 bytes
+            (i32.ne (i32.load8_u (i32.add (local.get $ptr) (i32.const 9))) (i32.const 88))))
+    "#;
+
+    fn manifest(capabilities: Vec<Capability>) -> PluginManifest {
+        PluginManifest {
+            name: "test".to_string(),
+            version: "1.0.0".to_string(),
+            description: String::new(),
+            capabilities,
+        }
+    }
+
+    fn plugins(wat: &str, capabilities: Vec<Capability>) -> Plugins {
+        let manifest = manifest(capabilities);
+        let module = compile(&manifest, &wat::parse_str(wat).unwrap()).unwrap();
+        Plugins(vec![Arc::new(Plugin {
+            name: manifest.name.clone(),
+            manifest: Some(manifest),
+            module: Some(module),
+            error: Mutex::new(None),
+        })])
+    }
+
+    #[test]
+    fn test_plugin_hooks() {
+        let mut session = plugins(
+            HOOKS_WAT,
+            vec![
+                Capability::Normalize,
+                Capability::Score,
+                Capability::PlaylistFilter,
+            ],
+        )
+        .session();
+        assert_eq!(session.normalize("us: news".to_string()), "news");
+        assert_eq!(session.score("news", "news", 0.9), 0.5);
+
+        let entry = |name| PlaylistEntry {
+            name,
+            number: 1,
+            group: None,
+            tvg_id: "news.us",
+        };
+        assert!(session.include(&entry("News")));
+        assert!(!session.include(&entry("XXX")));
+
+        // Hooks the manifest doesn't declare are not called
+        let mut session = plugins(HOOKS_WAT, vec![Capability::Score]).session();
+        assert_eq!(session.normalize("us: news".to_string()), "us: news");
+    }
+
+    #[test]
+    fn test_runaway_plugin_is_skipped() {
+        let runaway = plugins(
+            r#"(module
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0))
+                 (func (export "score") (param i32 i32) (result f64)
+                   (loop $forever (br $forever))
+                   (f64.const 1)))"#,
+            vec![Capability::Score],
+        );
+        let mut session = runaway.session();
+        assert_eq!(session.score("a", "b", 0.7), 0.7);
+        assert!(session.instances.is_empty());
+        assert!(runaway.0[0].error.lock().unwrap().is_some());
+    }
+
+    #[test]
+    fn test_modules_cannot_import() {
+        let wasm = wat::parse_str(
+            r#"(module
+                 (import "wasi_snapshot_preview1" "fd_write" (func (param i32 i32 i32 i32) (result i32)))
+                 (memory (export "memory") 1)
+                 (func (export "alloc") (param i32) (result i32) (i32.const 0)))"#,
+        )
+        .unwrap();
+        let error = compile(&manifest(Vec::new()), &wasm).unwrap_err();
+        assert!(error.contains("cannot import"), "{}", error);
+
+        let wasm = wat::parse_str(r#"(module (memory (export "memory") 1))"#).unwrap();
+        let error = compile(&manifest(Vec::new()), &wasm).unwrap_err();
+        assert!(error.contains("`alloc`"), "{}", error);
+    }
+
+    #[test]
+    fn test_enable_toggles() {
+        let mut conn = crate::db::test_connection();
+        assert!(enabled_names(&mut conn).is_empty());
+        set_enabled(&mut conn, "strip-prefixes", true).unwrap();
+        set_enabled(&mut conn, "other", false).unwrap();
+        assert_eq!(
+            enabled_names(&mut conn),
+            HashSet::from(["strip-prefixes".to_string()])
+        );
+    }
+}
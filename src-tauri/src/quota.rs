@@ -0,0 +1,434 @@
+//! Monthly account usage quotas
+//!
+//! Some subscriptions cap monthly traffic or viewing hours. Each account can
+//! have a limit in GB per month, hours per month, or both. The stream proxy
+//! accounts every finished session against the account it started on
+//! (failover to another account mid-session is still counted against the
+//! first one) in `account_usage`, bucketed by local calendar month.
+//!
+//! Crossing 80% and 100% of a limit is logged once per month. With
+//! `block_when_exceeded` set, new live and VOD streams skip the account once
+//! a limit is reached; sessions already running continue. DVR recordings are
+//! never blocked. Plex tunes recordings and live viewing through the same
+//! HDHomeRun lineup URLs, so every stream Plex Media Server requests counts
+//! as a possible recording (see `may_be_recording`).
+
+use axum::http::{header, HeaderMap};
+use chrono::Local;
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::time::Instant;
+
+use crate::commands::logs::log_event_internal;
+use crate::db::schema::{account_quotas, account_usage, accounts};
+use crate::db::{AccountQuota, AccountUsage, DbPool};
+
+/// Usage share that triggers the warning
+const WARN_PERCENT: i32 = 80;
+const EXCEEDED_PERCENT: i32 = 100;
+
+const BYTES_PER_GB: f64 = 1_000_000_000.0;
+const SECONDS_PER_HOUR: f64 = 3600.0;
+
+/// Quota and current-month usage of one account
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountQuotaStatus {
+    pub account_id: i32,
+    pub account_name: String,
+    pub monthly_gb: Option<f64>,
+    pub monthly_hours: Option<f64>,
+    pub block_when_exceeded: bool,
+    pub month: String,
+    pub used_bytes: i64,
+    pub used_seconds: i64,
+    /// Highest share of a configured limit used (None without limits)
+    pub used_percent: Option<f64>,
+    pub exceeded: bool,
+}
+
+/// Current usage month ("YYYY-MM", local time)
+pub fn current_month() -> String {
+    Local::now().format("%Y-%m").to_string()
+}
+
+/// Highest share (in percent) of the configured limits that `usage` uses
+pub fn used_percent(quota: &AccountQuota, bytes: i64, seconds: i64) -> Option<f64> {
+    let by_traffic = quota
+        .monthly_gb
+        .filter(|gb| *gb > 0.0)
+        .map(|gb| bytes as f64 / BYTES_PER_GB / gb * 100.0);
+    let by_time = quota
+        .monthly_hours
+        .filter(|hours| *hours > 0.0)
+        .map(|hours| seconds as f64 / SECONDS_PER_HOUR / hours * 100.0);
+
+    match (by_traffic, by_time) {
+        (Some(a), Some(b)) => Some(a.max(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+/// Load the quota of an account (None when none is configured)
+pub fn load_quota(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+) -> QueryResult<Option<AccountQuota>> {
+    account_quotas::table
+        .filter(account_quotas::account_id.eq(account_id))
+        .first(conn)
+        .optional()
+}
+
+/// Set or clear the quota of an account
+///
+/// Clearing both limits removes the quota; recorded usage is kept.
+pub fn save_quota(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    monthly_gb: Option<f64>,
+    monthly_hours: Option<f64>,
+    block_when_exceeded: bool,
+) -> QueryResult<()> {
+    if monthly_gb.is_none() && monthly_hours.is_none() {
+        diesel::delete(account_quotas::table.filter(account_quotas::account_id.eq(account_id)))
+            .execute(conn)?;
+        return Ok(());
+    }
+
+    diesel::replace_into(account_quotas::table)
+        .values(&AccountQuota {
+            account_id,
+            monthly_gb,
+            monthly_hours,
+            block_when_exceeded: block_when_exceeded as i32,
+        })
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Usage of an account in a month (zero if nothing was recorded)
+pub fn load_usage(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    month: &str,
+) -> QueryResult<AccountUsage> {
+    let usage = account_usage::table
+        .filter(account_usage::account_id.eq(account_id))
+        .filter(account_usage::month.eq(month))
+        .first(conn)
+        .optional()?;
+    Ok(usage.unwrap_or_else(|| AccountUsage {
+        account_id,
+        month: month.to_string(),
+        ..Default::default()
+    }))
+}
+
+/// Add a finished session to the account's usage for the current month
+///
+/// Logs a warning the first time the month's usage crosses 80% and 100% of
+/// a configured limit.
+pub fn record_usage(
+    conn: &mut SqliteConnection,
+    account_id: i32,
+    bytes: u64,
+    seconds: u64,
+) -> QueryResult<()> {
+    let month = current_month();
+    let (bytes, seconds) = (bytes as i64, seconds as i64);
+
+    diesel::insert_into(account_usage::table)
+        .values((
+            account_usage::account_id.eq(account_id),
+            account_usage::month.eq(&month),
+            account_usage::bytes.eq(bytes),
+            account_usage::seconds.eq(seconds),
+        ))
+        .on_conflict((account_usage::account_id, account_usage::month))
+        .do_update()
+        .set((
+            account_usage::bytes.eq(account_usage::bytes + bytes),
+            account_usage::seconds.eq(account_usage::seconds + seconds),
+        ))
+        .execute(conn)?;
+
+    let Some(quota) = load_quota(conn, account_id)? else {
+        return Ok(());
+    };
+    let usage = load_usage(conn, account_id, &month)?;
+    let Some(percent) = used_percent(&quota, usage.bytes, usage.seconds) else {
+        return Ok(());
+    };
+
+    let level = if percent >= EXCEEDED_PERCENT as f64 {
+        EXCEEDED_PERCENT
+    } else if percent >= WARN_PERCENT as f64 {
+        WARN_PERCENT
+    } else {
+        return Ok(());
+    };
+    if usage.alert_level >= level {
+        return Ok(());
+    }
+
+    diesel::update(
+        account_usage::table
+            .filter(account_usage::account_id.eq(account_id))
+            .filter(account_usage::month.eq(&month)),
+    )
+    .set(account_usage::alert_level.eq(level))
+    .execute(conn)?;
+
+    let account_name: String = accounts::table
+        .filter(accounts::id.eq(account_id))
+        .select(accounts::name)
+        .first(conn)
+        .unwrap_or_else(|_| format!("#{}", account_id));
+    let message = if level == EXCEEDED_PERCENT {
+        let action = if quota.block_when_exceeded != 0 {
+            "; new streams will not use it"
+        } else {
+            ""
+        };
+        format!(
+            "Account '{}' exceeded its monthly quota ({:.0}% used){}",
+            account_name, percent, action
+        )
+    } else {
+        format!(
+            "Account '{}' has used {:.0}% of its monthly quota",
+            account_name, percent
+        )
+    };
+    let details = serde_json::json!({
+        "accountId": account_id,
+        "month": month,
+        "usedBytes": usage.bytes,
+        "usedSeconds": usage.seconds,
+        "usedPercent": percent,
+        "monthlyGb": quota.monthly_gb,
+        "monthlyHours": quota.monthly_hours,
+    });
+    let _ = log_event_internal(
+        conn,
+        "warn",
+        "provider",
+        &message,
+        Some(&details.to_string()),
+    );
+    Ok(())
+}
+
+/// Whether a stream request may be a DVR recording, and so must not be
+/// blocked by a quota
+///
+/// `recording` is the request's explicit `?recording=` flag. Without it,
+/// requests from Plex Media Server (an `X-Plex-*` header, or a Plex or
+/// FFmpeg (`Lavf`) user agent as its recorder uses) count as recordings.
+pub fn may_be_recording(headers: &HeaderMap, recording: Option<bool>) -> bool {
+    if let Some(recording) = recording {
+        return recording;
+    }
+    if headers
+        .keys()
+        .any(|name| name.as_str().starts_with("x-plex-"))
+    {
+        return true;
+    }
+    headers
+        .get(header::USER_AGENT)
+        .and_then(|value| value.to_str().ok())
+        .map(|agent| agent.to_ascii_lowercase())
+        .is_some_and(|agent| agent.contains("plex") || agent.contains("lavf"))
+}
+
+/// Accounts that have reached a limit this month and block new streams
+pub fn blocked_accounts(conn: &mut SqliteConnection) -> QueryResult<HashSet<i32>> {
+    let month = current_month();
+    let quotas: Vec<AccountQuota> = account_quotas::table
+        .filter(account_quotas::block_when_exceeded.eq(1))
+        .load(conn)?;
+
+    let mut blocked = HashSet::new();
+    for quota in quotas {
+        let usage = load_usage(conn, quota.account_id, &month)?;
+        if used_percent(&quota, usage.bytes, usage.seconds)
+            .is_some_and(|percent| percent >= EXCEEDED_PERCENT as f64)
+        {
+            blocked.insert(quota.account_id);
+        }
+    }
+    Ok(blocked)
+}
+
+/// Quota status of every account for the current month
+pub fn list_status(conn: &mut SqliteConnection) -> QueryResult<Vec<AccountQuotaStatus>> {
+    let month = current_month();
+    let accounts: Vec<(Option<i32>, String)> = accounts::table
+        .select((accounts::id, accounts::name))
+        .order(accounts::name.asc())
+        .load(conn)?;
+
+    let mut statuses = Vec::with_capacity(accounts.len());
+    for (account_id, account_name) in accounts {
+        let Some(account_id) = account_id else {
+            continue;
+        };
+        let quota = load_quota(conn, account_id)?;
+        let usage = load_usage(conn, account_id, &month)?;
+        let used_percent = quota
+            .as_ref()
+            .and_then(|q| used_percent(q, usage.bytes, usage.seconds));
+
+        statuses.push(AccountQuotaStatus {
+            account_id,
+            account_name,
+            monthly_gb: quota.as_ref().and_then(|q| q.monthly_gb),
+            monthly_hours: quota.as_ref().and_then(|q| q.monthly_hours),
+            block_when_exceeded: quota.as_ref().is_some_and(|q| q.block_when_exceeded != 0),
+            month: month.clone(),
+            used_bytes: usage.bytes,
+            used_seconds: usage.seconds,
+            used_percent,
+            exceeded: used_percent.is_some_and(|p| p >= EXCEEDED_PERCENT as f64),
+        });
+    }
+    Ok(statuses)
+}
+
+/// Traffic and duration of one stream session, recorded when it finishes
+pub struct SessionUsage {
+    pool: DbPool,
+    account_id: i32,
+    started_at: Instant,
+    bytes: u64,
+}
+
+impl SessionUsage {
+    pub fn new(pool: DbPool, account_id: i32) -> Self {
+        Self {
+            pool,
+            account_id,
+            started_at: Instant::now(),
+            bytes: 0,
+        }
+    }
+
+    /// Count bytes delivered to the client
+    pub fn add_bytes(&mut self, bytes: usize) {
+        self.bytes += bytes as u64;
+    }
+
+    /// Record the session in the account's monthly usage
+    ///
+    /// Called when a response body is dropped, so the database work runs on
+    /// the blocking pool when inside the async runtime.
+    pub fn finish(self) {
+        let seconds = self.started_at.elapsed().as_secs();
+        let record = move || match self.pool.get() {
+            Ok(mut conn) => {
+                if let Err(e) = record_usage(&mut conn, self.account_id, self.bytes, seconds) {
+                    tracing::warn!(
+                        "Failed to record usage for account {}: {}",
+                        self.account_id,
+                        e
+                    );
+                }
+            }
+            Err(e) => tracing::warn!(
+                "Failed to record usage for account {}: {}",
+                self.account_id,
+                e
+            ),
+        };
+        match tokio::runtime::Handle::try_current() {
+            Ok(runtime) => {
+                runtime.spawn_blocking(record);
+            }
+            Err(_) => record(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::event_log;
+
+    fn test_connection() -> SqliteConnection {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://example.com");
+        conn
+    }
+
+    #[test]
+    fn test_used_percent_takes_highest_limit() {
+        let quota = AccountQuota {
+            account_id: 1,
+            monthly_gb: Some(100.0),
+            monthly_hours: Some(10.0),
+            block_when_exceeded: 0,
+        };
+        // 50 GB (50%) and 9 hours (90%)
+        let percent = used_percent(&quota, 50_000_000_000, 9 * 3600).unwrap();
+        assert!((percent - 90.0).abs() < 0.01);
+
+        let no_limits = AccountQuota {
+            monthly_gb: None,
+            monthly_hours: None,
+            ..quota
+        };
+        assert_eq!(used_percent(&no_limits, 1, 1), None);
+    }
+
+    #[test]
+    fn test_record_usage_warns_once_and_blocks() {
+        let mut conn = test_connection();
+        save_quota(&mut conn, 1, Some(1.0), None, true).unwrap();
+
+        record_usage(&mut conn, 1, 850_000_000, 60).unwrap();
+        record_usage(&mut conn, 1, 10_000_000, 60).unwrap();
+        assert!(blocked_accounts(&mut conn).unwrap().is_empty());
+
+        record_usage(&mut conn, 1, 200_000_000, 60).unwrap();
+        assert!(blocked_accounts(&mut conn).unwrap().contains(&1));
+
+        // One 80% warning and one exceeded warning
+        let warnings: i64 = event_log::table
+            .filter(event_log::level.eq("warn"))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(warnings, 2);
+
+        let status = list_status(&mut conn).unwrap();
+        assert_eq!(status[0].used_bytes, 1_060_000_000);
+        assert_eq!(status[0].used_seconds, 180);
+        assert!(status[0].exceeded);
+
+        // Clearing the limits lifts the block
+        save_quota(&mut conn, 1, None, None, true).unwrap();
+        assert!(blocked_accounts(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_may_be_recording() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::USER_AGENT,
+            "VLC/3.0.20 LibVLC/3.0.20".parse().unwrap(),
+        );
+        assert!(!may_be_recording(&headers, None));
+        assert!(may_be_recording(&headers, Some(true)));
+
+        headers.insert(header::USER_AGENT, "Lavf/60.3.100".parse().unwrap());
+        assert!(may_be_recording(&headers, None));
+        assert!(!may_be_recording(&headers, Some(false)));
+
+        let mut plex = HeaderMap::new();
+        plex.insert("x-plex-client-identifier", "abc".parse().unwrap());
+        assert!(may_be_recording(&plex, None));
+    }
+}
@@ -0,0 +1,153 @@
+//! Recent errors kept in memory
+//!
+//! The last error-level events are kept here as well as in the event log, so
+//! the tray tooltip and the dashboard can show that something is wrong
+//! without a database query, even when the database is what is failing.
+//! Errors logged while the event log can't be written, and failures to get a
+//! database connection, are recorded here too.
+//!
+//! Every new error updates the tray tooltip and is emitted as a
+//! `recent-error` event. Clearing the buffer (after the user has looked at
+//! the errors) resets the tooltip.
+
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter};
+
+/// Event emitted with each new `RecentError`
+pub const RECENT_ERROR_EVENT: &str = "recent-error";
+
+/// Errors kept (oldest dropped first)
+const RECENT_ERRORS_KEPT: usize = 50;
+
+/// Tooltip of the tray icon while there are no recent errors
+const TRAY_TOOLTIP: &str = "StreamForge";
+
+/// Longest error message shown in the tray tooltip
+const TOOLTIP_MESSAGE_CHARS: usize = 80;
+
+/// An error-level event
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentError {
+    /// Event log category ("database" for connection failures)
+    pub category: String,
+    pub message: String,
+    pub timestamp: String,
+    /// Whether the event also made it into the event log
+    pub persisted: bool,
+}
+
+static RECENT_ERRORS: OnceLock<Mutex<VecDeque<RecentError>>> = OnceLock::new();
+
+/// Handle used to update the tray and emit events (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+fn buffer() -> &'static Mutex<VecDeque<RecentError>> {
+    RECENT_ERRORS.get_or_init(|| Mutex::new(VecDeque::with_capacity(RECENT_ERRORS_KEPT)))
+}
+
+/// Store the app handle so new errors reach the tray and the frontend
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Record an error
+pub fn record(category: &str, message: &str, persisted: bool) {
+    let error = RecentError {
+        category: category.to_string(),
+        message: message.to_string(),
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        persisted,
+    };
+    let count = push(
+        &mut buffer().lock().unwrap_or_else(|e| e.into_inner()),
+        error.clone(),
+    );
+
+    if let Some(app) = APP_HANDLE.get() {
+        set_tray_tooltip(app, &tooltip(count, &error));
+        let _ = app.emit(RECENT_ERROR_EVENT, &error);
+    }
+}
+
+/// Append an error, dropping the oldest when full; returns the number kept
+fn push(errors: &mut VecDeque<RecentError>, error: RecentError) -> usize {
+    if errors.len() == RECENT_ERRORS_KEPT {
+        errors.pop_front();
+    }
+    errors.push_back(error);
+    errors.len()
+}
+
+/// Recent errors, newest first
+pub fn recent() -> Vec<RecentError> {
+    let errors = buffer().lock().unwrap_or_else(|e| e.into_inner());
+    errors.iter().rev().cloned().collect()
+}
+
+/// Errors that could not be written to the event log, oldest first
+pub fn unpersisted() -> Vec<RecentError> {
+    let errors = buffer().lock().unwrap_or_else(|e| e.into_inner());
+    errors.iter().filter(|e| !e.persisted).cloned().collect()
+}
+
+/// Forget the recent errors and reset the tray tooltip
+pub fn clear() {
+    buffer().lock().unwrap_or_else(|e| e.into_inner()).clear();
+    if let Some(app) = APP_HANDLE.get() {
+        set_tray_tooltip(app, TRAY_TOOLTIP);
+    }
+}
+
+/// Tray tooltip for `count` recent errors, the latest being `latest`
+fn tooltip(count: usize, latest: &RecentError) -> String {
+    let mut message: String = latest.message.chars().take(TOOLTIP_MESSAGE_CHARS).collect();
+    if latest.message.chars().count() > TOOLTIP_MESSAGE_CHARS {
+        message.push('…');
+    }
+    format!(
+        "{} - {} recent error{}\nLatest: {}",
+        TRAY_TOOLTIP,
+        count,
+        if count == 1 { "" } else { "s" },
+        message
+    )
+}
+
+fn set_tray_tooltip(app: &AppHandle, text: &str) {
+    if let Some(tray) = app.tray_by_id(crate::TRAY_ICON_ID) {
+        let _ = tray.set_tooltip(Some(text));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn error(message: String) -> RecentError {
+        RecentError {
+            category: "stream".to_string(),
+            message,
+            timestamp: String::new(),
+            persisted: true,
+        }
+    }
+
+    #[test]
+    fn test_buffer_is_bounded() {
+        let mut errors = VecDeque::new();
+        for i in 0..RECENT_ERRORS_KEPT + 5 {
+            assert!(push(&mut errors, error(format!("error {}", i))) <= RECENT_ERRORS_KEPT);
+        }
+        assert_eq!(errors.len(), RECENT_ERRORS_KEPT);
+        assert_eq!(errors.front().unwrap().message, "error 5");
+
+        let long = error("x".repeat(200));
+        let text = tooltip(2, &long);
+        assert!(text.starts_with("StreamForge - 2 recent errors\n"));
+        assert!(text.chars().count() < 120);
+    }
+}
@@ -4,13 +4,26 @@
 //! Uses tokio-cron-scheduler for robust cron-based job scheduling.
 //!
 //! Story 2-6: Implement Scheduled EPG Refresh
+//!
+//! An hourly job also prunes programs older than the EPG retention window
+//! (once a day) and VACUUMs the database weekly inside the maintenance window.
+//!
+//! A third, optional job re-scans the channel lists of all active provider
+//! accounts on a configurable cadence, auto-rematching new and removed streams
+//! the same way a manual "scan and rematch" does.
+//!
+//! A job every minute writes the metrics snapshot when export is enabled and
+//! due (see `crate::metrics`).
 
+use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tokio_cron_scheduler::{Job, JobScheduler, JobSchedulerError};
 use uuid::Uuid;
 
+use crate::clock::{self, Clock};
 use crate::db::DbPool;
+use crate::server::stream::StreamManager;
 
 /// Error types for scheduler operations
 #[derive(Debug, thiserror::Error)]
@@ -31,16 +44,25 @@ impl From<JobSchedulerError> for SchedulerError {
     }
 }
 
-/// EPG Scheduler that manages scheduled refresh jobs
+/// Scheduler that manages the background cron jobs
 ///
-/// The scheduler maintains a single cron job that triggers EPG refresh
-/// at the configured time each day.
+/// Maintains the daily EPG refresh job, the hourly pruning job, the
+/// optional channel re-scan job and the metrics export job.
 #[derive(Clone)]
 pub struct EpgScheduler {
     scheduler: Arc<RwLock<Option<JobScheduler>>>,
     job_uuid: Arc<RwLock<Option<Uuid>>>,
+    prune_job_uuid: Arc<RwLock<Option<Uuid>>>,
+    rescan_job_uuid: Arc<RwLock<Option<Uuid>>>,
+    metrics_job_uuid: Arc<RwLock<Option<Uuid>>>,
     db_pool: Arc<RwLock<Option<DbPool>>>,
+    /// App data directory, needed by the re-scan to read provider credentials
+    app_data_dir: Arc<RwLock<Option<PathBuf>>>,
+    /// Active sessions, reported by the metrics export
+    stream_manager: Arc<RwLock<Option<Arc<StreamManager>>>>,
     enabled: Arc<RwLock<bool>>,
+    /// Time source for due checks and recorded run times
+    clock: Arc<dyn Clock>,
 }
 
 impl EpgScheduler {
@@ -52,17 +74,39 @@ impl EpgScheduler {
         Self {
             scheduler: Arc::new(RwLock::new(None)),
             job_uuid: Arc::new(RwLock::new(None)),
+            prune_job_uuid: Arc::new(RwLock::new(None)),
+            rescan_job_uuid: Arc::new(RwLock::new(None)),
+            metrics_job_uuid: Arc::new(RwLock::new(None)),
             db_pool: Arc::new(RwLock::new(None)),
+            app_data_dir: Arc::new(RwLock::new(None)),
+            stream_manager: Arc::new(RwLock::new(None)),
             enabled: Arc::new(RwLock::new(true)),
+            clock: clock::system(),
         }
     }
 
+    /// Use a different time source (tests use `MockClock`)
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Set the database pool for refresh operations
     pub async fn set_db_pool(&self, pool: DbPool) {
         let mut db = self.db_pool.write().await;
         *db = Some(pool);
     }
 
+    /// Set the app data directory used by the channel re-scan
+    pub async fn set_app_data_dir(&self, app_data_dir: PathBuf) {
+        *self.app_data_dir.write().await = Some(app_data_dir);
+    }
+
+    /// Set the stream manager whose sessions the metrics export reports
+    pub async fn set_stream_manager(&self, stream_manager: Arc<StreamManager>) {
+        *self.stream_manager.write().await = Some(stream_manager);
+    }
+
     /// Start the scheduler
     ///
     /// Initializes the underlying JobScheduler and starts processing jobs.
@@ -71,6 +115,43 @@ impl EpgScheduler {
         let sched = JobScheduler::new().await?;
         sched.start().await?;
 
+        // EPG retention (and the daily account expiry check) runs independently
+        // of the refresh schedule
+        let db_pool = self.db_pool.clone();
+        let clock = self.clock.clone();
+        let prune_job = Job::new_async(PRUNE_CRON, move |_uuid, _lock| {
+            let pool = db_pool.clone();
+            let clock = clock.clone();
+            Box::pin(async move {
+                let pool = pool.read().await.clone();
+                if let Some(pool) = pool {
+                    run_scheduled_expiry_check(&pool, clock.as_ref());
+                    run_scheduled_pruning(pool, clock.as_ref());
+                }
+            })
+        })?;
+        let prune_uuid = sched.add(prune_job).await?;
+        *self.prune_job_uuid.write().await = Some(prune_uuid);
+
+        // Metrics export checks every minute whether a snapshot is due
+        let db_pool = self.db_pool.clone();
+        let stream_manager = self.stream_manager.clone();
+        let clock = self.clock.clone();
+        let metrics_job = Job::new_async(METRICS_EXPORT_CRON, move |_uuid, _lock| {
+            let pool = db_pool.clone();
+            let stream_manager = stream_manager.clone();
+            let clock = clock.clone();
+            Box::pin(async move {
+                let pool = pool.read().await.clone();
+                let stream_manager = stream_manager.read().await.clone();
+                if let (Some(pool), Some(stream_manager)) = (pool, stream_manager) {
+                    run_scheduled_metrics_export(pool, &stream_manager, clock.as_ref());
+                }
+            })
+        })?;
+        let metrics_uuid = sched.add(metrics_job).await?;
+        *self.metrics_job_uuid.write().await = Some(metrics_uuid);
+
         let mut scheduler = self.scheduler.write().await;
         *scheduler = Some(sched);
 
@@ -82,10 +163,17 @@ impl EpgScheduler {
     ///
     /// Stops all scheduled jobs and shuts down the scheduler.
     pub async fn stop(&self) -> Result<(), SchedulerError> {
-        // Remove the job first
-        if let Some(uuid) = *self.job_uuid.read().await {
-            if let Some(ref sched) = *self.scheduler.read().await {
-                let _ = sched.remove(&uuid).await;
+        // Remove the jobs first
+        for job in [
+            &self.job_uuid,
+            &self.prune_job_uuid,
+            &self.rescan_job_uuid,
+            &self.metrics_job_uuid,
+        ] {
+            if let Some(uuid) = *job.read().await {
+                if let Some(ref sched) = *self.scheduler.read().await {
+                    let _ = sched.remove(&uuid).await;
+                }
             }
         }
 
@@ -103,6 +191,9 @@ impl EpgScheduler {
             let mut job_uuid = self.job_uuid.write().await;
             *job_uuid = None;
         }
+        *self.prune_job_uuid.write().await = None;
+        *self.rescan_job_uuid.write().await = None;
+        *self.metrics_job_uuid.write().await = None;
 
         tracing::info!("EPG Scheduler stopped");
         Ok(())
@@ -158,15 +249,20 @@ impl EpgScheduler {
         let cron_expr = build_cron_expression(hour, minute);
         tracing::info!("Creating EPG refresh job with cron: {}", cron_expr);
 
-        // Clone pool for the job closure
+        // Clone pool, credential directory and clock for the job closure
         let db_pool = self.db_pool.clone();
+        let app_data_dir = self.app_data_dir.clone();
+        let clock = self.clock.clone();
 
         // Create the job
         let job = Job::new_async(cron_expr.as_str(), move |_uuid, _lock| {
             let pool = db_pool.clone();
+            let app_data_dir = app_data_dir.clone();
+            let clock = clock.clone();
             Box::pin(async move {
                 tracing::info!("Scheduled EPG refresh triggered");
-                run_scheduled_refresh(pool).await;
+                let app_data_dir = app_data_dir.read().await.clone();
+                run_scheduled_refresh(pool, app_data_dir, clock.as_ref()).await;
             })
         })
         .map_err(|e| SchedulerError::SchedulerError(e.to_string()))?;
@@ -214,6 +310,94 @@ impl EpgScheduler {
         Ok(())
     }
 
+    /// Re-apply the EPG and channel re-scan schedules stored in the current database
+    ///
+    /// Used after the database pool is replaced (workspace switch).
+    pub async fn apply_schedule_from_db(&self) -> Result<(), SchedulerError> {
+        let (schedule, rescan) = match self.get_db_connection().await {
+            Some(mut conn) => (
+                get_epg_schedule(&mut conn),
+                get_channel_rescan_schedule(&mut conn),
+            ),
+            None => {
+                return Err(SchedulerError::DatabaseError(
+                    "Database pool not available".to_string(),
+                ))
+            }
+        };
+
+        // The re-scan job is applied even if the refresh schedule fails
+        let rescan_result = self.update_rescan_schedule(&rescan).await;
+        self.set_enabled(schedule.enabled).await?;
+        if schedule.enabled {
+            self.update_schedule(schedule.hour, schedule.minute).await?;
+        }
+        rescan_result
+    }
+
+    /// Create, replace or remove the channel re-scan job
+    ///
+    /// The job fires daily at the configured time and re-scans when
+    /// `interval_days` have passed since the last scheduled re-scan.
+    pub async fn update_rescan_schedule(
+        &self,
+        schedule: &ChannelRescanSchedule,
+    ) -> Result<(), SchedulerError> {
+        schedule.validate().map_err(SchedulerError::InvalidSchedule)?;
+
+        let scheduler_guard = self.scheduler.read().await;
+        let sched = scheduler_guard.as_ref().ok_or_else(|| {
+            SchedulerError::SchedulerError("Scheduler not started".to_string())
+        })?;
+
+        if let Some(uuid) = self.rescan_job_uuid.write().await.take() {
+            let _ = sched.remove(&uuid).await;
+        }
+        if !schedule.enabled {
+            tracing::info!("Channel re-scan is disabled");
+            return Ok(());
+        }
+
+        let cron_expr = build_cron_expression(schedule.hour, schedule.minute);
+        let interval_days = schedule.interval_days;
+        let db_pool = self.db_pool.clone();
+        let app_data_dir = self.app_data_dir.clone();
+        let clock = self.clock.clone();
+        let job = Job::new_async(cron_expr.as_str(), move |_uuid, _lock| {
+            let db_pool = db_pool.clone();
+            let app_data_dir = app_data_dir.clone();
+            let clock = clock.clone();
+            Box::pin(async move {
+                let pool = db_pool.read().await.clone();
+                let dir = app_data_dir.read().await.clone();
+                match (pool, dir) {
+                    (Some(pool), Some(dir)) => {
+                        run_scheduled_rescan(pool, dir, interval_days, clock.as_ref()).await
+                    }
+                    _ => tracing::error!("Scheduled channel re-scan skipped: scheduler not configured"),
+                }
+            })
+        })
+        .map_err(|e| SchedulerError::SchedulerError(e.to_string()))?;
+
+        let uuid = sched.add(job).await?;
+        *self.rescan_job_uuid.write().await = Some(uuid);
+
+        tracing::info!(
+            "Channel re-scan scheduled for {:02}:{:02} every {} day(s) (job: {})",
+            schedule.hour,
+            schedule.minute,
+            interval_days,
+            uuid
+        );
+        Ok(())
+    }
+
+    /// Check if the channel re-scan job is scheduled
+    pub async fn has_rescan_job(&self) -> bool {
+        self.rescan_job_uuid.read().await.is_some()
+    }
+
     /// Check if the scheduler is enabled
     pub async fn is_enabled(&self) -> bool {
         *self.enabled.read().await
@@ -247,18 +431,29 @@ pub fn build_cron_expression(hour: u8, minute: u8) -> String {
     format!("0 {} {} * * *", minute, hour)
 }
 
+/// Refresh all active XMLTV sources now, outside the cron schedule
+///
+/// Used by the mobile dashboard quick action. Runs the same job as the scheduler.
+pub async fn refresh_all_sources_now(pool: DbPool, app_data_dir: PathBuf) {
+    run_scheduled_refresh(
+        Arc::new(RwLock::new(Some(pool))),
+        Some(app_data_dir),
+        &clock::SystemClock,
+    )
+    .await;
+}
+
 /// Run the scheduled refresh job
 ///
 /// This function is called by the cron job and performs the actual EPG refresh.
-async fn run_scheduled_refresh(db_pool: Arc<RwLock<Option<DbPool>>>) {
-    use crate::commands::epg::{preserve_channel_data, restore_channel_data};
-    use crate::db::schema::{xmltv_channels, xmltv_sources};
-    use crate::db::{NewProgram, NewXmltvChannel, XmltvSource};
-    use crate::xmltv::{fetch_xmltv, parse_xmltv_data};
+async fn run_scheduled_refresh(
+    db_pool: Arc<RwLock<Option<DbPool>>>,
+    app_data_dir: Option<PathBuf>,
+    clock: &dyn Clock,
+) {
+    use crate::db::schema::xmltv_sources;
+    use crate::db::XmltvSource;
     use diesel::prelude::*;
-    use std::collections::HashMap;
-
-    const BATCH_SIZE: usize = 500;
 
     let pool_guard = db_pool.read().await;
     let pool = match pool_guard.as_ref() {
@@ -291,174 +486,86 @@ async fn run_scheduled_refresh(db_pool: Arc<RwLock<Option<DbPool>>>) {
 
     if sources.is_empty() {
         tracing::info!("No active XMLTV sources to refresh");
-        update_last_scheduled_refresh(&mut conn);
+        update_last_scheduled_refresh(&mut conn, clock);
         return;
     }
 
     tracing::info!("Starting scheduled refresh of {} active sources", sources.len());
 
+    // Sources are fetched and parsed concurrently; a failed source keeps its old data
+    let results =
+        crate::xmltv::refresh::refresh_sources(pool.clone(), sources, app_data_dir, None).await;
+
     let mut success_count = 0;
     let mut failed_count = 0;
-
-    for source in sources {
-        let source_id = source.id.unwrap_or(0);
-        let source_name = source.name.clone();
-
-        tracing::info!("Refreshing source: {} (id: {})", source_name, source_id);
-
-        // Fetch and parse XMLTV data
-        let data = match fetch_xmltv(&source.url, &source.format).await {
-            Ok(d) => d,
-            Err(e) => {
-                tracing::error!("Failed to fetch source {}: {}", source_name, e);
-                failed_count += 1;
-                continue;
-            }
-        };
-
-        let (parsed_channels, parsed_programs) = match parse_xmltv_data(&data) {
-            Ok(p) => p,
-            Err(e) => {
-                tracing::error!("Failed to parse source {}: {}", source_name, e);
-                failed_count += 1;
-                continue;
-            }
-        };
-
-        // Wrap each source refresh in a transaction for atomicity
-        // If the refresh fails mid-way, the old data remains intact
-        let tx_result = conn.transaction::<_, diesel::result::Error, _>(|tx_conn| {
-            // Preserve manual mappings and channel settings before deletion
-            let preserved = preserve_channel_data(tx_conn, source_id)?;
-
-            // Clear existing data for this source (CASCADE deletes mappings/settings)
-            diesel::delete(
-                xmltv_channels::table.filter(xmltv_channels::source_id.eq(source_id)),
-            )
-            .execute(tx_conn)?;
-
-            // Insert channels
-            let mut channel_id_map: HashMap<String, i32> = HashMap::new();
-
-            for parsed_channel in &parsed_channels {
-                let new_channel = NewXmltvChannel::new(
-                    source_id,
-                    &parsed_channel.channel_id,
-                    &parsed_channel.display_name,
-                    parsed_channel.icon.clone(),
-                );
-
-                match diesel::insert_into(xmltv_channels::table)
-                    .values(&new_channel)
-                    .get_result::<crate::db::XmltvChannel>(tx_conn)
-                {
-                    Ok(inserted) => {
-                        if let Some(id) = inserted.id {
-                            channel_id_map.insert(parsed_channel.channel_id.clone(), id);
-                        }
-                    }
-                    Err(e) => {
-                        tracing::warn!(
-                            "Failed to insert channel {} for source {}: {}",
-                            parsed_channel.channel_id,
-                            source_name,
-                            e
-                        );
-                        // Don't fail the whole transaction for one channel
-                    }
-                }
-            }
-
-            // Insert programs in batches
-            use crate::db::schema::programs;
-            let mut programs_to_insert: Vec<NewProgram> = Vec::with_capacity(BATCH_SIZE);
-
-            for parsed_program in &parsed_programs {
-                if let Some(&channel_db_id) = channel_id_map.get(&parsed_program.channel_id) {
-                    let mut new_program = NewProgram::new(
-                        channel_db_id,
-                        &parsed_program.title,
-                        &parsed_program.start_time,
-                        &parsed_program.end_time,
-                    );
-
-                    if let Some(ref desc) = parsed_program.description {
-                        new_program = new_program.with_description(desc);
-                    }
-                    if let Some(ref cat) = parsed_program.category {
-                        new_program = new_program.with_category(cat);
-                    }
-                    if let Some(ref ep) = parsed_program.episode_info {
-                        new_program = new_program.with_episode_info(ep);
-                    }
-
-                    programs_to_insert.push(new_program);
-
-                    if programs_to_insert.len() >= BATCH_SIZE {
-                        diesel::insert_into(programs::table)
-                            .values(&programs_to_insert)
-                            .execute(tx_conn)?;
-                        programs_to_insert.clear();
-                    }
-                }
-            }
-
-            // Insert remaining programs
-            if !programs_to_insert.is_empty() {
-                diesel::insert_into(programs::table)
-                    .values(&programs_to_insert)
-                    .execute(tx_conn)?;
-            }
-
-            // Restore manual mappings and channel settings
-            restore_channel_data(tx_conn, &preserved, &channel_id_map)?;
-
-            // Update last_refresh timestamp on the source
-            let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
-            diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
-                .set(xmltv_sources::last_refresh.eq(&now))
-                .execute(tx_conn)?;
-
-            Ok(())
-        });
-
-        match tx_result {
-            Ok(_) => {
+    for result in results {
+        match result.outcome {
+            Ok((channel_count, program_count)) => {
                 success_count += 1;
                 tracing::info!(
                     "Completed refresh for source: {} ({} channels, {} programs)",
-                    source_name,
-                    parsed_channels.len(),
-                    parsed_programs.len()
+                    result.source_name,
+                    channel_count,
+                    program_count
                 );
             }
             Err(e) => {
                 tracing::error!(
-                    "Transaction failed for source {}: {}. Old data preserved.",
-                    source_name,
-                    e
+                    "Failed to refresh source {} ({:?}): {}. Old data preserved.",
+                    result.source_name,
+                    e.stage,
+                    e.error
                 );
                 failed_count += 1;
             }
         }
     }
 
+    // Merge per-channel feeds into the freshly refreshed channels
+    if success_count > 0 {
+        let feeds = crate::xmltv::channel_feed::refresh_channel_feeds(&mut conn, None).await;
+        if !feeds.errors.is_empty() {
+            tracing::warn!("{} channel feed(s) failed to refresh", feeds.errors.len());
+        }
+
+        // Serve the next Plex request from freshly generated output
+        crate::server::priming::prime_in_background("scheduled");
+    }
+
     // Update the last scheduled refresh timestamp
-    update_last_scheduled_refresh(&mut conn);
+    update_last_scheduled_refresh(&mut conn, clock);
 
     tracing::info!(
         "Scheduled EPG refresh completed: {} succeeded, {} failed",
         success_count,
         failed_count
     );
+
+    crate::hooks::fire(
+        crate::hooks::HookEvent::EpgRefreshCompleted,
+        serde_json::json!({
+            "trigger": "scheduled",
+            "succeeded": success_count,
+            "failed": failed_count,
+        }),
+    );
+    if success_count > 0 {
+        crate::plex::request_guide_reload("scheduled");
+    }
+
+    // Guide coverage changed, so re-evaluate the channel enablement policy.
+    // Disabling channels changes the lineup, so it waits for the maintenance window.
+    crate::maintenance::run_in_window(pool.clone(), "channel policy", |conn| {
+        crate::channel_policy::run_scheduled(conn);
+    });
 }
 
 /// Update the last scheduled refresh timestamp in settings
-fn update_last_scheduled_refresh(conn: &mut diesel::SqliteConnection) {
+fn update_last_scheduled_refresh(conn: &mut diesel::SqliteConnection, clock: &dyn Clock) {
     use crate::db::schema::settings;
     use diesel::prelude::*;
 
-    let now = chrono::Utc::now().to_rfc3339();
+    let now = clock.now_utc().to_rfc3339();
 
     let result = diesel::insert_into(settings::table)
         .values((
@@ -599,6 +706,483 @@ pub fn get_last_scheduled_refresh(
         .map(|dt| dt.with_timezone(&chrono::Utc))
 }
 
+// ============================================================================
+// EPG Retention
+// ============================================================================
+
+/// Settings key for the EPG retention window in days (0 keeps programs forever)
+pub const EPG_RETENTION_DAYS_KEY: &str = "epg_retention_days";
+
+/// Default EPG retention window in days
+pub const DEFAULT_EPG_RETENTION_DAYS: u32 = 7;
+
+/// Longest configurable EPG retention window in days
+pub const MAX_EPG_RETENTION_DAYS: u32 = 365;
+
+const EPG_LAST_PRUNED_KEY: &str = "epg_last_pruned_at";
+const DB_LAST_VACUUM_KEY: &str = "db_last_vacuum_at";
+
+/// Hourly check; pruning itself runs at most once per `PRUNE_INTERVAL_HOURS`
+const PRUNE_CRON: &str = "0 20 * * * *";
+/// Every minute, half a minute past (off the other jobs' second 0)
+const METRICS_EXPORT_CRON: &str = "30 * * * * *";
+const PRUNE_INTERVAL_HOURS: i64 = 24;
+const VACUUM_INTERVAL_DAYS: i64 = 7;
+
+/// Timestamp format of stored XMLTV program times
+const PROGRAM_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+fn get_setting_time(
+    conn: &mut diesel::SqliteConnection,
+    key: &str,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    use crate::db::schema::settings;
+    use diesel::prelude::*;
+
+    settings::table
+        .filter(settings::key.eq(key))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|s| chrono::DateTime::parse_from_rfc3339(&s).ok())
+        .map(|dt| dt.with_timezone(&chrono::Utc))
+}
+
+fn set_setting(
+    conn: &mut diesel::SqliteConnection,
+    key: &str,
+    value: &str,
+) -> Result<(), diesel::result::Error> {
+    use crate::db::schema::settings;
+    use diesel::prelude::*;
+
+    diesel::insert_into(settings::table)
+        .values((settings::key.eq(key), settings::value.eq(value)))
+        .on_conflict(settings::key)
+        .do_update()
+        .set(settings::value.eq(value))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Get the EPG retention window in days (0 = keep forever)
+pub fn get_epg_retention_days(conn: &mut diesel::SqliteConnection) -> u32 {
+    use crate::db::schema::settings;
+    use diesel::prelude::*;
+
+    settings::table
+        .filter(settings::key.eq(EPG_RETENTION_DAYS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .unwrap_or(DEFAULT_EPG_RETENTION_DAYS)
+}
+
+/// Set the EPG retention window in days (0 = keep forever)
+pub fn set_epg_retention_days(
+    conn: &mut diesel::SqliteConnection,
+    days: u32,
+) -> Result<(), diesel::result::Error> {
+    set_setting(conn, EPG_RETENTION_DAYS_KEY, &days.to_string())
+}
+
+/// When programs were last pruned
+pub fn get_last_pruned(conn: &mut diesel::SqliteConnection) -> Option<chrono::DateTime<chrono::Utc>> {
+    get_setting_time(conn, EPG_LAST_PRUNED_KEY)
+}
+
+/// When the database was last vacuumed
+pub fn get_last_vacuum(conn: &mut diesel::SqliteConnection) -> Option<chrono::DateTime<chrono::Utc>> {
+    get_setting_time(conn, DB_LAST_VACUUM_KEY)
+}
+
+/// Delete programs that ended more than `retention_days` before `now`
+///
+/// Aired programs are copied to the guide history first. Returns the number
+/// of programs deleted. A retention of 0 deletes nothing.
+pub fn prune_programs(
+    conn: &mut diesel::SqliteConnection,
+    retention_days: u32,
+    now: chrono::DateTime<chrono::Utc>,
+) -> Result<usize, diesel::result::Error> {
+    use crate::db::schema::programs;
+    use diesel::prelude::*;
+
+    if retention_days == 0 {
+        return Ok(0);
+    }
+    crate::xmltv::history::archive_aired_programs(
+        conn,
+        crate::xmltv::history::ArchiveScope::All,
+        now,
+    )?;
+    let cutoff = (now - chrono::Duration::days(i64::from(retention_days)))
+        .format(PROGRAM_TIME_FORMAT)
+        .to_string();
+    diesel::delete(programs::table.filter(programs::end_time.lt(cutoff))).execute(conn)
+}
+
+/// Whether a periodic job last run at `last` is due again
+fn is_due(
+    last: Option<chrono::DateTime<chrono::Utc>>,
+    interval: chrono::Duration,
+    now: chrono::DateTime<chrono::Utc>,
+) -> bool {
+    last.is_none_or(|last| now - last >= interval)
+}
+
+/// Prune old programs if due, and queue a VACUUM for the maintenance window if due
+fn run_scheduled_pruning(pool: DbPool, clock: &dyn Clock) {
+    let mut conn = match pool.get() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to get database connection for EPG pruning: {}", e);
+            return;
+        }
+    };
+
+    let now = clock.now_utc();
+    if is_due(
+        get_last_pruned(&mut conn),
+        chrono::Duration::hours(PRUNE_INTERVAL_HOURS),
+        now,
+    ) {
+        let retention_days = get_epg_retention_days(&mut conn);
+        match prune_programs(&mut conn, retention_days, now) {
+            Ok(deleted) => {
+                let _ = set_setting(&mut conn, EPG_LAST_PRUNED_KEY, &now.to_rfc3339());
+                if deleted > 0 {
+                    tracing::info!(
+                        "Pruned {} programs older than {} days",
+                        deleted,
+                        retention_days
+                    );
+                    let details = serde_json::json!({
+                        "deleted": deleted,
+                        "retentionDays": retention_days,
+                    });
+                    let _ = crate::commands::logs::log_event_internal(
+                        &mut conn,
+                        "info",
+                        "epg",
+                        &format!(
+                            "EPG pruned: {} programs older than {} days removed",
+                            deleted, retention_days
+                        ),
+                        Some(&details.to_string()),
+                    );
+                }
+            }
+            Err(e) => tracing::error!("EPG pruning failed: {}", e),
+        }
+
+        let history_days = crate::xmltv::history::get_epg_history_days(&mut conn);
+        match crate::xmltv::history::prune_program_history(&mut conn, history_days, now) {
+            Ok(deleted) if deleted > 0 => {
+                tracing::info!("Pruned {} programs from the guide history", deleted)
+            }
+            Ok(_) => {}
+            Err(e) => tracing::error!("Guide history pruning failed: {}", e),
+        }
+    }
+
+    if is_due(
+        get_last_vacuum(&mut conn),
+        chrono::Duration::days(VACUUM_INTERVAL_DAYS),
+        now,
+    ) {
+        drop(conn);
+        // VACUUM locks the database while it rewrites the file
+        crate::maintenance::run_in_window(pool, "database vacuum", vacuum_database);
+    }
+}
+
+/// Warn about expiring Xtream subscriptions if the daily check is due
+fn run_scheduled_expiry_check(pool: &DbPool, clock: &dyn Clock) {
+    let mut conn = match pool.get() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to get database connection for expiry check: {}", e);
+            return;
+        }
+    };
+    if let Err(e) = crate::account_expiry::check_if_due(&mut conn, clock.now_utc()) {
+        tracing::error!("Account expiry check failed: {}", e);
+    }
+}
+
+/// Write the metrics snapshot if export is enabled and due
+fn run_scheduled_metrics_export(pool: DbPool, stream_manager: &StreamManager, clock: &dyn Clock) {
+    let mut conn = match pool.get() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to get database connection for metrics export: {}", e);
+            return;
+        }
+    };
+    crate::metrics::export_if_due(&mut conn, stream_manager, clock.now_utc());
+}
+
+/// VACUUM the database and log the space reclaimed
+fn vacuum_database(conn: &mut diesel::SqliteConnection) {
+    use diesel::connection::SimpleConnection;
+
+    let size_before = database_size(conn);
+    let started = std::time::Instant::now();
+    if let Err(e) = conn.batch_execute("VACUUM") {
+        tracing::error!("Database VACUUM failed: {}", e);
+        return;
+    }
+    let _ = set_setting(conn, DB_LAST_VACUUM_KEY, &chrono::Utc::now().to_rfc3339());
+
+    let size_after = database_size(conn);
+    let reclaimed = size_before.saturating_sub(size_after);
+    tracing::info!(
+        "Database vacuumed in {} ms, {} bytes reclaimed",
+        started.elapsed().as_millis(),
+        reclaimed
+    );
+    let details = serde_json::json!({
+        "sizeBefore": size_before,
+        "sizeAfter": size_after,
+        "durationMs": started.elapsed().as_millis() as u64,
+    });
+    let _ = crate::commands::logs::log_event_internal(
+        conn,
+        "info",
+        "system",
+        &format!("Database vacuumed: {} KB reclaimed", reclaimed / 1024),
+        Some(&details.to_string()),
+    );
+}
+
+/// Database size in bytes (page_count * page_size)
+fn database_size(conn: &mut diesel::SqliteConnection) -> u64 {
+    use diesel::prelude::*;
+
+    #[derive(diesel::QueryableByName)]
+    struct Size {
+        #[diesel(sql_type = diesel::sql_types::BigInt)]
+        size: i64,
+    }
+
+    diesel::sql_query(
+        "SELECT page_count * page_size AS size FROM pragma_page_count(), pragma_page_size()",
+    )
+    .get_result::<Size>(conn)
+    .map(|s| s.size.max(0) as u64)
+    .unwrap_or(0)
+}
+
+// ============================================================================
+// Scheduled Channel Re-scan
+// ============================================================================
+
+/// Settings key for the channel re-scan schedule (JSON)
+pub const CHANNEL_RESCAN_SCHEDULE_KEY: &str = "channel_rescan_schedule";
+
+const CHANNEL_LAST_RESCAN_KEY: &str = "channel_last_scheduled_rescan";
+
+/// Longest configurable re-scan interval in days
+pub const MAX_RESCAN_INTERVAL_DAYS: u32 = 30;
+
+/// Slack allowed when checking the interval, so a re-scan that finished a
+/// few minutes after its start time doesn't push the next one back a day
+const RESCAN_INTERVAL_SLACK_HOURS: i64 = 1;
+
+/// Channel re-scan schedule
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ChannelRescanSchedule {
+    pub enabled: bool,
+    /// Local time of day the re-scan runs
+    pub hour: u8,
+    pub minute: u8,
+    /// Days between re-scans (1 = nightly)
+    pub interval_days: u32,
+}
+
+impl Default for ChannelRescanSchedule {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            hour: 3,
+            minute: 30,
+            interval_days: 1,
+        }
+    }
+}
+
+impl ChannelRescanSchedule {
+    /// Check the time of day and interval
+    pub fn validate(&self) -> Result<(), String> {
+        if self.hour > 23 {
+            return Err(format!("Hour must be 0-23, got {}", self.hour));
+        }
+        if self.minute > 59 {
+            return Err(format!("Minute must be 0-59, got {}", self.minute));
+        }
+        if self.interval_days == 0 || self.interval_days > MAX_RESCAN_INTERVAL_DAYS {
+            return Err(format!(
+                "Interval must be 1-{} days, got {}",
+                MAX_RESCAN_INTERVAL_DAYS, self.interval_days
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Read the channel re-scan schedule (defaults if unset or invalid)
+pub fn get_channel_rescan_schedule(conn: &mut diesel::SqliteConnection) -> ChannelRescanSchedule {
+    use crate::db::schema::settings;
+    use diesel::prelude::*;
+
+    settings::table
+        .filter(settings::key.eq(CHANNEL_RESCAN_SCHEDULE_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str::<ChannelRescanSchedule>(&value).ok())
+        .filter(|schedule| schedule.validate().is_ok())
+        .unwrap_or_default()
+}
+
+/// Persist the channel re-scan schedule
+pub fn set_channel_rescan_schedule(
+    conn: &mut diesel::SqliteConnection,
+    schedule: &ChannelRescanSchedule,
+) -> Result<(), String> {
+    schedule.validate()?;
+    let value = serde_json::to_string(schedule).map_err(|e| e.to_string())?;
+    set_setting(conn, CHANNEL_RESCAN_SCHEDULE_KEY, &value)
+        .map_err(|e| format!("Failed to save re-scan schedule: {}", e))
+}
+
+/// When channels were last re-scanned by the scheduler
+pub fn get_last_channel_rescan(
+    conn: &mut diesel::SqliteConnection,
+) -> Option<chrono::DateTime<chrono::Utc>> {
+    get_setting_time(conn, CHANNEL_LAST_RESCAN_KEY)
+}
+
+/// Interval checked before a re-scan runs
+fn rescan_interval(interval_days: u32) -> chrono::Duration {
+    chrono::Duration::days(i64::from(interval_days))
+        - chrono::Duration::hours(RESCAN_INTERVAL_SLACK_HOURS)
+}
+
+/// Re-scan and auto-rematch every active account if the interval has passed
+///
+/// Waits for the maintenance window, since removed streams drop their
+/// mappings and change the lineup. An account that fails to scan keeps its
+/// previous channels.
+async fn run_scheduled_rescan(
+    pool: DbPool,
+    app_data_dir: PathBuf,
+    interval_days: u32,
+    clock: &dyn Clock,
+) {
+    use crate::db::schema::accounts;
+    use diesel::prelude::*;
+
+    match pool.get() {
+        Ok(mut conn) => {
+            if !is_due(
+                get_last_channel_rescan(&mut conn),
+                rescan_interval(interval_days),
+                clock.now_utc(),
+            ) {
+                tracing::info!("Channel re-scan not due yet");
+                return;
+            }
+        }
+        Err(e) => {
+            tracing::error!("Failed to get database connection for channel re-scan: {}", e);
+            return;
+        }
+    }
+
+    if !crate::maintenance::wait_for_window(&pool, "channel rescan").await {
+        return;
+    }
+
+    let mut conn = match pool.get() {
+        Ok(c) => c,
+        Err(e) => {
+            tracing::error!("Failed to get database connection for channel re-scan: {}", e);
+            return;
+        }
+    };
+
+    let account_list: Vec<(Option<i32>, String)> = match accounts::table
+        .filter(accounts::is_active.eq(1))
+        .select((accounts::id, accounts::name))
+        .load(&mut conn)
+    {
+        Ok(list) => list,
+        Err(e) => {
+            tracing::error!("Failed to load accounts for channel re-scan: {}", e);
+            return;
+        }
+    };
+
+    tracing::info!("Starting scheduled re-scan of {} accounts", account_list.len());
+
+    let mut scanned = 0;
+    let mut failed: Vec<String> = Vec::new();
+    let mut lineup_changed = false;
+    for (id, name) in account_list {
+        let Some(id) = id else { continue };
+        let result =
+            crate::commands::channels::scan_and_rematch_internal(&mut conn, app_data_dir.clone(), id)
+                .await;
+        match result {
+            Ok(response) if response.success => {
+                scanned += 1;
+                lineup_changed |= response.new_channels > 0
+                    || response.removed_channels > 0
+                    || response.new_matches > 0
+                    || response.removed_matches > 0;
+            }
+            Ok(response) => {
+                tracing::warn!(
+                    "Scheduled re-scan of {} failed: {}",
+                    name,
+                    response.error_message.unwrap_or_default()
+                );
+                failed.push(name);
+            }
+            Err(e) => {
+                tracing::error!("Scheduled re-scan of {} failed: {}", name, e);
+                failed.push(name);
+            }
+        }
+    }
+
+    let _ = set_setting(&mut conn, CHANNEL_LAST_RESCAN_KEY, &clock.now_utc().to_rfc3339());
+
+    let details = serde_json::json!({
+        "scanned": scanned,
+        "failed": failed,
+    });
+    let _ = crate::commands::logs::log_event_internal(
+        &mut conn,
+        if failed.is_empty() { "info" } else { "warn" },
+        "provider",
+        &format!(
+            "Scheduled channel re-scan completed: {} accounts scanned, {} failed",
+            scanned,
+            failed.len()
+        ),
+        Some(&details.to_string()),
+    );
+
+    if lineup_changed {
+        crate::server::priming::prime_in_background("rescan");
+    }
+}
+
 // ============================================================================
 // Missed Refresh Detection
 // ============================================================================
@@ -621,7 +1205,16 @@ pub fn should_trigger_missed_refresh(
     schedule: &EpgScheduleConfig,
     last_scheduled_refresh: Option<chrono::DateTime<chrono::Utc>>,
 ) -> bool {
-    use chrono::{Local, NaiveTime};
+    should_trigger_missed_refresh_with(schedule, last_scheduled_refresh, &clock::SystemClock)
+}
+
+/// `should_trigger_missed_refresh` against the given clock's time and time zone
+pub fn should_trigger_missed_refresh_with(
+    schedule: &EpgScheduleConfig,
+    last_scheduled_refresh: Option<chrono::DateTime<chrono::Utc>>,
+    clock: &dyn Clock,
+) -> bool {
+    use chrono::NaiveTime;
 
     // If scheduling is disabled, no need to trigger
     if !schedule.enabled {
@@ -640,7 +1233,7 @@ pub fn should_trigger_missed_refresh(
         return false;
     }
 
-    let now = Local::now();
+    let now = clock.now();
     // Safe to unwrap after validation above
     let schedule_time = NaiveTime::from_hms_opt(
         schedule.hour as u32,
@@ -662,7 +1255,7 @@ pub fn should_trigger_missed_refresh(
     match last_scheduled_refresh {
         Some(last) => {
             // Convert to local for comparison
-            let last_local = last.with_timezone(&Local).naive_local();
+            let last_local = last.with_timezone(now.offset()).naive_local();
             // If last refresh is before most recent scheduled time, we missed one
             let missed = last_local < most_recent_scheduled;
             if missed {
@@ -718,14 +1311,16 @@ pub async fn check_and_trigger_missed_refresh(scheduler: &EpgScheduler) {
     let last_refresh = get_last_scheduled_refresh(&mut conn);
 
     // Check if refresh was missed
-    if should_trigger_missed_refresh(&schedule, last_refresh) {
+    if should_trigger_missed_refresh_with(&schedule, last_refresh, scheduler.clock.as_ref()) {
         tracing::info!("Triggering missed EPG refresh");
         // Drop the connection before running refresh (it needs its own connection)
         drop(conn);
         drop(pool_guard);
 
         // Trigger the refresh
-        run_scheduled_refresh(scheduler.db_pool.clone()).await;
+        let app_data_dir = scheduler.app_data_dir.read().await.clone();
+        run_scheduled_refresh(scheduler.db_pool.clone(), app_data_dir, scheduler.clock.as_ref())
+            .await;
     } else {
         tracing::info!("No missed EPG refresh detected");
     }
@@ -735,6 +1330,80 @@ pub async fn check_and_trigger_missed_refresh(scheduler: &EpgScheduler) {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prune_programs() {
+        use diesel::prelude::*;
+
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO programs (xmltv_channel_id, title, start_time, end_time) VALUES
+                (1, 'Old', '2026-01-01T00:00:00Z', '2026-01-01T01:00:00Z'),
+                (1, 'Recent', '2026-01-18T00:00:00Z', '2026-01-18T01:00:00Z'),
+                (1, 'Upcoming', '2026-01-20T13:00:00Z', '2026-01-20T14:00:00Z')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        let now = chrono::DateTime::parse_from_rfc3339("2026-01-20T12:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        assert_eq!(prune_programs(&mut conn, 0, now).unwrap(), 0);
+        assert_eq!(prune_programs(&mut conn, 7, now).unwrap(), 1);
+        assert_eq!(prune_programs(&mut conn, 1, now).unwrap(), 1);
+
+        use crate::db::schema::programs;
+        let titles: Vec<String> = programs::table
+            .select(programs::title)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(titles, vec!["Upcoming".to_string()]);
+
+        assert_eq!(get_epg_retention_days(&mut conn), DEFAULT_EPG_RETENTION_DAYS);
+        set_epg_retention_days(&mut conn, 30).unwrap();
+        assert_eq!(get_epg_retention_days(&mut conn), 30);
+        assert!(database_size(&mut conn) > 0);
+    }
+
+    #[test]
+    fn test_is_due() {
+        let now = chrono::Utc::now();
+        let day = chrono::Duration::days(1);
+        assert!(is_due(None, day, now));
+        assert!(is_due(Some(now - day), day, now));
+        assert!(!is_due(Some(now - chrono::Duration::hours(2)), day, now));
+    }
+
+    #[test]
+    fn test_channel_rescan_schedule() {
+        let mut conn = crate::db::test_connection();
+
+        assert_eq!(
+            get_channel_rescan_schedule(&mut conn),
+            ChannelRescanSchedule::default()
+        );
+        let weekly = ChannelRescanSchedule {
+            enabled: true,
+            hour: 2,
+            minute: 15,
+            interval_days: 7,
+        };
+        set_channel_rescan_schedule(&mut conn, &weekly).unwrap();
+        assert_eq!(get_channel_rescan_schedule(&mut conn), weekly);
+
+        let invalid = ChannelRescanSchedule {
+            interval_days: 0,
+            ..weekly.clone()
+        };
+        assert!(set_channel_rescan_schedule(&mut conn, &invalid).is_err());
+        assert_eq!(get_channel_rescan_schedule(&mut conn), weekly);
+
+        // A nightly re-scan that finished a few minutes late still runs the next night
+        let now = chrono::Utc::now();
+        let last = now - chrono::Duration::days(1) + chrono::Duration::minutes(10);
+        assert!(is_due(Some(last), rescan_interval(1), now));
+        assert!(!is_due(Some(last), rescan_interval(2), now));
+    }
+
     #[test]
     fn test_build_cron_expression() {
         // 4:00 AM daily
@@ -873,6 +1542,30 @@ mod tests {
         assert!(should_trigger_missed_refresh(&schedule, Some(two_days_ago)));
     }
 
+    #[test]
+    fn test_missed_refresh_follows_the_clock() {
+        use crate::clock::MockClock;
+
+        let schedule = EpgScheduleConfig {
+            hour: 4,
+            minute: 0,
+            enabled: true,
+        };
+        // Last refresh ran at 04:00 local (UTC+1) yesterday
+        let last = chrono::DateTime::parse_from_rfc3339("2026-03-09T03:00:00Z")
+            .unwrap()
+            .with_timezone(&chrono::Utc);
+
+        let clock = MockClock::at("2026-03-10T03:59:00+01:00");
+        assert!(!should_trigger_missed_refresh_with(&schedule, Some(last), clock.as_ref()));
+        clock.advance(chrono::Duration::minutes(1));
+        assert!(should_trigger_missed_refresh_with(&schedule, Some(last), clock.as_ref()));
+
+        // In UTC+5 the last run was at 08:00 local, after that day's 04:00 slot
+        let east = MockClock::at("2026-03-09T08:00:00+05:00");
+        assert!(!should_trigger_missed_refresh_with(&schedule, Some(last), east.as_ref()));
+    }
+
     #[test]
     fn test_epg_schedule_config_default() {
         let config = EpgScheduleConfig::default();
@@ -0,0 +1,311 @@
+//! Web Admin UI and REST Admin API
+//!
+//! Lets a browser manage a headless install without the Tauri window.
+//! The UI is a single static page bundled into the binary, backed by a small
+//! JSON API that reuses the same logic as the Tauri commands:
+//!
+//! - `GET  /admin` - Web admin UI
+//! - `GET  /api/admin/accounts` - Xtream accounts (without passwords)
+//! - `GET  /api/admin/sources` - XMLTV sources
+//! - `POST /api/admin/sources/{id}/active` - Enable/disable an XMLTV source
+//! - `GET  /api/admin/channels` - XMLTV channels with matched streams
+//! - `POST /api/admin/channels/{id}/toggle` - Enable/disable a channel in the lineup
+//! - `GET  /api/admin/matching/stats` - Match statistics
+//! - `POST /api/admin/matching/run` - Run channel matching
+//!
+//! Mobile dashboard:
+//!
+//! - `GET  /m` - Compact mobile status page
+//! - `GET  /api/admin/status` - Active streams, now playing, recent errors
+//! - `POST /api/admin/streams/{session_id}/stop` - Stop an active stream
+//! - `POST /api/admin/epg/refresh` - Refresh all EPG sources in the background
+//!
+//! All routes are protected by `auth::require_admin_auth`; `POST` routes
+//! also need the `X-Requested-With` header (`auth::require_same_origin`).
+
+use axum::{
+    extract::{Path, State},
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::IntoResponse,
+    Json,
+};
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use super::state::AppState;
+use crate::commands::accounts::AccountResponse;
+use crate::commands::epg::{toggle_xmltv_source_internal, XmltvSourceResponse};
+use crate::commands::logs::log_event_internal;
+use crate::commands::matcher::{run_channel_matching_internal, MatchResponse};
+use crate::commands::xmltv_channels::{
+    load_xmltv_channels_with_mappings, toggle_xmltv_channel_internal, XmltvChannelWithMappings,
+};
+use crate::db::models::{Account, EventLog, XmltvSource};
+use crate::db::schema::{accounts, event_log, programs, xmltv_channels, xmltv_sources};
+use crate::db::{DbPooledConnection, SYNTHETIC_SOURCE_ID};
+use crate::matcher::{calculate_match_stats, MatchStats};
+use crate::scheduler::refresh_all_sources_now;
+
+/// Bundled web admin page
+const WEB_ADMIN_HTML: &str = include_str!("webui/index.html");
+
+/// Bundled mobile dashboard page
+const MOBILE_DASHBOARD_HTML: &str = include_str!("webui/mobile.html");
+
+/// Number of recent error events shown on the mobile dashboard
+const DASHBOARD_RECENT_ERRORS: i64 = 10;
+
+type AdminResult<T> = Result<Json<T>, (StatusCode, String)>;
+
+/// Request body for enabling/disabling an XMLTV source
+#[derive(Deserialize)]
+pub struct SourceActiveRequest {
+    pub active: bool,
+}
+
+/// Request body for running channel matching
+#[derive(Deserialize, Default)]
+pub struct RunMatchingRequest {
+    pub threshold: Option<f64>,
+}
+
+/// Active stream shown on the mobile dashboard
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardStream {
+    pub session_id: String,
+    pub channel_id: i32,
+    pub channel_name: String,
+    /// Account the stream is played from
+    pub account_id: Option<i32>,
+    pub quality: String,
+    pub uptime_seconds: u64,
+    pub failover_count: u32,
+    /// Title of the program currently airing on the channel
+    pub now_playing: Option<String>,
+}
+
+/// Mobile dashboard status
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DashboardStatus {
+    pub active_streams: Vec<DashboardStream>,
+    pub max_connections: u32,
+    pub recent_errors: Vec<EventLog>,
+}
+
+/// Serve the web admin UI
+pub async fn admin_ui() -> impl IntoResponse {
+    html_page(WEB_ADMIN_HTML)
+}
+
+/// Serve the mobile dashboard
+pub async fn mobile_dashboard() -> impl IntoResponse {
+    html_page(MOBILE_DASHBOARD_HTML)
+}
+
+/// List Xtream accounts (passwords are never included)
+pub async fn admin_accounts(State(state): State<AppState>) -> AdminResult<Vec<AccountResponse>> {
+    let mut conn = admin_connection(&state)?;
+
+    let account_list: Vec<Account> = accounts::table
+        .load(&mut conn)
+        .map_err(internal_error)?;
+
+    Ok(Json(account_list.into_iter().map(AccountResponse::from).collect()))
+}
+
+/// List XMLTV sources
+pub async fn admin_sources(State(state): State<AppState>) -> AdminResult<Vec<XmltvSourceResponse>> {
+    let mut conn = admin_connection(&state)?;
+
+    let sources: Vec<XmltvSource> = xmltv_sources::table
+        .filter(xmltv_sources::id.ne(SYNTHETIC_SOURCE_ID))
+        .order(xmltv_sources::name.asc())
+        .load(&mut conn)
+        .map_err(internal_error)?;
+
+    Ok(Json(sources.into_iter().map(XmltvSourceResponse::from).collect()))
+}
+
+/// Enable or disable an XMLTV source
+pub async fn admin_set_source_active(
+    Path(source_id): Path<i32>,
+    State(state): State<AppState>,
+    Json(request): Json<SourceActiveRequest>,
+) -> AdminResult<XmltvSourceResponse> {
+    let mut conn = admin_connection(&state)?;
+
+    toggle_xmltv_source_internal(&mut conn, source_id, request.active)
+        .map(Json)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))
+}
+
+/// List XMLTV channels with their matched streams
+pub async fn admin_channels(
+    State(state): State<AppState>,
+) -> AdminResult<Vec<XmltvChannelWithMappings>> {
+    let mut conn = admin_connection(&state)?;
+
+    load_xmltv_channels_with_mappings(&mut conn)
+        .map(Json)
+        .map_err(|e| (StatusCode::INTERNAL_SERVER_ERROR, e))
+}
+
+/// Toggle whether a channel is part of the Plex lineup
+pub async fn admin_toggle_channel(
+    Path(channel_id): Path<i32>,
+    State(state): State<AppState>,
+) -> AdminResult<XmltvChannelWithMappings> {
+    let mut conn = admin_connection(&state)?;
+
+    let channel = toggle_xmltv_channel_internal(&mut conn, channel_id)
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    // Lineup changed - next /epg.xml request must regenerate
+    state.bump_epg_generation();
+
+    Ok(Json(channel))
+}
+
+/// Get match statistics
+pub async fn admin_match_stats(State(state): State<AppState>) -> AdminResult<MatchStats> {
+    calculate_match_stats(&state.pool())
+        .map(Json)
+        .map_err(internal_error)
+}
+
+/// Run channel matching
+///
+/// Matching is CPU-bound, so it runs on the blocking thread pool.
+pub async fn admin_run_matching(
+    State(state): State<AppState>,
+    request: Option<Json<RunMatchingRequest>>,
+) -> AdminResult<MatchResponse> {
+    let threshold = request.and_then(|Json(r)| r.threshold);
+    let mut conn = admin_connection(&state)?;
+
+    let app_data_dir = state.app_data_dir().clone();
+    let response = tokio::task::spawn_blocking(move || {
+        run_channel_matching_internal(&mut conn, threshold, Some(&app_data_dir), |_| {})
+    })
+    .await
+    .map_err(internal_error)?
+    .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    state.bump_epg_generation();
+
+    Ok(Json(response))
+}
+
+/// Get active streams, now playing, and recent errors
+pub async fn admin_status(State(state): State<AppState>) -> AdminResult<DashboardStatus> {
+    let mut conn = admin_connection(&state)?;
+    let stream_manager = state.stream_manager();
+
+    // Same timestamp format as program search (stored XMLTV times are ISO 8601)
+    let now = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%S").to_string();
+
+    let mut sessions = stream_manager.list_sessions();
+    sessions.sort_by_key(|(_, session)| session.started_at);
+
+    let mut active_streams = Vec::with_capacity(sessions.len());
+    for (session_id, session) in sessions {
+        let channel_name = xmltv_channels::table
+            .filter(xmltv_channels::id.eq(session.xmltv_channel_id))
+            .select(xmltv_channels::display_name)
+            .first::<String>(&mut conn)
+            .unwrap_or_else(|_| format!("Channel {}", session.xmltv_channel_id));
+
+        let now_playing = programs::table
+            .filter(programs::xmltv_channel_id.eq(session.xmltv_channel_id))
+            .filter(programs::start_time.le(&now))
+            .filter(programs::end_time.gt(&now))
+            .select(programs::title)
+            .first::<String>(&mut conn)
+            .optional()
+            .map_err(internal_error)?;
+
+        active_streams.push(DashboardStream {
+            session_id,
+            channel_id: session.xmltv_channel_id,
+            channel_name,
+            account_id: session.account_id,
+            quality: session.current_quality,
+            uptime_seconds: session.started_at.elapsed().as_secs(),
+            failover_count: session.failover_count,
+            now_playing,
+        });
+    }
+
+    let recent_errors: Vec<EventLog> = event_log::table
+        .filter(event_log::level.eq("error"))
+        .order(event_log::timestamp.desc())
+        .limit(DASHBOARD_RECENT_ERRORS)
+        .load(&mut conn)
+        .map_err(internal_error)?;
+
+    Ok(Json(DashboardStatus {
+        active_streams,
+        max_connections: stream_manager.max_connections(),
+        recent_errors,
+    }))
+}
+
+/// Stop an active stream, freeing its tuner slot
+pub async fn admin_stop_stream(
+    Path(session_id): Path<String>,
+    State(state): State<AppState>,
+) -> Result<StatusCode, (StatusCode, String)> {
+    if !state.stream_manager().request_stop(&session_id) {
+        return Err((StatusCode::NOT_FOUND, "Stream session not found".to_string()));
+    }
+
+    if let Ok(mut conn) = state.get_connection() {
+        let details = serde_json::json!({ "sessionId": session_id });
+        let _ = log_event_internal(
+            &mut conn,
+            "info",
+            "stream",
+            "Stream stopped from web dashboard",
+            Some(&details.to_string()),
+        );
+    }
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Refresh all active EPG sources in the background
+pub async fn admin_refresh_epg(State(state): State<AppState>) -> StatusCode {
+    let pool = state.pool();
+    let app_data_dir = state.app_data_dir().clone();
+    tokio::spawn(async move {
+        refresh_all_sources_now(pool, app_data_dir).await;
+        state.bump_epg_generation();
+    });
+    StatusCode::ACCEPTED
+}
+
+fn html_page(content: &'static str) -> impl IntoResponse {
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("text/html; charset=utf-8"),
+    );
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    (headers, content)
+}
+
+fn admin_connection(state: &AppState) -> Result<DbPooledConnection, (StatusCode, String)> {
+    state.get_connection().map_err(|e| {
+        eprintln!("Web admin error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Service temporarily unavailable".to_string(),
+        )
+    })
+}
+
+fn internal_error(e: impl std::fmt::Display) -> (StatusCode, String) {
+    (StatusCode::INTERNAL_SERVER_ERROR, e.to_string())
+}
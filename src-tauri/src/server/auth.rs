@@ -0,0 +1,474 @@
+//! Web Admin Authentication and Access Token
+//!
+//! The web admin UI and REST admin API are protected with HTTP Basic auth.
+//! The password is set from the desktop app and stored as an Argon2id hash
+//! (PHC string) in the settings table. Hashes from older versions (salted
+//! SHA-256) are still accepted and replaced on the next successful login.
+//! While no password is set, all admin routes are disabled. Browsers resend
+//! Basic credentials on their own, so admin requests that change something
+//! must carry the `X-Requested-With` header (which other sites cannot add
+//! without a CORS preflight this server never allows) and, when the browser
+//! sends an `Origin`, come from this server's own origin.
+//!
+//! Playlists, guide data, the HDHomeRun lineup and streams can additionally
+//! require an access token, passed as `?token=` or in the
+//! `X-StreamForge-Token` header. Without a token configured they stay open.
+//! Generated URLs (playlist stream URLs, `LineupURL`, lineup stream URLs)
+//! include the token so Plex needs no extra configuration. HDHomeRun
+//! discovery (`/discover.json`) has to stay open for Plex, so its tokenized
+//! `LineupURL` is visible to anyone who can reach the server; the token keeps
+//! out clients that only know the playlist or stream URLs.
+
+use argon2::password_hash::rand_core::OsRng;
+use argon2::password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use argon2::Argon2;
+use axum::{
+    body::Body,
+    extract::{Request, State},
+    http::{header, HeaderValue, Method, Response, StatusCode},
+    middleware::Next,
+};
+use base64::{engine::general_purpose::STANDARD, Engine as _};
+use diesel::prelude::*;
+use rand::RngCore;
+use sha2::{Digest, Sha256};
+
+use super::state::AppState;
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key holding the web admin password hash
+pub const WEB_ADMIN_PASSWORD_KEY: &str = "web_admin_password_hash";
+
+/// Username expected in the Basic auth header
+pub const WEB_ADMIN_USERNAME: &str = "admin";
+
+/// Minimum accepted web admin password length
+pub const WEB_ADMIN_MIN_PASSWORD_LEN: usize = 8;
+
+/// Salt length in bytes of legacy SHA-256 hashes
+#[cfg(test)]
+const SALT_LEN: usize = 16;
+
+/// Header required on admin requests that change something
+pub const ADMIN_REQUEST_HEADER: &str = "x-requested-with";
+
+/// Settings key holding the access token for playlist, guide and stream URLs
+pub const ACCESS_TOKEN_KEY: &str = "access_token";
+
+/// Header accepted as an alternative to the `token` query parameter
+pub const ACCESS_TOKEN_HEADER: &str = "x-streamforge-token";
+
+/// Access token length in bytes (hex-encoded in URLs)
+const ACCESS_TOKEN_LEN: usize = 16;
+
+/// Basic auth realm shown by browsers
+const AUTH_REALM: &str = "Basic realm=\"StreamForge Admin\", charset=\"UTF-8\"";
+
+/// Hash a web admin password with Argon2id and a fresh random salt
+///
+/// Returns the PHC string (`$argon2id$v=19$...`).
+pub fn hash_admin_password(password: &str) -> String {
+    let salt = SaltString::generate(&mut OsRng);
+    Argon2::default()
+        .hash_password(password.as_bytes(), &salt)
+        .expect("Argon2 hashing with default parameters")
+        .to_string()
+}
+
+/// Verify a password against a stored hash
+///
+/// Accepts PHC strings and legacy `"{salt_hex}${hash_hex}"` SHA-256 values;
+/// both comparisons are constant-time.
+pub fn verify_admin_password(stored: &str, password: &str) -> bool {
+    if is_legacy_hash(stored) {
+        let Some((salt_hex, expected)) = stored.split_once('$') else {
+            return false;
+        };
+        return constant_time_eq(
+            salted_hash(salt_hex, password).as_bytes(),
+            expected.as_bytes(),
+        );
+    }
+    PasswordHash::new(stored)
+        .map(|hash| {
+            Argon2::default()
+                .verify_password(password.as_bytes(), &hash)
+                .is_ok()
+        })
+        .unwrap_or(false)
+}
+
+/// Whether a stored hash predates Argon2 and should be replaced
+fn is_legacy_hash(stored: &str) -> bool {
+    !stored.starts_with('$')
+}
+
+/// Get the stored web admin password hash (None if web admin is disabled)
+pub fn get_admin_password_hash(conn: &mut SqliteConnection) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(WEB_ADMIN_PASSWORD_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// Set or clear the web admin password
+///
+/// Passing None disables the web admin UI and REST admin API.
+pub fn set_admin_password(
+    conn: &mut SqliteConnection,
+    password: Option<&str>,
+) -> Result<(), diesel::result::Error> {
+    match password {
+        Some(password) => {
+            diesel::replace_into(settings::table)
+                .values(&Setting::new(WEB_ADMIN_PASSWORD_KEY, hash_admin_password(password)))
+                .execute(conn)?;
+        }
+        None => {
+            diesel::delete(settings::table.filter(settings::key.eq(WEB_ADMIN_PASSWORD_KEY)))
+                .execute(conn)?;
+        }
+    }
+    Ok(())
+}
+
+/// Check whether the web admin UI is enabled (a password is set)
+pub fn is_web_admin_enabled(conn: &mut SqliteConnection) -> bool {
+    get_admin_password_hash(conn).is_some()
+}
+
+/// Get the access token (None when endpoints are open)
+pub fn get_access_token(conn: &mut SqliteConnection) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(ACCESS_TOKEN_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .filter(|value| !value.is_empty())
+}
+
+/// Generate and store a new access token, replacing any previous one
+pub fn rotate_access_token(conn: &mut SqliteConnection) -> Result<String, diesel::result::Error> {
+    let mut bytes = [0u8; ACCESS_TOKEN_LEN];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    let token = to_hex(&bytes);
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(ACCESS_TOKEN_KEY, token.clone()))
+        .execute(conn)?;
+    Ok(token)
+}
+
+/// Remove the access token, leaving the endpoints open
+pub fn clear_access_token(conn: &mut SqliteConnection) -> Result<(), diesel::result::Error> {
+    diesel::delete(settings::table.filter(settings::key.eq(ACCESS_TOKEN_KEY)))
+        .execute(conn)
+        .map(|_| ())
+}
+
+/// Append the access token to a URL as a `token` query parameter
+pub fn with_access_token(url: String, token: Option<&str>) -> String {
+    match token {
+        Some(token) => {
+            let separator = if url.contains('?') { '&' } else { '?' };
+            format!("{}{}token={}", url, separator, token)
+        }
+        None => url,
+    }
+}
+
+/// Token supplied with a request, from the header or the `token` query parameter
+fn request_token(request: &Request) -> Option<String> {
+    if let Some(value) = request
+        .headers()
+        .get(ACCESS_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+    {
+        return Some(value.trim().to_string());
+    }
+    url::form_urlencoded::parse(request.uri().query()?.as_bytes())
+        .find(|(key, _)| key == "token")
+        .map(|(_, value)| value.into_owned())
+}
+
+/// Middleware requiring the access token on playlist, guide and stream routes
+///
+/// Passes every request through while no token is configured; otherwise
+/// answers 401 when the token is missing or wrong.
+pub async fn require_access_token(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    let expected = match state.get_connection() {
+        Ok(mut conn) => get_access_token(&mut conn),
+        Err(e) => {
+            eprintln!("Access token check error - database connection failed: {}", e);
+            return plain_response(StatusCode::INTERNAL_SERVER_ERROR, "Service temporarily unavailable");
+        }
+    };
+
+    if let Some(expected) = expected {
+        let authorized = request_token(&request)
+            .map(|token| constant_time_eq(token.as_bytes(), expected.as_bytes()))
+            .unwrap_or(false);
+        if !authorized {
+            return plain_response(StatusCode::UNAUTHORIZED, "Access token required");
+        }
+    }
+
+    next.run(request).await
+}
+
+/// Parse a Basic auth header value into (username, password)
+pub fn parse_basic_auth(value: &str) -> Option<(String, String)> {
+    let encoded = value.strip_prefix("Basic ")?.trim();
+    let decoded = STANDARD.decode(encoded).ok()?;
+    let decoded = String::from_utf8(decoded).ok()?;
+    let (user, password) = decoded.split_once(':')?;
+    Some((user.to_string(), password.to_string()))
+}
+
+/// Middleware protecting the web admin UI and REST admin API
+///
+/// - 403 when no web admin password has been set
+/// - 401 with a Basic challenge when credentials are missing or wrong
+pub async fn require_admin_auth(
+    State(state): State<AppState>,
+    request: Request,
+    next: Next,
+) -> Response<Body> {
+    let stored = match state.get_connection() {
+        Ok(mut conn) => get_admin_password_hash(&mut conn),
+        Err(e) => {
+            eprintln!("Web admin auth error - database connection failed: {}", e);
+            return plain_response(StatusCode::INTERNAL_SERVER_ERROR, "Service temporarily unavailable");
+        }
+    };
+
+    let Some(stored) = stored else {
+        return plain_response(
+            StatusCode::FORBIDDEN,
+            "Web admin is disabled. Set a web admin password in the StreamForge app to enable it.",
+        );
+    };
+
+    let credentials = request
+        .headers()
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_basic_auth)
+        .filter(|(user, _)| user == WEB_ADMIN_USERNAME);
+
+    // Argon2 is deliberately slow, so verify off the async workers
+    let authorized = match credentials {
+        Some((_, password)) => {
+            let verify_state = state.clone();
+            tokio::task::spawn_blocking(move || {
+                let valid = verify_admin_password(&stored, &password);
+                if valid && is_legacy_hash(&stored) {
+                    if let Ok(mut conn) = verify_state.get_connection() {
+                        if let Err(e) = set_admin_password(&mut conn, Some(&password)) {
+                            eprintln!("Web admin auth error - rehashing password failed: {}", e);
+                        }
+                    }
+                }
+                valid
+            })
+            .await
+            .unwrap_or(false)
+        }
+        None => false,
+    };
+
+    if !authorized {
+        let mut response = plain_response(StatusCode::UNAUTHORIZED, "Authentication required");
+        response
+            .headers_mut()
+            .insert(header::WWW_AUTHENTICATE, HeaderValue::from_static(AUTH_REALM));
+        return response;
+    }
+
+    next.run(request).await
+}
+
+/// Middleware rejecting cross-site admin requests that change something
+///
+/// Safe methods pass through. Other requests answer 403 without the
+/// `X-Requested-With` header or with an `Origin` other than the `Host`.
+pub async fn require_same_origin(request: Request, next: Next) -> Response<Body> {
+    if !is_same_origin_request(&request) {
+        return plain_response(StatusCode::FORBIDDEN, "Cross-site request rejected");
+    }
+    next.run(request).await
+}
+
+fn is_same_origin_request(request: &Request) -> bool {
+    if matches!(
+        *request.method(),
+        Method::GET | Method::HEAD | Method::OPTIONS
+    ) {
+        return true;
+    }
+    let headers = request.headers();
+    if !headers.contains_key(ADMIN_REQUEST_HEADER) {
+        return false;
+    }
+    let Some(origin) = headers.get(header::ORIGIN) else {
+        return true;
+    };
+    let host = headers
+        .get(header::HOST)
+        .and_then(|value| value.to_str().ok());
+    let origin_host = origin
+        .to_str()
+        .ok()
+        .and_then(|origin| origin.split_once("://"))
+        .map(|(_, authority)| authority);
+    match (origin_host, host) {
+        (Some(origin_host), Some(host)) => origin_host.eq_ignore_ascii_case(host),
+        _ => false,
+    }
+}
+
+fn plain_response(status: StatusCode, message: &'static str) -> Response<Body> {
+    Response::builder()
+        .status(status)
+        .header(header::CONTENT_TYPE, "text/plain; charset=utf-8")
+        .body(Body::from(message))
+        .unwrap()
+}
+
+fn salted_hash(salt_hex: &str, password: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(salt_hex.as_bytes());
+    hasher.update(password.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    a.iter().zip(b.iter()).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hash_and_verify_admin_password() {
+        let stored = hash_admin_password("correct horse");
+        assert!(stored.starts_with("$argon2id$"));
+        assert!(!is_legacy_hash(&stored));
+        assert!(verify_admin_password(&stored, "correct horse"));
+        assert!(!verify_admin_password(&stored, "wrong horse"));
+    }
+
+    #[test]
+    fn test_verify_legacy_sha256_hash() {
+        let salt_hex = to_hex(&[7u8; SALT_LEN]);
+        let stored = format!("{}${}", salt_hex, salted_hash(&salt_hex, "secret123"));
+        assert!(is_legacy_hash(&stored));
+        assert!(verify_admin_password(&stored, "secret123"));
+        assert!(!verify_admin_password(&stored, "secret124"));
+    }
+
+    #[test]
+    fn test_hash_uses_random_salt() {
+        assert_ne!(hash_admin_password("secret123"), hash_admin_password("secret123"));
+    }
+
+    #[test]
+    fn test_verify_rejects_malformed_hash() {
+        assert!(!verify_admin_password("no-separator", "anything"));
+        assert!(!verify_admin_password("", ""));
+        assert!(!verify_admin_password("$argon2id$v=19$broken", "anything"));
+    }
+
+    #[test]
+    fn test_same_origin_check() {
+        let request = |method: Method, headers: &[(&str, &str)]| {
+            let mut builder = Request::builder()
+                .method(method)
+                .uri("/api/admin/epg/refresh");
+            for (name, value) in headers {
+                builder = builder.header(*name, *value);
+            }
+            builder.body(Body::empty()).unwrap()
+        };
+        let host = ("host", "192.168.1.5:5004");
+        let marker = (ADMIN_REQUEST_HEADER, "fetch");
+
+        assert!(is_same_origin_request(&request(Method::GET, &[host])));
+        assert!(!is_same_origin_request(&request(Method::POST, &[host])));
+        assert!(is_same_origin_request(&request(
+            Method::POST,
+            &[host, marker]
+        )));
+        assert!(is_same_origin_request(&request(
+            Method::POST,
+            &[host, marker, ("origin", "http://192.168.1.5:5004")]
+        )));
+        assert!(!is_same_origin_request(&request(
+            Method::POST,
+            &[host, marker, ("origin", "http://evil.example")]
+        )));
+        assert!(!is_same_origin_request(&request(
+            Method::POST,
+            &[host, marker, ("origin", "null")]
+        )));
+    }
+
+    #[test]
+    fn test_parse_basic_auth() {
+        let header = format!("Basic {}", STANDARD.encode("admin:p@ss:word"));
+        assert_eq!(
+            parse_basic_auth(&header),
+            Some(("admin".to_string(), "p@ss:word".to_string()))
+        );
+        assert_eq!(parse_basic_auth("Bearer abc"), None);
+        assert_eq!(parse_basic_auth("Basic !!!"), None);
+    }
+
+    #[test]
+    fn test_access_token_in_urls_and_requests() {
+        assert_eq!(
+            with_access_token("http://h:5004/epg.xml".to_string(), Some("abc")),
+            "http://h:5004/epg.xml?token=abc"
+        );
+        assert_eq!(
+            with_access_token("http://h:5004/playlist.m3u?group=News".to_string(), Some("abc")),
+            "http://h:5004/playlist.m3u?group=News&token=abc"
+        );
+        assert_eq!(with_access_token("http://h/x".to_string(), None), "http://h/x");
+
+        let request = Request::builder()
+            .uri("/stream/1?format=ts&token=abc")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("abc"));
+
+        let request = Request::builder()
+            .uri("/stream/1")
+            .header(ACCESS_TOKEN_HEADER, "def")
+            .body(Body::empty())
+            .unwrap();
+        assert_eq!(request_token(&request).as_deref(), Some("def"));
+
+        let mut conn = crate::db::test_connection();
+        assert_eq!(get_access_token(&mut conn), None);
+        let token = rotate_access_token(&mut conn).unwrap();
+        assert_eq!(token.len(), ACCESS_TOKEN_LEN * 2);
+        assert_eq!(get_access_token(&mut conn), Some(token));
+        clear_access_token(&mut conn).unwrap();
+        assert_eq!(get_access_token(&mut conn), None);
+    }
+}
@@ -3,10 +3,14 @@
 //! Outputs data at the same rate it's received from FFmpeg,
 //! preventing buffer drain faster than the source provides.
 //!
+//! Every upstream goes through FFmpeg, which remuxes it to MPEG-TS: raw
+//! MPEG-TS gets normalized timestamps, and HLS or other containers Plex can't
+//! consume are converted without re-encoding.
+//!
 //! # Requirements
 //!
-//! FFmpeg must be installed and available in the system PATH.
-//! See README.md for installation instructions.
+//! FFmpeg must be installed and available in the system PATH, or its path set
+//! in Settings (`ffmpeg_path`). See README.md for installation instructions.
 
 use bytes::Bytes;
 use futures_util::Stream;
@@ -14,18 +18,30 @@ use std::collections::VecDeque;
 use std::io;
 use std::pin::Pin;
 use std::process::Stdio;
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use std::task::{Context, Poll, Waker};
 use std::time::Instant;
 use tokio::io::{AsyncBufReadExt, AsyncReadExt, BufReader};
 use tokio::process::{Child, Command};
 use tokio::sync::Mutex;
 
+use diesel::prelude::*;
 use std::time::Duration;
 use tokio::sync::watch;
 
 use super::health::{HealthConfig, StreamHealthMonitor};
 use super::stream::StreamManager;
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key for the FFmpeg binary path (unset = `ffmpeg` from PATH)
+pub const FFMPEG_PATH_KEY: &str = "ffmpeg_path";
+
+const DEFAULT_FFMPEG_BINARY: &str = "ffmpeg";
+
+/// Configured FFmpeg path, mirrored from settings so stream startup and
+/// mid-stream failover don't need a database connection
+static FFMPEG_PATH: RwLock<Option<String>> = RwLock::new(None);
 
 /// Stream health status for monitoring (Story 4.7)
 #[derive(Debug, Clone, PartialEq)]
@@ -66,23 +82,87 @@ impl Default for BufferConfig {
     }
 }
 
-/// Check if FFmpeg is available in PATH
+/// FFmpeg binary used for stream remuxing
+pub fn ffmpeg_binary() -> String {
+    FFMPEG_PATH
+        .read()
+        .ok()
+        .and_then(|path| path.clone())
+        .unwrap_or_else(|| DEFAULT_FFMPEG_BINARY.to_string())
+}
+
+/// Configured FFmpeg path (None when FFmpeg is taken from PATH)
+pub fn configured_ffmpeg_path() -> Option<String> {
+    FFMPEG_PATH.read().ok().and_then(|path| path.clone())
+}
+
+/// Read the persisted FFmpeg path and apply it
+///
+/// Called at startup and after switching workspaces.
+pub fn load_ffmpeg_path(conn: &mut SqliteConnection) {
+    let path = settings::table
+        .filter(settings::key.eq(FFMPEG_PATH_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .filter(|value| !value.trim().is_empty());
+    if let Ok(mut current) = FFMPEG_PATH.write() {
+        *current = path;
+    }
+}
+
+/// Persist the FFmpeg path (None resets to PATH lookup) and apply it
+///
+/// Streams already running keep their FFmpeg process.
+pub fn set_ffmpeg_path(
+    conn: &mut SqliteConnection,
+    path: Option<&str>,
+) -> Result<(), diesel::result::Error> {
+    match path {
+        Some(path) => {
+            diesel::replace_into(settings::table)
+                .values(&Setting::new(FFMPEG_PATH_KEY, path))
+                .execute(conn)?;
+        }
+        None => {
+            diesel::delete(settings::table.filter(settings::key.eq(FFMPEG_PATH_KEY)))
+                .execute(conn)?;
+        }
+    }
+    if let Ok(mut current) = FFMPEG_PATH.write() {
+        *current = path.map(str::to_string);
+    }
+    Ok(())
+}
+
+/// Check if the configured FFmpeg (or FFmpeg in PATH) is available
 pub fn check_ffmpeg_available() -> Result<(), io::Error> {
-    match std::process::Command::new("ffmpeg")
+    check_ffmpeg_binary(&ffmpeg_binary())
+}
+
+/// Check that `binary` runs and reports its version
+pub fn check_ffmpeg_binary(binary: &str) -> Result<(), io::Error> {
+    match std::process::Command::new(binary)
         .arg("-version")
         .stdout(Stdio::null())
         .stderr(Stdio::null())
         .status()
     {
         Ok(status) if status.success() => Ok(()),
-        Ok(_) => Err(io::Error::new(
-            io::ErrorKind::Other,
-            "FFmpeg returned non-zero exit code",
-        )),
+        Ok(_) => Err(io::Error::other("FFmpeg returned non-zero exit code")),
+        Err(e) if e.kind() == io::ErrorKind::NotFound && binary == DEFAULT_FFMPEG_BINARY => {
+            Err(io::Error::new(
+                io::ErrorKind::NotFound,
+                "FFmpeg not found. Please install FFmpeg and ensure it's in your PATH. \
+                 See README.md for installation instructions.",
+            ))
+        }
         Err(e) if e.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
             io::ErrorKind::NotFound,
-            "FFmpeg not found. Please install FFmpeg and ensure it's in your PATH. \
-             See README.md for installation instructions.",
+            format!(
+                "FFmpeg not found at '{}'. Check the FFmpeg path in Settings.",
+                binary
+            ),
         )),
         Err(e) => Err(e),
     }
@@ -105,6 +185,8 @@ pub(crate) struct BufferState {
     pub(crate) stall_detected: bool,
     /// Timestamp when stall was first detected
     pub(crate) stall_start_time: Option<Instant>,
+    /// Upstream rejected FFmpeg with HTTP 401/403 (e.g. an expired stream token)
+    pub(crate) auth_rejected: bool,
 }
 
 pub struct BufferedStream {
@@ -118,6 +200,8 @@ pub struct BufferedStream {
     health_monitor_handle: Option<tokio::task::JoinHandle<()>>,
     /// Receiver for failover signals
     failover_rx: watch::Receiver<bool>,
+    /// End the stream session when dropped (false once handed off)
+    ends_session: bool,
 }
 
 impl BufferedStream {
@@ -130,14 +214,19 @@ impl BufferedStream {
         // Verify FFmpeg is available before attempting to spawn
         check_ffmpeg_available()?;
 
-        let mut child = Command::new("ffmpeg")
+        let mut input = crate::dns::ffmpeg_input(upstream_url);
+        crate::xtream::request_headers::for_url(upstream_url).add_ffmpeg_args(&mut input.args);
+        let mut child = Command::new(ffmpeg_binary())
             .args([
                 "-hide_banner",
                 "-loglevel", "warning",
                 "-reconnect", "1",
                 "-reconnect_streamed", "1",
                 "-reconnect_delay_max", "2",
-                "-i", upstream_url,
+            ])
+            .args(&input.args)
+            .args([
+                "-i", input.url.as_str(),
                 "-c", "copy",
                 "-f", "mpegts",
                 "-fflags", "+genpts",
@@ -167,6 +256,7 @@ impl BufferedStream {
             last_data_time: None,
             stall_detected: false,
             stall_start_time: None,
+            auth_rejected: false,
         }));
 
         let reader_state = state.clone();
@@ -180,8 +270,9 @@ impl BufferedStream {
         });
 
         // Spawn stderr handler to log FFmpeg warnings/errors with session context
+        let stderr_state = state.clone();
         let stderr_handle = tokio::spawn(async move {
-            Self::stderr_task(stderr, stderr_session_id).await;
+            Self::stderr_task(stderr, stderr_session_id, stderr_state).await;
         });
 
         // Set up health monitoring with default config (Story 4.7)
@@ -197,6 +288,7 @@ impl BufferedStream {
             stream_manager,
             health_monitor_handle,
             failover_rx,
+            ends_session: true,
         })
     }
 
@@ -211,14 +303,19 @@ impl BufferedStream {
         // Verify FFmpeg is available before attempting to spawn
         check_ffmpeg_available()?;
 
-        let mut child = Command::new("ffmpeg")
+        let mut input = crate::dns::ffmpeg_input(upstream_url);
+        crate::xtream::request_headers::for_url(upstream_url).add_ffmpeg_args(&mut input.args);
+        let mut child = Command::new(ffmpeg_binary())
             .args([
                 "-hide_banner",
                 "-loglevel", "warning",
                 "-reconnect", "1",
                 "-reconnect_streamed", "1",
                 "-reconnect_delay_max", "2",
-                "-i", upstream_url,
+            ])
+            .args(&input.args)
+            .args([
+                "-i", input.url.as_str(),
                 "-c", "copy",
                 "-f", "mpegts",
                 "-fflags", "+genpts",
@@ -247,6 +344,7 @@ impl BufferedStream {
             last_data_time: None,
             stall_detected: false,
             stall_start_time: None,
+            auth_rejected: false,
         }));
 
         let reader_state = state.clone();
@@ -260,8 +358,9 @@ impl BufferedStream {
         });
 
         // Spawn stderr handler
+        let stderr_state = state.clone();
         let stderr_handle = tokio::spawn(async move {
-            Self::stderr_task(stderr, stderr_session_id).await;
+            Self::stderr_task(stderr, stderr_session_id, stderr_state).await;
         });
 
         // Set up health monitoring with custom config (Story 4.7)
@@ -281,11 +380,19 @@ impl BufferedStream {
             stream_manager,
             health_monitor_handle,
             failover_rx,
+            ends_session: true,
         })
     }
 
     /// Handle FFmpeg stderr output - log warnings/errors with session context
-    async fn stderr_task(stderr: Option<tokio::process::ChildStderr>, session_id: String) {
+    ///
+    /// Also records HTTP 401/403 responses so the caller can refresh an
+    /// expired stream token instead of failing over.
+    async fn stderr_task(
+        stderr: Option<tokio::process::ChildStderr>,
+        session_id: String,
+        state: Arc<Mutex<BufferState>>,
+    ) {
         let stderr = match stderr {
             Some(s) => s,
             None => return,
@@ -299,6 +406,9 @@ impl BufferedStream {
             if line.contains("Last message repeated") {
                 continue;
             }
+            if is_auth_rejection(&line) {
+                state.lock().await.auth_rejected = true;
+            }
             eprintln!("FFmpeg [{}]: {}", session_id, line);
         }
     }
@@ -438,6 +548,29 @@ impl BufferedStream {
     pub fn session_id(&self) -> &str {
         &self.session_id
     }
+
+    /// Close this upstream connection without ending the stream session
+    ///
+    /// Used when another connection carries on the session (rolling reconnect).
+    pub fn close_keeping_session(mut self) {
+        self.ends_session = false;
+    }
+
+    /// Whether the upstream rejected FFmpeg with HTTP 401/403
+    ///
+    /// Providers with time-limited stream tokens start rejecting the stream
+    /// URL when the token expires, typically hours into playback.
+    pub async fn auth_rejected(&self) -> bool {
+        self.state.lock().await.auth_rejected
+    }
+}
+
+/// Check whether an FFmpeg log line reports an HTTP 401/403 response
+///
+/// FFmpeg reports these as e.g. "HTTP error 403 Forbidden" or
+/// "Server returned 401 Unauthorized (authorization failed)".
+fn is_auth_rejection(line: &str) -> bool {
+    line.contains("401 Unauthorized") || line.contains("403 Forbidden")
 }
 
 impl Stream for BufferedStream {
@@ -446,6 +579,11 @@ impl Stream for BufferedStream {
     fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
         let this = self.get_mut();
 
+        // Session stopped from the dashboard - end the stream
+        if this.stream_manager.is_stop_requested(&this.session_id) {
+            return Poll::Ready(None);
+        }
+
         let mut guard = match this.state.try_lock() {
             Ok(g) => g,
             Err(_) => {
@@ -473,6 +611,7 @@ impl Stream for BufferedStream {
             let len = chunk.len();
             guard.total_bytes -= len;
             guard.bytes_sent += len;
+            this.stream_manager.set_buffered_bytes(&this.session_id, guard.total_bytes);
 
             // Log periodically (every ~5MB sent)
             if guard.bytes_sent % (5 * 1024 * 1024) < len {
@@ -510,7 +649,9 @@ impl Drop for BufferedStream {
             handle.abort();
         }
         // End the stream session to free up tuner slot
-        self.stream_manager.end_session(&self.session_id);
+        if self.ends_session {
+            self.stream_manager.end_session(&self.session_id);
+        }
     }
 }
 
@@ -581,10 +722,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_check_ffmpeg_binary_reports_configured_path() {
+        let err = check_ffmpeg_binary("/nonexistent/bin/ffmpeg").unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::NotFound);
+        assert!(err.to_string().contains("/nonexistent/bin/ffmpeg"));
+    }
+
     // =========================================================================
     // Constants Tests
     // =========================================================================
 
+    #[test]
+    fn test_is_auth_rejection() {
+        assert!(is_auth_rejection("[http @ 0x55] HTTP error 403 Forbidden"));
+        assert!(is_auth_rejection(
+            "http://provider/live/u/p/1.ts: Server returned 401 Unauthorized (authorization failed)"
+        ));
+        assert!(!is_auth_rejection("[http @ 0x55] HTTP error 404 Not Found"));
+        assert!(!is_auth_rejection("Non-monotonous DTS in output stream 0:1"));
+    }
+
     #[test]
     fn test_mpegts_packet_size_is_standard() {
         // MPEG-TS packet size is defined by the spec as 188 bytes
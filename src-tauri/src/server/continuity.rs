@@ -0,0 +1,303 @@
+//! MPEG-TS continuity across upstream splices
+//!
+//! Every upstream connection is a separate FFmpeg process whose output starts
+//! over: continuity counters from zero and timestamps near zero. When a
+//! session switches connections mid-stream (failover, token refresh, rolling
+//! reconnect), Plex sees the counters jump and the clock run backwards, and
+//! seeking within its live buffer breaks after the splice.
+//!
+//! `TsContinuity` rewrites the packets that follow a splice so the output
+//! reads as one stream: continuity counters carry on per PID, PCR/PTS/DTS
+//! are shifted to follow the last timestamps sent, and the discontinuity
+//! flags FFmpeg sets on its first packets are cleared. Until the first splice
+//! packets pass through unchanged.
+
+use bytes::Bytes;
+use std::collections::HashMap;
+
+const TS_PACKET_SIZE: usize = 188;
+const SYNC_BYTE: u8 = 0x47;
+const NULL_PID: u16 = 0x1FFF;
+
+/// 90 kHz timestamps wrap at 2^33
+const TIMESTAMP_MODULUS: u64 = 1 << 33;
+
+/// Gap between the last timestamp before a splice and the first after it
+/// (100 ms, enough to cover B-frame reordering)
+const SPLICE_GAP: u64 = 9_000;
+
+/// Rewrites an MPEG-TS byte stream to stay continuous across splices
+#[derive(Debug, Default)]
+pub struct TsContinuity {
+    enabled: bool,
+    /// Bytes of an incomplete packet carried over to the next chunk
+    partial: Vec<u8>,
+    /// Last continuity counter sent, by PID
+    last_cc: HashMap<u16, u8>,
+    /// Counter shift of the current segment, by PID
+    cc_shift: HashMap<u16, u8>,
+    /// Timestamp shift of the current segment (None until its first timestamp)
+    ts_shift: Option<u64>,
+    /// Last PCR base and decode timestamp sent
+    last_pcr: Option<u64>,
+    last_dts: Option<u64>,
+    /// Whether a splice has happened
+    spliced: bool,
+}
+
+impl TsContinuity {
+    /// A rewriter; when disabled, data passes through untouched
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            ..Default::default()
+        }
+    }
+
+    /// The next data comes from a new upstream connection
+    ///
+    /// An incomplete packet left over from the old connection is dropped.
+    pub fn splice(&mut self) {
+        self.partial.clear();
+        self.cc_shift.clear();
+        self.ts_shift = None;
+        self.spliced = true;
+    }
+
+    /// Rewrite a chunk, returning the complete packets it finishes
+    ///
+    /// Bytes outside packet alignment are passed through as they are.
+    pub fn process(&mut self, data: Bytes) -> Bytes {
+        if !self.enabled {
+            return data;
+        }
+        self.partial.extend_from_slice(&data);
+
+        let mut out = Vec::with_capacity(self.partial.len());
+        let mut pos = 0;
+        while pos < self.partial.len() {
+            if self.partial[pos] != SYNC_BYTE {
+                let next = self.partial[pos..]
+                    .iter()
+                    .position(|b| *b == SYNC_BYTE)
+                    .map_or(self.partial.len(), |offset| pos + offset);
+                out.extend_from_slice(&self.partial[pos..next]);
+                pos = next;
+                continue;
+            }
+            if self.partial.len() - pos < TS_PACKET_SIZE {
+                break;
+            }
+            let mut packet = [0u8; TS_PACKET_SIZE];
+            packet.copy_from_slice(&self.partial[pos..pos + TS_PACKET_SIZE]);
+            self.rewrite(&mut packet);
+            out.extend_from_slice(&packet);
+            pos += TS_PACKET_SIZE;
+        }
+        self.partial.drain(..pos);
+        Bytes::from(out)
+    }
+
+    fn rewrite(&mut self, packet: &mut [u8; TS_PACKET_SIZE]) {
+        let pid = (u16::from(packet[1] & 0x1F) << 8) | u16::from(packet[2]);
+        if pid == NULL_PID {
+            return;
+        }
+        let payload_unit_start = packet[1] & 0x40 != 0;
+        let has_adaptation = packet[3] & 0x20 != 0;
+        let has_payload = packet[3] & 0x10 != 0;
+
+        // Continuity counter: only packets with payload advance it
+        let cc = packet[3] & 0x0F;
+        let first_in_segment = !self.cc_shift.contains_key(&pid);
+        let shift = *self
+            .cc_shift
+            .entry(pid)
+            .or_insert_with(|| match self.last_cc.get(&pid) {
+                Some(&last) => {
+                    let expected = if has_payload { last + 1 } else { last };
+                    expected.wrapping_sub(cc) & 0x0F
+                }
+                None => 0,
+            });
+        let cc = (cc + shift) & 0x0F;
+        packet[3] = (packet[3] & 0xF0) | cc;
+        self.last_cc.insert(pid, cc);
+
+        let mut payload_start = 4;
+        if has_adaptation {
+            let length = usize::from(packet[4]);
+            payload_start = 5 + length;
+            if length > 0 && payload_start <= TS_PACKET_SIZE {
+                if self.spliced && first_in_segment {
+                    packet[5] &= !0x80;
+                }
+                if packet[5] & 0x10 != 0 && length >= 7 {
+                    let pcr = read_pcr_base(&packet[6..11]);
+                    let pcr = self.shift_timestamp(pcr, self.last_pcr);
+                    write_pcr_base(&mut packet[6..11], pcr);
+                    self.last_pcr = Some(pcr);
+                }
+            }
+        }
+
+        // PES header timestamps at the start of audio, video and private streams
+        if !(has_payload && payload_unit_start) || payload_start + 19 > TS_PACKET_SIZE {
+            return;
+        }
+        let pes = &mut packet[payload_start..];
+        let stream_id = pes[3];
+        if pes[..3] != [0, 0, 1] || !(stream_id == 0xBD || (0xC0..=0xEF).contains(&stream_id)) {
+            return;
+        }
+        let flags = pes[7] >> 6;
+        let mut decode_time = None;
+        if flags & 0x02 != 0 {
+            let pts = self.shift_timestamp(read_pes_timestamp(&pes[9..14]), self.last_dts);
+            write_pes_timestamp(&mut pes[9..14], pts);
+            decode_time = Some(pts);
+        }
+        if flags == 0x03 {
+            let dts = self.shift_timestamp(read_pes_timestamp(&pes[14..19]), self.last_dts);
+            write_pes_timestamp(&mut pes[14..19], dts);
+            decode_time = Some(dts);
+        }
+        if decode_time.is_some() {
+            self.last_dts = decode_time;
+        }
+    }
+
+    /// Apply the segment's timestamp shift, fixing it on the first timestamp
+    /// so that one lands just after `last` of the same kind
+    fn shift_timestamp(&mut self, timestamp: u64, last: Option<u64>) -> u64 {
+        let shift = *self.ts_shift.get_or_insert_with(|| match last {
+            Some(last) => (last + SPLICE_GAP + TIMESTAMP_MODULUS - timestamp) % TIMESTAMP_MODULUS,
+            None => 0,
+        });
+        (timestamp + shift) % TIMESTAMP_MODULUS
+    }
+}
+
+/// 33-bit base of a PCR (the first 5 of its 6 bytes)
+fn read_pcr_base(bytes: &[u8]) -> u64 {
+    (u64::from(bytes[0]) << 25)
+        | (u64::from(bytes[1]) << 17)
+        | (u64::from(bytes[2]) << 9)
+        | (u64::from(bytes[3]) << 1)
+        | (u64::from(bytes[4]) >> 7)
+}
+
+fn write_pcr_base(bytes: &mut [u8], base: u64) {
+    bytes[0] = (base >> 25) as u8;
+    bytes[1] = (base >> 17) as u8;
+    bytes[2] = (base >> 9) as u8;
+    bytes[3] = (base >> 1) as u8;
+    bytes[4] = (((base & 1) as u8) << 7) | (bytes[4] & 0x7F);
+}
+
+/// 33-bit PTS or DTS from its 5-byte PES header field
+fn read_pes_timestamp(bytes: &[u8]) -> u64 {
+    (u64::from((bytes[0] >> 1) & 0x07) << 30)
+        | (u64::from(bytes[1]) << 22)
+        | (u64::from(bytes[2] >> 1) << 15)
+        | (u64::from(bytes[3]) << 7)
+        | u64::from(bytes[4] >> 1)
+}
+
+/// Write a PTS or DTS, keeping the field's 4-bit prefix
+fn write_pes_timestamp(bytes: &mut [u8], timestamp: u64) {
+    bytes[0] = (bytes[0] & 0xF0) | ((((timestamp >> 30) & 0x07) as u8) << 1) | 1;
+    bytes[1] = (timestamp >> 22) as u8;
+    bytes[2] = ((((timestamp >> 15) & 0x7F) as u8) << 1) | 1;
+    bytes[3] = (timestamp >> 7) as u8;
+    bytes[4] = (((timestamp & 0x7F) as u8) << 1) | 1;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Video packet on PID 0x100 starting a PES with a PCR and PTS/DTS
+    fn video_packet(cc: u8, pcr: u64, pts: u64, dts: u64, discontinuity: bool) -> Vec<u8> {
+        let mut packet = vec![0xFF; TS_PACKET_SIZE];
+        packet[..4].copy_from_slice(&[SYNC_BYTE, 0x41, 0x00, 0x30 | cc]);
+        // Adaptation field: length 7, flags, PCR
+        packet[4] = 7;
+        packet[5] = 0x10 | if discontinuity { 0x80 } else { 0 };
+        write_pcr_base(&mut packet[6..11], pcr);
+        packet[11] = 0;
+        // PES header with PTS and DTS
+        let pes = &mut packet[12..];
+        pes[..9].copy_from_slice(&[0, 0, 1, 0xE0, 0, 0, 0x80, 0xC0, 10]);
+        pes[9] = 0x30;
+        write_pes_timestamp(&mut pes[9..14], pts);
+        pes[14] = 0x10;
+        write_pes_timestamp(&mut pes[14..19], dts);
+        packet
+    }
+
+    /// Continuation packet on PID 0x100 (payload only)
+    fn payload_packet(cc: u8) -> Vec<u8> {
+        let mut packet = vec![0xAA; TS_PACKET_SIZE];
+        packet[..4].copy_from_slice(&[SYNC_BYTE, 0x01, 0x00, 0x10 | cc]);
+        packet
+    }
+
+    fn fields(packet: &[u8]) -> (u8, u64, u64, u64, bool) {
+        (
+            packet[3] & 0x0F,
+            read_pcr_base(&packet[6..11]),
+            read_pes_timestamp(&packet[21..26]),
+            read_pes_timestamp(&packet[26..31]),
+            packet[5] & 0x80 != 0,
+        )
+    }
+
+    #[test]
+    fn test_timestamp_fields_round_trip() {
+        let mut bytes = [0x31, 0, 0, 0, 0];
+        write_pes_timestamp(&mut bytes, TIMESTAMP_MODULUS - 5);
+        assert_eq!(read_pes_timestamp(&bytes), TIMESTAMP_MODULUS - 5);
+        assert_eq!(bytes[0] & 0xF1, 0x31);
+
+        let mut pcr = [0, 0, 0, 0, 0x7E];
+        write_pcr_base(&mut pcr, 0x1_2345_6789);
+        assert_eq!(read_pcr_base(&pcr), 0x1_2345_6789);
+        assert_eq!(pcr[4] & 0x7F, 0x7E);
+    }
+
+    #[test]
+    fn test_splice_continues_counters_and_timestamps() {
+        let mut continuity = TsContinuity::new(true);
+
+        let mut first = video_packet(0, 900_000, 990_000, 963_000, true);
+        first.extend(payload_packet(1));
+        let out = continuity.process(Bytes::from(first.clone()));
+        // Untouched before any splice
+        assert_eq!(out.as_ref(), first.as_slice());
+
+        // New connection: counters and clock start over, with a stray half packet before
+        continuity.process(Bytes::from(payload_packet(2)[..100].to_vec()));
+        continuity.splice();
+        let second = video_packet(0, 126_000, 216_000, 189_000, true);
+        let out = continuity.process(Bytes::from(second[..50].to_vec()));
+        assert!(out.is_empty());
+        let out = continuity.process(Bytes::from(second[50..].to_vec()));
+
+        let (cc, pcr, pts, dts, discontinuity) = fields(&out);
+        assert_eq!(cc, 2);
+        assert_eq!(pcr, 900_000 + SPLICE_GAP);
+        // PTS/DTS keep their distance from the PCR
+        assert_eq!(pts, pcr + 90_000);
+        assert_eq!(dts, pcr + 63_000);
+        assert!(!discontinuity);
+    }
+
+    #[test]
+    fn test_disabled_passes_data_through() {
+        let mut continuity = TsContinuity::new(false);
+        continuity.splice();
+        let partial = Bytes::from(payload_packet(5)[..10].to_vec());
+        assert_eq!(continuity.process(partial.clone()), partial);
+    }
+}
@@ -0,0 +1,374 @@
+//! HDHomeRun device profiles
+//!
+//! A device profile is an extra virtual tuner exposing a subset of the
+//! lineup, so Plex can record different channels into different libraries
+//! (e.g. a "Sports" tuner and a "News" tuner). Each profile is served under
+//! `/device/{slug}/` with its own discover.json, device.xml, lineup.json and
+//! playlist.m3u, and has its own DeviceID (see
+//! `hdhr::generate_profile_device_id`), generated once when the profile is
+//! created. A profile can advertise HLS stream URLs instead of MPEG-TS for
+//! clients that need them.
+//!
+//! Only channels enabled in the main lineup appear in a profile, with the
+//! numbers they have there, so every tuner can use the shared /epg.xml.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use super::hdhr;
+use super::stream::StreamFormat;
+use crate::db::schema::{device_profile_channels, device_profiles};
+
+/// Longest profile slug
+pub const MAX_SLUG_LEN: usize = 32;
+
+/// Most tuners a profile can advertise
+pub const MAX_PROFILE_TUNERS: i32 = 32;
+
+/// A virtual tuner with its channels
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceProfile {
+    pub id: i32,
+    /// Path segment of the profile's endpoints (`/device/{slug}/`)
+    pub slug: String,
+    /// FriendlyName shown in Plex
+    pub name: String,
+    pub device_id: String,
+    /// Advertised tuner count (None: same as the main device)
+    pub tuner_count: Option<i32>,
+    /// Container of the advertised stream URLs ("ts" or "m3u8")
+    pub stream_format: String,
+    /// XMLTV channel IDs in the profile
+    pub channel_ids: Vec<i32>,
+}
+
+/// Profile row: (id, slug, name, device_id, tuner_count, stream_format)
+type ProfileRow = (i32, String, String, String, Option<i32>, String);
+
+impl DeviceProfile {
+    fn from_row(row: ProfileRow, channel_ids: Vec<i32>) -> Self {
+        let (id, slug, name, device_id, tuner_count, stream_format) = row;
+        Self {
+            id,
+            slug,
+            name,
+            device_id,
+            tuner_count,
+            stream_format,
+            channel_ids,
+        }
+    }
+
+    /// Container of the advertised stream URLs
+    pub fn format(&self) -> StreamFormat {
+        StreamFormat::from_param(&self.stream_format).unwrap_or(StreamFormat::Ts)
+    }
+
+    /// Channel IDs as a set, for filtering outputs
+    pub fn channel_set(&self) -> HashSet<i32> {
+        self.channel_ids.iter().copied().collect()
+    }
+}
+
+fn profile_columns() -> (
+    diesel::dsl::AssumeNotNull<device_profiles::id>,
+    device_profiles::slug,
+    device_profiles::name,
+    device_profiles::device_id,
+    device_profiles::tuner_count,
+    device_profiles::stream_format,
+) {
+    (
+        device_profiles::id.assume_not_null(),
+        device_profiles::slug,
+        device_profiles::name,
+        device_profiles::device_id,
+        device_profiles::tuner_count,
+        device_profiles::stream_format,
+    )
+}
+
+/// Slug from a profile name ("Sports & News" -> "sports-news")
+pub fn slugify(name: &str) -> String {
+    let mut slug = String::new();
+    for c in name.trim().chars() {
+        if c.is_ascii_alphanumeric() {
+            slug.push(c.to_ascii_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+    slug.truncate(MAX_SLUG_LEN);
+    slug.trim_end_matches('-').to_string()
+}
+
+/// Check a slug: 1-32 lowercase letters, digits and '-'
+pub fn validate_slug(slug: &str) -> Result<(), String> {
+    if slug.is_empty()
+        || slug.len() > MAX_SLUG_LEN
+        || !slug
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+    {
+        return Err(format!(
+            "Profile slug must be 1 to {} lowercase letters, digits or '-'",
+            MAX_SLUG_LEN
+        ));
+    }
+    Ok(())
+}
+
+fn validate_name(name: &str) -> Result<(), String> {
+    if name.is_empty() {
+        return Err("Profile name is required".to_string());
+    }
+    if name.chars().count() > hdhr::MAX_FRIENDLY_NAME_LEN {
+        return Err(format!(
+            "Profile name must be at most {} characters",
+            hdhr::MAX_FRIENDLY_NAME_LEN
+        ));
+    }
+    Ok(())
+}
+
+fn validate_tuner_count(tuner_count: Option<i32>) -> Result<(), String> {
+    if tuner_count.is_some_and(|count| !(1..=MAX_PROFILE_TUNERS).contains(&count)) {
+        return Err(format!(
+            "Tuner count must be between 1 and {}",
+            MAX_PROFILE_TUNERS
+        ));
+    }
+    Ok(())
+}
+
+/// Canonical `stream_format` value ("ts" when not given)
+fn parse_stream_format(stream_format: Option<&str>) -> Result<StreamFormat, String> {
+    match stream_format.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(value) => StreamFormat::from_param(value)
+            .ok_or_else(|| format!("Unsupported stream format '{}' (use ts or m3u8)", value)),
+        None => Ok(StreamFormat::Ts),
+    }
+}
+
+/// All profiles with their channels, by name
+pub fn list_profiles(conn: &mut SqliteConnection) -> QueryResult<Vec<DeviceProfile>> {
+    let rows: Vec<ProfileRow> = device_profiles::table
+        .select(profile_columns())
+        .order(device_profiles::name.asc())
+        .load(conn)?;
+    let mut channels: HashMap<i32, Vec<i32>> = HashMap::new();
+    for (profile_id, channel_id) in device_profile_channels::table
+        .select((
+            device_profile_channels::profile_id,
+            device_profile_channels::xmltv_channel_id,
+        ))
+        .order(device_profile_channels::xmltv_channel_id.asc())
+        .load::<(i32, i32)>(conn)?
+    {
+        channels.entry(profile_id).or_default().push(channel_id);
+    }
+
+    Ok(rows
+        .into_iter()
+        .map(|row| {
+            let channel_ids = channels.remove(&row.0).unwrap_or_default();
+            DeviceProfile::from_row(row, channel_ids)
+        })
+        .collect())
+}
+
+fn load_profile(conn: &mut SqliteConnection, row: ProfileRow) -> QueryResult<DeviceProfile> {
+    let channel_ids = device_profile_channels::table
+        .filter(device_profile_channels::profile_id.eq(row.0))
+        .select(device_profile_channels::xmltv_channel_id)
+        .order(device_profile_channels::xmltv_channel_id.asc())
+        .load(conn)?;
+    Ok(DeviceProfile::from_row(row, channel_ids))
+}
+
+/// Profile by ID
+pub fn get_profile(conn: &mut SqliteConnection, id: i32) -> QueryResult<Option<DeviceProfile>> {
+    let row: Option<ProfileRow> = device_profiles::table
+        .filter(device_profiles::id.eq(id))
+        .select(profile_columns())
+        .first(conn)
+        .optional()?;
+    row.map(|row| load_profile(conn, row)).transpose()
+}
+
+/// Profile served under `/device/{slug}/`
+pub fn find_profile_by_slug(
+    conn: &mut SqliteConnection,
+    slug: &str,
+) -> QueryResult<Option<DeviceProfile>> {
+    let row: Option<ProfileRow> = device_profiles::table
+        .filter(device_profiles::slug.eq(slug))
+        .select(profile_columns())
+        .first(conn)
+        .optional()?;
+    row.map(|row| load_profile(conn, row)).transpose()
+}
+
+/// Create a profile with no channels
+///
+/// The slug defaults to one made from the name. The DeviceID is derived
+/// from the main device's and the slug, and kept if the slug changes later.
+pub fn create_profile(
+    conn: &mut SqliteConnection,
+    name: &str,
+    slug: Option<&str>,
+    tuner_count: Option<i32>,
+    stream_format: Option<&str>,
+) -> Result<DeviceProfile, String> {
+    let name = name.trim();
+    validate_name(name)?;
+    let slug = match slug.map(str::trim).filter(|s| !s.is_empty()) {
+        Some(slug) => slug.to_string(),
+        None => slugify(name),
+    };
+    validate_slug(&slug)?;
+    validate_tuner_count(tuner_count)?;
+    let stream_format = parse_stream_format(stream_format)?;
+
+    let main_device_id = hdhr::device_identity(conn).device_id;
+    let device_id = hdhr::generate_profile_device_id(&main_device_id, &slug);
+
+    diesel::insert_into(device_profiles::table)
+        .values((
+            device_profiles::slug.eq(&slug),
+            device_profiles::name.eq(name),
+            device_profiles::device_id.eq(&device_id),
+            device_profiles::tuner_count.eq(tuner_count),
+            device_profiles::stream_format.eq(stream_format.as_param()),
+        ))
+        .execute(conn)
+        .map_err(|e| match e {
+            diesel::result::Error::DatabaseError(
+                diesel::result::DatabaseErrorKind::UniqueViolation,
+                _,
+            ) => format!("A profile with the slug '{}' already exists", slug),
+            e => format!("Failed to create profile: {}", e),
+        })?;
+
+    find_profile_by_slug(conn, &slug)
+        .map_err(|e| format!("Failed to load profile: {}", e))?
+        .ok_or_else(|| "Profile not found".to_string())
+}
+
+/// Rename a profile or change its advertised tuner count or stream format
+pub fn update_profile(
+    conn: &mut SqliteConnection,
+    id: i32,
+    name: &str,
+    tuner_count: Option<i32>,
+    stream_format: Option<&str>,
+) -> Result<DeviceProfile, String> {
+    let name = name.trim();
+    validate_name(name)?;
+    validate_tuner_count(tuner_count)?;
+    let stream_format = parse_stream_format(stream_format)?;
+
+    let updated = diesel::update(device_profiles::table.filter(device_profiles::id.eq(id)))
+        .set((
+            device_profiles::name.eq(name),
+            device_profiles::tuner_count.eq(tuner_count),
+            device_profiles::stream_format.eq(stream_format.as_param()),
+            device_profiles::updated_at.eq(chrono::Utc::now().to_rfc3339()),
+        ))
+        .execute(conn)
+        .map_err(|e| format!("Failed to update profile: {}", e))?;
+    if updated == 0 {
+        return Err("Profile not found".to_string());
+    }
+    get_profile(conn, id)
+        .map_err(|e| format!("Failed to load profile: {}", e))?
+        .ok_or_else(|| "Profile not found".to_string())
+}
+
+/// Delete a profile; returns whether it existed
+pub fn delete_profile(conn: &mut SqliteConnection, id: i32) -> QueryResult<bool> {
+    conn.transaction(|conn| {
+        diesel::delete(
+            device_profile_channels::table.filter(device_profile_channels::profile_id.eq(id)),
+        )
+        .execute(conn)?;
+        let deleted = diesel::delete(device_profiles::table.filter(device_profiles::id.eq(id)))
+            .execute(conn)?;
+        Ok(deleted > 0)
+    })
+}
+
+/// Replace the channels of a profile
+pub fn set_profile_channels(
+    conn: &mut SqliteConnection,
+    id: i32,
+    channel_ids: &[i32],
+) -> Result<DeviceProfile, String> {
+    let unique: HashSet<i32> = channel_ids.iter().copied().collect();
+    conn.transaction::<_, diesel::result::Error, _>(|conn| {
+        diesel::delete(
+            device_profile_channels::table.filter(device_profile_channels::profile_id.eq(id)),
+        )
+        .execute(conn)?;
+        let rows: Vec<_> = unique
+            .iter()
+            .map(|&channel_id| {
+                (
+                    device_profile_channels::profile_id.eq(id),
+                    device_profile_channels::xmltv_channel_id.eq(channel_id),
+                )
+            })
+            .collect();
+        diesel::insert_into(device_profile_channels::table)
+            .values(&rows)
+            .execute(conn)?;
+        Ok(())
+    })
+    .map_err(|e| format!("Failed to save profile channels: {}", e))?;
+
+    get_profile(conn, id)
+        .map_err(|e| format!("Failed to load profile: {}", e))?
+        .ok_or_else(|| "Profile not found".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_profiles() {
+        let mut conn = crate::db::test_connection();
+        for id in 1..=2 {
+            diesel::sql_query(format!(
+                "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES ({id}, 1, 'ch{id}', 'Channel {id}')"
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        }
+
+        let sports = create_profile(&mut conn, "Sports & Events", None, None, None).unwrap();
+        assert_eq!(sports.slug, "sports-events");
+        assert_eq!(sports.format(), StreamFormat::Ts);
+        let news = create_profile(&mut conn, "News", None, Some(2), Some("HLS")).unwrap();
+        assert_eq!(news.stream_format, "m3u8");
+        assert_ne!(sports.device_id, news.device_id);
+        assert_ne!(sports.device_id, hdhr::device_identity(&mut conn).device_id);
+        assert!(create_profile(&mut conn, "Sports", Some("sports-events"), None, None).is_err());
+        assert!(create_profile(&mut conn, "Bad", Some("Not OK"), None, None).is_err());
+        assert!(create_profile(&mut conn, "Bad", None, None, Some("mkv")).is_err());
+
+        let sports = set_profile_channels(&mut conn, sports.id, &[2, 1, 2]).unwrap();
+        assert_eq!(sports.channel_ids, vec![1, 2]);
+        let found = find_profile_by_slug(&mut conn, "sports-events")
+            .unwrap()
+            .unwrap();
+        assert_eq!(found.channel_set(), HashSet::from([1, 2]));
+
+        assert!(delete_profile(&mut conn, sports.id).unwrap());
+        let profiles = list_profiles(&mut conn).unwrap();
+        assert_eq!(profiles.len(), 1);
+        assert_eq!(profiles[0].tuner_count, Some(2));
+    }
+}
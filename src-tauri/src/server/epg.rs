@@ -10,9 +10,251 @@ use diesel::prelude::*;
 use diesel::sql_types::{Integer, Nullable, Text};
 use quick_xml::events::{BytesDecl, BytesEnd, BytesStart, BytesText, Event};
 use quick_xml::Writer;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::io::Cursor;
 
+use crate::channel_numbers::resolve_channel_numbers;
 use crate::db::DbPooledConnection;
+use crate::xmltv::parser::onscreen_episode_number;
+use crate::xmltv::{ProgramCredit, ProgramTranslation};
+
+use super::logos::{self, LogoOutput};
+
+/// Settings key for how many days of programs go into /epg.xml
+pub const EPG_WINDOW_DAYS_KEY: &str = "epg_window_days";
+
+/// Default guide window in days
+pub const DEFAULT_EPG_WINDOW_DAYS: u32 = 7;
+
+/// Longest configurable guide window in days
+pub const MAX_EPG_WINDOW_DAYS: u32 = 14;
+
+/// Days of programs generated into /epg.xml
+pub fn get_epg_window_days(conn: &mut SqliteConnection) -> u32 {
+    use crate::db::schema::settings;
+
+    settings::table
+        .filter(settings::key.eq(EPG_WINDOW_DAYS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .filter(|days| (1..=MAX_EPG_WINDOW_DAYS).contains(days))
+        .unwrap_or(DEFAULT_EPG_WINDOW_DAYS)
+}
+
+/// Set the guide window in days
+///
+/// The cached /epg.xml was generated with the previous window, so callers
+/// must invalidate the EPG cache afterwards.
+pub fn set_epg_window_days(conn: &mut SqliteConnection, days: u32) -> Result<(), String> {
+    use crate::db::schema::settings;
+    use crate::db::Setting;
+
+    if !(1..=MAX_EPG_WINDOW_DAYS).contains(&days) {
+        return Err(format!(
+            "Guide window must be between 1 and {} days",
+            MAX_EPG_WINDOW_DAYS
+        ));
+    }
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(EPG_WINDOW_DAYS_KEY, days.to_string()))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Settings key for placeholder program generation (JSON)
+pub const EPG_PLACEHOLDER_KEY: &str = "epg_placeholder";
+
+/// Placeholder programs for channels without guide data
+///
+/// Synthetic channels always get placeholders. Other enabled channels get
+/// them when they have no programs in the guide window and placeholders are
+/// enabled, globally or for the channel (`placeholder_mode` in
+/// `xmltv_channel_settings`).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlaceholderEpgConfig {
+    /// Fill empty guides of enabled channels
+    pub enabled: bool,
+    /// Length of each placeholder program in minutes
+    pub block_minutes: u32,
+    /// Program title; `{channel}` is replaced with the channel name
+    pub title_template: String,
+    /// Program description; `{channel}` is replaced with the channel name
+    pub description_template: String,
+    pub category: Option<String>,
+}
+
+impl Default for PlaceholderEpgConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            block_minutes: 120,
+            title_template: "{channel} - Live Programming".to_string(),
+            description_template: "Live content on {channel}".to_string(),
+            category: None,
+        }
+    }
+}
+
+impl PlaceholderEpgConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if !(15..=720).contains(&self.block_minutes) {
+            return Err("Placeholder programs must be 15 to 720 minutes long".to_string());
+        }
+        if self.title_template.trim().is_empty() {
+            return Err("Placeholder title cannot be empty".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// Placeholder program settings (defaults if unset or unreadable)
+pub fn load_placeholder_epg_config(conn: &mut SqliteConnection) -> PlaceholderEpgConfig {
+    use crate::db::schema::settings;
+
+    settings::table
+        .filter(settings::key.eq(EPG_PLACEHOLDER_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str::<PlaceholderEpgConfig>(&value).ok())
+        .filter(|config| config.validate().is_ok())
+        .unwrap_or_default()
+}
+
+/// Save the placeholder program settings
+///
+/// Callers must invalidate the EPG cache afterwards.
+pub fn save_placeholder_epg_config(
+    conn: &mut SqliteConnection,
+    config: &PlaceholderEpgConfig,
+) -> Result<(), String> {
+    use crate::db::schema::settings;
+    use crate::db::Setting;
+
+    config.validate()?;
+    let value = serde_json::to_string(config)
+        .map_err(|e| format!("Failed to serialize placeholder settings: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(EPG_PLACEHOLDER_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Settings key for the lineup's preferred program languages (comma-separated)
+pub const EPG_LANGUAGES_KEY: &str = "epg_languages";
+
+/// Most preferred languages in a list
+const MAX_EPG_LANGUAGES: usize = 10;
+
+/// Parse a comma-separated list of language tags (e.g. "de, en-GB")
+///
+/// Tags are trimmed, `_` becomes `-`, and duplicates are dropped keeping
+/// the first. An empty list means the guide's own first language.
+pub fn parse_language_list(value: &str) -> Result<Vec<String>, String> {
+    let mut languages: Vec<String> = Vec::new();
+    for tag in value.split(',').map(str::trim).filter(|t| !t.is_empty()) {
+        let tag = tag.replace('_', "-");
+        let valid = tag.split('-').enumerate().all(|(i, subtag)| {
+            (1..=8).contains(&subtag.len())
+                && if i == 0 {
+                    subtag.chars().all(|c| c.is_ascii_alphabetic())
+                } else {
+                    subtag.chars().all(|c| c.is_ascii_alphanumeric())
+                }
+        });
+        if !valid {
+            return Err(format!("Invalid language '{}'", tag));
+        }
+        if !languages.iter().any(|l| l.eq_ignore_ascii_case(&tag)) {
+            languages.push(tag);
+        }
+    }
+    if languages.len() > MAX_EPG_LANGUAGES {
+        return Err(format!(
+            "At most {} languages can be preferred",
+            MAX_EPG_LANGUAGES
+        ));
+    }
+    Ok(languages)
+}
+
+/// Preferred program languages of the lineup, most preferred first
+pub fn get_epg_languages(conn: &mut SqliteConnection) -> Vec<String> {
+    use crate::db::schema::settings;
+
+    settings::table
+        .filter(settings::key.eq(EPG_LANGUAGES_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| parse_language_list(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Set the lineup's preferred program languages
+///
+/// Returns the normalized list. Callers must invalidate the EPG cache.
+pub fn set_epg_languages(
+    conn: &mut SqliteConnection,
+    languages: &[String],
+) -> Result<Vec<String>, String> {
+    use crate::db::schema::settings;
+    use crate::db::Setting;
+
+    let languages = parse_language_list(&languages.join(","))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(EPG_LANGUAGES_KEY, languages.join(",")))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(languages)
+}
+
+/// Whether a guide language is the preferred one, exactly or by its
+/// primary subtag ("en" and "en-GB")
+fn language_matches(preferred: &str, lang: &str, exact: bool) -> bool {
+    if exact {
+        return preferred.eq_ignore_ascii_case(lang);
+    }
+    let primary = |tag: &str| tag.split(['-', '_']).next().unwrap_or("").to_ascii_lowercase();
+    primary(preferred) == primary(lang)
+}
+
+/// Pick a program text in the first preferred language that has it
+///
+/// Falls back to the stored text, which is the guide's first. Returns the
+/// text and its language when known.
+fn localized_text(
+    stored: Option<String>,
+    translations: &[ProgramTranslation],
+    languages: &[String],
+    field: fn(&ProgramTranslation) -> Option<&String>,
+) -> (Option<String>, Option<String>) {
+    for preferred in languages {
+        for exact in [true, false] {
+            if let Some((text, lang)) = translations
+                .iter()
+                .filter(|t| language_matches(preferred, &t.lang, exact))
+                .find_map(|t| field(t).map(|text| (text, &t.lang)))
+            {
+                return (Some(text.clone()), Some(lang.clone()));
+            }
+        }
+    }
+
+    let lang = stored.as_ref().and_then(|stored| {
+        translations
+            .iter()
+            .find(|t| field(t) == Some(stored))
+            .map(|t| t.lang.clone())
+    });
+    (stored, lang)
+}
 
 /// Output structure for XMLTV channel data
 #[derive(Debug, Clone)]
@@ -27,12 +269,16 @@ pub struct XmltvChannelOutput {
     pub is_synthetic: bool,
     /// Internal database ID (for program lookup)
     pub internal_id: i32,
-    /// Channel number for Plex sorting (1-indexed, from plex_display_order + 1)
+    /// Channel number for Plex sorting (see `crate::channel_numbers`)
     pub channel_number: Option<i32>,
+    /// Whether an empty guide gets placeholders (None follows the global setting)
+    pub placeholder_mode: Option<bool>,
+    /// Placeholder title template replacing the global one
+    pub placeholder_title: Option<String>,
 }
 
 /// Output structure for XMLTV programme data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct XmltvProgramme {
     /// Channel ID (matches channel id attribute)
     pub channel_id: String,
@@ -48,8 +294,39 @@ pub struct XmltvProgramme {
     pub category: Option<String>,
     /// Optional episode info (e.g., "S1E100")
     pub episode_num: Option<String>,
+    /// Optional episode number in the `xmltv_ns` system (e.g., "0.99.0/1")
+    pub episode_num_xmltv_ns: Option<String>,
+    /// Optional episode sub-title
+    pub sub_title: Option<String>,
+    /// Optional artwork URL
+    pub icon: Option<String>,
+    /// Cast and crew
+    pub credits: Vec<ProgramCredit>,
+    /// Optional rating value and system (e.g., "TV-14", "VCHIP")
+    pub rating: Option<String>,
+    pub rating_system: Option<String>,
+    /// Optional star rating (e.g., "7.5/10")
+    pub star_rating: Option<String>,
+    /// Languages of the title, sub-title and description ("en" when unknown)
+    pub title_lang: Option<String>,
+    pub sub_title_lang: Option<String>,
+    pub description_lang: Option<String>,
 }
 
+/// Credit roles in the order the XMLTV DTD requires them
+const CREDIT_ROLES: [&str; 10] = [
+    "director",
+    "actor",
+    "writer",
+    "adapter",
+    "producer",
+    "composer",
+    "editor",
+    "presenter",
+    "commentator",
+    "guest",
+];
+
 /// Query result for enabled channels
 #[derive(QueryableByName, Debug)]
 struct EnabledChannelRow {
@@ -63,6 +340,12 @@ struct EnabledChannelRow {
     is_synthetic: Option<i32>,
     #[diesel(sql_type = Nullable<Integer>)]
     plex_display_order: Option<i32>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    channel_number: Option<i32>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    placeholder_mode: Option<i32>,
+    #[diesel(sql_type = Nullable<Text>)]
+    placeholder_title: Option<String>,
 }
 
 /// Query result for program data
@@ -85,6 +368,24 @@ struct ProgramRow {
     category: Option<String>,
     #[diesel(sql_type = Nullable<Text>)]
     episode_info: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    sub_title: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    icon: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    episode_num_xmltv_ns: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    episode_num_onscreen: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    rating: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    rating_system: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    star_rating: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    credits: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    translations: Option<String>,
 }
 
 /// Get enabled XMLTV channels that have at least one Xtream stream mapping
@@ -93,7 +394,7 @@ struct ProgramRow {
 /// then by display_name (ascending) for channels without explicit order.
 ///
 /// Only includes channels that:
-/// - Have is_enabled = 1 in xmltv_channel_settings
+/// - Are enabled in the published lineup (`published_channel_settings`)
 /// - Have at least one mapping in channel_mappings table
 pub fn get_enabled_channels_for_epg(
     conn: &mut DbPooledConnection,
@@ -105,9 +406,13 @@ pub fn get_enabled_channels_for_epg(
             xc.display_name,
             xc.icon,
             xc.is_synthetic,
-            xcs.plex_display_order
+            xcs.plex_display_order,
+            xcs.channel_number,
+            cs.placeholder_mode,
+            cs.placeholder_title
         FROM xmltv_channels xc
-        INNER JOIN xmltv_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
+        INNER JOIN published_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
+        LEFT JOIN xmltv_channel_settings cs ON xc.id = cs.xmltv_channel_id
         WHERE xcs.is_enabled = 1
         AND EXISTS (
             SELECT 1 FROM channel_mappings cm
@@ -121,29 +426,16 @@ pub fn get_enabled_channels_for_epg(
     )
     .load::<EnabledChannelRow>(conn)?;
 
-    // Find max explicit channel number to avoid collisions with fallback numbering
-    // e.g., if channels have plex_display_order 0,2,5 -> channel numbers 1,3,6
-    // fallback should start at 7, not 1
-    let max_explicit_channel = rows
-        .iter()
-        .filter_map(|r| r.plex_display_order)
-        .map(|o| o + 1)
-        .max()
-        .unwrap_or(0);
-
-    let mut fallback_number = max_explicit_channel + 1;
+    let numbers = resolve_channel_numbers(
+        &rows
+            .iter()
+            .map(|r| (r.channel_number, r.plex_display_order))
+            .collect::<Vec<_>>(),
+    );
     let channels = rows
         .into_iter()
-        .map(|row| {
-            // Convert 0-indexed plex_display_order to 1-indexed channel number
-            let channel_number = match row.plex_display_order {
-                Some(order) => order + 1,
-                None => {
-                    let num = fallback_number;
-                    fallback_number += 1;
-                    num
-                }
-            };
+        .zip(numbers)
+        .map(|(row, channel_number)| {
             XmltvChannelOutput {
                 // Use channel number as ID for Plex EPG matching
                 id: channel_number.to_string(),
@@ -152,6 +444,8 @@ pub fn get_enabled_channels_for_epg(
                 is_synthetic: row.is_synthetic.unwrap_or(0) == 1,
                 internal_id: row.id,
                 channel_number: Some(channel_number),
+                placeholder_mode: row.placeholder_mode.map(|mode| mode != 0),
+                placeholder_title: row.placeholder_title.filter(|s| !s.trim().is_empty()),
             }
         })
         .collect();
@@ -159,13 +453,14 @@ pub fn get_enabled_channels_for_epg(
     Ok(channels)
 }
 
-/// Get programs for the given channel internal IDs within the 7-day window
+/// Get programs for the given channel internal IDs within the guide window
 ///
 /// Uses a batch query to avoid N+1 pattern.
-/// Returns programs where start_time >= now - 1 hour AND start_time < now + 7 days
+/// Returns programs where start_time >= now - 1 hour AND start_time < now + `window_days`
 fn get_programs_for_channels(
     conn: &mut DbPooledConnection,
     channel_ids: &[i32],
+    window_days: u32,
 ) -> Result<Vec<ProgramRow>, diesel::result::Error> {
     if channel_ids.is_empty() {
         return Ok(Vec::new());
@@ -198,14 +493,23 @@ fn get_programs_for_channels(
             p.start_time,
             p.end_time,
             p.category,
-            p.episode_info
+            p.episode_info,
+            p.sub_title,
+            p.icon,
+            p.episode_num_xmltv_ns,
+            p.episode_num_onscreen,
+            p.rating,
+            p.rating_system,
+            p.star_rating,
+            p.credits,
+            p.translations
         FROM programs p
         WHERE p.xmltv_channel_id IN ({})
         AND p.start_time >= datetime('now', '-1 hour')
-        AND p.start_time < datetime('now', '+7 days')
+        AND p.start_time < datetime('now', '+{} days')
         ORDER BY p.xmltv_channel_id, p.start_time ASC
         "#,
-        in_clause
+        in_clause, window_days
     );
 
     diesel::sql_query(query).load::<ProgramRow>(conn)
@@ -237,12 +541,15 @@ fn parse_db_datetime(dt_str: &str) -> Option<DateTime<Utc>> {
     None
 }
 
-/// Generate placeholder programs for a synthetic channel
+/// Generate placeholder programs for a channel without guide data
 ///
-/// Creates 2-hour program blocks covering the next 7 days.
-/// Title format: "{display_name} - Live Programming"
-/// Description: "Live content on {display_name}"
-pub fn generate_placeholder_programs(channel: &XmltvChannelOutput) -> Vec<XmltvProgramme> {
+/// Creates `block_minutes` program blocks covering the next `window_days`
+/// days, titled from the channel's own template or the configured one.
+pub fn generate_placeholder_programs(
+    channel: &XmltvChannelOutput,
+    config: &PlaceholderEpgConfig,
+    window_days: u32,
+) -> Vec<XmltvProgramme> {
     let now = Utc::now();
     // Round down to current hour
     let start_hour = now
@@ -251,20 +558,31 @@ pub fn generate_placeholder_programs(channel: &XmltvChannelOutput) -> Vec<XmltvP
         .and_then(|dt| dt.with_nanosecond(0))
         .unwrap_or(now);
 
+    let fill = |template: &str| template.replace("{channel}", &channel.display_name);
+    let title = fill(
+        channel
+            .placeholder_title
+            .as_deref()
+            .unwrap_or(&config.title_template),
+    );
+    let description = Some(fill(&config.description_template)).filter(|d| !d.trim().is_empty());
+    let category = config.category.clone().filter(|c| !c.trim().is_empty());
+    let block = Duration::minutes(i64::from(config.block_minutes.max(1)));
+
     let mut programs = Vec::new();
     let mut current = start_hour;
-    let end_date = start_hour + Duration::days(7);
+    let end_date = start_hour + Duration::days(i64::from(window_days));
 
     while current < end_date {
-        let stop = current + Duration::hours(2);
+        let stop = current + block;
         programs.push(XmltvProgramme {
             channel_id: channel.id.clone(),
-            title: format!("{} - Live Programming", channel.display_name),
-            description: Some(format!("Live content on {}", channel.display_name)),
+            title: title.clone(),
+            description: description.clone(),
             start: format_xmltv_datetime(current),
             stop: format_xmltv_datetime(stop),
-            category: None,
-            episode_num: None,
+            category: category.clone(),
+            ..Default::default()
         });
         current = stop;
     }
@@ -277,11 +595,40 @@ pub fn generate_placeholder_programs(channel: &XmltvChannelOutput) -> Vec<XmltvP
 /// This is the main entry point for EPG generation. It:
 /// 1. Fetches enabled channels
 /// 2. Fetches programs for those channels
-/// 3. Generates placeholder programs for synthetic channels
+/// 3. Generates placeholder programs for synthetic channels and empty guides
 /// 4. Formats everything as XMLTV XML
-pub fn generate_xmltv_epg(conn: &mut DbPooledConnection) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+///
+/// `port` is used to build local logo URLs when the EPG logo mode is "proxy".
+/// Programs cover the configured guide window (`epg_window_days`) and use the
+/// lineup's preferred languages (`epg_languages`).
+pub fn generate_xmltv_epg(
+    conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
+    let languages = get_epg_languages(conn);
+    generate_xmltv_epg_in_languages(conn, host, port, &languages)
+}
+
+/// Generate the XMLTV EPG with titles and descriptions in `languages`
+///
+/// Each text uses the first language in the list the guide provides it in,
+/// falling back to the guide's first text. An empty list keeps the guide's
+/// first texts.
+pub fn generate_xmltv_epg_in_languages(
+    conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+    languages: &[String],
+) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
     // Get enabled channels
-    let channels = get_enabled_channels_for_epg(conn)?;
+    let mut channels = get_enabled_channels_for_epg(conn)?;
+
+    // Point channel icons at the local logo cache when proxy mode is configured
+    let logo_mode = logos::get_logo_mode(conn, LogoOutput::Epg);
+    for channel in &mut channels {
+        channel.icon = logos::resolve_logo_url(logo_mode, host, port, channel.icon.take());
+    }
 
     // Build a map from internal_id to channel_id for program mapping
     let mut id_map: std::collections::HashMap<i32, String> = std::collections::HashMap::new();
@@ -297,7 +644,8 @@ pub fn generate_xmltv_epg(conn: &mut DbPooledConnection) -> Result<String, Box<d
         .collect();
 
     // Fetch programs for non-synthetic channels
-    let program_rows = get_programs_for_channels(conn, &non_synthetic_ids)?;
+    let window_days = get_epg_window_days(conn);
+    let program_rows = get_programs_for_channels(conn, &non_synthetic_ids, window_days)?;
 
     // Convert program rows to XmltvProgramme
     let mut programmes: Vec<XmltvProgramme> = program_rows
@@ -307,24 +655,73 @@ pub fn generate_xmltv_epg(conn: &mut DbPooledConnection) -> Result<String, Box<d
             let start_dt = parse_db_datetime(&row.start_time)?;
             let end_dt = parse_db_datetime(&row.end_time)?;
 
+            let non_empty = |value: Option<String>| value.filter(|s| !s.trim().is_empty());
+            let episode_num_xmltv_ns = non_empty(row.episode_num_xmltv_ns);
+            // Programs stored before the numbering systems were split only have episode_info
+            let episode_num = non_empty(row.episode_num_onscreen)
+                .or_else(|| episode_num_xmltv_ns.as_deref().and_then(onscreen_episode_number))
+                .or_else(|| {
+                    non_empty(row.episode_info).filter(|_| episode_num_xmltv_ns.is_none())
+                });
+
+            let translations: Vec<ProgramTranslation> = row
+                .translations
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_default();
+            let (title, title_lang) =
+                localized_text(Some(row.title), &translations, languages, |t| t.title.as_ref());
+            let (sub_title, sub_title_lang) = localized_text(
+                non_empty(row.sub_title),
+                &translations,
+                languages,
+                |t| t.sub_title.as_ref(),
+            );
+            let (description, description_lang) = localized_text(
+                non_empty(row.description),
+                &translations,
+                languages,
+                |t| t.description.as_ref(),
+            );
+
             Some(XmltvProgramme {
                 channel_id: channel_id.clone(),
-                title: row.title,
-                description: row.description.filter(|s| !s.trim().is_empty()),
+                title: title.unwrap_or_default(),
+                description,
                 start: format_xmltv_datetime(start_dt),
                 stop: format_xmltv_datetime(end_dt),
-                category: row.category.filter(|s| !s.trim().is_empty()),
-                episode_num: row.episode_info.filter(|s| !s.trim().is_empty()),
+                category: non_empty(row.category),
+                episode_num,
+                episode_num_xmltv_ns,
+                sub_title,
+                icon: non_empty(row.icon),
+                credits: row
+                    .credits
+                    .and_then(|json| serde_json::from_str(&json).ok())
+                    .unwrap_or_default(),
+                rating: non_empty(row.rating),
+                rating_system: non_empty(row.rating_system),
+                star_rating: non_empty(row.star_rating),
+                title_lang,
+                sub_title_lang,
+                description_lang,
             })
         })
         .collect();
 
-    // Generate placeholder programs for synthetic channels
-    for channel in &channels {
-        if channel.is_synthetic {
-            programmes.extend(generate_placeholder_programs(channel));
-        }
-    }
+    // Generate placeholder programs for synthetic channels, and for channels
+    // without programs in the window when placeholders are enabled
+    let placeholder = load_placeholder_epg_config(conn);
+    let with_programs: HashSet<&str> = programmes.iter().map(|p| p.channel_id.as_str()).collect();
+    let placeholders: Vec<XmltvProgramme> = channels
+        .iter()
+        .filter(|c| {
+            c.is_synthetic
+                || (!with_programs.contains(c.id.as_str())
+                    && c.placeholder_mode.unwrap_or(placeholder.enabled))
+        })
+        .flat_map(|c| generate_placeholder_programs(c, &placeholder, window_days))
+        .collect();
+    programmes.extend(placeholders);
 
     // Sort programmes by channel_id then start time for consistent output
     programmes.sort_by(|a, b| {
@@ -430,20 +827,35 @@ fn write_programme<W: std::io::Write>(
 
     // <title lang="en">...</title>
     let mut title = BytesStart::new("title");
-    title.push_attribute(("lang", "en"));
+    title.push_attribute(("lang", programme.title_lang.as_deref().unwrap_or("en")));
     writer.write_event(Event::Start(title))?;
     writer.write_event(Event::Text(BytesText::new(&programme.title)))?;
     writer.write_event(Event::End(BytesEnd::new("title")))?;
 
+    // <sub-title lang="en">...</sub-title> (if present)
+    if let Some(ref sub_title) = programme.sub_title {
+        let mut sub_title_elem = BytesStart::new("sub-title");
+        let lang = programme.sub_title_lang.as_deref().unwrap_or("en");
+        sub_title_elem.push_attribute(("lang", lang));
+        writer.write_event(Event::Start(sub_title_elem))?;
+        writer.write_event(Event::Text(BytesText::new(sub_title)))?;
+        writer.write_event(Event::End(BytesEnd::new("sub-title")))?;
+    }
+
     // <desc lang="en">...</desc> (if present)
     if let Some(ref desc) = programme.description {
         let mut desc_elem = BytesStart::new("desc");
-        desc_elem.push_attribute(("lang", "en"));
+        desc_elem.push_attribute(("lang", programme.description_lang.as_deref().unwrap_or("en")));
         writer.write_event(Event::Start(desc_elem))?;
         writer.write_event(Event::Text(BytesText::new(desc)))?;
         writer.write_event(Event::End(BytesEnd::new("desc")))?;
     }
 
+    // <credits>...</credits> (if present), roles in DTD order
+    if !programme.credits.is_empty() {
+        write_credits(writer, &programme.credits)?;
+    }
+
     // <category lang="en">...</category> (if present)
     if let Some(ref cat) = programme.category {
         let mut cat_elem = BytesStart::new("category");
@@ -453,6 +865,22 @@ fn write_programme<W: std::io::Write>(
         writer.write_event(Event::End(BytesEnd::new("category")))?;
     }
 
+    // <icon src="..."/> (if present)
+    if let Some(ref icon) = programme.icon {
+        let mut icon_elem = BytesStart::new("icon");
+        icon_elem.push_attribute(("src", icon.as_str()));
+        writer.write_event(Event::Empty(icon_elem))?;
+    }
+
+    // <episode-num system="xmltv_ns">...</episode-num> (if present)
+    if let Some(ref ep_num) = programme.episode_num_xmltv_ns {
+        let mut ep_elem = BytesStart::new("episode-num");
+        ep_elem.push_attribute(("system", "xmltv_ns"));
+        writer.write_event(Event::Start(ep_elem))?;
+        writer.write_event(Event::Text(BytesText::new(ep_num)))?;
+        writer.write_event(Event::End(BytesEnd::new("episode-num")))?;
+    }
+
     // <episode-num system="onscreen">...</episode-num> (if present)
     if let Some(ref ep_num) = programme.episode_num {
         let mut ep_elem = BytesStart::new("episode-num");
@@ -462,6 +890,24 @@ fn write_programme<W: std::io::Write>(
         writer.write_event(Event::End(BytesEnd::new("episode-num")))?;
     }
 
+    // <rating system="..."><value>...</value></rating> (if present)
+    if let Some(ref rating) = programme.rating {
+        let mut rating_elem = BytesStart::new("rating");
+        if let Some(ref system) = programme.rating_system {
+            rating_elem.push_attribute(("system", system.as_str()));
+        }
+        writer.write_event(Event::Start(rating_elem))?;
+        write_value(writer, rating)?;
+        writer.write_event(Event::End(BytesEnd::new("rating")))?;
+    }
+
+    // <star-rating><value>...</value></star-rating> (if present)
+    if let Some(ref star_rating) = programme.star_rating {
+        writer.write_event(Event::Start(BytesStart::new("star-rating")))?;
+        write_value(writer, star_rating)?;
+        writer.write_event(Event::End(BytesEnd::new("star-rating")))?;
+    }
+
     // </programme>
     writer.write_event(Event::End(BytesEnd::new("programme")))?;
     writer.write_event(Event::Text(BytesText::new("\n")))?;
@@ -469,6 +915,37 @@ fn write_programme<W: std::io::Write>(
     Ok(())
 }
 
+/// Write a <credits> element, grouping roles in DTD order
+///
+/// Roles the DTD doesn't define are dropped.
+fn write_credits<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    credits: &[ProgramCredit],
+) -> Result<(), quick_xml::Error> {
+    writer.write_event(Event::Start(BytesStart::new("credits")))?;
+    for role in CREDIT_ROLES {
+        for credit in credits.iter().filter(|c| c.role == role) {
+            let mut elem = BytesStart::new(role);
+            if let Some(ref character) = credit.character {
+                elem.push_attribute(("role", character.as_str()));
+            }
+            writer.write_event(Event::Start(elem))?;
+            writer.write_event(Event::Text(BytesText::new(&credit.name)))?;
+            writer.write_event(Event::End(BytesEnd::new(role)))?;
+        }
+    }
+    writer.write_event(Event::End(BytesEnd::new("credits")))?;
+    Ok(())
+}
+
+/// Write a <value> element
+fn write_value<W: std::io::Write>(writer: &mut Writer<W>, value: &str) -> Result<(), quick_xml::Error> {
+    writer.write_event(Event::Start(BytesStart::new("value")))?;
+    writer.write_event(Event::Text(BytesText::new(value)))?;
+    writer.write_event(Event::End(BytesEnd::new("value")))?;
+    Ok(())
+}
+
 /// Generate XMLTV content from pre-fetched data (for testing without DB)
 pub fn generate_xmltv_from_data(
     channels: &[XmltvChannelOutput],
@@ -497,6 +974,8 @@ mod tests {
             is_synthetic,
             internal_id,
             channel_number: Some(internal_id), // Use internal_id as channel number for tests
+            placeholder_mode: None,
+            placeholder_title: None,
         }
     }
 
@@ -518,6 +997,7 @@ mod tests {
             stop: stop.to_string(),
             category: category.map(|s| s.to_string()),
             episode_num: episode_num.map(|s| s.to_string()),
+            ..Default::default()
         }
     }
 
@@ -647,6 +1127,64 @@ mod tests {
         assert!(!result.contains("<episode-num"));
     }
 
+    #[test]
+    fn test_programme_extended_metadata_in_dtd_order() {
+        let channels = vec![create_test_channel("HBO.US", "HBO", None, false, 1)];
+        let mut programme = create_test_programme(
+            "HBO.US",
+            "The Detective",
+            "20260120200000 +0000",
+            "20260120210000 +0000",
+            Some("A witness disappears."),
+            Some("Drama"),
+            Some("S02E05"),
+        );
+        programme.sub_title = Some("The Last Witness".into());
+        programme.icon = Some("https://example.com/poster.jpg".into());
+        programme.episode_num_xmltv_ns = Some("1.4.0/1".into());
+        programme.rating = Some("TV-14".into());
+        programme.rating_system = Some("VCHIP".into());
+        programme.star_rating = Some("7.5/10".into());
+        programme.credits = vec![
+            ProgramCredit {
+                role: "actor".into(),
+                name: "John Smith".into(),
+                character: Some("DI Hart".into()),
+            },
+            ProgramCredit {
+                role: "director".into(),
+                name: "Jane Roe".into(),
+                character: None,
+            },
+            ProgramCredit {
+                role: "stunt-double".into(),
+                name: "Nobody".into(),
+                character: None,
+            },
+        ];
+
+        let result = generate_xmltv_from_data(&channels, &[programme]).unwrap();
+
+        let expected = [
+            "<title lang=\"en\">The Detective</title>",
+            "<sub-title lang=\"en\">The Last Witness</sub-title>",
+            "<desc lang=\"en\">A witness disappears.</desc>",
+            "<credits><director>Jane Roe</director><actor role=\"DI Hart\">John Smith</actor></credits>",
+            "<category lang=\"en\">Drama</category>",
+            "<icon src=\"https://example.com/poster.jpg\"/>",
+            "<episode-num system=\"xmltv_ns\">1.4.0/1</episode-num>",
+            "<episode-num system=\"onscreen\">S02E05</episode-num>",
+            "<rating system=\"VCHIP\"><value>TV-14</value></rating>",
+            "<star-rating><value>7.5/10</value></star-rating>",
+        ];
+        let positions: Vec<usize> = expected
+            .iter()
+            .map(|element| result.find(element).unwrap_or_else(|| panic!("missing {}", element)))
+            .collect();
+        assert!(positions.windows(2).all(|pair| pair[0] < pair[1]));
+        assert!(!result.contains("Nobody"));
+    }
+
     // ============================================================================
     // Datetime format tests
     // ============================================================================
@@ -669,7 +1207,11 @@ mod tests {
     fn test_synthetic_channels_get_placeholder_programs() {
         let channel = create_test_channel("SYNTHETIC.1", "My Channel", None, true, 100);
 
-        let programs = generate_placeholder_programs(&channel);
+        let programs = generate_placeholder_programs(
+            &channel,
+            &PlaceholderEpgConfig::default(),
+            DEFAULT_EPG_WINDOW_DAYS,
+        );
 
         // Should have programs for 7 days at 2-hour intervals = 84 programs
         assert_eq!(programs.len(), 84);
@@ -688,17 +1230,130 @@ mod tests {
     #[test]
     fn test_placeholder_programs_cover_7_days() {
         let channel = create_test_channel("TEST", "Test Channel", None, true, 1);
-        let programs = generate_placeholder_programs(&channel);
+        let programs = generate_placeholder_programs(
+            &channel,
+            &PlaceholderEpgConfig::default(),
+            DEFAULT_EPG_WINDOW_DAYS,
+        );
 
         // 7 days at 2 hours per program = 84 programs
         // (Note: this is a simplified check - proper test would parse full dates)
         assert!(programs.len() >= 80 && programs.len() <= 90);
     }
 
+    #[test]
+    fn test_placeholder_programs_follow_window() {
+        let channel = create_test_channel("TEST", "Test Channel", None, true, 1);
+
+        // 2 days at 2 hours per program = 24 programs
+        assert_eq!(
+            generate_placeholder_programs(&channel, &PlaceholderEpgConfig::default(), 2).len(),
+            24
+        );
+    }
+
+    #[test]
+    fn test_placeholder_programs_follow_config() {
+        let config = PlaceholderEpgConfig {
+            enabled: true,
+            block_minutes: 60,
+            title_template: "{channel} Live".to_string(),
+            description_template: String::new(),
+            category: Some("Sports".to_string()),
+        };
+        let mut channel = create_test_channel("TEST", "Arena", None, false, 1);
+
+        let programs = generate_placeholder_programs(&channel, &config, 1);
+        assert_eq!(programs.len(), 24);
+        assert_eq!(programs[0].title, "Arena Live");
+        assert_eq!(programs[0].description, None);
+        assert_eq!(programs[0].category.as_deref(), Some("Sports"));
+
+        // The channel's own title wins
+        channel.placeholder_title = Some("Matchday on {channel}".to_string());
+        let programs = generate_placeholder_programs(&channel, &config, 1);
+        assert_eq!(programs[0].title, "Matchday on Arena");
+
+        assert!(PlaceholderEpgConfig {
+            block_minutes: 5,
+            ..config
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn test_localized_text_prefers_lineup_languages() {
+        let translations = vec![
+            ProgramTranslation {
+                lang: "de".to_string(),
+                title: Some("Die Reise".to_string()),
+                description: Some("Eine Dokumentation.".to_string()),
+                ..Default::default()
+            },
+            ProgramTranslation {
+                lang: "fr-CA".to_string(),
+                title: Some("Le Voyage".to_string()),
+                ..Default::default()
+            },
+        ];
+        let stored = || Some("Die Reise".to_string());
+        let title: fn(&ProgramTranslation) -> Option<&String> = |t| t.title.as_ref();
+        let desc: fn(&ProgramTranslation) -> Option<&String> = |t| t.description.as_ref();
+        let languages = |list: &str| parse_language_list(list).unwrap();
+
+        assert_eq!(
+            localized_text(stored(), &translations, &languages("fr"), title),
+            (Some("Le Voyage".to_string()), Some("fr-CA".to_string()))
+        );
+        // No French description: fall back to the guide's own text and language
+        assert_eq!(
+            localized_text(
+                Some("Eine Dokumentation.".to_string()),
+                &translations,
+                &languages("fr"),
+                desc
+            ),
+            (Some("Eine Dokumentation.".to_string()), Some("de".to_string()))
+        );
+        assert_eq!(
+            localized_text(stored(), &translations, &languages("it, de"), title),
+            (Some("Die Reise".to_string()), Some("de".to_string()))
+        );
+        assert_eq!(
+            localized_text(stored(), &[], &languages("fr"), title),
+            (stored(), None)
+        );
+    }
+
+    #[test]
+    fn test_parse_language_list() {
+        assert_eq!(parse_language_list(" de, en_GB ,DE,").unwrap(), vec!["de", "en-GB"]);
+        assert!(parse_language_list("").unwrap().is_empty());
+        assert!(parse_language_list("en;q=0.5").is_err());
+        assert!(parse_language_list("1en").is_err());
+    }
+
+    #[test]
+    fn test_epg_window_setting() {
+        let mut conn = crate::db::test_connection();
+
+        assert_eq!(get_epg_window_days(&mut conn), DEFAULT_EPG_WINDOW_DAYS);
+        set_epg_window_days(&mut conn, 2).unwrap();
+        assert_eq!(get_epg_window_days(&mut conn), 2);
+        assert!(set_epg_window_days(&mut conn, 0).is_err());
+        assert!(set_epg_window_days(&mut conn, MAX_EPG_WINDOW_DAYS + 1).is_err());
+        assert_eq!(get_epg_window_days(&mut conn), 2);
+    }
+
     #[test]
     fn test_placeholder_programs_are_2_hours() {
         let channel = create_test_channel("TEST", "Test", None, true, 1);
-        let programs = generate_placeholder_programs(&channel);
+        let programs = generate_placeholder_programs(
+            &channel,
+            &PlaceholderEpgConfig::default(),
+            DEFAULT_EPG_WINDOW_DAYS,
+        );
 
         // Check that each program is 2 hours
         // By verifying the pattern of timestamps
@@ -786,6 +1441,8 @@ mod tests {
             is_synthetic: false,
             internal_id: 100,
             channel_number: Some(42),
+            placeholder_mode: None,
+            placeholder_title: None,
         };
 
         // Generate EPG output
@@ -816,6 +1473,8 @@ mod tests {
             is_synthetic: false,
             internal_id: 1,
             channel_number: Some(66),
+            placeholder_mode: None,
+            placeholder_title: None,
         };
 
         let result = generate_xmltv_from_data(&[channel], &[]).unwrap();
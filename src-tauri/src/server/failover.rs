@@ -6,8 +6,19 @@
 //! - Detects stream failures (timeout, connection error, HTTP error)
 //! - Executes failover to backup streams in priority order
 //! - Supports quality upgrade retry after recovery period (60s)
-//! - Logs failover events to event_log table
+//! - Logs every failover event to the event_log table, with a per-channel
+//!   cooldown so repeated failovers produce one notification plus an
+//!   aggregated summary once the cooldown runs out
 //! - Provides FailoverStream for mid-stream failover (Story 4.7)
+//! - Refreshes expired stream tokens: when the provider answers 401/403
+//!   mid-session, re-authenticates and reconnects to the same stream instead
+//!   of failing over to a different feed
+//! - Optionally pads the response with MPEG-TS null packets while no upstream
+//!   data is flowing (FFmpeg startup, failover), so clients that time out
+//!   quickly without bytes keep the connection open
+//! - Reconnects upstream every N minutes for accounts whose provider cuts
+//!   connections after a fixed time: a second connection is opened and
+//!   playback switches to it at an MPEG-TS packet boundary once it has data
 //!
 //! Security note: All error messages returned to clients are opaque
 //! to avoid exposing internal details per FR33 requirements.
@@ -15,16 +26,21 @@
 use bytes::Bytes;
 use diesel::prelude::*;
 use futures_util::Stream;
+use std::collections::HashMap;
+use std::future::Future;
 use std::io;
 use std::pin::Pin;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock, Mutex, OnceLock};
 use std::task::{Context, Poll};
 use std::time::{Duration, Instant};
 use tokio::sync::mpsc;
 
-use crate::db::schema::{accounts, channel_mappings, xtream_channels};
-use crate::db::DbPooledConnection;
-use crate::xtream::quality::qualities_from_json;
+use crate::db::schema::{accounts, channel_mappings, settings, xtream_channels};
+use crate::db::{DbPooledConnection, SharedDbPool};
+use crate::matcher::{group_variants, variant_key};
+use crate::xtream::quality::{best_tier_rank, qualities_from_json};
+
+use super::continuity::TsContinuity;
 
 /// Timeout for stream read operations (5 seconds per AC #1)
 pub const STREAM_READ_TIMEOUT: Duration = Duration::from_secs(5);
@@ -41,6 +57,69 @@ pub const QUALITY_UPGRADE_RECOVERY_PERIOD: Duration = Duration::from_secs(60);
 /// Maximum backup attempts within the failover window
 pub const MAX_FAILOVER_ATTEMPTS: usize = 2;
 
+/// Minimum time between stream token refreshes in one session
+///
+/// Tokens expire after hours; a stream rejected again right after a refresh
+/// is genuinely unavailable, so it fails over instead of refreshing forever.
+pub const TOKEN_REFRESH_MIN_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Settings key for MPEG-TS null packet padding ("true"/"false", default off)
+pub const TS_PADDING_KEY: &str = "stream_ts_padding";
+
+/// Settings key for MPEG-TS continuity across splices ("true"/"false", default on)
+pub const TS_CONTINUITY_KEY: &str = "stream_ts_continuity";
+
+/// How long the client may go without bytes before padding is sent
+pub const TS_PADDING_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Null packets sent per padding chunk (7 x 188 bytes, one typical UDP payload)
+const TS_PADDING_PACKETS: usize = 7;
+
+/// MPEG-TS packet size
+const TS_PACKET_SIZE: usize = 188;
+
+/// How long a rolling reconnect waits for the new connection's first data
+const HANDOFF_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Delay before retrying a rolling reconnect whose new connection failed
+const HANDOFF_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Settings key for the per-channel failover notification cooldown (seconds, 0 = off)
+pub const FAILOVER_NOTIFICATION_COOLDOWN_KEY: &str = "failover_notification_cooldown_secs";
+
+/// Default failover notification cooldown (5 minutes)
+pub const DEFAULT_FAILOVER_NOTIFICATION_COOLDOWN_SECS: u64 = 300;
+
+/// Maximum failover notification cooldown (24 hours)
+pub const MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS: u64 = 86_400;
+
+/// Per-channel notification state: when the last failover was logged and how
+/// many have happened since
+static FAILOVER_NOTIFICATIONS: LazyLock<Mutex<HashMap<i32, FailoverNotificationState>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Database used to log a channel's summary when its cooldown runs out (set once at startup)
+static SUMMARY_POOL: OnceLock<SharedDbPool> = OnceLock::new();
+
+#[derive(Debug, Clone, Copy)]
+struct FailoverNotificationState {
+    last_logged_at: Instant,
+    /// Failovers in the current window, including the logged one that opened it
+    failovers: u32,
+}
+
+/// How a successful failover should be reported
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum FailoverNotification {
+    /// Log the failover as usual
+    Log,
+    /// Within the channel's cooldown: log it without a notification and
+    /// count it for the summary
+    Suppress,
+    /// Cooldown over with suppressed failovers: log one summary event
+    Aggregate { count: u32, window: Duration },
+}
+
 /// Represents an available backup stream for failover
 #[derive(Debug, Clone)]
 pub struct BackupStream {
@@ -60,6 +139,72 @@ pub struct BackupStream {
     pub password_encrypted: Vec<u8>,
     /// Account ID for credential decryption
     pub account_id: i32,
+    /// Upstream URL of M3U streams (None for Xtream streams)
+    pub stream_url: Option<String>,
+    /// Past programme to play instead of the live stream (Xtream only)
+    pub catchup: Option<super::stream::CatchupWindow>,
+}
+
+impl BackupStream {
+    /// URL to connect to for this stream
+    ///
+    /// M3U streams use their stored URL; Xtream URLs are built from the
+    /// account credentials.
+    pub fn upstream_url(
+        &self,
+        credential_manager: &crate::credentials::CredentialManager,
+    ) -> Result<String, String> {
+        if let Some(url) = &self.stream_url {
+            return Ok(url.clone());
+        }
+
+        let password = credential_manager
+            .retrieve_password(&self.account_id.to_string(), &self.password_encrypted)
+            .map_err(|e| format!("Credential error: {}", e))?;
+        Ok(self.xtream_url(&password))
+    }
+
+    /// HLS playlist URL for HLS passthrough
+    ///
+    /// M3U streams use their stored URL (which may or may not be HLS);
+    /// Xtream streams ask the provider for the live `.m3u8` playlist.
+    pub fn upstream_hls_url(
+        &self,
+        credential_manager: &crate::credentials::CredentialManager,
+    ) -> Result<String, String> {
+        if let Some(url) = &self.stream_url {
+            return Ok(url.clone());
+        }
+
+        let password = credential_manager
+            .retrieve_password(&self.account_id.to_string(), &self.password_encrypted)
+            .map_err(|e| format!("Credential error: {}", e))?;
+        Ok(super::stream::build_hls_url(
+            &self.server_url,
+            &self.username,
+            &password,
+            self.stream_id,
+        ))
+    }
+
+    /// Live or catch-up URL of an Xtream stream
+    fn xtream_url(&self, password: &str) -> String {
+        match &self.catchup {
+            Some(window) => super::stream::build_timeshift_url(
+                &self.server_url,
+                &self.username,
+                password,
+                self.stream_id,
+                window,
+            ),
+            None => super::stream::build_stream_url(
+                &self.server_url,
+                &self.username,
+                password,
+                self.stream_id,
+            ),
+        }
+    }
 }
 
 /// Maintains failover state for an active streaming session
@@ -276,9 +421,17 @@ impl std::error::Error for FailoverError {}
 ///
 /// Only includes streams from active accounts (is_active = 1).
 ///
+/// Quality variants of a channel on one account ("ESPN FHD", "ESPN HD") are
+/// kept together, so failover steps down in quality before it switches
+/// provider (see `crate::matcher::group_variants`).
+///
+/// Each mapped stream is followed by the same stream on sibling accounts
+/// (other active accounts with the same server URL), so tuner exhaustion on
+/// one line fails over to another line of the same provider.
+///
 /// Returns empty Vec if no mappings exist (caller handles this case).
 pub fn get_all_streams_for_channel(
-    conn: &mut DbPooledConnection,
+    conn: &mut SqliteConnection,
     xmltv_channel_id: i32,
 ) -> Result<Vec<BackupStream>, FailoverError> {
     // Query for all stream mappings with account info
@@ -294,6 +447,8 @@ pub fn get_all_streams_for_channel(
         String,        // username
         Vec<u8>,       // password_encrypted
         i32,           // account_id
+        Option<String>, // stream_url (M3U only)
+        String,        // stream name
     )> = channel_mappings::table
         .inner_join(
             xtream_channels::table
@@ -317,6 +472,8 @@ pub fn get_all_streams_for_channel(
             accounts::username,
             accounts::password_encrypted,
             accounts::id.assume_not_null(),
+            xtream_channels::stream_url,
+            xtream_channels::name,
         ))
         .load(conn)
         .map_err(|e| {
@@ -324,8 +481,8 @@ pub fn get_all_streams_for_channel(
             FailoverError::DatabaseError(e.to_string())
         })?;
 
-    // Convert to BackupStream structs
-    let streams = results
+    // Convert to BackupStream structs, with the variant key of each
+    let streams: Vec<(BackupStream, Option<(i32, String)>)> = results
         .into_iter()
         .map(
             |(
@@ -337,13 +494,15 @@ pub fn get_all_streams_for_channel(
                 username,
                 password_encrypted,
                 account_id,
+                stream_url,
+                name,
             )| {
                 let qualities = qualities_json
                     .as_deref()
                     .map(|q| qualities_from_json(q))
                     .unwrap_or_default();
 
-                BackupStream {
+                let stream = BackupStream {
                     xtream_channel_id,
                     stream_id,
                     stream_priority: stream_priority.unwrap_or(0),
@@ -352,12 +511,465 @@ pub fn get_all_streams_for_channel(
                     username,
                     password_encrypted,
                     account_id,
-                }
+                    stream_url,
+                    catchup: None,
+                };
+                (stream, variant_key(account_id, &name))
             },
         )
         .collect();
+    let streams = group_variants(
+        streams,
+        |(_, key)| key.clone(),
+        |(stream, _)| best_tier_rank(&stream.qualities),
+    )
+    .into_iter()
+    .map(|(stream, _)| stream)
+    .collect();
+
+    add_sibling_account_streams(conn, streams).map_err(|e| {
+        eprintln!("Failover - sibling stream query failed: {}", e);
+        FailoverError::DatabaseError(e.to_string())
+    })
+}
+
+/// Keep the streams that can play a catch-up window and point them at it
+///
+/// Only Xtream streams whose channel has the provider archive enabled
+/// (`tv_archive`) and whose archive reaches back to the window start are kept.
+/// Order is preserved, so failover priority is unchanged.
+pub fn catchup_streams(
+    conn: &mut SqliteConnection,
+    streams: Vec<BackupStream>,
+    window: super::stream::CatchupWindow,
+) -> QueryResult<Vec<BackupStream>> {
+    let ids: Vec<i32> = streams.iter().map(|s| s.xtream_channel_id).collect();
+    let archives: HashMap<i32, (Option<i32>, Option<i32>)> = xtream_channels::table
+        .filter(xtream_channels::id.eq_any(&ids))
+        .select((
+            xtream_channels::id.assume_not_null(),
+            xtream_channels::tv_archive,
+            xtream_channels::tv_archive_duration,
+        ))
+        .load::<(i32, Option<i32>, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, archive, days)| (id, (archive, days)))
+        .collect();
+
+    let age_days = window.age_days();
+    Ok(streams
+        .into_iter()
+        .filter(|stream| stream.stream_url.is_none())
+        .filter(|stream| match archives.get(&stream.xtream_channel_id) {
+            Some((Some(archive), days)) if *archive > 0 => {
+                days.is_none_or(|days| age_days < i64::from(days))
+            }
+            _ => false,
+        })
+        .map(|stream| BackupStream {
+            catchup: Some(window),
+            ..stream
+        })
+        .collect())
+}
+
+/// A stream of a channel, with whether a tune request could use it now
+#[derive(Debug, Clone)]
+pub struct PlannedStream {
+    pub stream: BackupStream,
+    /// The account has a free connection (or the session may switch to it)
+    pub has_capacity: bool,
+    /// The account exceeded its monthly quota and blocks new streams
+    pub quota_blocked: bool,
+}
+
+/// The streams the proxy considers for a channel, in the order it tries them
+#[derive(Debug, Clone)]
+pub struct ChannelStreamPlan {
+    /// Enabled in the lineup Plex sees; other channels are refused
+    pub published: bool,
+    /// Highest quality to stream, None for the best available
+    pub max_quality: Option<String>,
+    pub streams: Vec<PlannedStream>,
+}
+
+/// Work out which streams a tune request for a channel tries, and in what order
+///
+/// Shared by the stream proxy and the failover chain diagnostics so both
+/// agree: mapped and sibling streams (`get_all_streams_for_channel`),
+/// narrowed to a catch-up window if given, with streams within the channel's
+/// quality cap moved first. Streams are flagged rather than removed when
+/// their account is at its connection limit or over its quota; the caller
+/// decides what to do about it (e.g. preempting a session). Quota blocking
+/// does not apply to a running session or a possible DVR recording.
+pub fn plan_channel_streams(
+    conn: &mut SqliteConnection,
+    stream_manager: &super::stream::StreamManager,
+    channel_id: i32,
+    catchup: Option<super::stream::CatchupWindow>,
+    session: Option<&str>,
+    recording: bool,
+) -> Result<ChannelStreamPlan, FailoverError> {
+    let published = crate::lineup_staging::is_channel_published(conn, channel_id)
+        .map_err(|e| FailoverError::DatabaseError(e.to_string()))?;
+
+    let mut streams = get_all_streams_for_channel(conn, channel_id)?;
+    if let Some(window) = catchup {
+        streams = catchup_streams(conn, streams, window)
+            .map_err(|e| FailoverError::DatabaseError(e.to_string()))?;
+    }
+
+    // Account settings may have changed since the last stream
+    if let Ok(limits) = super::state::read_account_limits(conn) {
+        stream_manager.set_account_limits(limits);
+    }
+    let blocked_accounts = if recording || session.is_some() {
+        Default::default()
+    } else {
+        crate::quota::blocked_accounts(conn).unwrap_or_default()
+    };
+
+    let mut streams: Vec<PlannedStream> = streams
+        .into_iter()
+        .map(|stream| PlannedStream {
+            has_capacity: match session {
+                Some(session_id) => {
+                    stream_manager.can_switch_account(session_id, stream.account_id)
+                }
+                None => stream_manager.has_account_capacity(stream.account_id),
+            },
+            quota_blocked: blocked_accounts.contains(&stream.account_id),
+            stream,
+        })
+        .collect();
+
+    let max_quality = super::stream::get_preferred_quality(conn, channel_id);
+    super::stream::order_by_preferred_quality(&mut streams, max_quality.as_deref(), |planned| {
+        &planned.stream.qualities
+    });
+
+    Ok(ChannelStreamPlan {
+        published,
+        max_quality,
+        streams,
+    })
+}
+
+/// Normalize a server URL for comparing accounts of the same provider
+fn provider_key(server_url: &str) -> String {
+    server_url.trim().trim_end_matches('/').to_lowercase()
+}
+
+/// (xtream_channel_id, account_id, stream_id, qualities JSON, stream_url)
+type SiblingCandidate = (i32, i32, i32, Option<String>, Option<String>);
+
+/// Insert equivalent streams from sibling accounts after each stream
+///
+/// Sibling accounts are other active accounts with the same provider URL. A
+/// provider's stream IDs are shared by all of its lines, so the same
+/// `stream_id` on a sibling account is the same channel. Siblings keep the
+/// original stream's priority; streams already in the list are not repeated.
+fn add_sibling_account_streams(
+    conn: &mut SqliteConnection,
+    streams: Vec<BackupStream>,
+) -> QueryResult<Vec<BackupStream>> {
+    if streams.is_empty() {
+        return Ok(streams);
+    }
+
+    let active_accounts: Vec<(i32, String, String, Vec<u8>)> = accounts::table
+        .filter(accounts::is_active.eq(1))
+        .order(accounts::id.asc())
+        .select((
+            accounts::id.assume_not_null(),
+            accounts::server_url,
+            accounts::username,
+            accounts::password_encrypted,
+        ))
+        .load(conn)?;
+    if active_accounts.len() < 2 {
+        return Ok(streams);
+    }
 
-    Ok(streams)
+    let stream_ids: Vec<i32> = streams.iter().map(|s| s.stream_id).collect();
+    let candidates: Vec<SiblingCandidate> = xtream_channels::table
+        .filter(xtream_channels::stream_id.eq_any(&stream_ids))
+        .order(xtream_channels::account_id.asc())
+        .select((
+            xtream_channels::id.assume_not_null(),
+            xtream_channels::account_id,
+            xtream_channels::stream_id,
+            xtream_channels::qualities,
+            xtream_channels::stream_url,
+        ))
+        .load(conn)?;
+
+    let mut seen: std::collections::HashSet<i32> =
+        streams.iter().map(|s| s.xtream_channel_id).collect();
+    let mut expanded = Vec::with_capacity(streams.len());
+    for stream in streams {
+        let provider = provider_key(&stream.server_url);
+        let siblings: Vec<BackupStream> = candidates
+            .iter()
+            .filter(|(_, account_id, stream_id, _, _)| {
+                *stream_id == stream.stream_id && *account_id != stream.account_id
+            })
+            .filter_map(|(xtream_channel_id, account_id, _, qualities_json, stream_url)| {
+                let (_, server_url, username, password_encrypted) = active_accounts
+                    .iter()
+                    .find(|(id, url, _, _)| id == account_id && provider_key(url) == provider)?;
+                if !seen.insert(*xtream_channel_id) {
+                    return None;
+                }
+                Some(BackupStream {
+                    xtream_channel_id: *xtream_channel_id,
+                    stream_id: stream.stream_id,
+                    stream_priority: stream.stream_priority,
+                    qualities: qualities_json
+                        .as_deref()
+                        .map(qualities_from_json)
+                        .unwrap_or_default(),
+                    server_url: server_url.clone(),
+                    username: username.clone(),
+                    password_encrypted: password_encrypted.clone(),
+                    account_id: *account_id,
+                    stream_url: stream_url.clone(),
+                    catchup: stream.catchup,
+                })
+            })
+            .collect();
+        expanded.push(stream);
+        expanded.extend(siblings);
+    }
+
+    Ok(expanded)
+}
+
+/// Get the per-channel failover notification cooldown from settings
+pub fn get_failover_notification_cooldown(conn: &mut SqliteConnection) -> Duration {
+    let secs = settings::table
+        .filter(settings::key.eq(FAILOVER_NOTIFICATION_COOLDOWN_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| value.parse::<u64>().ok())
+        .unwrap_or(DEFAULT_FAILOVER_NOTIFICATION_COOLDOWN_SECS)
+        .min(MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS);
+    Duration::from_secs(secs)
+}
+
+/// Whether MPEG-TS null packet padding is enabled
+pub fn get_ts_padding_enabled(conn: &mut SqliteConnection) -> bool {
+    settings::table
+        .filter(settings::key.eq(TS_PADDING_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Whether packets after a failover splice are rewritten to stay continuous
+pub fn get_ts_continuity_enabled(conn: &mut SqliteConnection) -> bool {
+    settings::table
+        .filter(settings::key.eq(TS_CONTINUITY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value != "false")
+        .unwrap_or(true)
+}
+
+/// Rolling reconnect interval of each account that has one
+pub fn get_reconnect_intervals(conn: &mut SqliteConnection) -> HashMap<i32, Duration> {
+    accounts::table
+        .filter(accounts::reconnect_interval_minutes.is_not_null())
+        .select((accounts::id, accounts::reconnect_interval_minutes))
+        .load::<(Option<i32>, Option<i32>)>(conn)
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(id, minutes)| {
+            let minutes = u64::try_from(minutes?).ok().filter(|m| *m > 0)?;
+            Some((id?, Duration::from_secs(minutes * 60)))
+        })
+        .collect()
+}
+
+/// Build `count` MPEG-TS null packets (PID 0x1FFF)
+///
+/// Demuxers discard null packets, so they can be sent between any two whole
+/// packets without affecting playback.
+pub fn ts_null_packets(count: usize) -> Bytes {
+    let mut data = Vec::with_capacity(count * TS_PACKET_SIZE);
+    for _ in 0..count {
+        // Sync byte, PID 0x1FFF, payload only, then stuffing
+        data.extend_from_slice(&[0x47, 0x1F, 0xFF, 0x10]);
+        data.resize(data.len() + TS_PACKET_SIZE - 4, 0xFF);
+    }
+    Bytes::from(data)
+}
+
+/// Null packets to pad a client that has received `bytes_sent` bytes
+///
+/// A partial packet at the end of the output is completed with stuffing
+/// bytes first, so the null packets start on a packet boundary.
+fn realigned_null_packets(bytes_sent: u64) -> Bytes {
+    let partial = (bytes_sent % TS_PACKET_SIZE as u64) as usize;
+    if partial == 0 {
+        return ts_null_packets(TS_PADDING_PACKETS);
+    }
+    let mut data = vec![0xFF; TS_PACKET_SIZE - partial];
+    data.extend_from_slice(&ts_null_packets(TS_PADDING_PACKETS));
+    Bytes::from(data)
+}
+
+/// Decide how to report a successful failover on a channel
+///
+/// The first failover is logged and opens a window; later ones within the
+/// cooldown are counted. The first failover after the cooldown reports the
+/// whole burst at once and closes the window.
+fn next_failover_notification(
+    states: &mut HashMap<i32, FailoverNotificationState>,
+    channel_id: i32,
+    now: Instant,
+    cooldown: Duration,
+) -> FailoverNotification {
+    if cooldown.is_zero() {
+        return FailoverNotification::Log;
+    }
+
+    match states.get_mut(&channel_id) {
+        Some(state) if now.duration_since(state.last_logged_at) < cooldown => {
+            state.failovers += 1;
+            FailoverNotification::Suppress
+        }
+        Some(state) if state.failovers > 1 => {
+            state.failovers += 1;
+            let notification = FailoverNotification::Aggregate {
+                count: state.failovers,
+                window: now.duration_since(state.last_logged_at),
+            };
+            states.remove(&channel_id);
+            notification
+        }
+        _ => {
+            states.insert(
+                channel_id,
+                FailoverNotificationState {
+                    last_logged_at: now,
+                    failovers: 1,
+                },
+            );
+            FailoverNotification::Log
+        }
+    }
+}
+
+/// Summary of a channel's failover window, once its cooldown is over and
+/// failovers were suppressed in it
+///
+/// Returning a summary closes the window.
+fn take_due_summary(
+    states: &mut HashMap<i32, FailoverNotificationState>,
+    channel_id: i32,
+    now: Instant,
+    cooldown: Duration,
+) -> Option<FailoverNotification> {
+    let state = states.get(&channel_id)?;
+    let window = now.duration_since(state.last_logged_at);
+    if state.failovers <= 1 || window < cooldown {
+        return None;
+    }
+    let count = state.failovers;
+    states.remove(&channel_id);
+    Some(FailoverNotification::Aggregate { count, window })
+}
+
+/// Register the database used to log failover summaries when a cooldown runs out
+pub fn init(pool: SharedDbPool) {
+    let _ = SUMMARY_POOL.set(pool);
+}
+
+/// Apply the channel's cooldown to a successful failover notification
+///
+/// The first suppressed failover of a burst schedules the summary for the
+/// end of the cooldown.
+fn failover_notification(conn: &mut SqliteConnection, channel_id: i32) -> FailoverNotification {
+    let cooldown = get_failover_notification_cooldown(conn);
+    let mut states = FAILOVER_NOTIFICATIONS
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner());
+    let now = Instant::now();
+    let notification = next_failover_notification(&mut states, channel_id, now, cooldown);
+    if let Some(state) = states.get(&channel_id) {
+        if notification == FailoverNotification::Suppress && state.failovers == 2 {
+            let due = cooldown.saturating_sub(now.duration_since(state.last_logged_at));
+            schedule_summary(channel_id, due, cooldown);
+        }
+    }
+    notification
+}
+
+/// Log a channel's failover summary after `delay`, if failovers are still pending
+fn schedule_summary(channel_id: i32, delay: Duration, cooldown: Duration) {
+    let (Some(pool), Ok(runtime)) = (SUMMARY_POOL.get(), tokio::runtime::Handle::try_current())
+    else {
+        return;
+    };
+    let pool = pool.clone();
+    runtime.spawn(async move {
+        tokio::time::sleep(delay).await;
+        let summary = {
+            let mut states = FAILOVER_NOTIFICATIONS
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            take_due_summary(&mut states, channel_id, Instant::now(), cooldown)
+        };
+        let Some(FailoverNotification::Aggregate { count, window }) = summary else {
+            return;
+        };
+        let pool = pool.read().unwrap_or_else(|e| e.into_inner()).clone();
+        if let Ok(mut conn) = pool.get() {
+            let _ = log_failover_summary(&mut conn, channel_id, count, window);
+        }
+    });
+}
+
+/// Log the summary of the failovers in a channel's cooldown window
+fn log_failover_summary(
+    conn: &mut SqliteConnection,
+    channel_id: i32,
+    count: u32,
+    window: Duration,
+) -> Result<(), diesel::result::Error> {
+    let message = format!(
+        "Channel {} failed over {} times in the last {}",
+        channel_id,
+        count,
+        format_failover_window(window)
+    );
+    let details_json = serde_json::json!({
+        "channelId": channel_id,
+        "failoverCount": count,
+    });
+    crate::commands::logs::log_event_internal(
+        conn,
+        "warn",
+        "stream",
+        &message,
+        Some(&details_json.to_string()),
+    )?;
+    eprintln!("Failover event - warn: {}", message);
+    Ok(())
+}
+
+/// Human-readable span for aggregated failover messages ("12 minutes", "hour", "2 hours")
+fn format_failover_window(window: Duration) -> String {
+    let minutes = (window.as_secs() + 30) / 60;
+    match minutes {
+        0 | 1 => "minute".to_string(),
+        2..=59 => format!("{} minutes", minutes),
+        60..=89 => "hour".to_string(),
+        _ => format!("{} hours", (minutes + 30) / 60),
+    }
 }
 
 /// Log a failover event to the event_log table
@@ -372,7 +984,7 @@ pub fn log_failover_event(
     to_stream_id: Option<i32>,
     reason: &FailureReason,
 ) -> Result<(), diesel::result::Error> {
-    use crate::commands::logs::log_event_internal;
+    use crate::commands::logs::{log_event_internal, log_event_without_notification};
 
     let level_str = if to_stream_id.is_some() {
         "warn"
@@ -380,20 +992,55 @@ pub fn log_failover_event(
         "error"
     };
 
+    let mut aggregated_count = None;
+    let mut notify = true;
     let message_str = if to_stream_id.is_some() {
-        format!("Stream failover for channel {}", channel_id)
+        match failover_notification(conn, channel_id) {
+            FailoverNotification::Log => format!("Stream failover for channel {}", channel_id),
+            FailoverNotification::Suppress => {
+                notify = false;
+                format!("Stream failover for channel {}", channel_id)
+            }
+            FailoverNotification::Aggregate { count, window } => {
+                aggregated_count = Some(count);
+                format!(
+                    "Channel {} failed over {} times in the last {}",
+                    channel_id,
+                    count,
+                    format_failover_window(window)
+                )
+            }
+        }
     } else {
         format!("All streams failed for channel {}", channel_id)
     };
 
+    if to_stream_id.is_none() {
+        crate::hooks::fire(
+            crate::hooks::HookEvent::AllStreamsFailed,
+            serde_json::json!({
+                "channelId": channel_id,
+                "lastStreamId": from_stream_id,
+                "reason": format!("{:?}", reason),
+            }),
+        );
+    }
+
     let details_json = serde_json::json!({
         "channelId": channel_id,
         "fromStreamId": from_stream_id,
         "toStreamId": to_stream_id,
         "reason": format!("{:?}", reason),
+        "failoverCount": aggregated_count,
+        "notificationSuppressed": !notify,
     });
 
-    log_event_internal(
+    let log = if notify {
+        log_event_internal
+    } else {
+        log_event_without_notification
+    };
+    log(
         conn,
         level_str,
         "stream",
@@ -402,8 +1049,17 @@ pub fn log_failover_event(
     )?;
 
     eprintln!(
-        "Failover event - {}: {} (from: {}, to: {:?}, reason: {})",
-        level_str, message_str, from_stream_id, to_stream_id, reason
+        "Failover event - {}: {} (from: {}, to: {:?}, reason: {}){}",
+        level_str,
+        message_str,
+        from_stream_id,
+        to_stream_id,
+        reason,
+        if notify {
+            ""
+        } else {
+            " - notification in cooldown"
+        }
     );
 
     Ok(())
@@ -467,18 +1123,36 @@ pub fn log_mid_stream_failover_event(
     conn: &mut DbPooledConnection,
     event: &FailoverEvent,
 ) -> Result<(), diesel::result::Error> {
-    use crate::commands::logs::log_event_internal;
+    use crate::commands::logs::{log_event_internal, log_event_without_notification};
 
     let level_str = if event.success { "warn" } else { "error" };
 
+    let notification = match event.to_stream_id {
+        Some(_) => failover_notification(conn, event.xmltv_channel_id),
+        None => FailoverNotification::Log,
+    };
+    let mut aggregated_count = None;
+    let notify = notification != FailoverNotification::Suppress;
+
     let message_str = match event.to_stream_id {
-        Some(to_id) => format!(
-            "Mid-stream failover for channel {} (stream {} -> {}) after {:.1}s stall",
-            event.xmltv_channel_id,
-            event.from_stream_id,
-            to_id,
-            event.stall_duration.as_secs_f64()
-        ),
+        Some(to_id) => match notification {
+            FailoverNotification::Log | FailoverNotification::Suppress => format!(
+                "Mid-stream failover for channel {} (stream {} -> {}) after {:.1}s stall",
+                event.xmltv_channel_id,
+                event.from_stream_id,
+                to_id,
+                event.stall_duration.as_secs_f64()
+            ),
+            FailoverNotification::Aggregate { count, window } => {
+                aggregated_count = Some(count);
+                format!(
+                    "Channel {} failed over {} times in the last {}",
+                    event.xmltv_channel_id,
+                    count,
+                    format_failover_window(window)
+                )
+            }
+        },
         None => format!(
             "All streams exhausted for channel {} - session {} failed after {:.1}s stall",
             event.xmltv_channel_id,
@@ -487,6 +1161,18 @@ pub fn log_mid_stream_failover_event(
         ),
     };
 
+    if event.to_stream_id.is_none() {
+        crate::hooks::fire(
+            crate::hooks::HookEvent::AllStreamsFailed,
+            serde_json::json!({
+                "channelId": event.xmltv_channel_id,
+                "lastStreamId": event.from_stream_id,
+                "sessionId": event.session_id,
+                "reason": "mid_stream_stall",
+            }),
+        );
+    }
+
     let details_json = serde_json::json!({
         "failoverType": "mid_stream",
         "sessionId": event.session_id,
@@ -495,9 +1181,16 @@ pub fn log_mid_stream_failover_event(
         "toStreamId": event.to_stream_id,
         "stallDurationSecs": event.stall_duration.as_secs_f64(),
         "success": event.success,
+        "failoverCount": aggregated_count,
+        "notificationSuppressed": !notify,
     });
 
-    log_event_internal(
+    let log = if notify {
+        log_event_internal
+    } else {
+        log_event_without_notification
+    };
+    log(
         conn,
         level_str,
         "stream",
@@ -506,8 +1199,15 @@ pub fn log_mid_stream_failover_event(
     )?;
 
     eprintln!(
-        "Mid-stream failover - {}: {} (session: {})",
-        level_str, message_str, event.session_id
+        "Mid-stream failover - {}: {} (session: {}){}",
+        level_str,
+        message_str,
+        event.session_id,
+        if notify {
+            ""
+        } else {
+            " - notification in cooldown"
+        }
     );
 
     Ok(())
@@ -532,6 +1232,16 @@ pub struct FailoverStream {
     producer_handle: tokio::task::JoinHandle<()>,
     /// Whether the stream has ended
     finished: bool,
+    /// Deadline for the next null packet padding chunk (None = padding off)
+    padding: Option<Pin<Box<tokio::time::Sleep>>>,
+    /// Bytes sent so far; padding is only inserted on a packet boundary
+    bytes_sent: u64,
+    /// Quota accounting, recorded when the stream is dropped (None = not tracked)
+    usage: Option<crate::quota::SessionUsage>,
+    /// Session byte counter shown in the active streams list (None = not tracked)
+    bytes_counter: Option<Arc<std::sync::atomic::AtomicU64>>,
+    /// Bandwidth statistics, recorded when the stream is dropped (None = not tracked)
+    stats: Option<super::stream::SessionStatsRecorder>,
 }
 
 /// Context needed to create backup streams during failover
@@ -545,6 +1255,12 @@ pub struct FailoverContext {
     pub session_id: String,
     /// XMLTV channel ID for event logging
     pub xmltv_channel_id: i32,
+    /// Rolling reconnect interval by account ID
+    pub reconnect_intervals: HashMap<i32, Duration>,
+    /// Keep counters and timestamps continuous across splices
+    pub ts_continuity: bool,
+    /// Highest quality to stream (None for the best available)
+    pub max_quality: Option<String>,
 }
 
 impl FailoverContext {
@@ -559,9 +1275,43 @@ impl FailoverContext {
             current_idx: 0,
             session_id,
             xmltv_channel_id,
+            reconnect_intervals: HashMap::new(),
+            ts_continuity: false,
+            max_quality: None,
         }
     }
 
+    /// Reconnect upstream periodically for accounts with a rolling reconnect interval
+    pub fn with_reconnect_intervals(mut self, intervals: HashMap<i32, Duration>) -> Self {
+        self.reconnect_intervals = intervals;
+        self
+    }
+
+    /// Rewrite the output after each splice so it reads as one stream
+    /// (see `super::continuity`)
+    pub fn with_ts_continuity(mut self, enabled: bool) -> Self {
+        self.ts_continuity = enabled;
+        self
+    }
+
+    /// Cap the quality reported for backup streams
+    pub fn with_max_quality(mut self, max_quality: Option<String>) -> Self {
+        self.max_quality = max_quality;
+        self
+    }
+
+    /// Rolling reconnect interval of the current stream
+    ///
+    /// Catch-up streams are never reconnected: a new connection would
+    /// restart the programme from the beginning.
+    pub fn reconnect_interval(&self) -> Option<Duration> {
+        let stream = self.current_stream()?;
+        if stream.catchup.is_some() {
+            return None;
+        }
+        self.reconnect_intervals.get(&stream.account_id).copied()
+    }
+
     /// Get the current stream
     pub fn current_stream(&self) -> Option<&BackupStream> {
         self.available_streams.get(self.current_idx)
@@ -600,8 +1350,50 @@ impl FailoverStream {
             data_rx,
             producer_handle,
             finished: false,
+            padding: None,
+            bytes_sent: 0,
+            usage: None,
+            bytes_counter: None,
+            stats: None,
+        }
+    }
+
+    /// Record the session in the bandwidth statistics when it ends
+    pub fn with_stats(mut self, stats: super::stream::SessionStatsRecorder) -> Self {
+        self.stats = Some(stats);
+        self
+    }
+
+    /// Add the bytes sent to the client (padding included) to a session counter
+    pub fn with_bytes_counter(mut self, counter: Arc<std::sync::atomic::AtomicU64>) -> Self {
+        self.bytes_counter = Some(counter);
+        self
+    }
+
+    fn count_sent(&mut self, bytes: usize) {
+        self.bytes_sent += bytes as u64;
+        if let Some(counter) = &self.bytes_counter {
+            counter.fetch_add(bytes as u64, std::sync::atomic::Ordering::Relaxed);
         }
     }
+
+    /// Count the session against its account's monthly quota
+    ///
+    /// Upstream data is counted; null packet padding is not.
+    pub fn with_usage(mut self, usage: crate::quota::SessionUsage) -> Self {
+        self.usage = Some(usage);
+        self
+    }
+
+    /// Send MPEG-TS null packets while no upstream data arrives
+    ///
+    /// The first padding goes out immediately if FFmpeg has produced nothing
+    /// yet, then every `TS_PADDING_INTERVAL` without data. When the bytes sent
+    /// so far end mid-packet, the packet is completed with stuffing first.
+    pub fn with_ts_padding(mut self, enabled: bool) -> Self {
+        self.padding = enabled.then(|| Box::pin(tokio::time::sleep(Duration::ZERO)));
+        self
+    }
 }
 
 impl Stream for FailoverStream {
@@ -613,12 +1405,90 @@ impl Stream for FailoverStream {
         }
 
         match Pin::new(&mut self.data_rx).poll_recv(cx) {
-            Poll::Ready(Some(result)) => Poll::Ready(Some(result)),
+            Poll::Ready(Some(result)) => {
+                if let Ok(ref data) = result {
+                    self.count_sent(data.len());
+                    if let Some(usage) = self.usage.as_mut() {
+                        usage.add_bytes(data.len());
+                    }
+                    if let Some(padding) = self.padding.as_mut() {
+                        padding
+                            .as_mut()
+                            .reset(tokio::time::Instant::now() + TS_PADDING_INTERVAL);
+                    }
+                }
+                Poll::Ready(Some(result))
+            }
             Poll::Ready(None) => {
                 self.finished = true;
                 Poll::Ready(None)
             }
-            Poll::Pending => Poll::Pending,
+            Poll::Pending => {
+                let Some(padding) = self.padding.as_mut() else {
+                    return Poll::Pending;
+                };
+                if padding.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                padding
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + TS_PADDING_INTERVAL);
+                let chunk = realigned_null_packets(self.bytes_sent);
+                self.count_sent(chunk.len());
+                Poll::Ready(Some(Ok(chunk)))
+            }
+        }
+    }
+}
+
+/// Response body of a stream whose upstream is still connecting
+///
+/// Sends null packets right away and every `TS_PADDING_INTERVAL` while
+/// `connect` runs the connect and failover loop, then hands over to the
+/// stream it yields. The body ends if no stream could be connected.
+pub struct ConnectingStream {
+    connect: Option<Pin<Box<dyn Future<Output = Option<FailoverStream>> + Send>>>,
+    stream: Option<FailoverStream>,
+    padding: Pin<Box<tokio::time::Sleep>>,
+}
+
+impl ConnectingStream {
+    pub fn new(connect: impl Future<Output = Option<FailoverStream>> + Send + 'static) -> Self {
+        Self {
+            connect: Some(Box::pin(connect)),
+            stream: None,
+            padding: Box::pin(tokio::time::sleep(Duration::ZERO)),
+        }
+    }
+}
+
+impl Stream for ConnectingStream {
+    type Item = Result<Bytes, io::Error>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(stream) = self.stream.as_mut() {
+            return Pin::new(stream).poll_next(cx);
+        }
+        let Some(connect) = self.connect.as_mut() else {
+            return Poll::Ready(None);
+        };
+
+        match connect.as_mut().poll(cx) {
+            Poll::Ready(stream) => {
+                self.connect = None;
+                self.stream = stream;
+                self.poll_next(cx)
+            }
+            Poll::Pending => {
+                if self.padding.as_mut().poll(cx).is_pending() {
+                    return Poll::Pending;
+                }
+                self.padding
+                    .as_mut()
+                    .reset(tokio::time::Instant::now() + TS_PADDING_INTERVAL);
+                // Whole packets only, so the stream starts on a packet boundary
+                Poll::Ready(Some(Ok(ts_null_packets(TS_PADDING_PACKETS))))
+            }
         }
     }
 }
@@ -627,6 +1497,12 @@ impl Drop for FailoverStream {
     fn drop(&mut self) {
         // Abort the producer task when the stream is dropped
         self.producer_handle.abort();
+        if let Some(usage) = self.usage.take() {
+            usage.finish();
+        }
+        if let Some(stats) = self.stats.take() {
+            stats.finish();
+        }
     }
 }
 
@@ -641,6 +1517,10 @@ pub struct FailoverEvent {
     pub from_stream_id: i32,
     /// New stream ID (None if all streams exhausted)
     pub to_stream_id: Option<i32>,
+    /// xtream_channels ID of the previous stream
+    pub from_xtream_channel_id: i32,
+    /// xtream_channels ID of the new stream
+    pub to_xtream_channel_id: Option<i32>,
     /// Duration of the stall before failover
     pub stall_duration: Duration,
     /// Whether failover was successful
@@ -650,6 +1530,194 @@ pub struct FailoverEvent {
 /// Callback type for failover events
 pub type FailoverCallback = Arc<dyn Fn(FailoverEvent) + Send + Sync>;
 
+/// Re-authenticate and verify a stream URL after the provider rejected it
+///
+/// Providers with expiring links reject the old (redirected) URL once the
+/// token expires. Logging in again through the Xtream API and requesting the
+/// stream URL afresh issues a new token. Returns the URL to reconnect to.
+/// M3U streams have no login to repeat and are not refreshed.
+pub async fn refresh_stream_url(
+    stream: &BackupStream,
+    credential_manager: &crate::credentials::CredentialManager,
+) -> Result<String, FailureReason> {
+    if stream.stream_url.is_some() {
+        return Err(FailureReason::ConnectionError(
+            "M3U streams cannot be re-authenticated".to_string(),
+        ));
+    }
+
+    let password = credential_manager
+        .retrieve_password(&stream.account_id.to_string(), &stream.password_encrypted)
+        .map_err(|e| FailureReason::ConnectionError(format!("Credential error: {}", e)))?;
+
+    let client = crate::xtream::XtreamClient::new(&stream.server_url, &stream.username, &password)
+        .map_err(|e| FailureReason::ConnectionError(e.to_string()))?;
+    client
+        .authenticate()
+        .await
+        .map_err(|e| FailureReason::ConnectionError(format!("Re-authentication failed: {}", e)))?;
+
+    let url = stream.xtream_url(&password);
+    let http = crate::dns::configure(reqwest::Client::builder())
+        .connect_timeout(FAILOVER_CONNECT_TIMEOUT)
+        .timeout(STREAM_READ_TIMEOUT)
+        .build()
+        .map_err(|e| FailureReason::ConnectionError(e.to_string()))?;
+    let response = crate::xtream::request_headers::for_url(&url)
+        .apply(http.get(&url))
+        .send()
+        .await
+        .map_err(|e| FailureReason::from_reqwest_error(&e))?;
+    if !response.status().is_success() {
+        return Err(FailureReason::from_http_status(response.status()));
+    }
+
+    Ok(url)
+}
+
+/// Reconnect the session's current stream with a fresh token
+///
+/// Returns the new stream, or `None` if the refresh failed (the caller then
+/// fails over as usual).
+async fn reconnect_with_fresh_token(
+    ctx: &FailoverContext,
+    stream_manager: &Arc<super::stream::StreamManager>,
+    credential_manager: &crate::credentials::CredentialManager,
+) -> Option<super::buffer::BufferedStream> {
+    use super::buffer::{BufferConfig, BufferedStream};
+
+    let stream = ctx.current_stream()?;
+    eprintln!(
+        "[INFO] stream:{} upstream rejected stream {} (401/403), refreshing token",
+        ctx.session_id, stream.stream_id
+    );
+
+    let url = match refresh_stream_url(stream, credential_manager).await {
+        Ok(url) => url,
+        Err(reason) => {
+            eprintln!(
+                "[WARN] stream:{} token refresh failed for stream {}: {}",
+                ctx.session_id, stream.stream_id, reason
+            );
+            return None;
+        }
+    };
+
+    match BufferedStream::new(
+        &url,
+        BufferConfig::default(),
+        ctx.session_id.clone(),
+        stream_manager.clone(),
+    ) {
+        Ok(new_stream) => {
+            eprintln!(
+                "[INFO] stream:{} token refreshed, resumed stream {}",
+                ctx.session_id, stream.stream_id
+            );
+            Some(new_stream)
+        }
+        Err(e) => {
+            eprintln!(
+                "[ERROR] stream:{} failed to restart stream after token refresh: {}",
+                ctx.session_id, e
+            );
+            None
+        }
+    }
+}
+
+/// Whether a session may refresh its stream token now
+fn token_refresh_allowed(last_refresh: Option<Instant>) -> bool {
+    last_refresh.is_none_or(|at| at.elapsed() >= TOKEN_REFRESH_MIN_INTERVAL)
+}
+
+/// Bytes still to send before `sent` bytes end on an MPEG-TS packet boundary
+fn bytes_to_packet_boundary(sent: u64) -> usize {
+    let packet = TS_PACKET_SIZE as u64;
+    ((packet - sent % packet) % packet) as usize
+}
+
+/// Rolling reconnect state of a session
+///
+/// When the interval is up, a second connection to the current stream is
+/// opened. Once it has produced data, the old connection is read up to the
+/// next packet boundary and playback continues from the new one. A new
+/// connection without data within `HANDOFF_TIMEOUT` is dropped and retried
+/// after `HANDOFF_RETRY_DELAY`.
+struct RollingReconnect {
+    interval: Option<Duration>,
+    /// When the next handoff starts, or when a pending one is given up
+    deadline: Option<tokio::time::Instant>,
+    /// New connection waiting for its first data
+    pending: Option<super::buffer::BufferedStream>,
+    /// First data of the new connection, sent when the handoff completes
+    ready: Option<Bytes>,
+}
+
+impl RollingReconnect {
+    fn new(interval: Option<Duration>) -> Self {
+        Self {
+            interval,
+            deadline: interval.map(|interval| tokio::time::Instant::now() + interval),
+            pending: None,
+            ready: None,
+        }
+    }
+
+    /// Start over after the session switched streams
+    fn reset(&mut self, interval: Option<Duration>) {
+        self.abandon();
+        *self = Self::new(interval);
+    }
+
+    /// Wait for the deadline (forever if there is none)
+    fn timer(&self) -> impl Future<Output = ()> {
+        let deadline = self.deadline;
+        async move {
+            match deadline {
+                Some(deadline) => tokio::time::sleep_until(deadline).await,
+                None => std::future::pending().await,
+            }
+        }
+    }
+
+    /// Next chunk of the new connection (forever pending without one)
+    async fn next_pending_chunk(&mut self) -> Option<Result<Bytes, io::Error>> {
+        match self.pending.as_mut() {
+            Some(stream) => futures_util::StreamExt::next(stream).await,
+            None => std::future::pending().await,
+        }
+    }
+
+    /// Whether a new connection is open or has data
+    fn in_progress(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Drop the new connection and try again later
+    fn abandon(&mut self) {
+        if let Some(stream) = self.pending.take() {
+            stream.close_keeping_session();
+        }
+        self.ready = None;
+        self.deadline = self
+            .interval
+            .map(|_| tokio::time::Instant::now() + HANDOFF_RETRY_DELAY);
+    }
+
+    /// Replace `current` with the new connection, if one is open
+    ///
+    /// Returns the new connection's first data (empty if it has none yet).
+    fn hand_over(&mut self, current: &mut super::buffer::BufferedStream) -> Option<Bytes> {
+        let stream = self.pending.take()?;
+        std::mem::replace(current, stream).close_keeping_session();
+        self.deadline = self
+            .interval
+            .map(|interval| tokio::time::Instant::now() + interval);
+        Some(self.ready.take().unwrap_or_default())
+    }
+}
+
 /// Create a FailoverStream with mid-stream failover capability (Story 4.7)
 ///
 /// This function creates a stream that:
@@ -658,6 +1726,12 @@ pub type FailoverCallback = Arc<dyn Fn(FailoverEvent) + Send + Sync>;
 /// 3. When failover is triggered, creates a new BufferedStream with backup URL
 /// 4. Continues seamlessly from the new stream
 ///
+/// If the provider rejects the current stream with 401/403 (an expired
+/// token), the same stream is reconnected with a fresh token first.
+///
+/// Accounts with a rolling reconnect interval get a fresh upstream connection
+/// that often, handed over without a gap (see `RollingReconnect`).
+///
 /// # Arguments
 /// * `initial_stream` - The initial BufferedStream to read from
 /// * `context` - Failover context with backup streams
@@ -675,7 +1749,6 @@ pub fn create_failover_stream(
     on_failover: Option<FailoverCallback>,
 ) -> FailoverStream {
     use super::buffer::{BufferedStream, BufferConfig};
-    use super::stream::build_stream_url;
 
     let (data_tx, data_rx) = mpsc::channel(FAILOVER_CHANNEL_CAPACITY);
 
@@ -684,22 +1757,91 @@ pub fn create_failover_stream(
         let mut ctx = context;
         let mut failover_rx = current_stream.failover_receiver();
         let mut stall_start: Option<Instant> = None; // Track when stall started (H2 fix)
+        let mut last_token_refresh: Option<Instant> = None;
+        let mut rolling = RollingReconnect::new(ctx.reconnect_interval());
+        // Bytes forwarded from the current connection, to hand off on a packet boundary
+        let mut forwarded: u64 = 0;
+        let mut continuity = TsContinuity::new(ctx.ts_continuity);
 
         loop {
+            // Expired stream token: resume the same stream rather than failing over
+            if token_refresh_allowed(last_token_refresh) && current_stream.auth_rejected().await {
+                last_token_refresh = Some(Instant::now());
+                if let Some(new_stream) =
+                    reconnect_with_fresh_token(&ctx, &stream_manager, &credential_manager).await
+                {
+                    stall_start = None;
+                    drop(current_stream);
+                    current_stream = new_stream;
+                    failover_rx = current_stream.failover_receiver();
+                    rolling.reset(ctx.reconnect_interval());
+                    forwarded = 0;
+                    continuity.splice();
+                    continue;
+                }
+            }
+
             tokio::select! {
                 // Read data from current stream
                 chunk = futures_util::StreamExt::next(&mut current_stream) => {
                     match chunk {
-                        Some(Ok(data)) => {
+                        Some(Ok(mut data)) => {
                             stall_start = None; // Reset stall tracking on successful data (H2 fix)
-                            if data_tx.send(Ok(data)).await.is_err() {
+                            let mut hand_off = false;
+                            if rolling.ready.is_some() {
+                                // Forward only up to the packet boundary, then switch
+                                let remaining = bytes_to_packet_boundary(forwarded);
+                                if data.len() >= remaining {
+                                    data.truncate(remaining);
+                                    hand_off = true;
+                                }
+                            }
+                            forwarded += data.len() as u64;
+                            let data = continuity.process(data);
+                            if !data.is_empty() && data_tx.send(Ok(data)).await.is_err() {
                                 // Consumer dropped, exit
                                 break;
                             }
+                            if hand_off {
+                                if let Some(first) = rolling.hand_over(&mut current_stream) {
+                                    eprintln!(
+                                        "[INFO] stream:{} rolling reconnect: switched to new upstream connection",
+                                        ctx.session_id
+                                    );
+                                    failover_rx = current_stream.failover_receiver();
+                                    forwarded = first.len() as u64;
+                                    continuity.splice();
+                                    let first = continuity.process(first);
+                                    if !first.is_empty() && data_tx.send(Ok(first)).await.is_err() {
+                                        break;
+                                    }
+                                }
+                            }
                         }
                         Some(Err(e)) => {
+                            if let Some(first) = rolling.hand_over(&mut current_stream) {
+                                // The old connection failed during a rolling reconnect
+                                eprintln!(
+                                    "[INFO] stream:{} upstream read error during rolling reconnect, switching to new connection: {}",
+                                    ctx.session_id, e
+                                );
+                                failover_rx = current_stream.failover_receiver();
+                                forwarded = first.len() as u64;
+                                continuity.splice();
+                                let first = continuity.process(first);
+                                if !first.is_empty() && data_tx.send(Ok(first)).await.is_err() {
+                                    break;
+                                }
+                                continue;
+                            }
                             // Stream error - try failover
                             eprintln!("[ERROR] stream:{} read error: {}", ctx.session_id, e);
+                            if token_refresh_allowed(last_token_refresh)
+                                && current_stream.auth_rejected().await
+                            {
+                                // Token refresh at the top of the loop
+                                continue;
+                            }
                             if !ctx.has_more_backups() {
                                 let _ = data_tx.send(Err(e)).await;
                                 break;
@@ -707,11 +1849,94 @@ pub fn create_failover_stream(
                             // Fall through to failover
                         }
                         None => {
+                            if let Some(first) = rolling.hand_over(&mut current_stream) {
+                                // The provider cut the old connection first
+                                eprintln!(
+                                    "[INFO] stream:{} upstream ended during rolling reconnect, switching to new connection",
+                                    ctx.session_id
+                                );
+                                failover_rx = current_stream.failover_receiver();
+                                forwarded = first.len() as u64;
+                                continuity.splice();
+                                let first = continuity.process(first);
+                                if !first.is_empty() && data_tx.send(Ok(first)).await.is_err() {
+                                    break;
+                                }
+                                continue;
+                            }
+                            if token_refresh_allowed(last_token_refresh)
+                                && current_stream.auth_rejected().await
+                            {
+                                // FFmpeg gave up on an expired token - refresh it
+                                continue;
+                            }
                             // Stream ended normally
                             break;
                         }
                     }
                 }
+                // Rolling reconnect: open the new connection, or give up on it
+                _ = rolling.timer() => {
+                    if rolling.in_progress() {
+                        eprintln!(
+                            "[WARN] stream:{} rolling reconnect: new connection produced no data, retrying in {}s",
+                            ctx.session_id, HANDOFF_RETRY_DELAY.as_secs()
+                        );
+                        rolling.abandon();
+                        continue;
+                    }
+                    let url = match ctx.current_stream().map(|s| s.upstream_url(&credential_manager)) {
+                        Some(Ok(url)) => url,
+                        Some(Err(e)) => {
+                            eprintln!("[ERROR] stream:{} rolling reconnect: {}", ctx.session_id, e);
+                            rolling.abandon();
+                            continue;
+                        }
+                        None => {
+                            rolling.reset(None);
+                            continue;
+                        }
+                    };
+                    match BufferedStream::new(
+                        &url,
+                        BufferConfig::default(),
+                        ctx.session_id.clone(),
+                        stream_manager.clone(),
+                    ) {
+                        Ok(new_stream) => {
+                            eprintln!(
+                                "[INFO] stream:{} rolling reconnect: opening new upstream connection",
+                                ctx.session_id
+                            );
+                            rolling.pending = Some(new_stream);
+                            rolling.deadline = Some(tokio::time::Instant::now() + HANDOFF_TIMEOUT);
+                        }
+                        Err(e) => {
+                            eprintln!(
+                                "[ERROR] stream:{} rolling reconnect: failed to start new connection: {}",
+                                ctx.session_id, e
+                            );
+                            rolling.abandon();
+                        }
+                    }
+                }
+                // Rolling reconnect: first data of the new connection
+                chunk = rolling.next_pending_chunk(), if rolling.ready.is_none() => {
+                    match chunk {
+                        Some(Ok(data)) => {
+                            // Switched over with the next data of the current connection
+                            rolling.deadline = None;
+                            rolling.ready = Some(data);
+                        }
+                        Some(Err(_)) | None => {
+                            eprintln!(
+                                "[WARN] stream:{} rolling reconnect: new connection failed, retrying in {}s",
+                                ctx.session_id, HANDOFF_RETRY_DELAY.as_secs()
+                            );
+                            rolling.abandon();
+                        }
+                    }
+                }
                 // Monitor failover signal
                 result = failover_rx.changed() => {
                     if result.is_err() {
@@ -722,7 +1947,12 @@ pub fn create_failover_stream(
                         // Signal was false, continue
                         continue;
                     }
-
+                    if token_refresh_allowed(last_token_refresh)
+                        && current_stream.auth_rejected().await
+                    {
+                        // Stalled on an expired token - refresh it at the top of the loop
+                        continue;
+                    }
                     // Track when stall started for accurate duration (H2 fix)
                     let stall_duration = match stall_start {
                         Some(start) => start.elapsed(),
@@ -743,6 +1973,9 @@ pub fn create_failover_stream(
                         let from_stream_id = ctx.current_stream()
                             .map(|s| s.stream_id)
                             .unwrap_or(0);
+                        let from_xtream_channel_id = ctx.current_stream()
+                            .map(|s| s.xtream_channel_id)
+                            .unwrap_or(0);
 
                         eprintln!(
                             "[WARN] stream:{} ALL STREAMS EXHAUSTED - channel:{}, tried:{} streams",
@@ -756,6 +1989,8 @@ pub fn create_failover_stream(
                                 xmltv_channel_id: ctx.xmltv_channel_id,
                                 from_stream_id,
                                 to_stream_id: None, // No backup available
+                                from_xtream_channel_id,
+                                to_xtream_channel_id: None,
                                 stall_duration, // H2 fix: actual duration, not hardcoded
                                 success: false, // Exhaustion is a failure
                             });
@@ -774,14 +2009,12 @@ pub fn create_failover_stream(
                         );
 
                         // Read remaining data without checking failover (it would just loop)
-                        loop {
-                            match futures_util::StreamExt::next(&mut current_stream).await {
-                                Some(Ok(data)) => {
-                                    if data_tx.send(Ok(data)).await.is_err() {
-                                        break; // Consumer dropped
-                                    }
-                                }
-                                Some(Err(_)) | None => break, // Stream ended
+                        while let Some(Ok(data)) =
+                            futures_util::StreamExt::next(&mut current_stream).await
+                        {
+                            let data = continuity.process(data);
+                            if !data.is_empty() && data_tx.send(Ok(data)).await.is_err() {
+                                break; // Consumer dropped
                             }
                         }
 
@@ -795,6 +2028,9 @@ pub fn create_failover_stream(
                     let from_stream_id = ctx.current_stream()
                         .map(|s| s.stream_id)
                         .unwrap_or(0);
+                    let from_xtream_channel_id = ctx.current_stream()
+                        .map(|s| s.xtream_channel_id)
+                        .unwrap_or(0);
 
                     ctx.advance();
 
@@ -806,27 +2042,25 @@ pub fn create_failover_stream(
                         }
                     };
 
-                    // Decrypt password for backup stream
-                    let password = match credential_manager.retrieve_password(
-                        &backup.account_id.to_string(),
-                        &backup.password_encrypted,
-                    ) {
-                        Ok(p) => p,
+                    // Don't take a slot the backup's account doesn't have free
+                    if !stream_manager.can_switch_account(&ctx.session_id, backup.account_id) {
+                        eprintln!(
+                            "[INFO] stream:{} skipping backup stream {}: account {} is at its connection limit",
+                            ctx.session_id, backup.stream_id, backup.account_id
+                        );
+                        continue;
+                    }
+
+                    // Build backup stream URL (decrypts the password for Xtream streams)
+                    let backup_url = match backup.upstream_url(&credential_manager) {
+                        Ok(url) => url,
                         Err(e) => {
-                            eprintln!("[ERROR] stream:{} credential error: {}", ctx.session_id, e);
+                            eprintln!("[ERROR] stream:{} {}", ctx.session_id, e);
                             // Try next backup if available
                             continue;
                         }
                     };
 
-                    // Build backup stream URL
-                    let backup_url = build_stream_url(
-                        &backup.server_url,
-                        &backup.username,
-                        &password,
-                        backup.stream_id,
-                    );
-
                     eprintln!(
                         "[INFO] stream:{} switching to backup stream {} (priority {})",
                         ctx.session_id, backup.stream_id, backup.stream_priority
@@ -854,17 +2088,21 @@ pub fn create_failover_stream(
                             xmltv_channel_id: ctx.xmltv_channel_id,
                             from_stream_id,
                             to_stream_id: Some(backup.stream_id),
+                            from_xtream_channel_id,
+                            to_xtream_channel_id: Some(backup.xtream_channel_id),
                             stall_duration, // H2 fix: actual duration, not hardcoded
                             success: true,
                         });
                     }
 
                     // H1 fix: Update session to record the failover
-                    let backup_quality = backup.qualities.first()
-                        .cloned()
-                        .unwrap_or_else(|| "SD".to_string());
+                    let backup_quality = super::stream::select_quality(
+                        &backup.qualities,
+                        ctx.max_quality.as_deref(),
+                    );
                     stream_manager.update_session(&ctx.session_id, |session| {
                         session.record_failover(backup.stream_id, backup_quality.clone());
+                        session.account_id = Some(backup.account_id);
                     });
 
                     eprintln!(
@@ -879,6 +2117,9 @@ pub fn create_failover_stream(
                     drop(current_stream);
                     current_stream = new_stream;
                     failover_rx = current_stream.failover_receiver();
+                    rolling.reset(ctx.reconnect_interval());
+                    forwarded = 0;
+                    continuity.splice();
                 }
             }
         }
@@ -902,6 +2143,8 @@ mod tests {
             username: "testuser".to_string(),
             password_encrypted: vec![],
             account_id: 1,
+            stream_url: None,
+            catchup: None,
         }
     }
 
@@ -917,6 +2160,213 @@ mod tests {
         assert_eq!(stream.qualities.len(), 2);
     }
 
+    #[test]
+    fn test_upstream_url_prefers_stored_m3u_url() {
+        let credentials = crate::credentials::CredentialManager::new(std::env::temp_dir());
+        let mut stream = create_test_stream(100, 0);
+        stream.stream_url = Some("http://example.com/live/100.ts".to_string());
+        assert_eq!(
+            stream.upstream_url(&credentials).unwrap(),
+            "http://example.com/live/100.ts"
+        );
+    }
+
+    #[test]
+    fn test_token_refresh_allowed() {
+        assert!(token_refresh_allowed(None));
+        assert!(!token_refresh_allowed(Some(Instant::now())));
+        let long_ago = Instant::now().checked_sub(TOKEN_REFRESH_MIN_INTERVAL);
+        if let Some(at) = long_ago {
+            assert!(token_refresh_allowed(Some(at)));
+        }
+    }
+
+    #[test]
+    fn test_bytes_to_packet_boundary() {
+        assert_eq!(bytes_to_packet_boundary(0), 0);
+        assert_eq!(bytes_to_packet_boundary(100), 88);
+        assert_eq!(bytes_to_packet_boundary(188 * 5), 0);
+        assert_eq!(bytes_to_packet_boundary(188 * 5 + 1), 187);
+    }
+
+    #[test]
+    fn test_reconnect_intervals() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, reconnect_interval_minutes) VALUES
+                (1, 'Cut hourly', 'http://a.local', 'a', x'', 55), (2, 'No limit', 'http://b.local', 'b', x'', NULL)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let intervals = get_reconnect_intervals(&mut conn);
+        assert_eq!(intervals.len(), 1);
+        assert_eq!(intervals[&1], Duration::from_secs(55 * 60));
+
+        let mut live = create_test_stream(100, 0);
+        live.account_id = 1;
+        let mut other = create_test_stream(200, 1);
+        other.account_id = 2;
+        let mut ctx = FailoverContext::new(vec![live.clone(), other], "s".to_string(), 1)
+            .with_reconnect_intervals(intervals.clone());
+        assert_eq!(ctx.reconnect_interval(), Some(Duration::from_secs(55 * 60)));
+        ctx.advance();
+        assert_eq!(ctx.reconnect_interval(), None);
+
+        // Reconnecting would restart a catch-up programme
+        live.catchup = Some(crate::server::stream::CatchupWindow {
+            start: chrono::Utc::now(),
+            duration_minutes: 60,
+        });
+        let ctx = FailoverContext::new(vec![live], "s".to_string(), 1)
+            .with_reconnect_intervals(intervals);
+        assert_eq!(ctx.reconnect_interval(), None);
+    }
+
+    #[test]
+    fn test_add_sibling_account_streams() {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, is_active) VALUES
+                (1, 'Line A', 'http://provider.local:8080', 'a', x'', 1),
+                (2, 'Line B', 'http://PROVIDER.local:8080/', 'b', x'', 1),
+                (3, 'Other', 'http://other.local', 'c', x'', 1),
+                (4, 'Inactive', 'http://provider.local:8080', 'd', x'', 0)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+                (10, 1, 100, 'ESPN'), (20, 2, 100, 'ESPN'), (30, 3, 100, 'ESPN'),
+                (40, 4, 100, 'ESPN'), (11, 1, 200, 'CNN')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let mut primary = create_test_stream(100, 0);
+        primary.xtream_channel_id = 10;
+        primary.server_url = "http://provider.local:8080".to_string();
+        let mut backup = create_test_stream(200, 1);
+        backup.xtream_channel_id = 11;
+        backup.server_url = "http://provider.local:8080".to_string();
+
+        let streams = add_sibling_account_streams(&mut conn, vec![primary, backup]).unwrap();
+        let ids: Vec<i32> = streams.iter().map(|s| s.xtream_channel_id).collect();
+        // Line B's copy follows the primary; other providers and inactive lines are skipped
+        assert_eq!(ids, vec![10, 20, 11]);
+        assert_eq!(streams[1].account_id, 2);
+        assert_eq!(streams[1].username, "b");
+        assert_eq!(streams[1].stream_priority, 0);
+    }
+
+    #[test]
+    fn test_catchup_streams() {
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Line A", "http://provider.local");
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name, tv_archive, tv_archive_duration) VALUES
+                (10, 1, 100, 'No archive', 0, 0), (11, 1, 101, 'Short archive', 1, 1),
+                (12, 1, 102, 'Week archive', 1, 7), (13, 1, 103, 'M3U', 1, 7)",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let streams: Vec<BackupStream> = (10..=13)
+            .map(|id| {
+                let mut stream = create_test_stream(id + 90, 0);
+                stream.xtream_channel_id = id;
+                if id == 13 {
+                    stream.stream_url = Some("http://example.com/103.ts".to_string());
+                }
+                stream
+            })
+            .collect();
+        let window = crate::server::stream::CatchupWindow {
+            start: chrono::Utc::now() - chrono::Duration::days(3),
+            duration_minutes: 60,
+        };
+
+        let kept = catchup_streams(&mut conn, streams, window).unwrap();
+        let ids: Vec<i32> = kept.iter().map(|s| s.xtream_channel_id).collect();
+        assert_eq!(ids, vec![12]);
+        assert_eq!(kept[0].catchup, Some(window));
+        assert!(kept[0]
+            .xtream_url("pass")
+            .starts_with("http://test-102.local:8080/timeshift/testuser/pass/60/"));
+    }
+
+    #[test]
+    fn test_ts_null_packets() {
+        let data = ts_null_packets(3);
+        assert_eq!(data.len(), 3 * TS_PACKET_SIZE);
+        for packet in data.chunks(TS_PACKET_SIZE) {
+            assert_eq!(&packet[..4], &[0x47, 0x1F, 0xFF, 0x10]);
+            assert!(packet[4..].iter().all(|&b| b == 0xFF));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_failover_stream_ts_padding() {
+        use futures_util::StreamExt;
+
+        let (data_tx, data_rx) = mpsc::channel(4);
+        let producer = tokio::spawn(async {});
+        let mut stream = FailoverStream::new(data_rx, producer).with_ts_padding(true);
+
+        // Padding goes out immediately while upstream is silent
+        let padding = stream.next().await.unwrap().unwrap();
+        assert_eq!(padding, ts_null_packets(TS_PADDING_PACKETS));
+
+        // Upstream data passes through unchanged
+        data_tx.send(Ok(Bytes::from_static(&[0x47; 100]))).await.unwrap();
+        assert_eq!(stream.next().await.unwrap().unwrap().len(), 100);
+
+        // Mid-packet, padding completes the packet before the null packets
+        let padding = stream.next().await.unwrap().unwrap();
+        assert_eq!(padding.len(), 88 + TS_PADDING_PACKETS * TS_PACKET_SIZE);
+        assert!(padding[..88].iter().all(|&b| b == 0xFF));
+        assert_eq!(&padding[88..], &ts_null_packets(TS_PADDING_PACKETS)[..]);
+        assert_eq!(stream.bytes_sent % TS_PACKET_SIZE as u64, 0);
+
+        // Stream ends when the producer is gone
+        drop(data_tx);
+        assert!(stream.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connecting_stream_pads_until_connected() {
+        use futures_util::StreamExt;
+        use tokio::sync::oneshot;
+
+        let (connected_tx, connected_rx) = oneshot::channel();
+        let mut body = ConnectingStream::new(async move { connected_rx.await.ok() });
+
+        // Padding goes out before the upstream has connected
+        let padding = body.next().await.unwrap().unwrap();
+        assert_eq!(padding, ts_null_packets(TS_PADDING_PACKETS));
+
+        // Then the connected stream is forwarded
+        let (data_tx, data_rx) = mpsc::channel(4);
+        let stream = FailoverStream::new(data_rx, tokio::spawn(async {}));
+        assert!(connected_tx.send(stream).is_ok());
+        data_tx
+            .send(Ok(Bytes::from_static(&[0x47; 188])))
+            .await
+            .unwrap();
+        assert_eq!(body.next().await.unwrap().unwrap().len(), 188);
+
+        drop(data_tx);
+        assert!(body.next().await.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_connecting_stream_ends_when_connect_fails() {
+        use futures_util::StreamExt;
+
+        let mut body = ConnectingStream::new(async { None });
+        assert!(body.next().await.is_none());
+    }
+
     // =========================================================================
     // FailoverState Tests
     // =========================================================================
@@ -1335,6 +2785,8 @@ mod tests {
             xmltv_channel_id: 1,
             from_stream_id: 100,
             to_stream_id: Some(101),
+            from_xtream_channel_id: 10,
+            to_xtream_channel_id: Some(11),
             stall_duration: Duration::from_secs(5),
             success: true,
         };
@@ -1353,6 +2805,8 @@ mod tests {
             xmltv_channel_id: 1,
             from_stream_id: 102,
             to_stream_id: None, // All streams exhausted
+            from_xtream_channel_id: 12,
+            to_xtream_channel_id: None,
             stall_duration: Duration::from_secs(5),
             success: false,
         };
@@ -1360,4 +2814,106 @@ mod tests {
         assert!(event.to_stream_id.is_none());
         assert!(!event.success);
     }
+
+    // =========================================================================
+    // Failover Notification Cooldown Tests
+    // =========================================================================
+
+    #[test]
+    fn test_failover_notification_cooldown() {
+        let mut states = HashMap::new();
+        let cooldown = Duration::from_secs(300);
+        let start = Instant::now();
+
+        assert_eq!(
+            next_failover_notification(&mut states, 1, start, cooldown),
+            FailoverNotification::Log
+        );
+        for i in 1..=4 {
+            assert_eq!(
+                next_failover_notification(
+                    &mut states,
+                    1,
+                    start + Duration::from_secs(i * 10),
+                    cooldown
+                ),
+                FailoverNotification::Suppress
+            );
+        }
+        // Other channels have their own cooldown
+        assert_eq!(
+            next_failover_notification(&mut states, 2, start + Duration::from_secs(60), cooldown),
+            FailoverNotification::Log
+        );
+
+        // After the cooldown the burst is reported once, counting the failover
+        // that opened it and the one that closes it, then counting starts over
+        assert_eq!(
+            next_failover_notification(&mut states, 1, start + Duration::from_secs(600), cooldown),
+            FailoverNotification::Aggregate {
+                count: 6,
+                window: Duration::from_secs(600)
+            }
+        );
+        assert_eq!(
+            next_failover_notification(&mut states, 1, start + Duration::from_secs(1200), cooldown),
+            FailoverNotification::Log
+        );
+    }
+
+    #[test]
+    fn test_failover_summary_due_after_cooldown() {
+        let mut states = HashMap::new();
+        let cooldown = Duration::from_secs(300);
+        let start = Instant::now();
+
+        // The logged failover opens the window and is part of the summary's count
+
+        next_failover_notification(&mut states, 1, start, cooldown);
+        assert_eq!(take_due_summary(&mut states, 1, start, cooldown), None);
+        for i in 1..=3 {
+            next_failover_notification(
+                &mut states,
+                1,
+                start + Duration::from_secs(i * 10),
+                cooldown,
+            );
+        }
+        assert_eq!(
+            take_due_summary(&mut states, 1, start + Duration::from_secs(60), cooldown),
+            None
+        );
+        assert_eq!(
+            take_due_summary(&mut states, 1, start + Duration::from_secs(300), cooldown),
+            Some(FailoverNotification::Aggregate {
+                count: 4,
+                window: Duration::from_secs(300)
+            })
+        );
+        // The summary is only logged once
+        assert_eq!(
+            take_due_summary(&mut states, 1, start + Duration::from_secs(600), cooldown),
+            None
+        );
+    }
+
+    #[test]
+    fn test_failover_notification_cooldown_disabled() {
+        let mut states = HashMap::new();
+        let now = Instant::now();
+        for _ in 0..3 {
+            assert_eq!(
+                next_failover_notification(&mut states, 1, now, Duration::ZERO),
+                FailoverNotification::Log
+            );
+        }
+    }
+
+    #[test]
+    fn test_format_failover_window() {
+        assert_eq!(format_failover_window(Duration::from_secs(45)), "minute");
+        assert_eq!(format_failover_window(Duration::from_secs(12 * 60)), "12 minutes");
+        assert_eq!(format_failover_window(Duration::from_secs(3600)), "hour");
+        assert_eq!(format_failover_window(Duration::from_secs(3 * 3600)), "3 hours");
+    }
 }
@@ -0,0 +1,273 @@
+//! Running HTTP server handle
+//!
+//! Keeps the axum server task and its shutdown signal so the server can be
+//! restarted on a new port or bind address without restarting the app. A
+//! restart stops accepting connections, asks active stream sessions to stop,
+//! waits up to `DRAIN_TIMEOUT` for connections to drain, and then binds the
+//! configured address and port.
+//!
+//! The SSDP announcer (`super::ssdp`) runs alongside the server and is
+//! stopped and restarted with it, so announcements follow the bound address.
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::Serialize;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+
+use super::{routes, ssdp, AppState, ServerError};
+
+/// How long a restart waits for open connections to finish
+const DRAIN_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// How long a stop waits for the SSDP goodbye announcements
+const SSDP_STOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Outcome of a server restart
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerRestartResult {
+    /// Port the previous server listened on (None if it was not running)
+    pub previous_port: Option<u16>,
+    pub port: u16,
+    /// Stream sessions that were active when the restart began
+    pub stopped_streams: usize,
+    /// Whether connections were still open when the drain timeout expired
+    pub forced: bool,
+}
+
+/// Outcome of stopping the server (on quit)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServerStopResult {
+    /// Stream sessions that were active when the server was stopped
+    pub stopped_streams: usize,
+    /// Whether connections were still open when the drain timeout expired
+    pub forced: bool,
+}
+
+struct RunningServer {
+    addr: SocketAddr,
+    shutdown: oneshot::Sender<()>,
+    task: JoinHandle<Result<(), ServerError>>,
+    ssdp_stop: oneshot::Sender<()>,
+    ssdp_task: JoinHandle<()>,
+}
+
+/// Handle to the running HTTP server, managed as Tauri state
+#[derive(Clone, Default)]
+pub struct ServerHandle {
+    running: Arc<Mutex<Option<RunningServer>>>,
+}
+
+impl ServerHandle {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Port the server is currently listening on
+    pub async fn port(&self) -> Option<u16> {
+        self.running.lock().await.as_ref().map(|server| server.addr.port())
+    }
+
+    /// Bind the configured address and port and start serving
+    ///
+    /// Fails if the server is already running.
+    pub async fn start(&self, state: AppState) -> Result<u16, ServerError> {
+        let mut running = self.running.lock().await;
+        if running.is_some() {
+            return Err(ServerError::RuntimeError(
+                "Server is already running".to_string(),
+            ));
+        }
+
+        let addr = SocketAddr::new(state.get_bind_address(), state.get_port());
+        let listener = tokio::net::TcpListener::bind(addr).await?;
+        *running = Some(spawn(listener, addr, state));
+        Ok(addr.port())
+    }
+
+    /// Restart the server on the configured address and port
+    ///
+    /// When the address or port changes the new one is bound before the old
+    /// server is stopped, so an address that is in use leaves the running
+    /// server untouched.
+    pub async fn restart(&self, state: AppState) -> Result<ServerRestartResult, ServerError> {
+        let mut running = self.running.lock().await;
+        let addr = SocketAddr::new(state.get_bind_address(), state.get_port());
+        let previous_addr = running.as_ref().map(|server| server.addr);
+
+        let early_listener = if previous_addr.is_some_and(|a| a != addr) {
+            Some(tokio::net::TcpListener::bind(addr).await?)
+        } else {
+            None
+        };
+
+        let stopped_streams = stop_streams(&state);
+
+        let mut forced = false;
+        if let Some(server) = running.take() {
+            forced = !shutdown(server).await;
+        }
+
+        let listener = match early_listener {
+            Some(listener) => listener,
+            None => tokio::net::TcpListener::bind(addr).await?,
+        };
+        *running = Some(spawn(listener, addr, state));
+
+        Ok(ServerRestartResult {
+            previous_port: previous_addr.map(|a| a.port()),
+            port: addr.port(),
+            stopped_streams,
+            forced,
+        })
+    }
+
+    /// Stop the server for good, asking active stream sessions to stop first
+    ///
+    /// Returns None if the server was not running.
+    pub async fn stop(&self, state: &AppState) -> Option<ServerStopResult> {
+        let server = self.running.lock().await.take()?;
+        let stopped_streams = stop_streams(state);
+        let forced = !shutdown(server).await;
+        Some(ServerStopResult {
+            stopped_streams,
+            forced,
+        })
+    }
+}
+
+/// Ask every active stream session to stop; returns how many were asked
+fn stop_streams(state: &AppState) -> usize {
+    let stream_manager = state.stream_manager();
+    stream_manager
+        .list_sessions()
+        .iter()
+        .filter(|(session_id, _)| stream_manager.request_stop(session_id))
+        .count()
+}
+
+fn spawn(listener: tokio::net::TcpListener, addr: SocketAddr, state: AppState) -> RunningServer {
+    let (shutdown, shutdown_rx) = oneshot::channel::<()>();
+    let (ssdp_stop, ssdp_stop_rx) = oneshot::channel::<()>();
+    let ssdp_task = tokio::spawn(ssdp::run(state.clone(), ssdp_stop_rx));
+    let app = routes::create_router(state);
+
+    println!("HTTP server listening on http://{}", addr);
+    let task = tokio::spawn(async move {
+        // Connect info gives handlers the client address (shown for active streams)
+        axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+            .with_graceful_shutdown(async {
+                let _ = shutdown_rx.await;
+            })
+            .await
+            .map_err(|e| ServerError::RuntimeError(e.to_string()))
+    });
+
+    RunningServer {
+        addr,
+        shutdown,
+        task,
+        ssdp_stop,
+        ssdp_task,
+    }
+}
+
+/// Stop a server, waiting up to `DRAIN_TIMEOUT` for connections to drain
+///
+/// Returns false if the server had to be aborted.
+async fn shutdown(server: RunningServer) -> bool {
+    let _ = server.ssdp_stop.send(());
+    let ssdp_abort = server.ssdp_task.abort_handle();
+    if tokio::time::timeout(SSDP_STOP_TIMEOUT, server.ssdp_task)
+        .await
+        .is_err()
+    {
+        ssdp_abort.abort();
+    }
+
+    let _ = server.shutdown.send(());
+    let abort = server.task.abort_handle();
+
+    match tokio::time::timeout(DRAIN_TIMEOUT, server.task).await {
+        Ok(_) => {
+            tracing::info!("HTTP server on {} stopped", server.addr);
+            true
+        }
+        Err(_) => {
+            abort.abort();
+            tracing::warn!(
+                "HTTP server on {} did not drain within {:?}; remaining connections closed",
+                server.addr,
+                DRAIN_TIMEOUT
+            );
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_state(port: u16) -> AppState {
+        use diesel::prelude::*;
+        use diesel::r2d2::{ConnectionManager, Pool};
+
+        let manager = ConnectionManager::<diesel::SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        crate::db::run_migrations(&mut conn).unwrap();
+        diesel::replace_into(crate::db::schema::settings::table)
+            .values(&crate::db::Setting::new("server_port", port.to_string()))
+            .execute(&mut conn)
+            .unwrap();
+        crate::server::ssdp::set_enabled(&mut conn, false).unwrap();
+        drop(conn);
+        AppState::new(pool)
+    }
+
+    fn free_port() -> u16 {
+        std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port()
+    }
+
+    #[tokio::test]
+    async fn test_restart_moves_server_to_new_port() {
+        let first = free_port();
+        let handle = ServerHandle::new();
+        assert_eq!(handle.start(test_state(first)).await.unwrap(), first);
+        assert!(handle.start(test_state(first)).await.is_err());
+
+        let second = free_port();
+        let result = handle.restart(test_state(second)).await.unwrap();
+        assert_eq!(result.previous_port, Some(first));
+        assert_eq!(result.port, second);
+        assert!(!result.forced);
+        assert_eq!(handle.port().await, Some(second));
+
+        // The old port is free again
+        assert!(std::net::TcpListener::bind(("0.0.0.0", first)).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_stop_releases_port() {
+        let port = free_port();
+        let state = test_state(port);
+        let handle = ServerHandle::new();
+        handle.start(state.clone()).await.unwrap();
+
+        let result = handle.stop(&state).await.unwrap();
+        assert_eq!(result.stopped_streams, 0);
+        assert!(!result.forced);
+        assert_eq!(handle.port().await, None);
+        assert!(handle.stop(&state).await.is_none());
+        assert!(std::net::TcpListener::bind(("0.0.0.0", port)).is_ok());
+    }
+}
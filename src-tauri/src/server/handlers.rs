@@ -1,26 +1,39 @@
 use axum::{
     body::Body,
-    extract::{Path, State},
+    extract::{ConnectInfo, Path, Query, State},
     http::{header, HeaderMap, HeaderValue, Response, StatusCode},
     response::IntoResponse,
-    Json,
+    Extension, Json,
 };
 use diesel::prelude::*;
 use serde::Serialize;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::{Hash, Hasher};
+use std::net::{IpAddr, SocketAddr};
 
+use super::device_profiles::{self, DeviceProfile};
 use super::epg;
 use super::failover::{
-    get_all_streams_for_channel, log_failover_event, BackupStream, FailoverState, FailureReason,
-    FAILOVER_CONNECT_TIMEOUT, FAILOVER_TOTAL_TIMEOUT,
+    get_ts_padding_enabled, log_failover_event, plan_channel_streams, BackupStream,
+    ConnectingStream, FailoverState, FailoverStream, FailureReason, FAILOVER_CONNECT_TIMEOUT,
+    FAILOVER_TOTAL_TIMEOUT,
 };
 use super::hdhr;
+use super::lineup_preview::{self, LineupPreviewEntry};
+use super::logos;
 use super::m3u;
-use super::state::AppState;
-use super::stream::{build_stream_url, select_best_quality, StreamSession};
+use super::state::{AppState, EpgCache};
+use super::stream::{
+    get_preferred_quality, negotiate_stream_format, select_quality, CatchupWindow,
+    SessionStatsRecorder, StreamFormat, StreamManager, StreamSession,
+};
+use crate::channel_policy::record_stream_result;
+use crate::failover_policy;
+use crate::quota;
+use crate::tuner_priority::{self, Admission, StreamPriority};
 use crate::credentials::CredentialManager;
 use crate::db::schema::{accounts, channel_mappings, xmltv_channel_settings, xtream_channels};
+use crate::xmltv::history::{self, GuideHistoryEntry};
 
 /// Health check response structure
 #[derive(Serialize)]
@@ -56,25 +69,46 @@ pub async fn fallback_handler() -> StatusCode {
 /// - Channel numbers from plex_display_order
 /// - Stream URLs pointing to /stream/{xmltv_channel_id}
 ///
-/// Returns Content-Type: audio/x-mpegurl with ETag for caching
-pub async fn playlist_m3u(State(state): State<AppState>) -> Result<impl IntoResponse, (StatusCode, String)> {
-    let mut conn = state
-        .get_connection()
-        .map_err(|e| {
-            eprintln!("M3U playlist error - database connection failed: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Service temporarily unavailable".to_string())
-        })?;
-
-    let port = state.get_port();
-    let m3u_content = m3u::generate_m3u_playlist(&mut conn, port)
-        .map_err(|e| {
-            eprintln!("M3U playlist error - generation failed: {}", e);
-            (StatusCode::INTERNAL_SERVER_ERROR, "Unable to generate playlist".to_string())
-        })?;
+/// Optional query parameters filter the playlist: `?group=Sports` (provider
+/// category name) and/or `?category_id=5` (provider category ID).
+///
+/// Returns Content-Type: audio/x-mpegurl with ETag for caching. The
+/// unfiltered playlist is served from the server-side cache when it was
+/// primed after an EPG refresh.
+pub async fn playlist_m3u(
+    State(state): State<AppState>,
+    Query(filter): Query<m3u::M3uFilter>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let cached = if filter.is_empty() {
+        state.get_playlist_cache()
+    } else {
+        None
+    };
 
-    // Generate ETag from content hash for cache validation
-    // Plex can use this to avoid re-downloading unchanged playlists
-    let etag = generate_etag(&m3u_content);
+    let (m3u_content, etag) = match cached {
+        Some(cached) => (cached.content, cached.etag),
+        None => {
+            let mut conn = state
+                .get_connection()
+                .map_err(|e| {
+                    eprintln!("M3U playlist error - database connection failed: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Service temporarily unavailable".to_string())
+                })?;
+
+            let host = hdhr::advertised_host(state.get_bind_address());
+            let port = state.get_port();
+            let m3u_content = m3u::generate_m3u_playlist(&mut conn, &host, port, &filter)
+                .map_err(|e| {
+                    eprintln!("M3U playlist error - generation failed: {}", e);
+                    (StatusCode::INTERNAL_SERVER_ERROR, "Unable to generate playlist".to_string())
+                })?;
+
+            // Generate ETag from content hash for cache validation
+            // Plex can use this to avoid re-downloading unchanged playlists
+            let etag = generate_etag(&m3u_content);
+            (m3u_content, etag)
+        }
+    };
 
     let mut headers = HeaderMap::new();
     headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("audio/x-mpegurl"));
@@ -95,71 +129,68 @@ pub async fn playlist_m3u(State(state): State<AppState>) -> Result<impl IntoResp
     Ok((headers, m3u_content))
 }
 
+/// Look up the device profile of a `/device/{profile}/` request
+///
+/// Unknown profiles are 404.
+fn load_device_profile(
+    conn: &mut SqliteConnection,
+    slug: &str,
+) -> Result<DeviceProfile, (StatusCode, String)> {
+    match device_profiles::find_profile_by_slug(conn, slug) {
+        Ok(Some(profile)) => Ok(profile),
+        Ok(None) => Err((StatusCode::NOT_FOUND, "Not found".to_string())),
+        Err(e) => {
+            eprintln!("Device profile error - lookup failed: {}", e);
+            Err((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            ))
+        }
+    }
+}
+
+/// M3U playlist of a device profile
+///
+/// `GET /device/{profile}/playlist.m3u` is `/playlist.m3u` limited to the
+/// profile's channels, with stream URLs in the profile's format. Group and
+/// category filters apply as well.
+pub async fn profile_playlist_m3u(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+    Query(mut filter): Query<m3u::M3uFilter>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let profile = {
+        let mut conn = state.get_connection().map_err(|e| {
+            eprintln!("M3U playlist error - database connection failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Service temporarily unavailable".to_string(),
+            )
+        })?;
+        load_device_profile(&mut conn, &slug)?
+    };
+    filter.channel_ids = Some(profile.channel_set());
+    filter.stream_format = Some(profile.format());
+    playlist_m3u(State(state), Query(filter)).await
+}
+
 /// Generate ETag from content hash
 ///
 /// Uses fast non-cryptographic hash (DefaultHasher) since we only need
 /// cache validation, not security.
-fn generate_etag(content: &str) -> String {
+pub(super) fn generate_etag(content: &str) -> String {
     let mut hasher = DefaultHasher::new();
     content.hash(&mut hasher);
     format!("{:x}", hasher.finish())
 }
 
-/// XMLTV EPG endpoint handler (Story 4-2)
-///
-/// Generates an XMLTV-format EPG for Plex integration containing:
-/// - Only enabled XMLTV channels with Xtream stream mappings
-/// - Channel IDs matching M3U playlist tvg-id values
-/// - Program data for enabled channels (7-day window)
-/// - Placeholder programs for synthetic channels (2-hour blocks)
-///
-/// Returns Content-Type: application/xml with ETag for caching
-/// Supports If-None-Match for 304 Not Modified responses
-/// Implements server-side caching with 5-minute TTL
-pub async fn epg_xml(
-    State(state): State<AppState>,
-    headers: HeaderMap,
-) -> Result<impl IntoResponse, (StatusCode, String)> {
-    // Check server-side cache first
+/// Get the cached EPG, generating and caching it on a miss
+fn load_epg(state: &AppState) -> Result<EpgCache, (StatusCode, String)> {
     if let Some(cached) = state.get_epg_cache() {
-        let etag = format!("\"{}\"", cached.etag);
-
-        // Check If-None-Match for 304 response
-        if let Some(client_etag) = headers.get(header::IF_NONE_MATCH) {
-            if let Ok(etag_str) = client_etag.to_str() {
-                if etag_str == etag {
-                    // Return 304 Not Modified
-                    let mut response_headers = HeaderMap::new();
-                    response_headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
-                    response_headers.insert(
-                        header::CACHE_CONTROL,
-                        HeaderValue::from_static("public, max-age=300"),
-                    );
-                    return Ok((StatusCode::NOT_MODIFIED, response_headers, String::new()));
-                }
-            }
-        }
-
-        // Return cached content
-        let content_length = cached.content.len();
-        let mut response_headers = HeaderMap::new();
-        response_headers.insert(
-            header::CONTENT_TYPE,
-            HeaderValue::from_static("application/xml; charset=utf-8"),
-        );
-        response_headers.insert(
-            header::CONTENT_LENGTH,
-            HeaderValue::from_str(&content_length.to_string()).unwrap(),
-        );
-        response_headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
-        response_headers.insert(
-            header::CACHE_CONTROL,
-            HeaderValue::from_static("public, max-age=300"),
-        );
-        return Ok((StatusCode::OK, response_headers, cached.content));
+        return Ok(cached);
     }
+    let generation = state.epg_generation();
 
-    // Cache miss - generate fresh EPG
     let mut conn = state.get_connection().map_err(|e| {
         eprintln!("EPG endpoint error - database connection failed: {}", e);
         (
@@ -168,7 +199,9 @@ pub async fn epg_xml(
         )
     })?;
 
-    let xml_content = epg::generate_xmltv_epg(&mut conn).map_err(|e| {
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let port = state.get_port();
+    let xml_content = epg::generate_xmltv_epg(&mut conn, &host, port).map_err(|e| {
         eprintln!("EPG endpoint error - generation failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -176,43 +209,218 @@ pub async fn epg_xml(
         )
     })?;
 
-    // Generate ETag and store in cache
     let etag_hash = generate_etag(&xml_content);
-    let etag = format!("\"{}\"", etag_hash);
-    state.set_epg_cache(xml_content.clone(), etag_hash);
-
-    // Check If-None-Match for conditional request
-    if let Some(client_etag) = headers.get(header::IF_NONE_MATCH) {
-        if let Ok(etag_str) = client_etag.to_str() {
-            if etag_str == etag {
-                let mut response_headers = HeaderMap::new();
-                response_headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
-                response_headers.insert(
-                    header::CACHE_CONTROL,
-                    HeaderValue::from_static("public, max-age=300"),
-                );
-                return Ok((StatusCode::NOT_MODIFIED, response_headers, String::new()));
-            }
+    Ok(state.set_epg_cache(xml_content, etag_hash, generation))
+}
+
+/// Query parameters for `/epg.xml` and `/epg.xml.gz`
+#[derive(serde::Deserialize, Default)]
+pub struct EpgQuery {
+    /// Preferred program languages (e.g. "de,en"), overriding the lineup's
+    lang: Option<String>,
+}
+
+/// EPG for a request: the cached guide, or one generated in the requested
+/// languages (not cached)
+fn load_epg_for_query(state: &AppState, query: &EpgQuery) -> Result<EpgCache, (StatusCode, String)> {
+    let Some(lang) = query.lang.as_deref().filter(|l| !l.trim().is_empty()) else {
+        return load_epg(state);
+    };
+    let languages = epg::parse_language_list(lang).map_err(|e| (StatusCode::BAD_REQUEST, e))?;
+
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("EPG endpoint error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let xml_content =
+        epg::generate_xmltv_epg_in_languages(&mut conn, &host, state.get_port(), &languages)
+            .map_err(|e| {
+                eprintln!("EPG endpoint error - generation failed: {}", e);
+                (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal server error".to_string(),
+                )
+            })?;
+
+    let etag_hash = generate_etag(&xml_content);
+    Ok(EpgCache::uncached(xml_content, etag_hash))
+}
+
+/// Whether an Accept-Encoding header allows a gzip response
+///
+/// Honors `gzip`, `x-gzip` and `*`, and treats `q=0` as a refusal.
+pub(super) fn accepts_gzip(headers: &HeaderMap) -> bool {
+    let Some(value) = headers
+        .get(header::ACCEPT_ENCODING)
+        .and_then(|v| v.to_str().ok())
+    else {
+        return false;
+    };
+
+    let mut gzip = None;
+    let mut wildcard = None;
+    for coding in value.split(',') {
+        let mut parts = coding.split(';');
+        let name = parts.next().unwrap_or("").trim().to_ascii_lowercase();
+        let allowed = parts
+            .filter_map(|param| param.trim().strip_prefix("q="))
+            .next()
+            .map(|q| q.trim().parse::<f32>().map(|q| q > 0.0).unwrap_or(false))
+            .unwrap_or(true);
+        match name.as_str() {
+            "gzip" | "x-gzip" => gzip = Some(allowed),
+            "*" => wildcard = Some(allowed),
+            _ => {}
         }
     }
+    gzip.or(wildcard).unwrap_or(false)
+}
 
-    let content_length = xml_content.len();
-    let mut response_headers = HeaderMap::new();
-    response_headers.insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_static("application/xml; charset=utf-8"),
-    );
-    response_headers.insert(
-        header::CONTENT_LENGTH,
-        HeaderValue::from_str(&content_length.to_string()).unwrap(),
-    );
-    response_headers.insert(header::ETAG, HeaderValue::from_str(&etag).unwrap());
-    response_headers.insert(
-        header::CACHE_CONTROL,
-        HeaderValue::from_static("public, max-age=300"),
-    );
+/// Build an EPG response (plain XML, gzip-encoded XML, or a .gz file)
+///
+/// Compressed responses use their own ETag so caches never mix the variants
+/// up. Responses to /epg.xml carry `Vary: Accept-Encoding`.
+fn epg_response(cached: EpgCache, headers: &HeaderMap, variant: EpgVariant) -> Response<Body> {
+    let gzip = match variant {
+        EpgVariant::Negotiated if accepts_gzip(headers) => cached.gzip.clone(),
+        EpgVariant::Negotiated => None,
+        EpgVariant::GzipFile => match cached.gzip.clone() {
+            Some(gzip) => Some(gzip),
+            None => {
+                return (
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    "Internal server error".to_string(),
+                )
+                    .into_response()
+            }
+        },
+    };
+
+    let etag = if gzip.is_some() {
+        format!("\"{}-gz\"", cached.etag)
+    } else {
+        format!("\"{}\"", cached.etag)
+    };
+
+    let mut builder = Response::builder()
+        .header(header::ETAG, etag.as_str())
+        .header(header::CACHE_CONTROL, "public, max-age=300");
+    if variant == EpgVariant::Negotiated {
+        builder = builder.header(header::VARY, "accept-encoding");
+    }
+
+    // Check If-None-Match for 304 response
+    let not_modified = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|v| v.to_str().ok())
+        .map(|client_etag| client_etag == etag)
+        .unwrap_or(false);
+    if not_modified {
+        return builder
+            .status(StatusCode::NOT_MODIFIED)
+            .body(Body::empty())
+            .unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response());
+    }
+
+    let response = match (variant, gzip) {
+        (EpgVariant::GzipFile, Some(gzip)) => builder
+            .header(header::CONTENT_TYPE, "application/gzip")
+            .header(header::CONTENT_LENGTH, gzip.len())
+            .body(Body::from(gzip)),
+        (_, Some(gzip)) => builder
+            .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header(header::CONTENT_ENCODING, "gzip")
+            .header(header::CONTENT_LENGTH, gzip.len())
+            .body(Body::from(gzip)),
+        (_, None) => builder
+            .header(header::CONTENT_TYPE, "application/xml; charset=utf-8")
+            .header(header::CONTENT_LENGTH, cached.content.len())
+            .body(Body::from(cached.content)),
+    };
+    response.unwrap_or_else(|_| StatusCode::INTERNAL_SERVER_ERROR.into_response())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum EpgVariant {
+    /// /epg.xml: gzip only when the client sends `Accept-Encoding: gzip`
+    Negotiated,
+    /// /epg.xml.gz: always the gzip file
+    GzipFile,
+}
+
+/// XMLTV EPG endpoint handler (Story 4-2)
+///
+/// Generates an XMLTV-format EPG for Plex integration containing:
+/// - Only enabled XMLTV channels with Xtream stream mappings
+/// - Channel IDs matching M3U playlist tvg-id values
+/// - Program data for enabled channels (configured guide window)
+/// - Titles and descriptions in the lineup's preferred languages, or in the
+///   languages of `?lang=de,en` (such responses are not cached)
+/// - Placeholder programs for synthetic channels (2-hour blocks)
+///
+/// Returns Content-Type: application/xml with ETag for caching, compressed
+/// with gzip when the client's Accept-Encoding allows it
+/// Supports If-None-Match for 304 Not Modified responses
+/// Implements server-side caching with 5-minute TTL
+pub async fn epg_xml(
+    State(state): State<AppState>,
+    Query(query): Query<EpgQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let cached = load_epg_for_query(&state, &query)?;
+    Ok(epg_response(cached, &headers, EpgVariant::Negotiated))
+}
+
+/// Gzip-compressed XMLTV EPG endpoint handler
+///
+/// Serves the same guide as `/epg.xml` as an `application/gzip` file, for
+/// clients that expect an `.xml.gz` URL. Compressed once per cache refresh.
+pub async fn epg_xml_gz(
+    State(state): State<AppState>,
+    Query(query): Query<EpgQuery>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    let cached = load_epg_for_query(&state, &query)?;
+    Ok(epg_response(cached, &headers, EpgVariant::GzipFile))
+}
+
+/// Guide history endpoint handler
+///
+/// `GET /api/epg/history?channel=bbc1.uk&date=2026-03-01` lists what was on a
+/// channel during a UTC day, including programs that have since left the
+/// guide. `channel` is an XMLTV channel id or channel name; `date` defaults
+/// to today.
+pub async fn epg_history(
+    State(state): State<AppState>,
+    Query(params): Query<EpgHistoryParams>,
+) -> Result<Json<Vec<GuideHistoryEntry>>, (StatusCode, String)> {
+    if params.channel.trim().is_empty() {
+        return Err((StatusCode::BAD_REQUEST, "channel is required".to_string()));
+    }
+    let date = history::parse_history_date(params.date.as_deref())
+        .map_err(|e| (StatusCode::BAD_REQUEST, e))?;
 
-    Ok((StatusCode::OK, response_headers, xml_content))
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("EPG history error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+
+    history::load_channel_history(&mut conn, &params.channel, date)
+        .map(Json)
+        .map_err(|e| {
+            eprintln!("EPG history error - query failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            )
+        })
 }
 
 /// HDHomeRun discovery endpoint handler (Story 4-3)
@@ -235,8 +443,9 @@ pub async fn discover_json(
         )
     })?;
 
+    let host = hdhr::advertised_host(state.get_bind_address());
     let port = state.get_port();
-    let response = hdhr::generate_discover_response(&mut conn, port).map_err(|e| {
+    let response = hdhr::generate_discover_response(&mut conn, &host, port).map_err(|e| {
         eprintln!("HDHR discover error - generation failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -273,8 +482,90 @@ pub async fn lineup_json(
         )
     })?;
 
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let port = state.get_port();
+    let lineup = hdhr::generate_lineup(&mut conn, &host, port).map_err(|e| {
+        eprintln!("HDHR lineup error - generation failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+
+    Ok((headers, Json(lineup)))
+}
+
+/// HDHomeRun discovery endpoint of a device profile
+///
+/// `GET /device/{profile}/discover.json`, with the profile's FriendlyName,
+/// DeviceID and TunerCount, and BaseURL `/device/{profile}`.
+pub async fn profile_discover_json(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("HDHR discover error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+    let profile = load_device_profile(&mut conn, &slug)?;
+
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let port = state.get_port();
+    let response = hdhr::generate_profile_discover_response(&mut conn, &profile, &host, port)
+        .map_err(|e| {
+            eprintln!("HDHR discover error - generation failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            )
+        })?;
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/json"),
+    );
+
+    Ok((headers, Json(response)))
+}
+
+/// HDHomeRun lineup endpoint of a device profile
+///
+/// `GET /device/{profile}/lineup.json` lists the profile's channels that are
+/// in the main lineup, with the same GuideNumbers and stream URLs in the
+/// profile's format.
+pub async fn profile_lineup_json(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("HDHR lineup error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+    let profile = load_device_profile(&mut conn, &slug)?;
+
+    let host = hdhr::advertised_host(state.get_bind_address());
     let port = state.get_port();
-    let lineup = hdhr::generate_lineup(&mut conn, port).map_err(|e| {
+    let lineup = hdhr::generate_profile_lineup(
+        &mut conn,
+        &host,
+        port,
+        &profile.channel_set(),
+        profile.format(),
+    )
+    .map_err(|e| {
         eprintln!("HDHR lineup error - generation failed: {}", e);
         (
             StatusCode::INTERNAL_SERVER_ERROR,
@@ -291,6 +582,38 @@ pub async fn lineup_json(
     Ok((headers, Json(lineup)))
 }
 
+/// Lineup preview endpoint handler
+///
+/// `GET /debug/lineup` returns each channel as Plex sees it: guide number,
+/// tvg-id, logo, stream URL and the stream that will play, with any problems
+/// found. Returns 404 unless enabled in the settings.
+pub async fn debug_lineup(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<LineupPreviewEntry>>, (StatusCode, String)> {
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("Lineup preview error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+
+    if !lineup_preview::get_debug_lineup_enabled(&mut conn) {
+        return Err((StatusCode::NOT_FOUND, "Not found".to_string()));
+    }
+
+    let host = hdhr::advertised_host(state.get_bind_address());
+    lineup_preview::build_lineup_preview(&mut conn, &host, state.get_port())
+        .map(Json)
+        .map_err(|e| {
+            eprintln!("Lineup preview error - generation failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            )
+        })
+}
+
 /// HDHomeRun lineup status endpoint handler (Story 4-3)
 ///
 /// Returns static status indicating no scan in progress.
@@ -313,8 +636,17 @@ pub async fn lineup_status_json() -> impl IntoResponse {
 /// Returns UPnP device description XML for Plex device discovery.
 /// Plex requires this endpoint to properly identify and add the HDHomeRun device.
 pub async fn device_xml(State(state): State<AppState>) -> impl IntoResponse {
+    let identity = match state.get_connection() {
+        Ok(mut conn) => hdhr::device_identity(&mut conn),
+        Err(_) => hdhr::DeviceIdentity {
+            device_id: hdhr::generate_device_id(),
+            friendly_name: hdhr::DEFAULT_FRIENDLY_NAME.to_string(),
+            model_number: hdhr::DEFAULT_MODEL_NUMBER.to_string(),
+        },
+    };
+    let host = hdhr::advertised_host(state.get_bind_address());
     let port = state.get_port();
-    let xml = hdhr::generate_device_xml(port);
+    let xml = hdhr::generate_device_xml(&identity, &host, port);
 
     let mut headers = HeaderMap::new();
     headers.insert(
@@ -325,6 +657,114 @@ pub async fn device_xml(State(state): State<AppState>) -> impl IntoResponse {
     (headers, xml)
 }
 
+/// HDHomeRun lineup status endpoint of a device profile
+///
+/// Same static status as `/lineup_status.json`, for unknown profiles 404.
+pub async fn profile_lineup_status_json(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("HDHR lineup status error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+    load_device_profile(&mut conn, &slug)?;
+    Ok(lineup_status_json().await)
+}
+
+/// HDHomeRun device.xml endpoint of a device profile
+pub async fn profile_device_xml(
+    State(state): State<AppState>,
+    Path(slug): Path<String>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("HDHR device.xml error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+    let profile = load_device_profile(&mut conn, &slug)?;
+    let identity = hdhr::profile_identity(&mut conn, &profile);
+
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let base_url = format!(
+        "http://{}:{}/device/{}",
+        host,
+        state.get_port(),
+        profile.slug
+    );
+    let xml = hdhr::generate_device_xml_at(&identity, &base_url);
+
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("application/xml"),
+    );
+
+    Ok((headers, xml))
+}
+
+/// Channel logo endpoint handler
+///
+/// Serves logos from the local logo cache for outputs configured in "proxy" logo mode.
+/// On a cache miss the logo is fetched once on demand (only if it belongs to the
+/// current lineup). Missing or failed logos are served as a transparent placeholder
+/// with a short cache lifetime so Plex retries later. Responses carry a sandboxing
+/// Content-Security-Policy and `nosniff`, so provider SVGs cannot run scripts.
+pub async fn channel_logo(
+    Path(key): Path<String>,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    if !logos::is_valid_logo_key(&key) {
+        return Err((StatusCode::NOT_FOUND, "Logo not found".to_string()));
+    }
+
+    let cached = match logos::read_cached_logo(state.app_data_dir(), &key) {
+        Some(data) => Some(data),
+        None => logos::fetch_logo_on_demand(&state.pool(), state.app_data_dir(), &key).await,
+    };
+
+    // Logos come from providers and are served from this origin: an SVG
+    // opened directly must not run scripts, and nothing may be re-sniffed
+    let mut headers = HeaderMap::new();
+    headers.insert(
+        header::CONTENT_SECURITY_POLICY,
+        HeaderValue::from_static("default-src 'none'; style-src 'unsafe-inline'; sandbox"),
+    );
+    headers.insert(
+        header::X_CONTENT_TYPE_OPTIONS,
+        HeaderValue::from_static("nosniff"),
+    );
+    let body = match cached {
+        Some(data) => {
+            headers.insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static(logos::sniff_image_content_type(&data)),
+            );
+            // Logos rarely change - let Plex cache them for a day
+            headers.insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=86400"),
+            );
+            data
+        }
+        None => {
+            headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("image/png"));
+            headers.insert(
+                header::CACHE_CONTROL,
+                HeaderValue::from_static("public, max-age=300"),
+            );
+            logos::PLACEHOLDER_LOGO_PNG.to_vec()
+        }
+    };
+
+    Ok((headers, body))
+}
+
 /// Stream proxy endpoint handler (Story 4-4 + Story 4-5 Failover)
 ///
 /// Proxies live streams from Xtream providers to Plex with automatic failover:
@@ -336,36 +776,107 @@ pub async fn device_xml(State(state): State<AppState>) -> impl IntoResponse {
 /// - Enforces connection limits (tuner limit from account settings)
 /// - Logs failover events to event_log table
 ///
+/// The output container is negotiated per request from `?format=ts|m3u8` or
+/// the Accept header. HLS requests get the provider's playlist with its URIs
+/// proxied (see `super::hls`). `?priority=high` marks e.g. a DVR recording
+/// for the tuner reservation and priority rules (`crate::tuner_priority`).
+/// Streams that may be recordings (`?recording=true`, or requested by Plex)
+/// are not blocked by account quotas (`crate::quota::may_be_recording`).
+///
 /// Returns:
-/// - 200 OK with video/mp2t stream data on success
+/// - 200 OK with video/mp2t stream data (or an HLS playlist) on success
+/// - 400 Bad Request for an unknown `format` or `priority` value
 /// - 404 Not Found if channel doesn't exist, is disabled, or has no mapping
 /// - 503 Service Unavailable if tuner limit reached or all streams fail
 pub async fn stream_proxy(
     Path(channel_id): Path<i32>,
     State(state): State<AppState>,
+    Query(params): Query<StreamParams>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    request_headers: HeaderMap,
 ) -> Result<impl IntoResponse, (StatusCode, String)> {
-    // Step 1: Check connection limit FIRST (before expensive DB/crypto operations)
-    let stream_manager = state.stream_manager();
-    if !stream_manager.can_start_stream() {
-        eprintln!(
-            "Stream proxy error - tuner limit reached ({}/{}) for channel {}",
-            stream_manager.active_count(),
-            stream_manager.max_connections(),
-            channel_id
-        );
-
-        // Log tuner limit event (requires DB connection)
-        if let Ok(mut conn) = state.get_connection() {
-            log_tuner_limit_event(&mut conn, channel_id);
+    let priority = params
+        .priority
+        .as_deref()
+        .map(|value| {
+            StreamPriority::from_param(value).ok_or_else(|| {
+                (
+                    StatusCode::BAD_REQUEST,
+                    format!("Unsupported stream priority '{}' (use normal/high)", value),
+                )
+            })
+        })
+        .transpose()?;
+    let recording = quota::may_be_recording(&request_headers, params.recording);
+
+    // Step 0: Negotiate the output format
+    let accept = request_headers
+        .get(header::ACCEPT)
+        .and_then(|value| value.to_str().ok());
+    match negotiate_stream_format(params.format.as_deref(), accept) {
+        Ok(StreamFormat::Ts) => {
+            let client_ip = client_ip(connect_info);
+            proxy_channel_stream(&state, channel_id, None, priority, client_ip, recording).await
         }
-
-        return Err((
-            StatusCode::SERVICE_UNAVAILABLE,
-            "Tuner limit reached".to_string(),
-        ));
+        Ok(StreamFormat::M3u8) => {
+            let client_ip = client_ip(connect_info);
+            super::hls::serve_playlist(&state, channel_id, priority, client_ip, recording).await
+        }
+        Err(message) => Err((StatusCode::BAD_REQUEST, message)),
     }
+}
+
+/// Catch-up stream endpoint handler
+///
+/// Plays a past programme of a channel from the provider archive
+/// (`/stream/{channel_id}/catchup?start=...&duration=...`). `start` is a Unix
+/// timestamp or RFC 3339 date and `duration` the length in minutes. Only
+/// mapped Xtream streams whose channel has the archive enabled and reaching
+/// back far enough are tried; failover, tuner limits and session tracking
+/// work as for live streams.
+///
+/// Returns:
+/// - 200 OK with video/mp2t stream data on success
+/// - 400 Bad Request for an invalid `start` or `duration`
+/// - 404 Not Found if the channel doesn't exist or no stream has a usable archive
+/// - 503 Service Unavailable if tuner limit reached or all streams fail
+pub async fn stream_catchup(
+    Path(channel_id): Path<i32>,
+    State(state): State<AppState>,
+    Query(params): Query<CatchupParams>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Result<impl IntoResponse, (StatusCode, String)> {
+    let window = CatchupWindow::parse(&params.start, params.duration)
+        .map_err(|message| (StatusCode::BAD_REQUEST, message))?;
+    let client_ip = client_ip(connect_info);
+    proxy_channel_stream(&state, channel_id, Some(window), None, client_ip, false).await
+}
 
-    // Step 2: Get database connection
+/// Client address of a request (absent when the router is served without
+/// connect info, e.g. in tests)
+pub(super) fn client_ip(
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+) -> Option<IpAddr> {
+    connect_info.map(|Extension(ConnectInfo(addr))| addr.ip())
+}
+
+/// Connect a channel's streams with failover and proxy the first that works
+///
+/// With a catch-up window the provider archive is played instead of the live
+/// stream. `priority_hint` is the request's `?priority=` value, and a
+/// `recording` is not blocked by account quotas. With TS padding on, the
+/// response starts before the upstream has connected, and a stream that
+/// fails to connect ends the body instead of returning 503.
+async fn proxy_channel_stream(
+    state: &AppState,
+    channel_id: i32,
+    catchup: Option<CatchupWindow>,
+    priority_hint: Option<StreamPriority>,
+    client_ip: Option<IpAddr>,
+    recording: bool,
+) -> Result<Response<Body>, (StatusCode, String)> {
+    // Step 1: Get database connection
+    let stream_manager = state.stream_manager();
     let mut conn = state.get_connection().map_err(|e| {
         eprintln!("Stream proxy error - database connection failed: {}", e);
         (
@@ -374,47 +885,102 @@ pub async fn stream_proxy(
         )
     })?;
 
-    // Step 3: Check if channel is enabled
-    let is_enabled: Option<i32> = xmltv_channel_settings::table
-        .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id))
-        .select(xmltv_channel_settings::is_enabled)
-        .first(&mut conn)
-        .optional()
-        .map_err(|e| {
-            eprintln!("Stream lookup error - settings query failed: {}", e);
-            (
-                StatusCode::INTERNAL_SERVER_ERROR,
-                "Internal server error".to_string(),
-            )
-        })?
-        .flatten();
+    // Step 2: Check connection limit (before expensive lookups and crypto
+    // operations), applying the tuner reservation and priority rules. A
+    // session picked for preemption is only stopped once the new stream
+    // has connected (Step 9)
+    let priority_config = tuner_priority::load_config(&mut conn);
+    let priority = priority_config.priority_for(channel_id, priority_hint);
+    let tuner_preempt = admit_stream(
+        &mut conn,
+        stream_manager,
+        &priority_config,
+        channel_id,
+        priority,
+    )?;
+
+    let (available_streams, account_preempt) = channel_streams(
+        state, &mut conn, channel_id, catchup, priority, None, recording,
+    )?;
+    let ts_padding = get_ts_padding_enabled(&mut conn);
+    let admitted = AdmittedStream {
+        channel_id,
+        available_streams,
+        preempt: account_preempt.or(tuner_preempt),
+        max_quality: get_preferred_quality(&mut conn, channel_id),
+        priority,
+        client_ip,
+        ts_padding,
+    };
 
-    if is_enabled != Some(1) {
-        return Err((StatusCode::NOT_FOUND, "Channel not found".to_string()));
-    }
+    // With padding on, answer right away and send null packets while the
+    // upstream connects or fails over, so the client doesn't time out
+    let connect = connect_channel_stream(state.clone(), conn, admitted);
+    let body = if ts_padding {
+        Body::from_stream(ConnectingStream::new(async move { connect.await.ok() }))
+    } else {
+        Body::from_stream(connect.await?)
+    };
 
-    // Step 4: Load ALL available streams for failover (Story 4-5)
-    let available_streams = get_all_streams_for_channel(&mut conn, channel_id).map_err(|e| {
-        eprintln!(
-            "Stream proxy error - stream lookup failed for channel {}: {}",
-            channel_id, e
-        );
-        (
-            StatusCode::INTERNAL_SERVER_ERROR,
-            "Internal server error".to_string(),
-        )
-    })?;
+    let mut response = Response::new(body);
+    *response.status_mut() = StatusCode::OK;
 
-    if available_streams.is_empty() {
-        return Err((StatusCode::NOT_FOUND, "Channel not found".to_string()));
-    }
+    // Headers optimized for Plex HDHomeRun compatibility
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static("video/mp2t")
+    );
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-cache, no-store, must-revalidate")
+    );
+    response.headers_mut().insert(
+        header::PRAGMA,
+        HeaderValue::from_static("no-cache")
+    );
+    response.headers_mut().insert(
+        header::CONNECTION,
+        HeaderValue::from_static("close")
+    );
+
+    Ok(response)
+}
+
+/// A channel stream that passed admission, ready to connect
+struct AdmittedStream {
+    channel_id: i32,
+    available_streams: Vec<BackupStream>,
+    /// Session to stop once the stream has connected, if no tuner is free
+    preempt: Option<String>,
+    max_quality: Option<String>,
+    priority: StreamPriority,
+    client_ip: Option<IpAddr>,
+    ts_padding: bool,
+}
+
+/// Connect an admitted stream with failover and start proxying it
+async fn connect_channel_stream(
+    state: AppState,
+    mut conn: crate::db::DbPooledConnection,
+    admitted: AdmittedStream,
+) -> Result<FailoverStream, (StatusCode, String)> {
+    let AdmittedStream {
+        channel_id,
+        available_streams,
+        preempt,
+        max_quality,
+        priority,
+        client_ip,
+        ts_padding,
+    } = admitted;
+    let stream_manager = state.stream_manager();
 
     // Step 5: Initialize failover state
     let mut failover_state = FailoverState::new(channel_id, available_streams);
     let credential_manager = CredentialManager::new(state.app_data_dir().clone());
 
     // Step 6: Create HTTP client with aggressive failover timeouts
-    let client = reqwest::Client::builder()
+    let client = crate::dns::configure(reqwest::Client::builder())
         .connect_timeout(FAILOVER_CONNECT_TIMEOUT)
         .timeout(FAILOVER_TOTAL_TIMEOUT)
         .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
@@ -448,8 +1014,17 @@ pub async fn stream_proxy(
         };
 
         // Try to connect to current stream
-        match try_connect_stream(&client, &credential_manager, &current_stream).await {
+        match try_connect_stream(
+            &client,
+            &credential_manager,
+            &current_stream,
+            max_quality.as_deref(),
+        )
+        .await
+        {
             Ok((url, response)) => {
+                let _ = record_stream_result(&mut conn, current_stream.xtream_channel_id, true);
+
                 // Success! Log failover if we're not on the first stream
                 if failover_state.is_on_backup() {
                     if let Some(reason) = &last_failure_reason {
@@ -461,6 +1036,15 @@ pub async fn stream_proxy(
                             Some(current_stream.stream_id),
                             reason,
                         );
+                        if let Some(original) = failover_state.available_streams.first() {
+                            let _ = failover_policy::record_failover(
+                                &mut conn,
+                                channel_id,
+                                original.xtream_channel_id,
+                                current_stream.xtream_channel_id,
+                                chrono::Utc::now(),
+                            );
+                        }
                     }
                 }
 
@@ -472,6 +1056,7 @@ pub async fn stream_proxy(
                     "Stream proxy - stream {} failed for channel {}: {}",
                     current_stream.stream_id, channel_id, reason
                 );
+                let _ = record_stream_result(&mut conn, current_stream.xtream_channel_id, false);
                 last_failure_reason = Some(reason);
 
                 // Try next stream
@@ -504,15 +1089,23 @@ pub async fn stream_proxy(
         }
     };
 
-    // Step 9: Select quality and start session tracking
-    let qualities_json = if stream_info.qualities.is_empty() {
-        None
-    } else {
-        serde_json::to_string(&stream_info.qualities).ok()
-    };
-    let quality = select_best_quality(qualities_json.as_deref());
-
-    let session = StreamSession::new(channel_id, stream_info.stream_id, quality.clone());
+    // Step 9: Free a slot if the new stream still needs one, select quality
+    // and start session tracking
+    free_tuner(
+        &mut conn,
+        stream_manager,
+        preempt,
+        stream_info.account_id,
+        channel_id,
+    );
+    let quality = select_quality(&stream_info.qualities, max_quality.as_deref());
+
+    let session = StreamSession::new(channel_id, stream_info.stream_id, quality.clone())
+        .with_account(stream_info.account_id)
+        .with_client_ip(client_ip)
+        .with_priority(priority);
+    let bytes_counter = session.bytes_counter();
+    let stats = SessionStatsRecorder::new(state.pool(), &session);
     let session_id = stream_manager.start_session(session).ok_or_else(|| {
         eprintln!(
             "Stream proxy error - failed to start session (limit reached) for channel {}",
@@ -532,8 +1125,25 @@ pub async fn stream_proxy(
     // - FFmpeg normalizes timestamps and handles MPEG-TS quirks better than raw passthrough
     // - The verification ensures we don't spawn FFmpeg for a dead stream
     // - The delay between verify and FFmpeg connect is minimal (<100ms typically)
-    use super::buffer::{BufferedStream, BufferConfig};
-    use super::failover::{create_failover_stream, FailoverContext};
+    use super::buffer::{BufferConfig, BufferedStream};
+    use super::failover::{
+        create_failover_stream, get_reconnect_intervals, get_ts_continuity_enabled, FailoverContext,
+    };
+
+    // HLS and other non-TS upstreams go through the same pipeline; FFmpeg
+    // remuxes them to MPEG-TS without re-encoding
+    let upstream_content_type = upstream_response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or("unknown")
+        .to_string();
+    if StreamFormat::from_content_type(&upstream_content_type) != Some(StreamFormat::Ts) {
+        eprintln!(
+            "Stream proxy - remuxing {} upstream of stream {} to MPEG-TS",
+            upstream_content_type, stream_info.stream_id
+        );
+    }
 
     // Drop the reqwest response - FFmpeg will fetch the stream directly with its own
     // reconnection and timestamp normalization capabilities
@@ -555,55 +1165,198 @@ pub async fn stream_proxy(
 
     // Step 11: Wrap in FailoverStream for mid-stream failover capability (Story 4.7)
     //
-    // If backups are available, wrap the stream to enable seamless failover
-    // during playback if the current stream stalls.
-    let body = if failover_state.has_more_backups() {
-        let failover_context = FailoverContext::new(
-            failover_state.available_streams.clone(),
-            session_id.clone(),
-            channel_id,
+    // The wrapper fails over to backups if the current stream stalls, and
+    // refreshes expired stream tokens (401/403) even when there are no backups.
+    let mut ctx = FailoverContext::new(
+        failover_state.available_streams.clone(),
+        session_id.clone(),
+        channel_id,
+    )
+    .with_reconnect_intervals(get_reconnect_intervals(&mut conn))
+    .with_ts_continuity(get_ts_continuity_enabled(&mut conn))
+    .with_max_quality(max_quality);
+    // Advance context to current stream index
+    for _ in 0..failover_state.current_stream_idx {
+        ctx.advance();
+    }
+
+    let failover_stream = create_failover_stream(
+        buffered_stream,
+        ctx,
+        stream_manager.clone(),
+        credential_manager,
+        // Failover events are logged via eprintln; successful ones feed
+        // the automatic primary demotion
+        Some(record_mid_stream_failover(state.pool())),
+    )
+    .with_ts_padding(ts_padding)
+    .with_usage(quota::SessionUsage::new(
+        state.pool(),
+        stream_info.account_id,
+    ))
+    .with_bytes_counter(bytes_counter)
+    .with_stats(stats);
+
+    Ok(failover_stream)
+}
+
+/// Streams of a published channel that can be tried now, in failover order
+///
+/// Leaves out streams without a usable archive (for catch-up), from accounts
+/// at their connection limit, and from accounts over their quota (unless the
+/// stream may be a `recording` or continues a `session`). When every
+/// account is at its limit, the streams of an account with a session of
+/// lower `priority` are kept, and that session is returned so the caller can
+/// preempt it once a stream has connected. A `session` already playing the
+/// channel may always stay on its own account.
+pub(super) fn channel_streams(
+    state: &AppState,
+    conn: &mut crate::db::DbPooledConnection,
+    channel_id: i32,
+    catchup: Option<CatchupWindow>,
+    priority: StreamPriority,
+    session: Option<&str>,
+    recording: bool,
+) -> Result<(Vec<BackupStream>, Option<String>), (StatusCode, String)> {
+    let stream_manager = state.stream_manager();
+
+    // Steps 3-4: Published check and ALL available streams for failover
+    // (Story 4-5), in the order they are tried
+    let plan = plan_channel_streams(
+        conn,
+        stream_manager,
+        channel_id,
+        catchup,
+        session,
+        recording,
+    )
+    .map_err(|e| {
+        eprintln!(
+            "Stream proxy error - stream lookup failed for channel {}: {}",
+            channel_id, e
         );
-        // Advance context to current stream index
-        let mut ctx = failover_context;
-        for _ in 0..failover_state.current_stream_idx {
-            ctx.advance();
-        }
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })?;
+
+    if !plan.published {
+        return Err((StatusCode::NOT_FOUND, "Channel not found".to_string()));
+    }
+    let mut planned = plan.streams;
+    if planned.is_empty() {
+        let message = if catchup.is_some() {
+            "Catch-up not available for this channel"
+        } else {
+            "Channel not found"
+        };
+        return Err((StatusCode::NOT_FOUND, message.to_string()));
+    }
 
-        let failover_stream = create_failover_stream(
-            buffered_stream,
-            ctx,
-            stream_manager.clone(),
-            credential_manager,
-            None, // Failover events logged via eprintln
+    // Per-account tuner limits: skip streams from accounts whose connections
+    // are all in use, unless a lower-priority session can make room
+    let mut preempt = None;
+    let mut preempt_account = None;
+    if !planned.iter().any(|p| p.has_capacity) {
+        let accounts: Vec<i32> = planned.iter().map(|p| p.stream.account_id).collect();
+        let config = tuner_priority::load_config(conn);
+        preempt = tuner_priority::preemption(stream_manager, &config, priority, Some(&accounts));
+        preempt_account = preempt
+            .as_deref()
+            .and_then(|session_id| stream_manager.get_session(session_id))
+            .and_then(|session| session.account_id);
+    }
+    planned.retain(|p| p.has_capacity || Some(p.stream.account_id) == preempt_account);
+    if planned.is_empty() {
+        eprintln!(
+            "Stream proxy error - every account for channel {} is at its connection limit",
+            channel_id
         );
-        Body::from_stream(failover_stream)
-    } else {
-        // No backups available, use regular stream
-        Body::from_stream(buffered_stream)
-    };
+        log_tuner_limit_event(conn, channel_id);
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Tuner limit reached".to_string(),
+        ));
+    }
 
-    let mut response = Response::new(body);
-    *response.status_mut() = StatusCode::OK;
+    // Skip accounts that reached their monthly quota and block new streams
+    if planned.iter().all(|p| p.quota_blocked) {
+        eprintln!(
+            "Stream proxy error - all accounts for channel {} exceeded their quota",
+            channel_id
+        );
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Account quota exceeded".to_string(),
+        ));
+    }
+    let available_streams = planned
+        .into_iter()
+        .filter(|p| !p.quota_blocked)
+        .map(|p| p.stream)
+        .collect();
 
-    // Headers optimized for Plex HDHomeRun compatibility
-    response.headers_mut().insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_static("video/mp2t")
-    );
-    response.headers_mut().insert(
-        header::CACHE_CONTROL,
-        HeaderValue::from_static("no-cache, no-store, must-revalidate")
-    );
-    response.headers_mut().insert(
-        header::PRAGMA,
-        HeaderValue::from_static("no-cache")
-    );
-    response.headers_mut().insert(
-        header::CONNECTION,
-        HeaderValue::from_static("close")
-    );
+    Ok((available_streams, preempt))
+}
 
-    Ok(response)
+/// Decide whether a new stream of `priority` may take a tuner
+///
+/// Returns the session to preempt once the stream has connected, if one
+/// must make room; rejections are logged.
+pub(super) fn admit_stream(
+    conn: &mut crate::db::DbPooledConnection,
+    stream_manager: &StreamManager,
+    config: &tuner_priority::TunerPriorityConfig,
+    channel_id: i32,
+    priority: StreamPriority,
+) -> Result<Option<String>, (StatusCode, String)> {
+    match tuner_priority::admit(stream_manager, config, priority) {
+        Admission::Allowed => Ok(None),
+        Admission::Preempt(session_id) => Ok(Some(session_id)),
+        Admission::Reserved => {
+            eprintln!(
+                "Stream proxy error - only reserved tuners free ({}/{}) for channel {}",
+                stream_manager.active_count(),
+                stream_manager.max_connections(),
+                channel_id
+            );
+            log_reserved_tuner_event(conn, channel_id, config.reserved_tuners);
+            Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Tuner reserved for high-priority streams".to_string(),
+            ))
+        }
+        Admission::TunerLimit => {
+            eprintln!(
+                "Stream proxy error - tuner limit reached ({}/{}) for channel {}",
+                stream_manager.active_count(),
+                stream_manager.max_connections(),
+                channel_id
+            );
+            log_tuner_limit_event(conn, channel_id);
+            Err((
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Tuner limit reached".to_string(),
+            ))
+        }
+    }
+}
+
+/// Preempt `victim` if a new stream from `account_id` still has no free slot
+pub(super) fn free_tuner(
+    conn: &mut crate::db::DbPooledConnection,
+    stream_manager: &StreamManager,
+    victim: Option<String>,
+    account_id: i32,
+    channel_id: i32,
+) {
+    let Some(victim) = victim else {
+        return;
+    };
+    if !stream_manager.can_start_stream() || !stream_manager.has_account_capacity(account_id) {
+        preempt_session(conn, stream_manager, &victim, channel_id);
+    }
 }
 
 /// Try to connect to a stream and return the response if successful
@@ -611,42 +1364,25 @@ async fn try_connect_stream(
     client: &reqwest::Client,
     credential_manager: &CredentialManager,
     stream: &BackupStream,
+    max_quality: Option<&str>,
 ) -> Result<(String, reqwest::Response), FailureReason> {
-    // Decrypt password
-    let password = credential_manager
-        .retrieve_password(&stream.account_id.to_string(), &stream.password_encrypted)
-        .map_err(|e| {
-            eprintln!(
-                "Stream failover - credential decryption failed for account {}: {}",
-                stream.account_id, e
-            );
-            FailureReason::ConnectionError(format!("Credential error: {}", e))
-        })?;
+    // Build URL (decrypts the password for Xtream streams)
+    let stream_url = stream.upstream_url(credential_manager).map_err(|e| {
+        eprintln!("Stream failover - {} for account {}", e, stream.account_id);
+        FailureReason::ConnectionError(e)
+    })?;
 
     // Select quality
-    let qualities_json = if stream.qualities.is_empty() {
-        None
-    } else {
-        serde_json::to_string(&stream.qualities).ok()
-    };
-    let quality = select_best_quality(qualities_json.as_deref());
-
-    // Build URL
-    let stream_url = build_stream_url(
-        &stream.server_url,
-        &stream.username,
-        &password,
-        stream.stream_id,
-    );
+    let quality = select_quality(&stream.qualities, max_quality);
 
     eprintln!(
         "Stream failover - trying stream {} (priority {}, quality {})",
         stream.stream_id, stream.stream_priority, quality
     );
 
-    // Attempt connection
-    let response = client
-        .get(&stream_url)
+    // Attempt connection with the account's User-Agent and headers
+    let response = crate::xtream::request_headers::for_url(&stream_url)
+        .apply(client.get(&stream_url))
         .send()
         .await
         .map_err(|e| FailureReason::from_reqwest_error(&e))?;
@@ -662,6 +1398,24 @@ async fn try_connect_stream(
 /// Log tuner limit event to event_log table
 ///
 /// Story 6-3: Updated to use log_event_internal for verbosity support.
+/// Record successful mid-stream failovers for automatic primary demotion
+fn record_mid_stream_failover(pool: crate::db::DbPool) -> super::failover::FailoverCallback {
+    std::sync::Arc::new(move |event| {
+        let Some(to) = event.to_xtream_channel_id else {
+            return;
+        };
+        if let Ok(mut conn) = pool.get() {
+            let _ = failover_policy::record_failover(
+                &mut conn,
+                event.xmltv_channel_id,
+                event.from_xtream_channel_id,
+                to,
+                chrono::Utc::now(),
+            );
+        }
+    })
+}
+
 fn log_tuner_limit_event(conn: &mut crate::db::DbPooledConnection, channel_id: i32) {
     use crate::commands::logs::log_event_internal;
 
@@ -680,6 +1434,70 @@ fn log_tuner_limit_event(conn: &mut crate::db::DbPooledConnection, channel_id: i
     }
 }
 
+/// Stop a lower-priority session so a high-priority stream can take its tuner
+fn preempt_session(
+    conn: &mut crate::db::DbPooledConnection,
+    stream_manager: &StreamManager,
+    session_id: &str,
+    channel_id: i32,
+) {
+    use crate::commands::logs::log_event_internal;
+
+    let Some(session) = stream_manager.preempt(session_id) else {
+        return;
+    };
+    eprintln!(
+        "Stream proxy - preempted session {} on channel {} for channel {}",
+        session_id, session.xmltv_channel_id, channel_id
+    );
+
+    let details_json = serde_json::json!({
+        "channelId": channel_id,
+        "preemptedChannelId": session.xmltv_channel_id,
+        "sessionId": session_id,
+        "accountId": session.account_id,
+    });
+
+    if let Err(e) = log_event_internal(
+        conn,
+        "warn",
+        "stream",
+        &format!(
+            "Stopped stream on channel {} to free a tuner for high-priority channel {}",
+            session.xmltv_channel_id, channel_id
+        ),
+        Some(&details_json.to_string()),
+    ) {
+        eprintln!("Failed to log preemption event: {}", e);
+    }
+}
+
+fn log_reserved_tuner_event(
+    conn: &mut crate::db::DbPooledConnection,
+    channel_id: i32,
+    reserved_tuners: u32,
+) {
+    use crate::commands::logs::log_event_internal;
+
+    let details_json = serde_json::json!({
+        "channelId": channel_id,
+        "reservedTuners": reserved_tuners,
+    });
+
+    if let Err(e) = log_event_internal(
+        conn,
+        "warn",
+        "stream",
+        &format!(
+            "Tuner reserved for high-priority streams - rejected stream request for channel {}",
+            channel_id
+        ),
+        Some(&details_json.to_string()),
+    ) {
+        eprintln!("Failed to log reserved tuner event: {}", e);
+    }
+}
+
 /// Test data seeding endpoint (only available when IPTV_TEST_MODE=1)
 ///
 /// Seeds the database with test data for integration testing.
@@ -757,6 +1575,35 @@ pub async fn clear_test_data_endpoint(
     }))
 }
 
+/// Query parameters for `/stream/{channel_id}`
+#[derive(serde::Deserialize)]
+pub struct StreamParams {
+    /// Requested container: `ts` or `m3u8`
+    format: Option<String>,
+    /// Stream priority: `normal` or `high`
+    priority: Option<String>,
+    /// Whether the stream is a DVR recording (detected when absent)
+    recording: Option<bool>,
+}
+
+/// Query parameters for `/stream/{channel_id}/catchup`
+#[derive(serde::Deserialize)]
+pub struct CatchupParams {
+    /// Programme start: Unix timestamp or RFC 3339 date
+    start: String,
+    /// Playback length in minutes
+    duration: u32,
+}
+
+/// Query parameters for `/api/epg/history`
+#[derive(serde::Deserialize)]
+pub struct EpgHistoryParams {
+    #[serde(default)]
+    channel: String,
+    /// UTC day as YYYY-MM-DD
+    date: Option<String>,
+}
+
 #[derive(serde::Deserialize)]
 pub struct SeedParams {
     clear: Option<bool>,
@@ -790,7 +1637,7 @@ fn clear_test_data(conn: &mut crate::db::DbPooledConnection) -> Result<(), Strin
         .execute(conn)
         .map_err(|e| format!("Failed to clear xmltv_channels: {}", e))?;
 
-    diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.le(10)))
+    diesel::delete(xmltv_sources::table.filter(xmltv_sources::id.between(1, 10)))
         .execute(conn)
         .map_err(|e| format!("Failed to clear xmltv_sources: {}", e))?;
 
@@ -974,3 +1821,25 @@ fn seed_stream_proxy_data(conn: &mut crate::db::DbPooledConnection) -> Result<us
 
     Ok(records)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn with_accept_encoding(value: &str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::ACCEPT_ENCODING, HeaderValue::from_str(value).unwrap());
+        headers
+    }
+
+    #[test]
+    fn test_accepts_gzip() {
+        assert!(!accepts_gzip(&HeaderMap::new()));
+        assert!(accepts_gzip(&with_accept_encoding("gzip, deflate, br")));
+        assert!(accepts_gzip(&with_accept_encoding("x-gzip")));
+        assert!(accepts_gzip(&with_accept_encoding("br;q=1.0, *;q=0.5")));
+        assert!(!accepts_gzip(&with_accept_encoding("gzip;q=0")));
+        assert!(!accepts_gzip(&with_accept_encoding("*, gzip;q=0")));
+        assert!(!accepts_gzip(&with_accept_encoding("identity")));
+    }
+}
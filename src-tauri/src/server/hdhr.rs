@@ -11,19 +11,62 @@
 //!
 //! ## Security: Local-Only Access Model
 //! DeviceAuth uses a static value "streamforge" which is acceptable because:
-//! - Server binds to 127.0.0.1 only (NFR21 - local-only access)
-//! - No external network exposure
+//! - The server is only reachable on the configured bind address (loopback
+//!   or a LAN interface; all interfaces by default)
 //! - HDHomeRun protocol expects DeviceAuth but doesn't enforce it for local access
 //!
+//! URLs advertise the bind address, or the detected local IP when bound to
+//! all interfaces (see `advertised_host`).
+//!
+//! ## Device Identity
+//! Plex identifies tuners by DeviceID, so the ID is generated once and stored
+//! in settings (`hdhr_device_id`); it no longer changes with the hostname.
+//! FriendlyName and ModelNumber can be customized (see `DeviceIdentity`).
+//!
+//! ## Device Profiles
+//! Extra virtual tuners with a subset of the lineup are served under
+//! `/device/{slug}/` (see `super::device_profiles`). Each gets a DeviceID
+//! derived from the main one, so Plex sees them as separate devices.
+//!
 //! Story 4-3: Implement HDHomeRun Emulation
 
 use diesel::prelude::*;
 use diesel::sql_types::{Integer, Nullable, Text};
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 use std::collections::hash_map::DefaultHasher;
+use std::collections::HashSet;
 use std::hash::{Hash, Hasher};
-
-use crate::db::DbPooledConnection;
+use std::net::IpAddr;
+
+use super::device_profiles::DeviceProfile;
+use super::stream::{channel_stream_url, StreamFormat};
+use crate::channel_numbers::resolve_channel_numbers;
+use crate::db::schema::settings;
+use crate::db::{DbPooledConnection, Setting};
+
+/// Settings key holding the persisted DeviceID
+pub const DEVICE_ID_KEY: &str = "hdhr_device_id";
+/// Settings key for the advertised FriendlyName
+pub const FRIENDLY_NAME_KEY: &str = "hdhr_friendly_name";
+/// Settings key for the advertised ModelNumber
+pub const MODEL_NUMBER_KEY: &str = "hdhr_model_number";
+
+pub const DEFAULT_FRIENDLY_NAME: &str = "StreamForge";
+pub const DEFAULT_MODEL_NUMBER: &str = "HDHR5-4K";
+
+/// Maximum length of a custom FriendlyName
+pub const MAX_FRIENDLY_NAME_LEN: usize = 64;
+/// Maximum length of a custom ModelNumber
+pub const MAX_MODEL_NUMBER_LEN: usize = 32;
+
+/// How the emulated tuner identifies itself to Plex
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceIdentity {
+    pub device_id: String,
+    pub friendly_name: String,
+    pub model_number: String,
+}
 
 /// HDHomeRun discovery response
 ///
@@ -88,12 +131,14 @@ struct EnabledChannelRow {
     display_name: String,
     #[diesel(sql_type = Nullable<Integer>)]
     plex_display_order: Option<i32>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    channel_number: Option<i32>,
 }
 
 /// Generate a stable DeviceID based on machine hostname
 ///
-/// Creates a consistent ID like "STREAMFORGE12AB34CD" that persists across restarts.
-/// Uses hostname hash for stability across sessions.
+/// Creates a consistent ID like "STREAMFORGE12AB34CD". Used as the initial
+/// value stored by `device_identity`.
 pub fn generate_device_id() -> String {
     let hostname = hostname::get()
         .ok()
@@ -105,6 +150,90 @@ pub fn generate_device_id() -> String {
     format!("STREAMFORGE{:08X}", hasher.finish() as u32)
 }
 
+/// DeviceID of a device profile, derived from the main DeviceID and the slug
+pub fn generate_profile_device_id(base_device_id: &str, slug: &str) -> String {
+    let mut hasher = DefaultHasher::new();
+    base_device_id.hash(&mut hasher);
+    slug.hash(&mut hasher);
+    format!("STREAMFORGE{:08X}", hasher.finish() as u32)
+}
+
+fn get_setting(conn: &mut SqliteConnection, key: &str) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(key))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+}
+
+/// Load the device identity
+///
+/// The DeviceID is generated and stored the first time it is needed, so it
+/// stays the same even if the hostname changes later.
+pub fn device_identity(conn: &mut SqliteConnection) -> DeviceIdentity {
+    let device_id = match get_setting(conn, DEVICE_ID_KEY) {
+        Some(id) => id,
+        None => {
+            let _ = diesel::insert_or_ignore_into(settings::table)
+                .values(&Setting::new(DEVICE_ID_KEY, generate_device_id()))
+                .execute(conn);
+            get_setting(conn, DEVICE_ID_KEY).unwrap_or_else(generate_device_id)
+        }
+    };
+
+    DeviceIdentity {
+        device_id,
+        friendly_name: get_setting(conn, FRIENDLY_NAME_KEY)
+            .unwrap_or_else(|| DEFAULT_FRIENDLY_NAME.to_string()),
+        model_number: get_setting(conn, MODEL_NUMBER_KEY)
+            .unwrap_or_else(|| DEFAULT_MODEL_NUMBER.to_string()),
+    }
+}
+
+/// Save a custom FriendlyName and ModelNumber
+///
+/// Empty values restore the defaults.
+pub fn save_device_identity(
+    conn: &mut SqliteConnection,
+    friendly_name: &str,
+    model_number: &str,
+) -> Result<DeviceIdentity, String> {
+    let friendly_name = friendly_name.trim();
+    let model_number = model_number.trim();
+    if friendly_name.chars().count() > MAX_FRIENDLY_NAME_LEN {
+        return Err(format!(
+            "Friendly name must be at most {} characters",
+            MAX_FRIENDLY_NAME_LEN
+        ));
+    }
+    if model_number.len() > MAX_MODEL_NUMBER_LEN
+        || !model_number
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+    {
+        return Err(format!(
+            "Model number must be at most {} letters, digits, '-' or '_'",
+            MAX_MODEL_NUMBER_LEN
+        ));
+    }
+
+    for (key, value) in [
+        (FRIENDLY_NAME_KEY, friendly_name),
+        (MODEL_NUMBER_KEY, model_number),
+    ] {
+        let result = if value.is_empty() {
+            diesel::delete(settings::table.filter(settings::key.eq(key))).execute(conn)
+        } else {
+            diesel::replace_into(settings::table)
+                .values(&Setting::new(key, value))
+                .execute(conn)
+        };
+        result.map_err(|e| format!("Failed to save device identity: {}", e))?;
+    }
+
+    Ok(device_identity(conn))
+}
+
 /// Get the local IP address for HDHomeRun URLs
 ///
 /// Returns the local network IP address, falling back to 127.0.0.1 if detection fails.
@@ -114,6 +243,18 @@ pub fn get_local_ip() -> String {
         .unwrap_or_else(|_| "127.0.0.1".to_string())
 }
 
+/// Host to put in URLs for a bind address
+///
+/// All-interfaces binds advertise the detected local IP; other addresses are
+/// advertised as-is (IPv6 in brackets).
+pub fn advertised_host(bind_address: IpAddr) -> String {
+    match bind_address {
+        ip if ip.is_unspecified() => get_local_ip(),
+        IpAddr::V6(ip) => format!("[{}]", ip),
+        IpAddr::V4(ip) => ip.to_string(),
+    }
+}
+
 /// Get tuner count from active accounts
 ///
 /// Returns the maximum max_connections value from active accounts,
@@ -143,31 +284,79 @@ pub fn get_tuner_count(conn: &mut DbPooledConnection) -> Result<u32, diesel::res
 /// Generate HDHomeRun discovery response
 ///
 /// Creates a DiscoverResponse with:
-/// - FriendlyName: "StreamForge"
-/// - ModelNumber: "HDHR5-4K"
+/// - FriendlyName, ModelNumber and DeviceID from the device identity
+///   (defaults "StreamForge" / "HDHR5-4K")
 /// - TunerCount from active accounts
-/// - BaseURL and LineupURL with local IP and port
+/// - BaseURL and LineupURL with the advertised host and port
 pub fn generate_discover_response(
     conn: &mut DbPooledConnection,
+    host: &str,
     port: u16,
 ) -> Result<DiscoverResponse, diesel::result::Error> {
     let tuner_count = get_tuner_count(conn)?;
-    let local_ip = get_local_ip();
-    let base_url = format!("http://{}:{}", local_ip, port);
-    let lineup_url = format!("{}/lineup.json", base_url);
-    let device_id = generate_device_id();
-
-    Ok(DiscoverResponse {
-        friendly_name: "StreamForge".to_string(),
-        model_number: "HDHR5-4K".to_string(),
+    let identity = device_identity(conn);
+    Ok(discover_response(
+        conn,
+        identity,
+        format!("http://{}:{}", host, port),
+        tuner_count,
+    ))
+}
+
+/// Generate the discovery response of a device profile
+///
+/// Like `generate_discover_response`, with the profile's name, DeviceID and
+/// tuner count (the main device's when not set), and BaseURL
+/// `http://{host}:{port}/device/{slug}`.
+pub fn generate_profile_discover_response(
+    conn: &mut DbPooledConnection,
+    profile: &DeviceProfile,
+    host: &str,
+    port: u16,
+) -> Result<DiscoverResponse, diesel::result::Error> {
+    let tuner_count = match profile.tuner_count {
+        Some(count) => count as u32,
+        None => get_tuner_count(conn)?,
+    };
+    let identity = profile_identity(conn, profile);
+    Ok(discover_response(
+        conn,
+        identity,
+        format!("http://{}:{}/device/{}", host, port, profile.slug),
+        tuner_count,
+    ))
+}
+
+/// Identity a device profile advertises (ModelNumber from the main device)
+pub fn profile_identity(conn: &mut SqliteConnection, profile: &DeviceProfile) -> DeviceIdentity {
+    DeviceIdentity {
+        device_id: profile.device_id.clone(),
+        friendly_name: profile.name.clone(),
+        model_number: device_identity(conn).model_number,
+    }
+}
+
+fn discover_response(
+    conn: &mut SqliteConnection,
+    identity: DeviceIdentity,
+    base_url: String,
+    tuner_count: u32,
+) -> DiscoverResponse {
+    let token = super::auth::get_access_token(conn);
+    let lineup_url =
+        super::auth::with_access_token(format!("{}/lineup.json", base_url), token.as_deref());
+
+    DiscoverResponse {
+        friendly_name: identity.friendly_name,
+        model_number: identity.model_number,
         firmware_name: "hdhomerun5_atsc".to_string(),
         firmware_version: "20200101".to_string(),
-        device_id,
+        device_id: identity.device_id,
         device_auth: "streamforge".to_string(),
         base_url,
         lineup_url,
         tuner_count,
-    })
+    }
 }
 
 /// Get enabled channels for HDHomeRun lineup
@@ -184,9 +373,10 @@ fn get_enabled_channels_for_lineup(
         SELECT
             xc.id,
             xc.display_name,
-            xcs.plex_display_order
+            xcs.plex_display_order,
+            xcs.channel_number
         FROM xmltv_channels xc
-        INNER JOIN xmltv_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
+        INNER JOIN published_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
         WHERE xcs.is_enabled = 1
         AND EXISTS (
             SELECT 1 FROM channel_mappings cm
@@ -204,46 +394,61 @@ fn get_enabled_channels_for_lineup(
 /// Generate HDHomeRun lineup response
 ///
 /// Returns array of LineupEntry objects with:
-/// - GuideNumber: plex_display_order as string (or channel index if null)
+/// - GuideNumber: the channel number (see `crate::channel_numbers`)
 /// - GuideName: XMLTV display_name
-/// - URL: http://{local_ip}:{port}/stream/{xmltv_channel_id}
+/// - URL: http://{host}:{port}/stream/{xmltv_channel_id}
 pub fn generate_lineup(
     conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+) -> Result<Vec<LineupEntry>, diesel::result::Error> {
+    lineup_entries(conn, host, port, None, StreamFormat::Ts)
+}
+
+/// Generate the lineup of a device profile
+///
+/// The profile's channels that are in the main lineup, with the same
+/// GuideNumbers, so the shared /epg.xml matches.
+pub fn generate_profile_lineup(
+    conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+    channel_ids: &HashSet<i32>,
+    format: StreamFormat,
+) -> Result<Vec<LineupEntry>, diesel::result::Error> {
+    lineup_entries(conn, host, port, Some(channel_ids), format)
+}
+
+fn lineup_entries(
+    conn: &mut DbPooledConnection,
+    host: &str,
     port: u16,
+    only: Option<&HashSet<i32>>,
+    format: StreamFormat,
 ) -> Result<Vec<LineupEntry>, diesel::result::Error> {
     let channels = get_enabled_channels_for_lineup(conn)?;
-    let local_ip = get_local_ip();
+    let token = super::auth::get_access_token(conn);
 
     let mut lineup = Vec::with_capacity(channels.len());
 
-    // Find max explicit channel number to avoid collisions with fallback numbering
-    // e.g., if channels have plex_display_order 0,2,5 -> channel numbers 1,3,6
-    // fallback should start at 7, not 1
-    let max_explicit_channel = channels
-        .iter()
-        .filter_map(|c| c.plex_display_order)
-        .map(|o| o + 1)
-        .max()
-        .unwrap_or(0);
-
-    let mut fallback_number = max_explicit_channel + 1;
-
-    for channel in channels {
-        // Convert 0-indexed plex_display_order to 1-indexed GuideNumber
-        // Plex and most TV systems expect channel numbers starting at 1
-        let guide_number = match channel.plex_display_order {
-            Some(order) => (order + 1).to_string(),
-            None => {
-                let num = fallback_number.to_string();
-                fallback_number += 1;
-                num
-            }
-        };
-
+    let numbers = resolve_channel_numbers(
+        &channels
+            .iter()
+            .map(|c| (c.channel_number, c.plex_display_order))
+            .collect::<Vec<_>>(),
+    );
+
+    for (channel, number) in channels.into_iter().zip(numbers) {
+        if only.is_some_and(|ids| !ids.contains(&channel.id)) {
+            continue;
+        }
         lineup.push(LineupEntry {
-            guide_number,
+            guide_number: number.to_string(),
             guide_name: channel.display_name,
-            url: format!("http://{}:{}/stream/{}", local_ip, port, channel.id),
+            url: super::auth::with_access_token(
+                channel_stream_url(host, port, channel.id, format),
+                token.as_deref(),
+            ),
         });
     }
 
@@ -267,10 +472,13 @@ pub fn generate_lineup_status() -> LineupStatusResponse {
 ///
 /// Plex requires this XML endpoint for proper device discovery.
 /// Returns a valid UPnP device description with HDHomeRun information.
-pub fn generate_device_xml(port: u16) -> String {
-    let local_ip = get_local_ip();
-    let device_id = generate_device_id();
-    let base_url = format!("http://{}:{}", local_ip, port);
+pub fn generate_device_xml(identity: &DeviceIdentity, host: &str, port: u16) -> String {
+    generate_device_xml_at(identity, &format!("http://{}:{}", host, port))
+}
+
+/// Generate device.xml with the given URLBase (used by device profiles)
+pub fn generate_device_xml_at(identity: &DeviceIdentity, base_url: &str) -> String {
+    use quick_xml::escape::escape;
 
     format!(
         r#"<?xml version="1.0" encoding="UTF-8"?>
@@ -282,16 +490,18 @@ pub fn generate_device_xml(port: u16) -> String {
     <URLBase>{base_url}</URLBase>
     <device>
         <deviceType>urn:schemas-upnp-org:device:MediaServer:1</deviceType>
-        <friendlyName>StreamForge</friendlyName>
+        <friendlyName>{friendly_name}</friendlyName>
         <manufacturer>Silicondust</manufacturer>
-        <modelName>HDHR5-4K</modelName>
-        <modelNumber>HDHR5-4K</modelNumber>
+        <modelName>{model_number}</modelName>
+        <modelNumber>{model_number}</modelNumber>
         <serialNumber>{device_id}</serialNumber>
         <UDN>uuid:{device_id}</UDN>
     </device>
 </root>"#,
-        base_url = base_url,
-        device_id = device_id,
+        base_url = escape(base_url),
+        friendly_name = escape(identity.friendly_name.as_str()),
+        model_number = escape(identity.model_number.as_str()),
+        device_id = escape(identity.device_id.as_str()),
     )
 }
 
@@ -446,6 +656,25 @@ mod tests {
         assert_eq!(id1, id2);
     }
 
+    #[test]
+    fn test_profile_device_id_differs_per_slug() {
+        let sports = generate_profile_device_id("STREAMFORGE12345678", "sports");
+        assert!(sports.starts_with("STREAMFORGE"));
+        assert_eq!(sports.len(), 19);
+        assert_eq!(
+            sports,
+            generate_profile_device_id("STREAMFORGE12345678", "sports")
+        );
+        assert_ne!(
+            sports,
+            generate_profile_device_id("STREAMFORGE12345678", "news")
+        );
+        assert_ne!(
+            sports,
+            generate_profile_device_id("STREAMFORGE87654321", "sports")
+        );
+    }
+
     #[test]
     fn test_device_id_has_correct_length() {
         let device_id = generate_device_id();
@@ -466,6 +695,52 @@ mod tests {
         assert!(ip == "127.0.0.1" || ip.split('.').count() == 4);
     }
 
+    #[test]
+    fn test_advertised_host_follows_bind_address() {
+        assert_eq!(advertised_host("127.0.0.1".parse().unwrap()), "127.0.0.1");
+        assert_eq!(advertised_host("192.168.1.20".parse().unwrap()), "192.168.1.20");
+        assert_eq!(advertised_host("::1".parse().unwrap()), "[::1]");
+        assert_eq!(advertised_host("0.0.0.0".parse().unwrap()), get_local_ip());
+    }
+
+    // ============================================================================
+    // Device identity tests
+    // ============================================================================
+
+    #[test]
+    fn test_device_identity_is_persisted_and_customizable() {
+        let mut conn = crate::db::test_connection();
+
+        let identity = device_identity(&mut conn);
+        assert_eq!(identity.device_id, generate_device_id());
+        assert_eq!(identity.friendly_name, DEFAULT_FRIENDLY_NAME);
+
+        // A stored ID wins over the hostname-derived one
+        diesel::replace_into(settings::table)
+            .values(&Setting::new(DEVICE_ID_KEY, "STREAMFORGE0000CAFE"))
+            .execute(&mut conn)
+            .unwrap();
+        let identity = save_device_identity(&mut conn, " Living Room ", "HDHR3-US").unwrap();
+        assert_eq!(identity.device_id, "STREAMFORGE0000CAFE");
+        assert_eq!(identity.friendly_name, "Living Room");
+        assert_eq!(identity.model_number, "HDHR3-US");
+
+        assert!(save_device_identity(&mut conn, "Tuner", "HDHR <5>").is_err());
+        let identity = save_device_identity(&mut conn, "", "").unwrap();
+        assert_eq!(identity.model_number, DEFAULT_MODEL_NUMBER);
+
+        let xml = generate_device_xml(
+            &DeviceIdentity {
+                friendly_name: "Tom & Jerry".to_string(),
+                ..identity
+            },
+            "192.168.1.2",
+            5004,
+        );
+        assert!(xml.contains("<friendlyName>Tom &amp; Jerry</friendlyName>"));
+        assert!(xml.contains("<UDN>uuid:STREAMFORGE0000CAFE</UDN>"));
+    }
+
     // ============================================================================
     // Channel ordering tests (logic tests, no DB)
     // ============================================================================
@@ -218,6 +218,7 @@ mod tests {
             last_data_time,
             stall_detected: false,
             stall_start_time: None,
+            auth_rejected: false,
         }))
     }
 
@@ -0,0 +1,779 @@
+//! HLS passthrough for the stream proxy
+//!
+//! `/stream/{channel_id}?format=m3u8` (or an HLS Accept header) serves the
+//! provider's HLS playlist instead of remuxing the stream to MPEG-TS. Every
+//! URI in the playlist (variants, segments, keys, init sections) is
+//! rewritten to `/stream/{channel_id}/segment/{key}`, where the key maps to
+//! the upstream URL in memory. Provider URLs, which carry the account
+//! credentials, never reach the client; keys are salted hashes that cannot
+//! be guessed and expire after `KEY_TTL` without use.
+//!
+//! Failover happens when the channel playlist is fetched: the stream in use
+//! is kept while it answers, otherwise the channel's streams are tried in
+//! order. Segments are passed through unchanged (no FFmpeg) and count
+//! against the account's monthly quota.
+//!
+//! HLS is a series of short requests, so the tuner session of a client
+//! watching a channel is opened by its first playlist request (with the
+//! same tuner limits, account limits and priority rules as MPEG-TS streams)
+//! and ends once the client has made no request for `KEY_TTL`, or when the
+//! session is stopped or preempted.
+
+use axum::{
+    body::Body,
+    extract::{ConnectInfo, Path, State},
+    http::{header, HeaderMap, HeaderValue, Response, StatusCode},
+    Extension,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::{LazyLock, Mutex};
+use std::time::{Duration, Instant};
+use url::Url;
+
+use super::failover::{log_failover_event, FailureReason, FAILOVER_CONNECT_TIMEOUT};
+use super::handlers::{admit_stream, channel_streams, client_ip, free_tuner};
+use super::state::AppState;
+use super::stream::{get_preferred_quality, select_quality, StreamManager, StreamSession};
+use super::vod::CountingStream;
+use crate::channel_policy::record_stream_result;
+use crate::credentials::CredentialManager;
+use crate::quota::SessionUsage;
+use crate::tuner_priority::{self, StreamPriority};
+
+/// Content type of HLS playlists
+pub const HLS_CONTENT_TYPE: &str = "application/vnd.apple.mpegurl";
+
+/// Unused segment keys (and the channel's current stream) are forgotten after this
+const KEY_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// How often idle and stopped HLS sessions are looked for
+const SESSION_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Timeout of a playlist request
+const PLAYLIST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Largest playlist accepted from a provider
+const MAX_PLAYLIST_BYTES: usize = 4 * 1024 * 1024;
+
+/// Response headers copied from the provider for segments
+const FORWARDED_HEADERS: [header::HeaderName; 3] = [
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::ACCEPT_RANGES,
+];
+
+type ProxyError = (StatusCode, String);
+
+/// Upstream URL behind a segment key
+#[derive(Debug, Clone)]
+struct Upstream {
+    channel_id: i32,
+    account_id: i32,
+    url: String,
+    last_used: Instant,
+}
+
+/// Stream a channel is playing over HLS
+#[derive(Debug, Clone)]
+struct CurrentStream {
+    xtream_channel_id: i32,
+    stream_id: i32,
+    account_id: i32,
+    quality: String,
+    playlist_url: String,
+    last_used: Instant,
+}
+
+/// Tuner session of a client watching a channel over HLS
+#[derive(Debug, Clone)]
+struct HlsSession {
+    session_id: String,
+    last_used: Instant,
+}
+
+/// A client watching a channel
+type SessionKey = (i32, Option<IpAddr>);
+
+/// Upstream URLs by segment key
+static UPSTREAMS: LazyLock<Mutex<HashMap<String, Upstream>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Stream in use by channel, so playlist reloads skip failover
+static CURRENT: LazyLock<Mutex<HashMap<i32, CurrentStream>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Tuner sessions by channel and client
+static SESSIONS: LazyLock<Mutex<HashMap<SessionKey, HlsSession>>> =
+    LazyLock::new(|| Mutex::new(HashMap::new()));
+
+/// Per-process salt making segment keys unguessable
+static KEY_SALT: LazyLock<[u8; 32]> = LazyLock::new(rand::random);
+
+/// Serve a channel's HLS playlist with failover
+///
+/// A client without a session for the channel is admitted like an MPEG-TS
+/// stream; `priority_hint` is the request's `?priority=` value, and a
+/// `recording` is not blocked by account quotas.
+pub(super) async fn serve_playlist(
+    state: &AppState,
+    channel_id: i32,
+    priority_hint: Option<StreamPriority>,
+    client_ip: Option<IpAddr>,
+    recording: bool,
+) -> Result<Response<Body>, ProxyError> {
+    let stream_manager = state.stream_manager();
+    let mut conn = state.get_connection().map_err(|e| {
+        eprintln!("HLS proxy error - database connection failed: {}", e);
+        internal_error()
+    })?;
+
+    let key = (channel_id, client_ip);
+    let session = live_session(stream_manager, &key);
+    let mut priority = StreamPriority::Normal;
+    let mut tuner_preempt = None;
+    if session.is_none() {
+        let config = tuner_priority::load_config(&mut conn);
+        priority = config.priority_for(channel_id, priority_hint);
+        tuner_preempt = admit_stream(&mut conn, stream_manager, &config, channel_id, priority)?;
+    }
+    let (streams, account_preempt) = channel_streams(
+        state,
+        &mut conn,
+        channel_id,
+        None,
+        priority,
+        session.as_deref(),
+        recording,
+    )?;
+    let mut tuner = Tuner {
+        key,
+        session,
+        preempt: account_preempt.or(tuner_preempt),
+        priority,
+    };
+    let client = http_client(Some(PLAYLIST_TIMEOUT))?;
+
+    // Playlist reloads keep the stream in use while it answers
+    let current = current_stream(channel_id).filter(|current| {
+        streams
+            .iter()
+            .any(|s| s.xtream_channel_id == current.xtream_channel_id)
+    });
+    if let Some(current) = current {
+        if let Ok((base, body)) = fetch_playlist(&client, &current.playlist_url).await {
+            tuner.hold(state, &mut conn, &current)?;
+            return Ok(playlist_response(
+                &body,
+                &base,
+                channel_id,
+                current.account_id,
+            ));
+        }
+    }
+
+    let credential_manager = CredentialManager::new(state.app_data_dir().clone());
+    let max_quality = get_preferred_quality(&mut conn, channel_id);
+    let mut last_failure: Option<FailureReason> = None;
+    for (index, stream) in streams.iter().enumerate() {
+        let result = match stream.upstream_hls_url(&credential_manager) {
+            Ok(url) => fetch_playlist(&client, &url)
+                .await
+                .map(|(base, body)| (url, base, body)),
+            Err(e) => Err(FailureReason::ConnectionError(e)),
+        };
+        match result {
+            Ok((url, base, body)) => {
+                let _ = record_stream_result(&mut conn, stream.xtream_channel_id, true);
+                if let (true, Some(reason)) = (index > 0, &last_failure) {
+                    let _ = log_failover_event(
+                        &mut conn,
+                        channel_id,
+                        streams[0].stream_id,
+                        Some(stream.stream_id),
+                        reason,
+                    );
+                }
+                let current = CurrentStream {
+                    xtream_channel_id: stream.xtream_channel_id,
+                    stream_id: stream.stream_id,
+                    account_id: stream.account_id,
+                    quality: select_quality(&stream.qualities, max_quality.as_deref()),
+                    playlist_url: url,
+                    last_used: Instant::now(),
+                };
+                tuner.hold(state, &mut conn, &current)?;
+                set_current_stream(channel_id, current);
+                return Ok(playlist_response(
+                    &body,
+                    &base,
+                    channel_id,
+                    stream.account_id,
+                ));
+            }
+            Err(reason) => {
+                eprintln!(
+                    "HLS proxy - stream {} failed for channel {}: {}",
+                    stream.stream_id, channel_id, reason
+                );
+                let _ = record_stream_result(&mut conn, stream.xtream_channel_id, false);
+                last_failure = Some(reason);
+            }
+        }
+    }
+
+    if let (Some(first), Some(reason)) = (streams.first(), &last_failure) {
+        let _ = log_failover_event(&mut conn, channel_id, first.stream_id, None, reason);
+    }
+    Err((
+        StatusCode::SERVICE_UNAVAILABLE,
+        "Stream unavailable".to_string(),
+    ))
+}
+
+/// `GET /stream/{channel_id}/segment/{key}`: a URI from a rewritten playlist
+///
+/// Nested playlists are rewritten in turn; anything else is passed through
+/// with Range requests forwarded.
+pub async fn hls_segment(
+    Path((channel_id, key)): Path<(i32, String)>,
+    State(state): State<AppState>,
+    connect_info: Option<Extension<ConnectInfo<SocketAddr>>>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, ProxyError> {
+    let token = key.split('.').next().unwrap_or_default();
+    let upstream = lookup(channel_id, token)
+        .ok_or_else(|| (StatusCode::NOT_FOUND, "Segment not found".to_string()))?;
+    // Segments keep the client's session alive; a stopped session stays stopped
+    let session_key = (channel_id, client_ip(connect_info));
+    if live_session(state.stream_manager(), &session_key).is_none() {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Stream stopped".to_string(),
+        ));
+    }
+
+    let client = http_client(None)?;
+    let mut request =
+        crate::xtream::request_headers::for_url(&upstream.url).apply(client.get(&upstream.url));
+    if let Some(range) = headers.get(header::RANGE) {
+        request = request.header(header::RANGE, range.clone());
+    }
+    let response = request.send().await.map_err(|e| {
+        eprintln!(
+            "HLS proxy error - segment request failed: {}",
+            e.without_url()
+        );
+        bad_gateway()
+    })?;
+    let status = response.status();
+    if !status.is_success() {
+        eprintln!(
+            "HLS proxy error - provider returned HTTP {} for a segment",
+            status.as_u16()
+        );
+        return Err(bad_gateway());
+    }
+
+    let upstream_type = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .map(str::to_string);
+    if is_playlist_type(upstream_type.as_deref(), &upstream.url) {
+        let base = response.url().clone();
+        let body = read_playlist(response).await.map_err(|reason| {
+            eprintln!("HLS proxy error - nested playlist: {}", reason);
+            bad_gateway()
+        })?;
+        return Ok(playlist_response(
+            &body,
+            &base,
+            channel_id,
+            upstream.account_id,
+        ));
+    }
+
+    let content_type = segment_content_type(upstream_type.as_deref(), &upstream.url);
+    let mut builder = Response::builder()
+        .status(StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::OK))
+        .header(header::CONTENT_TYPE, content_type);
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = response.headers().get(&name) {
+            builder = builder.header(name, value.clone());
+        }
+    }
+    let body = CountingStream::new(
+        response,
+        SessionUsage::new(state.pool(), upstream.account_id),
+    );
+    builder.body(Body::from_stream(body)).map_err(|e| {
+        eprintln!("HLS proxy error - response build failed: {}", e);
+        internal_error()
+    })
+}
+
+fn http_client(timeout: Option<Duration>) -> Result<reqwest::Client, ProxyError> {
+    let mut builder =
+        crate::dns::configure(reqwest::Client::builder()).connect_timeout(FAILOVER_CONNECT_TIMEOUT);
+    if let Some(timeout) = timeout {
+        builder = builder.timeout(timeout);
+    }
+    builder.build().map_err(|e| {
+        eprintln!("HLS proxy error - HTTP client creation failed: {}", e);
+        internal_error()
+    })
+}
+
+/// Fetch a provider playlist; returns its final URL (after redirects) and text
+async fn fetch_playlist(
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<(Url, String), FailureReason> {
+    let response = crate::xtream::request_headers::for_url(url)
+        .apply(client.get(url))
+        .send()
+        .await
+        .map_err(|e| FailureReason::from_reqwest_error(&e.without_url()))?;
+    if !response.status().is_success() {
+        return Err(FailureReason::from_http_status(response.status()));
+    }
+    let base = response.url().clone();
+    Ok((base, read_playlist(response).await?))
+}
+
+async fn read_playlist(response: reqwest::Response) -> Result<String, FailureReason> {
+    if response
+        .content_length()
+        .is_some_and(|length| length > MAX_PLAYLIST_BYTES as u64)
+    {
+        return Err(FailureReason::StreamError("Playlist too large".to_string()));
+    }
+    let bytes = response
+        .bytes()
+        .await
+        .map_err(|e| FailureReason::from_reqwest_error(&e.without_url()))?;
+    if bytes.len() > MAX_PLAYLIST_BYTES {
+        return Err(FailureReason::StreamError("Playlist too large".to_string()));
+    }
+    let body = String::from_utf8_lossy(&bytes).into_owned();
+    if !body
+        .trim_start_matches('\u{feff}')
+        .trim_start()
+        .starts_with("#EXTM3U")
+    {
+        return Err(FailureReason::StreamError(
+            "Not an HLS playlist".to_string(),
+        ));
+    }
+    Ok(body)
+}
+
+/// Rewritten playlist response
+fn playlist_response(body: &str, base: &Url, channel_id: i32, account_id: i32) -> Response<Body> {
+    prune_expired(Instant::now());
+    let playlist = rewrite_playlist(body, base, |url| proxy_path(channel_id, account_id, url));
+
+    let mut response = Response::new(Body::from(playlist));
+    response.headers_mut().insert(
+        header::CONTENT_TYPE,
+        HeaderValue::from_static(HLS_CONTENT_TYPE),
+    );
+    response.headers_mut().insert(
+        header::CACHE_CONTROL,
+        HeaderValue::from_static("no-cache, no-store, must-revalidate"),
+    );
+    response
+}
+
+/// Replace every URI in a playlist with `proxy(absolute URL)`
+///
+/// URI lines and `URI="..."` attributes (keys, init sections, renditions)
+/// are resolved against `base`; URIs that are not http(s) are left alone.
+fn rewrite_playlist(body: &str, base: &Url, mut proxy: impl FnMut(&Url) -> String) -> String {
+    let mut out = String::with_capacity(body.len());
+    for line in body.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('#') {
+            out.push_str(&rewrite_uri_attributes(line, base, &mut proxy));
+        } else if trimmed.is_empty() {
+            out.push_str(line);
+        } else {
+            match resolve(base, trimmed) {
+                Some(url) => out.push_str(&proxy(&url)),
+                None => out.push_str(line),
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+fn rewrite_uri_attributes(
+    line: &str,
+    base: &Url,
+    proxy: &mut impl FnMut(&Url) -> String,
+) -> String {
+    const ATTRIBUTE: &str = "URI=\"";
+    let mut out = String::with_capacity(line.len());
+    let mut rest = line;
+    while let Some(start) = rest.find(ATTRIBUTE) {
+        let value_start = start + ATTRIBUTE.len();
+        let Some(length) = rest[value_start..].find('"') else {
+            break;
+        };
+        let value = &rest[value_start..value_start + length];
+        out.push_str(&rest[..value_start]);
+        match resolve(base, value) {
+            Some(url) => out.push_str(&proxy(&url)),
+            None => out.push_str(value),
+        }
+        rest = &rest[value_start + length..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Absolute http(s) URL of a playlist URI
+fn resolve(base: &Url, uri: &str) -> Option<Url> {
+    base.join(uri)
+        .ok()
+        .filter(|url| matches!(url.scheme(), "http" | "https"))
+}
+
+/// Path under `/stream/{channel_id}/segment/` serving `url`
+///
+/// The file extension is kept so clients that look at it recognize segments.
+fn proxy_path(channel_id: i32, account_id: i32, url: &Url) -> String {
+    let key = register(channel_id, account_id, url.as_str());
+    let extension = url
+        .path_segments()
+        .and_then(|mut segments| segments.next_back())
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, extension)| extension)
+        .filter(|ext| ext.len() <= 5 && ext.chars().all(|c| c.is_ascii_alphanumeric()));
+    match extension {
+        Some(extension) => format!("/stream/{}/segment/{}.{}", channel_id, key, extension),
+        None => format!("/stream/{}/segment/{}", channel_id, key),
+    }
+}
+
+/// Key for an upstream URL, remembered until unused for `KEY_TTL`
+fn register(channel_id: i32, account_id: i32, url: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(KEY_SALT.as_slice());
+    hasher.update(channel_id.to_le_bytes());
+    hasher.update(url.as_bytes());
+    let key: String = format!("{:x}", hasher.finalize())
+        .chars()
+        .take(32)
+        .collect();
+
+    UPSTREAMS.lock().unwrap_or_else(|e| e.into_inner()).insert(
+        key.clone(),
+        Upstream {
+            channel_id,
+            account_id,
+            url: url.to_string(),
+            last_used: Instant::now(),
+        },
+    );
+    key
+}
+
+/// Upstream behind a key of `channel_id`, marking it as used
+fn lookup(channel_id: i32, key: &str) -> Option<Upstream> {
+    let mut upstreams = UPSTREAMS.lock().unwrap_or_else(|e| e.into_inner());
+    let upstream = upstreams.get_mut(key)?;
+    if upstream.channel_id != channel_id || upstream.last_used.elapsed() >= KEY_TTL {
+        return None;
+    }
+    upstream.last_used = Instant::now();
+    Some(upstream.clone())
+}
+
+fn prune_expired(now: Instant) {
+    UPSTREAMS
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|_, upstream| now.duration_since(upstream.last_used) < KEY_TTL);
+    CURRENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .retain(|_, current| now.duration_since(current.last_used) < KEY_TTL);
+}
+
+fn current_stream(channel_id: i32) -> Option<CurrentStream> {
+    let mut current = CURRENT.lock().unwrap_or_else(|e| e.into_inner());
+    let stream = current.get_mut(&channel_id)?;
+    stream.last_used = Instant::now();
+    Some(stream.clone())
+}
+
+fn set_current_stream(channel_id: i32, stream: CurrentStream) {
+    CURRENT
+        .lock()
+        .unwrap_or_else(|e| e.into_inner())
+        .insert(channel_id, stream);
+}
+
+/// Tuner session a playlist request is served under
+struct Tuner {
+    key: SessionKey,
+    /// The client's open session, if any
+    session: Option<String>,
+    /// Session to preempt if a new session finds no free slot
+    preempt: Option<String>,
+    priority: StreamPriority,
+}
+
+impl Tuner {
+    /// Keep the client's session on `stream`, opening one if needed
+    fn hold(
+        &mut self,
+        state: &AppState,
+        conn: &mut crate::db::DbPooledConnection,
+        stream: &CurrentStream,
+    ) -> Result<(), ProxyError> {
+        let stream_manager = state.stream_manager();
+        if let Some(session_id) = &self.session {
+            stream_manager.update_session(session_id, |session| {
+                if session.xtream_stream_id != stream.stream_id {
+                    session.account_id = Some(stream.account_id);
+                    session.record_failover(stream.stream_id, stream.quality.clone());
+                }
+            });
+            return Ok(());
+        }
+
+        let (channel_id, client_ip) = self.key;
+        free_tuner(
+            conn,
+            stream_manager,
+            self.preempt.take(),
+            stream.account_id,
+            channel_id,
+        );
+        let session = StreamSession::new(channel_id, stream.stream_id, stream.quality.clone())
+            .with_account(stream.account_id)
+            .with_client_ip(client_ip)
+            .with_priority(self.priority);
+        let session_id = stream_manager.start_session(session).ok_or_else(|| {
+            eprintln!(
+                "HLS proxy error - failed to start session (limit reached) for channel {}",
+                channel_id
+            );
+            (
+                StatusCode::SERVICE_UNAVAILABLE,
+                "Tuner limit reached".to_string(),
+            )
+        })?;
+        SESSIONS.lock().unwrap_or_else(|e| e.into_inner()).insert(
+            self.key,
+            HlsSession {
+                session_id: session_id.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        tokio::spawn(expire_session(
+            state.stream_manager().clone(),
+            self.key,
+            session_id.clone(),
+        ));
+        self.session = Some(session_id);
+        Ok(())
+    }
+}
+
+/// The client's session for a channel, if still playing, marking it as used
+///
+/// A stopped or preempted session is forgotten.
+fn live_session(stream_manager: &StreamManager, key: &SessionKey) -> Option<String> {
+    let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+    let session = sessions.get_mut(key)?;
+    let playing = stream_manager.get_session(&session.session_id).is_some()
+        && !stream_manager.is_stop_requested(&session.session_id);
+    if !playing || session.last_used.elapsed() >= KEY_TTL {
+        sessions.remove(key);
+        return None;
+    }
+    session.last_used = Instant::now();
+    Some(session.session_id.clone())
+}
+
+/// End an HLS session once it is unused for `KEY_TTL` or asked to stop
+async fn expire_session(
+    stream_manager: std::sync::Arc<StreamManager>,
+    key: SessionKey,
+    session_id: String,
+) {
+    let mut interval = tokio::time::interval(SESSION_CHECK_INTERVAL);
+    loop {
+        interval.tick().await;
+        let mut sessions = SESSIONS.lock().unwrap_or_else(|e| e.into_inner());
+        let current = sessions
+            .get(&key)
+            .filter(|session| session.session_id == session_id);
+        let idle = current.is_none_or(|session| session.last_used.elapsed() >= KEY_TTL);
+        let stopped = stream_manager.get_session(&session_id).is_none()
+            || stream_manager.is_stop_requested(&session_id);
+        if idle || stopped {
+            if current.is_some() {
+                sessions.remove(&key);
+            }
+            drop(sessions);
+            stream_manager.end_session(&session_id);
+            return;
+        }
+    }
+}
+
+/// Whether an upstream response is a playlist, from its type or extension
+fn is_playlist_type(content_type: Option<&str>, url: &str) -> bool {
+    let media_type = content_type
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+    if matches!(
+        media_type.as_deref(),
+        Some("application/vnd.apple.mpegurl" | "application/x-mpegurl" | "audio/mpegurl")
+    ) {
+        return true;
+    }
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    path.ends_with(".m3u8") || path.ends_with(".m3u")
+}
+
+/// Content type for a segment
+///
+/// Providers often send segments as `application/octet-stream` or
+/// `text/html`; those are replaced by the type matching the file extension.
+fn segment_content_type(content_type: Option<&str>, url: &str) -> HeaderValue {
+    let generic = |value: &str| {
+        let media_type = value.split(';').next().unwrap_or_default().trim();
+        media_type.is_empty()
+            || media_type.eq_ignore_ascii_case("application/octet-stream")
+            || media_type.eq_ignore_ascii_case("binary/octet-stream")
+            || media_type.to_ascii_lowercase().starts_with("text/html")
+            || media_type.eq_ignore_ascii_case("text/plain")
+    };
+    if let Some(value) = content_type.filter(|value| !generic(value)) {
+        if let Ok(value) = HeaderValue::from_str(value) {
+            return value;
+        }
+    }
+
+    let path = url.split(['?', '#']).next().unwrap_or_default();
+    let extension = path
+        .rsplit_once('.')
+        .map(|(_, ext)| ext.to_ascii_lowercase())
+        .unwrap_or_default();
+    HeaderValue::from_static(match extension.as_str() {
+        "ts" => "video/mp2t",
+        "aac" => "audio/aac",
+        "mp4" | "m4s" | "m4v" => "video/mp4",
+        "m4a" => "audio/mp4",
+        "vtt" | "webvtt" => "text/vtt",
+        _ => "application/octet-stream",
+    })
+}
+
+fn internal_error() -> ProxyError {
+    (
+        StatusCode::INTERNAL_SERVER_ERROR,
+        "Internal server error".to_string(),
+    )
+}
+
+fn bad_gateway() -> ProxyError {
+    (StatusCode::BAD_GATEWAY, "Segment unavailable".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewrite_playlist_hides_upstream_urls() {
+        let base =
+            Url::parse("http://provider.example/live/user/secret/42.m3u8?token=abc").unwrap();
+        let playlist = "#EXTM3U\n\
+            #EXT-X-TARGETDURATION:6\n\
+            #EXT-X-KEY:METHOD=AES-128,URI=\"key.bin\",IV=0x1\n\
+            #EXT-X-MAP:URI=\"/init.mp4\"\n\
+            #EXTINF:6.0,\n\
+            seg-1.ts?token=abc\n\
+            \n\
+            #EXTINF:6.0,\n\
+            https://cdn.example/seg-2.ts\n\
+            #EXT-X-KEY:METHOD=SAMPLE-AES,URI=\"skd://key\"\n";
+
+        let mut seen = Vec::new();
+        let rewritten = rewrite_playlist(playlist, &base, |url| {
+            seen.push(url.to_string());
+            format!("/p/{}", seen.len())
+        });
+        assert_eq!(
+            seen,
+            [
+                "http://provider.example/live/user/secret/key.bin",
+                "http://provider.example/init.mp4",
+                "http://provider.example/live/user/secret/seg-1.ts?token=abc",
+                "https://cdn.example/seg-2.ts",
+            ]
+        );
+        assert!(!rewritten.contains("secret"));
+        assert!(rewritten.contains("#EXT-X-KEY:METHOD=AES-128,URI=\"/p/1\",IV=0x1\n"));
+        assert!(rewritten.contains("#EXT-X-MAP:URI=\"/p/2\"\n"));
+        assert!(rewritten.contains("\n/p/3\n\n#EXTINF:6.0,\n/p/4\n"));
+        assert!(rewritten.contains("URI=\"skd://key\""));
+
+        // Keys keep the extension and resolve only for their channel
+        let path = proxy_path(
+            7,
+            1,
+            &Url::parse("http://cdn.example/a/seg-9.ts?x=1").unwrap(),
+        );
+        assert!(path.starts_with("/stream/7/segment/") && path.ends_with(".ts"));
+        let key = path.rsplit('/').next().unwrap().split('.').next().unwrap();
+        assert_eq!(
+            lookup(7, key).unwrap().url,
+            "http://cdn.example/a/seg-9.ts?x=1"
+        );
+        assert!(lookup(8, key).is_none());
+
+        assert_eq!(
+            segment_content_type(Some("application/octet-stream"), "http://x/seg.ts?t=1"),
+            "video/mp2t"
+        );
+        assert!(is_playlist_type(
+            Some("application/x-mpegURL"),
+            "http://x/chunks"
+        ));
+    }
+
+    #[test]
+    fn test_live_session_forgets_preempted_session() {
+        let manager = StreamManager::new(2);
+        let session_id = manager
+            .start_session(StreamSession::new(901, 10, "HD".to_string()))
+            .unwrap();
+        let key = (901, None);
+        SESSIONS.lock().unwrap().insert(
+            key,
+            HlsSession {
+                session_id: session_id.clone(),
+                last_used: Instant::now(),
+            },
+        );
+        assert_eq!(live_session(&manager, &key), Some(session_id.clone()));
+        assert_eq!(
+            live_session(&manager, &(901, Some([10, 0, 0, 2].into()))),
+            None
+        );
+
+        assert!(manager.preempt(&session_id).is_some());
+        assert_eq!(live_session(&manager, &key), None);
+        assert!(!SESSIONS.lock().unwrap().contains_key(&key));
+    }
+}
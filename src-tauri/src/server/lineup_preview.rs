@@ -0,0 +1,329 @@
+//! Lineup Preview
+//!
+//! What Plex sees of a channel is spread over `/lineup.json` (guide number
+//! and stream URL), `/playlist.m3u` (tvg-id, logo and group) and `/epg.xml`
+//! (guide data). The preview joins them per channel, together with the stream
+//! the proxy will play, so "channel missing in Plex" can be diagnosed in one
+//! place. It is served by the `get_lineup_preview` command and, when enabled
+//! in the settings, by `/debug/lineup`.
+
+use chrono::{Duration, Utc};
+use diesel::dsl::count_star;
+use diesel::prelude::*;
+use serde::Serialize;
+use std::collections::HashMap;
+
+use crate::db::schema::{
+    accounts, channel_mappings, programs, settings, xmltv_channel_settings, xmltv_channels,
+    xtream_channels,
+};
+use crate::db::{DbPooledConnection, Setting};
+use crate::xtream::quality::qualities_from_json;
+
+use super::logos::{self, LogoOutput};
+use super::{auth, epg, m3u, stream};
+
+/// Settings key for serving the preview at `/debug/lineup`
+pub const DEBUG_LINEUP_KEY: &str = "debug_lineup_enabled";
+
+/// Stream the proxy plays for a channel
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupPreviewStream {
+    pub xtream_channel_id: i32,
+    pub name: String,
+    pub account_id: i32,
+    pub account_name: String,
+    /// Quality the proxy selects from the stream's qualities
+    pub quality: String,
+    pub is_primary: bool,
+}
+
+/// One channel as Plex sees it
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LineupPreviewEntry {
+    pub xmltv_channel_id: i32,
+    /// Channel number in `/lineup.json` and `tvg-chno`
+    pub guide_number: String,
+    pub guide_name: String,
+    /// `tvg-id` in the playlist and channel id in `/epg.xml`
+    pub tvg_id: String,
+    /// Logo as written to the playlist
+    pub icon_url: Option<String>,
+    pub group: Option<String>,
+    /// Stream URL as given to Plex
+    pub stream_url: String,
+    /// None when no mapped stream is on an active account
+    pub stream: Option<LineupPreviewStream>,
+    /// Mapped streams on active accounts, including the one played
+    pub stream_count: usize,
+    /// Programs in `/epg.xml` for this channel (placeholders for synthetic channels)
+    pub guide_programs: i64,
+    pub is_synthetic: bool,
+    /// Problems that keep the channel from working in Plex
+    pub issues: Vec<String>,
+}
+
+/// Whether `/debug/lineup` is served
+pub fn get_debug_lineup_enabled(conn: &mut SqliteConnection) -> bool {
+    settings::table
+        .filter(settings::key.eq(DEBUG_LINEUP_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value == "true")
+        .unwrap_or(false)
+}
+
+/// Enable or disable `/debug/lineup`
+pub fn set_debug_lineup_enabled(conn: &mut SqliteConnection, enabled: bool) -> QueryResult<()> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(DEBUG_LINEUP_KEY, enabled.to_string()))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Mapped stream row: (xmltv channel, xtream channel, name, qualities,
+/// account, account name, account active, primary)
+type MappedStreamRow = (
+    i32,
+    i32,
+    String,
+    Option<String>,
+    i32,
+    String,
+    i32,
+    Option<i32>,
+);
+
+/// Qualities of a mapped stream row
+fn row_qualities(row: &MappedStreamRow) -> Vec<String> {
+    row.3
+        .as_deref()
+        .map(qualities_from_json)
+        .unwrap_or_default()
+}
+
+/// Build the preview of the published lineup, in lineup order
+///
+/// `host` and `port` are the address advertised to Plex.
+pub fn build_lineup_preview(
+    conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+) -> QueryResult<Vec<LineupPreviewEntry>> {
+    let channels = m3u::get_enabled_channels_for_m3u(conn)?;
+    let ids: Vec<i32> = channels.iter().map(|c| c.xmltv_channel_id).collect();
+    let logo_mode = logos::get_logo_mode(conn, LogoOutput::M3u);
+    let token = auth::get_access_token(conn);
+
+    let synthetic: HashMap<i32, bool> = xmltv_channels::table
+        .filter(xmltv_channels::id.eq_any(&ids))
+        .select((
+            xmltv_channels::id.assume_not_null(),
+            xmltv_channels::is_synthetic,
+        ))
+        .load::<(i32, Option<i32>)>(conn)?
+        .into_iter()
+        .map(|(id, is_synthetic)| (id, is_synthetic.unwrap_or(0) != 0))
+        .collect();
+
+    // Same window as /epg.xml
+    let now = Utc::now();
+    let window_days = epg::get_epg_window_days(conn);
+    let from = (now - Duration::hours(1))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let until = (now + Duration::days(i64::from(window_days)))
+        .format("%Y-%m-%dT%H:%M:%SZ")
+        .to_string();
+    let program_counts: HashMap<i32, i64> = programs::table
+        .filter(programs::xmltv_channel_id.eq_any(&ids))
+        .filter(programs::start_time.ge(from))
+        .filter(programs::start_time.lt(until))
+        .group_by(programs::xmltv_channel_id)
+        .select((programs::xmltv_channel_id, count_star()))
+        .load::<(i32, i64)>(conn)?
+        .into_iter()
+        .collect();
+
+    // Quality caps, as applied by the proxy
+    let global_quality = stream::get_global_preferred_quality(conn);
+    let channel_quality: HashMap<i32, String> = xmltv_channel_settings::table
+        .filter(xmltv_channel_settings::xmltv_channel_id.eq_any(&ids))
+        .filter(xmltv_channel_settings::preferred_quality.is_not_null())
+        .select((
+            xmltv_channel_settings::xmltv_channel_id,
+            xmltv_channel_settings::preferred_quality.assume_not_null(),
+        ))
+        .load::<(i32, String)>(conn)?
+        .into_iter()
+        .collect();
+
+    // Streams in the order the proxy tries them
+    let mut streams: HashMap<i32, Vec<MappedStreamRow>> = HashMap::new();
+    for row in channel_mappings::table
+        .inner_join(
+            xtream_channels::table
+                .on(channel_mappings::xtream_channel_id.eq(xtream_channels::id.assume_not_null())),
+        )
+        .inner_join(
+            accounts::table.on(xtream_channels::account_id.eq(accounts::id.assume_not_null())),
+        )
+        .filter(channel_mappings::xmltv_channel_id.eq_any(&ids))
+        .order((
+            channel_mappings::stream_priority.asc(),
+            channel_mappings::is_primary.desc(),
+        ))
+        .select((
+            channel_mappings::xmltv_channel_id,
+            xtream_channels::id.assume_not_null(),
+            xtream_channels::name,
+            xtream_channels::qualities,
+            accounts::id.assume_not_null(),
+            accounts::name,
+            accounts::is_active,
+            channel_mappings::is_primary,
+        ))
+        .load::<MappedStreamRow>(conn)?
+    {
+        streams.entry(row.0).or_default().push(row);
+    }
+
+    let mut tvg_id_counts: HashMap<&str, usize> = HashMap::new();
+    for channel in &channels {
+        *tvg_id_counts.entry(channel.tvg_id.as_str()).or_default() += 1;
+    }
+
+    let entries = channels
+        .iter()
+        .map(|channel| {
+            let id = channel.xmltv_channel_id;
+            let is_synthetic = synthetic.get(&id).copied().unwrap_or(false);
+            let max_quality = channel_quality
+                .get(&id)
+                .or(global_quality.as_ref())
+                .and_then(|preference| stream::quality_cap(preference));
+            let mut active: Vec<(&MappedStreamRow, Vec<String>)> = streams
+                .get(&id)
+                .map(|rows| {
+                    rows.iter()
+                        .filter(|row| row.6 != 0)
+                        .map(|row| (row, row_qualities(row)))
+                        .collect()
+                })
+                .unwrap_or_default();
+            stream::order_by_preferred_quality(&mut active, max_quality, |row| &row.1);
+            let stream = active.first().map(|(row, qualities)| LineupPreviewStream {
+                xtream_channel_id: row.1,
+                name: row.2.clone(),
+                account_id: row.4,
+                account_name: row.5.clone(),
+                quality: stream::select_quality(qualities, max_quality),
+                is_primary: row.7.unwrap_or(0) != 0,
+            });
+            let guide_programs = if is_synthetic {
+                // Two-hour placeholder blocks over the window
+                i64::from(window_days) * 12
+            } else {
+                program_counts.get(&id).copied().unwrap_or(0)
+            };
+
+            let mut issues = Vec::new();
+            if stream.is_none() {
+                issues.push("No mapped stream is on an active account; playback fails".to_string());
+            }
+            if guide_programs == 0 {
+                issues
+                    .push("No programs in the guide window; Plex shows no guide data".to_string());
+            }
+            if tvg_id_counts
+                .get(channel.tvg_id.as_str())
+                .copied()
+                .unwrap_or(0)
+                > 1
+            {
+                issues.push(
+                    "tvg-id is shared with another channel; Plex may merge their guides"
+                        .to_string(),
+                );
+            }
+
+            LineupPreviewEntry {
+                xmltv_channel_id: id,
+                guide_number: channel.channel_number.to_string(),
+                guide_name: channel.display_name.clone(),
+                tvg_id: channel.tvg_id.clone(),
+                icon_url: logos::resolve_logo_url(logo_mode, host, port, channel.logo_url.clone()),
+                group: channel.group.clone(),
+                stream_url: auth::with_access_token(
+                    format!("http://{}:{}/stream/{}", host, port, id),
+                    token.as_deref(),
+                ),
+                stream,
+                stream_count: active.len(),
+                guide_programs,
+                is_synthetic,
+                issues,
+            }
+        })
+        .collect();
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::r2d2::{ConnectionManager, Pool};
+
+    #[test]
+    fn test_preview_reports_stream_and_guide_issues() {
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        let mut conn = pool.get().unwrap();
+        crate::db::run_migrations(&mut conn).unwrap();
+        diesel::sql_query(
+            "INSERT INTO accounts (id, name, server_url, username, password_encrypted, is_active) VALUES
+                (1, 'Main', 'http://a.local', 'a', x'', 1), (2, 'Old', 'http://b.local', 'b', x'', 0);
+             ",
+        )
+        .execute(&mut conn)
+        .unwrap();
+        for sql in [
+            "INSERT INTO xmltv_sources (id, name, url) VALUES (1, 'Guide', 'http://example.com/epg.xml')",
+            "INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (1, 1, 'news.uk', 'News'), (2, 1, 'film.uk', 'Film')",
+            "INSERT INTO xmltv_channel_settings (xmltv_channel_id, is_enabled, plex_display_order) VALUES (1, 1, 0), (2, 1, 1)",
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name, qualities) VALUES (10, 1, 100, 'UK: News HD', '[\"HD\",\"SD\"]'), (20, 2, 200, 'UK: Film', NULL)",
+            "INSERT INTO channel_mappings (xmltv_channel_id, xtream_channel_id, match_confidence, is_manual, is_primary, stream_priority) VALUES (1, 10, 1.0, 0, 1, 0), (2, 20, 1.0, 0, 1, 0)",
+        ] {
+            diesel::sql_query(sql).execute(&mut conn).unwrap();
+        }
+        let start = (Utc::now() + Duration::hours(1))
+            .format("%Y-%m-%dT%H:%M:%SZ")
+            .to_string();
+        diesel::insert_into(programs::table)
+            .values(crate::db::NewProgram::new(1, "Headlines", &start, &start))
+            .execute(&mut conn)
+            .unwrap();
+
+        let preview = build_lineup_preview(&mut conn, "192.168.1.5", 5004).unwrap();
+        assert_eq!(preview.len(), 2);
+
+        let news = &preview[0];
+        assert_eq!(news.guide_number, "1");
+        assert_eq!(news.tvg_id, "news.uk");
+        assert_eq!(news.stream_url, "http://192.168.1.5:5004/stream/1");
+        let stream = news.stream.as_ref().unwrap();
+        assert_eq!(stream.account_name, "Main");
+        assert_eq!(stream.quality, "HD");
+        assert_eq!(news.guide_programs, 1);
+        assert!(news.issues.is_empty());
+
+        // Only mapped to a disabled account, and no guide data
+        let film = &preview[1];
+        assert!(film.stream.is_none());
+        assert_eq!(film.stream_count, 0);
+        assert_eq!(film.issues.len(), 2);
+    }
+}
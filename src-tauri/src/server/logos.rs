@@ -0,0 +1,621 @@
+//! Channel Logo Cache Module
+//!
+//! Downloads the channel icons referenced by the Plex lineup into the app data
+//! directory so the M3U and EPG outputs can point Plex at a local `/logo/{key}`
+//! proxy instead of hundreds of remote (and often flaky) image hosts.
+//!
+//! - Pre-warm job fetches logos with bounded concurrency and a retry policy
+//! - Logos that cannot be fetched are remembered and served as a placeholder
+//! - Each output (M3U, EPG) can independently choose remote URLs or the local proxy
+
+use diesel::prelude::*;
+use futures::stream::{self, StreamExt};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use crate::db::schema::settings;
+use crate::db::{DbPool, DbPooledConnection, Setting, SharedDbPool};
+
+/// Subdirectory of the app data directory holding cached logos
+pub const LOGO_CACHE_DIR: &str = "logo_cache";
+
+/// Settings key for the M3U playlist logo mode
+pub const LOGO_MODE_M3U_KEY: &str = "logo_mode_m3u";
+
+/// Settings key for the XMLTV EPG logo mode
+pub const LOGO_MODE_EPG_KEY: &str = "logo_mode_epg";
+
+/// Attempts per logo before it is marked as failed
+const LOGO_FETCH_ATTEMPTS: u32 = 3;
+
+/// Delay before the first retry (doubled for each further attempt)
+const LOGO_RETRY_BASE_DELAY: Duration = Duration::from_millis(500);
+
+/// Per-request timeout for logo downloads
+const LOGO_FETCH_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Logos larger than this are rejected (protects small devices)
+const LOGO_MAX_BYTES: usize = 2 * 1024 * 1024;
+
+/// Cached logos older than this are refreshed by the pre-warm job
+const LOGO_CACHE_MAX_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+/// Failed logos are not retried by the pre-warm job until this has elapsed
+const LOGO_FAILURE_RETRY_AFTER: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Extension of the marker file written when a logo could not be fetched
+const FAILED_MARKER_EXTENSION: &str = "failed";
+
+/// 1x1 transparent PNG served when a logo is missing or failed to download
+pub const PLACEHOLDER_LOGO_PNG: &[u8] = &[
+    0x89, 0x50, 0x4E, 0x47, 0x0D, 0x0A, 0x1A, 0x0A, 0x00, 0x00, 0x00, 0x0D, 0x49, 0x48, 0x44,
+    0x52, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x01, 0x08, 0x06, 0x00, 0x00, 0x00, 0x1F,
+    0x15, 0xC4, 0x89, 0x00, 0x00, 0x00, 0x0A, 0x49, 0x44, 0x41, 0x54, 0x78, 0x9C, 0x63, 0x00,
+    0x01, 0x00, 0x00, 0x05, 0x00, 0x01, 0x0D, 0x0A, 0x2D, 0xB4, 0x00, 0x00, 0x00, 0x00, 0x49,
+    0x45, 0x4E, 0x44, 0xAE, 0x42, 0x60, 0x82,
+];
+
+/// How logo URLs are emitted in a generated output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogoMode {
+    /// Emit the original remote icon URL (default, previous behavior)
+    Remote,
+    /// Emit a local `/logo/{key}` URL served from the logo cache
+    Proxy,
+}
+
+impl LogoMode {
+    /// Parse a mode from its settings value
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "remote" => Some(LogoMode::Remote),
+            "proxy" => Some(LogoMode::Proxy),
+            _ => None,
+        }
+    }
+
+    /// Settings value for this mode
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogoMode::Remote => "remote",
+            LogoMode::Proxy => "proxy",
+        }
+    }
+}
+
+/// Generated outputs that embed channel logos
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogoOutput {
+    M3u,
+    Epg,
+}
+
+impl LogoOutput {
+    fn settings_key(&self) -> &'static str {
+        match self {
+            LogoOutput::M3u => LOGO_MODE_M3U_KEY,
+            LogoOutput::Epg => LOGO_MODE_EPG_KEY,
+        }
+    }
+}
+
+/// Result summary of a pre-warm run
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LogoPrewarmSummary {
+    /// Distinct logo URLs referenced by the lineup
+    pub total: usize,
+    /// Logos that were already cached and fresh
+    pub already_cached: usize,
+    /// Logos downloaded during this run
+    pub fetched: usize,
+    /// Logos that failed after all retries (served as placeholder)
+    pub failed: usize,
+    /// Recently failed logos that were skipped this run
+    pub skipped_failed: usize,
+}
+
+/// Get the configured logo mode for an output
+///
+/// Defaults to `LogoMode::Remote` when unset or invalid.
+pub fn get_logo_mode(conn: &mut diesel::SqliteConnection, output: LogoOutput) -> LogoMode {
+    settings::table
+        .filter(settings::key.eq(output.settings_key()))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| LogoMode::parse(&value))
+        .unwrap_or(LogoMode::Remote)
+}
+
+/// Persist the logo mode for an output
+pub fn set_logo_mode(
+    conn: &mut diesel::SqliteConnection,
+    output: LogoOutput,
+    mode: LogoMode,
+) -> Result<(), diesel::result::Error> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(output.settings_key(), mode.as_str()))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// Get the logo cache directory inside the app data directory
+pub fn logo_cache_dir(app_data_dir: &Path) -> PathBuf {
+    app_data_dir.join(LOGO_CACHE_DIR)
+}
+
+/// Compute the stable cache key for a logo URL
+///
+/// The key is the first 32 hex characters of the SHA-256 of the URL, which keeps
+/// file names short while making collisions practically impossible.
+pub fn logo_cache_key(url: &str) -> String {
+    let digest = format!("{:x}", Sha256::digest(url.trim().as_bytes()));
+    digest[..32].to_string()
+}
+
+/// Check that a key has the shape produced by `logo_cache_key`
+///
+/// Guards the `/logo/{key}` route against path traversal.
+pub fn is_valid_logo_key(key: &str) -> bool {
+    key.len() == 32 && key.chars().all(|c| c.is_ascii_hexdigit() && !c.is_ascii_uppercase())
+}
+
+/// Build the proxy URL for a remote logo on the advertised `host`
+pub fn proxy_logo_url(host: &str, port: u16, url: &str) -> String {
+    format!("http://{}:{}/logo/{}", host, port, logo_cache_key(url))
+}
+
+/// Resolve the logo URL emitted for an output according to its mode
+pub fn resolve_logo_url(
+    mode: LogoMode,
+    host: &str,
+    port: u16,
+    url: Option<String>,
+) -> Option<String> {
+    match mode {
+        LogoMode::Remote => url,
+        LogoMode::Proxy => url.map(|u| proxy_logo_url(host, port, &u)),
+    }
+}
+
+/// Detect the image content type from its leading bytes
+pub fn sniff_image_content_type(data: &[u8]) -> &'static str {
+    if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "image/png"
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "image/jpeg"
+    } else if data.starts_with(b"GIF8") {
+        "image/gif"
+    } else if data.len() >= 12 && &data[0..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        "image/webp"
+    } else if data.starts_with(b"<svg") || data.starts_with(b"<?xml") {
+        "image/svg+xml"
+    } else {
+        "application/octet-stream"
+    }
+}
+
+/// Read a cached logo by key
+///
+/// Returns None if the logo is not cached (or the key is invalid).
+pub fn read_cached_logo(app_data_dir: &Path, key: &str) -> Option<Vec<u8>> {
+    if !is_valid_logo_key(key) {
+        return None;
+    }
+    std::fs::read(logo_cache_dir(app_data_dir).join(key)).ok()
+}
+
+/// Collect the distinct logo URLs referenced by the current lineup
+///
+/// Uses the M3U channel query (XMLTV icon with Xtream fallback), which is a
+/// superset of the icons emitted in the EPG.
+pub fn collect_lineup_logo_urls(
+    conn: &mut DbPooledConnection,
+) -> Result<Vec<String>, diesel::result::Error> {
+    let channels = super::m3u::get_enabled_channels_for_m3u(conn)?;
+
+    let mut seen = HashSet::new();
+    Ok(channels
+        .into_iter()
+        .filter_map(|c| c.logo_url)
+        .map(|url| url.trim().to_string())
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        .filter(|url| seen.insert(url.clone()))
+        .collect())
+}
+
+/// Collect the distinct icons of all XMLTV channels and provider streams
+///
+/// Cached for icon similarity matching, which compares icons of channels and
+/// streams that aren't in the lineup yet.
+pub fn collect_matching_logo_urls(
+    conn: &mut diesel::SqliteConnection,
+) -> Result<Vec<String>, diesel::result::Error> {
+    use crate::db::schema::{xmltv_channels, xtream_channels};
+
+    let mut urls: Vec<Option<String>> = xmltv_channels::table
+        .select(xmltv_channels::icon)
+        .distinct()
+        .load(conn)?;
+    urls.extend(
+        xtream_channels::table
+            .select(xtream_channels::stream_icon)
+            .distinct()
+            .load::<Option<String>>(conn)?,
+    );
+
+    let mut seen = HashSet::new();
+    Ok(urls
+        .into_iter()
+        .flatten()
+        .map(|url| url.trim().to_string())
+        .filter(|url| url.starts_with("http://") || url.starts_with("https://"))
+        .filter(|url| seen.insert(url.clone()))
+        .collect())
+}
+
+/// Check whether a file exists and was modified within `max_age`
+fn is_fresh(path: &Path, max_age: Duration) -> bool {
+    std::fs::metadata(path)
+        .and_then(|m| m.modified())
+        .ok()
+        .and_then(|modified| SystemTime::now().duration_since(modified).ok())
+        .map(|age| age < max_age)
+        .unwrap_or(false)
+}
+
+/// Outcome of processing a single logo during pre-warm
+enum PrewarmOutcome {
+    AlreadyCached,
+    Fetched,
+    Failed,
+    SkippedFailed,
+}
+
+/// Download a logo once, validating status, size and content
+async fn fetch_logo_once(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let response = client.get(url).send().await.map_err(|e| e.to_string())?;
+
+    if !response.status().is_success() {
+        return Err(format!("HTTP {}", response.status()));
+    }
+
+    if let Some(length) = response.content_length() {
+        if length as usize > LOGO_MAX_BYTES {
+            return Err(format!("Logo too large ({} bytes)", length));
+        }
+    }
+
+    let bytes = response.bytes().await.map_err(|e| e.to_string())?;
+    if bytes.is_empty() {
+        return Err("Empty response".to_string());
+    }
+    if bytes.len() > LOGO_MAX_BYTES {
+        return Err(format!("Logo too large ({} bytes)", bytes.len()));
+    }
+
+    Ok(bytes.to_vec())
+}
+
+/// Download a logo with exponential backoff between attempts
+async fn fetch_logo_with_retry(client: &reqwest::Client, url: &str) -> Result<Vec<u8>, String> {
+    let mut last_error = String::new();
+
+    for attempt in 0..LOGO_FETCH_ATTEMPTS {
+        if attempt > 0 {
+            tokio::time::sleep(LOGO_RETRY_BASE_DELAY * 2u32.pow(attempt - 1)).await;
+        }
+
+        match fetch_logo_once(client, url).await {
+            Ok(data) => return Ok(data),
+            Err(e) => last_error = e,
+        }
+    }
+
+    Err(last_error)
+}
+
+/// Store a downloaded logo (and clear any previous failure marker)
+fn store_logo(cache_dir: &Path, key: &str, data: &[u8]) -> std::io::Result<()> {
+    std::fs::write(cache_dir.join(key), data)?;
+    let _ = std::fs::remove_file(cache_dir.join(format!("{}.{}", key, FAILED_MARKER_EXTENSION)));
+    Ok(())
+}
+
+/// Remember that a logo failed so it is served as a placeholder
+fn mark_logo_failed(cache_dir: &Path, key: &str, error: &str) {
+    let marker = cache_dir.join(format!("{}.{}", key, FAILED_MARKER_EXTENSION));
+    if let Err(e) = std::fs::write(&marker, error) {
+        eprintln!("Logo cache - failed to write failure marker {}: {}", marker.display(), e);
+    }
+}
+
+/// Pre-warm a single logo
+async fn prewarm_logo(client: &reqwest::Client, cache_dir: &Path, url: &str) -> PrewarmOutcome {
+    let key = logo_cache_key(url);
+
+    if is_fresh(&cache_dir.join(&key), LOGO_CACHE_MAX_AGE) {
+        return PrewarmOutcome::AlreadyCached;
+    }
+
+    let failed_marker = cache_dir.join(format!("{}.{}", key, FAILED_MARKER_EXTENSION));
+    if is_fresh(&failed_marker, LOGO_FAILURE_RETRY_AFTER) {
+        return PrewarmOutcome::SkippedFailed;
+    }
+
+    match fetch_logo_with_retry(client, url).await {
+        Ok(data) => match store_logo(cache_dir, &key, &data) {
+            Ok(()) => PrewarmOutcome::Fetched,
+            Err(e) => {
+                eprintln!("Logo cache - failed to store {}: {}", url, e);
+                PrewarmOutcome::Failed
+            }
+        },
+        Err(e) => {
+            eprintln!("Logo cache - giving up on {} after {} attempts: {}", url, LOGO_FETCH_ATTEMPTS, e);
+            mark_logo_failed(cache_dir, &key, &e);
+            PrewarmOutcome::Failed
+        }
+    }
+}
+
+/// Build the HTTP client used for logo downloads
+fn build_logo_client() -> Result<reqwest::Client, String> {
+    crate::dns::configure(reqwest::Client::builder())
+        .timeout(LOGO_FETCH_TIMEOUT)
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))
+}
+
+/// Pre-warm the logo cache for every logo referenced by the lineup
+///
+/// With icon similarity matching enabled, the icons of all channels and
+/// streams are cached as well. Downloads a few logos at once (one in low resource mode), retrying each up to
+/// `LOGO_FETCH_ATTEMPTS` times. Logos that still fail get a failure marker and are
+/// served as a placeholder until a later run succeeds.
+pub async fn prewarm_logo_cache(pool: &DbPool, app_data_dir: &Path) -> Result<LogoPrewarmSummary, String> {
+    let urls = {
+        let mut conn = pool
+            .get()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+        let mut urls = collect_lineup_logo_urls(&mut conn)
+            .map_err(|e| format!("Failed to load channel logos: {}", e))?;
+        if crate::matcher::load_icon_match_config(&mut conn).enabled {
+            let lineup: HashSet<String> = urls.iter().cloned().collect();
+            let matching = collect_matching_logo_urls(&mut conn)
+                .map_err(|e| format!("Failed to load channel icons: {}", e))?;
+            urls.extend(matching.into_iter().filter(|url| !lineup.contains(url)));
+        }
+        urls
+    };
+
+    let cache_dir = logo_cache_dir(app_data_dir);
+    std::fs::create_dir_all(&cache_dir)
+        .map_err(|e| format!("Cannot create logo cache directory '{}': {}", cache_dir.display(), e))?;
+
+    let client = build_logo_client()?;
+
+    let mut summary = LogoPrewarmSummary {
+        total: urls.len(),
+        ..Default::default()
+    };
+
+    let outcomes: Vec<PrewarmOutcome> = stream::iter(urls)
+        .map(|url| {
+            let client = client.clone();
+            let cache_dir = cache_dir.clone();
+            async move { prewarm_logo(&client, &cache_dir, &url).await }
+        })
+        .buffer_unordered(crate::low_resource::logo_prewarm_concurrency())
+        .collect()
+        .await;
+
+    for outcome in outcomes {
+        match outcome {
+            PrewarmOutcome::AlreadyCached => summary.already_cached += 1,
+            PrewarmOutcome::Fetched => summary.fetched += 1,
+            PrewarmOutcome::Failed => summary.failed += 1,
+            PrewarmOutcome::SkippedFailed => summary.skipped_failed += 1,
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Fetch a single lineup logo on demand (used on a cache miss)
+///
+/// Only URLs that are part of the current lineup are fetched, so the proxy
+/// cannot be abused to download arbitrary content. A single attempt is made
+/// to keep the request fast; the pre-warm job handles retries.
+pub async fn fetch_logo_on_demand(pool: &DbPool, app_data_dir: &Path, key: &str) -> Option<Vec<u8>> {
+    if !is_valid_logo_key(key) {
+        return None;
+    }
+
+    let cache_dir = logo_cache_dir(app_data_dir);
+    if is_fresh(
+        &cache_dir.join(format!("{}.{}", key, FAILED_MARKER_EXTENSION)),
+        LOGO_FAILURE_RETRY_AFTER,
+    ) {
+        return None;
+    }
+
+    let url = {
+        let mut conn = pool.get().ok()?;
+        collect_lineup_logo_urls(&mut conn)
+            .ok()?
+            .into_iter()
+            .find(|url| logo_cache_key(url) == key)?
+    };
+
+    let client = build_logo_client().ok()?;
+    std::fs::create_dir_all(&cache_dir).ok()?;
+
+    match fetch_logo_once(&client, &url).await {
+        Ok(data) => {
+            if let Err(e) = store_logo(&cache_dir, key, &data) {
+                eprintln!("Logo cache - failed to store {}: {}", url, e);
+            }
+            Some(data)
+        }
+        Err(e) => {
+            mark_logo_failed(&cache_dir, key, &e);
+            None
+        }
+    }
+}
+
+/// Check whether any output is configured to use the local logo proxy
+pub fn is_proxy_mode_enabled(conn: &mut diesel::SqliteConnection) -> bool {
+    get_logo_mode(conn, LogoOutput::M3u) == LogoMode::Proxy
+        || get_logo_mode(conn, LogoOutput::Epg) == LogoMode::Proxy
+}
+
+/// Delay before the first background pre-warm after startup
+const LOGO_PREWARM_STARTUP_DELAY: Duration = Duration::from_secs(60);
+
+/// Interval between background pre-warm runs
+const LOGO_PREWARM_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Background pre-warm job
+///
+/// Runs shortly after startup and then periodically. Runs are skipped while
+/// no output uses the local logo proxy and icon matching is off, so
+/// remote-mode users never download logos.
+pub async fn run_logo_prewarm_job(shared_pool: SharedDbPool, app_data_dir: PathBuf) {
+    tokio::time::sleep(LOGO_PREWARM_STARTUP_DELAY).await;
+
+    loop {
+        // Re-read the pool each run so workspace switches are followed
+        let pool = shared_pool.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let prewarm_enabled = pool
+            .get()
+            .map(|mut conn| {
+                is_proxy_mode_enabled(&mut conn)
+                    || crate::matcher::load_icon_match_config(&mut conn).enabled
+            })
+            .unwrap_or(false);
+
+        if prewarm_enabled {
+            match prewarm_logo_cache(&pool, &app_data_dir).await {
+                Ok(summary) => tracing::info!(
+                    "Logo cache pre-warm completed: {} logos, {} fetched, {} cached, {} failed",
+                    summary.total,
+                    summary.fetched,
+                    summary.already_cached,
+                    summary.failed
+                ),
+                Err(e) => tracing::warn!("Logo cache pre-warm failed: {}", e),
+            }
+        }
+
+        tokio::time::sleep(LOGO_PREWARM_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_logo_mode_round_trip() {
+        assert_eq!(LogoMode::parse("remote"), Some(LogoMode::Remote));
+        assert_eq!(LogoMode::parse("proxy"), Some(LogoMode::Proxy));
+        assert_eq!(LogoMode::parse("invalid"), None);
+        assert_eq!(LogoMode::Proxy.as_str(), "proxy");
+        assert_eq!(LogoMode::Remote.as_str(), "remote");
+    }
+
+    #[test]
+    fn test_logo_cache_key_is_stable_and_valid() {
+        let key1 = logo_cache_key("http://example.com/logo.png");
+        let key2 = logo_cache_key("http://example.com/logo.png");
+        let key3 = logo_cache_key("http://example.com/other.png");
+
+        assert_eq!(key1, key2);
+        assert_ne!(key1, key3);
+        assert!(is_valid_logo_key(&key1));
+    }
+
+    #[test]
+    fn test_logo_cache_key_ignores_surrounding_whitespace() {
+        assert_eq!(
+            logo_cache_key(" http://example.com/logo.png "),
+            logo_cache_key("http://example.com/logo.png")
+        );
+    }
+
+    #[test]
+    fn test_invalid_logo_keys_rejected() {
+        assert!(!is_valid_logo_key("../../etc/passwd"));
+        assert!(!is_valid_logo_key("abc"));
+        assert!(!is_valid_logo_key(&"A".repeat(32)));
+        assert!(!is_valid_logo_key(&"g".repeat(32)));
+    }
+
+    #[test]
+    fn test_resolve_logo_url_remote_mode_passthrough() {
+        let url = Some("http://example.com/logo.png".to_string());
+        assert_eq!(
+            resolve_logo_url(LogoMode::Remote, "127.0.0.1", 5004, url.clone()),
+            url
+        );
+    }
+
+    #[test]
+    fn test_resolve_logo_url_proxy_mode() {
+        let url = "http://example.com/logo.png";
+        let resolved =
+            resolve_logo_url(LogoMode::Proxy, "127.0.0.1", 5004, Some(url.to_string())).unwrap();
+        assert_eq!(
+            resolved,
+            format!("http://127.0.0.1:5004/logo/{}", logo_cache_key(url))
+        );
+    }
+
+    #[test]
+    fn test_resolve_logo_url_none_stays_none() {
+        assert_eq!(
+            resolve_logo_url(LogoMode::Proxy, "127.0.0.1", 5004, None),
+            None
+        );
+    }
+
+    #[test]
+    fn test_sniff_image_content_type() {
+        assert_eq!(sniff_image_content_type(PLACEHOLDER_LOGO_PNG), "image/png");
+        assert_eq!(sniff_image_content_type(&[0xFF, 0xD8, 0xFF, 0xE0]), "image/jpeg");
+        assert_eq!(sniff_image_content_type(b"GIF89a"), "image/gif");
+        assert_eq!(sniff_image_content_type(b"RIFF\0\0\0\0WEBPVP8 "), "image/webp");
+        assert_eq!(sniff_image_content_type(b"<svg xmlns=\"\"/>"), "image/svg+xml");
+        assert_eq!(sniff_image_content_type(b"garbage"), "application/octet-stream");
+    }
+
+    #[test]
+    fn test_read_cached_logo_rejects_invalid_key() {
+        let dir = std::env::temp_dir();
+        assert!(read_cached_logo(&dir, "../secret").is_none());
+    }
+
+    #[test]
+    fn test_store_logo_clears_failure_marker() {
+        let dir = std::env::temp_dir().join(format!("sf-logo-test-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let key = logo_cache_key("http://example.com/a.png");
+
+        mark_logo_failed(&dir, &key, "HTTP 404");
+        assert!(dir.join(format!("{}.failed", key)).exists());
+
+        store_logo(&dir, &key, PLACEHOLDER_LOGO_PNG).unwrap();
+        assert!(!dir.join(format!("{}.failed", key)).exists());
+        assert!(is_fresh(&dir.join(&key), LOGO_CACHE_MAX_AGE));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
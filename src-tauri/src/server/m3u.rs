@@ -5,11 +5,23 @@
 //! and Xtream streams are video sources.
 //!
 //! Story 4-1: Serve M3U Playlist Endpoint
+//!
+//! Channels carry the category of their primary Xtream stream as
+//! `group-title`, and the playlist can be filtered to one group or category
+//! (`/playlist.m3u?group=Sports`, `/playlist.m3u?category_id=5`) so different
+//! clients can pull subsets of the lineup. Device profile playlists
+//! (`/device/{profile}/playlist.m3u`) advertise the profile's stream format.
 
 use diesel::prelude::*;
 use diesel::sql_types::{Integer, Nullable, Text};
+use std::collections::HashSet;
 
+use crate::channel_numbers::resolve_channel_numbers;
 use crate::db::DbPooledConnection;
+use crate::plugins::PlaylistEntry;
+
+use super::logos::{self, LogoOutput};
+use super::stream::{channel_stream_url, StreamFormat};
 
 /// Internal representation of a channel for M3U generation
 #[derive(Debug, Clone)]
@@ -18,12 +30,62 @@ pub struct M3uChannel {
     pub xmltv_channel_id: i32,
     /// Display name from XMLTV (used in playlist)
     pub display_name: String,
-    /// Channel number for Plex (from plex_display_order)
+    /// Channel number for Plex (see `crate::channel_numbers`)
     pub channel_number: i32,
     /// Logo URL (XMLTV icon with Xtream fallback)
     pub logo_url: Option<String>,
     /// XMLTV channel_id (used for tvg-id attribute)
     pub tvg_id: String,
+    /// Category name of the primary Xtream stream (used for group-title)
+    pub group: Option<String>,
+    /// Category ID of the primary Xtream stream
+    pub category_id: Option<i32>,
+}
+
+/// Playlist filter from `/playlist.m3u` query parameters
+///
+/// Both filters apply when both are given.
+#[derive(Debug, Clone, Default, serde::Deserialize)]
+pub struct M3uFilter {
+    /// Group (category name), matched case-insensitively
+    pub group: Option<String>,
+    /// Xtream category ID
+    pub category_id: Option<i32>,
+    /// XMLTV channel IDs of a device profile (not a query parameter)
+    #[serde(skip)]
+    pub channel_ids: Option<HashSet<i32>>,
+    /// Stream format of a device profile (not a query parameter; None: MPEG-TS)
+    #[serde(skip)]
+    pub stream_format: Option<StreamFormat>,
+}
+
+impl M3uFilter {
+    /// Whether no filter is set (the full playlist)
+    pub fn is_empty(&self) -> bool {
+        self.group.as_deref().is_none_or(|g| g.trim().is_empty())
+            && self.category_id.is_none()
+            && self.channel_ids.is_none()
+            && self.stream_format.is_none()
+    }
+
+    /// Whether the channel belongs in the filtered playlist
+    pub fn matches(&self, channel: &M3uChannel) -> bool {
+        let group_matches = match self.group.as_deref().map(str::trim) {
+            None | Some("") => true,
+            Some(group) => channel
+                .group
+                .as_deref()
+                .is_some_and(|g| g.trim().eq_ignore_ascii_case(group)),
+        };
+        let category_matches = self
+            .category_id
+            .is_none_or(|id| channel.category_id == Some(id));
+        let channel_matches = self
+            .channel_ids
+            .as_ref()
+            .is_none_or(|ids| ids.contains(&channel.xmltv_channel_id));
+        group_matches && category_matches && channel_matches
+    }
 }
 
 /// Query result for enabled channels with resolved logos
@@ -39,8 +101,14 @@ struct EnabledChannelRow {
     icon: Option<String>,
     #[diesel(sql_type = Nullable<Integer>)]
     plex_display_order: Option<i32>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    channel_number: Option<i32>,
     #[diesel(sql_type = Nullable<Text>)]
     xtream_fallback_icon: Option<String>,
+    #[diesel(sql_type = Nullable<Text>)]
+    primary_category_name: Option<String>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    primary_category_id: Option<i32>,
 }
 
 /// Query result for Xtream stream icon fallback
@@ -68,6 +136,7 @@ pub fn get_enabled_channels_for_m3u(conn: &mut DbPooledConnection) -> Result<Vec
             xc.display_name,
             xc.icon,
             xcs.plex_display_order,
+            xcs.channel_number,
             (
                 SELECT xtc.stream_icon
                 FROM channel_mappings cm
@@ -78,9 +147,31 @@ pub fn get_enabled_channels_for_m3u(conn: &mut DbPooledConnection) -> Result<Vec
                     cm.stream_priority ASC,
                     cm.id ASC
                 LIMIT 1
-            ) as xtream_fallback_icon
+            ) as xtream_fallback_icon,
+            (
+                SELECT xtc.category_name
+                FROM channel_mappings cm
+                INNER JOIN xtream_channels xtc ON cm.xtream_channel_id = xtc.id
+                WHERE cm.xmltv_channel_id = xc.id
+                ORDER BY
+                    CASE WHEN cm.is_primary = 1 THEN 0 ELSE 1 END,
+                    cm.stream_priority ASC,
+                    cm.id ASC
+                LIMIT 1
+            ) as primary_category_name,
+            (
+                SELECT xtc.category_id
+                FROM channel_mappings cm
+                INNER JOIN xtream_channels xtc ON cm.xtream_channel_id = xtc.id
+                WHERE cm.xmltv_channel_id = xc.id
+                ORDER BY
+                    CASE WHEN cm.is_primary = 1 THEN 0 ELSE 1 END,
+                    cm.stream_priority ASC,
+                    cm.id ASC
+                LIMIT 1
+            ) as primary_category_id
         FROM xmltv_channels xc
-        INNER JOIN xmltv_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
+        INNER JOIN published_channel_settings xcs ON xc.id = xcs.xmltv_channel_id
         WHERE xcs.is_enabled = 1
         AND EXISTS (
             SELECT 1 FROM channel_mappings cm
@@ -97,19 +188,14 @@ pub fn get_enabled_channels_for_m3u(conn: &mut DbPooledConnection) -> Result<Vec
     // Convert to M3uChannel with logo resolution (no additional queries needed!)
     let mut channels = Vec::with_capacity(rows.len());
 
-    // Find max explicit channel number to avoid collisions with fallback numbering
-    // e.g., if channels have plex_display_order 0,2,5 -> channel numbers 1,3,6
-    // fallback should start at 7, not 1
-    let max_explicit_channel = rows
-        .iter()
-        .filter_map(|r| r.plex_display_order)
-        .map(|o| o + 1)
-        .max()
-        .unwrap_or(0);
-
-    let mut fallback_number = max_explicit_channel + 1;
+    let numbers = resolve_channel_numbers(
+        &rows
+            .iter()
+            .map(|r| (r.channel_number, r.plex_display_order))
+            .collect::<Vec<_>>(),
+    );
 
-    for row in rows {
+    for (row, channel_number) in rows.into_iter().zip(numbers) {
         // Logo priority: XMLTV icon -> Xtream fallback -> None
         let logo_url = if let Some(icon) = row.icon.as_ref().filter(|s| !s.trim().is_empty()) {
             Some(icon.clone())
@@ -119,22 +205,14 @@ pub fn get_enabled_channels_for_m3u(conn: &mut DbPooledConnection) -> Result<Vec
             None
         };
 
-        // Convert 0-indexed plex_display_order to 1-indexed channel number
-        let channel_number = match row.plex_display_order {
-            Some(order) => order + 1,
-            None => {
-                let num = fallback_number;
-                fallback_number += 1;
-                num
-            }
-        };
-
         channels.push(M3uChannel {
             xmltv_channel_id: row.id,
             display_name: row.display_name,
             channel_number,
             logo_url,
             tvg_id: row.channel_id,
+            group: row.primary_category_name.filter(|g| !g.trim().is_empty()),
+            category_id: row.primary_category_id,
         });
     }
 
@@ -197,12 +275,37 @@ pub fn resolve_channel_logo(
 /// Returns a properly formatted M3U8 playlist string with:
 /// - #EXTM3U header
 /// - EXTINF entries for each enabled channel
-/// - Stream URLs pointing to /stream/{xmltv_channel_id}
+/// - Stream URLs pointing to /stream/{xmltv_channel_id} on the advertised `host`
+///   (with the filter's stream format and the access token, if set)
+/// - Logo URLs rewritten to the local logo cache when the M3U logo mode is "proxy"
+/// - Only channels matching `filter` and included by enabled playlist filter
+///   plugins (channel numbers are kept, not renumbered)
 ///
 /// For large channel counts (>1000), consider using streaming response to reduce memory usage.
 /// This implementation builds the full string for simplicity and Plex compatibility.
-pub fn generate_m3u_playlist(conn: &mut DbPooledConnection, port: u16) -> Result<String, diesel::result::Error> {
-    let channels = get_enabled_channels_for_m3u(conn)?;
+pub fn generate_m3u_playlist(
+    conn: &mut DbPooledConnection,
+    host: &str,
+    port: u16,
+    filter: &M3uFilter,
+) -> Result<String, diesel::result::Error> {
+    let mut channels = get_enabled_channels_for_m3u(conn)?;
+    channels.retain(|channel| filter.matches(channel));
+    let mut plugins = crate::plugins::enabled(conn).session();
+    channels.retain(|channel| {
+        plugins.include(&PlaylistEntry {
+            name: &channel.display_name,
+            number: channel.channel_number,
+            group: channel.group.as_deref(),
+            tvg_id: &channel.tvg_id,
+        })
+    });
+
+    // Point logos at the local logo cache when proxy mode is configured
+    let logo_mode = logos::get_logo_mode(conn, LogoOutput::M3u);
+    for channel in &mut channels {
+        channel.logo_url = logos::resolve_logo_url(logo_mode, host, port, channel.logo_url.take());
+    }
 
     // Pre-allocate estimated capacity: ~200 bytes per channel + header
     let estimated_size = 50 + (channels.len() * 200);
@@ -210,8 +313,10 @@ pub fn generate_m3u_playlist(conn: &mut DbPooledConnection, port: u16) -> Result
 
     output.push_str("#EXTM3U\n");
 
+    let token = super::auth::get_access_token(conn);
+    let format = filter.stream_format.unwrap_or(StreamFormat::Ts);
     for channel in &channels {
-        generate_channel_entry(&mut output, channel, port);
+        generate_channel_entry(&mut output, channel, host, port, format, token.as_deref());
     }
 
     Ok(output)
@@ -220,7 +325,14 @@ pub fn generate_m3u_playlist(conn: &mut DbPooledConnection, port: u16) -> Result
 /// Generate a single M3U channel entry and append to output string
 ///
 /// Extracted for potential streaming implementation in future.
-fn generate_channel_entry(output: &mut String, channel: &M3uChannel, port: u16) {
+fn generate_channel_entry(
+    output: &mut String,
+    channel: &M3uChannel,
+    host: &str,
+    port: u16,
+    format: StreamFormat,
+    token: Option<&str>,
+) {
     // Build EXTINF line with attributes
     output.push_str(&format!(
         "#EXTINF:-1 tvg-id=\"{}\" tvg-name=\"{}\"",
@@ -236,11 +348,18 @@ fn generate_channel_entry(output: &mut String, channel: &M3uChannel, port: u16)
     // Add channel number
     output.push_str(&format!(" tvg-chno=\"{}\"", channel.channel_number));
 
+    // Add group (provider category)
+    if let Some(ref group) = channel.group {
+        output.push_str(&format!(" group-title=\"{}\"", escape_m3u_attribute(group)));
+    }
+
     // Add display name after comma
     output.push_str(&format!(",{}\n", channel.display_name));
 
     // Add stream URL
-    output.push_str(&format!("http://127.0.0.1:{}/stream/{}\n", port, channel.xmltv_channel_id));
+    let url = channel_stream_url(host, port, channel.xmltv_channel_id, format);
+    output.push_str(&super::auth::with_access_token(url, token));
+    output.push('\n');
 }
 
 /// Generate M3U playlist content from a list of channels
@@ -249,7 +368,7 @@ fn generate_channel_entry(output: &mut String, channel: &M3uChannel, port: u16)
 /// to allow unit testing without a database connection.
 ///
 /// Pre-allocates string capacity based on channel count for better performance.
-pub fn generate_m3u_from_channels(channels: &[M3uChannel], port: u16) -> String {
+pub fn generate_m3u_from_channels(channels: &[M3uChannel], host: &str, port: u16) -> String {
     // Pre-allocate estimated capacity: ~200 bytes per channel + header
     let estimated_size = 50 + (channels.len() * 200);
     let mut output = String::with_capacity(estimated_size);
@@ -257,7 +376,7 @@ pub fn generate_m3u_from_channels(channels: &[M3uChannel], port: u16) -> String
     output.push_str("#EXTM3U\n");
 
     for channel in channels {
-        generate_channel_entry(&mut output, channel, port);
+        generate_channel_entry(&mut output, channel, host, port, StreamFormat::Ts, None);
     }
 
     output
@@ -287,9 +406,83 @@ mod tests {
             channel_number,
             logo_url: logo.map(|s| s.to_string()),
             tvg_id: tvg_id.to_string(),
+            group: None,
+            category_id: None,
+        }
+    }
+
+    fn create_grouped_channel(id: i32, group: &str, category_id: i32) -> M3uChannel {
+        M3uChannel {
+            group: Some(group.to_string()),
+            category_id: Some(category_id),
+            ..create_test_channel(id, &format!("Channel {}", id), id, None, "TEST")
         }
     }
 
+    // ============================================================================
+    // Group and filter tests
+    // ============================================================================
+
+    #[test]
+    fn test_group_title_generated() {
+        let channels = vec![create_grouped_channel(1, "Sports \"Live\"", 5)];
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
+        assert!(result.contains(" group-title=\"Sports &quot;Live&quot;\",Channel 1\n"));
+    }
+
+    #[test]
+    fn test_group_title_omitted_without_group() {
+        let channels = vec![create_test_channel(1, "Test", 1, None, "TEST")];
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
+        assert!(!result.contains("group-title="));
+    }
+
+    #[test]
+    fn test_filter_by_group_and_category() {
+        let sports = create_grouped_channel(1, "Sports", 5);
+        let news = create_grouped_channel(2, "News", 6);
+        let ungrouped = create_test_channel(3, "Other", 3, None, "OTHER");
+
+        let all = M3uFilter::default();
+        assert!(all.matches(&sports) && all.matches(&news) && all.matches(&ungrouped));
+
+        let by_group = M3uFilter {
+            group: Some(" sports ".to_string()),
+            category_id: None,
+            channel_ids: None,
+            stream_format: None,
+        };
+        assert!(by_group.matches(&sports));
+        assert!(!by_group.matches(&news));
+        assert!(!by_group.matches(&ungrouped));
+
+        let by_category = M3uFilter {
+            group: None,
+            category_id: Some(6),
+            channel_ids: None,
+            stream_format: None,
+        };
+        assert!(by_category.matches(&news));
+        assert!(!by_category.matches(&sports));
+
+        let both = M3uFilter {
+            group: Some("Sports".to_string()),
+            category_id: Some(6),
+            channel_ids: None,
+            stream_format: None,
+        };
+        assert!(!both.matches(&sports));
+        assert!(!both.matches(&news));
+
+        let by_profile = M3uFilter {
+            channel_ids: Some(HashSet::from([1, 3])),
+            ..M3uFilter::default()
+        };
+        assert!(!by_profile.is_empty());
+        assert!(by_profile.matches(&sports) && by_profile.matches(&ungrouped));
+        assert!(!by_profile.matches(&news));
+    }
+
     // ============================================================================
     // Attribute escaping tests
     // ============================================================================
@@ -336,7 +529,7 @@ mod tests {
     #[test]
     fn test_generate_empty_playlist() {
         let channels: Vec<M3uChannel> = vec![];
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Empty playlist should only have the header
         assert_eq!(result.trim(), "#EXTM3U");
@@ -356,7 +549,7 @@ mod tests {
             "ESPN.US",
         )];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Verify header
         assert!(result.starts_with("#EXTM3U\n"));
@@ -375,7 +568,7 @@ mod tests {
     fn test_generate_single_channel_without_logo() {
         let channels = vec![create_test_channel(456, "CNN", 207, None, "CNN.US")];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Verify EXTINF line without logo
         assert!(result.contains("#EXTINF:-1 tvg-id=\"CNN.US\" tvg-name=\"CNN\""));
@@ -399,7 +592,7 @@ mod tests {
             create_test_channel(3, "Channel 3", 3, None, "CH3"),
         ];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Check that channels appear in order
         let pos1 = result.find("Channel 1").unwrap();
@@ -418,7 +611,7 @@ mod tests {
             create_test_channel(30, "CBS", 300, None, "CBS.US"),
         ];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         assert!(result.contains("tvg-chno=\"100\""));
         assert!(result.contains("tvg-chno=\"200\""));
@@ -433,8 +626,8 @@ mod tests {
     fn test_generate_playlist_uses_specified_port() {
         let channels = vec![create_test_channel(1, "Test", 1, None, "TEST")];
 
-        let result_5004 = generate_m3u_from_channels(&channels, 5004);
-        let result_8080 = generate_m3u_from_channels(&channels, 8080);
+        let result_5004 = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
+        let result_8080 = generate_m3u_from_channels(&channels, "127.0.0.1", 8080);
 
         assert!(result_5004.contains("http://127.0.0.1:5004/stream/1"));
         assert!(result_8080.contains("http://127.0.0.1:8080/stream/1"));
@@ -445,14 +638,12 @@ mod tests {
     // ============================================================================
 
     #[test]
-    fn test_stream_url_uses_localhost() {
+    fn test_stream_url_uses_advertised_host() {
         let channels = vec![create_test_channel(999, "Test", 1, None, "TEST")];
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "192.168.1.20", 5004);
 
-        // Verify localhost (127.0.0.1) is used
-        assert!(result.contains("http://127.0.0.1:"));
-        assert!(!result.contains("http://0.0.0.0:"));
-        assert!(!result.contains("http://localhost:"));
+        assert!(result.contains("http://192.168.1.20:5004/stream/999\n"));
+        assert!(!result.contains("http://127.0.0.1:"));
     }
 
     #[test]
@@ -462,13 +653,46 @@ mod tests {
             create_test_channel(456, "CNN", 2, None, "CNN.US"),
         ];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Stream URLs should use xmltv_channel_id (123, 456), not other IDs
         assert!(result.contains("/stream/123"));
         assert!(result.contains("/stream/456"));
     }
 
+    #[test]
+    fn test_stream_url_uses_profile_stream_format() {
+        let channel = create_test_channel(7, "ESPN", 1, None, "ESPN.US");
+
+        let mut hls = String::new();
+        generate_channel_entry(
+            &mut hls,
+            &channel,
+            "10.0.0.2",
+            5004,
+            StreamFormat::M3u8,
+            Some("abc"),
+        );
+        assert!(hls.ends_with("http://10.0.0.2:5004/stream/7?format=m3u8&token=abc\n"));
+
+        let mut ts = String::new();
+        generate_channel_entry(
+            &mut ts,
+            &channel,
+            "10.0.0.2",
+            5004,
+            StreamFormat::Ts,
+            Some("abc"),
+        );
+        assert!(ts.ends_with("http://10.0.0.2:5004/stream/7?token=abc\n"));
+
+        let profile = M3uFilter {
+            stream_format: Some(StreamFormat::M3u8),
+            ..M3uFilter::default()
+        };
+        assert!(!profile.is_empty());
+    }
+
     // ============================================================================
     // Special character handling tests
     // ============================================================================
@@ -477,7 +701,7 @@ mod tests {
     fn test_channel_name_with_quotes_escaped() {
         let channels = vec![create_test_channel(1, "Channel \"With\" Quotes", 1, None, "TEST")];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // tvg-name should have escaped quotes
         assert!(result.contains("tvg-name=\"Channel &quot;With&quot; Quotes\""));
@@ -495,7 +719,7 @@ mod tests {
             "TEST",
         )];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Logo URL should have escaped quotes
         assert!(result.contains("tvg-logo=\"http://example.com/logo?name=&quot;test&quot;\""));
@@ -509,7 +733,7 @@ mod tests {
     fn test_m3u_format_structure() {
         let channels = vec![create_test_channel(1, "ESPN", 206, Some("http://espn.png"), "ESPN.US")];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
         let lines: Vec<&str> = result.lines().collect();
 
         // First line should be #EXTM3U
@@ -526,7 +750,7 @@ mod tests {
     fn test_m3u_extinf_attribute_order() {
         let channels = vec![create_test_channel(1, "Test", 100, Some("http://logo.png"), "TEST.ID")];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         // Verify the attribute order: tvg-id, tvg-name, tvg-logo, tvg-chno
         let extinf_line = result.lines().nth(1).unwrap();
@@ -557,7 +781,7 @@ mod tests {
             "SYNTHETIC.777",
         )];
 
-        let result = generate_m3u_from_channels(&channels, 5004);
+        let result = generate_m3u_from_channels(&channels, "127.0.0.1", 5004);
 
         assert!(result.contains("tvg-id=\"SYNTHETIC.777\""));
         assert!(result.contains("tvg-name=\"My Custom Channel\""));
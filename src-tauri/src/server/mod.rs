@@ -1,19 +1,31 @@
+pub mod admin;
+pub mod auth;
 pub mod buffer;
+pub mod continuity;
+pub mod device_profiles;
 pub mod epg;
 pub mod failover;
+pub mod handle;
 pub mod handlers;
 pub mod hdhr;
+pub mod hls;
 pub mod health;
+pub mod lineup_preview;
+pub mod logos;
 pub mod m3u;
+pub mod priming;
 pub mod routes;
+pub mod ssdp;
 pub mod state;
+pub mod status;
 pub mod stream;
+pub mod vod;
 
-use std::net::SocketAddr;
 use std::path::PathBuf;
 
-use crate::db::DbPool;
+use crate::db::{DbPool, SharedDbPool};
 
+pub use handle::{ServerHandle, ServerRestartResult, ServerStopResult};
 pub use state::AppState;
 
 /// Server error types for proper error handling
@@ -29,30 +41,6 @@ pub enum ServerError {
     DatabaseError(String),
 }
 
-/// Start the HTTP server on the specified port
-///
-/// # Arguments
-/// * `state` - Application state containing database pool
-///
-/// # Returns
-/// * `Result<(), ServerError>` - Ok if server runs successfully, Err on failure
-pub async fn start_server(state: AppState) -> Result<(), ServerError> {
-    let port = state.get_port();
-    let app = routes::create_router(state);
-
-    // Bind to 0.0.0.0 to allow LAN access for Plex integration
-    // Plex needs to connect from other devices on the network
-    let addr = SocketAddr::from(([0, 0, 0, 0], port));
-    let listener = tokio::net::TcpListener::bind(addr).await?;
-
-    println!("HTTP server listening on http://{}", addr);
-    axum::serve(listener, app)
-        .await
-        .map_err(|e| ServerError::RuntimeError(e.to_string()))?;
-
-    Ok(())
-}
-
 /// Create AppState from database pool for server initialization
 ///
 /// Uses default app data directory. For production, prefer `create_app_state_with_dir`.
@@ -67,3 +55,10 @@ pub fn create_app_state(pool: DbPool) -> AppState {
 pub fn create_app_state_with_dir(pool: DbPool, app_data_dir: PathBuf) -> AppState {
     AppState::with_app_data_dir(pool, app_data_dir)
 }
+
+/// Create AppState from a swappable pool handle
+///
+/// Used by the app so workspace switches are picked up by the running server.
+pub fn create_app_state_with_shared_pool(pool: SharedDbPool, app_data_dir: PathBuf) -> AppState {
+    AppState::with_shared_pool(pool, app_data_dir)
+}
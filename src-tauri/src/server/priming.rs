@@ -0,0 +1,199 @@
+//! Output cache priming
+//!
+//! Regenerates `/epg.xml` and the unfiltered `/playlist.m3u` right after an
+//! EPG refresh and stores them in the server caches, so the next Plex request
+//! is served from memory instead of paying the generation cost. Primed
+//! output does not expire with the cache TTL; it is kept until channels or
+//! programs change (the output generation is bumped). Generation
+//! runs on a blocking thread so active stream sessions keep their async
+//! workers. When priming finishes an `output-cache-primed` event is emitted.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use std::time::Instant;
+use tauri::{AppHandle, Emitter, Manager};
+
+use super::epg;
+use super::m3u::{self, M3uFilter};
+use super::state::AppState;
+
+/// Tauri event carrying a `CachePrimeResult`
+pub const OUTPUT_CACHE_PRIMED_EVENT: &str = "output-cache-primed";
+
+/// Handle used to reach the server state and emit events (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+/// Set while a priming run is in progress, so overlapping refreshes prime once
+static PRIMING: AtomicBool = AtomicBool::new(false);
+
+/// Outcome of a priming run
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CachePrimeResult {
+    /// What triggered the run ("scheduled" or "manual")
+    pub trigger: String,
+    pub epg_bytes: usize,
+    pub playlist_bytes: usize,
+    pub duration_ms: u64,
+    pub error: Option<String>,
+}
+
+/// Register the app handle used by `prime_in_background`
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+/// Regenerate the EPG and playlist and store them in the caches
+pub fn prime_output_caches(state: &AppState, trigger: &str) -> CachePrimeResult {
+    let started = Instant::now();
+    let mut result = CachePrimeResult {
+        trigger: trigger.to_string(),
+        epg_bytes: 0,
+        playlist_bytes: 0,
+        duration_ms: 0,
+        error: None,
+    };
+
+    // Drop output generated before the refresh even if regeneration fails
+    state.bump_epg_generation();
+    let generation = state.epg_generation();
+
+    let outcome = (|| -> Result<(), String> {
+        let host = super::hdhr::advertised_host(state.get_bind_address());
+        let port = state.get_port();
+        let mut conn = state
+            .get_connection()
+            .map_err(|e| format!("Database connection error: {}", e))?;
+
+        let xml = epg::generate_xmltv_epg(&mut conn, &host, port).map_err(|e| e.to_string())?;
+        result.epg_bytes = xml.len();
+        let etag = super::handlers::generate_etag(&xml);
+        state.prime_epg_cache(xml, etag, generation);
+
+        let playlist = m3u::generate_m3u_playlist(&mut conn, &host, port, &M3uFilter::default())
+            .map_err(|e| e.to_string())?;
+        result.playlist_bytes = playlist.len();
+        let etag = super::handlers::generate_etag(&playlist);
+        state.prime_playlist_cache(playlist, etag, generation);
+        Ok(())
+    })();
+
+    result.error = outcome.err();
+    result.duration_ms = started.elapsed().as_millis() as u64;
+    result
+}
+
+/// Server state, once `init` ran and the server state is managed
+fn app_state() -> Option<(AppHandle, AppState)> {
+    let app = APP_HANDLE.get().cloned()?;
+    let state = app.try_state::<AppState>()?.inner().clone();
+    Some((app, state))
+}
+
+/// Mark the EPG and playlist outdated from code without the server state
+///
+/// For background jobs that change the lineup; see `AppState::bump_epg_generation`.
+pub fn bump_epg_generation() {
+    if let Some((_, state)) = app_state() {
+        state.bump_epg_generation();
+    }
+}
+
+/// Prime the output caches on a background thread
+///
+/// Does nothing before `init`. While another priming run is in progress the
+/// caches are only marked outdated, and the next request regenerates them.
+pub fn prime_in_background(trigger: &'static str) {
+    let Some((app, state)) = app_state() else {
+        return;
+    };
+    if PRIMING.swap(true, Ordering::AcqRel) {
+        // The running pass may have read the old data
+        state.bump_epg_generation();
+        tracing::debug!("Output cache priming already running");
+        return;
+    }
+
+    tauri::async_runtime::spawn_blocking(move || {
+        let result = prime_output_caches(&state, trigger);
+        PRIMING.store(false, Ordering::Release);
+
+        match &result.error {
+            None => tracing::info!(
+                "Primed output caches in {} ms (EPG {} bytes, playlist {} bytes)",
+                result.duration_ms,
+                result.epg_bytes,
+                result.playlist_bytes
+            ),
+            Some(e) => tracing::warn!("Output cache priming failed: {}", e),
+        }
+        let _ = app.emit(OUTPUT_CACHE_PRIMED_EVENT, result);
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::state::EpgCache;
+    use diesel::r2d2::{ConnectionManager, Pool};
+    use std::time::Duration;
+
+    #[test]
+    fn test_prime_output_caches_fills_both_caches() {
+        let manager = ConnectionManager::<diesel::SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        crate::db::run_migrations(&mut pool.get().unwrap()).unwrap();
+        let state = AppState::new(pool);
+
+        let result = prime_output_caches(&state, "manual");
+        assert_eq!(result.error, None);
+        assert!(result.epg_bytes > 0);
+
+        let epg_cache = state.get_epg_cache().unwrap();
+        assert_eq!(epg_cache.content.len(), result.epg_bytes);
+        let playlist_cache = state.get_playlist_cache().unwrap();
+        assert!(playlist_cache.content.starts_with("#EXTM3U"));
+    }
+
+    #[test]
+    fn test_primed_output_outlives_the_cache_ttl() {
+        let cache = |primed: bool| EpgCache {
+            content: String::new(),
+            etag: String::new(),
+            generated_at: Instant::now() - Duration::from_secs(600),
+            gzip: None,
+            generation: 3,
+            primed,
+        };
+
+        assert!(!cache(false).is_fresh(3));
+        assert!(cache(true).is_fresh(3));
+        assert!(!cache(true).is_fresh(4));
+    }
+
+    #[test]
+    fn test_output_from_an_older_generation_is_not_cached() {
+        let manager = ConnectionManager::<diesel::SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        crate::db::run_migrations(&mut pool.get().unwrap()).unwrap();
+        let state = AppState::new(pool);
+
+        prime_output_caches(&state, "manual");
+        state.bump_epg_generation();
+        assert!(state.get_epg_cache().is_none());
+        assert!(state.get_playlist_cache().is_none());
+
+        // Generated before a channel change, stored after it
+        let generation = state.epg_generation();
+        state.bump_epg_generation();
+        state.set_epg_cache("<tv/>".to_string(), "etag".to_string(), generation);
+        state.set_playlist_cache("#EXTM3U".to_string(), "etag".to_string(), generation);
+        assert!(state.get_epg_cache().is_none());
+        assert!(state.get_playlist_cache().is_none());
+
+        let generation = state.epg_generation();
+        state.set_epg_cache("<tv/>".to_string(), "etag".to_string(), generation);
+        assert_eq!(state.get_epg_cache().unwrap().content, "<tv/>");
+    }
+}
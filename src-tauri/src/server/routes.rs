@@ -1,10 +1,21 @@
-use axum::{routing::{get, post, delete}, Router};
+use axum::{middleware, routing::{get, post, delete}, Router};
+
+use super::admin::{
+    admin_accounts, admin_channels, admin_match_stats, admin_refresh_epg, admin_run_matching,
+    admin_set_source_active, admin_sources, admin_status, admin_stop_stream, admin_toggle_channel,
+    admin_ui, mobile_dashboard,
+};
+use super::auth::{require_access_token, require_admin_auth, require_same_origin};
+use super::hls::hls_segment;
 
 use super::handlers::{
-    device_xml, discover_json, epg_xml, fallback_handler, health_check, lineup_json,
-    lineup_status_json, playlist_m3u, stream_proxy, seed_test_data, clear_test_data_endpoint,
+    channel_logo, debug_lineup, device_xml, discover_json, epg_history, epg_xml, epg_xml_gz, fallback_handler, health_check, lineup_json,
+    lineup_status_json, playlist_m3u, profile_device_xml, profile_discover_json, profile_lineup_json,
+    profile_lineup_status_json, profile_playlist_m3u, stream_catchup, stream_proxy, seed_test_data, clear_test_data_endpoint,
 };
 use super::state::AppState;
+use super::status::status_streams;
+use super::vod::{vod_episode, vod_movie};
 
 /// Create the Axum router with all routes configured
 ///
@@ -14,21 +25,67 @@ use super::state::AppState;
 /// # Returns
 /// * `Router` - Configured Axum router ready for serving
 pub fn create_router(state: AppState) -> Router {
-    Router::new()
-        .route("/health", get(health_check))
+    // Web admin UI and REST admin API (require the web admin password)
+    let admin_routes = Router::new()
+        .route("/admin", get(admin_ui))
+        .route("/api/admin/accounts", get(admin_accounts))
+        .route("/api/admin/sources", get(admin_sources))
+        .route("/api/admin/sources/{id}/active", post(admin_set_source_active))
+        .route("/api/admin/channels", get(admin_channels))
+        .route("/api/admin/channels/{id}/toggle", post(admin_toggle_channel))
+        .route("/api/admin/matching/stats", get(admin_match_stats))
+        .route("/api/admin/matching/run", post(admin_run_matching))
+        // Mobile dashboard
+        .route("/m", get(mobile_dashboard))
+        .route("/api/admin/status", get(admin_status))
+        .route("/api/admin/streams/{session_id}/stop", post(admin_stop_stream))
+        .route("/api/admin/epg/refresh", post(admin_refresh_epg))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_admin_auth))
+        .route_layer(middleware::from_fn(require_same_origin));
+
+    // Playlist, guide, lineup and streams (require the access token when one is set)
+    let token_routes = Router::new()
         .route("/playlist.m3u", get(playlist_m3u))
         .route("/epg.xml", get(epg_xml))
-        // HDHomeRun emulation endpoints (Story 4-3)
-        .route("/discover.json", get(discover_json))
+        .route("/epg.xml.gz", get(epg_xml_gz))
         .route("/lineup.json", get(lineup_json))
-        .route("/lineup_status.json", get(lineup_status_json))
-        .route("/device.xml", get(device_xml))
+        // Device profiles: extra tuners with a subset of the lineup
+        .route("/device/{profile}/lineup.json", get(profile_lineup_json))
+        .route("/device/{profile}/playlist.m3u", get(profile_playlist_m3u))
+        // Channel list as Plex sees it (only when enabled in the settings)
+        .route("/debug/lineup", get(debug_lineup))
+        // What aired on a channel, including programs no longer in the guide
+        .route("/api/epg/history", get(epg_history))
         // Stream proxy endpoint (Story 4-4)
         // Routes stream requests to Xtream providers with quality selection
         .route("/stream/{channel_id}", get(stream_proxy))
+        .route("/stream/{channel_id}/catchup", get(stream_catchup))
+        // Provider VOD catalog playback (movies and series episodes)
+        .route("/vod/movie/{id}", get(vod_movie))
+        .route("/vod/episode/{id}", get(vod_episode))
+        // Live list of active streams (server-sent events)
+        .route("/status/streams", get(status_streams))
+        .route_layer(middleware::from_fn_with_state(state.clone(), require_access_token));
+
+    Router::new()
+        .route("/health", get(health_check))
+        // HDHomeRun emulation endpoints (Story 4-3)
+        .route("/discover.json", get(discover_json))
+        .route("/lineup_status.json", get(lineup_status_json))
+        .route("/device.xml", get(device_xml))
+        .route("/device/{profile}/discover.json", get(profile_discover_json))
+        .route("/device/{profile}/lineup_status.json", get(profile_lineup_status_json))
+        .route("/device/{profile}/device.xml", get(profile_device_xml))
+        // Cached channel logos for outputs in "proxy" logo mode
+        .route("/logo/{key}", get(channel_logo))
+        // HLS passthrough URIs; the unguessable key is only handed out in
+        // playlists served to authorized clients
+        .route("/stream/{channel_id}/segment/{key}", get(hls_segment))
         // Test data endpoints (only functional when IPTV_TEST_MODE=1)
         .route("/test/seed", post(seed_test_data))
         .route("/test/seed", delete(clear_test_data_endpoint))
+        .merge(token_routes)
+        .merge(admin_routes)
         .fallback(fallback_handler)
         .with_state(state)
 }
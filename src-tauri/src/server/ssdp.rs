@@ -0,0 +1,390 @@
+//! SSDP announcements for HDHomeRun auto-discovery
+//!
+//! Plex (and other UPnP clients) find tuners on the LAN with SSDP. While the
+//! HTTP server runs, this task joins the SSDP multicast group, answers
+//! `M-SEARCH` requests and periodically sends `NOTIFY ssdp:alive`, so the
+//! tuner shows up without pasting its URL. The LOCATION of each announcement
+//! is the device's device.xml, whose URLBase leads Plex to discover.json.
+//!
+//! Every device profile (see `super::device_profiles`) is announced as its
+//! own device with its own DeviceID.
+//!
+//! Announcements are controlled by the `ssdp_enabled` setting (on by
+//! default), which is checked every `SETTING_CHECK_INTERVAL`; disabling it
+//! sends `ssdp:byebye` and releases the port. Nothing is announced when the
+//! server is bound to loopback or to a specific IPv6 address, since SSDP
+//! here is IPv4 multicast and other devices could not connect anyway.
+
+use diesel::prelude::*;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr, SocketAddrV4};
+use std::time::{Duration, Instant};
+use tokio::net::UdpSocket;
+use tokio::sync::oneshot;
+
+use super::{device_profiles, hdhr, AppState};
+use crate::db::schema::settings;
+use crate::db::Setting;
+
+/// Settings key to turn SSDP announcements on or off
+pub const SSDP_ENABLED_KEY: &str = "ssdp_enabled";
+
+/// SSDP multicast group and port
+const SSDP_GROUP: Ipv4Addr = Ipv4Addr::new(239, 255, 255, 250);
+const SSDP_PORT: u16 = 1900;
+
+/// UPnP device type announced for each tuner
+const DEVICE_TYPE: &str = "urn:schemas-upnp-org:device:MediaServer:1";
+
+/// How long clients may cache an announcement (seconds)
+const MAX_AGE_SECS: u64 = 1800;
+
+/// Alive announcements are repeated well within MAX_AGE_SECS
+const ANNOUNCE_INTERVAL: Duration = Duration::from_secs(600);
+
+/// How often the setting (and so enabling or disabling) is checked
+const SETTING_CHECK_INTERVAL: Duration = Duration::from_secs(30);
+
+const SERVER_HEADER: &str = "StreamForge/1.0 UPnP/1.0 HDHomeRun/1.0";
+
+/// Whether SSDP announcements are enabled (default: true)
+pub fn is_enabled(conn: &mut SqliteConnection) -> bool {
+    settings::table
+        .filter(settings::key.eq(SSDP_ENABLED_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .map(|value| value != "false")
+        .unwrap_or(true)
+}
+
+/// Enable or disable SSDP announcements
+pub fn set_enabled(conn: &mut SqliteConnection, enabled: bool) -> QueryResult<()> {
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(SSDP_ENABLED_KEY, enabled.to_string()))
+        .execute(conn)?;
+    Ok(())
+}
+
+/// A device announced over SSDP
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SsdpDevice {
+    pub device_id: String,
+    /// URL of the device's device.xml
+    pub location: String,
+}
+
+impl SsdpDevice {
+    /// Notification types of the device (NT / ST values)
+    fn notification_types(&self) -> [String; 3] {
+        [
+            "upnp:rootdevice".to_string(),
+            format!("uuid:{}", self.device_id),
+            DEVICE_TYPE.to_string(),
+        ]
+    }
+
+    fn usn(&self, nt: &str) -> String {
+        if nt.starts_with("uuid:") {
+            nt.to_string()
+        } else {
+            format!("uuid:{}::{}", self.device_id, nt)
+        }
+    }
+}
+
+/// The main tuner and every device profile
+pub fn advertised_devices(conn: &mut SqliteConnection, host: &str, port: u16) -> Vec<SsdpDevice> {
+    let mut devices = vec![SsdpDevice {
+        device_id: hdhr::device_identity(conn).device_id,
+        location: format!("http://{}:{}/device.xml", host, port),
+    }];
+    match device_profiles::list_profiles(conn) {
+        Ok(profiles) => devices.extend(profiles.into_iter().map(|profile| SsdpDevice {
+            device_id: profile.device_id,
+            location: format!(
+                "http://{}:{}/device/{}/device.xml",
+                host, port, profile.slug
+            ),
+        })),
+        Err(e) => tracing::warn!("SSDP: failed to load device profiles: {}", e),
+    }
+    devices
+}
+
+/// Search target (ST) of an `M-SEARCH` discovery request
+pub fn search_target(message: &str) -> Option<String> {
+    let mut lines = message.lines();
+    if !lines
+        .next()?
+        .trim()
+        .eq_ignore_ascii_case("M-SEARCH * HTTP/1.1")
+    {
+        return None;
+    }
+
+    let mut target = None;
+    let mut discover = false;
+    for line in lines {
+        let Some((name, value)) = line.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        if name.trim().eq_ignore_ascii_case("ST") {
+            target = Some(value.to_string());
+        } else if name.trim().eq_ignore_ascii_case("MAN") {
+            discover = value
+                .trim_matches('"')
+                .eq_ignore_ascii_case("ssdp:discover");
+        }
+    }
+    target.filter(|_| discover)
+}
+
+/// Responses to an `M-SEARCH` for `target`, one per matching device and type
+pub fn search_responses(devices: &[SsdpDevice], target: &str) -> Vec<String> {
+    let mut responses = Vec::new();
+    for device in devices {
+        for nt in device.notification_types() {
+            if target.eq_ignore_ascii_case("ssdp:all") || target.eq_ignore_ascii_case(&nt) {
+                responses.push(format!(
+                    "HTTP/1.1 200 OK\r\n\
+                     CACHE-CONTROL: max-age={}\r\n\
+                     EXT:\r\n\
+                     LOCATION: {}\r\n\
+                     SERVER: {}\r\n\
+                     ST: {}\r\n\
+                     USN: {}\r\n\
+                     \r\n",
+                    MAX_AGE_SECS,
+                    device.location,
+                    SERVER_HEADER,
+                    nt,
+                    device.usn(&nt)
+                ));
+            }
+        }
+    }
+    responses
+}
+
+/// `NOTIFY` messages for a device (`ssdp:alive`, or `ssdp:byebye` when leaving)
+pub fn notify_messages(device: &SsdpDevice, alive: bool) -> Vec<String> {
+    device
+        .notification_types()
+        .iter()
+        .map(|nt| {
+            let mut message = format!(
+                "NOTIFY * HTTP/1.1\r\nHOST: {}:{}\r\nNT: {}\r\nNTS: {}\r\nUSN: {}\r\n",
+                SSDP_GROUP,
+                SSDP_PORT,
+                nt,
+                if alive { "ssdp:alive" } else { "ssdp:byebye" },
+                device.usn(nt)
+            );
+            if alive {
+                message.push_str(&format!(
+                    "CACHE-CONTROL: max-age={}\r\nLOCATION: {}\r\nSERVER: {}\r\n",
+                    MAX_AGE_SECS, device.location, SERVER_HEADER
+                ));
+            }
+            message.push_str("\r\n");
+            message
+        })
+        .collect()
+}
+
+/// Interface SSDP runs on for a bind address (None: not announced)
+fn multicast_interface(bind_address: IpAddr) -> Option<Ipv4Addr> {
+    match bind_address {
+        IpAddr::V4(ip) if ip.is_loopback() => None,
+        IpAddr::V4(ip) => Some(ip),
+        IpAddr::V6(ip) if ip.is_unspecified() => Some(Ipv4Addr::UNSPECIFIED),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// Bind the SSDP port (shared with other SSDP services) and join the group
+fn open_socket(interface: Ipv4Addr) -> std::io::Result<UdpSocket> {
+    use socket2::{Domain, Protocol, Socket, Type};
+
+    let socket = Socket::new(Domain::IPV4, Type::DGRAM, Some(Protocol::UDP))?;
+    socket.set_reuse_address(true)?;
+    socket.bind(&SocketAddrV4::new(Ipv4Addr::UNSPECIFIED, SSDP_PORT).into())?;
+    socket.join_multicast_v4(&SSDP_GROUP, &interface)?;
+    if !interface.is_unspecified() {
+        socket.set_multicast_if_v4(&interface)?;
+    }
+    socket.set_nonblocking(true)?;
+    UdpSocket::from_std(socket.into())
+}
+
+async fn send_all(socket: &UdpSocket, messages: &[String], to: SocketAddr) {
+    for message in messages {
+        if let Err(e) = socket.send_to(message.as_bytes(), to).await {
+            tracing::debug!("SSDP: failed to send to {}: {}", to, e);
+        }
+    }
+}
+
+async fn recv_from(
+    socket: Option<&UdpSocket>,
+    buf: &mut [u8],
+) -> std::io::Result<(usize, SocketAddr)> {
+    match socket {
+        Some(socket) => socket.recv_from(buf).await,
+        None => std::future::pending().await,
+    }
+}
+
+enum Event {
+    Stop,
+    Tick,
+    Received(std::io::Result<(usize, SocketAddr)>),
+}
+
+/// Announce the tuners until `stop` fires, then say goodbye
+///
+/// Started and stopped with the HTTP server (see `super::handle`).
+pub async fn run(state: AppState, mut stop: oneshot::Receiver<()>) {
+    let Some(interface) = multicast_interface(state.get_bind_address()) else {
+        return;
+    };
+    let host = hdhr::advertised_host(state.get_bind_address());
+    let port = state.get_port();
+    let group = SocketAddr::from((SSDP_GROUP, SSDP_PORT));
+    let devices = || match state.get_connection() {
+        Ok(mut conn) => advertised_devices(&mut conn, &host, port),
+        Err(e) => {
+            tracing::warn!("SSDP: database connection failed: {}", e);
+            Vec::new()
+        }
+    };
+
+    let mut socket: Option<UdpSocket> = None;
+    let mut last_announce: Option<Instant> = None;
+    let mut tick = tokio::time::interval(SETTING_CHECK_INTERVAL);
+    let mut buf = [0u8; 2048];
+
+    loop {
+        let event = tokio::select! {
+            _ = &mut stop => Event::Stop,
+            _ = tick.tick() => Event::Tick,
+            received = recv_from(socket.as_ref(), &mut buf) => Event::Received(received),
+        };
+
+        match event {
+            Event::Stop => break,
+            Event::Tick => {
+                let enabled = state
+                    .get_connection()
+                    .map(|mut conn| is_enabled(&mut conn))
+                    .unwrap_or(socket.is_some());
+                if enabled && socket.is_none() {
+                    match open_socket(interface) {
+                        Ok(opened) => {
+                            tracing::info!("SSDP: announcing tuners on {}", host);
+                            socket = Some(opened);
+                            last_announce = None;
+                        }
+                        Err(e) => tracing::warn!("SSDP: failed to open port {}: {}", SSDP_PORT, e),
+                    }
+                } else if !enabled {
+                    if let Some(closed) = socket.take() {
+                        for device in devices() {
+                            send_all(&closed, &notify_messages(&device, false), group).await;
+                        }
+                        tracing::info!("SSDP: announcements disabled");
+                    }
+                }
+
+                if let Some(socket) = &socket {
+                    if last_announce.is_none_or(|at| at.elapsed() >= ANNOUNCE_INTERVAL) {
+                        for device in devices() {
+                            send_all(socket, &notify_messages(&device, true), group).await;
+                        }
+                        last_announce = Some(Instant::now());
+                    }
+                }
+            }
+            Event::Received(Ok((len, from))) => {
+                let message = String::from_utf8_lossy(&buf[..len]);
+                if let (Some(target), Some(socket)) = (search_target(&message), &socket) {
+                    let responses = search_responses(&devices(), &target);
+                    send_all(socket, &responses, from).await;
+                }
+            }
+            Event::Received(Err(e)) => {
+                tracing::debug!("SSDP: receive failed: {}", e);
+            }
+        }
+    }
+
+    if let Some(socket) = socket {
+        for device in devices() {
+            send_all(&socket, &notify_messages(&device, false), group).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn devices() -> Vec<SsdpDevice> {
+        vec![
+            SsdpDevice {
+                device_id: "STREAMFORGE12345678".to_string(),
+                location: "http://192.168.1.10:5004/device.xml".to_string(),
+            },
+            SsdpDevice {
+                device_id: "STREAMFORGE87654321".to_string(),
+                location: "http://192.168.1.10:5004/device/sports/device.xml".to_string(),
+            },
+        ]
+    }
+
+    #[test]
+    fn test_search_requests_are_answered_per_device() {
+        let request = "M-SEARCH * HTTP/1.1\r\nHOST: 239.255.255.250:1900\r\nMAN: \"ssdp:discover\"\r\nMX: 1\r\nST: urn:schemas-upnp-org:device:MediaServer:1\r\n\r\n";
+        let target = search_target(request).unwrap();
+        assert_eq!(target, DEVICE_TYPE);
+        assert_eq!(
+            search_target("NOTIFY * HTTP/1.1\r\nNT: upnp:rootdevice\r\n\r\n"),
+            None
+        );
+
+        let responses = search_responses(&devices(), &target);
+        assert_eq!(responses.len(), 2);
+        assert!(responses[1]
+            .contains("LOCATION: http://192.168.1.10:5004/device/sports/device.xml\r\n"));
+        assert!(responses[1].contains(&format!(
+            "USN: uuid:STREAMFORGE87654321::{}\r\n",
+            DEVICE_TYPE
+        )));
+
+        assert_eq!(search_responses(&devices(), "ssdp:all").len(), 6);
+        assert_eq!(
+            search_responses(&devices(), "uuid:STREAMFORGE12345678").len(),
+            1
+        );
+        assert!(search_responses(&devices(), "urn:other").is_empty());
+
+        let byebye = notify_messages(&devices()[0], false);
+        assert_eq!(byebye.len(), 3);
+        assert!(byebye
+            .iter()
+            .all(|m| m.contains("NTS: ssdp:byebye") && !m.contains("LOCATION")));
+    }
+
+    #[test]
+    fn test_enabled_by_default() {
+        let mut conn = crate::db::test_connection();
+        assert!(is_enabled(&mut conn));
+        set_enabled(&mut conn, false).unwrap();
+        assert!(!is_enabled(&mut conn));
+        assert_eq!(multicast_interface("127.0.0.1".parse().unwrap()), None);
+        assert_eq!(
+            multicast_interface("0.0.0.0".parse().unwrap()),
+            Some(Ipv4Addr::UNSPECIFIED)
+        );
+    }
+}
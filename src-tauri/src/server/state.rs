@@ -1,23 +1,75 @@
+use bytes::Bytes;
 use diesel::prelude::*;
+use std::collections::HashMap;
+use std::net::{IpAddr, Ipv4Addr};
 use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::{Arc, RwLock};
 use std::time::Instant;
 
-use crate::db::{schema::settings, DbPool, DbPooledConnection};
+use crate::db::{schema::settings, DbPool, DbPooledConnection, SharedDbPool};
 use super::stream::StreamManager;
 
 /// Default server port constant
 const DEFAULT_SERVER_PORT: u16 = 5004;
-const SERVER_PORT_KEY: &str = "server_port";
+pub const SERVER_PORT_KEY: &str = "server_port";
+/// Settings key for the address the HTTP server binds to
+pub const BIND_ADDRESS_KEY: &str = "bind_address";
+/// Default bind address: all interfaces, so Plex on another machine can connect
+pub const DEFAULT_BIND_ADDRESS: IpAddr = IpAddr::V4(Ipv4Addr::UNSPECIFIED);
 /// Default maximum concurrent stream connections
 const DEFAULT_MAX_CONNECTIONS: u32 = 2;
 
-/// Cache for EPG XMLTV content
+/// Cache for generated EPG XMLTV (and playlist) content
 #[derive(Clone, Debug)]
 pub struct EpgCache {
     pub content: String,
     pub etag: String,
     pub generated_at: Instant,
+    /// Gzip-compressed content (EPG only; None for the playlist or if compression failed)
+    pub gzip: Option<Bytes>,
+    /// Output generation the content was built for
+    pub generation: u64,
+    /// Stored by cache priming: kept until the generation changes instead of
+    /// expiring after the cache TTL
+    pub primed: bool,
+}
+
+impl EpgCache {
+    /// Response content that is served without being cached
+    pub fn uncached(content: String, etag: String) -> Self {
+        let gzip = gzip_content(&content);
+        Self {
+            content,
+            etag,
+            generated_at: Instant::now(),
+            gzip,
+            generation: 0,
+            primed: false,
+        }
+    }
+
+    /// Whether the entry may still be served for the current `generation`
+    pub fn is_fresh(&self, generation: u64) -> bool {
+        self.generation == generation
+            && (self.primed || self.generated_at.elapsed() < crate::low_resource::epg_cache_ttl())
+    }
+}
+
+/// Gzip-compress generated output for `/epg.xml.gz` and `Accept-Encoding: gzip`
+fn gzip_content(content: &str) -> Option<Bytes> {
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use std::io::Write;
+
+    let mut encoder = GzEncoder::new(Vec::with_capacity(content.len() / 8), Compression::default());
+    match encoder.write_all(content.as_bytes()).and_then(|_| encoder.finish()) {
+        Ok(compressed) => Some(Bytes::from(compressed)),
+        Err(e) => {
+            eprintln!("EPG cache - gzip compression failed: {}", e);
+            None
+        }
+    }
 }
 
 /// Application state for the HTTP server
@@ -26,14 +78,55 @@ pub struct EpgCache {
 /// the database connection pool, caches, stream manager, and app data directory.
 #[derive(Clone)]
 pub struct AppState {
-    pool: DbPool,
+    /// Swappable pool handle (shared with `DbConnection` so workspace switches apply here too)
+    pool: SharedDbPool,
     epg_cache: Arc<RwLock<Option<EpgCache>>>,
+    /// Unfiltered `/playlist.m3u` content, same TTL as the EPG cache
+    playlist_cache: Arc<RwLock<Option<EpgCache>>>,
+    /// Bumped whenever channels or programs change; cached output built for
+    /// an older generation is never served
+    epg_generation: Arc<AtomicU64>,
     /// Stream manager for tracking active sessions and enforcing connection limits
     stream_manager: Arc<StreamManager>,
     /// App data directory for credential retrieval
     app_data_dir: PathBuf,
 }
 
+/// Read the bind address setting, falling back to `DEFAULT_BIND_ADDRESS`
+pub fn read_bind_address(conn: &mut diesel::SqliteConnection) -> IpAddr {
+    settings::table
+        .filter(settings::key.eq(BIND_ADDRESS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| value.parse::<IpAddr>().ok())
+        .unwrap_or(DEFAULT_BIND_ADDRESS)
+}
+
+/// Read each active account's connection limit
+///
+/// Uses the limit reported by the provider when known, otherwise the
+/// configured `max_connections`.
+pub fn read_account_limits(
+    conn: &mut diesel::SqliteConnection,
+) -> QueryResult<HashMap<i32, u32>> {
+    use crate::db::schema::accounts;
+
+    let rows: Vec<(i32, i32, Option<i32>)> = accounts::table
+        .filter(accounts::is_active.eq(1))
+        .select((
+            accounts::id.assume_not_null(),
+            accounts::max_connections,
+            accounts::max_connections_actual,
+        ))
+        .load(conn)?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(id, configured, actual)| (id, actual.unwrap_or(configured).max(0) as u32))
+        .collect())
+}
+
 impl AppState {
     /// Create new AppState from a database pool
     ///
@@ -42,22 +135,13 @@ impl AppState {
     ///
     /// NOTE: Uses default app data dir - prefer `with_app_data_dir` for production
     pub fn new(pool: DbPool) -> Self {
-        // Calculate total max_connections from all active accounts
-        let max_connections = Self::get_total_max_connections(&pool)
-            .unwrap_or(DEFAULT_MAX_CONNECTIONS);
-
         // Use a default app_data_dir for backward compatibility
         // Stream proxy will fail to decrypt passwords without proper app_data_dir
         let app_data_dir = dirs::data_dir()
             .map(|d| d.join("streamforge"))
             .unwrap_or_else(|| PathBuf::from("."));
 
-        Self {
-            pool,
-            epg_cache: Arc::new(RwLock::new(None)),
-            stream_manager: Arc::new(StreamManager::new(max_connections)),
-            app_data_dir,
-        }
+        Self::with_shared_pool(Arc::new(RwLock::new(pool)), app_data_dir)
     }
 
     /// Create new AppState with explicit app data directory
@@ -65,17 +149,49 @@ impl AppState {
     /// This is the preferred constructor for production use as it ensures
     /// the stream proxy can properly decrypt credentials.
     pub fn with_app_data_dir(pool: DbPool, app_data_dir: PathBuf) -> Self {
-        let max_connections = Self::get_total_max_connections(&pool)
+        Self::with_shared_pool(Arc::new(RwLock::new(pool)), app_data_dir)
+    }
+
+    /// Create new AppState from a swappable pool handle
+    ///
+    /// Used by the app so that switching workspaces (which replaces the pool
+    /// behind the handle) is picked up by the server without a restart.
+    pub fn with_shared_pool(pool: SharedDbPool, app_data_dir: PathBuf) -> Self {
+        let current = pool.read().unwrap_or_else(|e| e.into_inner()).clone();
+        let max_connections = Self::get_total_max_connections(&current)
             .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        let stream_manager = StreamManager::new(max_connections);
+        if let Ok(mut conn) = current.get() {
+            if let Ok(limits) = read_account_limits(&mut conn) {
+                stream_manager.set_account_limits(limits);
+            }
+        }
 
         Self {
             pool,
             epg_cache: Arc::new(RwLock::new(None)),
-            stream_manager: Arc::new(StreamManager::new(max_connections)),
+            playlist_cache: Arc::new(RwLock::new(None)),
+            epg_generation: Arc::new(AtomicU64::new(0)),
+            stream_manager: Arc::new(stream_manager),
             app_data_dir,
         }
     }
 
+    /// Recalculate the tuner limits from the active accounts
+    ///
+    /// Called after the database behind the pool changes (workspace switch).
+    pub fn refresh_max_connections(&self) {
+        let pool = self.pool();
+        let max_connections = Self::get_total_max_connections(&pool)
+            .unwrap_or(DEFAULT_MAX_CONNECTIONS);
+        self.stream_manager.set_max_connections(max_connections);
+        if let Ok(mut conn) = pool.get() {
+            if let Ok(limits) = read_account_limits(&mut conn) {
+                self.stream_manager.set_account_limits(limits);
+            }
+        }
+    }
+
     /// Get total max_connections from all active accounts
     fn get_total_max_connections(pool: &DbPool) -> Option<u32> {
         use crate::db::schema::accounts;
@@ -96,7 +212,7 @@ impl AppState {
     /// Reads actual value from database to honor user configuration.
     pub fn get_port(&self) -> u16 {
         // Try to read from settings table, fall back to default
-        match self.pool.get() {
+        match self.pool().get() {
             Ok(mut conn) => {
                 settings::table
                     .filter(settings::key.eq(SERVER_PORT_KEY))
@@ -110,24 +226,35 @@ impl AppState {
         }
     }
 
+    /// Get the configured bind address
+    ///
+    /// Returns the address from the settings table, defaulting to all
+    /// interfaces (0.0.0.0).
+    pub fn get_bind_address(&self) -> IpAddr {
+        match self.pool().get() {
+            Ok(mut conn) => read_bind_address(&mut conn),
+            Err(_) => DEFAULT_BIND_ADDRESS,
+        }
+    }
+
     /// Get a database connection from the pool
     pub fn get_connection(&self) -> Result<DbPooledConnection, r2d2::Error> {
-        self.pool.get()
+        self.pool().get()
     }
 
-    /// Get a reference to the database pool
-    pub fn pool(&self) -> &DbPool {
-        &self.pool
+    /// Get the current database pool
+    pub fn pool(&self) -> DbPool {
+        self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
     }
 
     /// Get cached EPG content if valid (not expired)
     ///
-    /// Cache TTL is 5 minutes per Story 4-2 requirements
+    /// Cache TTL is 5 minutes per Story 4-2 requirements (shorter in low
+    /// resource mode); primed content lasts until the generation changes.
     pub fn get_epg_cache(&self) -> Option<EpgCache> {
         if let Ok(cache_lock) = self.epg_cache.read() {
             if let Some(ref cache) = *cache_lock {
-                // Check if cache is still valid (5 minute TTL)
-                if cache.generated_at.elapsed().as_secs() < 300 {
+                if cache.is_fresh(self.epg_generation()) {
                     return Some(cache.clone());
                 }
             }
@@ -135,22 +262,106 @@ impl AppState {
         None
     }
 
-    /// Store EPG content in cache
-    pub fn set_epg_cache(&self, content: String, etag: String) {
+    /// Store EPG content in cache, along with its gzip-compressed form
+    ///
+    /// `generation` is the output generation read before the content was
+    /// generated. Content for an older generation is not stored, so output
+    /// generated while channels changed is never cached. Returns the new
+    /// cache entry.
+    pub fn set_epg_cache(&self, content: String, etag: String, generation: u64) -> EpgCache {
+        self.store_epg_cache(content, etag, generation, false)
+    }
+
+    /// Store primed EPG content, kept until the generation changes
+    pub fn prime_epg_cache(&self, content: String, etag: String, generation: u64) {
+        self.store_epg_cache(content, etag, generation, true);
+    }
+
+    fn store_epg_cache(
+        &self,
+        content: String,
+        etag: String,
+        generation: u64,
+        primed: bool,
+    ) -> EpgCache {
+        let gzip = gzip_content(&content);
+        let cache = EpgCache {
+            content,
+            etag,
+            generated_at: Instant::now(),
+            gzip,
+            generation,
+            primed,
+        };
         if let Ok(mut cache_lock) = self.epg_cache.write() {
-            *cache_lock = Some(EpgCache {
-                content,
-                etag,
-                generated_at: Instant::now(),
-            });
+            if generation == self.epg_generation() {
+                *cache_lock = Some(cache.clone());
+            }
         }
+        cache
     }
 
-    /// Invalidate EPG cache (called when channel settings or programs change)
-    pub fn invalidate_epg_cache(&self) {
+    /// Size in bytes of the cached EPG content (0 if nothing is cached)
+    pub fn epg_cache_size(&self) -> usize {
+        self.epg_cache
+            .read()
+            .ok()
+            .and_then(|cache| cache.as_ref().map(|c| c.content.len()))
+            .unwrap_or(0)
+    }
+
+    /// Get cached playlist content if valid (same TTL as the EPG cache)
+    pub fn get_playlist_cache(&self) -> Option<EpgCache> {
+        self.playlist_cache
+            .read()
+            .ok()?
+            .as_ref()
+            .filter(|cache| cache.is_fresh(self.epg_generation()))
+            .cloned()
+    }
+
+    /// Store unfiltered playlist content in cache (same generation rule as the EPG)
+    pub fn set_playlist_cache(&self, content: String, etag: String, generation: u64) {
+        self.store_playlist_cache(content, etag, generation, false);
+    }
+
+    /// Store primed playlist content, kept until the generation changes
+    pub fn prime_playlist_cache(&self, content: String, etag: String, generation: u64) {
+        self.store_playlist_cache(content, etag, generation, true);
+    }
+
+    fn store_playlist_cache(&self, content: String, etag: String, generation: u64, primed: bool) {
+        if let Ok(mut cache_lock) = self.playlist_cache.write() {
+            if generation == self.epg_generation() {
+                *cache_lock = Some(EpgCache {
+                    content,
+                    etag,
+                    generated_at: Instant::now(),
+                    gzip: None,
+                    generation,
+                    primed,
+                });
+            }
+        }
+    }
+
+    /// Current output generation; read it before generating content to cache
+    pub fn epg_generation(&self) -> u64 {
+        self.epg_generation.load(Ordering::Acquire)
+    }
+
+    /// Mark the EPG and playlist outdated (called when channel settings or programs change)
+    ///
+    /// Drops the cached output and bumps the generation, so output generated
+    /// concurrently from the old data is not cached either.
+    pub fn bump_epg_generation(&self) {
+        self.epg_generation.fetch_add(1, Ordering::AcqRel);
         if let Ok(mut cache_lock) = self.epg_cache.write() {
             *cache_lock = None;
         }
+        if let Ok(mut cache_lock) = self.playlist_cache.write() {
+            *cache_lock = None;
+        }
     }
 
     /// Get reference to the stream manager
@@ -0,0 +1,165 @@
+//! Live status of active streams
+//!
+//! `active_streams` lists the sessions tracked by the `StreamManager` with
+//! their channel, account, client and byte counts. The `get_active_streams`
+//! command returns it once; `/status/streams` sends it as server-sent events
+//! (a `streams` event every `STATUS_STREAMS_INTERVAL`).
+
+use std::convert::Infallible;
+use std::time::Duration;
+
+use axum::extract::State;
+use axum::response::sse::{Event, KeepAlive, Sse};
+use diesel::prelude::*;
+use futures_util::Stream;
+use serde::Serialize;
+
+use super::state::AppState;
+use super::stream::StreamManager;
+use crate::db::schema::{accounts, xmltv_channels};
+use crate::tuner_priority::StreamPriority;
+
+/// How often `/status/streams` sends the active streams
+pub const STATUS_STREAMS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// An active stream as shown in the dashboard
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActiveStream {
+    pub session_id: String,
+    pub channel_id: i32,
+    pub channel_name: String,
+    pub account_id: Option<i32>,
+    pub account_name: Option<String>,
+    pub quality: String,
+    pub uptime_seconds: u64,
+    /// Bytes sent to the client so far
+    pub bytes_transferred: u64,
+    /// Address of the client (e.g. the Plex server) receiving the stream
+    pub client_ip: Option<String>,
+    pub failover_count: u32,
+    /// Priority the stream was admitted with (see `crate::tuner_priority`)
+    pub priority: StreamPriority,
+}
+
+/// List the active streams, oldest first
+pub fn active_streams(
+    conn: &mut SqliteConnection,
+    stream_manager: &StreamManager,
+) -> QueryResult<Vec<ActiveStream>> {
+    let mut sessions = stream_manager.list_sessions();
+    sessions.sort_by_key(|(_, session)| session.started_at);
+
+    let mut streams = Vec::with_capacity(sessions.len());
+    for (session_id, session) in sessions {
+        let channel_name = xmltv_channels::table
+            .filter(xmltv_channels::id.eq(session.xmltv_channel_id))
+            .select(xmltv_channels::display_name)
+            .first::<String>(conn)
+            .optional()?
+            .unwrap_or_else(|| format!("Channel {}", session.xmltv_channel_id));
+
+        let account_name = match session.account_id {
+            Some(account_id) => accounts::table
+                .filter(accounts::id.eq(account_id))
+                .select(accounts::name)
+                .first::<String>(conn)
+                .optional()?,
+            None => None,
+        };
+
+        streams.push(ActiveStream {
+            session_id,
+            channel_id: session.xmltv_channel_id,
+            channel_name,
+            account_id: session.account_id,
+            account_name,
+            quality: session.current_quality.clone(),
+            uptime_seconds: session.started_at.elapsed().as_secs(),
+            bytes_transferred: session.bytes_transferred(),
+            client_ip: session.client_ip.map(|ip| ip.to_string()),
+            failover_count: session.failover_count,
+            priority: session.priority,
+        });
+    }
+    Ok(streams)
+}
+
+/// Build the next `/status/streams` event
+fn streams_event(state: &AppState) -> Event {
+    let streams = state
+        .get_connection()
+        .map_err(|e| e.to_string())
+        .and_then(|mut conn| {
+            active_streams(&mut conn, state.stream_manager()).map_err(|e| e.to_string())
+        });
+
+    match streams.and_then(|streams| serde_json::to_string(&streams).map_err(|e| e.to_string())) {
+        Ok(json) => Event::default().event("streams").data(json),
+        Err(e) => {
+            eprintln!("Status stream error - failed to list active streams: {}", e);
+            Event::default()
+                .event("error")
+                .data("Failed to list active streams")
+        }
+    }
+}
+
+/// Active streams as server-sent events
+///
+/// GET /status/streams
+///
+/// Sends a `streams` event with the `ActiveStream` list right away and then
+/// every `STATUS_STREAMS_INTERVAL` until the client disconnects.
+pub async fn status_streams(
+    State(state): State<AppState>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let events = futures_util::stream::unfold((state, true), |(state, first)| async move {
+        if !first {
+            tokio::time::sleep(STATUS_STREAMS_INTERVAL).await;
+        }
+        let event = streams_event(&state);
+        Some((Ok(event), (state, false)))
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::stream::StreamSession;
+    use diesel::connection::SimpleConnection;
+
+    #[test]
+    fn test_active_streams_include_channel_account_and_client() {
+        let mut conn = crate::db::test_connection();
+        conn.batch_execute(
+            "INSERT INTO xmltv_sources (id, name, url, format) VALUES (1, 'Guide', 'http://guide.local', 'xml');
+             INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (1, 1, 'news.us', 'News');",
+        )
+        .unwrap();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://a.local");
+
+        let manager = StreamManager::new(2);
+        let session = StreamSession::new(1, 10, "HD".into())
+            .with_account(1)
+            .with_client_ip(Some("192.168.1.20".parse().unwrap()));
+        session
+            .bytes_counter()
+            .fetch_add(1316, std::sync::atomic::Ordering::Relaxed);
+        manager.start_session(session).unwrap();
+        manager
+            .start_session(StreamSession::new(7, 70, "SD".into()))
+            .unwrap();
+
+        let streams = active_streams(&mut conn, &manager).unwrap();
+        assert_eq!(streams.len(), 2);
+        assert_eq!(streams[0].channel_name, "News");
+        assert_eq!(streams[0].account_name.as_deref(), Some("Main"));
+        assert_eq!(streams[0].client_ip.as_deref(), Some("192.168.1.20"));
+        assert_eq!(streams[0].bytes_transferred, 1316);
+        assert_eq!(streams[1].channel_name, "Channel 7");
+        assert!(streams[1].account_name.is_none());
+    }
+}
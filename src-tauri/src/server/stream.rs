@@ -5,22 +5,38 @@
 //! - Looks up the primary Xtream stream mapping
 //! - Selects the highest available quality (4K > FHD > HD > SD)
 //! - Proxies the stream from Xtream to Plex with minimal buffering
-//! - Enforces connection limits (tuner limit)
-//!
-//! Security note: All endpoints are bound to 127.0.0.1 only (NFR21).
+//! - Enforces connection limits (tuner limit), in total and per account
+//! - Records bytes and duration of finished sessions in `stream_stats` and
+//!   aggregates them per day or week
 
 use dashmap::DashMap;
-use std::sync::atomic::{AtomicU32, Ordering};
+use diesel::prelude::*;
+use diesel::sql_types::{BigInt, Integer, Nullable, Text};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::atomic::{AtomicU32, AtomicU64, Ordering};
+use std::sync::Arc;
 use std::time::Instant;
 use uuid::Uuid;
 
-use crate::xtream::quality::qualities_from_json;
+use crate::clock::Clock;
+use crate::db::schema::{settings, stream_stats, xmltv_channel_settings};
+use crate::db::DbPool;
+use crate::tuner_priority::StreamPriority;
+use crate::xtream::quality::{qualities_from_json, tier_rank, QUALITY_TIERS};
 
 use super::buffer::StreamHealth;
 
 /// Quality priority order (highest to lowest)
 /// 4K > FHD > HD > SD
-const QUALITY_PRIORITY: [&str; 4] = ["4K", "FHD", "HD", "SD"];
+const QUALITY_PRIORITY: [&str; 4] = QUALITY_TIERS;
+
+/// Settings key for the highest quality to stream ("best" or a quality tier)
+pub const PREFERRED_QUALITY_KEY: &str = "stream_preferred_quality";
+
+/// Values of the preferred quality setting; "best" means no cap
+pub const PREFERRED_QUALITY_CHOICES: [&str; 5] = ["best", "4K", "FHD", "HD", "SD"];
 
 /// Represents an active streaming session
 #[derive(Debug, Clone)]
@@ -29,6 +45,8 @@ pub struct StreamSession {
     pub xmltv_channel_id: i32,
     /// Xtream stream ID being used
     pub xtream_stream_id: i32,
+    /// Account the stream is played from (None = not tied to an account)
+    pub account_id: Option<i32>,
     /// Current quality tier being streamed
     pub current_quality: String,
     /// When the stream started
@@ -42,6 +60,12 @@ pub struct StreamSession {
     pub health_status: Option<StreamHealth>,
     /// When the last failover occurred
     pub last_failover_at: Option<Instant>,
+    /// Address of the client (e.g. Plex) receiving the stream
+    pub client_ip: Option<IpAddr>,
+    /// Priority the stream was admitted with
+    pub priority: StreamPriority,
+    /// Bytes sent to the client, shared with the proxy stream that counts them
+    bytes_transferred: Arc<AtomicU64>,
 }
 
 impl StreamSession {
@@ -50,15 +74,47 @@ impl StreamSession {
         Self {
             xmltv_channel_id,
             xtream_stream_id,
+            account_id: None,
             current_quality: quality,
             started_at: Instant::now(),
             failover_count: 0,
             original_stream_id: xtream_stream_id,
             health_status: Some(StreamHealth::Healthy),
             last_failover_at: None,
+            client_ip: None,
+            priority: StreamPriority::Normal,
+            bytes_transferred: Arc::new(AtomicU64::new(0)),
         }
     }
 
+    /// Record the address of the client receiving the stream
+    pub fn with_client_ip(mut self, client_ip: Option<IpAddr>) -> Self {
+        self.client_ip = client_ip;
+        self
+    }
+
+    /// Set the priority the stream was admitted with
+    pub fn with_priority(mut self, priority: StreamPriority) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    /// Counter the proxy stream adds sent bytes to
+    pub fn bytes_counter(&self) -> Arc<AtomicU64> {
+        self.bytes_transferred.clone()
+    }
+
+    /// Bytes sent to the client so far
+    pub fn bytes_transferred(&self) -> u64 {
+        self.bytes_transferred.load(Ordering::Relaxed)
+    }
+
+    /// Attribute the session to an account's connection limit
+    pub fn with_account(mut self, account_id: i32) -> Self {
+        self.account_id = Some(account_id);
+        self
+    }
+
     /// Update the health status of this session (Story 4.7)
     pub fn update_health(&mut self, status: StreamHealth) {
         self.health_status = Some(status);
@@ -105,13 +161,21 @@ impl StreamSession {
 /// Manages active stream sessions and connection limits
 ///
 /// Uses DashMap for thread-safe concurrent access to session tracking.
-/// Connection limit is enforced based on account's max_connections setting.
+/// The total limit is the sum of the active accounts' max_connections. Each
+/// account is also held to its own limit, so a full account doesn't use up
+/// slots another account has free.
 #[derive(Debug)]
 pub struct StreamManager {
     /// Active streaming sessions, keyed by session ID
     active_sessions: DashMap<String, StreamSession>,
     /// Maximum allowed concurrent connections (using AtomicU32 for thread-safe updates)
     max_connections: AtomicU32,
+    /// Connection limit per account ID (accounts not listed are only held to the total)
+    account_limits: DashMap<i32, u32>,
+    /// Sessions asked to stop (e.g. from the dashboard); their streams end on next poll
+    stop_requests: DashMap<String, Instant>,
+    /// Bytes currently queued in each session's stream buffer
+    buffered_bytes: DashMap<String, usize>,
 }
 
 impl StreamManager {
@@ -120,6 +184,9 @@ impl StreamManager {
         Self {
             active_sessions: DashMap::new(),
             max_connections: AtomicU32::new(max_connections),
+            account_limits: DashMap::new(),
+            stop_requests: DashMap::new(),
+            buffered_bytes: DashMap::new(),
         }
     }
 
@@ -128,13 +195,66 @@ impl StreamManager {
         self.active_sessions.len() < self.max_connections.load(Ordering::Relaxed) as usize
     }
 
+    /// Count the tuner slots still free
+    pub fn free_slots(&self) -> usize {
+        (self.max_connections.load(Ordering::Relaxed) as usize)
+            .saturating_sub(self.active_sessions.len())
+    }
+
+    /// Replace the per-account connection limits
+    pub fn set_account_limits(&self, limits: HashMap<i32, u32>) {
+        self.account_limits
+            .retain(|account_id, _| limits.contains_key(account_id));
+        for (account_id, limit) in limits {
+            self.account_limits.insert(account_id, limit);
+        }
+    }
+
+    /// Get an account's connection limit, if one is set
+    pub fn account_limit(&self, account_id: i32) -> Option<u32> {
+        self.account_limits.get(&account_id).map(|limit| *limit)
+    }
+
+    /// Count the active sessions playing from an account
+    pub fn account_active_count(&self, account_id: i32) -> usize {
+        self.active_sessions
+            .iter()
+            .filter(|entry| entry.value().account_id == Some(account_id))
+            .count()
+    }
+
+    /// Check if an account has a free connection slot
+    pub fn has_account_capacity(&self, account_id: i32) -> bool {
+        match self.account_limit(account_id) {
+            Some(limit) => self.account_active_count(account_id) < limit as usize,
+            None => true,
+        }
+    }
+
+    /// Check if a session may move to a stream from the given account
+    ///
+    /// Staying on the session's own account never needs an extra slot.
+    pub fn can_switch_account(&self, session_id: &str, account_id: i32) -> bool {
+        let current = self
+            .active_sessions
+            .get(session_id)
+            .and_then(|session| session.account_id);
+        current == Some(account_id) || self.has_account_capacity(account_id)
+    }
+
     /// Start a new streaming session
     ///
-    /// Returns the session ID if successful, or None if connection limit reached
+    /// Returns the session ID if successful, or None if the total limit or the
+    /// session's account limit is reached
     pub fn start_session(&self, session: StreamSession) -> Option<String> {
         if !self.can_start_stream() {
             return None;
         }
+        if let Some(account_id) = session.account_id {
+            if !self.has_account_capacity(account_id) {
+                return None;
+            }
+        }
 
         let session_id = Uuid::new_v4().to_string();
         self.active_sessions.insert(session_id.clone(), session);
@@ -144,6 +264,77 @@ impl StreamManager {
     /// End a streaming session by its ID
     pub fn end_session(&self, session_id: &str) {
         self.active_sessions.remove(session_id);
+        self.stop_requests.remove(session_id);
+        self.buffered_bytes.remove(session_id);
+    }
+
+    /// Ask an active session to stop
+    ///
+    /// The proxied stream ends on its next poll, which frees the tuner slot.
+    /// Returns false if no such session is active.
+    pub fn request_stop(&self, session_id: &str) -> bool {
+        if !self.active_sessions.contains_key(session_id) {
+            return false;
+        }
+        self.stop_requests.insert(session_id.to_string(), Instant::now());
+        true
+    }
+
+    /// Most recently started session of lower priority than `priority`
+    ///
+    /// Sessions already asked to stop are skipped; with `accounts`, only
+    /// sessions playing from one of them are considered.
+    pub fn preemption_candidate(
+        &self,
+        priority: StreamPriority,
+        accounts: Option<&[i32]>,
+    ) -> Option<String> {
+        self.active_sessions
+            .iter()
+            .filter(|entry| entry.value().priority < priority)
+            .filter(|entry| !self.stop_requests.contains_key(entry.key()))
+            .filter(|entry| match accounts {
+                Some(accounts) => entry
+                    .value()
+                    .account_id
+                    .is_some_and(|id| accounts.contains(&id)),
+                None => true,
+            })
+            .max_by_key(|entry| entry.value().started_at)
+            .map(|entry| entry.key().clone())
+    }
+
+    /// Stop a session to give its tuner slot to another stream
+    ///
+    /// Unlike `request_stop`, the slot is freed right away; the proxied
+    /// stream ends on its next poll. Returns the stopped session.
+    pub fn preempt(&self, session_id: &str) -> Option<StreamSession> {
+        let (_, session) = self.active_sessions.remove(session_id)?;
+        self.stop_requests.insert(session_id.to_string(), Instant::now());
+        Some(session)
+    }
+
+    /// Check whether a session has been asked to stop
+    pub fn is_stop_requested(&self, session_id: &str) -> bool {
+        self.stop_requests.contains_key(session_id)
+    }
+
+    /// Record how many bytes a session's stream buffer currently holds
+    pub fn set_buffered_bytes(&self, session_id: &str, bytes: usize) {
+        self.buffered_bytes.insert(session_id.to_string(), bytes);
+    }
+
+    /// Total bytes queued across all stream buffers
+    pub fn total_buffered_bytes(&self) -> usize {
+        self.buffered_bytes.iter().map(|entry| *entry.value()).sum()
+    }
+
+    /// Get a snapshot of all active sessions with their IDs
+    pub fn list_sessions(&self) -> Vec<(String, StreamSession)> {
+        self.active_sessions
+            .iter()
+            .map(|entry| (entry.key().clone(), entry.value().clone()))
+            .collect()
     }
 
     /// Get the count of active sessions
@@ -212,16 +403,176 @@ pub fn select_best_quality(qualities_json: Option<&str>) -> String {
             return "SD".to_string();
         }
     };
+    select_quality(&qualities, None)
+}
 
-    // Return first matching quality in priority order
-    for quality in QUALITY_PRIORITY.iter() {
-        if qualities.iter().any(|q| q.eq_ignore_ascii_case(quality)) {
-            return quality.to_string();
+/// Select the quality to stream, capped at `max_quality`
+///
+/// Picks the highest quality at or below the cap. A stream that only offers
+/// qualities above the cap is still played, at its lowest quality, rather
+/// than not at all. Returns "SD" if no quality is recognized.
+pub fn select_quality(qualities: &[String], max_quality: Option<&str>) -> String {
+    let available: Vec<&str> = QUALITY_PRIORITY
+        .iter()
+        .copied()
+        .filter(|quality| qualities.iter().any(|q| q.eq_ignore_ascii_case(quality)))
+        .collect();
+    let cap = max_quality.and_then(tier_rank).unwrap_or(0);
+
+    available
+        .iter()
+        .find(|quality| tier_rank(quality).is_some_and(|rank| rank >= cap))
+        .or(available.last())
+        .map_or_else(|| "SD".to_string(), |quality| quality.to_string())
+}
+
+/// Whether a stream offers a quality at or below `max_quality`
+///
+/// Streams without recognized quality information count as SD.
+pub fn fits_quality(qualities: &[String], max_quality: Option<&str>) -> bool {
+    let Some(cap) = max_quality.and_then(tier_rank) else {
+        return true;
+    };
+    let ranks: Vec<usize> = qualities.iter().filter_map(|q| tier_rank(q)).collect();
+    ranks.is_empty() || ranks.iter().any(|&rank| rank >= cap)
+}
+
+/// Highest quality to stream for a channel, None for the best available
+///
+/// The channel's own setting (`xmltv_channel_settings.preferred_quality`)
+/// replaces the global `PREFERRED_QUALITY_KEY` setting.
+pub fn get_preferred_quality(conn: &mut SqliteConnection, channel_id: i32) -> Option<String> {
+    let channel = xmltv_channel_settings::table
+        .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_id))
+        .select(xmltv_channel_settings::preferred_quality)
+        .first::<Option<String>>(conn)
+        .ok()
+        .flatten();
+    let preference = match channel {
+        Some(quality) => quality,
+        None => get_global_preferred_quality(conn)?,
+    };
+    quality_cap(&preference).map(str::to_string)
+}
+
+/// Value of the global `PREFERRED_QUALITY_KEY` setting, if set
+pub fn get_global_preferred_quality(conn: &mut SqliteConnection) -> Option<String> {
+    settings::table
+        .filter(settings::key.eq(PREFERRED_QUALITY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+}
+
+/// Quality tier a preference caps streams at, None for "best"
+pub fn quality_cap(preference: &str) -> Option<&'static str> {
+    tier_rank(preference).map(|rank| QUALITY_PRIORITY[rank])
+}
+
+/// Move streams within the quality cap ahead of the others
+///
+/// The sort is stable, so stream priority still decides within each group
+/// and streams above the cap remain as a last resort.
+pub fn order_by_preferred_quality<T>(
+    streams: &mut [T],
+    max_quality: Option<&str>,
+    qualities: impl Fn(&T) -> &[String],
+) {
+    if max_quality.is_some() {
+        streams.sort_by_key(|stream| !fits_quality(qualities(stream), max_quality));
+    }
+}
+
+/// Output container requested for `/stream/{id}`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StreamFormat {
+    /// MPEG-TS through the FFmpeg remux pipeline (default)
+    Ts,
+    /// HLS playlist
+    M3u8,
+}
+
+impl StreamFormat {
+    /// `?format=` value of the format
+    pub fn as_param(self) -> &'static str {
+        match self {
+            StreamFormat::Ts => "ts",
+            StreamFormat::M3u8 => "m3u8",
+        }
+    }
+
+    /// Parse a `?format=` value
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "ts" | "mpegts" => Some(StreamFormat::Ts),
+            "m3u8" | "hls" => Some(StreamFormat::M3u8),
+            _ => None,
         }
     }
 
-    // Default to SD if no recognized quality found
-    "SD".to_string()
+    /// Container of an upstream response from its Content-Type header
+    ///
+    /// Returns None for other types; FFmpeg probes those itself.
+    pub fn from_content_type(content_type: &str) -> Option<Self> {
+        Self::from_media_type(content_type.split(';').next().unwrap_or_default())
+    }
+
+    /// Map a media type from an Accept header
+    fn from_media_type(media_type: &str) -> Option<Self> {
+        match media_type.trim().to_ascii_lowercase().as_str() {
+            "video/mp2t" => Some(StreamFormat::Ts),
+            "application/vnd.apple.mpegurl" | "application/x-mpegurl" => Some(StreamFormat::M3u8),
+            _ => None,
+        }
+    }
+}
+
+/// URL of `/stream/{channel_id}` advertised in playlists and lineups
+///
+/// MPEG-TS is the default, so only other formats add `?format=`.
+pub fn channel_stream_url(host: &str, port: u16, channel_id: i32, format: StreamFormat) -> String {
+    match format {
+        StreamFormat::Ts => format!("http://{}:{}/stream/{}", host, port, channel_id),
+        format => format!(
+            "http://{}:{}/stream/{}?format={}",
+            host,
+            port,
+            channel_id,
+            format.as_param()
+        ),
+    }
+}
+
+/// Pick the output format for a stream request
+///
+/// An explicit `?format=` wins; otherwise the recognized Accept media type
+/// with the highest quality value is used. Requests expressing no preference
+/// get MPEG-TS. Returns an error for an unknown `format` value.
+pub fn negotiate_stream_format(
+    format_param: Option<&str>,
+    accept: Option<&str>,
+) -> Result<StreamFormat, String> {
+    if let Some(value) = format_param {
+        return StreamFormat::from_param(value)
+            .ok_or_else(|| format!("Unsupported stream format '{}' (use ts or m3u8)", value));
+    }
+
+    let mut best: Option<(StreamFormat, f32)> = None;
+    for item in accept.unwrap_or_default().split(',') {
+        let mut parts = item.split(';');
+        let Some(format) = parts.next().and_then(StreamFormat::from_media_type) else {
+            continue;
+        };
+        let quality = parts
+            .filter_map(|p| p.trim().strip_prefix("q="))
+            .find_map(|q| q.parse::<f32>().ok())
+            .unwrap_or(1.0);
+        if quality > 0.0 && best.is_none_or(|(_, q)| quality > q) {
+            best = Some((format, quality));
+        }
+    }
+
+    Ok(best.map(|(format, _)| format).unwrap_or(StreamFormat::Ts))
 }
 
 /// Generate Xtream stream URL
@@ -251,10 +602,315 @@ pub fn build_stream_url(server_url: &str, username: &str, password: &str, stream
     )
 }
 
+/// Build the Xtream HLS playlist URL of a live stream
+///
+/// Same as `build_stream_url` with the `.m3u8` extension, which makes the
+/// provider answer with an HLS playlist instead of MPEG-TS.
+pub fn build_hls_url(server_url: &str, username: &str, password: &str, stream_id: i32) -> String {
+    let ts_url = build_stream_url(server_url, username, password, stream_id);
+    format!("{}.m3u8", ts_url.trim_end_matches(".ts"))
+}
+
+/// Longest catch-up playback that can be requested, in minutes
+pub const MAX_CATCHUP_MINUTES: u32 = 24 * 60;
+
+/// Past programme requested through `/stream/{channel_id}/catchup`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CatchupWindow {
+    /// Programme start (UTC)
+    pub start: chrono::DateTime<chrono::Utc>,
+    /// Playback length in minutes
+    pub duration_minutes: u32,
+}
+
+impl CatchupWindow {
+    /// Build a window from the `start` and `duration` query parameters
+    ///
+    /// `start` is a Unix timestamp or an RFC 3339 date; `duration` is in
+    /// minutes. The start must lie in the past.
+    pub fn parse(start: &str, duration_minutes: u32) -> Result<Self, String> {
+        let start = start.trim();
+        let start = match start.parse::<i64>() {
+            Ok(timestamp) => chrono::DateTime::from_timestamp(timestamp, 0),
+            Err(_) => chrono::DateTime::parse_from_rfc3339(start)
+                .ok()
+                .map(|dt| dt.with_timezone(&chrono::Utc)),
+        }
+        .ok_or_else(|| "Invalid start: expected a Unix timestamp or RFC 3339 date".to_string())?;
+
+        if start >= chrono::Utc::now() {
+            return Err("Catch-up start must be in the past".to_string());
+        }
+        if duration_minutes == 0 || duration_minutes > MAX_CATCHUP_MINUTES {
+            return Err(format!(
+                "Invalid duration: expected 1-{} minutes",
+                MAX_CATCHUP_MINUTES
+            ));
+        }
+
+        Ok(Self {
+            start,
+            duration_minutes,
+        })
+    }
+
+    /// Days between the start and now, compared against a stream's archive length
+    pub fn age_days(&self) -> i64 {
+        (chrono::Utc::now() - self.start).num_days()
+    }
+}
+
+/// Generate Xtream catch-up (timeshift) URL
+///
+/// Format: `{server_url}/timeshift/{username}/{password}/{duration}/{YYYY-MM-DD:HH-MM}/{stream_id}.ts`
+///
+/// The start is sent in UTC; providers whose archive runs on another
+/// timezone will be offset by the difference.
+pub fn build_timeshift_url(
+    server_url: &str,
+    username: &str,
+    password: &str,
+    stream_id: i32,
+    window: &CatchupWindow,
+) -> String {
+    let server = server_url.trim_end_matches('/');
+    format!(
+        "{}/timeshift/{}/{}/{}/{}/{}.ts",
+        server,
+        urlencoding::encode(username),
+        urlencoding::encode(password),
+        window.duration_minutes,
+        window.start.format("%Y-%m-%d:%H-%M"),
+        stream_id
+    )
+}
+
+/// Statistics older than this many days are pruned
+const STREAM_STATS_RETENTION_DAYS: i64 = 366;
+
+/// Records a session in `stream_stats` when the stream ends
+///
+/// Bytes are read from the session's byte counter. The session is counted
+/// for the account it started on, even if it failed over to another one.
+pub struct SessionStatsRecorder {
+    pool: DbPool,
+    xmltv_channel_id: i32,
+    account_id: Option<i32>,
+    started_at: chrono::DateTime<chrono::FixedOffset>,
+    clock: Arc<dyn Clock>,
+    bytes: Arc<AtomicU64>,
+}
+
+impl SessionStatsRecorder {
+    pub fn new(pool: DbPool, session: &StreamSession) -> Self {
+        Self::with_clock(pool, session, crate::clock::system())
+    }
+
+    /// Recorder timing the session with the given clock
+    pub fn with_clock(pool: DbPool, session: &StreamSession, clock: Arc<dyn Clock>) -> Self {
+        Self {
+            pool,
+            xmltv_channel_id: session.xmltv_channel_id,
+            account_id: session.account_id,
+            started_at: clock.now(),
+            clock,
+            bytes: session.bytes_counter(),
+        }
+    }
+
+    /// Write the finished session's statistics
+    pub fn finish(self) {
+        let duration_seconds = (self.clock.now() - self.started_at).num_seconds().max(0) as u64;
+        let bytes = self.bytes.load(Ordering::Relaxed);
+        let result = self
+            .pool
+            .get()
+            .map_err(|e| e.to_string())
+            .and_then(|mut conn| {
+                record_session_stats(
+                    &mut conn,
+                    self.xmltv_channel_id,
+                    self.account_id,
+                    self.started_at,
+                    duration_seconds,
+                    bytes,
+                )
+                .map_err(|e| e.to_string())
+            });
+        if let Err(e) = result {
+            tracing::warn!(
+                "Failed to record stream statistics for channel {}: {}",
+                self.xmltv_channel_id,
+                e
+            );
+        }
+    }
+}
+
+/// Store one finished session and prune statistics past the retention period
+///
+/// The day is taken from `started_at`'s own (local) offset.
+pub fn record_session_stats<Tz>(
+    conn: &mut SqliteConnection,
+    xmltv_channel_id: i32,
+    account_id: Option<i32>,
+    started_at: chrono::DateTime<Tz>,
+    duration_seconds: u64,
+    bytes: u64,
+) -> QueryResult<()>
+where
+    Tz: chrono::TimeZone,
+    Tz::Offset: std::fmt::Display,
+{
+    diesel::insert_into(stream_stats::table)
+        .values((
+            stream_stats::xmltv_channel_id.eq(xmltv_channel_id),
+            stream_stats::account_id.eq(account_id),
+            stream_stats::day.eq(started_at.format("%Y-%m-%d").to_string()),
+            stream_stats::started_at.eq(started_at
+                .with_timezone(&chrono::Utc)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()),
+            stream_stats::duration_seconds.eq(duration_seconds as i64),
+            stream_stats::bytes.eq(bytes as i64),
+        ))
+        .execute(conn)?;
+
+    let cutoff = started_at.date_naive() - chrono::Duration::days(STREAM_STATS_RETENTION_DAYS);
+    diesel::delete(
+        stream_stats::table.filter(stream_stats::day.lt(cutoff.format("%Y-%m-%d").to_string())),
+    )
+    .execute(conn)?;
+    Ok(())
+}
+
+/// Period statistics are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPeriod {
+    Day,
+    Week,
+}
+
+impl StatsPeriod {
+    /// SQL expression for the first day of a row's period (weeks start on Monday)
+    fn period_sql(self) -> &'static str {
+        match self {
+            StatsPeriod::Day => "s.day",
+            StatsPeriod::Week => "date(s.day, 'weekday 0', '-6 days')",
+        }
+    }
+
+    /// First day of the period `count - 1` periods before the one containing `today`
+    pub fn since(self, today: chrono::NaiveDate, count: u32) -> chrono::NaiveDate {
+        use chrono::Datelike;
+
+        let back = i64::from(count.saturating_sub(1));
+        match self {
+            StatsPeriod::Day => today - chrono::Duration::days(back),
+            StatsPeriod::Week => {
+                let monday = today
+                    - chrono::Duration::days(i64::from(today.weekday().num_days_from_monday()));
+                monday - chrono::Duration::weeks(back)
+            }
+        }
+    }
+}
+
+/// Traffic of one channel and account in one period
+#[derive(Debug, Clone, Serialize, QueryableByName)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamStatsBucket {
+    /// First day of the period ("YYYY-MM-DD")
+    #[diesel(sql_type = Text)]
+    pub period_start: String,
+    #[diesel(sql_type = Integer)]
+    pub channel_id: i32,
+    /// None if the channel no longer exists
+    #[diesel(sql_type = Nullable<Text>)]
+    pub channel_name: Option<String>,
+    #[diesel(sql_type = Nullable<Integer>)]
+    pub account_id: Option<i32>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub account_name: Option<String>,
+    #[diesel(sql_type = BigInt)]
+    pub sessions: i64,
+    #[diesel(sql_type = BigInt)]
+    pub bytes: i64,
+    #[diesel(sql_type = BigInt)]
+    pub duration_seconds: i64,
+}
+
+/// Aggregate statistics per period, channel and account since a day
+///
+/// Newest period first, then by traffic. Sessions count for the day they
+/// started.
+pub fn stream_stats_by_period(
+    conn: &mut SqliteConnection,
+    period: StatsPeriod,
+    since: chrono::NaiveDate,
+) -> QueryResult<Vec<StreamStatsBucket>> {
+    let query = format!(
+        "SELECT {period} AS period_start, s.xmltv_channel_id AS channel_id,
+                c.display_name AS channel_name, s.account_id AS account_id,
+                a.name AS account_name, COUNT(*) AS sessions,
+                SUM(s.bytes) AS bytes, SUM(s.duration_seconds) AS duration_seconds
+         FROM stream_stats s
+         LEFT JOIN xmltv_channels c ON c.id = s.xmltv_channel_id
+         LEFT JOIN accounts a ON a.id = s.account_id
+         WHERE s.day >= ?
+         GROUP BY period_start, s.xmltv_channel_id, s.account_id
+         ORDER BY period_start DESC, bytes DESC",
+        period = period.period_sql()
+    );
+    diesel::sql_query(query)
+        .bind::<Text, _>(since.format("%Y-%m-%d").to_string())
+        .load(conn)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_negotiate_stream_format() {
+        assert_eq!(negotiate_stream_format(None, None), Ok(StreamFormat::Ts));
+        assert_eq!(negotiate_stream_format(None, Some("*/*")), Ok(StreamFormat::Ts));
+        assert_eq!(negotiate_stream_format(Some("M3U8"), None), Ok(StreamFormat::M3u8));
+        assert!(negotiate_stream_format(Some("mkv"), None).is_err());
+
+        // Query parameter overrides Accept
+        assert_eq!(
+            negotiate_stream_format(Some("ts"), Some("application/vnd.apple.mpegurl")),
+            Ok(StreamFormat::Ts)
+        );
+        assert_eq!(
+            negotiate_stream_format(None, Some("application/vnd.apple.mpegurl, */*;q=0.8")),
+            Ok(StreamFormat::M3u8)
+        );
+        assert_eq!(
+            negotiate_stream_format(
+                None,
+                Some("application/x-mpegURL;q=0.5, video/mp2t;q=0.9")
+            ),
+            Ok(StreamFormat::Ts)
+        );
+        assert_eq!(
+            negotiate_stream_format(None, Some("application/vnd.apple.mpegurl;q=0")),
+            Ok(StreamFormat::Ts)
+        );
+    }
+
+    #[test]
+    fn test_stream_format_from_content_type() {
+        assert_eq!(StreamFormat::from_content_type("video/mp2t"), Some(StreamFormat::Ts));
+        assert_eq!(
+            StreamFormat::from_content_type("application/vnd.apple.mpegurl; charset=utf-8"),
+            Some(StreamFormat::M3u8)
+        );
+        assert_eq!(StreamFormat::from_content_type("application/octet-stream"), None);
+    }
+
     // =========================================================================
     // StreamSession Tests
     // =========================================================================
@@ -395,6 +1051,52 @@ mod tests {
         assert_eq!(manager.active_count(), 0);
     }
 
+    #[test]
+    fn test_request_stop_and_end_session() {
+        let manager = StreamManager::new(2);
+        let session_id = manager
+            .start_session(StreamSession::new(1, 100, "HD".to_string()))
+            .unwrap();
+
+        assert!(!manager.is_stop_requested(&session_id));
+        assert!(manager.request_stop(&session_id));
+        assert!(manager.is_stop_requested(&session_id));
+
+        manager.end_session(&session_id);
+        assert!(!manager.is_stop_requested(&session_id));
+        assert!(!manager.request_stop(&session_id));
+    }
+
+    #[test]
+    fn test_list_sessions() {
+        let manager = StreamManager::new(2);
+        let id1 = manager.start_session(StreamSession::new(1, 100, "HD".to_string())).unwrap();
+        let id2 = manager.start_session(StreamSession::new(2, 200, "SD".to_string())).unwrap();
+
+        let mut sessions = manager.list_sessions();
+        sessions.sort_by_key(|(_, s)| s.xmltv_channel_id);
+        assert_eq!(sessions.len(), 2);
+        assert_eq!(sessions[0].0, id1);
+        assert_eq!(sessions[1].0, id2);
+        assert_eq!(sessions[1].1.current_quality, "SD");
+    }
+
+    #[test]
+    fn test_buffered_bytes_totals() {
+        let manager = StreamManager::new(2);
+        let id1 = manager.start_session(StreamSession::new(1, 100, "HD".to_string())).unwrap();
+        let id2 = manager.start_session(StreamSession::new(2, 200, "SD".to_string())).unwrap();
+
+        assert_eq!(manager.total_buffered_bytes(), 0);
+        manager.set_buffered_bytes(&id1, 1000);
+        manager.set_buffered_bytes(&id2, 500);
+        manager.set_buffered_bytes(&id1, 200);
+        assert_eq!(manager.total_buffered_bytes(), 700);
+
+        manager.end_session(&id2);
+        assert_eq!(manager.total_buffered_bytes(), 200);
+    }
+
     #[test]
     fn test_connection_limit_enforcement() {
         let manager = StreamManager::new(2);
@@ -418,6 +1120,114 @@ mod tests {
         assert_eq!(manager.active_count(), 2);
     }
 
+    #[test]
+    fn test_stream_stats_aggregate_per_day_and_week() {
+        use chrono::TimeZone;
+
+        let mut conn = crate::db::test_connection();
+        crate::db::seed_account(&mut conn, 1, "Main", "http://example.com");
+
+        // Wednesday and Thursday of the same week, then the next Monday
+        let at = |day| chrono::Local.with_ymd_and_hms(2026, 3, day, 20, 0, 0).unwrap();
+        record_session_stats(&mut conn, 5, Some(1), at(4), 600, 1_000).unwrap();
+        record_session_stats(&mut conn, 5, Some(1), at(5), 300, 500).unwrap();
+        record_session_stats(&mut conn, 5, Some(1), at(9), 60, 100).unwrap();
+
+        let since = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let days = stream_stats_by_period(&mut conn, StatsPeriod::Day, since).unwrap();
+        assert_eq!(days.len(), 3);
+        assert_eq!(days[0].period_start, "2026-03-09");
+        assert_eq!(days[0].account_name.as_deref(), Some("Main"));
+        assert!(days[0].channel_name.is_none());
+
+        let weeks = stream_stats_by_period(&mut conn, StatsPeriod::Week, since).unwrap();
+        assert_eq!(weeks.len(), 2);
+        assert_eq!(weeks[1].period_start, "2026-03-02");
+        assert_eq!(weeks[1].sessions, 2);
+        assert_eq!(weeks[1].bytes, 1_500);
+        assert_eq!(weeks[1].duration_seconds, 900);
+
+        let thursday = chrono::NaiveDate::from_ymd_opt(2026, 3, 5).unwrap();
+        assert_eq!(StatsPeriod::Week.since(thursday, 2).to_string(), "2026-02-23");
+        assert_eq!(StatsPeriod::Day.since(thursday, 1), thursday);
+    }
+
+    #[test]
+    fn test_stats_recorder_uses_local_day_of_session_start() {
+        use crate::clock::MockClock;
+        use diesel::r2d2::{ConnectionManager, Pool};
+
+        let manager = ConnectionManager::<SqliteConnection>::new(":memory:");
+        let pool = Pool::builder().max_size(1).build(manager).unwrap();
+        crate::db::run_migrations(&mut pool.get().unwrap()).unwrap();
+
+        // 23:30 local time at UTC+2 is still the previous day in UTC
+        let clock = MockClock::at("2026-03-04T23:30:00+02:00");
+        let session = StreamSession::new(7, 70, "HD".into());
+        session.bytes_counter().fetch_add(4_096, Ordering::Relaxed);
+        let recorder = SessionStatsRecorder::with_clock(pool.clone(), &session, clock.clone());
+        clock.advance(chrono::Duration::minutes(90));
+        recorder.finish();
+
+        let (day, started_at, duration, bytes): (String, String, i64, i64) = stream_stats::table
+            .select((
+                stream_stats::day,
+                stream_stats::started_at,
+                stream_stats::duration_seconds,
+                stream_stats::bytes,
+            ))
+            .first(&mut pool.get().unwrap())
+            .unwrap();
+        assert_eq!(day, "2026-03-04");
+        assert_eq!(started_at, "2026-03-04 21:30:00");
+        assert_eq!(duration, 5_400);
+        assert_eq!(bytes, 4_096);
+    }
+
+    #[test]
+    fn test_session_bytes_counter_is_shared() {
+        let manager = StreamManager::new(2);
+        let session = StreamSession::new(1, 10, "HD".into());
+        let counter = session.bytes_counter();
+        let id = manager.start_session(session).unwrap();
+
+        counter.fetch_add(188 * 7, Ordering::Relaxed);
+        assert_eq!(manager.get_session(&id).unwrap().bytes_transferred(), 1316);
+    }
+
+    #[test]
+    fn test_account_limits_are_enforced_separately() {
+        let manager = StreamManager::new(3);
+        manager.set_account_limits(HashMap::from([(1, 2), (2, 1)]));
+
+        let first = manager
+            .start_session(StreamSession::new(1, 10, "HD".into()).with_account(1))
+            .unwrap();
+        manager
+            .start_session(StreamSession::new(2, 20, "HD".into()).with_account(1))
+            .unwrap();
+
+        // Account 1 is full, account 2 still has a slot
+        assert!(!manager.has_account_capacity(1));
+        assert!(manager.has_account_capacity(2));
+        assert!(manager
+            .start_session(StreamSession::new(3, 30, "HD".into()).with_account(1))
+            .is_none());
+        assert!(manager.can_switch_account(&first, 1));
+        assert!(manager.can_switch_account(&first, 2));
+        manager
+            .start_session(StreamSession::new(3, 40, "HD".into()).with_account(2))
+            .unwrap();
+        assert!(!manager.can_switch_account(&first, 2));
+        assert_eq!(manager.account_active_count(1), 2);
+
+        // Unknown accounts are only held to the total
+        assert!(manager.has_account_capacity(99));
+
+        manager.end_session(&first);
+        assert!(manager.has_account_capacity(1));
+    }
+
     #[test]
     fn test_session_cleanup_frees_slot() {
         let manager = StreamManager::new(1);
@@ -542,6 +1352,30 @@ mod tests {
         assert_eq!(select_best_quality(Some(r#"["Hd", "Sd"]"#)), "HD");
     }
 
+    #[test]
+    fn test_quality_selection_with_cap() {
+        let qualities =
+            |list: &[&str]| -> Vec<String> { list.iter().map(|q| q.to_string()).collect() };
+        let all = qualities(&["4K", "FHD", "HD"]);
+        assert_eq!(select_quality(&all, Some("FHD")), "FHD");
+        assert_eq!(select_quality(&all, Some("sd")), "HD");
+        assert_eq!(select_quality(&all, None), "4K");
+        assert_eq!(select_quality(&[], Some("HD")), "SD");
+
+        let uhd = qualities(&["4K"]);
+        let hd = qualities(&["HD"]);
+        assert!(!fits_quality(&uhd, Some("FHD")));
+        assert!(fits_quality(&hd, Some("FHD")));
+        assert!(fits_quality(&uhd, None));
+        assert!(fits_quality(&[], Some("HD")));
+
+        // Stable: priority order is kept within each group
+        let mut streams = vec![(1, uhd.clone()), (2, hd.clone()), (3, uhd), (4, hd)];
+        order_by_preferred_quality(&mut streams, Some("FHD"), |s| &s.1);
+        let order: Vec<i32> = streams.iter().map(|s| s.0).collect();
+        assert_eq!(order, [2, 4, 1, 3]);
+    }
+
     // =========================================================================
     // Stream URL Generation Tests
     // =========================================================================
@@ -587,4 +1421,31 @@ mod tests {
         let url = build_stream_url("http://example.com", "user name", "pass word", 789);
         assert_eq!(url, "http://example.com/live/user%20name/pass%20word/789.ts");
     }
+
+    #[test]
+    fn test_build_timeshift_url() {
+        let window = CatchupWindow {
+            start: chrono::DateTime::from_timestamp(1_767_268_800, 0).unwrap(),
+            duration_minutes: 90,
+        };
+        let url = build_timeshift_url("http://example.com/", "user", "p@ss", 42, &window);
+        assert_eq!(
+            url,
+            "http://example.com/timeshift/user/p%40ss/90/2026-01-01:12-00/42.ts"
+        );
+    }
+
+    #[test]
+    fn test_catchup_window_parse() {
+        let window = CatchupWindow::parse("1767268800", 60).unwrap();
+        assert_eq!(window.start.timestamp(), 1_767_268_800);
+        let window = CatchupWindow::parse("2026-01-01T13:00:00+01:00", 60).unwrap();
+        assert_eq!(window.start.timestamp(), 1_767_268_800);
+
+        assert!(CatchupWindow::parse("yesterday", 60).is_err());
+        assert!(CatchupWindow::parse("1767268800", 0).is_err());
+        assert!(CatchupWindow::parse("1767268800", MAX_CATCHUP_MINUTES + 1).is_err());
+        let future = (chrono::Utc::now() + chrono::Duration::hours(1)).timestamp();
+        assert!(CatchupWindow::parse(&future.to_string(), 60).is_err());
+    }
 }
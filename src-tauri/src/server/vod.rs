@@ -0,0 +1,228 @@
+//! VOD playback proxy
+//!
+//! Serves movies (`/vod/movie/{id}`) and series episodes
+//! (`/vod/episode/{id}`) from the VOD catalog by proxying the provider file.
+//! Range requests are forwarded so players can seek. Files are passed through
+//! unchanged (no FFmpeg remux) and do not use a tuner slot. Traffic counts
+//! against the account's monthly quota like live streams.
+
+use axum::{
+    body::Body,
+    extract::{Path, State},
+    http::{header, HeaderMap, Response, StatusCode},
+};
+use bytes::Bytes;
+use diesel::prelude::*;
+use futures_util::Stream;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use std::time::Duration;
+
+use super::state::AppState;
+use crate::commands::vod::{vod_client, VOD_KIND_MOVIE};
+use crate::db::schema::{accounts, vod_episodes, vod_items};
+use crate::db::{Account, VodEpisode, VodItem};
+use crate::quota::{self, SessionUsage};
+
+/// Connect timeout for provider VOD requests
+const VOD_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Extension used when the provider did not report one
+const DEFAULT_VOD_EXTENSION: &str = "mp4";
+
+/// Response headers copied from the provider
+const FORWARDED_HEADERS: [header::HeaderName; 5] = [
+    header::CONTENT_TYPE,
+    header::CONTENT_LENGTH,
+    header::CONTENT_RANGE,
+    header::ACCEPT_RANGES,
+    header::LAST_MODIFIED,
+];
+
+type ProxyError = (StatusCode, String);
+
+/// `GET /vod/movie/{id}`: play a movie from the catalog
+pub async fn vod_movie(
+    State(state): State<AppState>,
+    Path(item_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, ProxyError> {
+    let mut conn = connection(&state)?;
+    let item: VodItem = vod_items::table
+        .filter(vod_items::id.eq(item_id))
+        .filter(vod_items::kind.eq(VOD_KIND_MOVIE))
+        .first(&mut conn)
+        .map_err(|_| not_found())?;
+    drop(conn);
+
+    let extension = item
+        .container_extension
+        .as_deref()
+        .unwrap_or(DEFAULT_VOD_EXTENSION);
+    let url = |client: &crate::xtream::XtreamClient| client.movie_url(item.provider_id, extension);
+    proxy_vod(&state, item.account_id, url, &headers).await
+}
+
+/// `GET /vod/episode/{id}`: play a series episode from the catalog
+pub async fn vod_episode(
+    State(state): State<AppState>,
+    Path(episode_row_id): Path<i32>,
+    headers: HeaderMap,
+) -> Result<Response<Body>, ProxyError> {
+    let mut conn = connection(&state)?;
+    let (episode, account_id): (VodEpisode, i32) = vod_episodes::table
+        .inner_join(vod_items::table)
+        .filter(vod_episodes::id.eq(episode_row_id))
+        .select((VodEpisode::as_select(), vod_items::account_id))
+        .first(&mut conn)
+        .map_err(|_| not_found())?;
+    drop(conn);
+
+    let extension = episode
+        .container_extension
+        .as_deref()
+        .unwrap_or(DEFAULT_VOD_EXTENSION);
+    let url =
+        |client: &crate::xtream::XtreamClient| client.episode_url(episode.episode_id, extension);
+    proxy_vod(&state, account_id, url, &headers).await
+}
+
+fn connection(state: &AppState) -> Result<crate::db::DbPooledConnection, ProxyError> {
+    state.get_connection().map_err(|e| {
+        eprintln!("VOD proxy error - database connection failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })
+}
+
+fn not_found() -> ProxyError {
+    (StatusCode::NOT_FOUND, "Not found".to_string())
+}
+
+/// Fetch the provider file and stream it to the client
+async fn proxy_vod(
+    state: &AppState,
+    account_id: i32,
+    build_url: impl FnOnce(&crate::xtream::XtreamClient) -> String,
+    headers: &HeaderMap,
+) -> Result<Response<Body>, ProxyError> {
+    let mut conn = connection(state)?;
+    let account: Account = accounts::table
+        .filter(accounts::id.eq(account_id))
+        .filter(accounts::is_active.eq(1))
+        .first(&mut conn)
+        .map_err(|_| not_found())?;
+
+    if quota::blocked_accounts(&mut conn)
+        .unwrap_or_default()
+        .contains(&account_id)
+    {
+        return Err((
+            StatusCode::SERVICE_UNAVAILABLE,
+            "Account quota exceeded".to_string(),
+        ));
+    }
+    drop(conn);
+
+    let client = vod_client(&account, state.app_data_dir().clone()).map_err(|e| {
+        eprintln!("VOD proxy error - {} for account {}", e, account_id);
+        not_found()
+    })?;
+    let url = build_url(&client);
+
+    let http = crate::dns::configure(reqwest::Client::builder())
+        .connect_timeout(VOD_CONNECT_TIMEOUT)
+        .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+        .build()
+        .map_err(|e| {
+            eprintln!("VOD proxy error - HTTP client creation failed: {}", e);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Internal server error".to_string(),
+            )
+        })?;
+
+    let mut request = crate::xtream::request_headers::for_url(&url).apply(http.get(&url));
+    if let Some(range) = headers.get(header::RANGE) {
+        request = request.header(header::RANGE, range.clone());
+    }
+    let upstream = request.send().await.map_err(|e| {
+        eprintln!(
+            "VOD proxy error - provider request failed: {}",
+            e.without_url()
+        );
+        (StatusCode::BAD_GATEWAY, "VOD unavailable".to_string())
+    })?;
+
+    let status = upstream.status();
+    if !status.is_success() {
+        eprintln!(
+            "VOD proxy error - provider returned HTTP {}",
+            status.as_u16()
+        );
+        let code = if status == reqwest::StatusCode::RANGE_NOT_SATISFIABLE {
+            StatusCode::RANGE_NOT_SATISFIABLE
+        } else {
+            StatusCode::BAD_GATEWAY
+        };
+        return Err((code, "VOD unavailable".to_string()));
+    }
+
+    let mut response =
+        Response::builder().status(StatusCode::from_u16(status.as_u16()).unwrap_or(StatusCode::OK));
+    for name in FORWARDED_HEADERS {
+        if let Some(value) = upstream.headers().get(&name) {
+            response = response.header(name, value.clone());
+        }
+    }
+
+    let body = CountingStream::new(upstream, SessionUsage::new(state.pool(), account_id));
+    response.body(Body::from_stream(body)).map_err(|e| {
+        eprintln!("VOD proxy error - response build failed: {}", e);
+        (
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "Internal server error".to_string(),
+        )
+    })
+}
+
+type ByteStream = Pin<Box<dyn Stream<Item = reqwest::Result<Bytes>> + Send>>;
+
+/// Provider body that records its traffic in the account usage when dropped
+pub(super) struct CountingStream {
+    inner: ByteStream,
+    usage: Option<SessionUsage>,
+}
+
+impl CountingStream {
+    pub(super) fn new(response: reqwest::Response, usage: SessionUsage) -> Self {
+        Self {
+            inner: Box::pin(response.bytes_stream()),
+            usage: Some(usage),
+        }
+    }
+}
+
+impl Stream for CountingStream {
+    type Item = reqwest::Result<Bytes>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let poll = self.inner.as_mut().poll_next(cx);
+        if let Poll::Ready(Some(Ok(ref chunk))) = poll {
+            if let Some(usage) = self.usage.as_mut() {
+                usage.add_bytes(chunk.len());
+            }
+        }
+        poll
+    }
+}
+
+impl Drop for CountingStream {
+    fn drop(&mut self) {
+        if let Some(usage) = self.usage.take() {
+            usage.finish();
+        }
+    }
+}
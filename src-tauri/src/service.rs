@@ -0,0 +1,636 @@
+//! Headless server mode and system service installation
+//!
+//! `streamforge --headless [--data-dir PATH]` runs the HTTP server and the
+//! EPG scheduler without a window or tray icon. Installing the system service
+//! registers that command to start at boot and keep running after the user
+//! logs out:
+//!
+//! - Linux: a systemd unit in `/etc/systemd/system`, run as the installing user
+//! - macOS: a launchd daemon in `/Library/LaunchDaemons`, run as the installing user
+//! - Windows: a Task Scheduler task with a boot trigger, run as the installing
+//!   user without stored password (S4U logon) and without elevation
+//!
+//! The service uses the installing user's data directory, so it serves the
+//! same database as the GUI. Installing needs administrator rights; the OS
+//! prompts for them (pkexec, an administrator dialog, or UAC).
+//!
+//! While the service is running the GUI's own HTTP server can't bind the
+//! port; the GUI still edits the shared database, and the service picks up
+//! changed server settings on its next restart. Automation hooks only run
+//! from the GUI. Credentials stored in the OS keyring may not be readable by
+//! the service after logout; passwords saved with the file fallback are.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use serde::Serialize;
+
+use crate::db::{self, workspace::WorkspaceRegistry, DbConnection};
+use crate::{maintenance, scheduler, server, shutdown, startup};
+
+/// Command-line flag that starts the headless server
+pub const HEADLESS_FLAG: &str = "--headless";
+
+/// Command-line option overriding the data directory in headless mode
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+
+/// Bundle identifier; the app data directory is `<data dir>/<identifier>`
+const APP_IDENTIFIER: &str = "com.streamforge.app";
+
+/// Name of the systemd unit and Windows task
+const SERVICE_NAME: &str = "streamforge";
+
+/// launchd label of the daemon
+const LAUNCHD_LABEL: &str = "com.streamforge.server";
+
+/// Windows task name
+const WINDOWS_TASK_NAME: &str = "StreamForge Server";
+
+/// Options for headless mode, parsed from the command line
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HeadlessOptions {
+    /// Data directory to use instead of the default app data directory
+    pub data_dir: Option<PathBuf>,
+}
+
+/// Parse the command line; `None` unless `--headless` is present
+pub fn headless_options(args: impl IntoIterator<Item = String>) -> Option<HeadlessOptions> {
+    let mut headless = false;
+    let mut options = HeadlessOptions::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+        if arg == HEADLESS_FLAG {
+            headless = true;
+        } else if arg == DATA_DIR_FLAG {
+            options.data_dir = args.next().map(PathBuf::from);
+        } else if let Some(dir) = arg.strip_prefix("--data-dir=") {
+            options.data_dir = Some(PathBuf::from(dir));
+        }
+    }
+    headless.then_some(options)
+}
+
+/// Default app data directory (the same one Tauri resolves for the GUI)
+pub fn default_app_data_dir() -> Option<PathBuf> {
+    dirs::data_dir().map(|dir| dir.join(APP_IDENTIFIER))
+}
+
+/// Run the HTTP server and EPG scheduler without the GUI until stopped
+///
+/// Returns when the process receives Ctrl+C (or SIGTERM on Unix), after
+/// shutting down in order (see `crate::shutdown`).
+pub fn run_headless(options: HeadlessOptions) -> Result<(), String> {
+    let app_data_dir = options
+        .data_dir
+        .or_else(default_app_data_dir)
+        .ok_or_else(|| "Cannot determine application data directory".to_string())?;
+    std::fs::create_dir_all(&app_data_dir).map_err(|e| {
+        format!(
+            "Cannot create data directory at '{}': {}",
+            app_data_dir.display(),
+            e
+        )
+    })?;
+
+    let db_path = WorkspaceRegistry::load(&app_data_dir).active_path(&app_data_dir);
+    let database_url = db_path.to_string_lossy().to_string();
+    println!("StreamForge headless mode, database {}", database_url);
+
+    startup::begin(startup::StartupPhase::Database);
+    let database = db::establish_connection(&database_url)
+        .map_err(|e| format!("Failed to connect to database: {}", e))
+        .and_then(|mut conn| {
+            crate::logging::init(&app_data_dir, &crate::logging::stored_verbosity(&mut conn));
+            crate::initialize_database(conn)
+        });
+    startup::finish(startup::StartupPhase::Database, database.clone());
+    database?;
+
+    let db_connection = DbConnection::new(database_url)
+        .map_err(|e| format!("Failed to create connection pool: {}", e))?;
+    let server_state = server::create_app_state_with_shared_pool(
+        db_connection.shared_pool(),
+        app_data_dir.clone(),
+    );
+    maintenance::init(server_state.stream_manager().clone());
+    server::failover::init(db_connection.shared_pool());
+    let stream_manager = server_state.stream_manager().clone();
+    let epg_scheduler = scheduler::EpgScheduler::new();
+
+    tauri::async_runtime::block_on(async move {
+        startup::begin(startup::StartupPhase::Server);
+        let server_handle = server::ServerHandle::new();
+        let server = server_handle
+            .start(server_state.clone())
+            .await
+            .map_err(|e| format!("HTTP server error: {}", e));
+        startup::finish(
+            startup::StartupPhase::Server,
+            server.as_ref().map(|_| ()).map_err(|e| e.clone()),
+        );
+        let port = server?;
+        println!("HTTP server listening on port {}", port);
+
+        let logo_pool = db_connection.shared_pool();
+        let logo_data_dir = app_data_dir.clone();
+        tauri::async_runtime::spawn(async move {
+            server::logos::run_logo_prewarm_job(logo_pool, logo_data_dir).await;
+        });
+
+        crate::start_epg_scheduler(
+            epg_scheduler.clone(),
+            db_connection.clone_pool(),
+            app_data_dir,
+            stream_manager,
+        )
+        .await;
+
+        shutdown_signal().await;
+        println!("StreamForge headless mode stopping");
+        shutdown::begin();
+        let stopped = tokio::time::timeout(
+            shutdown::SHUTDOWN_TIMEOUT,
+            shutdown::shutdown(&server_handle, &server_state, &epg_scheduler),
+        )
+        .await;
+        shutdown::finish();
+        match stopped {
+            Ok(report) => println!(
+                "Stopped {} active streams{}",
+                report.stopped_streams,
+                if report.forced { " (forced)" } else { "" }
+            ),
+            Err(_) => eprintln!("Shutdown timed out; exiting anyway"),
+        }
+        Ok(())
+    })
+}
+
+/// Wait for Ctrl+C, or SIGTERM from systemd/launchd
+async fn shutdown_signal() {
+    #[cfg(unix)]
+    {
+        use tokio::signal::unix::{signal, SignalKind};
+        match signal(SignalKind::terminate()) {
+            Ok(mut terminate) => {
+                tokio::select! {
+                    _ = tokio::signal::ctrl_c() => {}
+                    _ = terminate.recv() => {}
+                }
+                return;
+            }
+            Err(e) => eprintln!("Failed to listen for SIGTERM: {}", e),
+        }
+    }
+    if let Err(e) = tokio::signal::ctrl_c().await {
+        eprintln!("Failed to listen for Ctrl+C: {}", e);
+        std::future::pending::<()>().await;
+    }
+}
+
+/// Service manager a definition is written for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ServiceKind {
+    Systemd,
+    Launchd,
+    WindowsTask,
+}
+
+impl ServiceKind {
+    /// The service manager of the running platform
+    pub fn current() -> Option<Self> {
+        if cfg!(target_os = "linux") {
+            Some(ServiceKind::Systemd)
+        } else if cfg!(target_os = "macos") {
+            Some(ServiceKind::Launchd)
+        } else if cfg!(windows) {
+            Some(ServiceKind::WindowsTask)
+        } else {
+            None
+        }
+    }
+}
+
+/// A generated service definition
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceDefinition {
+    pub kind: ServiceKind,
+    /// Where the definition is installed (unit/plist file, or task name on Windows)
+    pub install_location: String,
+    /// Unit file, plist or Task Scheduler XML
+    pub content: String,
+    /// Shell commands that install it, for users who prefer doing it by hand
+    pub install_commands: Vec<String>,
+}
+
+/// Installation state of the service
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ServiceStatus {
+    /// Whether this platform supports installing the service
+    pub supported: bool,
+    pub installed: bool,
+    pub kind: Option<ServiceKind>,
+    pub install_location: Option<String>,
+}
+
+/// Quote a string for a POSIX shell
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', r"'\''"))
+}
+
+/// Escape text for XML content
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Quote a systemd ExecStart argument
+fn systemd_quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', r"\\")
+            .replace('"', "\\\"")
+            .replace('%', "%%")
+    )
+}
+
+fn install_location(kind: ServiceKind) -> String {
+    match kind {
+        ServiceKind::Systemd => format!("/etc/systemd/system/{}.service", SERVICE_NAME),
+        ServiceKind::Launchd => format!("/Library/LaunchDaemons/{}.plist", LAUNCHD_LABEL),
+        ServiceKind::WindowsTask => WINDOWS_TASK_NAME.to_string(),
+    }
+}
+
+/// Generate the service definition running `exe` headless on `data_dir`
+///
+/// `user` is the account the service runs as (`DOMAIN\user` on Windows).
+/// `staged_file` is where the definition is written before the install
+/// commands copy or import it.
+pub fn service_definition(
+    kind: ServiceKind,
+    exe: &Path,
+    data_dir: &Path,
+    user: &str,
+    staged_file: &Path,
+) -> ServiceDefinition {
+    let exe = exe.to_string_lossy();
+    let data_dir = data_dir.to_string_lossy();
+    let location = install_location(kind);
+    let staged = staged_file.to_string_lossy();
+
+    let (content, install_commands) = match kind {
+        ServiceKind::Systemd => (
+            format!(
+                "[Unit]\n\
+                 Description=StreamForge IPTV proxy (headless)\n\
+                 After=network-online.target\n\
+                 Wants=network-online.target\n\
+                 \n\
+                 [Service]\n\
+                 Type=simple\n\
+                 User={}\n\
+                 ExecStart={} {} {} {}\n\
+                 Restart=on-failure\n\
+                 RestartSec=5\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=multi-user.target\n",
+                user,
+                systemd_quote(&exe),
+                HEADLESS_FLAG,
+                DATA_DIR_FLAG,
+                systemd_quote(&data_dir),
+            ),
+            vec![
+                format!("install -m 644 {} {}", sh_quote(&staged), sh_quote(&location)),
+                "systemctl daemon-reload".to_string(),
+                format!("systemctl enable --now {}.service", SERVICE_NAME),
+            ],
+        ),
+        ServiceKind::Launchd => (
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+                 <plist version=\"1.0\">\n\
+                 <dict>\n\
+                 \t<key>Label</key>\n\
+                 \t<string>{}</string>\n\
+                 \t<key>ProgramArguments</key>\n\
+                 \t<array>\n\
+                 \t\t<string>{}</string>\n\
+                 \t\t<string>{}</string>\n\
+                 \t\t<string>{}</string>\n\
+                 \t\t<string>{}</string>\n\
+                 \t</array>\n\
+                 \t<key>UserName</key>\n\
+                 \t<string>{}</string>\n\
+                 \t<key>RunAtLoad</key>\n\
+                 \t<true/>\n\
+                 \t<key>KeepAlive</key>\n\
+                 \t<dict>\n\
+                 \t\t<key>SuccessfulExit</key>\n\
+                 \t\t<false/>\n\
+                 \t</dict>\n\
+                 </dict>\n\
+                 </plist>\n",
+                LAUNCHD_LABEL,
+                xml_escape(&exe),
+                HEADLESS_FLAG,
+                DATA_DIR_FLAG,
+                xml_escape(&data_dir),
+                xml_escape(user),
+            ),
+            vec![
+                format!(
+                    "install -m 644 -o root -g wheel {} {}",
+                    sh_quote(&staged),
+                    sh_quote(&location)
+                ),
+                format!("launchctl load -w {}", sh_quote(&location)),
+            ],
+        ),
+        ServiceKind::WindowsTask => (
+            format!(
+                "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+                 <Task version=\"1.2\" xmlns=\"http://schemas.microsoft.com/windows/2004/02/mit/task\">\n\
+                 \x20 <RegistrationInfo>\n\
+                 \x20   <Description>StreamForge IPTV proxy (headless)</Description>\n\
+                 \x20 </RegistrationInfo>\n\
+                 \x20 <Triggers>\n\
+                 \x20   <BootTrigger>\n\
+                 \x20     <Enabled>true</Enabled>\n\
+                 \x20   </BootTrigger>\n\
+                 \x20 </Triggers>\n\
+                 \x20 <Principals>\n\
+                 \x20   <Principal id=\"Author\">\n\
+                 \x20     <UserId>{}</UserId>\n\
+                 \x20     <LogonType>S4U</LogonType>\n\
+                 \x20     <RunLevel>LeastPrivilege</RunLevel>\n\
+                 \x20   </Principal>\n\
+                 \x20 </Principals>\n\
+                 \x20 <Settings>\n\
+                 \x20   <MultipleInstancesPolicy>IgnoreNew</MultipleInstancesPolicy>\n\
+                 \x20   <DisallowStartIfOnBatteries>false</DisallowStartIfOnBatteries>\n\
+                 \x20   <StopIfGoingOnBatteries>false</StopIfGoingOnBatteries>\n\
+                 \x20   <ExecutionTimeLimit>PT0S</ExecutionTimeLimit>\n\
+                 \x20   <RestartOnFailure>\n\
+                 \x20     <Interval>PT1M</Interval>\n\
+                 \x20     <Count>3</Count>\n\
+                 \x20   </RestartOnFailure>\n\
+                 \x20 </Settings>\n\
+                 \x20 <Actions Context=\"Author\">\n\
+                 \x20   <Exec>\n\
+                 \x20     <Command>{}</Command>\n\
+                 \x20     <Arguments>{} {} \"{}\"</Arguments>\n\
+                 \x20   </Exec>\n\
+                 \x20 </Actions>\n\
+                 </Task>\n",
+                xml_escape(user),
+                xml_escape(&exe),
+                HEADLESS_FLAG,
+                DATA_DIR_FLAG,
+                xml_escape(&data_dir),
+            ),
+            vec![
+                format!(
+                    "schtasks /Create /TN \"{}\" /XML \"{}\" /F",
+                    WINDOWS_TASK_NAME, staged
+                ),
+                format!("schtasks /Run /TN \"{}\"", WINDOWS_TASK_NAME),
+            ],
+        ),
+    };
+
+    ServiceDefinition {
+        kind,
+        install_location: location,
+        content,
+        install_commands,
+    }
+}
+
+/// Commands that stop and remove the service
+fn uninstall_commands(kind: ServiceKind) -> Vec<String> {
+    let location = install_location(kind);
+    match kind {
+        ServiceKind::Systemd => vec![
+            format!("systemctl disable --now {}.service", SERVICE_NAME),
+            format!("rm -f {}", sh_quote(&location)),
+            "systemctl daemon-reload".to_string(),
+        ],
+        ServiceKind::Launchd => vec![
+            format!("launchctl unload -w {}", sh_quote(&location)),
+            format!("rm -f {}", sh_quote(&location)),
+        ],
+        ServiceKind::WindowsTask => vec![
+            format!("schtasks /End /TN \"{}\"", WINDOWS_TASK_NAME),
+            format!("schtasks /Delete /TN \"{}\" /F", WINDOWS_TASK_NAME),
+        ],
+    }
+}
+
+/// Path the definition is staged at before installing
+fn staged_file(app_data_dir: &Path, kind: ServiceKind) -> PathBuf {
+    let name = match kind {
+        ServiceKind::Systemd => format!("{}.service", SERVICE_NAME),
+        ServiceKind::Launchd => format!("{}.plist", LAUNCHD_LABEL),
+        ServiceKind::WindowsTask => format!("{}-task.xml", SERVICE_NAME),
+    };
+    app_data_dir.join("service").join(name)
+}
+
+/// Service definition for this platform and the running executable
+pub fn current_definition(app_data_dir: &Path) -> Result<ServiceDefinition, String> {
+    let kind = ServiceKind::current()
+        .ok_or_else(|| "System services are not supported on this platform".to_string())?;
+    let exe = std::env::current_exe()
+        .map_err(|e| format!("Cannot determine the application path: {}", e))?;
+    let user = std::env::var("USER")
+        .or_else(|_| std::env::var("USERNAME"))
+        .map_err(|_| "Cannot determine the current user".to_string())?;
+    // Task Scheduler needs the domain (or computer name) with the user
+    let user = match (kind, std::env::var("USERDOMAIN")) {
+        (ServiceKind::WindowsTask, Ok(domain)) if !user.contains('\\') => {
+            format!("{}\\{}", domain, user)
+        }
+        _ => user,
+    };
+    Ok(service_definition(
+        kind,
+        &exe,
+        app_data_dir,
+        &user,
+        &staged_file(app_data_dir, kind),
+    ))
+}
+
+/// Run install/uninstall commands with administrator rights
+///
+/// Windows commands run one by one in an elevated PowerShell; the other
+/// platforms join them into one shell script.
+fn run_elevated(kind: ServiceKind, commands: &[String], stop_on_error: bool) -> Result<(), String> {
+    let separator = if stop_on_error { " && " } else { "; " };
+    let output = match kind {
+        ServiceKind::Systemd => Command::new("pkexec")
+            .arg("sh")
+            .arg("-c")
+            .arg(commands.join(separator))
+            .output(),
+        ServiceKind::Launchd => {
+            let script = commands
+                .join(separator)
+                .replace('\\', r"\\")
+                .replace('"', "\\\"");
+            Command::new("osascript")
+                .arg("-e")
+                .arg(format!(
+                    "do shell script \"{}\" with administrator privileges",
+                    script
+                ))
+                .output()
+        }
+        ServiceKind::WindowsTask => {
+            let script = commands
+                .iter()
+                .map(|command| command.replace('\'', "''"))
+                .collect::<Vec<_>>()
+                .join(if stop_on_error { " && " } else { " & " });
+            Command::new("powershell")
+                .args(["-NoProfile", "-NonInteractive", "-Command"])
+                .arg(format!(
+                    "$p = Start-Process -FilePath cmd.exe -ArgumentList '/c {}' -Verb RunAs -Wait -PassThru -WindowStyle Hidden; exit $p.ExitCode",
+                    script
+                ))
+                .output()
+        }
+    }
+    .map_err(|e| format!("Failed to request administrator rights: {}", e))?;
+
+    if output.status.success() {
+        Ok(())
+    } else {
+        let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+        Err(if stderr.is_empty() {
+            format!("Service command failed ({})", output.status)
+        } else {
+            format!("Service command failed: {}", stderr)
+        })
+    }
+}
+
+/// Write the definition and install the service
+pub fn install(app_data_dir: &Path) -> Result<ServiceDefinition, String> {
+    let definition = current_definition(app_data_dir)?;
+    let staged = staged_file(app_data_dir, definition.kind);
+    if let Some(parent) = staged.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create service directory: {}", e))?;
+    }
+    std::fs::write(&staged, &definition.content)
+        .map_err(|e| format!("Failed to write service definition: {}", e))?;
+
+    run_elevated(definition.kind, &definition.install_commands, true)?;
+    Ok(definition)
+}
+
+/// Stop and remove the service
+pub fn uninstall() -> Result<(), String> {
+    let kind = ServiceKind::current()
+        .ok_or_else(|| "System services are not supported on this platform".to_string())?;
+    // Keep going if the service is already stopped
+    run_elevated(kind, &uninstall_commands(kind), false)
+}
+
+/// Whether the service is installed
+pub fn status() -> ServiceStatus {
+    let Some(kind) = ServiceKind::current() else {
+        return ServiceStatus {
+            supported: false,
+            installed: false,
+            kind: None,
+            install_location: None,
+        };
+    };
+    let location = install_location(kind);
+    let installed = match kind {
+        ServiceKind::Systemd | ServiceKind::Launchd => Path::new(&location).exists(),
+        ServiceKind::WindowsTask => Command::new("schtasks")
+            .args(["/Query", "/TN", WINDOWS_TASK_NAME])
+            .output()
+            .map(|output| output.status.success())
+            .unwrap_or(false),
+    };
+    ServiceStatus {
+        supported: true,
+        installed,
+        kind: Some(kind),
+        install_location: Some(location),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headless_options_and_definitions() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            headless_options(args(&["streamforge", "--minimized"])),
+            None
+        );
+        assert_eq!(
+            headless_options(args(&[
+                "streamforge",
+                "--headless",
+                "--data-dir",
+                "/srv/sf"
+            ])),
+            Some(HeadlessOptions {
+                data_dir: Some(PathBuf::from("/srv/sf"))
+            })
+        );
+        assert_eq!(
+            headless_options(args(&["streamforge", "--data-dir=/srv/sf", "--headless"]))
+                .unwrap()
+                .data_dir,
+            Some(PathBuf::from("/srv/sf"))
+        );
+
+        let exe = Path::new("/opt/Stream Forge/streamforge");
+        let data = Path::new("/home/ana/.local/share/com.streamforge.app");
+        let staged = Path::new("/tmp/it's/streamforge.service");
+        let unit = service_definition(ServiceKind::Systemd, exe, data, "ana", staged);
+        assert!(unit.content.contains("User=ana\n"));
+        assert!(unit.content.contains(
+            "ExecStart=\"/opt/Stream Forge/streamforge\" --headless --data-dir \"/home/ana/.local/share/com.streamforge.app\"\n"
+        ));
+        assert_eq!(
+            unit.install_commands[0],
+            "install -m 644 '/tmp/it'\\''s/streamforge.service' '/etc/systemd/system/streamforge.service'"
+        );
+
+        let plist = service_definition(ServiceKind::Launchd, exe, data, "a&b", staged);
+        assert!(plist.content.contains("<string>a&amp;b</string>"));
+        assert!(plist.content.contains("<string>--headless</string>"));
+
+        let task = service_definition(ServiceKind::WindowsTask, exe, data, "PC\\ana", staged);
+        assert_eq!(task.install_location, "StreamForge Server");
+        assert!(task.content.contains("<BootTrigger>"));
+        assert!(task.content.contains("<UserId>PC\\ana</UserId>"));
+        assert!(task.content.contains("<LogonType>S4U</LogonType>"));
+        assert!(task.content.contains("<RunLevel>LeastPrivilege</RunLevel>"));
+        assert!(!task.content.contains("S-1-5-18"));
+        assert!(task.content.contains(
+            "<Arguments>--headless --data-dir \"/home/ana/.local/share/com.streamforge.app\"</Arguments>"
+        ));
+    }
+}
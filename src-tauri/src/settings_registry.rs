@@ -0,0 +1,353 @@
+//! Typed Settings Registry
+//!
+//! Describes the plain settings the settings screen edits: their key, the
+//! type and range of their value, and what has to be refreshed in the running
+//! app when they change. The registry lets a whole screen of values be
+//! validated up front and saved in one transaction (`apply_settings_batch`),
+//! so a bad value never leaves the settings half saved.
+//!
+//! Structured settings with their own commands (schedules, hooks, profiles,
+//! policies) are not part of the registry.
+
+use diesel::prelude::*;
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+use crate::account_expiry::{EXPIRY_WARNING_DAYS_KEY, MAX_EXPIRY_WARNING_DAYS};
+use crate::commands::logs::LOG_VERBOSITY_KEY;
+use crate::commands::matcher::{MATCH_ALGORITHM_KEY, MATCH_THRESHOLD_KEY};
+use crate::commands::update::AUTO_CHECK_UPDATES_KEY;
+use crate::db::schema::settings;
+use crate::db::Setting;
+use crate::low_resource::LOW_RESOURCE_MODE_KEY;
+use crate::scheduler::{EPG_RETENTION_DAYS_KEY, MAX_EPG_RETENTION_DAYS};
+use crate::server::epg::{EPG_WINDOW_DAYS_KEY, MAX_EPG_WINDOW_DAYS};
+use crate::server::failover::{
+    FAILOVER_NOTIFICATION_COOLDOWN_KEY, MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS, TS_CONTINUITY_KEY,
+    TS_PADDING_KEY,
+};
+use crate::server::lineup_preview::DEBUG_LINEUP_KEY;
+use crate::server::ssdp::SSDP_ENABLED_KEY;
+use crate::server::state::{BIND_ADDRESS_KEY, SERVER_PORT_KEY};
+use crate::server::stream::{PREFERRED_QUALITY_CHOICES, PREFERRED_QUALITY_KEY};
+use crate::xmltv::history::{EPG_HISTORY_DAYS_KEY, MAX_EPG_HISTORY_DAYS};
+
+/// Type and allowed range of a setting value
+#[derive(Debug, Clone, Copy)]
+pub enum SettingType {
+    /// Stored as "true" / "false"
+    Bool,
+    Integer {
+        min: i64,
+        max: i64,
+    },
+    Number {
+        min: f64,
+        max: f64,
+    },
+    /// One of a fixed set of strings
+    Choice(&'static [&'static str]),
+    /// Address the HTTP server can bind to
+    BindAddress,
+    /// Supported UI language code
+    Language,
+}
+
+/// What the running app refreshes after a setting changed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SettingEffect {
+    /// Read from the database when used
+    None,
+    /// Reload the active backend language
+    Language,
+    /// Reload the low resource mode flag
+    LowResourceMode,
+    /// Drop the cached /epg.xml and playlist
+    EpgOutput,
+    /// Change the level of the log files
+    LogVerbosity,
+}
+
+/// A setting known to the registry
+#[derive(Debug, Clone, Copy)]
+pub struct SettingDefinition {
+    pub key: &'static str,
+    pub value_type: SettingType,
+    pub effect: SettingEffect,
+}
+
+const fn setting(key: &'static str, value_type: SettingType) -> SettingDefinition {
+    SettingDefinition {
+        key,
+        value_type,
+        effect: SettingEffect::None,
+    }
+}
+
+/// All settings that can be saved with `apply_settings_batch`
+pub const SETTING_DEFINITIONS: &[SettingDefinition] = &[
+    setting(
+        SERVER_PORT_KEY,
+        SettingType::Integer {
+            min: 1024,
+            max: 65535,
+        },
+    ),
+    setting(BIND_ADDRESS_KEY, SettingType::BindAddress),
+    SettingDefinition {
+        key: crate::i18n::LANGUAGE_KEY,
+        value_type: SettingType::Language,
+        effect: SettingEffect::Language,
+    },
+    SettingDefinition {
+        key: LOG_VERBOSITY_KEY,
+        value_type: SettingType::Choice(&["minimal", "verbose"]),
+        effect: SettingEffect::LogVerbosity,
+    },
+    SettingDefinition {
+        key: LOW_RESOURCE_MODE_KEY,
+        value_type: SettingType::Bool,
+        effect: SettingEffect::LowResourceMode,
+    },
+    SettingDefinition {
+        key: EPG_WINDOW_DAYS_KEY,
+        value_type: SettingType::Integer {
+            min: 1,
+            max: MAX_EPG_WINDOW_DAYS as i64,
+        },
+        effect: SettingEffect::EpgOutput,
+    },
+    setting(
+        EPG_RETENTION_DAYS_KEY,
+        SettingType::Integer {
+            min: 0,
+            max: MAX_EPG_RETENTION_DAYS as i64,
+        },
+    ),
+    setting(
+        EPG_HISTORY_DAYS_KEY,
+        SettingType::Integer {
+            min: 0,
+            max: MAX_EPG_HISTORY_DAYS as i64,
+        },
+    ),
+    setting(
+        MATCH_THRESHOLD_KEY,
+        SettingType::Number { min: 0.0, max: 1.0 },
+    ),
+    setting(
+        MATCH_ALGORITHM_KEY,
+        SettingType::Choice(&["jaro_winkler", "levenshtein", "sorensen_dice"]),
+    ),
+    setting(TS_PADDING_KEY, SettingType::Bool),
+    setting(TS_CONTINUITY_KEY, SettingType::Bool),
+    setting(
+        PREFERRED_QUALITY_KEY,
+        SettingType::Choice(&PREFERRED_QUALITY_CHOICES),
+    ),
+    setting(
+        FAILOVER_NOTIFICATION_COOLDOWN_KEY,
+        SettingType::Integer {
+            min: 0,
+            max: MAX_FAILOVER_NOTIFICATION_COOLDOWN_SECS as i64,
+        },
+    ),
+    setting(
+        EXPIRY_WARNING_DAYS_KEY,
+        SettingType::Integer {
+            min: 1,
+            max: MAX_EXPIRY_WARNING_DAYS as i64,
+        },
+    ),
+    setting(DEBUG_LINEUP_KEY, SettingType::Bool),
+    setting(SSDP_ENABLED_KEY, SettingType::Bool),
+    setting(AUTO_CHECK_UPDATES_KEY, SettingType::Bool),
+];
+
+/// Look up a setting by key
+pub fn find_setting(key: &str) -> Option<&'static SettingDefinition> {
+    SETTING_DEFINITIONS.iter().find(|d| d.key == key)
+}
+
+impl SettingDefinition {
+    /// Validate a value, returning it as stored
+    ///
+    /// Accepts JSON booleans and numbers as well as their string forms, so
+    /// form values can be sent as they are.
+    pub fn validate(&self, value: &Value) -> Result<String, String> {
+        let text = match value {
+            Value::String(s) => s.trim().to_string(),
+            Value::Bool(b) => b.to_string(),
+            Value::Number(n) => n.to_string(),
+            _ => return Err("Expected a string, number or boolean".to_string()),
+        };
+
+        match self.value_type {
+            SettingType::Bool => match text.as_str() {
+                "true" => Ok("true".to_string()),
+                "false" => Ok("false".to_string()),
+                _ => Err("Must be true or false".to_string()),
+            },
+            SettingType::Integer { min, max } => match text.parse::<i64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(n.to_string()),
+                _ => Err(format!(
+                    "Must be a whole number between {} and {}",
+                    min, max
+                )),
+            },
+            SettingType::Number { min, max } => match text.parse::<f64>() {
+                Ok(n) if (min..=max).contains(&n) => Ok(n.to_string()),
+                _ => Err(format!("Must be a number between {} and {}", min, max)),
+            },
+            SettingType::Choice(choices) => {
+                if choices.contains(&text.as_str()) {
+                    Ok(text)
+                } else {
+                    Err(format!("Must be one of: {}", choices.join(", ")))
+                }
+            }
+            SettingType::BindAddress => {
+                crate::commands::validate_bind_address(&text).map(|ip| ip.to_string())
+            }
+            SettingType::Language => {
+                if crate::i18n::is_supported(&text) {
+                    Ok(text)
+                } else {
+                    Err(format!("Unsupported language: {}", text))
+                }
+            }
+        }
+    }
+}
+
+/// A saved setting whose value changed
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingChange {
+    pub key: String,
+    pub old_value: Option<String>,
+    pub new_value: String,
+}
+
+/// Result of saving a batch of settings
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SettingsBatchResult {
+    /// False when any value was invalid; nothing was saved then
+    pub applied: bool,
+    /// Settings whose value changed
+    pub changed: Vec<SettingChange>,
+    /// Validation error per key
+    pub errors: BTreeMap<String, String>,
+}
+
+/// Validate all values, then save them in one transaction
+///
+/// Nothing is saved if any key is unknown or any value invalid; the errors
+/// are returned per key instead.
+pub fn apply_settings_batch(
+    conn: &mut SqliteConnection,
+    values: &BTreeMap<String, Value>,
+) -> Result<SettingsBatchResult, String> {
+    let mut validated = Vec::with_capacity(values.len());
+    let mut errors = BTreeMap::new();
+    for (key, value) in values {
+        let Some(definition) = find_setting(key) else {
+            errors.insert(key.clone(), "Unknown setting".to_string());
+            continue;
+        };
+        match definition.validate(value) {
+            Ok(value) => validated.push((definition.key, value)),
+            Err(e) => {
+                errors.insert(key.clone(), e);
+            }
+        }
+    }
+    if !errors.is_empty() {
+        return Ok(SettingsBatchResult {
+            applied: false,
+            changed: Vec::new(),
+            errors,
+        });
+    }
+
+    let changed = conn
+        .transaction::<_, diesel::result::Error, _>(|conn| {
+            let mut changed = Vec::new();
+            for (key, value) in validated {
+                let old_value = settings::table
+                    .filter(settings::key.eq(key))
+                    .select(settings::value)
+                    .first::<String>(conn)
+                    .optional()?;
+                if old_value.as_deref() == Some(value.as_str()) {
+                    continue;
+                }
+                diesel::replace_into(settings::table)
+                    .values(&Setting::new(key, value.clone()))
+                    .execute(conn)?;
+                changed.push(SettingChange {
+                    key: key.to_string(),
+                    old_value,
+                    new_value: value,
+                });
+            }
+            Ok(changed)
+        })
+        .map_err(|e| format!("Failed to save settings: {}", e))?;
+
+    Ok(SettingsBatchResult {
+        applied: true,
+        changed,
+        errors,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matcher::MatchAlgorithm;
+
+    #[test]
+    fn test_batch_saves_nothing_when_a_value_is_invalid() {
+        let mut conn = crate::db::test_connection();
+
+        let mut values = BTreeMap::new();
+        values.insert(EPG_WINDOW_DAYS_KEY.to_string(), Value::from(3));
+        values.insert(TS_PADDING_KEY.to_string(), Value::from(true));
+        values.insert(MATCH_THRESHOLD_KEY.to_string(), Value::from("1.5"));
+        values.insert("not_a_setting".to_string(), Value::from("x"));
+
+        let result = apply_settings_batch(&mut conn, &values).unwrap();
+        assert!(!result.applied);
+        assert_eq!(
+            result.errors.keys().collect::<Vec<_>>(),
+            vec![MATCH_THRESHOLD_KEY, "not_a_setting"]
+        );
+        assert_eq!(crate::server::epg::get_epg_window_days(&mut conn), 7);
+
+        values.remove("not_a_setting");
+        values.insert(MATCH_THRESHOLD_KEY.to_string(), Value::from(0.9));
+        let result = apply_settings_batch(&mut conn, &values).unwrap();
+        assert!(result.applied);
+        assert_eq!(result.changed.len(), 3);
+        assert_eq!(crate::server::epg::get_epg_window_days(&mut conn), 3);
+        assert!(crate::server::failover::get_ts_padding_enabled(&mut conn));
+
+        // Unchanged values are not reported again
+        let result = apply_settings_batch(&mut conn, &values).unwrap();
+        assert!(result.applied && result.changed.is_empty());
+    }
+
+    #[test]
+    fn test_choices_match_algorithms() {
+        let definition = find_setting(MATCH_ALGORITHM_KEY).unwrap();
+        let SettingType::Choice(choices) = definition.value_type else {
+            panic!("match algorithm is a choice");
+        };
+        for choice in choices {
+            assert!(MatchAlgorithm::parse(choice).is_some());
+        }
+    }
+}
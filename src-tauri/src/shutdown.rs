@@ -0,0 +1,149 @@
+//! Coordinated shutdown
+//!
+//! Quitting (from the tray, or Ctrl+C / SIGTERM in headless mode) shuts the
+//! app down in order instead of killing it mid-stream:
+//!
+//! 1. The HTTP server stops accepting connections and asks active stream
+//!    sessions to end, waiting for connections to drain (see
+//!    `ServerHandle::stop`); the SSDP announcer says goodbye.
+//! 2. The EPG scheduler is stopped, so no new refresh or prune starts.
+//! 3. Errors that could not be written to the event log while running are
+//!    written now, followed by a shutdown event.
+//!
+//! The whole sequence is bounded by `SHUTDOWN_TIMEOUT`; the app exits
+//! afterwards whatever state it is in.
+
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::Duration;
+
+use diesel::prelude::*;
+use serde::Serialize;
+
+use crate::db::schema::event_log;
+use crate::db::NewEventLog;
+use crate::scheduler::EpgScheduler;
+use crate::server::{AppState, ServerHandle};
+
+/// Longest the shutdown sequence may take before the app exits anyway
+pub const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(15);
+
+const IDLE: u8 = 0;
+const RUNNING: u8 = 1;
+const FINISHED: u8 = 2;
+
+static STATE: AtomicU8 = AtomicU8::new(IDLE);
+
+/// What the shutdown sequence did
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShutdownReport {
+    /// Stream sessions asked to stop
+    pub stopped_streams: usize,
+    /// Whether connections were closed after the drain timeout
+    pub forced: bool,
+    /// Errors written to the event log on the way out
+    pub flushed_errors: usize,
+}
+
+/// Claim the shutdown; false if it is already running or done
+pub fn begin() -> bool {
+    STATE
+        .compare_exchange(IDLE, RUNNING, Ordering::SeqCst, Ordering::SeqCst)
+        .is_ok()
+}
+
+/// Mark the shutdown as done, so the exit it triggers is let through
+pub fn finish() {
+    STATE.store(FINISHED, Ordering::SeqCst);
+}
+
+/// Whether the shutdown sequence has finished
+pub fn is_finished() -> bool {
+    STATE.load(Ordering::SeqCst) == FINISHED
+}
+
+/// Stop the server, streams and scheduler, then flush the event log
+pub async fn shutdown(
+    server_handle: &ServerHandle,
+    server_state: &AppState,
+    scheduler: &EpgScheduler,
+) -> ShutdownReport {
+    let mut report = ShutdownReport::default();
+
+    if let Some(stopped) = server_handle.stop(server_state).await {
+        report.stopped_streams = stopped.stopped_streams;
+        report.forced = stopped.forced;
+    }
+
+    if let Err(e) = scheduler.stop().await {
+        tracing::warn!("Failed to stop EPG scheduler: {}", e);
+    }
+
+    match server_state.get_connection() {
+        Ok(mut conn) => {
+            report.flushed_errors = flush_event_log(&mut conn, &report);
+        }
+        Err(e) => eprintln!("Shutdown: event log not flushed: {}", e),
+    }
+
+    report
+}
+
+/// Write the errors kept only in memory and a shutdown event
+///
+/// Returns how many errors were written.
+fn flush_event_log(conn: &mut SqliteConnection, report: &ShutdownReport) -> usize {
+    let pending: Vec<NewEventLog> = crate::recent_errors::unpersisted()
+        .into_iter()
+        .map(|error| {
+            NewEventLog::new("error", error.category, error.message)
+                .with_details(serde_json::json!({ "at": error.timestamp }).to_string())
+        })
+        .collect();
+    let flushed = diesel::insert_into(event_log::table)
+        .values(&pending)
+        .execute(conn)
+        .unwrap_or_else(|e| {
+            eprintln!("Shutdown: failed to write pending errors: {}", e);
+            0
+        });
+
+    let details = serde_json::to_string(report).unwrap_or_default();
+    let _ = crate::commands::logs::log_event_internal(
+        conn,
+        if report.forced { "warn" } else { "info" },
+        "system",
+        &format!(
+            "StreamForge stopped ({} active streams ended)",
+            report.stopped_streams
+        ),
+        Some(&details),
+    );
+
+    flushed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_writes_shutdown_event() {
+        let mut conn = crate::db::test_connection();
+
+        let report = ShutdownReport {
+            stopped_streams: 2,
+            forced: true,
+            flushed_errors: 0,
+        };
+        flush_event_log(&mut conn, &report);
+
+        let (level, message): (String, String) = event_log::table
+            .select((event_log::level, event_log::message))
+            .order(event_log::id.desc())
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(level, "warn");
+        assert!(message.contains("2 active streams"));
+    }
+}
@@ -0,0 +1,208 @@
+//! Startup progress
+//!
+//! The window is shown as soon as the app is set up; database migrations,
+//! the HTTP server and the EPG scheduler are brought up afterwards in the
+//! background. Each of these phases is tracked here and reported by the
+//! `get_startup_status` command.
+//!
+//! Database connections handed out by `DbConnection` wait (up to
+//! `DATABASE_WAIT_TIMEOUT`) while the database phase is running, so commands
+//! invoked by the UI during startup never see a half-migrated schema.
+
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+
+/// Longest a database connection request waits for migrations
+const DATABASE_WAIT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A part of startup that runs in the background
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StartupPhase {
+    /// Migrations and loading persisted settings
+    Database,
+    /// Binding the HTTP server Plex connects to
+    Server,
+    /// Configuring the EPG refresh scheduler
+    Scheduler,
+}
+
+const PHASES: [StartupPhase; 3] = [
+    StartupPhase::Database,
+    StartupPhase::Server,
+    StartupPhase::Scheduler,
+];
+
+/// State of a startup phase
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PhaseState {
+    Pending,
+    Running,
+    Ready,
+    Failed,
+    /// Not run because an earlier phase failed
+    Skipped,
+}
+
+/// Status of one phase
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseStatus {
+    pub phase: StartupPhase,
+    pub state: PhaseState,
+    pub error: Option<String>,
+    /// How long the phase took (None until it finishes)
+    pub duration_ms: Option<u64>,
+}
+
+/// Status of all phases, as returned by `get_startup_status`
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StartupStatus {
+    /// Every phase is ready
+    pub ready: bool,
+    pub phases: Vec<PhaseStatus>,
+}
+
+struct PhaseEntry {
+    status: PhaseStatus,
+    started: Option<Instant>,
+}
+
+struct Startup {
+    phases: Mutex<Vec<PhaseEntry>>,
+    changed: Condvar,
+}
+
+static STARTUP: OnceLock<Startup> = OnceLock::new();
+
+fn startup() -> &'static Startup {
+    STARTUP.get_or_init(|| Startup {
+        phases: Mutex::new(
+            PHASES
+                .iter()
+                .map(|phase| PhaseEntry {
+                    status: PhaseStatus {
+                        phase: *phase,
+                        state: PhaseState::Pending,
+                        error: None,
+                        duration_ms: None,
+                    },
+                    started: None,
+                })
+                .collect(),
+        ),
+        changed: Condvar::new(),
+    })
+}
+
+fn update(phase: StartupPhase, f: impl FnOnce(&mut PhaseEntry)) {
+    let startup = startup();
+    let mut phases = startup.phases.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(entry) = phases.iter_mut().find(|entry| entry.status.phase == phase) {
+        f(entry);
+    }
+    startup.changed.notify_all();
+}
+
+/// Mark a phase as started
+pub fn begin(phase: StartupPhase) {
+    update(phase, |entry| {
+        entry.status.state = PhaseState::Running;
+        entry.started = Some(Instant::now());
+    });
+}
+
+/// Mark a phase as finished, successfully or with an error
+pub fn finish(phase: StartupPhase, result: Result<(), String>) {
+    update(phase, |entry| {
+        entry.status.duration_ms = entry.started.map(|at| at.elapsed().as_millis() as u64);
+        match result {
+            Ok(()) => entry.status.state = PhaseState::Ready,
+            Err(error) => {
+                tracing::error!("Startup phase {:?} failed: {}", phase, error);
+                entry.status.state = PhaseState::Failed;
+                entry.status.error = Some(error);
+            }
+        }
+    });
+}
+
+/// Mark a phase as not run
+pub fn skip(phase: StartupPhase, reason: &str) {
+    update(phase, |entry| {
+        entry.status.state = PhaseState::Skipped;
+        entry.status.error = Some(reason.to_string());
+    });
+}
+
+/// Current status of all phases
+pub fn status() -> StartupStatus {
+    let phases = startup().phases.lock().unwrap_or_else(|e| e.into_inner());
+    let phases: Vec<PhaseStatus> = phases.iter().map(|entry| entry.status.clone()).collect();
+    StartupStatus {
+        ready: phases
+            .iter()
+            .all(|status| status.state == PhaseState::Ready),
+        phases,
+    }
+}
+
+/// Block while the database phase is running
+///
+/// Returns immediately when it is not running (finished, failed, or never
+/// started, e.g. in tests and tools that open the database themselves).
+pub fn wait_for_database() {
+    let startup = startup();
+    let phases = startup.phases.lock().unwrap_or_else(|e| e.into_inner());
+    let (_phases, timeout) = startup
+        .changed
+        .wait_timeout_while(phases, DATABASE_WAIT_TIMEOUT, |phases| {
+            phases.iter().any(|entry| {
+                entry.status.phase == StartupPhase::Database
+                    && entry.status.state == PhaseState::Running
+            })
+        })
+        .unwrap_or_else(|e| e.into_inner());
+    if timeout.timed_out() {
+        tracing::warn!(
+            "Database still initializing after {:?}",
+            DATABASE_WAIT_TIMEOUT
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_phases_report_progress_and_release_waiters() {
+        // Nothing started yet: waiting returns right away
+        wait_for_database();
+        assert!(!status().ready);
+
+        begin(StartupPhase::Database);
+        let waiter = std::thread::spawn(|| {
+            wait_for_database();
+            status()
+        });
+        std::thread::sleep(Duration::from_millis(20));
+        finish(StartupPhase::Database, Ok(()));
+        let seen = waiter.join().unwrap();
+        assert_eq!(seen.phases[0].state, PhaseState::Ready);
+        assert!(seen.phases[0].duration_ms.is_some());
+
+        begin(StartupPhase::Server);
+        finish(StartupPhase::Server, Err("Address in use".to_string()));
+        skip(StartupPhase::Scheduler, "Server failed");
+        let status = status();
+        assert!(!status.ready);
+        assert_eq!(status.phases[1].state, PhaseState::Failed);
+        assert_eq!(status.phases[1].error.as_deref(), Some("Address in use"));
+        assert_eq!(status.phases[2].state, PhaseState::Skipped);
+    }
+}
@@ -0,0 +1,285 @@
+//! Background tasks for long operations
+//!
+//! Long operations (channel scans, matching, EPG refreshes) can run as tasks:
+//! the starting command returns a task ID right away, and the task's progress
+//! and outcome are kept here so the frontend can query them with
+//! `get_task_status`. Every change is also emitted as a `task-updated` event
+//! carrying the full `TaskInfo`.
+//!
+//! Cancelling aborts the task at its next await point. Work a task already
+//! saved (e.g. EPG sources refreshed before the cancel) is kept.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+
+use serde::Serialize;
+use tauri::async_runtime::JoinHandle;
+use tauri::{AppHandle, Emitter};
+use uuid::Uuid;
+
+/// Event emitted whenever a task starts, reports progress or finishes
+pub const TASK_UPDATED_EVENT: &str = "task-updated";
+
+/// Finished tasks kept for status queries (oldest dropped first)
+const FINISHED_TASKS_KEPT: usize = 50;
+
+/// Lifecycle state of a task
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TaskStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl TaskStatus {
+    fn is_finished(self) -> bool {
+        self != TaskStatus::Running
+    }
+}
+
+/// Snapshot of a task, as returned by `get_task_status` and emitted as events
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TaskInfo {
+    pub id: String,
+    /// What the task does, e.g. "channel_scan", "channel_matching", "epg_refresh"
+    pub kind: String,
+    pub status: TaskStatus,
+    /// Latest progress payload (same shape as the operation's own progress event)
+    pub progress: Option<serde_json::Value>,
+    /// Result of a completed task (same shape as the synchronous command's result)
+    pub result: Option<serde_json::Value>,
+    /// Error message of a failed task
+    pub error: Option<String>,
+    pub started_at: String,
+    pub finished_at: Option<String>,
+}
+
+struct TaskEntry {
+    info: TaskInfo,
+    handle: Option<JoinHandle<()>>,
+}
+
+type Registry = Mutex<HashMap<String, TaskEntry>>;
+
+static TASKS: OnceLock<Registry> = OnceLock::new();
+
+/// Handle used to emit task events (set once at startup)
+static APP_HANDLE: OnceLock<AppHandle> = OnceLock::new();
+
+fn tasks() -> &'static Registry {
+    TASKS.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+fn now() -> String {
+    chrono::Utc::now().to_rfc3339()
+}
+
+/// Store the app handle so task updates are emitted to the frontend
+pub fn init(app: AppHandle) {
+    let _ = APP_HANDLE.set(app);
+}
+
+fn emit(info: &TaskInfo) {
+    if let Some(app) = APP_HANDLE.get() {
+        let _ = app.emit(TASK_UPDATED_EVENT, info);
+    }
+}
+
+/// Apply a change to a running task and emit the new state
+///
+/// Finished tasks are left alone, so a late progress report or result can't
+/// overwrite a cancellation.
+fn update(id: &str, f: impl FnOnce(&mut TaskInfo)) {
+    let info = {
+        let mut tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+        let Some(entry) = tasks.get_mut(id) else {
+            return;
+        };
+        if entry.info.status.is_finished() {
+            return;
+        }
+        f(&mut entry.info);
+        if entry.info.status.is_finished() {
+            entry.info.finished_at = Some(now());
+            entry.handle = None;
+        }
+        entry.info.clone()
+    };
+    emit(&info);
+}
+
+/// Drop the oldest finished tasks beyond `FINISHED_TASKS_KEPT`
+fn prune(tasks: &mut HashMap<String, TaskEntry>) {
+    let mut finished: Vec<(String, String)> = tasks
+        .values()
+        .filter(|entry| entry.info.status.is_finished())
+        .map(|entry| {
+            let finished_at = entry.info.finished_at.clone().unwrap_or_default();
+            (finished_at, entry.info.id.clone())
+        })
+        .collect();
+    if finished.len() <= FINISHED_TASKS_KEPT {
+        return;
+    }
+    finished.sort();
+    for (_, id) in finished.iter().take(finished.len() - FINISHED_TASKS_KEPT) {
+        tasks.remove(id);
+    }
+}
+
+/// Reports progress for one task
+#[derive(Clone)]
+pub struct TaskProgress {
+    id: Arc<str>,
+}
+
+impl TaskProgress {
+    /// Replace the task's progress payload
+    pub fn report(&self, progress: serde_json::Value) {
+        update(&self.id, |info| info.progress = Some(progress));
+    }
+}
+
+fn register(kind: &str) -> String {
+    let id = Uuid::new_v4().to_string();
+    let info = TaskInfo {
+        id: id.clone(),
+        kind: kind.to_string(),
+        status: TaskStatus::Running,
+        progress: None,
+        result: None,
+        error: None,
+        started_at: now(),
+        finished_at: None,
+    };
+    {
+        let mut tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+        prune(&mut tasks);
+        tasks.insert(
+            id.clone(),
+            TaskEntry {
+                info: info.clone(),
+                handle: None,
+            },
+        );
+    }
+    emit(&info);
+    id
+}
+
+fn finish(id: &str, outcome: Result<serde_json::Value, String>) {
+    update(id, |info| match outcome {
+        Ok(result) => {
+            info.status = TaskStatus::Completed;
+            info.result = Some(result);
+        }
+        Err(error) => {
+            info.status = TaskStatus::Failed;
+            info.error = Some(error);
+        }
+    });
+}
+
+/// Start a task and return its ID
+///
+/// `run` receives a `TaskProgress` for reporting and returns the task's
+/// result, serialized into `TaskInfo::result`.
+pub fn spawn<T, F, Fut>(kind: &str, run: F) -> String
+where
+    T: Serialize,
+    F: FnOnce(TaskProgress) -> Fut,
+    Fut: Future<Output = Result<T, String>> + Send + 'static,
+{
+    let id = register(kind);
+    let progress = TaskProgress {
+        id: Arc::from(id.as_str()),
+    };
+    let future = run(progress);
+
+    let task_id = id.clone();
+    let handle = tauri::async_runtime::spawn(async move {
+        let outcome = future.await.and_then(|result| {
+            serde_json::to_value(result).map_err(|e| format!("Failed to serialize result: {}", e))
+        });
+        finish(&task_id, outcome);
+    });
+
+    let mut tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+    if let Some(entry) = tasks.get_mut(&id) {
+        if !entry.info.status.is_finished() {
+            entry.handle = Some(handle);
+        }
+    }
+    id
+}
+
+/// Get a task's current state
+pub fn status(id: &str) -> Option<TaskInfo> {
+    let tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+    tasks.get(id).map(|entry| entry.info.clone())
+}
+
+/// List known tasks, newest first
+pub fn list() -> Vec<TaskInfo> {
+    let tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+    let mut infos: Vec<TaskInfo> = tasks.values().map(|entry| entry.info.clone()).collect();
+    infos.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+    infos
+}
+
+/// Cancel a running task
+///
+/// Returns the task's state afterwards, or `None` for an unknown ID. Tasks
+/// that already finished are returned unchanged.
+pub fn cancel(id: &str) -> Option<TaskInfo> {
+    let handle = {
+        let mut tasks = tasks().lock().unwrap_or_else(|e| e.into_inner());
+        let entry = tasks.get_mut(id)?;
+        entry.handle.take()
+    };
+    if let Some(handle) = handle {
+        handle.abort();
+    }
+    update(id, |info| info.status = TaskStatus::Cancelled);
+    status(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_task_lifecycle() {
+        let id = register("channel_matching");
+        let progress = TaskProgress {
+            id: Arc::from(id.as_str()),
+        };
+        progress.report(serde_json::json!({ "status": "saving" }));
+
+        let info = status(&id).unwrap();
+        assert_eq!(info.status, TaskStatus::Running);
+        assert_eq!(info.progress.unwrap()["status"], "saving");
+
+        finish(&id, Ok(serde_json::json!({ "matchedCount": 3 })));
+        let info = status(&id).unwrap();
+        assert_eq!(info.status, TaskStatus::Completed);
+        assert_eq!(info.result.unwrap()["matchedCount"], 3);
+        assert!(info.finished_at.is_some());
+
+        // Finished tasks stay finished
+        assert_eq!(cancel(&id).unwrap().status, TaskStatus::Completed);
+
+        let cancelled = register("epg_refresh");
+        assert_eq!(cancel(&cancelled).unwrap().status, TaskStatus::Cancelled);
+        finish(&cancelled, Err("too late".to_string()));
+        let info = status(&cancelled).unwrap();
+        assert_eq!(info.status, TaskStatus::Cancelled);
+        assert!(info.error.is_none());
+
+        assert!(cancel("no-such-task").is_none());
+    }
+}
@@ -0,0 +1,262 @@
+//! Tuner reservation and priority rules
+//!
+//! Streams are normal priority unless their channel is on the high-priority
+//! list or, with `allow_priority_hint` enabled, the request asks for it
+//! (`/stream/{id}?priority=high`, e.g. from a DVR recording rule). The hint
+//! is off by default since any client on the network could send it. When
+//! tuners run short, the policy decides:
+//!
+//! - `off`: first come, first served (the default)
+//! - `reject`: normal-priority streams may not take the last
+//!   `reserved_tuners` tuners, so a recording always finds one free
+//! - `preempt`: a high-priority stream that finds no free tuner stops the
+//!   most recently started normal-priority stream to take its place, once
+//!   its own upstream has connected
+//!
+//! Rejections and preemptions are written to the event log by the stream
+//! proxy.
+
+use diesel::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::db::schema::settings;
+use crate::db::Setting;
+use crate::server::stream::StreamManager;
+
+/// Settings key for the tuner priority configuration (JSON)
+pub const TUNER_PRIORITY_KEY: &str = "tuner_priority";
+
+/// Most tuners that can be reserved for high-priority streams
+const MAX_RESERVED_TUNERS: u32 = 16;
+
+/// Most channels on the high-priority list
+const MAX_HIGH_PRIORITY_CHANNELS: usize = 1000;
+
+/// Priority of a stream request
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum StreamPriority {
+    #[default]
+    Normal,
+    High,
+}
+
+impl StreamPriority {
+    /// Parse a `?priority=` value
+    pub fn from_param(value: &str) -> Option<Self> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "normal" => Some(StreamPriority::Normal),
+            "high" => Some(StreamPriority::High),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            StreamPriority::Normal => "normal",
+            StreamPriority::High => "high",
+        }
+    }
+}
+
+/// What happens when tuners run short
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TunerPolicy {
+    #[default]
+    Off,
+    Reject,
+    Preempt,
+}
+
+/// Tuner priority configuration
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TunerPriorityConfig {
+    pub policy: TunerPolicy,
+    /// Tuners normal-priority streams may not use (`reject` policy)
+    pub reserved_tuners: u32,
+    /// XMLTV channel IDs whose streams are always high priority
+    pub high_priority_channels: Vec<i32>,
+    /// Honor `?priority=` on stream requests (off by default)
+    pub allow_priority_hint: bool,
+}
+
+impl Default for TunerPriorityConfig {
+    fn default() -> Self {
+        Self {
+            policy: TunerPolicy::Off,
+            reserved_tuners: 1,
+            high_priority_channels: Vec::new(),
+            allow_priority_hint: false,
+        }
+    }
+}
+
+impl TunerPriorityConfig {
+    pub fn validate(&self) -> Result<(), String> {
+        if self.reserved_tuners > MAX_RESERVED_TUNERS {
+            return Err(format!(
+                "At most {} tuners can be reserved",
+                MAX_RESERVED_TUNERS
+            ));
+        }
+        if self.high_priority_channels.len() > MAX_HIGH_PRIORITY_CHANNELS {
+            return Err(format!(
+                "At most {} channels can be high priority",
+                MAX_HIGH_PRIORITY_CHANNELS
+            ));
+        }
+        Ok(())
+    }
+
+    /// Priority of a request for a channel, with its `?priority=` hint
+    pub fn priority_for(&self, channel_id: i32, hint: Option<StreamPriority>) -> StreamPriority {
+        if self.high_priority_channels.contains(&channel_id) {
+            return StreamPriority::High;
+        }
+        match hint {
+            Some(priority) if self.allow_priority_hint => priority,
+            _ => StreamPriority::Normal,
+        }
+    }
+}
+
+/// Load the tuner priority configuration (defaults if unset or unreadable)
+pub fn load_config(conn: &mut SqliteConnection) -> TunerPriorityConfig {
+    settings::table
+        .filter(settings::key.eq(TUNER_PRIORITY_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| serde_json::from_str(&value).ok())
+        .unwrap_or_default()
+}
+
+/// Persist the tuner priority configuration
+///
+/// The channel list is sorted and deduplicated.
+pub fn save_config(
+    conn: &mut SqliteConnection,
+    config: &TunerPriorityConfig,
+) -> Result<TunerPriorityConfig, String> {
+    config.validate()?;
+    let mut config = config.clone();
+    config.high_priority_channels.sort_unstable();
+    config.high_priority_channels.dedup();
+
+    let value = serde_json::to_string(&config)
+        .map_err(|e| format!("Failed to serialize tuner priority: {}", e))?;
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(TUNER_PRIORITY_KEY, value))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(config)
+}
+
+/// Outcome of asking for a tuner
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Admission {
+    /// A tuner is free
+    Allowed,
+    /// Stop this session to free a tuner
+    Preempt(String),
+    /// Only reserved tuners are free
+    Reserved,
+    /// No tuner is free
+    TunerLimit,
+}
+
+/// Decide whether a stream of the given priority may take a tuner
+pub fn admit(
+    manager: &StreamManager,
+    config: &TunerPriorityConfig,
+    priority: StreamPriority,
+) -> Admission {
+    if manager.can_start_stream() {
+        let reserved = config.policy == TunerPolicy::Reject
+            && priority < StreamPriority::High
+            && manager.free_slots() <= config.reserved_tuners as usize;
+        return if reserved {
+            Admission::Reserved
+        } else {
+            Admission::Allowed
+        };
+    }
+    preemption(manager, config, priority, None).map_or(Admission::TunerLimit, Admission::Preempt)
+}
+
+/// Session to stop so a stream of the given priority can start
+///
+/// Only under the `preempt` policy, and only sessions of lower priority
+/// (playing from one of `accounts`, if given) are stopped.
+pub fn preemption(
+    manager: &StreamManager,
+    config: &TunerPriorityConfig,
+    priority: StreamPriority,
+    accounts: Option<&[i32]>,
+) -> Option<String> {
+    if config.policy != TunerPolicy::Preempt {
+        return None;
+    }
+    manager.preemption_candidate(priority, accounts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::server::stream::StreamSession;
+
+    #[test]
+    fn test_admission_by_policy() {
+        let manager = StreamManager::new(2);
+        let mut config = TunerPriorityConfig {
+            policy: TunerPolicy::Reject,
+            high_priority_channels: vec![7],
+            ..Default::default()
+        };
+        assert_eq!(config.priority_for(7, None), StreamPriority::High);
+        assert_eq!(
+            config.priority_for(1, Some(StreamPriority::High)),
+            StreamPriority::Normal
+        );
+        config.allow_priority_hint = true;
+        assert_eq!(
+            config.priority_for(1, Some(StreamPriority::High)),
+            StreamPriority::High
+        );
+
+        let normal = StreamPriority::Normal;
+        let high = StreamPriority::High;
+        assert_eq!(admit(&manager, &config, normal), Admission::Allowed);
+        let older = manager
+            .start_session(StreamSession::new(1, 10, "HD".to_string()))
+            .unwrap();
+        // The last tuner is reserved
+        assert_eq!(admit(&manager, &config, normal), Admission::Reserved);
+        assert_eq!(admit(&manager, &config, high), Admission::Allowed);
+
+        std::thread::sleep(std::time::Duration::from_millis(5));
+        let newer = manager
+            .start_session(StreamSession::new(2, 20, "HD".to_string()))
+            .unwrap();
+        let recording =
+            manager.start_session(StreamSession::new(7, 70, "HD".to_string()).with_priority(high));
+        assert!(recording.is_none());
+        assert_eq!(admit(&manager, &config, high), Admission::TunerLimit);
+
+        // Preemption stops the newest normal-priority stream
+        config.policy = TunerPolicy::Preempt;
+        assert_eq!(admit(&manager, &config, normal), Admission::TunerLimit);
+        assert_eq!(
+            admit(&manager, &config, high),
+            Admission::Preempt(newer.clone())
+        );
+        assert!(manager.preempt(&newer).is_some());
+        assert!(manager.is_stop_requested(&newer));
+        assert_eq!(admit(&manager, &config, high), Admission::Allowed);
+        assert!(manager.get_session(&older).is_some());
+    }
+}
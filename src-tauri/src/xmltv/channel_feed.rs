@@ -0,0 +1,339 @@
+//! Per-channel XMLTV feeds
+//!
+//! A channel feed is a small XMLTV document (usually a single-channel feed)
+//! attached to one guide channel by tvg-id. It is not an XMLTV source: it adds
+//! no channels of its own. After a refresh its programs are merged into the
+//! guide channel with that tvg-id, replacing any programs from the regular
+//! source in the time span the feed covers.
+
+use diesel::prelude::*;
+
+use super::fetcher::{fetch_xmltv, SourceAuth};
+use super::history::{self, ArchiveScope};
+use super::parser::parse_xmltv_data;
+use super::types::{ParsedChannel, ParsedProgram};
+use crate::db::schema::{channel_epg_feeds, programs, xmltv_channels};
+use crate::db::{ChannelEpgFeed, NewProgram};
+
+/// Rows inserted per statement when merging feed programs
+const PROGRAM_INSERT_CHUNK_SIZE: usize = 500;
+
+/// Outcome of refreshing a set of channel feeds
+#[derive(Debug, Default, Clone)]
+pub struct ChannelFeedSummary {
+    pub succeeded: usize,
+    /// "tvg-id: error" for every feed that failed
+    pub errors: Vec<String>,
+}
+
+/// Pick the programs of the feed channel out of a parsed feed
+///
+/// With `feed_channel_id` set only that channel's programs are used. Without
+/// it the feed must carry exactly one channel.
+pub fn select_feed_programs(
+    channels: &[ParsedChannel],
+    parsed_programs: Vec<ParsedProgram>,
+    feed_channel_id: Option<&str>,
+) -> Result<Vec<ParsedProgram>, String> {
+    let channel_id = match feed_channel_id {
+        Some(id) => id.to_string(),
+        None => {
+            let mut ids: Vec<&str> = channels.iter().map(|c| c.channel_id.as_str()).collect();
+            if ids.is_empty() {
+                ids = parsed_programs
+                    .iter()
+                    .map(|p| p.channel_id.as_str())
+                    .collect();
+            }
+            ids.sort_unstable();
+            ids.dedup();
+            match ids.as_slice() {
+                [id] => id.to_string(),
+                [] => return Err("Feed contains no channels".to_string()),
+                _ => {
+                    return Err(format!(
+                        "Feed contains {} channels; set the feed channel ID",
+                        ids.len()
+                    ))
+                }
+            }
+        }
+    };
+
+    let selected: Vec<ParsedProgram> = parsed_programs
+        .into_iter()
+        .filter(|p| p.channel_id == channel_id)
+        .collect();
+
+    if selected.is_empty() {
+        return Err(format!("Feed has no programs for channel '{}'", channel_id));
+    }
+    Ok(selected)
+}
+
+/// Merge feed programs into every guide channel with the given tvg-id
+///
+/// Existing programs overlapping the feed's time span are replaced; programs
+/// outside it are kept. Returns the number of guide channels updated.
+pub fn merge_feed_programs(
+    conn: &mut SqliteConnection,
+    tvg_id: &str,
+    feed_programs: &[ParsedProgram],
+) -> QueryResult<usize> {
+    let (Some(span_start), Some(span_end)) = (
+        feed_programs.iter().map(|p| p.start_time.as_str()).min(),
+        feed_programs.iter().map(|p| p.end_time.as_str()).max(),
+    ) else {
+        return Ok(0);
+    };
+
+    conn.transaction(|conn| {
+        let channel_ids: Vec<i32> = xmltv_channels::table
+            .filter(xmltv_channels::channel_id.eq(tvg_id))
+            .select(xmltv_channels::id)
+            .load::<Option<i32>>(conn)?
+            .into_iter()
+            .flatten()
+            .collect();
+
+        for &channel_id in &channel_ids {
+            history::archive_aired_programs(conn, ArchiveScope::Channel(channel_id), chrono::Utc::now())?;
+            diesel::delete(
+                programs::table
+                    .filter(programs::xmltv_channel_id.eq(channel_id))
+                    .filter(programs::start_time.lt(span_end))
+                    .filter(programs::end_time.gt(span_start)),
+            )
+            .execute(conn)?;
+
+            let rows: Vec<NewProgram> = feed_programs
+                .iter()
+                .map(|p| p.to_new_program(channel_id))
+                .collect();
+
+            for chunk in rows.chunks(PROGRAM_INSERT_CHUNK_SIZE) {
+                diesel::insert_into(programs::table)
+                    .values(chunk)
+                    .execute(conn)?;
+            }
+        }
+
+        Ok(channel_ids.len())
+    })
+}
+
+/// Fetch one feed and merge it into its guide channel
+///
+/// Returns the number of programs merged. The outcome is recorded on the feed
+/// row either way.
+pub async fn refresh_channel_feed(
+    conn: &mut SqliteConnection,
+    feed: &ChannelEpgFeed,
+) -> Result<usize, String> {
+    let result = fetch_and_merge(conn, feed).await;
+
+    let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+    let update = diesel::update(channel_epg_feeds::table.find(&feed.tvg_id));
+    let recorded = match &result {
+        Ok(count) => update
+            .set((
+                channel_epg_feeds::last_fetched_at.eq(&now),
+                channel_epg_feeds::program_count.eq(*count as i32),
+                channel_epg_feeds::last_error.eq(None::<String>),
+            ))
+            .execute(conn),
+        Err(e) => update
+            .set((
+                channel_epg_feeds::last_fetched_at.eq(&now),
+                channel_epg_feeds::last_error.eq(e),
+            ))
+            .execute(conn),
+    };
+    if let Err(e) = recorded {
+        tracing::warn!(
+            "Failed to record channel feed status for {}: {}",
+            feed.tvg_id,
+            e
+        );
+    }
+
+    result
+}
+
+async fn fetch_and_merge(
+    conn: &mut SqliteConnection,
+    feed: &ChannelEpgFeed,
+) -> Result<usize, String> {
+    let data = fetch_xmltv(&feed.url, "auto", &SourceAuth::default())
+        .await
+        .map_err(|e| e.to_string())?;
+    let (channels, parsed_programs) = parse_xmltv_data(&data).map_err(|e| e.to_string())?;
+    let feed_programs =
+        select_feed_programs(&channels, parsed_programs, feed.feed_channel_id.as_deref())?;
+
+    let updated = merge_feed_programs(conn, &feed.tvg_id, &feed_programs)
+        .map_err(|e| format!("Database error: {}", e))?;
+    if updated == 0 {
+        return Err(format!("No guide channel with ID '{}'", feed.tvg_id));
+    }
+    Ok(feed_programs.len())
+}
+
+/// Refresh all channel feeds, or only those for channels of one XMLTV source
+///
+/// Called after source refreshes, since a source refresh replaces the
+/// programs the feeds were merged into.
+pub async fn refresh_channel_feeds(
+    conn: &mut SqliteConnection,
+    source_id: Option<i32>,
+) -> ChannelFeedSummary {
+    let mut query = channel_epg_feeds::table.into_boxed();
+    if let Some(source_id) = source_id {
+        query = query.filter(
+            channel_epg_feeds::tvg_id.eq_any(
+                xmltv_channels::table
+                    .filter(xmltv_channels::source_id.eq(source_id))
+                    .select(xmltv_channels::channel_id),
+            ),
+        );
+    }
+
+    let feeds: Vec<ChannelEpgFeed> = match query.load(conn) {
+        Ok(feeds) => feeds,
+        Err(e) => {
+            return ChannelFeedSummary {
+                succeeded: 0,
+                errors: vec![format!("Failed to load channel feeds: {}", e)],
+            }
+        }
+    };
+
+    let mut summary = ChannelFeedSummary::default();
+    for feed in &feeds {
+        match refresh_channel_feed(conn, feed).await {
+            Ok(_) => summary.succeeded += 1,
+            Err(e) => {
+                tracing::warn!("Channel feed for {} failed: {}", feed.tvg_id, e);
+                summary.errors.push(format!("{}: {}", feed.tvg_id, e));
+            }
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::xmltv_sources;
+    use crate::db::{test_connection, NewXmltvChannel, NewXmltvSource, XmltvChannel, XmltvSource};
+
+    fn program(channel_id: &str, title: &str, start: &str, end: &str) -> ParsedProgram {
+        ParsedProgram {
+            channel_id: channel_id.to_string(),
+            title: title.to_string(),
+            start_time: start.to_string(),
+            end_time: end.to_string(),
+            ..Default::default()
+        }
+    }
+
+    fn channel(channel_id: &str) -> ParsedChannel {
+        ParsedChannel {
+            channel_id: channel_id.to_string(),
+            display_name: channel_id.to_string(),
+            icon: None,
+        }
+    }
+
+    #[test]
+    fn test_select_feed_programs() {
+        let parsed = vec![
+            program("a", "A1", "2026-01-01T00:00:00Z", "2026-01-01T01:00:00Z"),
+            program("b", "B1", "2026-01-01T00:00:00Z", "2026-01-01T01:00:00Z"),
+        ];
+
+        // Single-channel feed needs no channel ID
+        let selected = select_feed_programs(&[channel("a")], parsed[..1].to_vec(), None).unwrap();
+        assert_eq!(selected.len(), 1);
+
+        // Multi-channel feed requires one
+        assert!(select_feed_programs(&[channel("a"), channel("b")], parsed.clone(), None).is_err());
+        let selected =
+            select_feed_programs(&[channel("a"), channel("b")], parsed.clone(), Some("b")).unwrap();
+        assert_eq!(selected[0].title, "B1");
+
+        assert!(select_feed_programs(&[], parsed, Some("c")).is_err());
+    }
+
+    #[test]
+    fn test_merge_feed_programs_replaces_overlapping_span() {
+        let mut conn = test_connection();
+
+        let source: XmltvSource = diesel::insert_into(xmltv_sources::table)
+            .values(&NewXmltvSource::new(
+                "Main",
+                "https://example.com/epg.xml",
+                "xml",
+            ))
+            .get_result(&mut conn)
+            .unwrap();
+        let guide: XmltvChannel = diesel::insert_into(xmltv_channels::table)
+            .values(&NewXmltvChannel::new(
+                source.id.unwrap(),
+                "local.us",
+                "Local",
+                None,
+            ))
+            .get_result(&mut conn)
+            .unwrap();
+        let guide_id = guide.id.unwrap();
+
+        let existing = vec![
+            NewProgram::new(
+                guide_id,
+                "Early",
+                "2026-01-01T00:00:00Z",
+                "2026-01-01T01:00:00Z",
+            ),
+            NewProgram::new(
+                guide_id,
+                "Gap",
+                "2026-01-01T01:00:00Z",
+                "2026-01-01T03:00:00Z",
+            ),
+            NewProgram::new(
+                guide_id,
+                "Late",
+                "2026-01-01T05:00:00Z",
+                "2026-01-01T06:00:00Z",
+            ),
+        ];
+        diesel::insert_into(programs::table)
+            .values(&existing)
+            .execute(&mut conn)
+            .unwrap();
+
+        let feed = vec![
+            program("x", "News", "2026-01-01T02:00:00Z", "2026-01-01T03:00:00Z"),
+            program("x", "Movie", "2026-01-01T03:00:00Z", "2026-01-01T05:00:00Z"),
+        ];
+        assert_eq!(
+            merge_feed_programs(&mut conn, "local.us", &feed).unwrap(),
+            1
+        );
+
+        let titles: Vec<String> = programs::table
+            .filter(programs::xmltv_channel_id.eq(guide_id))
+            .order(programs::start_time.asc())
+            .select(programs::title)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(titles, vec!["Early", "News", "Movie", "Late"]);
+
+        // Unknown tvg-id touches nothing
+        assert_eq!(
+            merge_feed_programs(&mut conn, "other.us", &feed).unwrap(),
+            0
+        );
+    }
+}
@@ -1,15 +1,93 @@
 //! XMLTV fetcher for downloading and decompressing EPG data
+//!
+//! Sources are normally downloaded over HTTP(S). Sources with a `file://` URL
+//! (e.g. the output of a local EPG grabber) are read from disk instead and go
+//! through the same decompression and parsing.
+//!
+//! Sources may carry HTTP basic auth and/or a custom request header (e.g. an
+//! API key); their secrets live in the credential store (see `SourceAuth`).
 
 use flate2::read::GzDecoder;
 use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::time::Duration;
 
 use super::parser::detect_gzip;
 use super::types::XmltvError;
+use crate::credentials::CredentialManager;
+use crate::db::XmltvSource;
 
 /// Maximum download timeout in seconds
 const DOWNLOAD_TIMEOUT_SECS: u64 = 30;
 
+/// Credentials sent when downloading a source
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SourceAuth {
+    /// HTTP basic auth user and password
+    pub basic: Option<(String, String)>,
+    /// Extra request header name and value
+    pub header: Option<(String, String)>,
+}
+
+impl SourceAuth {
+    /// Decrypt the credentials configured for a source
+    ///
+    /// Sources without secrets need no credential manager.
+    pub fn for_source(
+        source: &XmltvSource,
+        credentials: Option<&CredentialManager>,
+    ) -> Result<Self, XmltvError> {
+        let source_id = source.id.unwrap_or(0);
+        let decrypt = |secret: &str, encrypted: &Option<Vec<u8>>| -> Result<Option<String>, XmltvError> {
+            let Some(encrypted) = encrypted else {
+                return Ok(None);
+            };
+            let credentials = credentials.ok_or_else(|| {
+                XmltvError::DownloadError("Source credentials are unavailable".into())
+            })?;
+            credentials
+                .retrieve_password(&source_credential_key(source_id, secret), encrypted)
+                .map(Some)
+                .map_err(|e| XmltvError::DownloadError(format!("Credential error: {}", e)))
+        };
+
+        let basic = match &source.auth_username {
+            Some(username) => Some((
+                username.clone(),
+                decrypt(PASSWORD_SECRET, &source.auth_password_encrypted)?.unwrap_or_default(),
+            )),
+            None => None,
+        };
+        let header = match &source.auth_header_name {
+            Some(name) => decrypt(HEADER_SECRET, &source.auth_header_value_encrypted)?
+                .map(|value| (name.clone(), value)),
+            None => None,
+        };
+        Ok(Self { basic, header })
+    }
+
+    fn apply(&self, mut request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        if let Some((username, password)) = &self.basic {
+            request = request.basic_auth(username, Some(password));
+        }
+        if let Some((name, value)) = &self.header {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        request
+    }
+}
+
+/// Credential store name of a source's basic auth password
+pub const PASSWORD_SECRET: &str = "password";
+
+/// Credential store name of a source's custom header value
+pub const HEADER_SECRET: &str = "header";
+
+/// Key under which a source secret is kept in the credential store
+pub fn source_credential_key(source_id: i32, secret: &str) -> String {
+    format!("xmltv-source-{}-{}", source_id, secret)
+}
+
 /// Fetch XMLTV data from a URL
 ///
 /// Handles both plain XML and gzipped (.xml.gz) formats.
@@ -18,20 +96,21 @@ const DOWNLOAD_TIMEOUT_SECS: u64 = 30;
 /// # Arguments
 /// * `url` - The URL to fetch XMLTV data from
 /// * `format` - The format hint: "xml", "xml_gz", or "auto"
+/// * `auth` - Basic auth and/or header to send (`SourceAuth::default()` for none)
 ///
 /// # Returns
 /// The decompressed XMLTV data as bytes
-pub async fn fetch_xmltv(url: &str, format: &str) -> Result<Vec<u8>, XmltvError> {
+pub async fn fetch_xmltv(url: &str, format: &str, auth: &SourceAuth) -> Result<Vec<u8>, XmltvError> {
     // Validate URL for SSRF protection
     validate_url_for_ssrf(url)?;
 
-    let client = reqwest::Client::builder()
+    let client = crate::dns::configure(reqwest::Client::builder())
         .timeout(Duration::from_secs(DOWNLOAD_TIMEOUT_SECS))
         .build()
         .map_err(|e| XmltvError::DownloadError(format!("Failed to create HTTP client: {}", e)))?;
 
-    let response = client
-        .get(url)
+    let response = auth
+        .apply(client.get(url))
         .send()
         .await
         .map_err(|e| XmltvError::DownloadError(format!("Failed to fetch URL: {}", e)))?;
@@ -60,13 +139,72 @@ pub async fn fetch_xmltv(url: &str, format: &str) -> Result<Vec<u8>, XmltvError>
         .map_err(|e| XmltvError::DownloadError(format!("Failed to read response body: {}", e)))?
         .to_vec();
 
+    decompress_if_needed(data, format, is_gzip_content_type)
+}
+
+/// Whether a source URL points at a local file
+pub fn is_local_file_url(url: &str) -> bool {
+    url.trim()
+        .get(..7)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("file://"))
+}
+
+/// Local path of a `file://` source URL
+pub fn local_file_path(url: &str) -> Result<PathBuf, XmltvError> {
+    let parsed = url::Url::parse(url.trim())
+        .map_err(|e| XmltvError::UrlNotAllowed(format!("Invalid URL: {}", e)))?;
+    if parsed.scheme() != "file" {
+        return Err(XmltvError::UrlNotAllowed(format!(
+            "URL scheme '{}' is not a local file",
+            parsed.scheme()
+        )));
+    }
+    parsed
+        .to_file_path()
+        .map_err(|_| XmltvError::UrlNotAllowed("Invalid local file path".into()))
+}
+
+/// Load an XMLTV source: `file://` URLs are read from disk, others downloaded
+///
+/// Only sources configured by the user go through here; other downloads
+/// (channel feeds, alias packs) use `fetch_xmltv` and stay HTTP(S)-only.
+pub async fn load_xmltv_source(
+    url: &str,
+    format: &str,
+    auth: &SourceAuth,
+) -> Result<Vec<u8>, XmltvError> {
+    if is_local_file_url(url) {
+        read_xmltv_file(&local_file_path(url)?, format).await
+    } else {
+        fetch_xmltv(url, format, auth).await
+    }
+}
+
+/// Read XMLTV data from a local file
+///
+/// With format "auto", `.gz` files and gzip magic bytes are decompressed.
+pub async fn read_xmltv_file(path: &Path, format: &str) -> Result<Vec<u8>, XmltvError> {
+    let data = tokio::fs::read(path).await.map_err(|e| {
+        XmltvError::DownloadError(format!("Failed to read {}: {}", path.display(), e))
+    })?;
+    let gz_extension = path
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("gz"));
+    decompress_if_needed(data, format, gz_extension)
+}
+
+/// Decompress data per the source format
+///
+/// `gzip_hint` (content-type or file extension) is used by "auto" along with
+/// the gzip magic bytes.
+fn decompress_if_needed(data: Vec<u8>, format: &str, gzip_hint: bool) -> Result<Vec<u8>, XmltvError> {
     // Determine if we need to decompress
     let should_decompress = match format {
         "xml_gz" => true,
         "xml" => false,
         "auto" | _ => {
-            // Auto-detect: check magic bytes first, then content-type
-            detect_gzip(&data) || is_gzip_content_type
+            // Auto-detect: check magic bytes first, then the hint
+            detect_gzip(&data) || gzip_hint
         }
     };
 
@@ -93,7 +231,7 @@ fn decompress_gzip(compressed: &[u8]) -> Result<Vec<u8>, XmltvError> {
 ///
 /// Blocks localhost, private IPs, and non-HTTP(S) schemes.
 /// In test mode (IPTV_TEST_MODE=1), localhost is allowed for mock servers.
-fn validate_url_for_ssrf(url_str: &str) -> Result<(), XmltvError> {
+pub(crate) fn validate_url_for_ssrf(url_str: &str) -> Result<(), XmltvError> {
     // In test mode, allow localhost for mock servers
     let test_mode = std::env::var("IPTV_TEST_MODE").unwrap_or_default() == "1";
     let parsed =
@@ -200,6 +338,37 @@ mod tests {
         assert!(validate_url_for_ssrf("file:///etc/passwd").is_err());
     }
 
+    #[tokio::test]
+    async fn test_load_local_xmltv_file() {
+        use flate2::write::GzEncoder;
+        use flate2::Compression;
+        use std::io::Write;
+
+        let original = b"<?xml version=\"1.0\"?><tv></tv>";
+        let dir = std::env::temp_dir().join(format!("xmltv-local-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("guide.xml.gz");
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder.write_all(original).unwrap();
+        std::fs::write(&path, encoder.finish().unwrap()).unwrap();
+
+        let url = url::Url::from_file_path(&path).unwrap().to_string();
+        assert!(is_local_file_url(&url));
+        assert!(!is_local_file_url("https://example.com/epg.xml"));
+        let none = SourceAuth::default();
+        assert_eq!(load_xmltv_source(&url, "auto", &none).await.unwrap(), original);
+
+        let missing = url::Url::from_file_path(dir.join("missing.xml")).unwrap();
+        assert!(matches!(
+            load_xmltv_source(missing.as_str(), "auto", &none).await,
+            Err(XmltvError::DownloadError(_))
+        ));
+        // Remote-only downloads still refuse local files
+        assert!(fetch_xmltv(&url, "auto", &none).await.is_err());
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_decompress_gzip() {
         use flate2::write::GzEncoder;
@@ -0,0 +1,405 @@
+//! Guide History
+//!
+//! Programs are replaced on every EPG refresh and pruned after the retention
+//! window, so the guide alone can't tell what aired last week. Before aired
+//! programs are deleted they are copied to `program_history`, keyed by source
+//! and XMLTV channel id, and kept for a configurable number of days. This
+//! answers "what was on" when a recording captured the wrong show.
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use diesel::prelude::*;
+use diesel::sql_types::{Integer, Nullable, Text};
+use serde::Serialize;
+use std::collections::HashSet;
+
+use crate::db::schema::{program_history, programs, settings, xmltv_channels};
+use crate::db::Setting;
+
+/// Settings key for how many days aired programs are kept (0 = no history)
+pub const EPG_HISTORY_DAYS_KEY: &str = "epg_history_days";
+
+/// Default guide history window in days
+pub const DEFAULT_EPG_HISTORY_DAYS: u32 = 14;
+
+/// Longest configurable guide history window in days
+pub const MAX_EPG_HISTORY_DAYS: u32 = 365;
+
+/// Time format of program start and end times
+const PROGRAM_TIME_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// Programs to copy to the history
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArchiveScope {
+    All,
+    /// Programs of one XMLTV source
+    Source(i32),
+    /// Programs of one XMLTV channel row
+    Channel(i32),
+}
+
+/// One program on a channel's history for a day
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GuideHistoryEntry {
+    pub source_id: i32,
+    /// XMLTV channel id
+    pub channel_id: String,
+    pub channel_name: String,
+    pub title: String,
+    pub sub_title: Option<String>,
+    pub description: Option<String>,
+    pub start_time: String,
+    pub end_time: String,
+    pub category: Option<String>,
+    pub episode_info: Option<String>,
+    /// False while the program is still in the current guide
+    pub archived: bool,
+}
+
+/// Columns of a history row, in `program_history` order
+type HistoryRow = (
+    i32,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+impl GuideHistoryEntry {
+    fn from_row(row: HistoryRow, archived: bool) -> Self {
+        let (
+            source_id,
+            channel_id,
+            channel_name,
+            title,
+            sub_title,
+            description,
+            start_time,
+            end_time,
+            category,
+            episode_info,
+        ) = row;
+        Self {
+            source_id,
+            channel_id,
+            channel_name,
+            title,
+            sub_title,
+            description,
+            start_time,
+            end_time,
+            category,
+            episode_info,
+            archived,
+        }
+    }
+}
+
+/// Days aired programs are kept in the history (0 = no history)
+pub fn get_epg_history_days(conn: &mut SqliteConnection) -> u32 {
+    settings::table
+        .filter(settings::key.eq(EPG_HISTORY_DAYS_KEY))
+        .select(settings::value)
+        .first::<String>(conn)
+        .ok()
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_EPG_HISTORY_DAYS)
+}
+
+/// Set the guide history window in days (0 = no history)
+pub fn set_epg_history_days(conn: &mut SqliteConnection, days: u32) -> Result<(), String> {
+    if days > MAX_EPG_HISTORY_DAYS {
+        return Err(format!(
+            "History must be between 0 and {} days",
+            MAX_EPG_HISTORY_DAYS
+        ));
+    }
+    diesel::replace_into(settings::table)
+        .values(&Setting::new(EPG_HISTORY_DAYS_KEY, days.to_string()))
+        .execute(conn)
+        .map_err(|e| format!("Failed to save setting: {}", e))?;
+    Ok(())
+}
+
+/// Copy programs that have started by `now` to the history
+///
+/// Call before programs are deleted. Programs already in the history are
+/// replaced, so a corrected listing wins. Does nothing when history is off.
+/// Returns the number of programs copied.
+pub fn archive_aired_programs(
+    conn: &mut SqliteConnection,
+    scope: ArchiveScope,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    let days = get_epg_history_days(conn);
+    if days == 0 {
+        return Ok(0);
+    }
+    let now_str = now.format(PROGRAM_TIME_FORMAT).to_string();
+    let cutoff = (now - Duration::days(i64::from(days)))
+        .format(PROGRAM_TIME_FORMAT)
+        .to_string();
+
+    let (source_id, channel_id) = match scope {
+        ArchiveScope::All => (None, None),
+        ArchiveScope::Source(id) => (Some(id), None),
+        ArchiveScope::Channel(id) => (None, Some(id)),
+    };
+
+    diesel::sql_query(
+        "INSERT OR REPLACE INTO program_history
+            (source_id, channel_id, channel_name, title, sub_title, description,
+             start_time, end_time, category, episode_info)
+         SELECT xc.source_id, xc.channel_id, xc.display_name, p.title, p.sub_title,
+                p.description, p.start_time, p.end_time, p.category, p.episode_info
+         FROM programs p
+         JOIN xmltv_channels xc ON xc.id = p.xmltv_channel_id
+         WHERE p.start_time < ? AND p.end_time >= ?
+           AND (? IS NULL OR xc.source_id = ?)
+           AND (? IS NULL OR p.xmltv_channel_id = ?)",
+    )
+    .bind::<Text, _>(now_str)
+    .bind::<Text, _>(cutoff)
+    .bind::<Nullable<Integer>, _>(source_id)
+    .bind::<Nullable<Integer>, _>(source_id)
+    .bind::<Nullable<Integer>, _>(channel_id)
+    .bind::<Nullable<Integer>, _>(channel_id)
+    .execute(conn)
+}
+
+/// Delete history older than the history window (all of it when history is off)
+pub fn prune_program_history(
+    conn: &mut SqliteConnection,
+    days: u32,
+    now: DateTime<Utc>,
+) -> QueryResult<usize> {
+    if days == 0 {
+        return diesel::delete(program_history::table).execute(conn);
+    }
+    let cutoff = (now - Duration::days(i64::from(days)))
+        .format(PROGRAM_TIME_FORMAT)
+        .to_string();
+    diesel::delete(program_history::table.filter(program_history::end_time.lt(cutoff)))
+        .execute(conn)
+}
+
+/// What was on a channel during a UTC day
+///
+/// `channel` is an XMLTV channel id or, ignoring case, a channel name.
+/// Combines the history with programs still in the guide, preferring the
+/// guide's listing, ordered by start time.
+pub fn load_channel_history(
+    conn: &mut SqliteConnection,
+    channel: &str,
+    date: NaiveDate,
+) -> QueryResult<Vec<GuideHistoryEntry>> {
+    let channel_ids = resolve_channel_ids(conn, channel.trim())?;
+    if channel_ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let day_start = date.format("%Y-%m-%dT00:00:00Z").to_string();
+    let day_end = (date + Duration::days(1))
+        .format("%Y-%m-%dT00:00:00Z")
+        .to_string();
+
+    let current: Vec<HistoryRow> = programs::table
+        .inner_join(xmltv_channels::table)
+        .filter(xmltv_channels::channel_id.eq_any(&channel_ids))
+        .filter(programs::start_time.lt(&day_end))
+        .filter(programs::end_time.gt(&day_start))
+        .select((
+            xmltv_channels::source_id,
+            xmltv_channels::channel_id,
+            xmltv_channels::display_name,
+            programs::title,
+            programs::sub_title,
+            programs::description,
+            programs::start_time,
+            programs::end_time,
+            programs::category,
+            programs::episode_info,
+        ))
+        .load(conn)?;
+
+    let archived: Vec<HistoryRow> = program_history::table
+        .filter(program_history::channel_id.eq_any(&channel_ids))
+        .filter(program_history::start_time.lt(&day_end))
+        .filter(program_history::end_time.gt(&day_start))
+        .select((
+            program_history::source_id,
+            program_history::channel_id,
+            program_history::channel_name,
+            program_history::title,
+            program_history::sub_title,
+            program_history::description,
+            program_history::start_time,
+            program_history::end_time,
+            program_history::category,
+            program_history::episode_info,
+        ))
+        .load(conn)?;
+
+    let in_guide: HashSet<(i32, String, String)> = current
+        .iter()
+        .map(|row| (row.0, row.1.clone(), row.6.clone()))
+        .collect();
+    let mut entries: Vec<GuideHistoryEntry> = current
+        .into_iter()
+        .map(|row| GuideHistoryEntry::from_row(row, false))
+        .collect();
+    entries.extend(
+        archived
+            .into_iter()
+            .filter(|row| !in_guide.contains(&(row.0, row.1.clone(), row.6.clone())))
+            .map(|row| GuideHistoryEntry::from_row(row, true)),
+    );
+    entries.sort_by(|a, b| {
+        a.start_time
+            .cmp(&b.start_time)
+            .then(a.source_id.cmp(&b.source_id))
+    });
+    Ok(entries)
+}
+
+/// Parse a history day (YYYY-MM-DD), defaulting to today in UTC
+pub fn parse_history_date(date: Option<&str>) -> Result<NaiveDate, String> {
+    match date.map(str::trim).filter(|d| !d.is_empty()) {
+        Some(date) => NaiveDate::parse_from_str(date, "%Y-%m-%d")
+            .map_err(|_| format!("Invalid date '{}', expected YYYY-MM-DD", date)),
+        None => Ok(Utc::now().date_naive()),
+    }
+}
+
+/// XMLTV channel ids matching `channel` by id, or else by name
+fn resolve_channel_ids(conn: &mut SqliteConnection, channel: &str) -> QueryResult<Vec<String>> {
+    if channel.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let by_id: i64 = xmltv_channels::table
+        .filter(xmltv_channels::channel_id.eq(channel))
+        .count()
+        .get_result(conn)?;
+    let archived_by_id: i64 = program_history::table
+        .filter(program_history::channel_id.eq(channel))
+        .count()
+        .get_result(conn)?;
+    if by_id + archived_by_id > 0 {
+        return Ok(vec![channel.to_string()]);
+    }
+
+    let mut names: Vec<(String, String)> = xmltv_channels::table
+        .select((xmltv_channels::channel_id, xmltv_channels::display_name))
+        .load(conn)?;
+    names.extend(
+        program_history::table
+            .select((program_history::channel_id, program_history::channel_name))
+            .distinct()
+            .load::<(String, String)>(conn)?,
+    );
+
+    let mut ids: Vec<String> = names
+        .into_iter()
+        .filter(|(_, name)| name.eq_ignore_ascii_case(channel))
+        .map(|(id, _)| id)
+        .collect();
+    ids.sort();
+    ids.dedup();
+    Ok(ids)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use diesel::connection::SimpleConnection;
+
+    fn setup_db() -> SqliteConnection {
+        let mut conn = crate::db::test_connection();
+        conn.batch_execute("PRAGMA foreign_keys = ON;").unwrap();
+        conn.batch_execute(
+            "INSERT INTO xmltv_sources (id, name, url, format) VALUES (1, 'Guide', 'http://example.com/epg.xml', 'xml');
+             INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (10, 1, 'bbc1.uk', 'BBC One');
+             INSERT INTO programs (xmltv_channel_id, title, start_time, end_time) VALUES
+                (10, 'Breakfast', '2026-03-01T06:00:00Z', '2026-03-01T09:00:00Z'),
+                (10, 'Homes Under the Hammer', '2026-03-01T09:00:00Z', '2026-03-01T10:00:00Z'),
+                (10, 'Bargain Hunt', '2026-03-01T12:00:00Z', '2026-03-01T13:00:00Z');",
+        )
+        .unwrap();
+        conn
+    }
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_aired_programs_survive_refresh() {
+        let mut conn = setup_db();
+        let now = at("2026-03-01T09:30:00Z");
+
+        let archived = archive_aired_programs(&mut conn, ArchiveScope::Source(1), now).unwrap();
+        assert_eq!(archived, 2);
+        // Archiving again replaces instead of duplicating
+        archive_aired_programs(&mut conn, ArchiveScope::All, now).unwrap();
+
+        // A refresh recreates the channel without the past programs
+        conn.batch_execute(
+            "DELETE FROM xmltv_channels WHERE id = 10;
+             INSERT INTO xmltv_channels (id, source_id, channel_id, display_name) VALUES (11, 1, 'bbc1.uk', 'BBC One');
+             INSERT INTO programs (xmltv_channel_id, title, start_time, end_time) VALUES
+                (11, 'Homes Under the Hammer', '2026-03-01T09:00:00Z', '2026-03-01T10:00:00Z'),
+                (11, 'Bargain Hunt', '2026-03-01T12:00:00Z', '2026-03-01T13:00:00Z');",
+        )
+        .unwrap();
+
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let day = load_channel_history(&mut conn, "bbc one", date).unwrap();
+        let titles: Vec<(&str, bool)> =
+            day.iter().map(|p| (p.title.as_str(), p.archived)).collect();
+        assert_eq!(
+            titles,
+            vec![
+                ("Breakfast", true),
+                ("Homes Under the Hammer", false),
+                ("Bargain Hunt", false)
+            ]
+        );
+
+        assert_eq!(
+            load_channel_history(&mut conn, "bbc1.uk", date)
+                .unwrap()
+                .len(),
+            3
+        );
+        assert!(load_channel_history(&mut conn, "ITV1", date)
+            .unwrap()
+            .is_empty());
+        let next_day = NaiveDate::from_ymd_opt(2026, 3, 2).unwrap();
+        assert!(load_channel_history(&mut conn, "bbc1.uk", next_day)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_history_window_and_disabling() {
+        let mut conn = setup_db();
+        archive_aired_programs(&mut conn, ArchiveScope::All, at("2026-03-01T23:00:00Z")).unwrap();
+
+        // Ten days later with a seven day window everything has expired
+        let later = at("2026-03-11T00:00:00Z");
+        assert_eq!(prune_program_history(&mut conn, 14, later).unwrap(), 0);
+        assert_eq!(prune_program_history(&mut conn, 7, later).unwrap(), 3);
+
+        set_epg_history_days(&mut conn, 0).unwrap();
+        assert_eq!(
+            archive_aired_programs(&mut conn, ArchiveScope::Channel(10), later).unwrap(),
+            0
+        );
+        assert!(set_epg_history_days(&mut conn, MAX_EPG_HISTORY_DAYS + 1).is_err());
+    }
+}
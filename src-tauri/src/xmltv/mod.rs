@@ -3,10 +3,13 @@
 //! This module provides functionality to download, decompress, and parse XMLTV
 //! format EPG (Electronic Program Guide) data.
 
+pub mod channel_feed;
 pub mod fetcher;
+pub mod history;
 pub mod parser;
+pub mod refresh;
 pub mod types;
 
-pub use fetcher::fetch_xmltv;
+pub use fetcher::{fetch_xmltv, load_xmltv_source};
 pub use parser::parse_xmltv_data;
-pub use types::{ParsedChannel, ParsedProgram, XmltvError};
+pub use types::{ParsedChannel, ParsedProgram, ProgramCredit, ProgramTranslation, XmltvError};
@@ -8,7 +8,7 @@ use chrono::{DateTime, FixedOffset, NaiveDateTime, TimeZone, Utc};
 use quick_xml::events::{BytesStart, Event};
 use quick_xml::Reader;
 
-use super::types::{ParsedChannel, ParsedProgram, XmltvError};
+use super::types::{ParsedChannel, ParsedProgram, ProgramCredit, ProgramTranslation, XmltvError};
 
 /// Parse XMLTV data from bytes
 ///
@@ -130,20 +130,34 @@ fn parse_program(
     let mut description: Option<String> = None;
     let mut category: Option<String> = None;
     let mut episode_info: Option<String> = None;
+    let mut sub_title: Option<String> = None;
+    let mut icon: Option<String> = None;
+    let mut image: Option<String> = None;
+    let mut episode_num_xmltv_ns: Option<String> = None;
+    let mut episode_num_onscreen: Option<String> = None;
+    let mut rating: Option<String> = None;
+    let mut rating_system: Option<String> = None;
+    let mut star_rating: Option<String> = None;
+    let mut credits: Vec<ProgramCredit> = Vec::new();
+    let mut texts: Vec<LangText> = Vec::new();
     let mut buf = Vec::new();
 
     loop {
         match reader.read_event_into(&mut buf) {
             Ok(Event::Start(e)) => match e.name().as_ref() {
                 b"title" => {
+                    let text = read_element_text(reader)?;
                     if title.is_none() {
-                        title = Some(read_element_text(reader)?);
+                        title = Some(text.clone());
                     }
+                    texts.push((TextField::Title, get_attribute(&e, b"lang"), text));
                 }
                 b"desc" => {
+                    let text = read_element_text(reader)?;
                     if description.is_none() {
-                        description = Some(read_element_text(reader)?);
+                        description = Some(text.clone());
                     }
+                    texts.push((TextField::Description, get_attribute(&e, b"lang"), text));
                 }
                 b"category" => {
                     if category.is_none() {
@@ -151,12 +165,64 @@ fn parse_program(
                     }
                 }
                 b"episode-num" => {
+                    let system = get_attribute(&e, b"system");
+                    let value = read_element_text(reader)?.trim().to_string();
+                    match system.as_deref() {
+                        Some("xmltv_ns") if episode_num_xmltv_ns.is_none() => {
+                            episode_num_xmltv_ns = Some(value.clone());
+                        }
+                        Some("onscreen") if episode_num_onscreen.is_none() => {
+                            episode_num_onscreen = Some(value.clone());
+                        }
+                        _ => {}
+                    }
                     if episode_info.is_none() {
-                        episode_info = Some(read_element_text(reader)?);
+                        episode_info = Some(value);
+                    }
+                }
+                b"sub-title" => {
+                    let text = read_element_text(reader)?;
+                    if sub_title.is_none() {
+                        sub_title = Some(text.clone());
                     }
+                    texts.push((TextField::SubTitle, get_attribute(&e, b"lang"), text));
+                }
+                b"icon" => {
+                    if icon.is_none() {
+                        icon = get_attribute(&e, b"src");
+                    }
+                    reader
+                        .read_to_end_into(e.name(), &mut Vec::new())
+                        .map_err(|e| XmltvError::ParseError(e.to_string()))?;
+                }
+                b"image" => {
+                    let url = read_direct_text(reader, b"image")?;
+                    if image.is_none() && !url.is_empty() {
+                        image = Some(url);
+                    }
+                }
+                b"rating" => {
+                    let system = get_attribute(&e, b"system");
+                    let value = read_value_element(reader, b"rating")?;
+                    if rating.is_none() && value.is_some() {
+                        rating = value;
+                        rating_system = system;
+                    }
+                }
+                b"star-rating" => {
+                    let value = read_value_element(reader, b"star-rating")?;
+                    if star_rating.is_none() {
+                        star_rating = value;
+                    }
+                }
+                b"credits" => {
+                    credits.extend(read_credits(reader)?);
                 }
                 _ => {}
             },
+            Ok(Event::Empty(e)) if e.name().as_ref() == b"icon" && icon.is_none() => {
+                icon = get_attribute(&e, b"src");
+            }
             Ok(Event::End(e)) if e.name().as_ref() == b"programme" => break,
             Ok(Event::Eof) => {
                 return Err(XmltvError::ParseError(
@@ -180,9 +246,194 @@ fn parse_program(
         end_time,
         category,
         episode_info,
+        sub_title,
+        icon: icon.or(image).filter(|url| !url.trim().is_empty()),
+        episode_num_xmltv_ns,
+        episode_num_onscreen,
+        rating,
+        rating_system,
+        star_rating,
+        credits,
+        translations: collect_translations(&texts),
     })
 }
 
+/// Program text element that can be given in several languages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TextField {
+    Title,
+    SubTitle,
+    Description,
+}
+
+/// A text element with its `lang` attribute
+type LangText = (TextField, Option<String>, String);
+
+/// Group texts by language when a programme has more than one language
+///
+/// Languages are compared ignoring case and keep their first spelling and
+/// document order. Texts without a `lang` attribute are left out.
+fn collect_translations(texts: &[LangText]) -> Vec<ProgramTranslation> {
+    let mut translations: Vec<ProgramTranslation> = Vec::new();
+    for (field, lang, text) in texts {
+        let Some(lang) = lang.as_deref().map(str::trim).filter(|l| !l.is_empty()) else {
+            continue;
+        };
+        let index = match translations
+            .iter()
+            .position(|t| t.lang.eq_ignore_ascii_case(lang))
+        {
+            Some(index) => index,
+            None => {
+                translations.push(ProgramTranslation {
+                    lang: lang.to_string(),
+                    ..Default::default()
+                });
+                translations.len() - 1
+            }
+        };
+        let slot = match field {
+            TextField::Title => &mut translations[index].title,
+            TextField::SubTitle => &mut translations[index].sub_title,
+            TextField::Description => &mut translations[index].description,
+        };
+        if slot.is_none() {
+            *slot = Some(text.clone());
+        }
+    }
+
+    if translations.len() < 2 {
+        translations.clear();
+    }
+    translations
+}
+
+/// Read the cast and crew of a <credits> element
+fn read_credits(reader: &mut Reader<&[u8]>) -> Result<Vec<ProgramCredit>, XmltvError> {
+    let mut credits = Vec::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) => {
+                let role = String::from_utf8_lossy(e.name().as_ref()).into_owned();
+                let character = get_attribute(&e, b"role").filter(|c| !c.trim().is_empty());
+                let name = read_direct_text(reader, role.as_bytes())?;
+                if !name.is_empty() {
+                    credits.push(ProgramCredit {
+                        role,
+                        name,
+                        character,
+                    });
+                }
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == b"credits" => break,
+            Ok(Event::Eof) => {
+                return Err(XmltvError::ParseError(
+                    "Unexpected EOF while parsing credits".into(),
+                ))
+            }
+            Err(e) => return Err(XmltvError::ParseError(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(credits)
+}
+
+/// Read the first <value> of a <rating> or <star-rating> element
+///
+/// Consumes the element up to its end tag, skipping any icons.
+fn read_value_element(
+    reader: &mut Reader<&[u8]>,
+    end: &[u8],
+) -> Result<Option<String>, XmltvError> {
+    let mut value: Option<String> = None;
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Start(e)) if e.name().as_ref() == b"value" => {
+                let text = read_element_text(reader)?.trim().to_string();
+                if value.is_none() && !text.is_empty() {
+                    value = Some(text);
+                }
+            }
+            Ok(Event::Start(e)) => {
+                reader
+                    .read_to_end_into(e.name(), &mut Vec::new())
+                    .map_err(|e| XmltvError::ParseError(e.to_string()))?;
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == end => break,
+            Ok(Event::Eof) => {
+                return Err(XmltvError::ParseError(
+                    "Unexpected EOF while reading rating".into(),
+                ))
+            }
+            Err(e) => return Err(XmltvError::ParseError(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(value)
+}
+
+/// Read the text directly inside the current element, skipping child elements
+/// (e.g. an actor's <image>), up to its end tag
+fn read_direct_text(reader: &mut Reader<&[u8]>, end: &[u8]) -> Result<String, XmltvError> {
+    let mut text = String::new();
+    let mut buf = Vec::new();
+
+    loop {
+        match reader.read_event_into(&mut buf) {
+            Ok(Event::Text(e)) => {
+                text.push_str(
+                    &e.unescape()
+                        .map_err(|e| XmltvError::ParseError(e.to_string()))?,
+                );
+            }
+            Ok(Event::Start(e)) => {
+                reader
+                    .read_to_end_into(e.name(), &mut Vec::new())
+                    .map_err(|e| XmltvError::ParseError(e.to_string()))?;
+            }
+            Ok(Event::End(e)) if e.name().as_ref() == end => break,
+            Ok(Event::Eof) => {
+                return Err(XmltvError::ParseError(
+                    "Unexpected EOF while reading element text".into(),
+                ))
+            }
+            Err(e) => return Err(XmltvError::ParseError(e.to_string())),
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(text.trim().to_string())
+}
+
+/// On-screen form ("S01E05") of a zero-based `xmltv_ns` episode number
+///
+/// `xmltv_ns` is "season.episode.part", each optionally "n/total" and any
+/// of them may be empty; "0.4.0/1" is season 1, episode 5.
+pub fn onscreen_episode_number(xmltv_ns: &str) -> Option<String> {
+    let mut parts = xmltv_ns.split('.');
+    let number = |part: Option<&str>| -> Option<u32> {
+        part?.split('/').next()?.trim().parse::<u32>().ok().map(|n| n + 1)
+    };
+    let season = number(parts.next());
+    let episode = number(parts.next());
+
+    match (season, episode) {
+        (Some(season), Some(episode)) => Some(format!("S{:02}E{:02}", season, episode)),
+        (Some(season), None) => Some(format!("S{:02}", season)),
+        (None, Some(episode)) => Some(format!("E{:02}", episode)),
+        (None, None) => None,
+    }
+}
+
 /// Parse XMLTV timestamp format to ISO 8601 UTC string
 ///
 /// XMLTV format: YYYYMMDDhhmmss ±HHMM (e.g., "20260119120000 +0000")
@@ -438,6 +689,114 @@ mod tests {
         assert_eq!(programs.len(), 1);
         assert_eq!(programs[0].channel_id, "duplicate.1");
     }
+
+    #[test]
+    fn test_parse_extended_program_metadata() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<tv>
+  <programme start="20260119200000 +0000" stop="20260119210000 +0000" channel="drama.1">
+    <title>The Detective</title>
+    <sub-title>The Last Witness</sub-title>
+    <credits>
+      <director>Jane Roe</director>
+      <actor role="DI Hart">John Smith<image type="person">https://example.com/js.jpg</image></actor>
+      <actor>Ann Lee</actor>
+    </credits>
+    <image type="backdrop">https://example.com/backdrop.jpg</image>
+    <icon src="https://example.com/poster.jpg"/>
+    <episode-num system="dd_progid">EP012345.0005</episode-num>
+    <episode-num system="xmltv_ns">1.4.0/1</episode-num>
+    <episode-num system="onscreen">S2 E5</episode-num>
+    <rating system="VCHIP">
+      <value>TV-14</value>
+      <icon src="https://example.com/tv14.png"/>
+    </rating>
+    <star-rating><value>7.5/10</value></star-rating>
+  </programme>
+</tv>"#;
+
+        let (_, programs) = parse_xmltv_data(xml.as_bytes()).unwrap();
+        let program = &programs[0];
+
+        assert_eq!(program.sub_title.as_deref(), Some("The Last Witness"));
+        assert_eq!(program.icon.as_deref(), Some("https://example.com/poster.jpg"));
+        assert_eq!(program.episode_info.as_deref(), Some("EP012345.0005"));
+        assert_eq!(program.episode_num_xmltv_ns.as_deref(), Some("1.4.0/1"));
+        assert_eq!(program.episode_num_onscreen.as_deref(), Some("S2 E5"));
+        assert_eq!(program.rating.as_deref(), Some("TV-14"));
+        assert_eq!(program.rating_system.as_deref(), Some("VCHIP"));
+        assert_eq!(program.star_rating.as_deref(), Some("7.5/10"));
+        assert_eq!(
+            program.credits,
+            vec![
+                ProgramCredit {
+                    role: "director".into(),
+                    name: "Jane Roe".into(),
+                    character: None,
+                },
+                ProgramCredit {
+                    role: "actor".into(),
+                    name: "John Smith".into(),
+                    character: Some("DI Hart".into()),
+                },
+                ProgramCredit {
+                    role: "actor".into(),
+                    name: "Ann Lee".into(),
+                    character: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_onscreen_episode_number() {
+        assert_eq!(onscreen_episode_number("0.4.0/1").as_deref(), Some("S01E05"));
+        assert_eq!(onscreen_episode_number("2/5.11/22.").as_deref(), Some("S03E12"));
+        assert_eq!(onscreen_episode_number(".7.").as_deref(), Some("E08"));
+        assert_eq!(onscreen_episode_number("1..").as_deref(), Some("S02"));
+        assert_eq!(onscreen_episode_number("..0/2"), None);
+    }
+
+    #[test]
+    fn test_parse_program_translations() {
+        let xml = br#"<?xml version="1.0" encoding="UTF-8"?>
+<tv>
+  <programme start="20260301200000 +0000" stop="20260301210000 +0000" channel="arte.de">
+    <title lang="de">Die Reise</title>
+    <title lang="FR">Le Voyage</title>
+    <desc lang="de">Eine Dokumentation.</desc>
+    <desc lang="fr">Un documentaire.</desc>
+    <sub-title lang="fr">Partie 1</sub-title>
+  </programme>
+  <programme start="20260301210000 +0000" stop="20260301220000 +0000" channel="arte.de">
+    <title lang="de">Nachrichten</title>
+    <desc>Ohne Sprache</desc>
+  </programme>
+</tv>"#;
+
+        let (_, programs) = parse_xmltv_data(xml).unwrap();
+        assert_eq!(programs[0].title, "Die Reise");
+        assert_eq!(programs[0].sub_title.as_deref(), Some("Partie 1"));
+        assert_eq!(
+            programs[0].translations,
+            vec![
+                ProgramTranslation {
+                    lang: "de".to_string(),
+                    title: Some("Die Reise".to_string()),
+                    sub_title: None,
+                    description: Some("Eine Dokumentation.".to_string()),
+                },
+                ProgramTranslation {
+                    lang: "FR".to_string(),
+                    title: Some("Le Voyage".to_string()),
+                    sub_title: Some("Partie 1".to_string()),
+                    description: Some("Un documentaire.".to_string()),
+                },
+            ]
+        );
+        // A single language needs no translations
+        assert!(programs[1].translations.is_empty());
+    }
 }
 
 /// Detect if data is gzip compressed by checking magic bytes
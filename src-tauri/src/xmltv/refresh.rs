@@ -0,0 +1,660 @@
+//! XMLTV source refresh pipeline
+//!
+//! Shared by the refresh commands and the scheduler. Sources are downloaded
+//! and parsed concurrently, at most `REFRESH_CONCURRENCY` at a time, while
+//! database writes are serialized because SQLite allows a single writer.
+//! Low resource mode still limits refreshes to one source at a time.
+//!
+//! Each stage of each source is reported through an optional progress
+//! callback; the commands forward these as `epg-refresh-progress` events so
+//! the UI can show per-source progress.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use diesel::prelude::*;
+use serde::Serialize;
+use tokio::sync::{Mutex, Semaphore};
+
+use super::fetcher::{load_xmltv_source, SourceAuth};
+use super::history::{self, ArchiveScope};
+use super::parser::parse_xmltv_data;
+use super::types::{ParsedChannel, ParsedProgram, XmltvError};
+use crate::commands::epg::{preserve_channel_data, restore_channel_data};
+use crate::credentials::CredentialManager;
+use crate::db::schema::{programs, xmltv_channels, xmltv_sources};
+use crate::db::{DbPool, NewProgram, NewXmltvChannel, XmltvChannel, XmltvSource};
+use crate::low_resource;
+
+/// Tauri event carrying `EpgRefreshProgress` updates
+pub const EPG_REFRESH_PROGRESS_EVENT: &str = "epg-refresh-progress";
+
+/// Sources downloaded and parsed at the same time
+const REFRESH_CONCURRENCY: usize = 3;
+
+/// Stage of a source refresh
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RefreshStage {
+    Queued,
+    Fetching,
+    Parsing,
+    Storing,
+    Completed,
+    Failed,
+}
+
+/// Progress update for one source
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EpgRefreshProgress {
+    pub source_id: i32,
+    pub source_name: String,
+    pub stage: RefreshStage,
+    /// Set once the source is stored
+    pub channel_count: Option<usize>,
+    pub program_count: Option<usize>,
+    /// Set when the stage is `Failed`
+    pub error: Option<String>,
+    /// Sources finished (completed or failed) in this refresh run
+    pub finished: usize,
+    pub total: usize,
+}
+
+/// Receives progress updates; called from the refresh tasks
+pub type ProgressCallback = Arc<dyn Fn(EpgRefreshProgress) + Send + Sync>;
+
+/// Why a source refresh failed, and in which stage
+#[derive(Debug)]
+pub struct SourceRefreshError {
+    pub stage: RefreshStage,
+    pub error: XmltvError,
+}
+
+/// Result of refreshing one source
+#[derive(Debug)]
+pub struct SourceRefreshResult {
+    pub source_id: i32,
+    pub source_name: String,
+    /// Channel and program counts on success
+    pub outcome: Result<(usize, usize), SourceRefreshError>,
+}
+
+struct ProgressReporter {
+    callback: Option<ProgressCallback>,
+    finished: AtomicUsize,
+    total: usize,
+}
+
+impl ProgressReporter {
+    fn stage(&self, source: &XmltvSource, stage: RefreshStage) {
+        self.send(source, stage, None, None);
+    }
+
+    fn finish(&self, source: &XmltvSource, outcome: &Result<(usize, usize), SourceRefreshError>) {
+        self.finished.fetch_add(1, Ordering::SeqCst);
+        match outcome {
+            Ok(counts) => self.send(source, RefreshStage::Completed, Some(*counts), None),
+            Err(e) => self.send(
+                source,
+                RefreshStage::Failed,
+                None,
+                Some(e.error.to_string()),
+            ),
+        }
+    }
+
+    fn send(
+        &self,
+        source: &XmltvSource,
+        stage: RefreshStage,
+        counts: Option<(usize, usize)>,
+        error: Option<String>,
+    ) {
+        if let Some(ref callback) = self.callback {
+            callback(EpgRefreshProgress {
+                source_id: source.id.unwrap_or(0),
+                source_name: source.name.clone(),
+                stage,
+                channel_count: counts.map(|(channels, _)| channels),
+                program_count: counts.map(|(_, programs)| programs),
+                error,
+                finished: self.finished.load(Ordering::SeqCst),
+                total: self.total,
+            });
+        }
+    }
+}
+
+/// Refresh sources concurrently
+///
+/// Results are returned in the order of `sources`. A failing source leaves
+/// its previous data intact and does not affect the others. `app_data_dir`
+/// holds the credential store of sources that need authentication.
+pub async fn refresh_sources(
+    pool: DbPool,
+    sources: Vec<XmltvSource>,
+    app_data_dir: Option<PathBuf>,
+    on_progress: Option<ProgressCallback>,
+) -> Vec<SourceRefreshResult> {
+    // Queued Plex guide reloads wait until every running refresh is done
+    let _refreshing = crate::plex::RefreshGuard::new();
+    let reporter = Arc::new(ProgressReporter {
+        callback: on_progress,
+        finished: AtomicUsize::new(0),
+        total: sources.len(),
+    });
+    let semaphore = Arc::new(Semaphore::new(REFRESH_CONCURRENCY));
+    let write_lock = Arc::new(Mutex::new(()));
+    let credentials = Arc::new(app_data_dir.map(CredentialManager::new));
+
+    for source in &sources {
+        reporter.stage(source, RefreshStage::Queued);
+    }
+
+    let handles: Vec<_> = sources
+        .into_iter()
+        .map(|source| {
+            let pool = pool.clone();
+            let reporter = reporter.clone();
+            let semaphore = semaphore.clone();
+            let write_lock = write_lock.clone();
+            let credentials = credentials.clone();
+            let task_source = source.clone();
+            let handle = tokio::spawn(async move {
+                let _permit = semaphore.acquire_owned().await;
+                // Low resource mode: one source refresh at a time
+                let _refresh_slot = low_resource::acquire_epg_refresh_slot().await;
+
+                let outcome = refresh_source(
+                    &pool,
+                    &task_source,
+                    credentials.as_ref().as_ref(),
+                    &reporter,
+                    &write_lock,
+                )
+                .await;
+                reporter.finish(&task_source, &outcome);
+                outcome
+            });
+            (source, handle)
+        })
+        .collect();
+
+    let mut results = Vec::with_capacity(handles.len());
+    for (source, handle) in handles {
+        let outcome = handle.await.unwrap_or_else(|e| {
+            Err(SourceRefreshError {
+                stage: RefreshStage::Failed,
+                error: XmltvError::ParseError(format!("Refresh task failed: {}", e)),
+            })
+        });
+        results.push(SourceRefreshResult {
+            source_id: source.id.unwrap_or(0),
+            source_name: source.name,
+            outcome,
+        });
+    }
+    results
+}
+
+async fn refresh_source(
+    pool: &DbPool,
+    source: &XmltvSource,
+    credentials: Option<&CredentialManager>,
+    reporter: &ProgressReporter,
+    write_lock: &Mutex<()>,
+) -> Result<(usize, usize), SourceRefreshError> {
+    let fail = |stage| move |error| SourceRefreshError { stage, error };
+    let source_id = source.id.unwrap_or(0);
+
+    reporter.stage(source, RefreshStage::Fetching);
+    let auth = SourceAuth::for_source(source, credentials).map_err(fail(RefreshStage::Fetching))?;
+    let data = load_xmltv_source(&source.url, &source.format, &auth)
+        .await
+        .map_err(fail(RefreshStage::Fetching))?;
+
+    reporter.stage(source, RefreshStage::Parsing);
+    let (channels, parsed_programs) = tokio::task::spawn_blocking(move || parse_xmltv_data(&data))
+        .await
+        .map_err(|e| XmltvError::ParseError(e.to_string()))
+        .and_then(|parsed| parsed)
+        .map_err(fail(RefreshStage::Parsing))?;
+
+    reporter.stage(source, RefreshStage::Storing);
+    let _writer = write_lock.lock().await;
+    let pool = pool.clone();
+    let counts = (channels.len(), parsed_programs.len());
+    tokio::task::spawn_blocking(move || {
+        let mut conn = pool.get().map_err(|e| {
+            XmltvError::DatabaseError(diesel::result::Error::QueryBuilderError(
+                format!("Failed to get database connection: {}", e).into(),
+            ))
+        })?;
+        store_source_data(&mut conn, source_id, &channels, &parsed_programs)
+            .map_err(XmltvError::from)
+    })
+    .await
+    .map_err(|e| XmltvError::ParseError(e.to_string()))
+    .and_then(|stored| stored)
+    .map_err(fail(RefreshStage::Storing))?;
+
+    Ok(counts)
+}
+
+/// Replace a source's channels and programs with freshly parsed data
+///
+/// Runs in one transaction: if anything fails the previous data remains.
+/// Manual mappings, channel settings, channel policy actions, failover
+/// history, the published lineup and device profile lineups are carried over
+/// by channel ID.
+pub fn store_source_data(
+    conn: &mut SqliteConnection,
+    source_id: i32,
+    channels: &[ParsedChannel],
+    parsed_programs: &[ParsedProgram],
+) -> QueryResult<()> {
+    let batch_size = low_resource::program_batch_size();
+
+    conn.transaction(|conn| {
+        // Preserve manual mappings and channel settings before deletion
+        let preserved = preserve_channel_data(conn, source_id)?;
+
+        // Keep what already aired in the guide history
+        history::archive_aired_programs(conn, ArchiveScope::Source(source_id), chrono::Utc::now())?;
+
+        // Clear existing data for this source (cascade deletes mappings)
+        diesel::delete(xmltv_channels::table.filter(xmltv_channels::source_id.eq(source_id)))
+            .execute(conn)?;
+
+        // Insert new channels and build mapping from channel_id to db id
+        let mut channel_id_map: HashMap<String, i32> = HashMap::new();
+        for parsed_channel in channels {
+            let new_channel = NewXmltvChannel::new(
+                source_id,
+                &parsed_channel.channel_id,
+                &parsed_channel.display_name,
+                parsed_channel.icon.clone(),
+            );
+
+            match diesel::insert_into(xmltv_channels::table)
+                .values(&new_channel)
+                .get_result::<XmltvChannel>(conn)
+            {
+                Ok(inserted) => {
+                    if let Some(id) = inserted.id {
+                        channel_id_map.insert(parsed_channel.channel_id.clone(), id);
+                    }
+                }
+                Err(e) => {
+                    // Don't fail the whole source for one channel
+                    tracing::warn!(
+                        "Failed to insert channel {} for source {}: {}",
+                        parsed_channel.channel_id,
+                        source_id,
+                        e
+                    );
+                }
+            }
+        }
+
+        // Insert programs in batches for performance
+        let mut programs_to_insert: Vec<NewProgram> = Vec::with_capacity(batch_size);
+        for parsed_program in parsed_programs {
+            let Some(&channel_db_id) = channel_id_map.get(&parsed_program.channel_id) else {
+                continue;
+            };
+
+            programs_to_insert.push(parsed_program.to_new_program(channel_db_id));
+
+            if programs_to_insert.len() >= batch_size {
+                diesel::insert_into(programs::table)
+                    .values(&programs_to_insert)
+                    .execute(conn)?;
+                programs_to_insert.clear();
+            }
+        }
+        if !programs_to_insert.is_empty() {
+            diesel::insert_into(programs::table)
+                .values(&programs_to_insert)
+                .execute(conn)?;
+        }
+
+        // Restore manual mappings, settings, policy actions, failovers and lineups
+        restore_channel_data(conn, &preserved, &channel_id_map)?;
+
+        // Update last_refresh timestamp on the source
+        let now = chrono::Utc::now().format("%Y-%m-%d %H:%M:%S").to_string();
+        diesel::update(xmltv_sources::table.filter(xmltv_sources::id.eq(source_id)))
+            .set(xmltv_sources::last_refresh.eq(&now))
+            .execute(conn)?;
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::schema::{channel_mappings, xmltv_channel_settings};
+    use crate::db::NewXmltvSource;
+
+    fn parsed_channel(id: &str) -> ParsedChannel {
+        ParsedChannel {
+            channel_id: id.to_string(),
+            display_name: id.to_string(),
+            icon: None,
+        }
+    }
+
+    fn parsed_program(channel_id: &str, start: &str) -> ParsedProgram {
+        ParsedProgram {
+            channel_id: channel_id.to_string(),
+            title: format!("{} show", channel_id),
+            start_time: start.to_string(),
+            end_time: start.replace("T00", "T01"),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_store_source_data_replaces_and_preserves_settings() {
+        let mut conn = crate::db::test_connection();
+
+        let source: XmltvSource = diesel::insert_into(xmltv_sources::table)
+            .values(&NewXmltvSource::new(
+                "Main",
+                "https://example.com/epg.xml",
+                "xml",
+            ))
+            .get_result(&mut conn)
+            .unwrap();
+        let source_id = source.id.unwrap();
+
+        store_source_data(
+            &mut conn,
+            source_id,
+            &[parsed_channel("a.us"), parsed_channel("b.us")],
+            &[
+                parsed_program("a.us", "2026-01-01T00:00:00Z"),
+                parsed_program("b.us", "2026-01-01T00:00:00Z"),
+                parsed_program("unknown", "2026-01-01T00:00:00Z"),
+            ],
+        )
+        .unwrap();
+        assert_eq!(
+            programs::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            2
+        );
+
+        // Enable channel a.us, then refresh with new data
+        let a_id: Option<i32> = xmltv_channels::table
+            .filter(xmltv_channels::channel_id.eq("a.us"))
+            .select(xmltv_channels::id)
+            .first(&mut conn)
+            .unwrap();
+        diesel::insert_into(xmltv_channel_settings::table)
+            .values((
+                xmltv_channel_settings::xmltv_channel_id.eq(a_id.unwrap()),
+                xmltv_channel_settings::is_enabled.eq(1),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        store_source_data(
+            &mut conn,
+            source_id,
+            &[parsed_channel("a.us")],
+            &[parsed_program("a.us", "2026-01-02T00:00:00Z")],
+        )
+        .unwrap();
+
+        let channel_ids: Vec<String> = xmltv_channels::table
+            .select(xmltv_channels::channel_id)
+            .load(&mut conn)
+            .unwrap();
+        assert_eq!(channel_ids, vec!["a.us"]);
+        let enabled: i64 = xmltv_channel_settings::table
+            .inner_join(xmltv_channels::table)
+            .filter(xmltv_channels::channel_id.eq("a.us"))
+            .filter(xmltv_channel_settings::is_enabled.eq(1))
+            .count()
+            .get_result(&mut conn)
+            .unwrap();
+        assert_eq!(enabled, 1);
+        assert_eq!(
+            programs::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            1
+        );
+        assert_eq!(
+            channel_mappings::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            0
+        );
+
+        let refreshed: XmltvSource = xmltv_sources::table
+            .filter(xmltv_sources::id.eq(source_id))
+            .first(&mut conn)
+            .unwrap();
+        assert!(refreshed.last_refresh.is_some());
+    }
+
+    /// Connection enforcing foreign keys like the pool, with one source
+    fn source_connection() -> (SqliteConnection, i32) {
+        let mut conn = crate::db::test_connection();
+        diesel::sql_query("PRAGMA foreign_keys = ON")
+            .execute(&mut conn)
+            .unwrap();
+        let source: XmltvSource = diesel::insert_into(xmltv_sources::table)
+            .values(&NewXmltvSource::new(
+                "Main",
+                "https://example.com/epg.xml",
+                "xml",
+            ))
+            .get_result(&mut conn)
+            .unwrap();
+        (conn, source.id.unwrap())
+    }
+
+    fn channel_db_id(conn: &mut SqliteConnection, channel_id: &str) -> i32 {
+        xmltv_channels::table
+            .filter(xmltv_channels::channel_id.eq(channel_id))
+            .select(xmltv_channels::id.assume_not_null())
+            .first(conn)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_store_source_data_keeps_device_profile_lineup() {
+        use crate::server::device_profiles;
+
+        let (mut conn, source_id) = source_connection();
+        let channels = [parsed_channel("a.us"), parsed_channel("b.us")];
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let profile = device_profiles::create_profile(&mut conn, "Kids", None, None, None).unwrap();
+        let a_id = channel_db_id(&mut conn, "a.us");
+        device_profiles::set_profile_channels(&mut conn, profile.id, &[a_id]).unwrap();
+
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let new_a_id = channel_db_id(&mut conn, "a.us");
+        assert_ne!(new_a_id, a_id);
+        let profile = device_profiles::get_profile(&mut conn, profile.id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(profile.channel_ids, vec![new_a_id]);
+    }
+
+    #[test]
+    fn test_store_source_data_keeps_published_lineup() {
+        use crate::db::schema::lineup_published;
+        use crate::lineup_staging;
+
+        let (mut conn, source_id) = source_connection();
+        let channels = [parsed_channel("a.us"), parsed_channel("b.us")];
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let a_id = channel_db_id(&mut conn, "a.us");
+        diesel::insert_into(xmltv_channel_settings::table)
+            .values((
+                xmltv_channel_settings::xmltv_channel_id.eq(a_id),
+                xmltv_channel_settings::is_enabled.eq(1),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+        lineup_staging::set_enabled(&mut conn, true).unwrap();
+
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let new_a_id = channel_db_id(&mut conn, "a.us");
+        assert_ne!(new_a_id, a_id);
+        assert!(lineup_staging::is_channel_published(&mut conn, new_a_id).unwrap());
+        assert_eq!(
+            lineup_published::table
+                .count()
+                .get_result::<i64>(&mut conn)
+                .unwrap(),
+            1
+        );
+        assert!(lineup_staging::draft_changes(&mut conn).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_store_source_data_keeps_channel_policy_actions() {
+        use crate::db::schema::channel_policy_actions;
+
+        let (mut conn, source_id) = source_connection();
+        let channels = [parsed_channel("a.us"), parsed_channel("b.us")];
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let a_id = channel_db_id(&mut conn, "a.us");
+        diesel::insert_into(channel_policy_actions::table)
+            .values((
+                channel_policy_actions::xmltv_channel_id.eq(a_id),
+                channel_policy_actions::action.eq("disabled"),
+                channel_policy_actions::applied_at.eq("2026-01-01T00:00:00Z"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let actions: Vec<(i32, String, String)> = channel_policy_actions::table
+            .select((
+                channel_policy_actions::xmltv_channel_id,
+                channel_policy_actions::action,
+                channel_policy_actions::applied_at,
+            ))
+            .load(&mut conn)
+            .unwrap();
+        let new_a_id = channel_db_id(&mut conn, "a.us");
+        assert_eq!(
+            actions,
+            vec![(
+                new_a_id,
+                "disabled".to_string(),
+                "2026-01-01T00:00:00Z".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_store_source_data_keeps_failover_history() {
+        use crate::db::schema::failover_history;
+
+        let (mut conn, source_id) = source_connection();
+        let channels = [parsed_channel("a.us"), parsed_channel("b.us")];
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+        crate::db::seed_account(&mut conn, 1, "Line A", "http://provider.local");
+        diesel::sql_query(
+            "INSERT INTO xtream_channels (id, account_id, stream_id, name) VALUES
+                (10, 1, 100, 'A'), (11, 1, 101, 'A HD'), (12, 1, 102, 'A SD')",
+        )
+        .execute(&mut conn)
+        .unwrap();
+
+        let a_id = channel_db_id(&mut conn, "a.us");
+        let now = chrono::Utc::now();
+        crate::failover_policy::record_failover(&mut conn, a_id, 10, 11, now).unwrap();
+        crate::failover_policy::record_failover(&mut conn, a_id, 10, 12, now).unwrap();
+
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let history: Vec<(i32, i32)> = failover_history::table
+            .order(failover_history::id.asc())
+            .select((
+                failover_history::xmltv_channel_id,
+                failover_history::to_xtream_channel_id,
+            ))
+            .load(&mut conn)
+            .unwrap();
+        let new_a_id = channel_db_id(&mut conn, "a.us");
+        assert_eq!(history, vec![(new_a_id, 11), (new_a_id, 12)]);
+    }
+
+    #[test]
+    fn test_store_source_data_keeps_every_setting() {
+        let (mut conn, source_id) = source_connection();
+        let channels = [parsed_channel("a.us")];
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let a_id = channel_db_id(&mut conn, "a.us");
+        diesel::insert_into(xmltv_channel_settings::table)
+            .values((
+                xmltv_channel_settings::xmltv_channel_id.eq(a_id),
+                xmltv_channel_settings::is_enabled.eq(1),
+                xmltv_channel_settings::plex_display_order.eq(3),
+                xmltv_channel_settings::placeholder_mode.eq(1),
+                xmltv_channel_settings::placeholder_title.eq("{channel} live"),
+                xmltv_channel_settings::channel_number.eq(501),
+                xmltv_channel_settings::preferred_quality.eq("HD"),
+            ))
+            .execute(&mut conn)
+            .unwrap();
+
+        store_source_data(&mut conn, source_id, &channels, &[]).unwrap();
+
+        let settings: crate::db::XmltvChannelSettings = xmltv_channel_settings::table
+            .filter(xmltv_channel_settings::xmltv_channel_id.eq(channel_db_id(&mut conn, "a.us")))
+            .first(&mut conn)
+            .unwrap();
+        assert_eq!(settings.is_enabled, Some(1));
+        assert_eq!(settings.plex_display_order, Some(3));
+        assert_eq!(settings.placeholder_mode, Some(1));
+        assert_eq!(
+            settings.placeholder_title.as_deref(),
+            Some("{channel} live")
+        );
+        assert_eq!(settings.channel_number, Some(501));
+        assert_eq!(settings.preferred_quality.as_deref(), Some("HD"));
+    }
+
+    #[test]
+    fn test_refresh_progress_serialization() {
+        let progress = EpgRefreshProgress {
+            source_id: 3,
+            source_name: "Main".to_string(),
+            stage: RefreshStage::Storing,
+            channel_count: None,
+            program_count: None,
+            error: None,
+            finished: 1,
+            total: 4,
+        };
+        let json = serde_json::to_value(&progress).unwrap();
+        assert_eq!(json["sourceId"], 3);
+        assert_eq!(json["stage"], "storing");
+        assert_eq!(json["total"], 4);
+    }
+}
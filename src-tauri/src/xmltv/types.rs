@@ -1,7 +1,10 @@
 //! XMLTV data types and error handling
 
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::db::NewProgram;
+
 /// Errors that can occur during XMLTV operations
 #[derive(Debug, Error)]
 pub enum XmltvError {
@@ -56,7 +59,7 @@ pub struct ParsedChannel {
 }
 
 /// A parsed program from XMLTV data
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Default)]
 pub struct ParsedProgram {
     /// Channel ID this program belongs to
     pub channel_id: String,
@@ -72,4 +75,71 @@ pub struct ParsedProgram {
     pub category: Option<String>,
     /// Episode info (raw XMLTV format)
     pub episode_info: Option<String>,
+    /// Episode sub-title
+    pub sub_title: Option<String>,
+    /// Artwork URL (icon src, or the first image element)
+    pub icon: Option<String>,
+    /// Episode number in the `xmltv_ns` system
+    pub episode_num_xmltv_ns: Option<String>,
+    /// Episode number in the `onscreen` system
+    pub episode_num_onscreen: Option<String>,
+    /// Rating value (e.g. "TV-14") and its system
+    pub rating: Option<String>,
+    pub rating_system: Option<String>,
+    /// Star rating value (e.g. "7.5/10")
+    pub star_rating: Option<String>,
+    /// Cast and crew in document order
+    pub credits: Vec<ProgramCredit>,
+    /// Texts by language, only when the programme has more than one language
+    pub translations: Vec<ProgramTranslation>,
+}
+
+impl ParsedProgram {
+    /// Row for the programs table under the given XMLTV channel
+    pub fn to_new_program(&self, xmltv_channel_id: i32) -> NewProgram {
+        let mut program =
+            NewProgram::new(xmltv_channel_id, &self.title, &self.start_time, &self.end_time);
+        program.description = self.description.clone();
+        program.category = self.category.clone();
+        program.episode_info = self.episode_info.clone();
+        program.sub_title = self.sub_title.clone();
+        program.icon = self.icon.clone();
+        program.episode_num_xmltv_ns = self.episode_num_xmltv_ns.clone();
+        program.episode_num_onscreen = self.episode_num_onscreen.clone();
+        program.rating = self.rating.clone();
+        program.rating_system = self.rating_system.clone();
+        program.star_rating = self.star_rating.clone();
+        if !self.credits.is_empty() {
+            program.credits = serde_json::to_string(&self.credits).ok();
+        }
+        if !self.translations.is_empty() {
+            program.translations = serde_json::to_string(&self.translations).ok();
+        }
+        program
+    }
+}
+
+/// A program's texts in one language
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProgramTranslation {
+    /// Language as given in the `lang` attribute (e.g. "de", "en-GB")
+    pub lang: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sub_title: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+}
+
+/// A cast or crew member of a program
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramCredit {
+    /// XMLTV credit element name (e.g. "director", "actor")
+    pub role: String,
+    pub name: String,
+    /// Character played (actor `role` attribute)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub character: Option<String>,
 }
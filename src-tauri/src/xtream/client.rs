@@ -4,15 +4,22 @@
 //! and account status retrieval.
 
 use reqwest::Client;
+use serde::de::DeserializeOwned;
 use std::time::Duration;
 use tracing::warn;
 
-use super::types::{AccountInfo, XtreamAuthResponse, XtreamCategory, XtreamLiveStream};
+use super::types::{
+    AccountInfo, XtreamAuthResponse, XtreamCategory, XtreamLiveStream, XtreamSeries,
+    XtreamSeriesInfo, XtreamVodStream,
+};
 use super::XtreamError;
 
 /// HTTP timeout for Xtream API requests (10 seconds)
 const REQUEST_TIMEOUT_SECS: u64 = 10;
 
+/// HTTP timeout for VOD catalog requests (movie and series lists can be large)
+const CATALOG_TIMEOUT_SECS: u64 = 60;
+
 /// Client for communicating with Xtream Codes API
 #[derive(Debug)]
 pub struct XtreamClient {
@@ -40,10 +47,12 @@ impl XtreamClient {
             return Err(XtreamError::InvalidUrl);
         }
 
-        // Create HTTP client with User-Agent (some servers reject requests without it)
-        let http = Client::builder()
+        // Create HTTP client with the User-Agent and headers set for this server
+        let headers = super::request_headers::for_url(trimmed_url);
+        let http = crate::dns::configure(Client::builder())
             .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
-            .user_agent("Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36")
+            .user_agent(headers.user_agent())
+            .default_headers(headers.header_map())
             .build()
             .map_err(XtreamError::Network)?;
 
@@ -172,6 +181,92 @@ impl XtreamClient {
 
         Ok(categories)
     }
+
+    /// Get all movie categories (`action=get_vod_categories`)
+    pub async fn get_vod_categories(&self) -> Result<Vec<XtreamCategory>, XtreamError> {
+        self.get_action("get_vod_categories", "").await
+    }
+
+    /// Get all movies (`action=get_vod_streams`)
+    pub async fn get_vod_streams(&self) -> Result<Vec<XtreamVodStream>, XtreamError> {
+        self.get_action("get_vod_streams", "").await
+    }
+
+    /// Get all series categories (`action=get_series_categories`)
+    pub async fn get_series_categories(&self) -> Result<Vec<XtreamCategory>, XtreamError> {
+        self.get_action("get_series_categories", "").await
+    }
+
+    /// Get all series (`action=get_series`)
+    pub async fn get_series(&self) -> Result<Vec<XtreamSeries>, XtreamError> {
+        self.get_action("get_series", "").await
+    }
+
+    /// Get the seasons and episodes of a series (`action=get_series_info`)
+    pub async fn get_series_info(&self, series_id: i32) -> Result<XtreamSeriesInfo, XtreamError> {
+        self.get_action("get_series_info", &format!("&series_id={}", series_id))
+            .await
+    }
+
+    /// URL of a movie file
+    pub fn movie_url(&self, stream_id: i32, extension: &str) -> String {
+        self.vod_url("movie", stream_id, extension)
+    }
+
+    /// URL of a series episode file
+    pub fn episode_url(&self, episode_id: i32, extension: &str) -> String {
+        self.vod_url("series", episode_id, extension)
+    }
+
+    fn vod_url(&self, kind: &str, id: i32, extension: &str) -> String {
+        format!(
+            "{}/{}/{}/{}/{}.{}",
+            self.server_url,
+            kind,
+            urlencoding::encode(&self.username),
+            urlencoding::encode(&self.password),
+            id,
+            extension
+        )
+    }
+
+    /// Call a `player_api.php` action and parse the JSON response
+    async fn get_action<T: DeserializeOwned>(
+        &self,
+        action: &str,
+        extra_query: &str,
+    ) -> Result<T, XtreamError> {
+        let url = format!(
+            "{}/player_api.php?username={}&password={}&action={}{}",
+            self.server_url,
+            urlencoding::encode(&self.username),
+            urlencoding::encode(&self.password),
+            action,
+            extra_query
+        );
+
+        let response = self
+            .http
+            .get(&url)
+            .timeout(Duration::from_secs(CATALOG_TIMEOUT_SECS))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(XtreamError::HttpError(response.status().as_u16()));
+        }
+
+        let text = response.text().await.map_err(XtreamError::Network)?;
+        serde_json::from_str(&text).map_err(|e| {
+            warn!(
+                error = %e,
+                action,
+                response_length = text.len(),
+                "Failed to parse Xtream API response"
+            );
+            XtreamError::InvalidResponse
+        })
+    }
 }
 
 #[cfg(test)]
@@ -211,4 +306,17 @@ mod tests {
         let result = XtreamClient::new("   ", "user", "pass");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_vod_urls() {
+        let client = XtreamClient::new("http://example.com:8080", "user", "p@ss").unwrap();
+        assert_eq!(
+            client.movie_url(42, "mkv"),
+            "http://example.com:8080/movie/user/p%40ss/42.mkv"
+        );
+        assert_eq!(
+            client.episode_url(901, "mp4"),
+            "http://example.com:8080/series/user/p%40ss/901.mp4"
+        );
+    }
 }
@@ -0,0 +1,351 @@
+//! M3U playlist provider
+//!
+//! Reads a remote M3U/M3U8 playlist into the same category and live stream
+//! types the Xtream API returns, so channel scanning, matching, and the stream
+//! proxy treat M3U-only providers like Xtream providers. Each entry's upstream
+//! URL is carried in `direct_source` and stored with the channel.
+
+use reqwest::Client;
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::time::Duration;
+use tokio::sync::OnceCell;
+use tracing::warn;
+
+use super::types::{XtreamCategory, XtreamLiveStream};
+use super::XtreamError;
+
+/// HTTP timeout for playlist downloads (large playlists can take a while)
+const REQUEST_TIMEOUT_SECS: u64 = 30;
+
+/// One channel entry of an M3U playlist
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct M3uEntry {
+    pub name: String,
+    pub url: String,
+    pub tvg_id: Option<String>,
+    pub tvg_logo: Option<String>,
+    pub group_title: Option<String>,
+}
+
+/// Parsed playlist in Xtream form
+#[derive(Debug, Clone, Default)]
+pub struct M3uPlaylist {
+    pub categories: Vec<XtreamCategory>,
+    pub streams: Vec<XtreamLiveStream>,
+}
+
+/// Parse the channel entries of an M3U playlist
+///
+/// Entries are `#EXTINF` lines followed by a stream URL. The `tvg-id`,
+/// `tvg-logo`, and `group-title` attributes are read; the display name is the
+/// text after the attribute list, falling back to `tvg-name`. Other directives
+/// and comments are skipped.
+pub fn parse_m3u(content: &str) -> Result<Vec<M3uEntry>, XtreamError> {
+    let content = content.trim_start_matches('\u{feff}');
+    let mut lines = content.lines().map(str::trim).filter(|l| !l.is_empty());
+
+    match lines.next() {
+        Some(first) if first.starts_with("#EXTM3U") => {}
+        _ => return Err(XtreamError::InvalidResponse),
+    }
+
+    let mut entries = Vec::new();
+    let mut pending: Option<(String, HashMap<String, String>)> = None;
+    let mut group_override: Option<String> = None;
+
+    for line in lines {
+        if let Some(info) = line.strip_prefix("#EXTINF:") {
+            pending = Some(parse_extinf(info));
+            group_override = None;
+        } else if let Some(group) = line.strip_prefix("#EXTGRP:") {
+            group_override = Some(group.trim().to_string());
+        } else if line.starts_with('#') {
+            continue;
+        } else if let Some((title, attrs)) = pending.take() {
+            let attr = |key: &str| attrs.get(key).filter(|v| !v.is_empty()).cloned();
+            let name = if title.is_empty() {
+                attr("tvg-name").unwrap_or_else(|| line.to_string())
+            } else {
+                title
+            };
+            entries.push(M3uEntry {
+                name,
+                url: line.to_string(),
+                tvg_id: attr("tvg-id"),
+                tvg_logo: attr("tvg-logo"),
+                group_title: attr("group-title").or(group_override.take()),
+            });
+        }
+    }
+
+    Ok(entries)
+}
+
+/// Split an `#EXTINF` line body into its title and attributes
+fn parse_extinf(info: &str) -> (String, HashMap<String, String>) {
+    let mut attrs = HashMap::new();
+    let mut rest = info;
+    let mut in_quotes = false;
+    let mut title_start = None;
+
+    // The title follows the first comma outside quoted attribute values
+    for (i, c) in info.char_indices() {
+        match c {
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => {
+                title_start = Some(i);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let title = match title_start {
+        Some(i) => {
+            rest = &info[..i];
+            info[i + 1..].trim().to_string()
+        }
+        None => String::new(),
+    };
+
+    // Attributes are key="value" pairs after the duration
+    let mut remaining = rest;
+    while let Some(eq) = remaining.find("=\"") {
+        let key = remaining[..eq]
+            .rsplit(|c: char| c.is_whitespace())
+            .next()
+            .unwrap_or("")
+            .to_ascii_lowercase();
+        let value_start = eq + 2;
+        let Some(len) = remaining[value_start..].find('"') else {
+            break;
+        };
+        attrs.insert(
+            key,
+            remaining[value_start..value_start + len].trim().to_string(),
+        );
+        remaining = &remaining[value_start + len + 1..];
+    }
+
+    (title, attrs)
+}
+
+/// Stable stream ID for a playlist entry
+///
+/// Derived from the stream URL so rescans keep channel mappings. Always
+/// positive, like Xtream stream IDs.
+pub fn stream_id_for_url(url: &str) -> i32 {
+    let digest = Sha256::digest(url.as_bytes());
+    let value = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]) & 0x7fff_ffff;
+    value.max(1) as i32
+}
+
+/// First ID from `stream_id` on that is not in `used`, wrapping to 1
+fn free_stream_id(mut stream_id: i32, used: &HashSet<i32>) -> i32 {
+    while used.contains(&stream_id) {
+        stream_id = stream_id % i32::MAX + 1;
+    }
+    stream_id
+}
+
+/// Convert playlist entries to Xtream categories and live streams
+///
+/// Groups become categories numbered in playlist order. Entries repeating an
+/// earlier URL are dropped. When two URLs hash to the same ID, the later one
+/// takes the next free ID.
+pub fn to_playlist(entries: Vec<M3uEntry>) -> M3uPlaylist {
+    let mut playlist = M3uPlaylist::default();
+    let mut category_ids: HashMap<String, String> = HashMap::new();
+    let mut seen_urls = HashSet::new();
+    let mut used_ids = HashSet::new();
+
+    for (index, entry) in entries.into_iter().enumerate() {
+        if !seen_urls.insert(entry.url.clone()) {
+            continue;
+        }
+        let stream_id = free_stream_id(stream_id_for_url(&entry.url), &used_ids);
+        used_ids.insert(stream_id);
+
+        let category_id = entry.group_title.map(|group| {
+            let next_id = (category_ids.len() + 1).to_string();
+            category_ids
+                .entry(group.clone())
+                .or_insert_with(|| {
+                    playlist.categories.push(XtreamCategory {
+                        category_id: next_id.clone(),
+                        category_name: group,
+                        parent_id: None,
+                    });
+                    next_id
+                })
+                .clone()
+        });
+
+        playlist.streams.push(XtreamLiveStream {
+            num: index as i32 + 1,
+            name: entry.name,
+            stream_type: "live".to_string(),
+            stream_id,
+            stream_icon: entry.tvg_logo,
+            epg_channel_id: entry.tvg_id,
+            added: None,
+            category_id,
+            category_ids: None,
+            custom_sid: None,
+            tv_archive: None,
+            direct_source: Some(entry.url),
+            tv_archive_duration: None,
+        });
+    }
+
+    playlist
+}
+
+/// Client for an M3U playlist provider
+///
+/// Mirrors the channel listing methods of `XtreamClient`. The playlist is
+/// downloaded once per client.
+#[derive(Debug)]
+pub struct M3uClient {
+    http: Client,
+    playlist_url: String,
+    playlist: OnceCell<M3uPlaylist>,
+}
+
+impl M3uClient {
+    pub fn new(playlist_url: &str) -> Result<Self, XtreamError> {
+        let trimmed_url = playlist_url.trim();
+        if url::Url::parse(trimmed_url).is_err() {
+            return Err(XtreamError::InvalidUrl);
+        }
+
+        let headers = super::request_headers::for_url(trimmed_url);
+        let http = crate::dns::configure(Client::builder())
+            .timeout(Duration::from_secs(REQUEST_TIMEOUT_SECS))
+            .user_agent(headers.user_agent())
+            .default_headers(headers.header_map())
+            .build()
+            .map_err(XtreamError::Network)?;
+
+        Ok(Self {
+            http,
+            playlist_url: trimmed_url.to_string(),
+            playlist: OnceCell::new(),
+        })
+    }
+
+    /// Download and parse the playlist (cached after the first call)
+    pub async fn playlist(&self) -> Result<&M3uPlaylist, XtreamError> {
+        self.playlist
+            .get_or_try_init(|| async {
+                let response = self.http.get(&self.playlist_url).send().await?;
+                if !response.status().is_success() {
+                    return Err(XtreamError::HttpError(response.status().as_u16()));
+                }
+
+                let text = response.text().await.map_err(XtreamError::Network)?;
+                let entries = parse_m3u(&text).inspect_err(|_| {
+                    warn!(
+                        response_length = text.len(),
+                        "Playlist response is not an M3U playlist"
+                    );
+                })?;
+                Ok(to_playlist(entries))
+            })
+            .await
+    }
+
+    pub async fn get_live_categories(&self) -> Result<Vec<XtreamCategory>, XtreamError> {
+        Ok(self.playlist().await?.categories.clone())
+    }
+
+    pub async fn get_live_streams(&self) -> Result<Vec<XtreamLiveStream>, XtreamError> {
+        Ok(self.playlist().await?.streams.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PLAYLIST: &str = r#"#EXTM3U x-tvg-url="http://example.com/epg.xml"
+#EXTINF:-1 tvg-id="bbc1.uk" tvg-name="BBC One" tvg-logo="http://example.com/bbc1.png" group-title="UK, General",BBC One HD
+http://example.com/live/1.ts
+#EXTINF:-1 tvg-id="" group-title="News",Sky News
+#EXTVLCOPT:http-user-agent=VLC
+http://example.com/live/2.ts
+
+#EXTINF:-1 tvg-name="Fallback Name",
+#EXTGRP:Other
+http://example.com/live/3.m3u8
+#EXTINF:-1 group-title="News",Duplicate
+http://example.com/live/2.ts
+"#;
+
+    #[test]
+    fn test_parse_m3u_entries() {
+        let entries = parse_m3u(PLAYLIST).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        assert_eq!(entries[0].name, "BBC One HD");
+        assert_eq!(entries[0].tvg_id.as_deref(), Some("bbc1.uk"));
+        assert_eq!(
+            entries[0].tvg_logo.as_deref(),
+            Some("http://example.com/bbc1.png")
+        );
+        // Commas inside quoted attributes don't end the attribute list
+        assert_eq!(entries[0].group_title.as_deref(), Some("UK, General"));
+
+        assert_eq!(entries[1].name, "Sky News");
+        assert_eq!(entries[1].tvg_id, None);
+        assert_eq!(entries[1].url, "http://example.com/live/2.ts");
+
+        assert_eq!(entries[2].name, "Fallback Name");
+        assert_eq!(entries[2].group_title.as_deref(), Some("Other"));
+    }
+
+    #[test]
+    fn test_parse_m3u_rejects_non_playlist() {
+        assert!(parse_m3u("<html>Not found</html>").is_err());
+        assert!(parse_m3u("").is_err());
+        assert!(parse_m3u("\u{feff}#EXTM3U\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_to_playlist() {
+        let playlist = to_playlist(parse_m3u(PLAYLIST).unwrap());
+
+        // Duplicate URL dropped
+        assert_eq!(playlist.streams.len(), 3);
+        let names: Vec<&str> = playlist
+            .categories
+            .iter()
+            .map(|c| c.category_name.as_str())
+            .collect();
+        assert_eq!(names, vec!["UK, General", "News", "Other"]);
+
+        let stream = &playlist.streams[1];
+        assert_eq!(stream.category_id.as_deref(), Some("2"));
+        assert_eq!(
+            stream.direct_source.as_deref(),
+            Some("http://example.com/live/2.ts")
+        );
+        assert_eq!(
+            stream.stream_id,
+            stream_id_for_url("http://example.com/live/2.ts")
+        );
+        assert!(stream.stream_id > 0);
+        assert_eq!(
+            playlist.streams[0].epg_channel_id.as_deref(),
+            Some("bbc1.uk")
+        );
+    }
+
+    #[test]
+    fn test_free_stream_id_probes_past_collisions() {
+        let used: HashSet<i32> = [5, 6, i32::MAX, 1].into_iter().collect();
+        assert_eq!(free_stream_id(4, &used), 4);
+        assert_eq!(free_stream_id(5, &used), 7);
+        assert_eq!(free_stream_id(i32::MAX, &used), 2);
+    }
+}
@@ -4,13 +4,42 @@
 //! This module implements FR2 (API authentication) and FR7 (connection testing) from the PRD.
 
 pub mod client;
+pub mod m3u;
 pub mod quality;
+pub mod request_headers;
 pub mod types;
 
 use thiserror::Error;
 
 pub use client::XtreamClient;
-pub use types::{AccountInfo, ServerInfo, UserInfo, XtreamAuthResponse, XtreamCategory, XtreamLiveStream};
+pub use m3u::M3uClient;
+pub use types::{
+    AccountInfo, ServerInfo, UserInfo, XtreamAuthResponse, XtreamCategory, XtreamEpisode,
+    XtreamLiveStream, XtreamSeries, XtreamSeriesInfo, XtreamVodStream,
+};
+
+/// Channel listing client for either provider type
+#[derive(Debug)]
+pub enum ProviderClient {
+    Xtream(XtreamClient),
+    M3u(M3uClient),
+}
+
+impl ProviderClient {
+    pub async fn get_live_categories(&self) -> Result<Vec<XtreamCategory>, XtreamError> {
+        match self {
+            Self::Xtream(client) => client.get_live_categories().await,
+            Self::M3u(client) => client.get_live_categories().await,
+        }
+    }
+
+    pub async fn get_live_streams(&self) -> Result<Vec<XtreamLiveStream>, XtreamError> {
+        match self {
+            Self::Xtream(client) => client.get_live_streams().await,
+            Self::M3u(client) => client.get_live_streams().await,
+        }
+    }
+}
 
 /// Errors that can occur during Xtream API operations
 #[derive(Debug, Error)]
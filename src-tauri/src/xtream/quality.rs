@@ -130,6 +130,25 @@ fn has_standalone_hd(name: &str) -> bool {
     false
 }
 
+/// Quality tiers from highest to lowest
+pub const QUALITY_TIERS: [&str; 4] = ["4K", "FHD", "HD", "SD"];
+
+/// Position of a quality in `QUALITY_TIERS` (0 is the highest)
+pub fn tier_rank(quality: &str) -> Option<usize> {
+    QUALITY_TIERS
+        .iter()
+        .position(|tier| tier.eq_ignore_ascii_case(quality))
+}
+
+/// Rank of the best quality in a list, unknown or missing qualities as SD
+pub fn best_tier_rank(qualities: &[String]) -> usize {
+    qualities
+        .iter()
+        .filter_map(|q| tier_rank(q))
+        .min()
+        .unwrap_or(QUALITY_TIERS.len() - 1)
+}
+
 /// Convert qualities vector to JSON string for database storage
 pub fn qualities_to_json(qualities: &[String]) -> String {
     serde_json::to_string(qualities).unwrap_or_else(|_| r#"["SD"]"#.to_string())
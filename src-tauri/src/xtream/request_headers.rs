@@ -0,0 +1,239 @@
+//! Per-account User-Agent and request headers
+//!
+//! Some providers reject the default User-Agent, or want a Referer or a
+//! custom header before they serve a stream. Each account can set its own
+//! User-Agent and extra headers; like the DNS overrides, they apply to the
+//! host of the account's server (or playlist) URL, so the API client, the
+//! stream proxy and FFmpeg all send them without being handed the account.
+//!
+//! The overrides are stored on the accounts table and mirrored in a
+//! process-wide map, refreshed by `load_from_db` when accounts change.
+
+use diesel::prelude::*;
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::{LazyLock, RwLock};
+
+use crate::db::schema::accounts;
+
+/// User-Agent sent when an account sets none (some servers reject requests without one)
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36";
+
+/// Most extra headers per account
+const MAX_EXTRA_HEADERS: usize = 16;
+
+/// Longest User-Agent or header value
+const MAX_VALUE_LENGTH: usize = 1024;
+
+/// Headers the client sets itself and an account may not override
+const RESERVED_HEADERS: &[&str] = &[
+    "host",
+    "user-agent",
+    "content-length",
+    "range",
+    "connection",
+];
+
+/// Overrides by lowercase host
+static ACTIVE: LazyLock<RwLock<HashMap<String, RequestHeaders>>> =
+    LazyLock::new(|| RwLock::new(HashMap::new()));
+
+/// User-Agent and extra headers for one provider host
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RequestHeaders {
+    pub user_agent: Option<String>,
+    pub extra: BTreeMap<String, String>,
+}
+
+impl RequestHeaders {
+    /// User-Agent to send
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT)
+    }
+
+    /// Extra headers for a reqwest client or request
+    ///
+    /// Entries were validated when saved; any that no longer parse are skipped.
+    pub fn header_map(&self) -> HeaderMap {
+        self.extra
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect()
+    }
+
+    /// Add the User-Agent and extra headers to a request
+    pub fn apply(&self, request: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        request
+            .header(reqwest::header::USER_AGENT, self.user_agent())
+            .headers(self.header_map())
+    }
+
+    /// Add `-user_agent` and the extra headers to FFmpeg input options
+    ///
+    /// FFmpeg keeps only the last `-headers` option, so extra headers are
+    /// appended to one already present (the DNS Host header).
+    pub fn add_ffmpeg_args(&self, args: &mut Vec<String>) {
+        args.extend(["-user_agent".to_string(), self.user_agent().to_string()]);
+        if self.extra.is_empty() {
+            return;
+        }
+        let lines: String = self
+            .extra
+            .iter()
+            .map(|(name, value)| format!("{}: {}\r\n", name, value))
+            .collect();
+        match args.iter().position(|arg| arg == "-headers") {
+            Some(i) if i + 1 < args.len() => args[i + 1].push_str(&lines),
+            _ => args.extend(["-headers".to_string(), lines]),
+        }
+    }
+}
+
+/// Overrides for the host of `url` (defaults if it has none)
+pub fn for_url(url: &str) -> RequestHeaders {
+    let Some(host) = crate::dns::url_host(url) else {
+        return RequestHeaders::default();
+    };
+    ACTIVE
+        .read()
+        .ok()
+        .and_then(|active| active.get(&host).cloned())
+        .unwrap_or_default()
+}
+
+/// Read the accounts' overrides and apply them
+///
+/// Called at startup, after switching workspaces and after accounts change.
+pub fn load_from_db(conn: &mut SqliteConnection) {
+    let rows: Vec<(String, Option<String>, Option<String>)> = accounts::table
+        .filter(
+            accounts::http_user_agent
+                .is_not_null()
+                .or(accounts::http_headers.is_not_null()),
+        )
+        .order(accounts::id.asc())
+        .select((
+            accounts::server_url,
+            accounts::http_user_agent,
+            accounts::http_headers,
+        ))
+        .load(conn)
+        .unwrap_or_default();
+
+    let mut hosts = HashMap::new();
+    for (server_url, user_agent, headers) in rows {
+        let Some(host) = crate::dns::url_host(&server_url) else {
+            continue;
+        };
+        // The first account of a host wins
+        hosts.entry(host).or_insert_with(|| RequestHeaders {
+            user_agent,
+            extra: parse_stored(headers.as_deref()),
+        });
+    }
+
+    if let Ok(mut active) = ACTIVE.write() {
+        *active = hosts;
+    }
+}
+
+/// Extra headers stored as a JSON object
+pub fn parse_stored(headers: Option<&str>) -> BTreeMap<String, String> {
+    headers
+        .and_then(|json| serde_json::from_str(json).ok())
+        .unwrap_or_default()
+}
+
+/// Check a User-Agent and extra headers before they are saved
+///
+/// Returns the trimmed User-Agent (None if blank) and headers.
+pub fn validate(
+    user_agent: Option<&str>,
+    extra: &BTreeMap<String, String>,
+) -> Result<(Option<String>, BTreeMap<String, String>), String> {
+    let user_agent = user_agent.map(str::trim).filter(|ua| !ua.is_empty());
+    if let Some(ua) = user_agent {
+        check_value("User-Agent", ua)?;
+    }
+
+    if extra.len() > MAX_EXTRA_HEADERS {
+        return Err(format!(
+            "At most {} extra headers are allowed",
+            MAX_EXTRA_HEADERS
+        ));
+    }
+    let mut headers = BTreeMap::new();
+    for (name, value) in extra {
+        let name = name.trim();
+        if HeaderName::from_bytes(name.as_bytes()).is_err() {
+            return Err(format!("Invalid header name: '{}'", name));
+        }
+        if RESERVED_HEADERS.contains(&name.to_ascii_lowercase().as_str()) {
+            return Err(format!("The {} header cannot be overridden", name));
+        }
+        let value = value.trim();
+        check_value(name, value)?;
+        headers.insert(name.to_string(), value.to_string());
+    }
+
+    Ok((user_agent.map(str::to_string), headers))
+}
+
+fn check_value(name: &str, value: &str) -> Result<(), String> {
+    if value.len() > MAX_VALUE_LENGTH {
+        return Err(format!(
+            "{} must be {} characters or less",
+            name, MAX_VALUE_LENGTH
+        ));
+    }
+    // HeaderValue accepts no control characters, so no header can be injected
+    if HeaderValue::from_str(value).is_err() {
+        return Err(format!("Invalid value for {}", name));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_and_ffmpeg_args() {
+        let mut extra = BTreeMap::new();
+        extra.insert(
+            " Referer ".to_string(),
+            " http://portal.example/ ".to_string(),
+        );
+        let (ua, headers) = validate(Some("  VLC/3.0  "), &extra).unwrap();
+        assert_eq!(ua.as_deref(), Some("VLC/3.0"));
+        assert_eq!(headers["Referer"], "http://portal.example/");
+
+        assert!(validate(Some("   "), &BTreeMap::new()).unwrap().0.is_none());
+        extra.insert("X-Token".to_string(), "a\r\nHost: evil".to_string());
+        assert!(validate(None, &extra).is_err());
+        let host: BTreeMap<String, String> = [("Host".to_string(), "x".to_string())].into();
+        assert!(validate(None, &host).is_err());
+
+        let overrides = RequestHeaders {
+            user_agent: ua,
+            extra: headers,
+        };
+        let mut args = vec!["-headers".to_string(), "Host: tv.example\r\n".to_string()];
+        overrides.add_ffmpeg_args(&mut args);
+        assert_eq!(
+            args,
+            [
+                "-headers",
+                "Host: tv.example\r\nReferer: http://portal.example/\r\n",
+                "-user_agent",
+                "VLC/3.0",
+            ]
+        );
+        assert_eq!(RequestHeaders::default().user_agent(), DEFAULT_USER_AGENT);
+    }
+}
@@ -174,6 +174,86 @@ pub struct XtreamCategory {
     pub parent_id: Option<i32>,
 }
 
+/// Movie from Xtream get_vod_streams API
+#[derive(Debug, Deserialize, Clone)]
+pub struct XtreamVodStream {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub stream_id: i32,
+    pub stream_icon: Option<String>,
+    /// Can be string or number in API; unparseable values are dropped
+    #[serde(default, deserialize_with = "deserialize_rating")]
+    pub rating: Option<f32>,
+    pub added: Option<String>,
+    pub category_id: Option<String>,
+    /// File extension of the movie (e.g. "mp4", "mkv")
+    pub container_extension: Option<String>,
+}
+
+/// Series from Xtream get_series API
+#[derive(Debug, Deserialize, Clone)]
+pub struct XtreamSeries {
+    pub name: String,
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub series_id: i32,
+    pub cover: Option<String>,
+    pub plot: Option<String>,
+    pub genre: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_rating")]
+    pub rating: Option<f32>,
+    pub last_modified: Option<String>,
+    pub category_id: Option<String>,
+}
+
+/// Episode from Xtream get_series_info API
+#[derive(Debug, Deserialize, Clone)]
+pub struct XtreamEpisode {
+    #[serde(deserialize_with = "deserialize_number_from_string")]
+    pub id: i32,
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub episode_num: Option<i32>,
+    pub title: Option<String>,
+    pub container_extension: Option<String>,
+    #[serde(default, deserialize_with = "deserialize_optional_number_from_string")]
+    pub season: Option<i32>,
+}
+
+/// Response of Xtream get_series_info API (only the episode list is used)
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct XtreamSeriesInfo {
+    /// Episodes keyed by season number; some servers send an empty array
+    /// instead of an object when a series has no episodes
+    #[serde(default, deserialize_with = "deserialize_episodes")]
+    pub episodes: std::collections::BTreeMap<String, Vec<XtreamEpisode>>,
+}
+
+/// Deserialize a rating that may be a number, a numeric string, or free text
+fn deserialize_rating<'de, D>(deserializer: D) -> Result<Option<f32>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Ok(match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(serde_json::Value::Number(n)) => n.as_f64().map(|v| v as f32),
+        Some(serde_json::Value::String(s)) => s.trim().parse().ok(),
+        _ => None,
+    })
+}
+
+fn deserialize_episodes<'de, D>(
+    deserializer: D,
+) -> Result<std::collections::BTreeMap<String, Vec<XtreamEpisode>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    // Providers send `[]` instead of `{}` for series without episodes
+    match Option::<serde_json::Value>::deserialize(deserializer)? {
+        Some(value @ serde_json::Value::Object(_)) => {
+            serde_json::from_value(value).map_err(serde::de::Error::custom)
+        }
+        _ => Ok(Default::default()),
+    }
+}
+
 impl From<XtreamAuthResponse> for AccountInfo {
     fn from(response: XtreamAuthResponse) -> Self {
         let user = response.user_info;
@@ -534,4 +614,28 @@ mod tests {
         // "invalid" should be skipped, only 1 and 3 remain
         assert_eq!(stream.category_ids, Some(vec![1, 3]));
     }
+
+    #[test]
+    fn test_parse_vod_and_series() {
+        let json = r#"[
+            {"num": 1, "name": "Movie", "stream_id": "42", "rating": "7.5",
+             "category_id": "3", "container_extension": "mkv"},
+            {"num": 2, "name": "Unrated", "stream_id": 43, "rating": "N/A"}
+        ]"#;
+        let movies: Vec<XtreamVodStream> = serde_json::from_str(json).unwrap();
+        assert_eq!(movies[0].stream_id, 42);
+        assert_eq!(movies[0].rating, Some(7.5));
+        assert_eq!(movies[0].container_extension.as_deref(), Some("mkv"));
+        assert_eq!(movies[1].rating, None);
+
+        let json = r#"{"episodes": {"1": [
+            {"id": "901", "episode_num": 1, "title": "Pilot", "container_extension": "mp4", "season": 1}
+        ]}}"#;
+        let info: XtreamSeriesInfo = serde_json::from_str(json).unwrap();
+        assert_eq!(info.episodes["1"][0].id, 901);
+
+        // Series without episodes may come back with an empty array
+        let info: XtreamSeriesInfo = serde_json::from_str(r#"{"episodes": []}"#).unwrap();
+        assert!(info.episodes.is_empty());
+    }
 }
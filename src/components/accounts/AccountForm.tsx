@@ -1,11 +1,29 @@
 import { useState, useEffect, FormEvent, ChangeEvent } from 'react';
 import { EyeOpenIcon, EyeClosedIcon } from '@radix-ui/react-icons';
+import type { ProviderType } from '../../lib/tauri';
 
 export interface AccountFormData {
   name: string;
   serverUrl: string;
   username: string;
   password: string;
+  providerType: ProviderType;
+  /** User-Agent override (edit mode only) */
+  httpUserAgent: string;
+  /** Extra request headers, one "Name: value" per line (edit mode only) */
+  httpHeaders: string;
+}
+
+/** Parse "Name: value" lines into a header map, skipping blank lines */
+export function parseHeaderLines(text: string): Record<string, string> {
+  const headers: Record<string, string> = {};
+  for (const line of text.split('\n')) {
+    const separator = line.indexOf(':');
+    if (separator > 0) {
+      headers[line.slice(0, separator).trim()] = line.slice(separator + 1).trim();
+    }
+  }
+  return headers;
 }
 
 export interface AccountFormErrors {
@@ -21,6 +39,9 @@ export interface AccountEditData {
   name: string;
   serverUrl: string;
   username: string;
+  providerType?: ProviderType;
+  httpUserAgent?: string | null;
+  httpHeaders?: Record<string, string>;
 }
 
 interface AccountFormProps {
@@ -32,9 +53,9 @@ interface AccountFormProps {
 }
 
 /**
- * AccountForm component for adding/editing Xtream Codes accounts
+ * AccountForm component for adding/editing Xtream Codes and M3U playlist accounts
  * Provides form fields for account name, server URL, username, and password
- * with client-side validation.
+ * with client-side validation. M3U accounts only need a name and playlist URL.
  */
 export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount }: AccountFormProps) {
   const isEditMode = !!editAccount;
@@ -44,7 +65,13 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
     serverUrl: editAccount?.serverUrl ?? '',
     username: editAccount?.username ?? '',
     password: '',
+    providerType: editAccount?.providerType ?? 'xtream',
+    httpUserAgent: editAccount?.httpUserAgent ?? '',
+    httpHeaders: Object.entries(editAccount?.httpHeaders ?? {})
+      .map(([name, value]) => `${name}: ${value}`)
+      .join('\n'),
   });
+  const isM3u = formData.providerType === 'm3u';
 
   const [errors, setErrors] = useState<AccountFormErrors>({});
   const [showPassword, setShowPassword] = useState(false);
@@ -57,6 +84,7 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
         serverUrl: editAccount.serverUrl,
         username: editAccount.username,
         password: '',
+        providerType: editAccount.providerType ?? 'xtream',
       });
     } else {
       setFormData({
@@ -64,6 +92,7 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
         serverUrl: '',
         username: '',
         password: '',
+        providerType: 'xtream',
       });
     }
     setErrors({});
@@ -87,6 +116,12 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
       newErrors.serverUrl = 'Please enter a valid URL (e.g., http://example.com:8080)';
     }
 
+    // M3U playlists need no credentials
+    if (isM3u) {
+      setErrors(newErrors);
+      return Object.keys(newErrors).length === 0;
+    }
+
     // Validate username
     if (!formData.username.trim()) {
       newErrors.username = 'Username is required';
@@ -121,6 +156,7 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
         serverUrl: '',
         username: '',
         password: '',
+        providerType: 'xtream',
       });
       setErrors({});
       setShowPassword(false);
@@ -143,6 +179,27 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
       onSubmit={handleSubmit}
       className="space-y-4 p-4 bg-gray-50 rounded-lg"
     >
+      {/* Provider Type Field (fixed once the account exists) */}
+      <div>
+        <label htmlFor="provider-type" className="block text-sm font-medium text-gray-700 mb-1">
+          Provider Type
+        </label>
+        <select
+          id="provider-type"
+          data-testid="provider-type-select"
+          value={formData.providerType}
+          onChange={(e) => {
+            setFormData((prev) => ({ ...prev, providerType: e.target.value as ProviderType }));
+            setErrors({});
+          }}
+          className="w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+          disabled={isLoading || isEditMode}
+        >
+          <option value="xtream">Xtream Codes</option>
+          <option value="m3u">M3U Playlist</option>
+        </select>
+      </div>
+
       {/* Account Name Field */}
       <div>
         <label htmlFor="account-name" className="block text-sm font-medium text-gray-700 mb-1">
@@ -171,7 +228,7 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
       {/* Server URL Field */}
       <div>
         <label htmlFor="server-url" className="block text-sm font-medium text-gray-700 mb-1">
-          Server URL
+          {isM3u ? 'Playlist URL' : 'Server URL'}
         </label>
         <input
           id="server-url"
@@ -179,7 +236,7 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
           data-testid="server-url-input"
           value={formData.serverUrl}
           onChange={handleChange('serverUrl')}
-          placeholder="http://example.com:8080"
+          placeholder={isM3u ? 'http://example.com/playlist.m3u' : 'http://example.com:8080'}
           className={`w-full px-3 py-2 border rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 ${
             errors.serverUrl ? 'border-red-500' : 'border-gray-300'
           }`}
@@ -192,70 +249,116 @@ export function AccountForm({ onSubmit, onCancel, isLoading = false, editAccount
         )}
       </div>
 
-      {/* Username Field */}
-      <div>
-        <label htmlFor="username" className="block text-sm font-medium text-gray-700 mb-1">
-          Username
-        </label>
-        <input
-          id="username"
-          type="text"
-          data-testid="username-input"
-          value={formData.username}
-          onChange={handleChange('username')}
-          placeholder="your_username"
-          maxLength={100}
-          className={`w-full px-3 py-2 border rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 ${
-            errors.username ? 'border-red-500' : 'border-gray-300'
-          }`}
-          disabled={isLoading}
-        />
-        {errors.username && (
-          <p data-testid="username-error" className="mt-1 text-sm text-red-600">
-            {errors.username}
-          </p>
-        )}
-      </div>
+      {!isM3u && (
+        <>
+          {/* Username Field */}
+          <div>
+            <label htmlFor="username" className="block text-sm font-medium text-gray-700 mb-1">
+              Username
+            </label>
+            <input
+              id="username"
+              type="text"
+              data-testid="username-input"
+              value={formData.username}
+              onChange={handleChange('username')}
+              placeholder="your_username"
+              maxLength={100}
+              className={`w-full px-3 py-2 border rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 ${
+                errors.username ? 'border-red-500' : 'border-gray-300'
+              }`}
+              disabled={isLoading}
+            />
+            {errors.username && (
+              <p data-testid="username-error" className="mt-1 text-sm text-red-600">
+                {errors.username}
+              </p>
+            )}
+          </div>
 
-      {/* Password Field */}
-      <div>
-        <label htmlFor="password" className="block text-sm font-medium text-gray-700 mb-1">
-          Password {isEditMode && <span className="text-gray-400 font-normal">(leave blank to keep current)</span>}
-        </label>
-        <div className="relative">
-          <input
-            id="password"
-            type={showPassword ? 'text' : 'password'}
-            data-testid="password-input"
-            value={formData.password}
-            onChange={handleChange('password')}
-            placeholder={isEditMode ? '••••••••' : 'your_password'}
-            maxLength={500}
-            className={`w-full px-3 py-2 pr-10 border rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 ${
-              errors.password ? 'border-red-500' : 'border-gray-300'
-            }`}
-            disabled={isLoading}
-          />
-          <button
-            type="button"
-            data-testid="toggle-password-visibility"
-            onClick={() => setShowPassword(!showPassword)}
-            className="absolute inset-y-0 right-0 flex items-center pr-3 text-gray-400 hover:text-gray-600"
-            tabIndex={-1}
-          >
-            {showPassword ? (
-              <EyeClosedIcon className="w-5 h-5" />
-            ) : (
-              <EyeOpenIcon className="w-5 h-5" />
+          {/* Password Field */}
+          <div>
+            <label htmlFor="password" className="block text-sm font-medium text-gray-700 mb-1">
+              Password {isEditMode && <span className="text-gray-400 font-normal">(leave blank to keep current)</span>}
+            </label>
+            <div className="relative">
+              <input
+                id="password"
+                type={showPassword ? 'text' : 'password'}
+                data-testid="password-input"
+                value={formData.password}
+                onChange={handleChange('password')}
+                placeholder={isEditMode ? '••••••••' : 'your_password'}
+                maxLength={500}
+                className={`w-full px-3 py-2 pr-10 border rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 ${
+                  errors.password ? 'border-red-500' : 'border-gray-300'
+                }`}
+                disabled={isLoading}
+              />
+              <button
+                type="button"
+                data-testid="toggle-password-visibility"
+                onClick={() => setShowPassword(!showPassword)}
+                className="absolute inset-y-0 right-0 flex items-center pr-3 text-gray-400 hover:text-gray-600"
+                tabIndex={-1}
+              >
+                {showPassword ? (
+                  <EyeClosedIcon className="w-5 h-5" />
+                ) : (
+                  <EyeOpenIcon className="w-5 h-5" />
+                )}
+              </button>
+            </div>
+            {errors.password && (
+              <p data-testid="password-error" className="mt-1 text-sm text-red-600">
+                {errors.password}
+              </p>
             )}
-          </button>
-        </div>
-        {errors.password && (
-          <p data-testid="password-error" className="mt-1 text-sm text-red-600">
-            {errors.password}
-          </p>
-        )}
-      </div>
+          </div>
+        </>
+      )}
+
+      {/* Request header overrides, for providers that block the default client */}
+      {isEditMode && (
+        <details data-testid="request-headers-section">
+          <summary className="text-sm font-medium text-gray-700 cursor-pointer">
+            Request headers
+          </summary>
+          <div className="mt-2 space-y-3">
+            <div>
+              <label htmlFor="http-user-agent" className="block text-sm font-medium text-gray-700 mb-1">
+                User-Agent <span className="text-gray-400 font-normal">(leave blank for the default)</span>
+              </label>
+              <input
+                id="http-user-agent"
+                type="text"
+                data-testid="http-user-agent-input"
+                value={formData.httpUserAgent}
+                onChange={(e) => setFormData((prev) => ({ ...prev, httpUserAgent: e.target.value }))}
+                placeholder="VLC/3.0.20 LibVLC/3.0.20"
+                maxLength={1024}
+                className="w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+                disabled={isLoading}
+              />
+            </div>
+            <div>
+              <label htmlFor="http-headers" className="block text-sm font-medium text-gray-700 mb-1">
+                Extra headers <span className="text-gray-400 font-normal">(one "Name: value" per line)</span>
+              </label>
+              <textarea
+                id="http-headers"
+                data-testid="http-headers-input"
+                value={formData.httpHeaders}
+                onChange={(e) => setFormData((prev) => ({ ...prev, httpHeaders: e.target.value }))}
+                placeholder="Referer: http://example.com/"
+                rows={3}
+                className="w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm font-mono text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+                disabled={isLoading}
+              />
+            </div>
+          </div>
+        </details>
+      )}
 
       {/* Form Actions */}
       <div className="flex justify-end space-x-3 pt-4">
@@ -1,5 +1,6 @@
 import { TrashIcon, Pencil1Icon } from '@radix-ui/react-icons';
 import { AccountStatus } from './AccountStatus';
+import type { ProviderType } from '../../lib/tauri';
 
 export interface Account {
   id: number;
@@ -10,6 +11,7 @@ export interface Account {
   isActive: boolean;
   createdAt: string;
   updatedAt: string;
+  providerType?: ProviderType;
   // Connection status fields (optional, populated after connection test)
   connectionStatus?: string;
   expiryDate?: string;
@@ -64,7 +66,9 @@ export function AccountsList({ accounts, onEdit, onDelete, isLoading = false }:
               <div className="mt-1 flex flex-col sm:flex-row sm:items-center sm:space-x-4 text-sm text-gray-500">
                 <span className="truncate">{extractHost(account.serverUrl)}</span>
                 <span className="hidden sm:inline">|</span>
-                <span className="text-gray-600">{account.username}</span>
+                <span className="text-gray-600">
+                  {account.providerType === 'm3u' ? 'M3U playlist' : account.username}
+                </span>
               </div>
             </div>
 
@@ -1,4 +1,4 @@
-export { AccountForm } from './AccountForm';
+export { AccountForm, parseHeaderLines } from './AccountForm';
 export type { AccountFormData, AccountFormErrors, AccountEditData } from './AccountForm';
 export { AccountsList } from './AccountsList';
 export type { Account } from './AccountsList';
@@ -0,0 +1,54 @@
+/**
+ * AccountExpiryBanner Component
+ *
+ * Warns on the dashboard when an Xtream subscription has expired or ends
+ * within the warning window. Renders nothing while every account is fine.
+ */
+import { useQuery } from '@tanstack/react-query';
+import { AlertTriangle } from 'lucide-react';
+import { getAccountExpiryInfo, type AccountExpiryInfo } from '../../lib/tauri';
+
+function describe(info: AccountExpiryInfo): string {
+  if (info.expired) {
+    return `${info.accountName}: subscription expired`;
+  }
+  const days = info.daysRemaining ?? 0;
+  if (days === 0) {
+    return `${info.accountName}: expires today`;
+  }
+  return `${info.accountName}: expires in ${days} day${days === 1 ? '' : 's'}`;
+}
+
+/**
+ * AccountExpiryBanner - Expired and soon-to-expire subscriptions
+ */
+export function AccountExpiryBanner() {
+  const { data: accounts = [] } = useQuery({
+    queryKey: ['accountExpiry'],
+    queryFn: getAccountExpiryInfo,
+  });
+
+  const flagged = accounts.filter((info) => info.expired || info.expiringSoon);
+  if (flagged.length === 0) {
+    return null;
+  }
+
+  return (
+    <div
+      data-testid="account-expiry-banner"
+      className="p-4 bg-amber-50 border border-amber-200 text-amber-800 rounded-md"
+    >
+      <div className="flex items-center gap-2 font-medium">
+        <AlertTriangle className="w-5 h-5" />
+        <span>Provider subscriptions need renewal</span>
+      </div>
+      <ul className="mt-2 space-y-1 text-sm">
+        {flagged.map((info) => (
+          <li key={info.accountId} data-testid="account-expiry-item">
+            {describe(info)}
+          </li>
+        ))}
+      </ul>
+    </div>
+  );
+}
@@ -0,0 +1,98 @@
+/**
+ * RecentErrorsSection Component
+ *
+ * Shows the latest error-level events from memory, so problems are visible
+ * at a glance even when the event log can't be read.
+ */
+import { useEffect } from 'react';
+import { useMutation, useQuery, useQueryClient } from '@tanstack/react-query';
+import { AlertTriangle, CheckCircle } from 'lucide-react';
+import { clearRecentErrors, getRecentErrors, onRecentError } from '../../lib/tauri';
+
+/** Errors listed before the rest are collapsed into a count */
+const ERRORS_SHOWN = 5;
+
+/**
+ * RecentErrorsSection - Latest errors with a way to dismiss them
+ */
+export function RecentErrorsSection() {
+  const queryClient = useQueryClient();
+  const { data: errors = [] } = useQuery({
+    queryKey: ['recentErrors'],
+    queryFn: getRecentErrors,
+  });
+
+  const dismiss = useMutation({
+    mutationFn: clearRecentErrors,
+    onSuccess: () => queryClient.invalidateQueries({ queryKey: ['recentErrors'] }),
+  });
+
+  // Refresh as errors come in
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    onRecentError(() => {
+      queryClient.invalidateQueries({ queryKey: ['recentErrors'] });
+    })
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch((err) => console.error('Failed to listen for errors:', err));
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, [queryClient]);
+
+  return (
+    <div data-testid="recent-errors-section" className="bg-white rounded-lg shadow p-6">
+      <div className="flex items-center justify-between mb-4">
+        <div className="flex items-center gap-2">
+          <AlertTriangle className="w-5 h-5 text-gray-500" />
+          <h2 className="text-lg font-semibold">Recent Errors</h2>
+        </div>
+        {errors.length > 0 && (
+          <button
+            data-testid="dismiss-recent-errors"
+            onClick={() => dismiss.mutate()}
+            disabled={dismiss.isPending}
+            className="text-sm text-blue-600 hover:underline disabled:opacity-50"
+          >
+            Dismiss
+          </button>
+        )}
+      </div>
+
+      {errors.length === 0 ? (
+        <div className="flex items-center gap-2 text-sm text-green-700">
+          <CheckCircle className="w-4 h-4" />
+          <span>No recent errors</span>
+        </div>
+      ) : (
+        <ul className="divide-y divide-gray-100">
+          {errors.slice(0, ERRORS_SHOWN).map((error, i) => (
+            <li key={`${error.timestamp}-${i}`} className="py-2" data-testid="recent-error">
+              <div className="flex items-center justify-between text-xs text-gray-500">
+                <span className="uppercase">{error.category}</span>
+                <span>{new Date(error.timestamp).toLocaleString()}</span>
+              </div>
+              <p className="text-sm text-red-700">{error.message}</p>
+              {!error.persisted && (
+                <p className="text-xs text-gray-500">Not saved to the event log</p>
+              )}
+            </li>
+          ))}
+          {errors.length > ERRORS_SHOWN && (
+            <li className="pt-2 text-xs text-gray-500">
+              {errors.length - ERRORS_SHOWN} more in the event log
+            </li>
+          )}
+        </ul>
+      )}
+    </div>
+  );
+}
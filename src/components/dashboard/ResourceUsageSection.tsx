@@ -0,0 +1,105 @@
+/**
+ * ResourceUsageSection Component
+ *
+ * Shows process memory and the size of caches, the database, and stream
+ * buffers, so users on small devices can see what is using memory.
+ */
+import { useQuery } from '@tanstack/react-query';
+import { AlertCircle, Cpu } from 'lucide-react';
+import { getResourceUsage } from '../../lib/tauri';
+
+function formatBytes(bytes: number): string {
+  if (bytes < 1024) return `${bytes} B`;
+  const units = ['KB', 'MB', 'GB'];
+  let value = bytes / 1024;
+  let unit = 0;
+  while (value >= 1024 && unit < units.length - 1) {
+    value /= 1024;
+    unit++;
+  }
+  return `${value.toFixed(1)} ${units[unit]}`;
+}
+
+interface UsageRowProps {
+  label: string;
+  value: string;
+  testId: string;
+}
+
+function UsageRow({ label, value, testId }: UsageRowProps) {
+  return (
+    <div className="flex items-center justify-between py-1.5">
+      <span className="text-sm text-gray-600">{label}</span>
+      <span data-testid={testId} className="text-sm font-mono text-gray-900">
+        {value}
+      </span>
+    </div>
+  );
+}
+
+/**
+ * ResourceUsageSection - Memory and disk usage overview
+ */
+export function ResourceUsageSection() {
+  const { data: usage, error, refetch } = useQuery({
+    queryKey: ['resourceUsage'],
+    queryFn: getResourceUsage,
+    refetchInterval: 10000,
+  });
+
+  return (
+    <div
+      data-testid="resource-usage-section"
+      className="bg-white rounded-lg shadow p-6"
+    >
+      <div className="flex items-center gap-2 mb-4">
+        <Cpu className="w-5 h-5 text-gray-500" />
+        <h2 className="text-lg font-semibold">Resource Usage</h2>
+      </div>
+
+      {error ? (
+        <div className="bg-red-50 border border-red-200 rounded p-3 text-red-700 text-sm flex items-center gap-2">
+          <AlertCircle className="w-4 h-4" />
+          <span>Failed to load resource usage. </span>
+          <button onClick={() => refetch()} className="underline hover:no-underline">
+            Retry
+          </button>
+        </div>
+      ) : !usage ? (
+        <div className="animate-pulse space-y-3">
+          <div className="h-4 bg-gray-200 rounded" />
+          <div className="h-4 bg-gray-200 rounded" />
+          <div className="h-4 bg-gray-200 rounded" />
+        </div>
+      ) : (
+        <div className="divide-y divide-gray-100">
+          <UsageRow
+            label="Process memory (RSS)"
+            value={usage.processRssBytes != null ? formatBytes(usage.processRssBytes) : 'Unavailable'}
+            testId="usage-process-rss"
+          />
+          <UsageRow
+            label={`Stream buffers (${usage.activeStreams} active)`}
+            value={formatBytes(usage.streamBufferBytes)}
+            testId="usage-stream-buffers"
+          />
+          <UsageRow
+            label="EPG cache"
+            value={formatBytes(usage.epgCacheBytes)}
+            testId="usage-epg-cache"
+          />
+          <UsageRow
+            label="Database"
+            value={formatBytes(usage.databaseBytes)}
+            testId="usage-database"
+          />
+          <UsageRow
+            label={`Logo cache (${usage.logoCacheFiles} files)`}
+            value={formatBytes(usage.logoCacheBytes)}
+            testId="usage-logo-cache"
+          />
+        </div>
+      )}
+    </div>
+  );
+}
@@ -74,8 +74,8 @@ export function EpgSourceDialog({
     // URL validation
     try {
       const parsed = new URL(url);
-      if (parsed.protocol !== 'http:' && parsed.protocol !== 'https:') {
-        setError('URL must use http or https');
+      if (!['http:', 'https:', 'file:'].includes(parsed.protocol)) {
+        setError('URL must use http, https or file');
         return;
       }
     } catch {
@@ -167,6 +167,9 @@ export function EpgSourceDialog({
               className="w-full px-3 py-2 border border-gray-300 rounded-md shadow-sm focus:outline-none focus:ring-2 focus:ring-blue-500 focus:border-blue-500"
               disabled={isLoading}
             />
+            <p className="mt-1 text-xs text-gray-500">
+              For a file written by a local EPG grabber, use file:///path/to/guide.xml.gz
+            </p>
           </div>
 
           {/* Format Field */}
@@ -1,7 +1,7 @@
 import { useState, useEffect, useCallback } from 'react';
 import { TrashIcon, Pencil1Icon, ReloadIcon } from '@radix-ui/react-icons';
-import type { XmltvSource, EpgStats } from '../../lib/tauri';
-import { refreshEpgSource, refreshAllEpgSources, getEpgStats } from '../../lib/tauri';
+import type { XmltvSource, EpgStats, EpgRefreshProgress, EpgRefreshStage } from '../../lib/tauri';
+import { refreshEpgSource, refreshAllEpgSources, getEpgStats, onEpgRefreshProgress } from '../../lib/tauri';
 
 interface EpgSourcesListProps {
   sources: XmltvSource[];
@@ -29,6 +29,28 @@ export function EpgSourcesList({
   const [epgStats, setEpgStats] = useState<Record<number, EpgStats>>({});
   const [error, setError] = useState<string | null>(null);
   const [successMessage, setSuccessMessage] = useState<string | null>(null);
+  const [refreshProgress, setRefreshProgress] = useState<Record<number, EpgRefreshProgress>>({});
+
+  // Track per-source refresh progress
+  useEffect(() => {
+    let unlisten: (() => void) | undefined;
+    let cancelled = false;
+    onEpgRefreshProgress((progress) => {
+      setRefreshProgress((prev) => ({ ...prev, [progress.sourceId]: progress }));
+    })
+      .then((fn) => {
+        if (cancelled) {
+          fn();
+        } else {
+          unlisten = fn;
+        }
+      })
+      .catch((err) => console.error('Failed to listen for EPG refresh progress:', err));
+    return () => {
+      cancelled = true;
+      unlisten?.();
+    };
+  }, []);
 
   // Load EPG stats for all sources
   const loadStats = useCallback(async () => {
@@ -53,6 +75,7 @@ export function EpgSourcesList({
   // Handle single source refresh
   const handleRefreshSource = async (source: XmltvSource) => {
     setRefreshingSource(source.id);
+    setRefreshProgress({});
     setError(null);
     setSuccessMessage(null);
 
@@ -83,6 +106,7 @@ export function EpgSourcesList({
   // Handle refresh all sources
   const handleRefreshAll = async () => {
     setRefreshingAll(true);
+    setRefreshProgress({});
     setError(null);
     setSuccessMessage(null);
 
@@ -166,6 +190,7 @@ export function EpgSourcesList({
         {sources.map((source) => {
           const stats = epgStats[source.id];
           const isRefreshing = refreshingSource === source.id;
+          const progress = refreshingAll || isRefreshing ? refreshProgress[source.id] : undefined;
 
           return (
             <div
@@ -220,6 +245,20 @@ export function EpgSourcesList({
                     : 'No EPG data'
                   }
                 </div>
+                {/* Refresh Progress */}
+                {progress && (
+                  <div data-testid={`epg-source-progress-${source.id}`} className="mt-2">
+                    <div className="h-1.5 w-full bg-gray-100 rounded-full overflow-hidden">
+                      <div
+                        className={`h-full transition-all ${progress.stage === 'failed' ? 'bg-red-500' : 'bg-blue-500'}`}
+                        style={{ width: `${STAGE_PERCENT[progress.stage]}%` }}
+                      />
+                    </div>
+                    <div className="text-xs text-gray-500 mt-1">
+                      {progress.stage === 'failed' ? `Failed: ${progress.error ?? 'unknown error'}` : STAGE_LABEL[progress.stage]}
+                    </div>
+                  </div>
+                )}
               </div>
 
               {/* Action Buttons */}
@@ -266,6 +305,26 @@ export function EpgSourcesList({
   );
 }
 
+/** Progress bar fill for each refresh stage */
+const STAGE_PERCENT: Record<EpgRefreshStage, number> = {
+  queued: 5,
+  fetching: 25,
+  parsing: 55,
+  storing: 80,
+  completed: 100,
+  failed: 100,
+};
+
+/** Status text for each refresh stage */
+const STAGE_LABEL: Record<EpgRefreshStage, string> = {
+  queued: 'Queued',
+  fetching: 'Downloading...',
+  parsing: 'Parsing...',
+  storing: 'Saving...',
+  completed: 'Done',
+  failed: 'Failed',
+};
+
 /**
  * Format the display string for XMLTV format
  */
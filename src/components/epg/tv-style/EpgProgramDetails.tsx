@@ -261,6 +261,12 @@ export function EpgProgramDetails({ selectedProgramId, onClose, onNavigateUp, on
           {program.title}
         </h2>
 
+        {program.subTitle && (
+          <p data-testid="program-sub-title" className="text-lg text-white/80 mt-1 line-clamp-1">
+            {program.subTitle}
+          </p>
+        )}
+
         {/* Episode info (Task 2.2, 2.3) */}
         {program.episodeInfo && (
           <p data-testid="episode-info" className="text-base text-white/60 mt-1">
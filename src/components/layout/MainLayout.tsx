@@ -65,8 +65,8 @@ export function MainLayout() {
         const info = await checkForUpdate();
         setUpdateInfo(info);
 
-        // Show dialog if update is available
-        if (info.available) {
+        // Show dialog if update is available and not deferred
+        if (info.available && !info.deferredUntil) {
           setShowUpdateDialog(true);
         }
       } catch (err) {
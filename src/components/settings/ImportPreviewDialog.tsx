@@ -4,12 +4,14 @@
  * AC #4: Preview shows counts of items to be imported
  */
 
+import { useState } from 'react';
 import { ImportPreview } from '../../lib/tauri';
 
 interface ImportPreviewDialogProps {
   preview: ImportPreview;
   isImporting: boolean;
-  onConfirm: () => void;
+  /** Receives the passphrase when the file carries sealed credentials */
+  onConfirm: (passphrase?: string) => void;
   onCancel: () => void;
 }
 
@@ -19,6 +21,8 @@ export function ImportPreviewDialog({
   onConfirm,
   onCancel,
 }: ImportPreviewDialogProps) {
+  const [passphrase, setPassphrase] = useState('');
+
   // Format the export date for display
   const formatDate = (dateStr: string): string => {
     try {
@@ -148,8 +152,89 @@ export function ImportPreviewDialog({
                 </ul>
               </div>
 
+              {/* Dry-run consequences */}
+              {preview.conflicts && (
+                <div
+                  data-testid="import-preview-conflicts"
+                  className="mb-4 max-h-48 overflow-y-auto text-sm text-gray-700 space-y-2"
+                >
+                  {preview.conflicts.existingAccounts.map((c) => (
+                    <p key={`account-${c.url}-${c.name}`}>
+                      Replaces account <strong>{c.existingName}</strong> ({c.url})
+                    </p>
+                  ))}
+                  {preview.conflicts.removedAccounts.length > 0 && (
+                    <p>
+                      Removes accounts: <strong>{preview.conflicts.removedAccounts.join(', ')}</strong>
+                    </p>
+                  )}
+                  {preview.conflicts.existingXmltvSources.map((c) => (
+                    <p key={`source-${c.url}`}>
+                      Replaces EPG source <strong>{c.existingName}</strong> ({c.url})
+                    </p>
+                  ))}
+                  {preview.conflicts.removedXmltvSources.length > 0 && (
+                    <p>
+                      Removes EPG sources:{' '}
+                      <strong>{preview.conflicts.removedXmltvSources.join(', ')}</strong>
+                    </p>
+                  )}
+                  {preview.conflicts.settingChanges.map((c) => (
+                    <p key={`setting-${c.key}`}>
+                      {c.key}: {c.currentValue ?? '(unset)'} → {c.importedValue ?? '(unset)'}
+                    </p>
+                  ))}
+                  {preview.conflicts.settingsReset.length > 0 && (
+                    <p>
+                      {preview.conflicts.settingsReset.length} other setting(s) reset to defaults
+                    </p>
+                  )}
+                  {preview.conflicts.unresolvedMappings.length > 0 && (
+                    <p>
+                      {preview.conflicts.unresolvedMappings.length} channel mapping(s) cannot be
+                      resolved in this database
+                    </p>
+                  )}
+                  {preview.conflicts.unresolvedPortableMappings.length > 0 && (
+                    <p>
+                      {preview.conflicts.unresolvedPortableMappings.length} channel mapping(s)
+                      reference an EPG source or account missing from the file
+                    </p>
+                  )}
+                </div>
+              )}
+
+              {/* Integrity check of the file */}
+              {preview.integrityWarning && (
+                <div
+                  data-testid="import-preview-integrity-warning"
+                  className="mb-4 p-3 bg-amber-50 border border-amber-200 rounded text-amber-700 text-sm"
+                >
+                  <strong>Note:</strong> {preview.integrityWarning}
+                </div>
+              )}
+
+              {/* Passphrase for included passwords */}
+              {preview.hasCredentials && (
+                <div className="mb-4 text-sm">
+                  <label htmlFor="import-passphrase" className="block text-gray-700 mb-1">
+                    This file includes account passwords. Enter its passphrase to restore them,
+                    or leave it empty to re-enter passwords after import.
+                  </label>
+                  <input
+                    id="import-passphrase"
+                    data-testid="import-passphrase-input"
+                    type="password"
+                    value={passphrase}
+                    onChange={(e) => setPassphrase(e.target.value)}
+                    disabled={isImporting}
+                    className="w-full px-3 py-2 border border-gray-300 rounded-md focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                </div>
+              )}
+
               {/* Warning about accounts */}
-              {preview.accountCount > 0 && (
+              {preview.accountCount > 0 && !preview.hasCredentials && (
                 <div className="mb-4 p-3 bg-amber-50 border border-amber-200 rounded text-amber-700 text-sm">
                   <strong>Note:</strong> Account passwords are not included in exports for
                   security reasons. You will need to re-enter passwords after import.
@@ -204,7 +289,7 @@ export function ImportPreviewDialog({
           {preview.valid && (
             <button
               data-testid="import-confirm-button"
-              onClick={onConfirm}
+              onClick={() => onConfirm(passphrase || undefined)}
               disabled={isImporting}
               className="px-4 py-2 bg-red-600 text-white rounded-md hover:bg-red-700 focus:outline-none focus:ring-2 focus:ring-red-500 focus:ring-offset-2 disabled:opacity-50 disabled:cursor-not-allowed flex items-center gap-2"
             >
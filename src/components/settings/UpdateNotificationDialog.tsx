@@ -8,7 +8,7 @@
  * and options to download/install or remind later.
  */
 import { useState } from 'react';
-import { UpdateInfo, downloadAndInstallUpdate } from '../../lib/tauri';
+import { UpdateInfo, deferUpdate, downloadAndInstallUpdate } from '../../lib/tauri';
 
 interface UpdateNotificationDialogProps {
   updateInfo: UpdateInfo;
@@ -29,6 +29,16 @@ export function UpdateNotificationDialog({
     return null;
   }
 
+  const handleRemindInWeek = async () => {
+    try {
+      setError(null);
+      await deferUpdate(7);
+      onRemindLater();
+    } catch (err) {
+      setError(`Failed to defer update: ${err instanceof Error ? err.message : String(err)}`);
+    }
+  };
+
   const handleDownloadAndInstall = async () => {
     try {
       setIsDownloading(true);
@@ -141,6 +151,14 @@ export function UpdateNotificationDialog({
 
         {/* Footer */}
         <div className="bg-gray-50 px-6 py-4 flex justify-end gap-3">
+          <button
+            data-testid="update-remind-week-button"
+            onClick={handleRemindInWeek}
+            disabled={isDownloading}
+            className="px-4 py-2 text-gray-700 bg-gray-200 rounded-md hover:bg-gray-300 focus:outline-none focus:ring-2 focus:ring-gray-500 focus:ring-offset-2 disabled:opacity-50 disabled:cursor-not-allowed"
+          >
+            Remind Me in a Week
+          </button>
           <button
             data-testid="update-remind-later-button"
             onClick={onRemindLater}
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 export async function greet(name: string): Promise<string> {
   return invoke('greet', { name });
@@ -25,6 +26,34 @@ export async function setAutostartEnabled(enabled: boolean): Promise<void> {
   return invoke('set_autostart_enabled', { enabled });
 }
 
+/** A setting whose value changed in a batch save */
+export interface SettingChange {
+  key: string;
+  oldValue: string | null;
+  newValue: string;
+}
+
+export interface SettingsBatchResult {
+  /** False when any value was invalid; nothing was saved then */
+  applied: boolean;
+  changed: SettingChange[];
+  /** Validation error per setting key */
+  errors: Record<string, string>;
+}
+
+/**
+ * Save several settings in one transaction
+ *
+ * All values are validated first; if any is invalid nothing is saved and
+ * `errors` lists the problem per key.
+ * @param settings - Values by setting key (strings, numbers or booleans)
+ */
+export async function applySettingsBatch(
+  settings: Record<string, string | number | boolean>
+): Promise<SettingsBatchResult> {
+  return invoke<SettingsBatchResult>('apply_settings_batch', { settings });
+}
+
 // Account types and functions
 
 /** Account response type (without password) */
@@ -37,23 +66,40 @@ export interface Account {
   isActive: boolean;
   createdAt: string;
   updatedAt: string;
+  providerType: ProviderType;
   // Connection status fields (populated after connection test)
   connectionStatus?: string;
   expiryDate?: string;
   maxConnectionsActual?: number;
   activeConnections?: number;
+  /** Minutes between proactive upstream reconnects; unset means never */
+  reconnectIntervalMinutes?: number | null;
+  /** Why the stored password could not be read; the user must re-enter it */
+  credentialError?: string | null;
+  /** User-Agent sent to the provider; unset means the default */
+  httpUserAgent?: string | null;
+  /** Extra request headers sent to the provider */
+  httpHeaders?: Record<string, string>;
 }
 
+/** How an account's channels are fetched: Xtream Codes API or M3U playlist */
+export type ProviderType = 'xtream' | 'm3u';
+
 /** Request type for adding a new account */
 export interface AddAccountRequest {
   name: string;
+  /** Xtream server URL, or the playlist URL for M3U accounts */
   serverUrl: string;
+  /** Not used for M3U accounts */
   username: string;
+  /** Not used for M3U accounts */
   password: string;
+  /** Defaults to 'xtream' */
+  providerType?: ProviderType;
 }
 
 /**
- * Add a new Xtream Codes account
+ * Add a new Xtream Codes or M3U playlist account
  * @param request - Account details including credentials
  * @returns The created account (without password)
  */
@@ -83,6 +129,10 @@ export interface UpdateAccountRequest {
   serverUrl: string;
   username: string;
   password?: string; // Optional - only update if provided
+  /** User-Agent override; empty restores the default */
+  httpUserAgent?: string;
+  /** Extra request headers (replace the stored ones) */
+  httpHeaders?: Record<string, string>;
 }
 
 /**
@@ -95,6 +145,17 @@ export async function updateAccount(id: number, request: UpdateAccountRequest):
   return invoke<Account>('update_account', { id, request });
 }
 
+/**
+ * Set how often streams from an account reconnect upstream (5-1440 minutes),
+ * or null to turn rolling reconnects off
+ */
+export async function setAccountReconnectInterval(
+  accountId: number,
+  minutes: number | null
+): Promise<Account> {
+  return invoke<Account>('set_account_reconnect_interval', { accountId, minutes });
+}
+
 /** Response type for test_connection command */
 export interface TestConnectionResponse {
   success: boolean;
@@ -115,6 +176,46 @@ export async function testConnection(accountId: number): Promise<TestConnectionR
   return invoke<TestConnectionResponse>('test_connection', { accountId });
 }
 
+/** Subscription expiry of an Xtream account */
+export interface AccountExpiryInfo {
+  accountId: number;
+  accountName: string;
+  /** ISO 8601; null if the provider reports none or it was never checked */
+  expiryDate: string | null;
+  /** Whole days left, negative once expired */
+  daysRemaining: number | null;
+  expired: boolean;
+  /** Ends within the warning window (setting account_expiry_warning_days) */
+  expiringSoon: boolean;
+  maxConnections: number | null;
+  lastCheck: string | null;
+}
+
+/**
+ * Get the expiry date and connection limit of every active Xtream account,
+ * as last reported by a connection test or channel scan
+ */
+export async function getAccountExpiryInfo(): Promise<AccountExpiryInfo[]> {
+  return invoke<AccountExpiryInfo[]>('get_account_expiry_info');
+}
+
+/** Result of reading back one account's stored password */
+export interface CredentialCheck {
+  accountId: number;
+  accountName: string;
+  ok: boolean;
+  error: string | null;
+}
+
+/**
+ * Check that every Xtream account's stored password can still be read
+ * (e.g. the keychain entry was not deleted). Broken accounts get
+ * `credentialError` set until a new password is saved.
+ */
+export async function verifyCredentials(): Promise<CredentialCheck[]> {
+  return invoke<CredentialCheck[]>('verify_credentials');
+}
+
 // Channel types and functions
 
 /** Channel response type */
@@ -187,6 +288,23 @@ export interface XmltvSource {
   isActive: boolean;
   createdAt: string;
   updatedAt: string;
+  /** HTTP basic auth user (the password is never returned) */
+  authUsername?: string | null;
+  hasAuthPassword: boolean;
+  /** Custom request header name (the value is never returned) */
+  authHeaderName?: string | null;
+  hasAuthHeaderValue: boolean;
+}
+
+/**
+ * Authentication for an XMLTV source. Empty names clear basic auth or the
+ * header; an omitted password or header value keeps the stored secret.
+ */
+export interface XmltvSourceAuth {
+  username?: string;
+  password?: string;
+  headerName?: string;
+  headerValue?: string;
 }
 
 /** Request type for adding a new XMLTV source */
@@ -247,6 +365,14 @@ export async function deleteXmltvSource(sourceId: number): Promise<void> {
   return invoke<void>('delete_xmltv_source', { sourceId });
 }
 
+/** Set the basic auth and/or custom header sent when downloading a source */
+export async function setXmltvSourceAuth(
+  sourceId: number,
+  auth: XmltvSourceAuth
+): Promise<XmltvSource> {
+  return invoke<XmltvSource>('set_xmltv_source_auth', { sourceId, auth });
+}
+
 /**
  * Toggle XMLTV source active state
  * @param sourceId - Source ID to toggle
@@ -307,6 +433,9 @@ export interface Program {
   category?: string;
   episodeInfo?: string;
   createdAt: string;
+  subTitle?: string;
+  /** Program artwork URL */
+  icon?: string;
 }
 
 /**
@@ -320,11 +449,63 @@ export async function refreshEpgSource(sourceId: number): Promise<void> {
 
 /**
  * Refresh EPG data for all active sources
+ *
+ * Sources are refreshed concurrently; subscribe with onEpgRefreshProgress
+ * for per-source progress.
  */
 export async function refreshAllEpgSources(): Promise<void> {
   return invoke<void>('refresh_all_epg_sources');
 }
 
+/** Stage of a source during an EPG refresh */
+export type EpgRefreshStage = 'queued' | 'fetching' | 'parsing' | 'storing' | 'completed' | 'failed';
+
+/** Per-source progress emitted during an EPG refresh */
+export interface EpgRefreshProgress {
+  sourceId: number;
+  sourceName: string;
+  stage: EpgRefreshStage;
+  /** Set once the source is stored */
+  channelCount: number | null;
+  programCount: number | null;
+  /** Set when the stage is 'failed' */
+  error: string | null;
+  /** Sources finished (completed or failed) in this refresh run */
+  finished: number;
+  total: number;
+}
+
+/**
+ * Subscribe to EPG refresh progress events
+ *
+ * @returns Function that removes the listener
+ */
+export async function onEpgRefreshProgress(
+  handler: (progress: EpgRefreshProgress) => void
+): Promise<UnlistenFn> {
+  return listen<EpgRefreshProgress>('epg-refresh-progress', (event) => handler(event.payload));
+}
+
+/** Emitted when `/epg.xml` and `/playlist.m3u` were regenerated after an EPG refresh */
+export interface CachePrimeResult {
+  trigger: 'scheduled' | 'manual';
+  epgBytes: number;
+  playlistBytes: number;
+  durationMs: number;
+  error: string | null;
+}
+
+/**
+ * Subscribe to output cache priming events
+ *
+ * @returns Function that removes the listener
+ */
+export async function onOutputCachePrimed(
+  handler: (result: CachePrimeResult) => void
+): Promise<UnlistenFn> {
+  return listen<CachePrimeResult>('output-cache-primed', (event) => handler(event.payload));
+}
+
 /**
  * Get EPG statistics for a source
  * @param sourceId - Source ID to get stats for
@@ -385,6 +566,124 @@ export async function setEpgSchedule(
   return invoke<EpgSchedule>('set_epg_schedule', { hour, minute, enabled });
 }
 
+/** EPG retention settings */
+export interface EpgRetention {
+  /** Days programs are kept after they end (0 = keep forever) */
+  retentionDays: number;
+  /** Days aired programs are kept in the guide history (0 = no history) */
+  historyDays: number;
+  lastPrunedAt: string | null;
+  lastVacuumAt: string | null;
+}
+
+/** Get the EPG retention settings */
+export async function getEpgRetention(): Promise<EpgRetention> {
+  return invoke<EpgRetention>('get_epg_retention');
+}
+
+/**
+ * Set how many days programs are kept after they end (0 = keep forever)
+ *
+ * Old programs are pruned once a day; the database is vacuumed weekly in the
+ * maintenance window.
+ */
+export async function setEpgRetention(retentionDays: number): Promise<EpgRetention> {
+  return invoke<EpgRetention>('set_epg_retention', { retentionDays });
+}
+
+/** Set how many days aired programs are kept in the guide history (0 = off) */
+export async function setEpgHistory(historyDays: number): Promise<EpgRetention> {
+  return invoke<EpgRetention>('set_epg_history', { historyDays });
+}
+
+/** Get how many days of programs are generated into /epg.xml */
+export async function getEpgWindow(): Promise<number> {
+  return invoke<number>('get_epg_window');
+}
+
+/**
+ * Set how many days of programs are generated into /epg.xml (1-14)
+ *
+ * A shorter window keeps Plex guide refreshes fast.
+ */
+export async function setEpgWindow(windowDays: number): Promise<number> {
+  return invoke<number>('set_epg_window', { windowDays });
+}
+
+/** Get the lineup's preferred program languages, most preferred first */
+export async function getEpgLanguages(): Promise<string[]> {
+  return invoke<string[]>('get_epg_languages');
+}
+
+/**
+ * Set the lineup's preferred program languages (e.g. ['de', 'en'])
+ *
+ * /epg.xml uses the first language each title and description is available
+ * in, falling back to the guide's own text. Returns the normalized list.
+ */
+export async function setEpgLanguages(languages: string[]): Promise<string[]> {
+  return invoke<string[]>('set_epg_languages', { languages });
+}
+
+/** Placeholder programs for channels without guide data */
+export interface PlaceholderEpgConfig {
+  /** Fill empty guides of enabled channels (synthetic channels always get placeholders) */
+  enabled: boolean;
+  blockMinutes: number;
+  /** `{channel}` is replaced with the channel name */
+  titleTemplate: string;
+  descriptionTemplate: string;
+  category: string | null;
+}
+
+export async function getPlaceholderEpg(): Promise<PlaceholderEpgConfig> {
+  return invoke<PlaceholderEpgConfig>('get_placeholder_epg');
+}
+
+export async function setPlaceholderEpg(config: PlaceholderEpgConfig): Promise<PlaceholderEpgConfig> {
+  return invoke<PlaceholderEpgConfig>('set_placeholder_epg', { config });
+}
+
+/**
+ * Override placeholder programs for one channel
+ *
+ * `mode` null follows the global setting; `title` null uses the global template.
+ */
+export async function setChannelPlaceholder(
+  channelId: number,
+  mode: boolean | null,
+  title: string | null
+): Promise<void> {
+  return invoke<void>('set_channel_placeholder', { channelId, mode, title });
+}
+
+/** A program that aired (or is airing) on a channel */
+export interface GuideHistoryEntry {
+  sourceId: number;
+  /** XMLTV channel id */
+  channelId: string;
+  channelName: string;
+  title: string;
+  subTitle: string | null;
+  description: string | null;
+  startTime: string;
+  endTime: string;
+  category: string | null;
+  episodeInfo: string | null;
+  /** False while the program is still in the current guide */
+  archived: boolean;
+}
+
+/**
+ * Get what was on a channel during a UTC day
+ *
+ * @param channel - XMLTV channel id or channel name
+ * @param date - Day as YYYY-MM-DD (default today)
+ */
+export async function getEpgHistory(channel: string, date?: string): Promise<GuideHistoryEntry[]> {
+  return invoke<GuideHistoryEntry[]>('get_epg_history', { channel, date: date ?? null });
+}
+
 /**
  * Format schedule time for display
  * @param hour - Hour (0-23)
@@ -566,6 +865,236 @@ export async function setMatchThreshold(threshold: number): Promise<void> {
   return invoke<void>('set_match_threshold', { threshold });
 }
 
+/** Kind of user-defined match rule */
+export type MatchRuleType = 'strip_prefix' | 'strip_suffix' | 'regex' | 'alias';
+
+/** User-defined name rule applied while normalizing channel names for matching */
+export interface MatchRule {
+  id: number;
+  ruleType: MatchRuleType;
+  pattern: string;
+  replacement: string;
+  isEnabled: number;
+  sortOrder: number;
+  createdAt: string;
+  updatedAt: string;
+}
+
+/** Match rule as entered by the user */
+export interface MatchRuleInput {
+  ruleType: MatchRuleType;
+  /** Text to strip, regular expression, or name to alias */
+  pattern: string;
+  /** Regex replacement, or the other name of an alias */
+  replacement?: string;
+  isEnabled?: boolean;
+  sortOrder?: number;
+}
+
+/** A sample name normalized with and without match rules */
+export interface MatchRuleTestResult {
+  name: string;
+  /** Built-in normalization only */
+  builtin: string;
+  /** With the saved, enabled rules */
+  current: string;
+  /** With the saved rules plus the rule under test */
+  withRule: string | null;
+  appliedRuleIds: number[];
+}
+
+/**
+ * List the match rules in the order they apply
+ */
+export async function listMatchRules(): Promise<MatchRule[]> {
+  return invoke<MatchRule[]>('list_match_rules');
+}
+
+/**
+ * Add a match rule (takes effect on the next matching run)
+ */
+export async function addMatchRule(rule: MatchRuleInput): Promise<MatchRule> {
+  return invoke<MatchRule>('add_match_rule', { rule });
+}
+
+/**
+ * Replace a match rule
+ */
+export async function updateMatchRule(ruleId: number, rule: MatchRuleInput): Promise<MatchRule> {
+  return invoke<MatchRule>('update_match_rule', { ruleId, rule });
+}
+
+/**
+ * Delete a match rule
+ */
+export async function deleteMatchRule(ruleId: number): Promise<void> {
+  return invoke<void>('delete_match_rule', { ruleId });
+}
+
+/**
+ * Normalize sample names with the saved rules and, optionally, a rule being edited
+ * @param sampleNames - Channel names to try (at most 500)
+ * @param rule - Rule to try alongside the saved rules
+ * @param ruleId - Saved rule that `rule` replaces, when editing
+ */
+export async function testMatchRule(
+  sampleNames: string[],
+  rule?: MatchRuleInput,
+  ruleId?: number
+): Promise<MatchRuleTestResult[]> {
+  return invoke<MatchRuleTestResult[]>('test_match_rule', { sampleNames, rule, ruleId });
+}
+
+/** String similarity algorithm used to compare channel names */
+export type MatchAlgorithm = 'jaro_winkler' | 'levenshtein' | 'sorensen_dice';
+
+/**
+ * Get the string similarity algorithm used for matching
+ */
+export async function getMatchAlgorithm(): Promise<MatchAlgorithm> {
+  return invoke<MatchAlgorithm>('get_match_algorithm');
+}
+
+/**
+ * Set the string similarity algorithm used for matching
+ * Takes effect on the next matching run.
+ */
+export async function setMatchAlgorithm(algorithm: MatchAlgorithm): Promise<void> {
+  return invoke<void>('set_match_algorithm', { algorithm });
+}
+
+/** Matching settings to try instead of the saved ones (unset = saved value) */
+export interface MatchConfigOverride {
+  threshold?: number;
+  epgIdBoost?: number;
+  exactNameBoost?: number;
+  iconBoost?: number;
+  iconMaxDistance?: number;
+  algorithm?: MatchAlgorithm;
+  regionBoost?: number;
+  regionPenalty?: number;
+}
+
+/** A stream in a simulated change */
+export interface SimulatedStream {
+  xtreamChannelId: number;
+  name: string;
+  confidence: number | null;
+  /** Region (ISO country code) detected from the stream name or category */
+  region: string | null;
+}
+
+/** A channel whose automatic mappings would change */
+export interface SimulatedChannelChange {
+  xmltvChannelId: number;
+  displayName: string;
+  /** Region (ISO country code) detected from the channel ID or name */
+  region: string | null;
+  currentPrimary: SimulatedStream | null;
+  simulatedPrimary: SimulatedStream | null;
+  added: SimulatedStream[];
+  removed: SimulatedStream[];
+  /** Manual mappings are kept by a matching run */
+  hasManual: boolean;
+}
+
+/** Outcome of matching with trial settings */
+export interface MatchSimulation {
+  threshold: number;
+  algorithm: MatchAlgorithm;
+  stats: MatchStats;
+  currentlyMatched: number;
+  gained: number;
+  lost: number;
+  mappingsAdded: number;
+  mappingsRemoved: number;
+  changes: SimulatedChannelChange[];
+}
+
+/**
+ * Match with trial settings without saving anything
+ * @param configOverride - Settings to try; unset fields use the saved settings
+ * @returns Statistics and the mappings that would change
+ */
+export async function simulateMatching(configOverride?: MatchConfigOverride): Promise<MatchSimulation> {
+  return invoke<MatchSimulation>('simulate_matching', { configOverride });
+}
+
+/** Per-account and per-source match settings (keyed by account / source ID) */
+export interface MatchProfiles {
+  accounts: Record<string, MatchConfigOverride>;
+  sources: Record<string, MatchConfigOverride>;
+}
+
+/** Where a match profile applies */
+export type MatchProfileScope = 'account' | 'source';
+
+/**
+ * Get the per-account and per-source match profiles
+ */
+export async function getMatchProfiles(): Promise<MatchProfiles> {
+  return invoke<MatchProfiles>('get_match_profiles');
+}
+
+/**
+ * Set or remove the match profile of an Xtream account or XMLTV source
+ * Unset fields fall back to the source profile (for accounts), then the global settings.
+ * @param profile - Settings to override; omit (or leave all fields unset) to remove the profile
+ */
+export async function setMatchProfile(
+  scope: MatchProfileScope,
+  id: number,
+  profile?: MatchConfigOverride
+): Promise<MatchProfiles> {
+  return invoke<MatchProfiles>('set_match_profile', { scope, id, profile });
+}
+
+/** Icon similarity as a matching signal */
+export interface IconMatchConfig {
+  enabled: boolean;
+  /** Boost added to the match score when the icons look the same */
+  boost: number;
+  /** Most differing hash bits (of 64) for icons to count as the same */
+  maxDistance: number;
+}
+
+/**
+ * Get the icon similarity matching settings
+ */
+export async function getIconMatchConfig(): Promise<IconMatchConfig> {
+  return invoke<IconMatchConfig>('get_icon_match_config');
+}
+
+/**
+ * Save the icon similarity matching settings
+ */
+export async function setIconMatchConfig(config: IconMatchConfig): Promise<IconMatchConfig> {
+  return invoke<IconMatchConfig>('set_icon_match_config', { config });
+}
+
+/** Boost same-country and penalize cross-country matches ("UK| Sky News" vs "US| Sky News") */
+export interface RegionMatchConfig {
+  enabled: boolean;
+  /** Added to the match score when the regions are the same */
+  boost: number;
+  /** Subtracted from the match score when the regions differ */
+  penalty: number;
+}
+
+/**
+ * Get the region-aware matching settings
+ */
+export async function getRegionMatchConfig(): Promise<RegionMatchConfig> {
+  return invoke<RegionMatchConfig>('get_region_match_config');
+}
+
+/**
+ * Save the region-aware matching settings
+ */
+export async function setRegionMatchConfig(config: RegionMatchConfig): Promise<RegionMatchConfig> {
+  return invoke<RegionMatchConfig>('set_region_match_config', { config });
+}
+
 /**
  * Normalize a channel name (for testing/debugging)
  * @param name - Channel name to normalize
@@ -659,6 +1188,8 @@ export interface XmltvChannelWithMappings {
   // Settings
   isEnabled: boolean;
   plexDisplayOrder: number | null;
+  /** Persistent channel number (null falls back to the lineup position) */
+  channelNumber: number | null;
   // Matches
   matchCount: number;
   matches: XtreamStreamMatch[];
@@ -867,6 +1398,37 @@ export function formatScanRematchSummary(response: ScanAndRematchResponse): stri
   return `Scanned ${response.totalChannels} channels. ${response.newMatches} ${matchText}, ${response.removedMatches} removed, ${response.updatedMatches} updated.`;
 }
 
+/** Schedule for re-scanning all active accounts automatically */
+export interface ChannelRescanSchedule {
+  enabled: boolean;
+  /** Local time of day (0-23 / 0-59) */
+  hour: number;
+  minute: number;
+  /** Days between re-scans (1 = nightly, max 30) */
+  intervalDays: number;
+}
+
+export interface ChannelRescanScheduleResponse extends ChannelRescanSchedule {
+  lastScheduledRescan: string | null;
+}
+
+/** Get the channel re-scan schedule */
+export async function getChannelRescanSchedule(): Promise<ChannelRescanScheduleResponse> {
+  return invoke<ChannelRescanScheduleResponse>('get_channel_rescan_schedule');
+}
+
+/**
+ * Set the channel re-scan schedule
+ *
+ * Scheduled re-scans run scan and rematch for every active account and wait
+ * for the maintenance window.
+ */
+export async function setChannelRescanSchedule(
+  schedule: ChannelRescanSchedule
+): Promise<ChannelRescanScheduleResponse> {
+  return invoke<ChannelRescanScheduleResponse>('set_channel_rescan_schedule', { schedule });
+}
+
 // Event Log types
 
 /** Event log level */
@@ -893,6 +1455,36 @@ export interface EventLogResponse {
   unreadCount: number;
 }
 
+/** Filters and page position for searchEvents */
+export interface EventSearch {
+  level?: EventLevel;
+  category?: string;
+  unreadOnly?: boolean;
+  createdAfter?: string;  // ISO 8601
+  createdBefore?: string; // ISO 8601
+  /** Text to find in the message or details (case-insensitive) */
+  query?: string;
+  /** nextCursor of the previous page; omit for the first page */
+  cursor?: string;
+  limit?: number;
+}
+
+/** Event counts per level and category (each ignores its own filter) */
+export interface EventCounts {
+  byLevel: Record<string, number>;
+  byCategory: Record<string, number>;
+}
+
+/** One page of searchEvents results */
+export interface EventSearchResponse {
+  events: EventLogEntry[];
+  /** Pass as cursor to load the next page; null on the last page */
+  nextCursor: string | null;
+  totalCount: number;
+  unreadCount: number;
+  counts: EventCounts;
+}
+
 /** Log event input */
 export interface LogEventInput {
   level: EventLevel;
@@ -943,6 +1535,15 @@ export async function getEvents(options?: {
   });
 }
 
+/**
+ * Search the event log, newest first, one page at a time
+ * @param search - Filters, free-text query and the cursor of the previous page
+ * @returns One page of events with totals and per-level/category counts
+ */
+export async function searchEvents(search: EventSearch = {}): Promise<EventSearchResponse> {
+  return invoke<EventSearchResponse>('search_events', { search });
+}
+
 /**
  * Get the count of unread events
  * @returns Number of unread events
@@ -951,6 +1552,37 @@ export async function getUnreadEventCount(): Promise<number> {
   return invoke<number>('get_unread_event_count');
 }
 
+/** An error-level event kept in memory */
+export interface RecentError {
+  category: string;
+  message: string;
+  timestamp: string;
+  /** Whether the event also made it into the event log */
+  persisted: boolean;
+}
+
+/**
+ * Get the latest errors, newest first (no database query)
+ */
+export async function getRecentErrors(): Promise<RecentError[]> {
+  return invoke<RecentError[]>('get_recent_errors');
+}
+
+/**
+ * Forget the recent errors and reset the tray tooltip (the event log keeps them)
+ */
+export async function clearRecentErrors(): Promise<void> {
+  return invoke<void>('clear_recent_errors');
+}
+
+/**
+ * Listen for new errors
+ * @param handler - Called with each error as it is recorded
+ */
+export async function onRecentError(handler: (error: RecentError) => void): Promise<UnlistenFn> {
+  return listen<RecentError>('recent-error', (event) => handler(event.payload));
+}
+
 /**
  * Mark an event as read
  * @param eventId - Event ID to mark as read
@@ -1158,6 +1790,8 @@ export interface TargetLineupChannel {
   streamCount: number;
   /** Display order in Plex lineup */
   plexDisplayOrder: number | null;
+  /** Persistent channel number (null falls back to the lineup position) */
+  channelNumber: number | null;
 }
 
 /**
@@ -1337,11 +1971,13 @@ export function getLinkStatusLabel(status: LinkStatus): string {
 export interface PlexConfig {
   /** Whether the HTTP server is running and accepting connections */
   server_running: boolean;
-  /** Local network IP address */
+  /** Host used in the URLs (local network IP when bound to all interfaces) */
   local_ip: string;
+  /** Address the server binds to */
+  bind_address: string;
   /** Server port (default 5004) */
   port: number;
-  /** M3U Playlist URL for Plex tuner configuration */
+  /** M3U Playlist URL for Plex tuner configuration (includes the access token, if set) */
   m3u_url: string;
   /** EPG/XMLTV URL for Plex guide data */
   epg_url: string;
@@ -1612,25 +2248,376 @@ export async function setServerPort(port: number): Promise<void> {
   return invoke<void>('set_server_port', { port });
 }
 
+/** How the emulated HDHomeRun tuner identifies itself to Plex */
+export interface HdhrIdentity {
+  /** Stored once and kept across restarts so Plex recognizes the tuner */
+  deviceId: string;
+  friendlyName: string;
+  modelNumber: string;
+}
+
+/** Get the HDHomeRun device identity */
+export async function getHdhrIdentity(): Promise<HdhrIdentity> {
+  return invoke<HdhrIdentity>('get_hdhr_identity');
+}
+
 /**
- * Restart the HTTP server on the new port
- *
- * Story 6.1: Settings GUI for Server and Startup Options
- * Task 2.3: TypeScript binding for restartServer
- *
- * Stops the current server and restarts it on the configured port.
- *
- * @returns Promise that resolves when server has restarted
+ * Set the advertised FriendlyName and ModelNumber
+ * Empty values restore the defaults ("StreamForge" / "HDHR5-4K")
  */
-export async function restartServer(): Promise<void> {
-  return invoke<void>('restart_server');
+export async function setHdhrIdentity(
+  friendlyName: string,
+  modelNumber: string
+): Promise<HdhrIdentity> {
+  return invoke<HdhrIdentity>('set_hdhr_identity', { friendlyName, modelNumber });
 }
 
-// ============================================================================
-// Configuration Export/Import (Story 6-2)
-// ============================================================================
+/** Whether the tuners are announced on the LAN over SSDP */
+export async function getSsdpEnabled(): Promise<boolean> {
+  return invoke<boolean>('get_ssdp_enabled');
+}
+
+/**
+ * Announce the tuners on the LAN over SSDP so Plex finds them by itself
+ * Takes effect within half a minute
+ */
+export async function setSsdpEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>('set_ssdp_enabled', { enabled });
+}
+
+/**
+ * An extra HDHomeRun tuner with a subset of the lineup,
+ * served under /device/{slug}/
+ */
+export interface DeviceProfile {
+  id: number;
+  slug: string;
+  /** FriendlyName shown in Plex */
+  name: string;
+  deviceId: string;
+  /** Advertised tuner count (null: same as the main device) */
+  tunerCount: number | null;
+  /** Container of the stream URLs in the profile's playlist and lineup */
+  streamFormat: 'ts' | 'm3u8';
+  /** XMLTV channel IDs in the profile */
+  channelIds: number[];
+}
+
+/** List the device profiles */
+export async function listDeviceProfiles(): Promise<DeviceProfile[]> {
+  return invoke<DeviceProfile[]>('list_device_profiles');
+}
+
+/**
+ * Create a device profile
+ * The slug defaults to one made from the name, the stream format to 'ts'
+ */
+export async function createDeviceProfile(
+  name: string,
+  slug: string | null,
+  tunerCount: number | null,
+  streamFormat: 'ts' | 'm3u8' | null = null
+): Promise<DeviceProfile> {
+  return invoke<DeviceProfile>('create_device_profile', { name, slug, tunerCount, streamFormat });
+}
+
+/** Rename a device profile or change its tuner count or stream format */
+export async function updateDeviceProfile(
+  id: number,
+  name: string,
+  tunerCount: number | null,
+  streamFormat: 'ts' | 'm3u8' | null = null
+): Promise<DeviceProfile> {
+  return invoke<DeviceProfile>('update_device_profile', { id, name, tunerCount, streamFormat });
+}
+
+/** Delete a device profile */
+export async function deleteDeviceProfile(id: number): Promise<void> {
+  return invoke<void>('delete_device_profile', { id });
+}
+
+/** Replace the channels of a device profile */
+export async function setDeviceProfileChannels(
+  id: number,
+  channelIds: number[]
+): Promise<DeviceProfile> {
+  return invoke<DeviceProfile>('set_device_profile_channels', { id, channelIds });
+}
+
+/**
+ * Get the access token required on playlist, guide and stream URLs
+ * @returns The token, or null while the endpoints are open
+ */
+export async function getAccessToken(): Promise<string | null> {
+  return invoke<string | null>('get_access_token');
+}
+
+/**
+ * Generate a new access token (enables token checks)
+ * URLs configured in Plex with the previous token stop working
+ */
+export async function rotateAccessToken(): Promise<string> {
+  return invoke<string>('rotate_access_token');
+}
+
+/** Remove the access token, leaving the endpoints open */
+export async function clearAccessToken(): Promise<void> {
+  return invoke<void>('clear_access_token');
+}
+
+/** A language backend messages are available in */
+export interface LanguageOption {
+  /** Language code, e.g. "es" */
+  code: string;
+  /** Native language name, e.g. "Español" */
+  name: string;
+}
+
+/** Get the language of backend messages (errors and event log) */
+export async function getLanguage(): Promise<string> {
+  return invoke<string>('get_language');
+}
+
+/** List the languages backend messages are available in */
+export async function getSupportedLanguages(): Promise<LanguageOption[]> {
+  return invoke<LanguageOption[]>('get_supported_languages');
+}
+
+/** Set the language of backend messages (applies to new messages) */
+export async function setLanguage(language: string): Promise<void> {
+  return invoke<void>('set_language', { language });
+}
+
+/** A bind address offered in the server settings */
+export interface BindAddressOption {
+  address: string;
+  label: string;
+}
+
+/** Get the address the HTTP server binds to */
+export async function getBindAddress(): Promise<string> {
+  return invoke<string>('get_bind_address');
+}
+
+/** List all interfaces, loopback, and each local network interface address */
+export async function getBindAddressOptions(): Promise<BindAddressOption[]> {
+  return invoke<BindAddressOption[]>('get_bind_address_options');
+}
+
+/**
+ * Set the address the HTTP server binds to
+ * Takes effect after restartServer()
+ */
+export async function setBindAddress(address: string): Promise<void> {
+  return invoke<void>('set_bind_address', { address });
+}
+
+/**
+ * Get whether streams are padded with MPEG-TS null packets
+ *
+ * Padding keeps clients connected while FFmpeg starts and during failover.
+ */
+export async function getStreamTsPadding(): Promise<boolean> {
+  return invoke<boolean>('get_stream_ts_padding');
+}
+
+/** Enable or disable MPEG-TS null packet padding for new streams */
+export async function setStreamTsPadding(enabled: boolean): Promise<void> {
+  return invoke<void>('set_stream_ts_padding', { enabled });
+}
+
+/**
+ * Get whether streams stay continuous across failover splices
+ *
+ * Continuity counters and timestamps are rewritten after an upstream switch so seeking in Plex's live buffer keeps working.
+ */
+export async function getStreamTsContinuity(): Promise<boolean> {
+  return invoke<boolean>('get_stream_ts_continuity');
+}
+
+/** Enable or disable MPEG-TS continuity rewriting for new streams */
+export async function setStreamTsContinuity(enabled: boolean): Promise<void> {
+  return invoke<void>('set_stream_ts_continuity', { enabled });
+}
+
+/** Stream quality cap: 'best' for no cap, otherwise the highest tier streamed */
+export type PreferredQuality = 'best' | '4K' | 'FHD' | 'HD' | 'SD';
+
+/** Get the highest quality streams are played at */
+export async function getStreamPreferredQuality(): Promise<PreferredQuality> {
+  return invoke<PreferredQuality>('get_stream_preferred_quality');
+}
+
+/**
+ * Cap the quality of new streams
+ *
+ * Streams within the cap are tried first and the highest quality at or below it is selected.
+ */
+export async function setStreamPreferredQuality(quality: PreferredQuality): Promise<void> {
+  return invoke<void>('set_stream_preferred_quality', { quality });
+}
+
+/** Cap the stream quality of one channel (null follows the global setting) */
+export async function setChannelPreferredQuality(
+  channelId: number,
+  quality: PreferredQuality | null
+): Promise<void> {
+  return invoke<void>('set_channel_preferred_quality', { channelId, quality });
+}
+
+/** Get the configured FFmpeg path (null when `ffmpeg` is taken from PATH) */
+export async function getFfmpegPath(): Promise<string | null> {
+  return invoke<string | null>('get_ffmpeg_path');
+}
+
+/**
+ * Set the FFmpeg binary used to remux streams to MPEG-TS
+ *
+ * @param path - Path to the binary; null or empty uses `ffmpeg` from PATH.
+ * Fails if the binary does not run.
+ */
+export async function setFfmpegPath(path: string | null): Promise<void> {
+  return invoke<void>('set_ffmpeg_path', { path });
+}
+
+/** Time window in which disruptive scheduled jobs may run */
+export interface MaintenanceWindow {
+  enabled: boolean;
+  /** Local start time, "HH:MM" */
+  start: string;
+  /** Local end time, "HH:MM" (before start for windows spanning midnight) */
+  end: string;
+}
+
+/** Whether disruptive jobs may run right now */
+export interface MaintenanceStatus {
+  enabled: boolean;
+  inWindow: boolean;
+  activeStreams: number;
+  canRun: boolean;
+}
+
+/** Get the maintenance window for disruptive scheduled jobs */
+export async function getMaintenanceWindow(): Promise<MaintenanceWindow> {
+  return invoke<MaintenanceWindow>('get_maintenance_window');
+}
+
+/**
+ * Set the maintenance window for disruptive scheduled jobs
+ *
+ * Scheduled jobs wait until the window is open and no streams are active.
+ */
+export async function setMaintenanceWindow(window: MaintenanceWindow): Promise<void> {
+  return invoke<void>('set_maintenance_window', { window });
+}
+
+/** Get whether the maintenance window is open and streams are idle */
+export async function getMaintenanceStatus(): Promise<MaintenanceStatus> {
+  return invoke<MaintenanceStatus>('get_maintenance_status');
+}
+
+/** Outcome of an HTTP server restart */
+export interface ServerRestartResult {
+  /** Port the previous server listened on (null if it was not running) */
+  previousPort: number | null;
+  port: number;
+  /** Stream sessions stopped by the restart */
+  stoppedStreams: number;
+  /** Whether connections had to be closed after the drain timeout */
+  forced: boolean;
+}
+
+/**
+ * Restart the HTTP server on the new port
+ *
+ * Story 6.1: Settings GUI for Server and Startup Options
+ * Task 2.3: TypeScript binding for restartServer
+ *
+ * Stops the current server (active streams are stopped) and restarts it on
+ * the configured port. If the new port cannot be bound the server keeps
+ * running on the old port and the promise rejects.
+ *
+ * @returns Promise that resolves when server has restarted
+ */
+export async function restartServer(): Promise<ServerRestartResult> {
+  return invoke<ServerRestartResult>('restart_server');
+}
+
+/** An active stream session */
+export interface ActiveStream {
+  sessionId: string;
+  channelId: number;
+  channelName: string;
+  accountId: number | null;
+  accountName: string | null;
+  quality: string;
+  uptimeSeconds: number;
+  /** Bytes sent to the client so far */
+  bytesTransferred: number;
+  /** Address of the client (e.g. the Plex server) receiving the stream */
+  clientIp: string | null;
+  failoverCount: number;
+  /** Priority the stream was admitted with */
+  priority: StreamPriority;
+}
+
+/**
+ * List active stream sessions
+ *
+ * The same list is served as server-sent events (`streams` events) at
+ * `/status/streams` on the HTTP server.
+ */
+export async function getActiveStreams(): Promise<ActiveStream[]> {
+  return invoke<ActiveStream[]>('get_active_streams');
+}
+
+/** Period stream statistics are grouped by */
+export type StatsPeriod = 'day' | 'week';
+
+/** Traffic of one channel and account in one day or week */
+export interface StreamStatsBucket {
+  /** First day of the period (YYYY-MM-DD, weeks start on Monday) */
+  periodStart: string;
+  channelId: number;
+  /** null if the channel no longer exists */
+  channelName: string | null;
+  accountId: number | null;
+  accountName: string | null;
+  sessions: number;
+  bytes: number;
+  durationSeconds: number;
+}
+
+/**
+ * Get traffic per channel and account for the last days or weeks
+ *
+ * @param period - 'day' or 'week'
+ * @param count - Number of periods including the current one (default 30 days / 12 weeks)
+ */
+export async function getStreamStats(
+  period: StatsPeriod,
+  count?: number
+): Promise<StreamStatsBucket[]> {
+  return invoke<StreamStatsBucket[]>('get_stream_stats', { period, count: count ?? null });
+}
+
+// ============================================================================
+// Configuration Export/Import (Story 6-2)
+// ============================================================================
 
 /** Import preview response type */
+/**
+ * Integrity check of a configuration file: missing (older export), verified
+ * hash, signed by this or another installation, or failed (not importable)
+ */
+export type ConfigIntegrityStatus =
+  | 'missing'
+  | 'verified'
+  | 'signedHere'
+  | 'signedElsewhere'
+  | 'hashMismatch'
+  | 'invalidSignature';
+
 export interface ImportPreview {
   valid: boolean;
   version: string;
@@ -1641,6 +2628,70 @@ export interface ImportPreview {
   xmltvChannelSettingsCount: number;
   settingsSummary: string[];
   errorMessage?: string;
+  /** Result of checking the file's integrity hash and signature */
+  integrity: ConfigIntegrityStatus;
+  /** Integrity problem worth confirming before import (valid files only) */
+  integrityWarning?: string;
+  /** Dry-run comparison with the current database (valid files only) */
+  conflicts?: ImportConflicts;
+  /** Whether the file carries passphrase-sealed account passwords */
+  hasCredentials: boolean;
+}
+
+/** An imported item that collides with an existing one */
+export interface ImportCollision {
+  name: string;
+  url: string;
+  /** Name of the existing item it replaces */
+  existingName: string;
+}
+
+/** A setting whose value the import would change */
+export interface SettingChange {
+  key: string;
+  currentValue?: string;
+  importedValue?: string;
+}
+
+/** A channel mapping in the file that cannot be resolved */
+export interface UnresolvedMapping {
+  xmltvChannelId: number;
+  xtreamChannelId: number;
+  reason: string;
+}
+
+/** What an import would change in the current database */
+export interface ImportConflicts {
+  existingAccounts: ImportCollision[];
+  /** Existing accounts deleted by the import */
+  removedAccounts: string[];
+  existingXmltvSources: ImportCollision[];
+  /** Existing XMLTV sources deleted by the import */
+  removedXmltvSources: string[];
+  settingChanges: SettingChange[];
+  /** Settings not included in exports (reset to defaults) */
+  settingsReset: string[];
+  unresolvedMappings: UnresolvedMapping[];
+  /** Portable mappings whose EPG source or account is not in the file */
+  unresolvedPortableMappings: UnresolvedPortableMapping[];
+}
+
+/** A portable mapping that cannot be linked to a channel and stream */
+export interface UnresolvedPortableMapping {
+  /** XMLTV channel id attribute */
+  channelId: string;
+  /** Provider stream ID */
+  streamId: number;
+  reason: string;
+}
+
+/** Result of re-linking imported mappings */
+export interface MappingResolution {
+  resolved: number;
+  /** Mappings still pending (channel or stream not fetched yet) */
+  unresolved: UnresolvedPortableMapping[];
+  /** Channel numbers set again */
+  numbersRestored: number;
 }
 
 /** Import result response type */
@@ -1650,6 +2701,8 @@ export interface ImportResult {
   xmltvSourcesImported: number;
   channelMappingsImported: number;
   settingsImported: number;
+  /** Account passwords restored from the file */
+  credentialsRestored: number;
   message: string;
 }
 
@@ -1660,12 +2713,14 @@ export interface ImportResult {
  * Task 3.1: TypeScript binding for exportConfiguration
  *
  * Returns the complete configuration as a JSON string that can be saved to a file.
- * SECURITY: Passwords are NOT included in the export.
+ * SECURITY: Passwords are NOT included in the export, unless a passphrase is
+ * given: account passwords are then encrypted with it.
  *
+ * @param passphrase - Optional passphrase (8+ characters) to include passwords
  * @returns JSON string of the configuration export
  */
-export async function exportConfiguration(): Promise<string> {
-  return invoke<string>('export_configuration');
+export async function exportConfiguration(passphrase?: string): Promise<string> {
+  return invoke<string>('export_configuration', { passphrase: passphrase ?? null });
 }
 
 /**
@@ -1694,10 +2749,47 @@ export async function validateImportFile(content: string): Promise<ImportPreview
  * Accounts are imported with empty passwords - user must re-enter.
  *
  * @param content - JSON content of the configuration file
+ * @param passphrase - Passphrase of a file that includes passwords
  * @returns Result of the import operation
  */
-export async function importConfiguration(content: string): Promise<ImportResult> {
-  return invoke<ImportResult>('import_configuration', { content });
+export async function importConfiguration(
+  content: string,
+  passphrase?: string
+): Promise<ImportResult> {
+  return invoke<ImportResult>('import_configuration', {
+    content,
+    passphrase: passphrase ?? null,
+  });
+}
+
+/**
+ * Link imported channel mappings to the channels and streams fetched since
+ *
+ * Mappings whose channel or stream is not there yet stay pending, so this can
+ * be run again after the next refresh.
+ */
+export async function resolveImportedMappings(): Promise<MappingResolution> {
+  return invoke<MappingResolution>('resolve_imported_mappings');
+}
+
+/** Outcome of re-encrypting stored credentials */
+export interface CredentialMigration {
+  /** Secrets rewritten with the current scheme (or new key) */
+  migrated: number;
+  /** Secrets already current, or kept in the OS keychain */
+  unchanged: number;
+  /** Secrets that could not be decrypted, with the reason */
+  failed: string[];
+  rotated: boolean;
+}
+
+/**
+ * Re-encrypt passwords stored without the OS keychain with the current scheme
+ *
+ * @param rotate - Also replace the encryption key (refused if any secret is unreadable)
+ */
+export async function migrateCredentials(rotate: boolean): Promise<CredentialMigration> {
+  return invoke<CredentialMigration>('migrate_credentials', { rotate });
 }
 
 // ============================================================================
@@ -1731,6 +2823,42 @@ export async function setLogVerbosity(verbosity: LogVerbosity): Promise<void> {
   return invoke<void>('set_log_verbosity', { verbosity });
 }
 
+/**
+ * Get the path of the current diagnostic log file
+ *
+ * Returns the log folder if no file has been written yet.
+ */
+export async function getLogFilePath(): Promise<string> {
+  return invoke<string>('get_log_file_path');
+}
+
+/**
+ * Zip the diagnostic log files into a file, e.g. for a bug report
+ *
+ * @param path - Absolute path of the .zip file to write
+ * @returns Number of log files exported
+ */
+export async function exportLogs(path: string): Promise<number> {
+  return invoke<number>('export_logs', { path });
+}
+
+/**
+ * Get the per-channel failover notification cooldown in seconds
+ *
+ * Failovers on the same channel within this window are summarized in one
+ * event ("Channel X failed over 5 times in the last hour"). 0 logs every failover.
+ */
+export async function getFailoverNotificationCooldown(): Promise<number> {
+  return invoke<number>('get_failover_notification_cooldown');
+}
+
+/**
+ * Set the per-channel failover notification cooldown in seconds (0-86400)
+ */
+export async function setFailoverNotificationCooldown(seconds: number): Promise<void> {
+  return invoke<void>('set_failover_notification_cooldown', { seconds });
+}
+
 // ============================================================================
 // Auto-Update (Story 6-5)
 // ============================================================================
@@ -1745,8 +2873,13 @@ export interface UpdateInfo {
   notes: string | null;
   /** Release date ISO string (null if no update) */
   date: string | null;
+  /** Set while the user has deferred updates (ISO 8601) */
+  deferredUntil: string | null;
 }
 
+/** Release channel to receive updates from */
+export type ReleaseChannel = 'stable' | 'beta';
+
 /** Update settings response */
 export interface UpdateSettings {
   /** Whether automatic update checks are enabled */
@@ -1755,6 +2888,14 @@ export interface UpdateSettings {
   lastCheck: string | null;
   /** Current application version */
   currentVersion: string;
+  /** Release channel */
+  channel: ReleaseChannel;
+  /** Updates are not offered automatically before this time (ISO 8601) */
+  deferredUntil: string | null;
+  /** Version pinned by the user (updates are skipped while set) */
+  pinnedVersion: string | null;
+  /** Version rollbackUpdate would reinstall */
+  rollbackVersion: string | null;
 }
 
 /**
@@ -1793,6 +2934,52 @@ export async function setAutoCheckUpdates(enabled: boolean): Promise<void> {
   return invoke<void>('set_auto_check_updates', { enabled });
 }
 
+/**
+ * Get the changelog of the available update for display before installing
+ *
+ * @returns Release notes, or null if no update is available
+ */
+export async function getUpdateChangelog(): Promise<string | null> {
+  return invoke<string | null>('get_update_changelog');
+}
+
+/**
+ * Set the release channel
+ *
+ * @param channel - "stable" or "beta"
+ */
+export async function setUpdateChannel(channel: ReleaseChannel): Promise<void> {
+  return invoke<void>('set_update_channel', { channel });
+}
+
+/**
+ * Defer update notifications
+ *
+ * @param days - Days to defer (default 7, 0 clears the deferral)
+ * @returns End of the deferral (ISO 8601), or null when cleared
+ */
+export async function deferUpdate(days?: number): Promise<string | null> {
+  return invoke<string | null>('defer_update', { days: days ?? null });
+}
+
+/**
+ * Pin or unpin the current version (updates are skipped while pinned)
+ *
+ * @returns The pinned version, or null when unpinned
+ */
+export async function setVersionPinned(pinned: boolean): Promise<string | null> {
+  return invoke<string | null>('set_version_pinned', { pinned });
+}
+
+/**
+ * Reinstall the version replaced by the last update and pin it
+ *
+ * The app restarts on most platforms once the previous version is installed.
+ */
+export async function rollbackUpdate(): Promise<void> {
+  return invoke('rollback_update');
+}
+
 /**
  * Download and install the available update
  *
@@ -1856,3 +3043,1422 @@ export function formatLastCheckTime(dateStr: string | null): string {
   return date.toLocaleDateString();
 }
 
+
+// ============================================================================
+// Channel Logo Cache
+// ============================================================================
+
+/** How channel logo URLs are emitted in an output */
+export type LogoMode = 'remote' | 'proxy';
+
+/** Logo mode settings per output */
+export interface LogoSettings {
+  /** Logo mode for the M3U playlist */
+  m3uMode: LogoMode;
+  /** Logo mode for the XMLTV EPG */
+  epgMode: LogoMode;
+}
+
+/** Result summary of a logo cache pre-warm run */
+export interface LogoPrewarmSummary {
+  /** Distinct logo URLs referenced by the lineup */
+  total: number;
+  /** Logos that were already cached and fresh */
+  alreadyCached: number;
+  /** Logos downloaded during this run */
+  fetched: number;
+  /** Logos that failed after all retries (served as placeholder) */
+  failed: number;
+  /** Recently failed logos that were skipped this run */
+  skippedFailed: number;
+}
+
+/**
+ * Get the logo mode for the M3U and EPG outputs
+ *
+ * @returns Logo mode per output ("remote" by default)
+ */
+export async function getLogoSettings(): Promise<LogoSettings> {
+  return invoke<LogoSettings>('get_logo_settings');
+}
+
+/**
+ * Set the logo mode for the M3U and EPG outputs
+ *
+ * @param m3uMode - "remote" (original URLs) or "proxy" (local /logo/{key} URLs)
+ * @param epgMode - "remote" (original URLs) or "proxy" (local /logo/{key} URLs)
+ * @returns Saved logo settings
+ */
+export async function setLogoSettings(m3uMode: LogoMode, epgMode: LogoMode): Promise<LogoSettings> {
+  return invoke<LogoSettings>('set_logo_settings', { m3uMode, epgMode });
+}
+
+/**
+ * Pre-warm the logo cache now
+ *
+ * Downloads all lineup logos with bounded concurrency and retries.
+ *
+ * @returns Summary of the pre-warm run
+ */
+export async function prewarmLogoCache(): Promise<LogoPrewarmSummary> {
+  return invoke<LogoPrewarmSummary>('prewarm_logo_cache');
+}
+
+// ============================================================================
+// Web Admin
+// ============================================================================
+
+/** Web admin status */
+export interface WebAdminStatus {
+  /** Whether a web admin password is set (UI and REST admin API enabled) */
+  enabled: boolean;
+  /** Username to log in with */
+  username: string;
+  /** URL of the web admin UI on the local network */
+  url: string;
+  /** URL of the mobile dashboard on the local network */
+  mobileUrl: string;
+}
+
+/**
+ * Get the web admin status
+ *
+ * @returns Whether the web admin UI is enabled and where to reach it
+ */
+export async function getWebAdminStatus(): Promise<WebAdminStatus> {
+  return invoke<WebAdminStatus>('get_web_admin_status');
+}
+
+/**
+ * Set or clear the web admin password
+ *
+ * @param password - New password (min 8 characters), or null to disable the web admin UI
+ * @returns Updated web admin status
+ */
+export async function setWebAdminPassword(password: string | null): Promise<WebAdminStatus> {
+  return invoke<WebAdminStatus>('set_web_admin_password', { password });
+}
+
+// ============================================================================
+// Workspaces
+// ============================================================================
+
+/** Workspace (named database) */
+export interface WorkspaceInfo {
+  name: string;
+  /** Database file path */
+  path: string;
+  isActive: boolean;
+  /** Built-in default workspace (cannot be removed) */
+  isDefault: boolean;
+}
+
+/**
+ * List all workspaces (default first)
+ */
+export async function listWorkspaces(): Promise<WorkspaceInfo[]> {
+  return invoke<WorkspaceInfo[]>('list_workspaces');
+}
+
+/**
+ * Create a new workspace database
+ *
+ * @param name - Workspace name (letters, digits, spaces, '-' and '_')
+ * @param location - Optional absolute directory or database file path
+ */
+export async function createWorkspace(name: string, location?: string): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>('create_workspace', { name, location: location ?? null });
+}
+
+/**
+ * Switch to another workspace at runtime
+ *
+ * Active streams are stopped; the scheduler and HTTP server move to the new database.
+ *
+ * @param name - Workspace to activate
+ */
+export async function switchWorkspace(name: string): Promise<WorkspaceInfo> {
+  return invoke<WorkspaceInfo>('switch_workspace', { name });
+}
+
+/**
+ * Remove a workspace from the list (its database file is kept on disk)
+ *
+ * @param name - Workspace to remove
+ */
+export async function removeWorkspace(name: string): Promise<void> {
+  return invoke<void>('remove_workspace', { name });
+}
+
+// ============================================================================
+// Diagnostics
+// ============================================================================
+
+/** Memory and disk usage snapshot */
+export interface ResourceUsage {
+  /** Resident memory of the app process (null where unsupported) */
+  processRssBytes: number | null;
+  /** Size of the cached EPG document */
+  epgCacheBytes: number;
+  /** Database size, including WAL and shared-memory files */
+  databaseBytes: number;
+  logoCacheBytes: number;
+  logoCacheFiles: number;
+  activeStreams: number;
+  /** Bytes queued across all active stream buffers */
+  streamBufferBytes: number;
+}
+
+/**
+ * Get process memory, cache, database, and stream buffer usage
+ */
+export async function getResourceUsage(): Promise<ResourceUsage> {
+  return invoke<ResourceUsage>('get_resource_usage');
+}
+
+/** A part of startup that runs in the background */
+export type StartupPhase = 'database' | 'server' | 'scheduler';
+
+/** State of a startup phase ('skipped' when an earlier phase failed) */
+export type StartupPhaseState = 'pending' | 'running' | 'ready' | 'failed' | 'skipped';
+
+/** Status of one startup phase */
+export interface StartupPhaseStatus {
+  phase: StartupPhase;
+  state: StartupPhaseState;
+  error: string | null;
+  /** How long the phase took (null until it finishes) */
+  durationMs: number | null;
+}
+
+/** Progress of the background startup phases */
+export interface StartupStatus {
+  /** Every phase is ready */
+  ready: boolean;
+  phases: StartupPhaseStatus[];
+}
+
+/**
+ * Get the state of the background startup phases
+ *
+ * Returns immediately even while migrations run, so it can be polled from
+ * the first render. Other commands wait for the database phase.
+ */
+export async function getStartupStatus(): Promise<StartupStatus> {
+  return invoke<StartupStatus>('get_startup_status');
+}
+
+/**
+ * Get whether low resource mode is enabled
+ */
+export async function getLowResourceMode(): Promise<boolean> {
+  return invoke<boolean>('get_low_resource_mode');
+}
+
+/**
+ * Enable or disable low resource mode (applies immediately)
+ *
+ * Uses smaller EPG insert batches, refreshes one EPG source at a time,
+ * shortens the EPG cache TTL, and downloads logos one at a time.
+ *
+ * @param enabled - True to enable low resource mode
+ */
+export async function setLowResourceMode(enabled: boolean): Promise<boolean> {
+  return invoke<boolean>('set_low_resource_mode', { enabled });
+}
+
+/** Recent connection health of a stream, from the proxy's connection attempts */
+export type StreamHealthStatus = 'healthy' | 'failing' | 'unknown';
+
+/** One stream in a channel's failover chain */
+export interface FailoverChainEntry {
+  /** Order in which the proxy tries the stream (1 = first) */
+  position: number;
+  xtreamChannelId: number;
+  streamId: number;
+  streamName: string;
+  accountId: number;
+  accountName: string;
+  /** Quality the proxy selects for this stream */
+  quality: string;
+  streamPriority: number;
+  /** Same stream on a sibling account of the provider (not mapped itself) */
+  siblingAccount: boolean;
+  /** Account at its connection limit; skipped unless a session can be preempted */
+  accountFull: boolean;
+  /** Account over its monthly quota; new streams skip it */
+  quotaBlocked: boolean;
+  health: StreamHealthStatus;
+  lastSuccessAt: string | null;
+  lastFailureAt: string | null;
+  failingSince: string | null;
+}
+
+/** Ordered streams the stream proxy tries for a channel */
+export interface FailoverChain {
+  channelId: number;
+  /** Channels not enabled in the published lineup are refused regardless of the chain */
+  enabled: boolean;
+  streams: FailoverChainEntry[];
+}
+
+/**
+ * Get the ordered streams the proxy will try for a channel
+ *
+ * @param channelId - XMLTV channel ID (database ID)
+ */
+export async function getChannelFailoverChain(channelId: number): Promise<FailoverChain> {
+  return invoke<FailoverChain>('get_channel_failover_chain', { channelId });
+}
+
+// ============================================================================
+// Automation Hooks
+// ============================================================================
+
+/** Events that can trigger an automation hook */
+export type HookEvent = 'epg_refresh_completed' | 'all_streams_failed';
+
+/** A command run when an event happens (receives the event as JSON on stdin) */
+export interface AutomationHook {
+  event: HookEvent;
+  /** Program to run (absolute path or a name on PATH) */
+  program: string;
+  args: string[];
+  enabled: boolean;
+}
+
+/** Automation hook settings */
+export interface AutomationHookSettings {
+  /** Whether hooks may run (explicit opt-in) */
+  enabled: boolean;
+  hooks: AutomationHook[];
+}
+
+/**
+ * Get the automation hook settings
+ */
+export async function getAutomationHooks(): Promise<AutomationHookSettings> {
+  return invoke<AutomationHookSettings>('get_automation_hooks');
+}
+
+/**
+ * Save the automation hook settings
+ *
+ * Hooks run the configured programs with the app's privileges and only run
+ * while `enabled` is true.
+ */
+export async function setAutomationHooks(
+  settings: AutomationHookSettings
+): Promise<AutomationHookSettings> {
+  return invoke<AutomationHookSettings>('set_automation_hooks', { settings });
+}
+
+// ============================================================================
+// Plugins
+// ============================================================================
+
+/** Hook a plugin provides */
+export type PluginCapability = 'normalize' | 'score' | 'playlist_filter';
+
+/** A plugin found in the app data plugins directory */
+export interface PluginInfo {
+  name: string;
+  version: string | null;
+  description: string | null;
+  capabilities: PluginCapability[];
+  enabled: boolean;
+  /** Load error, or the failure that made the plugin be skipped */
+  error: string | null;
+}
+
+/**
+ * List the discovered plugins
+ */
+export async function listPlugins(): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>('list_plugins');
+}
+
+/**
+ * Turn a plugin on or off (new plugins start disabled)
+ */
+export async function setPluginEnabled(name: string, enabled: boolean): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>('set_plugin_enabled', { name, enabled });
+}
+
+/**
+ * Rescan the plugins directory
+ */
+export async function reloadPlugins(): Promise<PluginInfo[]> {
+  return invoke<PluginInfo[]>('reload_plugins');
+}
+
+// ============================================================================
+// Notifications
+// ============================================================================
+
+/** Payload format of a notification webhook */
+export type WebhookKind = 'discord' | 'slack' | 'generic';
+
+/** A webhook called for notifications */
+export interface NotificationWebhook {
+  name: string;
+  kind: WebhookKind;
+  /** Webhook URL (includes its access token for Discord and Slack) */
+  url: string;
+  enabled: boolean;
+}
+
+/** Per-channel notification rule */
+export interface NotificationChannelRule {
+  /** XMLTV channel ID */
+  channelId: number;
+  /** mute: never notify; always: notify on warnings and errors whatever the category */
+  mode: 'mute' | 'always';
+}
+
+/** Desktop notification and webhook settings */
+export interface NotificationSettings {
+  desktop: boolean;
+  webhooks: NotificationWebhook[];
+  /** Event categories that notify */
+  categories: string[];
+  /** Lowest event level that notifies */
+  minLevel: 'warn' | 'error';
+  channelRules: NotificationChannelRule[];
+}
+
+/** Result of a test notification for one target */
+export interface NotificationTestResult {
+  /** "Desktop" or the webhook name */
+  target: string;
+  ok: boolean;
+  error: string | null;
+}
+
+/**
+ * Get the notification settings
+ */
+export async function getNotificationSettings(): Promise<NotificationSettings> {
+  return invoke<NotificationSettings>('get_notification_settings');
+}
+
+/**
+ * Save the notification settings
+ */
+export async function setNotificationSettings(
+  settings: NotificationSettings
+): Promise<NotificationSettings> {
+  return invoke<NotificationSettings>('set_notification_settings', { settings });
+}
+
+/**
+ * Send a test notification to every enabled target of the given (possibly unsaved) settings
+ */
+export async function sendTestNotification(
+  settings: NotificationSettings
+): Promise<NotificationTestResult[]> {
+  return invoke<NotificationTestResult[]>('send_test_notification', { settings });
+}
+
+// ============================================================================
+// Plex Guide Reload
+// ============================================================================
+
+/** Plex server asked to reload the guide after EPG refreshes */
+export interface PlexReloadConfig {
+  enabled: boolean;
+  /** Plex Media Server address, e.g. http://192.168.1.10:32400 */
+  serverUrl: string;
+  /** At most one reload per this many minutes (5-1440) */
+  minIntervalMinutes: number;
+  /** Quiet period after the last refresh before a reload is sent (10-3600) */
+  debounceSeconds: number;
+}
+
+/** State of the Plex guide reload queue */
+export interface PlexReloadStatus {
+  pending: boolean;
+  /** EPG refreshes the queued reload waits for */
+  activeRefreshes: number;
+  lastReloadAt: string | null;
+  nextReloadAt: string | null;
+}
+
+/** Plex guide reload settings as shown in the UI */
+export interface PlexReloadSettings {
+  config: PlexReloadConfig;
+  /** Whether a Plex token is stored (the token itself is never returned) */
+  hasToken: boolean;
+  status: PlexReloadStatus;
+}
+
+/**
+ * Get the Plex guide reload settings and queue state
+ */
+export async function getPlexReloadSettings(): Promise<PlexReloadSettings> {
+  return invoke<PlexReloadSettings>('get_plex_reload_settings');
+}
+
+/**
+ * Save the Plex guide reload settings
+ * @param config - Reload settings
+ * @param token - New Plex token; omit to keep the stored one, empty to remove it
+ */
+export async function setPlexReloadSettings(
+  config: PlexReloadConfig,
+  token?: string
+): Promise<PlexReloadSettings> {
+  return invoke<PlexReloadSettings>('set_plex_reload_settings', { settings: { config, token } });
+}
+
+// ============================================================================
+// Alias Packs
+// ============================================================================
+
+/** Imported community alias pack (provider name -> tvg-id hints) */
+export interface AliasPack {
+  id: number;
+  name: string;
+  /** URL the pack was imported from (re-importing it updates the pack) */
+  sourceUrl: string;
+  version: string | null;
+  aliasCount: number;
+  importedAt: string;
+  updatedAt: string;
+}
+
+/**
+ * Import an alias pack from a URL, or update it if already imported
+ *
+ * Aliases are used the next time channel matching runs.
+ */
+export async function importAliasPack(url: string): Promise<AliasPack> {
+  return invoke<AliasPack>('import_alias_pack', { url });
+}
+
+/**
+ * List imported alias packs
+ */
+export async function listAliasPacks(): Promise<AliasPack[]> {
+  return invoke<AliasPack[]>('list_alias_packs');
+}
+
+/**
+ * Remove an alias pack and the aliases imported from it
+ */
+export async function removeAliasPack(packId: number): Promise<void> {
+  return invoke('remove_alias_pack', { packId });
+}
+
+// ============================================================================
+// Provider Speed Test
+// ============================================================================
+
+/** Stored provider speed test result */
+export interface ProviderSpeedtest {
+  id: number;
+  accountId: number;
+  /** Xtream stream ID that was downloaded */
+  streamId: number | null;
+  testedAt: string;
+  success: boolean;
+  /** Time to first byte of the stream body */
+  ttfbMs: number | null;
+  bytesDownloaded: number;
+  downloadMs: number;
+  throughputKbps: number | null;
+  /** JSON array of advertised qualities the measured bandwidth cannot sustain */
+  unsupportedQualities: string | null;
+  error: string | null;
+}
+
+/** Speed test response */
+export interface SpeedtestResponse {
+  result: ProviderSpeedtest;
+  /** Qualities advertised by the provider's streams */
+  advertisedQualities: string[];
+  /** Advertised qualities the measured bandwidth cannot sustain */
+  unsupportedQualities: string[];
+}
+
+/**
+ * Run a speed test against a provider account
+ *
+ * Downloads a few seconds of the account's highest-quality stream and uses one
+ * tuner slot while it runs.
+ */
+export async function runProviderSpeedtest(accountId: number): Promise<SpeedtestResponse> {
+  return invoke<SpeedtestResponse>('run_provider_speedtest', { accountId });
+}
+
+/**
+ * Get speed test history for an account (newest first)
+ */
+export async function getProviderSpeedtestHistory(
+  accountId: number,
+  limit?: number
+): Promise<ProviderSpeedtest[]> {
+  return invoke<ProviderSpeedtest[]>('get_provider_speedtest_history', {
+    accountId,
+    limit: limit ?? null,
+  });
+}
+
+// ============================================================================
+// Account Quotas
+// ============================================================================
+
+/** Monthly quota and current-month usage of an account */
+export interface AccountQuotaStatus {
+  accountId: number;
+  accountName: string;
+  monthlyGb: number | null;
+  monthlyHours: number | null;
+  /** Refuse new non-recording streams on the account once a limit is reached */
+  blockWhenExceeded: boolean;
+  /** Usage month ("YYYY-MM", local time) */
+  month: string;
+  usedBytes: number;
+  usedSeconds: number;
+  /** Highest share of a configured limit used (null without limits) */
+  usedPercent: number | null;
+  exceeded: boolean;
+}
+
+/** Get the quota and current-month usage of every account */
+export async function getAccountQuotas(): Promise<AccountQuotaStatus[]> {
+  return invoke<AccountQuotaStatus[]>('get_account_quotas');
+}
+
+/**
+ * Set the monthly limits of an account
+ *
+ * Passing null for both limits removes the quota.
+ */
+export async function setAccountQuota(
+  accountId: number,
+  monthlyGb: number | null,
+  monthlyHours: number | null,
+  blockWhenExceeded: boolean
+): Promise<void> {
+  return invoke<void>('set_account_quota', {
+    accountId,
+    monthlyGb,
+    monthlyHours,
+    blockWhenExceeded,
+  });
+}
+
+// ============================================================================
+// VOD Catalog
+// ============================================================================
+
+/**
+ * Movie or series in a provider's VOD catalog
+ *
+ * Movies play from the local server at `/vod/movie/{id}`.
+ */
+export interface VodItem {
+  id: number;
+  accountId: number;
+  kind: 'movie' | 'series';
+  /** Xtream stream_id (movies) or series_id (series) */
+  providerId: number;
+  name: string;
+  icon: string | null;
+  categoryId: number | null;
+  categoryName: string | null;
+  containerExtension: string | null;
+  plot: string | null;
+  rating: number | null;
+  addedAt: string | null;
+  updatedAt: string;
+}
+
+/**
+ * Episode of a series in the VOD catalog
+ *
+ * Episodes play from the local server at `/vod/episode/{id}`.
+ */
+export interface VodEpisode {
+  id: number;
+  vodItemId: number;
+  episodeId: number;
+  season: number | null;
+  episodeNum: number | null;
+  title: string | null;
+  containerExtension: string | null;
+}
+
+/** Result of scanning an account's VOD catalog */
+export interface VodScanResponse {
+  movies: number;
+  series: number;
+  newItems: number;
+  removedItems: number;
+  scanDurationMs: number;
+}
+
+/** Scan the movie and series catalog of an Xtream account */
+export async function scanVod(accountId: number): Promise<VodScanResponse> {
+  return invoke<VodScanResponse>('scan_vod', { accountId });
+}
+
+/** List VOD catalog items, ordered by name */
+export async function getVodItems(options?: {
+  kind?: 'movie' | 'series';
+  accountId?: number;
+  search?: string;
+  limit?: number;
+  offset?: number;
+}): Promise<VodItem[]> {
+  return invoke<VodItem[]>('get_vod_items', {
+    kind: options?.kind ?? null,
+    accountId: options?.accountId ?? null,
+    search: options?.search ?? null,
+    limit: options?.limit ?? null,
+    offset: options?.offset ?? null,
+  });
+}
+
+/**
+ * Get the episodes of a series
+ *
+ * Episodes are fetched from the provider the first time, or when `refresh` is set.
+ */
+export async function getSeriesEpisodes(
+  vodItemId: number,
+  refresh?: boolean
+): Promise<VodEpisode[]> {
+  return invoke<VodEpisode[]>('get_series_episodes', {
+    vodItemId,
+    refresh: refresh ?? null,
+  });
+}
+
+// ============================================================================
+// Channel Enablement Policy
+// ============================================================================
+
+/** Automatic enable/disable policy driven by guide data and stream health */
+export interface ChannelPolicyConfig {
+  /** Run the policy after scheduled EPG refreshes */
+  enabled: boolean;
+  autoEnable: boolean;
+  autoDisable: boolean;
+  /** Guide data needed ahead of now before a channel is auto-enabled */
+  minEpgHours: number;
+  /** Days all mapped streams must have been failing before auto-disable */
+  deadDays: number;
+}
+
+/** A channel the policy would enable or disable */
+export interface PolicyChange {
+  channelId: number;
+  displayName: string;
+  reason: string;
+}
+
+/** Channels changed (or, for a dry run, to be changed) by the policy */
+export interface PolicyReport {
+  dryRun: boolean;
+  toEnable: PolicyChange[];
+  toDisable: PolicyChange[];
+}
+
+/** How automatically assigned channel numbers are spaced */
+export type NumberGap =
+  | { type: 'sequential' }
+  | { type: 'step'; step: number }
+  | { type: 'categoryBlocks'; block: number };
+
+/** Options of an automatic channel numbering run */
+export interface AutoNumberOptions {
+  start: number;
+  gap: NumberGap;
+  /** Only number channels without a number */
+  keepExisting: boolean;
+}
+
+/**
+ * Set or clear (null) the persistent number of a channel
+ */
+export async function setChannelNumber(channelId: number, number: number | null): Promise<void> {
+  return invoke<void>('set_channel_number', { channelId, number });
+}
+
+/**
+ * Number the enabled channels in lineup order, returning how many were numbered
+ */
+export async function autoAssignChannelNumbers(options: AutoNumberOptions): Promise<number> {
+  return invoke<number>('auto_assign_channel_numbers', { options });
+}
+
+/**
+ * Get the channel enablement policy
+ */
+export async function getChannelPolicy(): Promise<ChannelPolicyConfig> {
+  return invoke<ChannelPolicyConfig>('get_channel_policy');
+}
+
+/**
+ * Save the channel enablement policy
+ */
+export async function setChannelPolicy(config: ChannelPolicyConfig): Promise<ChannelPolicyConfig> {
+  return invoke<ChannelPolicyConfig>('set_channel_policy', { config });
+}
+
+/**
+ * Preview the channels the policy would change (dry run)
+ */
+export async function previewChannelPolicy(): Promise<PolicyReport> {
+  return invoke<PolicyReport>('preview_channel_policy');
+}
+
+/**
+ * Apply the policy now, regardless of its scheduled-run setting
+ */
+export async function runChannelPolicy(): Promise<PolicyReport> {
+  return invoke<PolicyReport>('run_channel_policy');
+}
+
+// ============================================================================
+// Automatic Primary Demotion
+// ============================================================================
+
+/** Promote a reliable backup when a channel's primary keeps failing over */
+export interface FailoverPolicyConfig {
+  enabled: boolean;
+  /** Failovers away from the primary that trigger a demotion */
+  failoverCount: number;
+  /** Window the failovers must fall within */
+  windowHours: number;
+}
+
+/**
+ * Get the automatic primary demotion settings
+ */
+export async function getFailoverPolicy(): Promise<FailoverPolicyConfig> {
+  return invoke<FailoverPolicyConfig>('get_failover_policy');
+}
+
+/**
+ * Save the automatic primary demotion settings
+ */
+export async function setFailoverPolicy(config: FailoverPolicyConfig): Promise<FailoverPolicyConfig> {
+  return invoke<FailoverPolicyConfig>('set_failover_policy', { config });
+}
+
+// ============================================================================
+// Tuner Reservation and Priority
+// ============================================================================
+
+/** Priority of a stream request (`/stream/{id}?priority=high`) */
+export type StreamPriority = 'normal' | 'high';
+
+/**
+ * What happens when tuners run short: first come first served, keep
+ * reserved tuners for high-priority streams, or stop a normal stream
+ */
+export type TunerPolicy = 'off' | 'reject' | 'preempt';
+
+export interface TunerPriorityConfig {
+  policy: TunerPolicy;
+  /** Tuners normal-priority streams may not use (`reject` policy) */
+  reservedTuners: number;
+  /** XMLTV channel IDs whose streams are always high priority */
+  highPriorityChannels: number[];
+  /** Honor `?priority=` on stream requests (off by default) */
+  allowPriorityHint: boolean;
+}
+
+/**
+ * Get the tuner reservation and priority settings
+ */
+export async function getTunerPriority(): Promise<TunerPriorityConfig> {
+  return invoke<TunerPriorityConfig>('get_tuner_priority');
+}
+
+/**
+ * Save the tuner reservation and priority settings
+ */
+export async function setTunerPriority(config: TunerPriorityConfig): Promise<TunerPriorityConfig> {
+  return invoke<TunerPriorityConfig>('set_tuner_priority', { config });
+}
+
+// ============================================================================
+// Lineup Export/Import
+// ============================================================================
+
+/** A stream mapped to a lineup channel (by provider stream ID and name) */
+export interface LineupStream {
+  streamId: number;
+  name: string;
+  isPrimary: boolean;
+  streamPriority: number;
+}
+
+/** A curated channel in a lineup export (by XMLTV tvg-id) */
+export interface LineupChannel {
+  channelId: string;
+  displayName: string;
+  enabled: boolean;
+  /** Position in the lineup (0-indexed) */
+  displayOrder: number | null;
+  /** Number set for the channel; without one the number is position + 1 */
+  channelNumber?: number | null;
+  streams: LineupStream[];
+}
+
+/** A lineup entry that could not be (fully) applied */
+export interface UnresolvedLineupEntry {
+  channelId: string;
+  displayName: string;
+  reason: string;
+}
+
+/** Result of a lineup import */
+export interface LineupImportResult {
+  channelsApplied: number;
+  mappingsApplied: number;
+  unresolved: UnresolvedLineupEntry[];
+}
+
+/**
+ * Export channel curation (enabled channels, order, mappings) as JSON
+ *
+ * Contains no accounts, EPG sources, or settings, and uses natural keys so it
+ * can be shared with users of the same providers and EPG sources.
+ */
+export async function exportLineup(): Promise<string> {
+  return invoke<string>('export_lineup');
+}
+
+/**
+ * Import a lineup, resolving entries against local channels
+ *
+ * @param content - JSON content of the lineup file
+ * @returns Applied counts and entries that could not be resolved
+ */
+export async function importLineup(content: string): Promise<LineupImportResult> {
+  return invoke<LineupImportResult>('import_lineup', { content });
+}
+
+/** A channel whose draft enable/order differs from the published lineup */
+export interface LineupDraftChange {
+  xmltvChannelId: number;
+  displayName: string;
+  publishedEnabled: boolean;
+  draftEnabled: boolean;
+  publishedOrder: number | null;
+  draftOrder: number | null;
+  publishedNumber: number | null;
+  draftNumber: number | null;
+}
+
+/**
+ * Lineup staging mode and pending draft changes
+ *
+ * While staging is enabled, channel enables and ordering are a draft; Plex
+ * keeps seeing the published lineup until `publishLineup` is called.
+ */
+export interface LineupStagingStatus {
+  enabled: boolean;
+  /** When the lineup was last published (RFC 3339) */
+  publishedAt: string | null;
+  changes: LineupDraftChange[];
+}
+
+/** Get the lineup staging mode and pending draft changes */
+export async function getLineupStaging(): Promise<LineupStagingStatus> {
+  return invoke<LineupStagingStatus>('get_lineup_staging');
+}
+
+/** Turn lineup staging on or off (turning it off makes the draft live) */
+export async function setLineupStaging(enabled: boolean): Promise<LineupStagingStatus> {
+  return invoke<LineupStagingStatus>('set_lineup_staging', { enabled });
+}
+
+/** Publish the lineup draft to Plex */
+export async function publishLineup(): Promise<LineupStagingStatus> {
+  return invoke<LineupStagingStatus>('publish_lineup');
+}
+
+/** Discard the lineup draft, restoring the published lineup */
+export async function discardLineupDraft(): Promise<LineupStagingStatus> {
+  return invoke<LineupStagingStatus>('discard_lineup_draft');
+}
+
+// ============================================================================
+// Lineup Preview
+// ============================================================================
+
+/** Stream the proxy plays for a channel */
+export interface LineupPreviewStream {
+  xtreamChannelId: number;
+  name: string;
+  accountId: number;
+  accountName: string;
+  /** Quality the proxy selects from the stream's qualities */
+  quality: string;
+  isPrimary: boolean;
+}
+
+/** One channel as Plex sees it */
+export interface LineupPreviewEntry {
+  xmltvChannelId: number;
+  /** Channel number in lineup.json and tvg-chno */
+  guideNumber: string;
+  guideName: string;
+  /** tvg-id in the playlist and channel id in epg.xml */
+  tvgId: string;
+  iconUrl: string | null;
+  group: string | null;
+  /** Stream URL as given to Plex */
+  streamUrl: string;
+  /** null when no mapped stream is on an active account */
+  stream: LineupPreviewStream | null;
+  /** Mapped streams on active accounts, including the one played */
+  streamCount: number;
+  /** Programs in epg.xml for this channel */
+  guidePrograms: number;
+  isSynthetic: boolean;
+  /** Problems that keep the channel from working in Plex */
+  issues: string[];
+}
+
+/** Get the channel list exactly as Plex sees it */
+export async function getLineupPreview(): Promise<LineupPreviewEntry[]> {
+  return invoke<LineupPreviewEntry[]>('get_lineup_preview');
+}
+
+/** Whether the lineup preview is served at /debug/lineup */
+export async function getDebugLineupEnabled(): Promise<boolean> {
+  return invoke<boolean>('get_debug_lineup_enabled');
+}
+
+/** Serve the lineup preview at /debug/lineup (behind the access token) */
+export async function setDebugLineupEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>('set_debug_lineup_enabled', { enabled });
+}
+
+// ============================================================================
+// Lineup Health Report
+// ============================================================================
+
+/** Health summary of one enabled channel */
+export interface LineupReportRow {
+  channelId: number;
+  /** Channel number shown in Plex (null without an explicit order) */
+  channelNumber: number | null;
+  displayName: string;
+  tvgId: string;
+  /** XMLTV source name, with " + channel feed" when a per-channel feed is set */
+  guideSource: string;
+  mappedStreams: number;
+  /** Distinct provider accounts among the mapped streams */
+  providers: number;
+  bestQuality: string | null;
+  /** Highest match confidence among the mappings (0.0-1.0) */
+  matchConfidence: number | null;
+  /** Share of streams with health data whose last attempt succeeded (0-100) */
+  reliabilityPercent: number | null;
+  streamsWithHealth: number;
+  guideHoursAhead: number;
+  /** Share of the next 24 hours covered by programmes (0-100) */
+  coveragePercent: number;
+}
+
+/** Get the health report of all enabled channels */
+export async function getLineupReport(): Promise<LineupReportRow[]> {
+  return invoke<LineupReportRow[]>('get_lineup_report');
+}
+
+/** Export the lineup health report as CSV text (save with the file dialog) */
+export async function exportLineupReportCsv(): Promise<string> {
+  return invoke<string>('export_lineup_report_csv');
+}
+
+// ============================================================================
+// Mapping Conflicts
+// ============================================================================
+
+/** What happens when a stream becomes primary for more than one channel */
+export type MappingConflictPolicy = 'allow' | 'warn' | 'prevent';
+
+/** A channel using the conflicting stream as its primary */
+export interface MappingConflictOwner {
+  mappingId: number;
+  xmltvChannelId: number;
+  displayName: string;
+  isEnabled: boolean;
+  isManual: boolean;
+  matchConfidence: number | null;
+}
+
+/** A stream that is the primary stream of several channels */
+export interface MappingConflict {
+  xtreamChannelId: number;
+  streamName: string;
+  channels: MappingConflictOwner[];
+}
+
+/** List streams that are the primary stream of more than one channel */
+export async function getMappingConflicts(): Promise<MappingConflict[]> {
+  return invoke<MappingConflict[]>('get_mapping_conflicts');
+}
+
+/**
+ * Keep a stream as the primary of one channel and demote it to a backup on
+ * the others (their next backup becomes primary)
+ * @returns The remaining conflicts
+ */
+export async function resolveMappingConflict(
+  xtreamChannelId: number,
+  ownerXmltvChannelId: number
+): Promise<MappingConflict[]> {
+  return invoke<MappingConflict[]>('resolve_mapping_conflict', {
+    xtreamChannelId,
+    ownerXmltvChannelId,
+  });
+}
+
+/** Get the mapping conflict policy */
+export async function getMappingConflictPolicy(): Promise<MappingConflictPolicy> {
+  return invoke<MappingConflictPolicy>('get_mapping_conflict_policy');
+}
+
+/** Set the mapping conflict policy */
+export async function setMappingConflictPolicy(policy: MappingConflictPolicy): Promise<void> {
+  return invoke<void>('set_mapping_conflict_policy', { policy });
+}
+
+// ============================================================================
+// Mapping CSV Import/Export
+// ============================================================================
+
+/** A CSV row that cannot be imported */
+export interface MappingCsvRowError {
+  /** Line in the file (1 = header, 0 = not tied to one row) */
+  line: number;
+  message: string;
+}
+
+/** A channel whose streams change on import */
+export interface MappingCsvChange {
+  channelId: string;
+  displayName: string;
+  /** Stream names in priority order, primary marked with "*" */
+  before: string[];
+  after: string[];
+}
+
+/** Dry run of a mapping CSV import */
+export interface MappingCsvPreview {
+  channels: number;
+  mappings: number;
+  mappingsRemoved: number;
+  changes: MappingCsvChange[];
+  /** Import is refused while there are any */
+  errors: MappingCsvRowError[];
+  warnings: string[];
+}
+
+export interface MappingCsvImportResult {
+  channelsUpdated: number;
+  mappingsCreated: number;
+  mappingsRemoved: number;
+}
+
+/** Export the channel mappings as CSV text (save with the file dialog) */
+export async function exportMappingsCsv(): Promise<string> {
+  return invoke<string>('export_mappings_csv');
+}
+
+/** Check a mapping CSV without changing anything */
+export async function previewMappingsCsv(content: string): Promise<MappingCsvPreview> {
+  return invoke<MappingCsvPreview>('preview_mappings_csv', { content });
+}
+
+/** Replace the mappings of the channels listed in a CSV with manual mappings */
+export async function importMappingsCsv(content: string): Promise<MappingCsvImportResult> {
+  return invoke<MappingCsvImportResult>('import_mappings_csv', { content });
+}
+
+// ============================================================================
+// Data Dump Export
+// ============================================================================
+
+export type DataDumpKind = 'channels' | 'streams' | 'mappings' | 'programs' | 'events';
+
+export type DataDumpFormat = 'jsonl' | 'csv';
+
+/** Time window (ISO 8601); programs overlapping it, events logged in it */
+export interface DataDumpRange {
+  from?: string | null;
+  to?: string | null;
+}
+
+export interface DataDumpResult {
+  path: string;
+  rows: number;
+  bytes: number;
+}
+
+/**
+ * Export one kind of data to a JSON Lines or CSV file
+ *
+ * @param path - Absolute file path chosen with the save dialog
+ * @param range - Only used for programs and events
+ */
+export async function exportDataDump(
+  kind: DataDumpKind,
+  format: DataDumpFormat,
+  path: string,
+  range?: DataDumpRange | null
+): Promise<DataDumpResult> {
+  return invoke<DataDumpResult>('export_data_dump', { kind, format, path, range: range ?? null });
+}
+
+// ============================================================================
+// Per-Channel XMLTV Feeds
+// ============================================================================
+
+/** XMLTV feed merged into one guide channel during EPG refresh */
+export interface ChannelEpgFeed {
+  /** XMLTV channel ID (tvg-id) of the guide channel the feed fills */
+  tvgId: string;
+  url: string;
+  /** Channel ID inside the feed; null for single-channel feeds */
+  feedChannelId: string | null;
+  lastFetchedAt: string | null;
+  programCount: number;
+  lastError: string | null;
+  createdAt: string;
+}
+
+/** List all per-channel XMLTV feeds */
+export async function getChannelEpgFeeds(): Promise<ChannelEpgFeed[]> {
+  return invoke<ChannelEpgFeed[]>('get_channel_epg_feeds');
+}
+
+/**
+ * Attach (or replace) the XMLTV feed of a guide channel
+ *
+ * The feed's programs replace the channel's programs in the time span the
+ * feed covers, starting with the next EPG refresh.
+ *
+ * @param tvgId - XMLTV channel ID of the guide channel
+ * @param url - Feed URL (plain or gzipped XMLTV)
+ * @param feedChannelId - Channel ID inside the feed (omit for single-channel feeds)
+ */
+export async function setChannelEpgFeed(
+  tvgId: string,
+  url: string,
+  feedChannelId?: string | null
+): Promise<ChannelEpgFeed> {
+  return invoke<ChannelEpgFeed>('set_channel_epg_feed', {
+    tvgId,
+    url,
+    feedChannelId: feedChannelId ?? null,
+  });
+}
+
+/** Remove the XMLTV feed of a guide channel */
+export async function removeChannelEpgFeed(tvgId: string): Promise<void> {
+  return invoke<void>('remove_channel_epg_feed', { tvgId });
+}
+
+/** Fetch a channel's feed now and merge it into the guide */
+export async function refreshChannelEpgFeed(tvgId: string): Promise<ChannelEpgFeed> {
+  return invoke<ChannelEpgFeed>('refresh_channel_epg_feed', { tvgId });
+}
+
+// ============================================================================
+// Background tasks
+// ============================================================================
+
+/** Lifecycle state of a background task */
+export type TaskStatus = 'running' | 'completed' | 'failed' | 'cancelled';
+
+/** Snapshot of a background task (also the payload of `task-updated` events) */
+export interface TaskInfo<TResult = unknown, TProgress = unknown> {
+  id: string;
+  /** 'channel_scan', 'channel_matching' or 'epg_refresh' */
+  kind: string;
+  status: TaskStatus;
+  /** Latest progress payload (same shape as the operation's progress event) */
+  progress: TProgress | null;
+  /** Result of a completed task (same shape as the synchronous command's result) */
+  result: TResult | null;
+  /** Error message of a failed task */
+  error: string | null;
+  startedAt: string;
+  finishedAt: string | null;
+}
+
+/** Scan an account's channels in the background; returns the task ID */
+export async function startChannelScanTask(accountId: number): Promise<string> {
+  return invoke<string>('start_channel_scan_task', { accountId });
+}
+
+/** Run channel matching in the background; returns the task ID */
+export async function startChannelMatchingTask(threshold?: number): Promise<string> {
+  return invoke<string>('start_channel_matching_task', { threshold: threshold ?? null });
+}
+
+/** Refresh all active EPG sources in the background; returns the task ID */
+export async function startEpgRefreshTask(): Promise<string> {
+  return invoke<string>('start_epg_refresh_task');
+}
+
+/** Get a task's status, progress and result */
+export async function getTaskStatus<TResult = unknown, TProgress = unknown>(
+  taskId: string
+): Promise<TaskInfo<TResult, TProgress>> {
+  return invoke<TaskInfo<TResult, TProgress>>('get_task_status', { taskId });
+}
+
+/** List running and recently finished tasks, newest first */
+export async function listTasks(): Promise<TaskInfo[]> {
+  return invoke<TaskInfo[]>('list_tasks');
+}
+
+/** Cancel a running task (work it already saved is kept) */
+export async function cancelTask(taskId: string): Promise<TaskInfo> {
+  return invoke<TaskInfo>('cancel_task', { taskId });
+}
+
+/**
+ * Subscribe to task updates (start, progress and completion)
+ *
+ * @returns Function that removes the listener
+ */
+export async function onTaskUpdated(handler: (task: TaskInfo) => void): Promise<UnlistenFn> {
+  return listen<TaskInfo>('task-updated', (event) => handler(event.payload));
+}
+
+// System service (headless server that keeps running after logout)
+
+export type ServiceKind = 'systemd' | 'launchd' | 'windows_task';
+
+export interface ServiceStatus {
+  /** Whether this platform supports installing the service */
+  supported: boolean;
+  installed: boolean;
+  kind: ServiceKind | null;
+  installLocation: string | null;
+}
+
+export interface ServiceDefinition {
+  kind: ServiceKind;
+  /** Unit/plist file path, or task name on Windows */
+  installLocation: string;
+  /** Unit file, plist or Task Scheduler XML */
+  content: string;
+  /** Commands that install it by hand */
+  installCommands: string[];
+}
+
+/** Get whether the system service is supported and installed */
+export async function getServiceStatus(): Promise<ServiceStatus> {
+  return invoke<ServiceStatus>('get_service_status');
+}
+
+/** Get the generated service definition without installing it */
+export async function getServiceDefinition(): Promise<ServiceDefinition> {
+  return invoke<ServiceDefinition>('get_service_definition');
+}
+
+/** Install and start the system service (asks for administrator rights) */
+export async function installService(): Promise<ServiceDefinition> {
+  return invoke<ServiceDefinition>('install_service');
+}
+
+/** Stop and remove the system service (asks for administrator rights) */
+export async function uninstallService(): Promise<void> {
+  return invoke<void>('uninstall_service');
+}
+
+// ============================================================================
+// Metrics Export
+// ============================================================================
+
+/** File format of the metrics snapshot */
+export type MetricsFormat = 'prometheus' | 'json';
+
+/** Periodic metrics snapshot written to a file (e.g. for node_exporter's textfile collector) */
+export interface MetricsExportConfig {
+  enabled: boolean;
+  /** Absolute path of the file to write */
+  path: string;
+  format: MetricsFormat;
+  /** Minutes between exports (1-1440) */
+  intervalMinutes: number;
+}
+
+/** Get the metrics export settings */
+export async function getMetricsExportConfig(): Promise<MetricsExportConfig> {
+  return invoke<MetricsExportConfig>('get_metrics_export_config');
+}
+
+/** Save the metrics export settings; writes a first snapshot when enabled */
+export async function setMetricsExportConfig(config: MetricsExportConfig): Promise<MetricsExportConfig> {
+  return invoke<MetricsExportConfig>('set_metrics_export_config', { config });
+}
+
+// ============================================================================
+// DNS Resolution
+// ============================================================================
+
+/** How provider and EPG hostnames are resolved */
+export type DnsMode = 'system' | 'custom' | 'doh';
+
+/** Public DNS-over-HTTPS resolvers */
+export type DohProvider = 'cloudflare' | 'google' | 'quad9';
+
+/** Resolution strategy for a set of hosts */
+export interface DnsStrategy {
+  mode: DnsMode;
+  /** Server addresses for custom mode ("1.1.1.1" or "1.1.1.1:53") */
+  servers: string[];
+  dohProvider: DohProvider;
+}
+
+/** Global DNS strategy plus overrides by account ID (applied to the account's server host) */
+export interface DnsConfig {
+  global: DnsStrategy;
+  accountOverrides: Record<string, DnsStrategy>;
+}
+
+/** Result of resolving a hostname with a strategy, next to the system resolver's answer */
+export interface DnsTestResult {
+  hostname: string;
+  addresses: string[];
+  error: string | null;
+  elapsedMs: number;
+  systemAddresses: string[];
+  systemError: string | null;
+}
+
+/** Get the DNS resolution settings */
+export async function getDnsConfig(): Promise<DnsConfig> {
+  return invoke<DnsConfig>('get_dns_config');
+}
+
+/** Save and apply the DNS resolution settings */
+export async function setDnsConfig(config: DnsConfig): Promise<DnsConfig> {
+  return invoke<DnsConfig>('set_dns_config', { config });
+}
+
+/**
+ * Resolve a hostname or URL host
+ *
+ * Uses the given strategy (to try settings before saving) or the one currently applied to the host.
+ */
+export async function testDnsResolution(
+  hostname: string,
+  strategy?: DnsStrategy
+): Promise<DnsTestResult> {
+  return invoke<DnsTestResult>('test_dns_resolution', { hostname, strategy: strategy ?? null });
+}
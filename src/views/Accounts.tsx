@@ -1,6 +1,6 @@
 import { useState, useEffect, useCallback } from 'react';
 import { PlusIcon } from '@radix-ui/react-icons';
-import { AccountForm, AccountsList } from '../components/accounts';
+import { AccountForm, AccountsList, parseHeaderLines } from '../components/accounts';
 import { EpgSourcesList, EpgSourceDialog } from '../components/epg';
 import type { AccountFormData, AccountEditData } from '../components/accounts';
 import type { Account, XmltvSource, NewXmltvSource } from '../lib/tauri';
@@ -14,6 +14,7 @@ import {
   updateXmltvSource,
   deleteXmltvSource,
   toggleXmltvSource,
+  verifyCredentials,
 } from '../lib/tauri';
 
 /**
@@ -40,6 +41,10 @@ export function Accounts() {
   const loadAccounts = useCallback(async () => {
     try {
       setError(null);
+      // Flag accounts whose stored password can no longer be read
+      await verifyCredentials().catch((err) =>
+        console.error('Failed to verify credentials:', err)
+      );
       const loadedAccounts = await getAccounts();
       setAccounts(loadedAccounts);
     } catch (err) {
@@ -78,6 +83,8 @@ export function Accounts() {
           serverUrl: data.serverUrl,
           username: data.username,
           password: data.password || undefined, // Only send password if provided
+          httpUserAgent: data.httpUserAgent,
+          httpHeaders: parseHeaderLines(data.httpHeaders),
         });
 
         setAccounts((prev) =>
@@ -91,6 +98,7 @@ export function Accounts() {
           serverUrl: data.serverUrl,
           username: data.username,
           password: data.password,
+          providerType: data.providerType,
         });
 
         setAccounts((prev) => [...prev, newAccount]);
@@ -112,6 +120,9 @@ export function Accounts() {
       name: account.name,
       serverUrl: account.serverUrl,
       username: account.username,
+      providerType: account.providerType,
+      httpUserAgent: account.httpUserAgent,
+      httpHeaders: account.httpHeaders,
     });
     setShowForm(true);
     setError(null);
@@ -241,6 +252,34 @@ export function Accounts() {
         </div>
       )}
 
+      {/* Accounts whose stored password is missing or unreadable */}
+      {!showForm && accounts.some((acc) => acc.credentialError) && (
+        <div
+          data-testid="credential-error-banner"
+          className="mb-4 p-4 bg-amber-50 border border-amber-200 text-amber-800 rounded-md"
+        >
+          <p className="font-medium">
+            The saved password for these accounts could not be read. Enter it again to keep
+            streams working.
+          </p>
+          <ul className="mt-2 space-y-1">
+            {accounts
+              .filter((acc) => acc.credentialError)
+              .map((acc) => (
+                <li key={acc.id} className="flex items-center justify-between text-sm">
+                  <span title={acc.credentialError ?? undefined}>{acc.name}</span>
+                  <button
+                    onClick={() => handleEditAccount(acc)}
+                    className="px-2 py-1 text-xs font-medium text-amber-900 bg-amber-100 rounded hover:bg-amber-200"
+                  >
+                    Re-enter password
+                  </button>
+                </li>
+              ))}
+          </ul>
+        </div>
+      )}
+
       {/* Form or List */}
       {showForm ? (
         <AccountForm
@@ -3,17 +3,27 @@
  * Story 1.3: Create React GUI Shell with Routing
  * Story 4-6: Display Plex Configuration URLs
  *
- * Main dashboard displaying status overview and Plex integration URLs.
+ * Main dashboard displaying status overview, Plex integration URLs, and
+ * resource usage.
  */
+import { AccountExpiryBanner } from '../components/dashboard/AccountExpiryBanner';
 import { PlexConfigSection } from '../components/dashboard/PlexConfigSection';
+import { RecentErrorsSection } from '../components/dashboard/RecentErrorsSection';
+import { ResourceUsageSection } from '../components/dashboard/ResourceUsageSection';
 
 export function Dashboard() {
   return (
     <div data-testid="dashboard-view" className="space-y-6">
       <h1 className="text-2xl font-bold">Dashboard</h1>
 
+      <AccountExpiryBanner />
+
       {/* Plex Integration Section - Story 4-6 */}
       <PlexConfigSection />
+
+      <RecentErrorsSection />
+
+      <ResourceUsageSection />
     </div>
   );
 }
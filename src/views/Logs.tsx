@@ -10,17 +10,45 @@ import {
   ExclamationTriangleIcon,
   CrossCircledIcon,
   TrashIcon,
+  MagnifyingGlassIcon,
 } from '@radix-ui/react-icons';
 import {
-  getEvents,
+  searchEvents,
   markEventRead,
   markAllEventsRead,
   clearOldEvents,
   parseEventDetails,
   type EventLogEntry,
   type EventLevel,
+  type EventCounts,
+  type EventSearch,
 } from '../lib/tauri';
 
+/** Events loaded per page */
+const PAGE_SIZE = 100;
+
+const LEVEL_OPTIONS: { value: EventLevel; label: string }[] = [
+  { value: 'info', label: 'Info' },
+  { value: 'warn', label: 'Warning' },
+  { value: 'error', label: 'Error' },
+];
+
+const CATEGORY_OPTIONS: { value: string; label: string }[] = [
+  { value: 'connection', label: 'Connection' },
+  { value: 'stream', label: 'Stream' },
+  { value: 'match', label: 'Match' },
+  { value: 'epg', label: 'EPG' },
+  { value: 'system', label: 'System' },
+  { value: 'provider', label: 'Provider' },
+];
+
+/**
+ * Option label with its event count, e.g. "Error (12)"
+ */
+function withCount(label: string, count: number | undefined): string {
+  return count === undefined ? label : `${label} (${count.toLocaleString()})`;
+}
+
 /**
  * Get icon for event level
  */
@@ -154,36 +182,78 @@ export function Logs() {
   const [unreadCount, setUnreadCount] = useState(0);
   const [isLoading, setIsLoading] = useState(true);
   const [error, setError] = useState<string | null>(null);
+  const [counts, setCounts] = useState<EventCounts | null>(null);
+  const [nextCursor, setNextCursor] = useState<string | null>(null);
+  const [isLoadingMore, setIsLoadingMore] = useState(false);
+  const [searchText, setSearchText] = useState('');
   const [filter, setFilter] = useState<{
     level?: EventLevel;
     category?: string;
     unreadOnly: boolean;
     startDate?: string;  // Story 6-4 AC #5: Date range filter
     endDate?: string;    // Story 6-4 AC #5: Date range filter
+    query?: string;
   }>({ unreadOnly: false });
 
+  // Search as the user types, once they pause
+  useEffect(() => {
+    const timer = setTimeout(() => {
+      const query = searchText.trim() || undefined;
+      setFilter((prev) => (prev.query === query ? prev : { ...prev, query }));
+    }, 300);
+    return () => clearTimeout(timer);
+  }, [searchText]);
+
+  const buildSearch = useCallback(
+    (cursor?: string): EventSearch => ({
+      limit: PAGE_SIZE,
+      level: filter.level,
+      category: filter.category,
+      unreadOnly: filter.unreadOnly,
+      createdAfter: filter.startDate,
+      createdBefore: filter.endDate,
+      query: filter.query,
+      cursor,
+    }),
+    [filter]
+  );
+
   const loadEvents = useCallback(async () => {
     setIsLoading(true);
     setError(null);
     try {
-      const response = await getEvents({
-        limit: 100,
-        level: filter.level,
-        category: filter.category,
-        unreadOnly: filter.unreadOnly,
-        createdAfter: filter.startDate,
-        createdBefore: filter.endDate,
-      });
+      const response = await searchEvents(buildSearch());
       setEvents(response.events);
+      setNextCursor(response.nextCursor);
       setTotalCount(response.totalCount);
       setUnreadCount(response.unreadCount);
+      setCounts(response.counts);
     } catch (err) {
       console.error('Failed to load events:', err);
       setError('Failed to load events. Please try again.');
     } finally {
       setIsLoading(false);
     }
-  }, [filter]);
+  }, [buildSearch]);
+
+  const handleLoadMore = async () => {
+    if (!nextCursor) {
+      return;
+    }
+    setIsLoadingMore(true);
+    try {
+      const response = await searchEvents(buildSearch(nextCursor));
+      setEvents((prev) => [...prev, ...response.events]);
+      setNextCursor(response.nextCursor);
+      setTotalCount(response.totalCount);
+      setCounts(response.counts);
+    } catch (err) {
+      console.error('Failed to load more events:', err);
+      setError('Failed to load more events. Please try again.');
+    } finally {
+      setIsLoadingMore(false);
+    }
+  };
 
   useEffect(() => {
     loadEvents();
@@ -303,15 +373,28 @@ export function Logs() {
 
       {/* Filters */}
       <div className="flex flex-wrap gap-2 mb-4">
+        <div className="relative">
+          <MagnifyingGlassIcon className="w-4 h-4 text-gray-400 absolute left-2.5 top-1/2 -translate-y-1/2" />
+          <input
+            type="search"
+            data-testid="event-search-input"
+            value={searchText}
+            onChange={(e) => setSearchText(e.target.value)}
+            placeholder="Search messages and details"
+            className="pl-8 pr-3 py-1.5 text-sm border border-gray-300 rounded-md bg-white w-64"
+          />
+        </div>
         <select
           value={filter.level || ''}
           onChange={(e) => setFilter((prev) => ({ ...prev, level: e.target.value as EventLevel || undefined }))}
           className="px-3 py-1.5 text-sm border border-gray-300 rounded-md bg-white"
         >
           <option value="">All Levels</option>
-          <option value="info">Info</option>
-          <option value="warn">Warning</option>
-          <option value="error">Error</option>
+          {LEVEL_OPTIONS.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {withCount(label, counts ? counts.byLevel[value] ?? 0 : undefined)}
+            </option>
+          ))}
         </select>
         <select
           value={filter.category || ''}
@@ -319,12 +402,11 @@ export function Logs() {
           className="px-3 py-1.5 text-sm border border-gray-300 rounded-md bg-white"
         >
           <option value="">All Categories</option>
-          <option value="connection">Connection</option>
-          <option value="stream">Stream</option>
-          <option value="match">Match</option>
-          <option value="epg">EPG</option>
-          <option value="system">System</option>
-          <option value="provider">Provider</option>
+          {CATEGORY_OPTIONS.map(({ value, label }) => (
+            <option key={value} value={value}>
+              {withCount(label, counts ? counts.byCategory[value] ?? 0 : undefined)}
+            </option>
+          ))}
         </select>
         <label className="inline-flex items-center px-3 py-1.5 text-sm border border-gray-300 rounded-md bg-white cursor-pointer">
           <input
@@ -355,7 +437,7 @@ export function Logs() {
           />
         </div>
         <span className="ml-auto text-sm text-gray-500">
-          Showing {events.length} of {totalCount} events
+          Showing {events.length.toLocaleString()} of {totalCount.toLocaleString()} events
         </span>
       </div>
 
@@ -377,7 +459,7 @@ export function Logs() {
           <InfoCircledIcon className="w-8 h-8 mx-auto mb-2 text-gray-400" />
           <p className="text-lg">No events found</p>
           <p className="text-sm mt-1">
-            {filter.level || filter.category || filter.unreadOnly
+            {filter.level || filter.category || filter.unreadOnly || filter.query
               ? 'Try adjusting your filters'
               : 'Events will appear here when provider changes are detected'}
           </p>
@@ -387,6 +469,19 @@ export function Logs() {
           {events.map((event) => (
             <EventItem key={event.id} event={event} onMarkRead={handleMarkRead} />
           ))}
+          {nextCursor && (
+            <div className="text-center pt-2">
+              <button
+                data-testid="load-more-events"
+                onClick={handleLoadMore}
+                disabled={isLoadingMore}
+                className="inline-flex items-center px-4 py-2 text-sm font-medium text-gray-600 bg-gray-100 hover:bg-gray-200 rounded-md transition-colors disabled:opacity-50"
+              >
+                {isLoadingMore && <ReloadIcon className="w-4 h-4 mr-1.5 animate-spin" />}
+                Load more
+              </button>
+            </div>
+          )}
         </div>
       )}
     </div>
@@ -27,10 +27,13 @@ import {
   exportConfiguration,
   validateImportFile,
   importConfiguration,
+  resolveImportedMappings,
   ImportPreview,
   getLogVerbosity,
   setLogVerbosity,
   LogVerbosity,
+  getLogFilePath,
+  exportLogs,
   getUpdateSettings,
   setAutoCheckUpdates,
   checkForUpdate,
@@ -114,7 +117,12 @@ export function Settings() {
 
   // Configuration export/import state (Story 6-2)
   const [isExporting, setIsExporting] = useState(false);
+  const [isExportingLogs, setIsExportingLogs] = useState(false);
+  const [logFilePath, setLogFilePath] = useState<string | null>(null);
+  const [includeCredentials, setIncludeCredentials] = useState(false);
+  const [exportPassphrase, setExportPassphrase] = useState('');
   const [isImporting, setIsImporting] = useState(false);
+  const [isResolving, setIsResolving] = useState(false);
   const [importPreview, setImportPreview] = useState<ImportPreview | null>(null);
   const [importFileContent, setImportFileContent] = useState<string | null>(null);
   const [showImportDialog, setShowImportDialog] = useState(false);
@@ -172,6 +180,7 @@ export function Settings() {
         const verbosity = await getLogVerbosity();
         setLogVerbosityState(verbosity);
         setSavedLogVerbosity(verbosity);
+        setLogFilePath(await getLogFilePath().catch(() => null));
 
         // Load update settings (Story 6-5)
         try {
@@ -248,16 +257,19 @@ export function Settings() {
         await setServerPort(portNum);
         setSavedServerPort(serverPort);
 
-        // Note: Server restart happens on next application restart
-        // The restart_server command is a placeholder in current implementation
+        // Rebind the running server on the new port
         setIsServerRestarting(true);
         try {
-          await restartServer();
-          messages.push('Port saved. Restart the app to apply changes.');
+          const result = await restartServer();
+          messages.push(
+            result.stoppedStreams > 0
+              ? `Port saved. Server restarted on port ${result.port} (${result.stoppedStreams} stream(s) stopped).`
+              : `Port saved. Server restarted on port ${result.port}.`
+          );
         } catch (restartErr) {
-          // Server restart may fail if not running - port is still saved
+          // Port is still saved; it applies on the next successful restart
           console.warn('Server restart warning:', restartErr);
-          messages.push('Port saved. Restart the app to apply changes.');
+          messages.push(`Port saved, but the server could not restart: ${restartErr}`);
         } finally {
           setIsServerRestarting(false);
         }
@@ -350,8 +362,10 @@ export function Settings() {
       setError(null);
       setSuccessMessage(null);
 
-      // Get configuration JSON from backend
-      const configJson = await exportConfiguration();
+      // Get configuration JSON from backend (passwords only with a passphrase)
+      const configJson = await exportConfiguration(
+        includeCredentials ? exportPassphrase : undefined
+      );
 
       // Generate default filename with timestamp
       const timestamp = new Date().toISOString().split('T')[0]; // YYYY-MM-DD
@@ -382,6 +396,36 @@ export function Settings() {
     }
   };
 
+  // Zip the diagnostic log files for a bug report
+  const handleExportLogs = async () => {
+    try {
+      setIsExportingLogs(true);
+      setError(null);
+      setSuccessMessage(null);
+
+      const timestamp = new Date().toISOString().split('T')[0]; // YYYY-MM-DD
+      const filePath = await save({
+        defaultPath: `streamforge-logs-${timestamp}.zip`,
+        filters: [
+          {
+            name: 'ZIP Archives',
+            extensions: ['zip'],
+          },
+        ],
+      });
+
+      if (filePath) {
+        const count = await exportLogs(filePath);
+        setSuccessMessage(`Exported ${count} log file${count === 1 ? '' : 's'}`);
+        setTimeout(() => setSuccessMessage(null), 5000);
+      }
+    } catch (err) {
+      setError(`Log export failed: ${err instanceof Error ? err.message : String(err)}`);
+    } finally {
+      setIsExportingLogs(false);
+    }
+  };
+
   // Handle import configuration - step 1: file selection (Story 6-2, AC #3)
   const handleImportSelect = async () => {
     try {
@@ -432,14 +476,14 @@ export function Settings() {
   };
 
   // Handle import confirmation (Story 6-2, AC #5, #6)
-  const handleImportConfirm = async () => {
+  const handleImportConfirm = async (passphrase?: string) => {
     if (!importFileContent) return;
 
     try {
       setIsImporting(true);
       setError(null);
 
-      const result = await importConfiguration(importFileContent);
+      const result = await importConfiguration(importFileContent, passphrase);
 
       setShowImportDialog(false);
       setImportPreview(null);
@@ -447,13 +491,17 @@ export function Settings() {
 
       if (result.success) {
         // Show message about accounts needing passwords
-        const accountMsg = result.accountsImported > 0
-          ? ` ${result.accountsImported} account(s) imported - passwords must be re-entered.`
+        const passwordsMissing = result.accountsImported - result.credentialsRestored;
+        const accountMsg = passwordsMissing > 0
+          ? ` ${passwordsMissing} account(s) imported - passwords must be re-entered.`
+          : '';
+        const mappingMsg = result.channelMappingsImported > 0
+          ? ` ${result.channelMappingsImported} channel mapping(s) can be restored after refreshing channels.`
           : '';
-        setSuccessMessage(`Configuration imported successfully.${accountMsg} Please restart the app to apply changes.`);
+        setSuccessMessage(`Configuration imported successfully.${accountMsg}${mappingMsg} Please restart the app to apply changes.`);
 
-        // Navigate to accounts page if accounts were imported
-        if (result.accountsImported > 0) {
+        // Navigate to accounts page if passwords need re-entering
+        if (passwordsMissing > 0) {
           setTimeout(() => {
             navigate('/accounts');
           }, 3000);
@@ -468,6 +516,29 @@ export function Settings() {
     }
   };
 
+  // Re-link mappings from an imported file once channels were refreshed
+  const handleResolveMappings = async () => {
+    try {
+      setIsResolving(true);
+      setError(null);
+      setSuccessMessage(null);
+
+      const result = await resolveImportedMappings();
+      const pendingMsg = result.unresolved.length > 0
+        ? ` ${result.unresolved.length} still waiting for their channel or stream - refresh and try again.`
+        : '';
+      const numbersMsg = result.numbersRestored > 0
+        ? ` ${result.numbersRestored} channel number(s) restored.`
+        : '';
+      setSuccessMessage(`${result.resolved} channel mapping(s) restored.${numbersMsg}${pendingMsg}`);
+      setTimeout(() => setSuccessMessage(null), 5000);
+    } catch (err) {
+      setError(`Restoring mappings failed: ${err instanceof Error ? err.message : String(err)}`);
+    } finally {
+      setIsResolving(false);
+    }
+  };
+
   // Handle import cancellation
   const handleImportCancel = () => {
     setShowImportDialog(false);
@@ -776,6 +847,34 @@ export function Settings() {
               <option value="minimal">Minimal</option>
             </select>
           </div>
+
+          <div className="flex items-center justify-between mt-4 pt-4 border-t border-gray-100">
+            <div className="flex-1 min-w-0">
+              <span className="font-medium text-gray-900">Log Files</span>
+              <p className="text-sm text-gray-500 mt-1">
+                Diagnostic logs for troubleshooting; the last 7 days are kept.
+              </p>
+              {logFilePath && (
+                <p
+                  data-testid="log-file-path"
+                  className="text-xs text-gray-400 mt-1 font-mono truncate"
+                  title={logFilePath}
+                >
+                  {logFilePath}
+                </p>
+              )}
+            </div>
+
+            <button
+              type="button"
+              data-testid="export-logs-button"
+              onClick={handleExportLogs}
+              disabled={isExportingLogs}
+              className="ml-4 px-4 py-2 bg-gray-200 text-gray-700 rounded-md hover:bg-gray-300 focus:outline-none focus:ring-2 focus:ring-gray-500 focus:ring-offset-2 disabled:opacity-50 disabled:cursor-not-allowed"
+            >
+              {isExportingLogs ? 'Exporting...' : 'Export Logs'}
+            </button>
+          </div>
         </div>
       </section>
 
@@ -988,11 +1087,32 @@ export function Settings() {
                   Save your current settings, accounts, and EPG sources to a JSON file.
                   <span className="text-amber-600 font-medium"> Passwords are not exported for security.</span>
                 </p>
+                <label className="flex items-center gap-2 mt-2 text-sm text-gray-700">
+                  <input
+                    type="checkbox"
+                    data-testid="export-include-credentials"
+                    checked={includeCredentials}
+                    onChange={(e) => setIncludeCredentials(e.target.checked)}
+                  />
+                  Include account passwords, encrypted with a passphrase
+                </label>
+                {includeCredentials && (
+                  <input
+                    type="password"
+                    data-testid="export-passphrase-input"
+                    placeholder="Passphrase (at least 8 characters)"
+                    value={exportPassphrase}
+                    onChange={(e) => setExportPassphrase(e.target.value)}
+                    className="mt-2 w-full max-w-xs px-3 py-2 border border-gray-300 rounded-md text-sm focus:outline-none focus:ring-2 focus:ring-blue-500"
+                  />
+                )}
               </div>
               <button
                 data-testid="export-config-button"
                 onClick={handleExport}
-                disabled={isExporting || isSaving}
+                disabled={
+                  isExporting || isSaving || (includeCredentials && exportPassphrase.length < 8)
+                }
                 className="px-4 py-2 bg-blue-600 text-white rounded-md hover:bg-blue-700 focus:outline-none focus:ring-2 focus:ring-blue-500 focus:ring-offset-2 disabled:opacity-50 disabled:cursor-not-allowed flex items-center gap-2"
               >
                 {isExporting ? (
@@ -1049,6 +1169,27 @@ export function Settings() {
                 )}
               </button>
             </div>
+
+            <hr className="border-gray-200" />
+
+            {/* Restore imported mappings */}
+            <div className="flex items-center justify-between">
+              <div className="flex-1">
+                <h3 className="font-medium text-gray-900">Restore Channel Mappings</h3>
+                <p className="text-sm text-gray-500 mt-1">
+                  After importing, refresh your accounts and EPG sources, then restore the
+                  channel mappings from the imported file.
+                </p>
+              </div>
+              <button
+                data-testid="resolve-mappings-button"
+                onClick={handleResolveMappings}
+                disabled={isResolving || isImporting || isSaving}
+                className="px-4 py-2 bg-gray-600 text-white rounded-md hover:bg-gray-700 focus:outline-none focus:ring-2 focus:ring-gray-500 focus:ring-offset-2 disabled:opacity-50 disabled:cursor-not-allowed"
+              >
+                {isResolving ? 'Restoring...' : 'Restore'}
+              </button>
+            </div>
           </div>
         </div>
       </section>
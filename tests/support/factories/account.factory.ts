@@ -26,6 +26,7 @@ export interface Account {
   isActive: boolean;
   createdAt: string;
   updatedAt: string;
+  providerType: 'xtream' | 'm3u';
   // New fields for Story 2.2 - connection status
   expiryDate?: string;
   maxConnectionsActual?: number;
@@ -67,6 +68,7 @@ export const createAccount = (overrides: Partial<Account> = {}): Account => {
     isActive: true,
     createdAt: faker.date.recent().toISOString(),
     updatedAt: faker.date.recent().toISOString(),
+    providerType: 'xtream',
     ...overrides,
   };
 };
@@ -12,6 +12,10 @@ export type XmltvSource = {
   isActive: boolean;
   createdAt: string;
   updatedAt: string;
+  authUsername?: string | null;
+  hasAuthPassword: boolean;
+  authHeaderName?: string | null;
+  hasAuthHeaderValue: boolean;
 };
 
 export type NewXmltvSource = {
@@ -39,6 +43,10 @@ export const createXmltvSource = (overrides: Partial<XmltvSource> = {}): XmltvSo
     isActive: true,
     createdAt: faker.date.recent().toISOString(),
     updatedAt: faker.date.recent().toISOString(),
+    authUsername: null,
+    hasAuthPassword: false,
+    authHeaderName: null,
+    hasAuthHeaderValue: false,
     ...overrides,
   };
 };
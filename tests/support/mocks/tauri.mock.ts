@@ -340,7 +340,12 @@ export async function injectSettingsStatefulMock(
         restart_server: () => {
           // Simulate server restart - just returns success
           console.log('[Tauri Mock] Server restart triggered on port:', window.__SETTINGS_STATE__.serverPort);
-          return undefined;
+          return {
+            previousPort: null,
+            port: window.__SETTINGS_STATE__.serverPort,
+            stoppedStreams: 0,
+            forced: false,
+          };
         },
 
         // Autostart commands
@@ -677,6 +682,7 @@ export async function injectSettingsStatefulMock(
                 channelMappingCount: 0,
                 xmltvChannelSettingsCount: 0,
                 settingsSummary: [],
+                integrity: config.integrity ? 'verified' : 'missing',
                 errorMessage: 'Unsupported configuration version: ' + (config.version || 'unknown') + '. Minimum supported: 1.0',
               };
             }
@@ -696,7 +702,9 @@ export async function injectSettingsStatefulMock(
               channelMappingCount: (data.channel_mappings || []).length,
               xmltvChannelSettingsCount: (data.xmltv_channel_settings || []).length,
               settingsSummary: settingsSummary,
+              integrity: config.integrity ? 'verified' : 'missing',
               errorMessage: null,
+              hasCredentials: !!config.credentials,
             };
           } catch (e) {
             return {
@@ -708,6 +716,7 @@ export async function injectSettingsStatefulMock(
               channelMappingCount: 0,
               xmltvChannelSettingsCount: 0,
               settingsSummary: [],
+              integrity: 'missing',
               errorMessage: 'Invalid JSON format: ' + e.message,
             };
           }
@@ -745,8 +754,9 @@ export async function injectSettingsStatefulMock(
               success: true,
               accountsImported: (data.accounts || []).length,
               xmltvSourcesImported: (data.xmltv_sources || []).length,
-              channelMappingsImported: 0,
+              channelMappingsImported: (data.portableMappings || []).length,
               settingsImported: Object.keys(settings).length,
+              credentialsRestored: 0,
               message: 'Configuration imported successfully.',
             };
           } catch (e) {
@@ -756,10 +766,13 @@ export async function injectSettingsStatefulMock(
               xmltvSourcesImported: 0,
               channelMappingsImported: 0,
               settingsImported: 0,
+              credentialsRestored: 0,
               message: 'Import failed: ' + e.message,
             };
           }
         },
+
+        resolve_imported_mappings: () => ({ resolved: 0, unresolved: [] }),
       };
 
       async function mockInvoke(cmd, args = {}) {